@@ -0,0 +1,158 @@
+//! Native menu, global shortcut and notifications for tool approvals
+//!
+//! Supervised sessions stop at every risky tool call. These let the user
+//! answer from outside the window: an OS notification with Approve/Deny
+//! buttons, an Approvals menu, and a global shortcut that brings the window
+//! back. Answers are emitted to the frontend as `approval-action`, which
+//! sends them to the server.
+
+use serde::Serialize;
+use tauri::menu::{Menu, MenuEvent, MenuItemBuilder, SubmenuBuilder};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
+use tauri_plugin_global_shortcut::{Shortcut, ShortcutEvent, ShortcutState};
+
+/// Shows or hides the window from any app
+pub const TOGGLE_SHORTCUT: &str = "CommandOrControl+Shift+Space";
+
+const APPROVE_ID: &str = "approval-approve";
+const DENY_ID: &str = "approval-deny";
+
+/// An answer chosen outside the web view
+#[derive(Debug, Clone, Serialize)]
+pub struct ApprovalAction {
+    /// None means the prompt the user is looking at (menu items)
+    pub prompt_id: Option<String>,
+    pub session_id: Option<String>,
+    pub approved: bool,
+}
+
+/// The platform's default menu with an Approvals submenu added
+pub fn build_menu<R: Runtime>(app: &AppHandle<R>) -> tauri::Result<Menu<R>> {
+    let menu = Menu::default(app)?;
+    let approvals = SubmenuBuilder::new(app, "Approvals")
+        .item(
+            &MenuItemBuilder::with_id(APPROVE_ID, "Approve Pending Tool")
+                .accelerator("CmdOrCtrl+Shift+Y")
+                .build(app)?,
+        )
+        .item(
+            &MenuItemBuilder::with_id(DENY_ID, "Deny Pending Tool")
+                .accelerator("CmdOrCtrl+Shift+N")
+                .build(app)?,
+        )
+        .build()?;
+    menu.append(&approvals)?;
+    Ok(menu)
+}
+
+pub fn handle_menu_event<R: Runtime>(app: &AppHandle<R>, event: MenuEvent) {
+    let approved = match event.id().as_ref() {
+        APPROVE_ID => true,
+        DENY_ID => false,
+        _ => return,
+    };
+    emit_action(
+        app,
+        ApprovalAction {
+            prompt_id: None,
+            session_id: None,
+            approved,
+        },
+    );
+}
+
+pub fn handle_shortcut<R: Runtime>(app: &AppHandle<R>, _shortcut: &Shortcut, event: ShortcutEvent) {
+    if event.state == ShortcutState::Pressed {
+        toggle_window(app);
+    }
+}
+
+/// Hide the window if it's showing, otherwise bring it to the front
+fn toggle_window<R: Runtime>(app: &AppHandle<R>) {
+    let Some(window) = app.get_webview_window("main") else {
+        return;
+    };
+    let focused = window.is_focused().unwrap_or(false);
+    if window.is_visible().unwrap_or(false) && focused {
+        let _ = window.hide();
+    } else {
+        show_window(app);
+    }
+}
+
+fn show_window<R: Runtime>(app: &AppHandle<R>) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.unminimize();
+        let _ = window.set_focus();
+    }
+}
+
+fn emit_action<R: Runtime>(app: &AppHandle<R>, action: ApprovalAction) {
+    if let Err(e) = app.emit("approval-action", action) {
+        eprintln!("Failed to send approval action: {}", e);
+    }
+}
+
+/// Notify about a tool waiting for approval
+///
+/// On Linux the notification has Approve and Deny buttons; elsewhere it is
+/// informational and the answer is given in the app.
+#[tauri::command]
+pub fn notify_approval(
+    app: AppHandle,
+    prompt_id: String,
+    session_id: String,
+    tool: String,
+    description: String,
+) -> Result<(), String> {
+    let title = format!("Approve {}?", tool);
+
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let handle = notify_rust::Notification::new()
+            .appname("Safe Coder")
+            .summary(&title)
+            .body(&description)
+            .action("approve", "Approve")
+            .action("deny", "Deny")
+            .action("default", "Open")
+            .show()
+            .map_err(|e| format!("Failed to show notification: {}", e))?;
+
+        // Waiting blocks until the notification is answered or dismissed
+        std::thread::spawn(move || {
+            handle.wait_for_action(|action| {
+                let approved = match action {
+                    "approve" => true,
+                    "deny" => false,
+                    "default" => return show_window(&app),
+                    _ => return,
+                };
+                emit_action(
+                    &app,
+                    ApprovalAction {
+                        prompt_id: Some(prompt_id),
+                        session_id: Some(session_id),
+                        approved,
+                    },
+                );
+            });
+        });
+    }
+
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    {
+        use tauri_plugin_notification::NotificationExt;
+
+        let _ = (prompt_id, session_id);
+        app.notification()
+            .builder()
+            .title(title)
+            .body(description)
+            .show()
+            .map_err(|e| format!("Failed to show notification: {}", e))?;
+    }
+
+    Ok(())
+}
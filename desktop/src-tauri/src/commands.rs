@@ -205,3 +205,167 @@ pub fn open_in_explorer(path: String) -> Result<(), String> {
 
     Ok(())
 }
+
+/// Save a pasted image to a temp file so it can be attached by path
+#[tauri::command]
+pub fn save_pasted_image(data: Vec<u8>, extension: String) -> Result<String, String> {
+    let extension = match extension.to_lowercase().as_str() {
+        "png" => "png",
+        "jpg" | "jpeg" => "jpg",
+        "gif" => "gif",
+        "webp" => "webp",
+        other => return Err(format!("Unsupported image type: {}", other)),
+    };
+
+    let dir = std::env::temp_dir().join("safe-coder");
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let path = dir.join(format!("paste-{}.{}", millis, extension));
+    std::fs::write(&path, data).map_err(|e| e.to_string())?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+/// Unpinned projects kept in the recent list
+const MAX_RECENT_PROJECTS: usize = 20;
+
+/// Settings a project's sessions start with; the server reads the same file
+/// (`safe_coder::persistence::projects`) for `GET /api/projects`
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct ProjectOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_preset: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ProjectEntry {
+    pub path: String,
+    pub last_opened: chrono::DateTime<chrono::Utc>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub overrides: ProjectOverrides,
+}
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+struct ProjectList {
+    #[serde(default)]
+    projects: Vec<ProjectEntry>,
+}
+
+/// `~/.config/safe-coder/projects.json`, shared with the CLI's config
+fn projects_path(app: &AppHandle) -> Result<PathBuf, String> {
+    let config_dir = app.path().config_dir().map_err(|e| e.to_string())?;
+    Ok(config_dir.join("safe-coder").join("projects.json"))
+}
+
+fn load_projects(app: &AppHandle) -> Result<ProjectList, String> {
+    let path = projects_path(app)?;
+    if !path.exists() {
+        return Ok(ProjectList::default());
+    }
+    let content = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Pinned projects first, then the most recently opened
+fn sort_projects(projects: &mut [ProjectEntry]) {
+    projects.sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.last_opened.cmp(&a.last_opened)));
+}
+
+/// Save the list, dropping the oldest unpinned projects
+fn save_projects(app: &AppHandle, mut list: ProjectList) -> Result<Vec<ProjectEntry>, String> {
+    sort_projects(&mut list.projects);
+    let mut unpinned = 0;
+    list.projects.retain(|p| {
+        unpinned += usize::from(!p.pinned);
+        p.pinned || unpinned <= MAX_RECENT_PROJECTS
+    });
+
+    let path = projects_path(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let json = serde_json::to_string_pretty(&list).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| e.to_string())?;
+    Ok(list.projects)
+}
+
+/// Apply `change` to the project at `path`, adding it if it is new
+fn update_project(
+    app: &AppHandle,
+    path: &str,
+    change: impl FnOnce(&mut ProjectEntry),
+) -> Result<Vec<ProjectEntry>, String> {
+    let mut list = load_projects(app)?;
+    let index = match list.projects.iter().position(|p| p.path == path) {
+        Some(index) => index,
+        None => {
+            list.projects.push(ProjectEntry {
+                path: path.to_string(),
+                last_opened: chrono::Utc::now(),
+                pinned: false,
+                overrides: ProjectOverrides::default(),
+            });
+            list.projects.len() - 1
+        }
+    };
+    change(&mut list.projects[index]);
+    save_projects(app, list)
+}
+
+/// Pinned and recent projects, pinned first
+#[tauri::command]
+pub fn list_projects(app: AppHandle) -> Result<Vec<ProjectEntry>, String> {
+    let mut list = load_projects(&app)?;
+    sort_projects(&mut list.projects);
+    Ok(list.projects)
+}
+
+/// Move a project to the top of the recent list; returns its entry
+#[tauri::command]
+pub fn record_project_opened(app: AppHandle, path: String) -> Result<ProjectEntry, String> {
+    let projects = update_project(&app, &path, |p| p.last_opened = chrono::Utc::now())?;
+    projects
+        .into_iter()
+        .find(|p| p.path == path)
+        .ok_or_else(|| format!("{} was not saved", path))
+}
+
+#[tauri::command]
+pub fn set_project_pinned(
+    app: AppHandle,
+    path: String,
+    pinned: bool,
+) -> Result<Vec<ProjectEntry>, String> {
+    update_project(&app, &path, |p| p.pinned = pinned)
+}
+
+/// Model, mode and permission preset new sessions in the project start with
+#[tauri::command]
+pub fn set_project_overrides(
+    app: AppHandle,
+    path: String,
+    overrides: ProjectOverrides,
+) -> Result<Vec<ProjectEntry>, String> {
+    let empty = |v: Option<String>| v.filter(|s| !s.trim().is_empty());
+    let overrides = ProjectOverrides {
+        model: empty(overrides.model),
+        mode: empty(overrides.mode),
+        permission_preset: empty(overrides.permission_preset),
+    };
+    update_project(&app, &path, |p| p.overrides = overrides)
+}
+
+#[tauri::command]
+pub fn remove_project(app: AppHandle, path: String) -> Result<Vec<ProjectEntry>, String> {
+    let mut list = load_projects(&app)?;
+    list.projects.retain(|p| p.path != path);
+    save_projects(&app, list)
+}
@@ -1,6 +1,7 @@
 // Prevents additional console window on Windows in release
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod approvals;
 mod commands;
 
 fn main() {
@@ -8,6 +9,16 @@ fn main() {
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_fs::init())
+        .plugin(tauri_plugin_notification::init())
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_shortcuts([approvals::TOGGLE_SHORTCUT])
+                .expect("invalid global shortcut")
+                .with_handler(approvals::handle_shortcut)
+                .build(),
+        )
+        .menu(approvals::build_menu)
+        .on_menu_event(approvals::handle_menu_event)
         .invoke_handler(tauri::generate_handler![
             commands::start_server,
             commands::stop_server,
@@ -15,6 +26,13 @@ fn main() {
             commands::is_server_ready,
             commands::select_directory,
             commands::open_in_explorer,
+            commands::save_pasted_image,
+            commands::list_projects,
+            commands::record_project_opened,
+            commands::set_project_pinned,
+            commands::set_project_overrides,
+            commands::remove_project,
+            approvals::notify_approval,
         ])
         .on_window_event(|_window, event| {
             // Clean up server when window is closed
@@ -5,8 +5,10 @@ import { Sidebar } from "./components/layout/Sidebar";
 import { ChatPanel } from "./components/layout/ChatPanel";
 import { ChangesPanel } from "./components/layout/ChangesPanel";
 import { DoomLoopModal } from "./components/chat/DoomLoopModal";
+import { ToolApprovalModal } from "./components/chat/ToolApprovalModal";
 import { useSessionStore } from "./stores/sessionStore";
 import { useSessionEvents } from "./hooks/useSSE";
+import { useApprovals } from "./hooks/useApprovals";
 import * as api from "./api/client";
 
 const DEFAULT_PORT = 9876;
@@ -20,6 +22,9 @@ function App() {
   // Subscribe to SSE events for the active session
   useSessionEvents(activeSessionId);
 
+  // Approval notifications, menu and shortcut actions for every session
+  useApprovals(serverStatus === "ready");
+
   // Start server and load sessions on mount
   useEffect(() => {
     const init = async () => {
@@ -111,6 +116,7 @@ function App() {
         changes={<ChangesPanel />}
       />
       <DoomLoopModal />
+      <ToolApprovalModal />
     </>
   );
 }
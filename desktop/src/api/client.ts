@@ -51,11 +51,17 @@ export async function listSessions(): Promise<{ sessions: Array<{
   created_at: string;
   message_count: number;
   file_changes: { total_files: number; additions: number; deletions: number };
+  is_processing: boolean;
+  clients: number;
 }> }> {
   return apiFetch("/api/sessions");
 }
 
-export async function createSession(projectPath: string, mode?: string): Promise<{
+export async function createSession(
+  projectPath: string,
+  mode?: string,
+  overrides: { model?: string; permission_preset?: string } = {}
+): Promise<{
   id: string;
   project_path: string;
   created_at: string;
@@ -63,10 +69,22 @@ export async function createSession(projectPath: string, mode?: string): Promise
 }> {
   return apiFetch("/api/sessions", {
     method: "POST",
-    body: JSON.stringify({ project_path: projectPath, mode }),
+    body: JSON.stringify({ project_path: projectPath, mode, ...overrides }),
   });
 }
 
+// Projects opened in the desktop app, pinned first
+export async function listProjects(): Promise<{ projects: Array<{
+  path: string;
+  name: string;
+  last_opened: string;
+  pinned: boolean;
+  exists: boolean;
+  overrides: { model?: string; mode?: string; permission_preset?: string };
+}> }> {
+  return apiFetch("/api/projects");
+}
+
 export async function getSession(sessionId: string): Promise<{
   id: string;
   project_path: string;
@@ -98,10 +116,16 @@ export async function getMessages(sessionId: string): Promise<Array<{
   return apiFetch(`/api/sessions/${sessionId}/messages`);
 }
 
+/** A file, directory, URL or image sent with a message; read on the server unless content is given */
+export interface AttachmentInput {
+  path: string;
+  content?: string;
+}
+
 export async function sendMessage(
   sessionId: string,
   content: string,
-  attachments: Array<{ path: string; content?: string }> = []
+  attachments: AttachmentInput[] = []
 ): Promise<{ status: string }> {
   return apiFetch(`/api/sessions/${sessionId}/messages`, {
     method: "POST",
@@ -124,6 +148,136 @@ export async function respondToDoomLoop(
   });
 }
 
+export async function respondToToolApproval(
+  sessionId: string,
+  promptId: string,
+  approved: boolean
+): Promise<{ status: string }> {
+  return apiFetch(`/api/sessions/${sessionId}/tool-approval-response`, {
+    method: "POST",
+    body: JSON.stringify({ prompt_id: promptId, approved }),
+  });
+}
+
+// Tool calls waiting for approval in any session, oldest first
+export async function listApprovals(): Promise<{ approvals: Array<{
+  prompt_id: string;
+  session_id: string;
+  tool: string;
+  description: string;
+  high_risk: boolean;
+  requested_at: string;
+}> }> {
+  return apiFetch("/api/approvals");
+}
+
+// Answer a tool approval without knowing its session
+export async function respondToApproval(
+  promptId: string,
+  approved: boolean
+): Promise<{ status: string }> {
+  return apiFetch(`/api/approvals/${promptId}`, {
+    method: "POST",
+    body: JSON.stringify({ approved }),
+  });
+}
+
+// Orchestration runs: a request split into tasks for CLI workers
+export interface Orchestration {
+  id: string;
+  project_path: string;
+  request: string;
+  created_at: string;
+  status: "planning" | "running" | "completed" | "failed";
+  run_id: string | null;
+  tasks: Array<{
+    id: string;
+    description: string;
+    worker: string;
+    status: "pending" | "running" | "cancelling" | "completed" | "failed" | "cancelled";
+    error?: string;
+  }>;
+  merged_tasks: string[];
+  branches: string[];
+  summary: string | null;
+  error: string | null;
+}
+
+export async function createOrchestration(
+  projectPath: string,
+  request: string,
+  options: { worker?: string; maxWorkers?: number } = {}
+): Promise<Orchestration> {
+  return apiFetch("/api/orchestrations", {
+    method: "POST",
+    body: JSON.stringify({
+      project_path: projectPath,
+      request,
+      worker: options.worker,
+      max_workers: options.maxWorkers,
+    }),
+  });
+}
+
+export async function listOrchestrations(): Promise<{ orchestrations: Orchestration[] }> {
+  return apiFetch("/api/orchestrations");
+}
+
+export async function getOrchestration(id: string): Promise<Orchestration> {
+  return apiFetch(`/api/orchestrations/${id}`);
+}
+
+export async function cancelOrchestrationTask(
+  id: string,
+  taskId: string
+): Promise<{ status: string }> {
+  return apiFetch(`/api/orchestrations/${id}/tasks/${taskId}/cancel`, {
+    method: "POST",
+  });
+}
+
+// A task's prompt, logs and diff, once the run's tasks have finished
+export async function getTaskArtifacts(
+  id: string,
+  taskId: string
+): Promise<{
+  task_id: string;
+  outcome: { success: boolean; error?: string; finished_at: string } | null;
+  prompt: string | null;
+  stdout: string | null;
+  stderr: string | null;
+  diff: string | null;
+  truncated: string[];
+}> {
+  return apiFetch(`/api/orchestrations/${id}/artifacts/${taskId}`);
+}
+
+// Follow a run's task events; the stream closes after "Finished"
+export function subscribeToOrchestrationEvents(
+  id: string,
+  onEvent: (event: { type: string; data: unknown }) => void,
+  onError?: (error: Error) => void
+): () => void {
+  const eventSource = new EventSource(`${baseUrl}/api/orchestrations/${id}/events`);
+
+  ["Planned", "TaskStarted", "TaskOutput", "TaskFinished", "Finished"].forEach((type) => {
+    eventSource.addEventListener(type, (event) => {
+      onEvent({ type, data: JSON.parse(event.data) });
+      if (type === "Finished") {
+        eventSource.close();
+      }
+    });
+  });
+
+  eventSource.onerror = () => {
+    if (eventSource.readyState !== EventSource.CLOSED) {
+      onError?.(new Error("SSE connection error"));
+    }
+  };
+
+  return () => eventSource.close();
+}
+
 // Project files (for @ mentions)
 export async function listProjectFiles(
   sessionId: string,
@@ -136,6 +290,28 @@ export async function listProjectFiles(
   return apiFetch(`/api/sessions/${sessionId}/files?${params.toString()}`);
 }
 
+// One directory of the project, or `depth` levels of it, skipping ignored files
+export async function listDirectory(
+  sessionId: string,
+  path: string = "",
+  depth: number = 1
+): Promise<{
+  files: Array<{ path: string; name: string; is_dir: boolean; size?: number }>;
+  truncated: boolean;
+}> {
+  const params = new URLSearchParams({ path, depth: depth.toString() });
+  return apiFetch(`/api/sessions/${sessionId}/files?${params.toString()}`);
+}
+
+// A project file's content; binary files come back without it
+export async function getFileContent(
+  sessionId: string,
+  path: string
+): Promise<{ path: string; size: number; is_text: boolean; content?: string }> {
+  const params = new URLSearchParams({ path });
+  return apiFetch(`/api/sessions/${sessionId}/files/content?${params.toString()}`);
+}
+
 // File changes
 export async function getSessionChanges(sessionId: string): Promise<{
   session_id: string;
@@ -152,13 +328,92 @@ export async function getSessionChanges(sessionId: string): Promise<{
   return apiFetch(`/api/sessions/${sessionId}/changes`);
 }
 
+// Everything changed since the session started, as structured hunks
+export async function getSessionDiff(sessionId: string): Promise<{
+  session_id: string;
+  base: string;
+  files: Array<{
+    path: string;
+    old_path?: string;
+    change_type: "created" | "modified" | "deleted" | "renamed";
+    additions: number;
+    deletions: number;
+    binary: boolean;
+    hunks: Array<{
+      header: string;
+      old_start: number;
+      old_lines: number;
+      new_start: number;
+      new_lines: number;
+      lines: Array<{
+        kind: "context" | "added" | "removed";
+        content: string;
+        old_line?: number;
+        new_line?: number;
+      }>;
+    }>;
+  }>;
+  additions: number;
+  deletions: number;
+}> {
+  return apiFetch(`/api/sessions/${sessionId}/diff`);
+}
+
+// Checkpoints: copies of the project taken before each message
+export interface Checkpoint {
+  id: string;
+  timestamp: string;
+  label: string;
+  files_count: number;
+  total_bytes: number;
+}
+
+export interface CheckpointDetail {
+  checkpoint: Checkpoint;
+  changes: Array<{ path: string; status: "modified" | "deleted" | "created" }>;
+}
+
+export async function listCheckpoints(
+  sessionId: string
+): Promise<{ session_id: string; enabled: boolean; checkpoints: Checkpoint[] }> {
+  return apiFetch(`/api/sessions/${sessionId}/checkpoints`);
+}
+
+export async function createCheckpoint(sessionId: string, label?: string): Promise<CheckpointDetail> {
+  return apiFetch(`/api/sessions/${sessionId}/checkpoints`, {
+    method: "POST",
+    body: JSON.stringify({ label }),
+  });
+}
+
+export async function getCheckpoint(sessionId: string, checkpointId: string): Promise<CheckpointDetail> {
+  return apiFetch(`/api/sessions/${sessionId}/checkpoints/${checkpointId}`);
+}
+
+// Restores the whole checkpoint, or only `files` when given
+export async function restoreCheckpoint(
+  sessionId: string,
+  checkpointId: string,
+  files?: string[]
+): Promise<{ checkpoint_id: string; restored: string[] }> {
+  return apiFetch(`/api/sessions/${sessionId}/checkpoints/${checkpointId}/restore`, {
+    method: "POST",
+    body: JSON.stringify({ files }),
+  });
+}
+
 // SSE event stream
+// `replay` replays up to that many events of the turn in progress first, for
+// attaching to a session another client (e.g. the TUI) is driving. After a
+// dropped connection EventSource reconnects by itself and sends
+// Last-Event-ID, so the server fills in the events missed meanwhile.
 export function subscribeToEvents(
   sessionId: string,
   onEvent: (event: { type: string; data: unknown }) => void,
-  onError?: (error: Error) => void
+  onError?: (error: Error) => void,
+  options: { replay?: number; onOpen?: () => void } = {}
 ): () => void {
-  const url = `${baseUrl}/api/sessions/${sessionId}/events`;
+  const url = `${baseUrl}/api/sessions/${sessionId}/events?replay=${options.replay ?? 0}`;
   const eventSource = new EventSource(url);
 
   // Handle different event types
@@ -173,6 +428,7 @@ export function subscribeToEvents(
     "FileDiff",
     "DiagnosticUpdate",
     "TextChunk",
+    "FileLinks",
     "SubagentStarted",
     "SubagentProgress",
     "SubagentCompleted",
@@ -184,7 +440,9 @@ export function subscribeToEvents(
     "PlanRejected",
     "TokenUsage",
     "ContextCompressed",
+    "RateLimited",
     "DoomLoopPrompt",
+    "ToolApprovalPrompt",
     "Error",
     "Completed",
   ];
@@ -200,6 +458,10 @@ export function subscribeToEvents(
     });
   });
 
+  eventSource.onopen = () => {
+    options.onOpen?.();
+  };
+
   eventSource.onerror = () => {
     onError?.(new Error("SSE connection error"));
   };
@@ -0,0 +1,123 @@
+import { useEffect, useState } from "react";
+import { History, RotateCcw } from "lucide-react";
+import { useSessionStore } from "../../stores/sessionStore";
+import * as api from "../../api/client";
+import { cn, truncatePath } from "../../lib/utils";
+
+const STATUS_COLORS = {
+  modified: "text-amber-500",
+  deleted: "text-destructive",
+  created: "text-success",
+} as const;
+
+// Checkpoints of the session's project, newest first. Expanding one shows
+// the files changed since, each of which can be restored on its own.
+export function CheckpointList() {
+  const sessionId = useSessionStore((s) => s.activeSessionId);
+  const isProcessing = useSessionStore((s) => s.isProcessing);
+  const loadFileChanges = useSessionStore((s) => s.loadFileChanges);
+  const [checkpoints, setCheckpoints] = useState<api.Checkpoint[]>([]);
+  const [open, setOpen] = useState<api.CheckpointDetail | null>(null);
+
+  // New checkpoints are taken as messages are sent
+  useEffect(() => {
+    if (!sessionId || isProcessing) return;
+    api
+      .listCheckpoints(sessionId)
+      .then((r) => setCheckpoints([...r.checkpoints].reverse()))
+      .catch((error) => console.error("Failed to load checkpoints:", error));
+  }, [sessionId, isProcessing]);
+
+  if (!sessionId || checkpoints.length === 0) {
+    return null;
+  }
+
+  const toggle = async (id: string) => {
+    if (open?.checkpoint.id === id) {
+      setOpen(null);
+      return;
+    }
+    try {
+      setOpen(await api.getCheckpoint(sessionId, id));
+    } catch (error) {
+      console.error("Failed to load checkpoint:", error);
+    }
+  };
+
+  const restore = async (id: string, files?: string[]) => {
+    const what = files ? files.join(", ") : "every file in this checkpoint";
+    if (!window.confirm(`Restore ${what}?`)) return;
+    try {
+      await api.restoreCheckpoint(sessionId, id, files);
+      setOpen(await api.getCheckpoint(sessionId, id));
+      await loadFileChanges();
+    } catch (error) {
+      console.error("Failed to restore checkpoint:", error);
+    }
+  };
+
+  return (
+    <div className="border-t border-border">
+      <div className="flex items-center gap-2 px-4 py-2.5 text-sm font-medium">
+        <History className="w-4 h-4 text-muted-foreground" />
+        Checkpoints
+      </div>
+      <div className="max-h-64 overflow-y-auto px-2 pb-2 space-y-0.5">
+        {checkpoints.map((checkpoint) => (
+          <div key={checkpoint.id}>
+            <div
+              onClick={() => toggle(checkpoint.id)}
+              className="group flex items-center gap-2 px-2 py-1.5 rounded-md cursor-pointer hover:bg-muted transition-colors"
+            >
+              <div className="flex-1 min-w-0">
+                <div className="text-xs truncate" title={checkpoint.label}>
+                  {checkpoint.label}
+                </div>
+                <div className="text-[10px] text-muted-foreground">
+                  {new Date(checkpoint.timestamp).toLocaleString()}
+                </div>
+              </div>
+              <button
+                onClick={(e) => {
+                  e.stopPropagation();
+                  restore(checkpoint.id);
+                }}
+                disabled={isProcessing}
+                className="p-1 opacity-0 group-hover:opacity-100 text-muted-foreground hover:text-foreground rounded transition-all disabled:opacity-30"
+                title="Restore this checkpoint"
+              >
+                <RotateCcw className="w-3 h-3" />
+              </button>
+            </div>
+
+            {open?.checkpoint.id === checkpoint.id && (
+              <div className="mx-2 mb-2 p-2 space-y-0.5 rounded-md bg-muted/50 text-xs">
+                {open.changes.length === 0 && (
+                  <div className="text-muted-foreground">No changes since this checkpoint</div>
+                )}
+                {open.changes.map((change) => (
+                  <div key={change.path} className="group flex items-center gap-2">
+                    <span className={cn("w-14 flex-shrink-0", STATUS_COLORS[change.status])}>
+                      {change.status}
+                    </span>
+                    <span className="flex-1 font-mono truncate" title={change.path}>
+                      {truncatePath(change.path, 30)}
+                    </span>
+                    <button
+                      onClick={() => restore(checkpoint.id, [change.path])}
+                      disabled={isProcessing}
+                      className="p-0.5 opacity-0 group-hover:opacity-100 text-muted-foreground hover:text-foreground rounded transition-all disabled:opacity-30"
+                      title={change.status === "created" ? "Remove this file" : "Restore this file"}
+                    >
+                      <RotateCcw className="w-3 h-3" />
+                    </button>
+                  </div>
+                ))}
+              </div>
+            )}
+          </div>
+        ))}
+      </div>
+    </div>
+  );
+}
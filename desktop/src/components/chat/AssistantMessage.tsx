@@ -279,6 +279,22 @@ export function AssistantMessage({ message, isStreaming }: AssistantMessageProps
             <span className="streaming-cursor" aria-hidden="true" />
           )}
         </div>
+        {!isStreaming && message.links && message.links.length > 0 && (
+          <div className="mt-2 flex flex-wrap gap-1.5">
+            {message.links.map((link) => (
+              <a
+                key={link.url}
+                href={link.url}
+                target="_blank"
+                rel="noopener noreferrer"
+                className="flex items-center gap-1 bg-muted/50 hover:bg-muted px-2 py-0.5 rounded text-xs font-mono text-primary/90"
+              >
+                <FileCode className="w-3 h-3" />
+                {link.line ? `${link.path}:${link.line}` : link.path}
+              </a>
+            ))}
+          </div>
+        )}
       </div>
     </div>
   );
@@ -0,0 +1,56 @@
+import { ShieldAlert, XCircle, CheckCircle } from "lucide-react";
+import { useSessionStore } from "../../stores/sessionStore";
+
+export function ToolApprovalModal() {
+  const toolApprovalPrompt = useSessionStore((s) => s.toolApprovalPrompt);
+  const respondToToolApproval = useSessionStore((s) => s.respondToToolApproval);
+
+  if (!toolApprovalPrompt) return null;
+
+  const headerClass = toolApprovalPrompt.highRisk
+    ? "bg-destructive/10 text-destructive"
+    : "bg-muted/20 text-foreground";
+
+  return (
+    <div className="fixed inset-0 z-50 flex items-center justify-center bg-black/60 backdrop-blur-sm">
+      <div className="bg-background border border-border rounded-lg shadow-xl max-w-md w-full mx-4 animate-in fade-in-0 zoom-in-95 duration-200">
+        {/* Header */}
+        <div className={`flex items-center gap-3 px-4 py-3 border-b border-border ${headerClass}`}>
+          <ShieldAlert className="w-5 h-5" />
+          <h2 className="text-sm font-semibold">
+            {toolApprovalPrompt.highRisk ? "High-Risk Action" : "Tool Approval Required"}
+          </h2>
+        </div>
+
+        {/* Content */}
+        <div className="p-4 space-y-3">
+          <p className="text-sm">
+            <span className="text-muted-foreground">Tool: </span>
+            <span className="font-mono">{toolApprovalPrompt.tool}</span>
+          </p>
+          <p className="text-sm text-muted-foreground break-words">
+            {toolApprovalPrompt.description}
+          </p>
+        </div>
+
+        {/* Actions */}
+        <div className="flex gap-2 px-4 py-3 border-t border-border bg-muted/20">
+          <button
+            onClick={() => respondToToolApproval(false)}
+            className="flex-1 flex items-center justify-center gap-2 px-3 py-2 rounded-md bg-muted hover:bg-muted/80 text-sm font-medium transition-colors"
+          >
+            <XCircle className="w-4 h-4" />
+            Deny
+          </button>
+          <button
+            onClick={() => respondToToolApproval(true)}
+            className="flex-1 flex items-center justify-center gap-2 px-3 py-2 rounded-md bg-primary hover:bg-primary/90 text-primary-foreground text-sm font-medium transition-colors"
+          >
+            <CheckCircle className="w-4 h-4" />
+            Allow
+          </button>
+        </div>
+      </div>
+    </div>
+  );
+}
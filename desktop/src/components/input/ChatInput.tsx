@@ -2,6 +2,7 @@ import { useState, useRef, useEffect, useCallback } from "react";
 import { Square, Image, ArrowUp, ChevronDown, X, FileCode2 } from "lucide-react";
 import { open } from "@tauri-apps/plugin-dialog";
 import { readFile } from "@tauri-apps/plugin-fs";
+import { invoke } from "@tauri-apps/api/core";
 import { useSessionStore } from "../../stores/sessionStore";
 import * as api from "../../api/client";
 import { cn } from "../../lib/utils";
@@ -79,7 +80,7 @@ export function ChatInput() {
         filters: [
           {
             name: "Images",
-            extensions: ["png", "jpg", "jpeg", "gif", "webp", "bmp"],
+            extensions: ["png", "jpg", "jpeg", "gif", "webp"],
           },
         ],
         title: "Select Images",
@@ -120,6 +121,28 @@ export function ChatInput() {
     }
   };
 
+  // Pasted images are saved to a temp file and attached by path
+  const handlePaste = async (e: React.ClipboardEvent<HTMLTextAreaElement>) => {
+    const images = Array.from(e.clipboardData.items)
+      .filter((item) => item.kind === "file" && item.type.startsWith("image/"))
+      .map((item) => item.getAsFile())
+      .filter((file): file is File => file !== null);
+    if (images.length === 0) return;
+    e.preventDefault();
+
+    for (const image of images) {
+      try {
+        const data = Array.from(new Uint8Array(await image.arrayBuffer()));
+        const extension = image.type.split("/")[1] || "png";
+        const path = await invoke<string>("save_pasted_image", { data, extension });
+        const name = path.split(/[\\/]/).pop() || path;
+        setAttachments((prev) => [...prev, { path, name, type: "image" }]);
+      } catch (err) {
+        console.error("Failed to paste image:", err);
+      }
+    }
+  };
+
   // Insert mention into input
   const insertMention = useCallback(
     (file: ProjectFile) => {
@@ -155,11 +178,11 @@ export function ChatInput() {
     if ((!input.trim() && attachments.length === 0) || isProcessing) return;
 
     const message = input.trim();
+    const sent = attachments.map(({ path, content }) => ({ path, content }));
     setInput("");
     setAttachments([]);
 
-    // Send message (attachments to be implemented later)
-    await sendMessage(message);
+    await sendMessage(message, sent);
   };
 
   const handleKeyDown = (e: React.KeyboardEvent) => {
@@ -305,6 +328,7 @@ export function ChatInput() {
             value={input}
             onChange={handleInputChange}
             onKeyDown={handleKeyDown}
+            onPaste={handlePaste}
             placeholder='Ask anything... Use @ to mention files'
             disabled={isProcessing}
             rows={1}
@@ -378,7 +402,7 @@ export function ChatInput() {
 
         {/* Hints */}
         <div className="flex items-center justify-between mt-2 text-[10px] text-muted-foreground">
-          <span>Enter to send • Shift+Enter for new line • @ to mention files</span>
+          <span>Enter to send • Shift+Enter for new line • @ to mention files • paste images</span>
         </div>
       </div>
     </div>
@@ -1,6 +1,7 @@
 import { useState } from "react";
 import { ChevronDown, ChevronRight, FileCode2, ChevronsDownUp, Plus } from "lucide-react";
 import { useSessionStore } from "../../stores/sessionStore";
+import { CheckpointList } from "../changes/CheckpointList";
 import { DiffViewer } from "../changes/DiffViewer";
 import { cn, truncatePath, getFileExtension } from "../../lib/utils";
 
@@ -142,6 +143,8 @@ export function ChangesPanel() {
         )}
       </div>
 
+      <CheckpointList />
+
       {/* Footer stats */}
       {fileChanges.length > 0 && (
         <div className="px-4 py-2.5 border-t border-border bg-muted/30">
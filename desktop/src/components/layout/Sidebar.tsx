@@ -3,6 +3,7 @@ import { open } from "@tauri-apps/plugin-dialog";
 import { Settings, MessageSquare, Folder } from "lucide-react";
 import { useSessionStore } from "../../stores/sessionStore";
 import { SessionList } from "../sidebar/SessionList";
+import { ProjectPicker } from "../sidebar/ProjectPicker";
 import { cn } from "../../lib/utils";
 
 export function Sidebar() {
@@ -72,6 +73,9 @@ export function Sidebar() {
             Safe Coder uses GitHub Copilot so you can start immediately.
           </p>
 
+          {/* Pinned and recent projects */}
+          <ProjectPicker onOpen={handleCreateSession} disabled={isCreating} />
+
           {/* Quick action buttons */}
           <div className="space-y-2">
             <button
@@ -0,0 +1,172 @@
+import { useEffect, useState } from "react";
+import { Star, Settings2, X } from "lucide-react";
+import { useSessionStore } from "../../stores/sessionStore";
+import {
+  listProjects,
+  removeProject,
+  setProjectOverrides,
+  setProjectPinned,
+} from "../../lib/projects";
+import { cn } from "../../lib/utils";
+import type { Project, ProjectOverrides } from "../../types";
+
+const PRESETS = ["safe", "dev", "full", "yolo"] as const;
+
+interface ProjectPickerProps {
+  onOpen: (path: string) => void;
+  disabled?: boolean;
+}
+
+// Pinned and recent projects, each with settings for the sessions opened there
+export function ProjectPicker({ onOpen, disabled }: ProjectPickerProps) {
+  const sessions = useSessionStore((s) => s.sessions);
+  const [projects, setProjects] = useState<Project[]>([]);
+  const [editing, setEditing] = useState<string | null>(null);
+  const [draft, setDraft] = useState<ProjectOverrides>({});
+
+  // Reload when sessions change, since opening a project records it
+  useEffect(() => {
+    listProjects()
+      .then(setProjects)
+      .catch((error) => console.error("Failed to load projects:", error));
+  }, [sessions.length]);
+
+  const update = async (change: Promise<Project[]>) => {
+    try {
+      setProjects(await change);
+    } catch (error) {
+      console.error("Failed to update project:", error);
+    }
+  };
+
+  const startEditing = (project: Project) => {
+    setEditing(editing === project.path ? null : project.path);
+    setDraft(project.overrides);
+  };
+
+  const saveOverrides = async (path: string) => {
+    await update(setProjectOverrides(path, draft));
+    setEditing(null);
+  };
+
+  if (projects.length === 0) {
+    return null;
+  }
+
+  return (
+    <div className="mb-3 space-y-0.5">
+      {projects.map((project) => {
+        const name = project.path.split("/").pop() || project.path;
+        const { model, mode, permission_preset } = project.overrides;
+        const summary = [model, mode, permission_preset].filter(Boolean).join(" · ");
+
+        return (
+          <div key={project.path}>
+            <div
+              onClick={() => !disabled && onOpen(project.path)}
+              title={project.path}
+              className="group flex items-center gap-2 px-2 py-1.5 rounded-md cursor-pointer hover:bg-muted transition-colors"
+            >
+              <button
+                onClick={(e) => {
+                  e.stopPropagation();
+                  update(setProjectPinned(project.path, !project.pinned));
+                }}
+                className={cn(
+                  "p-0.5 rounded transition-colors",
+                  project.pinned
+                    ? "text-amber-500"
+                    : "text-muted-foreground/40 hover:text-muted-foreground"
+                )}
+                title={project.pinned ? "Unpin" : "Pin to favorites"}
+              >
+                <Star className={cn("w-3 h-3", project.pinned && "fill-current")} />
+              </button>
+              <div className="flex-1 min-w-0">
+                <div className="text-xs text-foreground truncate">{name}</div>
+                {summary && (
+                  <div className="text-[10px] text-muted-foreground truncate">{summary}</div>
+                )}
+              </div>
+              <button
+                onClick={(e) => {
+                  e.stopPropagation();
+                  startEditing(project);
+                }}
+                className="p-1 opacity-0 group-hover:opacity-100 text-muted-foreground hover:text-foreground rounded transition-all"
+                title="Session settings for this project"
+              >
+                <Settings2 className="w-3 h-3" />
+              </button>
+              <button
+                onClick={(e) => {
+                  e.stopPropagation();
+                  update(removeProject(project.path));
+                }}
+                className="p-1 opacity-0 group-hover:opacity-100 text-muted-foreground hover:text-destructive rounded transition-all"
+                title="Remove from recent projects"
+              >
+                <X className="w-3 h-3" />
+              </button>
+            </div>
+
+            {editing === project.path && (
+              <div className="mx-2 mb-2 p-2 space-y-2 rounded-md bg-muted/50 text-xs">
+                <input
+                  value={draft.model ?? ""}
+                  onChange={(e) => setDraft({ ...draft, model: e.target.value || undefined })}
+                  placeholder="Model (default from config)"
+                  className="w-full px-2 py-1 rounded bg-background border border-border"
+                />
+                <div className="flex gap-2">
+                  <select
+                    value={draft.mode ?? ""}
+                    onChange={(e) =>
+                      setDraft({ ...draft, mode: (e.target.value || undefined) as ProjectOverrides["mode"] })
+                    }
+                    className="flex-1 px-1 py-1 rounded bg-background border border-border"
+                  >
+                    <option value="">Mode: default</option>
+                    <option value="build">Build</option>
+                    <option value="plan">Plan</option>
+                  </select>
+                  <select
+                    value={draft.permission_preset ?? ""}
+                    onChange={(e) =>
+                      setDraft({
+                        ...draft,
+                        permission_preset: (e.target.value || undefined) as ProjectOverrides["permission_preset"],
+                      })
+                    }
+                    className="flex-1 px-1 py-1 rounded bg-background border border-border"
+                  >
+                    <option value="">Permissions: default</option>
+                    {PRESETS.map((preset) => (
+                      <option key={preset} value={preset}>
+                        {preset}
+                      </option>
+                    ))}
+                  </select>
+                </div>
+                <div className="flex justify-end gap-2">
+                  <button
+                    onClick={() => setEditing(null)}
+                    className="px-2 py-1 text-muted-foreground hover:text-foreground"
+                  >
+                    Cancel
+                  </button>
+                  <button
+                    onClick={() => saveOverrides(project.path)}
+                    className="px-2 py-1 rounded bg-primary text-primary-foreground"
+                  >
+                    Save
+                  </button>
+                </div>
+              </div>
+            )}
+          </div>
+        );
+      })}
+    </div>
+  );
+}
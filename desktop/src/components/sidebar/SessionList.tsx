@@ -1,5 +1,5 @@
 import { useEffect, useRef, useState, useMemo } from "react";
-import { Trash2, RefreshCw, MessageSquare, FolderOpen, ChevronRight, Plus } from "lucide-react";
+import { Trash2, RefreshCw, MessageSquare, FolderOpen, ChevronRight, Plus, Users } from "lucide-react";
 import { open } from "@tauri-apps/plugin-dialog";
 import { useSessionStore } from "../../stores/sessionStore";
 import { formatTimestamp, cn } from "../../lib/utils";
@@ -249,6 +249,18 @@ export function SessionList() {
                           <span className="text-[10px] text-muted-foreground/60">
                             {formatTimestamp(session.created_at)}
                           </span>
+                          {session.is_processing && (
+                            <span className="text-[10px] text-primary">busy</span>
+                          )}
+                          {session.clients > 0 && (
+                            <span
+                              className="flex items-center gap-0.5 text-[10px] text-muted-foreground/60"
+                              title="Clients attached, e.g. the terminal"
+                            >
+                              <Users className="w-2.5 h-2.5" />
+                              {session.clients}
+                            </span>
+                          )}
                         </div>
 
                         {/* File changes badge */}
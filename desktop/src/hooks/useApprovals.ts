@@ -0,0 +1,74 @@
+import { useEffect, useRef } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { listApprovals } from "../api/client";
+import { notifyIfUnfocused } from "../lib/notifications";
+import { useSessionStore } from "../stores/sessionStore";
+import type { PendingApproval } from "../types";
+
+const POLL_INTERVAL_MS = 2000;
+
+// Sent by the native menu and notification buttons
+interface ApprovalAction {
+  prompt_id: string | null;
+  session_id: string | null;
+  approved: boolean;
+}
+
+// Notify about tool approvals in every session, and answer them from
+// notification buttons or the Approvals menu
+export function useApprovals(enabled: boolean) {
+  const pendingRef = useRef<PendingApproval[]>([]);
+
+  useEffect(() => {
+    if (!enabled) return;
+
+    const isTauri = "__TAURI__" in window;
+    const notified = new Set<string>();
+
+    const poll = async () => {
+      try {
+        const { approvals } = await listApprovals();
+        pendingRef.current = approvals;
+        for (const approval of approvals) {
+          if (notified.has(approval.prompt_id)) continue;
+          notified.add(approval.prompt_id);
+          if (document.hasFocus()) continue;
+          if (isTauri) {
+            await invoke("notify_approval", {
+              promptId: approval.prompt_id,
+              sessionId: approval.session_id,
+              tool: approval.tool,
+              description: approval.description,
+            });
+          } else {
+            notifyIfUnfocused("Approval needed", `${approval.tool}: ${approval.description}`);
+          }
+        }
+      } catch (error) {
+        console.error("Failed to check pending approvals:", error);
+      }
+    };
+
+    poll();
+    const interval = setInterval(poll, POLL_INTERVAL_MS);
+
+    // Menu items answer the prompt on screen, or else the oldest one
+    const unlisten = isTauri
+      ? listen<ApprovalAction>("approval-action", ({ payload }) => {
+          const promptId =
+            payload.prompt_id ??
+            useSessionStore.getState().toolApprovalPrompt?.id ??
+            pendingRef.current[0]?.prompt_id;
+          if (promptId) {
+            useSessionStore.getState().answerApproval(promptId, payload.approved);
+          }
+        })
+      : null;
+
+    return () => {
+      clearInterval(interval);
+      unlisten?.then((fn) => fn());
+    };
+  }, [enabled]);
+}
@@ -3,6 +3,9 @@ import { subscribeToEvents } from "../api/client";
 import { useSessionStore } from "../stores/sessionStore";
 import type { ServerEvent } from "../types";
 
+// Matches the server's replay buffer
+const REPLAY_EVENTS = 1000;
+
 export function useSessionEvents(sessionId: string | null) {
   const cleanupRef = useRef<(() => void) | null>(null);
 
@@ -28,6 +31,11 @@ export function useSessionEvents(sessionId: string | null) {
       return;
     }
 
+    // A session busy with a turn started elsewhere: replay the turn so far
+    const busy = useSessionStore
+      .getState()
+      .sessions.some((s) => s.id === sessionId && s.is_processing);
+
     // Subscribe to events
     const cleanup = subscribeToEvents(
       sessionId,
@@ -38,6 +46,11 @@ export function useSessionEvents(sessionId: string | null) {
       },
       () => {
         setIsConnectedRef.current(false);
+      },
+      {
+        replay: busy ? REPLAY_EVENTS : 0,
+        // Also fires after EventSource reconnects on its own
+        onOpen: () => setIsConnectedRef.current(true),
       }
     );
 
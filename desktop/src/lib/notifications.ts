@@ -0,0 +1,28 @@
+import {
+  isPermissionGranted,
+  requestPermission,
+  sendNotification,
+} from "@tauri-apps/plugin-notification";
+
+// Replies that took at least this long notify when they finish
+export const LONG_TASK_MS = 30_000;
+
+let permission: Promise<boolean> | null = null;
+
+async function canNotify(): Promise<boolean> {
+  if (!permission) {
+    permission = (async () => {
+      if (await isPermissionGranted()) return true;
+      return (await requestPermission()) === "granted";
+    })().catch(() => false);
+  }
+  return permission;
+}
+
+// Show a native notification, but only while the window is in the background
+export async function notifyIfUnfocused(title: string, body: string) {
+  if (document.hasFocus()) return;
+  if (await canNotify()) {
+    sendNotification({ title, body });
+  }
+}
@@ -0,0 +1,25 @@
+// Recent and pinned projects, persisted by the Tauri backend in
+// ~/.config/safe-coder/projects.json (the server lists them at /api/projects)
+
+import { invoke } from "@tauri-apps/api/core";
+import type { Project, ProjectOverrides } from "../types";
+
+export function listProjects(): Promise<Project[]> {
+  return invoke<Project[]>("list_projects");
+}
+
+export function recordProjectOpened(path: string): Promise<Project> {
+  return invoke<Project>("record_project_opened", { path });
+}
+
+export function setProjectPinned(path: string, pinned: boolean): Promise<Project[]> {
+  return invoke<Project[]>("set_project_pinned", { path, pinned });
+}
+
+export function setProjectOverrides(path: string, overrides: ProjectOverrides): Promise<Project[]> {
+  return invoke<Project[]>("set_project_overrides", { path, overrides });
+}
+
+export function removeProject(path: string): Promise<Project[]> {
+  return invoke<Project[]>("remove_project", { path });
+}
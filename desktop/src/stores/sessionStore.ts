@@ -9,9 +9,13 @@ import type {
   AgentMode,
   ServerEvent,
   DoomLoopPrompt,
+  ToolApprovalPrompt,
   TodoItem,
+  ProjectOverrides,
 } from "../types";
 import * as api from "../api/client";
+import { recordProjectOpened } from "../lib/projects";
+import { LONG_TASK_MS, notifyIfUnfocused } from "../lib/notifications";
 
 // Text chunk batching for smoother streaming
 let textBuffer = "";
@@ -19,6 +23,20 @@ let flushTimeout: ReturnType<typeof setTimeout> | null = null;
 let isFirstChunk = true;
 const FLUSH_INTERVAL_MS = 16; // ~60fps for smooth updates
 
+// When the current reply was requested, for "long task finished" notifications
+let turnStartedAt: number | null = null;
+
+function notifyTurnFinished(success: boolean) {
+  if (turnStartedAt !== null && Date.now() - turnStartedAt >= LONG_TASK_MS) {
+    const secs = Math.round((Date.now() - turnStartedAt) / 1000);
+    notifyIfUnfocused(
+      success ? "Task finished" : "Task failed",
+      `Safe Coder ${success ? "finished" : "stopped"} after ${secs}s`
+    );
+  }
+  turnStartedAt = null;
+}
+
 interface SessionState {
   // Session data
   sessions: SessionSummary[];
@@ -47,6 +65,9 @@ interface SessionState {
   // Doom loop
   doomLoopPrompt: DoomLoopPrompt | null;
 
+  // Tool approval
+  toolApprovalPrompt: ToolApprovalPrompt | null;
+
   // Todo list
   todoList: TodoItem[];
 
@@ -57,10 +78,12 @@ interface SessionState {
   deleteSession: (sessionId: string) => Promise<void>;
   loadMessages: () => Promise<void>;
   loadFileChanges: () => Promise<void>;
-  sendMessage: (content: string) => Promise<void>;
+  sendMessage: (content: string, attachments?: api.AttachmentInput[]) => Promise<void>;
   cancelOperation: () => Promise<void>;
   setAgentMode: (mode: AgentMode) => void;
   respondToDoomLoop: (continueAnyway: boolean) => Promise<void>;
+  respondToToolApproval: (approved: boolean) => Promise<void>;
+  answerApproval: (promptId: string, approved: boolean) => Promise<void>;
 
   // Event handlers
   handleServerEvent: (event: ServerEvent) => void;
@@ -87,6 +110,7 @@ export const useSessionStore = create<SessionState>((set, get) => ({
   },
   agentMode: "build",
   doomLoopPrompt: null,
+  toolApprovalPrompt: null,
   todoList: [],
 
   // Load all sessions
@@ -101,7 +125,17 @@ export const useSessionStore = create<SessionState>((set, get) => ({
 
   // Create a new session
   createSession: async (projectPath: string) => {
-    const session = await api.createSession(projectPath, get().agentMode);
+    // Record it as recent and start with the project's own settings
+    let overrides: ProjectOverrides = {};
+    try {
+      overrides = (await recordProjectOpened(projectPath)).overrides;
+    } catch (error) {
+      console.error("Failed to record recent project:", error);
+    }
+    const session = await api.createSession(projectPath, overrides.mode ?? get().agentMode, {
+      model: overrides.model,
+      permission_preset: overrides.permission_preset,
+    });
     await get().loadSessions();
     await get().selectSession(session.id);
     return session;
@@ -183,11 +217,39 @@ export const useSessionStore = create<SessionState>((set, get) => ({
     }
   },
 
-  // Load file changes for active session
+  // Load file changes for active session: the diff against the session's
+  // start snapshot, or plain git status outside git repositories
   loadFileChanges: async () => {
     const sessionId = get().activeSessionId;
     if (!sessionId) return;
 
+    try {
+      const diff = await api.getSessionDiff(sessionId);
+      const timestamp = new Date().toISOString();
+      set({
+        fileChanges: diff.files.map((f) => ({
+          path: f.path,
+          change_type: f.change_type,
+          additions: f.additions,
+          deletions: f.deletions,
+          timestamp,
+          diff: f.binary
+            ? undefined
+            : f.hunks
+                .flatMap((h) => [
+                  h.header,
+                  ...h.lines.map(
+                    (l) => (l.kind === "added" ? "+" : l.kind === "removed" ? "-" : " ") + l.content
+                  ),
+                ])
+                .join("\n"),
+        })),
+      });
+      return;
+    } catch {
+      // No snapshot for this project; fall back to git status
+    }
+
     try {
       const response = await api.getSessionChanges(sessionId);
       set({
@@ -202,9 +264,10 @@ export const useSessionStore = create<SessionState>((set, get) => ({
   },
 
   // Send a message
-  sendMessage: async (content: string) => {
+  sendMessage: async (content: string, attachments: api.AttachmentInput[] = []) => {
     const sessionId = get().activeSessionId;
     if (!sessionId) return;
+    turnStartedAt = Date.now();
 
     // Add user message to UI immediately
     const userMessage: Message = {
@@ -226,7 +289,7 @@ export const useSessionStore = create<SessionState>((set, get) => ({
     }));
 
     try {
-      await api.sendMessage(sessionId, content);
+      await api.sendMessage(sessionId, content, attachments);
 
       // Safety timeout in case SSE drops the Completed event
       setTimeout(() => {
@@ -290,6 +353,32 @@ export const useSessionStore = create<SessionState>((set, get) => ({
     }
   },
 
+  // Respond to tool approval prompt
+  respondToToolApproval: async (approved: boolean) => {
+    const sessionId = get().activeSessionId;
+    const prompt = get().toolApprovalPrompt;
+    if (!sessionId || !prompt) return;
+
+    try {
+      await api.respondToToolApproval(sessionId, prompt.id, approved);
+      set({ toolApprovalPrompt: null });
+    } catch (error) {
+      console.error("Failed to respond to tool approval:", error);
+    }
+  },
+
+  // Answer from a notification or menu, possibly for another session
+  answerApproval: async (promptId: string, approved: boolean) => {
+    try {
+      await api.respondToApproval(promptId, approved);
+      if (get().toolApprovalPrompt?.id === promptId) {
+        set({ toolApprovalPrompt: null });
+      }
+    } catch (error) {
+      console.error("Failed to respond to tool approval:", error);
+    }
+  },
+
   // Handle server events
   handleServerEvent: (event: ServerEvent) => {
     const currentMessages = get().messages;
@@ -331,6 +420,24 @@ export const useSessionStore = create<SessionState>((set, get) => ({
         }
         break;
 
+      case "FileLinks":
+        set((state) => {
+          if (state.streamingMessage) {
+            return {
+              streamingMessage: {
+                ...state.streamingMessage,
+                links: [...(state.streamingMessage.links || []), ...event.links],
+              },
+            };
+          }
+          // Links for the end of the text can arrive after Completed
+          const last = state.messages[state.messages.length - 1];
+          if (last?.role !== "assistant") return {};
+          const updated = { ...last, links: [...(last.links || []), ...event.links] };
+          return { messages: [...state.messages.slice(0, -1), updated] };
+        });
+        break;
+
       case "TextChunk":
         // Batch text chunks for smoother rendering
         // Add newline if current buffer ends with punctuation and new text starts with capital
@@ -543,8 +650,16 @@ export const useSessionStore = create<SessionState>((set, get) => ({
         }));
         break;
 
+      case "RateLimited":
+        // Shown in the thinking indicator until the retry starts streaming
+        set({
+          thinkingMessage: `Rate limited by ${event.provider}, retrying in ${event.retry_in_secs}s (attempt ${event.attempt}/${event.max_retries})`,
+        });
+        break;
+
       case "DoomLoopPrompt":
         // Show doom loop prompt to user for approval
+        notifyIfUnfocused("Approval needed", event.message);
         set({
           doomLoopPrompt: {
             id: event.prompt_id,
@@ -553,6 +668,18 @@ export const useSessionStore = create<SessionState>((set, get) => ({
         });
         break;
 
+      case "ToolApprovalPrompt":
+        // Ask user before running the tool; useApprovals sends the notification
+        set({
+          toolApprovalPrompt: {
+            id: event.prompt_id,
+            tool: event.tool,
+            description: event.description,
+            highRisk: event.high_risk,
+          },
+        });
+        break;
+
       case "TodoList":
         // Update todo list
         set({ todoList: event.todos });
@@ -567,10 +694,12 @@ export const useSessionStore = create<SessionState>((set, get) => ({
         textBuffer = "";
         isFirstChunk = true;
         console.error("Server error:", event.message);
+        notifyTurnFinished(false);
         set({ isProcessing: false, streamingMessage: null, thinkingMessage: null });
         break;
 
       case "Completed":
+        notifyTurnFinished(true);
         // Flush any pending text immediately
         if (flushTimeout) {
           clearTimeout(flushTimeout);
@@ -601,6 +730,7 @@ export const useSessionStore = create<SessionState>((set, get) => ({
               toolExecutions: state.streamingMessage.toolExecutions.length > 0
                 ? [...state.streamingMessage.toolExecutions]
                 : undefined,
+              links: state.streamingMessage.links,
             };
             console.log("[DEBUG] Completed - adding message, new total:", state.messages.length + 1);
             return {
@@ -12,6 +12,24 @@ export interface SessionSummary {
   created_at: string;
   message_count: number;
   file_changes: FileChangeStats;
+  is_processing: boolean;
+  clients: number;
+}
+
+// Recent projects, with the settings new sessions there start with
+export interface ProjectOverrides {
+  model?: string;
+  mode?: AgentMode;
+  permission_preset?: PermissionPreset;
+}
+
+export type PermissionPreset = "safe" | "dev" | "full" | "yolo";
+
+export interface Project {
+  path: string;
+  last_opened: string;
+  pinned: boolean;
+  overrides: ProjectOverrides;
 }
 
 export interface FileChangeStats {
@@ -29,6 +47,15 @@ export interface Message {
   tool_calls?: ToolCall[];
   // For displaying tool executions in chat history
   toolExecutions?: ToolExecution[];
+  // Project files referenced in the message
+  links?: FileLink[];
+}
+
+export interface FileLink {
+  path: string;
+  line?: number;
+  // file:// deep link, with #L<line> when there is a line
+  url: string;
 }
 
 export interface ToolCall {
@@ -41,7 +68,7 @@ export interface ToolCall {
 // File change types
 export interface FileChange {
   path: string;
-  change_type: "created" | "modified" | "deleted";
+  change_type: "created" | "modified" | "deleted" | "renamed";
   additions: number;
   deletions: number;
   timestamp: string;
@@ -60,6 +87,7 @@ export type ServerEvent =
   | { type: "FileDiff"; path: string; additions: number; deletions: number; diff: string }
   | { type: "DiagnosticUpdate"; errors: number; warnings: number }
   | { type: "TextChunk"; text: string }
+  | { type: "FileLinks"; links: FileLink[] }
   | { type: "SubagentStarted"; id: string; kind: string; task: string }
   | { type: "SubagentProgress"; id: string; message: string }
   | { type: "SubagentCompleted"; id: string; success: boolean; summary: string }
@@ -71,7 +99,9 @@ export type ServerEvent =
   | { type: "PlanRejected"; plan_id: string }
   | { type: "TokenUsage"; input_tokens: number; output_tokens: number; cache_read_tokens?: number; cache_creation_tokens?: number }
   | { type: "ContextCompressed"; tokens_compressed: number }
-  | { type: "DoomLoopPrompt"; prompt_id: string; message: string }
+  | { type: "RateLimited"; provider: string; retry_in_secs: number; attempt: number; max_retries: number }
+  | { type: "DoomLoopPrompt"; prompt_id: string; message: string; timeout_secs?: number }
+  | { type: "ToolApprovalPrompt"; prompt_id: string; tool: string; description: string; high_risk: boolean }
   | { type: "Error"; message: string }
   | { type: "Completed" }
   | { type: "TodoList"; todos: TodoItem[] };
@@ -127,9 +157,27 @@ export interface StreamingMessage {
   pendingReasoning?: string;
   // Ordered list of items as they stream in (for interleaved display)
   streamItems: StreamItem[];
+  links?: FileLink[];
 }
 
 export interface DoomLoopPrompt {
   id: string;
   message: string;
 }
+
+export interface ToolApprovalPrompt {
+  id: string;
+  tool: string;
+  description: string;
+  highRisk: boolean;
+}
+
+// A tool approval as listed by /api/approvals
+export interface PendingApproval {
+  prompt_id: string;
+  session_id: string;
+  tool: string;
+  description: string;
+  high_risk: boolean;
+  requested_at: string;
+}
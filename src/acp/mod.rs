@@ -0,0 +1,286 @@
+//! Agent Client Protocol server
+//!
+//! `safe-coder acp` speaks ACP (JSON-RPC 2.0, one message per line) on
+//! stdin/stdout so editors such as Zed can run safe-coder as their agent
+//! without the HTTP server. Each ACP session is a regular `Session` in the
+//! editor's working directory. Replies, reasoning, tool calls with their
+//! output and diffs, and the todo list stream back as `session/update`
+//! notifications; tool calls that need approval become
+//! `session/request_permission` requests.
+
+pub mod types;
+
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{mpsc, oneshot, Mutex};
+
+use crate::approval::UserMode;
+use crate::config::Config;
+use crate::session::{Session, SessionEvent};
+use types::{Incoming, UpdateMapper};
+
+/// Sends messages to the client and matches its answers to our requests
+#[derive(Clone)]
+struct Client {
+    out: mpsc::UnboundedSender<Value>,
+    next_id: Arc<AtomicU64>,
+    pending: Arc<std::sync::Mutex<HashMap<u64, oneshot::Sender<Option<Value>>>>>,
+}
+
+impl Client {
+    fn send(&self, message: Value) {
+        let _ = self.out.send(message);
+    }
+
+    fn notify(&self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Call a method on the client and wait for its result
+    async fn request(&self, method: &str, params: Value) -> Option<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(id, tx);
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        rx.await.ok().flatten()
+    }
+
+    fn resolve(&self, id: u64, result: Option<Value>) {
+        if let Some(tx) = self.pending.lock().unwrap().remove(&id) {
+            let _ = tx.send(result);
+        }
+    }
+}
+
+struct AcpSession {
+    session: Arc<Mutex<Session>>,
+    /// Stops the prompt that is running, if any
+    cancel: std::sync::Mutex<Option<oneshot::Sender<()>>>,
+}
+
+#[derive(Clone)]
+struct Server {
+    client: Client,
+    sessions: Arc<std::sync::Mutex<HashMap<String, Arc<AcpSession>>>>,
+}
+
+/// Serve ACP on stdin/stdout until the client closes stdin
+pub async fn run() -> Result<()> {
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<Value>();
+    let writer = tokio::spawn(async move {
+        let mut stdout = tokio::io::stdout();
+        while let Some(message) = out_rx.recv().await {
+            let mut line = message.to_string();
+            line.push('\n');
+            if stdout.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+            let _ = stdout.flush().await;
+        }
+    });
+
+    let server = Server {
+        client: Client {
+            out: out_tx,
+            next_id: Arc::new(AtomicU64::new(0)),
+            pending: Arc::new(std::sync::Mutex::new(HashMap::new())),
+        },
+        sessions: Arc::new(std::sync::Mutex::new(HashMap::new())),
+    };
+
+    let mut lines = BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        match Incoming::parse(&line) {
+            Some(Incoming::Response { id, result }) => server.client.resolve(id, result),
+            // Requests run on their own so a long prompt doesn't hold up
+            // cancellations and permission answers
+            Some(Incoming::Request { id, method, params }) => {
+                let server = server.clone();
+                tokio::spawn(async move {
+                    let result = server.handle(&method, params).await;
+                    let Some(id) = id else {
+                        return;
+                    };
+                    server.client.send(match result {
+                        Ok(result) => types::response(id, result),
+                        Err((code, message)) => types::error_response(id, code, &message),
+                    });
+                });
+            }
+            None => tracing::warn!("Ignoring malformed ACP message: {}", line),
+        }
+    }
+
+    // The client is gone; prompts still running have nobody to answer to
+    writer.abort();
+    Ok(())
+}
+
+type RpcResult = std::result::Result<Value, (i64, String)>;
+
+fn internal(e: anyhow::Error) -> (i64, String) {
+    (types::INTERNAL_ERROR, format!("{:#}", e))
+}
+
+impl Server {
+    async fn handle(&self, method: &str, params: Value) -> RpcResult {
+        match method {
+            "initialize" => Ok(json!({
+                "protocolVersion": types::PROTOCOL_VERSION,
+                "agentCapabilities": {
+                    "loadSession": false,
+                    "promptCapabilities": { "image": false, "audio": false, "embeddedContext": true },
+                },
+                "authMethods": [],
+            })),
+            // Providers are set up with `safe-coder login`; there is nothing to do here
+            "authenticate" => Ok(json!({})),
+            "session/new" => self.new_session(&params).await.map_err(internal),
+            "session/set_mode" => {
+                let session = self.session(&params)?;
+                let mode = params["modeId"].as_str().unwrap_or_default();
+                let mode =
+                    UserMode::from_str(mode).map_err(|e| (types::INVALID_PARAMS, e.to_string()))?;
+                session.session.lock().await.set_user_mode(mode);
+                Ok(json!({}))
+            }
+            "session/prompt" => {
+                let session = self.session(&params)?;
+                let session_id = params["sessionId"].as_str().unwrap_or_default().to_string();
+                self.prompt(&session_id, &session, types::prompt_text(&params["prompt"]))
+                    .await
+                    .map_err(internal)
+            }
+            "session/cancel" => {
+                if let Ok(session) = self.session(&params) {
+                    if let Some(cancel) = session.cancel.lock().unwrap().take() {
+                        let _ = cancel.send(());
+                    }
+                }
+                Ok(Value::Null)
+            }
+            _ => Err((
+                types::METHOD_NOT_FOUND,
+                format!("Unknown method {}", method),
+            )),
+        }
+    }
+
+    fn session(&self, params: &Value) -> std::result::Result<Arc<AcpSession>, (i64, String)> {
+        let id = params["sessionId"].as_str().unwrap_or_default();
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(id)
+            .cloned()
+            .ok_or_else(|| (types::INVALID_PARAMS, format!("Unknown session {}", id)))
+    }
+
+    async fn new_session(&self, params: &Value) -> Result<Value> {
+        let cwd = PathBuf::from(params["cwd"].as_str().context("session/new needs a cwd")?);
+        let cwd = cwd
+            .canonicalize()
+            .with_context(|| format!("Invalid cwd {}", cwd.display()))?;
+        let config = Config::load_for_project(&cwd)?;
+        let mut session = Session::new(config, cwd).await?;
+        session.start().await?;
+
+        let id = uuid::Uuid::new_v4().to_string();
+        self.sessions.lock().unwrap().insert(
+            id.clone(),
+            Arc::new(AcpSession {
+                session: Arc::new(Mutex::new(session)),
+                cancel: std::sync::Mutex::new(None),
+            }),
+        );
+        Ok(json!({
+            "sessionId": id,
+            "modes": {
+                "currentModeId": UserMode::Build.as_str(),
+                "availableModes": [
+                    { "id": UserMode::Build.as_str(), "name": "Build", "description": "Plan lightly and run the changes" },
+                    { "id": UserMode::Plan.as_str(), "name": "Plan", "description": "Plan in depth before making changes" },
+                ],
+            },
+        }))
+    }
+
+    /// Run one prompt, streaming its progress, until it ends or is cancelled
+    async fn prompt(&self, session_id: &str, acp: &AcpSession, text: String) -> Result<Value> {
+        let (cancel_tx, mut cancel_rx) = oneshot::channel();
+        *acp.cancel.lock().unwrap() = Some(cancel_tx);
+
+        let mut session = acp.session.lock().await;
+        let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+        let send = session.send_message_with_progress(text, event_tx);
+        tokio::pin!(send);
+
+        let mut mapper = UpdateMapper::default();
+        let result = loop {
+            tokio::select! {
+                result = &mut send => break Some(result),
+                _ = &mut cancel_rx => break None,
+                Some(event) = event_rx.recv() => self.forward(session_id, &mut mapper, event),
+            }
+        };
+        while let Ok(event) = event_rx.try_recv() {
+            self.forward(session_id, &mut mapper, event);
+        }
+        acp.cancel.lock().unwrap().take();
+
+        let stop_reason = match result {
+            Some(result) => {
+                result?;
+                "end_turn"
+            }
+            None => "cancelled",
+        };
+        Ok(json!({ "stopReason": stop_reason }))
+    }
+
+    fn forward(&self, session_id: &str, mapper: &mut UpdateMapper, event: SessionEvent) {
+        match event {
+            SessionEvent::ToolApprovalPrompt {
+                tool,
+                description,
+                response_tx,
+                ..
+            } => {
+                let params = json!({
+                    "sessionId": session_id,
+                    "toolCall": mapper.permission_call(&tool, &description),
+                    "options": [
+                        { "optionId": "allow", "name": "Allow", "kind": "allow_once" },
+                        { "optionId": "reject", "name": "Reject", "kind": "reject_once" },
+                    ],
+                });
+                let client = self.client.clone();
+                tokio::spawn(async move {
+                    let result = client.request("session/request_permission", params).await;
+                    let approved = result
+                        .as_ref()
+                        .and_then(|r| r["outcome"]["optionId"].as_str())
+                        == Some("allow");
+                    let _ = response_tx.send(approved);
+                });
+            }
+            // The editor can cancel the prompt instead; don't keep looping
+            SessionEvent::DoomLoopPrompt { response_tx, .. } => {
+                let _ = response_tx.send(false);
+            }
+            event => {
+                if let Some(update) = mapper.update(event) {
+                    self.client.notify(
+                        "session/update",
+                        json!({ "sessionId": session_id, "update": update }),
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,318 @@
+//! JSON-RPC messages and session updates of the Agent Client Protocol
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use crate::session::SessionEvent;
+use crate::tools::todo::TodoItem;
+
+/// Protocol version this agent speaks
+pub const PROTOCOL_VERSION: u64 = 1;
+
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+
+/// An incoming JSON-RPC message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Incoming {
+    /// A call from the client; notifications have no id
+    Request {
+        id: Option<Value>,
+        method: String,
+        params: Value,
+    },
+    /// The client's answer to one of our requests
+    Response { id: u64, result: Option<Value> },
+}
+
+impl Incoming {
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut message: Value = serde_json::from_str(line).ok()?;
+        if let Some(method) = message.get("method").and_then(Value::as_str) {
+            return Some(Self::Request {
+                method: method.to_string(),
+                id: message.get("id").cloned(),
+                params: message
+                    .get_mut("params")
+                    .map(Value::take)
+                    .unwrap_or(Value::Null),
+            });
+        }
+        Some(Self::Response {
+            id: message.get("id")?.as_u64()?,
+            result: message.get_mut("result").map(Value::take),
+        })
+    }
+}
+
+pub fn response(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "result": result })
+}
+
+pub fn error_response(id: Value, code: i64, message: &str) -> Value {
+    json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })
+}
+
+/// ACP tool kind for one of our tools, used by editors to pick an icon
+pub fn tool_kind(name: &str) -> &'static str {
+    match name {
+        "read_file" | "list" => "read",
+        "write_file" | "edit_file" | "add_dependency" | "remove_dependency" => "edit",
+        "bash" | "run_tests" => "execute",
+        "glob" | "grep" | "code_search" | "ast_grep" => "search",
+        "webfetch" | "http_request" | "browser" | "ci_status" => "fetch",
+        _ => "other",
+    }
+}
+
+/// Text of a `session/prompt` request. Embedded resources are inlined and
+/// linked files are mentioned with `@path` like in the TUI.
+pub fn prompt_text(prompt: &Value) -> String {
+    let mut parts = Vec::new();
+    for block in prompt.as_array().into_iter().flatten() {
+        match block.get("type").and_then(Value::as_str) {
+            Some("text") => {
+                if let Some(text) = block.get("text").and_then(Value::as_str) {
+                    parts.push(text.to_string());
+                }
+            }
+            Some("resource_link") => {
+                if let Some(uri) = block.get("uri").and_then(Value::as_str) {
+                    parts.push(format!("@{}", uri.trim_start_matches("file://")));
+                }
+            }
+            Some("resource") => {
+                let resource = &block["resource"];
+                if let Some(text) = resource.get("text").and_then(Value::as_str) {
+                    let uri = resource.get("uri").and_then(Value::as_str).unwrap_or("");
+                    parts.push(format!("{}:\n```\n{}\n```", uri, text));
+                }
+            }
+            _ => {}
+        }
+    }
+    parts.join("\n\n")
+}
+
+/// Turns session events into `session/update` payloads, giving every tool
+/// call an id the editor can follow
+#[derive(Debug, Default)]
+pub struct UpdateMapper {
+    next_id: u64,
+    /// Id of the running call of each tool
+    running: HashMap<String, String>,
+    /// Calls that were shown in a permission request and haven't started yet
+    requested: HashMap<String, String>,
+    output: HashMap<String, String>,
+}
+
+impl UpdateMapper {
+    /// Tool call for a permission request; the call keeps its id once it runs
+    pub fn permission_call(&mut self, tool: &str, description: &str) -> Value {
+        let id = self.new_id();
+        self.requested.insert(tool.to_string(), id.clone());
+        json!({
+            "toolCallId": id,
+            "title": description,
+            "kind": tool_kind(tool),
+            "status": "pending",
+        })
+    }
+
+    pub fn update(&mut self, event: SessionEvent) -> Option<Value> {
+        match event {
+            SessionEvent::TextChunk(text) => Some(json!({
+                "sessionUpdate": "agent_message_chunk",
+                "content": { "type": "text", "text": text },
+            })),
+            SessionEvent::Reasoning(text) | SessionEvent::ModelReasoning(text) => Some(json!({
+                "sessionUpdate": "agent_thought_chunk",
+                "content": { "type": "text", "text": text },
+            })),
+            SessionEvent::ToolStart { name, description } => {
+                let id = self
+                    .requested
+                    .remove(&name)
+                    .unwrap_or_else(|| self.new_id());
+                self.running.insert(name.clone(), id.clone());
+                Some(json!({
+                    "sessionUpdate": "tool_call",
+                    "toolCallId": id,
+                    "title": description,
+                    "kind": tool_kind(&name),
+                    "status": "in_progress",
+                }))
+            }
+            SessionEvent::ToolOutput { name, output } => {
+                self.output.insert(name, output);
+                None
+            }
+            SessionEvent::ToolComplete { name, success } => {
+                let id = self.running.remove(&name)?;
+                let content: Vec<Value> = self
+                    .output
+                    .remove(&name)
+                    .map(|text| json!({ "type": "content", "content": { "type": "text", "text": text } }))
+                    .into_iter()
+                    .collect();
+                Some(json!({
+                    "sessionUpdate": "tool_call_update",
+                    "toolCallId": id,
+                    "status": if success { "completed" } else { "failed" },
+                    "content": content,
+                }))
+            }
+            SessionEvent::FileDiff {
+                path,
+                old_content,
+                new_content,
+            } => {
+                // Diffs come from the edit that is running, if we know it
+                let id = self
+                    .running
+                    .iter()
+                    .find(|(name, _)| tool_kind(name) == "edit")
+                    .map(|(_, id)| id.clone())?;
+                Some(json!({
+                    "sessionUpdate": "tool_call_update",
+                    "toolCallId": id,
+                    "content": [{
+                        "type": "diff",
+                        "path": path,
+                        "oldText": old_content,
+                        "newText": new_content,
+                    }],
+                }))
+            }
+            SessionEvent::TodoList { todos } => Some(plan_update(&todos)),
+            _ => None,
+        }
+    }
+
+    fn new_id(&mut self) -> String {
+        self.next_id += 1;
+        format!("call_{}", self.next_id)
+    }
+}
+
+/// The todo list as an ACP plan
+fn plan_update(todos: &[TodoItem]) -> Value {
+    let entries: Vec<Value> = todos
+        .iter()
+        .map(|todo| {
+            json!({
+                "content": todo.content,
+                "priority": match todo.priority {
+                    1 | 2 => "high",
+                    3 => "medium",
+                    _ => "low",
+                },
+                "status": match todo.status.as_str() {
+                    "in_progress" | "completed" => todo.status.as_str(),
+                    _ => "pending",
+                },
+            })
+        })
+        .collect();
+    json!({ "sessionUpdate": "plan", "entries": entries })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_incoming() {
+        assert_eq!(
+            Incoming::parse(
+                r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":1}}"#
+            ),
+            Some(Incoming::Request {
+                id: Some(json!(1)),
+                method: "initialize".into(),
+                params: json!({ "protocolVersion": 1 }),
+            })
+        );
+        assert_eq!(
+            Incoming::parse(
+                r#"{"jsonrpc":"2.0","method":"session/cancel","params":{"sessionId":"s"}}"#
+            ),
+            Some(Incoming::Request {
+                id: None,
+                method: "session/cancel".into(),
+                params: json!({ "sessionId": "s" }),
+            })
+        );
+        assert_eq!(
+            Incoming::parse(
+                r#"{"jsonrpc":"2.0","id":7,"result":{"outcome":{"outcome":"cancelled"}}}"#
+            ),
+            Some(Incoming::Response {
+                id: 7,
+                result: Some(json!({ "outcome": { "outcome": "cancelled" } })),
+            })
+        );
+        assert_eq!(Incoming::parse("not json"), None);
+    }
+
+    #[test]
+    fn test_prompt_text() {
+        let prompt = json!([
+            { "type": "text", "text": "Explain" },
+            { "type": "resource_link", "uri": "file:///src/main.rs", "name": "main.rs" },
+            { "type": "resource", "resource": { "uri": "file:///a.txt", "text": "hello" } },
+            { "type": "image", "data": "..." },
+        ]);
+        assert_eq!(
+            prompt_text(&prompt),
+            "Explain\n\n@/src/main.rs\n\nfile:///a.txt:\n```\nhello\n```"
+        );
+    }
+
+    #[test]
+    fn test_tool_call_updates() {
+        let mut mapper = UpdateMapper::default();
+        let request = mapper.permission_call("edit_file", "Edit src/lib.rs");
+        let start = mapper
+            .update(SessionEvent::ToolStart {
+                name: "edit_file".into(),
+                description: "Edit src/lib.rs".into(),
+            })
+            .unwrap();
+        // The call keeps the id it was shown with in the permission request
+        assert_eq!(start["toolCallId"], request["toolCallId"]);
+        assert_eq!(start["kind"], "edit");
+
+        let diff = mapper
+            .update(SessionEvent::FileDiff {
+                path: "src/lib.rs".into(),
+                old_content: "a\n".into(),
+                new_content: "b\n".into(),
+            })
+            .unwrap();
+        assert_eq!(diff["content"][0]["type"], "diff");
+        assert_eq!(diff["toolCallId"], request["toolCallId"]);
+
+        mapper.update(SessionEvent::ToolOutput {
+            name: "edit_file".into(),
+            output: "done".into(),
+        });
+        let done = mapper
+            .update(SessionEvent::ToolComplete {
+                name: "edit_file".into(),
+                success: true,
+            })
+            .unwrap();
+        assert_eq!(done["status"], "completed");
+        assert_eq!(done["content"][0]["content"]["text"], "done");
+
+        // Completion of a call we never saw start is dropped
+        assert!(mapper
+            .update(SessionEvent::ToolComplete {
+                name: "bash".into(),
+                success: true,
+            })
+            .is_none());
+    }
+}
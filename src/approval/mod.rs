@@ -1,6 +1,9 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
+use crate::permissions::CommandAnalysis;
+use crate::tools::AgentMode;
+
 /// User-facing execution mode for the agent
 /// Controls whether to show plan and require approval before execution
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -61,7 +64,7 @@ impl std::fmt::Display for UserMode {
 pub enum ApprovalMode {
     /// Show execution plan before running
     Plan,
-    /// Ask before each tool use (default)
+    /// Ask before each tool that modifies files or runs commands (default)
     Default,
     /// Auto-approve file edits, ask for bash and other tools
     AutoEdit,
@@ -95,11 +98,16 @@ impl ApprovalMode {
 
     /// Check if tool execution needs approval
     pub fn needs_approval(&self, tool_name: &str) -> bool {
+        // Read-only tools never need approval
+        if AgentMode::Plan.is_tool_enabled(tool_name) {
+            return false;
+        }
+
         match self {
             ApprovalMode::Yolo => false,
             ApprovalMode::AutoEdit => {
-                // Auto-approve read, write, edit; ask for bash and others
-                !matches!(tool_name, "read_file" | "write_file" | "edit_file")
+                // Auto-approve reads and file edits; ask for bash and others
+                !matches!(tool_name, "write_file" | "edit_file")
             }
             ApprovalMode::Default => true,
             ApprovalMode::Plan => true,
@@ -359,20 +367,11 @@ impl PlannedTool {
     /// Determine risk level based on tool name and parameters
     pub fn auto_risk(mut self) -> Self {
         self.risk_level = match self.name.as_str() {
-            "bash" => {
-                // Check for dangerous bash commands
-                if let Some(cmd) = self.parameters.get("command").and_then(|v| v.as_str()) {
-                    if cmd.contains("rm ") || cmd.contains("sudo") || cmd.contains("chmod") {
-                        RiskLevel::High
-                    } else if cmd.contains("mv ") || cmd.contains("cp ") {
-                        RiskLevel::Medium
-                    } else {
-                        RiskLevel::Low
-                    }
-                } else {
-                    RiskLevel::Medium
-                }
-            }
+            "bash" => match self.parameters.get("command").and_then(|v| v.as_str()) {
+                // Classify the parsed command (pipes, chains, subshells)
+                Some(cmd) => CommandAnalysis::analyze(cmd).risk_level(),
+                None => RiskLevel::Medium,
+            },
             "write_file" => RiskLevel::Medium,
             "edit_file" => RiskLevel::Medium,
             "read_file" => RiskLevel::Low,
@@ -380,6 +379,20 @@ impl PlannedTool {
         };
         self
     }
+
+    /// Describe the risky operations of a bash command, if any
+    pub fn risk_summary(&self) -> Option<String> {
+        if self.name != "bash" {
+            return None;
+        }
+        let cmd = self.parameters.get("command").and_then(|v| v.as_str())?;
+        let analysis = CommandAnalysis::analyze(cmd);
+        if analysis.categories.is_empty() {
+            None
+        } else {
+            Some(analysis.summary())
+        }
+    }
 }
 
 #[cfg(test)]
@@ -428,6 +441,12 @@ mod tests {
 
         let yolo = ApprovalMode::Yolo;
         assert!(!yolo.needs_approval("bash"));
+
+        let default = ApprovalMode::Default;
+        assert!(!default.needs_approval("read_file"));
+        assert!(!default.needs_approval("grep"));
+        assert!(default.needs_approval("write_file"));
+        assert!(default.needs_approval("bash"));
     }
 
     #[test]
@@ -464,5 +483,16 @@ mod tests {
             .with_params(serde_json::json!({"command": "rm -rf temp/"}))
             .auto_risk();
         assert_eq!(rm_tool.risk_level, RiskLevel::High);
+
+        let chained = PlannedTool::new("bash", "Build and push")
+            .with_params(serde_json::json!({"command": "cargo build && git push --force"}))
+            .auto_risk();
+        assert_eq!(chained.risk_level, RiskLevel::High);
+        assert!(chained.risk_summary().unwrap().contains("force-pushes"));
+
+        let install = PlannedTool::new("bash", "Install deps")
+            .with_params(serde_json::json!({"command": "npm install"}))
+            .auto_risk();
+        assert_eq!(install.risk_level, RiskLevel::Medium);
     }
 }
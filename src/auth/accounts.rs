@@ -0,0 +1,158 @@
+//! Named accounts per provider
+//!
+//! Secrets live in the credential store; this index records which accounts
+//! exist for each provider and which one is active, since keychains can't
+//! be enumerated portably. It is kept in `~/.config/safe-coder/accounts.json`.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::write_credentials_file;
+
+/// Name of the account created by a plain `safe-coder login <provider>`
+pub const DEFAULT_ACCOUNT: &str = "default";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderAccounts {
+    /// Account used when the project config doesn't pick one
+    #[serde(default)]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Accounts {
+    /// Keyed by provider, e.g. `anthropic` or `github_copilot`
+    #[serde(flatten)]
+    pub providers: BTreeMap<String, ProviderAccounts>,
+}
+
+impl Accounts {
+    pub fn path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+        Ok(config_dir.join("safe-coder").join("accounts.json"))
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path).context("Failed to read accounts file")?;
+        serde_json::from_str(&content).context("Failed to parse accounts file")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(&Self::path()?)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        write_credentials_file(path, &serde_json::to_string_pretty(self)?)
+    }
+
+    /// Record a new account. The first account for a provider becomes active.
+    pub fn add(&mut self, provider: &str, name: &str) {
+        let entry = self.providers.entry(provider.to_string()).or_default();
+        if !entry.names.iter().any(|n| n == name) {
+            entry.names.push(name.to_string());
+        }
+        if entry.active.is_none() {
+            entry.active = Some(name.to_string());
+        }
+    }
+
+    /// Forget an account. Returns whether it was known.
+    pub fn remove(&mut self, provider: &str, name: &str) -> bool {
+        let Some(entry) = self.providers.get_mut(provider) else {
+            return false;
+        };
+        let before = entry.names.len();
+        entry.names.retain(|n| n != name);
+        let removed = entry.names.len() < before;
+        if entry.active.as_deref() == Some(name) {
+            entry.active = entry.names.first().cloned();
+        }
+        if entry.names.is_empty() {
+            self.providers.remove(provider);
+        }
+        removed
+    }
+
+    /// Make `name` the active account for a provider
+    pub fn switch(&mut self, provider: &str, name: &str) -> Result<()> {
+        let entry = self
+            .providers
+            .get_mut(provider)
+            .filter(|e| e.names.iter().any(|n| n == name))
+            .with_context(|| {
+                format!(
+                    "No '{}' account named '{}'. Log in with `safe-coder login {} --name {}`",
+                    provider, name, provider, name
+                )
+            })?;
+        entry.active = Some(name.to_string());
+        Ok(())
+    }
+
+    pub fn active(&self, provider: &str) -> Option<&str> {
+        self.providers.get(provider)?.active.as_deref()
+    }
+
+    pub fn names(&self, provider: &str) -> Vec<String> {
+        self.providers
+            .get(provider)
+            .map(|e| e.names.clone())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first_account_becomes_active() {
+        let mut accounts = Accounts::default();
+        accounts.add("anthropic", "default");
+        accounts.add("anthropic", "work");
+        assert_eq!(accounts.active("anthropic"), Some("default"));
+        assert_eq!(accounts.names("anthropic"), vec!["default", "work"]);
+
+        accounts.switch("anthropic", "work").unwrap();
+        assert_eq!(accounts.active("anthropic"), Some("work"));
+        assert!(accounts.switch("anthropic", "personal").is_err());
+        assert!(accounts.switch("github_copilot", "work").is_err());
+    }
+
+    #[test]
+    fn test_remove_reassigns_active() {
+        let mut accounts = Accounts::default();
+        accounts.add("anthropic", "work");
+        accounts.add("anthropic", "personal");
+        assert!(accounts.remove("anthropic", "work"));
+        assert_eq!(accounts.active("anthropic"), Some("personal"));
+        assert!(!accounts.remove("anthropic", "work"));
+
+        assert!(accounts.remove("anthropic", "personal"));
+        assert_eq!(accounts.active("anthropic"), None);
+        assert!(accounts.providers.is_empty());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("accounts.json");
+        let mut accounts = Accounts::default();
+        accounts.add("github_copilot", "default");
+        accounts.save_to(&path).unwrap();
+
+        let loaded = Accounts::load_from(&path).unwrap();
+        assert_eq!(loaded.active("github_copilot"), Some("default"));
+    }
+}
@@ -0,0 +1,533 @@
+//! OAuth for remote MCP servers.
+//!
+//! Follows the MCP authorization spec: the server's 401 challenge points at
+//! its protected resource metadata (RFC 9728), which names the authorization
+//! server (RFC 8414). Clients are registered dynamically (RFC 7591) unless a
+//! client ID is configured, and the user authorizes through an authorization
+//! code + PKCE flow with a loopback redirect.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+use super::{pkce, store};
+
+/// How long to wait for the browser to come back to the loopback redirect
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Refresh tokens this long before they expire
+const REFRESH_BUFFER_SECS: u64 = 5 * 60;
+
+/// Stored OAuth credentials for one MCP server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpCredentials {
+    pub access_token: String,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    /// Unix time in seconds
+    #[serde(default)]
+    pub expires_at: Option<u64>,
+    pub client_id: String,
+    #[serde(default)]
+    pub client_secret: Option<String>,
+    pub token_endpoint: String,
+}
+
+impl McpCredentials {
+    /// Path of the credentials file for a server, beside the provider tokens
+    pub fn path(server: &str) -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+        let name: String = server
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Ok(config_dir
+            .join("safe-coder")
+            .join(format!("mcp_{}_token.json", name)))
+    }
+
+    /// Load the stored credentials for a server, if any
+    pub fn load(server: &str) -> Result<Option<Self>> {
+        let path = Self::path(server)?;
+        let store = store::default_store()?;
+        let key = store::key_for_path(&path);
+        if let Some(content) = store.get(&key)? {
+            let credentials =
+                serde_json::from_str(&content).context("Failed to parse stored MCP token")?;
+            return Ok(Some(credentials));
+        }
+
+        // Migrate plaintext token files written by older versions
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path).context("Failed to read MCP token file")?;
+        let credentials =
+            serde_json::from_str(&content).context("Failed to parse MCP token file")?;
+        store.set(&key, &content)?;
+        let _ = std::fs::remove_file(&path);
+        Ok(Some(credentials))
+    }
+
+    pub fn save(&self, server: &str) -> Result<()> {
+        let key = store::key_for_path(&Self::path(server)?);
+        store::default_store()?.set(&key, &serde_json::to_string(self)?)
+    }
+
+    /// Delete the stored credentials for a server. Returns whether any existed.
+    pub fn remove(server: &str) -> Result<bool> {
+        let path = Self::path(server)?;
+        let mut removed = store::default_store()?.delete(&store::key_for_path(&path))?;
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+            removed = true;
+        }
+        Ok(removed)
+    }
+
+    /// Check if the access token expires within the next few minutes
+    pub fn needs_refresh(&self) -> bool {
+        match self.expires_at {
+            Some(expires_at) => now_secs() + REFRESH_BUFFER_SECS >= expires_at,
+            None => false,
+        }
+    }
+}
+
+/// Authorization server endpoints
+#[derive(Debug, Clone, Deserialize)]
+pub struct AuthServerMetadata {
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    #[serde(default)]
+    pub registration_endpoint: Option<String>,
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProtectedResourceMetadata {
+    #[serde(default)]
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RegistrationRequest<'a> {
+    client_name: &'a str,
+    redirect_uris: Vec<String>,
+    grant_types: Vec<&'a str>,
+    response_types: Vec<&'a str>,
+    token_endpoint_auth_method: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct RegistrationResponse {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    expires_in: Option<u64>,
+}
+
+/// OAuth client for a single MCP server
+pub struct McpOAuth {
+    client: reqwest::Client,
+    server_url: url::Url,
+}
+
+impl McpOAuth {
+    pub fn new(server_url: &str) -> Result<Self> {
+        Ok(Self {
+            client: reqwest::Client::new(),
+            server_url: url::Url::parse(server_url)
+                .with_context(|| format!("Invalid MCP server URL '{}'", server_url))?,
+        })
+    }
+
+    /// Send an unauthenticated request to see whether the server wants OAuth.
+    ///
+    /// Returns the `WWW-Authenticate` challenge (possibly empty) on a 401.
+    pub async fn challenge(&self) -> Result<Option<String>> {
+        let response = self
+            .client
+            .post(self.server_url.clone())
+            .header(
+                reqwest::header::ACCEPT,
+                "application/json, text/event-stream",
+            )
+            .json(&serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": 0,
+                "method": "ping"
+            }))
+            .send()
+            .await
+            .context("Failed to reach MCP server")?;
+
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(None);
+        }
+        Ok(Some(
+            response
+                .headers()
+                .get(reqwest::header::WWW_AUTHENTICATE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string(),
+        ))
+    }
+
+    /// Find the authorization server for this MCP server
+    pub async fn discover(&self, challenge: Option<&str>) -> Result<AuthServerMetadata> {
+        let resource_metadata_url = challenge
+            .and_then(parse_resource_metadata)
+            .unwrap_or_else(|| self.well_known_url(&self.server_url, "oauth-protected-resource"));
+
+        let resource: Option<ProtectedResourceMetadata> =
+            self.get_json(&resource_metadata_url).await;
+        let issuer = resource
+            .as_ref()
+            .and_then(|r| r.authorization_servers.first())
+            .and_then(|s| url::Url::parse(s).ok())
+            .unwrap_or_else(|| self.server_url.clone());
+
+        for suffix in ["oauth-authorization-server", "openid-configuration"] {
+            let url = self.well_known_url(&issuer, suffix);
+            if let Some(mut metadata) = self.get_json::<AuthServerMetadata>(&url).await {
+                if metadata.scopes_supported.is_empty() {
+                    if let Some(resource) = resource {
+                        metadata.scopes_supported = resource.scopes_supported;
+                    }
+                }
+                return Ok(metadata);
+            }
+        }
+
+        // No metadata published - fall back to the spec's default endpoints
+        let origin = issuer.origin().ascii_serialization();
+        Ok(AuthServerMetadata {
+            authorization_endpoint: format!("{}/authorize", origin),
+            token_endpoint: format!("{}/token", origin),
+            registration_endpoint: Some(format!("{}/register", origin)),
+            scopes_supported: resource.map(|r| r.scopes_supported).unwrap_or_default(),
+        })
+    }
+
+    /// Run the interactive authorization code + PKCE flow
+    pub async fn authorize(&self, client_id: Option<String>) -> Result<McpCredentials> {
+        let challenge = self.challenge().await?;
+        let metadata = self.discover(challenge.as_deref()).await?;
+
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .await
+            .context("Failed to start local callback listener")?;
+        let redirect_uri = format!(
+            "http://127.0.0.1:{}/callback",
+            listener.local_addr()?.port()
+        );
+
+        let (client_id, client_secret) = match client_id {
+            Some(id) => (id, None),
+            None => self.register(&metadata, &redirect_uri).await?,
+        };
+
+        let pkce_challenge = pkce::generate();
+        let state = pkce::generate().verifier;
+        let mut auth_url = url::Url::parse(&metadata.authorization_endpoint)
+            .context("Invalid authorization endpoint")?;
+        auth_url
+            .query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &client_id)
+            .append_pair("redirect_uri", &redirect_uri)
+            .append_pair("code_challenge", &pkce_challenge.challenge)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", &state)
+            .append_pair("resource", self.server_url.as_str());
+        if !metadata.scopes_supported.is_empty() {
+            auth_url
+                .query_pairs_mut()
+                .append_pair("scope", &metadata.scopes_supported.join(" "));
+        }
+
+        println!("Please visit this URL to authorize:");
+        println!("{}\n", auth_url);
+        println!("Waiting for authorization...");
+
+        let code = tokio::time::timeout(CALLBACK_TIMEOUT, wait_for_callback(&listener, &state))
+            .await
+            .context("Timed out waiting for authorization")??;
+
+        let mut params = vec![
+            ("grant_type", "authorization_code".to_string()),
+            ("code", code),
+            ("redirect_uri", redirect_uri),
+            ("client_id", client_id.clone()),
+            ("code_verifier", pkce_challenge.verifier),
+            ("resource", self.server_url.to_string()),
+        ];
+        if let Some(ref secret) = client_secret {
+            params.push(("client_secret", secret.clone()));
+        }
+        let token = self
+            .request_token(&metadata.token_endpoint, &params)
+            .await?;
+
+        Ok(McpCredentials {
+            access_token: token.access_token,
+            refresh_token: token.refresh_token,
+            expires_at: token.expires_in.map(|secs| now_secs() + secs),
+            client_id,
+            client_secret,
+            token_endpoint: metadata.token_endpoint,
+        })
+    }
+
+    /// Exchange a refresh token for a new access token
+    pub async fn refresh(&self, credentials: &McpCredentials) -> Result<McpCredentials> {
+        let refresh_token = credentials
+            .refresh_token
+            .clone()
+            .context("Token does not support refresh")?;
+
+        let mut params = vec![
+            ("grant_type", "refresh_token".to_string()),
+            ("refresh_token", refresh_token.clone()),
+            ("client_id", credentials.client_id.clone()),
+            ("resource", self.server_url.to_string()),
+        ];
+        if let Some(ref secret) = credentials.client_secret {
+            params.push(("client_secret", secret.clone()));
+        }
+        let token = self
+            .request_token(&credentials.token_endpoint, &params)
+            .await?;
+
+        Ok(McpCredentials {
+            access_token: token.access_token,
+            // Servers may keep the old refresh token valid rather than rotating it
+            refresh_token: token.refresh_token.or(Some(refresh_token)),
+            expires_at: token.expires_in.map(|secs| now_secs() + secs),
+            ..credentials.clone()
+        })
+    }
+
+    /// Register a public client with the authorization server
+    async fn register(
+        &self,
+        metadata: &AuthServerMetadata,
+        redirect_uri: &str,
+    ) -> Result<(String, Option<String>)> {
+        let endpoint = metadata.registration_endpoint.as_deref().context(
+            "The authorization server does not support dynamic client registration; \
+             set oauth_client_id for this server",
+        )?;
+
+        let response = self
+            .client
+            .post(endpoint)
+            .json(&RegistrationRequest {
+                client_name: "Safe Coder",
+                redirect_uris: vec![redirect_uri.to_string()],
+                grant_types: vec!["authorization_code", "refresh_token"],
+                response_types: vec!["code"],
+                token_endpoint_auth_method: "none",
+            })
+            .send()
+            .await
+            .context("Failed to register OAuth client")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Client registration failed ({}): {}", status, text);
+        }
+
+        let registration: RegistrationResponse = response
+            .json()
+            .await
+            .context("Failed to parse client registration response")?;
+        Ok((registration.client_id, registration.client_secret))
+    }
+
+    async fn request_token(
+        &self,
+        endpoint: &str,
+        params: &[(&str, String)],
+    ) -> Result<TokenResponse> {
+        let response = self
+            .client
+            .post(endpoint)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(params)
+            .send()
+            .await
+            .context("Failed to request token")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let text = response.text().await.unwrap_or_default();
+            anyhow::bail!("Token request failed ({}): {}", status, text);
+        }
+
+        response
+            .json()
+            .await
+            .context("Failed to parse token response")
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> Option<T> {
+        let response = self.client.get(url).send().await.ok()?;
+        if !response.status().is_success() {
+            return None;
+        }
+        response.json().await.ok()
+    }
+
+    /// `https://host/.well-known/<suffix><path>` per RFC 8414 / RFC 9728
+    fn well_known_url(&self, base: &url::Url, suffix: &str) -> String {
+        let path = base.path().trim_end_matches('/');
+        format!(
+            "{}/.well-known/{}{}",
+            base.origin().ascii_serialization(),
+            suffix,
+            path
+        )
+    }
+}
+
+/// Extract the `resource_metadata` URL from a `WWW-Authenticate` header
+fn parse_resource_metadata(challenge: &str) -> Option<String> {
+    let start = challenge.find("resource_metadata=")? + "resource_metadata=".len();
+    let value = &challenge[start..];
+    let value = match value.strip_prefix('"') {
+        Some(quoted) => &quoted[..quoted.find('"')?],
+        None => value.split([',', ' ']).next()?,
+    };
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Serve the loopback redirect until it delivers an authorization code
+async fn wait_for_callback(listener: &TcpListener, expected_state: &str) -> Result<String> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        let mut buf = vec![0u8; 8192];
+        let n = stream.read(&mut buf).await?;
+        let request = String::from_utf8_lossy(&buf[..n]);
+
+        // "GET /callback?code=...&state=... HTTP/1.1"
+        let target = request.split_whitespace().nth(1).unwrap_or_default();
+        let Ok(url) = url::Url::parse(&format!("http://127.0.0.1{}", target)) else {
+            continue;
+        };
+        if url.path() != "/callback" {
+            let _ = stream
+                .write_all(b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n")
+                .await;
+            continue;
+        }
+
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.into_owned())
+        };
+        let result = if let Some(error) = param("error") {
+            Err(anyhow::anyhow!(
+                "Authorization denied: {}",
+                param("error_description").unwrap_or(error)
+            ))
+        } else if param("state").as_deref() != Some(expected_state) {
+            Err(anyhow::anyhow!(
+                "Authorization response had an unexpected state"
+            ))
+        } else {
+            param("code").context("Authorization response had no code")
+        };
+
+        let body = match result {
+            Ok(_) => "Safe Coder is authorized. You can close this window.",
+            Err(_) => "Authorization failed. Check the terminal for details.",
+        };
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        return result;
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resource_metadata() {
+        assert_eq!(
+            parse_resource_metadata(
+                r#"Bearer error="invalid_token", resource_metadata="https://mcp.example.com/.well-known/oauth-protected-resource""#
+            ),
+            Some("https://mcp.example.com/.well-known/oauth-protected-resource".to_string())
+        );
+        assert_eq!(
+            parse_resource_metadata("Bearer resource_metadata=https://x.test/meta, scope=read"),
+            Some("https://x.test/meta".to_string())
+        );
+        assert_eq!(parse_resource_metadata("Bearer realm=\"mcp\""), None);
+    }
+
+    #[test]
+    fn test_well_known_url_keeps_path() {
+        let oauth = McpOAuth::new("https://mcp.example.com/v1/mcp").unwrap();
+        assert_eq!(
+            oauth.well_known_url(&oauth.server_url, "oauth-protected-resource"),
+            "https://mcp.example.com/.well-known/oauth-protected-resource/v1/mcp"
+        );
+    }
+
+    #[test]
+    fn test_credentials_refresh_window() {
+        let mut credentials = McpCredentials {
+            access_token: "a".to_string(),
+            refresh_token: Some("r".to_string()),
+            expires_at: Some(now_secs() + 60),
+            client_id: "c".to_string(),
+            client_secret: None,
+            token_endpoint: "https://auth.test/token".to_string(),
+        };
+        assert!(credentials.needs_refresh());
+
+        credentials.expires_at = Some(now_secs() + 3600);
+        assert!(!credentials.needs_refresh());
+
+        credentials.expires_at = None;
+        assert!(!credentials.needs_refresh());
+    }
+}
@@ -3,7 +3,10 @@ use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 
 pub mod github_copilot;
+pub mod accounts;
 pub mod anthropic;
+pub mod mcp;
+pub mod store;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -138,28 +141,74 @@ impl StoredToken {
         }
     }
 
+    /// Save the token to the credential store. `path` is the legacy token
+    /// file location and names the entry.
     pub fn save(&self, path: &std::path::Path) -> Result<()> {
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        let json = serde_json::to_string_pretty(self)?;
-        std::fs::write(path, json)?;
-        // Set restrictive permissions on the token file
-        #[cfg(unix)]
-        {
-            use std::os::unix::fs::PermissionsExt;
-            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
-        }
-        Ok(())
+        store::default_store()?.set(&store::key_for_path(path), &serde_json::to_string(self)?)
     }
 
+    /// Load the token from the credential store, migrating a plaintext
+    /// token file left by older versions if there is one.
     pub fn load(path: &std::path::Path) -> Result<Self> {
+        let store = store::default_store()?;
+        let key = store::key_for_path(path);
+        if let Some(content) = store.get(&key)? {
+            return serde_json::from_str(&content).context("Failed to parse stored token");
+        }
+
         let content = std::fs::read_to_string(path)
             .context("Failed to read token file")?;
         let token: StoredToken = serde_json::from_str(&content)
             .context("Failed to parse token file")?;
+        store.set(&key, &content)?;
+        if let Err(e) = std::fs::remove_file(path) {
+            tracing::warn!("Migrated token but could not remove {:?}: {}", path, e);
+        }
         Ok(token)
     }
+
+    /// Check whether a token has been stored for `path`
+    pub fn exists(path: &std::path::Path) -> bool {
+        path.exists()
+            || store::default_store()
+                .and_then(|store| store.get(&store::key_for_path(path)))
+                .is_ok_and(|token| token.is_some())
+    }
+
+    /// Delete a stored token, including any legacy token file. Returns
+    /// whether anything was removed.
+    pub fn remove(path: &std::path::Path) -> Result<bool> {
+        let mut removed = store::default_store()?.delete(&store::key_for_path(path))?;
+        if path.exists() {
+            std::fs::remove_file(path)?;
+            removed = true;
+        }
+        Ok(removed)
+    }
+}
+
+/// Write a credentials file readable only by the current user
+pub(crate) fn write_credentials_file(path: &std::path::Path, contents: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // Created private, so the secret is never readable by others
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    // The mode only applies to new files; tighten an existing one before writing
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::io::Write::write_all(&mut file, contents.as_bytes())?;
+    Ok(())
 }
 
 #[derive(Debug, Clone)]
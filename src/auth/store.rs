@@ -0,0 +1,364 @@
+//! Credential storage backends
+//!
+//! Secrets go into the OS keychain (macOS Keychain, Windows Credential
+//! Manager, libsecret) when one is available. Headless machines without a
+//! keyring fall back to AES-256-GCM encrypted files under
+//! `~/.config/safe-coder/credentials/`, keyed by a random per-user key.
+//!
+//! The encryption key never sits next to the files it protects: it is kept in
+//! the keychain when there is one, and otherwise in its own 0600 file under
+//! the local data directory (`~/.local/share/safe-coder/credential.key` on
+//! Linux). That file is the weak spot of a keychain-less machine; anyone who
+//! can read it and the credentials directory can decrypt the tokens.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, Context, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+use super::write_credentials_file;
+
+/// Keychain service name every entry is stored under
+const SERVICE: &str = "safe-coder";
+
+/// Set to `file` to skip the keychain and always use encrypted files
+const STORE_ENV: &str = "SAFE_CODER_CREDENTIAL_STORE";
+
+/// Keychain entry holding the encryption key of the file store
+const FILE_KEY_ENTRY: &str = "credential-encryption-key";
+
+const NONCE_LEN: usize = 12;
+
+/// A place to keep secrets, addressed by a short key such as `anthropic_token`
+pub trait CredentialStore: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<String>>;
+    fn set(&self, key: &str, secret: &str) -> Result<()>;
+    /// Delete a secret. Returns whether one existed.
+    fn delete(&self, key: &str) -> Result<bool>;
+    /// Human-readable backend name, shown after login
+    fn name(&self) -> String;
+}
+
+/// Store backed by the platform keychain
+pub struct KeyringStore;
+
+impl KeyringStore {
+    fn entry(key: &str) -> Result<keyring::Entry> {
+        keyring::Entry::new(SERVICE, key).context("Failed to open keychain entry")
+    }
+
+    /// Check that a keychain is reachable without touching any real entry
+    fn available() -> bool {
+        match Self::entry("__probe__").and_then(|e| e.get_password().map_err(Into::into)) {
+            Ok(_) => true,
+            Err(e) => matches!(
+                e.downcast_ref::<keyring::Error>(),
+                Some(keyring::Error::NoEntry)
+            ),
+        }
+    }
+}
+
+impl CredentialStore for KeyringStore {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        match Self::entry(key)?.get_password() {
+            Ok(secret) => Ok(Some(secret)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(anyhow!("Failed to read '{}' from keychain: {}", key, e)),
+        }
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<()> {
+        Self::entry(key)?
+            .set_password(secret)
+            .map_err(|e| anyhow!("Failed to write '{}' to keychain: {}", key, e))
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        match Self::entry(key)?.delete_credential() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(anyhow!("Failed to delete '{}' from keychain: {}", key, e)),
+        }
+    }
+
+    fn name(&self) -> String {
+        "OS keychain".to_string()
+    }
+}
+
+/// Where [`EncryptedFileStore`] keeps its encryption key
+#[derive(Debug, Clone)]
+pub enum KeyLocation {
+    /// An entry in the OS keychain
+    Keyring,
+    /// A file readable only by the current user, outside the credentials directory
+    File(PathBuf),
+}
+
+impl KeyLocation {
+    /// `credential.key` in the local data directory, e.g.
+    /// `~/.local/share/safe-coder/credential.key`
+    pub fn default_file() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+        Ok(data_dir.join("safe-coder").join("credential.key"))
+    }
+
+    fn load(&self) -> Result<Option<String>> {
+        match self {
+            Self::Keyring => KeyringStore.get(FILE_KEY_ENTRY),
+            Self::File(path) if path.exists() => std::fs::read_to_string(path)
+                .map(Some)
+                .context("Failed to read credential encryption key"),
+            Self::File(_) => Ok(None),
+        }
+    }
+
+    fn save(&self, encoded: &str) -> Result<()> {
+        match self {
+            Self::Keyring => KeyringStore.set(FILE_KEY_ENTRY, encoded),
+            Self::File(path) => {
+                tracing::warn!(
+                    "No OS keychain available; the credential encryption key is stored in {}. \
+                     Anyone who can read it can decrypt your saved credentials.",
+                    path.display()
+                );
+                write_credentials_file(path, encoded)
+            }
+        }
+    }
+}
+
+/// Store that keeps each secret in its own encrypted file
+pub struct EncryptedFileStore {
+    dir: PathBuf,
+    key: KeyLocation,
+}
+
+impl EncryptedFileStore {
+    pub fn new(dir: PathBuf, key: KeyLocation) -> Self {
+        Self { dir, key }
+    }
+
+    /// The default location, `~/.config/safe-coder/credentials/`
+    pub fn default_dir() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+        Ok(config_dir.join("safe-coder").join("credentials"))
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.enc", key))
+    }
+
+    /// Load the encryption key, generating it on first use
+    fn cipher(&self) -> Result<Aes256Gcm> {
+        let encoded = match self.key.load()? {
+            Some(encoded) => encoded,
+            None => {
+                let mut key = vec![0u8; 32];
+                rand::thread_rng().fill_bytes(&mut key);
+                let encoded = STANDARD.encode(&key);
+                self.key.save(&encoded)?;
+                encoded
+            }
+        };
+        let key = STANDARD
+            .decode(encoded.trim())
+            .context("Credential encryption key is corrupt")?;
+        if key.len() != 32 {
+            anyhow::bail!("Credential encryption key has the wrong length");
+        }
+        Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+    }
+}
+
+impl CredentialStore for EncryptedFileStore {
+    fn get(&self, key: &str) -> Result<Option<String>> {
+        let path = self.entry_path(key);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = STANDARD
+            .decode(std::fs::read_to_string(&path)?.trim())
+            .with_context(|| format!("Credential file {} is corrupt", path.display()))?;
+        if data.len() < NONCE_LEN {
+            anyhow::bail!("Credential file {} is corrupt", path.display());
+        }
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher()?
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| anyhow!("Failed to decrypt {}", path.display()))?;
+        Ok(Some(String::from_utf8(plaintext)?))
+    }
+
+    fn set(&self, key: &str, secret: &str) -> Result<()> {
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher()?
+            .encrypt(Nonce::from_slice(&nonce), secret.as_bytes())
+            .map_err(|_| anyhow!("Failed to encrypt '{}'", key))?;
+        let mut data = nonce.to_vec();
+        data.extend(ciphertext);
+        write_credentials_file(&self.entry_path(key), &STANDARD.encode(data))
+    }
+
+    fn delete(&self, key: &str) -> Result<bool> {
+        let path = self.entry_path(key);
+        if !path.exists() {
+            return Ok(false);
+        }
+        std::fs::remove_file(&path)?;
+        Ok(true)
+    }
+
+    fn name(&self) -> String {
+        let key = match &self.key {
+            KeyLocation::Keyring => "OS keychain".to_string(),
+            KeyLocation::File(path) => path.display().to_string(),
+        };
+        format!("encrypted file in {} (key in {})", self.dir.display(), key)
+    }
+}
+
+/// The store credentials should be saved to on this machine.
+///
+/// The keychain is probed once per process; if it is missing (no Secret
+/// Service on a headless Linux box, for example) encrypted files are used.
+pub fn default_store() -> Result<Arc<dyn CredentialStore>> {
+    static KEYRING_AVAILABLE: OnceLock<bool> = OnceLock::new();
+    let keyring = *KEYRING_AVAILABLE.get_or_init(|| {
+        let available = KeyringStore::available();
+        if !available {
+            tracing::debug!("No OS keychain available, using encrypted credential files");
+        }
+        available
+    });
+    let files_only = std::env::var(STORE_ENV).is_ok_and(|v| v.eq_ignore_ascii_case("file"));
+
+    if keyring && !files_only {
+        return Ok(Arc::new(KeyringStore));
+    }
+    let key = if keyring {
+        KeyLocation::Keyring
+    } else {
+        KeyLocation::File(KeyLocation::default_file()?)
+    };
+    Ok(Arc::new(EncryptedFileStore::new(
+        EncryptedFileStore::default_dir()?,
+        key,
+    )))
+}
+
+/// Derive a store key from a legacy token file path.
+///
+/// The file stem keeps entries readable (`anthropic_token-3f9a...`); the hash
+/// of the canonical path keeps files with the same name in different
+/// directories apart.
+pub fn key_for_path(path: &Path) -> String {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "token".to_string());
+    // The file itself may not exist yet, so canonicalize its directory
+    let canonical = match (path.parent(), path.file_name()) {
+        (Some(parent), Some(name)) => parent
+            .canonicalize()
+            .map(|dir| dir.join(name))
+            .unwrap_or_else(|_| path.to_path_buf()),
+        _ => path.to_path_buf(),
+    };
+    let digest = Sha256::digest(canonical.to_string_lossy().as_bytes());
+    let hash: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}", stem, hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file_store(dir: &Path, key_dir: &Path) -> EncryptedFileStore {
+        EncryptedFileStore::new(
+            dir.to_path_buf(),
+            KeyLocation::File(key_dir.join("credential.key")),
+        )
+    }
+
+    #[test]
+    fn test_encrypted_file_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_dir = tempfile::tempdir().unwrap();
+        let store = file_store(dir.path(), key_dir.path());
+
+        assert_eq!(store.get("anthropic_token").unwrap(), None);
+        store.set("anthropic_token", "sk-ant-secret").unwrap();
+        assert_eq!(
+            store.get("anthropic_token").unwrap().as_deref(),
+            Some("sk-ant-secret")
+        );
+
+        // The secret is never written in the clear, and the key lives elsewhere
+        let raw = std::fs::read_to_string(dir.path().join("anthropic_token.enc")).unwrap();
+        assert!(!raw.contains("sk-ant-secret"));
+        assert!(!dir.path().join("key").exists());
+        assert!(key_dir.path().join("credential.key").exists());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            for path in [
+                dir.path().join("anthropic_token.enc"),
+                key_dir.path().join("credential.key"),
+            ] {
+                let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+                assert_eq!(mode & 0o777, 0o600, "{}", path.display());
+            }
+        }
+
+        assert!(store.delete("anthropic_token").unwrap());
+        assert!(!store.delete("anthropic_token").unwrap());
+        assert_eq!(store.get("anthropic_token").unwrap(), None);
+    }
+
+    #[test]
+    fn test_encrypted_file_rejects_foreign_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_dir = tempfile::tempdir().unwrap();
+        let store = file_store(dir.path(), key_dir.path());
+        store.set("token", "secret").unwrap();
+
+        // Replacing the key makes existing entries unreadable rather than garbage
+        let other = tempfile::tempdir().unwrap();
+        file_store(other.path(), other.path())
+            .set("x", "y")
+            .unwrap();
+        std::fs::copy(
+            other.path().join("credential.key"),
+            key_dir.path().join("credential.key"),
+        )
+        .unwrap();
+        assert!(store.get("token").is_err());
+    }
+
+    #[test]
+    fn test_key_for_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let key = key_for_path(&dir.path().join("anthropic_token.json"));
+
+        assert!(key.starts_with("anthropic_token-"));
+        assert_eq!(
+            key,
+            key_for_path(&dir.path().join(".").join("anthropic_token.json"))
+        );
+        assert_ne!(
+            key,
+            key_for_path(&other.path().join("anthropic_token.json"))
+        );
+    }
+}
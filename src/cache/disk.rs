@@ -0,0 +1,292 @@
+//! Persistent, content-addressed response cache
+//!
+//! Each response is stored as `<dir>/<aa>/<digest>.json`, where the digest
+//! is the SHA-256 of the model, system prompt, messages and tools. Because
+//! the whole conversation is part of the key, re-running the same prompt
+//! against the same tree replays the recorded responses without calling
+//! the provider. Entries expire after a TTL and the oldest are evicted
+//! once the cache grows past its size limit.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::store::{CacheKey, CacheStats, CacheStore, CachedResponse};
+use crate::llm::LlmResponse;
+
+/// Hit/miss counters, kept across runs so `safe-coder cache stats` can report them
+const STATS_FILE: &str = "stats.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct DiskEntry {
+    model: String,
+    /// Unix time in seconds
+    created_at: u64,
+    response: LlmResponse,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct PersistedStats {
+    hits: usize,
+    misses: usize,
+    tokens_saved: usize,
+}
+
+/// Response cache stored on disk
+pub struct DiskCache {
+    dir: PathBuf,
+    ttl: Duration,
+    max_bytes: u64,
+}
+
+impl DiskCache {
+    pub fn new(dir: PathBuf, ttl: Duration, max_bytes: u64) -> Self {
+        Self {
+            dir,
+            ttl,
+            max_bytes,
+        }
+    }
+
+    /// The default location, `~/.cache/safe-coder/llm/`
+    pub fn default_dir() -> Result<PathBuf> {
+        let cache_dir = dirs::cache_dir().context("Could not determine cache directory")?;
+        Ok(cache_dir.join("safe-coder").join("llm"))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, digest: &str) -> PathBuf {
+        self.dir.join(&digest[..2]).join(format!("{}.json", digest))
+    }
+
+    /// All entry files with their size and modification time
+    fn entries(&self) -> Vec<(PathBuf, u64, SystemTime)> {
+        let mut entries = Vec::new();
+        let Ok(shards) = std::fs::read_dir(&self.dir) else {
+            return entries;
+        };
+        for shard in shards.flatten().filter(|e| e.path().is_dir()) {
+            let Ok(files) = std::fs::read_dir(shard.path()) else {
+                continue;
+            };
+            for file in files.flatten() {
+                if let Ok(meta) = file.metadata() {
+                    let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    entries.push((file.path(), meta.len(), modified));
+                }
+            }
+        }
+        entries
+    }
+
+    /// Drop the oldest entries until the cache fits in `max_bytes`
+    fn enforce_size_limit(&self) {
+        let mut entries = self.entries();
+        let mut total: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        if total <= self.max_bytes {
+            return;
+        }
+        entries.sort_by_key(|(_, _, modified)| *modified);
+        for (path, size, _) in entries {
+            if total <= self.max_bytes {
+                break;
+            }
+            if std::fs::remove_file(&path).is_ok() {
+                total -= size;
+            }
+        }
+    }
+
+    fn load_stats(&self) -> PersistedStats {
+        std::fs::read_to_string(self.dir.join(STATS_FILE))
+            .ok()
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default()
+    }
+
+    fn update_stats(&self, update: impl FnOnce(&mut PersistedStats)) {
+        let mut stats = self.load_stats();
+        update(&mut stats);
+        if std::fs::create_dir_all(&self.dir).is_ok() {
+            if let Ok(json) = serde_json::to_string(&stats) {
+                let _ = std::fs::write(self.dir.join(STATS_FILE), json);
+            }
+        }
+    }
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[async_trait]
+impl CacheStore for DiskCache {
+    async fn get(&self, key: &CacheKey) -> Option<CachedResponse> {
+        let path = self.entry_path(&key.digest);
+        let entry = tokio::fs::read_to_string(&path)
+            .await
+            .ok()
+            .and_then(|s| serde_json::from_str::<DiskEntry>(&s).ok());
+
+        match entry {
+            Some(entry)
+                if entry.model == key.model
+                    && now_secs().saturating_sub(entry.created_at) <= self.ttl.as_secs() =>
+            {
+                let tokens = entry
+                    .response
+                    .usage
+                    .as_ref()
+                    .map(|u| u.input_tokens)
+                    .unwrap_or(0);
+                self.update_stats(|s| {
+                    s.hits += 1;
+                    s.tokens_saved += tokens;
+                });
+                Some(CachedResponse::new(entry.response, self.ttl))
+            }
+            Some(_) => {
+                // Expired (or a digest collision from another model)
+                let _ = tokio::fs::remove_file(&path).await;
+                self.update_stats(|s| s.misses += 1);
+                None
+            }
+            None => {
+                self.update_stats(|s| s.misses += 1);
+                None
+            }
+        }
+    }
+
+    async fn set(&self, key: &CacheKey, response: CachedResponse) {
+        let path = self.entry_path(&key.digest);
+        let entry = DiskEntry {
+            model: key.model.clone(),
+            created_at: now_secs(),
+            response: response.response,
+        };
+        let Ok(json) = serde_json::to_string(&entry) else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            if let Err(e) = tokio::fs::create_dir_all(parent).await {
+                tracing::warn!("Failed to create cache directory {:?}: {}", parent, e);
+                return;
+            }
+        }
+        if let Err(e) = tokio::fs::write(&path, json).await {
+            tracing::warn!("Failed to write cache entry {:?}: {}", path, e);
+            return;
+        }
+        self.enforce_size_limit();
+    }
+
+    async fn invalidate(&self, key: &CacheKey) {
+        let _ = tokio::fs::remove_file(self.entry_path(&key.digest)).await;
+    }
+
+    async fn clear(&self) {
+        if self.dir.exists() {
+            if let Err(e) = tokio::fs::remove_dir_all(&self.dir).await {
+                tracing::warn!("Failed to clear cache {:?}: {}", self.dir, e);
+            }
+        }
+    }
+
+    async fn stats(&self) -> CacheStats {
+        let persisted = self.load_stats();
+        let entries = self.entries();
+        CacheStats {
+            hits: persisted.hits,
+            misses: persisted.misses,
+            tokens_saved: persisted.tokens_saved,
+            // Same estimate as CacheStats::record_hit: ~$3/1M input tokens
+            estimated_cost_saved: (persisted.tokens_saved as f64 / 1_000_000.0) * 3.0,
+            entries: entries.len(),
+            size_bytes: entries.iter().map(|(_, size, _)| *size as usize).sum(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::{ContentBlock, Message, Role, TokenUsage};
+
+    fn response(text: &str) -> CachedResponse {
+        CachedResponse::new(
+            LlmResponse {
+                message: Message {
+                    role: Role::Assistant,
+                    content: vec![ContentBlock::Text {
+                        text: text.to_string(),
+                    }],
+                },
+                usage: Some(TokenUsage::new(100, 50)),
+            },
+            Duration::from_secs(60),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_persists_across_instances() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = CacheKey::from_request(Some("sys"), &[Message::user("hi".into())], &[], "m");
+
+        let cache = DiskCache::new(dir.path().to_path_buf(), Duration::from_secs(60), u64::MAX);
+        assert!(cache.get(&key).await.is_none());
+        cache.set(&key, response("hello")).await;
+
+        // A fresh instance, as in a second run, sees the same entry
+        let cache = DiskCache::new(dir.path().to_path_buf(), Duration::from_secs(60), u64::MAX);
+        let cached = cache.get(&key).await.unwrap();
+        assert!(matches!(
+            &cached.response.message.content[0],
+            ContentBlock::Text { text } if text == "hello"
+        ));
+
+        let stats = cache.stats().await;
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.tokens_saved, 100);
+
+        cache.clear().await;
+        assert_eq!(cache.stats().await.entries, 0);
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_expires_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = CacheKey::from_request(None, &[], &[], "m");
+        let cache = DiskCache::new(dir.path().to_path_buf(), Duration::ZERO, u64::MAX);
+        cache.set(&key, response("old")).await;
+
+        // Backdate the entry past the TTL
+        let path = cache.entry_path(&key.digest);
+        let mut entry: DiskEntry =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        entry.created_at -= 10;
+        std::fs::write(&path, serde_json::to_string(&entry).unwrap()).unwrap();
+
+        assert!(cache.get(&key).await.is_none());
+        assert!(!path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_disk_cache_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = DiskCache::new(dir.path().to_path_buf(), Duration::from_secs(60), 1);
+        let key = CacheKey::from_request(Some("a"), &[], &[], "m");
+        cache.set(&key, response("too big")).await;
+        assert_eq!(cache.stats().await.entries, 0);
+    }
+}
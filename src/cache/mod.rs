@@ -1,9 +1,12 @@
 //! Token caching module for reducing LLM costs
 //!
 //! This module provides a provider-agnostic caching layer that works with
-//! any LLM client. It supports both in-memory caching and tracks cache
-//! statistics for cost analysis.
+//! any LLM client. It supports in-memory caching for a session, a
+//! persistent content-addressed cache for deterministic re-runs, and tracks
+//! cache statistics for cost analysis.
 
+mod disk;
 mod store;
 
+pub use disk::DiskCache;
 pub use store::{CacheKey, CacheStats, CacheStore, CachedResponse, MemoryCache};
@@ -21,6 +21,9 @@ pub struct CacheKey {
     pub tools_hash: u64,
     /// Model identifier
     pub model: String,
+    /// Stable content address: SHA-256 of the model, system prompt,
+    /// messages and tools. Used as the file name by the disk cache.
+    pub digest: String,
 }
 
 impl CacheKey {
@@ -54,6 +57,7 @@ impl CacheKey {
             messages_hash: messages_hasher.finish(),
             tools_hash: tools_hasher.finish(),
             model: model.to_string(),
+            digest: content_digest(system_prompt, messages, tools, model),
         }
     }
 
@@ -66,6 +70,34 @@ impl CacheKey {
     }
 }
 
+/// SHA-256 over the request, with each part length-prefixed so that
+/// moving text between parts changes the digest
+fn content_digest(
+    system_prompt: Option<&str>,
+    messages: &[Message],
+    tools: &[ToolDefinition],
+    model: &str,
+) -> String {
+    use sha2::{Digest, Sha256};
+
+    let parts = [
+        model.to_string(),
+        system_prompt.unwrap_or_default().to_string(),
+        serde_json::to_string(messages).unwrap_or_default(),
+        serde_json::to_string(tools).unwrap_or_default(),
+    ];
+    let mut hasher = Sha256::new();
+    for part in &parts {
+        hasher.update((part.len() as u64).to_le_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 impl Hash for CacheKey {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.system_prompt_hash.hash(state);
@@ -365,6 +397,9 @@ mod tests {
 
         // Different inputs should produce different key
         assert_ne!(key1, key3);
+        assert_eq!(key1.digest, key2.digest);
+        assert_ne!(key1.digest, key3.digest);
+        assert_eq!(key1.digest.len(), 64);
     }
 
     #[tokio::test]
@@ -3,6 +3,7 @@ use chrono::{DateTime, Utc};
 use glob::Pattern;
 use ignore::WalkBuilder;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::{Path, PathBuf};
 use tokio::process::Command;
 
@@ -110,6 +111,45 @@ pub struct Checkpoint {
     pub total_bytes: u64,
 }
 
+/// How a file differs from its checkpoint copy
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointFileChange {
+    pub path: String,
+    pub status: CheckpointFileStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckpointFileStatus {
+    /// Changed since the checkpoint
+    Modified,
+    /// In the checkpoint but no longer in the project
+    Deleted,
+    /// Added to the project after the checkpoint
+    Created,
+}
+
+/// A checkpoint's own manifest.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CheckpointManifest {
+    #[serde(flatten)]
+    checkpoint: Checkpoint,
+    /// Every file the project had when the checkpoint was taken, copied or
+    /// not. Without it, a file missing from the checkpoint may just have
+    /// been skipped.
+    #[serde(default)]
+    files: Option<Vec<String>>,
+}
+
+/// A file asked for in a restore that the checkpoint has no copy of, and
+/// that may have existed when it was taken
+#[derive(Debug, thiserror::Error)]
+#[error("'{path}' is not in checkpoint {checkpoint_id}")]
+pub struct NotInCheckpoint {
+    pub checkpoint_id: String,
+    pub path: String,
+}
+
 /// Metadata file containing all checkpoints
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct CheckpointMetadata {
@@ -241,7 +281,8 @@ impl DirectoryCheckpointManager {
             .context("Failed to create checkpoint directory")?;
 
         // Copy files
-        let (files_count, total_bytes) = self.copy_project_files(&files_path).await?;
+        let files = self.project_files();
+        let (files_count, total_bytes) = self.copy_project_files(&files, &files_path).await?;
 
         // Create checkpoint entry
         let checkpoint = Checkpoint {
@@ -253,8 +294,17 @@ impl DirectoryCheckpointManager {
         };
 
         // Save manifest for this checkpoint
+        let manifest = CheckpointManifest {
+            checkpoint: checkpoint.clone(),
+            files: Some(
+                files
+                    .iter()
+                    .map(|(_, relative)| relative.to_string_lossy().to_string())
+                    .collect(),
+            ),
+        };
         let manifest_path = checkpoint_path.join("manifest.json");
-        let manifest_content = serde_json::to_string_pretty(&checkpoint)?;
+        let manifest_content = serde_json::to_string_pretty(&manifest)?;
         tokio::fs::write(&manifest_path, manifest_content)
             .await
             .context("Failed to write checkpoint manifest")?;
@@ -277,10 +327,40 @@ impl DirectoryCheckpointManager {
     }
 
     /// Copy project files to checkpoint directory
-    async fn copy_project_files(&self, dest: &Path) -> Result<(usize, u64)> {
+    async fn copy_project_files(
+        &self,
+        files: &[(PathBuf, PathBuf)],
+        dest: &Path,
+    ) -> Result<(usize, u64)> {
         let mut files_count = 0usize;
         let mut total_bytes = 0u64;
 
+        for (path, relative) in files {
+            // Create destination path
+            let dest_path = dest.join(relative);
+
+            // Create parent directories
+            if let Some(parent) = dest_path.parent() {
+                tokio::fs::create_dir_all(parent).await.ok();
+            }
+
+            // Copy file
+            match tokio::fs::copy(path, &dest_path).await {
+                Ok(bytes) => {
+                    files_count += 1;
+                    total_bytes += bytes;
+                }
+                Err(e) => {
+                    tracing::debug!("Skipping file {}: {}", path.display(), e);
+                }
+            }
+        }
+
+        Ok((files_count, total_bytes))
+    }
+
+    /// Files a checkpoint would copy, as (absolute, relative) paths
+    fn project_files(&self) -> Vec<(PathBuf, PathBuf)> {
         // Use WalkBuilder which respects .gitignore automatically
         let walker = WalkBuilder::new(&self.project_path)
             .hidden(false) // Include hidden files
@@ -290,28 +370,17 @@ impl DirectoryCheckpointManager {
             .follow_links(false)
             .build();
 
-        for entry in walker {
-            let entry = match entry {
-                Ok(e) => e,
-                Err(_) => continue,
-            };
-
+        let mut files = Vec::new();
+        for entry in walker.flatten() {
             let path = entry.path();
 
             // Skip directories (we'll create them as needed)
-            if path.is_dir() {
+            if path.is_dir() || self.should_ignore(path) {
                 continue;
             }
 
-            // Check against our ignore patterns
-            if self.should_ignore(path) {
+            let Ok(relative) = path.strip_prefix(&self.project_path) else {
                 continue;
-            }
-
-            // Get relative path
-            let relative = match path.strip_prefix(&self.project_path) {
-                Ok(r) => r,
-                Err(_) => continue,
             };
 
             // Skip the checkpoint directory itself
@@ -319,27 +388,9 @@ impl DirectoryCheckpointManager {
                 continue;
             }
 
-            // Create destination path
-            let dest_path = dest.join(relative);
-
-            // Create parent directories
-            if let Some(parent) = dest_path.parent() {
-                tokio::fs::create_dir_all(parent).await.ok();
-            }
-
-            // Copy file
-            match tokio::fs::copy(path, &dest_path).await {
-                Ok(bytes) => {
-                    files_count += 1;
-                    total_bytes += bytes;
-                }
-                Err(e) => {
-                    tracing::debug!("Skipping file {}: {}", path.display(), e);
-                }
-            }
+            files.push((path.to_path_buf(), relative.to_path_buf()));
         }
-
-        Ok((files_count, total_bytes))
+        files
     }
 
     /// Check if a path should be ignored
@@ -410,15 +461,7 @@ impl DirectoryCheckpointManager {
 
     /// Restore to a specific checkpoint
     pub async fn restore_checkpoint(&self, checkpoint_id: &str) -> Result<()> {
-        let checkpoint_path = self
-            .checkpoint_dir
-            .join("checkpoints")
-            .join(checkpoint_id)
-            .join("files");
-
-        if !checkpoint_path.exists() {
-            anyhow::bail!("Checkpoint '{}' not found", checkpoint_id);
-        }
+        let checkpoint_path = self.checkpoint_files_dir(checkpoint_id)?;
 
         // Copy files back from checkpoint
         self.restore_files_from(&checkpoint_path).await?;
@@ -440,6 +483,139 @@ impl DirectoryCheckpointManager {
         self.restore_checkpoint(&latest.id).await
     }
 
+    /// Files that differ between a checkpoint and the project now
+    pub async fn changed_files(&self, checkpoint_id: &str) -> Result<Vec<CheckpointFileChange>> {
+        let source = self.checkpoint_files_dir(checkpoint_id)?;
+        let saved: std::collections::BTreeSet<PathBuf> = WalkBuilder::new(&source)
+            .hidden(false)
+            .git_ignore(false)
+            .follow_links(false)
+            .build()
+            .flatten()
+            .filter(|entry| !entry.path().is_dir())
+            .filter_map(|entry| entry.path().strip_prefix(&source).ok().map(Path::to_path_buf))
+            .collect();
+        let current: std::collections::BTreeSet<PathBuf> = self
+            .project_files()
+            .into_iter()
+            .map(|(_, relative)| relative)
+            .collect();
+
+        let mut changes = Vec::new();
+        for relative in saved.union(&current) {
+            let status = match (saved.contains(relative), current.contains(relative)) {
+                (true, true) => {
+                    let before = tokio::fs::read(source.join(relative)).await?;
+                    let now = tokio::fs::read(self.project_path.join(relative)).await?;
+                    if before == now {
+                        continue;
+                    }
+                    CheckpointFileStatus::Modified
+                }
+                (true, false) => CheckpointFileStatus::Deleted,
+                _ => CheckpointFileStatus::Created,
+            };
+            changes.push(CheckpointFileChange {
+                path: relative.to_string_lossy().to_string(),
+                status,
+            });
+        }
+        Ok(changes)
+    }
+
+    /// Restore single files to their state in a checkpoint. Files the
+    /// checkpoint's manifest shows were created since are removed; any other
+    /// file it has no copy of fails with [`NotInCheckpoint`], before anything
+    /// is written.
+    pub async fn restore_files(&self, checkpoint_id: &str, files: &[String]) -> Result<()> {
+        let source = self.checkpoint_files_dir(checkpoint_id)?;
+        let existed: Option<HashSet<PathBuf>> = self
+            .load_manifest(checkpoint_id)
+            .await?
+            .files
+            .map(|files| files.into_iter().map(PathBuf::from).collect());
+        // Ignored files aren't copied, so only these can be known to be new
+        let tracked: HashSet<PathBuf> = self
+            .project_files()
+            .into_iter()
+            .map(|(_, relative)| relative)
+            .collect();
+
+        let mut copies = Vec::new();
+        let mut removals = Vec::new();
+        for file in files {
+            let relative = Path::new(file);
+            if !relative
+                .components()
+                .all(|c| matches!(c, std::path::Component::Normal(_)))
+            {
+                anyhow::bail!("Invalid path: {}", file);
+            }
+            let saved = source.join(relative);
+            let dest_path = self.project_path.join(relative);
+            let created = existed
+                .as_ref()
+                .is_some_and(|existed| !existed.contains(relative));
+            if saved.is_file() {
+                copies.push((file, saved, dest_path));
+            } else if created && tracked.contains(relative) {
+                removals.push((file, dest_path));
+            } else if !created || dest_path.exists() {
+                return Err(NotInCheckpoint {
+                    checkpoint_id: checkpoint_id.to_string(),
+                    path: file.clone(),
+                }
+                .into());
+            }
+        }
+
+        for (file, saved, dest_path) in copies {
+            if let Some(parent) = dest_path.parent() {
+                tokio::fs::create_dir_all(parent).await.ok();
+            }
+            tokio::fs::copy(&saved, &dest_path)
+                .await
+                .context(format!("Failed to restore file: {}", file))?;
+        }
+        for (file, dest_path) in removals {
+            tokio::fs::remove_file(&dest_path)
+                .await
+                .context(format!("Failed to remove file: {}", file))?;
+        }
+
+        tracing::info!("Restored {} file(s) from checkpoint {}", files.len(), checkpoint_id);
+
+        Ok(())
+    }
+
+    async fn load_manifest(&self, checkpoint_id: &str) -> Result<CheckpointManifest> {
+        let manifest_path = self
+            .checkpoint_dir
+            .join("checkpoints")
+            .join(checkpoint_id)
+            .join("manifest.json");
+        let content = tokio::fs::read_to_string(&manifest_path)
+            .await
+            .context("Failed to read checkpoint manifest")?;
+        serde_json::from_str(&content).context("Failed to parse checkpoint manifest")
+    }
+
+    fn checkpoint_files_dir(&self, checkpoint_id: &str) -> Result<PathBuf> {
+        let path = self
+            .checkpoint_dir
+            .join("checkpoints")
+            .join(checkpoint_id)
+            .join("files");
+        // IDs come from clients too, so keep them to one path segment
+        if checkpoint_id.contains(['/', '\\'])
+            || checkpoint_id.starts_with('.')
+            || !path.exists()
+        {
+            anyhow::bail!("Checkpoint '{}' not found", checkpoint_id);
+        }
+        Ok(path)
+    }
+
     /// Restore files from a checkpoint directory
     async fn restore_files_from(&self, source: &Path) -> Result<()> {
         let walker = WalkBuilder::new(source)
@@ -800,4 +976,51 @@ mod tests {
         let restored = fs::read_to_string(&test_file).await.unwrap();
         assert_eq!(restored, "original content");
     }
+
+    #[tokio::test]
+    async fn test_restore_single_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let sandbox = temp_dir.path().to_path_buf();
+        fs::write(sandbox.join("a.txt"), "a").await.unwrap();
+        fs::write(sandbox.join("b.txt"), "b").await.unwrap();
+
+        let mut manager =
+            DirectoryCheckpointManager::new(sandbox.clone(), CheckpointConfig::default()).unwrap();
+        let id = manager.create_checkpoint("before").await.unwrap();
+
+        fs::write(sandbox.join("a.txt"), "changed").await.unwrap();
+        fs::remove_file(sandbox.join("b.txt")).await.unwrap();
+        fs::write(sandbox.join("c.txt"), "new").await.unwrap();
+
+        let status = |path: &str| CheckpointFileChange {
+            path: path.to_string(),
+            status: CheckpointFileStatus::Modified,
+        };
+        let changes = manager.changed_files(&id).await.unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                status("a.txt"),
+                CheckpointFileChange {
+                    status: CheckpointFileStatus::Deleted,
+                    ..status("b.txt")
+                },
+                CheckpointFileChange {
+                    status: CheckpointFileStatus::Created,
+                    ..status("c.txt")
+                },
+            ]
+        );
+
+        manager
+            .restore_files(&id, &["a.txt".to_string(), "c.txt".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(fs::read_to_string(sandbox.join("a.txt")).await.unwrap(), "a");
+        assert!(!sandbox.join("b.txt").exists());
+        assert!(!sandbox.join("c.txt").exists());
+
+        assert!(manager.restore_files(&id, &["../a.txt".to_string()]).await.is_err());
+        assert!(manager.restore_checkpoint("../..").await.is_err());
+    }
 }
@@ -11,8 +11,13 @@ use tokio::process::{Child, Command};
 use tokio::sync::mpsc;
 
 use crate::server::types::{
-    CreateSessionRequest, DoomLoopResponseRequest, SendMessageRequest, SessionResponse, ServerEvent,
+    CreateSessionRequest, DoomLoopResponseRequest, McpPromptDto, McpPromptRequest,
+    McpPromptResponse, McpResourceDto, McpServerStatusDto, SendMessageRequest, SessionListResponse,
+    SessionResponse, SessionSummary, ServerEvent, SetParamRequest, SetSkillRequest, SkillDto, ToolApprovalResponseRequest,
 };
+use crate::config::GenerationParams;
+use crate::llm::Capabilities;
+use crate::session::MessageOverrides;
 
 /// Default server port for TUI
 pub const DEFAULT_PORT: u16 = 9876;
@@ -23,19 +28,45 @@ pub struct SafeCoderClient {
     client: Client,
     sse_client: Client, // Separate client for SSE with no timeout
     session_id: Option<String>,
+    /// Whether this client created the session, rather than attaching to it
+    owns_session: bool,
+}
+
+/// Token for a server that requires one: `SAFE_CODER_TOKEN`, or the
+/// broadest token in the global `[[server.tokens]]` for the local server
+fn api_token() -> Option<String> {
+    if let Ok(token) = std::env::var("SAFE_CODER_TOKEN") {
+        return Some(token);
+    }
+    crate::config::Config::load_global_with_policy()
+        .ok()?
+        .server
+        .tokens
+        .into_iter()
+        .max_by_key(|t| t.scope)
+        .map(|t| t.token)
 }
 
 impl SafeCoderClient {
     /// Create a new client
     pub fn new(port: u16) -> Self {
+        let mut headers = reqwest::header::HeaderMap::new();
+        if let Some(token) = api_token() {
+            if let Ok(value) = format!("Bearer {}", token).parse() {
+                headers.insert(reqwest::header::AUTHORIZATION, value);
+            }
+        }
+
         // Regular client with timeout for API calls
         let client = Client::builder()
             .timeout(Duration::from_secs(30))
+            .default_headers(headers.clone())
             .build()
             .expect("Failed to create HTTP client");
 
         // SSE client without timeout for long-running streams
         let sse_client = Client::builder()
+            .default_headers(headers)
             .build()
             .expect("Failed to create SSE client");
 
@@ -44,6 +75,7 @@ impl SafeCoderClient {
             client,
             sse_client,
             session_id: None,
+            owns_session: false,
         }
     }
 
@@ -80,6 +112,8 @@ impl SafeCoderClient {
         let request = CreateSessionRequest {
             project_path: project_path.to_string(),
             mode: mode.map(|s| s.to_string()),
+            model: None,
+            permission_preset: None,
         };
 
         let resp = self
@@ -98,44 +132,72 @@ impl SafeCoderClient {
 
         let session: SessionResponse = resp.json().await.context("Failed to parse session response")?;
         self.session_id = Some(session.id.clone());
+        self.owns_session = true;
         Ok(session)
     }
 
-    /// Send a message to the current session
-    pub async fn send_message(&self, content: &str) -> Result<()> {
-        let session_id = self
-            .session_id
-            .as_ref()
-            .context("No active session")?;
-
-        let url = format!("{}/api/sessions/{}/messages", self.base_url, session_id);
-        let request = SendMessageRequest {
-            content: content.to_string(),
-            attachments: vec![],
-        };
-
+    /// List the sessions running on the server
+    pub async fn list_sessions(&self) -> Result<Vec<SessionSummary>> {
+        let url = format!("{}/api/sessions", self.base_url);
         let resp = self
             .client
-            .post(&url)
-            .json(&request)
+            .get(&url)
             .send()
             .await
-            .context("Failed to send message")?;
+            .context("Failed to list sessions")?;
 
         if !resp.status().is_success() {
             let status = resp.status();
             let text = resp.text().await.unwrap_or_default();
-            anyhow::bail!("Failed to send message: {} - {}", status, text);
+            anyhow::bail!("Failed to list sessions: {} - {}", status, text);
         }
 
-        Ok(())
+        let list: SessionListResponse = resp.json().await.context("Failed to parse session list")?;
+        Ok(list.sessions)
+    }
+
+    /// Attach to a session another client created, instead of creating one
+    ///
+    /// A unique prefix of the session ID is enough.
+    pub async fn attach_session(&mut self, id: &str) -> Result<SessionSummary> {
+        let mut matches: Vec<SessionSummary> = self
+            .list_sessions()
+            .await?
+            .into_iter()
+            .filter(|s| s.id.starts_with(id))
+            .collect();
+        let session = match matches.len() {
+            0 => anyhow::bail!("No running session matches '{}'", id),
+            1 => matches.remove(0),
+            n => anyhow::bail!("'{}' matches {} sessions; use more of the ID", id, n),
+        };
+        self.session_id = Some(session.id.clone());
+        self.owns_session = false;
+        Ok(session)
+    }
+
+    /// Send a message to the current session
+    pub async fn send_message(&self, content: &str) -> Result<()> {
+        self.send_message_with_overrides(content, MessageOverrides::default())
+            .await
     }
 
-    /// Send a message with attachments
+    /// Send a message with custom command overrides (allowed tools, model, shell snippets)
+    pub async fn send_message_with_overrides(
+        &self,
+        content: &str,
+        overrides: MessageOverrides,
+    ) -> Result<()> {
+        self.send_message_with_attachments(content, Vec::new(), overrides)
+            .await
+    }
+
+    /// Send a message with attachments; those without content are read by the server
     pub async fn send_message_with_attachments(
         &self,
         content: &str,
         attachments: Vec<AttachmentInput>,
+        overrides: MessageOverrides,
     ) -> Result<()> {
         let session_id = self
             .session_id
@@ -152,6 +214,9 @@ impl SafeCoderClient {
                     content: a.content,
                 })
                 .collect(),
+            allowed_tools: overrides.allowed_tools,
+            model: overrides.model,
+            shell_snippets: overrides.shell_snippets,
         };
 
         let resp = self
@@ -197,6 +262,246 @@ impl SafeCoderClient {
         Ok(())
     }
 
+    /// List the session's skills
+    pub async fn list_skills(&self) -> Result<Vec<SkillDto>> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!("{}/api/sessions/{}/skills", self.base_url, session_id);
+
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to list skills")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to list skills: {} - {}", status, text);
+        }
+
+        resp.json().await.context("Failed to parse skills")
+    }
+
+    /// Turn a skill on or off
+    pub async fn set_skill_active(&self, name: &str, active: bool) -> Result<()> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!("{}/api/sessions/{}/skills/{}", self.base_url, session_id, name);
+
+        let resp = self
+            .client
+            .put(&url)
+            .json(&SetSkillRequest { active })
+            .send()
+            .await
+            .context("Failed to update skill")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to update skill: {} - {}", status, text);
+        }
+
+        Ok(())
+    }
+
+    /// Get the session's sampling parameters
+    pub async fn get_params(&self) -> Result<GenerationParams> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!("{}/api/sessions/{}/params", self.base_url, session_id);
+
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to get parameters")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to get parameters: {} - {}", status, text);
+        }
+
+        resp.json().await.context("Failed to parse parameters")
+    }
+
+    /// Get what the session's current model supports
+    pub async fn get_capabilities(&self) -> Result<Capabilities> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!("{}/api/sessions/{}/capabilities", self.base_url, session_id);
+
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to get capabilities")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to get capabilities: {} - {}", status, text);
+        }
+
+        resp.json().await.context("Failed to parse capabilities")
+    }
+
+    /// Set a sampling parameter for the session, returning the new values
+    pub async fn set_param(&self, name: &str, value: &str) -> Result<GenerationParams> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!("{}/api/sessions/{}/params", self.base_url, session_id);
+
+        let resp = self
+            .client
+            .put(&url)
+            .json(&SetParamRequest {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+            .send()
+            .await
+            .context("Failed to set parameter")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to set parameter: {} - {}", status, text);
+        }
+
+        resp.json().await.context("Failed to parse parameters")
+    }
+
+    /// Get the health of the session's MCP servers
+    pub async fn mcp_status(&self) -> Result<Vec<McpServerStatusDto>> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!("{}/api/sessions/{}/mcp/status", self.base_url, session_id);
+
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to get MCP status")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to get MCP status: {} - {}", status, text);
+        }
+
+        resp.json().await.context("Failed to parse MCP status")
+    }
+
+    /// List resources from the session's MCP servers
+    pub async fn list_mcp_resources(&self) -> Result<Vec<McpResourceDto>> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!("{}/api/sessions/{}/mcp/resources", self.base_url, session_id);
+
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to list MCP resources")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to list MCP resources: {} - {}", status, text);
+        }
+
+        resp.json().await.context("Failed to parse MCP resources")
+    }
+
+    /// List prompts from the session's MCP servers
+    pub async fn list_mcp_prompts(&self) -> Result<Vec<McpPromptDto>> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!("{}/api/sessions/{}/mcp/prompts", self.base_url, session_id);
+
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .context("Failed to list MCP prompts")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to list MCP prompts: {} - {}", status, text);
+        }
+
+        resp.json().await.context("Failed to parse MCP prompts")
+    }
+
+    /// Render an MCP prompt with raw slash-command arguments
+    pub async fn get_mcp_prompt(&self, server: &str, name: &str, arguments: &str) -> Result<String> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!(
+            "{}/api/sessions/{}/mcp/prompts/{}/{}",
+            self.base_url, session_id, server, name
+        );
+
+        let resp = self
+            .client
+            .post(&url)
+            .json(&McpPromptRequest {
+                arguments: arguments.to_string(),
+            })
+            .send()
+            .await
+            .context("Failed to get MCP prompt")?;
+
+        if !resp.status().is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            let error = serde_json::from_str::<serde_json::Value>(&text)
+                .ok()
+                .and_then(|v| v["error"].as_str().map(|s| s.to_string()))
+                .unwrap_or(text);
+            anyhow::bail!("{}", error);
+        }
+
+        let prompt: McpPromptResponse = resp.json().await.context("Failed to parse MCP prompt")?;
+        Ok(prompt.text)
+    }
+
     /// Respond to a doom loop prompt
     pub async fn respond_to_doom_loop(&self, prompt_id: &str, continue_anyway: bool) -> Result<()> {
         let session_id = self
@@ -230,9 +535,45 @@ impl SafeCoderClient {
         Ok(())
     }
 
+    /// Respond to a tool approval prompt
+    pub async fn respond_to_tool_approval(&self, prompt_id: &str, approved: bool) -> Result<()> {
+        let session_id = self
+            .session_id
+            .as_ref()
+            .context("No active session")?;
+
+        let url = format!(
+            "{}/api/sessions/{}/tool-approval-response",
+            self.base_url, session_id
+        );
+        let request = ToolApprovalResponseRequest {
+            prompt_id: prompt_id.to_string(),
+            approved,
+        };
+
+        let resp = self
+            .client
+            .post(&url)
+            .json(&request)
+            .send()
+            .await
+            .context("Failed to respond to tool approval")?;
+
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let text = resp.text().await.unwrap_or_default();
+            anyhow::bail!("Failed to respond to tool approval: {} - {}", status, text);
+        }
+
+        Ok(())
+    }
+
     /// Delete/close the current session
+    ///
+    /// A session this client attached to is only detached from, so the
+    /// client that created it can carry on.
     pub async fn close_session(&mut self) -> Result<()> {
-        if let Some(session_id) = self.session_id.take() {
+        if let Some(session_id) = self.session_id.take().filter(|_| self.owns_session) {
             let url = format!("{}/api/sessions/{}", self.base_url, session_id);
             let _ = self.client.delete(&url).send().await;
         }
@@ -242,13 +583,25 @@ impl SafeCoderClient {
     /// Subscribe to SSE events for the current session
     /// Returns a receiver that yields ServerEvents
     pub async fn subscribe_events(&self) -> Result<mpsc::UnboundedReceiver<ServerEvent>> {
+        self.subscribe_events_with_replay(0).await
+    }
+
+    /// Subscribe to SSE events, first replaying up to `replay` events of the
+    /// turn in progress
+    pub async fn subscribe_events_with_replay(
+        &self,
+        replay: usize,
+    ) -> Result<mpsc::UnboundedReceiver<ServerEvent>> {
         let session_id = self
             .session_id
             .as_ref()
             .context("No active session")?
             .clone();
 
-        let url = format!("{}/api/sessions/{}/events", self.base_url, session_id);
+        let url = format!(
+            "{}/api/sessions/{}/events?replay={}",
+            self.base_url, session_id, replay
+        );
         tracing::debug!("Subscribing to SSE at: {}", url);
 
         let (tx, rx) = mpsc::unbounded_channel();
@@ -282,24 +635,81 @@ pub struct AttachmentInput {
     pub content: Option<String>,
 }
 
+/// Reconnection attempts in a row without receiving an event, before the
+/// event stream is given up on
+const SSE_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// How an SSE connection ended
+enum SseEnd {
+    /// The receiver was dropped; nobody is listening any more
+    ReceiverDropped,
+    /// The connection dropped or the server closed the stream
+    Disconnected,
+    /// The server refused the stream, e.g. because the session is gone
+    Rejected(reqwest::StatusCode),
+}
+
 /// SSE listener that parses server-sent events
+///
+/// When the connection drops, it reconnects with `Last-Event-ID` so the
+/// server replays whatever was missed in between.
 async fn sse_listener(
     client: Client,
     url: String,
     tx: mpsc::UnboundedSender<ServerEvent>,
 ) -> Result<()> {
+    let mut last_event_id = None;
+    let mut failures = 0;
+    loop {
+        let resumed_from = last_event_id;
+        match sse_connection(&client, &url, &mut last_event_id, &tx).await {
+            Ok(SseEnd::ReceiverDropped) => return Ok(()),
+            Ok(SseEnd::Rejected(status)) => {
+                tracing::error!("SSE: Connection failed with status {}", status);
+                anyhow::bail!("SSE connection failed: {}", status);
+            }
+            Ok(SseEnd::Disconnected) => tracing::debug!("SSE: Stream ended"),
+            Err(e) => tracing::warn!("SSE: {:#}", e),
+        }
+        if tx.is_closed() {
+            return Ok(());
+        }
+
+        if last_event_id != resumed_from {
+            failures = 0;
+        }
+        failures += 1;
+        if failures > SSE_RECONNECT_ATTEMPTS {
+            anyhow::bail!(
+                "Lost the event stream after {} reconnection attempts",
+                SSE_RECONNECT_ATTEMPTS
+            );
+        }
+        tokio::time::sleep(Duration::from_millis(500 * failures as u64)).await;
+        tracing::info!("SSE: Reconnecting after event {:?}", last_event_id);
+    }
+}
+
+/// Stream events from one SSE connection, tracking the last event ID
+async fn sse_connection(
+    client: &Client,
+    url: &str,
+    last_event_id: &mut Option<u64>,
+    tx: &mpsc::UnboundedSender<ServerEvent>,
+) -> Result<SseEnd> {
     tracing::debug!("SSE: Connecting to {}", url);
 
-    let resp = client
-        .get(&url)
-        .header("Accept", "text/event-stream")
+    let mut request = client.get(url).header("Accept", "text/event-stream");
+    if let Some(id) = last_event_id {
+        request = request.header("Last-Event-ID", id.to_string());
+    }
+    let resp = request
         .send()
         .await
         .context("Failed to connect to SSE endpoint")?;
 
     if !resp.status().is_success() {
-        tracing::error!("SSE: Connection failed with status {}", resp.status());
-        anyhow::bail!("SSE connection failed: {}", resp.status());
+        return Ok(SseEnd::Rejected(resp.status()));
     }
 
     tracing::debug!("SSE: Connected successfully");
@@ -318,13 +728,17 @@ async fn sse_listener(
             let message = buffer[..pos].to_string();
             buffer = buffer[pos + 2..].to_string();
 
+            if let Some(id) = parse_sse_id(&message) {
+                *last_event_id = Some(id);
+            }
+
             // Parse SSE message
             if let Some(event) = parse_sse_message(&message) {
                 tracing::trace!("SSE: Received event: {:?}", event);
                 if tx.send(event).is_err() {
                     // Receiver dropped, stop listening
                     tracing::debug!("SSE: Receiver dropped, stopping");
-                    return Ok(());
+                    return Ok(SseEnd::ReceiverDropped);
                 }
             } else {
                 tracing::warn!("SSE: Failed to parse message: {}", message);
@@ -332,8 +746,15 @@ async fn sse_listener(
         }
     }
 
-    tracing::debug!("SSE: Stream ended");
-    Ok(())
+    Ok(SseEnd::Disconnected)
+}
+
+/// The `id:` of an SSE message, if it has one
+fn parse_sse_id(message: &str) -> Option<u64> {
+    message
+        .lines()
+        .find_map(|line| line.strip_prefix("id:"))
+        .and_then(|id| id.trim().parse().ok())
 }
 
 /// Parse a single SSE message into a ServerEvent
@@ -463,6 +884,25 @@ impl ServerManager {
         }
     }
 
+    /// Stop managing the server we started if a client is still streaming
+    /// one of its sessions, so it outlives this process
+    ///
+    /// Returns whether the server was left running.
+    pub async fn release_if_shared(&mut self) -> bool {
+        if self.process.is_none() {
+            return false;
+        }
+        let shared = SafeCoderClient::new(self.port)
+            .list_sessions()
+            .await
+            .is_ok_and(|sessions| sessions.iter().any(|s| s.clients > 0));
+        if shared {
+            // Dropping a tokio Child leaves the process running
+            self.process = None;
+        }
+        shared
+    }
+
     /// Stop the server if we started it
     pub async fn stop(&mut self) {
         if let Some(mut child) = self.process.take() {
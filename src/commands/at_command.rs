@@ -54,6 +54,9 @@ impl AtCommand {
             files.push(current_pattern);
         }
 
+        // @mcp:server/resource mentions are attached by the session, not read from disk
+        files.retain(|pattern| !pattern.starts_with("mcp:"));
+
         if files.is_empty() {
             None
         } else {
@@ -172,6 +175,14 @@ mod tests {
         assert_eq!(cmd.files[0], "src/**/*.rs");
     }
 
+    #[test]
+    fn test_skips_mcp_resource_mentions() {
+        assert!(AtCommand::parse("Summarize @mcp:docs/readme").is_none());
+
+        let cmd = AtCommand::parse("Compare @main.rs with @mcp:docs/readme").unwrap();
+        assert_eq!(cmd.files, vec!["main.rs"]);
+    }
+
     #[test]
     fn test_ignore_email() {
         let cmd = AtCommand::parse("Email me at user@example.com");
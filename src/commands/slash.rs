@@ -15,6 +15,11 @@ pub enum SlashCommand {
     Model(Option<String>),
     /// List available models for the current provider
     Models,
+    /// Show or set a sampling parameter (`/set temperature 0.2`)
+    Set {
+        name: Option<String>,
+        value: Option<String>,
+    },
     Restore(Option<String>),
     ApprovalMode(Option<String>),
     ExecutionMode(Option<String>),
@@ -25,7 +30,8 @@ pub enum SlashCommand {
     About,
     Copy,
     Directory(DirectorySubcommand),
-    Init,
+    /// Generate SAFE_CODER.md from the code base (`force` replaces an existing one)
+    Init { force: bool },
     Commands,
     Checkpoint(CheckpointSubcommand),
     /// Undo the last change (git-based)
@@ -36,8 +42,21 @@ pub enum SlashCommand {
     Compact,
     /// Skill management
     Skill(SkillSubcommand),
+    /// List MCP resources or prompts
+    Mcp(McpSubcommand),
+    /// Run an MCP server prompt (`/mcp:<server>:<prompt> [args]`)
+    McpPrompt {
+        server: String,
+        name: String,
+        args: String,
+    },
     /// Show current unified plan status
     Plan(PlanSubcommand),
+    /// Show CI status for the current branch, sending failures to the AI
+    Ci,
+    /// Audit dependencies for vulnerabilities; the AI summarizes them and,
+    /// with `fix`, works through the remediations
+    Audit { fix: bool },
     Unknown(String),
 }
 
@@ -45,6 +64,12 @@ pub enum SlashCommand {
 pub enum PlanSubcommand {
     /// Show current plan status
     Show,
+    /// Show the remaining steps of the newest saved plan
+    Status,
+    /// List plans saved in .safe-coder/plans
+    List,
+    /// Continue a saved plan (the newest unfinished one by default)
+    Resume(Option<String>),
     /// List all step groups
     Groups,
     /// Show plan history
@@ -63,6 +88,16 @@ pub enum SkillSubcommand {
     Info(String),
 }
 
+#[derive(Debug, Clone)]
+pub enum McpSubcommand {
+    /// List resources from connected servers
+    Resources,
+    /// List prompts from connected servers
+    Prompts,
+    /// Show server health, latency and tool counts
+    Status,
+}
+
 #[derive(Debug, Clone)]
 pub enum CheckpointSubcommand {
     List,
@@ -103,6 +138,18 @@ impl SlashCommand {
             return SlashCommand::Unknown(String::new());
         }
 
+        // MCP server prompts keep their case: /mcp:<server>:<prompt> [args]
+        if let Some((server, name)) = parts[0]
+            .strip_prefix("mcp:")
+            .and_then(|prompt| prompt.split_once(':'))
+        {
+            return SlashCommand::McpPrompt {
+                server: server.to_string(),
+                name: name.to_string(),
+                args: parts[1..].join(" "),
+            };
+        }
+
         let cmd = parts[0].to_lowercase();
         let args = &parts[1..];
 
@@ -115,6 +162,10 @@ impl SlashCommand {
             "memory" => Self::parse_memory_subcommand(args),
             "model" => SlashCommand::Model(args.get(0).map(|s| s.to_string())),
             "models" => SlashCommand::Models,
+            "set" => SlashCommand::Set {
+                name: args.first().map(|s| s.to_string()),
+                value: (args.len() > 1).then(|| args[1..].join(" ")),
+            },
             "restore" => SlashCommand::Restore(args.get(0).map(|s| s.to_string())),
             "approval-mode" => SlashCommand::ApprovalMode(args.get(0).map(|s| s.to_string())),
             // /mode and /agent are aliases for execution mode
@@ -126,7 +177,9 @@ impl SlashCommand {
             "about" => SlashCommand::About,
             "copy" => SlashCommand::Copy,
             "directory" | "dir" => Self::parse_directory_subcommand(args),
-            "init" => SlashCommand::Init,
+            "init" => SlashCommand::Init {
+                force: args.first().is_some_and(|a| a.eq_ignore_ascii_case("force")),
+            },
             "commands" => SlashCommand::Commands,
             "checkpoint" | "cp" => Self::parse_checkpoint_subcommand(args),
             // Undo/Redo commands
@@ -138,8 +191,17 @@ impl SlashCommand {
             "sessions" => SlashCommand::Chat(ChatSubcommand::List),
             // Skill management
             "skill" | "skills" => Self::parse_skill_subcommand(args),
+            "mcp" => match args.first().map(|s| s.to_lowercase()).as_deref() {
+                Some("prompts" | "prompt") => SlashCommand::Mcp(McpSubcommand::Prompts),
+                Some("status") => SlashCommand::Mcp(McpSubcommand::Status),
+                _ => SlashCommand::Mcp(McpSubcommand::Resources),
+            },
             // Plan management
             "plan" => Self::parse_plan_subcommand(args),
+            "ci" => SlashCommand::Ci,
+            "audit" => SlashCommand::Audit {
+                fix: args.first().is_some_and(|a| a.eq_ignore_ascii_case("fix")),
+            },
             _ => SlashCommand::Unknown(input.to_string()),
         }
     }
@@ -150,7 +212,12 @@ impl SlashCommand {
         }
 
         match args[0].to_lowercase().as_str() {
-            "show" | "status" => SlashCommand::Plan(PlanSubcommand::Show),
+            "show" => SlashCommand::Plan(PlanSubcommand::Show),
+            "status" => SlashCommand::Plan(PlanSubcommand::Status),
+            "list" | "ls" => SlashCommand::Plan(PlanSubcommand::List),
+            "resume" | "continue" => {
+                SlashCommand::Plan(PlanSubcommand::Resume(args.get(1).map(|s| s.to_string())))
+            }
             "groups" | "parallel" => SlashCommand::Plan(PlanSubcommand::Groups),
             "history" | "log" => SlashCommand::Plan(PlanSubcommand::History),
             _ => SlashCommand::Plan(PlanSubcommand::Show),
@@ -325,6 +392,17 @@ pub async fn execute_slash_command(
             let models = session.list_available_models().await?;
             Ok(CommandResult::Message(models))
         }
+        SlashCommand::Set { name, value } => match (name, value) {
+            (Some(name), Some(value)) => match session.set_generation_param(&name, &value).await {
+                Ok(()) => Ok(CommandResult::Message(format!("✓ {} set to {}", name, value))),
+                Err(e) => Ok(CommandResult::Message(format!("❌ {}", e))),
+            },
+            (Some(name), None) => Ok(CommandResult::Message(format!(
+                "Usage: /set {} <value> (or 'default')",
+                name
+            ))),
+            _ => Ok(CommandResult::Message(session.generation_params().to_string())),
+        },
         SlashCommand::Restore(file) => {
             session.restore_file(file.as_deref()).await?;
             Ok(CommandResult::Message(
@@ -405,12 +483,14 @@ pub async fn execute_slash_command(
             ))
         }
         SlashCommand::Directory(subcmd) => execute_directory_command(subcmd, session).await,
-        SlashCommand::Init => {
-            session.init_project_context().await?;
-            Ok(CommandResult::Message(
-                "✓ Created project context file".to_string(),
-            ))
-        }
+        SlashCommand::Init { force } => match session.init_project_context(force).await {
+            Ok(path) => Ok(CommandResult::Message(format!(
+                "✓ Wrote {} from an analysis of the project. Review and edit it; it is \
+                 included in every session.",
+                path.display()
+            ))),
+            Err(e) => Ok(CommandResult::Message(format!("❌ {}", e))),
+        },
         SlashCommand::Commands => Ok(CommandResult::ShowCommandsModal),
         SlashCommand::Checkpoint(subcmd) => execute_checkpoint_command(subcmd, session).await,
         SlashCommand::Undo => {
@@ -425,8 +505,27 @@ pub async fn execute_slash_command(
             let result = session.compact_context().await?;
             Ok(CommandResult::Message(result))
         }
-        SlashCommand::Skill(subcmd) => execute_skill_command(subcmd).await,
+        SlashCommand::Skill(subcmd) => execute_skill_command(subcmd, session).await,
+        SlashCommand::Mcp(subcmd) => execute_mcp_command(subcmd, session).await,
+        SlashCommand::McpPrompt { server, name, args } => {
+            match session.mcp().get_prompt(&server, &name, &args).await {
+                Ok(prompt) => Ok(CommandResult::ModifiedInput(prompt)),
+                Err(e) => Ok(CommandResult::Message(format!("❌ {}", e))),
+            }
+        }
         SlashCommand::Plan(subcmd) => execute_plan_command(subcmd, session).await,
+        SlashCommand::Ci => match session.ci_status().await {
+            Ok(report) if report.failing => Ok(CommandResult::ModifiedInput(report.fix_prompt())),
+            Ok(report) => Ok(CommandResult::Message(report.text)),
+            Err(e) => Ok(CommandResult::Message(format!("❌ {}", e))),
+        },
+        SlashCommand::Audit { fix } => match session.audit_dependencies().await {
+            Ok(report) if report.vulnerabilities.is_empty() => Ok(CommandResult::Message(
+                format!("✅ {}: no known vulnerabilities", report.auditor),
+            )),
+            Ok(report) => Ok(CommandResult::ModifiedInput(report.remediation_prompt(fix))),
+            Err(e) => Ok(CommandResult::Message(format!("❌ {}", e))),
+        },
         SlashCommand::Unknown(cmd) => Ok(CommandResult::Message(format!(
             "Unknown command: /{}. Type /help for available commands.",
             cmd
@@ -434,6 +533,49 @@ pub async fn execute_slash_command(
     }
 }
 
+async fn execute_mcp_command(subcmd: McpSubcommand, session: &Session) -> Result<CommandResult> {
+    let output = match subcmd {
+        McpSubcommand::Resources => {
+            let resources = session.mcp().list_resources().await;
+            if resources.is_empty() {
+                "No MCP resources available.".to_string()
+            } else {
+                let mut output = String::from("🔌 MCP resources:\n\n");
+                for (server, resource) in &resources {
+                    output.push_str(&format!(
+                        "  @mcp:{}/{}  {}\n",
+                        server,
+                        resource.uri,
+                        resource.description.as_deref().unwrap_or(&resource.name)
+                    ));
+                }
+                output.push_str("\nMention a resource in your message to attach its contents.");
+                output
+            }
+        }
+        McpSubcommand::Prompts => {
+            let prompts = session.mcp().list_prompts().await;
+            if prompts.is_empty() {
+                "No MCP prompts available.".to_string()
+            } else {
+                let mut output = String::from("🔌 MCP prompts:\n\n");
+                for (server, prompt) in &prompts {
+                    output.push_str(&format!(
+                        "  {}  {}\n",
+                        crate::mcp::prompt_usage(server, prompt),
+                        prompt.description.as_deref().unwrap_or("")
+                    ));
+                }
+                output
+            }
+        }
+        McpSubcommand::Status => {
+            crate::mcp::format_server_status(&session.mcp().server_status().await)
+        }
+    };
+    Ok(CommandResult::Message(output))
+}
+
 async fn execute_plan_command(subcmd: PlanSubcommand, session: &Session) -> Result<CommandResult> {
     match subcmd {
         PlanSubcommand::Show => {
@@ -448,63 +590,110 @@ async fn execute_plan_command(subcmd: PlanSubcommand, session: &Session) -> Resu
             let output = session.format_plan_history();
             Ok(CommandResult::Message(output))
         }
+        PlanSubcommand::Status => {
+            let output = match session.plan_store().resolve(None) {
+                Ok(plan) => crate::planning::artifact::format_status(&plan),
+                Err(e) => format!("📋 {}", e),
+            };
+            Ok(CommandResult::Message(output))
+        }
+        PlanSubcommand::List => Ok(CommandResult::Message(
+            crate::planning::artifact::format_plan_list(&session.plan_store().list()),
+        )),
+        PlanSubcommand::Resume(id) => {
+            let store = session.plan_store();
+            match store.resolve(id.as_deref()) {
+                Ok(plan) => Ok(CommandResult::ModifiedInput(
+                    crate::planning::artifact::resume_prompt(&plan, &store.path_for(&plan.id)),
+                )),
+                Err(e) => Ok(CommandResult::Message(format!("📋 {}", e))),
+            }
+        }
     }
 }
 
-async fn execute_skill_command(subcmd: SkillSubcommand) -> Result<CommandResult> {
-    // Note: Full integration with session would require adding SkillManager to Session
-    // For now, we'll show placeholder responses
+async fn execute_skill_command(
+    subcmd: SkillSubcommand,
+    session: &mut Session,
+) -> Result<CommandResult> {
     match subcmd {
         SkillSubcommand::List => {
+            let skills = session.skills().list();
             let mut output = String::from("📚 Available Skills:\n\n");
-            output.push_str("Built-in skills:\n");
-            output.push_str(
-                "  • rust-patterns    - Rust idioms and best practices (triggers: *.rs)\n",
-            );
-            output.push_str(
-                "  • react-patterns   - React/TypeScript best practices (triggers: *.tsx, *.jsx)\n",
-            );
-            output.push_str(
-                "  • python-patterns  - Python idioms and best practices (triggers: *.py)\n",
-            );
-            output.push_str("\nTo load custom skills, add .md files to:\n");
+            for skill in &skills {
+                let status = if skill.active { "●" } else { "○" };
+                let triggers = if skill.triggers.is_empty() {
+                    "manual".to_string()
+                } else {
+                    skill.triggers.join(", ")
+                };
+                output.push_str(&format!(
+                    "  {} {:<18} {} (triggers: {}, ~{} tokens)\n",
+                    status,
+                    skill.name,
+                    skill.description.as_deref().unwrap_or(""),
+                    triggers,
+                    skill.estimated_tokens()
+                ));
+            }
+            output.push_str("\nSkills activate automatically when matching files are read or edited.\n");
+            output.push_str("To add custom skills, put .md files in:\n");
             output.push_str("  • .safe-coder/skills/ (project-level)\n");
             output.push_str("  • ~/.config/safe-coder/skills/ (user-level)\n");
-            output.push_str("\nUse /skill activate <name> to enable a skill.");
+            output.push_str("\nUse /skill on <name> or /skill off <name> to toggle a skill.");
             Ok(CommandResult::Message(output))
         }
-        SkillSubcommand::Activate(name) => Ok(CommandResult::Message(format!(
-            "✓ Skill '{}' activated. Its knowledge will be injected into prompts.",
-            name
-        ))),
-        SkillSubcommand::Deactivate(name) => Ok(CommandResult::Message(format!(
-            "✓ Skill '{}' deactivated.",
-            name
-        ))),
-        SkillSubcommand::Info(name) => {
-            // Show info about built-in skills
-            let info = match name.as_str() {
-                "rust-patterns" | "rust" => {
-                    "📖 rust-patterns\n\nRust idioms and best practices\n\nTriggers: *.rs\n\nContent: Error handling with Result, ownership patterns, iterator usage, pattern matching, documentation, testing, and async best practices."
-                }
-                "react-patterns" | "react" => {
-                    "📖 react-patterns\n\nReact and TypeScript best practices\n\nTriggers: *.tsx, *.jsx\n\nContent: Functional components, hooks rules, state management, memoization, effects cleanup, component structure, and key usage."
+        SkillSubcommand::Activate(name) => {
+            if session.skills_mut().activate(&name) {
+                Ok(CommandResult::Message(format!(
+                    "✓ Skill '{}' activated. Its knowledge will be injected into prompts.",
+                    name
+                )))
+            } else {
+                Ok(CommandResult::Message(skill_not_found(&name)))
+            }
+        }
+        SkillSubcommand::Deactivate(name) => {
+            if session.skills_mut().deactivate(&name) {
+                Ok(CommandResult::Message(format!(
+                    "✓ Skill '{}' deactivated. It won't be re-activated by matching files.",
+                    name
+                )))
+            } else {
+                Ok(CommandResult::Message(skill_not_found(&name)))
+            }
+        }
+        SkillSubcommand::Info(name) => match session.skills().get(&name) {
+            Some(skill) => {
+                let mut output = format!("📖 {}\n\n", skill.name);
+                if let Some(ref desc) = skill.description {
+                    output.push_str(&format!("{}\n\n", desc));
                 }
-                "python-patterns" | "python" => {
-                    "📖 python-patterns\n\nPython idioms and best practices\n\nTriggers: *.py\n\nContent: Type hints, docstrings, virtual environments, comprehensions, context managers, f-strings, dataclasses, and pathlib."
+                if !skill.triggers.is_empty() {
+                    output.push_str(&format!("Triggers: {}\n", skill.triggers.join(", ")));
                 }
-                _ => {
-                    return Ok(CommandResult::Message(format!(
-                        "Skill '{}' not found. Use /skill list to see available skills.",
-                        name
-                    )));
+                output.push_str(&format!(
+                    "Status: {}\n",
+                    if skill.active { "active" } else { "inactive" }
+                ));
+                if let Some(ref path) = skill.source_path {
+                    output.push_str(&format!("Source: {}\n", path.display()));
                 }
-            };
-            Ok(CommandResult::Message(info.to_string()))
-        }
+                output.push_str(&format!("\n{}", skill.content.trim()));
+                Ok(CommandResult::Message(output))
+            }
+            None => Ok(CommandResult::Message(skill_not_found(&name))),
+        },
     }
 }
 
+fn skill_not_found(name: &str) -> String {
+    format!(
+        "Skill '{}' not found. Use /skill list to see available skills.",
+        name
+    )
+}
+
 async fn execute_chat_command(
     subcmd: ChatSubcommand,
     session: &mut Session,
@@ -530,8 +719,11 @@ async fn execute_chat_command(
             Ok(CommandResult::Message(format!("✓ Deleted chat: {}", id)))
         }
         ChatSubcommand::Share(id) => {
-            let share_url = session.share_chat(&id).await?;
-            Ok(CommandResult::Message(format!("Share URL: {}", share_url)))
+            let share = session.share_chat(&id).await?;
+            Ok(CommandResult::Message(format!(
+                "🔗 Share URL: {}\n🔑 Key: {}\n\nSend both; open it with: safe-coder import-share {} --key {}",
+                share.url, share.key, share.url, share.key
+            )))
         }
     }
 }
@@ -641,6 +833,7 @@ CONFIGURATION
   /agent [plan|act]   Alias for /mode
   /model [name]       Switch model or show current
   /models             List available models for current provider
+  /set [name value]   Show or set temperature, top_p or stop for this session
   /approval-mode [mode]  Set approval mode (plan/default/auto-edit/yolo)
   /settings           Show current settings
 
@@ -650,12 +843,14 @@ EXECUTION MODES
 
 PROJECT TOOLS
   /summary            Generate project summary
+  /ci                 Show CI status of the current branch; failures go to the AI
+  /audit [fix]        Audit dependencies; the AI proposes (or with fix, applies) remediations
   /compress           Compress conversation to save tokens
   /restore [file]     Restore file(s) from git checkpoint
   /tools              List available tools
   /dir add <path>     Add directory to workspace
   /dir show           Show workspace directories
-  /init               Create project context file
+  /init [force]       Generate SAFE_CODER.md from the code base
 
 CHECKPOINTS (git-agnostic snapshots)
   /checkpoint list         List all saved checkpoints
@@ -665,14 +860,23 @@ CHECKPOINTS (git-agnostic snapshots)
 
 SKILLS (specialized knowledge)
   /skill list         List all available skills
-  /skill activate <name>   Activate a skill
-  /skill deactivate <name> Deactivate a skill
+  /skill on <name>    Activate a skill
+  /skill off <name>   Deactivate a skill
   /skill info <name>  Show skill details
 
+MCP SERVERS
+  /mcp resources      List resources (attach with @mcp:<server>/<resource>)
+  /mcp prompts        List server prompts
+  /mcp status         Show server health and latency
+  /mcp:<server>:<prompt> [args]  Run a server prompt
+
 UNIFIED PLANNING
   /plan               Show current plan status
   /plan groups        Show step groups and parallelism
   /plan history       Show plan execution history
+  /plan status        Show remaining steps of the saved plan
+  /plan list          List plans saved in .safe-coder/plans
+  /plan resume [id]   Continue a saved plan
 
 OTHER
   /copy               Copy last output to clipboard
@@ -734,7 +938,7 @@ pub fn get_commands_text() -> String {
   /chat resume <id>     Resume a previously saved conversation
   /chat list            List all saved conversations
   /chat delete <id>     Delete a saved conversation
-  /chat share <id>      Generate a shareable link for a conversation
+  /chat share <id>      Upload an encrypted copy and print its URL and key
   /sessions             List all saved sessions (alias for /chat list)
 
 ↩️  UNDO/REDO (Git-Based)
@@ -771,7 +975,7 @@ pub fn get_commands_text() -> String {
   /tools                List all available development tools
   /dir add <path>       Add directory to current workspace
   /dir show             Show all directories in workspace
-  /init                 Create a project context file (SAFE_CODER.md)
+  /init [force]         Generate SAFE_CODER.md from an analysis of the project
 
 📦 CHECKPOINTS (Git-Agnostic Snapshots)
   /checkpoint, /cp        List all saved checkpoints (alias: /cp list)
@@ -783,14 +987,24 @@ pub fn get_commands_text() -> String {
 🎯 SKILLS (Specialized Knowledge)
   /skill, /skills         List all available skills
   /skill list             List all skills with activation status
-  /skill activate <name>  Activate a skill (injects knowledge into prompts)
-  /skill deactivate <name> Deactivate a skill
+  /skill on <name>        Activate a skill (injects knowledge into prompts)
+  /skill off <name>       Deactivate a skill (stops auto-activation too)
   /skill info <name>      Show details about a specific skill
 
+🔌 MCP SERVERS
+  /mcp resources          List resources from connected MCP servers
+  /mcp prompts            List prompts from connected MCP servers
+  /mcp status             Show MCP server health, latency and tool counts
+  /mcp:<server>:<prompt>  Run a server prompt, e.g. /mcp:github:review 42
+  @mcp:<server>/<uri>     Attach a resource's contents to your message
+
 📐 UNIFIED PLANNING
   /plan                   Show current plan status and execution mode
   /plan groups            Show step groups with parallelism info
   /plan history           Show execution history of plans
+  /plan status            Show remaining steps of the newest saved plan
+  /plan list              List plans saved in .safe-coder/plans/
+  /plan resume [id]       Continue a saved plan in BUILD mode
                           The unified planning system creates mode-aware plans:
                           • Direct - Sequential inline execution
                           • Subagent - Parallel internal agents
@@ -0,0 +1,87 @@
+//! Shell completions
+//!
+//! `safe-coder completions <shell>` prints a script that asks the binary
+//! itself what to complete (`COMPLETE=<shell> safe-coder -- <words>`,
+//! answered by `CompleteEnv` at the top of `main`). Values that only exist
+//! at runtime complete that way too: the candidate functions here list
+//! saved session IDs, stored account names and installed skill packs.
+
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use clap_complete::env::Shells;
+use clap_complete::{CompletionCandidate, Shell};
+
+use crate::config::{Config, LlmProvider};
+
+/// Environment variable the completion scripts set when calling back
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Write the completion script for `shell`
+pub fn write_script(shell: Shell, buf: &mut dyn Write) -> Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(&shell.to_string())
+        .with_context(|| format!("No completion support for {}", shell))?;
+    completer.write_registration(COMPLETE_VAR, "safe-coder", "safe-coder", "safe-coder", buf)?;
+    Ok(())
+}
+
+/// Sessions from the last 30 days, for `resume` and `replay`
+pub fn session_ids() -> Vec<CompletionCandidate> {
+    crate::persistence::event_log::EventLogger::list_recent_sessions(30)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|session| {
+            CompletionCandidate::new(session.session_id).help(Some(
+                format!(
+                    "{} {}",
+                    session.created_at.format("%Y-%m-%d %H:%M"),
+                    session.project_path
+                )
+                .into(),
+            ))
+        })
+        .collect()
+}
+
+/// Stored account names of every provider that supports login
+pub fn account_names() -> Vec<CompletionCandidate> {
+    let Ok(accounts) = crate::auth::accounts::Accounts::load() else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = [LlmProvider::Anthropic, LlmProvider::GitHubCopilot]
+        .iter()
+        .filter_map(|provider| Config::provider_key(provider).ok())
+        .flat_map(|key| accounts.names(key))
+        .collect();
+    names.sort();
+    names.dedup();
+    names.into_iter().map(CompletionCandidate::new).collect()
+}
+
+/// Installed skill packs, for `skill update` and `skill remove`
+pub fn skill_pack_names() -> Vec<CompletionCandidate> {
+    crate::skills::packs::SkillPackManager::user()
+        .and_then(|manager| manager.list())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|pack| CompletionCandidate::new(pack.name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_script_calls_back_into_the_binary() {
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish, Shell::PowerShell] {
+            let mut script = Vec::new();
+            write_script(shell, &mut script).unwrap();
+            let script = String::from_utf8(script).unwrap();
+            assert!(script.contains(COMPLETE_VAR), "{}", shell);
+            assert!(script.contains("safe-coder"), "{}", shell);
+        }
+    }
+}
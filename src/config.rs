@@ -1,8 +1,41 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+use crate::hooks::HooksConfig;
 use crate::mcp::McpConfig;
+use crate::skills::SkillsConfig;
+use crate::tools::AgentMode;
+
+/// Settings only the global config or the organization policy may set. A
+/// cloned repository could otherwise launch programs, approve tools, turn
+/// off the command and path safety checks, grant itself server API tokens,
+/// or send requests and credentials elsewhere through them. A `*` segment
+/// matches every entry of a table.
+const GLOBAL_ONLY_KEYS: &[&str] = &[
+    "hooks",
+    "mcp",
+    "permissions",
+    "server",
+    "update",
+    "llm.base_url",
+    "llm.api_key",
+    "lsp.servers.*.command",
+    "lsp.servers.*.args",
+    "lsp.servers.*.env",
+    "orchestrator.claude_cli_path",
+    "orchestrator.gemini_cli_path",
+    "orchestrator.safe_coder_cli_path",
+    "orchestrator.gh_cli_path",
+    "build.commands",
+    "tools.warn_dangerous_commands",
+    "tools.dangerous_patterns",
+    "tools.protected_paths",
+    "tools.http_allowed_hosts",
+    "notifications.webhooks",
+    "share.paste_url",
+];
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Config {
@@ -27,6 +60,37 @@ pub struct Config {
     pub build: BuildConfig,
     #[serde(default)]
     pub context: ContextConfig,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub skills: SkillsConfig,
+    #[serde(default)]
+    pub permissions: PermissionsConfig,
+    #[serde(default)]
+    pub loop_detection: LoopDetectionConfig,
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    #[serde(default)]
+    pub memory: MemoryConfig,
+    #[serde(default)]
+    pub output: OutputConfig,
+    #[serde(default)]
+    pub share: ShareConfig,
+    #[serde(default)]
+    pub server: ServerAccessConfig,
+    #[serde(default)]
+    pub update: UpdateConfig,
+}
+
+/// Permission defaults applied when a session starts
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct PermissionsConfig {
+    /// Permission preset: "safe", "dev", "full", or "yolo"
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Bash command categories that are always denied (e.g. "git_force_push")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny_categories: Vec<crate::permissions::CommandCategory>,
 }
 
 /// Configuration for subagent models
@@ -51,6 +115,9 @@ pub struct SubagentConfig {
     /// Model for Custom subagent
     #[serde(default)]
     pub custom: Option<SubagentModelConfig>,
+    /// Model for Monitor subagents (summarizes background command output)
+    #[serde(default)]
+    pub monitor: Option<SubagentModelConfig>,
 }
 
 impl Default for SubagentConfig {
@@ -62,6 +129,7 @@ impl Default for SubagentConfig {
             documenter: None,
             explorer: None,
             custom: None,
+            monitor: None,
         }
     }
 }
@@ -136,6 +204,25 @@ pub struct ToolConfig {
     /// Regex patterns for dangerous commands to block
     #[serde(default = "default_dangerous_patterns")]
     pub dangerous_patterns: Vec<String>,
+    /// Glob patterns (relative to the project root) that write_file, edit_file
+    /// and bash may never modify, regardless of mode
+    #[serde(default = "default_protected_paths")]
+    pub protected_paths: Vec<String>,
+    /// Attach the target file's current content when an edit fails, so the
+    /// model can retry once with accurate text
+    #[serde(default = "default_true")]
+    pub enrich_errors: bool,
+    /// Hosts (glob patterns, e.g. `*.staging.example.com`) http_request may call
+    #[serde(default = "default_http_allowed_hosts")]
+    pub http_allowed_hosts: Vec<String>,
+    /// Status endpoint ci_status queries for CI systems other than GitHub
+    /// Actions; `{branch}` is replaced by the current branch
+    #[serde(default)]
+    pub ci_status_url: Option<String>,
+    /// Send only the core tool schemas each turn; the model loads the
+    /// others, including MCP tools, through search_tools
+    #[serde(default = "default_true")]
+    pub lazy_loading: bool,
 }
 
 fn default_bash_timeout() -> u64 {
@@ -159,6 +246,19 @@ fn default_dangerous_patterns() -> Vec<String> {
     ]
 }
 
+fn default_protected_paths() -> Vec<String> {
+    vec![".git/**".to_string(), "**/.env".to_string()]
+}
+
+fn default_http_allowed_hosts() -> Vec<String> {
+    vec![
+        "localhost".to_string(),
+        "*.localhost".to_string(),
+        "127.0.0.1".to_string(),
+        "[::1]".to_string(),
+    ]
+}
+
 impl Default for ToolConfig {
     fn default() -> Self {
         Self {
@@ -166,6 +266,11 @@ impl Default for ToolConfig {
             max_output_bytes: default_max_output(),
             warn_dangerous_commands: true,
             dangerous_patterns: default_dangerous_patterns(),
+            protected_paths: default_protected_paths(),
+            enrich_errors: true,
+            http_allowed_hosts: default_http_allowed_hosts(),
+            ci_status_url: None,
+            lazy_loading: true,
         }
     }
 }
@@ -187,12 +292,23 @@ pub struct BuildConfig {
     /// Maximum build output size in bytes before truncation
     #[serde(default = "default_build_max_output")]
     pub max_output_bytes: usize,
+    /// Measure test coverage (cargo llvm-cov or pytest-cov) before and after
+    /// BUILD-mode changes and warn when coverage of edited files drops
+    #[serde(default)]
+    pub coverage_check: bool,
+    /// Timeout for a coverage run in seconds
+    #[serde(default = "default_coverage_timeout")]
+    pub coverage_timeout_secs: u64,
 }
 
 fn default_build_timeout() -> u64 {
     60
 }
 
+fn default_coverage_timeout() -> u64 {
+    900
+}
+
 fn default_build_max_output() -> usize {
     8192 // 8KB - enough for error messages
 }
@@ -241,6 +357,8 @@ impl Default for BuildConfig {
             commands: default_build_commands(),
             timeout_secs: default_build_timeout(),
             max_output_bytes: default_build_max_output(),
+            coverage_check: false,
+            coverage_timeout_secs: default_coverage_timeout(),
         }
     }
 }
@@ -306,6 +424,13 @@ pub struct CacheConfig {
     /// Time-to-live for cached responses in minutes
     #[serde(default = "default_cache_ttl_minutes")]
     pub ttl_minutes: u64,
+    /// Keep responses in the on-disk content-addressed cache so identical
+    /// runs are replayed instead of re-requested (also enabled by `run --cached`)
+    #[serde(default)]
+    pub persistent: bool,
+    /// Size limit for the on-disk cache in megabytes
+    #[serde(default = "default_cache_max_size_mb")]
+    pub max_size_mb: u64,
 }
 
 fn default_cache_max_entries() -> usize {
@@ -316,6 +441,10 @@ fn default_cache_ttl_minutes() -> u64 {
     30
 }
 
+fn default_cache_max_size_mb() -> u64 {
+    100
+}
+
 impl Default for CacheConfig {
     fn default() -> Self {
         Self {
@@ -324,6 +453,8 @@ impl Default for CacheConfig {
             application_cache: true,
             max_entries: default_cache_max_entries(),
             ttl_minutes: default_cache_ttl_minutes(),
+            persistent: false,
+            max_size_mb: default_cache_max_size_mb(),
         }
     }
 }
@@ -337,8 +468,20 @@ impl CacheConfig {
             application_cache: self.application_cache,
             ttl: std::time::Duration::from_secs(self.ttl_minutes * 60),
             max_entries: self.max_entries,
+            // The persistent cache keys on the whole conversation, so tool
+            // calls replay correctly as long as the tool results match
+            cache_tool_calls: self.persistent,
         }
     }
+
+    /// Open the on-disk response cache
+    pub fn disk_cache(&self) -> Result<crate::cache::DiskCache> {
+        Ok(crate::cache::DiskCache::new(
+            crate::cache::DiskCache::default_dir()?,
+            std::time::Duration::from_secs(self.ttl_minutes * 60),
+            self.max_size_mb * 1024 * 1024,
+        ))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -357,6 +500,17 @@ pub struct LlmConfig {
     pub provider: LlmProvider,
     pub api_key: Option<String>,
     pub model: String,
+    /// Model for PLAN mode, e.g. a cheaper, faster one for exploration
+    /// (defaults to `model`)
+    #[serde(default)]
+    pub plan_model: Option<String>,
+    /// Model for BUILD mode (defaults to `model`)
+    #[serde(default)]
+    pub build_model: Option<String>,
+    /// Model for subagents without their own `[subagents.<kind>]` settings,
+    /// with the same provider (defaults to `model`)
+    #[serde(default)]
+    pub subagent_model: Option<String>,
     pub max_tokens: usize,
     /// Base URL for API (optional, for Ollama or custom endpoints)
     #[serde(default)]
@@ -366,6 +520,290 @@ pub struct LlmConfig {
     /// May violate Anthropic's Terms of Service. Use at your own risk.
     #[serde(default)]
     pub claude_code_oauth_compat: bool,
+    /// Stored account to authenticate with (see `safe-coder accounts`).
+    /// Overrides the account selected with `accounts switch`.
+    #[serde(default)]
+    pub account: Option<String>,
+    /// Request pacing and 429 retries, shared by every client of the provider
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+    /// Connect/read timeouts and retries of failed requests
+    #[serde(default)]
+    pub http: LlmHttpConfig,
+    /// Extended thinking / reasoning model settings
+    #[serde(default)]
+    pub reasoning: ReasoningConfig,
+    /// Sampling parameters sent with every request
+    #[serde(default)]
+    pub params: GenerationParams,
+    /// Record LLM exchanges to a fixture file, or serve them back from one
+    #[serde(default)]
+    pub fixtures: FixtureConfig,
+}
+
+impl LlmConfig {
+    /// Model to use in an agent mode
+    pub fn model_for_mode(&self, mode: AgentMode) -> &str {
+        let model = match mode {
+            AgentMode::Plan => self.plan_model.as_deref(),
+            AgentMode::Build => self.build_model.as_deref(),
+        };
+        model.filter(|m| !m.is_empty()).unwrap_or(&self.model)
+    }
+}
+
+/// Client-side rate limiting for LLM requests (`[llm.rate_limit]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RateLimitConfig {
+    /// Pace requests and retry 429 responses
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Client-side budget; unset paces only on what the provider reports
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+    /// Retries of a rate-limited request before giving up
+    #[serde(default = "default_rate_limit_retries")]
+    pub max_retries: u32,
+    /// First backoff delay, doubled on each retry
+    #[serde(default = "default_initial_backoff_ms")]
+    pub initial_backoff_ms: u64,
+    /// Longest single wait, including waits the provider asks for
+    #[serde(default = "default_max_backoff_secs")]
+    pub max_backoff_secs: u64,
+}
+
+fn default_rate_limit_retries() -> u32 {
+    5
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    1000
+}
+
+fn default_max_backoff_secs() -> u64 {
+    60
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            requests_per_minute: None,
+            max_retries: default_rate_limit_retries(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_secs: default_max_backoff_secs(),
+        }
+    }
+}
+
+/// Extended thinking for Anthropic and reasoning models on OpenAI (`[llm.reasoning]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReasoningConfig {
+    /// Ask the model to think before answering
+    #[serde(default)]
+    pub enabled: bool,
+    /// Tokens the model may spend thinking (Anthropic)
+    #[serde(default = "default_thinking_budget")]
+    pub budget_tokens: usize,
+    /// `low`, `medium` or `high` (OpenAI reasoning models)
+    #[serde(default = "default_reasoning_effort")]
+    pub effort: String,
+}
+
+fn default_thinking_budget() -> usize {
+    8192
+}
+
+fn default_reasoning_effort() -> String {
+    "medium".to_string()
+}
+
+impl Default for ReasoningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            budget_tokens: default_thinking_budget(),
+            effort: default_reasoning_effort(),
+        }
+    }
+}
+
+/// Sampling parameters (`[llm.params]`); unset values use the provider defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct GenerationParams {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f32>,
+    /// Sequences that end the response
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stop: Vec<String>,
+}
+
+impl GenerationParams {
+    /// Names accepted by [`GenerationParams::set`]
+    pub const NAMES: [&'static str; 3] = ["temperature", "top_p", "stop"];
+
+    /// Set a parameter from text, as in `/set temperature 0.2`. `default`
+    /// unsets it; `stop` takes a comma-separated list.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<()> {
+        let value = value.trim();
+        let reset = value.eq_ignore_ascii_case("default");
+        match name.to_lowercase().replace('-', "_").as_str() {
+            "temperature" => {
+                self.temperature = if reset {
+                    None
+                } else {
+                    Some(parse_param(value, 0.0, 2.0)?)
+                };
+            }
+            "top_p" => {
+                self.top_p = if reset {
+                    None
+                } else {
+                    Some(parse_param(value, 0.0, 1.0)?)
+                };
+            }
+            "stop" => {
+                self.stop = if reset {
+                    Vec::new()
+                } else {
+                    value
+                        .split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                };
+            }
+            _ => anyhow::bail!(
+                "Unknown parameter '{}' (expected one of: {})",
+                name,
+                Self::NAMES.join(", ")
+            ),
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for GenerationParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let show = |v: Option<f32>| v.map_or("default".to_string(), |v| v.to_string());
+        write!(
+            f,
+            "temperature: {}\ntop_p: {}\nstop: {}",
+            show(self.temperature),
+            show(self.top_p),
+            if self.stop.is_empty() {
+                "default".to_string()
+            } else {
+                self.stop.join(", ")
+            }
+        )
+    }
+}
+
+fn parse_param(value: &str, min: f32, max: f32) -> Result<f32> {
+    let parsed: f32 = value
+        .parse()
+        .with_context(|| format!("'{}' is not a number", value))?;
+    if !(min..=max).contains(&parsed) {
+        anyhow::bail!("{} is out of range ({} to {})", parsed, min, max);
+    }
+    Ok(parsed)
+}
+
+/// Record/replay of LLM exchanges (`[llm.fixtures]`), for hermetic tests
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct FixtureConfig {
+    #[serde(default)]
+    pub mode: FixtureMode,
+    /// JSONL file the exchanges are written to or read from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FixtureMode {
+    /// Talk to the provider as usual
+    #[default]
+    Off,
+    /// Talk to the provider and append every exchange to the fixture file
+    Record,
+    /// Answer from the fixture file without contacting the provider
+    Replay,
+}
+
+/// HTTP timeouts and retries for LLM requests (`[llm.http]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LlmHttpConfig {
+    #[serde(default = "default_connect_timeout_secs")]
+    pub connect_timeout_secs: u64,
+    /// Longest wait for the next chunk of a response
+    #[serde(default = "default_read_timeout_secs")]
+    pub read_timeout_secs: u64,
+    /// Retries after connection failures, timeouts and 5xx responses
+    #[serde(default = "default_http_retries")]
+    pub max_retries: u32,
+    /// First retry delay, doubled on each retry
+    #[serde(default = "default_retry_backoff_ms")]
+    pub retry_backoff_ms: u64,
+    /// Per-provider overrides, keyed like `[llm.http.providers.anthropic]`
+    #[serde(default)]
+    pub providers: std::collections::HashMap<String, LlmHttpOverride>,
+}
+
+/// Settings that replace the `[llm.http]` defaults for one provider
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct LlmHttpOverride {
+    pub connect_timeout_secs: Option<u64>,
+    pub read_timeout_secs: Option<u64>,
+    pub max_retries: Option<u32>,
+    pub retry_backoff_ms: Option<u64>,
+}
+
+fn default_connect_timeout_secs() -> u64 {
+    10
+}
+
+fn default_read_timeout_secs() -> u64 {
+    300
+}
+
+fn default_http_retries() -> u32 {
+    2
+}
+
+fn default_retry_backoff_ms() -> u64 {
+    500
+}
+
+impl Default for LlmHttpConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_secs: default_connect_timeout_secs(),
+            read_timeout_secs: default_read_timeout_secs(),
+            max_retries: default_http_retries(),
+            retry_backoff_ms: default_retry_backoff_ms(),
+            providers: std::collections::HashMap::new(),
+        }
+    }
+}
+
+impl LlmHttpConfig {
+    /// Settings for a provider key such as "anthropic" or "ollama"
+    pub fn for_provider(&self, provider: &str) -> crate::llm::http::HttpSettings {
+        let o = self.providers.get(provider).cloned().unwrap_or_default();
+        let secs = std::time::Duration::from_secs;
+        crate::llm::http::HttpSettings {
+            connect_timeout: secs(o.connect_timeout_secs.unwrap_or(self.connect_timeout_secs)),
+            read_timeout: secs(o.read_timeout_secs.unwrap_or(self.read_timeout_secs)),
+            max_retries: o.max_retries.unwrap_or(self.max_retries),
+            retry_backoff: std::time::Duration::from_millis(
+                o.retry_backoff_ms.unwrap_or(self.retry_backoff_ms),
+            ),
+        }
+    }
 }
 
 /// Configuration for the CLI orchestrator
@@ -398,11 +836,26 @@ pub struct OrchestratorConfig {
     /// Use git worktrees for task isolation
     #[serde(default = "default_true")]
     pub use_worktrees: bool,
+    /// Show each successful task's diff and ask before merging it
+    #[serde(default = "default_true")]
+    pub review_merges: bool,
+    /// Route tasks to workers by capability tag, checked in order
+    #[serde(default)]
+    pub routing: Vec<RoutingRuleConfig>,
     /// Throttle limits for worker types
     #[serde(default)]
     pub throttle_limits: ThrottleLimitsConfig,
 }
 
+/// Workers allowed to handle tasks with any of the given tags
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RoutingRuleConfig {
+    /// Capability tags, e.g. "frontend", "rust", "docs"
+    pub tags: Vec<String>,
+    /// Worker names, e.g. "gemini" or "claude"
+    pub workers: Vec<String>,
+}
+
 /// Throttle limits configuration for different worker types
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ThrottleLimitsConfig {
@@ -502,12 +955,14 @@ impl Default for OrchestratorConfig {
             worker_strategy: default_worker_strategy(),
             enabled_workers: default_enabled_workers(),
             use_worktrees: true,
+            review_merges: true,
+            routing: Vec::new(),
             throttle_limits: ThrottleLimitsConfig::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum LlmProvider {
     Anthropic,
@@ -523,45 +978,122 @@ pub enum LlmProvider {
 }
 
 impl Config {
-    /// Load configuration with the following priority:
-    /// 1. Local `safecoder.json` in project directory (highest priority)
+    /// Load configuration, merging these layers (later layers win):
+    /// 1. Default values
     /// 2. Global `~/.config/safe-coder/config.toml`
-    /// 3. Environment variables for API keys
-    /// 4. Default values
+    /// 3. `.safe-coder/config.toml` in the project's parent directories up to
+    ///    the repository root, outermost first
+    /// 4. `.safe-coder/config.toml` in the project directory
+    /// 5. Local `safecoder.json` in the project directory
+    /// 6. Environment variables for API keys not set by any layer
+    ///
+    /// An organization policy pulled with `safe-coder policy pull` is then
+    /// enforced over the result (see [`crate::policy`]).
     pub fn load() -> Result<Self> {
         Self::load_for_project(&std::env::current_dir().unwrap_or_default())
     }
 
     /// Load configuration for a specific project directory
-    pub fn load_for_project(project_path: &std::path::Path) -> Result<Self> {
-        // Try local safecoder.json first
+    pub fn load_for_project(project_path: &Path) -> Result<Self> {
+        Ok(Self::load_with_origins(project_path)?.0)
+    }
+
+    /// Load configuration for a project, recording which layer set each value
+    pub fn load_with_origins(project_path: &Path) -> Result<(Self, ConfigOrigins)> {
+        let mut merged =
+            toml::Value::try_from(Self::default()).context("Failed to build default config")?;
+        // The default API key is detected for the default provider; leave it to
+        // the env overrides so it follows whichever provider the layers pick
+        if let Some(llm) = merged.get_mut("llm").and_then(|v| v.as_table_mut()) {
+            llm.remove("api_key");
+        }
+
+        let mut origins = ConfigOrigins::default();
+        origins.record(&merged, "default");
+
+        for (source, layer) in Self::config_layers(project_path)? {
+            tracing::info!("Loading config from {}", source.display());
+            let source = source.display().to_string();
+            origins.record(&layer, &source);
+            merge_toml(&mut merged, layer);
+        }
+
+        let mut config: Config = merged.try_into().context("Invalid configuration")?;
+        if config.llm.api_key.is_none() {
+            config.apply_env_overrides();
+            if config.llm.api_key.is_some() {
+                origins.set("llm.api_key", "environment");
+            }
+        }
+
+        // The organization policy outranks every layer
+        if let Some(installed) = crate::policy::InstalledPolicy::load()? {
+            let origin = installed.origin();
+            for path in installed.policy.enforce(&mut config) {
+                origins.set(&path, &origin);
+            }
+        }
+        Ok((config, origins))
+    }
+
+    /// Config files that apply to a project, lowest priority first
+    fn config_layers(project_path: &Path) -> Result<Vec<(PathBuf, toml::Value)>> {
+        let mut layers = Vec::new();
+
+        let global = Self::config_path()?;
+        if global.exists() {
+            layers.push((global.clone(), read_layer(&global, true)?));
+        }
+
+        let mut project_files: Vec<PathBuf> = project_config_dirs(project_path)
+            .into_iter()
+            .map(|dir| dir.join(".safe-coder").join("config.toml"))
+            .filter(|path| path.exists() && *path != global)
+            .collect();
+        project_files.reverse();
+        for path in project_files {
+            let value = read_layer(&path, false)?;
+            layers.push((path, value));
+        }
+
         let local_json = project_path.join("safecoder.json");
         if local_json.exists() {
-            tracing::info!("Loading config from local safecoder.json");
             let content = std::fs::read_to_string(&local_json)
                 .context("Failed to read safecoder.json")?;
-            let mut config: Config = serde_json::from_str(&content)
+            let json: serde_json::Value = serde_json::from_str(&content)
                 .context("Failed to parse safecoder.json")?;
-            // Override with env vars if API key not set
-            config.apply_env_overrides();
-            return Ok(config);
+            if let Some(mut value) = json_to_toml(json) {
+                strip_global_only_keys(&mut value, &local_json);
+                resolve_references(&mut value, None)
+                    .context("Failed to resolve references in safecoder.json")?;
+                layers.push((local_json, value));
+            }
         }
 
-        // Try global config.toml
+        Ok(layers)
+    }
+
+    /// Load only the global config file, for commands that edit it
+    pub fn load_global() -> Result<Self> {
         let config_path = Self::config_path()?;
-        if config_path.exists() {
-            tracing::info!("Loading config from global config.toml");
-            let content = std::fs::read_to_string(&config_path)
-                .context("Failed to read config file")?;
-            let mut config: Config = toml::from_str(&content)
-                .context("Failed to parse config file")?;
-            config.apply_env_overrides();
-            return Ok(config);
+        if !config_path.exists() {
+            return Ok(Self::default());
         }
+        let mut config: Config = read_layer(&config_path, true)?
+            .try_into()
+            .context("Failed to parse config file")?;
+        config.apply_env_overrides();
+        Ok(config)
+    }
 
-        // Fall back to defaults (with env var detection)
-        tracing::info!("No config file found, using defaults with env vars");
-        Ok(Self::default())
+    /// Load the global config file with the organization policy enforced,
+    /// ignoring project layers, for settings a repository mustn't control
+    pub fn load_global_with_policy() -> Result<Self> {
+        let mut config = Self::load_global()?;
+        if let Some(installed) = crate::policy::InstalledPolicy::load()? {
+            installed.policy.enforce(&mut config);
+        }
+        Ok(config)
     }
 
     /// Apply environment variable overrides for API keys
@@ -586,7 +1118,17 @@ impl Config {
             std::fs::create_dir_all(parent)?;
         }
 
-        let content = toml::to_string_pretty(self)?;
+        // Keep `${...}` references for values that haven't changed, so saving
+        // never writes a secret out literally
+        let mut value = toml::Value::try_from(self)?;
+        if let Ok(content) = std::fs::read_to_string(&config_path) {
+            if let Ok(raw) = toml::from_str::<toml::Value>(&content) {
+                let base_dir = config_path.parent().unwrap_or(Path::new("."));
+                restore_references(&mut value, &raw, base_dir);
+            }
+        }
+
+        let content = toml::to_string_pretty(&value)?;
         std::fs::write(&config_path, content)?;
 
         Ok(())
@@ -606,25 +1148,79 @@ impl Config {
     }
 
     pub fn token_path(provider: &LlmProvider) -> Result<PathBuf> {
+        Self::account_token_path(provider, None)
+    }
+
+    /// Name used for a provider in token paths and `accounts.json`
+    pub fn provider_key(provider: &LlmProvider) -> Result<&'static str> {
+        match provider {
+            LlmProvider::Anthropic => Ok("anthropic"),
+            LlmProvider::GitHubCopilot => Ok("github_copilot"),
+            _ => Err(anyhow::anyhow!(
+                "Provider does not support device flow auth"
+            )),
+        }
+    }
+
+    /// Token location for a named account. The default account keeps the
+    /// original `<provider>_token` name so existing logins carry over.
+    pub fn account_token_path(provider: &LlmProvider, account: Option<&str>) -> Result<PathBuf> {
         let config_dir = dirs::config_dir().context("Could not determine config directory")?;
-        let token_file = match provider {
-            LlmProvider::Anthropic => "anthropic_token.json",
-            LlmProvider::GitHubCopilot => "github_copilot_token.json",
-            _ => {
-                return Err(anyhow::anyhow!(
-                    "Provider does not support device flow auth"
-                ))
+        let key = Self::provider_key(provider)?;
+        let token_file = match account {
+            None | Some(crate::auth::accounts::DEFAULT_ACCOUNT) => format!("{}_token.json", key),
+            Some(name) => {
+                let name: String = name
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+                    .collect();
+                format!("{}_{}_token.json", key, name)
             }
         };
         Ok(config_dir.join("safe-coder").join(token_file))
     }
 
+    /// The account to use for a provider: `llm.account` from the project
+    /// config when it applies, otherwise the one picked with `accounts switch`
+    pub fn active_account(&self, provider: &LlmProvider) -> Option<String> {
+        if *provider == self.llm.provider {
+            if let Some(account) = &self.llm.account {
+                return Some(account.clone());
+            }
+        }
+        let key = Self::provider_key(provider).ok()?;
+        crate::auth::accounts::Accounts::load()
+            .ok()?
+            .active(key)
+            .map(str::to_string)
+    }
+
+    /// Token location for the active account of a provider
+    pub fn active_token_path(&self, provider: &LlmProvider) -> Result<PathBuf> {
+        Self::account_token_path(provider, self.active_account(provider).as_deref())
+    }
+
+    /// Store a token for a named account (the default account when `None`)
+    /// and record it in `accounts.json`
+    pub fn save_account_token(
+        provider: &LlmProvider,
+        account: Option<&str>,
+        token: &crate::auth::StoredToken,
+    ) -> Result<()> {
+        use crate::auth::accounts::{Accounts, DEFAULT_ACCOUNT};
+
+        token.save(&Self::account_token_path(provider, account)?)?;
+        let mut accounts = Accounts::load()?;
+        accounts.add(Self::provider_key(provider)?, account.unwrap_or(DEFAULT_ACCOUNT));
+        accounts.save()
+    }
+
     /// Get the stored token for the current provider (if any)
     pub fn get_stored_token(&self) -> Option<crate::auth::StoredToken> {
-        match Self::token_path(&self.llm.provider) {
+        match self.active_token_path(&self.llm.provider) {
             Ok(token_path) => {
                 tracing::debug!("Looking for token at: {:?}", token_path);
-                if token_path.exists() {
+                if crate::auth::StoredToken::exists(&token_path) {
                     use crate::auth::StoredToken;
                     match StoredToken::load(&token_path) {
                         Ok(stored_token) => {
@@ -681,6 +1277,7 @@ impl Config {
             "documenter" => self.subagents.documenter.as_ref(),
             "explorer" => self.subagents.explorer.as_ref(),
             "custom" => self.subagents.custom.as_ref(),
+            "monitor" => self.subagents.monitor.as_ref(),
             _ => None,
         }
     }
@@ -720,9 +1317,18 @@ impl Default for Config {
                 provider,
                 api_key,
                 model,
+                plan_model: None,
+                build_model: None,
+                subagent_model: None,
                 max_tokens: 8192,
                 base_url: None,
                 claude_code_oauth_compat: false,
+                account: None,
+                rate_limit: RateLimitConfig::default(),
+                http: LlmHttpConfig::default(),
+                reasoning: ReasoningConfig::default(),
+                params: GenerationParams::default(),
+                fixtures: FixtureConfig::default(),
             },
             git: GitConfig::default(),
             orchestrator: OrchestratorConfig::default(),
@@ -734,6 +1340,16 @@ impl Default for Config {
             subagents: SubagentConfig::default(),
             build: BuildConfig::default(),
             context: ContextConfig::default(),
+            hooks: HooksConfig::default(),
+            skills: SkillsConfig::default(),
+            permissions: PermissionsConfig::default(),
+            loop_detection: LoopDetectionConfig::default(),
+            notifications: NotificationsConfig::default(),
+            memory: MemoryConfig::default(),
+            output: OutputConfig::default(),
+            share: ShareConfig::default(),
+            server: ServerAccessConfig::default(),
+            update: UpdateConfig::default(),
         }
     }
 }
@@ -817,9 +1433,10 @@ impl Default for CheckpointConfig {
 /// Based on Codex CLI's approach with token-based thresholds
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ContextConfig {
-    /// Maximum context window size in tokens
-    #[serde(default = "default_context_max_tokens")]
-    pub max_tokens: usize,
+    /// Maximum context size in tokens; defaults to the model's context
+    /// window from the model registry, and never exceeds it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<usize>,
     /// Trigger compaction at this percentage of max_tokens (0-100)
     #[serde(default = "default_compact_threshold_pct")]
     pub compact_threshold_pct: usize,
@@ -841,10 +1458,6 @@ pub struct ContextConfig {
     pub chars_per_token: usize,
 }
 
-fn default_context_max_tokens() -> usize {
-    128_000 // Claude's context window
-}
-
 fn default_compact_threshold_pct() -> usize {
     60 // Compact at 60% to leave room for responses
 }
@@ -872,7 +1485,7 @@ fn default_chars_per_token() -> usize {
 impl Default for ContextConfig {
     fn default() -> Self {
         Self {
-            max_tokens: default_context_max_tokens(),
+            max_tokens: None,
             compact_threshold_pct: default_compact_threshold_pct(),
             preserve_recent_tokens: default_preserve_recent_tokens(),
             min_preserve_messages: default_min_preserve_messages(),
@@ -884,10 +1497,16 @@ impl Default for ContextConfig {
 }
 
 impl ContextConfig {
+    /// Context size to work within for a model with `context_window` tokens
+    pub fn max_tokens_for(&self, context_window: usize) -> usize {
+        self.max_tokens.map_or(context_window, |max| max.min(context_window))
+    }
+
     /// Convert to the context module's ContextConfig type
     pub fn to_context_config(&self) -> crate::context::ContextConfig {
+        let defaults = crate::context::ContextConfig::default();
         crate::context::ContextConfig {
-            max_tokens: self.max_tokens,
+            max_tokens: self.max_tokens.unwrap_or(defaults.max_tokens),
             compact_threshold_pct: self.compact_threshold_pct,
             preserve_recent_tokens: self.preserve_recent_tokens,
             min_preserve_messages: self.min_preserve_messages,
@@ -897,3 +1516,965 @@ impl ContextConfig {
         }
     }
 }
+
+/// Desktop and webhook notifications (`[notifications]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NotificationsConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Stay quiet while the terminal has focus (when the terminal reports it)
+    #[serde(default = "default_true")]
+    pub only_when_unfocused: bool,
+    /// Shell commands and AI tasks that ran at least this long notify when done
+    #[serde(default = "default_long_task_secs")]
+    pub long_task_secs: u64,
+    #[serde(default = "default_true")]
+    pub long_task_finished: bool,
+    #[serde(default = "default_true")]
+    pub orchestration_complete: bool,
+    /// Tool approvals, plan approvals and loop prompts
+    #[serde(default = "default_true")]
+    pub approval_needed: bool,
+    /// Base URL of the `safe-coder serve` instance, used for links in webhook messages
+    #[serde(default)]
+    pub server_url: Option<String>,
+    /// Slack/Discord incoming webhooks (`[[notifications.webhooks]]`)
+    #[serde(default)]
+    pub webhooks: Vec<crate::notifications::webhook::WebhookConfig>,
+}
+
+fn default_long_task_secs() -> u64 {
+    30
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            only_when_unfocused: true,
+            long_task_secs: default_long_task_secs(),
+            long_task_finished: true,
+            orchestration_complete: true,
+            approval_needed: true,
+            server_url: None,
+            webhooks: Vec::new(),
+        }
+    }
+}
+
+/// Project memory (`[memory]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MemoryConfig {
+    /// Ask the model whether messages that look like corrections teach a
+    /// lasting project fact, and add it to SAFE_CODER.md
+    #[serde(default)]
+    pub auto_learn: bool,
+}
+
+/// Who may use the `serve` HTTP API (`[server]`)
+///
+/// With no tokens the API is open, as it is when serving the local TUI or
+/// desktop app. Once any token is configured, every request needs one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ServerAccessConfig {
+    #[serde(default)]
+    pub tokens: Vec<ApiToken>,
+}
+
+/// A bearer token for the server API and what it may do
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApiToken {
+    /// Who the token belongs to, shown in logs
+    pub name: String,
+    /// The token itself; use `${env:...}` or `${file:...}` rather than a literal
+    pub token: String,
+    #[serde(default)]
+    pub scope: TokenScope,
+}
+
+/// What an API token may do; each scope includes the ones before it
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenScope {
+    /// Read sessions, messages and event streams
+    #[default]
+    Viewer,
+    /// Also create sessions, send messages and answer tool approvals
+    Operator,
+    /// Also delete sessions and open terminals
+    Admin,
+}
+
+/// Where `/chat share` uploads encrypted sessions (`[share]`)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ShareConfig {
+    /// "gist" (a secret GitHub gist) or "paste"
+    #[serde(default)]
+    pub service: ShareService,
+    /// Paste service for `service = "paste"`: the body is POSTed as plain
+    /// text and the response is the paste's URL (e.g. "https://paste.rs")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub paste_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareService {
+    #[default]
+    Gist,
+    Paste,
+}
+
+/// Where `safe-coder update` gets releases from (`[update]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UpdateConfig {
+    /// Set to false where safe-coder is installed and updated by other means
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// GitHub repository ("owner/name") whose releases are installed
+    #[serde(default = "default_update_repository")]
+    pub repository: String,
+    /// Base64 Ed25519 key release binaries must be signed with, overriding
+    /// the one built into release binaries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+}
+
+fn default_update_repository() -> String {
+    "siddharth-ghatti/Safe-Coder".to_string()
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            repository: default_update_repository(),
+            public_key: None,
+        }
+    }
+}
+
+/// Post-processing of assistant output before display (`[output]`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct OutputConfig {
+    /// File paths in responses that don't exist in the project: "flag", "strip" or "off"
+    #[serde(default)]
+    pub missing_paths: crate::output_filters::MissingPaths,
+    /// Turn `path:line` references to project files into links
+    #[serde(default = "default_true")]
+    pub linkify: bool,
+    /// Code blocks longer than this are collapsed in the TUI until expanded (0 = never)
+    #[serde(default = "default_collapse_code_lines")]
+    pub collapse_code_lines: usize,
+}
+
+fn default_collapse_code_lines() -> usize {
+    40
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            missing_paths: Default::default(),
+            linkify: true,
+            collapse_code_lines: default_collapse_code_lines(),
+        }
+    }
+}
+
+/// Doom loop detection thresholds. Each threshold is the number of earlier
+/// matching calls (exact or near-duplicate) in the recent history.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoopDetectionConfig {
+    /// Repeats before a warning is shown
+    #[serde(default = "default_loop_warn_threshold")]
+    pub warn_threshold: usize,
+    /// Repeats before the user is asked whether to continue
+    #[serde(default = "default_loop_ask_threshold")]
+    pub ask_threshold: usize,
+    /// Repeats before guidance is appended to the tool result for the model
+    #[serde(default = "default_loop_guide_threshold")]
+    pub guide_threshold: usize,
+    /// Repeats before the call is blocked
+    #[serde(default = "default_loop_block_threshold")]
+    pub block_threshold: usize,
+    /// Treat calls that differ only in offsets, limits or path spelling as repeats
+    #[serde(default = "default_true")]
+    pub detect_similar: bool,
+    /// A-B cycles before alternating between two calls counts as a loop
+    #[serde(default = "default_loop_alternation_threshold")]
+    pub alternation_threshold: usize,
+    /// Per-tool overrides, e.g. `[loop_detection.tools.bash] block = 2`
+    #[serde(default)]
+    pub tools: std::collections::HashMap<String, crate::loop_detector::ToolThresholds>,
+    /// Seconds to wait for the user to answer a loop prompt before stopping
+    #[serde(default = "default_loop_prompt_timeout")]
+    pub prompt_timeout_secs: u64,
+    /// Keep going instead of stopping when a loop prompt goes unanswered
+    #[serde(default)]
+    pub continue_on_timeout: bool,
+}
+
+fn default_loop_warn_threshold() -> usize {
+    1
+}
+
+fn default_loop_ask_threshold() -> usize {
+    3
+}
+
+fn default_loop_guide_threshold() -> usize {
+    4
+}
+
+fn default_loop_block_threshold() -> usize {
+    5
+}
+
+fn default_loop_alternation_threshold() -> usize {
+    3
+}
+
+fn default_loop_prompt_timeout() -> u64 {
+    300
+}
+
+impl Default for LoopDetectionConfig {
+    fn default() -> Self {
+        Self {
+            warn_threshold: default_loop_warn_threshold(),
+            ask_threshold: default_loop_ask_threshold(),
+            guide_threshold: default_loop_guide_threshold(),
+            block_threshold: default_loop_block_threshold(),
+            detect_similar: true,
+            alternation_threshold: default_loop_alternation_threshold(),
+            tools: std::collections::HashMap::new(),
+            prompt_timeout_secs: default_loop_prompt_timeout(),
+            continue_on_timeout: false,
+        }
+    }
+}
+
+impl LoopDetectionConfig {
+    /// Convert to the loop_detector module's config type
+    pub fn to_detector_config(&self) -> crate::loop_detector::LoopDetectorConfig {
+        crate::loop_detector::LoopDetectorConfig {
+            warn_threshold: self.warn_threshold,
+            ask_threshold: self.ask_threshold,
+            guide_threshold: self.guide_threshold,
+            block_threshold: self.block_threshold,
+            detect_similar: self.detect_similar,
+            alternation_threshold: self.alternation_threshold,
+            tool_thresholds: self.tools.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Where each configuration value came from, keyed by dotted path (e.g. `llm.model`).
+///
+/// Entries of named lists such as `mcp.servers` are keyed by name:
+/// `mcp.servers[github].url`.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigOrigins {
+    origins: BTreeMap<String, String>,
+}
+
+impl ConfigOrigins {
+    /// The layer that set a value, if any layer did
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.origins.get(path).map(String::as_str)
+    }
+
+    fn set(&mut self, path: &str, origin: &str) {
+        self.origins.insert(path.to_string(), origin.to_string());
+    }
+
+    /// Attribute every value in a layer to `origin`
+    fn record(&mut self, layer: &toml::Value, origin: &str) {
+        for_each_leaf(layer, String::new(), &mut |path, _| self.set(&path, origin));
+    }
+
+    /// Render the config as `path = value  # origin` lines, masking secrets
+    pub fn explain(&self, config: &Config) -> Result<String> {
+        let value = toml::Value::try_from(config)?;
+        let mut output = String::new();
+        for_each_leaf(&value, String::new(), &mut |path, leaf| {
+            let key = path.rsplit('.').next().unwrap_or(&path);
+            let shown = if is_secret_key(key) {
+                "\"********\"".to_string()
+            } else {
+                leaf.to_string()
+            };
+            output.push_str(&format!(
+                "{} = {}  # {}\n",
+                path,
+                shown,
+                self.get(&path).unwrap_or("default")
+            ));
+        });
+        Ok(output)
+    }
+}
+
+fn is_secret_key(key: &str) -> bool {
+    key == "api_key"
+        || key == "token"
+        || key.ends_with("_token")
+        || key.ends_with("secret")
+        || key.eq_ignore_ascii_case("authorization")
+}
+
+/// Name of an array entry that is a table with a `name` key
+fn entry_name(value: &toml::Value) -> Option<&str> {
+    value.get("name").and_then(|v| v.as_str())
+}
+
+/// Whether an array holds named tables, like `[[mcp.servers]]`
+fn is_named_list(items: &[toml::Value]) -> bool {
+    !items.is_empty() && items.iter().all(|item| entry_name(item).is_some())
+}
+
+/// Visit each leaf value with its dotted path
+fn for_each_leaf(value: &toml::Value, path: String, visit: &mut dyn FnMut(String, &toml::Value)) {
+    let join = |key: &str| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", path, key)
+        }
+    };
+    match value {
+        toml::Value::Table(table) => {
+            for (key, child) in table {
+                for_each_leaf(child, join(key), visit);
+            }
+        }
+        toml::Value::Array(items) if is_named_list(items) => {
+            for item in items {
+                let name = entry_name(item).unwrap_or_default();
+                for_each_leaf(item, format!("{}[{}]", path, name), visit);
+            }
+        }
+        leaf => visit(path, leaf),
+    }
+}
+
+/// Directories whose `.safe-coder/config.toml` applies to a project, innermost
+/// first: the project directory and its parents up to the git repository
+/// root. Outside a repository only the project directory itself counts.
+fn project_config_dirs(project_path: &Path) -> Vec<&Path> {
+    let mut dirs = Vec::new();
+    for dir in project_path.ancestors() {
+        dirs.push(dir);
+        if dir.join(".git").exists() {
+            return dirs;
+        }
+    }
+    dirs.truncate(1);
+    dirs
+}
+
+/// Drop the `GLOBAL_ONLY_KEYS` from a project layer
+fn strip_global_only_keys(layer: &mut toml::Value, source: &Path) {
+    fn strip(value: &mut toml::Value, segments: &[&str], path: &str, source: &Path) {
+        let Some(table) = value.as_table_mut() else {
+            return;
+        };
+        let join = |key: &str| {
+            if path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{}.{}", path, key)
+            }
+        };
+        match segments {
+            [] => {}
+            [leaf] => {
+                if table.remove(*leaf).is_some() {
+                    tracing::warn!(
+                        "Ignoring `{}` in {}: only the global config or the organization policy may set it",
+                        join(leaf),
+                        source.display()
+                    );
+                }
+            }
+            ["*", rest @ ..] => {
+                for (key, child) in table.iter_mut() {
+                    strip(child, rest, &join(key), source);
+                }
+            }
+            [key, rest @ ..] => {
+                if let Some(child) = table.get_mut(*key) {
+                    strip(child, rest, &join(key), source);
+                }
+            }
+        }
+    }
+
+    for key in GLOBAL_ONLY_KEYS {
+        let segments: Vec<&str> = key.split('.').collect();
+        strip(layer, &segments, "", source);
+    }
+}
+
+/// Merge `overlay` into `base`. Tables merge key by key, named lists (such as
+/// MCP servers) merge entry by entry, and anything else is replaced.
+fn merge_toml(base: &mut toml::Value, overlay: toml::Value) {
+    match overlay {
+        toml::Value::Table(overlay) if base.is_table() => {
+            let base = base.as_table_mut().expect("checked is_table");
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        toml::Value::Array(overlay)
+            if is_named_list(&overlay)
+                && base
+                    .as_array()
+                    .is_some_and(|items| items.is_empty() || is_named_list(items)) =>
+        {
+            let base = base.as_array_mut().expect("checked is_array");
+            for item in overlay {
+                let name = entry_name(&item).map(str::to_string);
+                match base.iter_mut().find(|b| entry_name(b) == name.as_deref()) {
+                    Some(existing) => merge_toml(existing, item),
+                    None => base.push(item),
+                }
+            }
+        }
+        overlay => *base = overlay,
+    }
+}
+
+/// Read a config file and resolve its `${env:...}` / `${file:...}` references.
+/// Only the global config is `trusted` to use them; a project file could
+/// otherwise copy secrets into settings that leave the machine. An untrusted
+/// file has its `GLOBAL_ONLY_KEYS` dropped first, so references in them
+/// don't fail the load.
+fn read_layer(path: &Path, trusted: bool) -> Result<toml::Value> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut value: toml::Value =
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+    if !trusted {
+        strip_global_only_keys(&mut value, path);
+    }
+    let base_dir = trusted.then(|| path.parent().unwrap_or(Path::new(".")));
+    resolve_references(&mut value, base_dir)
+        .with_context(|| format!("Failed to resolve references in {}", path.display()))?;
+    Ok(value)
+}
+
+/// Replace secret references in every string value of a config layer.
+///
+/// `${env:VAR}` reads an environment variable and `${file:path}` reads a file
+/// (relative to `base_dir`, `~` allowed), trimming the trailing newline.
+/// `$${` escapes a literal `${`. Without a `base_dir` (project layers) any
+/// reference is an error.
+fn resolve_references(value: &mut toml::Value, base_dir: Option<&Path>) -> Result<()> {
+    fn walk(value: &mut toml::Value, path: &str, base_dir: Option<&Path>) -> Result<()> {
+        match value {
+            toml::Value::String(s) if s.contains("${") => {
+                *s = interpolate(s, base_dir).with_context(|| format!("in `{}`", path))?;
+            }
+            toml::Value::Table(table) => {
+                for (key, child) in table.iter_mut() {
+                    let child_path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    walk(child, &child_path, base_dir)?;
+                }
+            }
+            toml::Value::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    walk(item, &format!("{}[{}]", path, i), base_dir)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+    walk(value, "", base_dir)
+}
+
+/// Put back references from `raw` wherever `value` still holds what they resolve to
+fn restore_references(value: &mut toml::Value, raw: &toml::Value, base_dir: &Path) {
+    match (value, raw) {
+        (toml::Value::Table(value), toml::Value::Table(raw)) => {
+            for (key, raw_child) in raw {
+                if let Some(child) = value.get_mut(key) {
+                    restore_references(child, raw_child, base_dir);
+                }
+            }
+        }
+        (toml::Value::Array(items), toml::Value::Array(raw_items)) => {
+            for (item, raw_item) in items.iter_mut().zip(raw_items) {
+                restore_references(item, raw_item, base_dir);
+            }
+        }
+        (toml::Value::String(s), toml::Value::String(raw))
+            if raw.contains("${")
+                && interpolate(raw, Some(base_dir)).is_ok_and(|resolved| resolved == *s) =>
+        {
+            *s = raw.clone();
+        }
+        _ => {}
+    }
+}
+
+/// Expand `${env:VAR}` and `${file:path}` references in a string, or refuse
+/// them when there is no `base_dir`
+fn interpolate(input: &str, base_dir: Option<&Path>) -> Result<String> {
+    let mut output = String::new();
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        // `$${` is an escaped literal
+        if rest[..start].ends_with('$') {
+            output.push_str(&rest[..start - 1]);
+            output.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        output.push_str(&rest[..start]);
+
+        let end = rest[start..]
+            .find('}')
+            .with_context(|| format!("Unterminated reference in '{}'", input))?;
+        let reference = &rest[start + 2..start + end];
+        let (kind, target) = reference
+            .split_once(':')
+            .with_context(|| format!("Reference '${{{}}}' needs a kind, e.g. ${{env:VAR}}", reference))?;
+        let target = target.trim();
+        let Some(base_dir) = base_dir else {
+            anyhow::bail!(
+                "'${{{}}}' isn't allowed here: secret references are only resolved in the \
+                 global config (~/.config/safe-coder/config.toml), not in project files",
+                reference
+            );
+        };
+
+        match kind.trim() {
+            "env" => {
+                let value = std::env::var(target)
+                    .map_err(|_| anyhow::anyhow!("Environment variable {} is not set", target))?;
+                output.push_str(&value);
+            }
+            "file" => {
+                let path = match target.strip_prefix("~/") {
+                    Some(relative) => dirs::home_dir()
+                        .context("Could not determine home directory")?
+                        .join(relative),
+                    None => base_dir.join(target),
+                };
+                let contents = std::fs::read_to_string(&path)
+                    .with_context(|| format!("Failed to read secret file {}", path.display()))?;
+                output.push_str(contents.trim_end_matches(['\n', '\r']));
+            }
+            other => anyhow::bail!(
+                "Unknown reference kind '{}' in '${{{}}}' (expected env or file)",
+                other,
+                reference
+            ),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Convert JSON to TOML, dropping nulls (TOML has no null; an absent key means unset)
+fn json_to_toml(value: serde_json::Value) -> Option<toml::Value> {
+    use serde_json::Value as Json;
+    Some(match value {
+        Json::Null => return None,
+        Json::Bool(b) => toml::Value::Boolean(b),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => toml::Value::Integer(i),
+            None => toml::Value::Float(n.as_f64()?),
+        },
+        Json::String(s) => toml::Value::String(s),
+        Json::Array(items) => toml::Value::Array(items.into_iter().filter_map(json_to_toml).collect()),
+        Json::Object(map) => toml::Value::Table(
+            map.into_iter()
+                .filter_map(|(k, v)| json_to_toml(v).map(|v| (k, v)))
+                .collect(),
+        ),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(toml: &str) -> toml::Value {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_merge_overrides_nested_values() {
+        let mut base = value(
+            r#"
+[llm]
+provider = "anthropic"
+model = "claude-sonnet-4-20250514"
+max_tokens = 8192
+
+[tools]
+bash_timeout_secs = 120
+"#,
+        );
+        merge_toml(
+            &mut base,
+            value(
+                r#"
+[llm]
+model = "claude-opus-4"
+
+[tools]
+max_output_bytes = 2048
+"#,
+            ),
+        );
+
+        assert_eq!(base["llm"]["provider"].as_str(), Some("anthropic"));
+        assert_eq!(base["llm"]["model"].as_str(), Some("claude-opus-4"));
+        assert_eq!(base["tools"]["bash_timeout_secs"].as_integer(), Some(120));
+        assert_eq!(base["tools"]["max_output_bytes"].as_integer(), Some(2048));
+    }
+
+    #[test]
+    fn test_merge_named_lists_by_name() {
+        let mut base = value(
+            r#"
+[[mcp.servers]]
+name = "github"
+command = "github-mcp"
+mode = "plan"
+
+[[mcp.servers]]
+name = "fs"
+command = "mcp-fs"
+"#,
+        );
+        merge_toml(
+            &mut base,
+            value(
+                r#"
+[[mcp.servers]]
+name = "github"
+mode = "both"
+
+[[mcp.servers]]
+name = "db"
+command = "mcp-db"
+"#,
+            ),
+        );
+
+        let servers = base["mcp"]["servers"].as_array().unwrap();
+        let names: Vec<_> = servers.iter().filter_map(entry_name).collect();
+        assert_eq!(names, vec!["github", "fs", "db"]);
+        assert_eq!(servers[0]["command"].as_str(), Some("github-mcp"));
+        assert_eq!(servers[0]["mode"].as_str(), Some("both"));
+
+        // Plain lists are replaced, not appended to
+        let mut base = value(r#"protected_paths = [".env", ".git"]"#);
+        merge_toml(&mut base, value(r#"protected_paths = ["secrets/"]"#));
+        assert_eq!(base["protected_paths"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_origins_track_last_layer() {
+        let mut origins = ConfigOrigins::default();
+        origins.record(&value("[llm]\nmodel = \"a\"\nmax_tokens = 1"), "default");
+        origins.record(
+            &value("[llm]\nmodel = \"b\"\n[[mcp.servers]]\nname = \"github\"\nurl = \"https://x\""),
+            "/work/app/.safe-coder/config.toml",
+        );
+
+        assert_eq!(origins.get("llm.max_tokens"), Some("default"));
+        assert_eq!(
+            origins.get("llm.model"),
+            Some("/work/app/.safe-coder/config.toml")
+        );
+        assert_eq!(
+            origins.get("mcp.servers[github].url"),
+            Some("/work/app/.safe-coder/config.toml")
+        );
+    }
+
+    #[test]
+    fn test_explain_masks_secrets() {
+        let mut config = Config::default();
+        config.llm.api_key = Some("sk-secret".to_string());
+        let mut origins = ConfigOrigins::default();
+        origins.set("llm.api_key", "environment");
+
+        let output = origins.explain(&config).unwrap();
+        assert!(output.contains("llm.api_key = \"********\"  # environment"));
+        assert!(!output.contains("sk-secret"));
+        assert!(output.contains("llm.max_tokens = 8192  # default"));
+    }
+
+    #[test]
+    fn test_interpolate_env_and_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("token"), "s3cret\n").unwrap();
+        std::env::set_var("SAFE_CODER_TEST_INTERPOLATE", "from-env");
+
+        assert_eq!(
+            interpolate(
+                "Bearer ${env:SAFE_CODER_TEST_INTERPOLATE}",
+                Some(dir.path())
+            )
+            .unwrap(),
+            "Bearer from-env"
+        );
+        assert_eq!(
+            interpolate("${file:token}", Some(dir.path())).unwrap(),
+            "s3cret"
+        );
+        assert_eq!(
+            interpolate("cost: $${env:HOME} stays", Some(dir.path())).unwrap(),
+            "cost: ${env:HOME} stays"
+        );
+        assert_eq!(interpolate("plain", Some(dir.path())).unwrap(), "plain");
+    }
+
+    #[test]
+    fn test_interpolate_errors_name_the_problem() {
+        let dir = tempfile::tempdir().unwrap();
+        let err = interpolate("${env:SAFE_CODER_TEST_UNSET_VAR}", Some(dir.path())).unwrap_err();
+        assert!(err.to_string().contains("SAFE_CODER_TEST_UNSET_VAR is not set"));
+
+        let err = interpolate("${vault:x}", Some(dir.path())).unwrap_err();
+        assert!(err.to_string().contains("Unknown reference kind 'vault'"));
+
+        assert!(interpolate("${env:X", Some(dir.path())).is_err());
+        assert!(interpolate("${file:missing}", Some(dir.path())).is_err());
+
+        let mut value = value("[mcp]\n[[mcp.servers]]\nname = \"x\"\nenv = { TOKEN = \"${env:SAFE_CODER_TEST_UNSET_VAR}\" }");
+        let err = resolve_references(&mut value, Some(dir.path())).unwrap_err();
+        assert!(format!("{:#}", err).contains("mcp.servers[0].env.TOKEN"));
+    }
+
+    #[test]
+    fn test_project_layers_refuse_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = dir.path().join("config.toml");
+        std::fs::write(
+            &config,
+            "[llm]\nmodel = \"costs $${env:HOME}\"\nplan_model = \"${env:HOME}\"\n",
+        )
+        .unwrap();
+
+        let err = read_layer(&config, false).unwrap_err();
+        let message = format!("{:#}", err);
+        assert!(message.contains("llm.plan_model"), "{}", message);
+        assert!(
+            message.contains("only resolved in the global config"),
+            "{}",
+            message
+        );
+        assert!(read_layer(&config, true).is_ok());
+
+        // Escapes are still unescaped
+        std::fs::write(&config, "[llm]\nmodel = \"costs $${env:HOME}\"\n").unwrap();
+        let value = read_layer(&config, false).unwrap();
+        assert_eq!(value["llm"]["model"].as_str(), Some("costs ${env:HOME}"));
+
+        // References in keys a project can't set are dropped, not refused
+        std::fs::write(
+            &config,
+            "[llm]\nmodel = \"a\"\napi_key = \"${env:HOME}\"\n\
+             [[mcp.servers]]\nname = \"x\"\nenv = { TOKEN = \"${file:token}\" }\n",
+        )
+        .unwrap();
+        let value = read_layer(&config, false).unwrap();
+        assert!(value["llm"].get("api_key").is_none());
+        assert!(value.get("mcp").is_none());
+    }
+
+    #[test]
+    fn test_restore_references_keeps_unchanged_secrets() {
+        let dir = tempfile::tempdir().unwrap();
+        std::env::set_var("SAFE_CODER_TEST_RESTORE", "sk-live");
+        let raw = value("[llm]\napi_key = \"${env:SAFE_CODER_TEST_RESTORE}\"\nmodel = \"a\"");
+
+        let mut saved = value("[llm]\napi_key = \"sk-live\"\nmodel = \"b\"");
+        restore_references(&mut saved, &raw, dir.path());
+        assert_eq!(
+            saved["llm"]["api_key"].as_str(),
+            Some("${env:SAFE_CODER_TEST_RESTORE}")
+        );
+        assert_eq!(saved["llm"]["model"].as_str(), Some("b"));
+
+        // A key the user replaced is written as given
+        let mut saved = value("[llm]\napi_key = \"sk-new\"");
+        restore_references(&mut saved, &raw, dir.path());
+        assert_eq!(saved["llm"]["api_key"].as_str(), Some("sk-new"));
+    }
+
+    #[test]
+    fn test_project_config_layers() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = dir.path().join("repo");
+        let app = repo.join("app");
+        std::fs::create_dir_all(app.join(".safe-coder")).unwrap();
+        std::fs::create_dir_all(repo.join(".safe-coder")).unwrap();
+        std::fs::create_dir_all(repo.join(".git")).unwrap();
+        std::fs::create_dir_all(dir.path().join(".safe-coder")).unwrap();
+        std::fs::write(
+            dir.path().join(".safe-coder/config.toml"),
+            "[tools]\nbash_timeout_secs = 10\n",
+        )
+        .unwrap();
+        std::fs::write(
+            repo.join(".safe-coder/config.toml"),
+            "[llm]\nmodel = \"parent-model\"\n[tools]\nbash_timeout_secs = 30\n",
+        )
+        .unwrap();
+        std::fs::write(
+            app.join(".safe-coder/config.toml"),
+            "[llm]\nmodel = \"project-model\"\nbase_url = \"https://example.com\"\n\
+             [permissions]\npreset = \"dev\"\n[hooks]\nbuiltins = false\n",
+        )
+        .unwrap();
+
+        // Layers above the repository root don't apply
+        let layers = Config::config_layers(&app).unwrap();
+        let project_layers: Vec<_> = layers
+            .iter()
+            .filter(|(path, _)| path.starts_with(dir.path()))
+            .collect();
+        assert_eq!(project_layers.len(), 2);
+        assert_eq!(project_layers[0].0, repo.join(".safe-coder/config.toml"));
+        assert_eq!(project_layers[1].0, app.join(".safe-coder/config.toml"));
+
+        let mut merged = toml::Value::try_from(Config::default()).unwrap();
+        for (_, layer) in project_layers {
+            merge_toml(&mut merged, layer.clone());
+        }
+        let config: Config = merged.try_into().unwrap();
+        assert_eq!(config.llm.model, "project-model");
+        assert_eq!(config.tools.bash_timeout_secs, 30);
+
+        // Global-only settings are dropped from project layers
+        assert_eq!(config.llm.base_url, None);
+        assert_eq!(config.permissions.preset, None);
+        assert!(config.hooks.builtins);
+
+        // Outside a repository only the project directory counts
+        std::fs::remove_dir(repo.join(".git")).unwrap();
+        let layers = Config::config_layers(&app).unwrap();
+        assert_eq!(
+            layers
+                .iter()
+                .filter(|(path, _)| path.starts_with(dir.path()))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_project_layers_cannot_grant_server_tokens() {
+        let mut merged = value(
+            "[[server.tokens]]\nname = \"ci\"\ntoken = \"ci-token\"\nscope = \"viewer\"\n",
+        );
+        let mut project = value(
+            "[[server.tokens]]\nname = \"ci\"\ntoken = \"ci-token\"\nscope = \"admin\"\n\
+             [[server.tokens]]\nname = \"repo\"\ntoken = \"repo-token\"\nscope = \"admin\"\n",
+        );
+        strip_global_only_keys(&mut project, Path::new(".safe-coder/config.toml"));
+        merge_toml(&mut merged, project);
+
+        let server: ServerAccessConfig = merged["server"].clone().try_into().unwrap();
+        assert_eq!(
+            server.tokens,
+            vec![ApiToken {
+                name: "ci".to_string(),
+                token: "ci-token".to_string(),
+                scope: TokenScope::Viewer,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_project_layers_cannot_launch_programs_or_relax_checks() {
+        let mut project = value(
+            "[lsp.servers.rust]\ncommand = \"./evil\"\nargs = [\"-x\"]\nenv = { A = \"1\" }\ndisabled = true\n\
+             [orchestrator]\nclaude_cli_path = \"./evil\"\nmax_workers = 2\n\
+             [build.commands]\n\"Cargo.toml\" = \"./evil\"\n\
+             [tools]\nwarn_dangerous_commands = false\nprotected_paths = []\nbash_timeout_secs = 5\n",
+        );
+        strip_global_only_keys(&mut project, Path::new(".safe-coder/config.toml"));
+
+        let rust = project["lsp"]["servers"]["rust"].as_table().unwrap();
+        assert_eq!(rust.keys().collect::<Vec<_>>(), vec!["disabled"]);
+        let orchestrator = project["orchestrator"].as_table().unwrap();
+        assert_eq!(orchestrator.keys().collect::<Vec<_>>(), vec!["max_workers"]);
+        assert!(project["build"].get("commands").is_none());
+        let tools = project["tools"].as_table().unwrap();
+        assert_eq!(tools.keys().collect::<Vec<_>>(), vec!["bash_timeout_secs"]);
+    }
+
+    #[test]
+    fn test_loop_detection_prompt_defaults() {
+        let config: LoopDetectionConfig = toml::from_str("ask_threshold = 2").unwrap();
+        assert_eq!(config.ask_threshold, 2);
+        assert_eq!(config.prompt_timeout_secs, 300);
+        assert!(!config.continue_on_timeout);
+    }
+
+    #[test]
+    fn test_llm_http_provider_overrides() {
+        let config: LlmHttpConfig = toml::from_str(
+            "read_timeout_secs = 120\n[providers.anthropic]\nread_timeout_secs = 600\nmax_retries = 4\n",
+        )
+        .unwrap();
+        let anthropic = config.for_provider("anthropic");
+        assert_eq!(anthropic.read_timeout, std::time::Duration::from_secs(600));
+        assert_eq!(anthropic.max_retries, 4);
+        assert_eq!(anthropic.connect_timeout, std::time::Duration::from_secs(10));
+
+        let ollama = config.for_provider("ollama");
+        assert_eq!(ollama.read_timeout, std::time::Duration::from_secs(120));
+        assert_eq!(ollama.max_retries, 2);
+    }
+
+    #[test]
+    fn test_generation_params_set() {
+        let mut params = GenerationParams::default();
+        params.set("temperature", "0.2").unwrap();
+        params.set("top-p", "0.9").unwrap();
+        params.set("stop", "END, ###").unwrap();
+        assert_eq!(params.temperature, Some(0.2));
+        assert_eq!(params.top_p, Some(0.9));
+        assert_eq!(params.stop, vec!["END".to_string(), "###".to_string()]);
+
+        assert!(params.set("temperature", "3").is_err());
+        assert!(params.set("top_k", "40").is_err());
+
+        params.set("temperature", "default").unwrap();
+        assert_eq!(params.temperature, None);
+    }
+
+    #[test]
+    fn test_llm_model_for_mode() {
+        let mut llm = Config::default().llm;
+        llm.model = "strong".to_string();
+        assert_eq!(llm.model_for_mode(AgentMode::Plan), "strong");
+
+        llm.plan_model = Some("fast".to_string());
+        assert_eq!(llm.model_for_mode(AgentMode::Plan), "fast");
+        assert_eq!(llm.model_for_mode(AgentMode::Build), "strong");
+    }
+}
@@ -8,8 +8,15 @@
 //! 2. Summarizing older messages
 //! 3. Pruning large tool results
 //! 4. Providing warnings about potential accuracy degradation
+//!
+//! Before each request, `fit_request` checks the whole prompt (system prompt,
+//! tool schemas and messages) against the model's window and trims it when
+//! it wouldn't fit, so the provider never has to reject it.
+
+use crate::llm::{ContentBlock, Message, Role, ToolDefinition};
 
-use crate::llm::{ContentBlock, Message, Role};
+/// Tool results at most this long are left alone by `fit_request`
+const MIN_CLEARED_RESULT_CHARS: usize = 200;
 
 /// Configuration for context compaction
 /// Based on Codex CLI's approach with configurable thresholds
@@ -35,7 +42,7 @@ pub struct ContextConfig {
 impl Default for ContextConfig {
     fn default() -> Self {
         Self {
-            max_tokens: 128_000,               // Claude's context window
+            max_tokens: 128_000,               // Until sized to the model's window
             compact_threshold_pct: 60,         // Compact at 60% to leave room for responses
             preserve_recent_tokens: 20_000,    // Keep ~20k tokens like Codex
             min_preserve_messages: 5,          // Always keep at least 5 messages
@@ -89,12 +96,68 @@ impl CompactionResult {
     }
 }
 
+/// What `fit_request` did to make a request fit the context window
+#[derive(Debug, Clone)]
+pub struct PreflightResult {
+    /// Estimated request tokens before trimming
+    pub tokens_before: usize,
+    /// Estimated request tokens after trimming
+    pub tokens_after: usize,
+    /// Tokens the request may use: the window less the response reserve
+    pub budget: usize,
+    /// Tool results cleared or truncated
+    pub tool_results_trimmed: usize,
+    /// Compaction, when trimming tool results wasn't enough
+    pub compaction: Option<CompactionResult>,
+}
+
+impl PreflightResult {
+    /// Whether the request now fits
+    pub fn fits(&self) -> bool {
+        self.tokens_after <= self.budget
+    }
+
+    /// One line describing what was trimmed
+    pub fn summary(&self) -> String {
+        let mut actions = Vec::new();
+        if self.tool_results_trimmed > 0 {
+            actions.push(format!(
+                "trimmed {} tool results",
+                self.tool_results_trimmed
+            ));
+        }
+        if let Some(compaction) = self.compaction.as_ref().filter(|c| c.did_compact()) {
+            actions.push(format!(
+                "compacted {} messages",
+                compaction.messages_removed
+            ));
+        }
+        let mut summary = format!(
+            "Request of ~{}k tokens didn't fit the {}k available; {} (now ~{}k)",
+            self.tokens_before / 1000,
+            self.budget / 1000,
+            if actions.is_empty() {
+                "nothing could be trimmed".to_string()
+            } else {
+                actions.join(" and ")
+            },
+            self.tokens_after / 1000
+        );
+        if !self.fits() {
+            summary.push_str("; it is still too large and may be rejected");
+        }
+        summary
+    }
+}
+
 /// Manages context compaction for a conversation
 #[derive(Debug)]
 pub struct ContextManager {
     config: ContextConfig,
     /// Last known actual input tokens from API response (for accurate compaction decisions)
     last_actual_tokens: Option<usize>,
+    /// Tokens of the window kept free for the response
+    output_reserve: usize,
 }
 
 impl ContextManager {
@@ -103,6 +166,7 @@ impl ContextManager {
         Self {
             config: ContextConfig::default(),
             last_actual_tokens: None,
+            output_reserve: 0,
         }
     }
 
@@ -111,6 +175,7 @@ impl ContextManager {
         Self {
             config,
             last_actual_tokens: None,
+            output_reserve: 0,
         }
     }
 
@@ -119,6 +184,11 @@ impl ContextManager {
         self.config.max_tokens = max_tokens;
     }
 
+    /// Keep `tokens` of the window free for the response (the max output tokens)
+    pub fn set_output_reserve(&mut self, tokens: usize) {
+        self.output_reserve = tokens;
+    }
+
     /// Update with actual token count from API response
     /// This helps calibrate future compaction decisions
     pub fn record_actual_tokens(&mut self, input_tokens: usize) {
@@ -166,6 +236,8 @@ impl ContextManager {
                         tool_result_count += 1;
                         total_chars += content.len();
                     }
+                    // Providers drop earlier thinking from the prompt
+                    ContentBlock::Thinking { .. } => {}
                 }
             }
         }
@@ -184,6 +256,104 @@ impl ContextManager {
         }
     }
 
+    /// Estimated tokens of a whole request: system prompt, tool schemas and messages
+    pub fn estimate_request_tokens(
+        &self,
+        system_prompt: &str,
+        tools: &[ToolDefinition],
+        messages: &[Message],
+    ) -> usize {
+        self.request_overhead(system_prompt, tools) + self.analyze(messages).estimated_tokens
+    }
+
+    /// Estimated tokens of the system prompt and tool schemas
+    fn request_overhead(&self, system_prompt: &str, tools: &[ToolDefinition]) -> usize {
+        let tool_chars: usize = tools
+            .iter()
+            .map(|tool| {
+                tool.name.len() + tool.description.len() + tool.input_schema.to_string().len()
+            })
+            .sum();
+        (system_prompt.len() + tool_chars) / self.config.chars_per_token
+    }
+
+    /// Make a request fit the context window before it is sent
+    ///
+    /// When the system prompt, tool schemas and messages don't fit in the
+    /// window less the response reserve, tool results are cleared oldest
+    /// first, sparing the last `min_preserve_messages` messages. If that
+    /// isn't enough, the spared ones are truncated to `max_tool_result_chars`,
+    /// and as a last resort the conversation is compacted. Returns None when
+    /// the request already fits.
+    pub fn fit_request(
+        &self,
+        system_prompt: &str,
+        tools: &[ToolDefinition],
+        messages: &mut Vec<Message>,
+    ) -> Option<PreflightResult> {
+        let budget = self.config.max_tokens.saturating_sub(self.output_reserve);
+        let tokens_before = self.estimate_request_tokens(system_prompt, tools, messages);
+        if tokens_before <= budget {
+            return None;
+        }
+
+        let mut tokens = tokens_before;
+        let mut tool_results_trimmed = 0;
+        let spared = messages
+            .len()
+            .saturating_sub(self.config.min_preserve_messages);
+        let (older, recent) = messages.split_at_mut(spared);
+
+        for (msgs, keep_chars) in [
+            (older, None),
+            (recent, Some(self.config.max_tool_result_chars)),
+        ] {
+            for block in msgs.iter_mut().flat_map(|msg| msg.content.iter_mut()) {
+                if tokens <= budget {
+                    break;
+                }
+                let ContentBlock::ToolResult { content, .. } = block else {
+                    continue;
+                };
+                let limit = keep_chars.unwrap_or(0).max(MIN_CLEARED_RESULT_CHARS);
+                if content.len() <= limit {
+                    continue;
+                }
+                let before = content.len();
+                *content = match keep_chars {
+                    None => format!(
+                        "[Tool result cleared to fit the context window: {} chars]",
+                        before
+                    ),
+                    Some(keep) => format!(
+                        "{}...\n\n[Truncated to fit the context window: {} chars total]",
+                        &content[..floor_char_boundary(content, keep)],
+                        before
+                    ),
+                };
+                tokens = tokens.saturating_sub(
+                    before.saturating_sub(content.len()) / self.config.chars_per_token,
+                );
+                tool_results_trimmed += 1;
+            }
+        }
+
+        let mut compaction = None;
+        if self.estimate_request_tokens(system_prompt, tools, messages) > budget {
+            let (compacted, result) = self.compact(std::mem::take(messages));
+            *messages = compacted;
+            compaction = Some(result);
+        }
+
+        Some(PreflightResult {
+            tokens_before,
+            tokens_after: self.estimate_request_tokens(system_prompt, tools, messages),
+            budget,
+            tool_results_trimmed,
+            compaction,
+        })
+    }
+
     /// Check if compaction is needed
     pub fn needs_compaction(&self, messages: &[Message]) -> bool {
         self.analyze(messages).needs_compaction
@@ -291,6 +461,7 @@ impl ContextManager {
                 ContentBlock::ToolResult { content, .. } => {
                     chars += content.len();
                 }
+                ContentBlock::Thinking { .. } => {}
             }
         }
         chars / self.config.chars_per_token
@@ -401,6 +572,8 @@ impl ContextManager {
                         // Images are context; note their presence in summary
                         assistant_actions.push("[Image attached]".to_string());
                     }
+                    // Thinking is scratch work, not worth keeping in the summary
+                    ContentBlock::Thinking { .. } => {}
                 }
             }
         }
@@ -558,7 +731,7 @@ impl ContextManager {
                             tool_use_id,
                             content: format!(
                                 "{}...\n\n[Truncated: {} chars total]",
-                                &content[..max_result_len],
+                                &content[..floor_char_boundary(&content, max_result_len)],
                                 content.len()
                             ),
                         }
@@ -591,6 +764,15 @@ impl ContextManager {
     }
 }
 
+/// The largest char boundary in `text` at or before `index`
+fn floor_char_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
 impl Default for ContextManager {
     fn default() -> Self {
         Self::new()
@@ -635,6 +817,25 @@ mod tests {
         assert!(!stats.needs_compaction);
     }
 
+    #[test]
+    fn test_analyze_skips_thinking() {
+        let manager = ContextManager::new();
+        let plain = vec![make_text_message(Role::Assistant, "Done.")];
+        let mut with_thinking = plain.clone();
+        with_thinking[0].content.insert(
+            0,
+            ContentBlock::Thinking {
+                thinking: "Let me consider every file in turn. ".repeat(100),
+                signature: Some("sig".to_string()),
+            },
+        );
+
+        assert_eq!(
+            manager.analyze(&with_thinking).estimated_tokens,
+            manager.analyze(&plain).estimated_tokens
+        );
+    }
+
     #[test]
     fn test_compact_preserves_recent_tokens() {
         // Configure to preserve only 100 tokens
@@ -685,6 +886,122 @@ mod tests {
         assert!(!result.did_compact());
     }
 
+    fn make_tool_turn(id: &str, result: &str) -> Vec<Message> {
+        vec![
+            Message {
+                role: Role::Assistant,
+                content: vec![ContentBlock::ToolUse {
+                    id: id.to_string(),
+                    name: "read_file".to_string(),
+                    input: serde_json::json!({"path": "src/lib.rs"}),
+                }],
+            },
+            Message {
+                role: Role::User,
+                content: vec![ContentBlock::ToolResult {
+                    tool_use_id: id.to_string(),
+                    content: result.to_string(),
+                }],
+            },
+        ]
+    }
+
+    fn tool_result(msg: &Message) -> &str {
+        match &msg.content[0] {
+            ContentBlock::ToolResult { content, .. } => content,
+            _ => panic!("Expected ToolResult"),
+        }
+    }
+
+    #[test]
+    fn test_fit_request_leaves_fitting_requests_alone() {
+        let manager = ContextManager::new();
+        let mut messages = make_tool_turn("1", &"x".repeat(1000));
+        assert!(manager.fit_request("system", &[], &mut messages).is_none());
+        assert_eq!(tool_result(&messages[1]).len(), 1000);
+    }
+
+    #[test]
+    fn test_fit_request_clears_oldest_tool_results_first() {
+        let mut manager = ContextManager::with_config(ContextConfig {
+            max_tokens: 12_000,
+            min_preserve_messages: 2,
+            chars_per_token: 1,
+            ..Default::default()
+        });
+        manager.set_output_reserve(2_000);
+
+        // Three 4k results: ~12k tokens against a 10k budget
+        let mut messages: Vec<Message> = ["1", "2", "3"]
+            .iter()
+            .flat_map(|id| make_tool_turn(id, &"x".repeat(4_000)))
+            .collect();
+        let result = manager.fit_request("", &[], &mut messages).unwrap();
+
+        assert!(result.fits());
+        assert_eq!(result.budget, 10_000);
+        assert_eq!(result.tool_results_trimmed, 1);
+        assert!(result.compaction.is_none());
+        assert!(tool_result(&messages[1]).starts_with("[Tool result cleared"));
+        assert_eq!(tool_result(&messages[3]).len(), 4_000);
+        assert_eq!(tool_result(&messages[5]).len(), 4_000);
+    }
+
+    #[test]
+    fn test_fit_request_counts_system_prompt_and_tools() {
+        let manager = ContextManager::with_config(ContextConfig {
+            max_tokens: 5_000,
+            min_preserve_messages: 2,
+            chars_per_token: 1,
+            ..Default::default()
+        });
+        let tools = vec![ToolDefinition {
+            name: "bash".to_string(),
+            description: "d".repeat(1_000),
+            input_schema: serde_json::json!({"type": "object"}),
+        }];
+        let system_prompt = "s".repeat(2_000);
+        let mut messages: Vec<Message> = ["1", "2"]
+            .iter()
+            .flat_map(|id| make_tool_turn(id, &"x".repeat(1_500)))
+            .collect();
+
+        // The messages alone would fit
+        assert!(manager.analyze(&messages).estimated_tokens < 5_000);
+        let result = manager
+            .fit_request(&system_prompt, &tools, &mut messages)
+            .unwrap();
+        assert!(result.fits());
+        assert_eq!(result.tool_results_trimmed, 1);
+    }
+
+    #[test]
+    fn test_fit_request_truncates_recent_results_then_compacts() {
+        let manager = ContextManager::with_config(ContextConfig {
+            max_tokens: 3_000,
+            min_preserve_messages: 2,
+            preserve_recent_tokens: 100,
+            max_tool_result_chars: 500,
+            chars_per_token: 1,
+            ..Default::default()
+        });
+
+        // A single huge result is truncated rather than cleared
+        let mut messages = make_tool_turn("1", &"é".repeat(5_000));
+        let result = manager.fit_request("", &[], &mut messages).unwrap();
+        assert!(result.fits());
+        assert!(tool_result(&messages[1]).contains("[Truncated to fit the context window"));
+
+        // Plain text can only be compacted
+        let mut messages: Vec<Message> = (0..10)
+            .map(|i| make_text_message(Role::User, &format!("{} {}", i, "word ".repeat(100))))
+            .collect();
+        let result = manager.fit_request("", &[], &mut messages).unwrap();
+        assert_eq!(result.tool_results_trimmed, 0);
+        assert!(result.compaction.unwrap().did_compact());
+        assert!(messages.len() < 10);
+    }
+
     #[test]
     fn test_prune_large_tool_result() {
         let manager = ContextManager::new();
@@ -0,0 +1,232 @@
+//! Crash handling
+//!
+//! A panic in a TUI used to leave the terminal in raw mode and the
+//! conversation in memory only. The hook set by `install_panic_hook` handles
+//! panics on the main thread, which end the process: it restores the
+//! terminal if a TUI holds a `TerminalGuard`, writes a crash report and the
+//! conversation as of the last checkpoint to
+//! `~/.config/safe-coder/crashes/<time>/`, and prints how to carry on.
+//! Panics in spawned tasks don't end the process and only get the default
+//! message.
+
+use std::fmt::Write as _;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+use anyhow::{Context, Result};
+
+use crate::llm::Message;
+
+static RAW_TERMINALS: AtomicUsize = AtomicUsize::new(0);
+
+/// Conversation as of the last checkpoint, serialized up front so the hook
+/// doesn't serialize while the program is in an unknown state
+struct Snapshot {
+    project_path: PathBuf,
+    messages_json: String,
+}
+
+static SNAPSHOT: Mutex<Option<Snapshot>> = Mutex::new(None);
+
+/// Server session the shell TUI is driving
+static SERVER_SESSION: Mutex<Option<String>> = Mutex::new(None);
+
+/// While held, a crash restores the terminal before reporting
+#[must_use = "the terminal isn't restored on a crash once this is dropped"]
+pub struct TerminalGuard(());
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        RAW_TERMINALS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Call after putting the terminal in raw mode and the alternate screen
+pub fn guard_terminal() -> TerminalGuard {
+    RAW_TERMINALS.fetch_add(1, Ordering::SeqCst);
+    TerminalGuard(())
+}
+
+/// Keep `messages` to write out if the process crashes
+pub fn record_messages(project_path: &Path, messages: &[Message]) {
+    let Ok(messages_json) = serde_json::to_string_pretty(messages) else {
+        return;
+    };
+    if let Ok(mut snapshot) = SNAPSHOT.lock() {
+        *snapshot = Some(Snapshot {
+            project_path: project_path.to_path_buf(),
+            messages_json,
+        });
+    }
+}
+
+/// Remember which server session this client is driving, or forget it
+pub fn record_server_session(session_id: Option<&str>) {
+    if let Ok(mut session) = SERVER_SESSION.lock() {
+        *session = session_id.map(str::to_string);
+    }
+}
+
+/// Install the crash handler in front of the default panic hook
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() != Some("main") {
+            default_hook(info);
+            return;
+        }
+
+        if RAW_TERMINALS.load(Ordering::SeqCst) > 0 {
+            restore_terminal();
+        }
+        default_hook(info);
+
+        // The panic may have happened while a lock was held
+        let snapshot = SNAPSHOT.try_lock().ok();
+        let snapshot = snapshot.as_ref().and_then(|s| s.as_ref());
+        let server_session = SERVER_SESSION.try_lock().ok().and_then(|s| s.clone());
+        match write_report(info, snapshot) {
+            Ok(dir) => eprintln!(
+                "\n{}",
+                recovery_instructions(&dir, snapshot, server_session.as_deref())
+            ),
+            Err(e) => eprintln!(
+                "\nsafe-coder crashed and couldn't write a crash report: {:#}",
+                e
+            ),
+        }
+    }));
+}
+
+fn restore_terminal() {
+    use crossterm::event::{DisableBracketedPaste, DisableFocusChange, DisableMouseCapture};
+    use crossterm::terminal::{disable_raw_mode, LeaveAlternateScreen};
+
+    let _ = disable_raw_mode();
+    let _ = crossterm::execute!(
+        std::io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableFocusChange,
+        DisableBracketedPaste,
+        crossterm::cursor::Show
+    );
+}
+
+/// Write `report.txt`, plus `messages.json` when there is a conversation
+fn write_report(info: &PanicHookInfo, snapshot: Option<&Snapshot>) -> Result<PathBuf> {
+    let dir = dirs::config_dir()
+        .context("Could not determine config directory")?
+        .join("safe-coder")
+        .join("crashes")
+        .join(chrono::Local::now().format("%Y%m%d-%H%M%S").to_string());
+    std::fs::create_dir_all(&dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    let backtrace = std::backtrace::Backtrace::force_capture();
+    std::fs::write(
+        dir.join("report.txt"),
+        report_text(info, snapshot, &backtrace),
+    )?;
+    if let Some(snapshot) = snapshot {
+        std::fs::write(dir.join("messages.json"), &snapshot.messages_json)?;
+    }
+    Ok(dir)
+}
+
+fn report_text(
+    info: &PanicHookInfo,
+    snapshot: Option<&Snapshot>,
+    backtrace: &std::backtrace::Backtrace,
+) -> String {
+    let message = info
+        .payload()
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| info.payload().downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "(non-string panic payload)".to_string());
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let mut report = String::new();
+    let _ = writeln!(
+        report,
+        "safe-coder {} crash report",
+        env!("CARGO_PKG_VERSION")
+    );
+    let _ = writeln!(report, "Time: {}", chrono::Local::now().to_rfc3339());
+    let _ = writeln!(
+        report,
+        "Platform: {} {}",
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    );
+    let _ = writeln!(
+        report,
+        "Arguments: {:?}",
+        std::env::args().collect::<Vec<_>>()
+    );
+    if let Some(snapshot) = snapshot {
+        let _ = writeln!(report, "Project: {}", snapshot.project_path.display());
+    }
+    let _ = writeln!(report, "\nPanic: {}", message);
+    let _ = writeln!(report, "Location: {}", location);
+    let _ = writeln!(report, "\nBacktrace:\n{}", backtrace);
+    report
+}
+
+fn recovery_instructions(
+    dir: &Path,
+    snapshot: Option<&Snapshot>,
+    server_session: Option<&str>,
+) -> String {
+    let mut text = String::from("safe-coder crashed; sorry about that.\n");
+    let _ = writeln!(text, "  Crash report: {}", dir.join("report.txt").display());
+    if let Some(snapshot) = snapshot {
+        let _ = writeln!(
+            text,
+            "  Conversation up to the last finished turn: {}",
+            dir.join("messages.json").display()
+        );
+        let _ = writeln!(
+            text,
+            "  Continue it with: cd {} && safe-coder chat --recover",
+            snapshot.project_path.display()
+        );
+    }
+    if let Some(id) = server_session {
+        let _ = writeln!(
+            text,
+            "  The AI session is still on the server; run safe-coder and /connect {} to pick it up",
+            id
+        );
+    }
+    text.push_str("  Please attach the crash report when filing an issue.");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recovery_instructions() {
+        let snapshot = Snapshot {
+            project_path: PathBuf::from("/work/app"),
+            messages_json: "[]".to_string(),
+        };
+        let dir = Path::new("/tmp/crash");
+
+        let text = recovery_instructions(dir, Some(&snapshot), Some("abc123"));
+        assert!(text.contains("/tmp/crash/report.txt"));
+        assert!(text.contains("cd /work/app && safe-coder chat --recover"));
+        assert!(text.contains("/connect abc123"));
+
+        let text = recovery_instructions(dir, None, None);
+        assert!(!text.contains("messages.json"));
+        assert!(!text.contains("/connect"));
+    }
+}
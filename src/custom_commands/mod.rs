@@ -1,7 +1,32 @@
+//! Custom Commands
+//!
+//! User-defined slash commands loaded from `~/.config/safe-coder/commands/`
+//! and `.safe-coder/commands/`. Commands are markdown files with optional
+//! frontmatter (or TOML files with a `prompt` field):
+//!
+//! ```markdown
+//! ---
+//! description: Deploy the app
+//! arguments: [environment]
+//! allowed-tools: [bash, read_file]
+//! model: claude-sonnet-4-20250514
+//! ---
+//! Deploy the current branch to $1 and report any failing checks.
+//! ```
+//!
+//! Invoked as `/deploy staging`. Prompts may use `$ARGUMENTS` (all arguments),
+//! `$1`..`$9` (positional arguments), `$FILE` (the `@file` passed with the
+//! command) and `$SELECTION` (the editor selection, when available).
+//!
+//! Lines starting with `!` (e.g. `!git diff --staged`) and inline `!{command}`
+//! snippets are shell commands whose output is substituted into the prompt
+//! before it is sent. They run through the session's bash tool, so the usual
+//! permission checks and approval prompts apply.
+
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
 /// Custom user-defined commands
@@ -10,6 +35,359 @@ pub struct CustomCommand {
     pub name: String,
     pub description: Option<String>,
     pub prompt: String,
+    /// Names of required positional arguments (used for validation and usage)
+    #[serde(default)]
+    pub arguments: Vec<String>,
+    /// Free-form usage hint shown after the command name
+    #[serde(default)]
+    pub argument_hint: Option<String>,
+    /// Tools the command may use (empty = no restriction)
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// Model to use while running the command
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// Context available when rendering a command prompt
+#[derive(Debug, Clone, Default)]
+pub struct CommandContext {
+    /// File the command applies to (`$FILE`)
+    pub file: Option<String>,
+    /// Current editor selection (`$SELECTION`)
+    pub selection: Option<String>,
+}
+
+impl CustomCommand {
+    /// Parse a markdown command file with optional frontmatter
+    pub fn from_markdown(name: &str, content: &str) -> Result<Self> {
+        let (frontmatter, body) = crate::skills::parse_frontmatter(content)?;
+
+        let text = |key: &str| {
+            frontmatter
+                .get(key)
+                .and_then(|v| v.as_str())
+                .filter(|s| !s.is_empty())
+                .map(|s| s.to_string())
+        };
+        let list = |key: &str| -> Vec<String> {
+            match frontmatter.get(key) {
+                Some(serde_json::Value::Array(items)) => items
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+                    .collect(),
+                Some(serde_json::Value::String(s)) => s
+                    .split(',')
+                    .map(|s| s.trim())
+                    .filter(|s| !s.is_empty())
+                    .map(|s| s.to_string())
+                    .collect(),
+                _ => Vec::new(),
+            }
+        };
+
+        if body.trim().is_empty() {
+            anyhow::bail!("Command has an empty prompt");
+        }
+
+        Ok(Self {
+            name: text("name").unwrap_or_else(|| name.to_string()),
+            description: text("description"),
+            prompt: body,
+            arguments: list("arguments"),
+            argument_hint: text("argument-hint").or_else(|| text("argument_hint")),
+            allowed_tools: {
+                let tools = list("allowed-tools");
+                if tools.is_empty() {
+                    list("allowed_tools")
+                } else {
+                    tools
+                }
+            },
+            model: text("model"),
+        })
+    }
+
+    /// Usage line, e.g. `/deploy <environment>`
+    pub fn usage(&self) -> String {
+        let hint = match &self.argument_hint {
+            Some(hint) => hint.clone(),
+            None => self
+                .arguments
+                .iter()
+                .map(|a| format!("<{}>", a))
+                .collect::<Vec<_>>()
+                .join(" "),
+        };
+        if hint.is_empty() {
+            format!("/{}", self.name)
+        } else {
+            format!("/{} {}", self.name, hint)
+        }
+    }
+
+    /// Render the prompt for an invocation, validating its arguments.
+    ///
+    /// `@path` arguments are taken as `$FILE` (when the context has none) and
+    /// are not counted as positional arguments.
+    pub fn render(&self, args: &str, ctx: &CommandContext) -> Result<String> {
+        let mut file = ctx.file.clone();
+        let mut positional = Vec::new();
+        for arg in parse_arguments(args).with_context(|| format!("Usage: {}", self.usage()))? {
+            match arg.strip_prefix('@') {
+                Some(path) if file.is_none() && !path.is_empty() => file = Some(path.to_string()),
+                _ => positional.push(arg),
+            }
+        }
+        let arguments = positional.join(" ");
+
+        let refs = placeholder_refs(&self.prompt);
+        let required = refs.max_positional.max(self.arguments.len());
+        if positional.len() < required {
+            let missing: Vec<String> = (positional.len()..required)
+                .map(|i| match self.arguments.get(i) {
+                    Some(name) => format!("<{}>", name),
+                    None => format!("${}", i + 1),
+                })
+                .collect();
+            anyhow::bail!(
+                "Missing argument(s) {}. Usage: {}",
+                missing.join(", "),
+                self.usage()
+            );
+        }
+        if refs.file && file.is_none() {
+            anyhow::bail!(
+                "/{} needs a file; pass one as @path. Usage: {}",
+                self.name,
+                self.usage()
+            );
+        }
+        if refs.selection && ctx.selection.is_none() {
+            anyhow::bail!("/{} needs an editor selection, but none is available", self.name);
+        }
+
+        let mut prompt = substitute(&self.prompt, |placeholder| match placeholder {
+            Placeholder::Arguments => Some(arguments.clone()),
+            Placeholder::File => file.clone(),
+            Placeholder::Selection => ctx.selection.clone(),
+            Placeholder::Positional(n) => positional.get(n - 1).cloned(),
+        });
+
+        // Legacy TOML placeholder
+        prompt = prompt.replace("{{args}}", &arguments);
+
+        // Pass arguments along even if the prompt doesn't reference them
+        let uses_args = refs.arguments || refs.max_positional > 0 || self.prompt.contains("{{args}}");
+        if !uses_args && !arguments.is_empty() {
+            prompt.push_str(&format!("\n\nArguments: {}", arguments));
+        }
+
+        Ok(prompt)
+    }
+}
+
+/// Placeholders recognized in command prompts
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Placeholder {
+    Arguments,
+    File,
+    Selection,
+    Positional(usize),
+}
+
+/// Placeholders referenced by a prompt
+#[derive(Debug, Default)]
+struct PlaceholderRefs {
+    arguments: bool,
+    file: bool,
+    selection: bool,
+    max_positional: usize,
+}
+
+fn placeholder_refs(template: &str) -> PlaceholderRefs {
+    let mut refs = PlaceholderRefs::default();
+    substitute(template, |placeholder| {
+        match placeholder {
+            Placeholder::Arguments => refs.arguments = true,
+            Placeholder::File => refs.file = true,
+            Placeholder::Selection => refs.selection = true,
+            Placeholder::Positional(n) => refs.max_positional = refs.max_positional.max(n),
+        }
+        None
+    });
+    refs
+}
+
+/// Replace placeholders in a template. Placeholders the callback returns
+/// `None` for, and unknown `$NAMES`, are left as-is.
+fn substitute(template: &str, mut value: impl FnMut(Placeholder) -> Option<String>) -> String {
+    const NAMED: [(&str, Placeholder); 3] = [
+        ("ARGUMENTS", Placeholder::Arguments),
+        ("SELECTION", Placeholder::Selection),
+        ("FILE", Placeholder::File),
+    ];
+
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+
+        let named = NAMED.iter().find(|(name, _)| {
+            after.starts_with(name)
+                && !after[name.len()..]
+                    .starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        });
+        let digits = after.chars().take_while(|c| c.is_ascii_digit()).count();
+
+        let (placeholder, len) = if let Some((name, placeholder)) = named {
+            (Some(*placeholder), name.len())
+        } else if digits > 0 {
+            match after[..digits].parse::<usize>() {
+                Ok(n) if n > 0 => (Some(Placeholder::Positional(n)), digits),
+                _ => (None, 0),
+            }
+        } else {
+            (None, 0)
+        };
+
+        match placeholder {
+            Some(placeholder) => {
+                match value(placeholder) {
+                    Some(v) => out.push_str(&v),
+                    None => out.push_str(&rest[pos..pos + 1 + len]),
+                }
+                rest = &after[len..];
+            }
+            None => {
+                out.push('$');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// A `!` shell snippet from a command prompt
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShellSnippet {
+    /// Command to run
+    pub command: String,
+    /// Whole-line `!command` snippet (output is fenced) rather than inline `!{command}`
+    #[serde(default)]
+    pub block: bool,
+}
+
+/// Marker left in a prompt where a snippet's output goes
+fn snippet_marker(index: usize) -> String {
+    format!("{{{{shell:{}}}}}", index)
+}
+
+/// Replace `!command` lines and inline `!{command}` snippets with markers,
+/// returning the prompt and the snippets to run (in marker order).
+/// Markdown images (`![alt](url)`) are not snippets.
+pub fn extract_shell_snippets(prompt: &str) -> (String, Vec<ShellSnippet>) {
+    let mut snippets = Vec::new();
+    let mut lines = Vec::new();
+
+    for line in prompt.lines() {
+        if let Some(command) = line.strip_prefix('!') {
+            let command = command.trim();
+            if !command.is_empty() && !command.starts_with('[') && !command.starts_with('{') {
+                lines.push(snippet_marker(snippets.len()));
+                snippets.push(ShellSnippet {
+                    command: command.to_string(),
+                    block: true,
+                });
+                continue;
+            }
+        }
+
+        let mut out = String::with_capacity(line.len());
+        let mut rest = line;
+        while let Some(start) = rest.find("!{") {
+            let Some(len) = rest[start + 2..].find('}') else {
+                break;
+            };
+            let command = rest[start + 2..start + 2 + len].trim();
+            out.push_str(&rest[..start]);
+            if command.is_empty() {
+                out.push_str(&rest[start..start + 3 + len]);
+            } else {
+                out.push_str(&snippet_marker(snippets.len()));
+                snippets.push(ShellSnippet {
+                    command: command.to_string(),
+                    block: false,
+                });
+            }
+            rest = &rest[start + 3 + len..];
+        }
+        out.push_str(rest);
+        lines.push(out);
+    }
+
+    let mut rendered = lines.join("\n");
+    if prompt.ends_with('\n') {
+        rendered.push('\n');
+    }
+    (rendered, snippets)
+}
+
+/// Put snippet outputs in place of their markers
+pub fn fill_shell_snippets(prompt: &str, snippets: &[ShellSnippet], outputs: &[String]) -> String {
+    let mut filled = prompt.to_string();
+    for (index, (snippet, output)) in snippets.iter().zip(outputs).enumerate() {
+        let output = output.trim_end();
+        let replacement = if snippet.block {
+            format!("```\n$ {}\n{}\n```", snippet.command, output)
+        } else {
+            output.trim().to_string()
+        };
+        filled = filled.replace(&snippet_marker(index), &replacement);
+    }
+    filled
+}
+
+/// Split invocation arguments like a shell: whitespace-separated, with
+/// single or double quotes grouping words
+pub fn parse_arguments(input: &str) -> Result<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_word = false;
+    let mut quote: Option<char> = None;
+
+    for c in input.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_word = true;
+            }
+            None if c.is_whitespace() => {
+                if in_word {
+                    args.push(std::mem::take(&mut current));
+                    in_word = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_word = true;
+            }
+        }
+    }
+
+    if let Some(q) = quote {
+        anyhow::bail!("Unterminated {} quote in arguments", q);
+    }
+    if in_word {
+        args.push(current);
+    }
+    Ok(args)
 }
 
 /// Custom command manager
@@ -61,7 +439,10 @@ impl CustomCommandManager {
         while let Some(entry) = entries.next_entry().await? {
             let path = entry.path();
 
-            if path.extension().and_then(|s| s.to_str()) == Some("toml") {
+            if matches!(
+                path.extension().and_then(|s| s.to_str()),
+                Some("toml") | Some("md")
+            ) {
                 match Self::load_command_file(&path).await {
                     Ok(cmd) => {
                         commands.insert(cmd.name.clone(), cmd);
@@ -82,14 +463,18 @@ impl CustomCommandManager {
             .await
             .context("Failed to read command file")?;
 
-        let value: toml::Value = toml::from_str(&content)
-            .context("Failed to parse TOML")?;
-
         let name = path.file_stem()
             .and_then(|s| s.to_str())
             .context("Invalid filename")?
             .to_string();
 
+        if path.extension().and_then(|s| s.to_str()) == Some("md") {
+            return CustomCommand::from_markdown(&name, &content);
+        }
+
+        let value: toml::Value = toml::from_str(&content)
+            .context("Failed to parse TOML")?;
+
         let description = value.get("description")
             .and_then(|v| v.as_str())
             .map(|s| s.to_string());
@@ -99,10 +484,45 @@ impl CustomCommandManager {
             .context("Command must have a 'prompt' field")?
             .to_string();
 
+        let strings = |key: &str| -> Vec<String> {
+            value
+                .get(key)
+                .and_then(|v| v.as_array())
+                .map(|items| {
+                    items
+                        .iter()
+                        .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
         Ok(CustomCommand {
             name,
             description,
             prompt,
+            arguments: strings("arguments"),
+            argument_hint: value
+                .get("argument_hint")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            allowed_tools: strings("allowed_tools"),
+            model: value
+                .get("model")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+        })
+    }
+
+    /// Check whether a command file exists for a name without loading it
+    pub fn command_exists(project_path: &Path, name: &str) -> bool {
+        let mut dirs = vec![project_path.join(".safe-coder").join("commands")];
+        if let Some(config_dir) = dirs::config_dir() {
+            dirs.push(config_dir.join("safe-coder").join("commands"));
+        }
+        dirs.iter().any(|dir| {
+            dir.join(format!("{}.md", name)).is_file()
+                || dir.join(format!("{}.toml", name)).is_file()
         })
     }
 
@@ -120,18 +540,22 @@ impl CustomCommandManager {
         commands
     }
 
-    /// Execute a custom command by replacing {{args}} with provided arguments
+    /// Execute a custom command by rendering its prompt with the provided arguments.
+    /// Shell snippets are left in place; see [`extract_shell_snippets`].
     pub fn execute_command(&self, name: &str, args: &str) -> Result<String> {
         let cmd = self.get_command(name)
             .context("Command not found")?;
 
-        // Replace {{args}} with actual arguments
-        let prompt = cmd.prompt.replace("{{args}}", args);
-
-        // TODO: Support !{...} for shell execution
         // TODO: Support @{...} for file inclusion
 
-        Ok(prompt)
+        cmd.render(args, &CommandContext::default())
+    }
+
+    /// Split `/name args...` input into a known command and its arguments
+    pub fn parse_invocation<'a>(&self, input: &'a str) -> Option<(&CustomCommand, &'a str)> {
+        let rest = input.trim().strip_prefix('/')?;
+        let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        self.get_command(name).map(|cmd| (cmd, args.trim()))
     }
 
     /// Reload commands from disk
@@ -168,5 +592,116 @@ prompt = "Do something with {{args}}"
         let cmd = commands.get("test").unwrap();
         assert_eq!(cmd.name, "test");
         assert_eq!(cmd.prompt, "Do something with {{args}}");
+        assert_eq!(
+            cmd.render("a b", &CommandContext::default()).unwrap(),
+            "Do something with a b"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_load_markdown_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let commands_dir = temp_dir.path().join("commands");
+        fs::create_dir_all(&commands_dir).await.unwrap();
+
+        let content = r#"---
+description: Deploy the app
+arguments: [environment]
+allowed-tools: [bash, read_file]
+model: fast-model
+---
+Deploy to $1 with options: $ARGUMENTS
+"#;
+        fs::write(commands_dir.join("deploy.md"), content).await.unwrap();
+
+        let commands = CustomCommandManager::load_from_directory(&commands_dir).await.unwrap();
+        let cmd = commands.get("deploy").unwrap();
+        assert_eq!(cmd.description.as_deref(), Some("Deploy the app"));
+        assert_eq!(cmd.allowed_tools, vec!["bash", "read_file"]);
+        assert_eq!(cmd.model.as_deref(), Some("fast-model"));
+        assert_eq!(cmd.usage(), "/deploy <environment>");
+
+        let prompt = cmd.render("staging --dry-run", &CommandContext::default()).unwrap();
+        assert_eq!(prompt, "Deploy to staging with options: staging --dry-run");
+
+        let err = cmd.render("", &CommandContext::default()).unwrap_err();
+        assert!(err.to_string().contains("Missing argument(s) <environment>"));
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let cmd = CustomCommand::from_markdown(
+            "review",
+            "Review $FILE, focusing on $2 ($1). Budget: $5. Selection: $SELECTIONS",
+        )
+        .unwrap();
+
+        // $5 counts as a positional reference
+        assert!(cmd.render("perf memory", &CommandContext::default()).is_err());
+
+        let cmd = CustomCommand::from_markdown("review", "Review $FILE for $1. Keep $HOME as-is.").unwrap();
+        assert!(cmd
+            .render("perf", &CommandContext::default())
+            .unwrap_err()
+            .to_string()
+            .contains("needs a file"));
+        assert_eq!(
+            cmd.render("@src/main.rs perf", &CommandContext::default()).unwrap(),
+            "Review src/main.rs for perf. Keep $HOME as-is."
+        );
+
+        let explain = CustomCommand::from_markdown("explain", "Explain:\n$SELECTION").unwrap();
+        assert!(explain.render("", &CommandContext::default()).is_err());
+        let ctx = CommandContext {
+            file: None,
+            selection: Some("let x = 1;".to_string()),
+        };
+        assert_eq!(explain.render("", &ctx).unwrap(), "Explain:\nlet x = 1;");
+
+        // Arguments are appended when the prompt doesn't reference them
+        let plain = CustomCommand::from_markdown("fix", "Fix the build").unwrap();
+        assert_eq!(
+            plain.render("quickly", &CommandContext::default()).unwrap(),
+            "Fix the build\n\nArguments: quickly"
+        );
+    }
+
+    #[test]
+    fn test_shell_snippets() {
+        let prompt = "Review these changes:\n!git diff --staged\n![diagram](arch.png)\nOn branch !{git branch --show-current}, see !{}.\n";
+        let (rendered, snippets) = extract_shell_snippets(prompt);
+        assert_eq!(
+            snippets,
+            vec![
+                ShellSnippet {
+                    command: "git diff --staged".to_string(),
+                    block: true
+                },
+                ShellSnippet {
+                    command: "git branch --show-current".to_string(),
+                    block: false
+                },
+            ]
+        );
+        assert_eq!(
+            rendered,
+            "Review these changes:\n{{shell:0}}\n![diagram](arch.png)\nOn branch {{shell:1}}, see !{}.\n"
+        );
+
+        let outputs = vec!["+fn main() {}\n".to_string(), "main\n".to_string()];
+        assert_eq!(
+            fill_shell_snippets(&rendered, &snippets, &outputs),
+            "Review these changes:\n```\n$ git diff --staged\n+fn main() {}\n```\n![diagram](arch.png)\nOn branch main, see !{}.\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_arguments() {
+        assert_eq!(
+            parse_arguments(r#"staging "two words" 'it''s' x"#).unwrap(),
+            vec!["staging", "two words", "its", "x"]
+        );
+        assert_eq!(parse_arguments(r#"a "" b"#).unwrap(), vec!["a", "", "b"]);
+        assert!(parse_arguments("\"unterminated").is_err());
     }
 }
@@ -0,0 +1,312 @@
+//! Newline-delimited JSON events for `--json-events`
+//!
+//! With the flag, `run`, `chat` and `orchestrate` print one JSON object per
+//! line on stdout instead of their usual text, so editors and scripts can
+//! follow a run without scraping it. Each object has a `type`: `message`,
+//! `tool_start`, `tool_result`, `diff`, `cost` or `done`. Logs go to stderr.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::Write;
+
+use crate::orchestrator::WorkerEvent;
+use crate::persistence::usage::estimate_cost;
+use crate::session::SessionEvent;
+
+/// One line of the event stream
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JsonEvent {
+    /// Text from the assistant, the user or an orchestrator worker
+    Message {
+        role: String,
+        text: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        task_id: Option<String>,
+    },
+    /// A tool call (or orchestrator task) started
+    ToolStart {
+        name: String,
+        description: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        task_id: Option<String>,
+    },
+    /// A tool call (or orchestrator task) finished
+    ToolResult {
+        name: String,
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        output: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        task_id: Option<String>,
+    },
+    /// Unified diff of one changed file
+    Diff {
+        path: String,
+        patch: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        task_id: Option<String>,
+    },
+    /// Tokens used so far and their estimated price
+    Cost {
+        model: String,
+        input_tokens: usize,
+        output_tokens: usize,
+        cost_usd: f64,
+    },
+    /// The command (or one chat turn) finished
+    Done {
+        success: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        result: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+}
+
+impl JsonEvent {
+    pub fn done(result: &anyhow::Result<String>) -> Self {
+        match result {
+            Ok(result) => Self::Done {
+                success: true,
+                result: Some(result.clone()),
+                error: None,
+            },
+            Err(e) => Self::Done {
+                success: false,
+                result: None,
+                error: Some(format!("{:#}", e)),
+            },
+        }
+    }
+
+    /// Write the event to stdout as a single line
+    pub fn emit(&self) {
+        let mut stdout = std::io::stdout().lock();
+        if let Ok(line) = serde_json::to_string(self) {
+            let _ = writeln!(stdout, "{}", line);
+            let _ = stdout.flush();
+        }
+    }
+}
+
+/// Turns session events into JSON events, adding up token usage for the
+/// `cost` event
+pub struct SessionEventMapper {
+    model: String,
+    input_tokens: usize,
+    output_tokens: usize,
+    /// Output of tools that have not completed yet
+    pending_output: HashMap<String, String>,
+}
+
+impl SessionEventMapper {
+    pub fn new(model: impl Into<String>) -> Self {
+        Self {
+            model: model.into(),
+            input_tokens: 0,
+            output_tokens: 0,
+            pending_output: HashMap::new(),
+        }
+    }
+
+    pub fn map(&mut self, event: SessionEvent) -> Option<JsonEvent> {
+        match event {
+            SessionEvent::TextChunk(text) | SessionEvent::Reasoning(text) => {
+                Some(JsonEvent::Message {
+                    role: "assistant".to_string(),
+                    text,
+                    task_id: None,
+                })
+            }
+            SessionEvent::ToolStart { name, description } => Some(JsonEvent::ToolStart {
+                name,
+                description,
+                task_id: None,
+            }),
+            SessionEvent::ToolOutput { name, output } => {
+                self.pending_output.insert(name, output);
+                None
+            }
+            SessionEvent::ToolComplete { name, success } => {
+                let output = self.pending_output.remove(&name);
+                Some(JsonEvent::ToolResult {
+                    name,
+                    success,
+                    output,
+                    task_id: None,
+                })
+            }
+            SessionEvent::FileDiff {
+                path,
+                old_content,
+                new_content,
+            } => {
+                let patch = similar::TextDiff::from_lines(&old_content, &new_content)
+                    .unified_diff()
+                    .context_radius(3)
+                    .header(&path, &path)
+                    .to_string();
+                Some(JsonEvent::Diff {
+                    path,
+                    patch,
+                    task_id: None,
+                })
+            }
+            SessionEvent::TokenUsage {
+                input_tokens,
+                output_tokens,
+                ..
+            } => {
+                self.input_tokens += input_tokens;
+                self.output_tokens += output_tokens;
+                None
+            }
+            // Nobody can answer prompts on a stream; decline so the run goes on
+            SessionEvent::ToolApprovalPrompt { response_tx, .. }
+            | SessionEvent::DoomLoopPrompt { response_tx, .. } => {
+                let _ = response_tx.send(false);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// Tokens used since the mapper was created
+    pub fn cost(&self) -> JsonEvent {
+        JsonEvent::Cost {
+            model: self.model.clone(),
+            input_tokens: self.input_tokens,
+            output_tokens: self.output_tokens,
+            cost_usd: estimate_cost(&self.model, self.input_tokens, self.output_tokens),
+        }
+    }
+}
+
+/// JSON event for an orchestrator worker event
+pub fn from_worker_event(event: WorkerEvent) -> JsonEvent {
+    match event {
+        WorkerEvent::Started {
+            task_id,
+            description,
+            worker,
+        } => JsonEvent::ToolStart {
+            name: format!("{:?}", worker),
+            description,
+            task_id: Some(task_id),
+        },
+        WorkerEvent::OutputLine { task_id, line } => JsonEvent::Message {
+            role: "worker".to_string(),
+            text: line,
+            task_id: Some(task_id),
+        },
+        WorkerEvent::ErrorLine { task_id, line } => JsonEvent::Message {
+            role: "worker_stderr".to_string(),
+            text: line,
+            task_id: Some(task_id),
+        },
+        WorkerEvent::Completed { task_id, output } => JsonEvent::ToolResult {
+            name: "worker".to_string(),
+            success: true,
+            output: Some(output),
+            task_id: Some(task_id),
+        },
+        WorkerEvent::Failed { task_id, error } => JsonEvent::ToolResult {
+            name: "worker".to_string(),
+            success: false,
+            output: Some(error),
+            task_id: Some(task_id),
+        },
+    }
+}
+
+/// Split a multi-file git patch into one `(path, patch)` pair per file
+pub fn split_patch(patch: &str) -> Vec<(String, String)> {
+    let mut files: Vec<(String, String)> = Vec::new();
+    for line in patch.split_inclusive('\n') {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            let path = header
+                .trim_end()
+                .rsplit_once(" b/")
+                .map(|(_, path)| path.to_string())
+                .unwrap_or_else(|| header.trim_end().to_string());
+            files.push((path, String::new()));
+        }
+        if let Some((_, file_patch)) = files.last_mut() {
+            file_patch.push_str(line);
+        }
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_events_to_json() {
+        let mut mapper = SessionEventMapper::new("claude-sonnet-4");
+        assert_eq!(
+            mapper.map(SessionEvent::ToolOutput {
+                name: "bash".into(),
+                output: "ok".into(),
+            }),
+            None
+        );
+        let result = mapper
+            .map(SessionEvent::ToolComplete {
+                name: "bash".into(),
+                success: true,
+            })
+            .unwrap();
+        assert_eq!(
+            serde_json::to_string(&result).unwrap(),
+            r#"{"type":"tool_result","name":"bash","success":true,"output":"ok"}"#
+        );
+
+        mapper.map(SessionEvent::TokenUsage {
+            input_tokens: 1000,
+            output_tokens: 10,
+            cache_read_tokens: None,
+            cache_creation_tokens: None,
+        });
+        mapper.map(SessionEvent::TokenUsage {
+            input_tokens: 500,
+            output_tokens: 5,
+            cache_read_tokens: None,
+            cache_creation_tokens: None,
+        });
+        match mapper.cost() {
+            JsonEvent::Cost {
+                input_tokens,
+                output_tokens,
+                cost_usd,
+                ..
+            } => {
+                assert_eq!((input_tokens, output_tokens), (1500, 15));
+                assert!(cost_usd > 0.0);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        let done = serde_json::to_string(&JsonEvent::done(&Err(anyhow::anyhow!("boom")))).unwrap();
+        assert_eq!(done, r#"{"type":"done","success":false,"error":"boom"}"#);
+    }
+
+    #[test]
+    fn test_split_patch() {
+        let patch = "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-x\n+y\n\
+                     diff --git a/b.rs b/b.rs\nnew file mode 100644\n";
+        let files = split_patch(patch);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].0, "a.rs");
+        assert!(files[0].1.ends_with("+y\n"));
+        assert_eq!(
+            files[1],
+            (
+                "b.rs".to_string(),
+                "diff --git a/b.rs b/b.rs\nnew file mode 100644\n".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,248 @@
+//! Structured unified diffs
+//!
+//! Parses `git diff` output into files, hunks and numbered lines, for
+//! clients that render their own review screens instead of raw patches.
+
+use serde::{Deserialize, Serialize};
+
+/// One file's changes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileDiff {
+    pub path: String,
+    /// Previous path of a renamed file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_path: Option<String>,
+    /// "created", "modified", "deleted" or "renamed"
+    pub change_type: String,
+    pub additions: usize,
+    pub deletions: usize,
+    /// Binary files have no hunks
+    pub binary: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// A `@@ -a,b +c,d @@` block
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffHunk {
+    pub header: String,
+    pub old_start: usize,
+    pub old_lines: usize,
+    pub new_start: usize,
+    pub new_lines: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String,
+    /// Line number before the change; None for added lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_line: Option<usize>,
+    /// Line number after the change; None for removed lines
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_line: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+/// Parse the output of `git diff`
+pub fn parse_unified_diff(patch: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    // Next line numbers in the current hunk
+    let (mut old_line, mut new_line) = (0, 0);
+
+    for line in patch.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            let path = rest
+                .rsplit_once(" b/")
+                .map_or(rest, |(_, path)| path)
+                .to_string();
+            files.push(FileDiff {
+                path,
+                old_path: None,
+                change_type: "modified".to_string(),
+                additions: 0,
+                deletions: 0,
+                binary: false,
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if let Some(hunk) = file.hunks.last_mut().filter(|_| !line.starts_with("@@")) {
+            let (kind, content) = match line.split_at(line.len().min(1)) {
+                ("+", content) => (DiffLineKind::Added, content),
+                ("-", content) => (DiffLineKind::Removed, content),
+                (" ", content) | ("", content) => (DiffLineKind::Context, content),
+                // "\ No newline at end of file"
+                _ => continue,
+            };
+            let numbers = match kind {
+                DiffLineKind::Added => {
+                    file.additions += 1;
+                    new_line += 1;
+                    (None, Some(new_line - 1))
+                }
+                DiffLineKind::Removed => {
+                    file.deletions += 1;
+                    old_line += 1;
+                    (Some(old_line - 1), None)
+                }
+                DiffLineKind::Context => {
+                    old_line += 1;
+                    new_line += 1;
+                    (Some(old_line - 1), Some(new_line - 1))
+                }
+            };
+            hunk.lines.push(DiffLine {
+                kind,
+                content: content.to_string(),
+                old_line: numbers.0,
+                new_line: numbers.1,
+            });
+            continue;
+        }
+
+        if let Some(hunk) = parse_hunk_header(line) {
+            old_line = hunk.old_start;
+            new_line = hunk.new_start;
+            file.hunks.push(hunk);
+        } else if line.starts_with("new file mode") {
+            file.change_type = "created".to_string();
+        } else if line.starts_with("deleted file mode") {
+            file.change_type = "deleted".to_string();
+        } else if let Some(from) = line.strip_prefix("rename from ") {
+            file.change_type = "renamed".to_string();
+            file.old_path = Some(from.to_string());
+        } else if let Some(to) = line.strip_prefix("rename to ") {
+            file.path = to.to_string();
+        } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+            file.binary = true;
+        } else if let Some(path) = line.strip_prefix("+++ b/") {
+            file.path = path.to_string();
+        }
+    }
+
+    files
+}
+
+/// `@@ -12,3 +12,4 @@ fn main()`; a missing count means one line
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let rest = line.strip_prefix("@@ -")?;
+    let (ranges, _) = rest.split_once(" @@")?;
+    let (old, new) = ranges.split_once(" +")?;
+    let range = |range: &str| -> Option<(usize, usize)> {
+        match range.split_once(',') {
+            Some((start, count)) => Some((start.parse().ok()?, count.parse().ok()?)),
+            None => Some((range.parse().ok()?, 1)),
+        }
+    };
+    let (old_start, old_lines) = range(old)?;
+    let (new_start, new_lines) = range(new)?;
+    Some(DiffHunk {
+        header: line.to_string(),
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines: Vec::new(),
+    })
+}
+
+/// One line per file with its added and removed line counts
+pub fn format_stat(files: &[FileDiff]) -> String {
+    let (additions, deletions) = files
+        .iter()
+        .fold((0, 0), |(a, d), f| (a + f.additions, d + f.deletions));
+    let mut out = format!(
+        "{} file(s) changed, +{} -{}\n",
+        files.len(),
+        additions,
+        deletions
+    );
+    for file in files {
+        let name = match &file.old_path {
+            Some(old) => format!("{} → {}", old, file.path),
+            None => file.path.clone(),
+        };
+        if file.binary {
+            out.push_str(&format!("  {} {} (binary)\n", file.change_type, name));
+        } else {
+            out.push_str(&format!(
+                "  {} {} +{} -{}\n",
+                file.change_type, name, file.additions, file.deletions
+            ));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let patch = "\
+diff --git a/src/main.rs b/src/main.rs
+index 1111111..2222222 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1,3 +1,3 @@ fn main() {
+ fn main() {
+-    println!(\"hi\");
++    println!(\"hello\");
+ }
+diff --git a/notes.txt b/notes.txt
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/notes.txt
+@@ -0,0 +1 @@
++todo
+\\ No newline at end of file
+diff --git a/old.rs b/new.rs
+similarity index 100%
+rename from old.rs
+rename to new.rs
+diff --git a/logo.png b/logo.png
+deleted file mode 100644
+Binary files a/logo.png and /dev/null differ
+";
+        let files = parse_unified_diff(patch);
+        assert_eq!(files.len(), 4);
+
+        let main = &files[0];
+        assert_eq!((main.path.as_str(), main.change_type.as_str()), ("src/main.rs", "modified"));
+        assert_eq!((main.additions, main.deletions), (1, 1));
+        let hunk = &main.hunks[0];
+        assert_eq!((hunk.old_start, hunk.old_lines, hunk.new_start, hunk.new_lines), (1, 3, 1, 3));
+        assert_eq!(hunk.lines.len(), 4);
+        assert_eq!(hunk.lines[1].kind, DiffLineKind::Removed);
+        assert_eq!((hunk.lines[1].old_line, hunk.lines[1].new_line), (Some(2), None));
+        assert_eq!((hunk.lines[2].old_line, hunk.lines[2].new_line), (None, Some(2)));
+        assert_eq!((hunk.lines[3].old_line, hunk.lines[3].new_line), (Some(3), Some(3)));
+
+        let notes = &files[1];
+        assert_eq!(notes.change_type, "created");
+        assert_eq!(notes.hunks[0].new_lines, 1);
+        assert_eq!(notes.hunks[0].lines.len(), 1);
+
+        assert_eq!(files[2].change_type, "renamed");
+        assert_eq!(files[2].old_path.as_deref(), Some("old.rs"));
+        assert_eq!(files[2].path, "new.rs");
+
+        assert_eq!(files[3].change_type, "deleted");
+        assert!(files[3].binary && files[3].hunks.is_empty());
+    }
+}
@@ -2,6 +2,10 @@ use anyhow::{Context, Result};
 
 use tokio::process::Command;
 
+pub mod diff;
+
+use diff::FileDiff;
+
 pub struct GitManager {
     repo_path: std::path::PathBuf,
     /// Stack of commit hashes for redo functionality
@@ -317,6 +321,55 @@ impl GitManager {
         Ok(())
     }
 
+    /// Record the working tree, untracked files included, as a tree object.
+    /// A scratch index is used, so the real index and history are untouched.
+    pub async fn snapshot_tree(&self) -> Result<String> {
+        let scratch = tempfile::tempdir()?;
+        let index = scratch.path().join("index");
+        // Starting from the real index saves re-hashing unchanged files
+        let real_index = self.git(&["rev-parse", "--git-path", "index"], None).await?;
+        let real_index = self.repo_path.join(real_index.trim());
+        if real_index.exists() {
+            tokio::fs::copy(&real_index, &index).await?;
+        }
+        self.git(&["add", "-A", "."], Some(&index)).await?;
+        let tree = self.git(&["write-tree"], Some(&index)).await?;
+        Ok(tree.trim().to_string())
+    }
+
+    /// Changes since a `snapshot_tree`, with paths relative to the project
+    pub async fn diff_since(&self, tree: &str) -> Result<(String, Vec<FileDiff>)> {
+        let current = self.snapshot_tree().await?;
+        let patch = self
+            .git(
+                &["-c", "core.quotePath=false", "diff", "--find-renames", "--relative", tree, &current],
+                None,
+            )
+            .await?;
+        let files = diff::parse_unified_diff(&patch);
+        Ok((patch, files))
+    }
+
+    async fn git(&self, args: &[&str], index: Option<&std::path::Path>) -> Result<String> {
+        let mut command = Command::new("git");
+        command.args(args).current_dir(&self.repo_path);
+        if let Some(index) = index {
+            command.env("GIT_INDEX_FILE", index);
+        }
+        let output = command
+            .output()
+            .await
+            .with_context(|| format!("Failed to run git {}", args.join(" ")))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "git {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
     /// Get summary of changes for approval
     pub async fn get_change_summary(&self) -> Result<ChangeSummary> {
         let status = self.status().await?;
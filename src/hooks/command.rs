@@ -0,0 +1,276 @@
+//! Command Hooks
+//!
+//! Runs a user-configured shell command when a hook event fires. The event
+//! payload is written to the command's stdin as JSON, and the exit code and
+//! stdout decide whether the action continues, is blocked, or is modified.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+
+use super::config::HookCommandConfig;
+use super::matcher::HookMatcher;
+use super::types::{Hook, HookContext, HookResult, HookType};
+
+/// Exit code a hook command uses to block the action
+const BLOCK_EXIT_CODE: i32 = 2;
+
+/// Structured output a hook may return (command stdout or webhook response)
+#[derive(Debug, Default, Deserialize)]
+struct HookOutput {
+    /// "block" to stop the action, anything else continues
+    #[serde(default)]
+    decision: Option<String>,
+    /// Reason shown when blocking
+    #[serde(default)]
+    reason: Option<String>,
+    /// Message to surface as a warning
+    #[serde(default)]
+    message: Option<String>,
+    /// Replacement tool parameters (pre_tool hooks)
+    #[serde(default)]
+    tool_input: Option<serde_json::Value>,
+    /// Replacement user message (pre_message hooks)
+    #[serde(default)]
+    content: Option<String>,
+}
+
+/// Hook backed by a shell command
+pub struct CommandHook {
+    name: String,
+    hook_types: [HookType; 1],
+    matcher: HookMatcher,
+    command: String,
+    timeout_secs: u64,
+    working_dir: PathBuf,
+}
+
+impl CommandHook {
+    pub fn new(hook_type: HookType, config: &HookCommandConfig, working_dir: PathBuf) -> Self {
+        Self {
+            name: config
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("{}: {}", hook_type.display_name(), config.command)),
+            hook_types: [hook_type],
+            matcher: HookMatcher::new(&config.matcher),
+            command: config.command.clone(),
+            timeout_secs: config.timeout_secs,
+            working_dir,
+        }
+    }
+
+    /// Interpret the command's exit code and output
+    fn interpret(&self, code: Option<i32>, stdout: &str, stderr: &str) -> HookResult {
+        let stderr = stderr.trim();
+
+        if code == Some(BLOCK_EXIT_CODE) {
+            let reason = if stderr.is_empty() {
+                "blocked by hook command".to_string()
+            } else {
+                stderr.to_string()
+            };
+            return HookResult::Block(reason);
+        }
+
+        if code != Some(0) {
+            let status = code.map_or("signal".to_string(), |c| c.to_string());
+            return HookResult::ContinueWithWarning(format!(
+                "hook command failed (exit {}): {}",
+                status, stderr
+            ));
+        }
+
+        interpret_output(self.hook_types[0], stdout)
+    }
+}
+
+/// Interpret the output of a successful hook (command stdout or webhook
+/// response body): empty continues, plain text is a warning, and JSON may
+/// block the action or replace its input.
+pub(super) fn interpret_output(hook_type: HookType, output: &str) -> HookResult {
+    let output = output.trim();
+    if output.is_empty() {
+        return HookResult::Continue;
+    }
+
+    let parsed: HookOutput = match serde_json::from_str(output) {
+        Ok(parsed) => parsed,
+        Err(_) => {
+            // Plain text output is feedback for the user/model
+            return HookResult::ContinueWithWarning(output.to_string());
+        }
+    };
+
+    if parsed.decision.as_deref() == Some("block") {
+        return HookResult::Block(
+            parsed
+                .reason
+                .unwrap_or_else(|| "blocked by hook".to_string()),
+        );
+    }
+
+    let replacement = match hook_type {
+        HookType::PreToolUse => parsed.tool_input.map(|v| v.to_string()),
+        _ => parsed.content,
+    };
+    if let Some(content) = replacement {
+        return HookResult::Modify {
+            content,
+            message: parsed.message,
+        };
+    }
+
+    match parsed.message.or(parsed.reason) {
+        Some(message) => HookResult::ContinueWithWarning(message),
+        None => HookResult::Continue,
+    }
+}
+
+#[async_trait]
+impl Hook for CommandHook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn hook_types(&self) -> &[HookType] {
+        &self.hook_types
+    }
+
+    fn matches(&self, ctx: &HookContext) -> bool {
+        self.matcher.matches_context(ctx)
+    }
+
+    fn description(&self) -> &str {
+        &self.command
+    }
+
+    async fn execute(&self, ctx: &HookContext) -> HookResult {
+        let payload = match serde_json::to_string(&ctx.payload()) {
+            Ok(p) => p,
+            Err(e) => {
+                return HookResult::ContinueWithWarning(format!("invalid hook payload: {}", e))
+            }
+        };
+
+        let mut cmd = crate::platform::shell_command(&self.command);
+        cmd.current_dir(&self.working_dir)
+            .env("SAFE_CODER_HOOK_EVENT", ctx.hook_type.event_name())
+            .env("SAFE_CODER_PROJECT_DIR", &self.working_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(ref tool) = ctx.tool_name {
+            cmd.env("SAFE_CODER_TOOL_NAME", tool);
+        }
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                return HookResult::ContinueWithWarning(format!(
+                    "failed to run hook command: {}",
+                    e
+                ))
+            }
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            // The command may exit without reading stdin; that's fine
+            let _ = stdin.write_all(payload.as_bytes()).await;
+        }
+
+        let timeout = std::time::Duration::from_secs(self.timeout_secs);
+        match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(Ok(output)) => self.interpret(
+                output.status.code(),
+                &String::from_utf8_lossy(&output.stdout),
+                &String::from_utf8_lossy(&output.stderr),
+            ),
+            Ok(Err(e)) => HookResult::ContinueWithWarning(format!("hook command failed: {}", e)),
+            Err(_) => HookResult::ContinueWithWarning(format!(
+                "hook command timed out after {}s",
+                self.timeout_secs
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook(hook_type: HookType, matcher: &str, command: &str) -> CommandHook {
+        let mut config = HookCommandConfig::command(command).with_matcher(matcher);
+        config.timeout_secs = 5;
+        CommandHook::new(hook_type, &config, std::env::temp_dir())
+    }
+
+    #[test]
+    fn test_matches_tool_events_only() {
+        let h = hook(HookType::PreToolUse, "bash", "true");
+        let bash = HookContext::for_tool(HookType::PreToolUse, "bash", None);
+        let edit = HookContext::for_tool(HookType::PreToolUse, "edit_file", None);
+        assert!(h.matches(&bash));
+        assert!(!h.matches(&edit));
+
+        let msg = hook(HookType::PreMessage, "bash", "true");
+        assert!(msg.matches(&HookContext::for_message(HookType::PreMessage, "hi")));
+    }
+
+    #[tokio::test]
+    async fn test_exit_code_block() {
+        let h = hook(HookType::PreToolUse, "", "echo 'no rm allowed' >&2; exit 2");
+        let ctx = HookContext::for_tool(
+            HookType::PreToolUse,
+            "bash",
+            Some(serde_json::json!({"command": "rm -rf build"})),
+        );
+        match h.execute(&ctx).await {
+            HookResult::Block(reason) => assert_eq!(reason, "no rm allowed"),
+            other => panic!("expected block, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_modify_tool_input() {
+        // Only rewrites the call if the payload names the expected tool
+        let h = hook(
+            HookType::PreToolUse,
+            "bash",
+            r#"grep -q '"tool_name":"bash"' && echo '{"tool_input": {"command": "ls -la"}}'"#,
+        );
+        let ctx = HookContext::for_tool(
+            HookType::PreToolUse,
+            "bash",
+            Some(serde_json::json!({"command": "ls"})),
+        );
+        match h.execute(&ctx).await {
+            HookResult::Modify { content, .. } => {
+                let input: serde_json::Value = serde_json::from_str(&content).unwrap();
+                assert_eq!(input["command"], "ls -la");
+            }
+            other => panic!("expected modify, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_interpret_output() {
+        let h = hook(HookType::PostToolUse, "", "true");
+        assert!(matches!(h.interpret(Some(0), "", ""), HookResult::Continue));
+        assert!(matches!(
+            h.interpret(Some(0), "formatted 2 files", ""),
+            HookResult::ContinueWithWarning(_)
+        ));
+        assert!(matches!(
+            h.interpret(Some(0), r#"{"decision": "block", "reason": "nope"}"#, ""),
+            HookResult::Block(_)
+        ));
+        assert!(matches!(
+            h.interpret(Some(1), "", "boom"),
+            HookResult::ContinueWithWarning(_)
+        ));
+    }
+}
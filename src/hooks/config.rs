@@ -0,0 +1,242 @@
+//! Hook Configuration
+//!
+//! User-defined hooks are configured per event under `hooks`, e.g.:
+//!
+//! ```toml
+//! [[hooks.pre_tool]]
+//! matcher = "bash"
+//! command = "./scripts/check-command.sh"
+//!
+//! [[hooks.post_tool]]
+//! matcher = "edit_file|write_file"
+//! command = "cargo fmt"
+//!
+//! [hooks.format]
+//! enabled = true
+//!
+//! [[hooks.format.linters]]
+//! pattern = "*.rs"
+//! command = "cargo clippy --quiet"
+//!
+//! [[hooks.post_message]]
+//! url = "https://hooks.example.com/safe-coder"
+//! secret = "shared-signing-secret"
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::format::FormatHookConfig;
+use super::types::HookType;
+
+/// Configuration for lifecycle hooks
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HooksConfig {
+    /// Whether hooks run at all
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Whether the built-in hooks (comment checker, todo enforcer, ...) are registered
+    #[serde(default = "default_true")]
+    pub builtins: bool,
+    /// Formatter/linter run after file edits
+    #[serde(default)]
+    pub format: FormatHookConfig,
+    /// User-defined hooks, keyed by event (pre_tool, post_tool, pre_message, ...)
+    #[serde(flatten)]
+    pub events: HashMap<HookType, Vec<HookCommandConfig>>,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            builtins: true,
+            format: FormatHookConfig::default(),
+            events: HashMap::new(),
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Total number of user-defined hooks
+    pub fn hook_count(&self) -> usize {
+        self.events.values().map(Vec::len).sum()
+    }
+}
+
+/// A hook run when an event fires: a shell command or an HTTP webhook.
+///
+/// A command receives the event payload as JSON on stdin. Exit code 2 blocks
+/// the action (stderr is the reason). On exit code 0, stdout may contain JSON
+/// like `{"decision": "block", "reason": "..."}` or, for pre_tool hooks,
+/// `{"tool_input": {...}}` to replace the tool parameters.
+///
+/// A webhook receives the same payload as a JSON POST body and may answer
+/// with the same JSON. Set either `command` or `url`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HookCommandConfig {
+    /// Optional name shown in hook listings (defaults to the command or URL)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Tool names or glob patterns separated by `|` (tool events only; empty = all)
+    #[serde(default)]
+    pub matcher: String,
+    /// Shell command to run
+    #[serde(default)]
+    pub command: String,
+    /// Webhook URL to POST the payload to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Secret used to sign webhook bodies (HMAC-SHA256, `X-Safe-Coder-Signature` header)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    /// Extra headers sent with webhook requests
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+    /// Retries for failed webhook deliveries (default: 2, at most 10)
+    #[serde(
+        default = "default_webhook_retries",
+        deserialize_with = "deserialize_webhook_retries"
+    )]
+    pub retries: u32,
+    /// Timeout in seconds (default: 30)
+    #[serde(default = "default_hook_timeout")]
+    pub timeout_secs: u64,
+}
+
+impl HookCommandConfig {
+    /// Create a config for a shell command hook
+    pub fn command(command: impl Into<String>) -> Self {
+        Self {
+            name: None,
+            matcher: String::new(),
+            command: command.into(),
+            url: None,
+            secret: None,
+            headers: HashMap::new(),
+            retries: default_webhook_retries(),
+            timeout_secs: default_hook_timeout(),
+        }
+    }
+
+    /// Create a config for a webhook hook
+    pub fn webhook(url: impl Into<String>) -> Self {
+        Self {
+            url: Some(url.into()),
+            ..Self::command("")
+        }
+    }
+
+    /// Set the tool matcher
+    pub fn with_matcher(mut self, matcher: impl Into<String>) -> Self {
+        self.matcher = matcher.into();
+        self
+    }
+
+    /// Check if this hook posts to a webhook rather than running a command
+    pub fn is_webhook(&self) -> bool {
+        self.url.is_some()
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_hook_timeout() -> u64 {
+    30
+}
+
+fn default_webhook_retries() -> u32 {
+    2
+}
+
+/// Most retries a webhook may be configured with
+pub const MAX_WEBHOOK_RETRIES: u32 = 10;
+
+fn deserialize_webhook_retries<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u32, D::Error> {
+    let retries = u32::deserialize(deserializer)?;
+    Ok(retries.min(MAX_WEBHOOK_RETRIES))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hooks_config_deserialization() {
+        let toml = r#"
+[[pre_tool]]
+matcher = "bash"
+command = "./check.sh"
+
+[[post_message]]
+command = "notify-send done"
+timeout_secs = 5
+
+[[session_stop]]
+url = "https://hooks.example.com/events"
+secret = "s3cret"
+retries = 0
+
+[[session_start]]
+url = "https://hooks.example.com/events"
+retries = 4000000000
+"#;
+        let config: HooksConfig = toml::from_str(toml).unwrap();
+        assert!(config.enabled);
+        assert!(config.builtins);
+        assert_eq!(config.hook_count(), 4);
+
+        let pre_tool = &config.events[&HookType::PreToolUse];
+        assert_eq!(pre_tool[0].matcher, "bash");
+        assert_eq!(pre_tool[0].timeout_secs, 30);
+        assert_eq!(config.events[&HookType::PostMessage][0].timeout_secs, 5);
+
+        let webhook = &config.events[&HookType::SessionEnd][0];
+        assert!(webhook.is_webhook());
+        assert_eq!(webhook.secret.as_deref(), Some("s3cret"));
+        assert_eq!(webhook.retries, 0);
+        assert_eq!(
+            config.events[&HookType::SessionStart][0].retries,
+            MAX_WEBHOOK_RETRIES
+        );
+        assert!(!pre_tool[0].is_webhook());
+    }
+
+    #[test]
+    fn test_event_names_match_config_keys() {
+        for hook_type in HookType::all() {
+            assert_eq!(
+                serde_json::to_value(hook_type).unwrap(),
+                hook_type.event_name()
+            );
+        }
+    }
+
+    #[test]
+    fn test_hooks_config_roundtrip() {
+        let mut config = HooksConfig::default();
+        let mut webhook = HookCommandConfig::webhook("https://hooks.example.com/events");
+        webhook.secret = Some("s3cret".to_string());
+        config.events.insert(
+            HookType::PostToolUse,
+            vec![
+                HookCommandConfig::command("cargo fmt").with_matcher("edit_file|write_file"),
+                webhook,
+            ],
+        );
+
+        let toml = toml::to_string(&config).unwrap();
+        let parsed: HooksConfig = toml::from_str(&toml).unwrap();
+        assert_eq!(parsed, config);
+    }
+
+    #[test]
+    fn test_unknown_event_rejected() {
+        let json = r#"{"pre_tools": [{"command": "true"}]}"#;
+        assert!(serde_json::from_str::<HooksConfig>(json).is_err());
+    }
+}
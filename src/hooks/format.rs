@@ -0,0 +1,371 @@
+//! Formatter/Linter Hook
+//!
+//! Built-in post_tool hook that runs the project's formatter and configured
+//! linters on files touched by `edit_file`/`write_file`. Formatters are
+//! auto-detected per file type (rustfmt, prettier, black/ruff, gofmt) unless
+//! overridden, and any linter output is fed back to the model with the tool
+//! result.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use super::types::{Hook, HookContext, HookResult, HookType};
+use crate::utils::truncate_str;
+
+/// Maximum characters of linter output reported per linter
+const MAX_DIAGNOSTIC_CHARS: usize = 2000;
+
+/// Configuration for the formatter/linter hook
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FormatHookConfig {
+    /// Whether to format/lint files after edits (default: false)
+    #[serde(default)]
+    pub enabled: bool,
+    /// Detect formatters from the project when no override is configured
+    #[serde(default = "default_true")]
+    pub auto_detect: bool,
+    /// Formatter commands by file extension, e.g. `rs = "rustfmt {file}"`
+    #[serde(default)]
+    pub formatters: HashMap<String, String>,
+    /// Linters run after formatting
+    #[serde(default)]
+    pub linters: Vec<LinterConfig>,
+    /// Timeout for each formatter/linter run in seconds (default: 30)
+    #[serde(default = "default_format_timeout")]
+    pub timeout_secs: u64,
+}
+
+impl Default for FormatHookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            auto_detect: true,
+            formatters: HashMap::new(),
+            linters: Vec::new(),
+            timeout_secs: default_format_timeout(),
+        }
+    }
+}
+
+/// A linter run on touched files matching a glob
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LinterConfig {
+    /// Name shown with the linter's diagnostics (defaults to the command)
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Glob matched against the project-relative path, e.g. `*.rs` or `src/**/*.ts`
+    pub pattern: String,
+    /// Command to run; `{file}` is replaced with the file path (appended if absent)
+    pub command: String,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_format_timeout() -> u64 {
+    30
+}
+
+/// Output of a formatter or linter run
+struct RunOutput {
+    success: bool,
+    output: String,
+}
+
+/// Hook that formats and lints files after they are edited
+pub struct FormatHook {
+    config: FormatHookConfig,
+    working_dir: PathBuf,
+}
+
+impl FormatHook {
+    pub fn new(config: FormatHookConfig, working_dir: PathBuf) -> Self {
+        Self {
+            config,
+            working_dir,
+        }
+    }
+
+    /// Formatter command for a file, from configuration or auto-detection
+    fn formatter_for(&self, path: &Path) -> Option<String> {
+        let ext = path.extension()?.to_str()?;
+        if let Some(command) = self.config.formatters.get(ext) {
+            return Some(command.clone());
+        }
+        if !self.config.auto_detect {
+            return None;
+        }
+
+        match ext {
+            "rs" if which::which("rustfmt").is_ok() => Some(format!(
+                "rustfmt --edition {} {{file}}",
+                self.rust_edition()
+            )),
+            "js" | "jsx" | "ts" | "tsx" | "mjs" | "cjs" | "json" | "css" | "scss" | "html"
+            | "vue" | "md" | "yaml" | "yml" => {
+                let prettier = self.working_dir.join("node_modules/.bin/prettier");
+                prettier.exists().then(|| {
+                    format!(
+                        "{} --write {{file}}",
+                        shell_quote(&prettier.to_string_lossy())
+                    )
+                })
+            }
+            "py" | "pyi" => {
+                if which::which("black").is_ok() {
+                    Some("black -q {file}".to_string())
+                } else if which::which("ruff").is_ok() {
+                    Some("ruff format -q {file}".to_string())
+                } else {
+                    None
+                }
+            }
+            "go" if which::which("gofmt").is_ok() => Some("gofmt -w {file}".to_string()),
+            _ => None,
+        }
+    }
+
+    /// Rust edition from the project's Cargo.toml (rustfmt doesn't read it)
+    fn rust_edition(&self) -> String {
+        std::fs::read_to_string(self.working_dir.join("Cargo.toml"))
+            .ok()
+            .and_then(|manifest| {
+                manifest.lines().find_map(|line| {
+                    let (key, value) = line.split_once('=')?;
+                    (key.trim() == "edition").then(|| value.trim().trim_matches('"').to_string())
+                })
+            })
+            .unwrap_or_else(|| "2021".to_string())
+    }
+
+    /// Linters whose pattern matches a project-relative path
+    fn linters_for(&self, relative: &Path) -> Vec<&LinterConfig> {
+        let file_name = relative.file_name().map(Path::new);
+        self.config
+            .linters
+            .iter()
+            .filter(|linter| match glob::Pattern::new(&linter.pattern) {
+                Ok(pattern) => {
+                    pattern.matches_path(relative)
+                        || (!linter.pattern.contains('/')
+                            && file_name.is_some_and(|f| pattern.matches_path(f)))
+                }
+                Err(e) => {
+                    tracing::warn!("Invalid linter pattern '{}': {}", linter.pattern, e);
+                    false
+                }
+            })
+            .collect()
+    }
+
+    /// Run a formatter/linter command on a file
+    async fn run(&self, command: &str, file: &Path) -> RunOutput {
+        let command = expand_file_placeholder(command, &file.to_string_lossy());
+        let child = crate::platform::shell_command(&command)
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn();
+
+        let child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                return RunOutput {
+                    success: false,
+                    output: format!("failed to run `{}`: {}", command, e),
+                }
+            }
+        };
+
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        match tokio::time::timeout(timeout, child.wait_with_output()).await {
+            Ok(Ok(output)) => {
+                let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+                text.push_str(&String::from_utf8_lossy(&output.stderr));
+                RunOutput {
+                    success: output.status.success(),
+                    output: text.trim().to_string(),
+                }
+            }
+            Ok(Err(e)) => RunOutput {
+                success: false,
+                output: format!("`{}` failed: {}", command, e),
+            },
+            Err(_) => RunOutput {
+                success: false,
+                output: format!(
+                    "`{}` timed out after {}s",
+                    command, self.config.timeout_secs
+                ),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Hook for FormatHook {
+    fn name(&self) -> &str {
+        "format_on_edit"
+    }
+
+    fn hook_types(&self) -> &[HookType] {
+        &[HookType::PostToolUse]
+    }
+
+    fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    fn matches(&self, ctx: &HookContext) -> bool {
+        matches!(ctx.tool_name.as_deref(), Some("edit_file" | "write_file"))
+            && ctx.tool_success != Some(false)
+    }
+
+    fn description(&self) -> &str {
+        "Runs the project's formatter and linters on edited files"
+    }
+
+    async fn execute(&self, ctx: &HookContext) -> HookResult {
+        let Some(file_path) = ctx
+            .tool_input
+            .as_ref()
+            .and_then(|input| input.get("file_path"))
+            .and_then(|v| v.as_str())
+        else {
+            return HookResult::Continue;
+        };
+
+        let full_path = self.working_dir.join(file_path);
+        if !full_path.is_file() {
+            return HookResult::Continue;
+        }
+        let relative = full_path
+            .strip_prefix(&self.working_dir)
+            .unwrap_or(&full_path)
+            .to_path_buf();
+
+        let mut notes = Vec::new();
+
+        if let Some(formatter) = self.formatter_for(&full_path) {
+            let before = std::fs::read_to_string(&full_path).ok();
+            let result = self.run(&formatter, &full_path).await;
+            if !result.success {
+                notes.push(format!(
+                    "Formatter failed on {}:\n{}",
+                    relative.display(),
+                    truncate_str(&result.output, MAX_DIAGNOSTIC_CHARS)
+                ));
+            } else if std::fs::read_to_string(&full_path).ok() != before {
+                notes.push(format!(
+                    "{} was reformatted; re-read it before editing it again.",
+                    relative.display()
+                ));
+            }
+        }
+
+        for linter in self.linters_for(&relative) {
+            let result = self.run(&linter.command, &full_path).await;
+            if !result.success {
+                let name = linter.name.as_deref().unwrap_or(&linter.command);
+                notes.push(format!(
+                    "{} reported issues in {}:\n{}",
+                    name,
+                    relative.display(),
+                    truncate_str(&result.output, MAX_DIAGNOSTIC_CHARS)
+                ));
+            }
+        }
+
+        if notes.is_empty() {
+            HookResult::Continue
+        } else {
+            HookResult::ContinueWithWarning(notes.join("\n\n"))
+        }
+    }
+}
+
+/// Replace `{file}` in a command with the quoted path, appending it if absent
+fn expand_file_placeholder(command: &str, file: &str) -> String {
+    let quoted = shell_quote(file);
+    if command.contains("{file}") {
+        command.replace("{file}", &quoted)
+    } else {
+        format!("{} {}", command, quoted)
+    }
+}
+
+/// Quote a string for use as a single shell word
+fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_file_placeholder() {
+        assert_eq!(
+            expand_file_placeholder("rustfmt {file}", "src/main.rs"),
+            "rustfmt 'src/main.rs'"
+        );
+        assert_eq!(
+            expand_file_placeholder("eslint", "it's.js"),
+            "eslint 'it'\\''s.js'"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_formats_and_reports_lint_output() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "hello\n").unwrap();
+
+        let config = FormatHookConfig {
+            enabled: true,
+            auto_detect: false,
+            formatters: HashMap::from([("txt".to_string(), "echo world >> {file}".to_string())]),
+            linters: vec![LinterConfig {
+                name: Some("no-world".to_string()),
+                pattern: "*.txt".to_string(),
+                command: "! grep -n world".to_string(),
+            }],
+            timeout_secs: 5,
+        };
+        let hook = FormatHook::new(config, dir.path().to_path_buf());
+
+        let ctx = HookContext::for_tool(
+            HookType::PostToolUse,
+            "edit_file",
+            Some(serde_json::json!({"file_path": "notes.txt"})),
+        )
+        .with_tool_result("ok", true);
+        assert!(hook.matches(&ctx));
+
+        match hook.execute(&ctx).await {
+            HookResult::ContinueWithWarning(msg) => {
+                assert!(msg.contains("notes.txt was reformatted"));
+                assert!(msg.contains("no-world reported issues"));
+                assert!(msg.contains("2:world"));
+            }
+            other => panic!("expected diagnostics, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_skips_failed_and_other_tools() {
+        let hook = FormatHook::new(FormatHookConfig::default(), PathBuf::from("."));
+        let failed = HookContext::for_tool(HookType::PostToolUse, "write_file", None)
+            .with_tool_result("Error", false);
+        let bash = HookContext::for_tool(HookType::PostToolUse, "bash", None);
+        assert!(!hook.matches(&failed));
+        assert!(!hook.matches(&bash));
+        assert!(!hook.is_enabled());
+    }
+}
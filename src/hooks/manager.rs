@@ -3,11 +3,28 @@
 //! Manages registration and execution of hooks.
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use super::builtin::{CommentCheckerHook, ContextMonitorHook, EditValidatorHook, TodoEnforcerHook};
+use super::command::CommandHook;
+use super::config::HooksConfig;
+use super::format::FormatHook;
 use super::types::{Hook, HookContext, HookResult, HookType};
+use super::webhook::WebhookHook;
+
+/// Outcome of running pre_tool hooks for a tool call
+#[derive(Debug, Clone, PartialEq)]
+pub enum PreToolDecision {
+    /// Run the tool with these (possibly modified) parameters
+    Proceed {
+        input: serde_json::Value,
+        warnings: Vec<String>,
+    },
+    /// Don't run the tool
+    Block(String),
+}
 
 /// Manages lifecycle hooks
 pub struct HookManager {
@@ -53,6 +70,60 @@ impl HookManager {
         }
     }
 
+    /// Create a hook manager from configuration, registering built-in hooks
+    /// (if enabled) and a command or webhook hook for each configured entry
+    pub fn from_config(config: &HooksConfig, working_dir: &Path) -> Self {
+        if !config.enabled {
+            return Self::new();
+        }
+
+        let mut hooks_map = if config.builtins {
+            Self::with_builtins().hooks.into_inner()
+        } else {
+            HashMap::new()
+        };
+
+        if config.format.enabled {
+            let hook: Arc<dyn Hook> = Arc::new(FormatHook::new(
+                config.format.clone(),
+                working_dir.to_path_buf(),
+            ));
+            hooks_map
+                .entry(HookType::PostToolUse)
+                .or_default()
+                .push(hook);
+        }
+
+        for hook_type in HookType::all() {
+            let Some(entries) = config.events.get(hook_type) else {
+                continue;
+            };
+            for entry in entries {
+                let hook: Arc<dyn Hook> = if let Some(ref url) = entry.url {
+                    Arc::new(WebhookHook::new(*hook_type, url, entry))
+                } else if !entry.command.trim().is_empty() {
+                    Arc::new(CommandHook::new(
+                        *hook_type,
+                        entry,
+                        working_dir.to_path_buf(),
+                    ))
+                } else {
+                    tracing::warn!(
+                        "Ignoring {} hook with neither a command nor a url",
+                        hook_type.event_name()
+                    );
+                    continue;
+                };
+                hooks_map.entry(*hook_type).or_default().push(hook);
+            }
+        }
+
+        Self {
+            hooks: RwLock::new(hooks_map),
+            disabled: RwLock::new(Vec::new()),
+        }
+    }
+
     /// Register a hook
     pub async fn register(&self, hook: Arc<dyn Hook>) {
         let mut hooks = self.hooks.write().await;
@@ -106,6 +177,11 @@ impl HookManager {
                 continue;
             }
 
+            // Skip hooks whose matcher doesn't apply
+            if !hook.matches(ctx) {
+                continue;
+            }
+
             let result = hook.execute(ctx).await;
 
             match &result {
@@ -146,6 +222,67 @@ impl HookManager {
         final_result
     }
 
+    /// Run pre_tool hooks for a tool call.
+    ///
+    /// Hooks run in registration order and each sees the parameters produced by
+    /// the previous one, so several hooks can rewrite the same call.
+    pub async fn run_pre_tool_use(
+        &self,
+        tool_name: &str,
+        input: &serde_json::Value,
+    ) -> PreToolDecision {
+        let hooks = self.hooks.read().await;
+        let disabled = self.disabled.read().await;
+
+        let mut input = input.clone();
+        let mut warnings = Vec::new();
+
+        let Some(type_hooks) = hooks.get(&HookType::PreToolUse) else {
+            return PreToolDecision::Proceed { input, warnings };
+        };
+
+        for hook in type_hooks {
+            if disabled.contains(&hook.name().to_string()) || !hook.is_enabled() {
+                continue;
+            }
+
+            let ctx = HookContext::for_tool(HookType::PreToolUse, tool_name, Some(input.clone()));
+            if !hook.matches(&ctx) {
+                continue;
+            }
+
+            match hook.execute(&ctx).await {
+                HookResult::Continue => {}
+                HookResult::ContinueWithWarning(msg) => {
+                    warnings.push(format!("[{}] {}", hook.name(), msg));
+                }
+                HookResult::Skip(msg) | HookResult::Block(msg) => {
+                    return PreToolDecision::Block(format!("[{}] {}", hook.name(), msg));
+                }
+                HookResult::Modify { content, message } => {
+                    match serde_json::from_str::<serde_json::Value>(&content) {
+                        Ok(new_input) if new_input.is_object() => input = new_input,
+                        _ => warnings.push(format!(
+                            "[{}] ignored modified parameters: not a JSON object",
+                            hook.name()
+                        )),
+                    }
+                    if let Some(msg) = message {
+                        warnings.push(format!("[{}] {}", hook.name(), msg));
+                    }
+                }
+            }
+        }
+
+        PreToolDecision::Proceed { input, warnings }
+    }
+
+    /// Check if any hooks are registered for a type
+    pub async fn has_hooks(&self, hook_type: HookType) -> bool {
+        let hooks = self.hooks.read().await;
+        hooks.get(&hook_type).is_some_and(|h| !h.is_empty())
+    }
+
     /// Execute hooks and get just warnings (for non-blocking scenarios)
     pub async fn execute_and_collect_warnings(&self, ctx: &HookContext) -> Vec<String> {
         let result = self.execute(ctx).await;
@@ -233,6 +370,74 @@ mod tests {
         assert!(!warnings.iter().any(|w| w.contains("comment")));
     }
 
+    struct RewriteHook {
+        name: &'static str,
+        matcher: &'static str,
+        result: HookResult,
+    }
+
+    #[async_trait::async_trait]
+    impl Hook for RewriteHook {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn hook_types(&self) -> &[HookType] {
+            &[HookType::PreToolUse]
+        }
+
+        fn matches(&self, ctx: &HookContext) -> bool {
+            ctx.tool_name.as_deref() == Some(self.matcher)
+        }
+
+        async fn execute(&self, _ctx: &HookContext) -> HookResult {
+            self.result.clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pre_tool_use_modify_and_block() {
+        let manager = HookManager::new();
+        manager
+            .register(Arc::new(RewriteHook {
+                name: "rewrite",
+                matcher: "bash",
+                result: HookResult::Modify {
+                    content: r#"{"command": "ls -la"}"#.to_string(),
+                    message: None,
+                },
+            }))
+            .await;
+        manager
+            .register(Arc::new(RewriteHook {
+                name: "guard",
+                matcher: "write_file",
+                result: HookResult::Block("read-only checkout".to_string()),
+            }))
+            .await;
+
+        let decision = manager
+            .run_pre_tool_use("bash", &serde_json::json!({"command": "ls"}))
+            .await;
+        assert_eq!(
+            decision,
+            PreToolDecision::Proceed {
+                input: serde_json::json!({"command": "ls -la"}),
+                warnings: vec![],
+            }
+        );
+
+        let decision = manager
+            .run_pre_tool_use("write_file", &serde_json::json!({"file_path": "a"}))
+            .await;
+        assert!(matches!(decision, PreToolDecision::Block(r) if r.contains("read-only")));
+
+        // Unmatched tools pass through untouched
+        let input = serde_json::json!({"file_path": "a"});
+        let decision = manager.run_pre_tool_use("read_file", &input).await;
+        assert!(matches!(decision, PreToolDecision::Proceed { input: i, .. } if i == input));
+    }
+
     #[tokio::test]
     async fn test_list_hooks() {
         let manager = HookManager::with_builtins();
@@ -0,0 +1,89 @@
+//! Hook Matchers
+//!
+//! Decide which tool calls a configured hook applies to. A matcher is a
+//! `|`-separated list of tool names or glob patterns, e.g. `bash`,
+//! `edit_file|write_file` or `mcp__github__*`. An empty matcher or `*`
+//! matches every tool.
+
+use glob::Pattern;
+
+use super::types::HookContext;
+
+/// Matches tool names against a hook's `matcher` setting
+#[derive(Debug, Clone)]
+pub struct HookMatcher {
+    /// Compiled alternatives
+    patterns: Vec<Pattern>,
+    /// Whether the matcher matches every tool
+    any: bool,
+}
+
+impl HookMatcher {
+    /// Parse a matcher string, ignoring alternatives that aren't valid globs
+    pub fn new(matcher: &str) -> Self {
+        let alternatives: Vec<&str> = matcher
+            .split('|')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .collect();
+
+        let any = alternatives.is_empty() || alternatives.contains(&"*");
+        let patterns = alternatives
+            .into_iter()
+            .filter_map(|p| match Pattern::new(p) {
+                Ok(pattern) => Some(pattern),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid hook matcher '{}': {}", p, e);
+                    None
+                }
+            })
+            .collect();
+
+        Self { patterns, any }
+    }
+
+    /// Check if a tool name matches
+    pub fn matches_tool(&self, tool_name: &str) -> bool {
+        self.any || self.patterns.iter().any(|p| p.matches(tool_name))
+    }
+
+    /// Check if a hook context matches. Matchers only filter tool events;
+    /// every other event always matches.
+    pub fn matches_context(&self, ctx: &HookContext) -> bool {
+        if !ctx.hook_type.is_tool_event() {
+            return true;
+        }
+        ctx.tool_name
+            .as_deref()
+            .is_some_and(|name| self.matches_tool(name))
+    }
+}
+
+impl Default for HookMatcher {
+    fn default() -> Self {
+        Self::new("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matcher() {
+        assert!(HookMatcher::new("").matches_tool("bash"));
+        assert!(HookMatcher::new("*").matches_tool("edit_file"));
+
+        let edits = HookMatcher::new("edit_file | write_file");
+        assert!(edits.matches_tool("edit_file"));
+        assert!(edits.matches_tool("write_file"));
+        assert!(!edits.matches_tool("bash"));
+
+        let mcp = HookMatcher::new("mcp__github__*");
+        assert!(mcp.matches_tool("mcp__github__create_issue"));
+        assert!(!mcp.matches_tool("mcp__slack__post"));
+
+        // Invalid patterns never match rather than matching everything
+        assert!(!HookMatcher::new("[").matches_tool("bash"));
+    }
+}
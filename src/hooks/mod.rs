@@ -5,9 +5,19 @@
 //! allowing users to inject custom logic, validation, or transformations.
 
 pub mod builtin;
+pub mod command;
+pub mod config;
+pub mod format;
 pub mod manager;
+pub mod matcher;
 pub mod types;
+pub mod webhook;
 
 pub use builtin::{CommentCheckerHook, ContextMonitorHook, TodoEnforcerHook};
-pub use manager::HookManager;
-pub use types::{Hook, HookContext, HookResult, HookType};
+pub use command::CommandHook;
+pub use config::{HookCommandConfig, HooksConfig};
+pub use format::{FormatHook, FormatHookConfig, LinterConfig};
+pub use manager::{HookManager, PreToolDecision};
+pub use matcher::HookMatcher;
+pub use types::{Hook, HookContext, HookPayload, HookResult, HookType};
+pub use webhook::WebhookHook;
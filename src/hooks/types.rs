@@ -4,23 +4,31 @@
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 
 /// Types of lifecycle hooks available
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum HookType {
-    /// Before a tool is executed
+    /// Before a tool is executed (can block the call or modify its parameters)
+    #[serde(alias = "pre_tool")]
     PreToolUse,
     /// After a tool completes execution
+    #[serde(alias = "post_tool")]
     PostToolUse,
     /// Before sending a prompt to the LLM
     PrePrompt,
     /// After receiving a response from the LLM
     PostResponse,
+    /// When the user sends a message (can block or rewrite it)
+    PreMessage,
+    /// After the final response to a user message
+    PostMessage,
     /// When a session starts
     SessionStart,
     /// When a session ends
+    #[serde(alias = "session_stop")]
     SessionEnd,
     /// Before file is written/edited
     PreFileWrite,
@@ -38,6 +46,8 @@ impl HookType {
             HookType::PostToolUse,
             HookType::PrePrompt,
             HookType::PostResponse,
+            HookType::PreMessage,
+            HookType::PostMessage,
             HookType::SessionStart,
             HookType::SessionEnd,
             HookType::PreFileWrite,
@@ -53,6 +63,8 @@ impl HookType {
             HookType::PostToolUse => "Post-Tool Use",
             HookType::PrePrompt => "Pre-Prompt",
             HookType::PostResponse => "Post-Response",
+            HookType::PreMessage => "Pre-Message",
+            HookType::PostMessage => "Post-Message",
             HookType::SessionStart => "Session Start",
             HookType::SessionEnd => "Session End",
             HookType::PreFileWrite => "Pre-File Write",
@@ -60,6 +72,82 @@ impl HookType {
             HookType::OnCompaction => "On Compaction",
         }
     }
+
+    /// Event name used in configuration and hook payloads
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            HookType::PreToolUse => "pre_tool_use",
+            HookType::PostToolUse => "post_tool_use",
+            HookType::PrePrompt => "pre_prompt",
+            HookType::PostResponse => "post_response",
+            HookType::PreMessage => "pre_message",
+            HookType::PostMessage => "post_message",
+            HookType::SessionStart => "session_start",
+            HookType::SessionEnd => "session_end",
+            HookType::PreFileWrite => "pre_file_write",
+            HookType::PostFileWrite => "post_file_write",
+            HookType::OnCompaction => "on_compaction",
+        }
+    }
+
+    /// Check if this hook type fires around a tool call (and so uses tool matchers)
+    pub fn is_tool_event(&self) -> bool {
+        matches!(self, HookType::PreToolUse | HookType::PostToolUse)
+    }
+}
+
+impl std::fmt::Display for HookType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display_name())
+    }
+}
+
+/// Typed payload for each hook event, serialized for external hooks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum HookPayload {
+    PreToolUse {
+        tool_name: String,
+        tool_input: Value,
+    },
+    PostToolUse {
+        tool_name: String,
+        tool_input: Value,
+        tool_output: String,
+        success: bool,
+    },
+    PrePrompt {
+        prompt: String,
+    },
+    PostResponse {
+        response: String,
+    },
+    PreMessage {
+        message: String,
+    },
+    PostMessage {
+        message: String,
+        response: String,
+    },
+    SessionStart {
+        session_id: Option<String>,
+        project_path: Option<String>,
+    },
+    SessionEnd {
+        session_id: Option<String>,
+        project_path: Option<String>,
+    },
+    PreFileWrite {
+        file_path: String,
+        content: Option<String>,
+    },
+    PostFileWrite {
+        file_path: String,
+        content: Option<String>,
+    },
+    OnCompaction {
+        token_usage: Option<TokenUsageInfo>,
+    },
 }
 
 /// Context passed to hooks during execution
@@ -79,8 +167,16 @@ pub struct HookContext {
     pub file_content: Option<String>,
     /// The prompt being sent (for PrePrompt)
     pub prompt: Option<String>,
-    /// The LLM response (for PostResponse)
+    /// The LLM response (for PostResponse and PostMessage)
     pub response: Option<String>,
+    /// The user message (for PreMessage and PostMessage)
+    pub message: Option<String>,
+    /// Whether the tool succeeded (for PostToolUse)
+    pub tool_success: Option<bool>,
+    /// Session ID (for session hooks)
+    pub session_id: Option<String>,
+    /// Project directory (for session hooks)
+    pub project_path: Option<String>,
     /// Current token usage
     pub token_usage: Option<TokenUsageInfo>,
     /// Additional metadata
@@ -99,6 +195,10 @@ impl HookContext {
             file_content: None,
             prompt: None,
             response: None,
+            message: None,
+            tool_success: None,
+            session_id: None,
+            project_path: None,
             token_usage: None,
             metadata: HashMap::new(),
         }
@@ -119,6 +219,10 @@ impl HookContext {
             file_content: None,
             prompt: None,
             response: None,
+            message: None,
+            tool_success: None,
+            session_id: None,
+            project_path: None,
             token_usage: None,
             metadata: HashMap::new(),
         }
@@ -135,17 +239,101 @@ impl HookContext {
             file_content: content.map(|s| s.to_string()),
             prompt: None,
             response: None,
+            message: None,
+            tool_success: None,
+            session_id: None,
+            project_path: None,
             token_usage: None,
             metadata: HashMap::new(),
         }
     }
 
+    /// Create context for a message hook
+    pub fn for_message(hook_type: HookType, message: &str) -> Self {
+        let mut ctx = Self::new(hook_type);
+        ctx.message = Some(message.to_string());
+        ctx
+    }
+
+    /// Create context for a session lifecycle hook
+    pub fn for_session(hook_type: HookType, session_id: Option<&str>, project_path: &str) -> Self {
+        let mut ctx = Self::new(hook_type);
+        ctx.session_id = session_id.map(|s| s.to_string());
+        ctx.project_path = Some(project_path.to_string());
+        ctx
+    }
+
     /// Add tool output (for PostToolUse)
     pub fn with_tool_output(mut self, output: &str) -> Self {
         self.tool_output = Some(output.to_string());
         self
     }
 
+    /// Add tool output and success status (for PostToolUse)
+    pub fn with_tool_result(mut self, output: &str, success: bool) -> Self {
+        self.tool_output = Some(output.to_string());
+        self.tool_success = Some(success);
+        self
+    }
+
+    /// Add the final response (for PostMessage)
+    pub fn with_response(mut self, response: &str) -> Self {
+        self.response = Some(response.to_string());
+        self
+    }
+
+    /// Build the typed payload for this context's event
+    pub fn payload(&self) -> HookPayload {
+        let text = |v: &Option<String>| v.clone().unwrap_or_default();
+        let tool_name = text(&self.tool_name);
+        let tool_input = self.tool_input.clone().unwrap_or(Value::Null);
+
+        match self.hook_type {
+            HookType::PreToolUse => HookPayload::PreToolUse {
+                tool_name,
+                tool_input,
+            },
+            HookType::PostToolUse => HookPayload::PostToolUse {
+                tool_name,
+                tool_input,
+                tool_output: text(&self.tool_output),
+                success: self.tool_success.unwrap_or(true),
+            },
+            HookType::PrePrompt => HookPayload::PrePrompt {
+                prompt: text(&self.prompt),
+            },
+            HookType::PostResponse => HookPayload::PostResponse {
+                response: text(&self.response),
+            },
+            HookType::PreMessage => HookPayload::PreMessage {
+                message: text(&self.message),
+            },
+            HookType::PostMessage => HookPayload::PostMessage {
+                message: text(&self.message),
+                response: text(&self.response),
+            },
+            HookType::SessionStart => HookPayload::SessionStart {
+                session_id: self.session_id.clone(),
+                project_path: self.project_path.clone(),
+            },
+            HookType::SessionEnd => HookPayload::SessionEnd {
+                session_id: self.session_id.clone(),
+                project_path: self.project_path.clone(),
+            },
+            HookType::PreFileWrite => HookPayload::PreFileWrite {
+                file_path: text(&self.file_path),
+                content: self.file_content.clone(),
+            },
+            HookType::PostFileWrite => HookPayload::PostFileWrite {
+                file_path: text(&self.file_path),
+                content: self.file_content.clone(),
+            },
+            HookType::OnCompaction => HookPayload::OnCompaction {
+                token_usage: self.token_usage.clone(),
+            },
+        }
+    }
+
     /// Add token usage information
     pub fn with_token_usage(mut self, usage: TokenUsageInfo) -> Self {
         self.token_usage = Some(usage);
@@ -160,7 +348,7 @@ impl HookContext {
 }
 
 /// Token usage information for hooks
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TokenUsageInfo {
     pub input_tokens: usize,
     pub output_tokens: usize,
@@ -247,6 +435,11 @@ pub trait Hook: Send + Sync {
         true
     }
 
+    /// Check if this hook applies to the given context (e.g. by tool name)
+    fn matches(&self, _ctx: &HookContext) -> bool {
+        true
+    }
+
     /// Execute the hook
     async fn execute(&self, ctx: &HookContext) -> HookResult;
 
@@ -0,0 +1,213 @@
+//! Webhook Hooks
+//!
+//! POSTs the event payload as JSON to an HTTP endpoint so events can be piped
+//! into chat, CI or observability systems. Bodies are optionally signed with
+//! HMAC-SHA256, and failed deliveries are retried with exponential backoff.
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use super::command::interpret_output;
+use super::config::HookCommandConfig;
+use super::matcher::HookMatcher;
+use super::types::{Hook, HookContext, HookResult, HookType};
+
+/// Header carrying the event name
+pub const EVENT_HEADER: &str = "X-Safe-Coder-Event";
+/// Header carrying the body signature (`sha256=<hex>`)
+pub const SIGNATURE_HEADER: &str = "X-Safe-Coder-Signature";
+
+/// Base delay between retries (doubled after each attempt)
+const RETRY_BASE_DELAY_MS: u64 = 500;
+/// Longest delay between retries
+const RETRY_MAX_DELAY_MS: u64 = 30_000;
+
+/// Hook that posts events to an HTTP endpoint
+pub struct WebhookHook {
+    name: String,
+    hook_types: [HookType; 1],
+    matcher: HookMatcher,
+    url: String,
+    secret: Option<String>,
+    headers: HashMap<String, String>,
+    retries: u32,
+    client: reqwest::Client,
+}
+
+impl WebhookHook {
+    pub fn new(hook_type: HookType, url: &str, config: &HookCommandConfig) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            name: config
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("{}: {}", hook_type.display_name(), url)),
+            hook_types: [hook_type],
+            matcher: HookMatcher::new(&config.matcher),
+            url: url.to_string(),
+            secret: config.secret.clone(),
+            headers: config.headers.clone(),
+            retries: config.retries,
+            client,
+        }
+    }
+
+    /// Send the body once, returning the response text or an error description
+    /// and whether the failure is worth retrying
+    async fn deliver(&self, event: &str, body: &str) -> Result<String, (String, bool)> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .header(EVENT_HEADER, event)
+            .body(body.to_string());
+
+        if let Some(ref secret) = self.secret {
+            request = request.header(SIGNATURE_HEADER, sign_payload(secret, body.as_bytes()));
+        }
+        for (key, value) in &self.headers {
+            request = request.header(key, value);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| (format!("request failed: {}", e), true))?;
+
+        let status = response.status();
+        let text = response.text().await.unwrap_or_default();
+        if status.is_success() {
+            return Ok(text);
+        }
+
+        let retryable = status.is_server_error() || status.as_u16() == 429;
+        Err((format!("endpoint returned {}", status), retryable))
+    }
+}
+
+#[async_trait]
+impl Hook for WebhookHook {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn hook_types(&self) -> &[HookType] {
+        &self.hook_types
+    }
+
+    fn matches(&self, ctx: &HookContext) -> bool {
+        self.matcher.matches_context(ctx)
+    }
+
+    fn description(&self) -> &str {
+        &self.url
+    }
+
+    async fn execute(&self, ctx: &HookContext) -> HookResult {
+        let body = match serde_json::to_string(&ctx.payload()) {
+            Ok(b) => b,
+            Err(e) => {
+                return HookResult::ContinueWithWarning(format!("invalid hook payload: {}", e))
+            }
+        };
+        let event = ctx.hook_type.event_name();
+
+        let mut attempt = 0;
+        loop {
+            match self.deliver(event, &body).await {
+                Ok(response) => return interpret_output(ctx.hook_type, &response),
+                Err((error, retryable)) => {
+                    if !retryable || attempt >= self.retries {
+                        tracing::warn!("Webhook {} failed: {}", self.url, error);
+                        return HookResult::ContinueWithWarning(format!(
+                            "webhook delivery failed after {} attempt(s): {}",
+                            attempt + 1,
+                            error
+                        ));
+                    }
+                    let delay = retry_delay_ms(attempt);
+                    tracing::debug!(
+                        "Webhook {} failed ({}), retrying in {}ms",
+                        self.url,
+                        error,
+                        delay
+                    );
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Backoff before retry number `attempt + 1`
+fn retry_delay_ms(attempt: u32) -> u64 {
+    let factor = 2u64.checked_pow(attempt).unwrap_or(u64::MAX);
+    factor
+        .saturating_mul(RETRY_BASE_DELAY_MS)
+        .min(RETRY_MAX_DELAY_MS)
+}
+
+/// Signature header value for a webhook body: `sha256=<hex hmac>`
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes keys of any length");
+    mac.update(body);
+    let hex: String = mac
+        .finalize()
+        .into_bytes()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    format!("sha256={}", hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload() {
+        // RFC 4231 test case 1
+        assert_eq!(
+            sign_payload(&"\x0b".repeat(20), b"Hi There"),
+            "sha256=b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign_payload("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        assert_eq!(retry_delay_ms(0), 500);
+        assert_eq!(retry_delay_ms(3), 4000);
+        assert_eq!(retry_delay_ms(10), RETRY_MAX_DELAY_MS);
+        assert_eq!(retry_delay_ms(100), RETRY_MAX_DELAY_MS);
+        assert_eq!(retry_delay_ms(u32::MAX), RETRY_MAX_DELAY_MS);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_endpoint_warns() {
+        let mut config = HookCommandConfig::webhook("http://127.0.0.1:9/hook");
+        config.retries = 1;
+        config.timeout_secs = 2;
+        let hook = WebhookHook::new(HookType::PostMessage, "http://127.0.0.1:9/hook", &config);
+
+        let ctx = HookContext::for_message(HookType::PostMessage, "hello");
+        match hook.execute(&ctx).await {
+            HookResult::ContinueWithWarning(msg) => assert!(msg.contains("2 attempt(s)")),
+            other => panic!("expected warning, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Editor attachment for sessions run by the server
+//!
+//! An editor extension registers the project it has open, the current file
+//! and the selection through `/api/ide/*`. Sessions in that project see them
+//! in their system prompt, and the `open_in_editor` tool asks the editor to
+//! show a file, delivered to the extension over `GET /api/ide/events`.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tokio::sync::broadcast;
+
+use crate::utils::truncate_str;
+
+/// Selected text is cut to this many characters in the system prompt
+const MAX_SELECTION_CHARS: usize = 4000;
+
+/// Lines `start_line..=end_line` (1-based) of the current file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Selection {
+    pub start_line: usize,
+    pub end_line: usize,
+    #[serde(default)]
+    pub text: String,
+}
+
+/// What an attached editor shows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EditorContext {
+    /// Editor name, e.g. `vscode` or `neovim`
+    pub editor: String,
+    /// Workspace folder open in the editor
+    pub project_path: PathBuf,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection: Option<Selection>,
+}
+
+/// Ask the editor of `project_path` to show `path`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenRequest {
+    pub project_path: PathBuf,
+    pub path: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+}
+
+lazy_static::lazy_static! {
+    static ref EDITORS: RwLock<HashMap<PathBuf, EditorContext>> = RwLock::new(HashMap::new());
+    static ref OPEN_REQUESTS: broadcast::Sender<OpenRequest> = broadcast::channel(64).0;
+}
+
+/// Register or update an editor; one editor per workspace folder
+pub fn attach(context: EditorContext) {
+    EDITORS
+        .write()
+        .unwrap()
+        .insert(context.project_path.clone(), context);
+}
+
+pub fn detach(project_path: &Path) -> bool {
+    EDITORS.write().unwrap().remove(project_path).is_some()
+}
+
+pub fn attached() -> Vec<EditorContext> {
+    EDITORS.read().unwrap().values().cloned().collect()
+}
+
+/// Editor attached to `project_path`, to a folder inside it or to one of
+/// its parents
+pub fn context_for(project_path: &Path) -> Option<EditorContext> {
+    EDITORS
+        .read()
+        .unwrap()
+        .values()
+        .find(|c| {
+            project_path.starts_with(&c.project_path) || c.project_path.starts_with(project_path)
+        })
+        .cloned()
+}
+
+/// Show `path` in the editor attached to `project_path`. Returns the
+/// editor's name.
+pub fn open_in_editor(project_path: &Path, path: &Path, line: Option<usize>) -> Result<String> {
+    let Some(context) = context_for(project_path) else {
+        anyhow::bail!("No editor is attached to {}", project_path.display());
+    };
+    OPEN_REQUESTS
+        .send(OpenRequest {
+            project_path: context.project_path,
+            path: path.to_path_buf(),
+            line,
+        })
+        .map_err(|_| anyhow::anyhow!("The {} extension is not listening", context.editor))?;
+    Ok(context.editor)
+}
+
+pub fn subscribe() -> broadcast::Receiver<OpenRequest> {
+    OPEN_REQUESTS.subscribe()
+}
+
+/// System prompt section describing what the user has open
+pub fn editor_prompt(project_path: &Path) -> Option<String> {
+    let context = context_for(project_path)?;
+    let file = context.file?;
+    let file = file.strip_prefix(project_path).unwrap_or(&file);
+
+    let mut prompt = format!(
+        "\n\n## Editor\nThe user has {} open in {}.",
+        file.display(),
+        context.editor
+    );
+    if let Some(selection) = context.selection.filter(|s| !s.text.trim().is_empty()) {
+        prompt.push_str(&format!(
+            " Lines {}-{} are selected; \"this\" or \"here\" in the request likely refers to them:\n```\n{}\n```",
+            selection.start_line,
+            selection.end_line,
+            truncate_str(&selection.text, MAX_SELECTION_CHARS)
+        ));
+    }
+    prompt.push_str("\nUse open_in_editor to point the user at a file or line.\n");
+    Some(prompt)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_editor_attachment() {
+        // The registry is global, so use paths no other test touches
+        let project = PathBuf::from("/tmp/ide-test-project");
+        assert!(editor_prompt(&project).is_none());
+        assert!(open_in_editor(&project, &project.join("a.rs"), None).is_err());
+
+        attach(EditorContext {
+            editor: "vscode".into(),
+            project_path: project.clone(),
+            file: Some(project.join("src/main.rs")),
+            selection: Some(Selection {
+                start_line: 3,
+                end_line: 4,
+                text: "fn main() {}".into(),
+            }),
+        });
+        let prompt = editor_prompt(&project.join("sub")).unwrap();
+        assert!(prompt.contains("src/main.rs open in vscode"));
+        assert!(prompt.contains("Lines 3-4 are selected"));
+        assert!(prompt.contains("fn main() {}"));
+
+        let mut requests = subscribe();
+        assert_eq!(
+            open_in_editor(&project, &project.join("src/lib.rs"), Some(10)).unwrap(),
+            "vscode"
+        );
+        let request = requests.try_recv().unwrap();
+        assert_eq!(request.path, project.join("src/lib.rs"));
+        assert_eq!(request.line, Some(10));
+
+        assert!(detach(&project));
+        assert!(context_for(&project).is_none());
+    }
+}
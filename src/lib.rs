@@ -1,33 +1,49 @@
 // Library exports for safe-coder
 // This allows the modules to be imported in tests and external code
 
+// The OpenAPI spec is one large `json!` literal
+#![recursion_limit = "256"]
+
+pub mod acp;
 pub mod approval;
 pub mod auth;
 pub mod cache;
 pub mod checkpoint;
 pub mod client;
 pub mod commands;
+pub mod completions;
 pub mod config;
 pub mod context;
+pub mod crash;
 pub mod custom_commands;
+pub mod events;
 pub mod git;
 pub mod hooks;
+pub mod ide;
 pub mod llm;
 pub mod loop_detector;
 pub mod lsp;
 pub mod mcp;
 pub mod memory;
+pub mod notifications;
 pub mod orchestrator;
+pub mod output_filters;
 pub mod permissions;
 pub mod persistence;
 pub mod planning;
+pub mod policy;
+pub mod platform;
 pub mod prompts;
 pub mod session;
+pub mod setup;
 pub mod shell;
+pub mod shutdown;
 pub mod skills;
 pub mod subagent;
+pub mod templates;
 pub mod tools;
 pub mod tui;
 pub mod unified_planning;
+pub mod update;
 pub mod server;
 pub mod utils;
@@ -3,9 +3,11 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
-use super::{ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use super::http::HttpSettings;
+use super::{rate_limit, Capabilities, ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
 use crate::auth::anthropic::{get_oauth_beta_headers, get_oauth_user_agent};
 use crate::auth::{StoredToken, TokenManager};
+use crate::config::{GenerationParams, LlmProvider, ReasoningConfig};
 
 /// Authentication type for the Anthropic client
 #[derive(Debug, Clone)]
@@ -33,8 +35,13 @@ pub struct AnthropicClient {
     model: String,
     max_tokens: usize,
     client: reqwest::Client,
+    http: HttpSettings,
     /// Enable Claude Code OAuth compatibility mode (injects system prompt)
     claude_code_compat: bool,
+    /// Extended thinking budget, if enabled
+    thinking_budget: Option<usize>,
+    /// Sampling parameters from `[llm.params]`
+    params: GenerationParams,
 }
 
 /// Cache control marker for prompt caching
@@ -72,6 +79,22 @@ struct AnthropicRequest {
     messages: Vec<AnthropicMessage>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     tools: Vec<AnthropicTool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<AnthropicThinking>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop_sequences: Vec<String>,
+}
+
+/// Extended thinking request settings
+#[derive(Debug, Serialize)]
+struct AnthropicThinking {
+    #[serde(rename = "type")]
+    thinking_type: String,
+    budget_tokens: usize,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -98,6 +121,14 @@ enum AnthropicContent {
         tool_use_id: String,
         content: String,
     },
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    /// Thinking flagged by safety systems, returned encrypted
+    RedactedThinking {
+        data: String,
+    },
 }
 
 /// Image source for Anthropic API (base64 format)
@@ -145,14 +176,33 @@ impl AnthropicClient {
             auth: AuthType::ApiKey(api_key),
             model,
             max_tokens,
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .build()
-                .unwrap_or_else(|_| reqwest::Client::new()),
+            client: HttpSettings::default().client(),
+            http: HttpSettings::default(),
             claude_code_compat: false,
+            thinking_budget: None,
+            params: GenerationParams::default(),
         }
     }
 
+    /// Use these timeouts and retries instead of the `[llm.http]` defaults
+    pub fn with_http(mut self, http: HttpSettings) -> Self {
+        self.client = http.client();
+        self.http = http;
+        self
+    }
+
+    /// Request extended thinking when `[llm.reasoning]` enables it
+    pub fn with_reasoning(mut self, reasoning: &ReasoningConfig) -> Self {
+        self.thinking_budget = reasoning.enabled.then_some(reasoning.budget_tokens);
+        self
+    }
+
+    /// Send the `[llm.params]` sampling parameters
+    pub fn with_params(mut self, params: &GenerationParams) -> Self {
+        self.params = params.clone();
+        self
+    }
+
     /// Create a new Anthropic client from a stored token (legacy, no auto-refresh)
     pub fn from_token(
         token: &StoredToken,
@@ -177,11 +227,11 @@ impl AnthropicClient {
             auth,
             model,
             max_tokens,
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .build()
-                .unwrap_or_else(|_| reqwest::Client::new()),
+            client: HttpSettings::default().client(),
+            http: HttpSettings::default(),
             claude_code_compat,
+            thinking_budget: None,
+            params: GenerationParams::default(),
         }
     }
 
@@ -197,10 +247,10 @@ impl AnthropicClient {
             model,
             claude_code_compat,
             max_tokens,
-            client: reqwest::Client::builder()
-                .timeout(std::time::Duration::from_secs(120))
-                .build()
-                .unwrap_or_else(|_| reqwest::Client::new()),
+            client: HttpSettings::default().client(),
+            http: HttpSettings::default(),
+            thinking_budget: None,
+            params: GenerationParams::default(),
         }
     }
 
@@ -233,7 +283,7 @@ impl AnthropicClient {
             content: msg
                 .content
                 .iter()
-                .map(|c| match c {
+                .filter_map(|c| Some(match c {
                     ContentBlock::Text { text } => AnthropicContent::Text { text: text.clone() },
                     ContentBlock::Image { data, media_type } => AnthropicContent::Image {
                         source: AnthropicImageSource {
@@ -254,7 +304,22 @@ impl AnthropicClient {
                         tool_use_id: tool_use_id.clone(),
                         content: content.clone(),
                     },
-                })
+                    // Unsigned thinking came from another provider and can't be sent back
+                    ContentBlock::Thinking { signature: None, .. } => return None,
+                    ContentBlock::Thinking {
+                        thinking,
+                        signature: Some(signature),
+                    } if thinking.is_empty() => AnthropicContent::RedactedThinking {
+                        data: signature.clone(),
+                    },
+                    ContentBlock::Thinking {
+                        thinking,
+                        signature: Some(signature),
+                    } => AnthropicContent::Thinking {
+                        thinking: thinking.clone(),
+                        signature: signature.clone(),
+                    },
+                }))
                 .collect(),
         }
     }
@@ -280,6 +345,18 @@ impl AnthropicClient {
                         tool_use_id,
                         content,
                     },
+                    AnthropicContent::Thinking {
+                        thinking,
+                        signature,
+                    } => ContentBlock::Thinking {
+                        thinking,
+                        signature: Some(signature),
+                    },
+                    // Kept as thinking without text; the signature carries the encrypted data
+                    AnthropicContent::RedactedThinking { data } => ContentBlock::Thinking {
+                        thinking: String::new(),
+                        signature: Some(data),
+                    },
                 })
                 .collect(),
         }
@@ -337,15 +414,32 @@ impl LlmClient for AnthropicClient {
             })
             .collect();
 
+        // max_tokens includes the thinking budget, so leave room for the answer
+        let max_tokens = match self.thinking_budget {
+            Some(budget) => self.max_tokens.max(budget + 1024),
+            None => self.max_tokens,
+        };
+
         let request = AnthropicRequest {
             model: self.model.clone(),
-            max_tokens: self.max_tokens,
+            max_tokens,
             system: system_blocks,
             messages: messages
                 .iter()
                 .map(Self::convert_message_to_anthropic)
                 .collect(),
             tools: anthropic_tools,
+            thinking: self.thinking_budget.map(|budget_tokens| AnthropicThinking {
+                thinking_type: "enabled".to_string(),
+                budget_tokens,
+            }),
+            // Extended thinking rejects a custom temperature
+            temperature: self
+                .params
+                .temperature
+                .filter(|_| self.thinking_budget.is_none()),
+            top_p: self.params.top_p,
+            stop_sequences: self.params.stop.clone(),
         };
 
         // Build the request with appropriate auth headers
@@ -390,11 +484,12 @@ impl LlmClient for AnthropicClient {
             );
         }
 
-        let response = req_builder
-            .json(&request)
-            .send()
+        let response = self
+            .http
+            .send(req_builder.json(&request))
             .await
             .context("Failed to send request to Anthropic")?;
+        let response = rate_limit::track("anthropic", response).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -445,4 +540,8 @@ impl LlmClient for AnthropicClient {
             usage,
         })
     }
+
+    fn capabilities(&self) -> Capabilities {
+        super::models::capabilities_for(&LlmProvider::Anthropic, &self.model)
+    }
 }
@@ -9,7 +9,7 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use std::time::Duration;
 
-use super::{LlmClient, LlmResponse, Message, ToolDefinition};
+use super::{Capabilities, LlmClient, LlmResponse, Message, ToolDefinition};
 use crate::cache::{CacheKey, CacheStats, CacheStore, CachedResponse, MemoryCache};
 
 /// Configuration for the caching LLM client
@@ -26,6 +26,8 @@ pub struct CacheConfig {
     pub ttl: Duration,
     /// Maximum cache entries
     pub max_entries: usize,
+    /// Also cache responses that call tools (for deterministic re-runs)
+    pub cache_tool_calls: bool,
 }
 
 impl Default for CacheConfig {
@@ -36,6 +38,7 @@ impl Default for CacheConfig {
             application_cache: true,
             ttl: Duration::from_secs(30 * 60), // 30 minutes
             max_entries: 100,
+            cache_tool_calls: false,
         }
     }
 }
@@ -168,13 +171,14 @@ impl LlmClient for CachingLlmClient {
             .await?;
 
         // Only cache responses without tool calls (tool calls are dynamic)
+        // unless this is a replayable cache
         let has_tool_calls = response
             .message
             .content
             .iter()
             .any(|block| matches!(block, super::ContentBlock::ToolUse { .. }));
 
-        if !has_tool_calls {
+        if !has_tool_calls || self.config.cache_tool_calls {
             // Cache the response
             let cached_response = CachedResponse::new(response.clone(), self.config.ttl);
             self.cache.set(&cache_key, cached_response).await;
@@ -185,6 +189,10 @@ impl LlmClient for CachingLlmClient {
 
         Ok(response)
     }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
 }
 
 #[cfg(test)]
@@ -289,6 +297,39 @@ mod tests {
         assert_eq!(call_count.load(Ordering::SeqCst), 2);
     }
 
+    #[tokio::test]
+    async fn test_tool_calls_cached_only_when_enabled() {
+        fn tool_call_client() -> (MockLlmClient, Arc<AtomicUsize>) {
+            let mut mock = MockLlmClient::new();
+            mock.response.message.content = vec![ContentBlock::ToolUse {
+                id: "1".to_string(),
+                name: "read_file".to_string(),
+                input: serde_json::json!({ "path": "a.rs" }),
+            }];
+            let count = mock.call_count.clone();
+            (mock, count)
+        }
+        let messages = vec![Message::user("Read a.rs".to_string())];
+
+        let (mock, call_count) = tool_call_client();
+        let client = CachingLlmClient::new(Box::new(mock), "m".to_string(), CacheConfig::default());
+        for _ in 0..2 {
+            let _ = client.send_message_with_system(&messages, &[], None).await;
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 2);
+
+        let (mock, call_count) = tool_call_client();
+        let config = CacheConfig {
+            cache_tool_calls: true,
+            ..Default::default()
+        };
+        let client = CachingLlmClient::new(Box::new(mock), "m".to_string(), config);
+        for _ in 0..2 {
+            let _ = client.send_message_with_system(&messages, &[], None).await;
+        }
+        assert_eq!(call_count.load(Ordering::SeqCst), 1);
+    }
+
     #[tokio::test]
     async fn test_cache_stats() {
         let mock = MockLlmClient::new();
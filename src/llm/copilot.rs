@@ -3,7 +3,9 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use super::openai_compat::{self, OpenAiCompatMessage};
-use super::{ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use super::http::HttpSettings;
+use super::{rate_limit, Capabilities, ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use crate::config::{GenerationParams, LlmProvider};
 
 /// Information about a single Copilot model
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,6 +104,9 @@ pub struct CopilotClient {
     model: String,
     max_tokens: usize,
     client: reqwest::Client,
+    http: HttpSettings,
+    /// Sampling parameters from `[llm.params]`
+    params: GenerationParams,
 }
 
 #[derive(Debug, Serialize)]
@@ -112,6 +117,12 @@ struct CopilotRequest {
     tools: Vec<CopilotTool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
 }
 
 /// Content can be either a simple string or an array of content parts (for multimodal)
@@ -202,20 +213,32 @@ struct CopilotResponseMessage {
 
 impl CopilotClient {
     pub fn new(api_key: String, model: String, max_tokens: usize) -> Self {
-        // Create client with 120 second timeout to prevent hanging
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(120))
-            .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
+        let http = HttpSettings::default();
+        let client = http.client();
 
         Self {
             api_key,
             model,
             max_tokens,
             client,
+            http,
+            params: GenerationParams::default(),
         }
     }
 
+    /// Use these timeouts and retries instead of the `[llm.http]` defaults
+    pub fn with_http(mut self, http: HttpSettings) -> Self {
+        self.client = http.client();
+        self.http = http;
+        self
+    }
+
+    /// Send the `[llm.params]` sampling parameters
+    pub fn with_params(mut self, params: &GenerationParams) -> Self {
+        self.params = params.clone();
+        self
+    }
+
     /// Convert and validate messages using shared OpenAI-compatible logic
     fn prepare_messages(&self, messages: &[Message]) -> Vec<CopilotMessage> {
         // Use shared conversion logic
@@ -307,6 +330,9 @@ impl LlmClient for CopilotClient {
             messages: copilot_messages,
             tools: copilot_tools,
             max_tokens: Some(self.max_tokens),
+            temperature: self.params.temperature,
+            top_p: self.params.top_p,
+            stop: self.params.stop.clone(),
         };
 
         // Check if any messages contain images (need vision header)
@@ -336,11 +362,12 @@ impl LlmClient for CopilotClient {
         tracing::info!("[COPILOT DEBUG] Sending request to Copilot API, model: {}, messages: {}", self.model, messages.len());
         let request_start = std::time::Instant::now();
 
-        let response = req_builder
-            .json(&request)
-            .send()
+        let response = self
+            .http
+            .send(req_builder.json(&request))
             .await
             .context("Failed to send request to GitHub Copilot")?;
+        let response = rate_limit::track("github-copilot", response).await?;
 
         tracing::info!("[COPILOT DEBUG] Got response in {:?}, status: {}", request_start.elapsed(), response.status());
 
@@ -403,4 +430,8 @@ impl LlmClient for CopilotClient {
             usage,
         })
     }
+
+    fn capabilities(&self) -> Capabilities {
+        super::models::capabilities_for(&LlmProvider::GitHubCopilot, &self.model)
+    }
 }
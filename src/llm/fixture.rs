@@ -0,0 +1,269 @@
+//! Record/replay of LLM exchanges
+//!
+//! `RecordingClient` wraps a provider client and appends every request and
+//! response to a JSONL fixture file. `ReplayClient` serves those responses
+//! back in order without contacting a provider, so full session tool loops
+//! can run in tests without API keys.
+//!
+//! Replay is sequential: the Nth request gets the Nth recorded response.
+//! Subagents running in parallel make the order nondeterministic, so record
+//! fixtures for flows that run one request at a time.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use super::{Capabilities, LlmClient, LlmResponse, Message, ToolDefinition};
+
+/// One request/response pair, a line of the fixture file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    /// What was sent; optional so fixtures can be written by hand
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<RecordedRequest>,
+    pub response: LlmResponse,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedRequest {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system: Option<String>,
+    /// Names of the tools offered to the model
+    #[serde(default)]
+    pub tools: Vec<String>,
+    pub messages: Vec<Message>,
+}
+
+/// Read every exchange from a fixture file
+pub fn load(path: &Path) -> Result<Vec<Exchange>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read fixture {}", path.display()))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).with_context(|| {
+                format!("Invalid exchange on line {} of {}", i + 1, path.display())
+            })
+        })
+        .collect()
+}
+
+/// Passes requests through to a provider and appends each exchange to a fixture
+pub struct RecordingClient {
+    inner: Box<dyn LlmClient>,
+    path: PathBuf,
+    /// Keeps concurrent requests from interleaving their lines
+    lock: Mutex<()>,
+}
+
+impl RecordingClient {
+    pub fn new(inner: Box<dyn LlmClient>, path: PathBuf) -> Self {
+        Self {
+            inner,
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    fn append(&self, exchange: &Exchange) -> Result<()> {
+        let line = serde_json::to_string(exchange)?;
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LlmClient for RecordingClient {
+    async fn send_message_with_system(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        system_prompt: Option<&str>,
+    ) -> Result<LlmResponse> {
+        let response = self
+            .inner
+            .send_message_with_system(messages, tools, system_prompt)
+            .await?;
+
+        let exchange = Exchange {
+            request: Some(RecordedRequest {
+                system: system_prompt.map(str::to_string),
+                tools: tools.iter().map(|t| t.name.clone()).collect(),
+                messages: messages.to_vec(),
+            }),
+            response,
+        };
+        if let Err(e) = self.append(&exchange) {
+            tracing::warn!(
+                "Failed to record exchange to {}: {}",
+                self.path.display(),
+                e
+            );
+        }
+        Ok(exchange.response)
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+}
+
+/// Serves recorded responses in order instead of calling a provider
+pub struct ReplayClient {
+    path: PathBuf,
+    exchanges: Vec<Exchange>,
+    next: Mutex<usize>,
+}
+
+impl ReplayClient {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Ok(Self::new(path.to_path_buf(), load(path)?))
+    }
+
+    pub fn new(path: PathBuf, exchanges: Vec<Exchange>) -> Self {
+        Self {
+            path,
+            exchanges,
+            next: Mutex::new(0),
+        }
+    }
+
+    /// Exchanges not yet served
+    pub fn remaining(&self) -> usize {
+        let next = *self.next.lock().unwrap_or_else(|e| e.into_inner());
+        self.exchanges.len().saturating_sub(next)
+    }
+}
+
+#[async_trait]
+impl LlmClient for ReplayClient {
+    async fn send_message_with_system(
+        &self,
+        messages: &[Message],
+        _tools: &[ToolDefinition],
+        _system_prompt: Option<&str>,
+    ) -> Result<LlmResponse> {
+        let index = {
+            let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+            let index = *next;
+            *next += 1;
+            index
+        };
+
+        let Some(exchange) = self.exchanges.get(index) else {
+            anyhow::bail!(
+                "Fixture {} has {} exchanges but request {} was made",
+                self.path.display(),
+                self.exchanges.len(),
+                index + 1
+            );
+        };
+
+        // The conversation drifted from the recording; the fixture is stale
+        if let Some(recorded) = &exchange.request {
+            if recorded.messages.len() != messages.len() {
+                anyhow::bail!(
+                    "Request {} sent {} messages but {} were recorded in {}; re-record the fixture",
+                    index + 1,
+                    messages.len(),
+                    recorded.messages.len(),
+                    self.path.display()
+                );
+            }
+        }
+
+        Ok(exchange.response.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::ContentBlock;
+
+    struct EchoClient;
+
+    #[async_trait]
+    impl LlmClient for EchoClient {
+        async fn send_message_with_system(
+            &self,
+            messages: &[Message],
+            _tools: &[ToolDefinition],
+            _system_prompt: Option<&str>,
+        ) -> Result<LlmResponse> {
+            Ok(LlmResponse {
+                message: Message::assistant(vec![ContentBlock::Text {
+                    text: format!("{} messages", messages.len()),
+                }]),
+                usage: None,
+            })
+        }
+    }
+
+    fn text(response: &LlmResponse) -> &str {
+        match &response.message.content[0] {
+            ContentBlock::Text { text } => text,
+            _ => panic!("expected text"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.jsonl");
+
+        let recorder = RecordingClient::new(Box::new(EchoClient), path.clone());
+        let first = vec![Message::user("hi".to_string())];
+        let mut second = first.clone();
+        second.push(Message::user("again".to_string()));
+        recorder.send_message(&first, &[]).await.unwrap();
+        recorder.send_message(&second, &[]).await.unwrap();
+
+        let replay = ReplayClient::from_file(&path).unwrap();
+        assert_eq!(replay.remaining(), 2);
+        assert_eq!(
+            text(&replay.send_message(&first, &[]).await.unwrap()),
+            "1 messages"
+        );
+        assert_eq!(
+            text(&replay.send_message(&second, &[]).await.unwrap()),
+            "2 messages"
+        );
+        assert!(replay.send_message(&second, &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_detects_drift() {
+        let exchange = Exchange {
+            request: Some(RecordedRequest {
+                system: None,
+                tools: vec![],
+                messages: vec![],
+            }),
+            response: LlmResponse {
+                message: Message::assistant(vec![]),
+                usage: None,
+            },
+        };
+        let replay = ReplayClient::new(PathBuf::from("test.jsonl"), vec![exchange]);
+        let err = replay
+            .send_message(&[Message::user("hi".to_string())], &[])
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("re-record"));
+    }
+}
@@ -0,0 +1,110 @@
+//! HTTP timeouts and retries for the provider clients
+//!
+//! A single total timeout kills long non-streaming responses (a large
+//! Anthropic reply can take minutes), so clients use a connect timeout plus
+//! a read timeout between chunks instead. Requests that never produced an
+//! answer — connection failures, timeouts, 5xx and 529 "overloaded" — are
+//! retried. Completions have no side effects, so resending one is safe.
+
+use std::time::Duration;
+
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+/// Resolved `[llm.http]` settings for one provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct HttpSettings {
+    pub connect_timeout: Duration,
+    /// Longest wait for the next chunk of a response
+    pub read_timeout: Duration,
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled after each one
+    pub retry_backoff: Duration,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        crate::config::LlmHttpConfig::default().for_provider("")
+    }
+}
+
+impl HttpSettings {
+    /// A client with these timeouts
+    pub fn client(&self) -> reqwest::Client {
+        reqwest::Client::builder()
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.read_timeout)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new())
+    }
+
+    /// Send a request, retrying failures where the provider gave no answer
+    pub async fn send(&self, request: RequestBuilder) -> reqwest::Result<Response> {
+        let mut attempt = 0;
+        loop {
+            // Requests with streaming bodies cannot be cloned, so they get one try
+            let Some(next) = request.try_clone() else {
+                return request.send().await;
+            };
+            let retry_reason = match next.send().await {
+                Ok(response)
+                    if attempt < self.max_retries && is_retryable_status(response.status()) =>
+                {
+                    format!("HTTP {}", response.status())
+                }
+                Err(e) if attempt < self.max_retries && is_retryable_error(&e) => e.to_string(),
+                result => return result,
+            };
+
+            let delay = self
+                .retry_backoff
+                .saturating_mul(2u32.saturating_pow(attempt));
+            attempt += 1;
+            tracing::warn!(
+                "LLM request failed ({}), retrying in {:?} (attempt {}/{})",
+                retry_reason,
+                delay,
+                attempt,
+                self.max_retries
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Server-side failures where the request was not processed
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 500 | 502 | 503 | 504 | 529)
+}
+
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retryable_statuses() {
+        assert!(is_retryable_status(StatusCode::BAD_GATEWAY));
+        assert!(is_retryable_status(StatusCode::from_u16(529).unwrap()));
+        assert!(!is_retryable_status(StatusCode::BAD_REQUEST));
+        assert!(!is_retryable_status(StatusCode::TOO_MANY_REQUESTS));
+    }
+
+    #[tokio::test]
+    async fn test_connection_errors_are_retried() {
+        let settings = HttpSettings {
+            connect_timeout: Duration::from_millis(200),
+            read_timeout: Duration::from_millis(200),
+            max_retries: 2,
+            retry_backoff: Duration::from_millis(1),
+        };
+        // Nothing listens on port 9 of localhost, so every attempt fails to connect
+        let client = settings.client();
+        let started = std::time::Instant::now();
+        let result = settings.send(client.post("http://127.0.0.1:9/")).await;
+        assert!(result.unwrap_err().is_connect());
+        assert!(started.elapsed() >= Duration::from_millis(3));
+    }
+}
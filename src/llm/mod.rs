@@ -4,17 +4,20 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use crate::auth::{TokenManager, TokenProvider};
-use crate::config::{Config, LlmProvider};
+use crate::config::{FixtureMode, LlmProvider};
 
 pub mod anthropic;
 pub mod cached;
 pub mod copilot;
+pub mod fixture;
 pub mod ollama;
 pub mod openai;
 pub mod openai_compat;
 pub mod openai_generic;
 pub mod openrouter;
 pub mod models;
+pub mod http;
+pub mod rate_limit;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
@@ -50,6 +53,13 @@ pub enum ContentBlock {
         tool_use_id: String,
         content: String,
     },
+    /// The model's reasoning before it answered (extended thinking)
+    Thinking {
+        thinking: String,
+        /// Provider signature, needed to send the block back (Anthropic)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        signature: Option<String>,
+    },
 }
 
 /// Supported image media types for multimodal messages
@@ -155,6 +165,32 @@ pub struct LlmResponse {
     pub usage: Option<TokenUsage>,
 }
 
+/// What a client's model can handle, so callers can hide or degrade
+/// features up front instead of failing mid-request
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Accepts tool definitions and returns tool calls
+    pub tools: bool,
+    /// Accepts image content blocks
+    pub vision: bool,
+    /// Can stream responses token by token
+    pub streaming: bool,
+    /// Context window in tokens
+    pub max_context: usize,
+}
+
+impl Default for Capabilities {
+    /// Assume everything is supported when the model is unknown
+    fn default() -> Self {
+        Self {
+            tools: true,
+            vision: true,
+            streaming: false,
+            max_context: 128_000,
+        }
+    }
+}
+
 #[async_trait]
 pub trait LlmClient: Send + Sync {
     /// Send a message to the LLM with optional system prompt
@@ -174,12 +210,47 @@ pub trait LlmClient: Send + Sync {
     ) -> Result<LlmResponse> {
         self.send_message_with_system(messages, tools, None).await
     }
+
+    /// What the model behind this client supports
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
 }
 
 /// Create an LLM client with optional caching wrapper
 pub async fn create_client(config: &crate::config::Config) -> Result<Box<dyn LlmClient>> {
+    // Replay needs no provider, API key or network
+    if config.llm.fixtures.mode == FixtureMode::Replay {
+        let path = config
+            .llm
+            .fixtures
+            .path
+            .as_deref()
+            .context("llm.fixtures.path must be set to replay")?;
+        return Ok(Box::new(fixture::ReplayClient::from_file(path)?));
+    }
+
     // Create the underlying provider client
-    let inner_client = create_provider_client(config).await?;
+    let mut inner_client = create_provider_client(config).await?;
+
+    if config.llm.fixtures.mode == FixtureMode::Record {
+        let path = config
+            .llm
+            .fixtures
+            .path
+            .clone()
+            .context("llm.fixtures.path must be set to record")?;
+        inner_client = Box::new(fixture::RecordingClient::new(inner_client, path));
+    }
+
+    // Pace requests and retry 429s below the cache, so cache hits cost no budget
+    if config.llm.rate_limit.enabled {
+        inner_client = Box::new(rate_limit::RateLimitedClient::new(
+            inner_client,
+            rate_limit::provider_key(&config.llm.provider),
+            config.llm.rate_limit.clone(),
+        ));
+    }
 
     // Wrap with caching if enabled
     if config.cache.enabled {
@@ -190,6 +261,16 @@ pub async fn create_client(config: &crate::config::Config) -> Result<Box<dyn Llm
             cache_config.provider_native,
             config.cache.ttl_minutes
         );
+        if config.cache.persistent {
+            let disk_cache = config.cache.disk_cache()?;
+            tracing::info!("Using persistent response cache at {:?}", disk_cache.dir());
+            return Ok(Box::new(cached::CachingLlmClient::with_cache(
+                inner_client,
+                config.llm.model.clone(),
+                cache_config,
+                Arc::new(disk_cache),
+            )));
+        }
         Ok(Box::new(cached::CachingLlmClient::new(
             inner_client,
             config.llm.model.clone(),
@@ -202,13 +283,17 @@ pub async fn create_client(config: &crate::config::Config) -> Result<Box<dyn Llm
 
 /// Create the underlying provider-specific LLM client (without caching)
 async fn create_provider_client(config: &crate::config::Config) -> Result<Box<dyn LlmClient>> {
+    let http = config
+        .llm
+        .http
+        .for_provider(rate_limit::provider_key(&config.llm.provider));
     match config.llm.provider {
         LlmProvider::Anthropic => {
             // Check if we have a stored token (could be OAuth or API key)
             if let Some(stored_token) = config.get_stored_token() {
                 // For OAuth tokens, use TokenManager for automatic refresh
                 if stored_token.is_oauth() {
-                    let token_path = Config::token_path(&LlmProvider::Anthropic)?;
+                    let token_path = config.active_token_path(&LlmProvider::Anthropic)?;
                     let token_manager = Arc::new(TokenManager::new(
                         stored_token.clone(),
                         token_path,
@@ -241,53 +326,77 @@ async fn create_provider_client(config: &crate::config::Config) -> Result<Box<dy
                         "OAuth authentication requires Claude Code compatibility mode (auto-enabled)"
                     );
 
-                    return Ok(Box::new(anthropic::AnthropicClient::with_token_manager(
-                        token_manager,
-                        config.llm.model.clone(),
-                        config.llm.max_tokens,
-                        true, // Always enable for OAuth - it's required
-                    )));
+                    return Ok(Box::new(
+                        anthropic::AnthropicClient::with_token_manager(
+                            token_manager,
+                            config.llm.model.clone(),
+                            config.llm.max_tokens,
+                            true, // Always enable for OAuth - it's required
+                        )
+                        .with_http(http)
+                        .with_reasoning(&config.llm.reasoning)
+                        .with_params(&config.llm.params),
+                    ));
                 }
 
                 // For API key tokens, use the legacy path
                 if !stored_token.is_expired() {
                     tracing::info!("Using stored API key authentication for Anthropic");
-                    return Ok(Box::new(anthropic::AnthropicClient::from_token(
-                        &stored_token,
-                        config.llm.model.clone(),
-                        config.llm.max_tokens,
-                        config.llm.claude_code_oauth_compat,
-                    )));
+                    return Ok(Box::new(
+                        anthropic::AnthropicClient::from_token(
+                            &stored_token,
+                            config.llm.model.clone(),
+                            config.llm.max_tokens,
+                            config.llm.claude_code_oauth_compat,
+                        )
+                        .with_http(http)
+                        .with_reasoning(&config.llm.reasoning)
+                        .with_params(&config.llm.params),
+                    ));
                 }
             }
 
             // Fall back to configured API key or environment variable
             let api_key = config.get_auth_token()
                 .context("Anthropic API key not set. Use 'safe-coder login anthropic' or set ANTHROPIC_API_KEY")?;
-            Ok(Box::new(anthropic::AnthropicClient::new(
-                api_key,
-                config.llm.model.clone(),
-                config.llm.max_tokens,
-            )))
+            Ok(Box::new(
+                anthropic::AnthropicClient::new(
+                    api_key,
+                    config.llm.model.clone(),
+                    config.llm.max_tokens,
+                )
+                .with_http(http)
+                .with_reasoning(&config.llm.reasoning)
+                .with_params(&config.llm.params),
+            ))
         }
         LlmProvider::OpenAI => {
             let api_key = config
                 .get_auth_token()
                 .context("OpenAI API key not set. Set OPENAI_API_KEY or configure API key")?;
-            Ok(Box::new(openai::OpenAiClient::new(
-                api_key,
-                config.llm.model.clone(),
-                config.llm.max_tokens,
-                config.llm.base_url.clone(),
-            )))
+            Ok(Box::new(
+                openai::OpenAiClient::new(
+                    api_key,
+                    config.llm.model.clone(),
+                    config.llm.max_tokens,
+                    config.llm.base_url.clone(),
+                )
+                .with_http(http)
+                .with_reasoning(&config.llm.reasoning)
+                .with_params(&config.llm.params),
+            ))
         }
         LlmProvider::Ollama => {
             tracing::info!("🦙 Using Ollama (local LLM)");
-            Ok(Box::new(ollama::OllamaClient::new(
-                config.llm.base_url.clone(),
-                config.llm.model.clone(),
-                config.llm.max_tokens,
-            )))
+            Ok(Box::new(
+                ollama::OllamaClient::new(
+                    config.llm.base_url.clone(),
+                    config.llm.model.clone(),
+                    config.llm.max_tokens,
+                )
+                .with_http(http)
+                .with_params(&config.llm.params),
+            ))
         }
         LlmProvider::GitHubCopilot => {
             // For GitHub Copilot, we need to exchange the GitHub token for a Copilot token
@@ -343,11 +452,15 @@ async fn create_provider_client(config: &crate::config::Config) -> Result<Box<dy
             };
 
             tracing::info!("Successfully obtained Copilot token");
-            Ok(Box::new(copilot::CopilotClient::new(
-                copilot_token,
-                config.llm.model.clone(),
-                config.llm.max_tokens,
-            )))
+            Ok(Box::new(
+                copilot::CopilotClient::new(
+                    copilot_token,
+                    config.llm.model.clone(),
+                    config.llm.max_tokens,
+                )
+                .with_http(http)
+                .with_params(&config.llm.params),
+            ))
         }
         LlmProvider::OpenRouter => {
             let api_key = config.get_auth_token().context(
@@ -355,11 +468,15 @@ async fn create_provider_client(config: &crate::config::Config) -> Result<Box<dy
             )?;
 
             tracing::info!("🌐 Using OpenRouter (75+ models available)");
-            Ok(Box::new(openrouter::OpenRouterClient::new(
-                api_key,
-                config.llm.model.clone(),
-                config.llm.max_tokens,
-            )))
+            Ok(Box::new(
+                openrouter::OpenRouterClient::new(
+                    api_key,
+                    config.llm.model.clone(),
+                    config.llm.max_tokens,
+                )
+                .with_http(http)
+                .with_params(&config.llm.params),
+            ))
         }
         LlmProvider::OpenAIGeneric => {
             let base_url = config.llm.base_url.clone().context(
@@ -371,12 +488,16 @@ async fn create_provider_client(config: &crate::config::Config) -> Result<Box<dy
                 "🔗 Using generic OpenAI-compatible API at {}",
                 base_url
             );
-            Ok(Box::new(openai_generic::GenericOpenAiClient::new(
-                base_url,
-                config.llm.model.clone(),
-                config.llm.max_tokens,
-                api_key,
-            )))
+            Ok(Box::new(
+                openai_generic::GenericOpenAiClient::new(
+                    base_url,
+                    config.llm.model.clone(),
+                    config.llm.max_tokens,
+                    api_key,
+                )
+                .with_http(http)
+                .with_params(&config.llm.params),
+            ))
         }
     }
 }
@@ -419,6 +540,18 @@ impl Message {
 /// Used for per-subagent model configuration
 pub async fn create_client_from_subagent_config(
     subagent_config: &crate::config::SubagentModelConfig,
+) -> Result<Box<dyn LlmClient>> {
+    let client = create_subagent_provider_client(subagent_config).await?;
+    // Shares the provider's pacer with the main session
+    Ok(Box::new(rate_limit::RateLimitedClient::new(
+        client,
+        rate_limit::provider_key(&subagent_config.provider),
+        crate::config::RateLimitConfig::default(),
+    )))
+}
+
+async fn create_subagent_provider_client(
+    subagent_config: &crate::config::SubagentModelConfig,
 ) -> Result<Box<dyn LlmClient>> {
     let api_key = subagent_config.get_api_key();
 
@@ -1,28 +1,274 @@
-// Central registry of supported LLM model names for use in selection UIs, modals, etc.
-// Extend as needed when adding/removing providers
-// Used for modal model picker in CLI/TUI
-
-pub fn available_models() -> &'static [&'static str] {
-    // Add/remove models here as supported
-    &[
-        // Anthropic Claude
-        "claude-3-opus-20240229",
-        "claude-3-sonnet-20240229",
-        "claude-3-haiku-20240307",
-        // OpenAI
-        "gpt-4o",
-        "gpt-4",
-        "gpt-4-32k",
-        "gpt-3.5-turbo",
-        // OpenRouter top models (sample)
-        "openrouter/anthropic/claude-3-opus",
-        "openrouter/openai/gpt-4o",
-        "openrouter/google/gemini-pro",
-        // Ollama - add any local models you support
-        "llama3",
-        "mistral",
-        "phi3",
-        
-        // Add more as desired...
-    ]
+//! Model registry
+//!
+//! Context windows, list prices and capabilities of known models, and the
+//! models offered for each provider by the model picker, `/models` and
+//! `safe-coder setup`. The registry is bundled as `models.json`;
+//! `safe-coder models refresh` downloads a newer one to
+//! `~/.config/safe-coder/models.json`, which is used instead while its
+//! `updated` date is at least the bundled one's.
+//!
+//! Models are matched on their name after the last slash, so routed names
+//! like `anthropic/claude-3-opus` find `claude-3-opus`. The longest registry
+//! ID the name starts with wins, which lets dated releases such as
+//! `claude-sonnet-4-20250514` share their family's entry.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+
+use super::Capabilities;
+use crate::config::LlmProvider;
+
+/// Where `safe-coder models refresh` downloads the registry from by default
+pub const REGISTRY_URL: &str =
+    "https://raw.githubusercontent.com/siddharth-ghatti/Safe-Coder/main/src/llm/models.json";
+
+const BUNDLED: &str = include_str!("models.json");
+
+/// Context window assumed for models the registry doesn't know
+const DEFAULT_CONTEXT_WINDOW: usize = 128_000;
+
+/// Model name fragments that accept images, for unknown models
+const VISION_MODELS: &[&str] = &[
+    "claude-3", "claude-sonnet", "claude-opus", "claude-haiku",
+    "gpt-4o", "gpt-4.1", "gpt-4-turbo", "gpt-4-vision", "gpt-5",
+    "gemini", "llava", "pixtral", "vision", "-vl",
+];
+
+/// Model name fragments that reject tool definitions, for unknown models
+const NO_TOOL_MODELS: &[&str] = &["o1-mini", "o1-preview", "gemma", "codellama"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRegistry {
+    /// When the prices and limits were last checked (YYYY-MM-DD)
+    pub updated: String,
+    pub models: Vec<ModelInfo>,
+    /// Models offered for each provider, the default first
+    #[serde(default)]
+    pub providers: HashMap<LlmProvider, Vec<ModelChoice>>,
+}
+
+/// What is known about a model family
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelInfo {
+    /// Name prefix the entry applies to
+    pub id: String,
+    /// Context window in tokens
+    pub context_window: usize,
+    /// Most tokens a single response can have
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_output: Option<usize>,
+    /// List price in USD per million input tokens; None for local models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_price: Option<f64>,
+    /// List price in USD per million output tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_price: Option<f64>,
+    /// Accepts images; guessed from the name when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vision: Option<bool>,
+    /// Accepts tool definitions; guessed from the name when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<bool>,
+}
+
+/// A model offered for a provider
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ModelChoice {
+    /// Model ID as the provider expects it
+    pub id: String,
+    /// Display name
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+impl ModelInfo {
+    /// Estimated cost in USD of `input_tokens` and `output_tokens`
+    pub fn cost(&self, input_tokens: usize, output_tokens: usize) -> f64 {
+        (input_tokens as f64 * self.input_price.unwrap_or(0.0)
+            + output_tokens as f64 * self.output_price.unwrap_or(0.0))
+            / 1_000_000.0
+    }
+}
+
+impl ModelRegistry {
+    /// Parse a registry, rejecting ones without models
+    pub fn parse(json: &str) -> Result<Self> {
+        let registry: Self = serde_json::from_str(json).context("Invalid model registry")?;
+        if registry.models.is_empty() {
+            bail!("Model registry lists no models");
+        }
+        Ok(registry)
+    }
+
+    /// The bundled registry
+    pub fn bundled() -> Self {
+        Self::parse(BUNDLED).expect("bundled models.json is valid")
+    }
+
+    /// The entry for `model`: the longest ID its name starts with
+    pub fn lookup(&self, model: &str) -> Option<&ModelInfo> {
+        let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+        self.models
+            .iter()
+            .filter(|info| name.starts_with(&info.id))
+            .max_by_key(|info| info.id.len())
+    }
+
+    /// Models offered for `provider`, the default first
+    pub fn choices(&self, provider: &LlmProvider) -> &[ModelChoice] {
+        self.providers
+            .get(provider)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// `~/.config/safe-coder/models.json`
+pub fn refreshed_path() -> Result<PathBuf> {
+    let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+    Ok(config_dir.join("safe-coder").join("models.json"))
+}
+
+/// The registry in use: the refreshed one unless the bundled one is newer
+pub fn registry() -> &'static ModelRegistry {
+    static REGISTRY: OnceLock<ModelRegistry> = OnceLock::new();
+    REGISTRY.get_or_init(|| {
+        let bundled = ModelRegistry::bundled();
+        let Ok(path) = refreshed_path() else {
+            return bundled;
+        };
+        let Ok(json) = std::fs::read_to_string(&path) else {
+            return bundled;
+        };
+        match ModelRegistry::parse(&json) {
+            Ok(refreshed) if refreshed.updated >= bundled.updated => refreshed,
+            Ok(_) => bundled,
+            Err(e) => {
+                tracing::warn!("Ignoring {}: {:#}", path.display(), e);
+                bundled
+            }
+        }
+    })
+}
+
+/// Download the registry from `url` and save it for later runs
+pub async fn refresh(url: &str) -> Result<ModelRegistry> {
+    let json = reqwest::Client::builder()
+        .user_agent("SafeCoder/1.0")
+        .build()
+        .context("Failed to create HTTP client")?
+        .get(url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to download {}", url))?
+        .text()
+        .await
+        .with_context(|| format!("Failed to download {}", url))?;
+    let registry = ModelRegistry::parse(&json)?;
+
+    let path = refreshed_path()?;
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(registry)
+}
+
+/// Capabilities of `model` when served by `provider`
+///
+/// Unknown models get tools and a 128k context but no vision, so image
+/// attach is hidden rather than rejected mid-request.
+pub fn capabilities_for(provider: &LlmProvider, model: &str) -> Capabilities {
+    let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    let contains_any = |fragments: &[&str]| fragments.iter().any(|f| name.contains(f));
+    let info = registry().lookup(model);
+
+    let mut caps = Capabilities {
+        tools: info
+            .and_then(|info| info.tools)
+            .unwrap_or_else(|| !contains_any(NO_TOOL_MODELS)),
+        vision: info
+            .and_then(|info| info.vision)
+            .unwrap_or_else(|| contains_any(VISION_MODELS)),
+        streaming: false,
+        max_context: info.map_or(DEFAULT_CONTEXT_WINDOW, |info| info.context_window),
+    };
+
+    // The Ollama client sends text only
+    if *provider == LlmProvider::Ollama {
+        caps.vision = false;
+    }
+    caps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capabilities_for_known_models() {
+        let claude = capabilities_for(&LlmProvider::Anthropic, "claude-sonnet-4-20250514");
+        assert!(claude.tools && claude.vision);
+        assert_eq!(claude.max_context, 200_000);
+
+        let gpt35 = capabilities_for(&LlmProvider::OpenAI, "gpt-3.5-turbo");
+        assert!(!gpt35.vision);
+        assert_eq!(gpt35.max_context, 16_385);
+
+        let routed = capabilities_for(&LlmProvider::OpenRouter, "openai/gpt-4o");
+        assert!(routed.vision);
+
+        assert!(!capabilities_for(&LlmProvider::OpenAI, "o1-mini").tools);
+        assert!(!capabilities_for(&LlmProvider::Ollama, "llava").vision);
+
+        let unknown = capabilities_for(&LlmProvider::OpenAIGeneric, "my-finetune");
+        assert!(unknown.tools && !unknown.vision);
+        assert_eq!(unknown.max_context, DEFAULT_CONTEXT_WINDOW);
+    }
+
+    #[test]
+    fn test_lookup_prefers_the_longest_prefix() {
+        let registry = ModelRegistry::bundled();
+        assert_eq!(
+            registry.lookup("gpt-4o-mini-2024-07-18").unwrap().id,
+            "gpt-4o-mini"
+        );
+        assert_eq!(registry.lookup("gpt-4o-2024-08-06").unwrap().id, "gpt-4o");
+        assert_eq!(
+            registry.lookup("claude-3-opus-20240229").unwrap().id,
+            "claude-3-opus"
+        );
+        assert_eq!(registry.lookup("claude-instant-1").unwrap().id, "claude");
+        assert!(registry.lookup("my-finetune").is_none());
+    }
+
+    #[test]
+    fn test_every_offered_model_is_known() {
+        let registry = ModelRegistry::bundled();
+        for provider in [
+            LlmProvider::Anthropic,
+            LlmProvider::OpenAI,
+            LlmProvider::OpenRouter,
+            LlmProvider::GitHubCopilot,
+            LlmProvider::Ollama,
+        ] {
+            let choices = registry.choices(&provider);
+            assert!(!choices.is_empty(), "{:?}", provider);
+            for choice in choices {
+                assert!(registry.lookup(&choice.id).is_some(), "{}", choice.id);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_empty_registry() {
+        assert!(ModelRegistry::parse(r#"{"updated": "2026-01-01", "models": []}"#).is_err());
+        assert!(ModelRegistry::parse("<html>").is_err());
+    }
 }
@@ -3,13 +3,18 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use super::openai_compat::{self, OpenAiCompatMessage};
-use super::{ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use super::http::HttpSettings;
+use super::{rate_limit, Capabilities, ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use crate::config::{GenerationParams, LlmProvider};
 
 pub struct OllamaClient {
     base_url: String,
     model: String,
     max_tokens: usize,
     client: reqwest::Client,
+    http: HttpSettings,
+    /// Sampling parameters from `[llm.params]`
+    params: GenerationParams,
 }
 
 #[derive(Debug, Serialize)]
@@ -20,6 +25,12 @@ struct OllamaRequest {
     tools: Option<Vec<OllamaTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -106,10 +117,25 @@ impl OllamaClient {
             base_url: base_url.unwrap_or_else(|| "http://localhost:11434".to_string()),
             model,
             max_tokens,
-            client: reqwest::Client::new(),
+            client: HttpSettings::default().client(),
+            http: HttpSettings::default(),
+            params: GenerationParams::default(),
         }
     }
 
+    /// Use these timeouts and retries instead of the `[llm.http]` defaults
+    pub fn with_http(mut self, http: HttpSettings) -> Self {
+        self.client = http.client();
+        self.http = http;
+        self
+    }
+
+    /// Send the `[llm.params]` sampling parameters
+    pub fn with_params(mut self, params: &GenerationParams) -> Self {
+        self.params = params.clone();
+        self
+    }
+
     /// Convert and validate messages using shared OpenAI-compatible logic
     fn prepare_messages(&self, messages: &[Message]) -> Vec<OllamaMessage> {
         // Use shared conversion logic
@@ -204,11 +230,18 @@ impl LlmClient for OllamaClient {
             messages: ollama_messages,
             tools: ollama_tools,
             max_tokens: Some(self.max_tokens),
+            temperature: self.params.temperature,
+            top_p: self.params.top_p,
+            stop: self.params.stop.clone(),
         };
 
         let url = format!("{}/v1/chat/completions", self.base_url);
 
-        let response = self.client.post(&url).json(&request).send().await?;
+        let response = self
+            .http
+            .send(self.client.post(&url).json(&request))
+            .await?;
+        let response = rate_limit::track("ollama", response).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -264,4 +297,8 @@ impl LlmClient for OllamaClient {
             usage,
         })
     }
+
+    fn capabilities(&self) -> Capabilities {
+        super::models::capabilities_for(&LlmProvider::Ollama, &self.model)
+    }
 }
@@ -3,7 +3,9 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use super::openai_compat::{self, OpenAiCompatMessage};
-use super::{ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use super::http::HttpSettings;
+use super::{rate_limit, Capabilities, ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use crate::config::{GenerationParams, LlmProvider, ReasoningConfig};
 
 pub struct OpenAiClient {
     api_key: String,
@@ -11,6 +13,11 @@ pub struct OpenAiClient {
     max_tokens: usize,
     base_url: String,
     client: reqwest::Client,
+    http: HttpSettings,
+    /// Reasoning effort for reasoning models, if enabled
+    reasoning_effort: Option<String>,
+    /// Sampling parameters from `[llm.params]`
+    params: GenerationParams,
 }
 
 #[derive(Debug, Serialize)]
@@ -21,6 +28,17 @@ struct OpenAiRequest {
     tools: Vec<OpenAiTool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<usize>,
+    /// Reasoning models reject `max_tokens` and take this instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_completion_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_effort: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
 }
 
 /// Content can be either a simple string or an array of content parts (for multimodal)
@@ -117,6 +135,9 @@ struct OpenAiChoice {
 #[derive(Debug, Deserialize)]
 struct OpenAiResponseMessage {
     content: Option<String>,
+    /// Reasoning text, returned by OpenAI-compatible servers such as DeepSeek
+    #[serde(default)]
+    reasoning_content: Option<String>,
     #[serde(default)]
     tool_calls: Vec<OpenAiToolCall>,
 }
@@ -128,11 +149,8 @@ impl OpenAiClient {
         max_tokens: usize,
         base_url: Option<String>,
     ) -> Self {
-        // Create client with 120 second timeout to prevent hanging
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(120))
-            .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
+        let http = HttpSettings::default();
+        let client = http.client();
 
         Self {
             api_key,
@@ -140,9 +158,31 @@ impl OpenAiClient {
             max_tokens,
             base_url: base_url.unwrap_or_else(|| "https://api.openai.com/v1".to_string()),
             client,
+            http,
+            reasoning_effort: None,
+            params: GenerationParams::default(),
         }
     }
 
+    /// Use these timeouts and retries instead of the `[llm.http]` defaults
+    pub fn with_http(mut self, http: HttpSettings) -> Self {
+        self.client = http.client();
+        self.http = http;
+        self
+    }
+
+    /// Send a reasoning effort when `[llm.reasoning]` enables it
+    pub fn with_reasoning(mut self, reasoning: &ReasoningConfig) -> Self {
+        self.reasoning_effort = reasoning.enabled.then(|| reasoning.effort.clone());
+        self
+    }
+
+    /// Send the `[llm.params]` sampling parameters
+    pub fn with_params(mut self, params: &GenerationParams) -> Self {
+        self.params = params.clone();
+        self
+    }
+
     /// Convert and validate messages using shared OpenAI-compatible logic
     fn prepare_messages(&self, messages: &[Message]) -> Vec<OpenAiMessage> {
         // Use shared conversion logic
@@ -235,20 +275,29 @@ impl LlmClient for OpenAiClient {
             model: self.model.clone(),
             messages: openai_messages,
             tools: openai_tools,
-            max_tokens: Some(self.max_tokens),
+            max_tokens: self.reasoning_effort.is_none().then_some(self.max_tokens),
+            max_completion_tokens: self.reasoning_effort.is_some().then_some(self.max_tokens),
+            reasoning_effort: self.reasoning_effort.clone(),
+            // Reasoning models only accept the default sampling
+            temperature: self
+                .params
+                .temperature
+                .filter(|_| self.reasoning_effort.is_none()),
+            top_p: self.params.top_p.filter(|_| self.reasoning_effort.is_none()),
+            stop: self.params.stop.clone(),
         };
 
         let url = format!("{}/chat/completions", self.base_url);
 
-        let response = self
+        let req = self
             .client
             .post(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .header("Content-Type", "application/json")
-            .json(&request)
-            .send()
-            .await
+            .json(&request);
+        let response = self.http.send(req).await
             .context("Failed to send request to OpenAI")?;
+        let response = rate_limit::track("openai", response).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -268,6 +317,15 @@ impl LlmClient for OpenAiClient {
 
         let mut content_blocks = Vec::new();
 
+        if let Some(reasoning) = &choice.message.reasoning_content {
+            if !reasoning.is_empty() {
+                content_blocks.push(ContentBlock::Thinking {
+                    thinking: reasoning.clone(),
+                    signature: None,
+                });
+            }
+        }
+
         // Add text content if present
         if let Some(text) = &choice.message.content {
             if !text.is_empty() {
@@ -326,4 +384,8 @@ impl LlmClient for OpenAiClient {
             usage,
         })
     }
+
+    fn capabilities(&self) -> Capabilities {
+        super::models::capabilities_for(&LlmProvider::OpenAI, &self.model)
+    }
 }
@@ -79,6 +79,8 @@ pub fn convert_messages(messages: &[Message]) -> Vec<OpenAiCompatMessage> {
                 ContentBlock::ToolResult { tool_use_id, content } => {
                     tool_results.push((tool_use_id.clone(), content.clone()));
                 }
+                // The API doesn't take past reasoning back
+                ContentBlock::Thinking { .. } => {}
             }
         }
 
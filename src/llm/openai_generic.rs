@@ -15,7 +15,9 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use super::openai_compat::{self, OpenAiCompatMessage};
-use super::{ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use super::http::HttpSettings;
+use super::{rate_limit, Capabilities, ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use crate::config::{GenerationParams, LlmProvider};
 
 /// A client for any OpenAI-compatible API endpoint
 pub struct GenericOpenAiClient {
@@ -29,6 +31,9 @@ pub struct GenericOpenAiClient {
     base_url: String,
     /// HTTP client
     client: reqwest::Client,
+    http: HttpSettings,
+    /// Sampling parameters from `[llm.params]`
+    params: GenerationParams,
 }
 
 #[derive(Debug, Serialize)]
@@ -39,6 +44,12 @@ struct OpenAiRequest {
     tools: Vec<OpenAiTool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
 }
 
 /// Content can be either a simple string or an array of content parts (for multimodal)
@@ -153,11 +164,8 @@ impl GenericOpenAiClient {
         max_tokens: usize,
         api_key: Option<String>,
     ) -> Self {
-        // Create client with 120 second timeout to prevent hanging
-        let client = reqwest::Client::builder()
-            .timeout(std::time::Duration::from_secs(120))
-            .build()
-            .unwrap_or_else(|_| reqwest::Client::new());
+        let http = HttpSettings::default();
+        let client = http.client();
 
         // Normalize base URL - remove trailing slash if present
         let base_url = base_url.trim_end_matches('/').to_string();
@@ -168,9 +176,24 @@ impl GenericOpenAiClient {
             max_tokens,
             base_url,
             client,
+            http,
+            params: GenerationParams::default(),
         }
     }
 
+    /// Use these timeouts and retries instead of the `[llm.http]` defaults
+    pub fn with_http(mut self, http: HttpSettings) -> Self {
+        self.client = http.client();
+        self.http = http;
+        self
+    }
+
+    /// Send the `[llm.params]` sampling parameters
+    pub fn with_params(mut self, params: &GenerationParams) -> Self {
+        self.params = params.clone();
+        self
+    }
+
     /// Convert and validate messages using shared OpenAI-compatible logic
     fn prepare_messages(&self, messages: &[Message]) -> Vec<OpenAiMessage> {
         // Use shared conversion logic
@@ -270,6 +293,9 @@ impl LlmClient for GenericOpenAiClient {
             messages: openai_messages,
             tools: openai_tools,
             max_tokens: Some(self.max_tokens),
+            temperature: self.params.temperature,
+            top_p: self.params.top_p,
+            stop: self.params.stop.clone(),
         };
 
         let url = format!("{}/chat/completions", self.base_url);
@@ -285,11 +311,12 @@ impl LlmClient for GenericOpenAiClient {
             req = req.header("Authorization", format!("Bearer {}", api_key));
         }
 
-        let response = req
-            .json(&request)
-            .send()
+        let response = self
+            .http
+            .send(req.json(&request))
             .await
             .with_context(|| format!("Failed to send request to OpenAI-compatible API at {}", url))?;
+        let response = rate_limit::track("openai-generic", response).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -374,4 +401,8 @@ impl LlmClient for GenericOpenAiClient {
             usage,
         })
     }
+
+    fn capabilities(&self) -> Capabilities {
+        super::models::capabilities_for(&LlmProvider::OpenAIGeneric, &self.model)
+    }
 }
@@ -18,7 +18,9 @@ use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 
 use super::openai_compat::{self, OpenAiCompatMessage};
-use super::{ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use super::http::HttpSettings;
+use super::{rate_limit, Capabilities, ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use crate::config::{GenerationParams, LlmProvider};
 
 const OPENROUTER_API_URL: &str = "https://openrouter.ai/api/v1";
 
@@ -27,10 +29,13 @@ pub struct OpenRouterClient {
     model: String,
     max_tokens: usize,
     client: reqwest::Client,
+    http: HttpSettings,
     /// Optional site URL for OpenRouter rankings
     site_url: Option<String>,
     /// Optional site name for OpenRouter rankings
     site_name: Option<String>,
+    /// Sampling parameters from `[llm.params]`
+    params: GenerationParams,
 }
 
 #[derive(Debug, Serialize)]
@@ -44,6 +49,12 @@ struct OpenRouterRequest {
     /// OpenRouter-specific: allow fallback to similar models if primary is unavailable
     #[serde(skip_serializing_if = "Option::is_none")]
     route: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
 }
 
 /// Content can be either a simple string or an array of content parts (for multimodal)
@@ -142,12 +153,27 @@ impl OpenRouterClient {
             api_key,
             model,
             max_tokens,
-            client: reqwest::Client::new(),
+            client: HttpSettings::default().client(),
+            http: HttpSettings::default(),
             site_url: Some("https://github.com/siddharth-ghatti/safe-coder".to_string()),
             site_name: Some("Safe-Coder".to_string()),
+            params: GenerationParams::default(),
         }
     }
 
+    /// Use these timeouts and retries instead of the `[llm.http]` defaults
+    pub fn with_http(mut self, http: HttpSettings) -> Self {
+        self.client = http.client();
+        self.http = http;
+        self
+    }
+
+    /// Send the `[llm.params]` sampling parameters
+    pub fn with_params(mut self, params: &GenerationParams) -> Self {
+        self.params = params.clone();
+        self
+    }
+
     /// Create with custom site info for OpenRouter rankings
     pub fn with_site_info(
         api_key: String,
@@ -160,9 +186,11 @@ impl OpenRouterClient {
             api_key,
             model,
             max_tokens,
-            client: reqwest::Client::new(),
+            client: HttpSettings::default().client(),
+            http: HttpSettings::default(),
             site_url,
             site_name,
+            params: GenerationParams::default(),
         }
     }
 
@@ -260,6 +288,9 @@ impl LlmClient for OpenRouterClient {
             tools: openrouter_tools,
             max_tokens: Some(self.max_tokens),
             route: Some("fallback".to_string()), // Allow fallback to similar models
+            temperature: self.params.temperature,
+            top_p: self.params.top_p,
+            stop: self.params.stop.clone(),
         };
 
         let url = format!("{}/chat/completions", OPENROUTER_API_URL);
@@ -279,11 +310,12 @@ impl LlmClient for OpenRouterClient {
             req = req.header("X-Title", site_name);
         }
 
-        let response = req
-            .json(&request)
-            .send()
+        let response = self
+            .http
+            .send(req.json(&request))
             .await
             .context("Failed to send request to OpenRouter")?;
+        let response = rate_limit::track("openrouter", response).await?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -353,6 +385,10 @@ impl LlmClient for OpenRouterClient {
             usage,
         })
     }
+
+    fn capabilities(&self) -> Capabilities {
+        super::models::capabilities_for(&LlmProvider::OpenRouter, &self.model)
+    }
 }
 
 /// Popular OpenRouter models for quick reference
@@ -0,0 +1,478 @@
+//! Rate limit awareness and request pacing
+//!
+//! Provider clients are wrapped in a [`RateLimitedClient`]. Every client for
+//! the same provider in this process shares one [`Pacer`], so the main
+//! session, subagents and parallel workers draw from one budget instead of
+//! tripping each other's limits. The pacer learns from the provider's
+//! rate-limit headers, and a 429 is retried with jittered exponential
+//! backoff instead of failing the turn. Callers that want to show the wait
+//! wrap the request in [`with_retry_notifier`].
+
+use anyhow::Result;
+use async_trait::async_trait;
+use rand::Rng;
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::{Capabilities, LlmClient, LlmResponse, Message, ToolDefinition};
+use crate::config::{LlmProvider, RateLimitConfig};
+
+/// Limits reported by a provider in its response headers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RateLimitInfo {
+    pub remaining_requests: Option<u64>,
+    pub remaining_tokens: Option<u64>,
+    /// Time until the exhausted limit resets
+    pub reset_after: Option<Duration>,
+    /// Wait requested by the server (`retry-after`)
+    pub retry_after: Option<Duration>,
+}
+
+const REMAINING_REQUESTS_HEADERS: &[&str] = &[
+    "anthropic-ratelimit-requests-remaining",
+    "x-ratelimit-remaining-requests",
+    "x-ratelimit-remaining",
+];
+
+const REMAINING_TOKENS_HEADERS: &[&str] = &[
+    "anthropic-ratelimit-tokens-remaining",
+    "anthropic-ratelimit-input-tokens-remaining",
+    "x-ratelimit-remaining-tokens",
+];
+
+/// Longest the server can make us hold requests for; anything above this is
+/// a bogus header
+const MAX_SERVER_WAIT: Duration = Duration::from_secs(3600);
+
+const RESET_HEADERS: &[&str] = &[
+    "anthropic-ratelimit-requests-reset",
+    "anthropic-ratelimit-tokens-reset",
+    "x-ratelimit-reset-requests",
+    "x-ratelimit-reset-tokens",
+    "x-ratelimit-reset",
+];
+
+impl RateLimitInfo {
+    /// Parse the Anthropic, OpenAI and OpenRouter rate-limit headers
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let first_number = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| header(name).and_then(|v| v.trim().parse::<u64>().ok()))
+        };
+
+        let retry_after = header("retry-after-ms")
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .and_then(|ms| Duration::try_from_secs_f64(ms.max(0.0) / 1000.0).ok())
+            .or_else(|| {
+                header("retry-after")
+                    .and_then(|v| v.trim().parse::<f64>().ok())
+                    .and_then(|secs| Duration::try_from_secs_f64(secs.max(0.0)).ok())
+            });
+
+        Self {
+            remaining_requests: first_number(REMAINING_REQUESTS_HEADERS),
+            remaining_tokens: first_number(REMAINING_TOKENS_HEADERS),
+            reset_after: RESET_HEADERS
+                .iter()
+                .filter_map(|name| header(name).and_then(parse_reset))
+                .max(),
+            retry_after,
+        }
+    }
+
+    /// Whether a request or token budget has run out
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining_requests == Some(0) || self.remaining_tokens == Some(0)
+    }
+}
+
+/// Parse a reset header: an RFC 3339 time, a duration such as `1m30s` or
+/// `250ms`, an epoch timestamp, or a number of seconds
+fn parse_reset(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(at) = chrono::DateTime::parse_from_rfc3339(value) {
+        return (at.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .ok()
+            .or(Some(Duration::ZERO));
+    }
+    if let Ok(number) = value.parse::<f64>() {
+        if !number.is_finite() {
+            return None;
+        }
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?;
+        return if number > 1e12 {
+            Some(Duration::from_millis(number as u64).saturating_sub(now))
+        } else if number > 1e9 {
+            Some(Duration::from_secs(number as u64).saturating_sub(now))
+        } else {
+            Duration::try_from_secs_f64(number.max(0.0)).ok()
+        };
+    }
+    parse_go_duration(value)
+}
+
+/// Parse durations like `6m0s`, `1.5s` or `20ms`
+fn parse_go_duration(value: &str) -> Option<Duration> {
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .filter(|&i| i > 0)?;
+        let number: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let seconds = match &rest[..unit_len] {
+            "h" => 3600.0,
+            "m" => 60.0,
+            "s" => 1.0,
+            "ms" => 0.001,
+            _ => return None,
+        };
+        total += number * seconds;
+        rest = &rest[unit_len..];
+    }
+    Duration::try_from_secs_f64(total).ok()
+}
+
+/// A provider answered 429 Too Many Requests
+#[derive(Debug, thiserror::Error)]
+#[error("Rate limited by {provider} (HTTP 429): {message}")]
+pub struct RateLimitError {
+    pub provider: String,
+    pub retry_after: Option<Duration>,
+    pub message: String,
+}
+
+/// Record a response's rate-limit headers with the provider's pacer and turn
+/// a 429 into a [`RateLimitError`]. Other responses are returned untouched.
+pub async fn track(provider: &str, response: reqwest::Response) -> Result<reqwest::Response> {
+    let info = RateLimitInfo::from_headers(response.headers());
+    if let Some(pacer) = existing_pacer(provider) {
+        pacer.observe(&info);
+    }
+    if response.status() != reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return Ok(response);
+    }
+    let message = response.text().await.unwrap_or_default();
+    Err(RateLimitError {
+        provider: provider.to_string(),
+        retry_after: info.retry_after.or(info.reset_after),
+        message: message.chars().take(300).collect(),
+    }
+    .into())
+}
+
+/// Token bucket shared by every request to one provider
+pub struct Pacer {
+    state: Mutex<PacerState>,
+}
+
+struct PacerState {
+    /// Requests that may go out now; negative when requests are queued
+    tokens: f64,
+    capacity: f64,
+    /// Tokens added per second (0 means no client-side limit)
+    refill_per_sec: f64,
+    last_refill: Instant,
+    /// Hold every request until this time (server said to slow down)
+    blocked_until: Option<Instant>,
+}
+
+impl Pacer {
+    /// `requests_per_minute` of `None` only paces on what the server reports.
+    /// Bursts of up to a tenth of the per-minute budget are allowed.
+    pub fn new(requests_per_minute: Option<u32>) -> Self {
+        let rpm = requests_per_minute.unwrap_or(0) as f64;
+        let capacity = (rpm / 10.0).max(1.0);
+        Self {
+            state: Mutex::new(PacerState {
+                tokens: capacity,
+                capacity,
+                refill_per_sec: rpm / 60.0,
+                last_refill: Instant::now(),
+                blocked_until: None,
+            }),
+        }
+    }
+
+    /// Claim a slot for the next request and return how long to wait before sending it
+    fn reserve(&self) -> Duration {
+        let mut state = self.state.lock().unwrap();
+        let now = Instant::now();
+        let blocked = state
+            .blocked_until
+            .map(|until| until.saturating_duration_since(now))
+            .unwrap_or_default();
+        if state.refill_per_sec == 0.0 {
+            return blocked;
+        }
+
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * state.refill_per_sec).min(state.capacity);
+        state.last_refill = now;
+        state.tokens -= 1.0;
+        let queued = if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / state.refill_per_sec)
+        };
+        blocked.max(queued)
+    }
+
+    /// Wait until a request may be sent
+    pub async fn acquire(&self) {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            tracing::debug!("Pacing LLM request for {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Hold all requests for `duration`, at most [`MAX_SERVER_WAIT`]
+    pub fn block_for(&self, duration: Duration) {
+        let until = Instant::now() + duration.min(MAX_SERVER_WAIT);
+        let mut state = self.state.lock().unwrap();
+        state.blocked_until = Some(state.blocked_until.map_or(until, |u| u.max(until)));
+    }
+
+    /// Slow down when the server says a budget is spent
+    pub fn observe(&self, info: &RateLimitInfo) {
+        if let Some(wait) = info.retry_after {
+            self.block_for(wait);
+        } else if info.is_exhausted() {
+            if let Some(reset) = info.reset_after {
+                self.block_for(reset);
+            }
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref PACERS: Mutex<HashMap<String, Arc<Pacer>>> = Mutex::new(HashMap::new());
+}
+
+/// The process-wide pacer for a provider. The first caller's budget sets it up.
+pub fn pacer_for(provider: &str, requests_per_minute: Option<u32>) -> Arc<Pacer> {
+    PACERS
+        .lock()
+        .unwrap()
+        .entry(provider.to_string())
+        .or_insert_with(|| Arc::new(Pacer::new(requests_per_minute)))
+        .clone()
+}
+
+fn existing_pacer(provider: &str) -> Option<Arc<Pacer>> {
+    PACERS.lock().unwrap().get(provider).cloned()
+}
+
+/// Pacer key for a provider, matching the names the clients pass to [`track`]
+pub fn provider_key(provider: &LlmProvider) -> &'static str {
+    match provider {
+        LlmProvider::Anthropic => "anthropic",
+        LlmProvider::OpenAI => "openai",
+        LlmProvider::Ollama => "ollama",
+        LlmProvider::GitHubCopilot => "github-copilot",
+        LlmProvider::OpenRouter => "openrouter",
+        LlmProvider::OpenAIGeneric => "openai-generic",
+    }
+}
+
+/// A 429 retry that is about to wait
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryNotice {
+    pub provider: String,
+    pub attempt: u32,
+    pub max_retries: u32,
+    pub delay: Duration,
+}
+
+pub type RetryNotifier = Arc<dyn Fn(&RetryNotice) + Send + Sync>;
+
+tokio::task_local! {
+    static RETRY_NOTIFIER: RetryNotifier;
+}
+
+/// Run an LLM request, reporting any rate-limit waits inside it to `notifier`
+pub async fn with_retry_notifier<F: Future>(notifier: RetryNotifier, request: F) -> F::Output {
+    RETRY_NOTIFIER.scope(notifier, request).await
+}
+
+/// Jittered exponential backoff, or the server's requested wait
+fn backoff_delay(
+    config: &RateLimitConfig,
+    attempt: u32,
+    server_wait: Option<Duration>,
+) -> Duration {
+    let max = Duration::from_secs(config.max_backoff_secs);
+    if let Some(wait) = server_wait {
+        return wait.min(max);
+    }
+    let exponential = Duration::from_millis(config.initial_backoff_ms)
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(max);
+    // Equal jitter: half fixed, half random, so clients that were limited together spread out
+    let half = exponential / 2;
+    half + half.mul_f64(rand::thread_rng().gen::<f64>())
+}
+
+/// Paces requests through the provider's shared [`Pacer`] and retries 429s
+pub struct RateLimitedClient {
+    inner: Box<dyn LlmClient>,
+    provider: String,
+    pacer: Arc<Pacer>,
+    config: RateLimitConfig,
+}
+
+impl RateLimitedClient {
+    pub fn new(inner: Box<dyn LlmClient>, provider: &str, config: RateLimitConfig) -> Self {
+        Self {
+            inner,
+            provider: provider.to_string(),
+            pacer: pacer_for(provider, config.requests_per_minute),
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmClient for RateLimitedClient {
+    async fn send_message_with_system(
+        &self,
+        messages: &[Message],
+        tools: &[ToolDefinition],
+        system_prompt: Option<&str>,
+    ) -> Result<LlmResponse> {
+        let mut attempt = 0;
+        loop {
+            self.pacer.acquire().await;
+            let error = match self
+                .inner
+                .send_message_with_system(messages, tools, system_prompt)
+                .await
+            {
+                Ok(response) => return Ok(response),
+                Err(e) => e,
+            };
+            let Some(limited) = error.downcast_ref::<RateLimitError>() else {
+                return Err(error);
+            };
+            if attempt >= self.config.max_retries {
+                return Err(error);
+            }
+
+            let delay = backoff_delay(&self.config, attempt, limited.retry_after);
+            attempt += 1;
+            tracing::warn!(
+                "{} rate limited, retrying in {:?} (attempt {}/{})",
+                self.provider,
+                delay,
+                attempt,
+                self.config.max_retries
+            );
+            let notice = RetryNotice {
+                provider: self.provider.clone(),
+                attempt,
+                max_retries: self.config.max_retries,
+                delay,
+            };
+            let _ = RETRY_NOTIFIER.try_with(|notify| notify(&notice));
+            // Hold other requests to this provider too; they would hit the same limit
+            self.pacer.block_for(delay);
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities {
+        self.inner.capabilities()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_parse_provider_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "x-ratelimit-remaining-requests",
+            HeaderValue::from_static("0"),
+        );
+        headers.insert(
+            "x-ratelimit-reset-requests",
+            HeaderValue::from_static("1m30s"),
+        );
+        headers.insert("retry-after", HeaderValue::from_static("7"));
+        let info = RateLimitInfo::from_headers(&headers);
+        assert_eq!(info.remaining_requests, Some(0));
+        assert!(info.is_exhausted());
+        assert_eq!(info.reset_after, Some(Duration::from_secs(90)));
+        assert_eq!(info.retry_after, Some(Duration::from_secs(7)));
+
+        assert_eq!(parse_go_duration("250ms"), Some(Duration::from_millis(250)));
+        assert_eq!(parse_go_duration("soon"), None);
+        assert_eq!(
+            RateLimitInfo::from_headers(&HeaderMap::new()),
+            RateLimitInfo::default()
+        );
+    }
+
+    #[test]
+    fn test_bogus_waits_are_dropped_or_capped() {
+        let mut headers = HeaderMap::new();
+        headers.insert("retry-after-ms", HeaderValue::from_static("inf"));
+        headers.insert("retry-after", HeaderValue::from_static("1e300"));
+        headers.insert(
+            "x-ratelimit-reset-requests",
+            HeaderValue::from_static("99999999999999999999h"),
+        );
+        let info = RateLimitInfo::from_headers(&headers);
+        assert_eq!(info.retry_after, None);
+        assert_eq!(info.reset_after, None);
+        assert_eq!(parse_reset("inf"), None);
+        assert_eq!(parse_go_duration("99999999999999999999h"), None);
+
+        let pacer = Pacer::new(None);
+        pacer.block_for(Duration::MAX);
+        assert!(pacer.reserve() <= MAX_SERVER_WAIT);
+    }
+
+    #[test]
+    fn test_pacer_spaces_requests_past_burst() {
+        // 600 rpm: bursts of 60, then one request every 100ms
+        let pacer = Pacer::new(Some(600));
+        for _ in 0..60 {
+            assert_eq!(pacer.reserve(), Duration::ZERO);
+        }
+        let wait = pacer.reserve();
+        assert!(wait > Duration::from_millis(50) && wait <= Duration::from_millis(100));
+
+        let unlimited = Pacer::new(None);
+        assert_eq!(unlimited.reserve(), Duration::ZERO);
+        unlimited.block_for(Duration::from_secs(5));
+        assert!(unlimited.reserve() > Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_backoff_honors_server_wait_and_cap() {
+        let config = RateLimitConfig::default();
+        assert_eq!(
+            backoff_delay(&config, 0, Some(Duration::from_secs(3))),
+            Duration::from_secs(3)
+        );
+        let capped = backoff_delay(&config, 30, None);
+        assert!(capped <= Duration::from_secs(config.max_backoff_secs));
+        assert!(capped >= Duration::from_secs(config.max_backoff_secs) / 2);
+    }
+}
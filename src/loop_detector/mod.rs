@@ -5,11 +5,23 @@
 //!
 //! Also tracks error TYPES - if the same error pattern keeps occurring even with
 //! different fix attempts, we detect that as a loop.
+//!
+//! Calls are compared both exactly and as near-duplicates (paths normalized,
+//! paging parameters such as offset/limit ignored), and A-B-A-B alternation
+//! between two calls is caught as well. Before blocking, the detector asks the
+//! session to inject a guidance message so the model can change course.
 
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, VecDeque};
 use regex::Regex;
 
+/// Parameters that only page through the same data; ignored for near-duplicates
+const IGNORED_PARAMS: &[&str] = &["offset", "limit", "head_limit", "max_results", "timeout"];
+
+/// Parameters holding paths, normalized for near-duplicate comparison
+const PATH_PARAMS: &[&str] = &["path", "file_path", "file", "dir", "directory", "cwd"];
+
 /// Action to take when a doom loop is detected
 #[derive(Debug, Clone)]
 pub enum DoomLoopAction {
@@ -19,16 +31,33 @@ pub enum DoomLoopAction {
     Warn { message: String },
     /// Ask user whether to continue
     AskUser { message: String },
+    /// Run the call but append guidance to its result telling the model to
+    /// change approach
+    InjectGuidance { message: String },
     /// Block the action entirely
     Block { message: String },
 }
 
+impl DoomLoopAction {
+    fn severity(&self) -> u8 {
+        match self {
+            DoomLoopAction::Continue => 0,
+            DoomLoopAction::Warn { .. } => 1,
+            DoomLoopAction::AskUser { .. } => 2,
+            DoomLoopAction::InjectGuidance { .. } => 3,
+            DoomLoopAction::Block { .. } => 4,
+        }
+    }
+}
+
 /// Represents a tool call for comparison
 #[derive(Debug, Clone, PartialEq)]
 struct ToolCall {
     tool_name: String,
     /// Normalized parameters (sorted keys for consistent comparison)
     params_hash: String,
+    /// Parameters with paths normalized and paging parameters dropped
+    similar_key: String,
 }
 
 impl ToolCall {
@@ -36,12 +65,42 @@ impl ToolCall {
         // Create a normalized hash of parameters for comparison
         // This handles cases where JSON key order might differ
         let params_hash = Self::normalize_params(params);
+        let similar_key = Self::normalize_params(&Self::loosen_params(params));
         Self {
             tool_name: tool_name.to_string(),
             params_hash,
+            similar_key,
         }
     }
 
+    fn is_similar(&self, other: &ToolCall) -> bool {
+        self.tool_name == other.tool_name && self.similar_key == other.similar_key
+    }
+
+    /// Drop paging parameters, normalize paths and collapse whitespace
+    fn loosen_params(params: &Value) -> Value {
+        let Value::Object(map) = params else {
+            return params.clone();
+        };
+        let loosened = map
+            .iter()
+            .filter(|(k, _)| !IGNORED_PARAMS.contains(&k.as_str()))
+            .map(|(k, v)| {
+                let v = match v {
+                    Value::String(s) if PATH_PARAMS.contains(&k.as_str()) => {
+                        Value::String(normalize_path(s))
+                    }
+                    Value::String(s) => {
+                        Value::String(s.split_whitespace().collect::<Vec<_>>().join(" "))
+                    }
+                    other => other.clone(),
+                };
+                (k.clone(), v)
+            })
+            .collect();
+        Value::Object(loosened)
+    }
+
     fn normalize_params(params: &Value) -> String {
         // For simple comparison, we'll use the canonical JSON representation
         // This sorts object keys for consistent comparison
@@ -60,6 +119,41 @@ impl ToolCall {
     }
 }
 
+/// `./src//a/../b.rs` and `src/b.rs` name the same file
+fn normalize_path(path: &str) -> String {
+    let path = path.trim().replace('\\', "/");
+    let absolute = path.starts_with('/');
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." if parts.last().is_some_and(|p| *p != "..") => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    let joined = parts.join("/");
+    if absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+/// Per-tool overrides for the repeat thresholds; unset values use the defaults
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ToolThresholds {
+    #[serde(default)]
+    pub warn: Option<usize>,
+    #[serde(default)]
+    pub ask: Option<usize>,
+    #[serde(default)]
+    pub guide: Option<usize>,
+    #[serde(default)]
+    pub block: Option<usize>,
+}
+
 /// Configuration for doom loop detection
 #[derive(Debug, Clone)]
 pub struct LoopDetectorConfig {
@@ -69,10 +163,16 @@ pub struct LoopDetectorConfig {
     pub warn_threshold: usize,
     /// Number of identical calls before asking user
     pub ask_threshold: usize,
+    /// Number of identical calls before injecting guidance into the result
+    pub guide_threshold: usize,
     /// Number of identical calls before blocking
     pub block_threshold: usize,
     /// Whether to include similar (not just identical) calls
     pub detect_similar: bool,
+    /// Number of A-B cycles before alternation counts as a loop
+    pub alternation_threshold: usize,
+    /// Thresholds for specific tools, keyed by tool name
+    pub tool_thresholds: HashMap<String, ToolThresholds>,
 }
 
 impl Default for LoopDetectorConfig {
@@ -80,13 +180,32 @@ impl Default for LoopDetectorConfig {
         Self {
             max_history: 15,     // Smaller window for faster detection
             warn_threshold: 1,   // Warn on 1st repeat (2 total)
-            ask_threshold: 2,    // Ask on 2nd repeat (3 total) - more aggressive
-            block_threshold: 3,  // Block on 3rd repeat (4 total) - more aggressive
+            ask_threshold: 3,    // Ask on 3rd repeat (4 total)
+            guide_threshold: 4,  // Tell the model to change course on the 4th repeat
+            block_threshold: 5,  // Block on 5th repeat (6 total)
             detect_similar: true,
+            alternation_threshold: 3, // A-B-A-B-A-B
+            tool_thresholds: HashMap::new(),
         }
     }
 }
 
+impl LoopDetectorConfig {
+    /// Thresholds for a tool as (warn, ask, guide, block)
+    fn thresholds(&self, tool_name: &str) -> (usize, usize, usize, usize) {
+        let overrides = self.tool_thresholds.get(tool_name);
+        let pick = |f: fn(&ToolThresholds) -> Option<usize>, default| {
+            overrides.and_then(f).unwrap_or(default)
+        };
+        (
+            pick(|t| t.warn, self.warn_threshold),
+            pick(|t| t.ask, self.ask_threshold),
+            pick(|t| t.guide, self.guide_threshold),
+            pick(|t| t.block, self.block_threshold),
+        )
+    }
+}
+
 /// Represents a build error with extracted type information
 #[derive(Debug, Clone)]
 pub struct ErrorPattern {
@@ -305,40 +424,79 @@ impl LoopDetector {
     /// Call this BEFORE executing the tool
     pub fn check(&mut self, tool_name: &str, params: &Value) -> DoomLoopAction {
         let call = ToolCall::new(tool_name, params);
+        let repeat = self.check_repeats(&call);
+        let alternation = self.check_alternation(&call);
+        if alternation.severity() > repeat.severity() {
+            alternation
+        } else {
+            repeat
+        }
+    }
 
-        // Count identical calls in recent history
-        let identical_count = self.recent_calls.iter().filter(|c| *c == &call).count();
+    /// Repeats of the same call, exact or near-duplicate
+    fn check_repeats(&self, call: &ToolCall) -> DoomLoopAction {
+        let tool_name = &call.tool_name;
+        let identical_count = self.recent_calls.iter().filter(|c| *c == call).count();
+        let similar_count = if self.config.detect_similar {
+            self.recent_calls.iter().filter(|c| c.is_similar(call)).count()
+        } else {
+            identical_count
+        };
+        let count = similar_count.max(identical_count);
+        let same = if count > identical_count {
+            "nearly identical parameters"
+        } else {
+            "identical parameters"
+        };
+        let (warn, ask, guide, block) = self.config.thresholds(tool_name);
 
         // Determine action based on thresholds
-        if identical_count >= self.config.block_threshold {
+        if count >= block {
             return DoomLoopAction::Block {
                 message: format!(
-                    "🛑 Doom loop detected: '{}' called {} times with identical parameters. \
+                    "🛑 Doom loop detected: '{}' called {} times with {}. \
                      This action has been blocked to prevent infinite loops. \
                      Please try a different approach.",
                     tool_name,
-                    identical_count + 1
+                    count + 1,
+                    same
                 ),
             };
         }
 
-        if identical_count >= self.config.ask_threshold {
+        if count >= guide {
+            return DoomLoopAction::InjectGuidance {
+                message: format!(
+                    "You have called '{}' {} times with {} and the outcome is not changing. \
+                     Stop repeating this call. Re-read the last error or output, check your \
+                     assumptions, and try a different tool or approach. \
+                     Calling it again will be blocked.",
+                    tool_name,
+                    count + 1,
+                    same
+                ),
+            };
+        }
+
+        if count >= ask {
             return DoomLoopAction::AskUser {
                 message: format!(
-                    "⚠️ Tool '{}' has been called {} times with the same parameters. \
+                    "⚠️ Tool '{}' has been called {} times with {}. \
                      This may indicate a loop. Continue anyway?",
                     tool_name,
-                    identical_count + 1
+                    count + 1,
+                    same
                 ),
             };
         }
 
-        if identical_count >= self.config.warn_threshold {
+        if count >= warn {
             return DoomLoopAction::Warn {
                 message: format!(
-                    "⚡ Note: '{}' called {} times with same parameters.",
+                    "⚡ Note: '{}' called {} times with {}.",
                     tool_name,
-                    identical_count + 1
+                    count + 1,
+                    same
                 ),
             };
         }
@@ -346,6 +504,59 @@ impl LoopDetector {
         DoomLoopAction::Continue
     }
 
+    /// Number of complete A-B cycles at the end of the history, counting
+    /// this call, where A and B are different calls
+    fn alternation_cycles(&self, call: &ToolCall) -> usize {
+        let calls: Vec<&ToolCall> = self.recent_calls.iter().chain(std::iter::once(call)).collect();
+        let n = calls.len();
+        if n < 4 || calls[n - 1].is_similar(calls[n - 2]) {
+            return 0;
+        }
+        let mut len = 2;
+        while len < n && calls[n - 1 - len].is_similar(calls[n + 1 - len]) {
+            len += 1;
+        }
+        len / 2
+    }
+
+    fn check_alternation(&self, call: &ToolCall) -> DoomLoopAction {
+        let cycles = self.alternation_cycles(call);
+        let threshold = self.config.alternation_threshold;
+        if cycles < 2 {
+            return DoomLoopAction::Continue;
+        }
+
+        let previous = &self.recent_calls[self.recent_calls.len() - 1].tool_name;
+        let pattern = format!("'{}' and '{}'", previous, call.tool_name);
+        if cycles > threshold {
+            DoomLoopAction::Block {
+                message: format!(
+                    "🛑 Doom loop detected: alternating between {} {} times. \
+                     This action has been blocked to prevent infinite loops. \
+                     Please try a different approach.",
+                    pattern, cycles
+                ),
+            }
+        } else if cycles == threshold {
+            DoomLoopAction::InjectGuidance {
+                message: format!(
+                    "You have alternated between {} {} times with the same parameters \
+                     without making progress. Stop and reconsider: the second call is \
+                     undoing or repeating the first. Try a different approach. \
+                     Continuing the pattern will be blocked.",
+                    pattern, cycles
+                ),
+            }
+        } else {
+            DoomLoopAction::Warn {
+                message: format!(
+                    "⚡ Note: alternating between {} ({} times).",
+                    pattern, cycles
+                ),
+            }
+        }
+    }
+
     /// Record a tool call after it's been executed
     /// Call this AFTER executing the tool
     pub fn record(&mut self, tool_name: &str, params: &Value) {
@@ -476,6 +687,98 @@ mod tests {
         assert!(matches!(action, DoomLoopAction::Block { .. }));
     }
 
+    #[test]
+    fn test_guidance_before_block() {
+        let mut detector = LoopDetector::new();
+        let params = json!({"path": "test.rs"});
+
+        for _ in 0..4 {
+            detector.record("read_file", &params);
+        }
+
+        let action = detector.check("read_file", &params);
+        assert!(matches!(action, DoomLoopAction::InjectGuidance { .. }));
+    }
+
+    #[test]
+    fn test_near_duplicates_count_as_repeats() {
+        let mut detector = LoopDetector::new();
+
+        detector.record("read_file", &json!({"file_path": "./src/main.rs", "offset": 0}));
+        detector.record("read_file", &json!({"file_path": "src//main.rs", "offset": 100}));
+        detector.record("read_file", &json!({"file_path": "src/lib/../main.rs", "limit": 50}));
+
+        let action = detector.check("read_file", &json!({"file_path": "src/main.rs"}));
+        match action {
+            DoomLoopAction::AskUser { message } => assert!(message.contains("nearly identical")),
+            other => panic!("expected AskUser, got {:?}", other),
+        }
+
+        // Disabling similarity falls back to exact matching
+        let mut detector = LoopDetector::with_config(LoopDetectorConfig {
+            detect_similar: false,
+            ..Default::default()
+        });
+        detector.record("read_file", &json!({"file_path": "./src/main.rs"}));
+        let action = detector.check("read_file", &json!({"file_path": "src/main.rs"}));
+        assert!(matches!(action, DoomLoopAction::Continue));
+    }
+
+    #[test]
+    fn test_alternation_detection() {
+        let mut detector = LoopDetector::new();
+        let a = json!({"file_path": "a.rs", "old_string": "x", "new_string": "y"});
+        let b = json!({"file_path": "a.rs", "old_string": "y", "new_string": "x"});
+
+        detector.record("edit_file", &a);
+        detector.record("edit_file", &b);
+        detector.record("edit_file", &a);
+        assert!(matches!(
+            detector.check("edit_file", &b),
+            DoomLoopAction::Warn { .. }
+        ));
+
+        detector.record("edit_file", &b);
+        detector.record("edit_file", &a);
+        match detector.check("edit_file", &b) {
+            DoomLoopAction::InjectGuidance { message } => assert!(message.contains("alternated")),
+            other => panic!("expected InjectGuidance, got {:?}", other),
+        }
+
+        detector.record("edit_file", &b);
+        detector.record("edit_file", &a);
+        assert!(matches!(
+            detector.check("edit_file", &b),
+            DoomLoopAction::Block { .. }
+        ));
+    }
+
+    #[test]
+    fn test_per_tool_thresholds() {
+        let mut config = LoopDetectorConfig::default();
+        config.tool_thresholds.insert(
+            "bash".to_string(),
+            ToolThresholds {
+                block: Some(1),
+                ..Default::default()
+            },
+        );
+        let mut detector = LoopDetector::with_config(config);
+        let params = json!({"command": "cargo   build"});
+
+        detector.record("bash", &params);
+        assert!(matches!(
+            detector.check("bash", &json!({"command": "cargo build"})),
+            DoomLoopAction::Block { .. }
+        ));
+
+        detector.record("read_file", &json!({"path": "a.rs"}));
+        assert!(matches!(
+            detector.check("read_file", &json!({"path": "a.rs"})),
+            DoomLoopAction::Warn { .. }
+        ));
+    }
+
     #[test]
     fn test_failure_loop_detection() {
         let mut detector = LoopDetector::new();
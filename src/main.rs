@@ -1,36 +1,58 @@
+// The OpenAPI spec is one large `json!` literal
+#![recursion_limit = "256"]
+
+mod acp;
 mod approval;
 mod auth;
 mod cache;
 mod checkpoint;
 pub mod client;
 mod commands;
+mod completions;
 mod config;
 mod context;
+mod crash;
 mod custom_commands;
+mod events;
 mod git;
+// The binary only drives hooks through `HookManager`; the rest of the API is
+// there for library users
+#[allow(dead_code, unused_imports)]
+mod hooks;
+mod ide;
 mod llm;
 mod loop_detector;
 mod lsp;
 mod mcp;
 mod memory;
+mod notifications;
 mod orchestrator;
+mod output_filters;
 mod permissions;
 mod persistence;
 mod planning;
+mod policy;
+mod platform;
 mod prompts;
 mod server;
 mod session;
+mod setup;
 mod shell;
+mod shutdown;
+mod skills;
 mod subagent;
+mod templates;
 mod tools;
 mod tui;
 mod unified_planning;
+mod update;
 mod utils;
 
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::ArgValueCandidates;
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use commands::{CommandParser, CommandResult};
@@ -66,8 +88,13 @@ struct Cli {
     resume_last: bool,
 
     /// Resume a specific session by ID
-    #[arg(long, global = true, value_name = "SESSION_ID")]
+    #[arg(long, global = true, value_name = "SESSION_ID", add = ArgValueCandidates::new(completions::session_ids))]
     resume_id: Option<String>,
+
+    /// Print newline-delimited JSON events on stdout instead of text
+    /// (run, chat and orchestrate)
+    #[arg(long, global = true)]
+    json_events: bool,
 }
 
 #[derive(Subcommand)]
@@ -110,16 +137,64 @@ enum Commands {
         /// Use the TUI (Terminal User Interface) mode
         #[arg(long, default_value = "true")]
         tui: bool,
-        /// Run in demo mode (no LLM API required)
+        /// Run a scripted demo session (no LLM API required)
         #[arg(long, default_value = "false")]
         demo: bool,
         /// Execution mode: plan (deep planning with approval) or act (auto-execute)
         #[arg(short, long, default_value = "act")]
         mode: String,
+        /// Pick up the last session in this project that did not exit cleanly
+        #[arg(long)]
+        recover: bool,
+        /// Work in a dedicated git worktree and review the changes before
+        /// merging them back when the session ends
+        #[arg(long)]
+        isolated: bool,
+    },
+    /// Run a single prompt without the interactive UI and print the reply
+    #[command(alias = "act")]
+    Run {
+        /// The prompt to send
+        prompt: String,
+        /// Path to the project directory (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+        /// Execution mode: plan or build
+        #[arg(short, long, default_value = "build")]
+        mode: String,
+        /// Replay responses from the persistent cache when the conversation
+        /// matches an earlier run, and record new ones
+        #[arg(long)]
+        cached: bool,
+        /// Send a file, directory or URL with the prompt (repeatable)
+        #[arg(long, value_name = "PATH|URL")]
+        attach: Vec<String>,
+    },
+    /// Show token usage, cost and tool statistics across past sessions
+    Stats {
+        /// How many days back to include
+        #[arg(short, long, default_value = "30")]
+        days: i64,
+        /// Only include sessions in this project directory
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        /// Output format: table, csv or json
+        #[arg(short, long, default_value = "table")]
+        format: String,
+        /// Write the report to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Inspect or clear the persistent LLM response cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommand,
     },
     /// Orchestrate complex tasks by delegating to multiple AI agents
-    #[command(alias = "orch")]
+    #[command(alias = "orch", args_conflicts_with_subcommands = true)]
     Orchestrate {
+        #[command(subcommand)]
+        action: Option<OrchestrateCommand>,
         /// The task or request to execute
         #[arg(short, long)]
         task: Option<String>,
@@ -147,6 +222,17 @@ enum Commands {
         /// Execution mode: plan (create plan and wait for approval) or act (auto-execute)
         #[arg(short, long, default_value = "act")]
         mode: String,
+        /// Only plan and estimate cost and time; no workspaces or workers
+        #[arg(long, requires = "task")]
+        dry_run: bool,
+        /// Show a live dashboard of the workers, with per-worker cancel and restart
+        #[arg(long, requires = "task", conflicts_with = "dry_run")]
+        watch: bool,
+    },
+    /// Queue orchestration tasks and work through them unattended
+    Queue {
+        #[command(subcommand)]
+        action: QueueCommand,
     },
     /// Configure safe-coder settings and authentication
     #[command(alias = "cfg")]
@@ -154,6 +240,9 @@ enum Commands {
         /// Show current configuration
         #[arg(short, long)]
         show: bool,
+        /// With --show, explain which file (or default) each value came from
+        #[arg(long, requires = "show")]
+        origin: bool,
         /// Set API key
         #[arg(long)]
         api_key: Option<String>,
@@ -168,27 +257,114 @@ enum Commands {
     Login {
         /// Provider to login to (anthropic or github-copilot)
         provider: String,
+        /// Store the login as a named account (e.g. work, personal)
+        #[arg(long)]
+        name: Option<String>,
     },
     /// Logout and clear stored credentials
     Logout {
         /// Provider to logout from (anthropic, github-copilot, or all)
         #[arg(default_value = "all")]
         provider: String,
+        /// Only remove this account (default: every account for the provider)
+        #[arg(long, add = ArgValueCandidates::new(completions::account_names))]
+        name: Option<String>,
+    },
+    /// List stored accounts or switch the active one
+    Accounts {
+        #[command(subcommand)]
+        action: AccountsCommand,
+    },
+    /// Set up safe-coder: provider login, default model, permission preset
+    /// and shell completions, written to the global config
+    Setup,
+    /// Print a shell completion script
+    ///
+    /// Session IDs, account names and skill packs complete too. Load it with
+    /// e.g. `source <(safe-coder completions bash)` in ~/.bashrc, or let
+    /// `safe-coder setup` install it.
+    Completions {
+        /// bash, zsh, fish, powershell or elvish
+        shell: clap_complete::Shell,
+    },
+    /// Show or refresh the model registry: context windows, list prices and
+    /// capabilities used for cost estimates, compaction and the model picker
+    Models {
+        #[command(subcommand)]
+        action: ModelsCommand,
+    },
+    /// Install the latest release over this executable
+    ///
+    /// The download is checked against the release's checksum and signature.
+    /// Disabled by `update.enabled = false`.
+    Update {
+        /// Only report whether a newer release is available
+        #[arg(long)]
+        check: bool,
+        /// Install even though no release key is known to check the
+        /// signature with, trusting the release's checksum alone
+        #[arg(long)]
+        allow_unsigned: bool,
     },
     /// Initialize a new project with safe-coder
     Init {
         /// Path to initialize (default: current directory)
         #[arg(default_value = ".")]
         path: PathBuf,
+        /// Scaffold a starter project: rust-cli, axum-api or react-ts
+        #[arg(long)]
+        template: Option<String>,
     },
     /// Resume a previous session
     Resume {
         /// Session ID to resume (shows picker if not provided)
+        #[arg(add = ArgValueCandidates::new(completions::session_ids))]
         session_id: Option<String>,
         /// Resume the most recent session instead of showing picker
         #[arg(long)]
         last: bool,
     },
+    /// Step through a previous session turn by turn
+    ///
+    /// Tool calls and their diffs are shown from the session log without
+    /// running anything. Press Enter for the next turn, or q to stop.
+    Replay {
+        /// Session ID to replay
+        #[arg(add = ArgValueCandidates::new(completions::session_ids))]
+        session_id: String,
+        /// Re-run the tool calls in a scratch git worktree of the project's HEAD
+        #[arg(long)]
+        re_execute: bool,
+    },
+    /// Download and decrypt a session shared with `/chat share`
+    ///
+    /// The session is saved as a chat in this machine's session store; open
+    /// it with `/chat resume <id>`.
+    ImportShare {
+        /// Share URL (a gist or paste link)
+        url: String,
+        /// Key printed by `/chat share` (asked for when omitted)
+        #[arg(long)]
+        key: Option<String>,
+        /// Print the conversation as Markdown instead of saving it
+        #[arg(long)]
+        markdown: bool,
+    },
+    /// Manage skill packs installed from git
+    Skill {
+        #[command(subcommand)]
+        action: SkillCommand,
+    },
+    /// Install or inspect the organization policy enforced over all config
+    Policy {
+        #[command(subcommand)]
+        action: PolicyCommand,
+    },
+    /// Authorize or forget remote MCP servers that use OAuth
+    Mcp {
+        #[command(subcommand)]
+        action: McpCommand,
+    },
     /// Start HTTP server for desktop app integration
     ///
     /// This starts an HTTP/WebSocket server that exposes safe-coder's
@@ -205,22 +381,219 @@ enum Commands {
         #[arg(long)]
         cors: bool,
     },
+    /// Serve the Agent Client Protocol on stdin/stdout for editors such as Zed
+    Acp,
+}
+
+#[derive(Subcommand)]
+enum SkillCommand {
+    /// Install a skill pack from a git URL or `gh:user/repo[@ref]`
+    Install {
+        /// Git URL or GitHub shorthand (gh:user/repo)
+        source: String,
+        /// Name to install the pack under (default: repository name)
+        #[arg(long)]
+        name: Option<String>,
+        /// Branch or tag to install
+        #[arg(long = "ref")]
+        reference: Option<String>,
+    },
+    /// Update one or all installed skill packs
+    Update {
+        /// Pack to update (default: all)
+        #[arg(add = ArgValueCandidates::new(completions::skill_pack_names))]
+        name: Option<String>,
+    },
+    /// Remove an installed skill pack
+    Remove {
+        /// Pack to remove
+        #[arg(add = ArgValueCandidates::new(completions::skill_pack_names))]
+        name: String,
+    },
+    /// List installed skill packs
+    List,
+}
+
+#[derive(Subcommand)]
+enum PolicyCommand {
+    /// Fetch a signed policy bundle from git and enforce it
+    Pull {
+        /// Git URL of the bundle (policy.toml and policy.toml.sig)
+        url: String,
+        /// Base64 Ed25519 public key the bundle is signed with (pinned after
+        /// the first pull)
+        #[arg(long)]
+        key: Option<String>,
+    },
+    /// Show the installed policy
+    Show,
+    /// Generate a key pair for signing policy bundles
+    Keygen,
+    /// Sign a policy.toml, writing policy.toml.sig next to it
+    Sign {
+        /// Policy file to sign
+        #[arg(default_value = "policy.toml")]
+        file: PathBuf,
+        /// File holding the base64 secret key from `policy keygen`
+        #[arg(long)]
+        secret_key: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum OrchestrateCommand {
+    /// Show the prompt, output and diff recorded for a task of a past run
+    Logs {
+        /// Run id, or `latest` (default: list runs)
+        run: Option<String>,
+        /// Task id (default: list the run's tasks)
+        task: Option<String>,
+        /// Project the run belongs to (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum QueueCommand {
+    /// Add a task to the end of the queue
+    Add {
+        /// The task or request to orchestrate
+        task: String,
+        /// Preferred worker for this task: claude, gemini, safe-coder or copilot
+        #[arg(short, long)]
+        worker: Option<String>,
+        /// Project to queue the task for (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Show queued, running and finished tasks
+    List {
+        /// Project whose queue to show (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Remove a task that is not running
+    Remove {
+        /// Queue id shown by `queue list`
+        id: u32,
+        /// Project the task was queued for (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Remove finished tasks from the queue
+    Clear {
+        /// Project whose queue to clear (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Work through the queue with the orchestrator
+    Run {
+        /// Queued tasks to run at the same time
+        #[arg(long, default_value = "1")]
+        parallel: usize,
+        /// Keep waiting for new tasks when the queue is empty
+        #[arg(long)]
+        follow: bool,
+        /// Default worker for tasks that don't name one
+        #[arg(short, long, default_value = "claude")]
+        worker: String,
+        /// Maximum concurrent workers per task
+        #[arg(long, default_value = "3")]
+        max_workers: usize,
+        /// Project whose queue to run (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommand {
+    /// Show entry count, size and hit rate
+    Stats,
+    /// Delete every cached response
+    Clear,
+}
+
+#[derive(Subcommand)]
+enum AccountsCommand {
+    /// Show stored accounts per provider; the active one is starred
+    List,
+    /// Make an account the default for a provider
+    Switch {
+        /// Provider (anthropic or github-copilot)
+        provider: String,
+        /// Account name given at login
+        #[arg(add = ArgValueCandidates::new(completions::account_names))]
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ModelsCommand {
+    /// List known models with their context windows and list prices
+    List,
+    /// Download the latest model registry
+    Refresh {
+        /// Registry to download instead of the one in the safe-coder repository
+        #[arg(long, default_value = llm::models::REGISTRY_URL)]
+        url: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum McpCommand {
+    /// Authorize safe-coder with an MCP server via OAuth
+    Login {
+        /// Server name from the [mcp] config
+        server: String,
+        /// Project whose config defines the server (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
+    /// Remove stored OAuth credentials for an MCP server
+    Logout {
+        /// Server name from the [mcp] config
+        server: String,
+    },
+    /// Connect to the configured MCP servers and show their state, latency and tools
+    Status {
+        /// Project whose config defines the servers (default: current directory)
+        #[arg(short, long, default_value = ".")]
+        path: PathBuf,
+    },
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Answers the callbacks of `safe-coder completions` scripts, then exits
+    clap_complete::CompleteEnv::with_factory(Cli::command)
+        .var(completions::COMPLETE_VAR)
+        .complete();
+
     let cli = Cli::parse();
+    crash::install_panic_hook();
 
     // Only initialize tracing for non-TUI modes
     // TUI mode uses its own rendering and tracing would interfere with the alternate screen
     let use_tui = match &cli.command {
+        _ if cli.json_events => false,
         Some(Commands::Chat { tui: true, .. }) => true,
         Some(Commands::Shell { no_tui: false, .. }) => true,
         None if !cli.no_tui => true, // Default shell mode uses TUI
         _ => false,
     };
 
-    if !use_tui {
+    if cli.json_events || matches!(cli.command, Some(Commands::Acp)) {
+        // stdout carries the event stream or protocol, so logs go to stderr
+        tracing_subscriber::registry()
+            .with(
+                tracing_subscriber::EnvFilter::try_from_default_env()
+                    .unwrap_or_else(|_| "safe_coder=warn".into()),
+            )
+            .with(tracing_subscriber::fmt::layer().with_writer(io::stderr))
+            .init();
+    } else if !use_tui {
         tracing_subscriber::registry()
             .with(
                 tracing_subscriber::EnvFilter::try_from_default_env()
@@ -251,6 +624,11 @@ async fn main() -> Result<()> {
         }
     }
 
+    // Signals stop the command and drain what it registered, see shutdown.rs
+    shutdown::run(dispatch(cli)).await
+}
+
+async fn dispatch(cli: Cli) -> Result<()> {
     match cli.command.unwrap_or(Commands::Shell {
         path: cli.path,
         ai: cli.ai,
@@ -270,10 +648,46 @@ async fn main() -> Result<()> {
             tui,
             demo,
             mode,
+            recover,
+            isolated,
+        } => {
+            if cli.json_events {
+                if isolated {
+                    anyhow::bail!("--isolated asks for a review at the end and can't be used with --json-events");
+                }
+                run_chat_json(path, mode, recover).await?;
+            } else {
+                run_chat(path, tui, demo, mode, recover, isolated).await?;
+            }
+        }
+        Commands::Run {
+            prompt,
+            path,
+            mode,
+            cached,
+            attach,
         } => {
-            run_chat(path, tui, demo, mode).await?;
+            run_headless(prompt, path, mode, cached, attach, cli.json_events).await?;
+        }
+        Commands::Cache { action } => {
+            handle_cache_command(action).await?;
+        }
+        Commands::Stats {
+            days,
+            path,
+            format,
+            output,
+        } => {
+            handle_stats(days, path, &format, output).await?;
         }
         Commands::Orchestrate {
+            action: Some(OrchestrateCommand::Logs { run, task, path }),
+            ..
+        } => {
+            handle_orchestrate_logs(&path, run.as_deref(), task.as_deref())?;
+        }
+        Commands::Orchestrate {
+            action: None,
             task,
             path,
             worker,
@@ -283,6 +697,8 @@ async fn main() -> Result<()> {
             gemini_max,
             start_delay_ms,
             mode,
+            dry_run,
+            watch,
         } => {
             run_orchestrate(
                 task,
@@ -294,32 +710,78 @@ async fn main() -> Result<()> {
                 gemini_max,
                 start_delay_ms,
                 mode,
+                dry_run,
+                watch,
+                cli.json_events,
             )
             .await?;
         }
+        Commands::Queue { action } => {
+            handle_queue_command(action).await?;
+        }
         Commands::Config {
             show,
+            origin,
             api_key,
             model,
             pick_model: _, // Model picker is only for TUI, ignore here
         } => {
-            handle_config(show, api_key, model)?;
+            handle_config(show, origin, api_key, model)?;
         }
-        Commands::Login { provider } => {
-            handle_login(&provider).await?;
+        Commands::Login { provider, name } => {
+            handle_login(&provider, name.as_deref()).await?;
         }
-        Commands::Logout { provider } => {
-            handle_logout(&provider)?;
+        Commands::Logout { provider, name } => {
+            handle_logout(&provider, name.as_deref())?;
         }
-        Commands::Init { path } => {
-            init_project(path)?;
+        Commands::Accounts { action } => {
+            handle_accounts_command(action)?;
+        }
+        Commands::Setup => {
+            handle_setup().await?;
+        }
+        Commands::Completions { shell } => {
+            completions::write_script(shell, &mut io::stdout())?;
+        }
+        Commands::Models { action } => {
+            handle_models_command(action).await?;
+        }
+        Commands::Update {
+            check,
+            allow_unsigned,
+        } => {
+            handle_update(check, allow_unsigned).await?;
+        }
+        Commands::Init { path, template } => {
+            init_project(path, template.as_deref())?;
         }
         Commands::Resume { session_id, last } => {
             handle_resume(session_id, last).await?;
         }
+        Commands::Replay {
+            session_id,
+            re_execute,
+        } => {
+            handle_replay(&session_id, re_execute).await?;
+        }
+        Commands::ImportShare { url, key, markdown } => {
+            handle_import_share(&url, key, markdown).await?;
+        }
+        Commands::Skill { action } => {
+            handle_skill_command(action).await?;
+        }
+        Commands::Policy { action } => {
+            handle_policy_command(action).await?;
+        }
+        Commands::Mcp { action } => {
+            handle_mcp_command(action).await?;
+        }
         Commands::Serve { port, host, cors } => {
             run_server(port, host, cors).await?;
         }
+        Commands::Acp => {
+            acp::run().await?;
+        }
     }
 
     Ok(())
@@ -337,7 +799,14 @@ async fn run_server(port: u16, host: String, cors: bool) -> Result<()> {
     server::start_server(config).await
 }
 
-async fn run_chat(project_path: PathBuf, use_tui: bool, demo: bool, mode: String) -> Result<()> {
+async fn run_chat(
+    project_path: PathBuf,
+    use_tui: bool,
+    demo: bool,
+    mode: String,
+    recover: bool,
+    isolated: bool,
+) -> Result<()> {
     use approval::UserMode;
 
     let canonical_path = project_path.canonicalize()?;
@@ -345,31 +814,71 @@ async fn run_chat(project_path: PathBuf, use_tui: bool, demo: bool, mode: String
     // Parse user mode
     let user_mode = UserMode::from_str(&mode)?;
 
-    // Demo mode - no API required
+    // Demo mode - a scripted session, no API required
     if demo && use_tui {
-        let mut tui_runner = tui::TuiRunner::new(canonical_path.display().to_string());
-        tui_runner.initialize().await?;
-        tui_runner.run_demo().await?;
-        return Ok(());
+        return tui::run_shell_demo(canonical_path).await;
     }
 
+    // With --isolated the session works in its own worktree; the real
+    // working tree is only touched if the changes are merged at the end
+    let mut isolation = None;
+    let session_path = if isolated {
+        let mut workspaces = orchestrator::WorkspaceManager::new(canonical_path.clone(), true)?;
+        let workspace_id = format!("chat-{}", chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let workspace_path = workspaces
+            .create_workspace(&workspace_id)
+            .await
+            .context("Failed to create an isolated worktree")?;
+        isolation = Some((workspaces, workspace_id));
+        workspace_path
+    } else {
+        canonical_path.clone()
+    };
+
     let config = Config::load()?;
-    let mut session = Session::new(config, canonical_path.clone()).await?;
+    let mut session = Session::new(config, session_path.clone()).await?;
 
     // Set user mode
     session.set_user_mode(user_mode);
 
+    let recovery_note = if recover {
+        match session.recover_last_session().await? {
+            Some(count) => format!("♻️  Recovered {} messages from the last unfinished session", count),
+            None => "No unfinished session to recover in this project".to_string(),
+        }
+    } else {
+        String::new()
+    };
+
     // Show mode on startup
     let mode_desc = match user_mode {
         UserMode::Plan => "PLAN mode - deep planning with approval before execution",
         UserMode::Build => "BUILD mode - lightweight planning with auto-execution",
     };
 
+    let isolation_note = match &isolation {
+        Some((_, id)) => format!(
+            "🌿 Isolated session: working in {} on branch safe-coder/{}. Changes are reviewed before merging when you exit.",
+            session_path.display(),
+            id
+        ),
+        None => String::new(),
+    };
+
     if use_tui {
         // Use TUI mode - skip session.start() as it outputs to stdout and interferes with TUI
-        let mut tui_runner = tui::TuiRunner::new(canonical_path.display().to_string());
+        let mut tui_runner = tui::TuiRunner::new(session_path.display().to_string());
         tui_runner.initialize().await?;
+        if !isolation_note.is_empty() {
+            tui_runner.add_system_message(&isolation_note);
+        }
+        if !recovery_note.is_empty() {
+            tui_runner.add_system_message(&recovery_note);
+        }
         tui_runner.run(session).await?;
+        if let Some((workspaces, id)) = isolation {
+            review_isolated_session(workspaces, &id).await?;
+        }
         return Ok(());
     }
 
@@ -381,6 +890,12 @@ async fn run_chat(project_path: PathBuf, use_tui: bool, demo: bool, mode: String
     println!("Project: {}", canonical_path.display());
     println!("Mode: {}", mode_desc);
     println!("Type '/help' for commands or 'exit' to quit\n");
+    if !isolation_note.is_empty() {
+        println!("{}\n", isolation_note);
+    }
+    if !recovery_note.is_empty() {
+        println!("{}\n", recovery_note);
+    }
 
     // Interactive loop
     loop {
@@ -454,149 +969,531 @@ async fn run_chat(project_path: PathBuf, use_tui: bool, demo: bool, mode: String
         }
     }
 
+    if let Some((workspaces, id)) = isolation {
+        review_isolated_session(workspaces, &id).await?;
+    }
+
     Ok(())
 }
 
-/// Run the orchestrator to delegate tasks to external CLI agents
-async fn run_orchestrate(
-    task: Option<String>,
-    project_path: PathBuf,
-    worker: String,
-    use_worktrees: bool,
-    max_workers: usize,
-    claude_max: Option<usize>,
-    gemini_max: Option<usize>,
-    start_delay_ms: Option<u64>,
-    mode: String,
+/// End an `--isolated` chat: show what changed in its worktree and ask
+/// whether to merge, discard or keep it
+async fn review_isolated_session(
+    mut workspaces: orchestrator::WorkspaceManager,
+    id: &str,
 ) -> Result<()> {
-    use approval::UserMode;
+    let summary = workspaces.diff_summary(id).await?;
+    if summary.is_empty() {
+        println!("No changes were made in the isolated session.");
+        return workspaces.cleanup_workspace(id).await;
+    }
 
-    let canonical_path = project_path.canonicalize()?;
+    let target = workspaces.original_branch().unwrap_or("HEAD").to_string();
+    println!("\nChanges made in the isolated session:\n{}\n", summary);
+    print!("[m]erge into {}, [d]iscard, or [k]eep the worktree for later (default): ", target);
+    io::stdout().flush()?;
 
-    // Parse user mode
-    let user_mode = UserMode::from_str(&mode)?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
 
-    // Load config for throttle limits
-    let user_config = Config::load().unwrap_or_default();
-
-    // Helper function to parse worker string to WorkerKind
-    fn parse_worker_kind(s: &str) -> WorkerKind {
-        match s.to_lowercase().as_str() {
-            "claude" | "claude-code" => WorkerKind::ClaudeCode,
-            "gemini" | "gemini-cli" => WorkerKind::GeminiCli,
-            "safe-coder" | "safecoder" => WorkerKind::SafeCoder,
-            "github-copilot" | "copilot" | "gh-copilot" => WorkerKind::GitHubCopilot,
-            _ => WorkerKind::ClaudeCode,
-        }
-    }
-
-    // Parse worker preference
-    let default_worker = parse_worker_kind(&worker);
-
-    // Parse worker strategy from config
-    let worker_strategy = match user_config
-        .orchestrator
-        .worker_strategy
-        .to_lowercase()
-        .as_str()
-    {
-        "single" | "single-worker" => orchestrator::WorkerStrategy::SingleWorker,
-        "round-robin" | "roundrobin" => orchestrator::WorkerStrategy::RoundRobin,
-        "task-based" | "taskbased" => orchestrator::WorkerStrategy::TaskBased,
-        "load-balanced" | "loadbalanced" => orchestrator::WorkerStrategy::LoadBalanced,
-        _ => orchestrator::WorkerStrategy::SingleWorker,
-    };
+    match answer.trim().to_lowercase().as_str() {
+        "m" | "merge" => {
+            workspaces.merge_workspace(id).await?;
+            workspaces.cleanup_workspace(id).await?;
+            println!("✅ Merged into {}.", target);
+        }
+        "d" | "discard" => {
+            workspaces.cleanup_workspace(id).await?;
+            println!("🗑️  Discarded the isolated changes.");
+        }
+        _ => {
+            if let Some(path) = workspaces.get_workspace(id) {
+                println!(
+                    "Kept the worktree at {} (branch safe-coder/{}).",
+                    path.display(),
+                    id
+                );
+            }
+        }
+    }
 
-    // Parse enabled workers from config
-    let enabled_workers: Vec<WorkerKind> = user_config
-        .orchestrator
-        .enabled_workers
-        .iter()
-        .map(|s| parse_worker_kind(s))
-        .collect();
+    Ok(())
+}
 
-    // Create orchestrator config (CLI args override config file)
-    let config = orchestrator::OrchestratorConfig {
-        claude_cli_path: Some(user_config.orchestrator.claude_cli_path.clone()),
-        gemini_cli_path: Some(user_config.orchestrator.gemini_cli_path.clone()),
-        safe_coder_cli_path: Some(user_config.orchestrator.safe_coder_cli_path.clone()),
-        gh_cli_path: Some(user_config.orchestrator.gh_cli_path.clone()),
-        max_workers,
-        default_worker,
-        worker_strategy,
-        enabled_workers,
-        use_worktrees,
-        throttle_limits: orchestrator::ThrottleLimits {
-            claude_max_concurrent: claude_max.unwrap_or(
-                user_config
-                    .orchestrator
-                    .throttle_limits
-                    .claude_max_concurrent,
-            ),
-            gemini_max_concurrent: gemini_max.unwrap_or(
-                user_config
-                    .orchestrator
-                    .throttle_limits
-                    .gemini_max_concurrent,
-            ),
-            safe_coder_max_concurrent: user_config
-                .orchestrator
-                .throttle_limits
-                .safe_coder_max_concurrent,
-            copilot_max_concurrent: user_config
-                .orchestrator
-                .throttle_limits
-                .copilot_max_concurrent,
-            start_delay_ms: start_delay_ms
-                .unwrap_or(user_config.orchestrator.throttle_limits.start_delay_ms),
-        },
-        user_mode,
+/// `orchestrate --json-events`: stream the workers' events, then one `diff`
+/// event per changed file. The changes are merged, or kept on their branches
+/// when `review_merges` is set since nobody can review them here. Returns
+/// the run summary.
+async fn run_orchestrate_json(orchestrator: &mut Orchestrator, request: &str) -> Result<String> {
+    let review = orchestrator.config.review_merges;
+    // Hold every task's changes so their diffs can be reported before merging
+    orchestrator.config.review_merges = true;
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+    orchestrator.set_event_sender(event_tx);
+
+    let plan = orchestrator.plan_request(request).await?;
+    let mut response = {
+        let execute = orchestrator.execute_plan(plan);
+        tokio::pin!(execute);
+        let result = loop {
+            tokio::select! {
+                result = &mut execute => break result,
+                Some(event) = event_rx.recv() => events::from_worker_event(event).emit(),
+            }
+        };
+        while let Ok(event) = event_rx.try_recv() {
+            events::from_worker_event(event).emit();
+        }
+        result?
     };
 
-    // Create orchestrator
-    let mut orchestrator = Orchestrator::new(canonical_path.clone(), config).await?;
+    for task in &response.pending_review {
+        for (path, patch) in events::split_patch(&task.diff.patch) {
+            events::JsonEvent::Diff {
+                path,
+                patch,
+                task_id: Some(task.task_id.clone()),
+            }
+            .emit();
+        }
+    }
+    if review {
+        orchestrator.keep_for_review(&mut response).await?;
+    } else {
+        let accepted: Vec<String> = response
+            .pending_review
+            .iter()
+            .map(|task| task.task_id.clone())
+            .collect();
+        orchestrator.merge_tasks(&mut response, &accepted).await?;
+    }
+    Ok(response.summary)
+}
 
-    let mode_desc = match user_mode {
-        UserMode::Plan => "PLAN (requires approval before execution)",
-        UserMode::Build => "BUILD (auto-execute)",
-    };
+/// `chat --json-events`: every line on stdin is a message; each turn ends
+/// with `cost` and `done` events. The session ends at end of input.
+async fn run_chat_json(project_path: PathBuf, mode: String, recover: bool) -> Result<()> {
+    use approval::UserMode;
+    use tokio::io::AsyncBufReadExt;
 
-    println!("🎯 Safe Coder Orchestrator");
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("Project: {}", canonical_path.display());
-    println!("Mode: {}", mode_desc);
-    println!("Default worker: {:?}", orchestrator.config.default_worker);
-    println!(
-        "Max concurrent workers: {}",
-        orchestrator.config.max_workers
-    );
-    println!("Using worktrees: {}", use_worktrees);
-    println!("Throttle limits:");
-    println!(
-        "  - Claude max concurrent: {}",
-        orchestrator.config.throttle_limits.claude_max_concurrent
-    );
-    println!(
-        "  - Gemini max concurrent: {}",
-        orchestrator.config.throttle_limits.gemini_max_concurrent
-    );
-    println!(
-        "  - Start delay: {}ms",
-        orchestrator.config.throttle_limits.start_delay_ms
-    );
-    println!();
+    let canonical_path = project_path.canonicalize()?;
+    let config = Config::load_for_project(&canonical_path)?;
+    let mut mapper = events::SessionEventMapper::new(config.llm.model.clone());
+    let mut session = Session::new(config, canonical_path).await?;
+    session.set_user_mode(UserMode::from_str(&mode)?);
+    if recover {
+        session.recover_last_session().await?;
+    }
+    session.start().await?;
 
-    // If task provided via CLI, execute it directly
-    if let Some(task_text) = task {
-        println!("📋 Processing task: {}", task_text);
-        println!();
+    let mut lines = tokio::io::BufReader::new(tokio::io::stdin()).lines();
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        // A failed turn is reported in its `done` event; the session goes on
+        let _ = send_message_json(&mut session, &mut mapper, line.to_string(), Vec::new()).await;
+    }
 
-        match orchestrator.process_request(&task_text).await {
-            Ok(response) => {
-                println!("{}", response.summary);
-            }
-            Err(e) => {
-                eprintln!("❌ Orchestration failed: {}", e);
+    session.stop().await
+}
+
+/// Send `message` and stream what happens as JSON events
+async fn send_message_json(
+    session: &mut Session,
+    mapper: &mut events::SessionEventMapper,
+    message: String,
+    images: Vec<(String, String)>,
+) -> Result<String> {
+    let (event_tx, mut event_rx) = tokio::sync::mpsc::unbounded_channel();
+    let send = session.send_message_with_images_and_progress(message, images, event_tx);
+    tokio::pin!(send);
+
+    let result = loop {
+        tokio::select! {
+            result = &mut send => break result,
+            Some(event) = event_rx.recv() => {
+                if let Some(event) = mapper.map(event) {
+                    event.emit();
+                }
+            }
+        }
+    };
+    while let Ok(event) = event_rx.try_recv() {
+        if let Some(event) = mapper.map(event) {
+            event.emit();
+        }
+    }
+
+    mapper.cost().emit();
+    events::JsonEvent::done(&result).emit();
+    result
+}
+
+/// Send one prompt through a session and print the final reply
+async fn run_headless(
+    prompt: String,
+    project_path: PathBuf,
+    mode: String,
+    cached: bool,
+    attach: Vec<String>,
+    json_events: bool,
+) -> Result<()> {
+    use approval::UserMode;
+
+    // Attachment paths are relative to where the command was run
+    let cwd = std::env::current_dir()?;
+    let mut attachments = Vec::with_capacity(attach.len());
+    for spec in &attach {
+        attachments.push(session::attachments::resolve(spec, &cwd).await?);
+    }
+    let prompt = session::attachments::append_to_message(&prompt, &attachments);
+    let images = session::attachments::images(&attachments);
+
+    let canonical_path = project_path.canonicalize()?;
+    let mut config = Config::load_for_project(&canonical_path)?;
+    if cached {
+        config.cache.enabled = true;
+        config.cache.application_cache = true;
+        config.cache.persistent = true;
+    }
+
+    let model = config.llm.model.clone();
+    let mut session = Session::new(config, canonical_path).await?;
+    session.set_user_mode(UserMode::from_str(&mode)?);
+    if json_events {
+        let mut mapper = events::SessionEventMapper::new(model);
+        return send_message_json(&mut session, &mut mapper, prompt, images)
+            .await
+            .map(|_| ());
+    }
+    let response = if images.is_empty() {
+        session.send_message(prompt).await?
+    } else {
+        // Images go through the progress path; its events aren't shown here
+        let (event_tx, _event_rx) = tokio::sync::mpsc::unbounded_channel();
+        session
+            .send_message_with_images_and_progress(prompt, images, event_tx)
+            .await?
+    };
+    if !response.is_empty() {
+        println!("{}", response);
+    }
+
+    Ok(())
+}
+
+fn handle_orchestrate_logs(path: &Path, run: Option<&str>, task: Option<&str>) -> Result<()> {
+    use orchestrator::artifacts;
+
+    let project_path = path.canonicalize().context("Invalid project path")?;
+    let Some(run) = run else {
+        let runs = artifacts::list_runs(&project_path);
+        if runs.is_empty() {
+            println!("No orchestration runs recorded in this project yet.");
+        } else {
+            println!("Orchestration runs (newest first):");
+            for run in runs {
+                println!("  {}", run);
+            }
+        }
+        return Ok(());
+    };
+
+    let run_dir = artifacts::find_run(&project_path, run)?;
+    match task {
+        Some(task) => print!("{}", artifacts::format_task(&run_dir, task)?),
+        None => {
+            println!("Tasks in {}:", run_dir.display());
+            for (task_id, outcome) in artifacts::list_tasks(&run_dir) {
+                match outcome {
+                    Some(outcome) => println!(
+                        "  {} {}: {} ({:?})",
+                        if outcome.success { "✓" } else { "✗" },
+                        task_id,
+                        outcome.description,
+                        outcome.worker
+                    ),
+                    None => println!("  ? {}: did not finish", task_id),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn handle_queue_command(action: QueueCommand) -> Result<()> {
+    use orchestrator::queue::{self, QueueStore};
+
+    match action {
+        QueueCommand::Add { task, worker, path } => {
+            let project_path = path.canonicalize().context("Invalid project path")?;
+            let item = QueueStore::new(&project_path)
+                .add(&task, worker.as_deref().map(WorkerKind::from_name))?;
+            println!("✓ Queued task {}: {}", item.id, item.task);
+        }
+        QueueCommand::List { path } => {
+            let project_path = path.canonicalize().context("Invalid project path")?;
+            let items = QueueStore::new(&project_path).load()?;
+            if items.is_empty() {
+                println!("The queue is empty. Add tasks with `safe-coder queue add \"...\"`.");
+            }
+            for item in items {
+                println!("{} {:>3}  {}", item.status.icon(), item.id, item.task);
+                if let Some(result) = &item.result {
+                    println!("        {}", result);
+                }
+                if let Some(run_id) = &item.run_id {
+                    println!("        logs: safe-coder orchestrate logs {}", run_id);
+                }
+                for branch in &item.branches {
+                    println!("        review: git diff HEAD...{}", branch);
+                }
+            }
+        }
+        QueueCommand::Remove { id, path } => {
+            let project_path = path.canonicalize().context("Invalid project path")?;
+            let item = QueueStore::new(&project_path).remove(id)?;
+            println!("✓ Removed task {}: {}", item.id, item.task);
+        }
+        QueueCommand::Clear { path } => {
+            let project_path = path.canonicalize().context("Invalid project path")?;
+            let removed = QueueStore::new(&project_path).clear_finished()?;
+            println!("✓ Removed {} finished task(s)", removed);
+        }
+        QueueCommand::Run {
+            parallel,
+            follow,
+            worker,
+            max_workers,
+            path,
+        } => {
+            let project_path = path.canonicalize().context("Invalid project path")?;
+            let user_config = Config::load_for_project(&project_path).unwrap_or_default();
+            let config = build_orchestrator_config(
+                &user_config,
+                WorkerKind::from_name(&worker),
+                true,
+                max_workers,
+                None,
+                None,
+                None,
+                approval::UserMode::Build,
+            );
+            println!("🎯 Working through the queue of {}", project_path.display());
+            queue::run_queue(&project_path, config, parallel, follow).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn handle_cache_command(action: CacheCommand) -> Result<()> {
+    use cache::CacheStore;
+
+    let config = Config::load()?;
+    let disk_cache = config.cache.disk_cache()?;
+    match action {
+        CacheCommand::Stats => {
+            let stats = disk_cache.stats().await;
+            println!("Response cache: {}", disk_cache.dir().display());
+            println!(
+                "  Entries:      {} ({:.1} MB of {} MB)",
+                stats.entries,
+                stats.size_bytes as f64 / (1024.0 * 1024.0),
+                config.cache.max_size_mb
+            );
+            println!(
+                "  Hits/misses:  {}/{} ({:.1}% hit rate)",
+                stats.hits,
+                stats.misses,
+                stats.hit_rate()
+            );
+            println!(
+                "  Tokens saved: {} (~${:.2})",
+                stats.tokens_saved, stats.estimated_cost_saved
+            );
+        }
+        CacheCommand::Clear => {
+            disk_cache.clear().await;
+            println!("Cleared response cache at {}", disk_cache.dir().display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Aggregate the usage stats recorded by past sessions
+async fn handle_stats(
+    days: i64,
+    project_path: Option<PathBuf>,
+    format: &str,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    use persistence::usage::UsageReport;
+    use persistence::SessionPersistence;
+
+    let mut records = SessionPersistence::new().await?.recent_stats(days).await?;
+    if let Some(path) = project_path {
+        let path = path.canonicalize()?.to_string_lossy().to_string();
+        records.retain(|r| r.project_path == path);
+    }
+    let report = UsageReport::build(&records);
+
+    let rendered = match format {
+        "table" => format!(
+            "📊 Usage over the last {} days\n━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n{}",
+            days,
+            report.format_tables(15)
+        ),
+        "csv" => report.to_csv(),
+        "json" => serde_json::to_string_pretty(&report)?,
+        other => anyhow::bail!("Unknown format '{}'. Use table, csv or json.", other),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, rendered)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            println!("Wrote usage report to {}", path.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+/// Run the orchestrator to delegate tasks to external CLI agents
+async fn run_orchestrate(
+    task: Option<String>,
+    project_path: PathBuf,
+    worker: String,
+    use_worktrees: bool,
+    max_workers: usize,
+    claude_max: Option<usize>,
+    gemini_max: Option<usize>,
+    start_delay_ms: Option<u64>,
+    mode: String,
+    dry_run: bool,
+    watch: bool,
+    json_events: bool,
+) -> Result<()> {
+    use approval::UserMode;
+
+    let canonical_path = project_path.canonicalize()?;
+
+    // Parse user mode
+    let user_mode = UserMode::from_str(&mode)?;
+    if watch && user_mode == UserMode::Plan {
+        anyhow::bail!("--watch needs act mode; use --dry-run to review the plan first");
+    }
+    if json_events && (watch || user_mode == UserMode::Plan || task.is_none()) {
+        anyhow::bail!("--json-events needs --task and act mode, without --watch");
+    }
+
+    // Load config for throttle limits and routing rules, including the project's own
+    let user_config = Config::load_for_project(&canonical_path).unwrap_or_default();
+
+    let config = build_orchestrator_config(
+        &user_config,
+        WorkerKind::from_name(&worker),
+        use_worktrees,
+        max_workers,
+        claude_max,
+        gemini_max,
+        start_delay_ms,
+        user_mode,
+    );
+
+    // Create orchestrator
+    let mut orchestrator = Orchestrator::new(canonical_path.clone(), config).await?;
+
+    if dry_run {
+        let task_text = task.unwrap_or_default();
+        // The model only refines the estimates, so go on without it
+        let client = match llm::create_client(&user_config).await {
+            Ok(client) => Some(client),
+            Err(e) => {
+                eprintln!("⚠️  No LLM available ({}), using heuristic estimates", e);
+                None
+            }
+        };
+        let report = orchestrator
+            .dry_run(&task_text, &user_config.llm.model, client.as_deref())
+            .await?;
+        println!("{}", report.format());
+        return Ok(());
+    }
+
+    if let (true, Some(task_text)) = (json_events, &task) {
+        let result = run_orchestrate_json(&mut orchestrator, task_text).await;
+        events::JsonEvent::done(&result).emit();
+        orchestrator.cleanup().await?;
+        return result.map(|_| ());
+    }
+
+    let mode_desc = match user_mode {
+        UserMode::Plan => "PLAN (requires approval before execution)",
+        UserMode::Build => "BUILD (auto-execute)",
+    };
+
+    println!("🎯 Safe Coder Orchestrator");
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("Project: {}", canonical_path.display());
+    println!("Mode: {}", mode_desc);
+    println!("Default worker: {:?}", orchestrator.config.default_worker);
+    println!(
+        "Max concurrent workers: {}",
+        orchestrator.config.max_workers
+    );
+    println!("Using worktrees: {}", use_worktrees);
+    println!("Throttle limits:");
+    println!(
+        "  - Claude max concurrent: {}",
+        orchestrator.config.throttle_limits.claude_max_concurrent
+    );
+    println!(
+        "  - Gemini max concurrent: {}",
+        orchestrator.config.throttle_limits.gemini_max_concurrent
+    );
+    println!(
+        "  - Start delay: {}ms",
+        orchestrator.config.throttle_limits.start_delay_ms
+    );
+    println!();
+
+    let notifier = notifications::Notifier::new(user_config.notifications.clone());
+    let webhooks = notifications::webhook::WebhookNotifier::from_config(&user_config.notifications);
+
+    // If task provided via CLI, execute it directly
+    if let Some(task_text) = task {
+        println!("📋 Processing task: {}", task_text);
+        println!();
+
+        let result = if watch {
+            let (returned, result) =
+                tui::run_orchestrator_dashboard(orchestrator, task_text).await?;
+            orchestrator = returned;
+            result
+        } else {
+            orchestrator.process_request(&task_text).await
+        };
+
+        match result {
+            Ok(mut response) => {
+                if let Err(e) = orchestrator.review_in_terminal(&mut response).await {
+                    eprintln!("❌ Merge failed: {}", e);
+                }
+                println!("{}", response.summary);
+                notifier.notify_and_wait(orchestration_notification(&response));
+                if let Some(webhooks) = &webhooks {
+                    for event in orchestration_webhook_events(&response) {
+                        webhooks.send(&event).await;
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("❌ Orchestration failed: {}", e);
             }
         }
 
@@ -662,8 +1559,17 @@ async fn run_orchestrate(
         println!();
 
         match orchestrator.process_request(input).await {
-            Ok(response) => {
+            Ok(mut response) => {
+                if let Err(e) = orchestrator.review_in_terminal(&mut response).await {
+                    eprintln!("❌ Merge failed: {}", e);
+                }
                 println!("\n{}", response.summary);
+                notifier.notify(orchestration_notification(&response));
+                if let Some(webhooks) = &webhooks {
+                    for event in orchestration_webhook_events(&response) {
+                        webhooks.notify(event);
+                    }
+                }
             }
             Err(e) => {
                 eprintln!("❌ Error: {}", e);
@@ -674,6 +1580,84 @@ async fn run_orchestrate(
     Ok(())
 }
 
+/// Orchestrator settings from the user's config and `orchestrate` options
+#[allow(clippy::too_many_arguments)]
+fn build_orchestrator_config(
+    user_config: &Config,
+    default_worker: WorkerKind,
+    use_worktrees: bool,
+    max_workers: usize,
+    claude_max: Option<usize>,
+    gemini_max: Option<usize>,
+    start_delay_ms: Option<u64>,
+    user_mode: approval::UserMode,
+) -> orchestrator::OrchestratorConfig {
+    let mut config = orchestrator::OrchestratorConfig::from_config(user_config);
+
+    // CLI args override the config file
+    config.default_worker = default_worker;
+    config.use_worktrees = use_worktrees;
+    config.max_workers = max_workers;
+    let limits = &mut config.throttle_limits;
+    limits.claude_max_concurrent = claude_max.unwrap_or(limits.claude_max_concurrent);
+    limits.gemini_max_concurrent = gemini_max.unwrap_or(limits.gemini_max_concurrent);
+    limits.start_delay_ms = start_delay_ms.unwrap_or(limits.start_delay_ms);
+    config.user_mode = user_mode;
+    config
+}
+
+/// Desktop notification for a finished orchestration run
+fn orchestration_notification(
+    response: &orchestrator::OrchestratorResponse,
+) -> notifications::Notification {
+    let failed = response
+        .task_results
+        .iter()
+        .filter(|r| r.result.is_err())
+        .count();
+    notifications::Notification::OrchestrationComplete {
+        succeeded: response.task_results.len() - failed,
+        failed,
+    }
+}
+
+/// Webhook messages for a finished orchestration run: one per failed task, then the summary
+fn orchestration_webhook_events(
+    response: &orchestrator::OrchestratorResponse,
+) -> Vec<notifications::webhook::WebhookEvent> {
+    use notifications::webhook::WebhookEvent;
+
+    let mut events: Vec<WebhookEvent> = response
+        .task_results
+        .iter()
+        .filter_map(|r| {
+            let error = r.result.as_ref().err()?;
+            let task = response
+                .plan
+                .tasks
+                .iter()
+                .find(|t| t.id == r.task_id)
+                .map(|t| t.description.clone())
+                .unwrap_or_else(|| r.task_id.clone());
+            Some(WebhookEvent::TaskFailed {
+                task,
+                worker: format!("{:?}", r.worker_kind),
+                error: error.clone(),
+                session_id: None,
+            })
+        })
+        .collect();
+    let failed = events.len();
+    events.push(WebhookEvent::OrchestrationComplete {
+        request: response.plan.original_request.clone(),
+        succeeded: response.task_results.len() - failed,
+        failed,
+        summary: response.summary.clone(),
+        session_id: None,
+    });
+    events
+}
+
 fn print_orchestrator_help() {
     println!();
     println!("🎯 Orchestrator Commands:");
@@ -688,15 +1672,26 @@ fn print_orchestrator_help() {
     println!();
 }
 
-fn handle_config(show: bool, api_key: Option<String>, model: Option<String>) -> Result<()> {
-    let mut config = Config::load()?;
-
+fn handle_config(
+    show: bool,
+    origin: bool,
+    api_key: Option<String>,
+    model: Option<String>,
+) -> Result<()> {
     if show {
+        let (config, origins) = Config::load_with_origins(&std::env::current_dir()?)?;
         println!("Current configuration:");
-        println!("{}", toml::to_string_pretty(&config)?);
+        if origin {
+            print!("{}", origins.explain(&config)?);
+        } else {
+            println!("{}", toml::to_string_pretty(&config)?);
+        }
         return Ok(());
     }
 
+    // Edits go to the global file only, so project overrides don't leak into it
+    let mut config = Config::load_global()?;
+
     let mut changed = false;
 
     if let Some(key) = api_key {
@@ -721,20 +1716,24 @@ fn handle_config(show: bool, api_key: Option<String>, model: Option<String>) ->
     Ok(())
 }
 
-async fn handle_login(provider: &str) -> Result<()> {
+fn parse_auth_provider(provider: &str) -> Result<config::LlmProvider> {
+    use config::LlmProvider;
+
+    match provider.to_lowercase().as_str() {
+        "anthropic" | "claude" => Ok(LlmProvider::Anthropic),
+        "github-copilot" | "copilot" => Ok(LlmProvider::GitHubCopilot),
+        _ => anyhow::bail!(
+            "Unknown provider '{}'. Supported: anthropic, github-copilot",
+            provider
+        ),
+    }
+}
+
+async fn handle_login(provider: &str, name: Option<&str>) -> Result<()> {
     use auth::run_device_flow;
     use config::{Config, LlmProvider};
 
-    let llm_provider = match provider.to_lowercase().as_str() {
-        "anthropic" | "claude" => LlmProvider::Anthropic,
-        "github-copilot" | "copilot" => LlmProvider::GitHubCopilot,
-        _ => {
-            anyhow::bail!(
-                "Unknown provider '{}'. Supported: anthropic, github-copilot",
-                provider
-            );
-        }
-    };
+    let llm_provider = parse_auth_provider(provider)?;
 
     let token = match llm_provider {
         LlmProvider::GitHubCopilot => {
@@ -751,10 +1750,18 @@ async fn handle_login(provider: &str) -> Result<()> {
     };
 
     // Save the token
-    let token_path = Config::token_path(&llm_provider)?;
-    token.save(&token_path)?;
+    Config::save_account_token(&llm_provider, name, &token)?;
 
-    println!("\nToken saved to: {:?}", token_path);
+    println!(
+        "\nToken saved to the {}",
+        auth::store::default_store()?.name()
+    );
+    if let Some(name) = name {
+        println!(
+            "Stored as account '{}'. Use `safe-coder accounts switch {} {}` to make it the default.",
+            name, provider, name
+        );
+    }
     println!(
         "\nYou can now use safe-coder with your {} account!",
         provider
@@ -763,38 +1770,203 @@ async fn handle_login(provider: &str) -> Result<()> {
     Ok(())
 }
 
-fn handle_logout(provider: &str) -> Result<()> {
-    use config::{Config, LlmProvider};
+async fn handle_policy_command(action: PolicyCommand) -> Result<()> {
+    match action {
+        PolicyCommand::Pull { url, key } => {
+            println!("Pulling policy from {}...", url);
+            let installed = policy::pull(&url, key.as_deref()).await?;
+            println!("✓ Signature verified; enforcing {}", installed.origin());
+            print_policy(&installed.policy)?;
+        }
+        PolicyCommand::Show => match policy::InstalledPolicy::load()? {
+            Some(installed) => {
+                println!("📜 {} (from {})\n", installed.origin(), installed.url);
+                print_policy(&installed.policy)?;
+            }
+            None => println!("No organization policy installed."),
+        },
+        PolicyCommand::Keygen => {
+            let (secret, public) = policy::generate_keypair();
+            println!("Secret key (keep private, pass to `policy sign`):\n  {}\n", secret);
+            println!("Public key (give to `policy pull --key`):\n  {}", public);
+        }
+        PolicyCommand::Sign { file, secret_key } => {
+            let secret = std::fs::read_to_string(&secret_key)
+                .with_context(|| format!("Failed to read {}", secret_key.display()))?;
+            let content = std::fs::read(&file)
+                .with_context(|| format!("Failed to read {}", file.display()))?;
+            let signature_path = file.with_file_name(policy::SIGNATURE_FILE);
+            std::fs::write(&signature_path, policy::sign(&content, &secret)?)?;
+            println!("✓ Wrote {}", signature_path.display());
+        }
+    }
+    Ok(())
+}
 
-    let providers_to_clear: Vec<LlmProvider> = match provider.to_lowercase().as_str() {
-        "anthropic" | "claude" => vec![LlmProvider::Anthropic],
-        "github-copilot" | "copilot" => vec![LlmProvider::GitHubCopilot],
-        "all" => vec![LlmProvider::Anthropic, LlmProvider::GitHubCopilot],
-        _ => {
-            anyhow::bail!(
-                "Unknown provider '{}'. Supported: anthropic, github-copilot, all",
-                provider
+fn print_policy(policy: &policy::Policy) -> Result<()> {
+    print!("{}", toml::to_string_pretty(policy)?);
+    Ok(())
+}
+
+async fn handle_skill_command(action: SkillCommand) -> Result<()> {
+    let packs = skills::SkillPackManager::user()?;
+
+    match action {
+        SkillCommand::Install {
+            source,
+            name,
+            reference,
+        } => {
+            println!("Installing skill pack from {}...", source);
+            let pack = packs
+                .install(&source, name.as_deref(), reference.as_deref())
+                .await?;
+            println!(
+                "✓ Installed '{}' ({}) to {}",
+                pack.name,
+                pack.version,
+                packs.skills_dir().join(&pack.name).display()
             );
         }
+        SkillCommand::Update { name } => {
+            let updated = packs.update(name.as_deref()).await?;
+            if updated.is_empty() {
+                println!("No skill packs installed.");
+            }
+            for (pack, previous) in updated {
+                if pack.commit == previous {
+                    println!("  {} is up to date ({})", pack.name, pack.version);
+                } else {
+                    println!(
+                        "✓ Updated {} {} -> {}",
+                        pack.name,
+                        &previous[..previous.len().min(7)],
+                        pack.version
+                    );
+                }
+            }
+        }
+        SkillCommand::Remove { name } => {
+            let pack = packs.remove(&name)?;
+            println!("✓ Removed skill pack '{}'", pack.name);
+        }
+        SkillCommand::List => {
+            let installed = packs.list()?;
+            if installed.is_empty() {
+                println!("No skill packs installed.");
+                println!("Install one with: safe-coder skill install gh:user/repo");
+            }
+            for pack in installed {
+                let reference = pack
+                    .reference
+                    .map(|r| format!(" [{}]", r))
+                    .unwrap_or_default();
+                println!(
+                    "  {:<20} {:<12}{} {}",
+                    pack.name, pack.version, reference, pack.url
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_mcp_command(action: McpCommand) -> Result<()> {
+    use auth::mcp::{McpCredentials, McpOAuth};
+
+    match action {
+        McpCommand::Login { server, path } => {
+            let config = Config::load_for_project(&path)?;
+            let server_config = config
+                .mcp
+                .servers
+                .iter()
+                .find(|s| s.name == server)
+                .with_context(|| format!("No MCP server named '{}' is configured", server))?;
+            let url = server_config
+                .url
+                .as_deref()
+                .with_context(|| format!("MCP server '{}' is not a remote server", server))?;
+
+            let credentials = McpOAuth::new(url)?
+                .authorize(server_config.oauth_client_id.clone())
+                .await?;
+            credentials.save(&server)?;
+            println!(
+                "\n✓ Authorized MCP server '{}'. Token saved to the {}",
+                server,
+                auth::store::default_store()?.name()
+            );
+        }
+        McpCommand::Logout { server } => {
+            if McpCredentials::remove(&server)? {
+                println!("Cleared credentials for MCP server '{}'", server);
+            } else {
+                println!("No stored credentials found for MCP server '{}'.", server);
+            }
+        }
+        McpCommand::Status { path } => {
+            let config = Config::load_for_project(&path)?;
+            if !config.mcp.enabled {
+                println!("MCP is disabled. Set `enabled = true` under [mcp] to use servers.\n");
+            }
+            let mut manager = mcp::McpManager::new(config.mcp);
+            manager.initialize(&path).await?;
+            print!("{}", mcp::format_server_status(&manager.server_status().await));
+            manager.shutdown().await?;
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_logout(provider: &str, name: Option<&str>) -> Result<()> {
+    use auth::accounts::{Accounts, DEFAULT_ACCOUNT};
+    use config::{Config, LlmProvider};
+
+    let providers_to_clear: Vec<LlmProvider> = if provider.eq_ignore_ascii_case("all") {
+        vec![LlmProvider::Anthropic, LlmProvider::GitHubCopilot]
+    } else {
+        vec![parse_auth_provider(provider)?]
     };
 
+    let mut accounts = Accounts::load()?;
     let mut cleared_any = false;
 
     for llm_provider in providers_to_clear {
-        if let Ok(token_path) = Config::token_path(&llm_provider) {
-            if token_path.exists() {
-                match std::fs::remove_file(&token_path) {
-                    Ok(_) => {
+        let key = Config::provider_key(&llm_provider)?;
+        let names = match name {
+            Some(name) => vec![name.to_string()],
+            None => {
+                let mut names = accounts.names(key);
+                if !names.iter().any(|n| n == DEFAULT_ACCOUNT) {
+                    names.push(DEFAULT_ACCOUNT.to_string());
+                }
+                names
+            }
+        };
+
+        for account in names {
+            accounts.remove(key, &account);
+            let token_path = Config::account_token_path(&llm_provider, Some(&account))?;
+            match auth::StoredToken::remove(&token_path) {
+                Ok(true) => {
+                    if account == DEFAULT_ACCOUNT {
                         println!("Cleared credentials for {:?}", llm_provider);
-                        cleared_any = true;
-                    }
-                    Err(e) => {
-                        eprintln!("Failed to remove {:?}: {}", token_path, e);
+                    } else {
+                        println!("Cleared credentials for {:?} account '{}'", llm_provider, account);
                     }
+                    cleared_any = true;
+                }
+                Ok(false) => {}
+                Err(e) => {
+                    eprintln!("Failed to remove credentials for {:?}: {}", llm_provider, e);
                 }
             }
         }
     }
+    accounts.save()?;
 
     if cleared_any {
         println!("\nCredentials cleared. Run 'safe-coder login <provider>' to re-authenticate.");
@@ -805,6 +1977,336 @@ fn handle_logout(provider: &str) -> Result<()> {
     Ok(())
 }
 
+fn handle_accounts_command(action: AccountsCommand) -> Result<()> {
+    use auth::accounts::Accounts;
+    use config::{Config, LlmProvider};
+
+    let mut accounts = Accounts::load()?;
+    match action {
+        AccountsCommand::List => {
+            let mut any = false;
+            for llm_provider in [LlmProvider::Anthropic, LlmProvider::GitHubCopilot] {
+                let key = Config::provider_key(&llm_provider)?;
+                let names = accounts.names(key);
+                if names.is_empty() {
+                    continue;
+                }
+                any = true;
+                println!("{:?}:", llm_provider);
+                for name in names {
+                    let marker = if accounts.active(key) == Some(name.as_str()) { "*" } else { " " };
+                    println!("  {} {}", marker, name);
+                }
+            }
+            if !any {
+                println!("No stored accounts. Run 'safe-coder login <provider> [--name <account>]'.");
+            }
+        }
+        AccountsCommand::Switch { provider, name } => {
+            let llm_provider = parse_auth_provider(&provider)?;
+            accounts.switch(Config::provider_key(&llm_provider)?, &name)?;
+            accounts.save()?;
+            println!("Now using {:?} account '{}'", llm_provider, name);
+        }
+    }
+
+    Ok(())
+}
+
+/// First-run wizard behind `safe-coder setup`
+async fn handle_setup() -> Result<()> {
+    use config::LlmProvider;
+
+    println!("🛠  safe-coder setup\n");
+    let config_path = Config::config_path()?;
+    if config_path.exists() {
+        println!("Updating {}; press Enter to keep a default.\n", config_path.display());
+    }
+    let mut config = Config::load_global()?;
+
+    println!("Agent CLIs for `safe-coder orchestrate`:");
+    let clis = setup::detect_clis(&config.orchestrator);
+    for cli in &clis {
+        match &cli.path {
+            Some(path) => println!("  ✓ {} ({})", cli.worker, path.display()),
+            None => println!("  ✗ {} not installed", cli.worker),
+        }
+    }
+    setup::configure_workers(&mut config.orchestrator, &clis);
+    println!(
+        "  Tasks will go to: {}\n",
+        config.orchestrator.enabled_workers.join(", ")
+    );
+
+    println!("Provider:");
+    for (i, (_, description)) in setup::PROVIDERS.iter().enumerate() {
+        println!("  {}. {}", i + 1, description);
+    }
+    let current = setup::PROVIDERS
+        .iter()
+        .position(|(provider, _)| *provider == config.llm.provider)
+        .unwrap_or(0);
+    let provider = setup::PROVIDERS[prompt_choice(setup::PROVIDERS.len(), current)?]
+        .0
+        .clone();
+    let provider_changed = provider != config.llm.provider;
+    config.llm.provider = provider.clone();
+
+    // A key from the environment is saved as a reference, never literally
+    let env_key = setup::api_key_env(&provider).filter(|var| std::env::var(var).is_ok());
+    let env_reference = env_key.map(|var| format!("${{env:{}}}", var));
+    config.llm.api_key = None;
+    match provider {
+        LlmProvider::Anthropic | LlmProvider::GitHubCopilot => {
+            let name = if provider == LlmProvider::Anthropic {
+                "anthropic"
+            } else {
+                "github-copilot"
+            };
+            let use_env = match env_key {
+                Some(var) => prompt_yes_no(&format!("Use the key in ${}?", var), true)?,
+                None => false,
+            };
+            if use_env {
+                config.llm.api_key = env_reference.clone();
+            } else if prompt_yes_no("Log in now?", true)? {
+                handle_login(name, None).await?;
+            } else {
+                println!("Log in later with `safe-coder login {}`.", name);
+            }
+        }
+        LlmProvider::OpenAI | LlmProvider::OpenRouter | LlmProvider::OpenAIGeneric => {
+            if provider == LlmProvider::OpenAIGeneric {
+                let url = prompt_line("Server URL (e.g. http://localhost:8000/v1)", None)?;
+                config.llm.base_url = (!url.is_empty()).then_some(url);
+            }
+            config.llm.api_key = match env_key {
+                Some(var) => {
+                    let key = prompt_line(&format!("API key (Enter to use ${})", var), None)?;
+                    if key.is_empty() {
+                        env_reference.clone()
+                    } else {
+                        Some(key)
+                    }
+                }
+                None => Some(prompt_line("API key", None)?).filter(|key| !key.is_empty()),
+            };
+            if config.llm.api_key.is_none() && provider != LlmProvider::OpenAIGeneric {
+                println!("No key set; add one later with `safe-coder config --api-key <key>`.");
+            }
+        }
+        LlmProvider::Ollama => {
+            let url = prompt_line("Ollama URL", Some("http://localhost:11434"))?;
+            config.llm.base_url = Some(url);
+        }
+    }
+
+    println!("\nDefault model:");
+    let models = setup::suggested_models(&provider);
+    for (i, model) in models.iter().enumerate() {
+        println!("  {}. {}", i + 1, model);
+    }
+    let default_model = if provider_changed || config.llm.model.is_empty() {
+        models.first().copied().unwrap_or_default().to_string()
+    } else {
+        config.llm.model.clone()
+    };
+    let answer = prompt_line(
+        "Number or model name",
+        (!default_model.is_empty()).then_some(default_model.as_str()),
+    )?;
+    config.llm.model = match answer.parse::<usize>() {
+        Ok(n) if (1..=models.len()).contains(&n) => models[n - 1].to_string(),
+        _ => answer,
+    };
+
+    println!("\nPermission preset:");
+    for (i, (name, description)) in setup::PRESET_DESCRIPTIONS.iter().enumerate() {
+        println!("  {}. {:<5} {}", i + 1, name, description);
+    }
+    let current = config
+        .permissions
+        .preset
+        .as_deref()
+        .and_then(|preset| setup::PRESET_DESCRIPTIONS.iter().position(|(name, _)| *name == preset))
+        .unwrap_or(0);
+    let preset = setup::PRESET_DESCRIPTIONS[prompt_choice(setup::PRESET_DESCRIPTIONS.len(), current)?].0;
+    config.permissions.preset = Some(preset.to_string());
+
+    config.save()?;
+    println!("\n✓ Configuration saved to {}", config_path.display());
+
+    if let Some(shell) = clap_complete::Shell::from_env() {
+        if prompt_yes_no(&format!("\nInstall {} completions?", shell), true)? {
+            install_completions(shell)?;
+        }
+    }
+
+    println!("\nAll set. Run `safe-coder` in a project to start.");
+    Ok(())
+}
+
+/// Write the completion script for `shell` where the shell picks it up
+fn install_completions(shell: clap_complete::Shell) -> Result<()> {
+    let home = dirs::home_dir().context("Could not determine home directory")?;
+    let Some((path, note)) = setup::completion_target(shell, &home) else {
+        println!(
+            "Load the output of `safe-coder completions {}` from your {} profile.",
+            shell, shell
+        );
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut script = Vec::new();
+    completions::write_script(shell, &mut script)?;
+    std::fs::write(&path, script)?;
+    println!("✓ Completions written to {}", path.display());
+    if let Some(note) = note {
+        println!("  {}", note);
+    }
+    Ok(())
+}
+
+async fn handle_models_command(action: ModelsCommand) -> Result<()> {
+    use llm::models::{registry, ModelRegistry};
+
+    match action {
+        ModelsCommand::List => {
+            let registry = registry();
+            let price = |price: Option<f64>| price.map_or("-".to_string(), |p| format!("{:.2}", p));
+            println!("Model registry of {}\n", registry.updated);
+            println!(
+                "{:<20} {:>9} {:>8} {:>8} {:>8}  CAPABILITIES",
+                "MODEL", "CONTEXT", "OUTPUT", "IN $/M", "OUT $/M"
+            );
+            for info in &registry.models {
+                let mut capabilities = Vec::new();
+                if info.tools != Some(false) {
+                    capabilities.push("tools");
+                }
+                if info.vision == Some(true) {
+                    capabilities.push("vision");
+                }
+                println!(
+                    "{:<20} {:>9} {:>8} {:>8} {:>8}  {}",
+                    info.id,
+                    info.context_window,
+                    info.max_output.map_or("-".to_string(), |t| t.to_string()),
+                    price(info.input_price),
+                    price(info.output_price),
+                    capabilities.join(", ")
+                );
+            }
+        }
+        ModelsCommand::Refresh { url } => {
+            let refreshed = llm::models::refresh(&url).await?;
+            println!(
+                "✓ Saved the model registry of {} ({} models) to {}",
+                refreshed.updated,
+                refreshed.models.len(),
+                llm::models::refreshed_path()?.display()
+            );
+            let bundled = ModelRegistry::bundled();
+            if refreshed.updated < bundled.updated {
+                println!(
+                    "  The registry built into this version is newer ({}) and stays in use.",
+                    bundled.updated
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Check for a newer release and, unless `check`, install it
+async fn handle_update(check: bool, allow_unsigned: bool) -> Result<()> {
+    // A project's .safe-coder/config.toml must not choose where the binary
+    // comes from or which key it is checked against
+    let config = Config::load_global_with_policy()?;
+    if !config.update.enabled {
+        anyhow::bail!(
+            "Updates are turned off (update.enabled = false); this installation is managed elsewhere"
+        );
+    }
+
+    let version = env!("CARGO_PKG_VERSION");
+    let installed = update::BUILD_TAG
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("v{}", version));
+    let release = update::latest_release(&config.update.repository).await?;
+    if !update::is_newer(&release.tag_name, update::BUILD_TAG, version) {
+        println!("safe-coder {} is up to date.", installed);
+        return Ok(());
+    }
+    if check {
+        println!(
+            "safe-coder {} is available (installed: {}). Run `safe-coder update` to install it.",
+            release.tag_name, installed
+        );
+        return Ok(());
+    }
+
+    println!("Downloading safe-coder {}...", release.tag_name);
+    let (binary, signed) =
+        update::download_verified(&release, &config.update, allow_unsigned).await?;
+    if signed {
+        println!("✓ Checksum and signature verified");
+    } else {
+        println!("⚠ Checksum verified; the signature wasn't checked (--allow-unsigned)");
+    }
+
+    let exe = std::env::current_exe()
+        .and_then(|exe| exe.canonicalize())
+        .context("Could not locate the safe-coder executable")?;
+    update::install(&binary, &exe)?;
+    println!(
+        "✓ Updated {} from {} to {}",
+        exe.display(),
+        installed,
+        release.tag_name
+    );
+    Ok(())
+}
+
+/// Ask for a line of input, returning `default` (or "") when left empty
+fn prompt_line(question: &str, default: Option<&str>) -> Result<String> {
+    match default {
+        Some(default) => print!("{} [{}]: ", question, default),
+        None => print!("{}: ", question),
+    }
+    io::stdout().flush()?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    let input = input.trim();
+    Ok(if input.is_empty() {
+        default.unwrap_or_default().to_string()
+    } else {
+        input.to_string()
+    })
+}
+
+/// Ask for one of `count` numbered options; returns its index
+fn prompt_choice(count: usize, default: usize) -> Result<usize> {
+    loop {
+        let answer = prompt_line("Choice", Some(&(default + 1).to_string()))?;
+        match answer.parse::<usize>() {
+            Ok(n) if (1..=count).contains(&n) => return Ok(n - 1),
+            _ => println!("Enter a number from 1 to {}", count),
+        }
+    }
+}
+
+fn prompt_yes_no(question: &str, default: bool) -> Result<bool> {
+    let answer = prompt_line(question, Some(if default { "Y/n" } else { "y/N" }))?;
+    Ok(match answer.to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    })
+}
+
 async fn handle_anthropic_login() -> Result<auth::StoredToken> {
     use auth::anthropic::{AnthropicAuth, AuthMode};
 
@@ -897,10 +2399,39 @@ async fn handle_anthropic_login() -> Result<auth::StoredToken> {
     }
 }
 
-fn init_project(path: PathBuf) -> Result<()> {
+fn init_project(path: PathBuf, template: Option<&str>) -> Result<()> {
+    let template = match template {
+        Some(name) => Some(templates::find(name).with_context(|| {
+            format!(
+                "Unknown template '{}'. Available: {}",
+                name,
+                templates::names().join(", ")
+            )
+        })?),
+        None => None,
+    };
     std::fs::create_dir_all(&path)?;
 
     println!("✓ Initialized safe-coder project at: {}", path.display());
+    if let Some(template) = template {
+        let result = templates::scaffold(template, &path)?;
+        println!("\n📦 {} ({})", template.name, template.description);
+        for file in &result.created {
+            println!("  created {}", file.display());
+        }
+        for file in &result.skipped {
+            println!("  kept existing {}", file.display());
+        }
+        println!(
+            "\n  Project memory, config and skills are in {}",
+            path.join(".safe-coder").display()
+        );
+        println!("  Get started:");
+        println!("     cd {}", path.display());
+        for step in template.next_steps {
+            println!("     {}", step);
+        }
+    }
     println!("\nNext steps:");
     println!("  1. Configure authentication:");
     println!("     safe-coder login anthropic        # Login with Claude");
@@ -934,6 +2465,92 @@ async fn run_shell_legacy(project_path: PathBuf, connect_ai: bool) -> Result<()>
 }
 
 /// Handle session resumption
+async fn handle_replay(session_id: &str, re_execute: bool) -> Result<()> {
+    use persistence::event_log::EventLogger;
+    use persistence::replay::{self, Scratch};
+    use std::io::IsTerminal;
+
+    let sessions = EventLogger::list_recent_sessions(30)?;
+    let Some(info) = sessions.into_iter().find(|s| s.session_id == session_id) else {
+        println!("Session not found: {}", session_id);
+        println!("\nUse 'safe-coder resume' to see available sessions.");
+        return Ok(());
+    };
+
+    let turns = replay::split_turns(EventLogger::load_messages_from(&info.log_path)?);
+    println!("⏪ Replaying session: {}", info.session_id);
+    println!("   Project: {}", info.project_path);
+    println!("   Model: {}", info.model);
+    println!("   Turns: {}", turns.len());
+
+    let scratch = if re_execute {
+        let project_path = PathBuf::from(&info.project_path);
+        let config = Config::load_for_project(&project_path).unwrap_or_default();
+        let scratch = Scratch::new(&project_path, config.tools).await?;
+        println!("   Re-running tools in: {}", scratch.path().display());
+        Some(scratch)
+    } else {
+        None
+    };
+
+    let interactive = io::stdin().is_terminal();
+    for (i, turn) in turns.iter().enumerate() {
+        println!("\n━━━ Turn {}/{} ━━━\n", i + 1, turns.len());
+        print!("{}", replay::render_turn(turn, scratch.as_ref()).await);
+
+        if interactive && i + 1 < turns.len() {
+            print!("[Enter] next turn, [q] quit: ");
+            io::stdout().flush()?;
+            let mut input = String::new();
+            io::stdin().read_line(&mut input)?;
+            if input.trim() == "q" {
+                break;
+            }
+        }
+    }
+
+    if let Some(scratch) = scratch {
+        scratch.cleanup().await;
+    }
+    Ok(())
+}
+
+async fn handle_import_share(url: &str, key: Option<String>, markdown: bool) -> Result<()> {
+    use persistence::share;
+    use persistence::SessionPersistence;
+
+    let key = match key {
+        Some(key) => key,
+        None => {
+            print!("Share key: ");
+            io::stdout().flush()?;
+            let mut key = String::new();
+            io::stdin().read_line(&mut key)?;
+            key.trim().to_string()
+        }
+    };
+
+    let chat = share::import(url, &key).await?;
+    if markdown {
+        print!("{}", chat.markdown);
+        return Ok(());
+    }
+
+    let project_path = std::env::current_dir()?;
+    let persistence = SessionPersistence::new().await?;
+    let id = persistence
+        .save_session(chat.name.clone(), &project_path, &chat.messages)
+        .await?;
+    println!(
+        "✓ Imported {} ({} messages, shared {})",
+        chat.name.as_deref().unwrap_or("session"),
+        chat.messages.len(),
+        chat.created_at.format("%Y-%m-%d %H:%M")
+    );
+    println!("  Saved as chat {}; open it with /chat resume {}", id, id);
+    Ok(())
+}
+
 async fn handle_resume(session_id: Option<String>, last: bool) -> Result<()> {
     use persistence::event_log::EventLogger;
 
@@ -2,16 +2,33 @@
 
 use anyhow::{Context, Result};
 use rmcp::{
-    model::{CallToolRequestParam, Tool as RmcpTool},
-    transport::{ConfigureCommandExt, TokioChildProcess},
-    ServiceExt,
+    model::{
+        CallToolRequestParam, ClientRequest, GetPromptRequestParam, Prompt as RmcpPrompt,
+        PromptMessageContent, ReadResourceRequestParam, Resource as RmcpResource,
+        ResourceContents, Tool as RmcpTool,
+    },
+    transport::{
+        common::client_side_sse::ExponentialBackoff, sse_client::SseClientConfig,
+        streamable_http_client::StreamableHttpClientTransportConfig, ConfigureCommandExt,
+        SseClientTransport, StreamableHttpClientTransport, TokioChildProcess,
+    },
+    ServiceError, ServiceExt,
 };
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::process::Command;
 use tokio::sync::RwLock;
+use tracing::warn;
 
-use super::config::McpServerConfig;
+use super::config::{McpServerConfig, McpTransport};
+use crate::auth::mcp::{McpCredentials, McpOAuth};
+
+/// Upper bound on the delay between reconnect attempts.
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// How long a liveness ping may take before the server counts as unresponsive.
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// State of an MCP client connection.
 #[derive(Debug, Clone, PartialEq)]
@@ -22,6 +39,17 @@ pub enum McpClientState {
     Failed(String),
 }
 
+impl std::fmt::Display for McpClientState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            McpClientState::Disconnected => write!(f, "disconnected"),
+            McpClientState::Connecting => write!(f, "connecting"),
+            McpClientState::Connected => write!(f, "connected"),
+            McpClientState::Failed(reason) => write!(f, "failed: {}", reason),
+        }
+    }
+}
+
 /// Tool definition from MCP server.
 #[derive(Debug, Clone)]
 pub struct McpToolDefinition {
@@ -40,6 +68,62 @@ impl From<RmcpTool> for McpToolDefinition {
     }
 }
 
+/// Resource exposed by an MCP server.
+#[derive(Debug, Clone)]
+pub struct McpResourceDefinition {
+    pub uri: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+}
+
+impl From<RmcpResource> for McpResourceDefinition {
+    fn from(resource: RmcpResource) -> Self {
+        let resource = resource.raw;
+        Self {
+            uri: resource.uri,
+            name: resource.name,
+            description: resource.description,
+            mime_type: resource.mime_type,
+        }
+    }
+}
+
+/// Argument accepted by an MCP prompt.
+#[derive(Debug, Clone)]
+pub struct McpPromptArgument {
+    pub name: String,
+    pub description: Option<String>,
+    pub required: bool,
+}
+
+/// Prompt template exposed by an MCP server.
+#[derive(Debug, Clone)]
+pub struct McpPromptDefinition {
+    pub name: String,
+    pub description: Option<String>,
+    pub arguments: Vec<McpPromptArgument>,
+}
+
+impl From<RmcpPrompt> for McpPromptDefinition {
+    fn from(prompt: RmcpPrompt) -> Self {
+        Self {
+            name: prompt.name,
+            description: prompt.description,
+            arguments: prompt
+                .arguments
+                .unwrap_or_default()
+                .into_iter()
+                .map(|arg| McpPromptArgument {
+                    name: arg.name,
+                    description: arg.description,
+                    required: arg.required.unwrap_or(false),
+                })
+                .collect(),
+        }
+    }
+}
+
 /// The rmcp client handle type.
 type RmcpClientHandle = rmcp::service::RunningService<rmcp::RoleClient, ()>;
 
@@ -49,6 +133,14 @@ pub struct McpClient {
     service: Option<RmcpClientHandle>,
     state: RwLock<McpClientState>,
     tools: RwLock<Vec<McpToolDefinition>>,
+    resources: RwLock<Vec<McpResourceDefinition>>,
+    prompts: RwLock<Vec<McpPromptDefinition>>,
+    /// Working directory from the last `connect`, reused when reconnecting.
+    working_dir: Option<PathBuf>,
+    /// OAuth credentials from `safe-coder mcp login`, for remote servers.
+    credentials: Option<McpCredentials>,
+    /// Restarts after crashes so far this session.
+    restarts: u32,
 }
 
 impl McpClient {
@@ -59,12 +151,142 @@ impl McpClient {
             service: None,
             state: RwLock::new(McpClientState::Disconnected),
             tools: RwLock::new(Vec::new()),
+            resources: RwLock::new(Vec::new()),
+            prompts: RwLock::new(Vec::new()),
+            working_dir: None,
+            credentials: None,
+            restarts: 0,
         }
     }
 
     /// Connect to the MCP server and initialize the connection.
     pub async fn connect(&mut self, working_dir: &Path) -> Result<()> {
         *self.state.write().await = McpClientState::Connecting;
+        self.working_dir = Some(working_dir.to_path_buf());
+
+        let service = match self.config.effective_transport() {
+            McpTransport::Stdio => self.serve_stdio(working_dir).await,
+            McpTransport::Sse | McpTransport::Http => self.serve_remote().await,
+        };
+        let service = match service {
+            Ok(service) => service,
+            Err(e) => {
+                *self.state.write().await = McpClientState::Failed(e.to_string());
+                return Err(e);
+            }
+        };
+
+        self.service = Some(service);
+
+        // Discover tools
+        if let Err(e) = self.refresh_tools().await {
+            *self.state.write().await = McpClientState::Failed(e.to_string());
+            return Err(e);
+        }
+
+        // Resources and prompts are optional extras - a server that fails to
+        // list them is still usable for its tools
+        if let Err(e) = self.refresh_resources().await {
+            warn!("Failed to list resources from '{}': {}", self.config.name, e);
+        }
+        if let Err(e) = self.refresh_prompts().await {
+            warn!("Failed to list prompts from '{}': {}", self.config.name, e);
+        }
+
+        *self.state.write().await = McpClientState::Connected;
+        Ok(())
+    }
+
+    /// Drop the current connection and connect again, backing off between attempts.
+    pub async fn reconnect(&mut self) -> Result<()> {
+        let working_dir = self
+            .working_dir
+            .clone()
+            .context("MCP server was never connected")?;
+        if self.config.max_reconnect_attempts == 0 {
+            anyhow::bail!(
+                "Lost connection to MCP server '{}' (reconnect disabled)",
+                self.config.name
+            );
+        }
+
+        if let Some(service) = self.service.take() {
+            let _ = service.cancel().await;
+        }
+
+        let mut attempt = 1;
+        loop {
+            match self.connect(&working_dir).await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt >= self.config.max_reconnect_attempts => {
+                    return Err(e.context(format!(
+                        "Giving up on MCP server '{}' after {} reconnect attempt(s)",
+                        self.config.name, attempt
+                    )));
+                }
+                Err(e) => {
+                    let delay = reconnect_delay(self.config.reconnect_backoff_ms, attempt);
+                    warn!(
+                        "Reconnect to MCP server '{}' failed (attempt {}): {}. Retrying in {:?}",
+                        self.config.name, attempt, e, delay
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+            }
+        }
+    }
+
+    /// Restart a server that crashed or stopped responding.
+    ///
+    /// Restarts are capped at `max_restarts` per session so a server that
+    /// keeps crashing doesn't get respawned forever.
+    pub async fn restart(&mut self) -> Result<()> {
+        if self.restarts >= self.config.max_restarts {
+            let reason = format!(
+                "Stopped after {} restart(s); fix the server and start a new session",
+                self.restarts
+            );
+            *self.state.write().await = McpClientState::Failed(reason.clone());
+            anyhow::bail!("MCP server '{}': {}", self.config.name, reason);
+        }
+        self.restarts += 1;
+        self.reconnect().await
+    }
+
+    /// Send a ping and return the round-trip time.
+    pub async fn ping(&self) -> Result<Duration> {
+        let service = self.service.as_ref().context("Not connected")?;
+        let started = Instant::now();
+        tokio::time::timeout(
+            PING_TIMEOUT,
+            service.send_request(ClientRequest::PingRequest(Default::default())),
+        )
+        .await
+        .context("Ping timed out")?
+        .context("Ping failed")?;
+
+        Ok(started.elapsed())
+    }
+
+    /// Number of restarts after crashes this session.
+    pub fn restart_count(&self) -> u32 {
+        self.restarts
+    }
+
+    /// Restarts allowed per session.
+    pub fn max_restarts(&self) -> u32 {
+        self.config.max_restarts
+    }
+
+    /// Spawn the server process and talk to it over stdin/stdout.
+    async fn serve_stdio(&self, working_dir: &Path) -> Result<RmcpClientHandle> {
+        if self.config.command.is_empty() {
+            anyhow::bail!(
+                "MCP server '{}' uses the stdio transport but has no command",
+                self.config.name
+            );
+        }
 
         // Determine working directory
         let work_dir = self
@@ -91,25 +313,194 @@ impl McpClient {
                 anyhow::anyhow!("Failed to spawn MCP server '{}': {}", self.config.name, e)
             })?;
 
-        let service = ().serve(transport).await.map_err(|e| {
+        ().serve(transport).await.map_err(|e| {
             let err_msg = e.to_string();
             anyhow::anyhow!(
                 "Failed to initialize MCP server '{}': {}",
                 self.config.name,
                 err_msg
             )
+        })
+    }
+
+    /// Connect to a remote server, handling OAuth when it asks for authorization.
+    async fn serve_remote(&mut self) -> Result<RmcpClientHandle> {
+        self.load_credentials().await;
+
+        let error = match self.serve_remote_transport().await {
+            Ok(service) => return Ok(service),
+            Err(e) => e,
+        };
+
+        // rmcp doesn't surface the HTTP status, so probe for a 401 ourselves
+        let oauth = McpOAuth::new(self.url()?)?;
+        if !matches!(oauth.challenge().await, Ok(Some(_))) {
+            return Err(error);
+        }
+
+        // A rejected token may just be stale - refresh once before giving up
+        if let Some(credentials) = self.credentials.take() {
+            if credentials.refresh_token.is_some() {
+                if let Ok(refreshed) = oauth.refresh(&credentials).await {
+                    self.save_credentials(&refreshed);
+                    self.credentials = Some(refreshed);
+                    return self.serve_remote_transport().await;
+                }
+            }
+        }
+
+        anyhow::bail!(
+            "MCP server '{}' requires authorization. Run `safe-coder mcp login {}`",
+            self.config.name,
+            self.config.name
+        )
+    }
+
+    async fn serve_remote_transport(&self) -> Result<RmcpClientHandle> {
+        match self.config.effective_transport() {
+            McpTransport::Sse => self.serve_sse().await,
+            _ => self.serve_http().await,
+        }
+    }
+
+    /// Load stored OAuth credentials, refreshing them if they are about to expire.
+    async fn load_credentials(&mut self) {
+        let credentials = match McpCredentials::load(&self.config.name) {
+            Ok(Some(credentials)) => credentials,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Ignoring OAuth credentials for '{}': {}", self.config.name, e);
+                return;
+            }
+        };
+
+        if credentials.needs_refresh() && credentials.refresh_token.is_some() {
+            let refreshed = match McpOAuth::new(self.url().unwrap_or_default()) {
+                Ok(oauth) => oauth.refresh(&credentials).await,
+                Err(e) => Err(e),
+            };
+            match refreshed {
+                Ok(refreshed) => {
+                    self.save_credentials(&refreshed);
+                    self.credentials = Some(refreshed);
+                    return;
+                }
+                Err(e) => warn!(
+                    "Failed to refresh OAuth token for '{}': {}",
+                    self.config.name, e
+                ),
+            }
+        }
+        self.credentials = Some(credentials);
+    }
+
+    fn save_credentials(&self, credentials: &McpCredentials) {
+        if let Err(e) = credentials.save(&self.config.name) {
+            warn!(
+                "Failed to save OAuth token for '{}': {}",
+                self.config.name, e
+            );
+        }
+    }
+
+    /// Connect using the HTTP+SSE transport.
+    async fn serve_sse(&self) -> Result<RmcpClientHandle> {
+        let url = self.url()?;
+        let transport = SseClientTransport::start_with_client(
+            self.http_client()?,
+            SseClientConfig {
+                sse_endpoint: url.into(),
+                retry_policy: Arc::new(self.retry_policy()),
+                ..Default::default()
+            },
+        )
+        .await
+        .map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to open SSE stream for MCP server '{}': {}",
+                self.config.name,
+                e
+            )
         })?;
 
-        self.service = Some(service);
+        ().serve(transport).await.map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to initialize MCP server '{}': {}",
+                self.config.name,
+                e
+            )
+        })
+    }
 
-        // Discover tools
-        if let Err(e) = self.refresh_tools().await {
-            *self.state.write().await = McpClientState::Failed(e.to_string());
-            return Err(e);
+    /// Connect using the streamable HTTP transport.
+    async fn serve_http(&self) -> Result<RmcpClientHandle> {
+        let url = self.url()?;
+        let transport = StreamableHttpClientTransport::with_client(
+            self.http_client()?,
+            StreamableHttpClientTransportConfig {
+                uri: url.into(),
+                retry_config: Arc::new(self.retry_policy()),
+                ..Default::default()
+            },
+        );
+
+        ().serve(transport).await.map_err(|e| {
+            anyhow::anyhow!(
+                "Failed to initialize MCP server '{}': {}",
+                self.config.name,
+                e
+            )
+        })
+    }
+
+    /// The configured endpoint for remote transports.
+    fn url(&self) -> Result<&str> {
+        self.config.url.as_deref().with_context(|| {
+            format!(
+                "MCP server '{}' uses a remote transport but has no url",
+                self.config.name
+            )
+        })
+    }
+
+    /// HTTP client that sends the configured headers, plus any OAuth bearer
+    /// token, with every request.
+    fn http_client(&self) -> Result<reqwest::Client> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        for (name, value) in &self.config.headers {
+            let name = reqwest::header::HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("Invalid header name '{}'", name))?;
+            let mut value = reqwest::header::HeaderValue::from_str(value)
+                .with_context(|| format!("Invalid value for header '{}'", name))?;
+            value.set_sensitive(true);
+            headers.insert(name, value);
         }
 
-        *self.state.write().await = McpClientState::Connected;
-        Ok(())
+        // Explicitly configured headers win over stored OAuth credentials
+        if let Some(ref credentials) = self.credentials {
+            if !headers.contains_key(reqwest::header::AUTHORIZATION) {
+                let mut value = reqwest::header::HeaderValue::from_str(&format!(
+                    "Bearer {}",
+                    credentials.access_token
+                ))
+                .context("Invalid OAuth access token")?;
+                value.set_sensitive(true);
+                headers.insert(reqwest::header::AUTHORIZATION, value);
+            }
+        }
+
+        reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .context("Failed to build HTTP client")
+    }
+
+    /// Backoff used by the transports when an event stream drops.
+    fn retry_policy(&self) -> ExponentialBackoff {
+        ExponentialBackoff {
+            max_times: Some(self.config.max_reconnect_attempts as usize),
+            base_duration: Duration::from_millis(self.config.reconnect_backoff_ms),
+        }
     }
 
     /// Refresh the list of available tools from the server.
@@ -130,6 +521,126 @@ impl McpClient {
         Ok(())
     }
 
+    /// Refresh the list of resources, if the server offers any.
+    pub async fn refresh_resources(&self) -> Result<()> {
+        let service = self.service.as_ref().context("Not connected")?;
+        let supported = service
+            .peer_info()
+            .map(|info| info.capabilities.resources.is_some())
+            .unwrap_or(false);
+
+        let resources = if supported {
+            service
+                .list_all_resources()
+                .await
+                .context("Failed to list resources")?
+                .into_iter()
+                .map(McpResourceDefinition::from)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        *self.resources.write().await = resources;
+        Ok(())
+    }
+
+    /// Refresh the list of prompts, if the server offers any.
+    pub async fn refresh_prompts(&self) -> Result<()> {
+        let service = self.service.as_ref().context("Not connected")?;
+        let supported = service
+            .peer_info()
+            .map(|info| info.capabilities.prompts.is_some())
+            .unwrap_or(false);
+
+        let prompts = if supported {
+            service
+                .list_all_prompts()
+                .await
+                .context("Failed to list prompts")?
+                .into_iter()
+                .map(McpPromptDefinition::from)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        *self.prompts.write().await = prompts;
+        Ok(())
+    }
+
+    /// Read a resource and return its text contents.
+    ///
+    /// Binary contents are summarized rather than inlined.
+    pub async fn read_resource(&self, uri: &str) -> Result<String> {
+        let service = self.service.as_ref().context("Not connected")?;
+
+        let result = service
+            .read_resource(ReadResourceRequestParam {
+                uri: uri.to_string(),
+            })
+            .await
+            .with_context(|| format!("Failed to read resource '{}'", uri))?;
+
+        let mut output = String::new();
+        for contents in result.contents {
+            if !output.is_empty() {
+                output.push('\n');
+            }
+            match contents {
+                ResourceContents::TextResourceContents { text, .. } => output.push_str(&text),
+                ResourceContents::BlobResourceContents {
+                    uri,
+                    mime_type,
+                    blob,
+                    ..
+                } => output.push_str(&format!(
+                    "[binary resource {} ({}, {} bytes base64)]",
+                    uri,
+                    mime_type.as_deref().unwrap_or("unknown type"),
+                    blob.len()
+                )),
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Render a prompt with the given arguments into a single text message.
+    pub async fn get_prompt(
+        &self,
+        name: &str,
+        arguments: serde_json::Map<String, serde_json::Value>,
+    ) -> Result<String> {
+        let service = self.service.as_ref().context("Not connected")?;
+
+        let result = service
+            .get_prompt(GetPromptRequestParam {
+                name: name.to_string(),
+                arguments: (!arguments.is_empty()).then_some(arguments),
+            })
+            .await
+            .with_context(|| format!("Failed to get prompt '{}'", name))?;
+
+        let mut output = String::new();
+        for message in result.messages {
+            let text = match message.content {
+                PromptMessageContent::Text { text } => text,
+                PromptMessageContent::Resource { resource } => resource.get_text(),
+                PromptMessageContent::ResourceLink { link } => {
+                    format!("[resource {}]", link.uri)
+                }
+                PromptMessageContent::Image { .. } => continue,
+            };
+            if !output.is_empty() {
+                output.push_str("\n\n");
+            }
+            output.push_str(&text);
+        }
+
+        Ok(output)
+    }
+
     /// Call a tool on this server.
     pub async fn call_tool(&self, name: &str, arguments: serde_json::Value) -> Result<String> {
         let service = self.service.as_ref().context("Not connected")?;
@@ -173,6 +684,16 @@ impl McpClient {
         self.tools.read().await.clone()
     }
 
+    /// Get the list of discovered resources.
+    pub async fn get_resources(&self) -> Vec<McpResourceDefinition> {
+        self.resources.read().await.clone()
+    }
+
+    /// Get the list of discovered prompts.
+    pub async fn get_prompts(&self) -> Vec<McpPromptDefinition> {
+        self.prompts.read().await.clone()
+    }
+
     /// Get the server name from configuration.
     pub fn name(&self) -> &str {
         &self.config.name
@@ -193,6 +714,22 @@ impl McpClient {
         *self.state.read().await == McpClientState::Connected && self.service.is_some()
     }
 
+    /// Check whether an error means the connection itself is gone, so a reconnect may help.
+    pub fn is_connection_error(error: &anyhow::Error) -> bool {
+        matches!(
+            error.downcast_ref::<ServiceError>(),
+            Some(ServiceError::TransportClosed | ServiceError::TransportSend(_))
+        )
+    }
+
+    /// Whether the underlying transport has closed since connecting.
+    pub fn is_transport_closed(&self) -> bool {
+        self.service
+            .as_ref()
+            .map(|service| service.is_transport_closed())
+            .unwrap_or(true)
+    }
+
     /// Disconnect from the server.
     pub async fn disconnect(&mut self) -> Result<()> {
         if let Some(service) = self.service.take() {
@@ -201,10 +738,18 @@ impl McpClient {
         }
         *self.state.write().await = McpClientState::Disconnected;
         self.tools.write().await.clear();
+        self.resources.write().await.clear();
+        self.prompts.write().await.clear();
         Ok(())
     }
 }
 
+/// Delay after the given (1-based) failed reconnect attempt, doubling each time.
+fn reconnect_delay(base_ms: u64, attempt: u32) -> Duration {
+    let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+    Duration::from_millis(base_ms.saturating_mul(factor)).min(MAX_RECONNECT_DELAY)
+}
+
 /// A connected MCP client wrapped in Arc for sharing.
 pub type SharedMcpClient = Arc<RwLock<McpClient>>;
 
@@ -223,4 +768,39 @@ mod tests {
         assert_eq!(client.name(), "test");
         assert_eq!(client.mode(), "both");
     }
+
+    #[test]
+    fn test_reconnect_delay_backs_off() {
+        assert_eq!(reconnect_delay(500, 1), Duration::from_millis(500));
+        assert_eq!(reconnect_delay(500, 2), Duration::from_millis(1000));
+        assert_eq!(reconnect_delay(500, 4), Duration::from_millis(4000));
+        assert_eq!(reconnect_delay(500, 20), MAX_RECONNECT_DELAY);
+    }
+
+    #[tokio::test]
+    async fn test_restarts_are_capped() {
+        let mut client = McpClient::new(McpServerConfig {
+            name: "flaky".to_string(),
+            command: "flaky-server".to_string(),
+            max_restarts: 0,
+            ..Default::default()
+        });
+        let err = client.restart().await.unwrap_err();
+        assert!(err.to_string().contains("Stopped after 0 restart(s)"));
+        assert!(matches!(client.state().await, McpClientState::Failed(_)));
+        assert!(client.ping().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remote_transport_requires_url() {
+        let config = McpServerConfig {
+            name: "remote".to_string(),
+            transport: Some(McpTransport::Http),
+            ..Default::default()
+        };
+        let mut client = McpClient::new(config);
+        let err = client.connect(Path::new(".")).await.unwrap_err();
+        assert!(err.to_string().contains("has no url"));
+        assert!(matches!(client.state().await, McpClientState::Failed(_)));
+    }
 }
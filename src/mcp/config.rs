@@ -4,22 +4,66 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// Top-level MCP configuration.
-#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct McpConfig {
     /// Whether MCP is enabled globally.
     #[serde(default)]
     pub enabled: bool,
+    /// Seconds between liveness pings to connected servers (default: 30, 0 disables).
+    #[serde(default = "default_health_check_interval")]
+    pub health_check_interval_secs: u64,
     /// List of MCP server configurations.
     #[serde(default)]
     pub servers: Vec<McpServerConfig>,
 }
 
+impl Default for McpConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            health_check_interval_secs: default_health_check_interval(),
+            servers: Vec::new(),
+        }
+    }
+}
+
+/// Transport used to talk to an MCP server.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum McpTransport {
+    /// Spawn `command` and exchange JSON-RPC over stdin/stdout.
+    #[default]
+    Stdio,
+    /// HTTP with server-sent events (the 2024-11-05 protocol revision).
+    Sse,
+    /// Streamable HTTP (the 2025-03-26 protocol revision).
+    #[serde(alias = "streamable-http", alias = "streamable_http")]
+    Http,
+}
+
+/// How much to trust calls to an MCP server's tools.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpTrust {
+    /// Run without asking.
+    #[serde(alias = "allow")]
+    AutoAllow,
+    /// Always ask, even when the approval mode would auto-approve.
+    Ask,
+    /// Never run.
+    Deny,
+}
+
 /// Configuration for a single MCP server.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct McpServerConfig {
     /// Unique name for this server (used for tool namespacing).
     pub name: String,
-    /// Command to spawn the server process.
+    /// Transport to use. Defaults to "http" when `url` is set, "stdio" otherwise.
+    #[serde(default)]
+    pub transport: Option<McpTransport>,
+    /// Command to spawn the server process (stdio transport).
+    #[serde(default)]
     pub command: String,
     /// Arguments to pass to the command.
     #[serde(default)]
@@ -27,6 +71,16 @@ pub struct McpServerConfig {
     /// Environment variables for the server process.
     #[serde(default)]
     pub env: HashMap<String, String>,
+    /// Server endpoint (sse and http transports).
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Extra HTTP headers sent with every request, e.g. `Authorization`.
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+    /// OAuth client ID for servers that require authorization. When unset the
+    /// client is registered dynamically during `safe-coder mcp login`.
+    #[serde(default)]
+    pub oauth_client_id: Option<String>,
     /// Working directory (optional, defaults to project directory).
     #[serde(default)]
     pub working_dir: Option<String>,
@@ -39,9 +93,59 @@ pub struct McpServerConfig {
     /// Agent mode restriction: "plan", "build", or "both" (default: "both").
     #[serde(default = "default_mode")]
     pub mode: String,
+    /// Trust level for this server's tools. Unset follows the approval mode.
+    #[serde(default)]
+    pub trust: Option<McpTrust>,
+    /// Per-tool trust levels keyed by the server's tool name, overriding `trust`.
+    #[serde(default)]
+    pub tool_trust: HashMap<String, McpTrust>,
     /// Whether this server is disabled.
     #[serde(default)]
     pub disabled: bool,
+    /// How many times to try reconnecting after the connection drops (default: 5, 0 disables).
+    #[serde(default = "default_max_reconnect_attempts")]
+    pub max_reconnect_attempts: u32,
+    /// Delay before the first reconnect attempt in milliseconds, doubled on each retry (default: 500).
+    #[serde(default = "default_reconnect_backoff_ms")]
+    pub reconnect_backoff_ms: u64,
+    /// How many times per session to restart a server that crashed or stopped responding (default: 3).
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+}
+
+impl McpServerConfig {
+    /// The transport in effect, inferring it from `url` when not set explicitly.
+    pub fn effective_transport(&self) -> McpTransport {
+        match self.transport {
+            Some(transport) => transport,
+            None if self.url.is_some() => McpTransport::Http,
+            None => McpTransport::Stdio,
+        }
+    }
+
+    /// Trust level for one of this server's tools, if configured.
+    pub fn trust_for(&self, tool: &str) -> Option<McpTrust> {
+        self.tool_trust.get(tool).copied().or(self.trust)
+    }
+
+    /// Human-readable description of where the server lives, for logging.
+    pub fn endpoint(&self) -> String {
+        match self.effective_transport() {
+            McpTransport::Stdio => {
+                let mut endpoint = self.command.clone();
+                for arg in &self.args {
+                    endpoint.push(' ');
+                    endpoint.push_str(arg);
+                }
+                endpoint
+            }
+            McpTransport::Sse | McpTransport::Http => self.url.clone().unwrap_or_default(),
+        }
+    }
+}
+
+fn default_health_check_interval() -> u64 {
+    30
 }
 
 fn default_init_timeout() -> u64 {
@@ -56,18 +160,39 @@ fn default_mode() -> String {
     "both".to_string()
 }
 
+fn default_max_reconnect_attempts() -> u32 {
+    5
+}
+
+fn default_reconnect_backoff_ms() -> u64 {
+    500
+}
+
+fn default_max_restarts() -> u32 {
+    3
+}
+
 impl Default for McpServerConfig {
     fn default() -> Self {
         Self {
             name: String::new(),
+            transport: None,
             command: String::new(),
             args: Vec::new(),
             env: HashMap::new(),
+            url: None,
+            headers: HashMap::new(),
+            oauth_client_id: None,
             working_dir: None,
             init_timeout_secs: default_init_timeout(),
             call_timeout_secs: default_call_timeout(),
             mode: default_mode(),
+            trust: None,
+            tool_trust: HashMap::new(),
             disabled: false,
+            max_reconnect_attempts: default_max_reconnect_attempts(),
+            reconnect_backoff_ms: default_reconnect_backoff_ms(),
+            max_restarts: default_max_restarts(),
         }
     }
 }
@@ -80,6 +205,7 @@ mod tests {
     fn test_default_config() {
         let config = McpConfig::default();
         assert!(!config.enabled);
+        assert_eq!(config.health_check_interval_secs, 30);
         assert!(config.servers.is_empty());
     }
 
@@ -90,6 +216,10 @@ mod tests {
         assert_eq!(config.call_timeout_secs, 120);
         assert_eq!(config.mode, "both");
         assert!(!config.disabled);
+        assert_eq!(config.effective_transport(), McpTransport::Stdio);
+        assert_eq!(config.max_reconnect_attempts, 5);
+        assert_eq!(config.reconnect_backoff_ms, 500);
+        assert_eq!(config.max_restarts, 3);
     }
 
     #[test]
@@ -115,5 +245,54 @@ args = ["mcp-server-weather"]
         assert_eq!(config.servers[0].mode, "build");
         assert_eq!(config.servers[1].name, "weather");
         assert_eq!(config.servers[1].mode, "both"); // default
+        assert_eq!(config.health_check_interval_secs, 30); // default
+    }
+
+    #[test]
+    fn test_remote_server_deserialization() {
+        let toml = r#"
+enabled = true
+
+[[servers]]
+name = "docs"
+url = "https://mcp.example.com/mcp"
+headers = { Authorization = "Bearer secret" }
+
+[[servers]]
+name = "legacy"
+transport = "sse"
+url = "http://localhost:8000/sse"
+max_reconnect_attempts = 0
+oauth_client_id = "safe-coder"
+"#;
+        let config: McpConfig = toml::from_str(toml).unwrap();
+        let docs = &config.servers[0];
+        assert_eq!(docs.effective_transport(), McpTransport::Http);
+        assert_eq!(docs.endpoint(), "https://mcp.example.com/mcp");
+        assert_eq!(docs.headers["Authorization"], "Bearer secret");
+        assert!(docs.command.is_empty());
+
+        let legacy = &config.servers[1];
+        assert_eq!(legacy.effective_transport(), McpTransport::Sse);
+        assert_eq!(legacy.max_reconnect_attempts, 0);
+        assert_eq!(legacy.oauth_client_id.as_deref(), Some("safe-coder"));
+        assert!(docs.oauth_client_id.is_none());
+    }
+
+    #[test]
+    fn test_trust_deserialization() {
+        let toml = r#"
+[[servers]]
+name = "github"
+command = "github-mcp"
+trust = "ask"
+tool_trust = { get_issue = "auto-allow", delete_repo = "deny" }
+"#;
+        let config: McpConfig = toml::from_str(toml).unwrap();
+        let github = &config.servers[0];
+        assert_eq!(github.trust_for("get_issue"), Some(McpTrust::AutoAllow));
+        assert_eq!(github.trust_for("delete_repo"), Some(McpTrust::Deny));
+        assert_eq!(github.trust_for("create_issue"), Some(McpTrust::Ask));
+        assert_eq!(McpServerConfig::default().trust_for("anything"), None);
     }
 }
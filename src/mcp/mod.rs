@@ -2,39 +2,159 @@
 //!
 //! This module enables safe-coder to act as an MCP client, connecting to
 //! user-specified MCP servers and exposing their tools alongside built-in tools.
+//! Server resources can be attached to a message with `@mcp:server/resource`
+//! mentions, and server prompts run as `/mcp:server:prompt` slash commands.
 
 pub mod client;
 pub mod config;
 pub mod tool;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tokio::task::AbortHandle;
 use tracing::{error, info, warn};
 
-pub use client::{McpClient, McpClientState, SharedMcpClient};
-pub use config::{McpConfig, McpServerConfig};
+pub use client::{
+    McpClient, McpClientState, McpPromptArgument, McpPromptDefinition, McpResourceDefinition,
+    SharedMcpClient,
+};
+pub use config::{McpConfig, McpServerConfig, McpTransport, McpTrust};
 pub use tool::McpTool;
 
+use crate::permissions::{ApprovedPattern, PermissionManager};
 use crate::tools::{AgentMode, Tool};
 
+/// What happened to a server during a health check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum McpHealthStatus {
+    /// The server crashed or stopped answering pings.
+    Dropped,
+    /// The server was restarted and is answering again.
+    Restarted,
+    /// The server could not be restarted; its tools are unavailable.
+    Failed,
+}
+
+/// A change in a server's health, reported by the background health checks.
+#[derive(Debug, Clone)]
+pub struct McpHealthEvent {
+    pub server: String,
+    pub status: McpHealthStatus,
+    pub message: String,
+}
+
+/// Point-in-time status of one configured server, for `mcp status`.
+#[derive(Debug, Clone)]
+pub struct McpServerStatus {
+    pub name: String,
+    pub transport: McpTransport,
+    pub endpoint: String,
+    pub state: McpClientState,
+    pub latency: Option<Duration>,
+    pub tool_count: usize,
+    pub restarts: u32,
+}
+
 /// Manages all MCP server connections.
 pub struct McpManager {
     config: McpConfig,
     clients: HashMap<String, SharedMcpClient>,
     tools: Vec<Arc<McpTool>>,
+    health: broadcast::Sender<McpHealthEvent>,
+    health_task: Option<AbortHandle>,
+    /// Disconnects the servers if the process is stopped by a signal
+    on_shutdown: Option<crate::shutdown::Registration>,
 }
 
 impl McpManager {
     /// Create a new MCP manager with the given configuration.
     pub fn new(config: McpConfig) -> Self {
+        let (health, _) = broadcast::channel(64);
         Self {
             config,
             clients: HashMap::new(),
             tools: Vec::new(),
+            health,
+            health_task: None,
+            on_shutdown: None,
+        }
+    }
+
+    /// Ping connected servers in the background, restarting any that crashed.
+    ///
+    /// Changes are broadcast to `subscribe_health` receivers.
+    pub fn start_health_checks(&mut self) {
+        let interval = self.config.health_check_interval_secs;
+        if interval == 0 || self.clients.is_empty() || self.health_task.is_some() {
+            return;
+        }
+
+        let clients: Vec<_> = self
+            .clients
+            .iter()
+            .map(|(name, client)| (name.clone(), client.clone()))
+            .collect();
+        let health = self.health.clone();
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(interval));
+            ticker.tick().await; // The first tick fires immediately
+            loop {
+                ticker.tick().await;
+                for (name, client) in &clients {
+                    for event in check_server(name, client).await {
+                        let _ = health.send(event);
+                    }
+                }
+            }
+        });
+        self.health_task = Some(task.abort_handle());
+    }
+
+    /// Receive server health changes as they happen.
+    pub fn subscribe_health(&self) -> broadcast::Receiver<McpHealthEvent> {
+        self.health.subscribe()
+    }
+
+    /// Status of every configured server, pinging connected ones for latency.
+    pub async fn server_status(&self) -> Vec<McpServerStatus> {
+        let mut statuses = Vec::new();
+        for server in &self.config.servers {
+            let mut status = McpServerStatus {
+                name: server.name.clone(),
+                transport: server.effective_transport(),
+                endpoint: server.endpoint(),
+                state: if server.disabled {
+                    McpClientState::Failed("Disabled".to_string())
+                } else {
+                    McpClientState::Disconnected
+                },
+                latency: None,
+                tool_count: 0,
+                restarts: 0,
+            };
+
+            if let Some(client) = self.clients.get(&server.name) {
+                let client = client.read().await;
+                if client.is_connected().await {
+                    status.latency = match client.ping().await {
+                        Ok(latency) => Some(latency),
+                        Err(e) => {
+                            warn!("Ping to MCP server '{}' failed: {}", server.name, e);
+                            None
+                        }
+                    };
+                }
+                status.state = client.state().await;
+                status.tool_count = client.get_tools().await.len();
+                status.restarts = client.restart_count();
+            }
+            statuses.push(status);
         }
+        statuses
     }
 
     /// Initialize all configured MCP servers.
@@ -60,8 +180,10 @@ impl McpManager {
             }
 
             info!(
-                "Connecting to MCP server '{}': {} {:?}",
-                server_config.name, server_config.command, server_config.args
+                "Connecting to MCP server '{}' over {:?}: {}",
+                server_config.name,
+                server_config.effective_transport(),
+                server_config.endpoint()
             );
 
             let mut client = McpClient::new(server_config.clone());
@@ -116,6 +238,24 @@ impl McpManager {
             self.tools.len()
         );
 
+        let clients: Vec<_> = self
+            .clients
+            .iter()
+            .map(|(name, client)| (name.clone(), client.clone()))
+            .collect();
+        if !clients.is_empty() {
+            self.on_shutdown = Some(crate::shutdown::register(
+                "disconnect MCP servers",
+                move || async move {
+                    for (name, client) in clients {
+                        if let Err(e) = client.write().await.disconnect().await {
+                            warn!("Error disconnecting from '{}': {}", name, e);
+                        }
+                    }
+                },
+            ));
+        }
+
         Ok(())
     }
 
@@ -136,6 +276,47 @@ impl McpManager {
             .collect()
     }
 
+    /// Register each tool's configured trust level as a permission rule.
+    pub fn apply_trust(&self, permissions: &mut PermissionManager) {
+        for tool in &self.tools {
+            let Some(server) = self.server_config(tool.server_name()) else {
+                continue;
+            };
+            let Some(trust) = server.trust_for(tool.original_name()) else {
+                continue;
+            };
+
+            let origin = format!("MCP server '{}'", server.name);
+            let pattern = ApprovedPattern::new(tool.name()).permanent();
+            match trust {
+                McpTrust::AutoAllow => permissions.approve_pattern(
+                    pattern.with_description(format!("Auto-approve {} ({})", tool.name(), origin)),
+                ),
+                McpTrust::Ask => permissions.require_approval(
+                    pattern.with_description(format!("Always ask for {} ({})", tool.name(), origin)),
+                ),
+                McpTrust::Deny => permissions.deny_pattern(
+                    pattern.with_description(format!("{} ({})", tool.name(), origin)),
+                ),
+            }
+        }
+    }
+
+    /// Describe which server a namespaced MCP tool comes from, for approval prompts.
+    pub fn tool_origin(&self, tool_name: &str) -> Option<String> {
+        let tool = self.tools.iter().find(|t| t.name() == tool_name)?;
+        let server = self.server_config(tool.server_name())?;
+        Some(format!(
+            "MCP server '{}' ({})",
+            server.name,
+            server.endpoint()
+        ))
+    }
+
+    fn server_config(&self, name: &str) -> Option<&McpServerConfig> {
+        self.config.servers.iter().find(|s| s.name == name)
+    }
+
     /// Get the number of connected servers.
     pub fn connected_count(&self) -> usize {
         self.clients.len()
@@ -164,6 +345,11 @@ impl McpManager {
 
     /// Shutdown all MCP servers.
     pub async fn shutdown(&mut self) -> Result<()> {
+        if let Some(task) = self.health_task.take() {
+            task.abort();
+        }
+
+        self.on_shutdown = None;
         info!("Shutting down {} MCP server(s)", self.clients.len());
 
         for (name, client) in self.clients.drain() {
@@ -208,6 +394,288 @@ impl McpManager {
     pub fn list_tool_names(&self) -> Vec<String> {
         self.tools.iter().map(|t| t.name().to_string()).collect()
     }
+
+    /// List resources from all connected servers as (server, resource) pairs.
+    pub async fn list_resources(&self) -> Vec<(String, McpResourceDefinition)> {
+        let mut resources = Vec::new();
+        for (name, client) in &self.clients {
+            let client = client.read().await;
+            for resource in client.get_resources().await {
+                resources.push((name.clone(), resource));
+            }
+        }
+        resources.sort_by(|a, b| (&a.0, &a.1.uri).cmp(&(&b.0, &b.1.uri)));
+        resources
+    }
+
+    /// List prompts from all connected servers as (server, prompt) pairs.
+    pub async fn list_prompts(&self) -> Vec<(String, McpPromptDefinition)> {
+        let mut prompts = Vec::new();
+        for (name, client) in &self.clients {
+            let client = client.read().await;
+            for prompt in client.get_prompts().await {
+                prompts.push((name.clone(), prompt));
+            }
+        }
+        prompts.sort_by(|a, b| (&a.0, &a.1.name).cmp(&(&b.0, &b.1.name)));
+        prompts
+    }
+
+    /// Read a resource from a server, by URI or by name.
+    ///
+    /// Returns the resolved URI along with the contents.
+    pub async fn read_resource(&self, server: &str, resource: &str) -> Result<(String, String)> {
+        let client = self
+            .clients
+            .get(server)
+            .with_context(|| format!("MCP server '{}' is not connected", server))?;
+        let client = client.read().await;
+
+        // Accept a listed resource's name as shorthand for its URI; anything
+        // else is passed through so templated URIs still work
+        let uri = client
+            .get_resources()
+            .await
+            .into_iter()
+            .find(|r| r.uri == resource || r.name == resource)
+            .map(|r| r.uri)
+            .unwrap_or_else(|| resource.to_string());
+
+        let contents = client.read_resource(&uri).await?;
+        Ok((uri, contents))
+    }
+
+    /// Render a server prompt, mapping the raw slash-command arguments onto
+    /// the prompt's declared arguments.
+    pub async fn get_prompt(&self, server: &str, name: &str, raw_args: &str) -> Result<String> {
+        let client = self
+            .clients
+            .get(server)
+            .with_context(|| format!("MCP server '{}' is not connected", server))?;
+        let client = client.read().await;
+
+        let prompt = client
+            .get_prompts()
+            .await
+            .into_iter()
+            .find(|p| p.name == name)
+            .with_context(|| format!("MCP server '{}' has no prompt '{}'", server, name))?;
+
+        let arguments = parse_prompt_arguments(&prompt, raw_args).map_err(|e| {
+            anyhow::anyhow!("{}. Usage: {}", e, prompt_usage(server, &prompt))
+        })?;
+        client.get_prompt(name, arguments).await
+    }
+
+    /// Read the resources mentioned as `@mcp:server/resource` in a message and
+    /// format them as attachments. Returns None if the message has no mentions.
+    pub async fn attach_resource_mentions(&self, message: &str) -> Option<String> {
+        let mentions = parse_resource_mentions(message);
+        if mentions.is_empty() {
+            return None;
+        }
+
+        let mut attachments = String::from("[MCP RESOURCES]");
+        for (server, resource) in mentions {
+            match self.read_resource(&server, &resource).await {
+                Ok((uri, contents)) => attachments.push_str(&format!(
+                    "\n--- Resource: {}/{} ---\n{}\n",
+                    server, uri, contents
+                )),
+                Err(e) => attachments.push_str(&format!(
+                    "\n--- Resource: {}/{} (error: {}) ---\n",
+                    server, resource, e
+                )),
+            }
+        }
+        Some(attachments)
+    }
+}
+
+impl Drop for McpManager {
+    fn drop(&mut self) {
+        if let Some(task) = self.health_task.take() {
+            task.abort();
+        }
+    }
+}
+
+/// Ping one server and restart it if it crashed or stopped responding.
+async fn check_server(name: &str, client: &SharedMcpClient) -> Vec<McpHealthEvent> {
+    let error = {
+        let client = client.read().await;
+        // Servers that failed for good (or are mid-reconnect) are left alone
+        if client.state().await != McpClientState::Connected {
+            return Vec::new();
+        }
+        if client.is_transport_closed() {
+            "connection closed".to_string()
+        } else {
+            match client.ping().await {
+                Ok(_) => return Vec::new(),
+                Err(e) => e.to_string(),
+            }
+        }
+    };
+
+    warn!("MCP server '{}' dropped out: {}", name, error);
+    let mut events = vec![McpHealthEvent {
+        server: name.to_string(),
+        status: McpHealthStatus::Dropped,
+        message: error,
+    }];
+
+    let mut client = client.write().await;
+    events.push(match client.restart().await {
+        Ok(()) => {
+            info!("Restarted MCP server '{}'", name);
+            McpHealthEvent {
+                server: name.to_string(),
+                status: McpHealthStatus::Restarted,
+                message: format!(
+                    "Restarted ({} of {} allowed restarts)",
+                    client.restart_count(),
+                    client.max_restarts()
+                ),
+            }
+        }
+        Err(e) => {
+            error!("Could not restart MCP server '{}': {}", name, e);
+            McpHealthEvent {
+                server: name.to_string(),
+                status: McpHealthStatus::Failed,
+                message: e.to_string(),
+            }
+        }
+    });
+    events
+}
+
+/// Format server statuses for `mcp status`.
+pub fn format_server_status(statuses: &[McpServerStatus]) -> String {
+    if statuses.is_empty() {
+        return "No MCP servers configured.".to_string();
+    }
+
+    let mut output = String::from("🔌 MCP servers:\n\n");
+    for status in statuses {
+        let icon = match status.state {
+            McpClientState::Connected => "●",
+            McpClientState::Connecting => "◐",
+            McpClientState::Disconnected | McpClientState::Failed(_) => "○",
+        };
+        let mut details = vec![status.state.to_string()];
+        if let Some(latency) = status.latency {
+            details.push(format!("{}ms", latency.as_millis()));
+        }
+        if status.tool_count > 0 {
+            details.push(format!("{} tool(s)", status.tool_count));
+        }
+        if status.restarts > 0 {
+            details.push(format!("{} restart(s)", status.restarts));
+        }
+        output.push_str(&format!(
+            "  {} {}  {}\n      {:?} {}\n",
+            icon,
+            status.name,
+            details.join(", "),
+            status.transport,
+            status.endpoint
+        ));
+    }
+    output
+}
+
+/// Find `@mcp:server/resource` mentions in a message, in order and without duplicates.
+pub fn parse_resource_mentions(message: &str) -> Vec<(String, String)> {
+    let mut mentions = Vec::new();
+    for word in message.split_whitespace() {
+        let Some(reference) = word.strip_prefix("@mcp:") else {
+            continue;
+        };
+        let reference = reference.trim_end_matches(['.', ',', ';', '!', '?', ')']);
+        let Some((server, resource)) = reference.split_once('/') else {
+            continue;
+        };
+        if server.is_empty() || resource.is_empty() {
+            continue;
+        }
+
+        let mention = (server.to_string(), resource.to_string());
+        if !mentions.contains(&mention) {
+            mentions.push(mention);
+        }
+    }
+    mentions
+}
+
+/// Map slash-command arguments onto a prompt's declared arguments.
+///
+/// `key=value` words set arguments by name; remaining words fill the other
+/// arguments in order, with the last one taking whatever text is left over.
+pub fn parse_prompt_arguments(
+    prompt: &McpPromptDefinition,
+    raw_args: &str,
+) -> Result<serde_json::Map<String, serde_json::Value>> {
+    let mut arguments = serde_json::Map::new();
+    let mut positional = Vec::new();
+
+    for word in raw_args.split_whitespace() {
+        match word.split_once('=') {
+            Some((key, value)) if prompt.arguments.iter().any(|a| a.name == key) => {
+                arguments.insert(key.to_string(), serde_json::Value::String(value.to_string()));
+            }
+            _ => positional.push(word),
+        }
+    }
+
+    let unfilled: Vec<&McpPromptArgument> = prompt
+        .arguments
+        .iter()
+        .filter(|a| !arguments.contains_key(&a.name))
+        .collect();
+    let mut words = positional.into_iter();
+    for (i, arg) in unfilled.iter().enumerate() {
+        let value = if i + 1 == unfilled.len() {
+            words.by_ref().collect::<Vec<_>>().join(" ")
+        } else {
+            words.next().unwrap_or_default().to_string()
+        };
+        if !value.is_empty() {
+            arguments.insert(arg.name.clone(), serde_json::Value::String(value));
+        }
+    }
+    if words.next().is_some() {
+        anyhow::bail!("Too many arguments for prompt '{}'", prompt.name);
+    }
+
+    let missing: Vec<String> = prompt
+        .arguments
+        .iter()
+        .filter(|a| a.required && !arguments.contains_key(&a.name))
+        .map(|a| match &a.description {
+            Some(description) => format!("{} ({})", a.name, description),
+            None => a.name.clone(),
+        })
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!("Missing required argument(s): {}", missing.join(", "));
+    }
+
+    Ok(arguments)
+}
+
+/// Usage line for a prompt's slash command, e.g. `/mcp:github:review <pr> [focus]`.
+pub fn prompt_usage(server: &str, prompt: &McpPromptDefinition) -> String {
+    let mut usage = format!("/mcp:{}:{}", server, prompt.name);
+    for arg in &prompt.arguments {
+        if arg.required {
+            usage.push_str(&format!(" <{}>", arg.name));
+        } else {
+            usage.push_str(&format!(" [{}]", arg.name));
+        }
+    }
+    usage
 }
 
 /// A reference wrapper for McpTool that implements Tool.
@@ -237,6 +705,10 @@ impl Tool for McpToolRef {
     ) -> Result<String> {
         self.0.execute(params, ctx).await
     }
+
+    fn available_in(&self, mode: AgentMode) -> bool {
+        self.0.is_available_in_mode(&mode)
+    }
 }
 
 #[cfg(test)]
@@ -251,11 +723,156 @@ mod tests {
         assert_eq!(manager.tool_count(), 0);
     }
 
+    #[test]
+    fn test_apply_trust() {
+        use crate::permissions::Permission;
+        use serde_json::json;
+
+        let server = McpServerConfig {
+            name: "github".to_string(),
+            url: Some("https://api.example.com/mcp".to_string()),
+            trust: Some(McpTrust::Ask),
+            tool_trust: HashMap::from([
+                ("get_issue".to_string(), McpTrust::AutoAllow),
+                ("delete_repo".to_string(), McpTrust::Deny),
+            ]),
+            ..Default::default()
+        };
+        let mut manager = McpManager::new(McpConfig {
+            enabled: true,
+            servers: vec![server.clone()],
+            ..Default::default()
+        });
+        let client = Arc::new(RwLock::new(McpClient::new(server)));
+        for name in ["get_issue", "delete_repo", "create_issue"] {
+            manager.tools.push(Arc::new(McpTool::new(
+                "github".to_string(),
+                client::McpToolDefinition {
+                    name: name.to_string(),
+                    description: None,
+                    input_schema: json!({}),
+                },
+                client.clone(),
+                "both".to_string(),
+            )));
+        }
+
+        let mut permissions = PermissionManager::new();
+        manager.apply_trust(&mut permissions);
+        assert_eq!(
+            permissions.check("mcp_github_get_issue", &json!({})),
+            Permission::Allowed
+        );
+        assert_eq!(
+            permissions.check("mcp_github_delete_repo", &json!({})),
+            Permission::Denied
+        );
+        assert_eq!(
+            permissions.check("mcp_github_create_issue", &json!({})),
+            Permission::AlwaysAsk
+        );
+
+        assert_eq!(
+            manager.tool_origin("mcp_github_get_issue").as_deref(),
+            Some("MCP server 'github' (https://api.example.com/mcp)")
+        );
+        assert_eq!(manager.tool_origin("read_file"), None);
+    }
+
+    #[test]
+    fn test_format_server_status() {
+        let output = format_server_status(&[
+            McpServerStatus {
+                name: "github".to_string(),
+                transport: McpTransport::Http,
+                endpoint: "https://api.example.com/mcp".to_string(),
+                state: McpClientState::Connected,
+                latency: Some(Duration::from_millis(42)),
+                tool_count: 8,
+                restarts: 1,
+            },
+            McpServerStatus {
+                name: "fs".to_string(),
+                transport: McpTransport::Stdio,
+                endpoint: "mcp-fs .".to_string(),
+                state: McpClientState::Failed("spawn failed".to_string()),
+                latency: None,
+                tool_count: 0,
+                restarts: 0,
+            },
+        ]);
+        assert!(output.contains("● github  connected, 42ms, 8 tool(s), 1 restart(s)"));
+        assert!(output.contains("○ fs  failed: spawn failed\n"));
+        assert_eq!(format_server_status(&[]), "No MCP servers configured.");
+    }
+
+    #[tokio::test]
+    async fn test_health_check_skips_disconnected_servers() {
+        let client: SharedMcpClient = Arc::new(RwLock::new(McpClient::new(McpServerConfig {
+            name: "fs".to_string(),
+            command: "echo".to_string(),
+            ..Default::default()
+        })));
+        assert!(check_server("fs", &client).await.is_empty());
+    }
+
+    fn review_prompt() -> McpPromptDefinition {
+        McpPromptDefinition {
+            name: "review".to_string(),
+            description: None,
+            arguments: vec![
+                McpPromptArgument {
+                    name: "pr".to_string(),
+                    description: Some("Pull request number".to_string()),
+                    required: true,
+                },
+                McpPromptArgument {
+                    name: "focus".to_string(),
+                    description: None,
+                    required: false,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_parse_resource_mentions() {
+        let mentions = parse_resource_mentions(
+            "Compare @mcp:docs/readme with @mcp:github/repo://org/app/README.md, and @mcp:docs/readme. Also @file.rs",
+        );
+        assert_eq!(
+            mentions,
+            vec![
+                ("docs".to_string(), "readme".to_string()),
+                ("github".to_string(), "repo://org/app/README.md".to_string()),
+            ]
+        );
+        assert!(parse_resource_mentions("@mcp:noslash @mcp:/x").is_empty());
+    }
+
+    #[test]
+    fn test_parse_prompt_arguments() {
+        let prompt = review_prompt();
+
+        let args = parse_prompt_arguments(&prompt, "42 error handling").unwrap();
+        assert_eq!(args["pr"], "42");
+        assert_eq!(args["focus"], "error handling");
+
+        let args = parse_prompt_arguments(&prompt, "focus=tests 7").unwrap();
+        assert_eq!(args["pr"], "7");
+        assert_eq!(args["focus"], "tests");
+
+        let err = parse_prompt_arguments(&prompt, "").unwrap_err();
+        assert!(err.to_string().contains("pr (Pull request number)"));
+        assert_eq!(prompt_usage("github", &prompt), "/mcp:github:review <pr> [focus]");
+    }
+
     #[tokio::test]
     async fn test_manager_disabled() {
         let config = McpConfig {
             enabled: false,
             servers: vec![],
+            ..Default::default()
         };
         let mut manager = McpManager::new(config);
         manager.initialize(Path::new(".")).await.unwrap();
@@ -4,6 +4,7 @@ use anyhow::Result;
 use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::RwLock;
+use tracing::warn;
 
 use super::client::{McpClient, McpToolDefinition};
 use crate::tools::{AgentMode, Tool, ToolContext};
@@ -79,7 +80,23 @@ impl Tool for McpTool {
     }
 
     async fn execute(&self, params: serde_json::Value, _ctx: &ToolContext<'_>) -> Result<String> {
-        let client = self.client.read().await;
+        {
+            let client = self.client.read().await;
+            if !client.is_transport_closed() {
+                match client.call_tool(&self.definition.name, params.clone()).await {
+                    Err(e) if McpClient::is_connection_error(&e) => {
+                        warn!("Lost connection to MCP server '{}': {}", self.server_name, e);
+                    }
+                    result => return result,
+                }
+            }
+        }
+
+        // The connection dropped - reconnect and retry once
+        let mut client = self.client.write().await;
+        if client.is_transport_closed() {
+            client.reconnect().await?;
+        }
         client.call_tool(&self.definition.name, params).await
     }
 }
@@ -0,0 +1,666 @@
+//! Project analysis for `/init` and `/summary`
+//!
+//! Walks the repository (respecting .gitignore) to find its languages,
+//! build systems, build/test/lint commands, layout and tooling conventions.
+//! `/init` turns that into `.safe-coder/SAFE_CODER.md`, asking the model
+//! once to describe the project's purpose, architecture and coding
+//! conventions from the facts, the README and the entry points.
+
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::llm::{ContentBlock, LlmClient, Message};
+use crate::utils::truncate_str;
+
+/// Files looked at when walking the project
+const MAX_FILES: usize = 20_000;
+
+/// Rust files checked for inline `#[cfg(test)]` modules
+const MAX_SCANNED_SOURCES: usize = 300;
+
+/// File paths listed for the model
+const MAX_LISTED_PATHS: usize = 300;
+
+/// Lines of each entry point shown to the model
+const ENTRY_POINT_LINES: usize = 80;
+
+const GENERATE_PROMPT: &str = "You write the project memory file an AI coding assistant reads \
+at the start of every session. From the facts, README and source excerpts you are given, write \
+Markdown with exactly these three sections and nothing else:\n\
+## Overview - two to four sentences on what the project is and who uses it.\n\
+## Architecture - bullets naming the key directories and modules and how they fit together.\n\
+## Conventions - bullets on code style, error handling, testing and naming that a new \
+contributor must follow.\n\
+Only state what the evidence supports; do not invent commands, files or rules. Be concise.";
+
+/// What analysis found out about a project
+#[derive(Debug, Default)]
+pub struct ProjectAnalysis {
+    pub name: String,
+    /// Languages with their file counts, most used first
+    pub languages: Vec<(String, usize)>,
+    pub build_systems: Vec<String>,
+    /// Labelled commands, e.g. ("Test", "cargo test")
+    pub commands: Vec<(String, String)>,
+    /// Top-level directories with their file counts, largest first
+    pub layout: Vec<(String, usize)>,
+    /// Tooling and conventions found in config files and the tree
+    pub conventions: Vec<String>,
+    pub file_count: usize,
+    files: Vec<String>,
+}
+
+/// Analyze the project at `root`
+pub fn analyze(root: &Path) -> ProjectAnalysis {
+    let files: Vec<String> = ignore::WalkBuilder::new(root)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            entry
+                .path()
+                .strip_prefix(root)
+                .ok()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+        })
+        .filter(|path| !path.starts_with(".safe-coder"))
+        .take(MAX_FILES)
+        .collect();
+
+    let mut languages: HashMap<&str, usize> = HashMap::new();
+    let mut layout: BTreeMap<String, usize> = BTreeMap::new();
+    for file in &files {
+        if let Some(language) = language_of(file) {
+            *languages.entry(language).or_default() += 1;
+        }
+        if let Some((dir, _)) = file.split_once('/') {
+            *layout.entry(format!("{}/", dir)).or_default() += 1;
+        }
+    }
+    let mut languages: Vec<(String, usize)> = languages
+        .into_iter()
+        .map(|(l, n)| (l.to_string(), n))
+        .collect();
+    languages.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut layout: Vec<(String, usize)> = layout.into_iter().collect();
+    layout.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut analysis = ProjectAnalysis {
+        name: root
+            .canonicalize()
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .unwrap_or_else(|| "Project".to_string()),
+        languages,
+        layout,
+        file_count: files.len(),
+        ..Default::default()
+    };
+    detect_build_systems(root, &mut analysis);
+    detect_conventions(root, &files, &mut analysis);
+    analysis.files = files;
+    analysis
+}
+
+fn language_of(path: &str) -> Option<&'static str> {
+    let ext = Path::new(path).extension()?.to_str()?;
+    Some(match ext {
+        "rs" => "Rust",
+        "ts" | "tsx" | "mts" | "cts" => "TypeScript",
+        "js" | "jsx" | "mjs" | "cjs" => "JavaScript",
+        "py" => "Python",
+        "go" => "Go",
+        "java" => "Java",
+        "kt" | "kts" => "Kotlin",
+        "rb" => "Ruby",
+        "php" => "PHP",
+        "cs" => "C#",
+        "c" | "h" => "C",
+        "cc" | "cpp" | "cxx" | "hpp" => "C++",
+        "swift" => "Swift",
+        "scala" => "Scala",
+        "ex" | "exs" => "Elixir",
+        "dart" => "Dart",
+        "vue" => "Vue",
+        "svelte" => "Svelte",
+        "sh" | "bash" => "Shell",
+        "sql" => "SQL",
+        _ => return None,
+    })
+}
+
+fn read(root: &Path, file: &str) -> Option<String> {
+    std::fs::read_to_string(root.join(file)).ok()
+}
+
+fn detect_build_systems(root: &Path, analysis: &mut ProjectAnalysis) {
+    let mut add = |label: &str, command: String| {
+        analysis.commands.push((label.to_string(), command));
+    };
+
+    if let Some(manifest) = read(root, "Cargo.toml") {
+        let workspace = if manifest.contains("[workspace]") {
+            " --workspace"
+        } else {
+            ""
+        };
+        add("Build", format!("cargo build{}", workspace));
+        add("Test", format!("cargo test{}", workspace));
+        add(
+            "Lint",
+            format!("cargo clippy{} --all-targets -- -D warnings", workspace),
+        );
+        add("Format", "cargo fmt".to_string());
+        analysis.build_systems.push(if workspace.is_empty() {
+            "Cargo".to_string()
+        } else {
+            "Cargo workspace".to_string()
+        });
+    }
+
+    if let Some(package) = read(root, "package.json") {
+        let manager = if root.join("pnpm-lock.yaml").exists() {
+            "pnpm"
+        } else if root.join("yarn.lock").exists() {
+            "yarn"
+        } else if root.join("bun.lockb").exists() || root.join("bun.lock").exists() {
+            "bun"
+        } else {
+            "npm"
+        };
+        analysis
+            .build_systems
+            .push(format!("{} (package.json)", manager));
+        let json: serde_json::Value = serde_json::from_str(&package).unwrap_or_default();
+        let scripts = json["scripts"].as_object();
+        for (script, label) in [
+            ("dev", "Dev server"),
+            ("start", "Start"),
+            ("build", "Build"),
+            ("test", "Test"),
+            ("lint", "Lint"),
+            ("typecheck", "Type check"),
+            ("format", "Format"),
+        ] {
+            if scripts.is_some_and(|s| s.contains_key(script)) {
+                let command = match (manager, script) {
+                    ("npm", "test" | "start") => format!("npm {}", script),
+                    ("npm", _) => format!("npm run {}", script),
+                    _ => format!("{} {}", manager, script),
+                };
+                add(label, command);
+            }
+        }
+    }
+
+    let pyproject = read(root, "pyproject.toml").unwrap_or_default();
+    let requirements = read(root, "requirements.txt").unwrap_or_default();
+    if !pyproject.is_empty() || !requirements.is_empty() || root.join("setup.py").exists() {
+        let runner = if pyproject.contains("[tool.poetry") {
+            analysis.build_systems.push("Poetry".to_string());
+            "poetry run "
+        } else if root.join("uv.lock").exists() {
+            analysis.build_systems.push("uv".to_string());
+            "uv run "
+        } else {
+            analysis.build_systems.push("pip".to_string());
+            ""
+        };
+        let uses_pytest = pyproject.contains("pytest")
+            || requirements.contains("pytest")
+            || root.join("pytest.ini").exists()
+            || root.join("conftest.py").exists();
+        if uses_pytest {
+            add("Test", format!("{}pytest", runner));
+        }
+        if pyproject.contains("[tool.ruff") || root.join("ruff.toml").exists() {
+            add("Lint", format!("{}ruff check .", runner));
+        }
+        if pyproject.contains("[tool.mypy") || root.join("mypy.ini").exists() {
+            add("Type check", format!("{}mypy .", runner));
+        }
+        if pyproject.contains("[tool.black") {
+            add("Format", format!("{}black .", runner));
+        }
+    }
+
+    if root.join("go.mod").exists() {
+        analysis.build_systems.push("Go modules".to_string());
+        add("Build", "go build ./...".to_string());
+        add("Test", "go test ./...".to_string());
+        add("Lint", "go vet ./...".to_string());
+    }
+    if root.join("pom.xml").exists() {
+        analysis.build_systems.push("Maven".to_string());
+        add("Build", "mvn package".to_string());
+        add("Test", "mvn test".to_string());
+    }
+    if root.join("build.gradle").exists() || root.join("build.gradle.kts").exists() {
+        analysis.build_systems.push("Gradle".to_string());
+        let gradle = if root.join("gradlew").exists() {
+            "./gradlew"
+        } else {
+            "gradle"
+        };
+        add("Build", format!("{} build", gradle));
+        add("Test", format!("{} test", gradle));
+    }
+    if root.join("CMakeLists.txt").exists() {
+        analysis.build_systems.push("CMake".to_string());
+    }
+    if let Some(makefile) = read(root, "Makefile") {
+        analysis.build_systems.push("Make".to_string());
+        let targets = make_targets(&makefile);
+        if !targets.is_empty() {
+            add("Make targets", targets.join(", "));
+        }
+    }
+}
+
+/// Targets defined in a Makefile, skipping special and pattern rules
+pub fn make_targets(makefile: &str) -> Vec<String> {
+    makefile
+        .lines()
+        .filter(|line| !line.starts_with(['\t', ' ', '.', '#']))
+        .filter_map(|line| {
+            let (target, rest) = line.split_once(':')?;
+            if rest.starts_with('=') {
+                return None; // `VAR := value`
+            }
+            let target = target.trim();
+            let valid = !target.is_empty()
+                && target
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_'));
+            valid.then(|| target.to_string())
+        })
+        .collect()
+}
+
+fn detect_conventions(root: &Path, files: &[String], analysis: &mut ProjectAnalysis) {
+    let exists = |name: &str| root.join(name).exists();
+    let conventions = &mut analysis.conventions;
+
+    if exists("rustfmt.toml") || exists(".rustfmt.toml") {
+        conventions.push("Rust formatting is configured in rustfmt.toml".to_string());
+    }
+    if exists("clippy.toml") || exists(".clippy.toml") {
+        conventions.push("Clippy lints are configured in clippy.toml".to_string());
+    }
+    let rust_sources: Vec<&String> = files
+        .iter()
+        .filter(|f| f.ends_with(".rs") && !f.starts_with("tests/"))
+        .take(MAX_SCANNED_SOURCES)
+        .collect();
+    let inline_tests = rust_sources
+        .iter()
+        .filter(|f| read(root, f).is_some_and(|c| c.contains("#[cfg(test)]")))
+        .count();
+    if inline_tests > 0 {
+        conventions.push(format!(
+            "Rust unit tests live inline in `#[cfg(test)]` modules ({} of {} source files)",
+            inline_tests,
+            rust_sources.len()
+        ));
+    }
+    if files.iter().any(|f| f.starts_with("tests/")) {
+        conventions.push("Integration tests live under tests/".to_string());
+    }
+
+    let colocated = files
+        .iter()
+        .filter(|f| f.contains(".test.") || f.contains(".spec."))
+        .count();
+    if colocated > 0 {
+        conventions.push(format!(
+            "JavaScript/TypeScript tests sit next to the code as *.test.* or *.spec.* files ({})",
+            colocated
+        ));
+    }
+    if files.iter().any(|f| f.contains("__tests__/")) {
+        conventions.push("Some tests are grouped in __tests__ directories".to_string());
+    }
+    let python_tests = files
+        .iter()
+        .filter(|f| {
+            let name = f.rsplit('/').next().unwrap_or(f);
+            name.starts_with("test_") && name.ends_with(".py")
+        })
+        .count();
+    if python_tests > 0 {
+        conventions.push(format!(
+            "Python tests are test_*.py modules ({})",
+            python_tests
+        ));
+    }
+
+    let package = read(root, "package.json").unwrap_or_default();
+    let has_prefix = |prefix: &str| {
+        files
+            .iter()
+            .any(|f| !f.contains('/') && f.starts_with(prefix))
+    };
+    if has_prefix(".prettierrc")
+        || has_prefix("prettier.config")
+        || package.contains("\"prettier\"")
+    {
+        conventions.push("Formatted with Prettier".to_string());
+    }
+    if has_prefix(".eslintrc") || has_prefix("eslint.config") {
+        conventions.push("Linted with ESLint".to_string());
+    }
+    if let Some(tsconfig) = read(root, "tsconfig.json") {
+        if tsconfig.contains("\"strict\": true") || tsconfig.contains("\"strict\":true") {
+            conventions.push("TypeScript runs in strict mode".to_string());
+        }
+    }
+    let pyproject = read(root, "pyproject.toml").unwrap_or_default();
+    if pyproject.contains("[tool.ruff") || exists("ruff.toml") {
+        conventions.push("Python is linted with Ruff".to_string());
+    }
+    if pyproject.contains("[tool.black") {
+        conventions.push("Python is formatted with Black".to_string());
+    }
+    if pyproject.contains("[tool.mypy") || exists("mypy.ini") {
+        conventions.push("Python is type-checked with mypy".to_string());
+    }
+
+    if let Some(editorconfig) = read(root, ".editorconfig") {
+        let style = editorconfig_value(&editorconfig, "indent_style");
+        let size = editorconfig_value(&editorconfig, "indent_size");
+        match (style, size) {
+            (Some(style), Some(size)) => {
+                conventions.push(format!("Indentation: {} {} (.editorconfig)", size, style))
+            }
+            (Some(style), None) => {
+                conventions.push(format!("Indentation: {} (.editorconfig)", style))
+            }
+            _ => {}
+        }
+    }
+    if exists(".pre-commit-config.yaml") {
+        conventions.push("pre-commit hooks run on commit (.pre-commit-config.yaml)".to_string());
+    }
+
+    let workflows: Vec<String> = std::fs::read_dir(root.join(".github").join("workflows"))
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.file_name().to_string_lossy().into_owned())
+        .filter(|name| name.ends_with(".yml") || name.ends_with(".yaml"))
+        .collect();
+    if !workflows.is_empty() {
+        let mut workflows = workflows;
+        workflows.sort();
+        conventions.push(format!("CI: GitHub Actions ({})", workflows.join(", ")));
+    } else if exists(".gitlab-ci.yml") {
+        conventions.push("CI: GitLab CI (.gitlab-ci.yml)".to_string());
+    }
+}
+
+/// First value of `key` in an .editorconfig
+fn editorconfig_value(editorconfig: &str, key: &str) -> Option<String> {
+    editorconfig.lines().find_map(|line| {
+        let (k, v) = line.split_once('=')?;
+        (k.trim() == key).then(|| v.trim().to_string())
+    })
+}
+
+impl ProjectAnalysis {
+    /// Human-readable summary for `/summary`
+    pub fn summary(&self) -> String {
+        let mut out = String::new();
+        out.push_str("📊 Project Summary\n");
+        out.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+        out.push_str(&format!("{} ({} files)\n\n", self.name, self.file_count));
+        out.push_str(&self.facts());
+        out
+    }
+
+    /// Languages, build systems, commands, layout and conventions as
+    /// Markdown sections
+    fn facts(&self) -> String {
+        let mut out = String::new();
+        if !self.languages.is_empty() {
+            out.push_str("## Languages\n\n");
+            for (language, count) in self.languages.iter().take(6) {
+                out.push_str(&format!("- {} ({} files)\n", language, count));
+            }
+            out.push('\n');
+        }
+        if !self.commands.is_empty() || !self.build_systems.is_empty() {
+            out.push_str("## Build and Test\n\n");
+            if !self.build_systems.is_empty() {
+                out.push_str(&format!(
+                    "Build systems: {}\n\n",
+                    self.build_systems.join(", ")
+                ));
+            }
+            for (label, command) in &self.commands {
+                if label == "Make targets" {
+                    out.push_str(&format!("- {}: {}\n", label, command));
+                } else {
+                    out.push_str(&format!("- {}: `{}`\n", label, command));
+                }
+            }
+            out.push('\n');
+        }
+        if !self.layout.is_empty() {
+            out.push_str("## Layout\n\n");
+            for (dir, count) in self.layout.iter().take(12) {
+                out.push_str(&format!("- `{}` ({} files)\n", dir, count));
+            }
+            out.push('\n');
+        }
+        if !self.conventions.is_empty() {
+            out.push_str("## Tooling\n\n");
+            for convention in &self.conventions {
+                out.push_str(&format!("- {}\n", convention));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// The SAFE_CODER.md contents, with the model-written sections if any
+    pub fn to_memory(&self, described: Option<&str>) -> String {
+        let mut out = format!(
+            "# {}\n\n<!-- Generated by /init from the code base. Edit it freely; it is read at \
+             the start of every session. -->\n\n",
+            self.name
+        );
+        match described {
+            Some(described) => {
+                out.push_str(described.trim());
+                out.push_str("\n\n");
+            }
+            None => out.push_str("## Overview\n\n[Describe the project here]\n\n"),
+        }
+        out.push_str(&self.facts());
+        out.trim_end().to_string() + "\n"
+    }
+
+    /// Source files the model sees the start of: manifests and entry points
+    fn excerpts(&self, root: &Path) -> String {
+        const CANDIDATES: &[&str] = &[
+            "Cargo.toml",
+            "package.json",
+            "pyproject.toml",
+            "go.mod",
+            "src/main.rs",
+            "src/lib.rs",
+            "src/index.ts",
+            "src/main.ts",
+            "src/index.js",
+            "src/App.tsx",
+            "main.py",
+            "app.py",
+            "main.go",
+        ];
+        let mut out = String::new();
+        for file in CANDIDATES
+            .iter()
+            .filter(|f| self.files.iter().any(|p| p == *f))
+        {
+            let Some(content) = read(root, file) else {
+                continue;
+            };
+            let head: Vec<&str> = content.lines().take(ENTRY_POINT_LINES).collect();
+            out.push_str(&format!("\n### {}\n```\n{}\n```\n", file, head.join("\n")));
+        }
+        out
+    }
+}
+
+/// Ask the model for the Overview, Architecture and Conventions sections
+async fn describe(
+    client: &dyn LlmClient,
+    analysis: &ProjectAnalysis,
+    root: &Path,
+) -> Result<String> {
+    let readme = ["README.md", "README", "readme.md", "README.rst"]
+        .iter()
+        .find_map(|f| read(root, f))
+        .unwrap_or_default();
+    let paths: Vec<&str> = analysis
+        .files
+        .iter()
+        .take(MAX_LISTED_PATHS)
+        .map(String::as_str)
+        .collect();
+    let message = format!(
+        "Project: {}\n\n{}\n## README (start)\n{}\n\n## Files\n{}\n\n## Excerpts\n{}",
+        analysis.name,
+        analysis.facts(),
+        truncate_str(&readme, 4000),
+        paths.join("\n"),
+        analysis.excerpts(root)
+    );
+    let response = client
+        .send_message_with_system(&[Message::user(message)], &[], Some(GENERATE_PROMPT))
+        .await?;
+    let reply: String = response
+        .message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    // Keep only from the first section heading on
+    let start = reply
+        .find("## ")
+        .context("The model's reply has no sections")?;
+    Ok(reply[start..].trim().to_string())
+}
+
+/// Analyze the project and write `.safe-coder/SAFE_CODER.md`. With a client,
+/// the model describes the project; without one, or if that fails, the file
+/// holds the detected facts only. An existing file is kept unless `force`.
+pub async fn write_project_memory(
+    root: &Path,
+    client: Option<&dyn LlmClient>,
+    force: bool,
+) -> Result<PathBuf> {
+    let path = root.join(".safe-coder").join("SAFE_CODER.md");
+    if path.exists() && !force {
+        bail!(
+            "{} already exists; use `/init force` to regenerate it",
+            path.display()
+        );
+    }
+
+    let analysis = analyze(root);
+    let described = match client {
+        Some(client) => match describe(client, &analysis, root).await {
+            Ok(described) => Some(described),
+            Err(e) => {
+                tracing::warn!(
+                    "Project description failed, writing detected facts only: {:#}",
+                    e
+                );
+                None
+            }
+        },
+        None => None,
+    };
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, analysis.to_memory(described.as_deref()))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_detects_stack_and_conventions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src")).unwrap();
+        std::fs::create_dir_all(root.join("tests")).unwrap();
+        std::fs::create_dir_all(root.join(".github/workflows")).unwrap();
+        std::fs::write(root.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "#[cfg(test)]\nmod tests {}\n").unwrap();
+        std::fs::write(root.join("src/util.rs"), "pub fn f() {}\n").unwrap();
+        std::fs::write(root.join("tests/it.rs"), "").unwrap();
+        std::fs::write(root.join(".github/workflows/ci.yml"), "").unwrap();
+        std::fs::write(
+            root.join(".editorconfig"),
+            "[*]\nindent_style = space\nindent_size = 4\n",
+        )
+        .unwrap();
+
+        let analysis = analyze(root);
+        assert_eq!(analysis.languages, vec![("Rust".to_string(), 3)]);
+        assert_eq!(analysis.build_systems, vec!["Cargo"]);
+        assert!(analysis
+            .commands
+            .contains(&("Test".to_string(), "cargo test".to_string())));
+        assert_eq!(analysis.layout[0], ("src/".to_string(), 2));
+        assert!(analysis.conventions.contains(
+            &"Rust unit tests live inline in `#[cfg(test)]` modules (1 of 2 source files)"
+                .to_string()
+        ));
+        assert!(analysis
+            .conventions
+            .contains(&"CI: GitHub Actions (ci.yml)".to_string()));
+        assert!(analysis
+            .conventions
+            .contains(&"Indentation: 4 space (.editorconfig)".to_string()));
+
+        let memory = analysis.to_memory(Some("## Overview\n\nA demo crate."));
+        assert!(memory.contains("## Overview\n\nA demo crate.\n\n## Languages"));
+        assert!(memory.contains("- Lint: `cargo clippy --all-targets -- -D warnings`"));
+    }
+
+    #[test]
+    fn test_package_scripts_and_make_targets() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("package.json"),
+            r#"{"scripts": {"dev": "vite", "test": "vitest run", "lint": "eslint ."}}"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("pnpm-lock.yaml"), "").unwrap();
+        let analysis = analyze(root);
+        assert_eq!(
+            analysis.commands,
+            vec![
+                ("Dev server".to_string(), "pnpm dev".to_string()),
+                ("Test".to_string(), "pnpm test".to_string()),
+                ("Lint".to_string(), "pnpm lint".to_string()),
+            ]
+        );
+
+        let makefile = "CC := gcc\n.PHONY: all\nall: build\nbuild:\n\tcc main.c\n%.o: %.c\n";
+        assert_eq!(make_targets(makefile), vec!["all", "build"]);
+    }
+}
@@ -0,0 +1,271 @@
+//! SAFE_CODER.md as sections of individual entries, so single entries can
+//! be edited or deleted without rewriting the rest of the file.
+//!
+//! An entry is a list item (with its continuation lines) or a paragraph;
+//! fenced code blocks stay in one entry. Edits re-read the file and find
+//! the entry by its text, so changes made elsewhere in the file by an editor
+//! or another session since it was loaded are kept.
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+use super::learned::memory_file;
+
+/// One entry of the memory file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryEntry {
+    /// Heading of the section it's in ("Learned"), None before the first heading
+    pub section: Option<String>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Block {
+    text: String,
+    /// Preceded by a blank line (paragraphs) rather than directly following
+    /// the previous entry (items of the same list)
+    spaced: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Section {
+    heading: Option<String>,
+    blocks: Vec<Block>,
+}
+
+/// Parsed memory file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoryDocument {
+    sections: Vec<Section>,
+}
+
+impl MemoryDocument {
+    pub fn parse(content: &str) -> Self {
+        let mut sections = vec![Section::default()];
+        let mut current: Option<Block> = None;
+        let mut blank = false;
+        let mut in_fence = false;
+
+        for line in content.lines() {
+            let section = sections.last_mut().unwrap();
+            if in_fence {
+                let block = current.get_or_insert_with(Block::default);
+                block.text.push('\n');
+                block.text.push_str(line);
+                in_fence = !line.trim_start().starts_with("```");
+                continue;
+            }
+            if let Some(heading) = line.strip_prefix("## ") {
+                section.blocks.extend(current.take());
+                sections.push(Section {
+                    heading: Some(heading.trim().to_string()),
+                    blocks: Vec::new(),
+                });
+                blank = false;
+                continue;
+            }
+            if line.trim().is_empty() {
+                section.blocks.extend(current.take());
+                blank = true;
+                continue;
+            }
+
+            match current.as_mut() {
+                Some(block) if !is_item(line) => {
+                    block.text.push('\n');
+                    block.text.push_str(line);
+                }
+                _ => {
+                    section.blocks.extend(current.take());
+                    current = Some(Block {
+                        text: line.to_string(),
+                        spaced: blank,
+                    });
+                }
+            }
+            blank = false;
+            if line.trim_start().starts_with("```") {
+                in_fence = true;
+            }
+        }
+        sections.last_mut().unwrap().blocks.extend(current);
+
+        if sections[0].blocks.is_empty() && sections.len() > 1 {
+            sections.remove(0);
+        }
+        Self { sections }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for section in &self.sections {
+            if let Some(heading) = &section.heading {
+                if !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str("## ");
+                out.push_str(heading);
+                out.push('\n');
+            }
+            for (i, block) in section.blocks.iter().enumerate() {
+                let first = i == 0 && (section.heading.is_some() || !out.is_empty());
+                if (block.spaced || first) && !out.is_empty() {
+                    out.push('\n');
+                }
+                out.push_str(&block.text);
+                out.push('\n');
+            }
+        }
+        out
+    }
+
+    /// All entries in file order
+    pub fn entries(&self) -> Vec<MemoryEntry> {
+        self.sections
+            .iter()
+            .flat_map(|section| {
+                section.blocks.iter().map(|block| MemoryEntry {
+                    section: section.heading.clone(),
+                    text: block.text.clone(),
+                })
+            })
+            .collect()
+    }
+
+    /// Replace `entry` with `text`, or delete it when `text` is empty.
+    /// Fails when the document no longer has the entry.
+    pub fn replace(&mut self, entry: &MemoryEntry, text: &str) -> Result<()> {
+        let section = self
+            .sections
+            .iter_mut()
+            .find(|s| s.heading == entry.section)
+            .context("The entry's section is no longer in the file")?;
+        let Some(index) = section.blocks.iter().position(|b| b.text == entry.text) else {
+            bail!("The entry was changed or removed in the file; reloaded the latest version");
+        };
+        let text = text.trim_end();
+        if text.trim().is_empty() {
+            section.blocks.remove(index);
+        } else {
+            section.blocks[index].text = text.to_string();
+        }
+        Ok(())
+    }
+
+    /// Add an entry at the end of `section`, creating the section if needed
+    pub fn add(&mut self, section: Option<&str>, text: &str) {
+        let text = text.trim_end().to_string();
+        let heading = section.map(str::to_string);
+        let spaced = !is_item(&text);
+        match self.sections.iter_mut().find(|s| s.heading == heading) {
+            Some(section) => section.blocks.push(Block { text, spaced }),
+            None => self.sections.push(Section {
+                heading,
+                blocks: vec![Block { text, spaced }],
+            }),
+        }
+    }
+}
+
+fn is_item(line: &str) -> bool {
+    let first = line.lines().next().unwrap_or_default();
+    if first.starts_with("- ") || first.starts_with("* ") || first.starts_with("+ ") {
+        return true;
+    }
+    let digits = first.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && first[digits..].starts_with(". ")
+}
+
+/// Read and parse the project's memory file (empty when there is none)
+pub fn load(project: &Path) -> Result<MemoryDocument> {
+    let path = memory_file(project);
+    if !path.exists() {
+        return Ok(MemoryDocument::default());
+    }
+    let content = std::fs::read_to_string(&path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(MemoryDocument::parse(&content))
+}
+
+/// Replace or delete (empty `text`) one entry of the memory file on disk
+pub fn update_entry(project: &Path, entry: &MemoryEntry, text: &str) -> Result<()> {
+    let mut document = load(project)?;
+    document.replace(entry, text)?;
+    save(project, &document)
+}
+
+/// Add an entry to the memory file on disk
+pub fn add_entry(project: &Path, section: Option<&str>, text: &str) -> Result<()> {
+    let mut document = load(project)?;
+    document.add(section, text);
+    save(project, &document)
+}
+
+/// Write through a temporary file so readers never see a half-written file
+fn save(project: &Path, document: &MemoryDocument) -> Result<()> {
+    let path = memory_file(project);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let temp = path.with_extension("md.tmp");
+    std::fs::write(&temp, document.render())
+        .with_context(|| format!("Failed to write {}", temp.display()))?;
+    std::fs::rename(&temp, &path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MEMORY: &str = "# Project: demo\n\nA small CLI.\n\n## Commands\n\n- Build: `cargo build`\n- Test: `cargo test`\n  (needs Docker)\n\n## Conventions\n\nErrors use anyhow.\n\n```rust\nfn main() {\n\n}\n```\n";
+
+    #[test]
+    fn parses_entries_and_renders_unchanged() {
+        let document = MemoryDocument::parse(MEMORY);
+        let entries = document.entries();
+        let texts: Vec<&str> = entries.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(
+            texts,
+            [
+                "# Project: demo",
+                "A small CLI.",
+                "- Build: `cargo build`",
+                "- Test: `cargo test`\n  (needs Docker)",
+                "Errors use anyhow.",
+                "```rust\nfn main() {\n\n}\n```",
+            ]
+        );
+        assert_eq!(entries[2].section.as_deref(), Some("Commands"));
+        assert_eq!(entries[4].section.as_deref(), Some("Conventions"));
+        assert_eq!(document.render(), MEMORY);
+    }
+
+    #[test]
+    fn edits_keep_changes_made_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = memory_file(dir.path());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, MEMORY).unwrap();
+
+        let entries = load(dir.path()).unwrap().entries();
+        let build = entries[2].clone();
+        let errors = entries[4].clone();
+
+        // Someone else edits another entry in the meantime
+        std::fs::write(&path, MEMORY.replace("A small CLI.", "A small, fast CLI.")).unwrap();
+
+        update_entry(dir.path(), &build, "- Build: `make`").unwrap();
+        update_entry(dir.path(), &errors, "").unwrap();
+        add_entry(dir.path(), Some("Learned"), "- Use pnpm").unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("A small, fast CLI."));
+        assert!(content.contains("- Build: `make`\n- Test"));
+        assert!(!content.contains("anyhow"));
+        assert!(content.ends_with("```\n\n## Learned\n\n- Use pnpm\n"));
+
+        // The entry was changed on disk since it was loaded
+        assert!(update_entry(dir.path(), &build, "- Build: `just`").is_err());
+    }
+}
@@ -0,0 +1,260 @@
+//! Facts learned during a session ("we use pnpm, not npm"), appended to the
+//! project memory file so later sessions start with them.
+//!
+//! Facts come from an explicit `#remember <fact>` message or, with
+//! `[memory] auto_learn = true`, from a model pass over messages that look
+//! like corrections.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+use crate::llm::{ContentBlock, LlmClient, Message};
+use crate::utils::truncate_str;
+
+/// Section of SAFE_CODER.md that learned facts are appended to
+const LEARNED_HEADING: &str = "## Learned";
+
+/// Prefix that stores the rest of a message as a project fact
+const REMEMBER_PREFIX: &str = "#remember";
+
+const DETECT_PROMPT: &str = "You maintain the project memory of an AI coding assistant. \
+The user has just replied to the assistant. Decide whether the reply corrects the assistant \
+with a durable fact about this project: a tool, command, convention, path or preference that \
+will still hold in future sessions (for example \"we use pnpm, not npm\" or \"tests live in \
+tests/, not next to the code\"). One-off instructions about the current task are not durable.\n\n\
+If there is such a fact, answer with that fact alone as one short imperative or declarative \
+sentence, without quotes. Otherwise answer exactly NONE.";
+
+/// Where a learned fact came from, recorded next to it in the memory file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LearnedSource {
+    /// Stored with `#remember`
+    User,
+    /// Picked out of a correction by the model
+    Correction,
+}
+
+impl LearnedSource {
+    fn label(self) -> &'static str {
+        match self {
+            LearnedSource::User => "added by user",
+            LearnedSource::Correction => "learned from correction",
+        }
+    }
+}
+
+/// The fact in a `#remember <fact>` message, if the message is one
+pub fn parse_remember(message: &str) -> Option<&str> {
+    let message = message.trim_start();
+    let prefix = message.get(..REMEMBER_PREFIX.len())?;
+    if !prefix.eq_ignore_ascii_case(REMEMBER_PREFIX) {
+        return None;
+    }
+    let rest = &message[REMEMBER_PREFIX.len()..];
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) && !rest.starts_with(':') {
+        return None;
+    }
+    Some(rest.trim_start_matches(':').trim())
+}
+
+/// Path of the project memory file
+pub fn memory_file(project: &Path) -> PathBuf {
+    project.join(".safe-coder").join("SAFE_CODER.md")
+}
+
+/// Append `fact` to the project memory under "## Learned". Returns false
+/// when the memory already holds the same fact.
+pub fn remember(project: &Path, fact: &str, source: LearnedSource) -> Result<bool> {
+    let fact = fact.trim().trim_start_matches("- ").trim();
+    anyhow::ensure!(!fact.is_empty(), "Nothing to remember");
+
+    let path = memory_file(project);
+    let content = std::fs::read_to_string(&path).unwrap_or_default();
+    if contains_fact(&content, fact) {
+        return Ok(false);
+    }
+
+    let entry = format!(
+        "- {} _({}, {})_",
+        fact.replace('\n', " "),
+        source.label(),
+        chrono::Local::now().format("%Y-%m-%d")
+    );
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(&path, insert_learned(&content, &entry))
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(true)
+}
+
+/// Whether a message is worth asking the model about. Cheap wording check so
+/// that ordinary requests don't each cost an extra model call.
+pub fn looks_like_correction(message: &str) -> bool {
+    const MARKERS: &[&str] = &[
+        "not ",
+        "n't ",
+        "instead",
+        "we use",
+        "we don't",
+        "always ",
+        "never ",
+        "should be",
+        "wrong",
+        "actually",
+        "prefer",
+    ];
+    let message = message.to_lowercase();
+    message.len() < 1000 && MARKERS.iter().any(|m| message.contains(m))
+}
+
+/// Ask the model whether `message`, a reply to `previous_reply`, teaches a
+/// durable project fact
+pub async fn detect_fact(
+    client: &dyn LlmClient,
+    previous_reply: &str,
+    message: &str,
+) -> Result<Option<String>> {
+    let prompt = format!(
+        "Assistant's previous reply (end):\n{}\n\nUser's reply:\n{}",
+        tail(previous_reply, 2000),
+        message
+    );
+    let response = client
+        .send_message_with_system(&[Message::user(prompt)], &[], Some(DETECT_PROMPT))
+        .await?;
+    let reply: String = response
+        .message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    let fact = reply.trim().trim_matches('"').trim();
+    if fact.is_empty() || fact.eq_ignore_ascii_case("none") || fact.lines().count() > 1 {
+        return Ok(None);
+    }
+    Ok(Some(truncate_str(fact, 300).to_string()))
+}
+
+fn tail(text: &str, max: usize) -> &str {
+    let mut start = text.len().saturating_sub(max);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    &text[start..]
+}
+
+/// Lowercased words only, so punctuation and spacing don't defeat dedup
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn contains_fact(content: &str, fact: &str) -> bool {
+    let fact = normalize(fact);
+    content.lines().any(|line| {
+        let line = line.trim().trim_start_matches(['-', '*']).trim();
+        // Drop the attribution of learned entries
+        let line = match line.rfind(" _(") {
+            Some(i) if line.ends_with(")_") => &line[..i],
+            _ => line,
+        };
+        normalize(line) == fact
+    })
+}
+
+/// Add `entry` at the end of the "## Learned" section, creating the section
+/// at the end of the file when there is none
+fn insert_learned(content: &str, entry: &str) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let Some(heading) = lines.iter().position(|l| l.trim() == LEARNED_HEADING) else {
+        let mut out = content.trim_end().to_string();
+        if !out.is_empty() {
+            out.push_str("\n\n");
+        }
+        out.push_str(LEARNED_HEADING);
+        out.push_str("\n\n");
+        out.push_str(entry);
+        out.push('\n');
+        return out;
+    };
+
+    let section_end = lines[heading + 1..]
+        .iter()
+        .position(|l| l.starts_with("## "))
+        .map(|i| heading + 1 + i)
+        .unwrap_or(lines.len());
+    // Insert after the section's last non-blank line
+    let insert_at = (heading + 1..section_end)
+        .rev()
+        .find(|&i| !lines[i].trim().is_empty())
+        .map(|i| i + 1)
+        .unwrap_or(heading + 1);
+
+    let mut out: Vec<&str> = lines[..insert_at].to_vec();
+    if insert_at == heading + 1 {
+        out.push("");
+    }
+    out.push(entry);
+    if insert_at < lines.len() && !lines[insert_at].trim().is_empty() {
+        out.push("");
+    }
+    out.extend_from_slice(&lines[insert_at..]);
+    let mut result = out.join("\n");
+    result.push('\n');
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_remember_prefix() {
+        assert_eq!(parse_remember("#remember we use pnpm"), Some("we use pnpm"));
+        assert_eq!(
+            parse_remember("  #Remember: tabs, not spaces "),
+            Some("tabs, not spaces")
+        );
+        assert_eq!(parse_remember("#remember"), Some(""));
+        assert_eq!(parse_remember("#remembered that"), None);
+        assert_eq!(parse_remember("please #remember this"), None);
+    }
+
+    #[test]
+    fn appends_to_learned_section_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = memory_file(dir.path());
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &path,
+            "# Project\n\n## Learned\n\n- Run tests with `make test`\n\n## Notes\n\nHand-written.\n",
+        )
+        .unwrap();
+
+        assert!(remember(dir.path(), "We use pnpm, not npm.", LearnedSource::User).unwrap());
+        assert!(!remember(dir.path(), "we use pnpm not npm", LearnedSource::Correction).unwrap());
+        assert!(!remember(dir.path(), "run tests with make test", LearnedSource::User).unwrap());
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        let learned = content.find("We use pnpm").unwrap();
+        assert!(learned > content.find("make test").unwrap());
+        assert!(learned < content.find("## Notes").unwrap());
+        assert!(content.contains("(added by user, "));
+        assert_eq!(content.matches("pnpm").count(), 1);
+    }
+
+    #[test]
+    fn creates_section_when_missing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(remember(dir.path(), "Use tabs", LearnedSource::Correction).unwrap());
+        let content = std::fs::read_to_string(memory_file(dir.path())).unwrap();
+        assert!(content.starts_with("## Learned\n\n- Use tabs _(learned from correction, "));
+    }
+}
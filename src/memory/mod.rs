@@ -1,3 +1,7 @@
+pub mod analysis;
+pub mod entries;
+pub mod learned;
+
 use anyhow::{Context, Result};
 use std::path::PathBuf;
 use tokio::fs;
@@ -102,45 +106,6 @@ impl MemoryManager {
         Ok(())
     }
 
-    /// Create default SAFE_CODER.md file
-    pub async fn init_file(&self) -> Result<()> {
-        let memory_path = self.memory_file_path();
-
-        // Create .safe-coder directory
-        if let Some(parent) = memory_path.parent() {
-            fs::create_dir_all(parent).await?;
-        }
-
-        let default_content = r#"# Project Context for Safe Coder
-
-This file contains instructions and context that will be included in every conversation with Safe Coder AI.
-
-## Project Overview
-
-[Describe your project here]
-
-## Code Style Guidelines
-
-[Add your coding standards and preferences]
-
-## Important Conventions
-
-[List any project-specific conventions the AI should follow]
-
-## Files to Focus On
-
-[List important files or directories]
-
-## Things to Avoid
-
-[List any patterns or practices to avoid]
-"#;
-
-        fs::write(&memory_path, default_content).await?;
-
-        Ok(())
-    }
-
     /// Clear custom instructions
     pub fn clear_custom(&mut self) {
         self.custom_instructions.clear();
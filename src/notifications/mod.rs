@@ -0,0 +1,171 @@
+//! Desktop notifications for long-running work
+//!
+//! Fires a native notification when an orchestration run completes, a long
+//! shell command or AI task finishes, or the agent is waiting for approval.
+//! By default notifications only fire while the terminal is in the
+//! background; terminals that don't report focus are treated as unfocused.
+//! Each kind of event can be switched off under `[notifications]`. Teams
+//! can also get run summaries in chat; see [`webhook`].
+
+pub mod webhook;
+
+use std::time::Duration;
+
+use crate::config::NotificationsConfig;
+
+/// Something worth interrupting the user for
+#[derive(Debug, Clone, PartialEq)]
+pub enum Notification {
+    OrchestrationComplete {
+        succeeded: usize,
+        failed: usize,
+    },
+    TaskFinished {
+        title: String,
+        elapsed: Duration,
+        success: bool,
+    },
+    ApprovalNeeded {
+        what: String,
+    },
+}
+
+impl Notification {
+    fn summary(&self) -> String {
+        match self {
+            Self::OrchestrationComplete { failed: 0, .. } => "Orchestration complete".to_string(),
+            Self::OrchestrationComplete { .. } => {
+                "Orchestration finished with failures".to_string()
+            }
+            Self::TaskFinished { success: true, .. } => "Task finished".to_string(),
+            Self::TaskFinished { success: false, .. } => "Task failed".to_string(),
+            Self::ApprovalNeeded { .. } => "Approval needed".to_string(),
+        }
+    }
+
+    fn body(&self) -> String {
+        match self {
+            Self::OrchestrationComplete { succeeded, failed } => {
+                format!("{} tasks succeeded, {} failed", succeeded, failed)
+            }
+            Self::TaskFinished { title, elapsed, .. } => {
+                let secs = elapsed.as_secs();
+                format!(
+                    "{} ({}m {}s)",
+                    crate::utils::truncate_str(title, 80),
+                    secs / 60,
+                    secs % 60
+                )
+            }
+            Self::ApprovalNeeded { what } => what.clone(),
+        }
+    }
+}
+
+/// Sends notifications according to the config and the terminal's focus
+pub struct Notifier {
+    config: NotificationsConfig,
+    /// `None` until the terminal reports focus
+    focused: Option<bool>,
+}
+
+impl Notifier {
+    pub fn new(config: NotificationsConfig) -> Self {
+        Self {
+            config,
+            focused: None,
+        }
+    }
+
+    pub fn set_focused(&mut self, focused: bool) {
+        self.focused = Some(focused);
+    }
+
+    fn should_show(&self, notification: &Notification) -> bool {
+        let wanted = match notification {
+            Notification::OrchestrationComplete { .. } => self.config.orchestration_complete,
+            Notification::TaskFinished { elapsed, .. } => {
+                self.config.long_task_finished
+                    && *elapsed >= Duration::from_secs(self.config.long_task_secs)
+            }
+            Notification::ApprovalNeeded { .. } => self.config.approval_needed,
+        };
+        self.config.enabled
+            && wanted
+            && !(self.config.only_when_unfocused && self.focused == Some(true))
+    }
+
+    /// Show a notification if it is enabled, without blocking the caller
+    pub fn notify(&self, notification: Notification) {
+        self.spawn(notification);
+    }
+
+    /// Like `notify`, but wait until it has been handed to the OS. Use this
+    /// right before the process exits.
+    pub fn notify_and_wait(&self, notification: Notification) {
+        if let Some(handle) = self.spawn(notification) {
+            let _ = handle.join();
+        }
+    }
+
+    fn spawn(&self, notification: Notification) -> Option<std::thread::JoinHandle<()>> {
+        if !self.should_show(&notification) {
+            return None;
+        }
+        let summary = notification.summary();
+        let body = notification.body();
+        Some(std::thread::spawn(move || {
+            if let Err(e) = notify_rust::Notification::new()
+                .appname("Safe-Coder")
+                .summary(&summary)
+                .body(&body)
+                .show()
+            {
+                tracing::debug!("Failed to show notification: {}", e);
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finished(secs: u64) -> Notification {
+        Notification::TaskFinished {
+            title: "cargo build".to_string(),
+            elapsed: Duration::from_secs(secs),
+            success: true,
+        }
+    }
+
+    #[test]
+    fn test_long_tasks_only() {
+        let notifier = Notifier::new(NotificationsConfig::default());
+        assert!(!notifier.should_show(&finished(5)));
+        assert!(notifier.should_show(&finished(45)));
+    }
+
+    #[test]
+    fn test_focus_and_toggles() {
+        let mut notifier = Notifier::new(NotificationsConfig {
+            approval_needed: false,
+            ..Default::default()
+        });
+        let approval = Notification::ApprovalNeeded {
+            what: "bash: rm -rf target".to_string(),
+        };
+        let done = Notification::OrchestrationComplete {
+            succeeded: 2,
+            failed: 1,
+        };
+        assert!(!notifier.should_show(&approval));
+        assert!(notifier.should_show(&done));
+
+        notifier.set_focused(true);
+        assert!(!notifier.should_show(&done));
+        notifier.set_focused(false);
+        assert!(notifier.should_show(&done));
+        assert_eq!(done.summary(), "Orchestration finished with failures");
+    }
+}
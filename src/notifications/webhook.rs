@@ -0,0 +1,307 @@
+//! Slack and Discord webhooks for monitoring long autonomous runs
+//!
+//! Each `[[notifications.webhooks]]` entry is an incoming-webhook URL plus
+//! the events it wants. Messages link back to the session on the server when
+//! `notifications.server_url` is set. Posting happens in the background and
+//! failures are only logged, so a dead webhook never stalls the agent.
+
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::NotificationsConfig;
+use crate::utils::truncate_str;
+
+/// Discord rejects messages longer than this
+const DISCORD_MAX_CHARS: usize = 2000;
+/// Keep Slack messages readable
+const SLACK_MAX_CHARS: usize = 3000;
+
+/// One incoming webhook
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Message format; guessed from the URL when unset
+    #[serde(default)]
+    pub kind: Option<WebhookKind>,
+    /// Events to post; all of them when empty
+    #[serde(default)]
+    pub events: Vec<WebhookEventKind>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookKind {
+    Slack,
+    Discord,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEventKind {
+    Orchestration,
+    TaskFailed,
+    ApprovalNeeded,
+}
+
+impl WebhookConfig {
+    fn kind(&self) -> WebhookKind {
+        self.kind.unwrap_or_else(|| {
+            if self.url.contains("discord.com/") || self.url.contains("discordapp.com/") {
+                WebhookKind::Discord
+            } else {
+                // Slack's `{"text": ...}` payload is also what most other chat tools accept
+                WebhookKind::Slack
+            }
+        })
+    }
+
+    fn wants(&self, event: WebhookEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// Something a team watching a run should hear about
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebhookEvent {
+    OrchestrationComplete {
+        request: String,
+        succeeded: usize,
+        failed: usize,
+        summary: String,
+        session_id: Option<String>,
+    },
+    TaskFailed {
+        task: String,
+        worker: String,
+        error: String,
+        session_id: Option<String>,
+    },
+    ApprovalNeeded {
+        what: String,
+        project: String,
+        session_id: String,
+    },
+}
+
+impl WebhookEvent {
+    fn kind(&self) -> WebhookEventKind {
+        match self {
+            Self::OrchestrationComplete { .. } => WebhookEventKind::Orchestration,
+            Self::TaskFailed { .. } => WebhookEventKind::TaskFailed,
+            Self::ApprovalNeeded { .. } => WebhookEventKind::ApprovalNeeded,
+        }
+    }
+
+    fn session_id(&self) -> Option<&str> {
+        match self {
+            Self::OrchestrationComplete { session_id, .. }
+            | Self::TaskFailed { session_id, .. } => session_id.as_deref(),
+            Self::ApprovalNeeded { session_id, .. } => Some(session_id),
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Self::OrchestrationComplete {
+                request,
+                succeeded,
+                failed,
+                summary,
+                ..
+            } => format!(
+                "{} Orchestration finished: {} succeeded, {} failed\n> {}\n{}",
+                if *failed == 0 { "✅" } else { "⚠️" },
+                succeeded,
+                failed,
+                truncate_str(request, 200),
+                summary.trim()
+            ),
+            Self::TaskFailed {
+                task,
+                worker,
+                error,
+                ..
+            } => format!(
+                "❌ Task failed ({}): {}\n{}",
+                worker,
+                truncate_str(task, 200),
+                error.trim()
+            ),
+            Self::ApprovalNeeded { what, project, .. } => {
+                format!("⏸️ Approval needed in {}\n{}", project, what.trim())
+            }
+        }
+    }
+}
+
+/// Posts events to the configured webhooks
+#[derive(Clone)]
+pub struct WebhookNotifier {
+    hooks: Arc<Vec<WebhookConfig>>,
+    server_url: Option<String>,
+    client: reqwest::Client,
+}
+
+impl WebhookNotifier {
+    /// `None` when no webhooks are configured
+    pub fn from_config(config: &NotificationsConfig) -> Option<Self> {
+        if config.webhooks.is_empty() {
+            return None;
+        }
+        Some(Self {
+            hooks: Arc::new(config.webhooks.clone()),
+            server_url: config
+                .server_url
+                .as_ref()
+                .map(|url| url.trim_end_matches('/').to_string()),
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_else(|_| reqwest::Client::new()),
+        })
+    }
+
+    /// Post in the background
+    pub fn notify(&self, event: WebhookEvent) {
+        let notifier = self.clone();
+        tokio::spawn(async move { notifier.send(&event).await });
+    }
+
+    /// Post to every webhook that wants this event
+    pub async fn send(&self, event: &WebhookEvent) {
+        for hook in self.hooks.iter().filter(|h| h.wants(event.kind())) {
+            let payload = self.payload(hook.kind(), event);
+            match self.client.post(&hook.url).json(&payload).send().await {
+                Ok(response) if !response.status().is_success() => {
+                    tracing::warn!("Webhook returned {}", response.status());
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Failed to post webhook: {}", e),
+            }
+        }
+    }
+
+    fn payload(&self, kind: WebhookKind, event: &WebhookEvent) -> serde_json::Value {
+        let link = match (&self.server_url, event.session_id()) {
+            (Some(base), Some(id)) => Some(format!("{}/api/sessions/{}", base, id)),
+            _ => None,
+        };
+        match kind {
+            WebhookKind::Slack => {
+                let mut text = event.text();
+                if let Some(link) = link {
+                    text.push_str(&format!("\n<{}|Open session>", link));
+                }
+                serde_json::json!({ "text": limit(&text, SLACK_MAX_CHARS) })
+            }
+            WebhookKind::Discord => {
+                let mut text = event.text();
+                if let Some(link) = link {
+                    text.push_str(&format!("\n[Open session]({})", link));
+                }
+                serde_json::json!({
+                    "username": "Safe-Coder",
+                    "content": limit(&text, DISCORD_MAX_CHARS),
+                })
+            }
+        }
+    }
+}
+
+fn limit(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        text.to_string()
+    } else {
+        format!("{}…", truncate_str(text, max_chars - 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notifier(hooks: Vec<WebhookConfig>) -> WebhookNotifier {
+        WebhookNotifier::from_config(&NotificationsConfig {
+            server_url: Some("http://build-box:9876/".to_string()),
+            webhooks: hooks,
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn approval() -> WebhookEvent {
+        WebhookEvent::ApprovalNeeded {
+            what: "bash: rm -rf target".to_string(),
+            project: "/work/app".to_string(),
+            session_id: "abc".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_payload_formats() {
+        let hook = |url: &str| WebhookConfig {
+            url: url.to_string(),
+            kind: None,
+            events: vec![],
+        };
+        let notifier = notifier(vec![hook("https://hooks.slack.com/services/x")]);
+        assert_eq!(
+            hook("https://discord.com/api/webhooks/1/x").kind(),
+            WebhookKind::Discord
+        );
+        assert_eq!(
+            hook("https://hooks.slack.com/services/x").kind(),
+            WebhookKind::Slack
+        );
+
+        let slack = notifier.payload(WebhookKind::Slack, &approval());
+        assert!(slack["text"]
+            .as_str()
+            .unwrap()
+            .ends_with("<http://build-box:9876/api/sessions/abc|Open session>"));
+
+        let failed = WebhookEvent::TaskFailed {
+            task: "migrate".to_string(),
+            worker: "claude".to_string(),
+            error: "x".repeat(5000),
+            session_id: None,
+        };
+        let discord = notifier.payload(WebhookKind::Discord, &failed);
+        let content = discord["content"].as_str().unwrap();
+        assert_eq!(content.chars().count(), DISCORD_MAX_CHARS);
+        assert!(!content.contains("Open session"));
+    }
+
+    #[tokio::test]
+    async fn test_send_respects_event_filter() {
+        let mut server = mockito::Server::new_async().await;
+        let approvals = server
+            .mock("POST", "/approvals")
+            .match_body(mockito::Matcher::Regex("Approval needed".to_string()))
+            .create_async()
+            .await;
+        let failures = server
+            .mock("POST", "/failures")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let notifier = notifier(vec![
+            WebhookConfig {
+                url: format!("{}/approvals", server.url()),
+                kind: Some(WebhookKind::Slack),
+                events: vec![],
+            },
+            WebhookConfig {
+                url: format!("{}/failures", server.url()),
+                kind: Some(WebhookKind::Discord),
+                events: vec![WebhookEventKind::TaskFailed],
+            },
+        ]);
+        notifier.send(&approval()).await;
+
+        approvals.assert_async().await;
+        failures.assert_async().await;
+    }
+}
@@ -0,0 +1,265 @@
+//! Per-task artifacts of orchestration runs
+//!
+//! Every run gets a directory under `.safe-coder/orchestration/<run>/` with
+//! the plan and one subdirectory per task holding what the worker was asked
+//! (`prompt.md`), everything it printed (`stdout.log`, `stderr.log`), what
+//! it changed (`diff.patch`) and how it ended (`result.json`). Logs are
+//! written as lines arrive, so they survive cancelled and timed out workers.
+//! `safe-coder orchestrate logs <run> <task>` reads them back.
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::orchestrator::{Task, TaskPlan, TaskResult, WorkerKind, WorkspaceDiff};
+
+pub const PROMPT_FILE: &str = "prompt.md";
+pub const STDOUT_FILE: &str = "stdout.log";
+pub const STDERR_FILE: &str = "stderr.log";
+pub const DIFF_FILE: &str = "diff.patch";
+pub const RESULT_FILE: &str = "result.json";
+
+/// Directory holding all runs of a project
+pub fn runs_dir(project_path: &Path) -> PathBuf {
+    project_path.join(".safe-coder").join("orchestration")
+}
+
+/// How a task ended, as stored in `result.json`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskOutcome {
+    pub task_id: String,
+    pub description: String,
+    pub worker: WorkerKind,
+    pub success: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub finished_at: String,
+}
+
+/// Artifact directory of one orchestration run
+#[derive(Debug, Clone)]
+pub struct RunArtifacts {
+    id: String,
+    dir: PathBuf,
+}
+
+impl RunArtifacts {
+    /// Start a new run directory named after the current time
+    pub fn create(project_path: &Path) -> Result<Self> {
+        let root = runs_dir(project_path);
+        std::fs::create_dir_all(&root)
+            .with_context(|| format!("Failed to create {}", root.display()))?;
+        // Keep artifacts out of commits made by branch-mode merges
+        let gitignore = root.join(".gitignore");
+        if !gitignore.exists() {
+            std::fs::write(&gitignore, "*\n")?;
+        }
+
+        let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let mut id = stamp.clone();
+        let mut n = 1;
+        while root.join(&id).exists() {
+            n += 1;
+            id = format!("{}-{}", stamp, n);
+        }
+        let dir = root.join(&id);
+        std::fs::create_dir_all(&dir)?;
+        Ok(Self { id, dir })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Directory for one task, created if needed
+    pub fn task_dir(&self, task_id: &str) -> Result<PathBuf> {
+        let dir = self.dir.join(task_id);
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    pub fn write_plan(&self, plan: &TaskPlan) -> Result<()> {
+        std::fs::write(
+            self.dir.join("plan.json"),
+            serde_json::to_string_pretty(plan)?,
+        )?;
+        Ok(())
+    }
+
+    /// Save the instructions exactly as the worker receives them
+    pub fn write_prompt(&self, task: &Task) -> Result<()> {
+        std::fs::write(
+            self.task_dir(&task.id)?.join(PROMPT_FILE),
+            &task.instructions,
+        )?;
+        Ok(())
+    }
+
+    pub fn write_diff(&self, task_id: &str, diff: &WorkspaceDiff) -> Result<()> {
+        std::fs::write(self.task_dir(task_id)?.join(DIFF_FILE), &diff.patch)?;
+        Ok(())
+    }
+
+    pub fn write_result(&self, result: &TaskResult, description: &str) -> Result<()> {
+        let outcome = TaskOutcome {
+            task_id: result.task_id.clone(),
+            description: description.to_string(),
+            worker: result.worker_kind.clone(),
+            success: result.result.is_ok(),
+            error: result.result.as_ref().err().cloned(),
+            finished_at: Local::now().to_rfc3339(),
+        };
+        std::fs::write(
+            self.task_dir(&result.task_id)?.join(RESULT_FILE),
+            serde_json::to_string_pretty(&outcome)?,
+        )?;
+        Ok(())
+    }
+}
+
+/// Run ids of a project, newest first
+pub fn list_runs(project_path: &Path) -> Vec<String> {
+    let mut runs: Vec<String> = std::fs::read_dir(runs_dir(project_path))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    runs.sort_by(|a, b| b.cmp(a));
+    runs
+}
+
+/// Directory of run `run`; `latest` picks the newest one
+pub fn find_run(project_path: &Path, run: &str) -> Result<PathBuf> {
+    let id = if run == "latest" {
+        list_runs(project_path)
+            .into_iter()
+            .next()
+            .context("No orchestration runs recorded yet")?
+    } else {
+        run.to_string()
+    };
+    let dir = runs_dir(project_path).join(&id);
+    if !dir.is_dir() {
+        anyhow::bail!("No orchestration run '{}' in {}", id, dir.display());
+    }
+    Ok(dir)
+}
+
+/// Tasks recorded in a run with their outcome, if they finished
+pub fn list_tasks(run_dir: &Path) -> Vec<(String, Option<TaskOutcome>)> {
+    let mut tasks: Vec<(String, Option<TaskOutcome>)> = std::fs::read_dir(run_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| {
+            let outcome = std::fs::read_to_string(entry.path().join(RESULT_FILE))
+                .ok()
+                .and_then(|json| serde_json::from_str(&json).ok());
+            (entry.file_name().to_string_lossy().to_string(), outcome)
+        })
+        .collect();
+    tasks.sort_by(|a, b| a.0.cmp(&b.0));
+    tasks
+}
+
+/// All artifacts of one task as a single report
+pub fn format_task(run_dir: &Path, task_id: &str) -> Result<String> {
+    let dir = run_dir.join(task_id);
+    if !dir.is_dir() {
+        anyhow::bail!("No task '{}' in run {}", task_id, run_dir.display());
+    }
+
+    let mut out = String::new();
+    let read = |name: &str| std::fs::read_to_string(dir.join(name)).ok();
+    match read(RESULT_FILE).and_then(|json| serde_json::from_str::<TaskOutcome>(&json).ok()) {
+        Some(outcome) => {
+            out.push_str(&format!(
+                "{} {} ({:?}, finished {})\n",
+                if outcome.success { "✅" } else { "❌" },
+                outcome.description,
+                outcome.worker,
+                outcome.finished_at
+            ));
+            if let Some(error) = outcome.error {
+                out.push_str(&format!("Error: {}\n", error.trim_end()));
+            }
+        }
+        None => out.push_str("⏳ Task did not finish\n"),
+    }
+
+    for (title, name) in [
+        ("Prompt", PROMPT_FILE),
+        ("stdout", STDOUT_FILE),
+        ("stderr", STDERR_FILE),
+        ("Diff", DIFF_FILE),
+    ] {
+        out.push_str(&format!("\n── {} ──\n", title));
+        match read(name) {
+            Some(text) if !text.trim().is_empty() => {
+                out.push_str(text.trim_end());
+                out.push('\n');
+            }
+            Some(_) => out.push_str("(empty)\n"),
+            None => out.push_str("(not recorded)\n"),
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_artifacts_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let run = RunArtifacts::create(dir.path()).unwrap();
+        let second = RunArtifacts::create(dir.path()).unwrap();
+        assert_ne!(run.id(), second.id());
+        assert!(runs_dir(dir.path()).join(".gitignore").exists());
+
+        let task = Task::new("task-1".into(), "Fix it".into(), "Fix the bug".into());
+        run.write_prompt(&task).unwrap();
+        std::fs::write(run.task_dir("task-1").unwrap().join(STDERR_FILE), "boom\n").unwrap();
+        run.write_result(
+            &TaskResult {
+                task_id: "task-1".into(),
+                worker_kind: WorkerKind::GeminiCli,
+                workspace_path: dir.path().to_path_buf(),
+                result: Err("exit 1".into()),
+            },
+            "Fix it",
+        )
+        .unwrap();
+
+        let tasks = list_tasks(run.dir());
+        assert_eq!(tasks.len(), 1);
+        assert!(!tasks[0].1.as_ref().unwrap().success);
+
+        let report = format_task(run.dir(), "task-1").unwrap();
+        assert!(report.starts_with("❌ Fix it (GeminiCli"));
+        assert!(report.contains("Error: exit 1"));
+        assert!(report.contains("── Prompt ──\nFix the bug\n"));
+        assert!(report.contains("── stderr ──\nboom\n"));
+        assert!(report.contains("── Diff ──\n(not recorded)"));
+        assert!(format_task(run.dir(), "task-9").is_err());
+
+        assert_eq!(list_runs(dir.path()).len(), 2);
+        assert_eq!(
+            find_run(dir.path(), "latest").unwrap(),
+            list_runs(dir.path())
+                .first()
+                .map(|id| runs_dir(dir.path()).join(id))
+                .unwrap()
+        );
+        assert!(find_run(dir.path(), "nope").is_err());
+    }
+}
@@ -0,0 +1,420 @@
+//! Cost and time estimates for `safe-coder orchestrate --dry-run`
+//!
+//! Every task gets a heuristic estimate from its size, the number of files it
+//! is likely to touch and a few keywords. When an LLM is available it is asked
+//! once for the whole plan, and its answers for time and affected files
+//! replace the heuristic ones; token counts and costs are always derived from
+//! the file count, priced at the worker's list prices.
+
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::llm::{ContentBlock, LlmClient, Message};
+use crate::orchestrator::{TaskPlan, WorkerKind};
+use crate::persistence::usage::estimate_cost;
+
+/// Most project files listed for matching and for the model
+const MAX_PROJECT_FILES: usize = 2000;
+
+/// Most likely-affected files kept per task
+const MAX_FILES_PER_TASK: usize = 10;
+
+/// File stems too common to say anything about a task
+const GENERIC_STEMS: &[&str] = &["mod", "lib", "main", "index", "init", "readme"];
+
+const ESTIMATE_PROMPT: &str = "You estimate work for coding agents. For each task, \
+estimate the minutes an autonomous coding agent needs and list the project files it will most likely change. \
+Reply with only a JSON array, one object per task: \
+[{\"id\": \"task-1\", \"minutes\": 8, \"files\": [\"src/lib.rs\"]}]. \
+Only list files from the project file list.";
+
+/// Where an estimate came from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EstimateSource {
+    Heuristic,
+    Model,
+}
+
+/// Predicted cost and time for one task
+#[derive(Debug, Clone)]
+pub struct TaskEstimate {
+    pub task_id: String,
+    pub description: String,
+    pub worker: WorkerKind,
+    pub minutes: f64,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub cost_usd: f64,
+    /// Files the task is likely to change
+    pub files: Vec<String>,
+    pub source: EstimateSource,
+}
+
+/// Result of a dry run: the plan and what running it would take
+#[derive(Debug, Clone)]
+pub struct DryRun {
+    pub plan: TaskPlan,
+    pub estimates: Vec<TaskEstimate>,
+    pub max_workers: usize,
+}
+
+impl DryRun {
+    pub fn total_cost(&self) -> f64 {
+        self.estimates.iter().map(|e| e.cost_usd).sum()
+    }
+
+    /// Sum of all task times
+    pub fn total_minutes(&self) -> f64 {
+        self.estimates.iter().map(|e| e.minutes).sum()
+    }
+
+    /// Time until the last task finishes with `max_workers` running at once,
+    /// starting tasks in plan order
+    pub fn wall_clock_minutes(&self) -> f64 {
+        let mut slots = vec![0.0_f64; self.max_workers.max(1)];
+        for estimate in &self.estimates {
+            let earliest = slots
+                .iter_mut()
+                .min_by(|a, b| a.total_cmp(b))
+                .expect("at least one slot");
+            *earliest += estimate.minutes;
+        }
+        slots.into_iter().fold(0.0, f64::max)
+    }
+
+    /// Report for the terminal
+    pub fn format(&self) -> String {
+        let mut output = String::new();
+
+        output.push_str("🧪 ORCHESTRATION DRY RUN\n");
+        output.push_str("══════════════════════════════════════════════════════════════\n\n");
+        output.push_str(&format!("📝 Request: {}\n\n", self.plan.original_request));
+        output.push_str(&format!("🔧 Tasks ({}):\n", self.estimates.len()));
+        output.push_str("───────────────────────────────────────────────────────────────\n");
+
+        for (i, estimate) in self.estimates.iter().enumerate() {
+            output.push_str(&format!(
+                "\n  {}. {} ({})\n",
+                i + 1,
+                estimate.description,
+                estimate.task_id
+            ));
+            output.push_str(&format!("     🤖 Worker: {:?}\n", estimate.worker));
+            output.push_str(&format!(
+                "     ⏱️  ~{:.0} min, ~{}k tokens in / {}k out, ~${:.2}{}\n",
+                estimate.minutes,
+                estimate.input_tokens / 1000,
+                estimate.output_tokens / 1000,
+                estimate.cost_usd,
+                match estimate.source {
+                    EstimateSource::Heuristic => " (heuristic)",
+                    EstimateSource::Model => "",
+                }
+            ));
+            if estimate.files.is_empty() {
+                output.push_str("     📁 Files: unknown\n");
+            } else {
+                output.push_str(&format!("     📁 Files: {}\n", estimate.files.join(", ")));
+            }
+        }
+
+        output.push_str("\n───────────────────────────────────────────────────────────────\n");
+        output.push_str(&format!(
+            "\n💰 Estimated cost: ~${:.2} (list prices)\n",
+            self.total_cost()
+        ));
+        output.push_str(&format!(
+            "⏱️  Estimated time: ~{:.0} min with {} worker{} (~{:.0} min of work)\n",
+            self.wall_clock_minutes(),
+            self.max_workers,
+            if self.max_workers == 1 { "" } else { "s" },
+            self.total_minutes()
+        ));
+        output.push_str("\nNo workspaces were created and no workers were started.\n");
+
+        output
+    }
+}
+
+/// Estimate every task in `plan`, asking `client` to refine the estimates
+/// when given. `safe_coder_model` prices tasks assigned to Safe Coder itself.
+pub async fn estimate_plan(
+    project_path: &Path,
+    plan: &TaskPlan,
+    default_worker: &WorkerKind,
+    max_workers: usize,
+    safe_coder_model: &str,
+    client: Option<&dyn LlmClient>,
+) -> DryRun {
+    let project_files = list_project_files(project_path);
+
+    let model_estimates = match client {
+        Some(client) => match ask_model(client, plan, &project_files).await {
+            Ok(estimates) => estimates,
+            Err(e) => {
+                tracing::warn!("Model estimate failed, using heuristics: {}", e);
+                HashMap::new()
+            }
+        },
+        None => HashMap::new(),
+    };
+
+    let estimates = plan
+        .tasks_in_order()
+        .into_iter()
+        .map(|task| {
+            let worker = task
+                .preferred_worker
+                .clone()
+                .unwrap_or_else(|| default_worker.clone());
+            let text = format!("{}\n{}", task.description, task.instructions);
+
+            let (minutes, files, source) = match model_estimates.get(&task.id) {
+                Some(model) => {
+                    let files: Vec<String> = model
+                        .files
+                        .iter()
+                        .filter(|f| project_files.contains(f))
+                        .take(MAX_FILES_PER_TASK)
+                        .cloned()
+                        .collect();
+                    (model.minutes, files, EstimateSource::Model)
+                }
+                None => {
+                    let mut files = task.relevant_files.clone();
+                    for file in likely_files(&text, &project_files) {
+                        if files.len() >= MAX_FILES_PER_TASK {
+                            break;
+                        }
+                        if !files.contains(&file) {
+                            files.push(file);
+                        }
+                    }
+                    let minutes = heuristic_minutes(&text, files.len());
+                    (minutes, files, EstimateSource::Heuristic)
+                }
+            };
+
+            let (input_tokens, output_tokens) = heuristic_tokens(files.len());
+            let cost_usd = estimate_cost(
+                worker_model(&worker, safe_coder_model),
+                input_tokens,
+                output_tokens,
+            );
+
+            TaskEstimate {
+                task_id: task.id.clone(),
+                description: task.description.clone(),
+                worker,
+                minutes,
+                input_tokens,
+                output_tokens,
+                cost_usd,
+                files,
+                source,
+            }
+        })
+        .collect();
+
+    DryRun {
+        plan: plan.clone(),
+        estimates,
+        max_workers,
+    }
+}
+
+/// Model used to price a worker's tokens; Copilot is a subscription and free
+fn worker_model<'a>(worker: &WorkerKind, safe_coder_model: &'a str) -> &'a str {
+    match worker {
+        WorkerKind::ClaudeCode => "claude-sonnet-4",
+        WorkerKind::GeminiCli => "gemini-2.5-pro",
+        WorkerKind::SafeCoder => safe_coder_model,
+        WorkerKind::GitHubCopilot => "",
+    }
+}
+
+/// Minutes for a task from its size, file count and kind of work
+fn heuristic_minutes(text: &str, file_count: usize) -> f64 {
+    let text_lower = text.to_lowercase();
+    let mut minutes = 3.0 + 2.0 * file_count.max(1) as f64 + text.chars().count() as f64 / 500.0;
+
+    if ["refactor", "migrate", "rewrite", "redesign"]
+        .iter()
+        .any(|k| text_lower.contains(k))
+    {
+        minutes *= 1.5;
+    } else if ["doc", "readme", "comment", "typo", "rename"]
+        .iter()
+        .any(|k| text_lower.contains(k))
+    {
+        minutes *= 0.6;
+    }
+    if text_lower.contains("test") {
+        minutes *= 1.2;
+    }
+    minutes
+}
+
+/// (input, output) tokens: agents re-read context on every turn, so input
+/// grows much faster with the number of files than output does
+fn heuristic_tokens(file_count: usize) -> (usize, usize) {
+    let files = file_count.max(1);
+    (15_000 + 12_000 * files, 2_000 + 3_000 * files)
+}
+
+/// Project files whose name or stem appears as a word in `text`
+fn likely_files(text: &str, project_files: &[String]) -> Vec<String> {
+    let words: Vec<String> = text
+        .to_lowercase()
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-' || c == '.'))
+        .map(|w| w.trim_matches('.').to_string())
+        .filter(|w| !w.is_empty())
+        .collect();
+
+    project_files
+        .iter()
+        .filter(|file| {
+            let path = Path::new(file.as_str());
+            let name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let stem_matches = stem.len() >= 4
+                && !GENERIC_STEMS.contains(&stem.as_str())
+                && words.contains(&stem);
+            stem_matches || words.contains(&name)
+        })
+        .take(MAX_FILES_PER_TASK)
+        .cloned()
+        .collect()
+}
+
+/// Tracked-looking files in the project, relative to its root
+fn list_project_files(project_path: &Path) -> Vec<String> {
+    ignore::WalkBuilder::new(project_path)
+        .build()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| {
+            entry
+                .path()
+                .strip_prefix(project_path)
+                .ok()
+                .map(|p| p.to_string_lossy().replace('\\', "/"))
+        })
+        .filter(|path| !path.starts_with(".safe-coder-workspaces/"))
+        .take(MAX_PROJECT_FILES)
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct ModelEstimate {
+    id: String,
+    minutes: f64,
+    #[serde(default)]
+    files: Vec<String>,
+}
+
+/// Ask the model for per-task minutes and files, keyed by task id
+async fn ask_model(
+    client: &dyn LlmClient,
+    plan: &TaskPlan,
+    project_files: &[String],
+) -> Result<HashMap<String, ModelEstimate>> {
+    let tasks: Vec<String> = plan
+        .tasks_in_order()
+        .iter()
+        .map(|t| format!("- {}: {}\n  {}", t.id, t.description, t.instructions))
+        .collect();
+    let message = format!(
+        "Request: {}\n\nTasks:\n{}\n\nProject files:\n{}",
+        plan.original_request,
+        tasks.join("\n"),
+        project_files.join("\n")
+    );
+
+    let response = client
+        .send_message_with_system(&[Message::user(message)], &[], Some(ESTIMATE_PROMPT))
+        .await?;
+    let reply: String = response
+        .message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    parse_model_estimates(&reply)
+}
+
+fn parse_model_estimates(reply: &str) -> Result<HashMap<String, ModelEstimate>> {
+    let start = reply
+        .find('[')
+        .ok_or_else(|| anyhow::anyhow!("No JSON array in reply"))?;
+    let end = reply
+        .rfind(']')
+        .ok_or_else(|| anyhow::anyhow!("No JSON array in reply"))?;
+    let estimates: Vec<ModelEstimate> = serde_json::from_str(&reply[start..=end])?;
+    Ok(estimates
+        .into_iter()
+        .filter(|e| e.minutes.is_finite() && e.minutes > 0.0)
+        .map(|e| (e.id.clone(), e))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orchestrator::Task;
+
+    #[tokio::test]
+    async fn test_heuristic_dry_run() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(temp.path().join("src")).unwrap();
+        std::fs::write(temp.path().join("src/planner.rs"), "").unwrap();
+        std::fs::write(temp.path().join("src/main.rs"), "").unwrap();
+
+        let mut plan = TaskPlan::new("p".into(), "req".into(), "summary".into());
+        plan.add_task(Task::new(
+            "task-1".into(),
+            "Refactor the planner".into(),
+            "Split planner into smaller functions".into(),
+        ));
+        plan.add_task(Task::new(
+            "task-2".into(),
+            "Fix a typo".into(),
+            "Fix the typo in the main help text".into(),
+        ));
+
+        let dry_run = estimate_plan(temp.path(), &plan, &WorkerKind::ClaudeCode, 2, "", None).await;
+        let [refactor, typo] = &dry_run.estimates[..] else {
+            panic!("expected two estimates");
+        };
+        assert_eq!(refactor.files, vec!["src/planner.rs"]);
+        assert!(typo.files.is_empty(), "generic stems are not matched");
+        assert!(refactor.minutes > typo.minutes);
+        assert!(refactor.cost_usd > 0.0);
+        assert_eq!(refactor.source, EstimateSource::Heuristic);
+        assert_eq!(dry_run.wall_clock_minutes(), refactor.minutes);
+    }
+
+    #[test]
+    fn test_parse_model_estimates() {
+        let estimates = parse_model_estimates(
+            "Here you go:\n```json\n[{\"id\": \"task-1\", \"minutes\": 12, \"files\": [\"src/a.rs\"]}, {\"id\": \"task-2\", \"minutes\": -1}]\n```",
+        )
+        .unwrap();
+        assert_eq!(estimates.len(), 1);
+        assert_eq!(estimates["task-1"].minutes, 12.0);
+        assert_eq!(estimates["task-1"].files, vec!["src/a.rs"]);
+        assert!(parse_model_estimates("no idea").is_err());
+    }
+}
@@ -4,25 +4,33 @@
 //! high-level planner that delegates tasks to specialized CLI agents (Claude Code,
 //! Gemini CLI) running in isolated git workspaces.
 
+pub mod artifacts;
+pub mod estimate;
 // TODO: Fix type mismatches in these modules
 // pub mod live_orchestration;
 pub mod planner;
+pub mod queue;
+pub mod routing;
 // pub mod self_orchestration;
 // pub mod streaming_worker;
 pub mod task;
 pub mod worker;
 pub mod workspace;
 
+pub use artifacts::RunArtifacts;
+pub use estimate::DryRun;
 pub use planner::Planner;
+pub use routing::RoutingRule;
 pub use task::{Task, TaskPlan, TaskStatus};
 pub use worker::{Worker, WorkerEvent, WorkerEventSender, WorkerKind, WorkerStatus};
-pub use workspace::WorkspaceManager;
+pub use workspace::{WorkspaceDiff, WorkspaceManager};
 
 use anyhow::Result;
+use std::collections::HashMap;
 use std::io::{self, Write};
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex, Notify};
 
 use crate::approval::UserMode;
 
@@ -38,6 +46,23 @@ pub struct Orchestrator {
     project_path: PathBuf,
     /// Configuration for the orchestrator
     pub config: OrchestratorConfig,
+    /// Where workers stream their events, if anyone is watching
+    event_tx: Option<WorkerEventSender>,
+    /// Cancel and restart requests for single tasks
+    control_rx: Option<mpsc::UnboundedReceiver<WorkerControl>>,
+    /// Cancel handles of started workers by task id
+    cancel_handles: HashMap<String, Arc<Notify>>,
+    /// Prompts, logs and diffs of the current run
+    artifacts: Option<RunArtifacts>,
+}
+
+/// A request to stop or rerun one task while the others keep going
+#[derive(Debug, Clone, PartialEq)]
+pub enum WorkerControl {
+    /// Stop the task's worker; the task is reported as cancelled
+    Cancel(String),
+    /// Stop the task's worker if it is running and start it again in a fresh workspace
+    Restart(String),
 }
 
 /// Strategy for distributing tasks across multiple workers
@@ -84,6 +109,10 @@ pub struct OrchestratorConfig {
     pub throttle_limits: ThrottleLimits,
     /// User mode: Plan (requires approval) or Build (auto-execute)
     pub user_mode: UserMode,
+    /// Hold successful tasks for review instead of merging them right away
+    pub review_merges: bool,
+    /// Rules routing tasks to workers by capability tag, applied before the strategy
+    pub routing_rules: Vec<RoutingRule>,
 }
 
 /// Throttle limits for different worker types
@@ -118,6 +147,57 @@ impl Default for OrchestratorConfig {
             use_worktrees: true,
             throttle_limits: ThrottleLimits::default(),
             user_mode: UserMode::default(),
+            review_merges: true,
+            routing_rules: Vec::new(),
+        }
+    }
+}
+
+impl OrchestratorConfig {
+    /// Settings from the `[orchestrator]` section of the user's config
+    pub fn from_config(user_config: &crate::config::Config) -> Self {
+        let settings = &user_config.orchestrator;
+        let worker_strategy = match settings.worker_strategy.to_lowercase().as_str() {
+            "single" | "single-worker" => WorkerStrategy::SingleWorker,
+            "round-robin" | "roundrobin" => WorkerStrategy::RoundRobin,
+            "task-based" | "taskbased" => WorkerStrategy::TaskBased,
+            "load-balanced" | "loadbalanced" => WorkerStrategy::LoadBalanced,
+            _ => WorkerStrategy::SingleWorker,
+        };
+        let routing_rules = settings
+            .routing
+            .iter()
+            .map(|rule| RoutingRule {
+                tags: rule.tags.iter().map(|t| t.to_lowercase()).collect(),
+                workers: rule.workers.iter().map(|w| WorkerKind::from_name(w)).collect(),
+            })
+            .collect();
+        let limits = &settings.throttle_limits;
+
+        Self {
+            claude_cli_path: Some(settings.claude_cli_path.clone()),
+            gemini_cli_path: Some(settings.gemini_cli_path.clone()),
+            safe_coder_cli_path: Some(settings.safe_coder_cli_path.clone()),
+            gh_cli_path: Some(settings.gh_cli_path.clone()),
+            max_workers: settings.max_workers,
+            default_worker: WorkerKind::from_name(&settings.default_worker),
+            worker_strategy,
+            enabled_workers: settings
+                .enabled_workers
+                .iter()
+                .map(|w| WorkerKind::from_name(w))
+                .collect(),
+            use_worktrees: settings.use_worktrees,
+            throttle_limits: ThrottleLimits {
+                claude_max_concurrent: limits.claude_max_concurrent,
+                gemini_max_concurrent: limits.gemini_max_concurrent,
+                safe_coder_max_concurrent: limits.safe_coder_max_concurrent,
+                copilot_max_concurrent: limits.copilot_max_concurrent,
+                start_delay_ms: limits.start_delay_ms,
+            },
+            user_mode: UserMode::default(),
+            review_merges: settings.review_merges,
+            routing_rules,
         }
     }
 }
@@ -146,24 +226,32 @@ impl Orchestrator {
             workers: Vec::new(),
             project_path,
             config,
+            event_tx: None,
+            control_rx: None,
+            cancel_handles: HashMap::new(),
+            artifacts: None,
         })
     }
 
-    /// Process a user request by planning and delegating to workers
-    pub async fn process_request(&mut self, request: &str) -> Result<OrchestratorResponse> {
-        // Step 1: Create a high-level plan
-        let mut plan = self.planner.create_plan(request).await?;
+    /// Stream worker events (start, output lines, completion) to `event_tx`
+    pub fn set_event_sender(&mut self, event_tx: WorkerEventSender) {
+        self.event_tx = Some(event_tx);
+    }
 
-        // Step 1.5: Assign workers to tasks based on configured strategy
-        self.assign_workers_to_tasks(&mut plan);
+    /// Sender for cancelling or restarting single tasks during `process_request`
+    pub fn controls(&mut self) -> mpsc::UnboundedSender<WorkerControl> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.control_rx = Some(rx);
+        tx
+    }
 
-        let mut response = OrchestratorResponse {
-            plan: plan.clone(),
-            task_results: Vec::new(),
-            summary: String::new(),
-        };
+    /// Process a user request by planning and delegating to workers. In
+    /// Plan mode the plan is shown in the terminal and needs approval first;
+    /// callers with their own UI use `plan_request` and `execute_plan`.
+    pub async fn process_request(&mut self, request: &str) -> Result<OrchestratorResponse> {
+        let plan = self.plan_request(request).await?;
 
-        // Step 1.5: Handle planning mode - show detailed plan and ask for approval
+        // Handle planning mode - show detailed plan and ask for approval
         match self.config.user_mode {
             UserMode::Plan => {
                 // Show detailed plan
@@ -172,9 +260,7 @@ impl Orchestrator {
 
                 // Ask for user approval
                 if !self.ask_plan_approval().await? {
-                    response.summary =
-                        "❌ Plan rejected by user. No tasks were executed.".to_string();
-                    return Ok(response);
+                    return Ok(OrchestratorResponse::rejected(plan));
                 }
                 println!("\n✅ Plan approved. Distributing tasks to workers...\n");
             }
@@ -185,17 +271,78 @@ impl Orchestrator {
             }
         }
 
+        self.execute_plan(plan).await
+    }
+
+    /// First phase of a request: break it into tasks and pick a worker for
+    /// each, without touching the repository. The plan can be shown,
+    /// approved or trimmed before it is passed to `execute_plan`.
+    pub async fn plan_request(&self, request: &str) -> Result<TaskPlan> {
+        let mut plan = self.planner.create_plan(request).await?;
+        self.assign_workers_to_tasks(&mut plan);
+        Ok(plan)
+    }
+
+    /// Second phase of a request: run the plan's tasks in their workspaces,
+    /// then merge the results or hold them for review
+    pub async fn execute_plan(&mut self, plan: TaskPlan) -> Result<OrchestratorResponse> {
+        self.artifacts = match RunArtifacts::create(&self.project_path) {
+            Ok(artifacts) => {
+                tracing::info!("Saving run artifacts to {}", artifacts.dir().display());
+                if let Err(e) = artifacts.write_plan(&plan) {
+                    tracing::warn!("Failed to save orchestration plan: {}", e);
+                }
+                Some(artifacts)
+            }
+            Err(e) => {
+                tracing::warn!("Failed to create orchestration artifacts: {}", e);
+                None
+            }
+        };
+
+        let mut response = OrchestratorResponse {
+            run_id: self.artifacts.as_ref().map(|a| a.id().to_string()),
+            plan: plan.clone(),
+            task_results: Vec::new(),
+            pending_review: Vec::new(),
+            merged_tasks: Vec::new(),
+            summary: String::new(),
+        };
+
         // Step 2: Execute tasks in parallel with throttling
         // Pass the plan to enhance task instructions with context
         let task_results = self.execute_tasks_parallel(&plan).await?;
         response.task_results = task_results;
+        self.save_task_artifacts(&response).await;
 
-        // Step 3: Merge results back
-        for task_result in &response.task_results {
-            if task_result.result.is_ok() {
-                self.workspace_manager
-                    .merge_workspace(&task_result.task_id)
-                    .await?;
+        // Step 3: Merge results back, or hold them for review
+        let successful: Vec<String> = response
+            .task_results
+            .iter()
+            .filter(|r| r.result.is_ok())
+            .map(|r| r.task_id.clone())
+            .collect();
+        if self.config.review_merges {
+            for task_id in &successful {
+                let diff = self.workspace_manager.diff(task_id).await?;
+                // Nothing to merge when a task changed no files
+                if diff.is_empty() {
+                    continue;
+                }
+                let task = plan.tasks.iter().find(|t| &t.id == task_id);
+                response.pending_review.push(TaskReview {
+                    task_id: task_id.clone(),
+                    description: task.map(|t| t.description.clone()).unwrap_or_default(),
+                    worker_kind: task
+                        .and_then(|t| t.preferred_worker.clone())
+                        .unwrap_or_else(|| self.config.default_worker.clone()),
+                    diff,
+                });
+            }
+        } else {
+            for task_id in &successful {
+                self.workspace_manager.merge_workspace(task_id).await?;
+                response.merged_tasks.push(task_id.clone());
             }
         }
 
@@ -205,8 +352,145 @@ impl Orchestrator {
         Ok(response)
     }
 
+    /// Record how each task ended and what it changed
+    async fn save_task_artifacts(&self, response: &OrchestratorResponse) {
+        let Some(artifacts) = &self.artifacts else {
+            return;
+        };
+        for result in &response.task_results {
+            let description = response
+                .plan
+                .tasks
+                .iter()
+                .find(|t| t.id == result.task_id)
+                .map(|t| t.description.as_str())
+                .unwrap_or_default();
+            if let Err(e) = artifacts.write_result(result, description) {
+                tracing::warn!("Failed to save result of {}: {}", result.task_id, e);
+            }
+            match self.workspace_manager.diff(&result.task_id).await {
+                Ok(diff) => {
+                    if let Err(e) = artifacts.write_diff(&result.task_id, &diff) {
+                        tracing::warn!("Failed to save diff of {}: {}", result.task_id, e);
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to diff {}: {}", result.task_id, e),
+            }
+        }
+    }
+
+    /// Plan a request and estimate what running it would take, without
+    /// creating workspaces or starting workers. `client` refines the
+    /// heuristic estimates when given.
+    pub async fn dry_run(
+        &self,
+        request: &str,
+        safe_coder_model: &str,
+        client: Option<&dyn crate::llm::LlmClient>,
+    ) -> Result<DryRun> {
+        let plan = self.plan_request(request).await?;
+
+        Ok(estimate::estimate_plan(
+            &self.project_path,
+            &plan,
+            &self.config.default_worker,
+            self.config.max_workers,
+            safe_coder_model,
+            client,
+        )
+        .await)
+    }
+
+    /// Merge the reviewed tasks in `accepted` and drop the rest of
+    /// `response.pending_review`
+    pub async fn merge_tasks(
+        &mut self,
+        response: &mut OrchestratorResponse,
+        accepted: &[String],
+    ) -> Result<()> {
+        for review in std::mem::take(&mut response.pending_review) {
+            if accepted.contains(&review.task_id) {
+                self.workspace_manager
+                    .merge_workspace(&review.task_id)
+                    .await?;
+                response.merged_tasks.push(review.task_id);
+            }
+        }
+        response.summary = self.generate_summary(response);
+        Ok(())
+    }
+
+    /// Keep this orchestrator's branches and worktrees apart from other
+    /// orchestrators running on the same repository
+    pub fn set_workspace_namespace(&mut self, namespace: &str) {
+        self.workspace_manager.set_namespace(namespace);
+    }
+
+    /// Leave the changes in `response.pending_review` on their branches
+    /// instead of merging them, so they can be reviewed after an unattended
+    /// run. Returns the branch names.
+    pub async fn keep_for_review(
+        &mut self,
+        response: &mut OrchestratorResponse,
+    ) -> Result<Vec<String>> {
+        let mut branches = Vec::new();
+        for review in std::mem::take(&mut response.pending_review) {
+            branches.push(self.workspace_manager.keep_branch(&review.task_id).await?);
+        }
+        response.summary = self.generate_summary(response);
+        Ok(branches)
+    }
+
+    /// Walk through `response.pending_review` in the terminal, asking for each
+    /// task whether to merge it, then merge the accepted ones
+    pub async fn review_in_terminal(&mut self, response: &mut OrchestratorResponse) -> Result<()> {
+        if response.pending_review.is_empty() {
+            return Ok(());
+        }
+
+        println!("\n🔍 REVIEW CHANGES BEFORE MERGING");
+        println!("══════════════════════════════════════════════════════════════");
+
+        let mut accepted = Vec::new();
+        for (i, review) in response.pending_review.iter().enumerate() {
+            println!(
+                "\n  {}. {} ({:?}, task {})",
+                i + 1,
+                review.description,
+                review.worker_kind,
+                review.task_id
+            );
+            println!("     📊 {}", review.diff.stat_line());
+            for file in &review.diff.files {
+                println!(
+                    "        +{:<4} -{:<4} {}",
+                    file.additions, file.deletions, file.path
+                );
+            }
+
+            loop {
+                print!("\n   Merge this task? [y]es / [n]o / [d]iff: ");
+                io::stdout().flush()?;
+
+                let mut input = String::new();
+                io::stdin().read_line(&mut input)?;
+
+                match input.trim().to_lowercase().as_str() {
+                    "y" | "yes" => {
+                        accepted.push(review.task_id.clone());
+                        break;
+                    }
+                    "d" | "diff" => println!("\n{}", review.diff.patch),
+                    _ => break,
+                }
+            }
+        }
+
+        self.merge_tasks(response, &accepted).await
+    }
+
     /// Format a detailed orchestration plan for display
-    fn format_orchestration_plan(&self, plan: &TaskPlan) -> String {
+    pub fn format_orchestration_plan(&self, plan: &TaskPlan) -> String {
         let mut output = String::new();
 
         output.push_str("🎯 ORCHESTRATION PLAN\n");
@@ -246,6 +530,12 @@ impl Orchestrator {
                 output.push('\n');
             }
 
+            if !task.tags.is_empty() {
+                output.push_str("     🏷️  Tags: ");
+                output.push_str(&task.tags.join(", "));
+                output.push('\n');
+            }
+
             // Show truncated instructions
             let instructions_preview = if task.instructions.len() > 100 {
                 format!("{}...", &task.instructions[..100])
@@ -297,7 +587,7 @@ impl Orchestrator {
 
     /// Execute tasks in parallel with throttling (max concurrent workers)
     async fn execute_tasks_parallel(&mut self, plan: &TaskPlan) -> Result<Vec<TaskResult>> {
-        use std::collections::{HashMap, VecDeque};
+        use std::collections::{HashSet, VecDeque};
         use tokio::task::JoinSet;
 
         let mut results = Vec::new();
@@ -326,10 +616,63 @@ impl Orchestrator {
             }
         }
 
+        // Running tasks to start again once their cancelled worker returns
+        let mut restarting: HashSet<String> = HashSet::new();
+        let mut control_rx = self.control_rx.take();
+
         // As workers complete, start new ones until all tasks are done
-        while let Some(result) = join_set.join_next().await {
+        loop {
+            let result = tokio::select! {
+                result = join_set.join_next() => match result {
+                    Some(result) => result,
+                    None => break,
+                },
+                Some(control) = next_control(&mut control_rx) => {
+                    match control {
+                        WorkerControl::Cancel(task_id) => {
+                            if let Some(cancel) = self.cancel_handles.get(&task_id) {
+                                cancel.notify_one();
+                            }
+                        }
+                        WorkerControl::Restart(task_id) => {
+                            if let Some(index) = results
+                                .iter()
+                                .position(|r: &TaskResult| r.task_id == task_id)
+                            {
+                                // Already finished: drop the old result and queue it again
+                                results.remove(index);
+                                if let Some(task) = plan.tasks.iter().find(|t| t.id == task_id) {
+                                    task_queue.push_front(task.clone());
+                                }
+                            } else if let Some(cancel) = self.cancel_handles.get(&task_id) {
+                                restarting.insert(task_id);
+                                cancel.notify_one();
+                            }
+                        }
+                    }
+                    if !task_queue.is_empty() && join_set.len() < self.config.max_workers {
+                        self.try_start_next_task(
+                            &mut task_queue,
+                            &mut active_by_type,
+                            &mut last_start_time,
+                            &mut join_set,
+                            plan,
+                        )
+                        .await?;
+                    }
+                    continue;
+                }
+            };
             let (task_result, completed_worker_kind) = result?;
-            results.push(task_result);
+            self.cancel_handles.remove(&task_result.task_id);
+
+            if restarting.remove(&task_result.task_id) {
+                if let Some(task) = plan.tasks.iter().find(|t| t.id == task_result.task_id) {
+                    task_queue.push_front(task.clone());
+                }
+            } else {
+                results.push(task_result);
+            }
 
             // Decrement active count for this worker type
             if let Some(count) = active_by_type.get_mut(&completed_worker_kind) {
@@ -350,6 +693,7 @@ impl Orchestrator {
             }
         }
 
+        self.control_rx = control_rx;
         Ok(results)
     }
 
@@ -410,12 +754,23 @@ impl Orchestrator {
             let workspace = self.workspace_manager.create_workspace(&task_id).await?;
             let cli_path = self.get_cli_path(&worker_kind);
 
-            let worker = Worker::new(
+            let mut worker = Worker::new(
                 task.clone(),
                 workspace.clone(),
                 worker_kind.clone(),
                 cli_path,
             )?;
+            if let Some(event_tx) = &self.event_tx {
+                worker.set_event_sender(event_tx.clone());
+            }
+            if let Some(artifacts) = &self.artifacts {
+                match artifacts.write_prompt(&task) {
+                    Ok(()) => worker.set_log_dir(artifacts.task_dir(&task_id)?),
+                    Err(e) => tracing::warn!("Failed to save prompt of {}: {}", task_id, e),
+                }
+            }
+            self.cancel_handles
+                .insert(task_id.clone(), worker.cancel_handle());
 
             let worker = Arc::new(Mutex::new(worker));
             self.workers.push(worker.clone());
@@ -479,6 +834,20 @@ impl Orchestrator {
     /// Assign workers to tasks based on the configured strategy
     /// This modifies the plan's tasks to set their preferred_worker field
     fn assign_workers_to_tasks(&self, plan: &mut TaskPlan) {
+        // Routing rules come first and override the planner's guesses
+        if !self.config.routing_rules.is_empty() {
+            let mut router =
+                routing::Router::new(&self.config.routing_rules, &self.config.enabled_workers);
+            for task in &mut plan.tasks {
+                if task.tags.is_empty() {
+                    task.tags = routing::infer_tags(task);
+                }
+                if let Some(worker) = router.route(&task.tags) {
+                    task.preferred_worker = Some(worker);
+                }
+            }
+        }
+
         if self.config.enabled_workers.is_empty() {
             // No workers enabled, use default for all
             return;
@@ -580,16 +949,33 @@ impl Orchestrator {
             total, successful, failed
         );
 
-        for (i, task) in response.plan.tasks.iter().enumerate() {
-            let result = &response.task_results[i];
+        for task in &response.plan.tasks {
+            let Some(result) = response.task_results.iter().find(|r| r.task_id == task.id) else {
+                continue;
+            };
             let status = if result.result.is_ok() { "✓" } else { "✗" };
+            let merge_status = if response.merged_tasks.contains(&task.id) {
+                "merged"
+            } else if response.pending_review.iter().any(|r| r.task_id == task.id) {
+                "awaiting review"
+            } else {
+                "not merged"
+            };
             summary.push_str(&format!(
-                "{} Task {}: {}\n  Worker: {:?}\n  Workspace: {}\n\n",
+                "{} Task {}: {}\n  Worker: {:?}\n  Workspace: {} ({})\n\n",
                 status,
                 task.id,
                 task.description,
                 result.worker_kind,
-                result.workspace_path.display()
+                result.workspace_path.display(),
+                merge_status
+            ));
+        }
+
+        if let Some(run_id) = &response.run_id {
+            summary.push_str(&format!(
+                "Logs: safe-coder orchestrate logs {} <task-id>\n",
+                run_id
             ));
         }
 
@@ -621,17 +1007,60 @@ impl Orchestrator {
     }
 }
 
+/// Next control request, or never when there is no control channel
+async fn next_control(
+    control_rx: &mut Option<mpsc::UnboundedReceiver<WorkerControl>>,
+) -> Option<WorkerControl> {
+    match control_rx {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
 /// Response from the orchestrator
 #[derive(Debug, Clone)]
 pub struct OrchestratorResponse {
+    /// Id of the run's artifact directory, if it could be created
+    pub run_id: Option<String>,
     /// The execution plan
     pub plan: TaskPlan,
     /// Results from each task
     pub task_results: Vec<TaskResult>,
+    /// Successful tasks whose changes are waiting for review
+    pub pending_review: Vec<TaskReview>,
+    /// Tasks whose changes were merged
+    pub merged_tasks: Vec<String>,
     /// Summary of the orchestration
     pub summary: String,
 }
 
+impl OrchestratorResponse {
+    /// Response for a plan that was turned down before any task ran
+    pub fn rejected(plan: TaskPlan) -> Self {
+        Self {
+            run_id: None,
+            plan,
+            task_results: Vec::new(),
+            pending_review: Vec::new(),
+            merged_tasks: Vec::new(),
+            summary: "❌ Plan rejected by user. No tasks were executed.".to_string(),
+        }
+    }
+}
+
+/// A successful task's changes, waiting to be accepted or rejected
+#[derive(Debug, Clone)]
+pub struct TaskReview {
+    /// Task identifier
+    pub task_id: String,
+    /// What the task was meant to do
+    pub description: String,
+    /// Which worker made the changes
+    pub worker_kind: WorkerKind,
+    /// The changes themselves
+    pub diff: WorkspaceDiff,
+}
+
 /// Result of a single task execution
 #[derive(Debug, Clone)]
 pub struct TaskResult {
@@ -660,6 +1089,28 @@ mod tests {
         assert_eq!(config.throttle_limits.start_delay_ms, 100);
     }
 
+    #[test]
+    fn test_config_from_user_config() {
+        let mut user_config = crate::config::Config::default();
+        user_config.orchestrator.default_worker = "gemini".to_string();
+        user_config.orchestrator.worker_strategy = "round-robin".to_string();
+        user_config.orchestrator.enabled_workers = vec!["claude".into(), "copilot".into()];
+        user_config.orchestrator.routing = vec![crate::config::RoutingRuleConfig {
+            tags: vec!["Docs".into()],
+            workers: vec!["safe-coder".into()],
+        }];
+
+        let config = OrchestratorConfig::from_config(&user_config);
+        assert_eq!(config.default_worker, WorkerKind::GeminiCli);
+        assert_eq!(config.worker_strategy, WorkerStrategy::RoundRobin);
+        assert_eq!(
+            config.enabled_workers,
+            vec![WorkerKind::ClaudeCode, WorkerKind::GitHubCopilot]
+        );
+        assert_eq!(config.routing_rules[0].tags, vec!["docs"]);
+        assert_eq!(config.routing_rules[0].workers, vec![WorkerKind::SafeCoder]);
+    }
+
     #[tokio::test]
     async fn test_throttle_limits_configuration() {
         let temp_dir = TempDir::new().unwrap();
@@ -682,6 +1133,8 @@ mod tests {
                 start_delay_ms: 50,
             },
             user_mode: UserMode::default(),
+            review_merges: true,
+            routing_rules: Vec::new(),
         };
 
         let orchestrator = Orchestrator::new(temp_dir.path().to_path_buf(), config)
@@ -715,6 +1168,8 @@ mod tests {
                 start_delay_ms: 0,
             },
             user_mode: UserMode::default(),
+            review_merges: true,
+            routing_rules: Vec::new(),
         };
 
         let mut orchestrator = Orchestrator::new(temp_dir.path().to_path_buf(), config)
@@ -741,4 +1196,64 @@ mod tests {
         assert_eq!(plan.tasks.len(), 5);
         assert_eq!(orchestrator.config.max_workers, 2);
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_plan_then_execute() {
+        let temp_dir = TempDir::new().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .current_dir(temp_dir.path())
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::write(temp_dir.path().join("README.md"), "# Test\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "init"]);
+
+        let config = OrchestratorConfig {
+            claude_cli_path: Some("echo".to_string()), // Use echo as mock CLI
+            throttle_limits: ThrottleLimits {
+                start_delay_ms: 0,
+                ..ThrottleLimits::default()
+            },
+            ..OrchestratorConfig::default()
+        };
+        let mut orchestrator = Orchestrator::new(temp_dir.path().to_path_buf(), config)
+            .await
+            .unwrap();
+
+        // Planning alone touches nothing
+        let mut plan = orchestrator
+            .plan_request("Fix the typo in the README")
+            .await
+            .unwrap();
+        assert!(plan.tasks.iter().all(|t| t.preferred_worker.is_some()));
+        assert!(!artifacts::runs_dir(temp_dir.path()).exists());
+
+        // Callers may change the plan before running it
+        plan.tasks.truncate(1);
+        plan.tasks[0].preferred_worker = Some(WorkerKind::ClaudeCode);
+        let task_id = plan.tasks[0].id.clone();
+        let response = orchestrator.execute_plan(plan).await.unwrap();
+        orchestrator.cleanup().await.unwrap();
+
+        assert_eq!(response.task_results.len(), 1);
+        assert!(response.task_results[0].result.is_ok());
+        // echo changes no files, so there is nothing to review
+        assert!(response.pending_review.is_empty());
+
+        let run_dir = artifacts::find_run(temp_dir.path(), "latest").unwrap();
+        assert_eq!(
+            Some(run_dir.file_name().unwrap().to_str().unwrap()),
+            response.run_id.as_deref()
+        );
+        let stdout = std::fs::read_to_string(run_dir.join(&task_id).join("stdout.log")).unwrap();
+        assert!(stdout.contains("Fix the typo"));
+        assert!(run_dir.join(&task_id).join("result.json").exists());
+    }
 }
@@ -3,6 +3,7 @@
 use anyhow::Result;
 use uuid::Uuid;
 
+use crate::orchestrator::routing::infer_tags;
 use crate::orchestrator::{Task, TaskPlan, TaskStatus, WorkerKind};
 use crate::utils::truncate_str;
 
@@ -137,6 +138,7 @@ impl Planner {
         
         task.relevant_files = relevant_files;
         task.preferred_worker = preferred_worker;
+        task.tags = infer_tags(&task);
         task.status = TaskStatus::Pending;
         
         Ok(task)
@@ -0,0 +1,425 @@
+//! Queue of orchestration requests worked through unattended
+//!
+//! `safe-coder queue add "..."` appends requests to `.safe-coder/queue.json`;
+//! `safe-coder queue run` takes them one at a time (or several at once with
+//! `--parallel`) and runs each through its own orchestrator. Parallel runs
+//! get their own workspace namespace (`safe-coder/q-<id>/...`) so their
+//! task branches don't collide, and merges are done one run at a time.
+//!
+//! Nobody is around to review changes during a queue run, so when
+//! `review_merges` is on the changes are left on their task branches and
+//! listed by `queue list` instead of being merged.
+//!
+//! The file is re-read before every change, so tasks can be added while a
+//! run is going. Only one `queue run` should work on a project at a time.
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::task::JoinSet;
+
+use crate::approval::UserMode;
+use crate::orchestrator::{Orchestrator, OrchestratorConfig, WorkerKind};
+
+/// How often `queue run --follow` looks for new tasks
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QueueStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+impl QueueStatus {
+    pub fn icon(self) -> &'static str {
+        match self {
+            Self::Queued => "⏳",
+            Self::Running => "🔄",
+            Self::Done => "✅",
+            Self::Failed => "❌",
+        }
+    }
+}
+
+/// One queued orchestration request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueueItem {
+    pub id: u32,
+    pub task: String,
+    /// Worker to prefer over the run's default
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub worker: Option<WorkerKind>,
+    pub status: QueueStatus,
+    pub added_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    /// Artifact run of the orchestration, for `orchestrate logs`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    /// One-line outcome, or the error that stopped the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    /// Branches holding changes left for review
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub branches: Vec<String>,
+}
+
+/// Contents of `queue.json`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct QueueFile {
+    /// Ids are never reused, so branches kept for review stay unique
+    next_id: u32,
+    items: Vec<QueueItem>,
+}
+
+/// How a queued request ended
+#[derive(Debug, Clone)]
+pub struct ItemOutcome {
+    pub success: bool,
+    pub result: String,
+    pub run_id: Option<String>,
+    pub branches: Vec<String>,
+}
+
+/// The queue file of one project
+pub struct QueueStore {
+    path: PathBuf,
+}
+
+impl QueueStore {
+    pub fn new(project_path: &Path) -> Self {
+        Self {
+            path: project_path.join(".safe-coder").join("queue.json"),
+        }
+    }
+
+    /// Saved items, oldest first; a missing file is an empty queue
+    pub fn load(&self) -> Result<Vec<QueueItem>> {
+        Ok(self.read()?.items)
+    }
+
+    fn read(&self) -> Result<QueueFile> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", self.path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(QueueFile::default()),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        }
+    }
+
+    fn write(&self, queue: &QueueFile) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).context("Failed to create .safe-coder directory")?;
+        }
+        // Write then rename so a concurrent reader never sees half a file
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(queue)?)
+            .with_context(|| format!("Failed to write {}", tmp.display()))?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    /// Load, change and save the queue
+    fn update<T>(&self, change: impl FnOnce(&mut QueueFile) -> Result<T>) -> Result<T> {
+        let mut queue = self.read()?;
+        let result = change(&mut queue)?;
+        self.write(&queue)?;
+        Ok(result)
+    }
+
+    /// Append a request to the queue
+    pub fn add(&self, task: &str, worker: Option<WorkerKind>) -> Result<QueueItem> {
+        self.update(|queue| {
+            queue.next_id = queue.next_id.max(1);
+            let item = QueueItem {
+                id: queue.next_id,
+                task: task.to_string(),
+                worker,
+                status: QueueStatus::Queued,
+                added_at: Local::now().to_rfc3339(),
+                started_at: None,
+                finished_at: None,
+                run_id: None,
+                result: None,
+                branches: Vec::new(),
+            };
+            queue.next_id += 1;
+            queue.items.push(item.clone());
+            Ok(item)
+        })
+    }
+
+    /// Remove an item that is not running
+    pub fn remove(&self, id: u32) -> Result<QueueItem> {
+        self.update(|QueueFile { items, .. }| {
+            let index = items
+                .iter()
+                .position(|i| i.id == id)
+                .with_context(|| format!("No queued task {}", id))?;
+            if items[index].status == QueueStatus::Running {
+                anyhow::bail!("Task {} is running", id);
+            }
+            Ok(items.remove(index))
+        })
+    }
+
+    /// Remove finished items, returning how many there were
+    pub fn clear_finished(&self) -> Result<usize> {
+        self.update(|QueueFile { items, .. }| {
+            let before = items.len();
+            items.retain(|i| matches!(i.status, QueueStatus::Queued | QueueStatus::Running));
+            Ok(before - items.len())
+        })
+    }
+
+    /// Mark the oldest queued item as running and return it
+    pub fn claim_next(&self) -> Result<Option<QueueItem>> {
+        self.update(|QueueFile { items, .. }| {
+            let Some(item) = items.iter_mut().find(|i| i.status == QueueStatus::Queued) else {
+                return Ok(None);
+            };
+            item.status = QueueStatus::Running;
+            item.started_at = Some(Local::now().to_rfc3339());
+            Ok(Some(item.clone()))
+        })
+    }
+
+    /// Record how a running item ended
+    pub fn finish(&self, id: u32, outcome: &ItemOutcome) -> Result<()> {
+        self.update(|QueueFile { items, .. }| {
+            if let Some(item) = items.iter_mut().find(|i| i.id == id) {
+                item.status = if outcome.success {
+                    QueueStatus::Done
+                } else {
+                    QueueStatus::Failed
+                };
+                item.finished_at = Some(Local::now().to_rfc3339());
+                item.run_id = outcome.run_id.clone();
+                item.result = Some(outcome.result.clone());
+                item.branches = outcome.branches.clone();
+            }
+            Ok(())
+        })
+    }
+
+    /// Put items left running by an interrupted `queue run` back in the queue
+    pub fn requeue_interrupted(&self) -> Result<usize> {
+        self.update(|QueueFile { items, .. }| {
+            let mut count = 0;
+            for item in items
+                .iter_mut()
+                .filter(|i| i.status == QueueStatus::Running)
+            {
+                item.status = QueueStatus::Queued;
+                item.started_at = None;
+                count += 1;
+            }
+            Ok(count)
+        })
+    }
+}
+
+/// Work through the queue of `project_path`, running up to `parallel`
+/// requests at once. With `follow`, keep waiting for new requests instead of
+/// returning once the queue is empty. Ctrl+C or SIGTERM stops the run and
+/// puts unfinished requests back in the queue.
+pub async fn run_queue(
+    project_path: &Path,
+    config: OrchestratorConfig,
+    parallel: usize,
+    follow: bool,
+) -> Result<()> {
+    if parallel > 1 && !config.use_worktrees {
+        anyhow::bail!("Running queued tasks in parallel needs worktrees");
+    }
+
+    let store = QueueStore::new(project_path);
+    let requeued = store.requeue_interrupted()?;
+    if requeued > 0 {
+        println!("↺ Re-queued {} task(s) from an interrupted run", requeued);
+    }
+    // Runs after the requests in flight were dropped, killing their workers
+    let interrupted = QueueStore::new(project_path);
+    let _requeue = crate::shutdown::register("requeue interrupted tasks", move || async move {
+        match interrupted.requeue_interrupted() {
+            Ok(count) => println!(
+                "\nStopped; {} unfinished task(s) are back in the queue.",
+                count
+            ),
+            Err(e) => eprintln!("\nStopped, but re-queueing failed: {}", e),
+        }
+    });
+
+    let merge_lock = Arc::new(Mutex::new(()));
+    let mut running = JoinSet::new();
+    loop {
+        while running.len() < parallel.max(1) {
+            let Some(item) = store.claim_next()? else {
+                break;
+            };
+            println!("▶ [{}] {}", item.id, item.task);
+            running.spawn(run_item(
+                project_path.to_path_buf(),
+                config.clone(),
+                item,
+                merge_lock.clone(),
+            ));
+        }
+
+        if running.is_empty() && !follow {
+            println!("Queue is empty.");
+            return Ok(());
+        }
+
+        tokio::select! {
+            Some(joined) = running.join_next() => {
+                let (id, outcome) = joined?;
+                store.finish(id, &outcome)?;
+                println!(
+                    "{} [{}] {}",
+                    if outcome.success { "✓" } else { "✗" },
+                    id,
+                    outcome.result
+                );
+                for branch in &outcome.branches {
+                    println!("    review: git diff HEAD...{}", branch);
+                }
+            }
+            _ = tokio::time::sleep(POLL_INTERVAL), if follow => {}
+        }
+    }
+}
+
+/// Run one queued request through a fresh orchestrator
+async fn run_item(
+    project_path: PathBuf,
+    mut config: OrchestratorConfig,
+    item: QueueItem,
+    merge_lock: Arc<Mutex<()>>,
+) -> (u32, ItemOutcome) {
+    if let Some(worker) = &item.worker {
+        config.default_worker = worker.clone();
+    }
+    config.user_mode = UserMode::Build;
+    // Merges are done below, one run at a time
+    let merge = !config.review_merges;
+    config.review_merges = true;
+
+    let mut run_id = None;
+    let result: Result<ItemOutcome> = async {
+        let mut orchestrator = Orchestrator::new(project_path, config).await?;
+        orchestrator.set_workspace_namespace(&format!("q-{}", item.id));
+
+        let outcome = async {
+            let mut response = orchestrator.process_request(&item.task).await?;
+            run_id = response.run_id.clone();
+
+            let _guard = merge_lock.lock().await;
+            let mut branches = Vec::new();
+            if merge {
+                let accepted: Vec<String> = response
+                    .pending_review
+                    .iter()
+                    .map(|r| r.task_id.clone())
+                    .collect();
+                orchestrator.merge_tasks(&mut response, &accepted).await?;
+            } else {
+                branches = orchestrator.keep_for_review(&mut response).await?;
+            }
+
+            let total = response.task_results.len();
+            let failed = response
+                .task_results
+                .iter()
+                .filter(|r| r.result.is_err())
+                .count();
+            let mut result = format!("{}/{} tasks succeeded", total - failed, total);
+            if !response.merged_tasks.is_empty() {
+                result.push_str(&format!(", {} merged", response.merged_tasks.len()));
+            }
+            if !branches.is_empty() {
+                result.push_str(&format!(", {} left for review", branches.len()));
+            }
+            Ok::<_, anyhow::Error>(ItemOutcome {
+                success: failed == 0,
+                result,
+                run_id: run_id.clone(),
+                branches,
+            })
+        }
+        .await;
+
+        orchestrator.cleanup().await?;
+        outcome
+    }
+    .await;
+
+    let outcome = result.unwrap_or_else(|e| ItemOutcome {
+        success: false,
+        result: format!("Error: {}", e),
+        run_id,
+        branches: Vec::new(),
+    });
+    (item.id, outcome)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queue_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = QueueStore::new(dir.path());
+        assert!(store.load().unwrap().is_empty());
+
+        store.add("Rename the config module", None).unwrap();
+        let second = store
+            .add("Add docs for the API", Some(WorkerKind::GeminiCli))
+            .unwrap();
+        assert_eq!(second.id, 2);
+
+        let claimed = store.claim_next().unwrap().unwrap();
+        assert_eq!(claimed.id, 1);
+        assert_eq!(claimed.status, QueueStatus::Running);
+        assert!(store.remove(1).is_err());
+
+        store
+            .finish(
+                1,
+                &ItemOutcome {
+                    success: true,
+                    result: "1/1 tasks succeeded, 1 left for review".into(),
+                    run_id: Some("20260101-020304".into()),
+                    branches: vec!["safe-coder/q-1/task-1".into()],
+                },
+            )
+            .unwrap();
+
+        // An interrupted run leaves items running; the next run picks them up again
+        assert_eq!(store.claim_next().unwrap().unwrap().id, 2);
+        assert_eq!(store.requeue_interrupted().unwrap(), 1);
+        assert!(store.claim_next().unwrap().is_some());
+        assert!(store.claim_next().unwrap().is_none());
+
+        let items = store.load().unwrap();
+        assert_eq!(items[0].status, QueueStatus::Done);
+        assert_eq!(items[0].branches, vec!["safe-coder/q-1/task-1"]);
+        assert_eq!(items[1].worker, Some(WorkerKind::GeminiCli));
+
+        assert_eq!(store.clear_finished().unwrap(), 1);
+        assert_eq!(store.remove(1).unwrap_err().to_string(), "No queued task 1");
+        // Ids of cleared items are not handed out again
+        assert_eq!(store.add("Bump deps", None).unwrap().id, 3);
+        assert_eq!(store.load().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,214 @@
+//! Capability-based routing of tasks to workers
+//!
+//! Tasks carry capability tags such as `frontend` or `rust`, inferred from
+//! their text and files. Routing rules map tags to the workers allowed to
+//! handle them:
+//!
+//! ```toml
+//! [[orchestrator.routing]]
+//! tags = ["frontend"]
+//! workers = ["gemini"]
+//!
+//! [[orchestrator.routing]]
+//! tags = ["rust"]
+//! workers = ["claude", "safe-coder"]
+//! ```
+//!
+//! The first rule sharing a tag with the task decides. When it allows more
+//! than one worker, the one with the fewest tasks so far gets it. Tasks no
+//! rule matches fall back to the orchestrator's worker strategy.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::orchestrator::{Task, WorkerKind};
+
+/// Tags recognised in task text, with the keywords and file extensions that imply them
+const TAGS: &[(&str, &[&str], &[&str])] = &[
+    (
+        "frontend",
+        &[
+            "frontend",
+            "ui",
+            "css",
+            "html",
+            "react",
+            "vue",
+            "svelte",
+            "component",
+            "tailwind",
+            "layout",
+            "styling",
+        ],
+        &["tsx", "jsx", "css", "scss", "html", "vue", "svelte"],
+    ),
+    (
+        "backend",
+        &["backend", "api", "endpoint", "server", "handler", "route"],
+        &[],
+    ),
+    ("rust", &["rust", "cargo", "crate", "clippy"], &["rs"]),
+    (
+        "python",
+        &["python", "pip", "pytest", "django", "flask"],
+        &["py"],
+    ),
+    (
+        "javascript",
+        &["javascript", "typescript", "node", "npm"],
+        &["js", "ts", "mjs"],
+    ),
+    ("go", &["golang"], &["go"]),
+    (
+        "database",
+        &["database", "sql", "migration", "schema", "query"],
+        &["sql"],
+    ),
+    ("tests", &["test", "tests", "testing", "coverage"], &[]),
+    (
+        "docs",
+        &["docs", "documentation", "readme", "changelog", "docstring"],
+        &["md", "rst"],
+    ),
+    (
+        "devops",
+        &[
+            "ci",
+            "docker",
+            "dockerfile",
+            "deploy",
+            "pipeline",
+            "workflow",
+        ],
+        &["yml", "yaml"],
+    ),
+];
+
+/// Workers allowed for tasks with any of `tags`
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutingRule {
+    pub tags: Vec<String>,
+    pub workers: Vec<WorkerKind>,
+}
+
+/// Capability tags for a task from its description, instructions and files
+pub fn infer_tags(task: &Task) -> Vec<String> {
+    let text = format!("{} {}", task.description, task.instructions).to_lowercase();
+    let words: Vec<&str> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let extensions: Vec<String> = task
+        .relevant_files
+        .iter()
+        .filter_map(|f| Path::new(f).extension())
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .collect();
+
+    TAGS.iter()
+        .filter(|(_, keywords, exts)| {
+            keywords.iter().any(|k| words.contains(k))
+                || exts.iter().any(|e| extensions.iter().any(|x| x == e))
+        })
+        .map(|(tag, _, _)| tag.to_string())
+        .collect()
+}
+
+/// Applies routing rules, keeping count of tasks given to each worker
+pub struct Router<'a> {
+    rules: &'a [RoutingRule],
+    /// Workers the orchestrator may use; empty allows any
+    enabled: &'a [WorkerKind],
+    assigned: HashMap<WorkerKind, usize>,
+}
+
+impl<'a> Router<'a> {
+    pub fn new(rules: &'a [RoutingRule], enabled: &'a [WorkerKind]) -> Self {
+        Self {
+            rules,
+            enabled,
+            assigned: HashMap::new(),
+        }
+    }
+
+    /// Worker for a task with `tags`, or `None` when no rule applies
+    pub fn route(&mut self, tags: &[String]) -> Option<WorkerKind> {
+        let candidates: Vec<&WorkerKind> = self
+            .rules
+            .iter()
+            .filter(|rule| rule.tags.iter().any(|t| tags.contains(t)))
+            .map(|rule| {
+                rule.workers
+                    .iter()
+                    .filter(|w| self.enabled.is_empty() || self.enabled.contains(w))
+                    .collect::<Vec<_>>()
+            })
+            .find(|workers| !workers.is_empty())?;
+
+        // Least loaded first; ties go to the rule's order
+        let worker = candidates
+            .into_iter()
+            .min_by_key(|w| self.assigned.get(*w).copied().unwrap_or(0))?
+            .clone();
+        *self.assigned.entry(worker.clone()).or_insert(0) += 1;
+        Some(worker)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task(description: &str, files: &[&str]) -> Task {
+        Task::new("t".into(), description.into(), description.into())
+            .with_files(files.iter().map(|f| f.to_string()).collect())
+    }
+
+    #[test]
+    fn test_infer_tags() {
+        assert_eq!(
+            infer_tags(&task("Style the settings page", &["web/Settings.tsx"])),
+            vec!["frontend"]
+        );
+        assert_eq!(
+            infer_tags(&task("Add tests for the API handler", &["src/api.rs"])),
+            vec!["backend", "rust", "tests"]
+        );
+        // Substrings don't count: "build" is not "ui"
+        assert!(infer_tags(&task("Rebuild the cache", &[])).is_empty());
+    }
+
+    #[test]
+    fn test_router() {
+        let rules = vec![
+            RoutingRule {
+                tags: vec!["frontend".into()],
+                workers: vec![WorkerKind::GeminiCli],
+            },
+            RoutingRule {
+                tags: vec!["rust".into()],
+                workers: vec![WorkerKind::ClaudeCode, WorkerKind::SafeCoder],
+            },
+        ];
+        let enabled = vec![
+            WorkerKind::ClaudeCode,
+            WorkerKind::SafeCoder,
+            WorkerKind::GeminiCli,
+        ];
+        let mut router = Router::new(&rules, &enabled);
+        let rust = vec!["rust".to_string()];
+
+        assert_eq!(
+            router.route(&["frontend".to_string()]),
+            Some(WorkerKind::GeminiCli)
+        );
+        assert_eq!(router.route(&rust), Some(WorkerKind::ClaudeCode));
+        assert_eq!(router.route(&rust), Some(WorkerKind::SafeCoder));
+        assert_eq!(router.route(&rust), Some(WorkerKind::ClaudeCode));
+        assert_eq!(router.route(&["docs".to_string()]), None);
+
+        // Rules naming only disabled workers are skipped
+        let mut router = Router::new(&rules, &[WorkerKind::ClaudeCode]);
+        assert_eq!(router.route(&["frontend".to_string()]), None);
+    }
+}
@@ -478,10 +478,7 @@ impl SelfOrchestrationManager {
     pub async fn stop_instance(&mut self, instance_id: &str) -> Result<()> {
         if let Some(instance) = self.active_instances.remove(instance_id) {
             if let Some(pid) = instance.process_id {
-                let _ = Command::new("kill")
-                    .arg(pid.to_string())
-                    .output()
-                    .await;
+                let _ = crate::platform::kill_command(pid).output().await;
             }
         }
         Ok(())
@@ -433,11 +433,7 @@ impl StreamingWorker {
             // Timeout check (5 minutes default)
             if now.duration_since(self.started_at.unwrap_or(now)) > Duration::from_secs(300) {
                 if let Some(pid) = child_id {
-                    let _ = Command::new("kill")
-                        .arg("-9")
-                        .arg(pid.to_string())
-                        .output()
-                        .await;
+                    let _ = crate::platform::kill_command(pid).output().await;
                 }
                 return Err(anyhow::anyhow!("CLI process timed out after 5 minutes"));
             }
@@ -18,6 +18,9 @@ pub struct Task {
     pub dependencies: Vec<String>,
     /// Preferred worker for this task
     pub preferred_worker: Option<WorkerKind>,
+    /// Capability tags used for routing (e.g. "frontend", "rust")
+    #[serde(default)]
+    pub tags: Vec<String>,
     /// Priority (lower = higher priority)
     pub priority: u32,
     /// Current status of the task
@@ -106,6 +109,7 @@ impl Task {
             relevant_files: Vec::new(),
             dependencies: Vec::new(),
             preferred_worker: None,
+            tags: Vec::new(),
             priority: 0,
             status: TaskStatus::default(),
         }
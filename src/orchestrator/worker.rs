@@ -5,17 +5,24 @@ use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use tokio::process::Command;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, Notify};
 
-use crate::orchestrator::Task;
+use crate::orchestrator::{artifacts, Task};
+
+/// Error returned by a worker stopped through its cancel handle
+pub const CANCELLED: &str = "Cancelled by user";
 
 /// Events emitted by workers during execution for streaming output
 #[derive(Debug, Clone)]
 pub enum WorkerEvent {
     /// Worker started executing
-    Started { task_id: String, worker: WorkerKind },
+    Started {
+        task_id: String,
+        description: String,
+        worker: WorkerKind,
+    },
     /// Output line from stdout
     OutputLine { task_id: String, line: String },
     /// Error line from stderr
@@ -48,6 +55,20 @@ impl Default for WorkerKind {
     }
 }
 
+impl WorkerKind {
+    /// Parse a worker name as written in config and on the command line,
+    /// falling back to Claude Code for unknown names
+    pub fn from_name(name: &str) -> Self {
+        match name.to_lowercase().as_str() {
+            "claude" | "claude-code" => WorkerKind::ClaudeCode,
+            "gemini" | "gemini-cli" => WorkerKind::GeminiCli,
+            "safe-coder" | "safecoder" => WorkerKind::SafeCoder,
+            "github-copilot" | "copilot" | "gh-copilot" => WorkerKind::GitHubCopilot,
+            _ => WorkerKind::ClaudeCode,
+        }
+    }
+}
+
 /// Status of a worker
 #[derive(Debug, Clone)]
 pub struct WorkerStatus {
@@ -96,6 +117,10 @@ pub struct Worker {
     process_handle: Option<tokio::process::Child>,
     /// Optional event sender for streaming output
     event_tx: Option<WorkerEventSender>,
+    /// Notified to stop the running CLI process
+    cancel: Arc<Notify>,
+    /// Directory to copy stdout and stderr into as they stream
+    log_dir: Option<PathBuf>,
 }
 
 impl Worker {
@@ -110,6 +135,8 @@ impl Worker {
             output: String::new(),
             process_handle: None,
             event_tx: None,
+            cancel: Arc::new(Notify::new()),
+            log_dir: None,
         })
     }
 
@@ -130,6 +157,8 @@ impl Worker {
             output: String::new(),
             process_handle: None,
             event_tx: Some(event_tx),
+            cancel: Arc::new(Notify::new()),
+            log_dir: None,
         })
     }
 
@@ -138,6 +167,17 @@ impl Worker {
         self.event_tx = Some(event_tx);
     }
 
+    /// Write the CLI's stdout and stderr to `stdout.log` and `stderr.log` in `dir`
+    pub fn set_log_dir(&mut self, dir: PathBuf) {
+        self.log_dir = Some(dir);
+    }
+
+    /// Handle that stops this worker's CLI process from another task, even
+    /// while `execute` holds the worker
+    pub fn cancel_handle(&self) -> Arc<Notify> {
+        self.cancel.clone()
+    }
+
     /// Send an event if event sender is configured
     fn send_event(&self, event: WorkerEvent) {
         if let Some(tx) = &self.event_tx {
@@ -152,6 +192,7 @@ impl Worker {
         // Send started event
         self.send_event(WorkerEvent::Started {
             task_id: self.task.id.clone(),
+            description: self.task.description.clone(),
             worker: self.kind.clone(),
         });
 
@@ -175,7 +216,11 @@ impl Worker {
             }
             Err(e) => {
                 let error_msg = e.to_string();
-                self.state = WorkerState::Failed(error_msg.clone());
+                self.state = if error_msg == CANCELLED {
+                    WorkerState::Cancelled
+                } else {
+                    WorkerState::Failed(error_msg.clone())
+                };
                 self.send_event(WorkerEvent::Failed {
                     task_id: self.task.id.clone(),
                     error: error_msg.clone(),
@@ -310,6 +355,8 @@ impl Worker {
 
     /// Run a command and collect output with timeout, streaming lines as they arrive
     async fn run_command(&mut self, mut cmd: Command) -> Result<String> {
+        // Dropping the worker, as on shutdown, kills the CLI with it
+        cmd.kill_on_drop(true);
         let mut child = cmd.spawn().context("Failed to spawn CLI process")?;
 
         // Store the child handle for potential cancellation
@@ -333,6 +380,9 @@ impl Worker {
         tracing::info!("[WORKER] Starting stdout/stderr readers, has_event_tx: {}", self.event_tx.is_some());
 
         // Spawn tasks to read both streams concurrently, streaming lines as they arrive
+        let mut stdout_log = self.open_log(artifacts::STDOUT_FILE).await;
+        let mut stderr_log = self.open_log(artifacts::STDERR_FILE).await;
+
         let stdout_task = tokio::spawn(async move {
             let mut output = String::new();
             let mut lines = stdout_reader.lines();
@@ -347,6 +397,9 @@ impl Worker {
                         line: line.clone(),
                     });
                 }
+                if let Some(log) = &mut stdout_log {
+                    let _ = log.write_all(format!("{}\n", line).as_bytes()).await;
+                }
                 output.push_str(&line);
                 output.push('\n');
             }
@@ -365,6 +418,9 @@ impl Worker {
                         line: line.clone(),
                     });
                 }
+                if let Some(log) = &mut stderr_log {
+                    let _ = log.write_all(format!("{}\n", line).as_bytes()).await;
+                }
                 errors.push_str(&line);
                 errors.push('\n');
             }
@@ -374,7 +430,8 @@ impl Worker {
         // Wait for process with a timeout (5 minutes max)
         let timeout_duration = tokio::time::Duration::from_secs(300);
 
-        let wait_result = tokio::time::timeout(timeout_duration, async {
+        let cancel = self.cancel.clone();
+        let run = tokio::time::timeout(timeout_duration, async {
             // Wait for both streams to complete
             let (stdout_result, stderr_result) = tokio::join!(stdout_task, stderr_task);
             let output = stdout_result.unwrap_or_default();
@@ -386,8 +443,17 @@ impl Worker {
             Ok::<(String, String, std::process::ExitStatus), anyhow::Error>((
                 output, errors, status,
             ))
-        })
-        .await;
+        });
+
+        let wait_result = tokio::select! {
+            result = run => result,
+            _ = cancel.notified() => {
+                if let Some(pid) = child_id {
+                    let _ = crate::platform::kill_command(pid).output().await;
+                }
+                return Err(anyhow::anyhow!(CANCELLED));
+            }
+        };
 
         match wait_result {
             Ok(Ok((output, errors, status))) => {
@@ -405,11 +471,7 @@ impl Worker {
             Err(_) => {
                 // Timeout - try to kill the process
                 if let Some(pid) = child_id {
-                    let _ = tokio::process::Command::new("kill")
-                        .arg("-9")
-                        .arg(pid.to_string())
-                        .output()
-                        .await;
+                    let _ = crate::platform::kill_command(pid).output().await;
                 }
                 Err(anyhow::anyhow!(
                     "CLI process timed out after {} seconds",
@@ -419,6 +481,18 @@ impl Worker {
         }
     }
 
+    /// Create a log file in the log directory, if there is one
+    async fn open_log(&self, name: &str) -> Option<tokio::fs::File> {
+        let path = self.log_dir.as_ref()?.join(name);
+        match tokio::fs::File::create(&path).await {
+            Ok(file) => Some(file),
+            Err(e) => {
+                tracing::warn!("Failed to create {}: {}", path.display(), e);
+                None
+            }
+        }
+    }
+
     /// Get current status
     pub fn status(&self) -> WorkerStatus {
         WorkerStatus {
@@ -432,6 +506,7 @@ impl Worker {
 
     /// Cancel the worker
     pub async fn cancel(&mut self) -> Result<()> {
+        self.cancel.notify_one();
         if let Some(mut process) = self.process_handle.take() {
             process.kill().await.context("Failed to kill process")?;
         }
@@ -470,6 +545,7 @@ mod tests {
             relevant_files: vec![],
             dependencies: vec![],
             preferred_worker: None,
+            tags: vec![],
             priority: 0,
             status: TaskStatus::Pending,
         };
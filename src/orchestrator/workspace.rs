@@ -1,11 +1,75 @@
 //! Workspace manager for creating isolated git workspaces for each task
 
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 
 use tokio::process::Command;
 
+/// Everything a workspace changed since it was created, for review before merging
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceDiff {
+    /// Changed files with line counts
+    pub files: Vec<FileChange>,
+    /// Unified diff of all changes
+    pub patch: String,
+}
+
+/// Line counts for one changed file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileChange {
+    pub path: String,
+    /// Added lines (0 for binary files)
+    pub additions: usize,
+    /// Removed lines (0 for binary files)
+    pub deletions: usize,
+}
+
+impl WorkspaceDiff {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn additions(&self) -> usize {
+        self.files.iter().map(|f| f.additions).sum()
+    }
+
+    pub fn deletions(&self) -> usize {
+        self.files.iter().map(|f| f.deletions).sum()
+    }
+
+    /// One-line summary, e.g. `3 files changed, +42 -7`
+    pub fn stat_line(&self) -> String {
+        format!(
+            "{} file{} changed, +{} -{}",
+            self.files.len(),
+            if self.files.len() == 1 { "" } else { "s" },
+            self.additions(),
+            self.deletions()
+        )
+    }
+
+    /// Parse `git diff --numstat` output
+    fn parse_numstat(numstat: &str) -> Vec<FileChange> {
+        numstat
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(3, '\t');
+                let additions = parts.next()?;
+                let deletions = parts.next()?;
+                let path = parts.next()?;
+                Some(FileChange {
+                    path: path.to_string(),
+                    // Binary files show `-` for both counts
+                    additions: additions.parse().unwrap_or(0),
+                    deletions: deletions.parse().unwrap_or(0),
+                })
+            })
+            .collect()
+    }
+}
+
 /// Manages git workspaces (worktrees or branches) for task isolation
 pub struct WorkspaceManager {
     /// Base project path
@@ -18,6 +82,12 @@ pub struct WorkspaceManager {
     workspaces: HashMap<String, PathBuf>,
     /// Original branch name
     original_branch: Option<String>,
+    /// Commit the workspaces were created from
+    base_commit: Option<String>,
+    /// Keeps branches and worktrees apart from other managers on the same repo
+    namespace: Option<String>,
+    /// Removes the worktrees if the process is stopped by a signal
+    on_shutdown: Option<crate::shutdown::Registration>,
 }
 
 impl WorkspaceManager {
@@ -32,9 +102,31 @@ impl WorkspaceManager {
             use_worktrees,
             workspaces: HashMap::new(),
             original_branch: None,
+            base_commit: None,
+            namespace: None,
+            on_shutdown: None,
         })
     }
 
+    /// Put this manager's branches under `safe-coder/<namespace>/` and its
+    /// worktrees in their own directory, so several orchestrators can run on
+    /// one repository without touching each other's workspaces
+    pub fn set_namespace(&mut self, namespace: &str) {
+        self.worktree_base = self
+            .project_path
+            .join(".safe-coder-workspaces")
+            .join(namespace);
+        self.namespace = Some(namespace.to_string());
+    }
+
+    /// Branch holding a task's changes
+    pub fn branch_name(&self, task_id: &str) -> String {
+        match &self.namespace {
+            Some(namespace) => format!("safe-coder/{}/{}", namespace, task_id),
+            None => format!("safe-coder/{}", task_id),
+        }
+    }
+
     /// Initialize the workspace manager (ensure git is set up)
     pub async fn init(&mut self) -> Result<()> {
         // Check if this is a git repository
@@ -65,9 +157,38 @@ impl WorkspaceManager {
             );
         }
 
+        let head_output = Command::new("git")
+            .current_dir(&self.project_path)
+            .args(["rev-parse", "HEAD"])
+            .output()
+            .await?;
+
+        if head_output.status.success() {
+            self.base_commit = Some(
+                String::from_utf8_lossy(&head_output.stdout)
+                    .trim()
+                    .to_string(),
+            );
+        }
+
         // Create worktree base directory if using worktrees
         if self.use_worktrees {
             std::fs::create_dir_all(&self.worktree_base)?;
+
+            // Branches are kept, so an interrupted task's commits survive
+            let project_path = self.project_path.clone();
+            let worktree_base = self.worktree_base.clone();
+            self.on_shutdown = Some(crate::shutdown::register(
+                "remove orchestration worktrees",
+                move || async move {
+                    let _ = std::fs::remove_dir_all(&worktree_base);
+                    let _ = Command::new("git")
+                        .current_dir(&project_path)
+                        .args(["worktree", "prune"])
+                        .output()
+                        .await;
+                },
+            ));
         }
 
         Ok(())
@@ -80,7 +201,7 @@ impl WorkspaceManager {
             self.init().await?;
         }
 
-        let branch_name = format!("safe-coder/{}", task_id);
+        let branch_name = self.branch_name(task_id);
 
         if self.use_worktrees {
             self.create_worktree(task_id, &branch_name).await
@@ -191,7 +312,7 @@ impl WorkspaceManager {
 
     /// Merge a workspace back to the main branch
     pub async fn merge_workspace(&mut self, task_id: &str) -> Result<()> {
-        let branch_name = format!("safe-coder/{}", task_id);
+        let branch_name = self.branch_name(task_id);
 
         if self.use_worktrees {
             self.merge_worktree(task_id, &branch_name).await
@@ -208,19 +329,7 @@ impl WorkspaceManager {
             .context("Original branch not known")?;
 
         // First, commit any changes in the worktree
-        if let Some(worktree_path) = self.workspaces.get(task_id) {
-            let _ = Command::new("git")
-                .current_dir(worktree_path)
-                .args(["add", "."])
-                .output()
-                .await?;
-
-            let _ = Command::new("git")
-                .current_dir(worktree_path)
-                .args(["commit", "-m", &format!("Task {} completed", task_id)])
-                .output()
-                .await;
-        }
+        self.commit_workspace(task_id).await?;
 
         // Merge the branch into the original branch
         let merge = Command::new("git")
@@ -249,6 +358,44 @@ impl WorkspaceManager {
         Ok(())
     }
 
+    /// Commit everything changed in a workspace to its branch
+    async fn commit_workspace(&self, task_id: &str) -> Result<()> {
+        if let Some(workspace) = self.workspaces.get(task_id) {
+            let _ = Command::new("git")
+                .current_dir(workspace)
+                .args(["add", "."])
+                .output()
+                .await?;
+
+            let _ = Command::new("git")
+                .current_dir(workspace)
+                .args(["commit", "-m", &format!("Task {} completed", task_id)])
+                .output()
+                .await;
+        }
+        Ok(())
+    }
+
+    /// Commit a workspace's changes and let go of it without deleting its
+    /// branch, so the changes can be reviewed and merged later. Returns the
+    /// branch name.
+    pub async fn keep_branch(&mut self, task_id: &str) -> Result<String> {
+        self.commit_workspace(task_id).await?;
+        if let Some(workspace) = self.workspaces.remove(task_id) {
+            if self.use_worktrees {
+                let _ = Command::new("git")
+                    .current_dir(&self.project_path)
+                    .arg("worktree")
+                    .arg("remove")
+                    .arg(&workspace)
+                    .arg("--force")
+                    .output()
+                    .await;
+            }
+        }
+        Ok(self.branch_name(task_id))
+    }
+
     /// Merge a branch back to original
     async fn merge_branch(&self, branch_name: &str) -> Result<()> {
         let original_branch = self
@@ -289,7 +436,7 @@ impl WorkspaceManager {
 
     /// Cleanup a single workspace
     pub async fn cleanup_workspace(&mut self, task_id: &str) -> Result<()> {
-        let branch_name = format!("safe-coder/{}", task_id);
+        let branch_name = self.branch_name(task_id);
 
         if self.use_worktrees {
             // Remove worktree
@@ -323,6 +470,7 @@ impl WorkspaceManager {
         }
 
         // Remove the worktree base directory
+        self.on_shutdown = None;
         if self.worktree_base.exists() {
             let _ = std::fs::remove_dir_all(&self.worktree_base);
         }
@@ -339,6 +487,65 @@ impl WorkspaceManager {
         Ok(())
     }
 
+    /// Stat summary of everything changed in a workspace since it was
+    /// created, including uncommitted files. Empty when nothing changed.
+    pub async fn diff_summary(&self, task_id: &str) -> Result<String> {
+        Ok(self
+            .staged_diff(task_id, "--stat")
+            .await?
+            .trim_end()
+            .to_string())
+    }
+
+    /// Files and patch of everything changed in a workspace since it was created
+    pub async fn diff(&self, task_id: &str) -> Result<WorkspaceDiff> {
+        let numstat = self.staged_diff(task_id, "--numstat").await?;
+        let patch = self.staged_diff(task_id, "--patch").await?;
+        Ok(WorkspaceDiff {
+            files: WorkspaceDiff::parse_numstat(&numstat),
+            patch,
+        })
+    }
+
+    /// Stage all changes in a workspace and diff them against the base commit
+    async fn staged_diff(&self, task_id: &str, format: &str) -> Result<String> {
+        let workspace = self
+            .workspaces
+            .get(task_id)
+            .with_context(|| format!("No workspace for task {}", task_id))?;
+        let base = self
+            .base_commit
+            .as_deref()
+            .context("Base commit not known")?;
+
+        // Stage everything so new files show up in the diff
+        let _ = Command::new("git")
+            .current_dir(workspace)
+            .args(["add", "-A"])
+            .output()
+            .await?;
+
+        let diff = Command::new("git")
+            .current_dir(workspace)
+            .args(["diff", "--cached", format, base])
+            .output()
+            .await?;
+
+        if !diff.status.success() {
+            return Err(anyhow::anyhow!(
+                "Failed to diff workspace: {}",
+                String::from_utf8_lossy(&diff.stderr)
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&diff.stdout).to_string())
+    }
+
+    /// Name of the branch the workspaces were created from
+    pub fn original_branch(&self) -> Option<&str> {
+        self.original_branch.as_deref()
+    }
+
     /// List all active workspaces
     pub fn list_workspaces(&self) -> Vec<(String, PathBuf)> {
         self.workspaces
@@ -365,4 +572,80 @@ mod tests {
 
         assert!(manager.workspaces.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_diff_summary() {
+        let temp = tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .current_dir(temp.path())
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::write(temp.path().join("a.txt"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "init"]);
+
+        let mut manager = WorkspaceManager::new(temp.path().to_path_buf(), true).unwrap();
+        let workspace = manager.create_workspace("chat-1").await.unwrap();
+        assert_eq!(manager.diff_summary("chat-1").await.unwrap(), "");
+
+        std::fs::write(workspace.join("a.txt"), "one\ntwo\n").unwrap();
+        std::fs::write(workspace.join("b.txt"), "new\n").unwrap();
+        let summary = manager.diff_summary("chat-1").await.unwrap();
+        assert!(summary.contains("a.txt"));
+        assert!(summary.contains("2 files changed"));
+
+        let diff = manager.diff("chat-1").await.unwrap();
+        assert_eq!(diff.stat_line(), "2 files changed, +2 -0");
+        assert_eq!(diff.files[0].path, "a.txt");
+        assert!(diff.patch.contains("+two"));
+
+        manager.merge_workspace("chat-1").await.unwrap();
+        manager.cleanup_workspace("chat-1").await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("b.txt")).unwrap(),
+            "new\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keep_branch_in_namespace() {
+        let temp = tempdir().unwrap();
+        let git = |args: &[&str]| {
+            std::process::Command::new("git")
+                .current_dir(temp.path())
+                .args(args)
+                .output()
+                .unwrap()
+        };
+        git(&["init", "-q"]);
+        git(&["config", "user.email", "test@example.com"]);
+        git(&["config", "user.name", "Test"]);
+        std::fs::write(temp.path().join("a.txt"), "one\n").unwrap();
+        git(&["add", "."]);
+        git(&["commit", "-qm", "init"]);
+
+        let mut manager = WorkspaceManager::new(temp.path().to_path_buf(), true).unwrap();
+        manager.set_namespace("q-1");
+        let workspace = manager.create_workspace("task-1").await.unwrap();
+        assert!(workspace.ends_with(".safe-coder-workspaces/q-1/task-1"));
+        std::fs::write(workspace.join("a.txt"), "two\n").unwrap();
+
+        let branch = manager.keep_branch("task-1").await.unwrap();
+        assert_eq!(branch, "safe-coder/q-1/task-1");
+        manager.cleanup_all().await.unwrap();
+
+        // The branch outlives the cleanup; the checkout is untouched
+        let kept = git(&["show", &format!("{}:a.txt", branch)]);
+        assert_eq!(String::from_utf8_lossy(&kept.stdout), "two\n");
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("a.txt")).unwrap(),
+            "one\n"
+        );
+    }
 }
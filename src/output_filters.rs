@@ -0,0 +1,386 @@
+//! Post-processing of assistant output before it is displayed
+//!
+//! An [`OutputPipeline`] runs a list of [`OutputFilter`]s over the text of a
+//! response. The built-in filters check file paths against the project tree,
+//! turn `path:line` references into links and collapse very long code
+//! blocks; frontends can add their own with [`OutputPipeline::push`].
+//! Only the displayed copy is changed; the conversation keeps the original.
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::OutputConfig;
+
+/// What to do with file paths in a response that don't exist in the project
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MissingPaths {
+    /// Mark them as not found
+    #[default]
+    Flag,
+    /// Replace them with a placeholder
+    Strip,
+    /// Leave them alone
+    Off,
+}
+
+/// One step of the pipeline
+pub trait OutputFilter: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn apply(&self, text: &str) -> String;
+}
+
+/// Filters applied in order to assistant output
+#[derive(Default)]
+pub struct OutputPipeline {
+    filters: Vec<Box<dyn OutputFilter>>,
+}
+
+impl OutputPipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The path check and link filters enabled in `[output]`. Code block
+    /// collapsing depends on display state, so frontends apply
+    /// [`CollapseCodeBlocks`] themselves.
+    pub fn from_config(config: &OutputConfig, project: &Path) -> Self {
+        let mut pipeline = Self::new();
+        if config.missing_paths != MissingPaths::Off {
+            pipeline.push(Box::new(CheckPaths {
+                root: project.to_path_buf(),
+                strip: config.missing_paths == MissingPaths::Strip,
+            }));
+        }
+        if config.linkify {
+            pipeline.push(Box::new(LinkifyPaths {
+                root: project.to_path_buf(),
+            }));
+        }
+        tracing::debug!("Output filters: [{}]", pipeline.names().join(", "));
+        pipeline
+    }
+
+    pub fn push(&mut self, filter: Box<dyn OutputFilter>) {
+        self.filters.push(filter);
+    }
+
+    /// Names of the filters, in order
+    pub fn names(&self) -> Vec<&'static str> {
+        self.filters.iter().map(|f| f.name()).collect()
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        self.filters
+            .iter()
+            .fold(text.to_string(), |text, filter| filter.apply(&text))
+    }
+}
+
+/// A relative file path mentioned in prose, with an optional line number
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathRef {
+    pub path: String,
+    pub line: Option<usize>,
+    /// Written as inline code (`` `src/main.rs` ``)
+    pub code: bool,
+}
+
+impl PathRef {
+    /// How the reference was written, e.g. `` `src/main.rs:12` ``
+    pub fn written(&self) -> String {
+        let mut text = self.path.clone();
+        if let Some(line) = self.line {
+            text.push_str(&format!(":{}", line));
+        }
+        if self.code {
+            format!("`{}`", text)
+        } else {
+            text
+        }
+    }
+
+    pub fn exists_in(&self, root: &Path) -> bool {
+        root.join(&self.path).exists()
+    }
+}
+
+fn path_regex() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        // A relative path with at least one directory and a file extension,
+        // optionally followed by :line[:column], either in backticks or
+        // standing on its own (not part of a URL or a longer word)
+        Regex::new(
+            r"(?x)
+            (?P<tick>`)?
+            (?P<path>(?:\.{1,2}/)?[\w.-]+(?:/[\w.-]+)+\.[A-Za-z0-9]{1,8})
+            (?::(?P<line>\d+)(?::\d+)?)?
+            (?P<close>`)?",
+        )
+        .unwrap()
+    })
+}
+
+/// Replace each path reference outside fenced code blocks with the result
+/// of `replace` (None keeps it as written)
+pub fn replace_path_refs(text: &str, replace: impl FnMut(&PathRef) -> Option<String>) -> String {
+    scan_path_refs(text, false, replace)
+}
+
+/// Every path reference in `text`, including those in code blocks and in
+/// the text of links made by [`LinkifyPaths`]
+pub fn find_path_refs(text: &str) -> Vec<PathRef> {
+    let mut refs = Vec::new();
+    scan_path_refs(text, true, |reference| {
+        refs.push(reference.clone());
+        None
+    });
+    refs
+}
+
+fn scan_path_refs(
+    text: &str,
+    everywhere: bool,
+    mut replace: impl FnMut(&PathRef) -> Option<String>,
+) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_fence = false;
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if !everywhere && (in_fence || line.trim_start().starts_with("```")) {
+            out.push_str(line);
+            continue;
+        }
+
+        let mut last = 0;
+        for caps in path_regex().captures_iter(line) {
+            let whole = caps.get(0).unwrap();
+            let ticked = caps.name("tick").is_some();
+            let closed = caps.name("close").is_some();
+            // Inline code counts only when the backticks surround the
+            // reference exactly; otherwise look at the bare path
+            let (start, end, code) = if ticked && closed {
+                (whole.start(), whole.end(), true)
+            } else {
+                let path = caps.name("path").unwrap();
+                let end = caps
+                    .name("line")
+                    .map_or(path.end(), |_| whole.end() - usize::from(closed));
+                (path.start(), end, false)
+            };
+            // Skip parts of URLs, longer words and existing links
+            let before = line[..start].chars().next_back();
+            let after = line[end..].chars().next();
+            let in_link = before == Some('[') || line[..start].ends_with("](");
+            let embedded = before
+                .is_some_and(|c| c.is_alphanumeric() || matches!(c, '/' | ':' | '@' | '`'))
+                || after.is_some_and(|c| c.is_alphanumeric() || matches!(c, '/' | '`' | ']'));
+            if (in_link && !everywhere) || (!code && embedded) {
+                continue;
+            }
+
+            let reference = PathRef {
+                path: caps["path"].to_string(),
+                line: caps.name("line").and_then(|l| l.as_str().parse().ok()),
+                code,
+            };
+            if let Some(replacement) = replace(&reference) {
+                out.push_str(&line[last..start]);
+                out.push_str(&replacement);
+                last = end;
+            }
+        }
+        out.push_str(&line[last..]);
+    }
+    out
+}
+
+/// Marks (or removes) file paths that don't exist in the project, which
+/// usually means the model made them up
+pub struct CheckPaths {
+    pub root: PathBuf,
+    pub strip: bool,
+}
+
+impl OutputFilter for CheckPaths {
+    fn name(&self) -> &'static str {
+        "check_paths"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        replace_path_refs(text, |reference| {
+            if reference.exists_in(&self.root) {
+                None
+            } else if self.strip {
+                Some("*(unknown file)*".to_string())
+            } else {
+                Some(format!("{} *(not found)*", reference.written()))
+            }
+        })
+    }
+}
+
+/// Turns references to existing files into `file://` links, with the line
+/// number as the fragment
+pub struct LinkifyPaths {
+    pub root: PathBuf,
+}
+
+impl OutputFilter for LinkifyPaths {
+    fn name(&self) -> &'static str {
+        "linkify"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        replace_path_refs(text, |reference| {
+            reference.exists_in(&self.root).then(|| {
+                format!(
+                    "[{}]({})",
+                    reference.written(),
+                    file_url(&self.root, reference)
+                )
+            })
+        })
+    }
+}
+
+/// `file://` URL of a reference, e.g. `file:///repo/src/main.rs#L12`
+pub fn file_url(root: &Path, reference: &PathRef) -> String {
+    let path = root.join(reference.path.trim_start_matches("./"));
+    let mut url = format!("file://{}", path.display());
+    if let Some(line) = reference.line {
+        url.push_str(&format!("#L{}", line));
+    }
+    url
+}
+
+/// Shortens fenced code blocks longer than `max_lines`, noting how many
+/// lines were hidden
+pub struct CollapseCodeBlocks {
+    pub max_lines: usize,
+    /// Appended to the note, e.g. how to expand the block again
+    pub hint: &'static str,
+}
+
+impl OutputFilter for CollapseCodeBlocks {
+    fn name(&self) -> &'static str {
+        "collapse_code_blocks"
+    }
+
+    fn apply(&self, text: &str) -> String {
+        if self.max_lines == 0 {
+            return text.to_string();
+        }
+        let mut out: Vec<&str> = Vec::new();
+        let mut block: Option<Vec<&str>> = None;
+        let mut notes = Vec::new();
+        for line in text.split('\n') {
+            let fence = line.trim_start().starts_with("```");
+            match block.as_mut() {
+                None if fence => block = Some(Vec::new()),
+                None => out.push(line),
+                Some(lines) if fence => {
+                    let hidden = lines.len().saturating_sub(self.max_lines);
+                    out.extend(lines.iter().take(self.max_lines));
+                    if hidden > 0 {
+                        notes.push((out.len(), hidden));
+                        out.push("");
+                    }
+                    block = None;
+                }
+                Some(lines) => lines.push(line),
+            }
+            if fence {
+                out.push(line);
+            }
+        }
+        // An unclosed block (still streaming) is left as it is
+        if let Some(lines) = block {
+            out.extend(lines);
+        }
+
+        let mut result: Vec<String> = out.into_iter().map(str::to_string).collect();
+        for (index, hidden) in notes {
+            result[index] = format!("… {} more lines{}", hidden, self.hint);
+        }
+        result.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn flags_missing_paths_and_links_existing_ones() {
+        let dir = project();
+        let config = OutputConfig::default();
+        let pipeline = OutputPipeline::from_config(&config, dir.path());
+        assert_eq!(pipeline.names(), ["check_paths", "linkify"]);
+
+        let text = "See `src/main.rs:1` and src/lib/util.rs, not https://example.com/a/b.rs.\n\
+                    ```\nsrc/main.rs\n```";
+        let out = pipeline.apply(text);
+        let url = format!("file://{}#L1", dir.path().join("src/main.rs").display());
+        assert!(
+            out.contains(&format!("[`src/main.rs:1`]({})", url)),
+            "{}",
+            out
+        );
+        assert!(out.contains("src/lib/util.rs *(not found)*"), "{}", out);
+        assert!(out.contains("https://example.com/a/b.rs."), "{}", out);
+        assert!(out.ends_with("```\nsrc/main.rs\n```"), "{}", out);
+
+        let strip = CheckPaths {
+            root: dir.path().to_path_buf(),
+            strip: true,
+        };
+        assert_eq!(
+            strip.apply("Edit src/nope.rs now"),
+            "Edit *(unknown file)* now"
+        );
+
+        let found: Vec<(String, Option<usize>)> = find_path_refs(&out)
+            .into_iter()
+            .map(|r| (r.path, r.line))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("src/main.rs".to_string(), Some(1)),
+                ("src/lib/util.rs".to_string(), None),
+                ("src/main.rs".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn collapses_long_code_blocks() {
+        let code: Vec<String> = (0..10).map(|i| format!("line {}", i)).collect();
+        let text = format!("Before\n```rust\n{}\n```\nAfter", code.join("\n"));
+        let filter = CollapseCodeBlocks {
+            max_lines: 3,
+            hint: " (Ctrl+O)",
+        };
+        assert_eq!(
+            filter.apply(&text),
+            "Before\n```rust\nline 0\nline 1\nline 2\n… 7 more lines (Ctrl+O)\n```\nAfter"
+        );
+        assert_eq!(filter.apply("```\nshort\n```"), "```\nshort\n```");
+    }
+}
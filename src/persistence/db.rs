@@ -1,8 +1,11 @@
-use super::models::SavedSession;
+use super::models::{SavedSession, SessionStats, SessionStatsRecord};
 use anyhow::{Context, Result};
 use sqlx::{sqlite::SqlitePoolOptions, SqlitePool};
 use std::path::PathBuf;
 
+/// Days an unfinished session stays recoverable
+const JOURNAL_RETENTION_DAYS: i64 = 7;
+
 /// SQLite database for session persistence
 pub struct SessionDatabase {
     pool: SqlitePool,
@@ -65,6 +68,38 @@ impl SessionDatabase {
         .execute(pool)
         .await?;
 
+        // Write-ahead copy of the live conversation, removed on clean exit
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS journal (
+                id TEXT PRIMARY KEY,
+                project_path TEXT NOT NULL,
+                messages TEXT NOT NULL,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
+        // Per-session usage, kept after the session ends for `safe-coder stats`
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS session_stats (
+                session_id TEXT PRIMARY KEY,
+                project_path TEXT NOT NULL,
+                model TEXT NOT NULL,
+                stats TEXT NOT NULL,
+                completed INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )
+            "#,
+        )
+        .execute(pool)
+        .await?;
+
         Ok(())
     }
 
@@ -168,4 +203,127 @@ impl SessionDatabase {
 
         Ok(())
     }
+
+    /// Insert or update the journal entry of a live session
+    pub async fn write_journal(&self, id: &str, project_path: &str, messages: &str) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO journal (id, project_path, messages, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET messages = excluded.messages, updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(id)
+        .bind(project_path)
+        .bind(messages)
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await?;
+
+        // Sessions that never ended cleanly are only worth recovering for a while
+        let cutoff = (chrono::Utc::now() - chrono::Duration::days(JOURNAL_RETENTION_DAYS)).to_rfc3339();
+        sqlx::query("DELETE FROM journal WHERE updated_at < ?")
+            .bind(cutoff)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Remove a journal entry
+    pub async fn delete_journal(&self, id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM journal WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    /// Most recently updated journal entry for a project
+    pub async fn latest_journal(&self, project_path: &str) -> Result<Option<SavedSession>> {
+        let row = sqlx::query_as::<_, (String, String, String, String, String)>(
+            r#"
+            SELECT id, project_path, messages, created_at, updated_at
+            FROM journal
+            WHERE project_path = ?
+            ORDER BY updated_at DESC
+            LIMIT 1
+            "#,
+        )
+        .bind(project_path)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(|row| {
+            Ok(SavedSession {
+                id: row.0,
+                name: None,
+                project_path: row.1,
+                messages: row.2,
+                created_at: row.3.parse().context("Invalid created_at")?,
+                updated_at: row.4.parse().context("Invalid updated_at")?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Insert or update the usage stats of a session
+    pub async fn write_stats(&self, record: &SessionStatsRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO session_stats (session_id, project_path, model, stats, completed, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(session_id) DO UPDATE SET
+                model = excluded.model,
+                stats = excluded.stats,
+                completed = excluded.completed,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&record.session_id)
+        .bind(&record.project_path)
+        .bind(&record.model)
+        .bind(serde_json::to_string(&record.stats)?)
+        .bind(record.completed)
+        .bind(record.created_at.to_rfc3339())
+        .bind(record.updated_at.to_rfc3339())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Usage stats of sessions started at or after `since`, oldest first
+    pub async fn list_stats(&self, since: chrono::DateTime<chrono::Utc>) -> Result<Vec<SessionStatsRecord>> {
+        let rows = sqlx::query_as::<_, (String, String, String, String, bool, String, String)>(
+            r#"
+            SELECT session_id, project_path, model, stats, completed, created_at, updated_at
+            FROM session_stats
+            WHERE created_at >= ?
+            ORDER BY created_at ASC
+            "#,
+        )
+        .bind(since.to_rfc3339())
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| {
+                let stats: SessionStats =
+                    serde_json::from_str(&row.3).context("Invalid session stats")?;
+                Ok(SessionStatsRecord {
+                    session_id: row.0,
+                    project_path: row.1,
+                    model: row.2,
+                    stats,
+                    completed: row.4,
+                    created_at: row.5.parse().context("Invalid created_at")?,
+                    updated_at: row.6.parse().context("Invalid updated_at")?,
+                })
+            })
+            .collect()
+    }
 }
@@ -9,7 +9,7 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs::{self, File, OpenOptions};
 use std::io::{BufRead, BufReader, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use crate::llm::Message;
 
@@ -407,7 +407,12 @@ impl EventLogger {
             anyhow::bail!("Session log not found: {}", session_id);
         }
 
-        let file = File::open(&log_path)?;
+        Self::load_messages_from(&log_path)
+    }
+
+    /// Load messages from a session log file at a known path
+    pub fn load_messages_from(log_path: &Path) -> Result<Vec<Message>> {
+        let file = File::open(log_path)?;
         let reader = BufReader::new(file);
 
         let mut messages = Vec::new();
@@ -1,15 +1,19 @@
 mod db;
 pub mod event_log;
 pub mod models;
+pub mod projects;
+pub mod replay;
+pub mod share;
+pub mod usage;
 
 pub use db::SessionDatabase;
 pub use event_log::{EventLogger, SessionInfo, SessionLogEvent};
-pub use models::{SavedSession, SessionStats, ToolUsage};
+pub use models::{SavedSession, SessionStats, SessionStatsRecord, ToolUsage};
 
 use anyhow::Result;
 use chrono::Utc;
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use crate::llm::Message;
 
 /// Session persistence manager
@@ -65,4 +69,41 @@ impl SessionPersistence {
         let messages_json = serde_json::to_string(messages)?;
         self.db.update_session_messages(id, &messages_json).await
     }
+
+    /// Write the live message list ahead of the next turn, so a crash loses at most one turn
+    pub async fn checkpoint_turn(
+        &self,
+        journal_id: &str,
+        project_path: &Path,
+        messages: &[Message],
+    ) -> Result<()> {
+        let messages_json = serde_json::to_string(messages)?;
+        self.db
+            .write_journal(journal_id, &project_path.to_string_lossy(), &messages_json)
+            .await
+    }
+
+    /// Forget the journal of a session that ended cleanly
+    pub async fn discard_journal(&self, journal_id: &str) -> Result<()> {
+        self.db.delete_journal(journal_id).await
+    }
+
+    /// The newest session in this project that did not end cleanly
+    pub async fn unfinished_session(&self, project_path: &Path) -> Result<Option<SavedSession>> {
+        self.db
+            .latest_journal(&project_path.to_string_lossy())
+            .await
+    }
+
+    /// Record the usage of a live or finished session
+    pub async fn record_stats(&self, record: &SessionStatsRecord) -> Result<()> {
+        self.db.write_stats(record).await
+    }
+
+    /// Usage of every session started in the last `days` days
+    pub async fn recent_stats(&self, days: i64) -> Result<Vec<SessionStatsRecord>> {
+        self.db
+            .list_stats(Utc::now() - chrono::Duration::days(days))
+            .await
+    }
 }
@@ -21,12 +21,30 @@ pub struct SessionStats {
     pub total_tool_calls: usize,
     pub session_duration_secs: i64,
     pub tools_used: Vec<ToolUsage>,
+    /// Tool calls that returned an error or a non-zero exit status
+    #[serde(default)]
+    pub total_tool_failures: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolUsage {
     pub tool_name: String,
     pub count: usize,
+    #[serde(default)]
+    pub failures: usize,
+}
+
+/// Stats of one session as kept in the database for `safe-coder stats`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionStatsRecord {
+    pub session_id: String,
+    pub project_path: String,
+    pub model: String,
+    pub stats: SessionStats,
+    /// The session ended cleanly rather than crashing or being killed
+    pub completed: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
 }
 
 impl SessionStats {
@@ -38,6 +56,15 @@ impl SessionStats {
             total_tool_calls: 0,
             session_duration_secs: 0,
             tools_used: Vec::new(),
+            total_tool_failures: 0,
+        }
+    }
+
+    /// Count a failed call of a tool already recorded in `tools_used`
+    pub fn record_tool_failure(&mut self, tool_name: &str) {
+        self.total_tool_failures += 1;
+        if let Some(tool) = self.tools_used.iter_mut().find(|t| t.tool_name == tool_name) {
+            tool.failures += 1;
         }
     }
 
@@ -54,7 +81,10 @@ impl SessionStats {
 
         output.push_str(&format!("⏱️  Duration: {}h {}m {}s\n", hours, minutes, seconds));
         output.push_str(&format!("💬 Messages: {}\n", self.total_messages));
-        output.push_str(&format!("🔧 Tool Calls: {}\n\n", self.total_tool_calls));
+        output.push_str(&format!(
+            "🔧 Tool Calls: {} ({} failed)\n\n",
+            self.total_tool_calls, self.total_tool_failures
+        ));
 
         // Tokens
         output.push_str("📝 Token Usage:\n");
@@ -0,0 +1,99 @@
+//! Recent projects, pinned favorites and per-project session settings
+//!
+//! The desktop app records projects in `~/.config/safe-coder/projects.json`
+//! as they are opened, and applies a project's overrides when it creates a
+//! session there. `GET /api/projects` lists them for other clients.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Settings a project's sessions start with instead of the config's
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProjectOverrides {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// "build" or "plan"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
+    /// safe, dev, full or yolo
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_preset: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectEntry {
+    pub path: String,
+    pub last_opened: DateTime<Utc>,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub overrides: ProjectOverrides,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProjectList {
+    #[serde(default)]
+    pub projects: Vec<ProjectEntry>,
+}
+
+impl ProjectList {
+    /// `~/.config/safe-coder/projects.json`
+    pub fn path() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+        Ok(config_dir.join("safe-coder").join("projects.json"))
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::path()?)
+    }
+
+    fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        serde_json::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Pinned projects first, then the most recently opened
+    pub fn sorted(mut self) -> Vec<ProjectEntry> {
+        self.projects
+            .sort_by(|a, b| b.pinned.cmp(&a.pinned).then(b.last_opened.cmp(&a.last_opened)));
+        self.projects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pinned_first_then_recent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("projects.json");
+        std::fs::write(
+            &path,
+            r#"{"projects": [
+                {"path": "/old", "last_opened": "2026-01-01T00:00:00Z"},
+                {"path": "/pinned", "last_opened": "2025-01-01T00:00:00Z", "pinned": true,
+                 "overrides": {"model": "claude-haiku-4-5", "mode": "plan"}},
+                {"path": "/new", "last_opened": "2026-02-01T00:00:00Z"}
+            ]}"#,
+        )
+        .unwrap();
+
+        let projects = ProjectList::load_from(&path).unwrap().sorted();
+        let paths: Vec<&str> = projects.iter().map(|p| p.path.as_str()).collect();
+        assert_eq!(paths, ["/pinned", "/new", "/old"]);
+        assert_eq!(projects[0].overrides.mode.as_deref(), Some("plan"));
+        assert_eq!(projects[1].overrides, ProjectOverrides::default());
+
+        assert!(ProjectList::load_from(&dir.path().join("missing.json"))
+            .unwrap()
+            .projects
+            .is_empty());
+    }
+}
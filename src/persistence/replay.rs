@@ -0,0 +1,321 @@
+//! Step through a recorded session turn by turn
+//!
+//! Tool calls and the diffs they made are rendered from the session log
+//! without running anything. With re-execution, each tool call is run again
+//! in a scratch git worktree so a problem can be reproduced without touching
+//! the project.
+
+use anyhow::{Context, Result};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::config::ToolConfig;
+use crate::llm::{ContentBlock, Message, Role};
+use crate::tools::{ToolContext, ToolRegistry};
+
+/// Lines of tool output shown per call
+const OUTPUT_PREVIEW_LINES: usize = 12;
+
+/// Tools that are not re-run because they reach outside the worktree
+const SKIPPED_TOOLS: &[&str] = &["open_in_editor", "subagent", "orchestrate"];
+
+/// Split a conversation into turns, each starting at a user prompt
+///
+/// Tool results are sent back as user messages too, so they stay in the turn
+/// of the assistant message that asked for them.
+pub fn split_turns(messages: Vec<Message>) -> Vec<Vec<Message>> {
+    let mut turns: Vec<Vec<Message>> = Vec::new();
+    for message in messages {
+        if is_prompt(&message) || turns.is_empty() {
+            turns.push(Vec::new());
+        }
+        turns.last_mut().unwrap().push(message);
+    }
+    turns
+}
+
+fn is_prompt(message: &Message) -> bool {
+    matches!(message.role, Role::User)
+        && message.content.iter().any(|block| {
+            matches!(
+                block,
+                ContentBlock::Text { .. } | ContentBlock::Image { .. }
+            )
+        })
+}
+
+/// Render a turn, re-running its tool calls in `scratch` when given
+pub async fn render_turn(turn: &[Message], scratch: Option<&Scratch>) -> String {
+    let mut out = String::new();
+    for message in turn {
+        for block in &message.content {
+            match (&message.role, block) {
+                (Role::User, ContentBlock::Text { text }) => {
+                    out.push_str(&format!("👤 {}\n\n", text.trim()));
+                }
+                (_, ContentBlock::Image { media_type, .. }) => {
+                    out.push_str(&format!("🖼  [{} image]\n\n", media_type));
+                }
+                (Role::Assistant, ContentBlock::Text { text }) if !text.trim().is_empty() => {
+                    out.push_str(&format!("🤖 {}\n\n", text.trim()));
+                }
+                (_, ContentBlock::Thinking { thinking, .. }) if !thinking.is_empty() => {
+                    out.push_str(&format!(
+                        "💭 Thought for {} lines\n\n",
+                        thinking.lines().count()
+                    ));
+                }
+                (_, ContentBlock::ToolUse { id, name, input }) => {
+                    out.push_str(&format!("🔧 {} {}\n", name, summarize_input(input)));
+                    if let Some(diff) = recorded_diff(name, input) {
+                        out.push_str(&diff);
+                    }
+                    if let Some(recorded) = find_result(turn, id) {
+                        out.push_str(&preview("↳", recorded));
+                    }
+                    if let Some(scratch) = scratch {
+                        out.push_str(&scratch.rerun(name, input, find_result(turn, id)).await);
+                    }
+                    out.push('\n');
+                }
+                // Results are shown under the call that produced them
+                _ => {}
+            }
+        }
+    }
+    out
+}
+
+fn find_result<'a>(turn: &'a [Message], tool_use_id: &str) -> Option<&'a str> {
+    turn.iter()
+        .flat_map(|message| &message.content)
+        .find_map(|block| match block {
+            ContentBlock::ToolResult {
+                tool_use_id: id,
+                content,
+            } if id == tool_use_id => Some(content.as_str()),
+            _ => None,
+        })
+}
+
+/// One-line summary of a tool call's input
+fn summarize_input(input: &Value) -> String {
+    for key in ["file_path", "path", "command", "pattern", "url", "query"] {
+        if let Some(value) = input.get(key).and_then(Value::as_str) {
+            return value.lines().next().unwrap_or_default().to_string();
+        }
+    }
+    let json = input.to_string();
+    if json.chars().count() > 80 {
+        format!("{}…", json.chars().take(80).collect::<String>())
+    } else {
+        json
+    }
+}
+
+/// The diff an edit or write made, rebuilt from the tool input
+fn recorded_diff(name: &str, input: &Value) -> Option<String> {
+    let path = input.get("file_path")?.as_str()?;
+    match name {
+        "edit_file" => Some(unified_diff(
+            path,
+            input.get("old_string")?.as_str()?,
+            input.get("new_string")?.as_str()?,
+        )),
+        "write_file" => Some(unified_diff(path, "", input.get("content")?.as_str()?)),
+        _ => None,
+    }
+}
+
+fn unified_diff(path: &str, old: &str, new: &str) -> String {
+    similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(3)
+        .header(path, path)
+        .to_string()
+}
+
+fn preview(marker: &str, output: &str) -> String {
+    let total = output.lines().count();
+    let mut out = String::new();
+    for line in output.lines().take(OUTPUT_PREVIEW_LINES) {
+        out.push_str(&format!("  {} {}\n", marker, line));
+    }
+    if total > OUTPUT_PREVIEW_LINES {
+        out.push_str(&format!(
+            "  {} … {} more lines\n",
+            marker,
+            total - OUTPUT_PREVIEW_LINES
+        ));
+    }
+    out
+}
+
+/// Rewrite absolute paths under `from` so they point under `to`
+pub fn rebase_paths(value: &Value, from: &Path, to: &Path) -> Value {
+    match value {
+        Value::String(s) => {
+            let from = from.to_string_lossy();
+            match s.strip_prefix(from.as_ref()) {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                    Value::String(format!("{}{}", to.to_string_lossy(), rest))
+                }
+                _ => Value::String(
+                    s.replace(&format!("{}/", from), &format!("{}/", to.to_string_lossy())),
+                ),
+            }
+        }
+        Value::Array(items) => {
+            Value::Array(items.iter().map(|v| rebase_paths(v, from, to)).collect())
+        }
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), rebase_paths(v, from, to)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+/// A detached git worktree of the project's HEAD that tool calls are re-run in
+pub struct Scratch {
+    project_path: PathBuf,
+    dir: tempfile::TempDir,
+    registry: ToolRegistry,
+    tools: ToolConfig,
+}
+
+impl Scratch {
+    pub async fn new(project_path: &Path, tools: ToolConfig) -> Result<Self> {
+        let dir = tempfile::Builder::new()
+            .prefix("safe-coder-replay-")
+            .tempdir()
+            .context("Failed to create scratch directory")?;
+
+        let output = Command::new("git")
+            .current_dir(project_path)
+            .args(["worktree", "add", "--detach"])
+            .arg(dir.path())
+            .arg("HEAD")
+            .output()
+            .await
+            .context("Failed to run git")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "Failed to create scratch worktree: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+
+        Ok(Self {
+            project_path: project_path.to_path_buf(),
+            dir,
+            registry: ToolRegistry::new_without_subagents(),
+            tools,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    /// Run a recorded tool call again and render what happened
+    async fn rerun(&self, name: &str, input: &Value, recorded: Option<&str>) -> String {
+        if SKIPPED_TOOLS.contains(&name) {
+            return format!("  ⟳ {} is not re-run\n", name);
+        }
+        let Some(tool) = self.registry.get_tool(name) else {
+            return format!("  ⟳ {} is not available outside a session\n", name);
+        };
+
+        let input = rebase_paths(input, &self.project_path, self.path());
+        let target = input
+            .get("file_path")
+            .and_then(Value::as_str)
+            .map(|p| self.path().join(p));
+        let before = target
+            .as_ref()
+            .map(|p| std::fs::read_to_string(p).unwrap_or_default());
+
+        let ctx = ToolContext::new(self.path(), &self.tools);
+        let result = tool.execute(input.clone(), &ctx).await;
+
+        let mut out = String::new();
+        if let (Some(target), Some(before)) = (&target, before) {
+            let after = std::fs::read_to_string(target).unwrap_or_default();
+            if after != before {
+                let shown = target.strip_prefix(self.path()).unwrap_or(target);
+                out.push_str(&unified_diff(&shown.to_string_lossy(), &before, &after));
+            }
+        }
+        match result {
+            Ok(output) if Some(output.as_str()) == recorded => {
+                out.push_str("  ⟳ same output as recorded\n");
+            }
+            Ok(output) => out.push_str(&preview("⟳", &output)),
+            Err(e) => out.push_str(&format!("  ⟳ error: {}\n", e)),
+        }
+        out
+    }
+
+    /// Remove the worktree from the project's git metadata
+    pub async fn cleanup(self) {
+        let _ = Command::new("git")
+            .current_dir(&self.project_path)
+            .args(["worktree", "remove", "--force"])
+            .arg(self.dir.path())
+            .output()
+            .await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_split_turns_keeps_tool_results_in_turn() {
+        let messages = vec![
+            Message::user("fix it".to_string()),
+            Message {
+                role: Role::Assistant,
+                content: vec![ContentBlock::ToolUse {
+                    id: "t1".to_string(),
+                    name: "read_file".to_string(),
+                    input: json!({"file_path": "a.rs"}),
+                }],
+            },
+            Message {
+                role: Role::User,
+                content: vec![ContentBlock::ToolResult {
+                    tool_use_id: "t1".to_string(),
+                    content: "fn main() {}".to_string(),
+                }],
+            },
+            Message::assistant(vec![ContentBlock::Text {
+                text: "done".to_string(),
+            }]),
+            Message::user("thanks".to_string()),
+        ];
+
+        let turns = split_turns(messages);
+        assert_eq!(turns.len(), 2);
+        assert_eq!(turns[0].len(), 4);
+        assert_eq!(find_result(&turns[0], "t1"), Some("fn main() {}"));
+    }
+
+    #[test]
+    fn test_rebase_paths() {
+        let input = json!({
+            "file_path": "/work/project/src/main.rs",
+            "command": "cat /work/project/Cargo.toml",
+            "other": "/work/projects/x",
+        });
+        let rebased = rebase_paths(&input, Path::new("/work/project"), Path::new("/tmp/s"));
+        assert_eq!(rebased["file_path"], "/tmp/s/src/main.rs");
+        assert_eq!(rebased["command"], "cat /tmp/s/Cargo.toml");
+        assert_eq!(rebased["other"], "/work/projects/x");
+    }
+}
@@ -0,0 +1,315 @@
+//! Encrypted session sharing
+//!
+//! `/chat share <id>` renders a saved session to Markdown, encrypts it along
+//! with the raw messages under a freshly generated key, and uploads only the
+//! ciphertext to a secret GitHub gist or a paste service. The URL and the
+//! key are printed separately; both are needed to read it.
+//! `safe-coder import-share <url> --key <key>` downloads and decrypts a share
+//! and saves it as a chat session.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use anyhow::{anyhow, Context, Result};
+use base64::engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::config::{ShareConfig, ShareService};
+use crate::llm::{ContentBlock, Message, Role};
+
+/// First line of every upload, so a wrong URL fails with a clear error
+const HEADER: &str = "safe-coder-share v1";
+
+/// File name of the upload inside a gist
+const GIST_FILE: &str = "safe-coder-share.txt";
+
+/// Description of every gist; the chat's name is only in the encrypted body
+const GIST_DESCRIPTION: &str = "safe-coder shared session";
+
+const NONCE_LEN: usize = 12;
+
+/// Lines of tool output kept in the Markdown transcript
+const TOOL_OUTPUT_LINES: usize = 40;
+
+/// What a share decrypts to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SharedChat {
+    pub name: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Readable transcript
+    pub markdown: String,
+    /// The conversation, for loading it back
+    pub messages: Vec<Message>,
+}
+
+impl SharedChat {
+    pub fn new(name: Option<String>, messages: Vec<Message>) -> Self {
+        Self {
+            markdown: render_markdown(name.as_deref(), &messages),
+            name,
+            created_at: Utc::now(),
+            messages,
+        }
+    }
+}
+
+/// Where a share was uploaded and the key that opens it
+#[derive(Debug, Clone)]
+pub struct Share {
+    pub url: String,
+    pub key: String,
+}
+
+/// Encrypt and upload a chat
+pub async fn share(chat: &SharedChat, config: &ShareConfig) -> Result<Share> {
+    let key = generate_key();
+    let body = encrypt(chat, &key)?;
+    let url = match config.service {
+        ShareService::Gist => upload_gist(&body).await?,
+        ShareService::Paste => {
+            let paste_url = config
+                .paste_url
+                .as_deref()
+                .context("[share] service = \"paste\" needs paste_url")?;
+            upload_paste(paste_url, &body).await?
+        }
+    };
+    Ok(Share { url, key })
+}
+
+/// Download and decrypt a share
+pub async fn import(url: &str, key: &str) -> Result<SharedChat> {
+    let body = match gist_id(url) {
+        Some(id) => download_gist(&id).await?,
+        None => download(url).await?,
+    };
+    decrypt(&body, key)
+}
+
+/// 32 random bytes, URL-safe base64
+pub fn generate_key() -> String {
+    let mut key = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut key);
+    URL_SAFE_NO_PAD.encode(key)
+}
+
+fn cipher(key: &str) -> Result<Aes256Gcm> {
+    let key = URL_SAFE_NO_PAD
+        .decode(key.trim())
+        .ok()
+        .filter(|k| k.len() == 32)
+        .context("Invalid share key")?;
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+pub fn encrypt(chat: &SharedChat, key: &str) -> Result<String> {
+    let plaintext = serde_json::to_vec(chat)?;
+    let mut nonce = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let ciphertext = cipher(key)?
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_slice())
+        .map_err(|_| anyhow!("Failed to encrypt the session"))?;
+    let mut data = nonce.to_vec();
+    data.extend(ciphertext);
+    Ok(format!("{}\n{}\n", HEADER, STANDARD.encode(data)))
+}
+
+pub fn decrypt(body: &str, key: &str) -> Result<SharedChat> {
+    let encoded = body
+        .trim()
+        .strip_prefix(HEADER)
+        .context("Not a safe-coder share")?;
+    let data = STANDARD
+        .decode(encoded.trim())
+        .ok()
+        .filter(|d| d.len() > NONCE_LEN)
+        .context("Share is corrupt")?;
+    let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+    let plaintext = cipher(key)?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow!("Failed to decrypt the share; check the key"))?;
+    serde_json::from_slice(&plaintext).context("Share is corrupt")
+}
+
+/// Render a conversation as Markdown, with tool output shortened
+pub fn render_markdown(name: Option<&str>, messages: &[Message]) -> String {
+    let mut out = format!("# {}\n", name.unwrap_or("Safe Coder session"));
+    for message in messages {
+        for block in &message.content {
+            match (&message.role, block) {
+                (Role::User, ContentBlock::Text { text }) => {
+                    out.push_str(&format!("\n## User\n\n{}\n", text.trim()));
+                }
+                (Role::Assistant, ContentBlock::Text { text }) => {
+                    out.push_str(&format!("\n## Assistant\n\n{}\n", text.trim()));
+                }
+                (_, ContentBlock::Image { media_type, .. }) => {
+                    out.push_str(&format!("\n*[{} image]*\n", media_type));
+                }
+                (_, ContentBlock::ToolUse { name, input, .. }) => {
+                    out.push_str(&format!(
+                        "\n**Tool: `{}`**\n\n```json\n{}\n```\n",
+                        name,
+                        serde_json::to_string_pretty(input).unwrap_or_default()
+                    ));
+                }
+                (_, ContentBlock::ToolResult { content, .. }) => {
+                    let lines: Vec<&str> = content.lines().collect();
+                    let mut shown = lines
+                        .iter()
+                        .take(TOOL_OUTPUT_LINES)
+                        .copied()
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    if lines.len() > TOOL_OUTPUT_LINES {
+                        shown.push_str(&format!(
+                            "\n... ({} more lines)",
+                            lines.len() - TOOL_OUTPUT_LINES
+                        ));
+                    }
+                    out.push_str(&format!("\n```\n{}\n```\n", shown));
+                }
+                (_, ContentBlock::Thinking { .. }) => {}
+            }
+        }
+    }
+    out
+}
+
+fn http_client() -> Result<reqwest::Client> {
+    Ok(reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent("SafeCoder/1.0")
+        .build()?)
+}
+
+async fn upload_gist(body: &str) -> Result<String> {
+    let token = crate::tools::ci_status::github_token().await.context(
+        "Sharing to a gist needs GitHub access: set GITHUB_TOKEN or run `gh auth login`, \
+         or set [share] service = \"paste\"",
+    )?;
+    let response = http_client()?
+        .post("https://api.github.com/gists")
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .json(&serde_json::json!({
+            "description": GIST_DESCRIPTION,
+            "public": false,
+            "files": { GIST_FILE: { "content": body } },
+        }))
+        .send()
+        .await
+        .context("Failed to create gist")?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("GitHub returned {} creating the gist", status);
+    }
+    let gist: serde_json::Value = response.json().await?;
+    gist["html_url"]
+        .as_str()
+        .map(str::to_string)
+        .context("GitHub didn't return the gist's URL")
+}
+
+async fn upload_paste(paste_url: &str, body: &str) -> Result<String> {
+    let response = http_client()?
+        .post(paste_url)
+        .header("Content-Type", "text/plain")
+        .body(body.to_string())
+        .send()
+        .await
+        .with_context(|| format!("Failed to upload to {}", paste_url))?;
+    let status = response.status();
+    let text = response.text().await?;
+    let url = text.trim();
+    if !status.is_success() || !(url.starts_with("https://") || url.starts_with("http://")) {
+        anyhow::bail!("{} returned {}: {}", paste_url, status, url);
+    }
+    Ok(url.to_string())
+}
+
+/// The gist ID in `https://gist.github.com/<user>/<id>` or `.../<id>`
+fn gist_id(url: &str) -> Option<String> {
+    let path = url
+        .strip_prefix("https://gist.github.com/")?
+        .split(['#', '?'])
+        .next()?;
+    let id = path.trim_end_matches('/').rsplit('/').next()?;
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+async fn download_gist(id: &str) -> Result<String> {
+    let client = http_client()?;
+    let mut request = client
+        .get(format!("https://api.github.com/gists/{}", id))
+        .header("Accept", "application/vnd.github+json");
+    // Secret gists are readable without a token, but rate limits are lower
+    if let Some(token) = crate::tools::ci_status::github_token().await {
+        request = request.bearer_auth(token);
+    }
+    let response = request.send().await.context("Failed to fetch gist")?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("GitHub returned {} for gist {}", status, id);
+    }
+    let gist: serde_json::Value = response.json().await?;
+    let file = &gist["files"][GIST_FILE];
+    // Large files are truncated in the API response
+    match (file["truncated"].as_bool(), file["content"].as_str()) {
+        (Some(false), Some(content)) => Ok(content.to_string()),
+        _ => {
+            let raw_url = file["raw_url"]
+                .as_str()
+                .context("Gist is not a safe-coder share")?;
+            download(raw_url).await
+        }
+    }
+}
+
+async fn download(url: &str) -> Result<String> {
+    let response = http_client()?
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch {}", url))?;
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("Failed to fetch {}: HTTP {}", url, status.as_u16());
+    }
+    Ok(response.text().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encrypt_round_trip() {
+        let chat = SharedChat::new(
+            Some("fix login".to_string()),
+            vec![Message::user("why does login fail?".to_string())],
+        );
+        assert!(chat.markdown.contains("## User\n\nwhy does login fail?"));
+
+        let key = generate_key();
+        let body = encrypt(&chat, &key).unwrap();
+        assert!(!body.contains("login"));
+
+        let opened = decrypt(&body, &key).unwrap();
+        assert_eq!(opened.name.as_deref(), Some("fix login"));
+        assert_eq!(opened.messages.len(), 1);
+        assert!(decrypt(&body, &generate_key()).is_err());
+    }
+
+    #[test]
+    fn test_gist_id() {
+        assert_eq!(
+            gist_id("https://gist.github.com/octocat/aa5a315d61ae9438b18d"),
+            Some("aa5a315d61ae9438b18d".to_string())
+        );
+        assert_eq!(gist_id("https://paste.rs/abc"), None);
+    }
+}
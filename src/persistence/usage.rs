@@ -0,0 +1,344 @@
+//! Usage analytics across sessions for `safe-coder stats`
+//!
+//! Sessions record their stats in the session database after every turn.
+//! This module rolls those records up by day, model and project and renders
+//! the result as terminal tables, CSV or JSON. Costs are estimates from the
+//! list prices in the model registry; local models count as free.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::models::SessionStatsRecord;
+
+/// Estimated cost in USD of a model's token usage, at the list prices in
+/// the model registry
+pub fn estimate_cost(model: &str, input_tokens: usize, output_tokens: usize) -> f64 {
+    crate::llm::models::registry()
+        .lookup(model)
+        .map(|info| info.cost(input_tokens, output_tokens))
+        .unwrap_or(0.0)
+}
+
+/// Token and cost totals for one day, model or project
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct UsageTotals {
+    pub sessions: usize,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub cost_usd: f64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &SessionStatsRecord, cost: f64) {
+        self.sessions += 1;
+        self.input_tokens += record.stats.total_tokens_sent;
+        self.output_tokens += record.stats.total_tokens_received;
+        self.cost_usd += cost;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ToolTotals {
+    pub tool: String,
+    pub calls: usize,
+    pub failures: usize,
+}
+
+/// Usage rolled up across sessions
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageReport {
+    pub totals: UsageTotals,
+    /// Keyed by `YYYY-MM-DD` (UTC)
+    pub by_day: BTreeMap<String, UsageTotals>,
+    pub by_model: BTreeMap<String, UsageTotals>,
+    pub by_project: BTreeMap<String, UsageTotals>,
+    /// Most used first
+    pub tools: Vec<ToolTotals>,
+    pub tool_calls: usize,
+    pub tool_failures: usize,
+    pub average_session_secs: i64,
+    pub average_messages: f64,
+    /// Sessions that ended cleanly rather than crashing or being killed
+    pub completed_sessions: usize,
+}
+
+impl UsageReport {
+    pub fn build(records: &[SessionStatsRecord]) -> Self {
+        let mut report = Self::default();
+        let mut tools: BTreeMap<String, ToolTotals> = BTreeMap::new();
+        let mut total_secs = 0;
+        let mut total_messages = 0;
+
+        for record in records {
+            let stats = &record.stats;
+            let cost = estimate_cost(
+                &record.model,
+                stats.total_tokens_sent,
+                stats.total_tokens_received,
+            );
+            report.totals.add(record, cost);
+            report
+                .by_day
+                .entry(record.created_at.format("%Y-%m-%d").to_string())
+                .or_default()
+                .add(record, cost);
+            report
+                .by_model
+                .entry(record.model.clone())
+                .or_default()
+                .add(record, cost);
+            report
+                .by_project
+                .entry(record.project_path.clone())
+                .or_default()
+                .add(record, cost);
+
+            for usage in &stats.tools_used {
+                let tool = tools
+                    .entry(usage.tool_name.clone())
+                    .or_insert_with(|| ToolTotals {
+                        tool: usage.tool_name.clone(),
+                        calls: 0,
+                        failures: 0,
+                    });
+                tool.calls += usage.count;
+                tool.failures += usage.failures;
+            }
+            report.tool_calls += stats.total_tool_calls;
+            report.tool_failures += stats.total_tool_failures;
+            total_secs += stats.session_duration_secs;
+            total_messages += stats.total_messages;
+            if record.completed {
+                report.completed_sessions += 1;
+            }
+        }
+
+        if !records.is_empty() {
+            report.average_session_secs = total_secs / records.len() as i64;
+            report.average_messages = total_messages as f64 / records.len() as f64;
+        }
+        report.tools = tools.into_values().collect();
+        report
+            .tools
+            .sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.tool.cmp(&b.tool)));
+        report
+    }
+
+    /// Share of tool calls that succeeded, as a percentage
+    pub fn tool_success_rate(&self) -> f64 {
+        if self.tool_calls == 0 {
+            return 100.0;
+        }
+        (self.tool_calls - self.tool_failures.min(self.tool_calls)) as f64 * 100.0
+            / self.tool_calls as f64
+    }
+
+    /// Terminal tables, listing at most `top_tools` tools
+    pub fn format_tables(&self, top_tools: usize) -> String {
+        if self.totals.sessions == 0 {
+            return "No recorded sessions in this period.\n".to_string();
+        }
+
+        let mut output = String::new();
+        output.push_str(&format!(
+            "Sessions:  {} ({} ended cleanly, {} interrupted)\n",
+            self.totals.sessions,
+            self.completed_sessions,
+            self.totals.sessions - self.completed_sessions
+        ));
+        output.push_str(&format!(
+            "Tokens:    {} in / {} out (~${:.2})\n",
+            self.totals.input_tokens, self.totals.output_tokens, self.totals.cost_usd
+        ));
+        output.push_str(&format!(
+            "Average:   {} per session, {:.1} messages\n",
+            format_duration(self.average_session_secs),
+            self.average_messages
+        ));
+        output.push_str(&format!(
+            "Tools:     {} calls, {:.1}% succeeded\n",
+            self.tool_calls,
+            self.tool_success_rate()
+        ));
+
+        for (title, header, rows) in [
+            ("By day", "Date", &self.by_day),
+            ("By model", "Model", &self.by_model),
+            ("By project", "Project", &self.by_project),
+        ] {
+            output.push_str(&format!("\n{}\n", title));
+            output.push_str(&totals_table(header, rows));
+        }
+
+        if !self.tools.is_empty() {
+            output.push_str("\nMost used tools\n");
+            output.push_str(&format!(
+                "  {:<20} {:>8} {:>8} {:>9}\n",
+                "Tool", "Calls", "Failed", "Success"
+            ));
+            for tool in self.tools.iter().take(top_tools) {
+                let success = if tool.calls == 0 {
+                    100.0
+                } else {
+                    (tool.calls - tool.failures.min(tool.calls)) as f64 * 100.0 / tool.calls as f64
+                };
+                output.push_str(&format!(
+                    "  {:<20} {:>8} {:>8} {:>8.1}%\n",
+                    tool.tool, tool.calls, tool.failures, success
+                ));
+            }
+        }
+        output
+    }
+
+    /// One row per day, model, project and tool
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from(
+            "group,key,sessions,input_tokens,output_tokens,cost_usd,tool_calls,tool_failures\n",
+        );
+        for (group, rows) in [
+            ("day", &self.by_day),
+            ("model", &self.by_model),
+            ("project", &self.by_project),
+        ] {
+            for (key, totals) in rows {
+                csv.push_str(&format!(
+                    "{},{},{},{},{},{:.4},,\n",
+                    group,
+                    csv_field(key),
+                    totals.sessions,
+                    totals.input_tokens,
+                    totals.output_tokens,
+                    totals.cost_usd
+                ));
+            }
+        }
+        for tool in &self.tools {
+            csv.push_str(&format!(
+                "tool,{},,,,,{},{}\n",
+                csv_field(&tool.tool),
+                tool.calls,
+                tool.failures
+            ));
+        }
+        csv
+    }
+}
+
+fn totals_table(header: &str, rows: &BTreeMap<String, UsageTotals>) -> String {
+    let width = rows
+        .keys()
+        .map(|k| k.chars().count())
+        .max()
+        .unwrap_or(0)
+        .clamp(header.len(), 60);
+    let mut table = format!(
+        "  {:<width$} {:>8} {:>12} {:>12} {:>10}\n",
+        header,
+        "Sessions",
+        "Input",
+        "Output",
+        "Cost",
+        width = width
+    );
+    for (key, totals) in rows {
+        table.push_str(&format!(
+            "  {:<width$} {:>8} {:>12} {:>12} {:>10}\n",
+            key,
+            totals.sessions,
+            totals.input_tokens,
+            totals.output_tokens,
+            format!("${:.2}", totals.cost_usd),
+            width = width
+        ));
+    }
+    table
+}
+
+fn format_duration(secs: i64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, (secs % 3600) / 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else {
+        format!("{}m {}s", minutes, seconds)
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persistence::{SessionStats, ToolUsage};
+    use chrono::{TimeZone, Utc};
+
+    fn record(model: &str, project: &str, day: u32, completed: bool) -> SessionStatsRecord {
+        let mut stats = SessionStats::new();
+        stats.total_messages = 4;
+        stats.total_tokens_sent = 1_000_000;
+        stats.total_tokens_received = 100_000;
+        stats.total_tool_calls = 4;
+        stats.total_tool_failures = 1;
+        stats.session_duration_secs = 600;
+        stats.tools_used = vec![
+            ToolUsage {
+                tool_name: "read_file".to_string(),
+                count: 3,
+                failures: 0,
+            },
+            ToolUsage {
+                tool_name: "bash".to_string(),
+                count: 1,
+                failures: 1,
+            },
+        ];
+        let created_at = Utc.with_ymd_and_hms(2026, 10, day, 12, 0, 0).unwrap();
+        SessionStatsRecord {
+            session_id: format!("{}-{}", project, day),
+            project_path: project.to_string(),
+            model: model.to_string(),
+            stats,
+            completed,
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    #[test]
+    fn test_estimate_cost() {
+        assert_eq!(estimate_cost("claude-sonnet-4-20250514", 1_000_000, 0), 3.0);
+        assert_eq!(estimate_cost("gpt-4o-mini", 0, 1_000_000), 0.6);
+        assert_eq!(estimate_cost("llama3.2", 1_000_000, 1_000_000), 0.0);
+    }
+
+    #[test]
+    fn test_report_groups_and_ratios() {
+        let records = vec![
+            record("claude-sonnet-4", "/work/app", 1, true),
+            record("claude-sonnet-4", "/work/app", 2, false),
+            record("llama3.2", "/work/lib", 2, true),
+        ];
+        let report = UsageReport::build(&records);
+
+        assert_eq!(report.totals.sessions, 3);
+        assert_eq!(report.by_day["2026-10-02"].sessions, 2);
+        assert_eq!(report.by_model["claude-sonnet-4"].cost_usd, 9.0);
+        assert_eq!(report.by_project["/work/lib"].cost_usd, 0.0);
+        assert_eq!(report.tools[0].tool, "read_file");
+        assert_eq!(report.tools[0].calls, 9);
+        assert_eq!(report.tool_success_rate(), 75.0);
+        assert_eq!(report.completed_sessions, 2);
+        assert_eq!(report.average_session_secs, 600);
+
+        let csv = report.to_csv();
+        assert!(csv.contains("model,claude-sonnet-4,2,2000000,200000,9.0000,,"));
+        assert!(csv.contains("tool,bash,,,,,3,3"));
+        assert!(report.format_tables(10).contains("By project"));
+    }
+}
@@ -0,0 +1,372 @@
+//! Plan artifacts saved to disk
+//!
+//! Plans made in PLAN mode are written to `.safe-coder/plans/<id>.md` so
+//! they outlive the chat: BUILD mode (in this session or a later one) ticks
+//! steps off through the `update_plan` tool, and `/plan status` shows what
+//! is left. The file is readable Markdown with the full plan embedded as
+//! JSON in a trailing comment; step checkboxes win over the embedded JSON,
+//! so ticking a box by hand is picked up too.
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+
+use super::types::{PlanStatus, PlanStepStatus, TaskPlan};
+
+const JSON_START: &str = "<!-- safe-coder:plan";
+const JSON_END: &str = "-->";
+
+/// Directory of saved plans for one project
+pub struct PlanStore {
+    dir: PathBuf,
+}
+
+impl PlanStore {
+    pub fn new(project_path: &Path) -> Self {
+        Self {
+            dir: project_path.join(".safe-coder").join("plans"),
+        }
+    }
+
+    pub fn path_for(&self, id: &str) -> PathBuf {
+        self.dir.join(format!("{}.md", id))
+    }
+
+    pub fn save(&self, plan: &TaskPlan) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir).context("Failed to create plans directory")?;
+        let path = self.path_for(&plan.id);
+        std::fs::write(&path, to_markdown(plan))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    pub fn load(&self, id: &str) -> Result<TaskPlan> {
+        let path = self.path_for(id);
+        let content =
+            std::fs::read_to_string(&path).with_context(|| format!("No saved plan '{}'", id))?;
+        from_markdown(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// Every readable plan, newest first
+    pub fn list(&self) -> Vec<TaskPlan> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut plans: Vec<TaskPlan> = entries
+            .flatten()
+            .filter(|e| e.path().extension().is_some_and(|ext| ext == "md"))
+            .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+            .filter_map(|content| from_markdown(&content).ok())
+            .collect();
+        plans.sort_by_key(|plan| std::cmp::Reverse(plan.created_at));
+        plans
+    }
+
+    /// The newest plan that still has work left
+    pub fn latest_open(&self) -> Option<TaskPlan> {
+        self.list().into_iter().find(|p| !p.status.is_terminal())
+    }
+
+    /// Load a plan by id, or the newest open one
+    pub fn resolve(&self, id: Option<&str>) -> Result<TaskPlan> {
+        match id {
+            Some(id) => self.load(id),
+            None => self
+                .latest_open()
+                .context("No open plan in .safe-coder/plans. Create one in PLAN mode first."),
+        }
+    }
+
+    /// Set the status of step `number` (1-based) and save the plan
+    pub fn update_step(
+        &self,
+        id: Option<&str>,
+        number: usize,
+        status: PlanStepStatus,
+        note: Option<String>,
+    ) -> Result<TaskPlan> {
+        let mut plan = self.resolve(id)?;
+        let total = plan.steps.len();
+        let step = number
+            .checked_sub(1)
+            .and_then(|i| plan.steps.get_mut(i))
+            .with_context(|| format!("Plan '{}' has no step {} (1-{})", plan.id, number, total))?;
+        step.status = status;
+        match status {
+            PlanStepStatus::Failed => step.error = note,
+            _ => {
+                if note.is_some() {
+                    step.output = note;
+                }
+            }
+        }
+
+        refresh_status(&mut plan);
+        self.save(&plan)?;
+        Ok(plan)
+    }
+}
+
+/// Derive the overall status from the steps once execution has begun
+fn refresh_status(plan: &mut TaskPlan) {
+    if plan.steps.iter().all(|s| s.status.is_terminal()) {
+        plan.status = if plan
+            .steps
+            .iter()
+            .any(|s| s.status == PlanStepStatus::Failed)
+        {
+            PlanStatus::Failed
+        } else {
+            PlanStatus::Completed
+        };
+        plan.completed_at.get_or_insert_with(Utc::now);
+    } else if plan
+        .steps
+        .iter()
+        .any(|s| s.status != PlanStepStatus::Pending)
+    {
+        plan.status = PlanStatus::Executing;
+        plan.started_at.get_or_insert_with(Utc::now);
+    }
+}
+
+fn checkbox(status: PlanStepStatus) -> char {
+    match status {
+        PlanStepStatus::Pending => ' ',
+        PlanStepStatus::InProgress => '~',
+        PlanStepStatus::Completed => 'x',
+        PlanStepStatus::Failed => '!',
+        PlanStepStatus::Skipped => '-',
+    }
+}
+
+fn status_from_checkbox(c: char) -> Option<PlanStepStatus> {
+    match c {
+        ' ' => Some(PlanStepStatus::Pending),
+        '~' => Some(PlanStepStatus::InProgress),
+        'x' | 'X' => Some(PlanStepStatus::Completed),
+        '!' => Some(PlanStepStatus::Failed),
+        '-' => Some(PlanStepStatus::Skipped),
+        _ => None,
+    }
+}
+
+/// Render a plan as Markdown with the structured plan embedded
+pub fn to_markdown(plan: &TaskPlan) -> String {
+    let mut md = format!("# Plan: {}\n\n", plan.title);
+    md.push_str(&format!("- **ID:** {}\n", plan.id));
+    md.push_str(&format!("- **Status:** {:?}\n", plan.status));
+    md.push_str(&format!(
+        "- **Created:** {}\n",
+        plan.created_at.format("%Y-%m-%d %H:%M UTC")
+    ));
+    md.push_str(&format!("- **Request:** {}\n\n", plan.request.trim()));
+
+    md.push_str("## Steps\n\n");
+    for (i, step) in plan.steps.iter().enumerate() {
+        md.push_str(&format!(
+            "- [{}] {}. {}\n",
+            checkbox(step.status),
+            i + 1,
+            step.description
+        ));
+        if !step.relevant_files.is_empty() {
+            let files: Vec<String> = step
+                .relevant_files
+                .iter()
+                .map(|f| format!("`{}`", f))
+                .collect();
+            md.push_str(&format!("  - Files: {}\n", files.join(", ")));
+        }
+        if let Some(error) = &step.error {
+            md.push_str(&format!("  - Failed: {}\n", error));
+        }
+    }
+
+    if !plan.risks.is_empty() {
+        md.push_str("\n## Risks\n\n");
+        for risk in &plan.risks {
+            md.push_str(&format!("- {}\n", risk));
+        }
+    }
+
+    md.push_str(&format!(
+        "\n{}\n{}\n{}\n",
+        JSON_START,
+        // A literal "-->" in any text would end the comment early
+        serde_json::to_string_pretty(plan)
+            .unwrap_or_default()
+            .replace("-->", "--\\u003e"),
+        JSON_END
+    ));
+    md
+}
+
+/// Parse a saved plan, applying any checkbox edits made by hand
+pub fn from_markdown(content: &str) -> Result<TaskPlan> {
+    let start = content
+        .rfind(JSON_START)
+        .context("Plan file has no embedded plan data")?
+        + JSON_START.len();
+    let end = content[start..]
+        .find(JSON_END)
+        .context("Plan data is not terminated")?
+        + start;
+    let mut plan: TaskPlan = serde_json::from_str(content[start..end].trim())?;
+
+    let step_line = regex::Regex::new(r"^- \[(.)\] (\d+)\. ").unwrap();
+    let mut changed = false;
+    for line in content[..start].lines() {
+        let Some(caps) = step_line.captures(line) else {
+            continue;
+        };
+        let status = caps[1].chars().next().and_then(status_from_checkbox);
+        let index = caps[2].parse::<usize>().ok().and_then(|n| n.checked_sub(1));
+        if let (Some(status), Some(step)) = (status, index.and_then(|i| plan.steps.get_mut(i))) {
+            if step.status != status {
+                step.status = status;
+                changed = true;
+            }
+        }
+    }
+    if changed {
+        refresh_status(&mut plan);
+    }
+    Ok(plan)
+}
+
+/// Progress summary with the steps still to do, for `/plan status`
+pub fn format_status(plan: &TaskPlan) -> String {
+    let mut output = format!(
+        "📋 {} ({})\n   {}/{} steps done · {:?}\n",
+        plan.title,
+        plan.id,
+        plan.completed_count(),
+        plan.steps.len(),
+        plan.status
+    );
+    let remaining: Vec<_> = plan
+        .steps
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.status.is_terminal())
+        .collect();
+    if remaining.is_empty() {
+        output.push_str("\nNo steps remaining.\n");
+    } else {
+        output.push_str("\nRemaining:\n");
+        for (i, step) in remaining {
+            output.push_str(&format!(
+                "  {} {}. {}\n",
+                step.status.icon(),
+                i + 1,
+                step.description
+            ));
+        }
+    }
+    if let Some(failed) = plan
+        .steps
+        .iter()
+        .position(|s| s.status == PlanStepStatus::Failed)
+    {
+        output.push_str(&format!("\nStep {} failed.\n", failed + 1));
+    }
+    output
+}
+
+/// One line per saved plan, for `/plan list`
+pub fn format_plan_list(plans: &[TaskPlan]) -> String {
+    if plans.is_empty() {
+        return "No saved plans. Plans created in PLAN mode are saved to .safe-coder/plans/."
+            .to_string();
+    }
+    let mut output = String::from("📋 Saved plans:\n\n");
+    for plan in plans {
+        output.push_str(&format!(
+            "  {:<14} {:>2}/{:<2} {:<16} {}\n",
+            plan.id,
+            plan.completed_count(),
+            plan.steps.len(),
+            format!("{:?}", plan.status),
+            plan.title
+        ));
+    }
+    output.push_str("\nUse /plan resume <id> to continue one.");
+    output
+}
+
+/// The message that asks the agent to carry on with a saved plan
+pub fn resume_prompt(plan: &TaskPlan, path: &Path) -> String {
+    format!(
+        "Continue the saved plan \"{}\" in {}.\n\n{}\n\
+        Work through the remaining steps in order. After finishing each step, \
+        call update_plan with its number and status.",
+        plan.title,
+        path.display(),
+        format_status(plan)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::planning::PlanStep;
+
+    fn sample_plan() -> TaskPlan {
+        let mut plan = TaskPlan::new("plan-1234".to_string(), "Add retries".to_string())
+            .with_title("Add retries to the client".to_string())
+            .with_steps(vec![
+                PlanStep::new("step-1".to_string(), "Add a backoff helper".to_string())
+                    .with_files(vec!["src/retry.rs".to_string()]),
+                PlanStep::new("step-2".to_string(), "Use it in the client".to_string()),
+            ]);
+        plan.risks = vec!["Retries may hide real outages".to_string()];
+        plan.status = PlanStatus::AwaitingApproval;
+        plan
+    }
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let plan = sample_plan();
+        let md = to_markdown(&plan);
+        assert!(md.contains("- [ ] 1. Add a backoff helper"));
+        assert!(md.contains("  - Files: `src/retry.rs`"));
+        assert!(md.contains("## Risks"));
+
+        let loaded = from_markdown(&md).unwrap();
+        assert_eq!(loaded.id, "plan-1234");
+        assert_eq!(loaded.steps.len(), 2);
+        assert_eq!(loaded.risks, plan.risks);
+        assert_eq!(loaded.status, PlanStatus::AwaitingApproval);
+    }
+
+    #[test]
+    fn test_checkbox_edits_win() {
+        let md = to_markdown(&sample_plan()).replace("- [ ] 1.", "- [x] 1.");
+        let plan = from_markdown(&md).unwrap();
+        assert_eq!(plan.steps[0].status, PlanStepStatus::Completed);
+        assert_eq!(plan.status, PlanStatus::Executing);
+    }
+
+    #[test]
+    fn test_update_step_and_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PlanStore::new(dir.path());
+        store.save(&sample_plan()).unwrap();
+
+        let plan = store
+            .update_step(None, 1, PlanStepStatus::Completed, None)
+            .unwrap();
+        assert_eq!(plan.status, PlanStatus::Executing);
+        assert!(format_status(&plan).contains("2. Use it in the client"));
+
+        let plan = store
+            .update_step(Some("plan-1234"), 2, PlanStepStatus::Completed, None)
+            .unwrap();
+        assert_eq!(plan.status, PlanStatus::Completed);
+        assert!(store.latest_open().is_none());
+        assert!(store
+            .update_step(Some("plan-1234"), 3, PlanStepStatus::Completed, None)
+            .is_err());
+    }
+}
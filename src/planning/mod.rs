@@ -3,11 +3,13 @@
 //! Provides structured task planning with complexity scoring and
 //! intelligent subagent assignment for complex steps.
 
+pub mod artifact;
 pub mod complexity;
 pub mod executor;
 pub mod planner;
 pub mod types;
 
+pub use artifact::PlanStore;
 pub use complexity::{calculate_complexity, complexity_from_score};
 pub use executor::PlanExecutor;
 pub use planner::TaskPlanner;
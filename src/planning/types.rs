@@ -223,6 +223,9 @@ pub struct TaskPlan {
     pub title: String,
     /// Ordered steps
     pub steps: Vec<PlanStep>,
+    /// Risks called out while planning
+    #[serde(default)]
+    pub risks: Vec<String>,
     /// Overall plan status
     pub status: PlanStatus,
     /// Timestamp when planning started
@@ -240,6 +243,7 @@ impl TaskPlan {
             request,
             title: String::new(),
             steps: Vec::new(),
+            risks: Vec::new(),
             status: PlanStatus::Planning,
             created_at: Utc::now(),
             started_at: None,
@@ -0,0 +1,162 @@
+//! Platform differences for running shell commands
+//!
+//! Unix runs commands with `sh -c`. Windows has no `sh` unless Git for
+//! Windows or MSYS is installed, so there the first available of `sh`,
+//! PowerShell 7 (`pwsh`), Windows PowerShell and `cmd` is used. Every place
+//! that runs a user, hook or model supplied command line goes through
+//! [`shell_command`] so they all agree on the shell.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tokio::process::Command;
+
+/// The shell that command lines are run with
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub enum Shell {
+    /// POSIX `sh`
+    Sh,
+    /// `pwsh` or `powershell`
+    Pwsh(&'static str),
+    /// `cmd.exe`
+    Cmd,
+}
+
+impl Shell {
+    /// The shell for this machine, detected once
+    pub fn current() -> &'static Shell {
+        static SHELL: OnceLock<Shell> = OnceLock::new();
+        SHELL.get_or_init(Self::detect)
+    }
+
+    #[cfg(not(windows))]
+    fn detect() -> Self {
+        Self::Sh
+    }
+
+    #[cfg(windows)]
+    fn detect() -> Self {
+        if which::which("sh").is_ok() {
+            Self::Sh
+        } else if which::which("pwsh").is_ok() {
+            Self::Pwsh("pwsh")
+        } else if which::which("powershell").is_ok() {
+            Self::Pwsh("powershell")
+        } else {
+            Self::Cmd
+        }
+    }
+
+    /// Name for prompts and tool descriptions
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sh => "sh",
+            Self::Pwsh(_) => "PowerShell",
+            Self::Cmd => "cmd.exe",
+        }
+    }
+
+    /// A command that runs `command_line` with this shell
+    pub fn command(&self, command_line: &str) -> Command {
+        match self {
+            Self::Sh => {
+                let mut cmd = Command::new("sh");
+                cmd.arg("-c").arg(command_line);
+                cmd
+            }
+            Self::Pwsh(program) => {
+                let mut cmd = Command::new(program);
+                cmd.args(["-NoProfile", "-NonInteractive", "-Command"])
+                    .arg(command_line);
+                cmd
+            }
+            Self::Cmd => {
+                let mut cmd = Command::new("cmd");
+                cmd.arg("/C");
+                // cmd does its own parsing, so the line must not be quoted again
+                #[cfg(windows)]
+                cmd.raw_arg(command_line);
+                #[cfg(not(windows))]
+                cmd.arg(command_line);
+                cmd
+            }
+        }
+    }
+}
+
+/// A command that runs `command_line` with the platform's shell
+pub fn shell_command(command_line: &str) -> Command {
+    Shell::current().command(command_line)
+}
+
+/// A command that forcibly stops process `pid`
+pub fn kill_command(pid: u32) -> Command {
+    #[cfg(windows)]
+    {
+        let mut cmd = Command::new("taskkill");
+        cmd.args(["/F", "/T", "/PID"]).arg(pid.to_string());
+        cmd
+    }
+    #[cfg(not(windows))]
+    {
+        let mut cmd = Command::new("kill");
+        cmd.arg("-9").arg(pid.to_string());
+        cmd
+    }
+}
+
+/// Expand a leading `~` to the home directory, accepting either separator
+pub fn expand_home(path: &str) -> Option<PathBuf> {
+    if path == "~" {
+        return dirs::home_dir();
+    }
+    let rest = path
+        .strip_prefix("~/")
+        .or_else(|| path.strip_prefix("~\\"))?;
+    dirs::home_dir().map(|home| home.join(rest))
+}
+
+/// Canonicalize a path without the `\\?\` prefix Windows adds, which many
+/// programs cannot handle as a working directory
+pub fn canonicalize(path: &Path) -> std::io::Result<PathBuf> {
+    let canonical = path.canonicalize()?;
+    #[cfg(windows)]
+    {
+        let text = canonical.to_string_lossy();
+        if let Some(rest) = text.strip_prefix(r"\\?\UNC\") {
+            return Ok(PathBuf::from(format!(r"\\{}", rest)));
+        }
+        if let Some(rest) = text.strip_prefix(r"\\?\") {
+            return Ok(PathBuf::from(rest));
+        }
+    }
+    Ok(canonical)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_commands() {
+        let sh = Shell::Sh.command("echo hi");
+        let args: Vec<_> = sh.as_std().get_args().collect();
+        assert_eq!(sh.as_std().get_program(), "sh");
+        assert_eq!(args, ["-c", "echo hi"]);
+
+        let pwsh = Shell::Pwsh("pwsh").command("Get-ChildItem");
+        let args: Vec<_> = pwsh.as_std().get_args().collect();
+        assert_eq!(args.last().unwrap(), &"Get-ChildItem");
+        assert_eq!(Shell::Cmd.name(), "cmd.exe");
+    }
+
+    #[test]
+    fn test_expand_home() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_home("~"), Some(home.clone()));
+        assert_eq!(expand_home("~/src"), Some(home.join("src")));
+        assert_eq!(expand_home("~\\src"), Some(home.join("src")));
+        assert_eq!(expand_home("src/~"), None);
+    }
+}
@@ -0,0 +1,436 @@
+//! Organization policy bundles
+//!
+//! A policy bundle is a git repository holding `policy.toml` and its
+//! Ed25519 signature `policy.toml.sig`. `safe-coder policy pull <git-url>
+//! --key <public-key>` clones it into `~/.config/safe-coder/policy/`, pinning
+//! the key on first pull. Every config load then verifies the signature and
+//! applies the policy over all config layers; a bundle whose signature does
+//! not verify stops safe-coder from running rather than being ignored.
+//!
+//! ```toml
+//! name = "acme"
+//!
+//! [permissions]
+//! preset = "safe"
+//! deny_categories = ["git_force_push", "privileged"]
+//!
+//! [commands]
+//! deny = ['terraform\s+destroy']
+//!
+//! [mcp]
+//! allowed_servers = ["github"]
+//!
+//! [update]
+//! enabled = false
+//!
+//! [[hooks.pre_tool]]
+//! matcher = "bash"
+//! command = "/opt/acme/audit-command.sh"
+//! ```
+
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+use crate::config::Config;
+use crate::hooks::{HookCommandConfig, HookType};
+use crate::permissions::CommandCategory;
+
+pub const POLICY_FILE: &str = "policy.toml";
+pub const SIGNATURE_FILE: &str = "policy.toml.sig";
+
+/// Where the bundle was pulled from and the key it must be signed with
+const SOURCE_FILE: &str = "source.toml";
+
+/// What a policy bundle enforces
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct Policy {
+    /// Shown in `policy show` and config origins
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub permissions: PolicyPermissions,
+    #[serde(default)]
+    pub commands: PolicyCommands,
+    #[serde(default)]
+    pub mcp: PolicyMcp,
+    #[serde(default)]
+    pub update: PolicyUpdate,
+    /// Hooks added to every session, keyed by event like `[hooks]`
+    #[serde(default)]
+    pub hooks: HashMap<HookType, Vec<HookCommandConfig>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PolicyPermissions {
+    /// Permission preset every session starts with
+    #[serde(default)]
+    pub preset: Option<String>,
+    /// Bash command categories that are always denied
+    #[serde(default)]
+    pub deny_categories: Vec<CommandCategory>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PolicyCommands {
+    /// Regex patterns of bash commands that are always blocked
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PolicyMcp {
+    /// MCP servers that may be configured; others are dropped (None = any)
+    #[serde(default)]
+    pub allowed_servers: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct PolicyUpdate {
+    /// Whether `safe-coder update` may replace the installed binary
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct PolicySource {
+    url: String,
+    /// Base64 Ed25519 public key
+    public_key: String,
+}
+
+/// The policy installed on this machine
+pub struct InstalledPolicy {
+    pub policy: Policy,
+    pub url: String,
+}
+
+impl InstalledPolicy {
+    /// `~/.config/safe-coder/policy/`
+    pub fn dir() -> Result<PathBuf> {
+        let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+        Ok(config_dir.join("safe-coder").join("policy"))
+    }
+
+    /// Load and verify the installed policy, if there is one
+    pub fn load() -> Result<Option<Self>> {
+        Self::load_from(&Self::dir()?)
+    }
+
+    fn load_from(dir: &Path) -> Result<Option<Self>> {
+        let source_path = dir.join(SOURCE_FILE);
+        if !source_path.exists() {
+            return Ok(None);
+        }
+        let source: PolicySource = toml::from_str(&std::fs::read_to_string(&source_path)?)
+            .with_context(|| format!("Failed to parse {}", source_path.display()))?;
+        let key = parse_public_key(&source.public_key)?;
+        let policy = read_bundle(&dir.join("bundle"), &key).with_context(|| {
+            format!(
+                "Refusing to run: the organization policy from {} failed verification. \
+                 Run `safe-coder policy pull {}` to fetch it again",
+                source.url, source.url
+            )
+        })?;
+        Ok(Some(Self {
+            policy,
+            url: source.url,
+        }))
+    }
+
+    /// Label recorded as the origin of enforced config values
+    pub fn origin(&self) -> String {
+        match &self.policy.name {
+            Some(name) => format!("policy {}", name),
+            None => format!("policy {}", self.url),
+        }
+    }
+}
+
+impl Policy {
+    /// Apply the policy over a loaded config, returning the paths it set
+    pub fn enforce(&self, config: &mut Config) -> Vec<String> {
+        let mut enforced = Vec::new();
+
+        if let Some(preset) = &self.permissions.preset {
+            config.permissions.preset = Some(preset.clone());
+            enforced.push("permissions.preset".to_string());
+        }
+        if !self.permissions.deny_categories.is_empty() {
+            for category in &self.permissions.deny_categories {
+                if !config.permissions.deny_categories.contains(category) {
+                    config.permissions.deny_categories.push(*category);
+                }
+            }
+            enforced.push("permissions.deny_categories".to_string());
+        }
+
+        if !self.commands.deny.is_empty() {
+            config.tools.warn_dangerous_commands = true;
+            for pattern in &self.commands.deny {
+                if !config.tools.dangerous_patterns.contains(pattern) {
+                    config.tools.dangerous_patterns.push(pattern.clone());
+                }
+            }
+            enforced.push("tools.warn_dangerous_commands".to_string());
+            enforced.push("tools.dangerous_patterns".to_string());
+        }
+
+        if let Some(allowed) = &self.mcp.allowed_servers {
+            config.mcp.servers.retain(|server| {
+                let keep = allowed.contains(&server.name);
+                if !keep {
+                    tracing::warn!(
+                        "MCP server '{}' is not approved by the organization policy",
+                        server.name
+                    );
+                }
+                keep
+            });
+        }
+
+        if let Some(enabled) = self.update.enabled {
+            config.update.enabled = enabled;
+            enforced.push("update.enabled".to_string());
+        }
+
+        if !self.hooks.is_empty() {
+            config.hooks.enabled = true;
+            enforced.push("hooks.enabled".to_string());
+            for (event, hooks) in &self.hooks {
+                let configured = config.hooks.events.entry(*event).or_default();
+                for hook in hooks {
+                    if !configured.contains(hook) {
+                        configured.push(hook.clone());
+                    }
+                }
+            }
+        }
+
+        enforced
+    }
+}
+
+fn parse_public_key(key: &str) -> Result<VerifyingKey> {
+    let bytes: [u8; 32] = STANDARD
+        .decode(key.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .context("Invalid policy public key")?;
+    VerifyingKey::from_bytes(&bytes).context("Invalid policy public key")
+}
+
+/// Read `policy.toml` from a checkout after checking its signature
+fn read_bundle(dir: &Path, key: &VerifyingKey) -> Result<Policy> {
+    let content = std::fs::read(dir.join(POLICY_FILE))
+        .with_context(|| format!("{} is missing from the policy bundle", POLICY_FILE))?;
+    let signature = std::fs::read_to_string(dir.join(SIGNATURE_FILE))
+        .with_context(|| format!("{} is missing from the policy bundle", SIGNATURE_FILE))?;
+    let signature: [u8; 64] = STANDARD
+        .decode(signature.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .context("Policy signature is malformed")?;
+    key.verify(&content, &Signature::from_bytes(&signature))
+        .map_err(|_| anyhow::anyhow!("Policy signature is invalid"))?;
+
+    let content = String::from_utf8(content).context("policy.toml is not UTF-8")?;
+    let policy: Policy = toml::from_str(&content).context("Failed to parse policy.toml")?;
+    // The bash tool skips patterns that don't compile, which would quietly
+    // drop the rule
+    for pattern in &policy.commands.deny {
+        regex::Regex::new(pattern)
+            .with_context(|| format!("Invalid commands.deny pattern '{}'", pattern))?;
+    }
+    Ok(policy)
+}
+
+/// Clone a policy bundle, verify it and install it in place of the current one
+///
+/// `public_key` is required the first time and when the URL changes;
+/// otherwise the pinned key is used.
+pub async fn pull(url: &str, public_key: Option<&str>) -> Result<InstalledPolicy> {
+    let dir = InstalledPolicy::dir()?;
+    let pinned: Option<PolicySource> = std::fs::read_to_string(dir.join(SOURCE_FILE))
+        .ok()
+        .and_then(|content| toml::from_str(&content).ok());
+    let public_key = match (public_key, &pinned) {
+        (Some(key), _) => key.to_string(),
+        (None, Some(source)) if source.url == url => source.public_key.clone(),
+        _ => anyhow::bail!("Pass the bundle's public key with --key"),
+    };
+    let key = parse_public_key(&public_key)?;
+
+    std::fs::create_dir_all(&dir)?;
+    let staging = dir.join("bundle.new");
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)?;
+    }
+    let output = Command::new("git")
+        .args(["clone", "--depth", "1", url])
+        .arg(&staging)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .await
+        .context("Failed to run git (is it installed?)")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git clone failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let policy = match read_bundle(&staging, &key) {
+        Ok(policy) => policy,
+        Err(e) => {
+            let _ = std::fs::remove_dir_all(&staging);
+            return Err(e.context(format!("Refusing to install the policy from {}", url)));
+        }
+    };
+
+    let bundle = dir.join("bundle");
+    if bundle.exists() {
+        std::fs::remove_dir_all(&bundle)?;
+    }
+    std::fs::rename(&staging, &bundle)?;
+    let source = PolicySource {
+        url: url.to_string(),
+        public_key,
+    };
+    std::fs::write(dir.join(SOURCE_FILE), toml::to_string_pretty(&source)?)?;
+
+    Ok(InstalledPolicy {
+        policy,
+        url: url.to_string(),
+    })
+}
+
+/// Generate a signing key pair, returning (secret, public) as base64
+pub fn generate_keypair() -> (String, String) {
+    let mut secret = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut secret);
+    let signing_key = SigningKey::from_bytes(&secret);
+    (
+        STANDARD.encode(secret),
+        STANDARD.encode(signing_key.verifying_key().to_bytes()),
+    )
+}
+
+/// Sign a policy file with a base64 secret key, returning the base64 signature
+pub fn sign(content: &[u8], secret_key: &str) -> Result<String> {
+    let secret: [u8; 32] = STANDARD
+        .decode(secret_key.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .context("Invalid policy secret key")?;
+    let signature = SigningKey::from_bytes(&secret).sign(content);
+    Ok(STANDARD.encode(signature.to_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: &str = r#"
+name = "acme"
+
+[permissions]
+preset = "safe"
+deny_categories = ["git_force_push"]
+
+[commands]
+deny = ['terraform\s+destroy']
+
+[mcp]
+allowed_servers = ["github"]
+
+[update]
+enabled = false
+
+[[hooks.pre_tool]]
+matcher = "bash"
+command = "audit"
+"#;
+
+    fn install(dir: &Path, policy: &str, signed: &str, secret: &str, public: &str) {
+        let bundle = dir.join("bundle");
+        std::fs::create_dir_all(&bundle).unwrap();
+        std::fs::write(bundle.join(POLICY_FILE), policy).unwrap();
+        std::fs::write(
+            bundle.join(SIGNATURE_FILE),
+            sign(signed.as_bytes(), secret).unwrap(),
+        )
+        .unwrap();
+        let source = PolicySource {
+            url: "https://git.example.com/policy.git".to_string(),
+            public_key: public.to_string(),
+        };
+        std::fs::write(dir.join(SOURCE_FILE), toml::to_string(&source).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_policy_overrides_user_config() {
+        let dir = tempfile::tempdir().unwrap();
+        let (secret, public) = generate_keypair();
+        install(dir.path(), POLICY, POLICY, &secret, &public);
+
+        let installed = InstalledPolicy::load_from(dir.path()).unwrap().unwrap();
+        assert_eq!(installed.origin(), "policy acme");
+
+        let mut config = Config::default();
+        config.permissions.preset = Some("yolo".to_string());
+        config.tools.warn_dangerous_commands = false;
+        config.mcp.servers = toml::from_str::<crate::mcp::McpConfig>(
+            "[[servers]]\nname = \"github\"\n[[servers]]\nname = \"random\"",
+        )
+        .unwrap()
+        .servers;
+
+        installed.policy.enforce(&mut config);
+        assert_eq!(config.permissions.preset.as_deref(), Some("safe"));
+        assert_eq!(
+            config.permissions.deny_categories,
+            vec![CommandCategory::GitForcePush]
+        );
+        assert!(config.tools.warn_dangerous_commands);
+        assert!(config
+            .tools
+            .dangerous_patterns
+            .contains(&r"terraform\s+destroy".to_string()));
+        assert_eq!(config.mcp.servers.len(), 1);
+        assert!(!config.update.enabled);
+        assert_eq!(config.hooks.events[&HookType::PreToolUse].len(), 1);
+    }
+
+    #[test]
+    fn test_tampered_policy_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let (secret, public) = generate_keypair();
+        let tampered = POLICY.replace("preset = \"safe\"", "preset = \"yolo\"");
+        install(dir.path(), &tampered, POLICY, &secret, &public);
+
+        let err = InstalledPolicy::load_from(dir.path()).err().unwrap();
+        assert!(format!("{:#}", err).contains("signature is invalid"));
+    }
+
+    #[test]
+    fn test_invalid_deny_pattern_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let (secret, public) = generate_keypair();
+        let policy = POLICY.replace(r"terraform\s+destroy", r"terraform\s+(destroy");
+        install(dir.path(), &policy, &policy, &secret, &public);
+
+        let err = InstalledPolicy::load_from(dir.path()).err().unwrap();
+        assert!(
+            format!("{:#}", err).contains(r"Invalid commands.deny pattern 'terraform\s+(destroy'"),
+            "{:#}",
+            err
+        );
+    }
+}
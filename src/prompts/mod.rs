@@ -6,9 +6,16 @@
 //! 3. Tool usage guidelines
 //! 4. Project context (from SAFE_CODER.md or AGENTS.md)
 //! 5. Session context
+//!
+//! Projects can replace or extend sections 1-3 with files in
+//! `.safe-coder/prompts/` (see [`overrides`]).
 
 use crate::tools::AgentMode;
 
+pub mod overrides;
+
+use overrides::{PromptOverrides, PromptSection, PromptVars};
+
 /// Base system prompt that establishes identity and core behavior
 /// Inspired by Codex CLI's approach to autonomy and task completion
 pub const BASE_SYSTEM_PROMPT: &str = r#"You are Safe Coder, an expert AI coding assistant.
@@ -66,11 +73,12 @@ You are in **read-only exploration mode**. Explore, analyze, and create a concis
 - `glob` - Find files by pattern
 - `grep` - Search within files
 - `code_search` - Advanced multi-pattern search (preferred for exploration)
+- `inspect_data` - Schema and sample rows of CSV/Parquet files, read-only SQL on SQLite/DuckDB
 - `webfetch` - Fetch documentation
 - `todoread` - View task list
 
 ### BLOCKED (Require BUILD mode)
-`write_file`, `edit_file`, `bash`, `todowrite`
+`write_file`, `edit_file`, `bash`, `todowrite`, `todoupdate`
 
 ### Planning Guidelines
 
@@ -109,7 +117,7 @@ You have full execution capabilities. Complete the task autonomously.
 - `write_file` - Create new files only
 - `bash` - Run shell commands
 - `list_file`, `glob`, `grep` - Find files
-- `todowrite`, `todoread` - Track multi-step progress (USE THIS!)
+- `todowrite`, `todoupdate`, `todoread` - Track multi-step progress (USE THIS!)
 - `subagent` - Spawn specialized agents for focused tasks
 - `orchestrate` - Delegate tasks to external CLI agents (Claude, Gemini, Copilot)
 
@@ -203,7 +211,7 @@ pub const TOOL_USAGE_GUIDELINES: &str = r#"
 ## Tool Quick Reference
 
 ### Files
-- `read_file` - ALWAYS read before editing. Use offset/limit for large files.
+- `read_file` - ALWAYS read before editing. In large files, read one definition with `symbol` (or `around_line`) instead of the whole file.
 - `edit_file` - Use unique context in `old_string`. One logical change per edit.
 - `write_file` - New files only. Prefer `edit_file` for existing.
 
@@ -216,12 +224,20 @@ pub const TOOL_USAGE_GUIDELINES: &str = r#"
 - `glob` - Find files: `**/*.rs`, `**/*_test.rs`
 - `grep` - Find content: `fn function_name`, `use.*module`
 - `list_file` - Directory structure exploration
+- `inspect_data` - Data files (CSV, Parquet, SQLite, DuckDB): schema, row counts, samples, read-only SQL. Use instead of reading datasets.
 
 ### Execution
 - `bash` - Build, test, git. Check exit codes. Capture stderr with `2>&1`.
+- `run_tests` - Run the test suite (cargo test, jest, pytest) and get only the failing tests with file:line and message. Use `filter` to rerun one test.
+- `add_dependency` / `remove_dependency` - Change dependencies through cargo, npm/yarn/pnpm, poetry or pip. Never edit Cargo.toml, package.json or requirements files by hand to add or remove a package.
+- `audit_dependencies` - Known vulnerabilities in the project's dependencies (cargo-audit, npm/pnpm audit, pip-audit) as JSON with the available fixes.
+- `ci_status` - Status of the current branch's latest CI run, with condensed logs of failing jobs. Check it after pushing.
+- `http_request` - Call the API you are building (method, URL, headers, body). Cookies persist across calls.
+- `browser` - When built with the browser feature: load a page of the app you changed (`browse`, `screenshot`, `query_dom`) to check it renders. Screenshots are shown to you.
 
 ### Tracking
 - `todowrite` - Track multi-step progress. Mark complete immediately.
+- `todoupdate` - Add, update or remove one item. The list is saved in `.safe-coder/todos.json` and kept across sessions.
 - `todoread` - Check current task status.
 
 ### Delegation
@@ -240,27 +256,25 @@ When reporting command results, include:
 - Relevant output (truncate if > 50 lines)
 "#;
 
-/// Build a complete system prompt for the current context
-pub fn build_system_prompt(
-    agent_mode: AgentMode,
+/// Build a complete system prompt with a project's prompt files applied
+pub fn build_system_prompt_with(
+    overrides: &PromptOverrides,
+    vars: &PromptVars,
     project_context: Option<&str>,
     additional_instructions: Option<&str>,
 ) -> String {
     let mut prompt = String::new();
 
     // 1. Base identity
-    prompt.push_str(BASE_SYSTEM_PROMPT);
+    prompt.push_str(&overrides.render(PromptSection::Base, vars));
     prompt.push('\n');
 
     // 2. Agent-specific instructions
-    match agent_mode {
-        AgentMode::Plan => prompt.push_str(PLAN_AGENT_PROMPT),
-        AgentMode::Build => prompt.push_str(BUILD_AGENT_PROMPT),
-    }
+    prompt.push_str(&overrides.render(PromptSection::for_mode(vars.mode), vars));
     prompt.push('\n');
 
     // 3. Tool usage guidelines
-    prompt.push_str(TOOL_USAGE_GUIDELINES);
+    prompt.push_str(&overrides.render(PromptSection::Tools, vars));
     prompt.push('\n');
 
     // 4. Project context (from memory/SAFE_CODER.md)
@@ -300,6 +314,21 @@ pub fn mode_switch_prompt(from: AgentMode, to: AgentMode) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
+
+    /// The built-in prompt, without project prompt files
+    fn build_system_prompt(
+        agent_mode: AgentMode,
+        project_context: Option<&str>,
+        additional_instructions: Option<&str>,
+    ) -> String {
+        build_system_prompt_with(
+            &PromptOverrides::default(),
+            &PromptVars::new(Path::new(""), agent_mode),
+            project_context,
+            additional_instructions,
+        )
+    }
 
     #[test]
     fn test_build_system_prompt_plan_mode() {
@@ -0,0 +1,334 @@
+//! Project overrides for the built-in system prompt sections
+//!
+//! A file in `.safe-coder/prompts/` named after a section (`base.md`,
+//! `plan.md`, `build.md`, `tools.md`) replaces that section; one named
+//! `<section>.append.md` is added after it. Files are templates that can use
+//! `{{project_name}}`, `{{mode}}`, `{{date}}` and `{{default}}` (the built-in
+//! text of the section). A file that doesn't validate is skipped and the
+//! built-in text is used. Sessions check the directory before every turn and
+//! reload it when a file changed.
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::{BASE_SYSTEM_PROMPT, BUILD_AGENT_PROMPT, PLAN_AGENT_PROMPT, TOOL_USAGE_GUIDELINES};
+use crate::tools::AgentMode;
+
+/// Variables available in prompt files
+const VARIABLES: &[&str] = &["project_name", "mode", "date", "default"];
+
+/// A section of the system prompt that can be overridden
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptSection {
+    Base,
+    Plan,
+    Build,
+    Tools,
+}
+
+impl PromptSection {
+    pub const ALL: [PromptSection; 4] = [
+        PromptSection::Base,
+        PromptSection::Plan,
+        PromptSection::Build,
+        PromptSection::Tools,
+    ];
+
+    /// File name without extension, e.g. "plan"
+    pub fn name(self) -> &'static str {
+        match self {
+            PromptSection::Base => "base",
+            PromptSection::Plan => "plan",
+            PromptSection::Build => "build",
+            PromptSection::Tools => "tools",
+        }
+    }
+
+    /// The built-in text of the section
+    pub fn default_text(self) -> &'static str {
+        match self {
+            PromptSection::Base => BASE_SYSTEM_PROMPT,
+            PromptSection::Plan => PLAN_AGENT_PROMPT,
+            PromptSection::Build => BUILD_AGENT_PROMPT,
+            PromptSection::Tools => TOOL_USAGE_GUIDELINES,
+        }
+    }
+
+    /// Agent-specific section for a mode
+    pub fn for_mode(mode: AgentMode) -> Self {
+        match mode {
+            AgentMode::Plan => PromptSection::Plan,
+            AgentMode::Build => PromptSection::Build,
+        }
+    }
+}
+
+/// Values substituted into prompt files
+#[derive(Debug, Clone)]
+pub struct PromptVars {
+    pub project_name: String,
+    pub mode: AgentMode,
+    pub date: String,
+}
+
+impl PromptVars {
+    pub fn new(project: &Path, mode: AgentMode) -> Self {
+        Self {
+            project_name: project
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default(),
+            mode,
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+        }
+    }
+}
+
+/// A valid prompt file
+#[derive(Debug, Clone)]
+struct PromptFile {
+    section: PromptSection,
+    append: bool,
+    template: String,
+}
+
+/// Prompt files of a project, with the problems found loading them
+#[derive(Debug, Clone, Default)]
+pub struct PromptOverrides {
+    project: PathBuf,
+    files: Vec<PromptFile>,
+    /// Files that were skipped, with the reason
+    pub errors: Vec<String>,
+    /// Modification times of the files when they were loaded
+    stamps: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+impl PromptOverrides {
+    /// Directory of a project's prompt files
+    pub fn dir(project: &Path) -> PathBuf {
+        project.join(".safe-coder").join("prompts")
+    }
+
+    /// Load and validate the project's prompt files (none when the directory
+    /// doesn't exist)
+    pub fn load(project: &Path) -> Self {
+        let stamps = stamps(&Self::dir(project));
+        let mut overrides = Self {
+            project: project.to_path_buf(),
+            ..Self::default()
+        };
+        for (path, _) in &stamps {
+            match load_file(path) {
+                Ok(file) => overrides.files.push(file),
+                Err(e) => overrides.errors.push(format!("{:#}", e)),
+            }
+        }
+        overrides.stamps = stamps;
+        overrides
+    }
+
+    /// Reload if a prompt file was added, removed or modified since the last
+    /// load. Returns true when the files were reloaded.
+    pub fn reload_if_changed(&mut self) -> bool {
+        if stamps(&Self::dir(&self.project)) == self.stamps {
+            return false;
+        }
+        *self = Self::load(&self.project);
+        true
+    }
+
+    /// Files in effect, e.g. "plan.md (replaces)"
+    pub fn active(&self) -> Vec<String> {
+        self.files
+            .iter()
+            .map(|f| {
+                if f.append {
+                    format!("{}.append.md (extends)", f.section.name())
+                } else {
+                    format!("{}.md (replaces)", f.section.name())
+                }
+            })
+            .collect()
+    }
+
+    /// Text of a section with the project's files applied
+    pub fn render(&self, section: PromptSection, vars: &PromptVars) -> String {
+        let default = section.default_text();
+        let file = |append: bool| {
+            self.files
+                .iter()
+                .find(|f| f.section == section && f.append == append)
+                .map(|f| expand(&f.template, vars, default))
+        };
+        let mut text = file(false).unwrap_or_else(|| default.to_string());
+        if let Some(extra) = file(true) {
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            text.push_str(&extra);
+        }
+        text
+    }
+}
+
+/// Prompt files in `dir` with their modification times, in name order
+fn stamps(dir: &Path) -> Vec<(PathBuf, Option<SystemTime>)> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut stamps: Vec<_> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|e| e == "md"))
+        .map(|p| {
+            let modified = p.metadata().and_then(|m| m.modified()).ok();
+            (p, modified)
+        })
+        .collect();
+    stamps.sort();
+    stamps
+}
+
+fn load_file(path: &Path) -> Result<PromptFile> {
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let stem = file_name.trim_end_matches(".md");
+    let (name, append) = match stem.strip_suffix(".append") {
+        Some(name) => (name, true),
+        None => (stem, false),
+    };
+    let Some(section) = PromptSection::ALL.into_iter().find(|s| s.name() == name) else {
+        bail!(
+            "{}: unknown prompt file; expected base, plan, build or tools (.md or .append.md)",
+            file_name
+        );
+    };
+    let template = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    validate_template(&template).with_context(|| file_name.clone())?;
+    if template.trim().is_empty() {
+        bail!("{}: file is empty", file_name);
+    }
+    Ok(PromptFile {
+        section,
+        append,
+        template,
+    })
+}
+
+/// Check that every `{{...}}` in a template is a known variable
+pub fn validate_template(template: &str) -> Result<()> {
+    for (line_number, line) in template.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                bail!("line {}: unclosed {{{{", line_number + 1);
+            };
+            let name = rest[start + 2..start + 2 + len].trim();
+            if !VARIABLES.contains(&name) {
+                bail!(
+                    "line {}: unknown variable {{{{{}}}}}; available: {}",
+                    line_number + 1,
+                    name,
+                    VARIABLES.join(", ")
+                );
+            }
+            rest = &rest[start + 2 + len + 2..];
+        }
+    }
+    Ok(())
+}
+
+/// Substitute the variables of a validated template
+fn expand(template: &str, vars: &PromptVars, default: &str) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        match rest[start + 2..start + 2 + len].trim() {
+            "project_name" => out.push_str(&vars.project_name),
+            "mode" => out.push_str(vars.mode.short_name()),
+            "date" => out.push_str(&vars.date),
+            "default" => out.push_str(default),
+            _ => out.push_str(&rest[start..start + 2 + len + 2]),
+        }
+        rest = &rest[start + 2 + len + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_and_extends_sections() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = PromptOverrides::dir(project.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("base.md"),
+            "You work on {{project_name}} ({{ mode }}, {{date}}).\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("tools.append.md"), "Never run `git push`.\n").unwrap();
+        std::fs::write(dir.join("plan.md"), "Plan for {{projet}}\n").unwrap();
+        std::fs::write(dir.join("notes.md"), "hello\n").unwrap();
+
+        let overrides = PromptOverrides::load(project.path());
+        assert_eq!(
+            overrides.active(),
+            ["base.md (replaces)", "tools.append.md (extends)"]
+        );
+        assert_eq!(overrides.errors.len(), 2, "{:?}", overrides.errors);
+        assert!(overrides
+            .errors
+            .iter()
+            .any(|e| e.contains("unknown variable {{projet}}")));
+
+        let vars = PromptVars {
+            project_name: "demo".to_string(),
+            mode: AgentMode::Plan,
+            date: "2024-01-02".to_string(),
+        };
+        assert_eq!(
+            overrides.render(PromptSection::Base, &vars),
+            "You work on demo (PLAN, 2024-01-02).\n"
+        );
+        let tools = overrides.render(PromptSection::Tools, &vars);
+        assert!(tools.starts_with(TOOL_USAGE_GUIDELINES));
+        assert!(tools.ends_with("Never run `git push`.\n"));
+        // The invalid plan.md falls back to the built-in text
+        assert_eq!(
+            overrides.render(PromptSection::Plan, &vars),
+            PLAN_AGENT_PROMPT
+        );
+    }
+
+    #[test]
+    fn reloads_when_files_change() {
+        let project = tempfile::tempdir().unwrap();
+        let mut overrides = PromptOverrides::load(project.path());
+        assert!(!overrides.reload_if_changed());
+
+        let dir = PromptOverrides::dir(project.path());
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("build.md"), "{{default}}\nAlso run clippy.\n").unwrap();
+        assert!(overrides.reload_if_changed());
+        let vars = PromptVars::new(project.path(), AgentMode::Build);
+        let build = overrides.render(PromptSection::Build, &vars);
+        assert!(build.starts_with(BUILD_AGENT_PROMPT));
+        assert!(build.ends_with("Also run clippy.\n"));
+
+        std::fs::remove_file(dir.join("build.md")).unwrap();
+        assert!(overrides.reload_if_changed());
+        assert!(overrides.active().is_empty());
+    }
+}
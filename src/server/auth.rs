@@ -0,0 +1,153 @@
+//! API token scopes
+//!
+//! Tokens from `[[server.tokens]]` are sent as `Authorization: Bearer
+//! <token>`, or as `?access_token=<token>` for event streams and WebSockets,
+//! which browsers open without custom headers. Each route needs a minimum
+//! scope: reads need a viewer, anything that changes a session needs an
+//! operator, and deleting sessions or opening a terminal needs an admin.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{header, Method, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::config::{ApiToken, TokenScope};
+use crate::server::state::AppState;
+use crate::server::types::ErrorResponse;
+
+/// The scope a request needs, or None for public routes
+pub fn required_scope(method: &Method, path: &str) -> Option<TokenScope> {
+    if matches!(path, "/api/health" | "/api/openapi.json") {
+        return None;
+    }
+    // A terminal on the host can do anything the server can
+    if path.ends_with("/pty") {
+        return Some(TokenScope::Admin);
+    }
+    if *method == Method::DELETE && path.starts_with("/api/sessions/") {
+        return Some(TokenScope::Admin);
+    }
+    if *method == Method::GET {
+        Some(TokenScope::Viewer)
+    } else {
+        Some(TokenScope::Operator)
+    }
+}
+
+/// Reject requests whose token is missing or lacks the route's scope
+pub async fn require_scope(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(required) = required_scope(request.method(), request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let scope = {
+        let config = state.config.read().await;
+        let tokens = &config.server.tokens;
+        if tokens.is_empty() {
+            return next.run(request).await;
+        }
+        presented_token(&request).and_then(|presented| find_token(tokens, &presented))
+    };
+
+    match scope {
+        None => error(
+            StatusCode::UNAUTHORIZED,
+            "A valid API token is required".to_string(),
+            "UNAUTHORIZED",
+        ),
+        Some(token) if token.scope < required => {
+            tracing::info!(
+                "Denied {} {} to token '{}' ({:?} scope)",
+                request.method(),
+                request.uri().path(),
+                token.name,
+                token.scope
+            );
+            error(
+                StatusCode::FORBIDDEN,
+                format!("This needs an API token with {:?} scope", required),
+                "FORBIDDEN",
+            )
+        }
+        Some(_) => next.run(request).await,
+    }
+}
+
+fn presented_token(request: &Request) -> Option<String> {
+    let header = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "));
+    if let Some(token) = header {
+        return Some(token.trim().to_string());
+    }
+    request.uri().query().and_then(|query| {
+        url::form_urlencoded::parse(query.as_bytes())
+            .find(|(key, _)| key == "access_token")
+            .map(|(_, value)| value.to_string())
+    })
+}
+
+fn find_token(tokens: &[ApiToken], presented: &str) -> Option<ApiToken> {
+    tokens
+        .iter()
+        .find(|t| constant_time_eq(t.token.as_bytes(), presented.as_bytes()))
+        .cloned()
+}
+
+/// Compare without returning early, so timing doesn't reveal a prefix
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn error(status: StatusCode, message: String, code: &str) -> Response {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message,
+            code: code.to_string(),
+        }),
+    )
+        .into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_required_scope() {
+        let scope = |method: Method, path: &str| required_scope(&method, path);
+        assert_eq!(scope(Method::GET, "/api/health"), None);
+        assert_eq!(
+            scope(Method::GET, "/api/sessions/abc/events"),
+            Some(TokenScope::Viewer)
+        );
+        assert_eq!(
+            scope(Method::POST, "/api/sessions/abc/messages"),
+            Some(TokenScope::Operator)
+        );
+        assert_eq!(
+            scope(Method::POST, "/api/sessions/abc/tool-approval-response"),
+            Some(TokenScope::Operator)
+        );
+        assert_eq!(
+            scope(Method::GET, "/api/sessions/abc/pty"),
+            Some(TokenScope::Admin)
+        );
+        assert_eq!(
+            scope(Method::DELETE, "/api/sessions/abc"),
+            Some(TokenScope::Admin)
+        );
+    }
+}
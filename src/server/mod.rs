@@ -3,6 +3,7 @@
 //! This module provides an HTTP/WebSocket server that exposes safe-coder's
 //! functionality via REST APIs and real-time event streams.
 
+pub mod auth;
 pub mod routes;
 pub mod state;
 pub mod types;
@@ -44,7 +45,10 @@ impl Default for ServerConfig {
 /// Start the HTTP server
 pub async fn start_server(config: ServerConfig) -> anyhow::Result<()> {
     // Load safe-coder config
-    let app_config = Config::load().unwrap_or_default();
+    let mut app_config = Config::load().unwrap_or_default();
+    // Tokens never come from the project the server happens to start in
+    app_config.server = Config::load_global_with_policy()?.server;
+    let tokens = app_config.server.tokens.len();
 
     // Create shared state with persistence
     let state = match AppState::new_with_persistence(app_config.clone()).await {
@@ -58,28 +62,68 @@ pub async fn start_server(config: ServerConfig) -> anyhow::Result<()> {
         }
     };
 
+    // Idle sessions are saved once more if a signal stops the server
+    let on_shutdown = state.clone();
+    let _persist = crate::shutdown::register("save server sessions", move || async move {
+        on_shutdown.persist_sessions().await;
+    });
+
     // Build router
     let mut app = Router::new()
         // Health & config
         .route("/api/health", get(routes::health::health_check))
         .route("/api/config", get(routes::config::get_config))
 
+        // Tool approvals across sessions
+        .route("/api/approvals", get(routes::approvals::list_approvals))
+        .route("/api/approvals/:prompt_id", post(routes::approvals::respond_to_approval))
+
+        // Orchestration runs
+        .route("/api/orchestrations", get(routes::orchestrations::list_orchestrations))
+        .route("/api/orchestrations", post(routes::orchestrations::create_orchestration))
+        .route("/api/orchestrations/:id", get(routes::orchestrations::get_orchestration))
+        .route("/api/orchestrations/:id/events", get(routes::orchestrations::orchestration_events))
+        .route("/api/orchestrations/:id/tasks/:task_id/cancel", post(routes::orchestrations::cancel_task))
+        .route("/api/orchestrations/:id/artifacts", get(routes::orchestrations::list_artifacts))
+        .route("/api/orchestrations/:id/artifacts/:task_id", get(routes::orchestrations::get_task_artifacts))
+
+        // Projects
+        .route("/api/projects", get(routes::projects::list_projects))
+
         // Sessions
         .route("/api/sessions", get(routes::sessions::list_sessions))
         .route("/api/sessions", post(routes::sessions::create_session))
         .route("/api/sessions/:id", get(routes::sessions::get_session))
         .route("/api/sessions/:id", delete(routes::sessions::delete_session))
         .route("/api/sessions/:id/mode", put(routes::sessions::set_session_mode))
+        .route("/api/sessions/:id/params", get(routes::sessions::get_params))
+        .route("/api/sessions/:id/params", put(routes::sessions::set_param))
+        .route("/api/sessions/:id/capabilities", get(routes::sessions::get_capabilities))
+        .route("/api/sessions/:id/skills", get(routes::sessions::list_skills))
+        .route("/api/sessions/:id/skills/:name", put(routes::sessions::set_skill))
+        .route("/api/sessions/:id/mcp/status", get(routes::sessions::mcp_status))
+        .route("/api/sessions/:id/mcp/resources", get(routes::sessions::list_mcp_resources))
+        .route("/api/sessions/:id/mcp/prompts", get(routes::sessions::list_mcp_prompts))
+        .route("/api/sessions/:id/mcp/prompts/:server/:name", post(routes::sessions::get_mcp_prompt))
 
         // Messages
         .route("/api/sessions/:id/messages", get(routes::messages::get_messages))
         .route("/api/sessions/:id/messages", post(routes::messages::send_message))
         .route("/api/sessions/:id/cancel", post(routes::messages::cancel_operation))
         .route("/api/sessions/:id/doom-loop-response", post(routes::sessions::respond_to_doom_loop))
+        .route("/api/sessions/:id/tool-approval-response", post(routes::sessions::respond_to_tool_approval))
 
         // File changes
         .route("/api/sessions/:id/changes", get(routes::files::get_session_changes))
+        .route("/api/sessions/:id/diff", get(routes::files::get_session_diff))
         .route("/api/sessions/:id/files", get(routes::files::list_project_files))
+        .route("/api/sessions/:id/files/content", get(routes::files::get_file_content))
+
+        // Checkpoints
+        .route("/api/sessions/:id/checkpoints", get(routes::checkpoints::list_checkpoints))
+        .route("/api/sessions/:id/checkpoints", post(routes::checkpoints::create_checkpoint))
+        .route("/api/sessions/:id/checkpoints/:checkpoint_id", get(routes::checkpoints::get_checkpoint))
+        .route("/api/sessions/:id/checkpoints/:checkpoint_id/restore", post(routes::checkpoints::restore_checkpoint))
 
         // Real-time events (SSE)
         .route("/api/sessions/:id/events", get(routes::events::session_events))
@@ -87,9 +131,16 @@ pub async fn start_server(config: ServerConfig) -> anyhow::Result<()> {
         // PTY WebSocket
         .route("/api/sessions/:id/pty", get(routes::pty::pty_websocket))
 
+        // Editor attachment
+        .route("/api/ide/context", get(routes::ide::get_context))
+        .route("/api/ide/context", put(routes::ide::set_context))
+        .route("/api/ide/context", delete(routes::ide::clear_context))
+        .route("/api/ide/events", get(routes::ide::ide_events))
+
         // OpenAPI docs
         .route("/api/openapi.json", get(routes::openapi::openapi_spec))
 
+        .layer(axum::middleware::from_fn_with_state(state.clone(), auth::require_scope))
         .with_state(state)
         .layer(TraceLayer::new_for_http());
 
@@ -109,6 +160,9 @@ pub async fn start_server(config: ServerConfig) -> anyhow::Result<()> {
 
     println!("Starting safe-coder server on http://{}", addr);
     println!("API documentation: http://{}/api/openapi.json", addr);
+    if tokens > 0 {
+        println!("API tokens required ({} configured)", tokens);
+    }
 
     let listener = tokio::net::TcpListener::bind(addr).await?;
     axum::serve(listener, app).await?;
@@ -0,0 +1,46 @@
+//! Tool approvals across sessions
+//!
+//! The desktop app polls these to raise a notification for a prompt in any
+//! session, and answers from the notification with only the prompt ID.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::server::state::AppState;
+use crate::server::types::{ApprovalDecisionRequest, ApprovalListResponse, ErrorResponse};
+
+/// GET /api/approvals - Tool calls waiting for approval, oldest first
+pub async fn list_approvals(State(state): State<Arc<AppState>>) -> Json<ApprovalListResponse> {
+    Json(ApprovalListResponse {
+        approvals: state.pending_approvals().await,
+    })
+}
+
+/// POST /api/approvals/:prompt_id - Approve or deny a pending tool call
+pub async fn respond_to_approval(
+    State(state): State<Arc<AppState>>,
+    Path(prompt_id): Path<String>,
+    Json(request): Json<ApprovalDecisionRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    if state
+        .send_tool_approval_response(&prompt_id, request.approved)
+        .await
+    {
+        let action = if request.approved { "approve" } else { "deny" };
+        tracing::info!("Tool approval response sent: {} (prompt_id={})", action, prompt_id);
+        Ok(Json(serde_json::json!({ "status": "ok", "action": action })))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Tool approval prompt not found or already responded: {}", prompt_id),
+                code: "PROMPT_NOT_FOUND".to_string(),
+            }),
+        ))
+    }
+}
@@ -0,0 +1,270 @@
+//! Checkpoints of a session's project
+//!
+//! The same directory checkpoints `/checkpoint` uses in the CLI, so clients
+//! of the server can roll back the agent's work too: a whole checkpoint, or
+//! single files after looking at what changed since.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Json,
+};
+
+use crate::checkpoint::{CheckpointFileStatus, DirectoryCheckpointManager, NotInCheckpoint};
+use crate::server::state::{AppState, SessionHandle};
+use crate::server::types::{
+    CheckpointDetailResponse, CheckpointListResponse, CreateCheckpointRequest, ErrorResponse,
+    RestoreCheckpointRequest, RestoreCheckpointResponse,
+};
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn error(status: StatusCode, message: String, code: &str) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message,
+            code: code.to_string(),
+        }),
+    )
+}
+
+fn internal(e: anyhow::Error) -> ApiError {
+    error(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        e.to_string(),
+        "CHECKPOINT_FAILED",
+    )
+}
+
+/// The session and a checkpoint manager for its project. The manager keeps
+/// everything on disk, so this one sees the checkpoints the session takes
+/// without waiting for the session's lock during a turn.
+async fn manager(
+    state: &AppState,
+    session_id: &str,
+) -> Result<(SessionHandle, DirectoryCheckpointManager), ApiError> {
+    let handle = state.get_session(session_id).await.ok_or_else(|| {
+        error(
+            StatusCode::NOT_FOUND,
+            format!("Session not found: {}", session_id),
+            "SESSION_NOT_FOUND",
+        )
+    })?;
+    let config = state.config.read().await.checkpoint.clone();
+    let manager =
+        DirectoryCheckpointManager::new(handle.project_path.clone(), config).map_err(internal)?;
+    Ok((handle, manager))
+}
+
+/// Restoring under a running turn would mix its edits with the old files
+async fn ensure_idle(handle: &SessionHandle) -> Result<(), ApiError> {
+    if *handle.is_processing.read().await {
+        return Err(error(
+            StatusCode::CONFLICT,
+            "Session is processing a message; cancel it or wait before restoring".to_string(),
+            "SESSION_BUSY",
+        ));
+    }
+    Ok(())
+}
+
+fn not_found(checkpoint_id: &str) -> ApiError {
+    error(
+        StatusCode::NOT_FOUND,
+        format!("Checkpoint not found: {}", checkpoint_id),
+        "CHECKPOINT_NOT_FOUND",
+    )
+}
+
+/// GET /api/sessions/:id/checkpoints - Checkpoints of the project, oldest first
+pub async fn list_checkpoints(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<CheckpointListResponse>, ApiError> {
+    let (_, manager) = manager(&state, &session_id).await?;
+    let checkpoints = manager.list_checkpoints().await.map_err(internal)?;
+    Ok(Json(CheckpointListResponse {
+        session_id,
+        enabled: manager.is_enabled(),
+        checkpoints,
+    }))
+}
+
+/// POST /api/sessions/:id/checkpoints - Take a checkpoint now
+pub async fn create_checkpoint(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    request: Option<Json<CreateCheckpointRequest>>,
+) -> Result<Json<CheckpointDetailResponse>, ApiError> {
+    let (_, mut manager) = manager(&state, &session_id).await?;
+    if !manager.is_enabled() {
+        return Err(error(
+            StatusCode::CONFLICT,
+            "Checkpoints are disabled in [checkpoint]".to_string(),
+            "CHECKPOINTS_DISABLED",
+        ));
+    }
+    let label = request
+        .and_then(|Json(r)| r.label)
+        .unwrap_or_else(|| "Manual checkpoint".to_string());
+    let id = manager.create_checkpoint(&label).await.map_err(internal)?;
+    checkpoint_detail(&manager, &id).await.map(Json)
+}
+
+/// GET /api/sessions/:id/checkpoints/:checkpoint_id - Files changed since a checkpoint
+pub async fn get_checkpoint(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, checkpoint_id)): Path<(String, String)>,
+) -> Result<Json<CheckpointDetailResponse>, ApiError> {
+    let (_, manager) = manager(&state, &session_id).await?;
+    checkpoint_detail(&manager, &checkpoint_id).await.map(Json)
+}
+
+async fn checkpoint_detail(
+    manager: &DirectoryCheckpointManager,
+    checkpoint_id: &str,
+) -> Result<CheckpointDetailResponse, ApiError> {
+    let checkpoint = manager
+        .list_checkpoints()
+        .await
+        .map_err(internal)?
+        .into_iter()
+        .find(|c| c.id == checkpoint_id)
+        .ok_or_else(|| not_found(checkpoint_id))?;
+    let changes = manager
+        .changed_files(checkpoint_id)
+        .await
+        .map_err(|_| not_found(checkpoint_id))?;
+    Ok(CheckpointDetailResponse {
+        checkpoint,
+        changes,
+    })
+}
+
+/// POST /api/sessions/:id/checkpoints/:checkpoint_id/restore - Roll back
+///
+/// Without `files`, every file in the checkpoint is written back, and files
+/// created since are kept, as with `/checkpoint restore`. With `files`,
+/// only those are restored, and listed files created since the checkpoint
+/// are removed. A listed file the checkpoint never captured, such as an
+/// ignored one, fails the whole request with 404.
+pub async fn restore_checkpoint(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, checkpoint_id)): Path<(String, String)>,
+    request: Option<Json<RestoreCheckpointRequest>>,
+) -> Result<Json<RestoreCheckpointResponse>, ApiError> {
+    let (handle, manager) = manager(&state, &session_id).await?;
+    ensure_idle(&handle).await?;
+    let changes = manager
+        .changed_files(&checkpoint_id)
+        .await
+        .map_err(|_| not_found(&checkpoint_id))?;
+
+    let files = request.and_then(|Json(r)| r.files);
+    let restored = match files {
+        Some(files) => {
+            manager
+                .restore_files(&checkpoint_id, &files)
+                .await
+                .map_err(|e| match e.downcast_ref::<NotInCheckpoint>() {
+                    Some(missing) => error(
+                        StatusCode::NOT_FOUND,
+                        missing.to_string(),
+                        "NOT_IN_CHECKPOINT",
+                    ),
+                    None => error(StatusCode::BAD_REQUEST, e.to_string(), "RESTORE_FAILED"),
+                })?;
+            files
+        }
+        None => {
+            manager
+                .restore_checkpoint(&checkpoint_id)
+                .await
+                .map_err(internal)?;
+            changes
+                .into_iter()
+                .filter(|c| c.status != CheckpointFileStatus::Created)
+                .map(|c| c.path)
+                .collect()
+        }
+    };
+
+    tracing::info!(
+        "Session {} restored {} file(s) from checkpoint {}",
+        session_id,
+        restored.len(),
+        checkpoint_id
+    );
+    Ok(Json(RestoreCheckpointResponse {
+        checkpoint_id,
+        restored,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::server::types::RestoreCheckpointRequest;
+    use crate::session::Session;
+    use tokio::sync::RwLock;
+
+    #[tokio::test]
+    async fn test_restore_keeps_files_the_checkpoint_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().canonicalize().unwrap();
+        std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&project)
+            .status()
+            .unwrap();
+        std::fs::write(project.join(".gitignore"), ".env\n").unwrap();
+        std::fs::write(project.join(".env"), "SECRET=1").unwrap();
+        std::fs::write(project.join("main.rs"), "fn main() {}").unwrap();
+
+        let mut config = Config::default();
+        config.llm.api_key = Some("test-key".to_string());
+        let session = Session::new(config.clone(), project.clone()).await.unwrap();
+        let state = Arc::new(AppState::new(config));
+        state.sessions.write().await.insert(
+            "s1".to_string(),
+            SessionHandle {
+                session: Arc::new(RwLock::new(session)),
+                project_path: project.clone(),
+                created_at: chrono::Utc::now(),
+                is_processing: Arc::new(RwLock::new(false)),
+                file_changes: Arc::new(RwLock::new(Vec::new())),
+                start_snapshot: None,
+            },
+        );
+
+        let Json(created) = create_checkpoint(State(state.clone()), Path("s1".to_string()), None)
+            .await
+            .unwrap();
+        std::fs::write(project.join("new.rs"), "// new").unwrap();
+
+        let restore = |files: &[&str]| {
+            let request = RestoreCheckpointRequest {
+                files: Some(files.iter().map(|f| f.to_string()).collect()),
+            };
+            restore_checkpoint(
+                State(state.clone()),
+                Path(("s1".to_string(), created.checkpoint.id.clone())),
+                Some(Json(request)),
+            )
+        };
+
+        let (status, Json(body)) = restore(&[".env", "new.rs"]).await.unwrap_err();
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body.code, "NOT_IN_CHECKPOINT");
+        assert_eq!(std::fs::read_to_string(project.join(".env")).unwrap(), "SECRET=1");
+        assert!(project.join("new.rs").exists());
+
+        let Json(restored) = restore(&["new.rs"]).await.unwrap();
+        assert_eq!(restored.restored, vec!["new.rs".to_string()]);
+        assert!(!project.join("new.rs").exists());
+    }
+}
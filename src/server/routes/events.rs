@@ -5,32 +5,89 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
     response::sse::{Event, KeepAlive, Sse},
+    Json,
 };
 use futures::stream::Stream;
+use serde::Deserialize;
 use tokio_stream::StreamExt;
 
-use crate::server::state::AppState;
-use crate::server::types::ServerEvent;
+use crate::server::state::{AppState, ReplayFrom, SequencedEvent};
+use crate::server::types::{ErrorResponse, ServerEvent};
+
+/// Query parameters for the event stream
+#[derive(Debug, Default, Deserialize)]
+pub struct EventsQuery {
+    /// Replay up to this many events of the current turn before going live,
+    /// for a client attaching to a session another client is driving
+    #[serde(default)]
+    pub replay: usize,
+    /// Resume after this event ID; the `Last-Event-ID` header takes
+    /// precedence, since browsers send it when they reconnect
+    #[serde(default)]
+    pub since: Option<u64>,
+}
+
+/// ID to resume after: `Last-Event-ID`, which browsers send when they
+/// reconnect, or else `?since=`
+pub(super) fn resume_after(headers: &HeaderMap, since: Option<u64>) -> Option<u64> {
+    headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse().ok())
+        .or(since)
+}
 
 /// GET /api/sessions/:id/events - SSE event stream
+///
+/// Every event carries an SSE `id`. A client that lost its connection
+/// reconnects with `Last-Event-ID` (or `?since=`) and gets the events it
+/// missed from the session's buffer before the live stream.
 pub async fn session_events(
     State(state): State<Arc<AppState>>,
     Path(session_id): Path<String>,
-) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    Query(query): Query<EventsQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, Json<ErrorResponse>)> {
+    // Without a session nothing would ever be sent, and a reconnecting
+    // client would wait forever
+    if !state.session_exists(&session_id).await {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        ));
+    }
+
+    let from = match resume_after(&headers, query.since) {
+        Some(id) => ReplayFrom::After(id),
+        None => ReplayFrom::Turn(query.replay),
+    };
+
     // Subscribe to events for this session
-    let rx = state.subscribe_events(&session_id).await;
+    let (replay, rx) = state.subscribe_events_with_replay(&session_id, from).await;
+    if replay.missed {
+        tracing::warn!(
+            "Session {}: events after {:?} were dropped from the replay buffer before the client resumed",
+            session_id,
+            from
+        );
+    }
 
     // Convert broadcast receiver to stream
-    let stream = tokio_stream::wrappers::BroadcastStream::new(rx)
-        .filter_map(|result| {
-            match result {
-                Ok(event) => Some(event),
-                Err(_) => None, // Skip lagged messages
-            }
-        })
-        .map(|event: ServerEvent| {
+    let live = tokio_stream::wrappers::BroadcastStream::new(rx).filter_map(|result| {
+        match result {
+            Ok(event) => Some(event),
+            Err(_) => None, // Skip lagged messages
+        }
+    });
+    let stream = tokio_stream::iter(replay.events)
+        .chain(live)
+        .map(|SequencedEvent { id, event }| {
             // Serialize event to JSON
             let json = serde_json::to_string(&event).unwrap_or_else(|_| "{}".to_string());
 
@@ -39,6 +96,7 @@ pub async fn session_events(
                 ServerEvent::Connected => "Connected",
                 ServerEvent::Thinking { .. } => "Thinking",
                 ServerEvent::Reasoning { .. } => "Reasoning",
+                ServerEvent::ModelReasoning { .. } => "ModelReasoning",
                 ServerEvent::ToolStart { .. } => "ToolStart",
                 ServerEvent::ToolOutput { .. } => "ToolOutput",
                 ServerEvent::BashOutputLine { .. } => "BashOutputLine",
@@ -46,6 +104,7 @@ pub async fn session_events(
                 ServerEvent::FileDiff { .. } => "FileDiff",
                 ServerEvent::DiagnosticUpdate { .. } => "DiagnosticUpdate",
                 ServerEvent::TextChunk { .. } => "TextChunk",
+                ServerEvent::FileLinks { .. } => "FileLinks",
                 ServerEvent::SubagentStarted { .. } => "SubagentStarted",
                 ServerEvent::SubagentProgress { .. } => "SubagentProgress",
                 ServerEvent::SubagentCompleted { .. } => "SubagentCompleted",
@@ -58,9 +117,14 @@ pub async fn session_events(
                 ServerEvent::TokenUsage { .. } => "TokenUsage",
                 ServerEvent::ContextCompressed { .. } => "ContextCompressed",
                 ServerEvent::DoomLoopPrompt { .. } => "DoomLoopPrompt",
+                ServerEvent::ToolApprovalPrompt { .. } => "ToolApprovalPrompt",
                 ServerEvent::Error { .. } => "Error",
                 ServerEvent::Completed => "Completed",
                 ServerEvent::TodoList { .. } => "TodoList",
+                ServerEvent::SkillActivated { .. } => "SkillActivated",
+                ServerEvent::RateLimited { .. } => "RateLimited",
+                ServerEvent::MonitorNotification { .. } => "MonitorNotification",
+                ServerEvent::McpServerHealth { .. } => "McpServerHealth",
                 ServerEvent::OrchestrateStarted { .. } => "OrchestrateStarted",
                 ServerEvent::OrchestrateOutput { .. } => "OrchestrateOutput",
                 ServerEvent::OrchestrateCompleted { .. } => "OrchestrateCompleted",
@@ -68,15 +132,16 @@ pub async fn session_events(
 
             Ok::<_, Infallible>(
                 Event::default()
+                    .id(id.to_string())
                     .event(event_type)
                     .data(json)
             )
         });
 
     // Return SSE response with keep-alive
-    Sse::new(stream).keep_alive(
+    Ok(Sse::new(stream).keep_alive(
         KeepAlive::new()
             .interval(Duration::from_secs(15))
             .text("keep-alive"),
-    )
+    ))
 }
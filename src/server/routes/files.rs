@@ -1,5 +1,6 @@
 //! File operations endpoints
 
+use std::path::{Path as StdPath, PathBuf};
 use std::sync::Arc;
 
 use axum::{
@@ -8,15 +9,31 @@ use axum::{
     Json,
 };
 use serde::{Deserialize, Serialize};
-use walkdir::WalkDir;
 
+use crate::git::diff::FileDiff;
+use crate::git::GitManager;
 use crate::server::state::AppState;
 use crate::server::types::{ErrorResponse, FileChangeDto, FileChangeStats, FileChangesResponse};
+use crate::tools::protected::ProtectedPaths;
+
+/// Directories never listed, even in projects without ignore files
+const SKIPPED_DIRS: &[&str] = &["node_modules", "target", "dist", "build", "__pycache__"];
+
+/// Most entries a tree listing returns
+const MAX_TREE_ENTRIES: usize = 5000;
+
+/// Largest file whose content is returned
+const MAX_CONTENT_BYTES: u64 = 1024 * 1024;
 
 #[derive(Debug, Deserialize)]
 pub struct ListFilesQuery {
+    /// Search file paths instead of listing a directory
     pub query: Option<String>,
     pub limit: Option<usize>,
+    /// Directory to list, relative to the project root
+    pub path: Option<String>,
+    /// Levels below `path` to include (default 1)
+    pub depth: Option<usize>,
 }
 
 #[derive(Debug, Serialize)]
@@ -24,99 +41,310 @@ pub struct ProjectFile {
     pub path: String,
     pub name: String,
     pub is_dir: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
 }
 
 #[derive(Debug, Serialize)]
 pub struct ListFilesResponse {
     pub files: Vec<ProjectFile>,
+    /// More entries matched than were returned
+    pub truncated: bool,
 }
 
-/// GET /api/sessions/:id/files - List files in project directory for @ mentions
-pub async fn list_project_files(
-    State(state): State<Arc<AppState>>,
-    Path(session_id): Path<String>,
-    Query(query): Query<ListFilesQuery>,
-) -> Result<Json<ListFilesResponse>, (StatusCode, Json<ErrorResponse>)> {
-    // Check if session exists and get project path
-    let sessions = state.sessions.read().await;
-    let session = sessions.get(&session_id).ok_or_else(|| {
-        (
+#[derive(Debug, Deserialize)]
+pub struct FileContentQuery {
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileContentResponse {
+    pub path: String,
+    pub size: u64,
+    /// False for binary files; `content` is then omitted
+    pub is_text: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SessionDiffResponse {
+    pub session_id: String,
+    /// Git tree the diff starts from, recorded when the session was created
+    pub base: String,
+    pub files: Vec<FileDiff>,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn error(status: StatusCode, message: String, code: &str) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message,
+            code: code.to_string(),
+        }),
+    )
+}
+
+/// Root of a session's project, which may be a linked git worktree
+async fn project_root(state: &AppState, session_id: &str) -> Result<PathBuf, ApiError> {
+    let handle = state.get_session(session_id).await.ok_or_else(|| {
+        error(
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: format!("Session not found: {}", session_id),
-                code: "SESSION_NOT_FOUND".to_string(),
-            }),
+            format!("Session not found: {}", session_id),
+            "SESSION_NOT_FOUND",
         )
     })?;
+    Ok(handle.project_path)
+}
 
-    let project_path = session.project_path.display().to_string();
-    drop(sessions);
-
-    let search_query = query.query.unwrap_or_default().to_lowercase();
-    let limit = query.limit.unwrap_or(50);
-
-    // Walk directory and collect matching files
-    let mut files: Vec<ProjectFile> = Vec::new();
+/// Resolve a client path inside the project, refusing anything that leads
+/// out of it, including through symlinks
+fn resolve(root: &StdPath, relative: &str) -> Result<PathBuf, ApiError> {
+    let invalid = |message: String| error(StatusCode::BAD_REQUEST, message, "INVALID_PATH");
+    let relative = relative.trim_start_matches('/');
+    let path = root
+        .join(relative)
+        .canonicalize()
+        .map_err(|e| invalid(format!("{}: {}", relative, e)))?;
+    let root = root.canonicalize().map_err(|e| invalid(e.to_string()))?;
+    if !path.starts_with(&root) {
+        return Err(invalid(format!("{} is outside the project", relative)));
+    }
+    Ok(path)
+}
 
-    for entry in WalkDir::new(&project_path)
-        .max_depth(10)
-        .into_iter()
-        .filter_entry(|e| {
-            // Skip hidden files and common ignore patterns
-            let name = e.file_name().to_string_lossy();
-            !name.starts_with('.')
-                && name != "node_modules"
-                && name != "target"
-                && name != "dist"
-                && name != "build"
-                && name != "__pycache__"
-                && name != ".git"
+/// Walker over `dir` honoring .gitignore, .ignore and git's excludes
+///
+/// Hidden entries are skipped, which also keeps the `.git` directory and
+/// orchestration worktrees in `.safe-coder-workspaces` out of listings.
+fn walker(dir: &StdPath, max_depth: usize) -> ignore::Walk {
+    ignore::WalkBuilder::new(dir)
+        .max_depth(Some(max_depth))
+        .hidden(true)
+        .git_ignore(true)
+        .git_global(false)
+        .git_exclude(true)
+        .follow_links(false)
+        .filter_entry(|entry| {
+            !(entry.file_type().is_some_and(|t| t.is_dir())
+                && SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()))
         })
-    {
-        if files.len() >= limit {
-            break;
+        .build()
+}
+
+/// Whether listings show `path`, a canonical path under the canonical
+/// `root`: every component has to pass the same filter as [`walker`]
+fn listed(root: &StdPath, path: &StdPath) -> bool {
+    let Ok(relative) = path.strip_prefix(root) else {
+        return false;
+    };
+    let mut dir = root.to_path_buf();
+    for component in relative.components() {
+        let child = dir.join(component);
+        let shown = walker(&dir, 1)
+            .flatten()
+            .any(|entry| entry.depth() == 1 && entry.path() == child);
+        if !shown {
+            return false;
         }
+        dir = child;
+    }
+    true
+}
 
-        if let Ok(entry) = entry {
-            let path = entry.path();
-            let relative_path = path
-                .strip_prefix(&project_path)
-                .unwrap_or(path)
-                .to_string_lossy()
-                .to_string();
+fn project_file(root: &StdPath, entry: &ignore::DirEntry) -> ProjectFile {
+    let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+    ProjectFile {
+        path: entry
+            .path()
+            .strip_prefix(root)
+            .unwrap_or(entry.path())
+            .to_string_lossy()
+            .to_string(),
+        name: entry.file_name().to_string_lossy().to_string(),
+        is_dir,
+        size: (!is_dir)
+            .then(|| entry.metadata().ok().map(|m| m.len()))
+            .flatten(),
+    }
+}
 
-            // Skip root directory
-            if relative_path.is_empty() {
+/// GET /api/sessions/:id/files - List project files
+///
+/// With `query`, searches file paths for @ mentions, shortest first. Without
+/// it, lists the directory `path` down to `depth` levels as a tree, folders
+/// first. Both skip what the project's ignore files exclude.
+pub async fn list_project_files(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(query): Query<ListFilesQuery>,
+) -> Result<Json<ListFilesResponse>, ApiError> {
+    let root = project_root(&state, &session_id).await?;
+
+    if let Some(search) = query.query.as_deref().filter(|q| !q.is_empty()) {
+        let search = search.to_lowercase();
+        let limit = query.limit.unwrap_or(50);
+        let mut files: Vec<ProjectFile> = Vec::new();
+        let mut truncated = false;
+        for entry in walker(&root, 10).flatten() {
+            if entry.depth() == 0 || !entry.file_type().is_some_and(|t| t.is_file()) {
                 continue;
             }
-
-            let name = entry.file_name().to_string_lossy().to_string();
-            let is_dir = entry.file_type().is_dir();
-
-            // Filter by search query if provided
-            if !search_query.is_empty() {
-                if !relative_path.to_lowercase().contains(&search_query)
-                    && !name.to_lowercase().contains(&search_query)
-                {
-                    continue;
-                }
+            let file = project_file(&root, &entry);
+            if !file.path.to_lowercase().contains(&search) {
+                continue;
             }
-
-            // Prefer files over directories for @ mentions
-            if !is_dir {
-                files.push(ProjectFile {
-                    path: relative_path,
-                    name,
-                    is_dir,
-                });
+            if files.len() >= limit {
+                truncated = true;
+                break;
             }
+            files.push(file);
         }
+        // Sort by path length (shorter = more relevant)
+        files.sort_by(|a, b| a.path.len().cmp(&b.path.len()));
+        return Ok(Json(ListFilesResponse { files, truncated }));
+    }
+
+    let dir = resolve(&root, query.path.as_deref().unwrap_or(""))?;
+    if !dir.is_dir() {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            format!("Not a directory: {}", query.path.unwrap_or_default()),
+            "NOT_A_DIRECTORY",
+        ));
     }
+    let limit = query.limit.unwrap_or(MAX_TREE_ENTRIES).min(MAX_TREE_ENTRIES);
+    let root = root.canonicalize().unwrap_or(root);
+    let mut files: Vec<ProjectFile> = Vec::new();
+    let mut truncated = false;
+    for entry in walker(&dir, query.depth.unwrap_or(1).max(1)).flatten() {
+        if entry.depth() == 0 {
+            continue;
+        }
+        if files.len() >= limit {
+            truncated = true;
+            break;
+        }
+        files.push(project_file(&root, &entry));
+    }
+    // Grouped by directory, folders first
+    files.sort_by(|a, b| {
+        let parent = |f: &ProjectFile| StdPath::new(&f.path).parent().map(|p| p.to_path_buf());
+        parent(a)
+            .cmp(&parent(b))
+            .then(b.is_dir.cmp(&a.is_dir))
+            .then(a.name.cmp(&b.name))
+    });
+
+    Ok(Json(ListFilesResponse { files, truncated }))
+}
+
+/// GET /api/sessions/:id/files/content?path= - A project file's content
+///
+/// Files the listings leave out (hidden, ignored or in a skipped directory)
+/// and `tools.protected_paths` are refused, so `.env` and the like aren't
+/// served. Files over 1 MiB are refused too; binary files are reported
+/// without content.
+pub async fn get_file_content(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Query(query): Query<FileContentQuery>,
+) -> Result<Json<FileContentResponse>, ApiError> {
+    let root = project_root(&state, &session_id).await?;
+    let path = resolve(&root, &query.path)?;
+    let root = root.canonicalize().unwrap_or(root);
+    let protected = ProtectedPaths::new(&state.config.read().await.tools.protected_paths);
+    let relative = path.strip_prefix(&root).unwrap_or(&path).to_string_lossy();
+    if protected.matching_pattern(&root, &relative).is_some() || !listed(&root, &path) {
+        return Err(error(
+            StatusCode::FORBIDDEN,
+            format!("{} is ignored or protected, so it can't be fetched", query.path),
+            "FILE_HIDDEN",
+        ));
+    }
+    let metadata = std::fs::metadata(&path)
+        .map_err(|e| error(StatusCode::NOT_FOUND, e.to_string(), "FILE_NOT_FOUND"))?;
+    if !metadata.is_file() {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            format!("Not a file: {}", query.path),
+            "NOT_A_FILE",
+        ));
+    }
+    if metadata.len() > MAX_CONTENT_BYTES {
+        return Err(error(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "{} is {} bytes; files over {} bytes can't be fetched",
+                query.path,
+                metadata.len(),
+                MAX_CONTENT_BYTES
+            ),
+            "FILE_TOO_LARGE",
+        ));
+    }
+
+    let bytes = std::fs::read(&path).map_err(|e| {
+        error(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to read {}: {}", query.path, e),
+            "FILE_READ_FAILED",
+        )
+    })?;
+    // NUL bytes are valid UTF-8, but no text file has them
+    let content = String::from_utf8(bytes).ok().filter(|text| !text.contains('\0'));
+    Ok(Json(FileContentResponse {
+        path: query.path,
+        size: metadata.len(),
+        is_text: content.is_some(),
+        content,
+    }))
+}
+
+/// GET /api/sessions/:id/diff - Everything changed since the session started
+///
+/// Compares the project, untracked files included, with the snapshot taken
+/// when the session was created, so commits made during the session are
+/// part of the diff and changes that predate it are not.
+pub async fn get_session_diff(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<SessionDiffResponse>, ApiError> {
+    let handle = state.get_session(&session_id).await.ok_or_else(|| {
+        error(
+            StatusCode::NOT_FOUND,
+            format!("Session not found: {}", session_id),
+            "SESSION_NOT_FOUND",
+        )
+    })?;
+    let base = handle.start_snapshot.clone().ok_or_else(|| {
+        error(
+            StatusCode::CONFLICT,
+            "The project is not a git repository, so the session has no start snapshot"
+                .to_string(),
+            "NO_SNAPSHOT",
+        )
+    })?;
 
-    // Sort by path length (shorter = more relevant)
-    files.sort_by(|a, b| a.path.len().cmp(&b.path.len()));
+    let (_, files) = GitManager::new(handle.project_path.clone())
+        .diff_since(&base)
+        .await
+        .map_err(|e| error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string(), "DIFF_FAILED"))?;
+    let (additions, deletions) = files
+        .iter()
+        .fold((0, 0), |(a, d), f| (a + f.additions, d + f.deletions));
 
-    Ok(Json(ListFilesResponse { files }))
+    Ok(Json(SessionDiffResponse {
+        session_id,
+        base,
+        files,
+        additions,
+        deletions,
+    }))
 }
 
 /// GET /api/sessions/:id/changes - Get file changes for a session using git
@@ -280,3 +508,36 @@ fn get_file_diff(project_path: &std::path::Path, file_path: &str, change_type: &
 
     (additions, deletions, Some(diff_str))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_stays_in_project() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        assert!(resolve(dir.path(), "src").unwrap().ends_with("src"));
+        assert!(resolve(dir.path(), "/src").is_ok());
+        assert!(resolve(dir.path(), "src/../..").is_err());
+        assert!(resolve(dir.path(), "missing").is_err());
+    }
+
+    #[test]
+    fn test_listed_follows_the_tree_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().canonicalize().unwrap();
+        for sub in [".git", "src", "out", "node_modules"] {
+            std::fs::create_dir(root.join(sub)).unwrap();
+        }
+        std::fs::write(root.join(".gitignore"), "secret.txt\nout/\n").unwrap();
+        for file in ["src/main.rs", "secret.txt", ".env", "out/app", "node_modules/a.js"] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        assert!(listed(&root, &root.join("src/main.rs")));
+        for hidden in ["secret.txt", ".env", "out/app", "node_modules/a.js"] {
+            assert!(!listed(&root, &root.join(hidden)), "{}", hidden);
+        }
+    }
+}
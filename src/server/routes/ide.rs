@@ -0,0 +1,88 @@
+//! Editor attachment endpoints (`/api/ide/*`)
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::{
+    extract::Query,
+    http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures::stream::Stream;
+use serde::Deserialize;
+use tokio_stream::StreamExt;
+
+use crate::ide::{self, EditorContext};
+use crate::server::types::ErrorResponse;
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectQuery {
+    pub project_path: Option<String>,
+}
+
+/// GET /api/ide/context - Attached editors and what they show
+pub async fn get_context() -> Json<Vec<EditorContext>> {
+    Json(ide::attached())
+}
+
+/// PUT /api/ide/context - Attach an editor or update its file and selection
+pub async fn set_context(
+    Json(mut context): Json<EditorContext>,
+) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+    // Sessions use canonical paths, so match them
+    context.project_path = context.project_path.canonicalize().map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Invalid project path: {}", e),
+                code: "INVALID_PROJECT_PATH".to_string(),
+            }),
+        )
+    })?;
+    ide::attach(context);
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// DELETE /api/ide/context?project_path=... - Detach an editor
+pub async fn clear_context(Query(query): Query<ProjectQuery>) -> StatusCode {
+    let Some(path) = query.project_path else {
+        return StatusCode::BAD_REQUEST;
+    };
+    let path = std::path::PathBuf::from(path);
+    let path = path.canonicalize().unwrap_or(path);
+    if ide::detach(&path) {
+        StatusCode::NO_CONTENT
+    } else {
+        StatusCode::NOT_FOUND
+    }
+}
+
+/// GET /api/ide/events?project_path=... - Files the agent wants shown (SSE)
+pub async fn ide_events(
+    Query(query): Query<ProjectQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let project = query
+        .project_path
+        .map(std::path::PathBuf::from)
+        .map(|p| p.canonicalize().unwrap_or(p));
+
+    let stream = tokio_stream::wrappers::BroadcastStream::new(ide::subscribe())
+        .filter_map(move |result| {
+            let request = result.ok()?;
+            match &project {
+                Some(project) if &request.project_path != project => None,
+                _ => Some(request),
+            }
+        })
+        .map(|request| {
+            let json = serde_json::to_string(&request).unwrap_or_else(|_| "{}".to_string());
+            Ok::<_, Infallible>(Event::default().event("OpenFile").data(json))
+        });
+
+    Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(15))
+            .text("keep-alive"),
+    )
+}
@@ -1,5 +1,6 @@
 //! Message handling endpoints
 
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use axum::{
@@ -9,9 +10,14 @@ use axum::{
 };
 use tokio::sync::mpsc;
 
-use crate::server::state::AppState;
-use crate::server::types::{ErrorResponse, MessageDto, SendMessageRequest, ServerEvent};
-use crate::session::SessionEvent;
+use crate::notifications::webhook::WebhookEvent;
+use crate::planning::PlanEvent;
+use crate::server::state::{AppState, PendingApproval};
+use crate::server::types::{
+    ErrorResponse, FileLinkDto, MessageDto, SendMessageRequest, ServerEvent,
+};
+use crate::session::attachments::{self, Attachment};
+use crate::session::{MessageOverrides, SessionEvent};
 
 /// GET /api/sessions/:id/messages - Get message history
 pub async fn get_messages(
@@ -51,6 +57,7 @@ pub async fn get_messages(
                     Some(MessageDto {
                         id: format!("msg_{}", i),
                         role: format!("{:?}", msg.role).to_lowercase(),
+                        links: FileLinkDto::find(&content, &handle.project_path),
                         content,
                         timestamp: chrono::Utc::now().to_rfc3339(), // TODO: store timestamps
                         tool_calls: None, // TODO: extract tool calls
@@ -95,6 +102,26 @@ pub async fn send_message(
         }
     };
 
+    // Read attachments before taking the session, so a bad path is a 400
+    let mut resolved = Vec::with_capacity(request.attachments.len());
+    for attachment in &request.attachments {
+        let attachment = match &attachment.content {
+            Some(content) => Attachment::with_content(attachment.path.clone(), content.clone()),
+            None => attachments::resolve(&attachment.path, &handle.project_path)
+                .await
+                .map_err(|e| {
+                    (
+                        StatusCode::BAD_REQUEST,
+                        Json(ErrorResponse {
+                            error: e.to_string(),
+                            code: "INVALID_ATTACHMENT".to_string(),
+                        }),
+                    )
+                })?,
+        };
+        resolved.push(attachment);
+    }
+
     // Check if already processing
     {
         let is_processing = handle.is_processing.read().await;
@@ -128,8 +155,12 @@ pub async fn send_message(
     let event_sender_clone = event_sender.clone();
     let session_id_clone = session_id.clone();
     let state_clone = Arc::clone(&state);
+    let project = handle.project_path.display().to_string();
+    let mut file_links = FileLinkTracker::new(handle.project_path.clone());
     tokio::spawn(async move {
         tracing::info!("Event forwarding task started for session {}", session_id_clone);
+        // (worker, task) of running sub-agents, for failure webhooks
+        let mut orchestrations = std::collections::HashMap::new();
         while let Some(event) = session_rx.recv().await {
             tracing::debug!("Forwarding event: {:?}", std::mem::discriminant(&event));
 
@@ -147,6 +178,14 @@ pub async fn send_message(
                 _ => {}
             }
 
+            if let Some(webhooks) = &state_clone.webhooks {
+                if let Some(webhook_event) =
+                    webhook_event(&event, &session_id_clone, &project, &mut orchestrations)
+                {
+                    webhooks.notify(webhook_event);
+                }
+            }
+
             // Handle file diff events specially to track changes
             if let SessionEvent::FileDiff { ref path, ref old_content, ref new_content } = event {
                 tracing::info!("FileDiff event received for path: {}", path);
@@ -185,15 +224,65 @@ pub async fn send_message(
                 state_clone.register_doom_loop_response(prompt_id.clone(), response_tx.clone()).await;
             }
 
+            // Handle tool approval prompts - register response channel with state
+            if let SessionEvent::ToolApprovalPrompt {
+                ref prompt_id,
+                ref tool,
+                ref description,
+                high_risk,
+                ref response_tx,
+                ..
+            } = event
+            {
+                tracing::info!("ToolApprovalPrompt event received, registering response channel: {}", prompt_id);
+                state_clone
+                    .register_tool_approval_response(
+                        prompt_id.clone(),
+                        PendingApproval {
+                            session_id: session_id_clone.clone(),
+                            tool: tool.clone(),
+                            description: description.clone(),
+                            high_risk,
+                            requested_at: chrono::Utc::now(),
+                            response_tx: response_tx.clone(),
+                        },
+                    )
+                    .await;
+            }
+
+            let links = match &event {
+                SessionEvent::TextChunk(text) => file_links.push_text(text),
+                SessionEvent::ToolOutput { output, .. } => {
+                    let mut links = file_links.flush();
+                    links.extend(file_links.links_in(output));
+                    links
+                }
+                _ => file_links.flush(),
+            };
+
             // Convert and broadcast
             let server_event: ServerEvent = event.into();
             let _ = event_sender_clone.send(server_event);
+            if !links.is_empty() {
+                let _ = event_sender_clone.send(ServerEvent::FileLinks { links });
+            }
+        }
+
+        let links = file_links.flush();
+        if !links.is_empty() {
+            let _ = event_sender_clone.send(ServerEvent::FileLinks { links });
         }
     });
 
     // Send message to session (in background)
     let handle_clone = handle.clone();
-    let message = request.content.clone();
+    let message = attachments::append_to_message(&request.content, &resolved);
+    let images = attachments::images(&resolved);
+    let overrides = MessageOverrides {
+        allowed_tools: request.allowed_tools.clone(),
+        model: request.model.clone(),
+        shell_snippets: request.shell_snippets.clone(),
+    };
     let session_id_for_log = session_id.clone();
     let state_clone = Arc::clone(&state);
     tokio::spawn(async move {
@@ -205,7 +294,16 @@ pub async fn send_message(
         // Use send_message_with_progress for proper build/plan mode handling
         // This bypasses unified planning and uses direct execution like the TUI
         tracing::info!("Calling send_message_with_progress, agent_mode: {:?}", session.agent_mode());
-        match session.send_message_with_progress(message, session_tx).await {
+        let result = if !overrides.is_empty() {
+            session
+                .send_message_with_overrides(message, images, overrides, session_tx)
+                .await
+        } else {
+            session
+                .send_message_with_images_and_progress(message, images, session_tx)
+                .await
+        };
+        match result {
             Ok(response) => {
                 tracing::info!("Message processed successfully, response length: {}", response.len());
 
@@ -278,3 +376,101 @@ pub async fn cancel_operation(
         )),
     }
 }
+
+/// Webhook message for a session event, if it's one teams want to hear about
+/// Finds file references in streamed text for `FileLinks` events. Chunks
+/// can split a path, so only text up to the last whitespace is scanned
+/// until the stream moves on.
+struct FileLinkTracker {
+    project: PathBuf,
+    pending: String,
+    sent: Vec<FileLinkDto>,
+}
+
+impl FileLinkTracker {
+    fn new(project: PathBuf) -> Self {
+        Self {
+            project,
+            pending: String::new(),
+            sent: Vec::new(),
+        }
+    }
+
+    /// Add a text chunk, returning new links in the words it completed
+    fn push_text(&mut self, text: &str) -> Vec<FileLinkDto> {
+        self.pending.push_str(text);
+        let Some(end) = self.pending.rfind(char::is_whitespace) else {
+            return Vec::new();
+        };
+        let complete: String = self.pending.drain(..end).collect();
+        self.links_in(&complete)
+    }
+
+    /// New links in the rest of the streamed text
+    fn flush(&mut self) -> Vec<FileLinkDto> {
+        let rest = std::mem::take(&mut self.pending);
+        self.links_in(&rest)
+    }
+
+    /// Links in `text` that haven't been sent yet
+    fn links_in(&mut self, text: &str) -> Vec<FileLinkDto> {
+        let links: Vec<FileLinkDto> = FileLinkDto::find(text, &self.project)
+            .into_iter()
+            .filter(|link| !self.sent.contains(link))
+            .collect();
+        self.sent.extend(links.iter().cloned());
+        links
+    }
+}
+
+fn webhook_event(
+    event: &SessionEvent,
+    session_id: &str,
+    project: &str,
+    orchestrations: &mut std::collections::HashMap<String, (String, String)>,
+) -> Option<WebhookEvent> {
+    let approval = |what: String| {
+        Some(WebhookEvent::ApprovalNeeded {
+            what,
+            project: project.to_string(),
+            session_id: session_id.to_string(),
+        })
+    };
+    match event {
+        SessionEvent::ToolApprovalPrompt {
+            tool, description, ..
+        } => approval(format!("{}: {}", tool, description)),
+        SessionEvent::DoomLoopPrompt { message, .. } => approval(message.clone()),
+        SessionEvent::Plan(PlanEvent::AwaitingApproval { plan_id }) => {
+            approval(format!("Plan {} is waiting for review", plan_id))
+        }
+        SessionEvent::OrchestrateStarted { id, worker, task } => {
+            orchestrations.insert(id.clone(), (worker.clone(), task.clone()));
+            None
+        }
+        SessionEvent::OrchestrateCompleted {
+            id,
+            success,
+            output,
+        } => {
+            let (worker, task) = orchestrations.remove(id).unwrap_or_default();
+            Some(if *success {
+                WebhookEvent::OrchestrationComplete {
+                    request: task,
+                    succeeded: 1,
+                    failed: 0,
+                    summary: output.clone(),
+                    session_id: Some(session_id.to_string()),
+                }
+            } else {
+                WebhookEvent::TaskFailed {
+                    task,
+                    worker,
+                    error: output.clone(),
+                    session_id: Some(session_id.to_string()),
+                }
+            })
+        }
+        _ => None,
+    }
+}
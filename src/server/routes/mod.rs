@@ -2,11 +2,16 @@
 //!
 //! This module contains all HTTP route handlers for the server.
 
+pub mod approvals;
+pub mod checkpoints;
 pub mod config;
 pub mod events;
 pub mod files;
 pub mod health;
+pub mod ide;
 pub mod messages;
 pub mod openapi;
+pub mod orchestrations;
+pub mod projects;
 pub mod pty;
 pub mod sessions;
@@ -54,6 +54,219 @@ pub async fn openapi_spec() -> Json<serde_json::Value> {
                     }
                 }
             },
+            "/api/approvals": {
+                "get": {
+                    "summary": "List tool calls waiting for approval in any session",
+                    "operationId": "listApprovals",
+                    "responses": {
+                        "200": {
+                            "description": "Pending approvals, oldest first",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "approvals": {
+                                                "type": "array",
+                                                "items": { "$ref": "#/components/schemas/PendingApproval" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
+            "/api/approvals/{prompt_id}": {
+                "post": {
+                    "summary": "Approve or deny a pending tool call",
+                    "operationId": "respondToApproval",
+                    "parameters": [
+                        {
+                            "name": "prompt_id",
+                            "in": "path",
+                            "required": true,
+                            "schema": { "type": "string" }
+                        }
+                    ],
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "object",
+                                    "required": ["approved"],
+                                    "properties": { "approved": { "type": "boolean" } }
+                                }
+                            }
+                        }
+                    },
+                    "responses": {
+                        "200": { "description": "Answer delivered to the session" },
+                        "404": { "description": "Prompt not found or already answered" }
+                    }
+                }
+            },
+            "/api/orchestrations": {
+                "get": {
+                    "summary": "List orchestration runs started on this server, newest first",
+                    "operationId": "listOrchestrations",
+                    "responses": {
+                        "200": {
+                            "description": "Orchestration runs",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "orchestrations": {
+                                                "type": "array",
+                                                "items": { "$ref": "#/components/schemas/Orchestration" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "post": {
+                    "summary": "Plan a request and run its tasks with CLI workers",
+                    "operationId": "createOrchestration",
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "object",
+                                    "required": ["project_path", "request"],
+                                    "properties": {
+                                        "project_path": { "type": "string", "description": "Root of a git repository" },
+                                        "request": { "type": "string" },
+                                        "worker": { "type": "string", "description": "claude, gemini, safe-coder or copilot" },
+                                        "max_workers": { "type": "integer" }
+                                    }
+                                }
+                            }
+                        }
+                    },
+                    "responses": {
+                        "201": {
+                            "description": "Run started",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Orchestration" }
+                                }
+                            }
+                        },
+                        "400": { "description": "Invalid path, not a git repository, or empty request" }
+                    }
+                }
+            },
+            "/api/orchestrations/{id}": {
+                "get": {
+                    "summary": "Get a run's plan, task progress and outcome",
+                    "operationId": "getOrchestration",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "The run",
+                            "content": {
+                                "application/json": {
+                                    "schema": { "$ref": "#/components/schemas/Orchestration" }
+                                }
+                            }
+                        },
+                        "404": { "description": "Orchestration not found" }
+                    }
+                }
+            },
+            "/api/orchestrations/{id}/events": {
+                "get": {
+                    "summary": "Stream a run's task events (SSE)",
+                    "description": "Event types: Planned, TaskStarted, TaskOutput, TaskFinished and Finished, after which the stream ends. Buffered events are sent first; resume with Last-Event-ID or ?since=.",
+                    "operationId": "streamOrchestrationEvents",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "since", "in": "query", "required": false, "schema": { "type": "integer" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "SSE event stream" },
+                        "404": { "description": "Orchestration not found" }
+                    }
+                }
+            },
+            "/api/orchestrations/{id}/tasks/{task_id}/cancel": {
+                "post": {
+                    "summary": "Stop a running task; the others keep going",
+                    "operationId": "cancelOrchestrationTask",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "task_id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Cancel requested" },
+                        "404": { "description": "Orchestration or task not found" },
+                        "409": { "description": "Task is not running" }
+                    }
+                }
+            },
+            "/api/orchestrations/{id}/artifacts": {
+                "get": {
+                    "summary": "List the tasks recorded in a run's artifacts, with their outcome",
+                    "operationId": "listOrchestrationArtifacts",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "Run ID and tasks" },
+                        "409": { "description": "The run's tasks have not finished yet" }
+                    }
+                }
+            },
+            "/api/orchestrations/{id}/artifacts/{task_id}": {
+                "get": {
+                    "summary": "Get a task's prompt, stdout, stderr, diff and outcome",
+                    "description": "Files over 512 KiB are cut to their end and listed in `truncated`.",
+                    "operationId": "getTaskArtifacts",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "task_id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": { "description": "The task's artifacts" },
+                        "404": { "description": "Orchestration or task not found" },
+                        "409": { "description": "The run's tasks have not finished yet" }
+                    }
+                }
+            },
+            "/api/projects": {
+                "get": {
+                    "summary": "List pinned and recently opened projects",
+                    "operationId": "listProjects",
+                    "responses": {
+                        "200": {
+                            "description": "Projects, pinned first, with the overrides new sessions there use",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "projects": {
+                                                "type": "array",
+                                                "items": { "$ref": "#/components/schemas/Project" }
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            },
             "/api/sessions": {
                 "get": {
                     "summary": "List sessions",
@@ -170,6 +383,24 @@ pub async fn openapi_spec() -> Json<serde_json::Value> {
                             "in": "path",
                             "required": true,
                             "schema": { "type": "string" }
+                        },
+                        {
+                            "name": "replay",
+                            "in": "query",
+                            "description": "Replay up to this many events of the current turn before streaming live",
+                            "schema": { "type": "integer", "minimum": 0 }
+                        },
+                        {
+                            "name": "since",
+                            "in": "query",
+                            "description": "Resume after this event ID; the Last-Event-ID header takes precedence",
+                            "schema": { "type": "integer", "minimum": 0 }
+                        },
+                        {
+                            "name": "Last-Event-ID",
+                            "in": "header",
+                            "description": "ID of the last event received, sent when reconnecting",
+                            "schema": { "type": "string" }
                         }
                     ],
                     "responses": {
@@ -178,7 +409,8 @@ pub async fn openapi_spec() -> Json<serde_json::Value> {
                             "content": {
                                 "text/event-stream": {}
                             }
-                        }
+                        },
+                        "404": { "description": "Session not found" }
                     }
                 }
             },
@@ -199,6 +431,217 @@ pub async fn openapi_spec() -> Json<serde_json::Value> {
                     }
                 }
             },
+            "/api/sessions/{id}/diff": {
+                "get": {
+                    "summary": "Get everything changed since the session started",
+                    "description": "Diffs the project, untracked files included, against the snapshot taken when the session was created, as structured hunks.",
+                    "operationId": "getSessionDiff",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "Changed files with their hunks",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "session_id": { "type": "string" },
+                                            "base": { "type": "string", "description": "Git tree of the start snapshot" },
+                                            "files": { "type": "array", "items": { "$ref": "#/components/schemas/FileDiff" } },
+                                            "additions": { "type": "integer" },
+                                            "deletions": { "type": "integer" }
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        "404": { "description": "Session not found" },
+                        "409": { "description": "The project is not a git repository" }
+                    }
+                }
+            },
+            "/api/sessions/{id}/checkpoints": {
+                "get": {
+                    "summary": "List the project's checkpoints, oldest first",
+                    "operationId": "listCheckpoints",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "Checkpoints, and whether one is taken before each message",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "session_id": { "type": "string" },
+                                            "enabled": { "type": "boolean" },
+                                            "checkpoints": { "type": "array", "items": { "$ref": "#/components/schemas/Checkpoint" } }
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+                "post": {
+                    "summary": "Take a checkpoint now",
+                    "operationId": "createCheckpoint",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "requestBody": {
+                        "required": false,
+                        "content": {
+                            "application/json": {
+                                "schema": { "type": "object", "properties": { "label": { "type": "string" } } }
+                            }
+                        }
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "The checkpoint and the files that differ from it now",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/CheckpointDetail" } } }
+                        },
+                        "409": { "description": "Checkpoints are disabled" }
+                    }
+                }
+            },
+            "/api/sessions/{id}/checkpoints/{checkpoint_id}": {
+                "get": {
+                    "summary": "Get a checkpoint with the files changed since",
+                    "operationId": "getCheckpoint",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "checkpoint_id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "The checkpoint and the files that differ from it now",
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/CheckpointDetail" } } }
+                        },
+                        "404": { "description": "Checkpoint not found" }
+                    }
+                }
+            },
+            "/api/sessions/{id}/checkpoints/{checkpoint_id}/restore": {
+                "post": {
+                    "summary": "Restore a checkpoint, or single files from it",
+                    "description": "Without `files`, writes back every file in the checkpoint and keeps files created since. With `files`, restores only those, removing listed files created since the checkpoint; a listed file it never captured, such as an ignored one, fails the request.",
+                    "operationId": "restoreCheckpoint",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "checkpoint_id", "in": "path", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "requestBody": {
+                        "required": false,
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "type": "object",
+                                    "properties": { "files": { "type": "array", "items": { "type": "string" } } }
+                                }
+                            }
+                        }
+                    },
+                    "responses": {
+                        "200": {
+                            "description": "Files restored",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "checkpoint_id": { "type": "string" },
+                                            "restored": { "type": "array", "items": { "type": "string" } }
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        "400": { "description": "A listed path is invalid" },
+                        "404": { "description": "Checkpoint not found, or a listed file isn't in it" },
+                        "409": { "description": "The session is processing a message" }
+                    }
+                }
+            },
+            "/api/sessions/{id}/files": {
+                "get": {
+                    "summary": "List project files, honoring the project's ignore files",
+                    "description": "Without `query`, lists the directory `path` down to `depth` levels, grouped by directory with folders first (at most 5000 entries). With `query`, searches file paths, shortest first.",
+                    "operationId": "listProjectFiles",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "path", "in": "query", "required": false, "schema": { "type": "string" }, "description": "Directory relative to the project root" },
+                        { "name": "depth", "in": "query", "required": false, "schema": { "type": "integer", "default": 1 } },
+                        { "name": "query", "in": "query", "required": false, "schema": { "type": "string" } },
+                        { "name": "limit", "in": "query", "required": false, "schema": { "type": "integer" } }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "Files and folders",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "files": {
+                                                "type": "array",
+                                                "items": {
+                                                    "type": "object",
+                                                    "properties": {
+                                                        "path": { "type": "string" },
+                                                        "name": { "type": "string" },
+                                                        "is_dir": { "type": "boolean" },
+                                                        "size": { "type": "integer" }
+                                                    }
+                                                }
+                                            },
+                                            "truncated": { "type": "boolean" }
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        "400": { "description": "Path is outside the project or not a directory" }
+                    }
+                }
+            },
+            "/api/sessions/{id}/files/content": {
+                "get": {
+                    "summary": "Get a project file's content",
+                    "operationId": "getFileContent",
+                    "parameters": [
+                        { "name": "id", "in": "path", "required": true, "schema": { "type": "string" } },
+                        { "name": "path", "in": "query", "required": true, "schema": { "type": "string" } }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "The file; binary files have `is_text: false` and no content",
+                            "content": {
+                                "application/json": {
+                                    "schema": {
+                                        "type": "object",
+                                        "properties": {
+                                            "path": { "type": "string" },
+                                            "size": { "type": "integer" },
+                                            "is_text": { "type": "boolean" },
+                                            "content": { "type": "string" }
+                                        }
+                                    }
+                                }
+                            }
+                        },
+                        "400": { "description": "Path is outside the project or not a file" },
+                        "403": { "description": "File is hidden, ignored or in tools.protected_paths" },
+                        "404": { "description": "File not found" },
+                        "413": { "description": "File is over 1 MiB" }
+                    }
+                }
+            },
             "/api/sessions/{id}/pty": {
                 "get": {
                     "summary": "PTY WebSocket connection",
@@ -215,9 +658,82 @@ pub async fn openapi_spec() -> Json<serde_json::Value> {
                         "101": { "description": "WebSocket upgrade" }
                     }
                 }
+            },
+            "/api/ide/context": {
+                "get": {
+                    "summary": "List attached editors",
+                    "operationId": "getIdeContext",
+                    "responses": {
+                        "200": { "description": "Attached editors with their file and selection" }
+                    }
+                },
+                "put": {
+                    "summary": "Attach an editor or update its file and selection",
+                    "operationId": "setIdeContext",
+                    "requestBody": {
+                        "required": true,
+                        "content": {
+                            "application/json": {
+                                "schema": {
+                                    "$ref": "#/components/schemas/EditorContext"
+                                }
+                            }
+                        }
+                    },
+                    "responses": {
+                        "204": { "description": "Editor attached" },
+                        "400": { "description": "Invalid project path" }
+                    }
+                },
+                "delete": {
+                    "summary": "Detach an editor",
+                    "operationId": "clearIdeContext",
+                    "parameters": [
+                        {
+                            "name": "project_path",
+                            "in": "query",
+                            "required": true,
+                            "schema": { "type": "string" }
+                        }
+                    ],
+                    "responses": {
+                        "204": { "description": "Editor detached" },
+                        "404": { "description": "No editor attached to the project" }
+                    }
+                }
+            },
+            "/api/ide/events": {
+                "get": {
+                    "summary": "Files the agent asks the editor to open (SSE)",
+                    "operationId": "ideEvents",
+                    "parameters": [
+                        {
+                            "name": "project_path",
+                            "in": "query",
+                            "required": false,
+                            "schema": { "type": "string" }
+                        }
+                    ],
+                    "responses": {
+                        "200": {
+                            "description": "OpenFile events with project_path, path and line",
+                            "content": {
+                                "text/event-stream": {}
+                            }
+                        }
+                    }
+                }
             }
         },
+        "security": [{ "bearerAuth": [] }],
         "components": {
+            "securitySchemes": {
+                "bearerAuth": {
+                    "type": "http",
+                    "scheme": "bearer",
+                    "description": "Required once `[[server.tokens]]` are configured. GET needs a viewer token, other methods an operator token, and deleting sessions or opening a PTY an admin token. Event streams and WebSockets may pass `?access_token=` instead."
+                }
+            },
             "schemas": {
                 "HealthResponse": {
                     "type": "object",
@@ -239,7 +755,127 @@ pub async fn openapi_spec() -> Json<serde_json::Value> {
                     "required": ["project_path"],
                     "properties": {
                         "project_path": { "type": "string" },
-                        "mode": { "type": "string" }
+                        "mode": { "type": "string" },
+                        "model": { "type": "string" },
+                        "permission_preset": { "type": "string", "enum": ["safe", "dev", "full", "yolo"] }
+                    }
+                },
+                "Orchestration": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "project_path": { "type": "string" },
+                        "request": { "type": "string" },
+                        "created_at": { "type": "string", "format": "date-time" },
+                        "status": { "type": "string", "enum": ["planning", "running", "completed", "failed"] },
+                        "run_id": { "type": "string", "nullable": true },
+                        "tasks": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "id": { "type": "string" },
+                                    "description": { "type": "string" },
+                                    "worker": { "type": "string" },
+                                    "status": { "type": "string", "enum": ["pending", "running", "cancelling", "completed", "failed", "cancelled"] },
+                                    "error": { "type": "string" }
+                                }
+                            }
+                        },
+                        "merged_tasks": { "type": "array", "items": { "type": "string" } },
+                        "branches": { "type": "array", "items": { "type": "string" } },
+                        "summary": { "type": "string", "nullable": true },
+                        "error": { "type": "string", "nullable": true }
+                    }
+                },
+                "FileDiff": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string" },
+                        "old_path": { "type": "string", "description": "Previous path of a renamed file" },
+                        "change_type": { "type": "string", "enum": ["created", "modified", "deleted", "renamed"] },
+                        "additions": { "type": "integer" },
+                        "deletions": { "type": "integer" },
+                        "binary": { "type": "boolean" },
+                        "hunks": { "type": "array", "items": { "$ref": "#/components/schemas/DiffHunk" } }
+                    }
+                },
+                "DiffHunk": {
+                    "type": "object",
+                    "properties": {
+                        "header": { "type": "string" },
+                        "old_start": { "type": "integer" },
+                        "old_lines": { "type": "integer" },
+                        "new_start": { "type": "integer" },
+                        "new_lines": { "type": "integer" },
+                        "lines": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "kind": { "type": "string", "enum": ["context", "added", "removed"] },
+                                    "content": { "type": "string" },
+                                    "old_line": { "type": "integer" },
+                                    "new_line": { "type": "integer" }
+                                }
+                            }
+                        }
+                    }
+                },
+                "Checkpoint": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "timestamp": { "type": "string", "format": "date-time" },
+                        "label": { "type": "string" },
+                        "files_count": { "type": "integer" },
+                        "total_bytes": { "type": "integer" }
+                    }
+                },
+                "CheckpointDetail": {
+                    "type": "object",
+                    "properties": {
+                        "checkpoint": { "$ref": "#/components/schemas/Checkpoint" },
+                        "changes": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "properties": {
+                                    "path": { "type": "string" },
+                                    "status": { "type": "string", "enum": ["modified", "deleted", "created"] }
+                                }
+                            }
+                        }
+                    }
+                },
+                "PendingApproval": {
+                    "type": "object",
+                    "properties": {
+                        "prompt_id": { "type": "string" },
+                        "session_id": { "type": "string" },
+                        "tool": { "type": "string" },
+                        "description": { "type": "string" },
+                        "high_risk": { "type": "boolean" },
+                        "requested_at": { "type": "string", "format": "date-time" }
+                    }
+                },
+                "ProjectOverrides": {
+                    "type": "object",
+                    "properties": {
+                        "model": { "type": "string" },
+                        "mode": { "type": "string" },
+                        "permission_preset": { "type": "string" }
+                    }
+                },
+                "Project": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string" },
+                        "name": { "type": "string" },
+                        "last_opened": { "type": "string", "format": "date-time" },
+                        "pinned": { "type": "boolean" },
+                        "exists": { "type": "boolean" },
+                        "overrides": { "$ref": "#/components/schemas/ProjectOverrides" }
                     }
                 },
                 "SendMessageRequest": {
@@ -250,14 +886,55 @@ pub async fn openapi_spec() -> Json<serde_json::Value> {
                         "attachments": {
                             "type": "array",
                             "items": { "$ref": "#/components/schemas/Attachment" }
+                        },
+                        "allowed_tools": {
+                            "type": "array",
+                            "items": { "type": "string" }
+                        },
+                        "model": { "type": "string" },
+                        "shell_snippets": {
+                            "type": "array",
+                            "items": { "$ref": "#/components/schemas/ShellSnippet" }
                         }
                     }
                 },
+                "ShellSnippet": {
+                    "type": "object",
+                    "required": ["command"],
+                    "properties": {
+                        "command": { "type": "string" },
+                        "block": { "type": "boolean" }
+                    }
+                },
                 "Attachment": {
                     "type": "object",
+                    "required": ["path"],
                     "properties": {
-                        "path": { "type": "string" },
-                        "content": { "type": "string" }
+                        "path": {
+                            "type": "string",
+                            "description": "File, directory or http(s) URL; relative paths are in the project"
+                        },
+                        "content": {
+                            "type": "string",
+                            "description": "Contents to send; read from path when omitted"
+                        }
+                    }
+                },
+                "EditorContext": {
+                    "type": "object",
+                    "required": ["editor", "project_path"],
+                    "properties": {
+                        "editor": { "type": "string" },
+                        "project_path": { "type": "string" },
+                        "file": { "type": "string" },
+                        "selection": {
+                            "type": "object",
+                            "properties": {
+                                "start_line": { "type": "integer" },
+                                "end_line": { "type": "integer" },
+                                "text": { "type": "string" }
+                            }
+                        }
                     }
                 },
                 "ServerEvent": {
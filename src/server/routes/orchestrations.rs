@@ -0,0 +1,500 @@
+//! Orchestration runs over the API
+//!
+//! `POST /api/orchestrations` plans a request and runs its tasks in the
+//! background, as `safe-coder orchestrate --mode act` does. The returned ID
+//! is used to follow the run's events, cancel running tasks and read each
+//! task's artifacts. Nobody is there to review merges, so with
+//! `review_merges` on, successful tasks are left on their branches.
+
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    Json,
+};
+use futures::stream::Stream;
+use serde::Deserialize;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use tokio_stream::StreamExt;
+
+use crate::approval::UserMode;
+use crate::config::Config;
+use crate::orchestrator::artifacts::{self, DIFF_FILE, PROMPT_FILE, STDERR_FILE, STDOUT_FILE};
+use crate::orchestrator::{Orchestrator, OrchestratorConfig, WorkerControl, WorkerEvent, WorkerKind};
+use crate::server::state::{AppState, EventChannel, OrchestrationHandle, ReplayFrom, SequencedEvent};
+use crate::server::types::{
+    CreateOrchestrationRequest, ErrorResponse, OrchestrationArtifactsResponse, OrchestrationDto,
+    OrchestrationEvent, OrchestrationListResponse, OrchestrationTaskDto, TaskArtifactSummary,
+    TaskArtifactsDto,
+};
+
+use super::events::resume_after;
+
+/// Largest artifact file sent whole; longer logs are cut to their end
+const ARTIFACT_LIMIT: usize = 512 * 1024;
+
+type ApiError = (StatusCode, Json<ErrorResponse>);
+
+fn error(status: StatusCode, message: String, code: &str) -> ApiError {
+    (
+        status,
+        Json(ErrorResponse {
+            error: message,
+            code: code.to_string(),
+        }),
+    )
+}
+
+async fn find(state: &AppState, id: &str) -> Result<OrchestrationHandle, ApiError> {
+    state.get_orchestration(id).await.ok_or_else(|| {
+        error(
+            StatusCode::NOT_FOUND,
+            format!("Orchestration not found: {}", id),
+            "ORCHESTRATION_NOT_FOUND",
+        )
+    })
+}
+
+/// POST /api/orchestrations - Plan a request and run its tasks
+pub async fn create_orchestration(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<CreateOrchestrationRequest>,
+) -> Result<(StatusCode, Json<OrchestrationDto>), ApiError> {
+    let project_path = PathBuf::from(&request.project_path)
+        .canonicalize()
+        .map_err(|e| {
+            error(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid project path: {}", e),
+                "INVALID_PATH",
+            )
+        })?;
+    // Tasks run in worktrees, so fail now rather than in the background
+    if !crate::git::GitManager::new(project_path.clone()).is_git_repo() {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            format!("{} is not the root of a git repository", project_path.display()),
+            "NOT_A_GIT_REPO",
+        ));
+    }
+    if request.request.trim().is_empty() {
+        return Err(error(
+            StatusCode::BAD_REQUEST,
+            "The request is empty".to_string(),
+            "EMPTY_REQUEST",
+        ));
+    }
+
+    let user_config = Config::load_for_project(&project_path).unwrap_or_default();
+    let mut config = OrchestratorConfig::from_config(&user_config);
+    config.user_mode = UserMode::Build;
+    if let Some(worker) = &request.worker {
+        config.default_worker = WorkerKind::from_name(worker);
+    }
+    if let Some(max_workers) = request.max_workers.filter(|n| *n > 0) {
+        config.max_workers = max_workers;
+    }
+
+    let mut orchestrator = Orchestrator::new(project_path.clone(), config)
+        .await
+        .map_err(|e| {
+            error(
+                StatusCode::BAD_REQUEST,
+                format!("Failed to start orchestrator: {:#}", e),
+                "ORCHESTRATOR_FAILED",
+            )
+        })?;
+
+    let id = uuid::Uuid::new_v4().to_string();
+    // Branches of concurrent runs on the same repository must not collide
+    orchestrator.set_workspace_namespace(&format!("api-{}", &id[..8]));
+
+    let progress = OrchestrationDto {
+        id: id.clone(),
+        project_path: project_path.to_string_lossy().to_string(),
+        request: request.request.clone(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        status: "planning".to_string(),
+        run_id: None,
+        tasks: Vec::new(),
+        merged_tasks: Vec::new(),
+        branches: Vec::new(),
+        summary: None,
+        error: None,
+    };
+    let handle = OrchestrationHandle {
+        progress: Arc::new(RwLock::new(progress.clone())),
+        events: EventChannel::new(),
+        controls: orchestrator.controls(),
+    };
+    {
+        let mut orchestrations = state.orchestrations.write().await;
+        orchestrations.insert(id.clone(), handle.clone());
+    }
+
+    tracing::info!("Orchestration {} started in {}", id, project_path.display());
+    tokio::spawn(crate::shutdown::until_requested(run_orchestration(
+        orchestrator,
+        request.request,
+        handle,
+    )));
+
+    Ok((StatusCode::CREATED, Json(progress)))
+}
+
+/// Run a request to the end and record how it went
+async fn run_orchestration(mut orchestrator: Orchestrator, request: String, handle: OrchestrationHandle) {
+    let result = execute(&mut orchestrator, &request, &handle).await;
+    if let Err(e) = orchestrator.cleanup().await {
+        tracing::warn!("Failed to clean up orchestration workspaces: {}", e);
+    }
+
+    let mut progress = handle.progress.write().await;
+    match result {
+        Ok(()) => progress.status = "completed".to_string(),
+        Err(e) => {
+            tracing::warn!("Orchestration {} failed: {:#}", progress.id, e);
+            progress.status = "failed".to_string();
+            progress.error = Some(format!("{:#}", e));
+        }
+    }
+    let _ = handle.events.send(OrchestrationEvent::Finished {
+        status: progress.status.clone(),
+        summary: progress
+            .summary
+            .clone()
+            .or_else(|| progress.error.clone())
+            .unwrap_or_default(),
+    });
+}
+
+async fn execute(
+    orchestrator: &mut Orchestrator,
+    request: &str,
+    handle: &OrchestrationHandle,
+) -> anyhow::Result<()> {
+    let review = orchestrator.config.review_merges;
+    // Hold every task's changes; they are merged or kept below
+    orchestrator.config.review_merges = true;
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    orchestrator.set_event_sender(event_tx);
+
+    let plan = orchestrator.plan_request(request).await?;
+    let tasks: Vec<OrchestrationTaskDto> = plan
+        .tasks
+        .iter()
+        .map(|task| OrchestrationTaskDto {
+            id: task.id.clone(),
+            description: task.description.clone(),
+            worker: task
+                .preferred_worker
+                .clone()
+                .unwrap_or_else(|| orchestrator.config.default_worker.clone()),
+            status: "pending".to_string(),
+            error: None,
+        })
+        .collect();
+    {
+        let mut progress = handle.progress.write().await;
+        progress.status = "running".to_string();
+        progress.tasks = tasks.clone();
+    }
+    let _ = handle.events.send(OrchestrationEvent::Planned { tasks });
+
+    let mut response = {
+        let execute = orchestrator.execute_plan(plan);
+        tokio::pin!(execute);
+        let result = loop {
+            tokio::select! {
+                result = &mut execute => break result,
+                Some(event) = event_rx.recv() => record(handle, event).await,
+            }
+        };
+        while let Ok(event) = event_rx.try_recv() {
+            record(handle, event).await;
+        }
+        result?
+    };
+
+    let branches = if review {
+        orchestrator.keep_for_review(&mut response).await?
+    } else {
+        let accepted: Vec<String> = response
+            .pending_review
+            .iter()
+            .map(|task| task.task_id.clone())
+            .collect();
+        orchestrator.merge_tasks(&mut response, &accepted).await?;
+        Vec::new()
+    };
+
+    let mut progress = handle.progress.write().await;
+    progress.run_id = response.run_id;
+    progress.merged_tasks = response.merged_tasks;
+    progress.branches = branches;
+    progress.summary = Some(response.summary);
+    Ok(())
+}
+
+/// Apply a worker event to the run's progress and pass it on
+async fn record(handle: &OrchestrationHandle, event: WorkerEvent) {
+    let mut progress = handle.progress.write().await;
+    let event = match event {
+        WorkerEvent::Started {
+            task_id,
+            description,
+            worker,
+        } => {
+            set_task_status(&mut progress, &task_id, "running", None);
+            OrchestrationEvent::TaskStarted {
+                task_id,
+                description,
+                worker,
+            }
+        }
+        WorkerEvent::OutputLine { task_id, line } => OrchestrationEvent::TaskOutput {
+            task_id,
+            stream: "stdout".to_string(),
+            line,
+        },
+        WorkerEvent::ErrorLine { task_id, line } => OrchestrationEvent::TaskOutput {
+            task_id,
+            stream: "stderr".to_string(),
+            line,
+        },
+        WorkerEvent::Completed { task_id, .. } => {
+            set_task_status(&mut progress, &task_id, "completed", None);
+            OrchestrationEvent::TaskFinished {
+                task_id,
+                status: "completed".to_string(),
+                error: None,
+            }
+        }
+        WorkerEvent::Failed { task_id, error } => {
+            // A cancelled worker fails like any other
+            let cancelling = progress
+                .tasks
+                .iter()
+                .any(|t| t.id == task_id && t.status == "cancelling");
+            let status = if cancelling { "cancelled" } else { "failed" };
+            set_task_status(&mut progress, &task_id, status, Some(error.clone()));
+            OrchestrationEvent::TaskFinished {
+                task_id,
+                status: status.to_string(),
+                error: Some(error),
+            }
+        }
+    };
+    let _ = handle.events.send(event);
+}
+
+fn set_task_status(progress: &mut OrchestrationDto, task_id: &str, status: &str, error: Option<String>) {
+    if let Some(task) = progress.tasks.iter_mut().find(|t| t.id == task_id) {
+        task.status = status.to_string();
+        task.error = error;
+    }
+}
+
+/// GET /api/orchestrations - Runs started on this server, newest first
+pub async fn list_orchestrations(State(state): State<Arc<AppState>>) -> Json<OrchestrationListResponse> {
+    let handles: Vec<OrchestrationHandle> = state.orchestrations.read().await.values().cloned().collect();
+    let mut orchestrations = Vec::with_capacity(handles.len());
+    for handle in handles {
+        orchestrations.push(handle.progress.read().await.clone());
+    }
+    orchestrations.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+    Json(OrchestrationListResponse { orchestrations })
+}
+
+/// GET /api/orchestrations/:id - A run's plan, task progress and outcome
+pub async fn get_orchestration(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<OrchestrationDto>, ApiError> {
+    let handle = find(&state, &id).await?;
+    let progress = handle.progress.read().await.clone();
+    Ok(Json(progress))
+}
+
+/// POST /api/orchestrations/:id/tasks/:task_id/cancel - Stop a running task
+///
+/// The other tasks keep going; the task is reported as cancelled.
+pub async fn cancel_task(
+    State(state): State<Arc<AppState>>,
+    Path((id, task_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let handle = find(&state, &id).await?;
+    let mut progress = handle.progress.write().await;
+    let task = progress
+        .tasks
+        .iter_mut()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| {
+            error(
+                StatusCode::NOT_FOUND,
+                format!("No task '{}' in orchestration {}", task_id, id),
+                "TASK_NOT_FOUND",
+            )
+        })?;
+    if task.status != "running" || handle.controls.send(WorkerControl::Cancel(task_id.clone())).is_err() {
+        return Err(error(
+            StatusCode::CONFLICT,
+            format!("Task '{}' is not running ({})", task_id, task.status),
+            "TASK_NOT_RUNNING",
+        ));
+    }
+    task.status = "cancelling".to_string();
+
+    tracing::info!("Cancelling task {} of orchestration {}", task_id, id);
+    Ok(Json(serde_json::json!({ "status": "ok" })))
+}
+
+/// Query parameters for a run's event stream
+#[derive(Debug, Default, Deserialize)]
+pub struct OrchestrationEventsQuery {
+    /// Resume after this event ID, as with session events
+    #[serde(default)]
+    pub since: Option<u64>,
+}
+
+/// GET /api/orchestrations/:id/events - SSE stream of task events
+///
+/// Starts with the run's buffered events, so a client that connects late
+/// sees the plan and output so far, and ends after `Finished`.
+pub async fn orchestration_events(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<OrchestrationEventsQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let handle = find(&state, &id).await?;
+    let from = match resume_after(&headers, query.since) {
+        Some(id) => ReplayFrom::After(id),
+        None => ReplayFrom::Turn(usize::MAX),
+    };
+    let (replay, rx) = handle.events.subscribe_with_replay(from);
+    // `Finished` is sent under the progress lock, so a finished status
+    // means it is already out, maybe before `since`
+    let finished = matches!(
+        handle.progress.read().await.status.as_str(),
+        "completed" | "failed"
+    );
+
+    // Live events until `Finished`, so the response ends with the run
+    let live = futures::stream::unfold((rx, finished), |(mut rx, done)| async move {
+        if done {
+            return None;
+        }
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    let done = matches!(event.event, OrchestrationEvent::Finished { .. });
+                    return Some((event, (rx, done)));
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+    let stream = tokio_stream::iter(replay.events)
+        .chain(live)
+        .map(|SequencedEvent { id, event }| {
+            let json = serde_json::to_string(&event).unwrap_or_else(|_| "{}".to_string());
+            let event_type = match &event {
+                OrchestrationEvent::Planned { .. } => "Planned",
+                OrchestrationEvent::TaskStarted { .. } => "TaskStarted",
+                OrchestrationEvent::TaskOutput { .. } => "TaskOutput",
+                OrchestrationEvent::TaskFinished { .. } => "TaskFinished",
+                OrchestrationEvent::Finished { .. } => "Finished",
+            };
+            Ok::<_, Infallible>(Event::default().id(id.to_string()).event(event_type).data(json))
+        });
+
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(Duration::from_secs(15))
+            .text("keep-alive"),
+    ))
+}
+
+/// Artifact directory of a run, once its tasks have finished
+async fn run_dir(handle: &OrchestrationHandle) -> Result<(String, PathBuf), ApiError> {
+    let progress = handle.progress.read().await;
+    let run_id = progress.run_id.clone().ok_or_else(|| {
+        error(
+            StatusCode::CONFLICT,
+            format!(
+                "Orchestration {} has no artifacts yet ({}); follow its events for live output",
+                progress.id, progress.status
+            ),
+            "ARTIFACTS_NOT_READY",
+        )
+    })?;
+    let dir = artifacts::find_run(std::path::Path::new(&progress.project_path), &run_id)
+        .map_err(|e| error(StatusCode::NOT_FOUND, format!("{:#}", e), "ARTIFACTS_NOT_FOUND"))?;
+    Ok((run_id, dir))
+}
+
+/// GET /api/orchestrations/:id/artifacts - Tasks recorded for a run
+pub async fn list_artifacts(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+) -> Result<Json<OrchestrationArtifactsResponse>, ApiError> {
+    let handle = find(&state, &id).await?;
+    let (run_id, dir) = run_dir(&handle).await?;
+    let tasks = artifacts::list_tasks(&dir)
+        .into_iter()
+        .map(|(task_id, outcome)| TaskArtifactSummary { task_id, outcome })
+        .collect();
+    Ok(Json(OrchestrationArtifactsResponse { run_id, tasks }))
+}
+
+/// GET /api/orchestrations/:id/artifacts/:task_id - A task's prompt, logs and diff
+pub async fn get_task_artifacts(
+    State(state): State<Arc<AppState>>,
+    Path((id, task_id)): Path<(String, String)>,
+) -> Result<Json<TaskArtifactsDto>, ApiError> {
+    let handle = find(&state, &id).await?;
+    let (_, dir) = run_dir(&handle).await?;
+    let outcome = artifacts::list_tasks(&dir)
+        .into_iter()
+        .find(|(id, _)| *id == task_id)
+        .ok_or_else(|| {
+            error(
+                StatusCode::NOT_FOUND,
+                format!("No task '{}' in orchestration {}", task_id, id),
+                "TASK_NOT_FOUND",
+            )
+        })?
+        .1;
+
+    let task_dir = dir.join(&task_id);
+    let mut truncated = Vec::new();
+    let mut read = |name: &str| {
+        let text = std::fs::read_to_string(task_dir.join(name)).ok()?;
+        if text.len() <= ARTIFACT_LIMIT {
+            return Some(text);
+        }
+        truncated.push(name.to_string());
+        let mut start = text.len() - ARTIFACT_LIMIT;
+        while !text.is_char_boundary(start) {
+            start += 1;
+        }
+        Some(text[start..].to_string())
+    };
+
+    Ok(Json(TaskArtifactsDto {
+        prompt: read(PROMPT_FILE),
+        stdout: read(STDOUT_FILE),
+        stderr: read(STDERR_FILE),
+        diff: read(DIFF_FILE),
+        task_id,
+        outcome,
+        truncated,
+    }))
+}
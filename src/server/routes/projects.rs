@@ -0,0 +1,40 @@
+//! Recent projects endpoint
+
+use axum::{http::StatusCode, Json};
+
+use crate::persistence::projects::ProjectList;
+use crate::server::types::{ErrorResponse, ProjectDto, ProjectListResponse};
+
+/// GET /api/projects - Pinned and recently opened projects
+pub async fn list_projects() -> Result<Json<ProjectListResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let list = ProjectList::load().map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ErrorResponse {
+                error: format!("Failed to load projects: {:#}", e),
+                code: "PROJECTS_LOAD_FAILED".to_string(),
+            }),
+        )
+    })?;
+
+    let projects = list
+        .sorted()
+        .into_iter()
+        .map(|project| {
+            let path = std::path::Path::new(&project.path);
+            ProjectDto {
+                name: path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| project.path.clone()),
+                exists: path.is_dir(),
+                last_opened: project.last_opened.to_rfc3339(),
+                pinned: project.pinned,
+                overrides: project.overrides,
+                path: project.path,
+            }
+        })
+        .collect();
+
+    Ok(Json(ProjectListResponse { projects }))
+}
@@ -11,13 +11,20 @@ use axum::{
 use tokio::sync::RwLock;
 
 use crate::approval::UserMode;
-use crate::config::Config;
+use crate::config::{Config, GenerationParams};
+use crate::git::GitManager;
+use crate::llm::Capabilities;
+use crate::mcp::McpHealthStatus;
 use crate::persistence::models::SavedSession;
 use crate::tools::AgentMode;
 use crate::server::state::{AppState, SessionHandle};
 use crate::server::types::{
     CreateSessionRequest, DoomLoopResponseRequest, ErrorResponse, FileChangeStats,
-    SessionListResponse, SessionResponse, SessionSummary, SetModeRequest,
+    McpPromptDto, McpPromptRequest, McpPromptResponse, McpResourceDto, McpServerStatusDto,
+    ServerEvent,
+    SessionListResponse, SessionResponse, SessionSummary, SetModeRequest, SetParamRequest,
+    SetSkillRequest,
+    SkillDto, ToolApprovalResponseRequest,
 };
 use crate::session::Session;
 
@@ -33,6 +40,7 @@ pub async fn list_sessions(
         let (additions, deletions) = file_changes
             .iter()
             .fold((0, 0), |(a, d), c| (a + c.additions, d + c.deletions));
+        let is_processing = *handle.is_processing.read().await;
 
         summaries.push(SessionSummary {
             id: id.clone(),
@@ -44,8 +52,11 @@ pub async fn list_sessions(
                 additions,
                 deletions,
             },
+            is_processing,
+            clients: state.client_count(id).await,
         });
     }
+    summaries.sort_by(|a, b| b.created_at.cmp(&a.created_at));
 
     Json(SessionListResponse { sessions: summaries })
 }
@@ -67,11 +78,45 @@ pub async fn create_session(
         )
     })?;
 
-    // Load config and disable git auto-commit (user can use bash for git operations)
-    // Also disable checkpointing as it can interfere with some build systems (e.g., nx)
-    let mut config = Config::load().unwrap_or_default();
+    // Load the project's config, layers included, and disable git auto-commit
+    // (user can use bash for git operations).
+    // Checkpoints follow [checkpoint]; projects whose build tools watch the
+    // checkpoint directory (e.g. nx) can move it with storage_path.
+    let mut config = Config::load_for_project(&canonical_path).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: format!("Invalid configuration for project: {:#}", e),
+                code: "INVALID_CONFIG".to_string(),
+            }),
+        )
+    })?;
     config.git.auto_commit = false;
-    config.checkpoint.enabled = false;
+
+    // Per-project overrides from the client
+    if let Some(model) = request.model.as_deref().filter(|m| !m.is_empty()) {
+        config.llm.model = model.to_string();
+    }
+    if let Some(preset) = request.permission_preset.as_deref().filter(|p| !p.is_empty()) {
+        if !crate::permissions::PRESETS.contains(&preset) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ErrorResponse {
+                    error: format!(
+                        "Unknown permission preset '{}' (expected one of {})",
+                        preset,
+                        crate::permissions::PRESETS.join(", ")
+                    ),
+                    code: "INVALID_PRESET".to_string(),
+                }),
+            ));
+        }
+        config.permissions.preset = Some(preset.to_string());
+        // A preset the organization policy locks still wins
+        if let Ok(Some(installed)) = crate::policy::InstalledPolicy::load() {
+            installed.policy.enforce(&mut config);
+        }
+    }
 
     // Create session
     let mut session = Session::new(config, canonical_path.clone())
@@ -100,6 +145,17 @@ pub async fn create_session(
     let session_id = uuid::Uuid::new_v4().to_string();
     let created_at = chrono::Utc::now();
 
+    let start_snapshot = match GitManager::new(canonical_path.clone()).snapshot_tree().await {
+        Ok(tree) => Some(tree),
+        Err(e) => {
+            tracing::debug!("No start snapshot for {}: {}", canonical_path.display(), e);
+            None
+        }
+    };
+
+    let monitors = session.monitors();
+    let mut mcp_health_rx = session.mcp().subscribe_health();
+
     // Create session handle
     let handle = SessionHandle {
         session: Arc::new(RwLock::new(session)),
@@ -107,6 +163,7 @@ pub async fn create_session(
         created_at,
         is_processing: Arc::new(RwLock::new(false)),
         file_changes: Arc::new(RwLock::new(Vec::new())),
+        start_snapshot,
     };
 
     // Store session in memory
@@ -116,7 +173,51 @@ pub async fn create_session(
     }
 
     // Create event channel for this session
-    let _ = state.get_event_sender(&session_id).await;
+    let event_sender = state.get_event_sender(&session_id).await;
+
+    // Forward background monitor notifications, even between messages
+    let mut monitor_rx = monitors.subscribe();
+    drop(monitors);
+    tokio::spawn(async move {
+        loop {
+            match monitor_rx.recv().await {
+                Ok(notification) => {
+                    let _ = event_sender.send(ServerEvent::MonitorNotification {
+                        monitor_id: notification.monitor_id,
+                        command: notification.command,
+                        summary: notification.summary,
+                        needs_attention: notification.needs_attention,
+                    });
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                // The session (and its monitors) is gone
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+
+    // Forward MCP server health changes so dropped servers show up mid-conversation
+    let event_sender = state.get_event_sender(&session_id).await;
+    tokio::spawn(async move {
+        loop {
+            match mcp_health_rx.recv().await {
+                Ok(event) => {
+                    let status = match event.status {
+                        McpHealthStatus::Dropped => "dropped",
+                        McpHealthStatus::Restarted => "restarted",
+                        McpHealthStatus::Failed => "failed",
+                    };
+                    let _ = event_sender.send(ServerEvent::McpServerHealth {
+                        server: event.server,
+                        status: status.to_string(),
+                        message: event.message,
+                    });
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
 
     // Save to persistent storage if available
     if let Some(persistence) = state.persistence() {
@@ -208,6 +309,300 @@ pub async fn set_session_mode(
     }
 }
 
+/// GET /api/sessions/:id/skills - List skills and whether they are active
+pub async fn list_skills(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<Vec<SkillDto>>, (StatusCode, Json<ErrorResponse>)> {
+    let sessions = state.sessions.read().await;
+
+    match sessions.get(&session_id) {
+        Some(handle) => {
+            let session = handle.session.read().await;
+            let skills = session
+                .skills()
+                .list()
+                .into_iter()
+                .map(|skill| SkillDto {
+                    name: skill.name.clone(),
+                    description: skill.description.clone(),
+                    triggers: skill.triggers.clone(),
+                    active: skill.active,
+                    tokens: skill.estimated_tokens(),
+                })
+                .collect();
+            Ok(Json(skills))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        )),
+    }
+}
+
+/// PUT /api/sessions/:id/skills/:name - Turn a skill on or off
+pub async fn set_skill(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, name)): Path<(String, String)>,
+    Json(request): Json<SetSkillRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    let sessions = state.sessions.read().await;
+
+    let Some(handle) = sessions.get(&session_id) else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        ));
+    };
+
+    let mut session = handle.session.write().await;
+    let found = if request.active {
+        session.skills_mut().activate(&name)
+    } else {
+        session.skills_mut().deactivate(&name)
+    };
+
+    if found {
+        tracing::info!("Session {} skill {} active={}", session_id, name, request.active);
+        Ok(Json(serde_json::json!({
+            "status": "ok",
+            "skill": name,
+            "active": request.active
+        })))
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Skill not found: {}", name),
+                code: "SKILL_NOT_FOUND".to_string(),
+            }),
+        ))
+    }
+}
+
+/// GET /api/sessions/:id/params - Sampling parameters of the session
+pub async fn get_params(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<GenerationParams>, (StatusCode, Json<ErrorResponse>)> {
+    let sessions = state.sessions.read().await;
+
+    let Some(handle) = sessions.get(&session_id) else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        ));
+    };
+
+    let session = handle.session.read().await;
+    Ok(Json(session.generation_params().clone()))
+}
+
+/// GET /api/sessions/:id/capabilities - What the current mode's model supports
+pub async fn get_capabilities(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<Capabilities>, (StatusCode, Json<ErrorResponse>)> {
+    let sessions = state.sessions.read().await;
+
+    let Some(handle) = sessions.get(&session_id) else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        ));
+    };
+
+    let session = handle.session.read().await;
+    Ok(Json(session.capabilities()))
+}
+
+/// PUT /api/sessions/:id/params - Set a sampling parameter for the session
+pub async fn set_param(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(request): Json<SetParamRequest>,
+) -> Result<Json<GenerationParams>, (StatusCode, Json<ErrorResponse>)> {
+    let sessions = state.sessions.read().await;
+
+    let Some(handle) = sessions.get(&session_id) else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        ));
+    };
+
+    let mut session = handle.session.write().await;
+    match session.set_generation_param(&request.name, &request.value).await {
+        Ok(()) => {
+            tracing::info!("Session {} {} set to {}", session_id, request.name, request.value);
+            Ok(Json(session.generation_params().clone()))
+        }
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+                code: "INVALID_PARAM".to_string(),
+            }),
+        )),
+    }
+}
+
+/// GET /api/sessions/:id/mcp/status - Health of the session's MCP servers
+pub async fn mcp_status(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<Vec<McpServerStatusDto>>, (StatusCode, Json<ErrorResponse>)> {
+    let sessions = state.sessions.read().await;
+
+    match sessions.get(&session_id) {
+        Some(handle) => {
+            let session = handle.session.read().await;
+            let statuses = session
+                .mcp()
+                .server_status()
+                .await
+                .into_iter()
+                .map(|status| McpServerStatusDto {
+                    name: status.name,
+                    transport: format!("{:?}", status.transport).to_lowercase(),
+                    endpoint: status.endpoint,
+                    state: status.state.to_string(),
+                    latency_ms: status.latency.map(|l| l.as_millis() as u64),
+                    tool_count: status.tool_count,
+                    restarts: status.restarts,
+                })
+                .collect();
+            Ok(Json(statuses))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        )),
+    }
+}
+
+/// GET /api/sessions/:id/mcp/resources - List resources from connected MCP servers
+pub async fn list_mcp_resources(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<Vec<McpResourceDto>>, (StatusCode, Json<ErrorResponse>)> {
+    let sessions = state.sessions.read().await;
+
+    match sessions.get(&session_id) {
+        Some(handle) => {
+            let session = handle.session.read().await;
+            let resources = session
+                .mcp()
+                .list_resources()
+                .await
+                .into_iter()
+                .map(|(server, resource)| McpResourceDto {
+                    server,
+                    uri: resource.uri,
+                    name: resource.name,
+                    description: resource.description,
+                    mime_type: resource.mime_type,
+                })
+                .collect();
+            Ok(Json(resources))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        )),
+    }
+}
+
+/// GET /api/sessions/:id/mcp/prompts - List prompts from connected MCP servers
+pub async fn list_mcp_prompts(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+) -> Result<Json<Vec<McpPromptDto>>, (StatusCode, Json<ErrorResponse>)> {
+    let sessions = state.sessions.read().await;
+
+    match sessions.get(&session_id) {
+        Some(handle) => {
+            let session = handle.session.read().await;
+            let prompts = session
+                .mcp()
+                .list_prompts()
+                .await
+                .into_iter()
+                .map(|(server, prompt)| McpPromptDto {
+                    usage: crate::mcp::prompt_usage(&server, &prompt),
+                    server,
+                    name: prompt.name,
+                    description: prompt.description,
+                })
+                .collect();
+            Ok(Json(prompts))
+        }
+        None => Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        )),
+    }
+}
+
+/// POST /api/sessions/:id/mcp/prompts/:server/:name - Render an MCP prompt
+pub async fn get_mcp_prompt(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, server, name)): Path<(String, String, String)>,
+    Json(request): Json<McpPromptRequest>,
+) -> Result<Json<McpPromptResponse>, (StatusCode, Json<ErrorResponse>)> {
+    let sessions = state.sessions.read().await;
+
+    let Some(handle) = sessions.get(&session_id) else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        ));
+    };
+
+    let session = handle.session.read().await;
+    match session
+        .mcp()
+        .get_prompt(&server, &name, &request.arguments)
+        .await
+    {
+        Ok(text) => Ok(Json(McpPromptResponse { text })),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(ErrorResponse {
+                error: e.to_string(),
+                code: "MCP_PROMPT_FAILED".to_string(),
+            }),
+        )),
+    }
+}
+
 /// DELETE /api/sessions/:id - Delete a session
 pub async fn delete_session(
     State(state): State<Arc<AppState>>,
@@ -289,3 +684,49 @@ pub async fn respond_to_doom_loop(
         ))
     }
 }
+
+/// POST /api/sessions/:id/tool-approval-response - Respond to a tool approval prompt
+pub async fn respond_to_tool_approval(
+    State(state): State<Arc<AppState>>,
+    Path(session_id): Path<String>,
+    Json(request): Json<ToolApprovalResponseRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ErrorResponse>)> {
+    // Check if session exists
+    let sessions = state.sessions.read().await;
+    if !sessions.contains_key(&session_id) {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Session not found: {}", session_id),
+                code: "SESSION_NOT_FOUND".to_string(),
+            }),
+        ));
+    }
+    drop(sessions);
+
+    // Send response through the registered channel
+    let sent = state
+        .send_tool_approval_response(&request.prompt_id, request.approved)
+        .await;
+
+    if sent {
+        tracing::info!(
+            "Tool approval response sent: {} (prompt_id={})",
+            if request.approved { "approve" } else { "deny" },
+            request.prompt_id
+        );
+        Ok(Json(serde_json::json!({
+            "status": "ok",
+            "action": if request.approved { "approve" } else { "deny" }
+        })))
+    } else {
+        tracing::warn!("Tool approval prompt not found or already responded: {}", request.prompt_id);
+        Err((
+            StatusCode::NOT_FOUND,
+            Json(ErrorResponse {
+                error: format!("Tool approval prompt not found or already responded: {}", request.prompt_id),
+                code: "PROMPT_NOT_FOUND".to_string(),
+            }),
+        ))
+    }
+}
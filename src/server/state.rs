@@ -3,17 +3,20 @@
 //! This module defines the shared state for the HTTP server, including
 //! session management and event broadcasting.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use tokio::sync::{broadcast, mpsc, RwLock};
 
 use crate::config::Config;
+use crate::notifications::webhook::WebhookNotifier;
 use crate::persistence::SessionPersistence;
 use crate::session::Session;
 
-use super::types::ServerEvent;
+use crate::orchestrator::WorkerControl;
+
+use super::types::{OrchestrationDto, OrchestrationEvent, PendingApprovalDto, ServerEvent};
 
 /// Shared application state for the server
 pub struct AppState {
@@ -24,13 +27,32 @@ pub struct AppState {
     pub sessions: RwLock<HashMap<String, SessionHandle>>,
 
     /// Event broadcast channels per session
-    pub event_channels: RwLock<HashMap<String, broadcast::Sender<ServerEvent>>>,
+    pub event_channels: RwLock<HashMap<String, EventChannel>>,
 
     /// Session persistence layer (SQLite)
     pub persistence: Option<Arc<SessionPersistence>>,
 
     /// Pending doom loop response channels (prompt_id -> response sender)
     pub doom_loop_responses: RwLock<HashMap<String, mpsc::UnboundedSender<bool>>>,
+
+    /// Pending tool approvals (prompt_id -> prompt and response sender)
+    pub tool_approval_responses: RwLock<HashMap<String, PendingApproval>>,
+
+    /// Slack/Discord webhooks for approvals and orchestration results
+    pub webhooks: Option<WebhookNotifier>,
+
+    /// Orchestration runs started over the API, mapped by ID
+    pub orchestrations: RwLock<HashMap<String, OrchestrationHandle>>,
+}
+
+/// A tool call waiting for the user's approval
+pub struct PendingApproval {
+    pub session_id: String,
+    pub tool: String,
+    pub description: String,
+    pub high_risk: bool,
+    pub requested_at: chrono::DateTime<chrono::Utc>,
+    pub response_tx: mpsc::UnboundedSender<bool>,
 }
 
 /// Handle to a managed session
@@ -49,6 +71,170 @@ pub struct SessionHandle {
 
     /// Tracked file changes in this session
     pub file_changes: Arc<RwLock<Vec<FileChange>>>,
+
+    /// Git tree of the project when the session started, which the session
+    /// diff compares against; None outside git repositories
+    pub start_snapshot: Option<String>,
+}
+
+/// Handle to an orchestration run started over the API
+#[derive(Clone)]
+pub struct OrchestrationHandle {
+    /// Plan, task progress and outcome, kept up to date as the run goes
+    pub progress: Arc<RwLock<OrchestrationDto>>,
+
+    /// Task events of the run
+    pub events: EventChannel<OrchestrationEvent>,
+
+    /// Cancels single tasks while the run is going
+    pub controls: mpsc::UnboundedSender<WorkerControl>,
+}
+
+/// Events kept per session, for clients that attach mid-turn or reconnect
+const REPLAY_CAPACITY: usize = 1000;
+
+/// Events that go through an [`EventChannel`]
+pub trait StreamEvent: Clone + Send + 'static {
+    /// Whether this event begins a turn, where `ReplayFrom::Turn` starts
+    fn starts_turn(&self) -> bool {
+        false
+    }
+}
+
+impl StreamEvent for ServerEvent {
+    fn starts_turn(&self) -> bool {
+        matches!(self, ServerEvent::Connected)
+    }
+}
+
+/// An orchestration run is a single turn
+impl StreamEvent for OrchestrationEvent {}
+
+/// An event and its position in the session's stream
+///
+/// IDs start at 1 and are sent as the SSE `id`, so a reconnecting client
+/// can resume with `Last-Event-ID` or `?since=`.
+#[derive(Debug, Clone)]
+pub struct SequencedEvent<E = ServerEvent> {
+    pub id: u64,
+    pub event: E,
+}
+
+/// Where a new subscriber's stream starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayFrom {
+    /// Up to this many of the current turn's latest events
+    Turn(usize),
+    /// Every buffered event after this ID
+    After(u64),
+}
+
+/// Events replayed to a new subscriber
+#[derive(Debug)]
+pub struct Replay<E = ServerEvent> {
+    pub events: Vec<SequencedEvent<E>>,
+    /// Events after the requested ID fell out of the buffer before the
+    /// client came back
+    pub missed: bool,
+}
+
+struct History<E> {
+    events: VecDeque<SequencedEvent<E>>,
+    last_id: u64,
+    /// ID of the latest event that started a turn
+    turn_start: u64,
+}
+
+impl<E> Default for History<E> {
+    fn default() -> Self {
+        Self {
+            events: VecDeque::new(),
+            last_id: 0,
+            turn_start: 0,
+        }
+    }
+}
+
+/// A session's event fan-out, shared by every attached client
+///
+/// Besides broadcasting, it numbers events and keeps the latest
+/// [`REPLAY_CAPACITY`] in a ring, so a client that attaches to a session
+/// another client started can catch up on the turn in progress, and one
+/// whose connection dropped can resume where it left off.
+#[derive(Clone)]
+pub struct EventChannel<E = ServerEvent> {
+    sender: broadcast::Sender<SequencedEvent<E>>,
+    history: Arc<Mutex<History<E>>>,
+}
+
+impl<E: StreamEvent> EventChannel<E> {
+    pub(crate) fn new() -> Self {
+        let (sender, _) = broadcast::channel(1024);
+        Self {
+            sender,
+            history: Arc::new(Mutex::new(History::default())),
+        }
+    }
+
+    /// Record and broadcast an event
+    pub fn send(&self, event: E) -> Result<usize, broadcast::error::SendError<SequencedEvent<E>>> {
+        let mut history = self.history.lock().unwrap();
+        history.last_id += 1;
+        let id = history.last_id;
+        if event.starts_turn() {
+            history.turn_start = id;
+        }
+        if history.events.len() == REPLAY_CAPACITY {
+            history.events.pop_front();
+        }
+        let event = SequencedEvent { id, event };
+        history.events.push_back(event.clone());
+        self.sender.send(event)
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<SequencedEvent<E>> {
+        self.sender.subscribe()
+    }
+
+    /// Subscribe, along with the buffered events `from` asks for
+    ///
+    /// Both are taken under the history lock, so nothing is missed or
+    /// delivered twice between the replay and the live stream.
+    pub fn subscribe_with_replay(
+        &self,
+        from: ReplayFrom,
+    ) -> (Replay<E>, broadcast::Receiver<SequencedEvent<E>>) {
+        let history = self.history.lock().unwrap();
+        let replay = match from {
+            ReplayFrom::Turn(limit) => {
+                let turn: Vec<_> = history
+                    .events
+                    .iter()
+                    .filter(|e| e.id >= history.turn_start)
+                    .collect();
+                let skip = turn.len().saturating_sub(limit);
+                Replay {
+                    events: turn.into_iter().skip(skip).cloned().collect(),
+                    missed: false,
+                }
+            }
+            ReplayFrom::After(after) => Replay {
+                events: history
+                    .events
+                    .iter()
+                    .filter(|e| e.id > after)
+                    .cloned()
+                    .collect(),
+                missed: history.events.front().is_some_and(|e| e.id > after + 1),
+            },
+        };
+        (replay, self.sender.subscribe())
+    }
+
+    /// Number of clients currently streaming this session's events
+    pub fn client_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
 }
 
 /// Represents a file change in a session
@@ -76,11 +262,14 @@ impl AppState {
     /// Create new application state
     pub fn new(config: Config) -> Self {
         Self {
+            webhooks: WebhookNotifier::from_config(&config.notifications),
             config: RwLock::new(config),
             sessions: RwLock::new(HashMap::new()),
             event_channels: RwLock::new(HashMap::new()),
             persistence: None,
             doom_loop_responses: RwLock::new(HashMap::new()),
+            tool_approval_responses: RwLock::new(HashMap::new()),
+            orchestrations: RwLock::new(HashMap::new()),
         }
     }
 
@@ -88,11 +277,14 @@ impl AppState {
     pub async fn new_with_persistence(config: Config) -> anyhow::Result<Self> {
         let persistence = SessionPersistence::new().await?;
         Ok(Self {
+            webhooks: WebhookNotifier::from_config(&config.notifications),
             config: RwLock::new(config),
             sessions: RwLock::new(HashMap::new()),
             event_channels: RwLock::new(HashMap::new()),
             persistence: Some(Arc::new(persistence)),
             doom_loop_responses: RwLock::new(HashMap::new()),
+            tool_approval_responses: RwLock::new(HashMap::new()),
+            orchestrations: RwLock::new(HashMap::new()),
         })
     }
 
@@ -112,30 +304,79 @@ impl AppState {
         }
     }
 
+    /// Register a pending tool approval
+    pub async fn register_tool_approval_response(&self, prompt_id: String, approval: PendingApproval) {
+        let mut responses = self.tool_approval_responses.write().await;
+        // Forget prompts the session stopped waiting for (timed out or cancelled)
+        responses.retain(|_, pending| !pending.response_tx.is_closed());
+        responses.insert(prompt_id, approval);
+    }
+
+    /// Send a tool approval response
+    pub async fn send_tool_approval_response(&self, prompt_id: &str, approved: bool) -> bool {
+        let mut responses = self.tool_approval_responses.write().await;
+        if let Some(pending) = responses.remove(prompt_id) {
+            pending.response_tx.send(approved).is_ok()
+        } else {
+            false
+        }
+    }
+
+    /// Tool approvals still waiting for an answer, across sessions, oldest first
+    pub async fn pending_approvals(&self) -> Vec<PendingApprovalDto> {
+        let responses = self.tool_approval_responses.read().await;
+        let mut pending: Vec<PendingApprovalDto> = responses
+            .iter()
+            .filter(|(_, p)| !p.response_tx.is_closed())
+            .map(|(prompt_id, p)| PendingApprovalDto {
+                prompt_id: prompt_id.clone(),
+                session_id: p.session_id.clone(),
+                tool: p.tool.clone(),
+                description: p.description.clone(),
+                high_risk: p.high_risk,
+                requested_at: p.requested_at.to_rfc3339(),
+            })
+            .collect();
+        pending.sort_by(|a, b| a.requested_at.cmp(&b.requested_at));
+        pending
+    }
+
     /// Get the persistence layer
     pub fn persistence(&self) -> Option<&Arc<SessionPersistence>> {
         self.persistence.as_ref()
     }
 
     /// Get or create an event channel for a session
-    pub async fn get_event_sender(&self, session_id: &str) -> broadcast::Sender<ServerEvent> {
+    pub async fn get_event_sender(&self, session_id: &str) -> EventChannel {
         let mut channels = self.event_channels.write().await;
-
-        if let Some(sender) = channels.get(session_id) {
-            sender.clone()
-        } else {
-            let (sender, _) = broadcast::channel(1024);
-            channels.insert(session_id.to_string(), sender.clone());
-            sender
-        }
+        channels
+            .entry(session_id.to_string())
+            .or_insert_with(EventChannel::new)
+            .clone()
     }
 
     /// Subscribe to events for a session
-    pub async fn subscribe_events(&self, session_id: &str) -> broadcast::Receiver<ServerEvent> {
+    pub async fn subscribe_events(&self, session_id: &str) -> broadcast::Receiver<SequencedEvent> {
         let sender = self.get_event_sender(session_id).await;
         sender.subscribe()
     }
 
+    /// Subscribe to events for a session, replaying buffered ones first
+    pub async fn subscribe_events_with_replay(
+        &self,
+        session_id: &str,
+        from: ReplayFrom,
+    ) -> (Replay, broadcast::Receiver<SequencedEvent>) {
+        let sender = self.get_event_sender(session_id).await;
+        sender.subscribe_with_replay(from)
+    }
+
+    /// Number of clients streaming a session's events
+    pub async fn client_count(&self, session_id: &str) -> usize {
+        let channels = self.event_channels.read().await;
+        channels.get(session_id).map_or(0, EventChannel::client_count)
+    }
+
     /// Broadcast an event to all subscribers
     pub async fn broadcast_event(&self, session_id: &str, event: ServerEvent) {
         let channels = self.event_channels.read().await;
@@ -144,6 +385,12 @@ impl AppState {
         }
     }
 
+    /// Get an orchestration run by ID
+    pub async fn get_orchestration(&self, id: &str) -> Option<OrchestrationHandle> {
+        let orchestrations = self.orchestrations.read().await;
+        orchestrations.get(id).cloned()
+    }
+
     /// Get a session by ID
     pub async fn get_session(&self, session_id: &str) -> Option<SessionHandle> {
         let sessions = self.sessions.read().await;
@@ -184,6 +431,31 @@ impl AppState {
             Vec::new()
         }
     }
+
+    /// Save every idle session's messages, as on shutdown. Sessions in the
+    /// middle of a turn are skipped; their last finished turn was already
+    /// saved when it completed.
+    pub async fn persist_sessions(&self) {
+        let Some(persistence) = self.persistence() else {
+            return;
+        };
+        let sessions: Vec<(String, SessionHandle)> = self
+            .sessions
+            .read()
+            .await
+            .iter()
+            .map(|(id, handle)| (id.clone(), handle.clone()))
+            .collect();
+        for (id, handle) in sessions {
+            let Ok(session) = handle.session.try_read() else {
+                tracing::debug!("Session {} is mid-turn; keeping its last saved turn", id);
+                continue;
+            };
+            if let Err(e) = persistence.update_session(&id, session.get_messages()).await {
+                tracing::warn!("Failed to persist session {}: {}", id, e);
+            }
+        }
+    }
 }
 
 impl Clone for SessionHandle {
@@ -194,6 +466,55 @@ impl Clone for SessionHandle {
             created_at: self.created_at,
             is_processing: Arc::clone(&self.is_processing),
             file_changes: Arc::clone(&self.file_changes),
+            start_snapshot: self.start_snapshot.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(replay: &Replay) -> Vec<u64> {
+        replay.events.iter().map(|e| e.id).collect()
+    }
+
+    #[test]
+    fn test_replay_current_turn() {
+        let channel = EventChannel::new();
+        // No one is subscribed yet, so these are only recorded
+        let _ = channel.send(ServerEvent::Connected);
+        let _ = channel.send(ServerEvent::Completed);
+        let _ = channel.send(ServerEvent::Connected);
+        let _ = channel.send(ServerEvent::TextChunk {
+            text: "hi".to_string(),
+        });
+
+        let (replay, mut rx) = channel.subscribe_with_replay(ReplayFrom::Turn(usize::MAX));
+        assert_eq!(ids(&replay), vec![3, 4]);
+        assert_eq!(ids(&channel.subscribe_with_replay(ReplayFrom::Turn(1)).0), vec![4]);
+        assert_eq!(channel.client_count(), 1);
+
+        channel.send(ServerEvent::Completed).unwrap();
+        let live = rx.try_recv().unwrap();
+        assert_eq!(live.id, 5);
+        assert!(matches!(live.event, ServerEvent::Completed));
+    }
+
+    #[test]
+    fn test_resume_after_id() {
+        let channel = EventChannel::new();
+        for _ in 0..REPLAY_CAPACITY + 5 {
+            let _ = channel.send(ServerEvent::Completed);
         }
+
+        let (replay, _rx) = channel.subscribe_with_replay(ReplayFrom::After(1000));
+        assert_eq!(ids(&replay), vec![1001, 1002, 1003, 1004, 1005]);
+        assert!(!replay.missed);
+
+        // IDs 1 to 5 were pushed out of the ring
+        let (replay, _rx) = channel.subscribe_with_replay(ReplayFrom::After(2));
+        assert_eq!(replay.events.len(), REPLAY_CAPACITY);
+        assert!(replay.missed);
     }
 }
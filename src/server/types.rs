@@ -4,6 +4,10 @@
 //! and server-sent events.
 
 use serde::{Deserialize, Serialize};
+use std::path::Path;
+use crate::checkpoint::{Checkpoint, CheckpointFileChange};
+use crate::custom_commands::ShellSnippet;
+use crate::output_filters::{file_url, find_path_refs};
 use crate::session::SessionEvent;
 use crate::planning::types::PlanEvent;
 
@@ -20,6 +24,9 @@ pub enum ServerEvent {
     /// AI reasoning (before tool execution)
     Reasoning { text: String },
 
+    /// The model's thinking blocks (extended thinking / reasoning models)
+    ModelReasoning { text: String },
+
     /// Tool execution started
     ToolStart { name: String, description: String },
 
@@ -46,6 +53,9 @@ pub enum ServerEvent {
     /// Text chunk from AI response
     TextChunk { text: String },
 
+    /// Project files referenced by the preceding text or tool output
+    FileLinks { links: Vec<FileLinkDto> },
+
     /// Subagent started
     SubagentStarted { id: String, kind: String, task: String },
 
@@ -85,7 +95,25 @@ pub enum ServerEvent {
     ContextCompressed { tokens_compressed: usize },
 
     /// Doom loop detected - user needs to approve continuation
-    DoomLoopPrompt { prompt_id: String, message: String },
+    DoomLoopPrompt {
+        prompt_id: String,
+        message: String,
+        /// Seconds before the session stops waiting and uses its default
+        #[serde(default)]
+        timeout_secs: u64,
+    },
+
+    /// Tool call needs user approval before it runs
+    ToolApprovalPrompt {
+        prompt_id: String,
+        tool: String,
+        description: String,
+        high_risk: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        origin: Option<String>,
+        #[serde(default)]
+        always_ask: bool,
+    },
 
     /// Error occurred
     Error { message: String },
@@ -96,6 +124,33 @@ pub enum ServerEvent {
     /// Todo list update
     TodoList { todos: Vec<TodoItemDto> },
 
+    /// A skill was activated by a file the session touched
+    SkillActivated { name: String, file: String },
+
+    /// The provider rate limited a request; it is retried after a wait
+    RateLimited {
+        provider: String,
+        retry_in_secs: u64,
+        attempt: u32,
+        max_retries: u32,
+    },
+
+    /// A background monitor reported on the command it watches
+    MonitorNotification {
+        monitor_id: String,
+        command: String,
+        summary: String,
+        needs_attention: bool,
+    },
+
+    /// An MCP server dropped out, was restarted, or could not be restarted
+    McpServerHealth {
+        server: String,
+        /// "dropped", "restarted", or "failed"
+        status: String,
+        message: String,
+    },
+
     /// Orchestration started (external CLI task)
     OrchestrateStarted {
         id: String,
@@ -125,6 +180,36 @@ pub struct PlanStepDto {
     pub status: String,
 }
 
+/// A `path:line` reference to a project file, with a `file://` deep link
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileLinkDto {
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// e.g. `file:///repo/src/main.rs#L12`
+    pub url: String,
+}
+
+impl FileLinkDto {
+    /// Links for the references in `text` to files that exist in `project`
+    pub fn find(text: &str, project: &Path) -> Vec<Self> {
+        let mut links: Vec<Self> = Vec::new();
+        for reference in find_path_refs(text) {
+            let seen = links
+                .iter()
+                .any(|l| l.path == reference.path && l.line == reference.line);
+            if !seen && reference.exists_in(project) {
+                links.push(Self {
+                    url: file_url(project, &reference),
+                    path: reference.path,
+                    line: reference.line,
+                });
+            }
+        }
+        links
+    }
+}
+
 /// Todo item DTO for API responses
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TodoItemDto {
@@ -142,6 +227,8 @@ impl From<SessionEvent> for ServerEvent {
 
             SessionEvent::Reasoning(text) => ServerEvent::Reasoning { text },
 
+            SessionEvent::ModelReasoning(text) => ServerEvent::ModelReasoning { text },
+
             SessionEvent::ToolStart { name, description } => {
                 ServerEvent::ToolStart { name, description }
             }
@@ -257,9 +344,33 @@ impl From<SessionEvent> for ServerEvent {
                 ServerEvent::Error { message }
             }
 
-            SessionEvent::DoomLoopPrompt { prompt_id, message, .. } => {
-                ServerEvent::DoomLoopPrompt { prompt_id, message }
-            }
+            SessionEvent::DoomLoopPrompt {
+                prompt_id,
+                message,
+                timeout_secs,
+                ..
+            } => ServerEvent::DoomLoopPrompt {
+                prompt_id,
+                message,
+                timeout_secs,
+            },
+
+            SessionEvent::ToolApprovalPrompt {
+                prompt_id,
+                tool,
+                description,
+                high_risk,
+                origin,
+                always_ask,
+                ..
+            } => ServerEvent::ToolApprovalPrompt {
+                prompt_id,
+                tool,
+                description,
+                high_risk,
+                origin,
+                always_ask,
+            },
 
             // Handle the approval sender - we don't forward this directly
             SessionEvent::PlanApprovalSender(_) => {
@@ -286,6 +397,22 @@ impl From<SessionEvent> for ServerEvent {
                 }
             }
 
+            SessionEvent::SkillActivated { name, file } => {
+                ServerEvent::SkillActivated { name, file }
+            }
+
+            SessionEvent::RateLimited {
+                provider,
+                retry_in_secs,
+                attempt,
+                max_retries,
+            } => ServerEvent::RateLimited {
+                provider,
+                retry_in_secs,
+                attempt,
+                max_retries,
+            },
+
             // Handle orchestration events (external CLI streaming)
             SessionEvent::OrchestrateStarted { id, worker, task } => {
                 ServerEvent::OrchestrateStarted { id, worker, task }
@@ -312,6 +439,12 @@ pub struct CreateSessionRequest {
     pub project_path: String,
     #[serde(default)]
     pub mode: Option<String>,
+    /// Model to use instead of the configured one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Permission preset (safe, dev, full, yolo) instead of the configured one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_preset: Option<String>,
 }
 
 /// Request for changing session mode
@@ -320,6 +453,84 @@ pub struct SetModeRequest {
     pub mode: String,
 }
 
+/// Skill summary
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkillDto {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub triggers: Vec<String>,
+    pub active: bool,
+    /// Estimated prompt tokens when active
+    #[serde(default)]
+    pub tokens: usize,
+}
+
+/// Request for turning a skill on or off
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetSkillRequest {
+    pub active: bool,
+}
+
+/// Request for setting a sampling parameter (`value` may be `default`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetParamRequest {
+    pub name: String,
+    pub value: String,
+}
+
+/// Resource offered by a connected MCP server
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpResourceDto {
+    pub server: String,
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+/// Health of a configured MCP server
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpServerStatusDto {
+    pub name: String,
+    pub transport: String,
+    pub endpoint: String,
+    /// "connected", "connecting", "disconnected", or "failed: <reason>"
+    pub state: String,
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+    pub tool_count: usize,
+    pub restarts: u32,
+}
+
+/// Prompt offered by a connected MCP server
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpPromptDto {
+    pub server: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Slash command usage, e.g. `/mcp:github:review <pr> [focus]`
+    pub usage: String,
+}
+
+/// Request for rendering an MCP prompt
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpPromptRequest {
+    /// Raw slash-command arguments
+    #[serde(default)]
+    pub arguments: String,
+}
+
+/// Rendered MCP prompt text
+#[derive(Debug, Serialize, Deserialize)]
+pub struct McpPromptResponse {
+    pub text: String,
+}
+
 /// Response for session creation
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SessionResponse {
@@ -335,6 +546,15 @@ pub struct SendMessageRequest {
     pub content: String,
     #[serde(default)]
     pub attachments: Vec<AttachmentDto>,
+    /// Restrict this message to these tools (custom commands)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_tools: Option<Vec<String>>,
+    /// Use this model for this message (custom commands)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    /// Shell snippets to run and substitute into the content (custom commands)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shell_snippets: Vec<ShellSnippet>,
 }
 
 /// Attachment DTO
@@ -354,6 +574,9 @@ pub struct MessageDto {
     pub timestamp: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCallDto>>,
+    /// Project files referenced in the content
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub links: Vec<FileLinkDto>,
 }
 
 /// Tool call DTO
@@ -366,24 +589,48 @@ pub struct ToolCallDto {
     pub output: Option<String>,
 }
 
+/// Recent and pinned projects
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectListResponse {
+    pub projects: Vec<ProjectDto>,
+}
+
+/// A project the desktop app has opened, with its session overrides
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProjectDto {
+    pub path: String,
+    pub name: String,
+    pub last_opened: String,
+    pub pinned: bool,
+    /// False once the directory has been moved or deleted
+    pub exists: bool,
+    pub overrides: crate::persistence::projects::ProjectOverrides,
+}
+
 /// Session list response
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SessionListResponse {
     pub sessions: Vec<SessionSummary>,
 }
 
 /// Session summary for list view
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct SessionSummary {
     pub id: String,
     pub project_path: String,
     pub created_at: String,
     pub message_count: usize,
     pub file_changes: FileChangeStats,
+    /// Whether a message is being processed right now
+    #[serde(default)]
+    pub is_processing: bool,
+    /// Clients currently streaming the session's events
+    #[serde(default)]
+    pub clients: usize,
 }
 
 /// File change statistics
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct FileChangeStats {
     pub total_files: usize,
     pub additions: i32,
@@ -438,3 +685,193 @@ pub struct DoomLoopResponseRequest {
     pub prompt_id: String,
     pub continue_anyway: bool,
 }
+
+/// Tool approval response request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ToolApprovalResponseRequest {
+    pub prompt_id: String,
+    pub approved: bool,
+}
+
+/// A tool call waiting for approval in some session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingApprovalDto {
+    pub prompt_id: String,
+    pub session_id: String,
+    pub tool: String,
+    pub description: String,
+    pub high_risk: bool,
+    pub requested_at: String,
+}
+
+/// A session project's checkpoints, oldest first
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckpointListResponse {
+    pub session_id: String,
+    /// Whether a checkpoint is taken before each message
+    pub enabled: bool,
+    pub checkpoints: Vec<Checkpoint>,
+}
+
+/// Take a checkpoint now
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CreateCheckpointRequest {
+    pub label: Option<String>,
+}
+
+/// A checkpoint and how the project differs from it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CheckpointDetailResponse {
+    pub checkpoint: Checkpoint,
+    pub changes: Vec<CheckpointFileChange>,
+}
+
+/// Restore a whole checkpoint, or only `files`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RestoreCheckpointRequest {
+    pub files: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreCheckpointResponse {
+    pub checkpoint_id: String,
+    /// Files written back or, for files created since, removed
+    pub restored: Vec<String>,
+}
+
+/// Pending tool approvals across sessions
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApprovalListResponse {
+    pub approvals: Vec<PendingApprovalDto>,
+}
+
+/// Answer to a tool approval, by prompt ID alone
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApprovalDecisionRequest {
+    pub approved: bool,
+}
+
+/// Request to start an orchestration run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateOrchestrationRequest {
+    pub project_path: String,
+    /// What to do, broken into tasks by the planner
+    pub request: String,
+    /// Worker for tasks the planner doesn't assign ("claude", "gemini", ...)
+    #[serde(default)]
+    pub worker: Option<String>,
+    #[serde(default)]
+    pub max_workers: Option<usize>,
+}
+
+/// An orchestration run and the progress of its tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrchestrationDto {
+    pub id: String,
+    pub project_path: String,
+    pub request: String,
+    pub created_at: String,
+    /// "planning", "running", "completed" or "failed"
+    pub status: String,
+    /// Artifact directory of the run, once its tasks have finished
+    #[serde(default)]
+    pub run_id: Option<String>,
+    pub tasks: Vec<OrchestrationTaskDto>,
+    /// Tasks whose changes were merged
+    #[serde(default)]
+    pub merged_tasks: Vec<String>,
+    /// Branches holding changes left for review
+    #[serde(default)]
+    pub branches: Vec<String>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    /// Why the run failed as a whole
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// A task of an orchestration run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrchestrationTaskDto {
+    pub id: String,
+    pub description: String,
+    pub worker: crate::orchestrator::WorkerKind,
+    /// "pending", "running", "cancelling", "completed", "failed" or "cancelled"
+    pub status: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Orchestration runs started on this server
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrchestrationListResponse {
+    pub orchestrations: Vec<OrchestrationDto>,
+}
+
+/// Events of an orchestration run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum OrchestrationEvent {
+    /// The request was broken into tasks
+    Planned { tasks: Vec<OrchestrationTaskDto> },
+
+    /// A worker started on a task
+    TaskStarted {
+        task_id: String,
+        description: String,
+        worker: crate::orchestrator::WorkerKind,
+    },
+
+    /// A line printed by a task's worker; `stream` is "stdout" or "stderr"
+    TaskOutput {
+        task_id: String,
+        stream: String,
+        line: String,
+    },
+
+    /// A task ended as "completed", "failed" or "cancelled"
+    TaskFinished {
+        task_id: String,
+        status: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+
+    /// The run ended; its events stop here
+    Finished { status: String, summary: String },
+}
+
+/// Tasks recorded in an orchestration run's artifacts
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OrchestrationArtifactsResponse {
+    pub run_id: String,
+    pub tasks: Vec<TaskArtifactSummary>,
+}
+
+/// A task in a run's artifacts, with its outcome if it finished
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskArtifactSummary {
+    pub task_id: String,
+    #[serde(default)]
+    pub outcome: Option<crate::orchestrator::artifacts::TaskOutcome>,
+}
+
+/// Everything recorded for one task of a run
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TaskArtifactsDto {
+    pub task_id: String,
+    #[serde(default)]
+    pub outcome: Option<crate::orchestrator::artifacts::TaskOutcome>,
+    /// Instructions as the worker received them
+    #[serde(default)]
+    pub prompt: Option<String>,
+    #[serde(default)]
+    pub stdout: Option<String>,
+    #[serde(default)]
+    pub stderr: Option<String>,
+    #[serde(default)]
+    pub diff: Option<String>,
+    /// Files too large to send whole; only their end is included
+    #[serde(default)]
+    pub truncated: Vec<String>,
+}
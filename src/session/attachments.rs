@@ -0,0 +1,378 @@
+//! Files, directories, URLs and images attached to a chat message
+//!
+//! The TUI and `safe-coder run --attach` resolve attachments when they are
+//! added, so a bad path fails right away instead of with the next message.
+//! Attachments sent over the API without content are read on the server,
+//! relative to the session's project.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Characters of one attachment sent to the model
+const MAX_ATTACHMENT_CHARS: usize = 100_000;
+
+/// Larger files in an attached directory are listed but not included
+const MAX_DIRECTORY_FILE_BYTES: u64 = 64 * 1024;
+
+/// Largest image attached; providers reject images over 5 MB once
+/// base64-encoded
+const MAX_IMAGE_BYTES: usize = 3_750_000;
+
+/// Image formats every vision provider accepts, by extension
+const IMAGE_TYPES: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttachmentKind {
+    File,
+    Directory,
+    Url,
+    /// Sent to the model as an image block rather than text
+    Image,
+}
+
+#[derive(Debug, Clone)]
+pub struct Attachment {
+    pub kind: AttachmentKind,
+    /// The path or URL as given
+    pub source: String,
+    /// Text, or a `data:` URL for images
+    pub content: String,
+}
+
+impl Attachment {
+    /// An attachment whose content was supplied by the client
+    pub fn with_content(source: String, content: String) -> Self {
+        let kind = if content.starts_with("data:image/") {
+            AttachmentKind::Image
+        } else if is_url(&source) {
+            AttachmentKind::Url
+        } else {
+            AttachmentKind::File
+        };
+        Self {
+            kind,
+            source,
+            content,
+        }
+    }
+
+    /// Base64 data and media type of an image attachment
+    pub fn image_data(&self) -> Option<(String, String)> {
+        if self.kind != AttachmentKind::Image {
+            return None;
+        }
+        let (media_type, data) = self
+            .content
+            .strip_prefix("data:")?
+            .split_once(";base64,")?;
+        Some((data.to_string(), media_type.to_string()))
+    }
+
+    /// Short name shown on the attachment's chip
+    pub fn label(&self) -> String {
+        let name = |source: &str| {
+            Path::new(source.trim_end_matches(['/', '\\']))
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| source.to_string())
+        };
+        match self.kind {
+            AttachmentKind::File | AttachmentKind::Image => name(&self.source),
+            AttachmentKind::Directory => format!("{}/", name(&self.source)),
+            AttachmentKind::Url => {
+                let url = self
+                    .source
+                    .trim_start_matches("https://")
+                    .trim_start_matches("http://");
+                if url.chars().count() > 40 {
+                    format!("{}…", url.chars().take(40).collect::<String>())
+                } else {
+                    url.to_string()
+                }
+            }
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self.kind {
+            AttachmentKind::File => "📄",
+            AttachmentKind::Directory => "📁",
+            AttachmentKind::Url => "🌐",
+            AttachmentKind::Image => "🖼",
+        }
+    }
+}
+
+pub fn is_url(spec: &str) -> bool {
+    spec.starts_with("http://") || spec.starts_with("https://")
+}
+
+/// Read a file or directory, or fetch a URL
+///
+/// Relative paths are resolved against `base`; a leading `~` is expanded.
+pub async fn resolve(spec: &str, base: &Path) -> Result<Attachment> {
+    let spec = spec.trim();
+    if is_url(spec) {
+        return fetch_url(spec).await;
+    }
+
+    let path = crate::platform::expand_home(spec).unwrap_or_else(|| base.join(spec));
+    if path.is_dir() {
+        read_directory(&path, spec.to_string())
+    } else if let Some(media_type) = image_media_type(&path) {
+        read_image(&path, spec.to_string(), media_type)
+    } else {
+        read_file(&path, spec.to_string())
+    }
+}
+
+fn image_media_type(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    IMAGE_TYPES
+        .iter()
+        .find(|(ext, _)| *ext == extension)
+        .map(|(_, media_type)| *media_type)
+}
+
+fn read_image(path: &Path, source: String, media_type: &str) -> Result<Attachment> {
+    use base64::Engine;
+
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read {}", source))?;
+    if bytes.len() > MAX_IMAGE_BYTES {
+        anyhow::bail!(
+            "{} is too large to attach ({} bytes, at most {})",
+            source,
+            bytes.len(),
+            MAX_IMAGE_BYTES
+        );
+    }
+    Ok(Attachment {
+        kind: AttachmentKind::Image,
+        source,
+        content: format!(
+            "data:{};base64,{}",
+            media_type,
+            base64::engine::general_purpose::STANDARD.encode(&bytes)
+        ),
+    })
+}
+
+fn read_file(path: &Path, source: String) -> Result<Attachment> {
+    let bytes = std::fs::read(path).with_context(|| format!("Failed to read {}", source))?;
+    let content = String::from_utf8(bytes)
+        .map_err(|_| anyhow::anyhow!("{} is not a text file", source))?;
+    Ok(Attachment {
+        kind: AttachmentKind::File,
+        source,
+        content: truncate(content),
+    })
+}
+
+/// List the directory's files (respecting .gitignore) and include the small
+/// text ones while there is room
+fn read_directory(dir: &Path, source: String) -> Result<Attachment> {
+    let mut listing = String::new();
+    let mut contents = String::new();
+    for entry in ignore::WalkBuilder::new(dir).build().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_some_and(|t| t.is_file()) {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(dir).unwrap_or(entry.path());
+        listing.push_str(&format!("{}\n", relative.display()));
+
+        let small = entry
+            .metadata()
+            .is_ok_and(|m| m.len() <= MAX_DIRECTORY_FILE_BYTES);
+        if small && contents.len() < MAX_ATTACHMENT_CHARS {
+            if let Ok(text) = std::fs::read_to_string(entry.path()) {
+                contents.push_str(&format!("\n--- {} ---\n{}\n", relative.display(), text));
+            }
+        }
+    }
+    if listing.is_empty() {
+        anyhow::bail!("{} has no files", source);
+    }
+
+    Ok(Attachment {
+        kind: AttachmentKind::Directory,
+        source,
+        content: truncate(format!("Files:\n{}{}", listing, contents)),
+    })
+}
+
+async fn fetch_url(url: &str) -> Result<Attachment> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent("SafeCoder/1.0")
+        .build()?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to fetch {}", url))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        anyhow::bail!("Failed to fetch {}: HTTP {}", url, status.as_u16());
+    }
+    let is_html = response
+        .headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|t| t.contains("text/html"));
+    let body = response.text().await?;
+    let content = if is_html {
+        crate::tools::webfetch::extract_text_from_html(&body)
+    } else {
+        body
+    };
+
+    Ok(Attachment {
+        kind: AttachmentKind::Url,
+        source: url.to_string(),
+        content: truncate(content),
+    })
+}
+
+fn truncate(content: String) -> String {
+    match content.char_indices().nth(MAX_ATTACHMENT_CHARS) {
+        Some((end, _)) => format!(
+            "{}\n... [truncated at {} characters]",
+            &content[..end],
+            MAX_ATTACHMENT_CHARS
+        ),
+        None => content,
+    }
+}
+
+/// Append text attachments to a user message; images are sent separately
+pub fn append_to_message(message: &str, attachments: &[Attachment]) -> String {
+    let mut out = message.to_string();
+    for attachment in attachments.iter().filter(|a| a.kind != AttachmentKind::Image) {
+        out.push_str(&format!(
+            "\n\n--- Attachment: {} ---\n{}",
+            attachment.source, attachment.content
+        ));
+    }
+    out
+}
+
+/// Images to send with the message, as (base64 data, media type)
+pub fn images(attachments: &[Attachment]) -> Vec<(String, String)> {
+    attachments.iter().filter_map(Attachment::image_data).collect()
+}
+
+/// The paths in pasted text, if it contains nothing but existing paths
+///
+/// Terminals paste a dragged-and-dropped file as its path: quoted, with
+/// backslash-escaped spaces, or as a `file://` URI.
+pub fn dropped_paths(text: &str, cwd: &Path) -> Option<Vec<PathBuf>> {
+    let words = split_words(text.trim())?;
+    if words.is_empty() {
+        return None;
+    }
+    words
+        .iter()
+        .map(|word| {
+            let word = word.strip_prefix("file://").unwrap_or(word);
+            let word = word.replace("%20", " ");
+            let path = crate::platform::expand_home(&word).unwrap_or_else(|| PathBuf::from(&word));
+            // Only absolute paths, so ordinary text is never mistaken for a drop
+            (path.is_absolute() && path.exists()).then(|| {
+                path.strip_prefix(cwd).map(Path::to_path_buf).unwrap_or(path)
+            })
+        })
+        .collect()
+}
+
+/// Split on unquoted whitespace, honouring quotes and backslash escapes
+fn split_words(text: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => current.push(c),
+            (None, '\'' | '"') => quote = Some(c),
+            (None, '\\') if !cfg!(windows) => current.push(chars.next()?),
+            (None, c) if c.is_whitespace() => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            (None, c) => current.push(c),
+        }
+    }
+    if quote.is_some() {
+        return None;
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    Some(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropped_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("my notes.txt");
+        std::fs::write(&file, "hi").unwrap();
+        let escaped = file.to_string_lossy().replace(' ', "\\ ");
+
+        assert_eq!(
+            dropped_paths(&escaped, Path::new("/elsewhere")),
+            Some(vec![file.clone()])
+        );
+        assert_eq!(
+            dropped_paths(&format!("'{}'", file.display()), dir.path()),
+            Some(vec![PathBuf::from("my notes.txt")])
+        );
+        assert_eq!(dropped_paths("fix the bug", dir.path()), None);
+        assert_eq!(dropped_paths(&format!("see {}", escaped), dir.path()), None);
+    }
+
+    #[tokio::test]
+    async fn test_resolve_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "pub fn a() {}").unwrap();
+
+        let attachment = resolve("src", dir.path()).await.unwrap();
+        assert_eq!(attachment.kind, AttachmentKind::Directory);
+        assert_eq!(attachment.label(), "src/");
+        assert!(attachment.content.contains("lib.rs"));
+        assert!(attachment.content.contains("pub fn a() {}"));
+    }
+
+    #[tokio::test]
+    async fn test_images_are_sent_separately() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("shot.png"), [0x89, b'P', b'N', b'G']).unwrap();
+
+        let image = resolve("shot.png", dir.path()).await.unwrap();
+        assert_eq!(image.kind, AttachmentKind::Image);
+        let note = Attachment::with_content("notes.md".to_string(), "hi".to_string());
+
+        let attachments = [image, note];
+        assert_eq!(
+            append_to_message("look", &attachments),
+            "look\n\n--- Attachment: notes.md ---\nhi"
+        );
+        let images = images(&attachments);
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].1, "image/png");
+    }
+}
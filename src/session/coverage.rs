@@ -0,0 +1,288 @@
+//! Coverage check for BUILD-mode changes
+//!
+//! With `build.coverage_check` on, the session measures line coverage
+//! (cargo llvm-cov or pytest-cov) before its first BUILD-mode turn and again
+//! after each turn that edited files. Edited files whose coverage dropped
+//! against that baseline are reported at the end of the turn, so changes
+//! that leave code untested don't go unnoticed.
+
+use anyhow::{bail, Context, Result};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+/// Drops smaller than this (in percentage points) are noise
+const MIN_DROP_PERCENT: f64 = 0.1;
+
+/// Covered and total lines per file, keyed by path relative to the project
+pub type FileCoverage = HashMap<String, (u64, u64)>;
+
+#[derive(Default)]
+pub struct CoverageCheck {
+    baseline: Option<FileCoverage>,
+    /// Set when coverage can't be measured, so it isn't retried every turn
+    unavailable: bool,
+    /// Files edited since the last comparison
+    edited: BTreeSet<PathBuf>,
+}
+
+impl CoverageCheck {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether the baseline still has to be measured
+    pub fn needs_baseline(&self) -> bool {
+        self.baseline.is_none() && !self.unavailable
+    }
+
+    /// Measure the pre-change baseline
+    pub async fn measure_baseline(&mut self, project: &Path, timeout_secs: u64) {
+        match measure(project, timeout_secs).await {
+            Ok(coverage) => self.baseline = Some(coverage),
+            Err(e) => {
+                tracing::warn!("Coverage check disabled for this session: {:#}", e);
+                self.unavailable = true;
+            }
+        }
+    }
+
+    /// Note a file the model edited
+    pub fn record_edit(&mut self, path: PathBuf) {
+        if self.baseline.is_some() {
+            self.edited.insert(path);
+        }
+    }
+
+    /// Re-measure after a turn that edited files and describe edited files
+    /// whose coverage dropped below the baseline
+    pub async fn check(&mut self, project: &Path, timeout_secs: u64) -> Option<String> {
+        if self.edited.is_empty() {
+            return None;
+        }
+        let edited: Vec<String> = std::mem::take(&mut self.edited)
+            .iter()
+            .map(|p| relative(p, project))
+            .collect();
+        let baseline = self.baseline.as_ref()?;
+        let after = match measure(project, timeout_secs).await {
+            Ok(coverage) => coverage,
+            Err(e) => {
+                tracing::warn!("Coverage measurement failed: {:#}", e);
+                return None;
+            }
+        };
+        let drops = coverage_drops(baseline, &after, &edited);
+        if drops.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "⚠️ Test coverage dropped for files edited in this turn:\n{}",
+            drops.join("\n")
+        ))
+    }
+}
+
+/// Lines describing edited files covered less than in the baseline. A new
+/// file counts as a drop when no test covers any of it.
+pub fn coverage_drops(
+    baseline: &FileCoverage,
+    after: &FileCoverage,
+    edited: &[String],
+) -> Vec<String> {
+    let mut drops = Vec::new();
+    for file in edited {
+        let Some(&(covered, total)) = after.get(file) else {
+            continue;
+        };
+        let now = percent(covered, total);
+        match baseline.get(file) {
+            Some(&(was_covered, was_total)) => {
+                let before = percent(was_covered, was_total);
+                if before - now >= MIN_DROP_PERCENT {
+                    drops.push(format!(
+                        "  {}: {:.1}% → {:.1}% ({} of {} lines uncovered)",
+                        file,
+                        before,
+                        now,
+                        total - covered,
+                        total
+                    ));
+                }
+            }
+            None if total > 0 && covered == 0 => {
+                drops.push(format!("  {}: new file, no lines covered by tests", file));
+            }
+            None => {}
+        }
+    }
+    drops
+}
+
+fn percent(covered: u64, total: u64) -> f64 {
+    if total == 0 {
+        100.0
+    } else {
+        covered as f64 * 100.0 / total as f64
+    }
+}
+
+fn relative(path: &Path, project: &Path) -> String {
+    path.strip_prefix(project)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+/// Run the project's coverage tool and read its per-file line coverage
+async fn measure(project: &Path, timeout_secs: u64) -> Result<FileCoverage> {
+    let report_dir = tempfile::tempdir()?;
+    let report = report_dir.path().join("coverage.json");
+
+    let mut command = if project.join("Cargo.toml").exists() {
+        if which::which("cargo-llvm-cov").is_err() {
+            bail!("cargo-llvm-cov is not installed (cargo install cargo-llvm-cov)");
+        }
+        let mut command = Command::new("cargo");
+        command
+            .args(["llvm-cov", "--json", "--summary-only", "--output-path"])
+            .arg(&report);
+        command
+    } else if ["pyproject.toml", "setup.py", "setup.cfg", "pytest.ini"]
+        .iter()
+        .any(|m| project.join(m).exists())
+    {
+        let python = if which::which("python3").is_ok() {
+            "python3"
+        } else {
+            "python"
+        };
+        let mut command = Command::new(python);
+        command
+            .args(["-m", "pytest", "-q", "--cov=.", "--cov-report"])
+            .arg(format!("json:{}", report.display()));
+        command
+    } else {
+        bail!("no supported coverage tool for this project");
+    };
+
+    let output = tokio::time::timeout(
+        Duration::from_secs(timeout_secs),
+        command
+            .current_dir(project)
+            .env("CARGO_TERM_COLOR", "never")
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .with_context(|| format!("coverage run timed out after {}s", timeout_secs))??;
+
+    // Failing tests still produce a report; only a missing one is an error
+    let json = std::fs::read_to_string(&report).with_context(|| {
+        format!(
+            "no coverage report written: {}",
+            String::from_utf8_lossy(&output.stderr)
+                .lines()
+                .last()
+                .unwrap_or_default()
+        )
+    })?;
+    let json: serde_json::Value = serde_json::from_str(&json)?;
+    if json.get("data").is_some() {
+        Ok(parse_llvm_cov(&json, project))
+    } else {
+        Ok(parse_coverage_py(&json))
+    }
+}
+
+/// Per-file line coverage from `cargo llvm-cov --json --summary-only`
+pub fn parse_llvm_cov(json: &serde_json::Value, project: &Path) -> FileCoverage {
+    let mut coverage = FileCoverage::new();
+    let exports = json["data"].as_array().into_iter().flatten();
+    for file in exports.flat_map(|e| e["files"].as_array().into_iter().flatten()) {
+        let Some(name) = file["filename"].as_str() else {
+            continue;
+        };
+        let lines = &file["summary"]["lines"];
+        coverage.insert(
+            relative(Path::new(name), project),
+            (
+                lines["covered"].as_u64().unwrap_or(0),
+                lines["count"].as_u64().unwrap_or(0),
+            ),
+        );
+    }
+    coverage
+}
+
+/// Per-file line coverage from coverage.py's JSON report
+pub fn parse_coverage_py(json: &serde_json::Value) -> FileCoverage {
+    json["files"]
+        .as_object()
+        .into_iter()
+        .flatten()
+        .map(|(name, file)| {
+            let summary = &file["summary"];
+            (
+                name.replace('\\', "/"),
+                (
+                    summary["covered_lines"].as_u64().unwrap_or(0),
+                    summary["num_statements"].as_u64().unwrap_or(0),
+                ),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_reports() {
+        let llvm = serde_json::json!({
+            "data": [{
+                "files": [
+                    {"filename": "/work/app/src/lib.rs",
+                     "summary": {"lines": {"count": 40, "covered": 30, "percent": 75.0}}}
+                ]
+            }]
+        });
+        let coverage = parse_llvm_cov(&llvm, Path::new("/work/app"));
+        assert_eq!(coverage.get("src/lib.rs"), Some(&(30, 40)));
+
+        let py = serde_json::json!({
+            "files": {
+                "pkg/util.py": {"summary": {"covered_lines": 8, "num_statements": 10}}
+            }
+        });
+        assert_eq!(parse_coverage_py(&py).get("pkg/util.py"), Some(&(8, 10)));
+    }
+
+    #[test]
+    fn test_coverage_drops_for_edited_files_only() {
+        let baseline: FileCoverage = [
+            ("src/a.rs".to_string(), (90, 100)),
+            ("src/b.rs".to_string(), (50, 100)),
+        ]
+        .into();
+        let after: FileCoverage = [
+            ("src/a.rs".to_string(), (90, 120)),
+            ("src/b.rs".to_string(), (10, 100)),
+            ("src/new.rs".to_string(), (0, 15)),
+            ("src/tested.rs".to_string(), (15, 15)),
+        ]
+        .into();
+        let edited = ["src/a.rs", "src/new.rs", "src/tested.rs"].map(String::from);
+
+        let drops = coverage_drops(&baseline, &after, &edited);
+        assert_eq!(
+            drops,
+            vec![
+                "  src/a.rs: 90.0% → 75.0% (30 of 120 lines uncovered)",
+                "  src/new.rs: new file, no lines covered by tests",
+            ]
+        );
+    }
+}
@@ -0,0 +1,208 @@
+//! Watches files the AI has read for edits made outside the session
+//!
+//! When the user (or an editor, or git) changes a file the model has seen,
+//! the model's copy is stale and its next edit may not apply or may undo
+//! the change. The session tracks the content the model last saw of each
+//! file; before the next turn, files that changed on disk are reported with
+//! a diff against that content.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// Diff lines shown per changed file; the rest is summarised
+const MAX_DIFF_LINES: usize = 40;
+
+/// Content the model last saw of each tracked file
+#[derive(Default)]
+struct Tracked {
+    seen: HashMap<PathBuf, String>,
+    /// Directories being watched (files are watched through their parent,
+    /// which survives editors that save by renaming over the file)
+    dirs: HashSet<PathBuf>,
+}
+
+pub struct FileWatcher {
+    watcher: Option<RecommendedWatcher>,
+    tracked: Mutex<Tracked>,
+    /// Tracked files with filesystem events since the last check
+    dirty: Arc<Mutex<BTreeSet<PathBuf>>>,
+}
+
+impl FileWatcher {
+    pub fn new() -> Self {
+        let dirty: Arc<Mutex<BTreeSet<PathBuf>>> = Arc::default();
+        let events = Arc::clone(&dirty);
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if event.kind.is_modify() || event.kind.is_create() || event.kind.is_remove() {
+                    events.lock().unwrap().extend(event.paths);
+                }
+            }
+        });
+        let watcher = match watcher {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                tracing::warn!("File watching unavailable: {}", e);
+                None
+            }
+        };
+        Self {
+            watcher,
+            tracked: Mutex::default(),
+            dirty,
+        }
+    }
+
+    /// Record what the model now knows of `path`, e.g. after reading or
+    /// editing it, and start watching it
+    pub fn track(&mut self, path: &Path) {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return;
+        };
+        let mut tracked = self.tracked.lock().unwrap();
+        tracked.seen.insert(path.to_path_buf(), content);
+
+        let Some(dir) = path.parent() else {
+            return;
+        };
+        if tracked.dirs.contains(dir) {
+            return;
+        }
+        if let Some(watcher) = &mut self.watcher {
+            match watcher.watch(dir, RecursiveMode::NonRecursive) {
+                Ok(()) => {
+                    tracked.dirs.insert(dir.to_path_buf());
+                }
+                Err(e) => tracing::debug!("Failed to watch {}: {}", dir.display(), e),
+            }
+        }
+    }
+
+    /// Report tracked files whose content changed since the model last saw
+    /// it. The new content counts as seen from here on.
+    pub fn take_changes(&self, project_path: &Path) -> Option<String> {
+        let dirty = std::mem::take(&mut *self.dirty.lock().unwrap());
+        let mut tracked = self.tracked.lock().unwrap();
+
+        let mut notices = Vec::new();
+        for path in dirty {
+            let Some(seen) = tracked.seen.get(&path) else {
+                continue;
+            };
+            let name = path
+                .strip_prefix(project_path)
+                .unwrap_or(&path)
+                .display()
+                .to_string();
+            match std::fs::read_to_string(&path) {
+                Ok(current) if current == *seen => {}
+                Ok(current) => {
+                    notices.push(format!(
+                        "{}:\n```diff\n{}```",
+                        name,
+                        short_diff(seen, &current)
+                    ));
+                    tracked.seen.insert(path, current);
+                }
+                Err(_) if !path.exists() => {
+                    notices.push(format!("{} was deleted.", name));
+                    tracked.seen.remove(&path);
+                }
+                Err(_) => {}
+            }
+        }
+
+        if notices.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "[FILES CHANGED ON DISK]\n\
+            These files were changed outside this conversation since you last saw them. \
+            Read a file again before editing it.\n{}",
+            notices.join("\n")
+        ))
+    }
+}
+
+impl Default for FileWatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Unified diff of `old` to `new`, cut to `MAX_DIFF_LINES` lines
+fn short_diff(old: &str, new: &str) -> String {
+    let diff = similar::TextDiff::from_lines(old, new)
+        .unified_diff()
+        .context_radius(2)
+        .to_string();
+    let lines: Vec<&str> = diff.lines().collect();
+    if lines.len() <= MAX_DIFF_LINES {
+        return diff;
+    }
+    format!(
+        "{}\n... {} more diff lines\n",
+        lines[..MAX_DIFF_LINES].join("\n"),
+        lines.len() - MAX_DIFF_LINES
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    /// Wait for the watcher to notice events, which arrive on another thread
+    fn changes_after_events(watcher: &FileWatcher, project: &Path) -> Option<String> {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            if let Some(changes) = watcher.take_changes(project) {
+                return Some(changes);
+            }
+            if Instant::now() > deadline {
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    #[test]
+    fn test_reports_external_edits() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().canonicalize().unwrap();
+        let path = project.join("a.rs");
+        std::fs::write(&path, "fn a() {}\n").unwrap();
+        std::fs::write(project.join("untracked.rs"), "x\n").unwrap();
+
+        let mut watcher = FileWatcher::new();
+        watcher.track(&path);
+        assert!(watcher.take_changes(&project).is_none());
+
+        std::fs::write(project.join("untracked.rs"), "y\n").unwrap();
+        std::fs::write(&path, "fn a() {}\nfn b() {}\n").unwrap();
+        let changes = changes_after_events(&watcher, &project).unwrap();
+        assert!(changes.starts_with("[FILES CHANGED ON DISK]"));
+        assert!(changes.contains("a.rs:\n```diff\n"));
+        assert!(changes.contains("+fn b() {}"));
+        assert!(!changes.contains("untracked.rs"));
+
+        // The new content is what the model knows now
+        watcher.dirty.lock().unwrap().insert(path.clone());
+        assert!(watcher.take_changes(&project).is_none());
+
+        std::fs::remove_file(&path).unwrap();
+        let changes = changes_after_events(&watcher, &project).unwrap();
+        assert!(changes.contains("a.rs was deleted."));
+    }
+
+    #[test]
+    fn test_short_diff_is_cut() {
+        let old: String = (0..100).map(|i| format!("{}\n", i)).collect();
+        let new: String = (0..100).map(|i| format!("{}!\n", i)).collect();
+        let diff = short_diff(&old, &new);
+        assert_eq!(diff.lines().count(), MAX_DIFF_LINES + 1);
+        assert!(diff.ends_with("more diff lines\n"));
+    }
+}
@@ -1,3 +1,7 @@
+pub mod attachments;
+mod coverage;
+mod file_watcher;
+
 use anyhow::{Context, Result};
 use chrono::Utc;
 use std::io::{self, Write};
@@ -10,20 +14,29 @@ use crate::utils::truncate_str;
 use crate::checkpoint::{CheckpointManager, DirectoryCheckpointManager};
 use crate::config::Config;
 use crate::context::ContextManager;
-use crate::custom_commands::CustomCommandManager;
+use crate::custom_commands::{fill_shell_snippets, CustomCommandManager, ShellSnippet};
 use crate::git::GitManager;
-use crate::llm::{create_client, ContentBlock, LlmClient, Message, ToolDefinition};
+use crate::hooks::{HookContext, HookManager, HookResult, HookType, PreToolDecision};
+use crate::llm::rate_limit::{with_retry_notifier, RateLimitError, RetryNotice, RetryNotifier};
+use crate::llm::{create_client, Capabilities, ContentBlock, LlmClient, Message, ToolDefinition};
 use crate::loop_detector::{DoomLoopAction, LoopDetector};
 use crate::lsp::LspManager;
 use crate::mcp::McpManager;
 use crate::memory::MemoryManager;
-use crate::permissions::PermissionManager;
-use crate::persistence::{SessionPersistence, SessionStats, ToolUsage};
+use crate::permissions::{Permission, PermissionManager};
+use crate::persistence::{SessionPersistence, SessionStats, SessionStatsRecord, ToolUsage};
 use crate::unified_planning::create_runner;
-use crate::planning::{PlanEvent, PlanStatus, PlanStep, TaskPlan};
+use crate::planning::{PlanEvent, PlanStatus, PlanStep, PlanStore, TaskPlan};
 use crate::prompts;
-use crate::tools::todo::{clear_todo_list, get_todo_list, increment_turns_without_update, should_show_reminder};
-use crate::tools::{AgentMode, ToolContext, ToolRegistry};
+use crate::prompts::overrides::{PromptOverrides, PromptVars};
+use crate::skills::SkillManager;
+use crate::subagent::MonitorHub;
+use crate::tools::todo::{get_todo_list, TodoStore, increment_turns_without_update, load_todo_list, open_todos_prompt, should_show_reminder};
+use crate::tools::{
+    render_tool_error, repair, AgentMode, FileCache, ToolContext, ToolErrorInfo, ToolRegistry,
+};
+use coverage::CoverageCheck;
+use file_watcher::FileWatcher;
 // Unified planning imports (reserved for future use)
 // use crate::unified_planning::{ExecutionMode as UnifiedExecutionMode, UnifiedPlanner, PlanEvent as UnifiedPlanEvent};
 // use crate::unified_planning::integration::create_runner;
@@ -32,6 +45,13 @@ use crate::tools::{AgentMode, ToolContext, ToolRegistry};
 /// This prevents context bloat from large tool outputs
 const MAX_TOOL_RESULT_CHARS: usize = 8000;
 
+/// Tool result reported to the model when the user stops a detected loop
+const LOOP_STOPPED_RESULT: &str = "Operation stopped by user due to detected loop pattern.";
+
+/// Largest screenshot attached for the model; providers reject images over
+/// 5 MB once base64-encoded
+const MAX_SCREENSHOT_BYTES: usize = 3_750_000;
+
 /// Truncate tool result to prevent context bloat
 fn truncate_tool_result(result: String) -> String {
     if result.len() <= MAX_TOOL_RESULT_CHARS {
@@ -57,6 +77,37 @@ fn truncate_tool_result(result: String) -> String {
     )
 }
 
+/// Tool result reported to the model when a call is blocked by permission rules
+fn permission_denied_result(name: &str, reason: &str) -> String {
+    format!(
+        "Error: Permission denied - '{}' was blocked by the user's permission rules ({}). \
+        Do not retry this call; use a different approach or ask the user.",
+        name, reason
+    )
+}
+
+/// Tool result reported to the model when the user declines a call
+fn approval_declined_result(name: &str) -> String {
+    format!(
+        "Error: The user declined to run '{}'. \
+        Do not retry this call; use a different approach or ask the user how to proceed.",
+        name
+    )
+}
+
+/// Tool result reported to the model when a pre_tool hook blocks a call
+fn hook_blocked_result(name: &str, reason: &str) -> String {
+    format!("Error: Tool '{}' was blocked by a hook: {}", name, reason)
+}
+
+/// Append the loop detector's guidance to a tool result so the model sees it
+fn with_loop_guidance(result: String, guidance: Option<String>) -> String {
+    match guidance {
+        Some(guidance) => format!("{}\n\n[LOOP DETECTED]\n{}", result, guidance),
+        None => result,
+    }
+}
+
 /// Events emitted during AI message processing for real-time UI updates
 #[derive(Debug, Clone)]
 pub enum SessionEvent {
@@ -64,6 +115,8 @@ pub enum SessionEvent {
     Thinking(String),
     /// AI reasoning text before/between tool calls (the LLM's explanation of what it's doing)
     Reasoning(String),
+    /// The model's own thinking blocks (extended thinking / reasoning models)
+    ModelReasoning(String),
     /// Tool execution started
     ToolStart { name: String, description: String },
     /// Tool produced output
@@ -148,17 +201,130 @@ pub enum SessionEvent {
     DoomLoopPrompt {
         prompt_id: String,
         message: String,
+        /// Seconds before the prompt resolves on its own
+        timeout_secs: u64,
+        response_tx: tokio::sync::mpsc::UnboundedSender<bool>,
+    },
+    /// Tool call needs user approval before it runs
+    /// The sender is used to receive the user's decision (true = run, false = skip)
+    ToolApprovalPrompt {
+        prompt_id: String,
+        tool: String,
+        description: String,
+        high_risk: bool,
+        /// Where the tool comes from when it isn't built in (e.g. an MCP server)
+        origin: Option<String>,
+        /// Configured to always ask, even when the UI would auto-approve the tool
+        always_ask: bool,
         response_tx: tokio::sync::mpsc::UnboundedSender<bool>,
     },
     /// Todo list was updated
     TodoList {
         todos: Vec<crate::tools::todo::TodoItem>,
     },
+    /// A skill was activated by a file the session read or edited
+    SkillActivated { name: String, file: String },
+    /// The provider rate limited a request; it is retried after a wait
+    RateLimited {
+        provider: String,
+        retry_in_secs: u64,
+        attempt: u32,
+        max_retries: u32,
+    },
+}
+
+/// Forward rate-limit retries inside an LLM request to the UI
+fn rate_limit_notifier(event_tx: &mpsc::UnboundedSender<SessionEvent>) -> RetryNotifier {
+    let event_tx = event_tx.clone();
+    Arc::new(move |notice: &RetryNotice| {
+        let _ = event_tx.send(SessionEvent::RateLimited {
+            provider: notice.provider.clone(),
+            retry_in_secs: notice.delay.as_secs_f64().ceil() as u64,
+            attempt: notice.attempt,
+            max_retries: notice.max_retries,
+        });
+    })
+}
+
+/// Per-message settings used by custom commands
+#[derive(Debug, Clone, Default)]
+pub struct MessageOverrides {
+    /// Restrict the message to these tools (exact names or globs)
+    pub allowed_tools: Option<Vec<String>>,
+    /// Use this model for the message
+    pub model: Option<String>,
+    /// `!` shell snippets whose output fills the `{{shell:N}}` markers
+    pub shell_snippets: Vec<ShellSnippet>,
+}
+
+impl MessageOverrides {
+    /// Whether any override is set
+    pub fn is_empty(&self) -> bool {
+        self.allowed_tools.is_none() && self.model.is_none() && self.shell_snippets.is_empty()
+    }
+}
+
+/// Outcome of the permission gate for a single tool call
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ToolPermission {
+    /// Run without asking
+    Allowed,
+    /// Ask the user first
+    NeedsApproval,
+    /// Never run; carries the reason shown to the model
+    Denied(String),
+}
+
+/// LLM clients for PLAN and BUILD mode; one shared client unless
+/// `llm.plan_model` or `llm.build_model` picks a different model
+struct ModeClients {
+    plan: Arc<dyn LlmClient>,
+    build: Arc<dyn LlmClient>,
+}
+
+impl ModeClients {
+    async fn create(config: &Config) -> Result<Self> {
+        let plan_model = config.llm.model_for_mode(AgentMode::Plan);
+        let build_model = config.llm.model_for_mode(AgentMode::Build);
+        let build = client_for_model(config, build_model).await?;
+        let plan = if plan_model == build_model {
+            Arc::clone(&build)
+        } else {
+            client_for_model(config, plan_model).await?
+        };
+        Ok(Self { plan, build })
+    }
+
+    fn get(&self, mode: AgentMode) -> Arc<dyn LlmClient> {
+        match mode {
+            AgentMode::Plan => Arc::clone(&self.plan),
+            AgentMode::Build => Arc::clone(&self.build),
+        }
+    }
+}
+
+/// Show the model's thinking blocks, if it returned any
+fn emit_model_reasoning(message: &Message, event_tx: &mpsc::UnboundedSender<SessionEvent>) {
+    for block in &message.content {
+        if let ContentBlock::Thinking { thinking, .. } = block {
+            if !thinking.trim().is_empty() {
+                let _ = event_tx.send(SessionEvent::ModelReasoning(thinking.clone()));
+            }
+        }
+    }
+}
+
+async fn client_for_model(config: &Config, model: &str) -> Result<Arc<dyn LlmClient>> {
+    let mut config = config.clone();
+    config.llm.model = model.to_string();
+    Ok(Arc::from(create_client(&config).await?))
 }
 
 pub struct Session {
     config: Config,
+    /// Client for the current agent mode
     llm_client: Arc<dyn LlmClient>,
+    mode_clients: ModeClients,
     tool_registry: Arc<ToolRegistry>,
     messages: Vec<Message>,
     project_path: PathBuf,
@@ -168,6 +334,25 @@ pub struct Session {
     loop_detector: LoopDetector,
     context_manager: ContextManager,
     permission_manager: PermissionManager,
+    hooks: HookManager,
+    /// Tools allowed for the current message (set by custom commands)
+    tool_allowlist: Option<Vec<String>>,
+    /// Knowledge modules injected into the system prompt
+    skills: SkillManager,
+    /// Project prompt files from `.safe-coder/prompts/`
+    prompt_overrides: PromptOverrides,
+    /// Background monitor subagents and their pending notifications
+    monitors: Arc<MonitorHub>,
+    /// Contents of files read this session, shared by read_file and grep
+    file_cache: FileCache,
+    /// Files the model has seen, watched for edits made outside the session
+    file_watcher: FileWatcher,
+    /// Browser screenshots to show the model with the next tool results
+    pending_screenshots: Vec<ContentBlock>,
+    /// Coverage baseline and files edited since, for `build.coverage_check`
+    coverage: CoverageCheck,
+    /// Files whose failed edit already got an enriched retry
+    repair_attempts: std::collections::HashSet<String>,
 
     // Features
     persistence: SessionPersistence,
@@ -181,6 +366,10 @@ pub struct Session {
     custom_commands: CustomCommandManager,
     session_start: chrono::DateTime<Utc>,
     current_session_id: Option<String>,
+    /// Key of this session's write-ahead journal (for `chat --recover`)
+    journal_id: String,
+    /// Key of this session's usage record (for `safe-coder stats`)
+    stats_id: String,
     last_output: String,
 
     // Event channel for subagent streaming
@@ -208,7 +397,8 @@ impl Session {
         project_path: PathBuf,
         event_tx: Option<mpsc::UnboundedSender<SessionEvent>>,
     ) -> Result<Self> {
-        let llm_client: Arc<dyn LlmClient> = Arc::from(create_client(&config).await?);
+        let mode_clients = ModeClients::create(&config).await?;
+        let llm_client = mode_clients.get(AgentMode::default());
 
         // Initialize tool registry with subagent support
         let mut tool_registry = if let Some(tx) = event_tx.clone() {
@@ -224,14 +414,29 @@ impl Session {
         // Initialize MCP manager and register its tools
         let mut mcp_manager = McpManager::new(config.mcp.clone());
         mcp_manager.initialize(&project_path).await?;
+        mcp_manager.start_health_checks();
 
         // Register MCP tools with the tool registry before wrapping in Arc
         for tool in mcp_manager.get_tools() {
             tool_registry.register(tool);
         }
+        if config.tools.lazy_loading {
+            tool_registry.enable_lazy_loading();
+        }
 
+        let monitors = tool_registry.monitor_hub().unwrap_or_default();
         let tool_registry = Arc::new(tool_registry);
 
+        // Configured preset first, then MCP trust levels as permission rules
+        let mut permission_manager = PermissionManager::new();
+        if let Some(ref preset) = config.permissions.preset {
+            permission_manager.apply_preset(preset);
+        }
+        for category in &config.permissions.deny_categories {
+            permission_manager.deny_command_category(*category);
+        }
+        mcp_manager.apply_trust(&mut permission_manager);
+
         if mcp_manager.is_active() {
             tracing::info!(
                 "MCP active: {} server(s), {} tool(s)",
@@ -247,6 +452,15 @@ impl Session {
         let persistence = SessionPersistence::new().await?;
         let memory = MemoryManager::new(project_path.clone());
         let custom_commands = CustomCommandManager::new(project_path.clone()).await?;
+        let mut skills = SkillManager::with_project_paths(&project_path);
+        skills.register_builtins();
+        if let Err(e) = skills.load_all().await {
+            tracing::warn!("Failed to load skills: {}", e);
+        }
+        let prompt_overrides = PromptOverrides::load(&project_path);
+        for error in &prompt_overrides.errors {
+            tracing::warn!("Skipping prompt file: {}", error);
+        }
         let checkpoints = CheckpointManager::new(project_path.clone());
         let dir_checkpoints =
             DirectoryCheckpointManager::new(project_path.clone(), config.checkpoint.clone())?;
@@ -262,19 +476,38 @@ impl Session {
         }
 
         // Create context manager with config settings before moving config into struct
-        let context_manager = ContextManager::with_config(config.context.to_context_config());
+        let mut context_manager = ContextManager::with_config(config.context.to_context_config());
+        context_manager.set_max_tokens(
+            config
+                .context
+                .max_tokens_for(llm_client.capabilities().max_context),
+        );
+        context_manager.set_output_reserve(config.llm.max_tokens);
+        let hooks = HookManager::from_config(&config.hooks, &project_path);
+        let loop_detector = LoopDetector::with_config(config.loop_detection.to_detector_config());
 
-        Ok(Self {
+        let session = Self {
             config,
             llm_client,
+            mode_clients,
             tool_registry,
             messages: vec![],
             project_path: project_path.clone(),
 
             git_manager,
-            loop_detector: LoopDetector::new(),
+            loop_detector,
             context_manager,
-            permission_manager: PermissionManager::new(),
+            permission_manager,
+            hooks,
+            tool_allowlist: None,
+            skills,
+            prompt_overrides,
+            monitors,
+            file_cache: FileCache::new(),
+            file_watcher: FileWatcher::new(),
+            pending_screenshots: Vec::new(),
+            coverage: CoverageCheck::new(),
+            repair_attempts: std::collections::HashSet::new(),
 
             persistence,
             approval_mode: ApprovalMode::default(),
@@ -287,13 +520,18 @@ impl Session {
             custom_commands,
             session_start: Utc::now(),
             current_session_id: None,
+            journal_id: uuid::Uuid::new_v4().to_string(),
+            stats_id: uuid::Uuid::new_v4().to_string(),
             last_output: String::new(),
             subagent_event_tx: event_tx,
             mcp_manager,
             lsp_manager,
             current_plan: None,
             plan_history: Vec::new(),
-        })
+        };
+
+        session.run_session_hooks(HookType::SessionStart).await;
+        Ok(session)
     }
 
     /// Set user mode (Plan or Build)
@@ -311,6 +549,7 @@ impl Session {
     pub fn set_agent_mode(&mut self, mode: AgentMode) {
         self.agent_mode = mode;
         tracing::info!("Agent mode set to: {}", mode);
+        self.use_mode_client();
     }
 
     /// Get current agent mode
@@ -322,6 +561,25 @@ impl Session {
     pub fn cycle_agent_mode(&mut self) {
         self.agent_mode = self.agent_mode.next();
         tracing::info!("Agent mode cycled to: {}", self.agent_mode);
+        self.use_mode_client();
+    }
+
+    /// Switch to the client of the current agent mode
+    fn use_mode_client(&mut self) {
+        self.llm_client = self.mode_clients.get(self.agent_mode);
+        self.fit_context_to_model();
+        tracing::info!(
+            "Using model {} for {} mode",
+            self.config.llm.model_for_mode(self.agent_mode),
+            self.agent_mode
+        );
+    }
+
+    /// Size the context to the model's window, or the configured limit when smaller
+    fn fit_context_to_model(&mut self) {
+        let max_context = self.capabilities().max_context;
+        self.context_manager
+            .set_max_tokens(self.config.context.max_tokens_for(max_context));
     }
 
     /// Reset the loop detector (used when user chooses to continue after doom loop detection)
@@ -341,11 +599,103 @@ impl Session {
         self.permission_manager.summary()
     }
 
+    /// Get the skill manager
+    pub fn skills(&self) -> &SkillManager {
+        &self.skills
+    }
+
+    /// Get mutable reference to the skill manager (for /skill on|off)
+    pub fn skills_mut(&mut self) -> &mut SkillManager {
+        &mut self.skills
+    }
+
+    /// Plans saved by PLAN mode in this project
+    pub fn plan_store(&self) -> PlanStore {
+        PlanStore::new(&self.project_path)
+    }
+
+    /// CI status of the checked-out branch, with condensed failure logs
+    pub async fn ci_status(&self) -> Result<crate::tools::ci_status::CiReport> {
+        crate::tools::ci_status::ci_report(&self.project_path, &self.config.tools, None, true).await
+    }
+
+    /// Known vulnerabilities in the project's dependencies
+    pub async fn audit_dependencies(&self) -> Result<crate::tools::audit::AuditReport> {
+        crate::tools::audit::audit(&self.project_path, 300).await
+    }
+
+    /// Connected MCP servers, for listing their resources and prompts
+    pub fn mcp(&self) -> &McpManager {
+        &self.mcp_manager
+    }
+
+    /// Attach the contents of any `@mcp:server/resource` mentions to a user message
+    async fn with_mcp_resources(&self, user_message: &str) -> String {
+        match self.mcp_manager.attach_resource_mentions(user_message).await {
+            Some(attachments) => format!("{}\n\n{}", user_message, attachments),
+            None => user_message.to_string(),
+        }
+    }
+
+    /// Add a notice about files changed on disk since the model saw them
+    fn with_file_changes(&self, user_message: &str) -> String {
+        match self.file_watcher.take_changes(&self.project_path) {
+            Some(changes) => format!("{}\n\n{}", user_message, changes),
+            None => user_message.to_string(),
+        }
+    }
+
+    /// Background monitors, for subscribing to their notifications
+    pub fn monitors(&self) -> Arc<MonitorHub> {
+        Arc::clone(&self.monitors)
+    }
+
+    /// Notifications from background monitors since the last check,
+    /// formatted for the model
+    fn take_monitor_updates(&self) -> Option<String> {
+        let updates = self.monitors.take_pending();
+        if updates.is_empty() {
+            return None;
+        }
+        let lines: Vec<String> = updates.iter().map(|n| n.to_prompt_line()).collect();
+        Some(format!("[BACKGROUND MONITORS]\n{}", lines.join("\n")))
+    }
+
+    /// Add pending monitor notifications to a user message
+    fn with_monitor_updates(&self, user_message: &str) -> String {
+        match self.take_monitor_updates() {
+            Some(updates) => format!("{}\n\n{}", user_message, updates),
+            None => user_message.to_string(),
+        }
+    }
+
     /// Get LLM client for unified planning
     pub fn get_llm_client(&self) -> Arc<dyn LlmClient> {
         self.llm_client.clone()
     }
 
+    /// What the current agent mode's model supports
+    pub fn capabilities(&self) -> Capabilities {
+        self.llm_client.capabilities()
+    }
+
+    /// Tool definitions sent for `mode`; none when the model can't call tools
+    fn tool_definitions(&self, mode: AgentMode) -> Vec<ToolDefinition> {
+        if !self.capabilities().tools {
+            return Vec::new();
+        }
+        let loaded = crate::tools::search_tools::loaded_tools(&self.messages);
+        self.tool_registry
+            .get_tools_schema_for_turn(mode, Some(&loaded))
+            .into_iter()
+            .map(|schema| ToolDefinition {
+                name: schema["name"].as_str().unwrap().to_string(),
+                description: schema["description"].as_str().unwrap().to_string(),
+                input_schema: schema["input_schema"].clone(),
+            })
+            .collect()
+    }
+
     /// Get tool registry for unified planning
     pub fn get_tool_registry(&self) -> Arc<ToolRegistry> {
         self.tool_registry.clone()
@@ -382,6 +732,33 @@ impl Session {
         self.subagent_event_tx = None;
     }
 
+    /// Trim or compact the conversation so the next request fits the
+    /// model's context window, rather than letting the provider reject it
+    fn preflight_context(
+        &mut self,
+        tools: &[ToolDefinition],
+        system_prompt: &str,
+        event_tx: Option<&mpsc::UnboundedSender<SessionEvent>>,
+    ) {
+        let Some(result) = self
+            .context_manager
+            .fit_request(system_prompt, tools, &mut self.messages)
+        else {
+            return;
+        };
+        if result.fits() {
+            tracing::info!("Context pre-flight: {}", result.summary());
+        } else {
+            tracing::warn!("Context pre-flight: {}", result.summary());
+        }
+        if let Some(tx) = event_tx {
+            let _ = tx.send(SessionEvent::TextChunk(format!("\n📦 {}\n", result.summary())));
+            let _ = tx.send(SessionEvent::ContextCompressed {
+                tokens_compressed: result.tokens_before.saturating_sub(result.tokens_after),
+            });
+        }
+    }
+
     /// Send LLM message with automatic retry on token limit errors
     /// Will auto-compact context and retry if token limit is exceeded
     async fn send_llm_with_auto_compact(
@@ -393,16 +770,22 @@ impl Session {
         const MAX_RETRIES: usize = 3;
 
         for attempt in 0..MAX_RETRIES {
-            match self.llm_client
-                .send_message_with_system(&self.messages, tools, Some(system_prompt))
-                .await
-            {
+            self.preflight_context(tools, system_prompt, event_tx);
+            let request = self
+                .llm_client
+                .send_message_with_system(&self.messages, tools, Some(system_prompt));
+            let result = match event_tx {
+                Some(tx) => with_retry_notifier(rate_limit_notifier(tx), request).await,
+                None => request.await,
+            };
+            match result {
                 Ok(response) => return Ok(response),
                 Err(e) => {
                     let error_str = e.to_string().to_lowercase();
 
-                    // Check if it's a token limit error
-                    let is_token_limit = error_str.contains("token")
+                    // Check if it's a token limit error (a 429 is not)
+                    let is_token_limit = e.downcast_ref::<RateLimitError>().is_none()
+                        && error_str.contains("token")
                         && (error_str.contains("exceed")
                             || error_str.contains("limit")
                             || error_str.contains("max_prompt_tokens"));
@@ -912,8 +1295,17 @@ impl Session {
     }
 
     pub async fn send_message(&mut self, user_message: String) -> Result<String> {
-        // Clear todo list at the start of each new request
-        clear_todo_list();
+        // Let pre_message hooks block or rewrite the message
+        let user_message = self.run_pre_message_hooks(user_message).await?;
+
+        // `#remember <fact>` goes to project memory, not the model
+        if let Some(reply) = self.remember_from_message(&user_message) {
+            return reply;
+        }
+        self.learn_from_correction(&user_message);
+
+        // Reload the project's todo list; open items carry over between requests
+        load_todo_list(&self.project_path);
 
         // Create checkpoint before processing user task (git-agnostic safety)
         if self.dir_checkpoints.is_enabled() {
@@ -951,6 +1343,7 @@ impl Session {
             }
 
             if let Ok(resp) = planning_result {
+                self.run_post_message_hooks(&user_message, &resp).await;
                 return Ok(resp);
             }
 
@@ -962,8 +1355,12 @@ impl Session {
         // Track stats
         self.stats.total_messages += 1;
 
-        // Add user message to history
-        self.messages.push(Message::user(user_message.clone()));
+        // Add user message to history, with mentioned MCP resources, any
+        // background monitor updates and files changed on disk
+        let message_text = self.with_mcp_resources(&user_message).await;
+        let message_text = self.with_monitor_updates(&message_text);
+        self.messages
+            .push(Message::user(self.with_file_changes(&message_text)));
 
         // Check if context compaction is needed
         if self.context_manager.needs_compaction(&self.messages) {
@@ -979,23 +1376,13 @@ impl Session {
         let mut response_text = String::new();
 
         // Build hierarchical system prompt
-        let project_context = self.memory.get_system_prompt().await.ok();
-        let system_prompt =
-            prompts::build_system_prompt(self.agent_mode, project_context.as_deref(), None);
+        let mut system_prompt = self.system_prompt().await;
 
         loop {
-            // Get tools schema
             // Get tools filtered by current agent mode
-            let tools: Vec<ToolDefinition> = self
-                .tool_registry
-                .get_tools_schema_for_mode(self.agent_mode)
-                .into_iter()
-                .map(|schema| ToolDefinition {
-                    name: schema["name"].as_str().unwrap().to_string(),
-                    description: schema["description"].as_str().unwrap().to_string(),
-                    input_schema: schema["input_schema"].clone(),
-                })
-                .collect();
+            let tools = self.tool_definitions(self.agent_mode);
+
+            self.preflight_context(&tools, &system_prompt, None);
 
             // Send to LLM with hierarchical system prompt, with auto-retry on token limit errors
             tracing::info!("[LLM DEBUG] Sending to LLM, msgs: {}", self.messages.len());
@@ -1011,7 +1398,8 @@ impl Session {
                 }
                 Err(e) => {
                     let error_str = e.to_string().to_lowercase();
-                    let is_token_limit = error_str.contains("token")
+                    let is_token_limit = e.downcast_ref::<RateLimitError>().is_none()
+                        && error_str.contains("token")
                         && (error_str.contains("exceed")
                             || error_str.contains("limit")
                             || error_str.contains("max_prompt_tokens"));
@@ -1048,6 +1436,7 @@ impl Session {
             // Track stats from actual token usage if available
             if let Some(usage) = &llm_response.usage {
                 self.stats.total_tokens_sent += usage.input_tokens;
+                self.stats.total_tokens_received += usage.output_tokens;
                 // Record actual tokens for better compaction decisions
                 self.context_manager.record_actual_tokens(usage.input_tokens);
                 // Check if we need to compact based on actual token usage
@@ -1086,6 +1475,7 @@ impl Session {
 
             // Add assistant message to history
             self.messages.push(assistant_message.clone());
+            self.checkpoint_turn().await;
 
             if !has_tool_calls {
                 // No tool calls, we're done
@@ -1149,6 +1539,9 @@ impl Session {
                 }
             }
 
+            // Tool calls in an approved plan don't need individual approval
+            let plan_approved = self.user_mode == UserMode::Plan;
+
             // Execute tool calls
             let mut tool_results = Vec::new();
             let mut tools_executed = Vec::new();
@@ -1171,6 +1564,7 @@ impl Session {
                         self.stats.tools_used.push(ToolUsage {
                             tool_name: name.clone(),
                             count: 1,
+                            failures: 0,
                         });
                     }
 
@@ -1193,6 +1587,7 @@ impl Session {
                     }
 
                     // Check for doom loop (repeated identical tool calls)
+                    let mut loop_guidance = None;
                     match self.loop_detector.check(name, input) {
                         DoomLoopAction::Block { message } => {
                             tracing::warn!("Doom loop blocked: {}", message);
@@ -1206,15 +1601,66 @@ impl Session {
                             tracing::warn!("{}", message);
                         }
                         DoomLoopAction::AskUser { message } => {
-                            // In non-streaming mode, just warn and continue
-                            // (blocking behavior is in send_message_with_progress)
                             tracing::warn!("Doom loop detected: {}", message);
+                            if !self.ask_doom_loop_decision(&message)? {
+                                tool_results.push(ContentBlock::ToolResult {
+                                    tool_use_id: id.clone(),
+                                    content: LOOP_STOPPED_RESULT.to_string(),
+                                });
+                                continue;
+                            }
+                            self.loop_detector.reset();
+                        }
+                        DoomLoopAction::InjectGuidance { message } => {
+                            tracing::warn!("Doom loop detected, guiding model: {}", message);
+                            loop_guidance = Some(message);
                         }
                         DoomLoopAction::Continue => {}
                     }
 
+                    // Let pre_tool hooks block the call or rewrite its parameters
+                    let (input, hook_notes) = match self.hooks.run_pre_tool_use(name, input).await {
+                        PreToolDecision::Proceed { input, warnings } => (input, warnings),
+                        PreToolDecision::Block(reason) => {
+                            let result = hook_blocked_result(name, &reason);
+                            response_text.push_str(&format!("🚫 {}\n", result));
+                            tool_results.push(ContentBlock::ToolResult {
+                                tool_use_id: id.clone(),
+                                content: result,
+                            });
+                            continue;
+                        }
+                    };
+                    let input = &input;
+
+                    // Check permission rules; an approved plan already covers its tool calls
+                    let blocked = match self.check_tool_permission(name, input) {
+                        ToolPermission::Allowed => None,
+                        ToolPermission::Denied(reason) => {
+                            tracing::warn!("Tool call denied: {} ({})", name, reason);
+                            Some(permission_denied_result(name, &reason))
+                        }
+                        ToolPermission::NeedsApproval if plan_approved => None,
+                        ToolPermission::NeedsApproval => {
+                            if self.ask_tool_approval(name, input).await? {
+                                None
+                            } else {
+                                Some(approval_declined_result(name))
+                            }
+                        }
+                    };
+                    if let Some(result) = blocked {
+                        response_text.push_str(&format!("🚫 {}\n", result));
+                        tool_results.push(ContentBlock::ToolResult {
+                            tool_use_id: id.clone(),
+                            content: result,
+                        });
+                        continue;
+                    }
+
                     // Create tool context with working directory and config
-                    let tool_ctx = ToolContext::new(&self.project_path, &self.config.tools);
+                    let tool_ctx = ToolContext::new(&self.project_path, &self.config.tools)
+                        .with_file_cache(self.file_cache.clone());
 
                     let (result, success) = match self.tool_registry.get_tool(name) {
                         Some(tool) => match tool.execute(input.clone(), &tool_ctx).await {
@@ -1224,16 +1670,34 @@ impl Session {
                                 let cmd_success = !output.contains("[Exit status:");
                                 (output, cmd_success)
                             }
-                            Err(e) => (format!("Error: {}", e), false),
+                            Err(e) => (render_tool_error(&e), false),
                         },
                         None => (format!("Error: Unknown tool '{}'", name), false),
                     };
+                    let result = self.prepare_retry(name, input, result, success);
+                    let result = self
+                        .run_post_tool_hooks(name, input, result, success, hook_notes)
+                        .await;
+                    let result = with_loop_guidance(result, loop_guidance);
+
+                    self.track_file_for_tool(name, input, success);
+                    self.capture_screenshot_for_tool(name, input, success);
+
+                    // Pull in skills triggered by the file this tool touched
+                    let activated = self.activate_skills_for_tool(name, input, success, None);
+                    if !activated.is_empty() {
+                        for skill in &activated {
+                            response_text.push_str(&format!("📚 Skill activated: {}\n", skill));
+                        }
+                        system_prompt = self.system_prompt().await;
+                    }
 
                     // Record tool call for doom loop detection
                     self.loop_detector.record(name, input);
                     if success {
                         self.loop_detector.record_success();
                     } else {
+                        self.stats.record_tool_failure(name);
                         self.loop_detector.record_failure(&result);
                     }
 
@@ -1289,19 +1753,128 @@ impl Session {
                     }
                 }
 
+                // Let background monitors interrupt with failures mid-task
+                if let Some(updates) = self.take_monitor_updates() {
+                    final_results.push(ContentBlock::Text {
+                        text: format!("\n\n{}", updates),
+                    });
+                }
+                final_results.append(&mut self.pending_screenshots);
+
                 self.messages.push(Message {
                     role: crate::llm::Role::User,
                     content: final_results,
                 });
+                self.checkpoint_turn().await;
             }
         }
 
         let final_response = response_text.trim().to_string();
         self.last_output = final_response.clone();
+        self.run_post_message_hooks(&user_message, &final_response).await;
 
         Ok(final_response)
     }
 
+    /// Send a message with custom command overrides (tool allowlist, model,
+    /// shell snippets), restoring the session's settings afterwards
+    pub async fn send_message_with_overrides(
+        &mut self,
+        user_message: String,
+        images: Vec<(String, String)>,
+        overrides: MessageOverrides,
+        event_tx: mpsc::UnboundedSender<SessionEvent>,
+    ) -> Result<String> {
+        let previous_llm = match overrides.model {
+            Some(ref m) if m != self.config.llm.model_for_mode(self.agent_mode) => {
+                let previous = self.config.llm.clone();
+                self.switch_model(m).await?;
+                Some(previous)
+            }
+            _ => None,
+        };
+        self.tool_allowlist = overrides.allowed_tools.filter(|tools| !tools.is_empty());
+
+        let result = match self
+            .run_shell_snippets(&overrides.shell_snippets, &event_tx)
+            .await
+        {
+            Ok(outputs) => {
+                let message = fill_shell_snippets(&user_message, &overrides.shell_snippets, &outputs);
+                self.send_message_with_images_and_progress(message, images, event_tx)
+                    .await
+            }
+            Err(e) => Err(e),
+        };
+
+        self.tool_allowlist = None;
+        if let Some(previous) = previous_llm {
+            self.config.llm = previous;
+            if let Err(e) = self.reload_clients().await {
+                tracing::warn!("Failed to restore model {}: {}", self.config.llm.model, e);
+            }
+        }
+        result
+    }
+
+    /// Run a custom command's `!` snippets through the bash tool, with the
+    /// same hooks and permission gate as model tool calls
+    async fn run_shell_snippets(
+        &self,
+        snippets: &[ShellSnippet],
+        event_tx: &mpsc::UnboundedSender<SessionEvent>,
+    ) -> Result<Vec<String>> {
+        let mut outputs = Vec::with_capacity(snippets.len());
+        for snippet in snippets {
+            let input = serde_json::json!({ "command": snippet.command });
+            let input = match self.hooks.run_pre_tool_use("bash", &input).await {
+                PreToolDecision::Proceed { input, warnings } => {
+                    for warning in warnings {
+                        tracing::warn!("Hook: {}", warning);
+                    }
+                    input
+                }
+                PreToolDecision::Block(reason) => {
+                    self.report_blocked_tool("bash", &input, &hook_blocked_result("bash", &reason), event_tx);
+                    anyhow::bail!("Shell snippet `{}` was blocked by a hook: {}", snippet.command, reason);
+                }
+            };
+
+            if let Some(blocked) = self.gate_tool_call("bash", &input, event_tx).await {
+                tracing::warn!("Shell snippet not run: {}", blocked);
+                anyhow::bail!(
+                    "Shell snippet `{}` was not allowed to run; check the command's allowed tools and your permission rules",
+                    snippet.command
+                );
+            }
+
+            let _ = event_tx.send(SessionEvent::ToolStart {
+                name: "bash".to_string(),
+                description: format!("Running {}", snippet.command),
+            });
+            let bash_tool = self
+                .tool_registry
+                .get_tool("bash")
+                .context("Bash tool not found")?;
+            let tool_ctx = ToolContext::new(&self.project_path, &self.config.tools);
+            let (output, success) = match bash_tool.execute(input, &tool_ctx).await {
+                Ok(output) => (output, true),
+                Err(e) => (render_tool_error(&e), false),
+            };
+            let output = truncate_tool_result(output);
+            let _ = event_tx.send(SessionEvent::ToolOutput {
+                name: "bash".to_string(),
+                output: output.clone(),
+            });
+            let _ = event_tx.send(SessionEvent::ToolComplete {
+                name: "bash".to_string(),
+                success,
+            });
+            outputs.push(output);
+        }
+        Ok(outputs)
+    }
+
     /// Send a message with real-time progress updates via channel
     /// This allows the UI to show tool executions as they happen
     pub async fn send_message_with_progress(
@@ -1322,8 +1895,22 @@ impl Session {
         images: Vec<(String, String)>,
         event_tx: mpsc::UnboundedSender<SessionEvent>,
     ) -> Result<String> {
-        // Clear todo list at the start of each new request
-        clear_todo_list();
+        // Let pre_message hooks block or rewrite the message
+        let user_message = self.run_pre_message_hooks(user_message).await?;
+
+        // `#remember <fact>` goes to project memory, not the model
+        if let Some(reply) = self.remember_from_message(&user_message) {
+            let reply = reply?;
+            let _ = event_tx.send(SessionEvent::TextChunk(reply.clone()));
+            return Ok(reply);
+        }
+        self.learn_from_correction(&user_message);
+
+        // Reload the project's todo list; open items carry over between requests
+        let todos = load_todo_list(&self.project_path);
+        if !todos.is_empty() {
+            let _ = event_tx.send(SessionEvent::TodoList { todos });
+        }
 
         // Create checkpoint before processing user task (git-agnostic safety)
         if self.dir_checkpoints.is_enabled() {
@@ -1337,16 +1924,37 @@ impl Session {
             }
         }
 
+        // Measure coverage before the session's first change
+        if self.coverage_check_enabled() && self.coverage.needs_baseline() {
+            let _ = event_tx.send(SessionEvent::TextChunk(
+                "📊 Measuring baseline test coverage...\n".to_string(),
+            ));
+            self.coverage
+                .measure_baseline(&self.project_path, self.config.build.coverage_timeout_secs)
+                .await;
+        }
+
         // Track stats
         self.stats.total_messages += 1;
 
-        // Add user message to history (with images if present)
-        if images.is_empty() {
-            self.messages.push(Message::user(user_message.clone()));
+        // Add user message to history (with images, mentioned MCP resources,
+        // monitor updates and files changed on disk if present)
+        let message_text = self.with_mcp_resources(&user_message).await;
+        let message_text = self.with_monitor_updates(&message_text);
+        let message_text = self.with_file_changes(&message_text);
+        if !images.is_empty() && !self.capabilities().vision {
+            let _ = event_tx.send(SessionEvent::TextChunk(format!(
+                "⚠️ {} doesn't accept images; sending text only ({} image(s) dropped)\n",
+                self.get_current_model(),
+                images.len()
+            )));
+        }
+        if images.is_empty() || !self.capabilities().vision {
+            self.messages.push(Message::user(message_text));
         } else {
             tracing::info!("Sending message with {} image(s)", images.len());
             self.messages
-                .push(Message::user_with_images(user_message.clone(), images));
+                .push(Message::user_with_images(message_text, images));
         }
 
         // Check if context compaction is needed
@@ -1372,9 +1980,7 @@ impl Session {
         let mut response_text = String::new();
 
         // Build hierarchical system prompt
-        let project_context = self.memory.get_system_prompt().await.ok();
-        let system_prompt =
-            prompts::build_system_prompt(self.agent_mode, project_context.as_deref(), None);
+        let mut system_prompt = self.system_prompt().await;
 
         // Create a persistent plan ID for this task
         let task_plan_id = format!("plan-{}", uuid::Uuid::new_v4().to_string()[..8].to_string());
@@ -1390,38 +1996,34 @@ impl Session {
                 Then create a concise plan showing what steps you would take to accomplish this task.\n\
                 Format your plan as:\n\
                 ## Plan: [Title]\n\
-                1. [Step description]\n\
-                2. [Step description]\n\
-                ...\n\n\
+                1. [Step description, naming the files it touches in backticks]\n\
+                2. [Step description, naming the files it touches in backticks]\n\
+                ...\n\
+                ## Risks\n\
+                - [Anything that could go wrong or needs care]\n\n\
                 Do NOT execute any changes yet - just explore and plan.",
                 user_message
             );
 
             self.messages.push(Message::user(plan_prompt));
 
-            // Let LLM explore and create plan
-            let tools: Vec<ToolDefinition> = self
-                .tool_registry
-                .get_tools_schema_for_mode(AgentMode::Plan) // Read-only tools only
-                .into_iter()
-                .map(|schema| ToolDefinition {
-                    name: schema["name"].as_str().unwrap().to_string(),
-                    description: schema["description"].as_str().unwrap().to_string(),
-                    input_schema: schema["input_schema"].clone(),
-                })
-                .collect();
-
             // Run exploration loop until LLM produces a plan
             loop {
-                let llm_response = match self
-                    .llm_client
-                    .send_message_with_system(&self.messages, &tools, Some(&system_prompt))
-                    .await
+                // Read-only tools only, and any loaded by the last turn
+                let tools = self.tool_definitions(AgentMode::Plan);
+                self.preflight_context(&tools, &system_prompt, Some(&event_tx));
+                let llm_response = match with_retry_notifier(
+                    rate_limit_notifier(&event_tx),
+                    self.llm_client
+                        .send_message_with_system(&self.messages, &tools, Some(&system_prompt)),
+                )
+                .await
                 {
                     Ok(resp) => resp,
                     Err(e) => {
                         let error_str = e.to_string().to_lowercase();
-                        let is_token_limit = error_str.contains("token")
+                        let is_token_limit = e.downcast_ref::<RateLimitError>().is_none()
+                            && error_str.contains("token")
                             && (error_str.contains("exceed")
                                 || error_str.contains("limit")
                                 || error_str.contains("max_prompt_tokens"));
@@ -1462,6 +2064,8 @@ impl Session {
                     .iter()
                     .any(|c| matches!(c, ContentBlock::ToolUse { .. }));
 
+                emit_model_reasoning(&assistant_message, &event_tx);
+
                 // Collect reasoning sentences to send interleaved with tool calls
                 let mut reasoning_sentences: Vec<String> = Vec::new();
                 for block in &assistant_message.content {
@@ -1501,6 +2105,7 @@ impl Session {
                 }
 
                 self.messages.push(assistant_message.clone());
+                self.checkpoint_turn().await;
 
                 if !has_tool_calls {
                     // No more tool calls - plan should be complete
@@ -1522,13 +2127,36 @@ impl Session {
                         }
                         step_index += 1;
 
+                        let (input, hook_notes) = match self.hooks.run_pre_tool_use(name, input).await {
+                            PreToolDecision::Proceed { input, warnings } => (input, warnings),
+                            PreToolDecision::Block(reason) => {
+                                let result = hook_blocked_result(name, &reason);
+                                self.report_blocked_tool(name, input, &result, &event_tx);
+                                tool_results.push(ContentBlock::ToolResult {
+                                    tool_use_id: id.clone(),
+                                    content: result,
+                                });
+                                continue;
+                            }
+                        };
+                        let input = &input;
+
+                        if let Some(result) = self.gate_tool_call(name, input, &event_tx).await {
+                            tool_results.push(ContentBlock::ToolResult {
+                                tool_use_id: id.clone(),
+                                content: result,
+                            });
+                            continue;
+                        }
+
                         // Send proper tool events for vertical rendering
                         let _ = event_tx.send(SessionEvent::ToolStart {
                             name: name.clone(),
                             description: description.clone(),
                         });
 
-                        let tool_context = ToolContext::new(&self.project_path, &self.config.tools);
+                        let tool_context = ToolContext::new(&self.project_path, &self.config.tools)
+                            .with_file_cache(self.file_cache.clone());
                         let (result, success) = if let Some(tool) = self.tool_registry.get_tool(name) {
                             match tool.execute(input.clone(), &tool_context).await {
                                 Ok(r) => {
@@ -1536,11 +2164,25 @@ impl Session {
                                     let cmd_success = !r.contains("[Exit status:");
                                     (r, cmd_success)
                                 }
-                                Err(e) => (format!("Error: {}", e), false),
+                                Err(e) => (render_tool_error(&e), false),
                             }
                         } else {
                             (format!("Unknown tool: {}", name), false)
                         };
+                        let result = self
+                            .run_post_tool_hooks(name, input, result, success, hook_notes)
+                            .await;
+
+                        self.track_file_for_tool(name, input, success);
+                        self.capture_screenshot_for_tool(name, input, success);
+
+                        // Pull in skills triggered by the file this tool touched
+                        if !self
+                            .activate_skills_for_tool(name, input, success, Some(&event_tx))
+                            .is_empty()
+                        {
+                            system_prompt = self.system_prompt().await;
+                        }
 
                         // Send tool completion event
                         let _ = event_tx.send(SessionEvent::ToolComplete {
@@ -1554,11 +2196,13 @@ impl Session {
                         });
                     }
                 }
+                tool_results.append(&mut self.pending_screenshots);
 
                 self.messages.push(Message {
                     role: crate::llm::Role::User,
                     content: tool_results,
                 });
+                self.checkpoint_turn().await;
             }
 
             // Plan created - parse the plan from response text
@@ -1568,8 +2212,22 @@ impl Session {
             let mut task_plan = TaskPlan::new(task_plan_id.clone(), user_message.clone());
             task_plan.title = plan_title;
             task_plan.steps = plan_steps;
+            task_plan.risks = parse_plan_risks(&response_text);
             task_plan.status = PlanStatus::AwaitingApproval;
 
+            // Keep the plan on disk so BUILD mode (now or in a later session) can follow it
+            match PlanStore::new(&self.project_path).save(&task_plan) {
+                Ok(path) => {
+                    let note = format!(
+                        "\n📋 Plan saved to {}\n",
+                        path.strip_prefix(&self.project_path).unwrap_or(&path).display()
+                    );
+                    response_text.push_str(&note);
+                    let _ = event_tx.send(SessionEvent::TextChunk(note));
+                }
+                Err(e) => tracing::warn!("Failed to save plan: {}", e),
+            }
+
             // Send PlanCreated event FIRST so UI has the plan data
             let _ = event_tx.send(SessionEvent::Plan(PlanEvent::PlanCreated {
                 plan: task_plan,
@@ -1589,6 +2247,7 @@ impl Session {
                 plan_id: task_plan_id.clone(),
             }));
 
+            self.run_post_message_hooks(&user_message, &response_text).await;
             return Ok(response_text);
         }
 
@@ -1602,26 +2261,20 @@ impl Session {
             // Notify UI that we're thinking
             let _ = event_tx.send(SessionEvent::Thinking("Processing...".to_string()));
 
-            // Get tools schema
             // Get tools filtered by current agent mode
-            let tools: Vec<ToolDefinition> = self
-                .tool_registry
-                .get_tools_schema_for_mode(self.agent_mode)
-                .into_iter()
-                .map(|schema| ToolDefinition {
-                    name: schema["name"].as_str().unwrap().to_string(),
-                    description: schema["description"].as_str().unwrap().to_string(),
-                    input_schema: schema["input_schema"].clone(),
-                })
-                .collect();
+            let tools = self.tool_definitions(self.agent_mode);
+
+            self.preflight_context(&tools, &system_prompt, Some(&event_tx));
 
             // Send to LLM with hierarchical system prompt, with auto-retry on token limit errors
             tracing::info!("[LLM DEBUG] Sending to LLM, msgs: {}", self.messages.len());
             let llm_start = std::time::Instant::now();
-            let llm_response = match self
-                .llm_client
-                .send_message_with_system(&self.messages, &tools, Some(&system_prompt))
-                .await
+            let llm_response = match with_retry_notifier(
+                rate_limit_notifier(&event_tx),
+                self.llm_client
+                    .send_message_with_system(&self.messages, &tools, Some(&system_prompt)),
+            )
+            .await
             {
                 Ok(resp) => {
                     tracing::info!("[LLM DEBUG] LLM responded in {:?}", llm_start.elapsed());
@@ -1629,7 +2282,8 @@ impl Session {
                 }
                 Err(e) => {
                     let error_str = e.to_string().to_lowercase();
-                    let is_token_limit = error_str.contains("token")
+                    let is_token_limit = e.downcast_ref::<RateLimitError>().is_none()
+                        && error_str.contains("token")
                         && (error_str.contains("exceed")
                             || error_str.contains("limit")
                             || error_str.contains("max_prompt_tokens"));
@@ -1667,6 +2321,7 @@ impl Session {
             // Track stats and emit token usage event
             if let Some(usage) = &llm_response.usage {
                 self.stats.total_tokens_sent += usage.input_tokens;
+                self.stats.total_tokens_received += usage.output_tokens;
                 // Record actual tokens for better compaction decisions
                 self.context_manager.record_actual_tokens(usage.input_tokens);
                 // Emit token usage event for sidebar (including cache stats)
@@ -1702,6 +2357,8 @@ impl Session {
                 .iter()
                 .any(|c| matches!(c, ContentBlock::ToolUse { .. }));
 
+            emit_model_reasoning(&assistant_message, &event_tx);
+
             // Extract text from response
             // Collect reasoning sentences to send interleaved with tool calls
             let mut reasoning_sentences: Vec<String> = Vec::new();
@@ -1746,6 +2403,7 @@ impl Session {
 
             // Add assistant message to history
             self.messages.push(assistant_message.clone());
+            self.checkpoint_turn().await;
 
             if !has_tool_calls {
                 break;
@@ -1827,6 +2485,7 @@ impl Session {
                         self.stats.tools_used.push(ToolUsage {
                             tool_name: name.clone(),
                             count: 1,
+                            failures: 0,
                         });
                     }
 
@@ -1859,6 +2518,7 @@ impl Session {
                     }
 
                     // Check for doom loop (repeated identical tool calls)
+                    let mut loop_guidance = None;
                     match self.loop_detector.check(name, input) {
                         DoomLoopAction::Block { message } => {
                             let _ = event_tx.send(SessionEvent::ToolStart {
@@ -1887,40 +2547,50 @@ impl Session {
                         }
                         DoomLoopAction::AskUser { message } => {
                             tracing::warn!("Doom loop detected, asking user: {}", message);
-
-                            // Create channel for user response
-                            let (response_tx, mut response_rx) = tokio::sync::mpsc::unbounded_channel::<bool>();
-                            let prompt_id = uuid::Uuid::new_v4().to_string();
-
-                            // Send prompt to UI
-                            let _ = event_tx.send(SessionEvent::DoomLoopPrompt {
-                                prompt_id: prompt_id.clone(),
-                                message: message.clone(),
-                                response_tx,
-                            });
-
-                            // Wait for user response (with timeout)
-                            let should_continue = tokio::time::timeout(
-                                std::time::Duration::from_secs(300), // 5 minute timeout
-                                response_rx.recv()
-                            ).await.ok().flatten().unwrap_or(false);
-
-                            if !should_continue {
-                                tracing::info!("User chose to stop due to doom loop (prompt_id={})", prompt_id);
+                            if !self.request_doom_loop_decision(&message, &event_tx).await {
                                 tool_results.push(ContentBlock::ToolResult {
                                     tool_use_id: id.clone(),
-                                    content: "Operation stopped by user due to detected loop pattern.".to_string(),
+                                    content: LOOP_STOPPED_RESULT.to_string(),
                                 });
                                 continue;
                             }
-
                             // User chose to continue - reset loop detector
                             self.loop_detector.reset();
-                            tracing::info!("User chose to continue past doom loop (prompt_id={})", prompt_id);
+                        }
+                        DoomLoopAction::InjectGuidance { message } => {
+                            tracing::warn!("Doom loop detected, guiding model: {}", message);
+                            let _ = event_tx.send(SessionEvent::TextChunk(
+                                "\n🔁 Loop detected - asking the AI to change approach\n".to_string(),
+                            ));
+                            loop_guidance = Some(message);
                         }
                         DoomLoopAction::Continue => {}
                     }
 
+                    // Let pre_tool hooks block the call or rewrite its parameters
+                    let (input, hook_notes) = match self.hooks.run_pre_tool_use(name, input).await {
+                        PreToolDecision::Proceed { input, warnings } => (input, warnings),
+                        PreToolDecision::Block(reason) => {
+                            let result = hook_blocked_result(name, &reason);
+                            self.report_blocked_tool(name, input, &result, &event_tx);
+                            tool_results.push(ContentBlock::ToolResult {
+                                tool_use_id: id.clone(),
+                                content: result,
+                            });
+                            continue;
+                        }
+                    };
+                    let input = &input;
+
+                    // Check permission rules, asking the user when approval is needed
+                    if let Some(result) = self.gate_tool_call(name, input, &event_tx).await {
+                        tool_results.push(ContentBlock::ToolResult {
+                            tool_use_id: id.clone(),
+                            content: result,
+                        });
+                        continue;
+                    }
+
                     // Generate description for the tool action
                     let description = self.describe_tool_action(name, input);
 
@@ -1987,9 +2657,11 @@ impl Session {
                             callback,
                         )
                         .with_session_events(event_tx.clone())
+                        .with_file_cache(self.file_cache.clone())
                     } else {
                         ToolContext::new(&self.project_path, &self.config.tools)
                             .with_session_events(event_tx.clone())
+                            .with_file_cache(self.file_cache.clone())
                     };
 
                     tracing::info!("[TOOL DEBUG] Starting tool execution: {}", name);
@@ -2008,7 +2680,7 @@ impl Session {
                             Err(e) => {
                                 tracing::error!("[TOOL DEBUG] Tool {} failed in {:?}: {}",
                                     name, tool_start.elapsed(), e);
-                                (format!("Error: {}", e), false)
+                                (render_tool_error(&e), false)
                             },
                         },
                         None => {
@@ -2017,45 +2689,41 @@ impl Session {
                         },
                     };
                     tracing::info!("[TOOL DEBUG] Tool {} finished, success: {}", name, success);
+                    let result = self.prepare_retry(name, input, result, success);
+                    let result = self
+                        .run_post_tool_hooks(name, input, result, success, hook_notes)
+                        .await;
+                    let result = with_loop_guidance(result, loop_guidance);
+
+                    self.track_file_for_tool(name, input, success);
+                    self.capture_screenshot_for_tool(name, input, success);
+
+                    // Pull in skills triggered by the file this tool touched
+                    if !self
+                        .activate_skills_for_tool(name, input, success, Some(&event_tx))
+                        .is_empty()
+                    {
+                        system_prompt = self.system_prompt().await;
+                    }
 
                     // Record tool call for doom loop detection
                     self.loop_detector.record(name, input);
                     if success {
                         self.loop_detector.record_success();
                     } else {
+                        self.stats.record_tool_failure(name);
                         self.loop_detector.record_failure(&result);
                         // Check for failure loop
                         if let Some(DoomLoopAction::AskUser { message }) =
                             self.loop_detector.check_failure_loop()
                         {
                             tracing::warn!("Failure loop detected, asking user: {}", message);
-
-                            // Create channel for user response
-                            let (response_tx, mut response_rx) = tokio::sync::mpsc::unbounded_channel::<bool>();
-                            let prompt_id = uuid::Uuid::new_v4().to_string();
-
-                            // Send prompt to UI
-                            let _ = event_tx.send(SessionEvent::DoomLoopPrompt {
-                                prompt_id: prompt_id.clone(),
-                                message: message.clone(),
-                                response_tx,
-                            });
-
-                            // Wait for user response (with timeout)
-                            let should_continue = tokio::time::timeout(
-                                std::time::Duration::from_secs(300),
-                                response_rx.recv()
-                            ).await.ok().flatten().unwrap_or(false);
-
-                            if !should_continue {
-                                tracing::info!("User chose to stop due to failure loop (prompt_id={})", prompt_id);
+                            if !self.request_doom_loop_decision(&message, &event_tx).await {
                                 // Break out of the loop - session will end
                                 break;
                             }
-
                             // User chose to continue - reset loop detector
                             self.loop_detector.reset();
-                            tracing::info!("User chose to continue past failure loop (prompt_id={})", prompt_id);
                         }
                     }
 
@@ -2095,8 +2763,8 @@ impl Session {
                         }
                     }
 
-                    // For todowrite, send todo list update event
-                    if name == "todowrite" && success {
+                    // For todowrite/todoupdate, send todo list update event
+                    if matches!(name.as_str(), "todowrite" | "todoupdate") && success {
                         let todos = crate::tools::todo::get_todo_list();
                         let _ = event_tx.send(SessionEvent::TodoList { todos });
                     }
@@ -2245,10 +2913,19 @@ impl Session {
                     }
                 }
 
+                // Let background monitors interrupt with failures mid-task
+                if let Some(updates) = self.take_monitor_updates() {
+                    final_results.push(ContentBlock::Text {
+                        text: format!("\n\n{}", updates),
+                    });
+                }
+                final_results.append(&mut self.pending_screenshots);
+
                 self.messages.push(Message {
                     role: crate::llm::Role::User,
                     content: final_results,
                 });
+                self.checkpoint_turn().await;
             }
         }
 
@@ -2272,12 +2949,29 @@ impl Session {
             }
         }
 
-        let final_response = response_text.trim().to_string();
+        let mut final_response = response_text.trim().to_string();
+        if self.coverage_check_enabled() {
+            if let Some(warning) = self
+                .coverage
+                .check(&self.project_path, self.config.build.coverage_timeout_secs)
+                .await
+            {
+                let _ = event_tx.send(SessionEvent::TextChunk(format!("\n{}\n", warning)));
+                final_response.push_str(&format!("\n\n{}", warning));
+            }
+        }
         self.last_output = final_response.clone();
+        self.run_post_message_hooks(&user_message, &final_response).await;
 
         Ok(final_response)
     }
 
+    /// Whether to measure coverage around changes: only in BUILD mode, when
+    /// `build.coverage_check` is on
+    fn coverage_check_enabled(&self) -> bool {
+        self.config.build.coverage_check && self.agent_mode == AgentMode::Build
+    }
+
     /// Build an execution plan from an assistant message with tool calls
     fn build_execution_plan(&self, assistant_message: &Message) -> ExecutionPlan {
         let mut plan = ExecutionPlan::new();
@@ -2320,7 +3014,13 @@ impl Session {
             .tools
             .iter()
             .filter(|t| t.risk_level == crate::approval::RiskLevel::High)
-            .map(|t| format!("High-risk operation: {} - {}", t.name, t.description))
+            .map(|t| match t.risk_summary() {
+                Some(summary) => format!(
+                    "High-risk operation: {} - {} ({})",
+                    t.name, t.description, summary
+                ),
+                None => format!("High-risk operation: {} - {}", t.name, t.description),
+            })
             .collect();
         for risk in high_risk_tools {
             plan.add_risk(risk);
@@ -2351,9 +3051,7 @@ impl Session {
         // Run the build command with timeout
         let output = tokio::time::timeout(
             std::time::Duration::from_secs(timeout),
-            tokio::process::Command::new("sh")
-                .arg("-c")
-                .arg(&build_cmd)
+            crate::platform::shell_command(&build_cmd)
                 .current_dir(&self.project_path)
                 .output(),
         )
@@ -2470,6 +3168,39 @@ impl Session {
                     url.to_string()
                 }
             }
+            "http_request" => {
+                let method = params.get("method").and_then(|v| v.as_str()).unwrap_or("GET");
+                let url = params.get("url").and_then(|v| v.as_str()).unwrap_or("?");
+                format!("{} {}", method.to_uppercase(), url)
+            }
+            "run_tests" => params
+                .get("command")
+                .or_else(|| params.get("filter"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("detected suite")
+                .to_string(),
+            "add_dependency" => {
+                let name = params.get("name").and_then(|v| v.as_str()).unwrap_or("?");
+                match params.get("version").and_then(|v| v.as_str()) {
+                    Some(version) => format!("{}@{}", name, version),
+                    None => name.to_string(),
+                }
+            }
+            "remove_dependency" => params
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("?")
+                .to_string(),
+            "ci_status" => params
+                .get("branch")
+                .and_then(|v| v.as_str())
+                .unwrap_or("current branch")
+                .to_string(),
+            "browser" => {
+                let operation = params.get("operation").and_then(|v| v.as_str()).unwrap_or("?");
+                let url = params.get("url").and_then(|v| v.as_str()).unwrap_or("?");
+                format!("{} {}", operation, url)
+            }
             "todowrite" => {
                 let count = params.get("todos")
                     .and_then(|v| v.as_array())
@@ -2477,10 +3208,434 @@ impl Session {
                     .unwrap_or(0);
                 format!("{} items", count)
             }
+            "todoupdate" => {
+                let action = params.get("action").and_then(|v| v.as_str()).unwrap_or("?");
+                match params.get("item").and_then(|v| v.as_u64()) {
+                    Some(item) => format!("{} #{}", action, item),
+                    None => action.to_string(),
+                }
+            }
             _ => name.to_string(),
         }
     }
 
+    /// Decide whether a tool call may run, combining permission patterns with the approval mode
+    fn check_tool_permission(&self, name: &str, input: &serde_json::Value) -> ToolPermission {
+        if let Some(ref allowed) = self.tool_allowlist {
+            let permitted = allowed.iter().any(|pattern| {
+                pattern == name
+                    || glob::Pattern::new(pattern).is_ok_and(|p| p.matches(name))
+            });
+            if !permitted {
+                return ToolPermission::Denied(format!(
+                    "not in the command's allowed tools ({})",
+                    allowed.join(", ")
+                ));
+            }
+        }
+
+        match self.permission_manager.check(name, input) {
+            Permission::Denied => ToolPermission::Denied(
+                self.permission_manager
+                    .denial_reason(name, input)
+                    .unwrap_or_else(|| "denied by permission rules".to_string()),
+            ),
+            Permission::Allowed => ToolPermission::Allowed,
+            Permission::AlwaysAsk => ToolPermission::NeedsApproval,
+            Permission::NeedsApproval if self.approval_mode.needs_approval(name) => {
+                ToolPermission::NeedsApproval
+            }
+            Permission::NeedsApproval => ToolPermission::Allowed,
+        }
+    }
+
+    /// Check if a tool call is high risk (used to highlight approval prompts)
+    fn is_high_risk_call(name: &str, input: &serde_json::Value) -> bool {
+        PlannedTool::new(name, "")
+            .with_params(input.clone())
+            .auto_risk()
+            .risk_level
+            == crate::approval::RiskLevel::High
+    }
+
+    /// Ask the user on stdin whether a single tool call may run
+    async fn ask_tool_approval(&self, name: &str, input: &serde_json::Value) -> Result<bool> {
+        let warning = if Self::is_high_risk_call(name, input) {
+            "⚠️  "
+        } else {
+            ""
+        };
+        if let Some(origin) = self.mcp_manager.tool_origin(name) {
+            print!("\n🔌 From {}", origin);
+        }
+        print!(
+            "\n🔒 {}Allow {}? [y/N]: ",
+            warning,
+            self.describe_tool_action(name, input)
+        );
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+
+        let answer = answer.trim().to_lowercase();
+        Ok(answer == "y" || answer == "yes")
+    }
+
+    /// Ask the UI whether a tool call may run, denying it if no answer arrives in time
+    async fn request_tool_approval(
+        &self,
+        name: &str,
+        input: &serde_json::Value,
+        event_tx: &mpsc::UnboundedSender<SessionEvent>,
+    ) -> bool {
+        let (response_tx, mut response_rx) = tokio::sync::mpsc::unbounded_channel::<bool>();
+        let prompt_id = uuid::Uuid::new_v4().to_string();
+
+        let _ = event_tx.send(SessionEvent::ToolApprovalPrompt {
+            prompt_id: prompt_id.clone(),
+            tool: name.to_string(),
+            description: self.describe_tool_action(name, input),
+            high_risk: Self::is_high_risk_call(name, input),
+            origin: self.mcp_manager.tool_origin(name),
+            always_ask: self.permission_manager.check(name, input) == Permission::AlwaysAsk,
+            response_tx,
+        });
+
+        let approved = tokio::time::timeout(
+            std::time::Duration::from_secs(300), // 5 minute timeout
+            response_rx.recv(),
+        )
+        .await
+        .ok()
+        .flatten()
+        .unwrap_or(false);
+
+        tracing::info!(
+            "Tool approval for {}: {} (prompt_id={})",
+            name,
+            if approved { "approved" } else { "declined" },
+            prompt_id
+        );
+        approved
+    }
+
+    /// Give a failed edit one retry with the file's current content attached.
+    /// A second failure on the same file is reported unchanged.
+    fn prepare_retry(
+        &mut self,
+        name: &str,
+        input: &serde_json::Value,
+        result: String,
+        success: bool,
+    ) -> String {
+        let Some(path) = repair::repair_target(name, input) else {
+            return result;
+        };
+        if success {
+            self.repair_attempts.remove(&path);
+            return result;
+        }
+        // Only stale-content failures benefit; a protected path or a missing
+        // file won't be fixed by showing the model the file
+        let is_conflict = ToolErrorInfo::parse(&result).is_some_and(|e| e.code == "patch_conflict");
+        if !self.config.tools.enrich_errors
+            || !is_conflict
+            || !self.repair_attempts.insert(path.clone())
+        {
+            return result;
+        }
+        let ctx = ToolContext::new(&self.project_path, &self.config.tools)
+            .with_file_cache(self.file_cache.clone());
+        repair::enrich_error(&result, &path, input, &ctx)
+    }
+
+    /// Ask on stdin whether to keep going after a loop was detected
+    fn ask_doom_loop_decision(&self, message: &str) -> Result<bool> {
+        print!("\n🔁 {}\nContinue anyway? [y/N]: ", message);
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer)?;
+
+        let answer = answer.trim().to_lowercase();
+        Ok(answer == "y" || answer == "yes")
+    }
+
+    /// Ask the UI whether to keep going after a loop was detected. The tool
+    /// call waits for the answer; if none arrives within the configured
+    /// timeout the configured default is used (stop, unless overridden).
+    async fn request_doom_loop_decision(
+        &self,
+        message: &str,
+        event_tx: &mpsc::UnboundedSender<SessionEvent>,
+    ) -> bool {
+        let (response_tx, mut response_rx) = tokio::sync::mpsc::unbounded_channel::<bool>();
+        let prompt_id = uuid::Uuid::new_v4().to_string();
+        let config = &self.config.loop_detection;
+
+        let _ = event_tx.send(SessionEvent::DoomLoopPrompt {
+            prompt_id: prompt_id.clone(),
+            message: message.to_string(),
+            timeout_secs: config.prompt_timeout_secs,
+            response_tx,
+        });
+
+        let should_continue = match tokio::time::timeout(
+            std::time::Duration::from_secs(config.prompt_timeout_secs),
+            response_rx.recv(),
+        )
+        .await
+        {
+            Ok(Some(answer)) => answer,
+            Ok(None) => false,
+            Err(_) => {
+                let _ = event_tx.send(SessionEvent::TextChunk(format!(
+                    "\n⏱️ No answer to the loop prompt after {}s - {}\n",
+                    config.prompt_timeout_secs,
+                    if config.continue_on_timeout {
+                        "continuing"
+                    } else {
+                        "stopping"
+                    }
+                )));
+                config.continue_on_timeout
+            }
+        };
+
+        tracing::info!(
+            "User chose to {} past doom loop (prompt_id={})",
+            if should_continue { "continue" } else { "stop" },
+            prompt_id
+        );
+        should_continue
+    }
+
+    /// Run the permission gate for a tool call in the streaming path.
+    /// Returns the result to report to the model when the call must not run.
+    async fn gate_tool_call(
+        &self,
+        name: &str,
+        input: &serde_json::Value,
+        event_tx: &mpsc::UnboundedSender<SessionEvent>,
+    ) -> Option<String> {
+        let blocked = match self.check_tool_permission(name, input) {
+            ToolPermission::Allowed => return None,
+            ToolPermission::Denied(reason) => {
+                tracing::warn!("Tool call denied: {} ({})", name, reason);
+                permission_denied_result(name, &reason)
+            }
+            ToolPermission::NeedsApproval => {
+                if self.request_tool_approval(name, input, event_tx).await {
+                    return None;
+                }
+                approval_declined_result(name)
+            }
+        };
+
+        self.report_blocked_tool(name, input, &blocked, event_tx);
+        Some(blocked)
+    }
+
+    /// Show a tool call that was not allowed to run in the UI
+    fn report_blocked_tool(
+        &self,
+        name: &str,
+        input: &serde_json::Value,
+        result: &str,
+        event_tx: &mpsc::UnboundedSender<SessionEvent>,
+    ) {
+        let _ = event_tx.send(SessionEvent::ToolStart {
+            name: name.to_string(),
+            description: format!("Blocked: {}", self.describe_tool_action(name, input)),
+        });
+        let _ = event_tx.send(SessionEvent::ToolOutput {
+            name: name.to_string(),
+            output: result.to_string(),
+        });
+        let _ = event_tx.send(SessionEvent::ToolComplete {
+            name: name.to_string(),
+            success: false,
+        });
+    }
+
+    /// Run pre_message hooks, returning the (possibly rewritten) message
+    async fn run_pre_message_hooks(&self, message: String) -> Result<String> {
+        let ctx = HookContext::for_message(HookType::PreMessage, &message);
+        match self.hooks.execute(&ctx).await {
+            HookResult::Block(reason) | HookResult::Skip(reason) => {
+                anyhow::bail!("Message blocked by hook: {}", reason)
+            }
+            HookResult::Modify { content, message: note } => {
+                if let Some(note) = note.filter(|n| !n.is_empty()) {
+                    tracing::warn!("Hook: {}", note);
+                }
+                Ok(content)
+            }
+            HookResult::ContinueWithWarning(warning) => {
+                tracing::warn!("Hook: {}", warning);
+                Ok(message)
+            }
+            HookResult::Continue => Ok(message),
+        }
+    }
+
+    /// Run post_message hooks once the final response is ready
+    async fn run_post_message_hooks(&self, message: &str, response: &str) {
+        let ctx = HookContext::for_message(HookType::PostMessage, message).with_response(response);
+        for warning in self.hooks.execute_and_collect_warnings(&ctx).await {
+            tracing::warn!("Hook: {}", warning);
+        }
+    }
+
+    /// Run post_tool hooks and append their feedback (and any pre_tool
+    /// warnings) to the tool result so the model sees it
+    async fn run_post_tool_hooks(
+        &self,
+        name: &str,
+        input: &serde_json::Value,
+        result: String,
+        success: bool,
+        mut notes: Vec<String>,
+    ) -> String {
+        let ctx = HookContext::for_tool(HookType::PostToolUse, name, Some(input.clone()))
+            .with_tool_result(&result, success);
+        notes.extend(self.hooks.execute_and_collect_warnings(&ctx).await);
+        notes.retain(|n| !n.is_empty());
+
+        if notes.is_empty() {
+            result
+        } else {
+            format!("{}\n\n[HOOKS]\n{}", result, notes.join("\n"))
+        }
+    }
+
+    /// System prompt for the current agent mode, with project context and active skills
+    async fn system_prompt(&mut self) -> String {
+        let project_context = self.memory.get_system_prompt().await.ok();
+        if self.prompt_overrides.reload_if_changed() {
+            tracing::info!("Reloaded prompt files: {:?}", self.prompt_overrides.active());
+            for error in &self.prompt_overrides.errors {
+                tracing::warn!("Skipping prompt file: {}", error);
+            }
+        }
+        let vars = PromptVars::new(&self.project_path, self.agent_mode);
+        let mut prompt = prompts::build_system_prompt_with(
+            &self.prompt_overrides,
+            &vars,
+            project_context.as_deref(),
+            None,
+        );
+        if let Some(skills) = self
+            .skills
+            .get_active_skills_prompt(self.config.skills.token_budget)
+        {
+            prompt.push_str(&skills);
+        }
+        if let Some(todos) = open_todos_prompt(&TodoStore::new(&self.project_path).load()) {
+            prompt.push_str(&todos);
+        }
+        if let Some(editor) = crate::ide::editor_prompt(&self.project_path) {
+            prompt.push_str(&editor);
+        }
+        prompt
+    }
+
+    /// Remember the content of the file a successful read_file/edit_file/
+    /// write_file call touched, so later outside edits are reported, and
+    /// note edits for the coverage check
+    fn track_file_for_tool(&mut self, name: &str, input: &serde_json::Value, success: bool) {
+        if !success || !matches!(name, "read_file" | "edit_file" | "write_file") {
+            return;
+        }
+        if let Some(file) = input.get("file_path").and_then(|v| v.as_str()) {
+            let path = self.project_path.join(file);
+            self.file_watcher.track(&path);
+            if name != "read_file" {
+                self.coverage.record_edit(path);
+            }
+        }
+    }
+
+    /// Queue the image a successful browser screenshot wrote, so the model
+    /// sees it alongside the tool results
+    fn capture_screenshot_for_tool(&mut self, name: &str, input: &serde_json::Value, success: bool) {
+        if !success || name != "browser" || !self.capabilities().vision {
+            return;
+        }
+        let Some(path) = crate::tools::browser::screenshot_path(&self.project_path, input) else {
+            return;
+        };
+        match std::fs::read(&path) {
+            Ok(png) if png.len() <= MAX_SCREENSHOT_BYTES => {
+                use base64::Engine;
+                self.pending_screenshots.push(ContentBlock::Image {
+                    data: base64::engine::general_purpose::STANDARD.encode(&png),
+                    media_type: "image/png".to_string(),
+                });
+            }
+            Ok(png) => tracing::warn!(
+                "Screenshot {} is too large to attach ({} bytes)",
+                path.display(),
+                png.len()
+            ),
+            Err(e) => tracing::warn!("Failed to read screenshot {}: {}", path.display(), e),
+        }
+    }
+
+    /// Activate skills whose triggers match the file a successful
+    /// read_file/edit_file/write_file call touched, announcing them to the UI.
+    /// Returns the names of newly activated skills.
+    fn activate_skills_for_tool(
+        &mut self,
+        name: &str,
+        input: &serde_json::Value,
+        success: bool,
+        event_tx: Option<&mpsc::UnboundedSender<SessionEvent>>,
+    ) -> Vec<String> {
+        if !success || !matches!(name, "read_file" | "edit_file" | "write_file") {
+            return Vec::new();
+        }
+        let Some(file) = input.get("file_path").and_then(|v| v.as_str()) else {
+            return Vec::new();
+        };
+        let file = std::path::Path::new(file)
+            .strip_prefix(&self.project_path)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| file.to_string());
+
+        let activated = self.skills.auto_activate_for_files(&[&file]);
+        for skill in &activated {
+            tracing::info!("Skill '{}' activated by {}", skill, file);
+            if let Some(tx) = event_tx {
+                let _ = tx.send(SessionEvent::SkillActivated {
+                    name: skill.clone(),
+                    file: file.clone(),
+                });
+            }
+        }
+        activated
+    }
+
+    /// Run session_start/session_end hooks
+    async fn run_session_hooks(&self, hook_type: HookType) {
+        let mut ctx = HookContext::for_session(
+            hook_type,
+            self.current_session_id.as_deref(),
+            &self.project_path.to_string_lossy(),
+        );
+        let incomplete = get_todo_list()
+            .iter()
+            .filter(|t| t.status != "completed")
+            .count();
+        ctx.metadata
+            .insert("incomplete_todos".to_string(), incomplete.to_string());
+
+        for warning in self.hooks.execute_and_collect_warnings(&ctx).await {
+            tracing::warn!("Hook: {}", warning);
+        }
+    }
+
     /// Ask user for approval (for Plan mode)
     async fn ask_user_approval(&self) -> Result<bool> {
         print!("\n🔒 Execute this plan? [y/N]: ");
@@ -2496,6 +3651,14 @@ impl Session {
     pub async fn stop(&mut self) -> Result<()> {
         tracing::info!("Ending Safe Coder session");
 
+        self.run_session_hooks(HookType::SessionEnd).await;
+
+        // A clean exit leaves nothing to recover
+        if let Err(e) = self.persistence.discard_journal(&self.journal_id).await {
+            tracing::warn!("Failed to discard session journal: {}", e);
+        }
+        self.record_stats(true).await;
+
         // Show final change summary if git tracking is enabled
         if self.config.git.auto_commit {
             if let Ok(summary) = self.git_manager.get_change_summary().await {
@@ -2565,7 +3728,19 @@ impl Session {
         let duration = Utc::now() - self.session_start;
         self.stats.session_duration_secs = duration.num_seconds();
 
-        Ok(self.stats.format())
+        let mut output = self.stats.format();
+        let file_cache = self.file_cache.stats();
+        if file_cache.hits + file_cache.misses > 0 {
+            output.push_str(&format!(
+                "\n📂 File Cache: {} hits / {} misses ({:.0}%), {} KB served from memory, ~{}ms saved\n",
+                file_cache.hits,
+                file_cache.misses,
+                file_cache.hit_rate(),
+                file_cache.bytes_served / 1024,
+                file_cache.time_saved().as_millis()
+            ));
+        }
+        Ok(output)
     }
 
     /// Save current chat session
@@ -2592,6 +3767,79 @@ impl Session {
         Ok(())
     }
 
+    /// Write the conversation ahead after an assistant or tool turn, so a
+    /// crash mid-request loses at most the turn in flight
+    async fn checkpoint_turn(&self) {
+        crate::crash::record_messages(&self.project_path, &self.messages);
+        if let Err(e) = self
+            .persistence
+            .checkpoint_turn(&self.journal_id, &self.project_path, &self.messages)
+            .await
+        {
+            tracing::warn!("Failed to checkpoint turn: {}", e);
+        }
+        self.record_stats(false).await;
+    }
+
+    /// Save this session's usage so far for `safe-coder stats`
+    async fn record_stats(&self, completed: bool) {
+        if self.stats.total_messages == 0 {
+            return;
+        }
+        let mut stats = self.stats.clone();
+        stats.session_duration_secs = (Utc::now() - self.session_start).num_seconds();
+        let record = SessionStatsRecord {
+            session_id: self.stats_id.clone(),
+            project_path: self.project_path.to_string_lossy().to_string(),
+            model: self.get_current_model(),
+            stats,
+            completed,
+            created_at: self.session_start,
+            updated_at: Utc::now(),
+        };
+        if let Err(e) = self.persistence.record_stats(&record).await {
+            tracing::warn!("Failed to record session stats: {}", e);
+        }
+    }
+
+    /// Restore the newest session in this project that did not end cleanly.
+    /// Returns the number of messages recovered, or `None` if there is nothing to recover.
+    pub async fn recover_last_session(&mut self) -> Result<Option<usize>> {
+        let Some(saved) = self.persistence.unfinished_session(&self.project_path).await? else {
+            return Ok(None);
+        };
+        let mut messages: Vec<Message> = serde_json::from_str(&saved.messages)
+            .context("Failed to deserialize recovered messages")?;
+
+        // Tool calls that were running when the process died never got a result
+        let interrupted: Vec<ContentBlock> = match messages.last() {
+            Some(last) if matches!(last.role, crate::llm::Role::Assistant) => last
+                .content
+                .iter()
+                .filter_map(|block| match block {
+                    ContentBlock::ToolUse { id, .. } => Some(ContentBlock::ToolResult {
+                        tool_use_id: id.clone(),
+                        content: "Error: interrupted - safe-coder exited before this tool finished"
+                            .to_string(),
+                    }),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        if !interrupted.is_empty() {
+            messages.push(Message {
+                role: crate::llm::Role::User,
+                content: interrupted,
+            });
+        }
+
+        self.messages = messages;
+        // Keep journaling into the recovered entry
+        self.journal_id = saved.id;
+        Ok(Some(self.messages.len()))
+    }
+
     /// List all saved chat sessions
     pub async fn list_chats(&self) -> Result<String> {
         let sessions = self.persistence.list_sessions().await?;
@@ -2621,22 +3869,52 @@ impl Session {
         self.persistence.delete_session(id).await
     }
 
-    /// Share a chat session (placeholder - could upload to pastebin/gist)
-    pub async fn share_chat(&self, _id: &str) -> Result<String> {
-        // TODO: Implement actual sharing (upload to gist, pastebin, etc.)
-        Ok("Sharing not yet implemented. Save the session locally for now.".to_string())
+    /// Encrypt a saved chat session and upload it as configured in `[share]`
+    pub async fn share_chat(&self, id: &str) -> Result<crate::persistence::share::Share> {
+        use crate::persistence::share::{self, SharedChat};
+
+        let saved = self.persistence.resume_session(id).await?;
+        let messages: Vec<Message> = serde_json::from_str(&saved.messages)
+            .context("Failed to deserialize messages")?;
+        share::share(&SharedChat::new(saved.name, messages), &self.config.share).await
     }
 
-    /// Switch to a different model
+    /// Switch to a different model, in both agent modes
     pub async fn switch_model(&mut self, model: &str) -> Result<()> {
         self.config.llm.model = model.to_string();
-        self.llm_client = Arc::from(create_client(&self.config).await?);
+        self.config.llm.plan_model = None;
+        self.config.llm.build_model = None;
+        self.reload_clients().await
+    }
+
+    /// Set a sampling parameter for the rest of the session, as in
+    /// `/set temperature 0.2`
+    pub async fn set_generation_param(&mut self, name: &str, value: &str) -> Result<()> {
+        let previous = self.config.llm.params.clone();
+        self.config.llm.params.set(name, value)?;
+        if let Err(e) = self.reload_clients().await {
+            self.config.llm.params = previous;
+            return Err(e);
+        }
         Ok(())
     }
 
-    /// Get current model name
+    /// Sampling parameters sent with each request
+    pub fn generation_params(&self) -> &crate::config::GenerationParams {
+        &self.config.llm.params
+    }
+
+    /// Recreate the LLM clients from the current config
+    async fn reload_clients(&mut self) -> Result<()> {
+        self.mode_clients = ModeClients::create(&self.config).await?;
+        self.llm_client = self.mode_clients.get(self.agent_mode);
+        self.fit_context_to_model();
+        Ok(())
+    }
+
+    /// Get the current agent mode's model name
     pub fn get_current_model(&self) -> String {
-        self.config.llm.model.clone()
+        self.config.llm.model_for_mode(self.agent_mode).to_string()
     }
 
     /// List available models for the current provider
@@ -2646,8 +3924,8 @@ impl Session {
         match &self.config.llm.provider {
             LlmProvider::GitHubCopilot => {
                 // Get the stored GitHub token
-                let token_path = Config::token_path(&LlmProvider::GitHubCopilot)?;
-                if !token_path.exists() {
+                let token_path = self.config.active_token_path(&LlmProvider::GitHubCopilot)?;
+                if !crate::auth::StoredToken::exists(&token_path) {
                     return Ok("Not logged in to GitHub Copilot. Run /login to authenticate.".to_string());
                 }
 
@@ -2672,35 +3950,8 @@ impl Session {
                 output.push_str("\nUse /model <name> to switch models.");
                 Ok(output)
             }
-            LlmProvider::Anthropic => {
-                let mut output = String::from("📋 Available Anthropic Models:\n\n");
-                let current_model = &self.config.llm.model;
-                let models = [
-                    "claude-opus-4-20250514",
-                    "claude-sonnet-4-20250514",
-                    "claude-3-5-sonnet-20241022",
-                    "claude-3-5-haiku-20241022",
-                    "claude-3-opus-20240229",
-                    "claude-3-haiku-20240307",
-                ];
-                for model in models {
-                    let marker = if model == current_model { " ← current" } else { "" };
-                    output.push_str(&format!("  • {}{}\n", model, marker));
-                }
-                output.push_str("\nUse /model <name> to switch models.");
-                Ok(output)
-            }
-            LlmProvider::OpenAI => {
-                let mut output = String::from("📋 Available OpenAI Models:\n\n");
-                let current_model = &self.config.llm.model;
-                let models = ["gpt-4o", "gpt-4o-mini", "gpt-4-turbo", "gpt-4", "gpt-3.5-turbo"];
-                for model in models {
-                    let marker = if model == current_model { " ← current" } else { "" };
-                    output.push_str(&format!("  • {}{}\n", model, marker));
-                }
-                output.push_str("\nUse /model <name> to switch models.");
-                Ok(output)
-            }
+            LlmProvider::Anthropic => Ok(self.format_registry_models("Anthropic")),
+            LlmProvider::OpenAI => Ok(self.format_registry_models("OpenAI")),
             LlmProvider::OpenRouter => {
                 Ok("📋 OpenRouter Models:\n\nOpenRouter supports many models. Visit https://openrouter.ai/models for the full list.\n\nUse /model <provider/model-name> to switch models.".to_string())
             }
@@ -2713,6 +3964,30 @@ impl Session {
         }
     }
 
+    /// The provider's models from the model registry, with context windows
+    /// and list prices
+    fn format_registry_models(&self, provider_name: &str) -> String {
+        let registry = crate::llm::models::registry();
+        let current_model = &self.config.llm.model;
+        let mut output = format!("📋 Available {} Models:\n\n", provider_name);
+        for choice in registry.choices(&self.config.llm.provider) {
+            let marker = if choice.id == *current_model { " ← current" } else { "" };
+            let details = match registry.lookup(&choice.id) {
+                Some(info) => match (info.input_price, info.output_price) {
+                    (Some(input), Some(output)) => format!(
+                        " ({} tokens, ${}/${} per M)",
+                        info.context_window, input, output
+                    ),
+                    _ => format!(" ({} tokens)", info.context_window),
+                },
+                None => String::new(),
+            };
+            output.push_str(&format!("  • {}{}{}\n", choice.id, details, marker));
+        }
+        output.push_str("\nUse /model <name> to switch models.");
+        output
+    }
+
     /// Set approval mode
     pub fn set_approval_mode(&mut self, mode: &str) -> Result<()> {
         self.approval_mode = ApprovalMode::from_str(mode)?;
@@ -2765,22 +4040,13 @@ impl Session {
         self.dir_checkpoints.delete_checkpoint(checkpoint_id).await
     }
 
-    /// Generate project summary
+    /// Summarize the project's languages, build commands, layout and tooling
     pub async fn generate_project_summary(&self) -> Result<String> {
         let sandbox_dir = self.get_sandbox_dir()?;
-
-        // Use read_file tool to scan project structure
-        // This is a simplified version - could be enhanced
-        let mut summary = String::new();
-        summary.push_str("📊 Project Summary\n");
-        summary.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
-        summary.push_str(&format!("Project Path: {}\n\n", sandbox_dir.display()));
-
-        // TODO: Implement actual project analysis
-        summary.push_str("Note: Full project analysis coming soon.\n");
-        summary.push_str("For now, use the AI to analyze your codebase.\n");
-
-        Ok(summary)
+        let analysis =
+            tokio::task::spawn_blocking(move || crate::memory::analysis::analyze(&sandbox_dir))
+                .await?;
+        Ok(analysis.summary())
     }
 
     /// Compress conversation to save tokens
@@ -2804,9 +4070,16 @@ impl Session {
         output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
 
         output.push_str(&format!("Model: {}\n", self.config.llm.model));
+        for mode in [AgentMode::Plan, AgentMode::Build] {
+            let model = self.config.llm.model_for_mode(mode);
+            if model != self.config.llm.model {
+                output.push_str(&format!("{} Model: {}\n", mode, model));
+            }
+        }
         output.push_str(&format!("Provider: {:?}\n", self.config.llm.provider));
         output.push_str(&format!("Approval Mode: {}\n", self.approval_mode));
         output.push_str(&format!("Max Tokens: {}\n", self.config.llm.max_tokens));
+        output.push_str(&format!("{}\n", self.config.llm.params));
         output.push_str(&format!(
             "Git Auto-Commit: {}\n",
             self.config.git.auto_commit
@@ -2875,9 +4148,83 @@ impl Session {
         self.memory.refresh().await
     }
 
-    /// Initialize project context file
-    pub async fn init_project_context(&self) -> Result<()> {
-        self.memory.init_file().await
+    /// Handle a `#remember <fact>` message by appending the fact to
+    /// SAFE_CODER.md. Returns None for any other message.
+    fn remember_from_message(&mut self, message: &str) -> Option<Result<String>> {
+        let fact = crate::memory::learned::parse_remember(message)?;
+        if fact.is_empty() {
+            return Some(Err(anyhow::anyhow!("Usage: #remember <fact about this project>")));
+        }
+        let result = crate::memory::learned::remember(
+            &self.project_path,
+            fact,
+            crate::memory::learned::LearnedSource::User,
+        )
+        .map(|added| {
+            if added {
+                format!("🧠 Remembered: {}
+", fact)
+            } else {
+                format!("🧠 Already in project memory: {}
+", fact)
+            }
+        });
+        Some(result)
+    }
+
+    /// With `[memory] auto_learn`, ask the model in the background whether
+    /// a message that looks like a correction teaches a lasting project fact
+    fn learn_from_correction(&self, message: &str) {
+        if !self.config.memory.auto_learn
+            || !crate::memory::learned::looks_like_correction(message)
+        {
+            return;
+        }
+        // Corrections answer something the assistant said
+        let Some(previous_reply) = self
+            .messages
+            .iter()
+            .rev()
+            .find(|m| matches!(m.role, crate::llm::Role::Assistant))
+            .map(|m| {
+                m.content
+                    .iter()
+                    .filter_map(|block| match block {
+                        ContentBlock::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+        else {
+            return;
+        };
+
+        let client = self.llm_client.clone();
+        let project = self.project_path.clone();
+        let message = message.to_string();
+        tokio::spawn(async move {
+            use crate::memory::learned::{detect_fact, remember, LearnedSource};
+            match detect_fact(&*client, &previous_reply, &message).await {
+                Ok(Some(fact)) => match remember(&project, &fact, LearnedSource::Correction) {
+                    Ok(true) => tracing::info!("Learned project fact: {}", fact),
+                    Ok(false) => {}
+                    Err(e) => tracing::warn!("Failed to save learned fact: {}", e),
+                },
+                Ok(None) => {}
+                Err(e) => tracing::debug!("Correction detection failed: {}", e),
+            }
+        });
+    }
+
+    /// Generate SAFE_CODER.md from an analysis of the project, returning its path
+    pub async fn init_project_context(&self, force: bool) -> Result<PathBuf> {
+        crate::memory::analysis::write_project_memory(
+            &self.project_path,
+            Some(&*self.llm_client),
+            force,
+        )
+        .await
     }
 
     /// Add directory to workspace (placeholder)
@@ -2980,7 +4327,8 @@ fn parse_plan_from_response(response: &str) -> (String, Vec<PlanStep>) {
             if let Some(description) = caps.get(2) {
                 let desc_text = description.as_str().trim().to_string();
                 let step_id = format!("step-{}", steps.len() + 1);
-                steps.push(PlanStep::new(step_id, desc_text));
+                let files = plan_step_files(&desc_text);
+                steps.push(PlanStep::new(step_id, desc_text).with_files(files));
             }
         }
     }
@@ -3004,3 +4352,31 @@ fn parse_plan_from_response(response: &str) -> (String, Vec<PlanStep>) {
 
     (title, steps)
 }
+
+/// Backticked paths in a plan step, e.g. "Update `src/config.rs`"
+fn plan_step_files(description: &str) -> Vec<String> {
+    description
+        .split('`')
+        .skip(1)
+        .step_by(2)
+        .filter(|s| !s.contains(' ') && (s.contains('/') || s.contains('.')))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Bullet points under a "Risks" heading in a plan response
+fn parse_plan_risks(response: &str) -> Vec<String> {
+    let mut risks = Vec::new();
+    let mut in_risks = false;
+    for line in response.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            in_risks = trimmed.trim_start_matches('#').trim().eq_ignore_ascii_case("risks");
+        } else if in_risks {
+            if let Some(risk) = trimmed.strip_prefix("- ").or_else(|| trimmed.strip_prefix("* ")) {
+                risks.push(risk.trim().to_string());
+            }
+        }
+    }
+    risks
+}
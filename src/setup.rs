@@ -0,0 +1,156 @@
+//! First-run setup
+//!
+//! The non-interactive parts of `safe-coder setup`: finding the agent CLIs
+//! the orchestrator can drive, the providers and models offered, and where
+//! shell completion scripts are installed. The wizard itself, which prompts
+//! and logs in, lives with the other command handlers in `main.rs`.
+
+use std::path::{Path, PathBuf};
+
+use clap_complete::Shell;
+
+use crate::config::{LlmProvider, OrchestratorConfig};
+
+/// Providers offered by the wizard, with a short description
+pub const PROVIDERS: &[(LlmProvider, &str)] = &[
+    (
+        LlmProvider::Anthropic,
+        "Anthropic (Claude Pro/Max login or API key)",
+    ),
+    (LlmProvider::OpenAI, "OpenAI (API key)"),
+    (LlmProvider::OpenRouter, "OpenRouter (API key)"),
+    (LlmProvider::GitHubCopilot, "GitHub Copilot (device login)"),
+    (LlmProvider::Ollama, "Ollama (local models)"),
+    (
+        LlmProvider::OpenAIGeneric,
+        "OpenAI-compatible server (vLLM, LiteLLM, LocalAI, ...)",
+    ),
+];
+
+/// What each permission preset auto-approves, in [`crate::permissions::PRESETS`] order
+pub const PRESET_DESCRIPTIONS: &[(&str, &str)] = &[
+    (
+        "safe",
+        "reads and searches run without asking; edits and commands ask",
+    ),
+    ("dev", "like safe, plus edits under src/ and tests/"),
+    ("full", "reads and edits anywhere; shell commands ask"),
+    ("yolo", "nothing asks (use in sandboxes only)"),
+];
+
+/// Models offered for `provider` by the model registry, the default first.
+/// Empty when the models depend on the server, so the user types one.
+pub fn suggested_models(provider: &LlmProvider) -> Vec<&'static str> {
+    if *provider == LlmProvider::OpenAIGeneric {
+        return Vec::new();
+    }
+    crate::llm::models::registry()
+        .choices(provider)
+        .iter()
+        .map(|choice| choice.id.as_str())
+        .collect()
+}
+
+/// Environment variable the provider's API key is read from, if any
+pub fn api_key_env(provider: &LlmProvider) -> Option<&'static str> {
+    match provider {
+        LlmProvider::Anthropic => Some("ANTHROPIC_API_KEY"),
+        LlmProvider::OpenAI => Some("OPENAI_API_KEY"),
+        LlmProvider::OpenRouter => Some("OPENROUTER_API_KEY"),
+        LlmProvider::GitHubCopilot => Some("GITHUB_COPILOT_TOKEN"),
+        LlmProvider::OpenAIGeneric => Some("OPENAI_GENERIC_API_KEY"),
+        LlmProvider::Ollama => None,
+    }
+}
+
+/// An agent CLI the orchestrator can hand tasks to
+#[derive(Debug, Clone)]
+pub struct DetectedCli {
+    /// Worker name, as used in `orchestrator.enabled_workers`
+    pub worker: &'static str,
+    /// Where it was found on PATH, None if it isn't installed
+    pub path: Option<PathBuf>,
+}
+
+/// Look up the Claude Code and Gemini CLIs at their configured paths
+pub fn detect_clis(config: &OrchestratorConfig) -> Vec<DetectedCli> {
+    [
+        ("claude", config.claude_cli_path.as_str()),
+        ("gemini", config.gemini_cli_path.as_str()),
+    ]
+    .into_iter()
+    .map(|(worker, program)| DetectedCli {
+        worker,
+        path: which::which(program).ok(),
+    })
+    .collect()
+}
+
+/// Point the orchestrator at the installed CLIs, falling back to
+/// safe-coder itself when neither is installed
+pub fn configure_workers(config: &mut OrchestratorConfig, clis: &[DetectedCli]) {
+    let installed: Vec<String> = clis
+        .iter()
+        .filter(|cli| cli.path.is_some())
+        .map(|cli| cli.worker.to_string())
+        .collect();
+    config.enabled_workers = if installed.is_empty() {
+        vec!["safe-coder".to_string()]
+    } else {
+        installed
+    };
+    config.default_worker = config.enabled_workers[0].clone();
+}
+
+/// File a completion script for `shell` is installed to, under `home`, and
+/// what the user still has to do for the shell to load it. None for shells
+/// without a per-user completions directory.
+pub fn completion_target(shell: Shell, home: &Path) -> Option<(PathBuf, Option<&'static str>)> {
+    match shell {
+        Shell::Bash => Some((
+            home.join(".local/share/bash-completion/completions/safe-coder"),
+            None,
+        )),
+        Shell::Zsh => Some((
+            home.join(".zfunc/_safe-coder"),
+            Some("Add `fpath=(~/.zfunc $fpath); autoload -Uz compinit && compinit` to ~/.zshrc"),
+        )),
+        Shell::Fish => Some((home.join(".config/fish/completions/safe-coder.fish"), None)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_configure_workers() {
+        let mut config = OrchestratorConfig::default();
+        let found = |worker, installed: bool| DetectedCli {
+            worker,
+            path: installed.then(|| PathBuf::from("/usr/bin").join(worker)),
+        };
+
+        configure_workers(
+            &mut config,
+            &[found("claude", false), found("gemini", true)],
+        );
+        assert_eq!(config.enabled_workers, vec!["gemini"]);
+        assert_eq!(config.default_worker, "gemini");
+
+        configure_workers(
+            &mut config,
+            &[found("claude", false), found("gemini", false)],
+        );
+        assert_eq!(config.enabled_workers, vec!["safe-coder"]);
+        assert_eq!(config.default_worker, "safe-coder");
+    }
+
+    #[test]
+    fn test_every_preset_is_described() {
+        for preset in crate::permissions::PRESETS {
+            assert!(PRESET_DESCRIPTIONS.iter().any(|(name, _)| name == preset));
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! AI help for shell mode: explaining failed commands and suggesting new ones
+//!
+//! Both use a plain completion without tools, so they are quick and never
+//! touch the project. The model answers in a fixed two-line format that is
+//! parsed here; anything unexpected falls back to the raw reply.
+
+use anyhow::{bail, Result};
+
+use crate::llm::{ContentBlock, LlmClient, Message};
+
+/// Most stderr sent along with a failed command
+pub const MAX_STDERR_CHARS: usize = 4000;
+
+const EXPLAIN_PROMPT: &str =
+    "You help a developer in their terminal. A shell command just failed. \
+Reply with exactly two lines and nothing else:\n\
+EXPLANATION: <one sentence on why it failed>\n\
+FIX: <a single shell command that fixes or works around it, or NONE>";
+
+const SUGGEST_PROMPT: &str = "You turn requests into shell commands. \
+Reply with exactly two lines and nothing else:\n\
+COMMAND: <a single shell command, using && or pipes if needed>\n\
+EXPLANATION: <one short sentence on what it does>\n\
+Prefer common, portable tools. Never suggest destructive commands unless explicitly asked.";
+
+/// Why a command failed and how to fix it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub explanation: String,
+    pub fix: Option<String>,
+}
+
+/// A command proposed for a natural-language request
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub command: String,
+    pub explanation: String,
+}
+
+/// Ask the model why `command` failed
+pub async fn explain_failure(
+    client: &dyn LlmClient,
+    command: &str,
+    exit_code: i32,
+    stderr: &str,
+    cwd: &str,
+) -> Result<Explanation> {
+    let stderr = tail(stderr, MAX_STDERR_CHARS);
+    let message = format!(
+        "OS: {}\nDirectory: {}\nCommand: {}\nExit code: {}\nStderr:\n```\n{}\n```",
+        std::env::consts::OS,
+        cwd,
+        command,
+        exit_code,
+        if stderr.trim().is_empty() {
+            "(empty)"
+        } else {
+            stderr
+        }
+    );
+    let reply = complete(client, EXPLAIN_PROMPT, message).await?;
+    Ok(parse_explanation(&reply))
+}
+
+/// Ask the model for a command that does what `request` describes
+pub async fn suggest_command(
+    client: &dyn LlmClient,
+    request: &str,
+    cwd: &str,
+) -> Result<Suggestion> {
+    let message = format!(
+        "OS: {}\nDirectory: {}\nRequest: {}",
+        std::env::consts::OS,
+        cwd,
+        request
+    );
+    let reply = complete(client, SUGGEST_PROMPT, message).await?;
+    match parse_suggestion(&reply) {
+        Some(suggestion) => Ok(suggestion),
+        None => bail!("The model did not suggest a command: {}", reply.trim()),
+    }
+}
+
+async fn complete(client: &dyn LlmClient, system_prompt: &str, message: String) -> Result<String> {
+    let response = client
+        .send_message_with_system(&[Message::user(message)], &[], Some(system_prompt))
+        .await?;
+    Ok(response
+        .message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Value of a `KEY: value` line, with any code formatting stripped
+fn field(reply: &str, key: &str) -> Option<String> {
+    reply.lines().find_map(|line| {
+        let value = line.trim().strip_prefix(key)?.strip_prefix(':')?.trim();
+        let value = value.trim_matches('`').trim();
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+fn parse_explanation(reply: &str) -> Explanation {
+    let fix = field(reply, "FIX").filter(|fix| !fix.eq_ignore_ascii_case("none"));
+    let explanation = field(reply, "EXPLANATION").unwrap_or_else(|| {
+        reply
+            .lines()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or("No explanation available")
+            .trim()
+            .to_string()
+    });
+    Explanation { explanation, fix }
+}
+
+fn parse_suggestion(reply: &str) -> Option<Suggestion> {
+    Some(Suggestion {
+        command: field(reply, "COMMAND")?,
+        explanation: field(reply, "EXPLANATION").unwrap_or_default(),
+    })
+}
+
+/// Last `max_chars` characters of `text`; the end of stderr has the error
+fn tail(text: &str, max_chars: usize) -> &str {
+    let skip = text.chars().count().saturating_sub(max_chars);
+    match text.char_indices().nth(skip) {
+        Some((index, _)) => &text[index..],
+        None => text,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_replies() {
+        let explanation = parse_explanation(
+            "EXPLANATION: The branch has no upstream.\nFIX: `git push -u origin main`",
+        );
+        assert_eq!(explanation.explanation, "The branch has no upstream.");
+        assert_eq!(explanation.fix.as_deref(), Some("git push -u origin main"));
+
+        let explanation = parse_explanation("EXPLANATION: Port 80 needs root.\nFIX: NONE");
+        assert_eq!(explanation.fix, None);
+        assert_eq!(
+            parse_explanation("Something odd happened.\n").explanation,
+            "Something odd happened."
+        );
+
+        let suggestion =
+            parse_suggestion("COMMAND: du -sh * | sort -h\nEXPLANATION: Sizes, largest last.")
+                .unwrap();
+        assert_eq!(suggestion.command, "du -sh * | sort -h");
+        assert!(parse_suggestion("I can't help with that.").is_none());
+    }
+
+    #[test]
+    fn test_tail() {
+        assert_eq!(tail("abcdef", 3), "def");
+        assert_eq!(tail("ab", 3), "ab");
+        assert_eq!(tail("ééé", 2), "éé");
+    }
+}
@@ -0,0 +1,267 @@
+//! Tab completion for shell mode
+//!
+//! The first word completes to shell built-ins and executables on `PATH`;
+//! later words complete to paths relative to the shell's working directory
+//! (directories only after `cd`). Spaces in completed paths are escaped with
+//! a backslash since commands run through `sh -c`.
+
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use rustyline::completion::{Completer, Pair};
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+
+/// Commands handled by the shell itself
+pub const BUILTINS: &[&str] = &[
+    "ai",
+    "ai-connect",
+    "ai-disconnect",
+    "ai-explain",
+    "ai-suggest",
+    "alias",
+    "cd",
+    "chat",
+    "clear",
+    "env",
+    "exit",
+    "export",
+    "fg",
+    "help",
+    "history",
+    "jobs",
+    "pwd",
+    "quit",
+    "unalias",
+];
+
+/// Completion helper for the shell's line editor
+pub struct ShellHelper {
+    cwd: PathBuf,
+    /// Executables on `PATH`, scanned on the first command completion
+    executables: OnceLock<Vec<String>>,
+}
+
+impl ShellHelper {
+    pub fn new(cwd: PathBuf) -> Self {
+        Self {
+            cwd,
+            executables: OnceLock::new(),
+        }
+    }
+
+    /// Follow the shell's `cd`
+    pub fn set_cwd(&mut self, cwd: PathBuf) {
+        self.cwd = cwd;
+    }
+
+    fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<Pair>) {
+        let line = &line[..pos];
+        let start = word_start(line);
+        let word = &line[start..];
+        let preceding = line[..start].trim();
+
+        // `!cmd` runs a shell command from chat mode
+        let (preceding, start, word) = match (preceding, word.strip_prefix('!')) {
+            ("", Some(rest)) => ("", start + 1, rest),
+            _ => (preceding, start, word),
+        };
+
+        let mut pairs = if preceding.is_empty() && !word.contains('/') {
+            self.complete_command(word)
+        } else {
+            let dirs_only = preceding == "cd";
+            self.complete_path(&word.replace("\\ ", " "), dirs_only)
+        };
+        pairs.sort_by(|a, b| a.display.cmp(&b.display));
+        pairs.dedup_by(|a, b| a.display == b.display);
+        (start, pairs)
+    }
+
+    fn complete_command(&self, prefix: &str) -> Vec<Pair> {
+        let executables = self.executables.get_or_init(path_executables);
+        BUILTINS
+            .iter()
+            .copied()
+            .chain(executables.iter().map(String::as_str))
+            .filter(|name| name.starts_with(prefix))
+            .map(|name| Pair {
+                display: name.to_string(),
+                replacement: format!("{} ", name),
+            })
+            .collect()
+    }
+
+    fn complete_path(&self, word: &str, dirs_only: bool) -> Vec<Pair> {
+        let (dir_part, prefix) = match word.rfind('/') {
+            Some(i) => word.split_at(i + 1),
+            None => ("", word),
+        };
+        let dir = if dir_part.is_empty() {
+            self.cwd.clone()
+        } else if let Some(rest) = dir_part.strip_prefix("~/") {
+            match dirs::home_dir() {
+                Some(home) => home.join(rest),
+                None => return Vec::new(),
+            }
+        } else if Path::new(dir_part).is_absolute() {
+            PathBuf::from(dir_part)
+        } else {
+            self.cwd.join(dir_part)
+        };
+
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.'))
+                {
+                    return None;
+                }
+                // Follow symlinks so linked directories complete with a slash
+                let is_dir = entry.path().is_dir();
+                if dirs_only && !is_dir {
+                    return None;
+                }
+                let escaped = format!("{}{}", dir_part, name).replace(' ', "\\ ");
+                Some(if is_dir {
+                    Pair {
+                        display: format!("{}/", name),
+                        replacement: format!("{}/", escaped),
+                    }
+                } else {
+                    Pair {
+                        display: name,
+                        replacement: format!("{} ", escaped),
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// Byte offset where the word under the cursor starts, skipping escaped spaces
+fn word_start(line: &str) -> usize {
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c.is_whitespace() {
+            start = i + c.len_utf8();
+        }
+    }
+    start
+}
+
+fn path_executables() -> Vec<String> {
+    let Some(path) = std::env::var_os("PATH") else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = std::env::split_paths(&path)
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter(|entry| is_executable(&entry.path()))
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    const EXTENSIONS: [&str; 5] = ["exe", "cmd", "bat", "com", "ps1"];
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| EXTENSIONS.iter().any(|e| ext.eq_ignore_ascii_case(e)))
+}
+
+impl Completer for ShellHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for ShellHelper {
+    type Hint = String;
+}
+
+impl Highlighter for ShellHelper {}
+
+impl Validator for ShellHelper {}
+
+impl Helper for ShellHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replacements(helper: &ShellHelper, line: &str) -> (usize, Vec<String>) {
+        let (start, pairs) = helper.candidates(line, line.len());
+        (start, pairs.into_iter().map(|p| p.replacement).collect())
+    }
+
+    #[test]
+    fn test_word_start() {
+        assert_eq!(word_start("git che"), 4);
+        assert_eq!(word_start("cat my\\ fi"), 4);
+        assert_eq!(word_start(""), 0);
+    }
+
+    #[test]
+    fn test_completes_builtins_and_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        std::fs::write(dir.path().join("my notes.txt"), "").unwrap();
+        std::fs::write(dir.path().join(".hidden"), "").unwrap();
+        let helper = ShellHelper::new(dir.path().to_path_buf());
+
+        let (_, commands) = replacements(&helper, "ai-");
+        assert_eq!(
+            commands,
+            vec!["ai-connect ", "ai-disconnect ", "ai-explain ", "ai-suggest "]
+        );
+
+        assert_eq!(replacements(&helper, "cd s"), (3, vec!["src/".to_string()]));
+        assert_eq!(
+            replacements(&helper, "cat src/m"),
+            (4, vec!["src/main.rs ".to_string()])
+        );
+        assert_eq!(
+            replacements(&helper, "cat my"),
+            (4, vec!["my\\ notes.txt ".to_string()])
+        );
+        // Directories only after cd, hidden files only when asked for
+        assert!(replacements(&helper, "cd m").1.is_empty());
+        assert_eq!(replacements(&helper, "cat .h").1, vec![".hidden "]);
+        assert!(!replacements(&helper, "cat ")
+            .1
+            .contains(&".hidden ".to_string()));
+    }
+}
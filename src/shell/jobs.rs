@@ -0,0 +1,223 @@
+//! Background jobs for shell mode
+//!
+//! A command ending in `&` runs in the background with its output going
+//! straight to the terminal. Jobs get small ids (`%1`, `%2`, ...) used by
+//! `fg` and `kill`, and finished jobs are reported before the next prompt.
+//! Each job gets its own process group so Ctrl+C in the foreground leaves
+//! it alone. Jobs still running when the shell exits are killed.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::{ExitStatus, Stdio};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use tokio::process::Child;
+
+/// A command running in the background
+pub struct Job {
+    pub id: usize,
+    pub command: String,
+    pub started: Instant,
+    child: Child,
+}
+
+impl Job {
+    pub fn pid(&self) -> Option<u32> {
+        self.child.id()
+    }
+
+    /// Wait for the job in the foreground; Ctrl+C kills it
+    pub async fn wait(mut self) -> Result<ExitStatus> {
+        tokio::select! {
+            status = self.child.wait() => Ok(status?),
+            _ = tokio::signal::ctrl_c() => {
+                let _ = self.child.start_kill();
+                Ok(self.child.wait().await?)
+            }
+        }
+    }
+}
+
+/// A job that finished since the last check
+#[derive(Debug, Clone, PartialEq)]
+pub struct FinishedJob {
+    pub id: usize,
+    pub command: String,
+    pub exit_code: i32,
+}
+
+impl FinishedJob {
+    /// Notification line, e.g. `[1]  Done       make test`
+    pub fn describe(&self) -> String {
+        let status = if self.exit_code == 0 {
+            "Done".to_string()
+        } else {
+            format!("Exit {}", self.exit_code)
+        };
+        format!("[{}]  {:<10} {}", self.id, status, self.command)
+    }
+}
+
+/// The shell's job table
+#[derive(Default)]
+pub struct Jobs {
+    jobs: Vec<Job>,
+}
+
+impl Jobs {
+    /// Start `command` in the background and return its job id
+    pub fn spawn(
+        &mut self,
+        command: &str,
+        cwd: &Path,
+        env: &HashMap<String, String>,
+    ) -> Result<&Job> {
+        let mut cmd = crate::platform::shell_command(command);
+        cmd.current_dir(cwd)
+            .envs(env)
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
+        #[cfg(unix)]
+        cmd.process_group(0);
+        let child = cmd
+            .spawn()
+            .with_context(|| format!("Failed to start background job: {}", command))?;
+
+        // Reuse the lowest free id, like other shells
+        let id = (1..)
+            .find(|id| self.jobs.iter().all(|job| job.id != *id))
+            .unwrap_or(1);
+        self.jobs.push(Job {
+            id,
+            command: command.to_string(),
+            started: Instant::now(),
+            child,
+        });
+        Ok(self.jobs.last().expect("job was just added"))
+    }
+
+    /// Remove and return jobs that have exited
+    pub fn reap(&mut self) -> Vec<FinishedJob> {
+        let mut finished = Vec::new();
+        self.jobs.retain_mut(|job| match job.child.try_wait() {
+            Ok(Some(status)) => {
+                finished.push(FinishedJob {
+                    id: job.id,
+                    command: job.command.clone(),
+                    exit_code: status.code().unwrap_or(1),
+                });
+                false
+            }
+            Ok(None) => true,
+            Err(_) => false,
+        });
+        finished.sort_by_key(|job| job.id);
+        finished
+    }
+
+    /// Running jobs as `(id, command, running for)`
+    pub fn running(&self) -> Vec<(usize, &str, Duration)> {
+        let mut jobs: Vec<_> = self
+            .jobs
+            .iter()
+            .map(|job| (job.id, job.command.as_str(), job.started.elapsed()))
+            .collect();
+        jobs.sort_by_key(|(id, _, _)| *id);
+        jobs
+    }
+
+    pub fn len(&self) -> usize {
+        self.jobs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jobs.is_empty()
+    }
+
+    /// Take a job out of the table to bring it to the foreground;
+    /// the most recent one when no id is given
+    pub fn take(&mut self, id: Option<usize>) -> Option<Job> {
+        let index = match id {
+            Some(id) => self.jobs.iter().position(|job| job.id == id)?,
+            None => self.jobs.len().checked_sub(1)?,
+        };
+        Some(self.jobs.remove(index))
+    }
+
+    /// Kill a job and wait for it to exit
+    pub async fn kill(&mut self, id: usize) -> Result<FinishedJob> {
+        let mut job = self
+            .take(Some(id))
+            .with_context(|| format!("kill: %{}: no such job", id))?;
+        job.child.kill().await?;
+        let status = job.child.wait().await?;
+        Ok(FinishedJob {
+            id,
+            command: job.command,
+            exit_code: status.code().unwrap_or(1),
+        })
+    }
+}
+
+/// Split a trailing `&` off a command, ignoring `&&`
+pub fn strip_background(input: &str) -> Option<&str> {
+    let command = input.trim_end().strip_suffix('&')?;
+    if command.ends_with('&') {
+        return None;
+    }
+    let command = command.trim_end();
+    (!command.is_empty()).then_some(command)
+}
+
+/// Parse a job reference: `%2` or `2`
+pub fn parse_job_id(arg: &str) -> Option<usize> {
+    arg.trim().trim_start_matches('%').parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_background() {
+        assert_eq!(strip_background("sleep 5 &"), Some("sleep 5"));
+        assert_eq!(strip_background("make&"), Some("make"));
+        assert_eq!(strip_background("make && make test"), None);
+        assert_eq!(strip_background("true &&"), None);
+        assert_eq!(strip_background("&"), None);
+        assert_eq!(parse_job_id("%3"), Some(3));
+        assert_eq!(parse_job_id("x"), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_jobs_lifecycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let env = HashMap::new();
+        let mut jobs = Jobs::default();
+
+        let quick = jobs.spawn("exit 3", dir.path(), &env).unwrap().id;
+        let slow = jobs.spawn("sleep 30", dir.path(), &env).unwrap().id;
+        assert_eq!((quick, slow), (1, 2));
+
+        let mut finished = Vec::new();
+        for _ in 0..100 {
+            finished = jobs.reap();
+            if !finished.is_empty() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(finished[0].describe(), "[1]  Exit 3     exit 3");
+
+        // Freed ids are reused
+        assert_eq!(jobs.spawn("sleep 30", dir.path(), &env).unwrap().id, 1);
+        assert_eq!(jobs.running().len(), 2);
+
+        jobs.kill(slow).await.unwrap();
+        assert!(jobs.kill(slow).await.is_err());
+        assert_eq!(jobs.take(None).map(|job| job.id), Some(1));
+        assert!(jobs.is_empty());
+    }
+}
@@ -1,30 +1,47 @@
 //! Interactive shell mode for safe-coder
 //!
 //! This module provides a standalone shell mode that can be used to run
-//! commands directly, with optional AI assistance when needed.
+//! commands directly, with optional AI assistance when needed. Input goes
+//! through a rustyline editor, giving line editing, Ctrl+R history search,
+//! tab completion and a history file that persists across sessions.
+
+pub mod assist;
+mod completion;
+pub mod jobs;
+pub mod rc;
 
 use anyhow::{Context, Result};
-use std::collections::VecDeque;
+use rustyline::error::ReadlineError;
+use rustyline::history::FileHistory;
+use rustyline::{CompletionType, Editor};
+use std::collections::BTreeMap;
 use std::env;
 use std::io::{self, Write};
 use std::path::PathBuf;
 
-
-
 use crate::config::Config;
+use crate::llm::{create_client, LlmClient};
+use crate::permissions::CommandAnalysis;
 use crate::session::Session;
 
+use completion::ShellHelper;
+use jobs::Jobs;
+use rc::ShellRc;
+
 /// Maximum number of commands to keep in history
 const MAX_HISTORY_SIZE: usize = 1000;
 
+/// Where shell history is kept between sessions
+fn history_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("safe-coder").join("shell_history"))
+}
+
 /// Shell mode for interactive command execution
 pub struct Shell {
     /// Current working directory
     cwd: PathBuf,
-    /// Command history
-    history: VecDeque<String>,
-    /// History position for navigation
-    history_pos: usize,
+    /// Line editor holding the command history
+    editor: Editor<ShellHelper, FileHistory>,
     /// Optional coding session for AI assistance
     session: Option<Session>,
     /// Configuration
@@ -33,22 +50,60 @@ pub struct Shell {
     last_exit_code: i32,
     /// Environment variables set in this shell
     env_vars: std::collections::HashMap<String, String>,
+    /// Aliases from the shellrc and the `alias` built-in
+    aliases: BTreeMap<String, String>,
+    /// Shellrc commands still to run when the shell starts
+    startup: Vec<String>,
+    /// Commands started with a trailing `&`
+    jobs: Jobs,
+    /// Explain failed commands with the LLM (`ai-explain`)
+    explain_errors: bool,
+    /// Client for `ai-explain` and `ai-suggest`, created on first use
+    assistant: Option<Box<dyn LlmClient>>,
 }
 
 impl Shell {
     /// Create a new shell instance
     pub async fn new(path: PathBuf) -> Result<Self> {
         let config = Config::load().unwrap_or_default();
-        let cwd = path.canonicalize().context("Failed to resolve path")?;
+        let cwd = crate::platform::canonicalize(&path).context("Failed to resolve path")?;
+
+        let editor_config = rustyline::Config::builder()
+            .max_history_size(MAX_HISTORY_SIZE)?
+            .history_ignore_dups(true)?
+            .completion_type(CompletionType::List)
+            .build();
+        let mut editor = Editor::with_config(editor_config)
+            .context("Failed to initialize line editor")?;
+        editor.set_helper(Some(ShellHelper::new(cwd.clone())));
+        if let Some(path) = history_path() {
+            // A missing file just means no history yet
+            let _ = editor.load_history(&path);
+        }
+
+        let rc = ShellRc::load().unwrap_or_else(|e| ShellRc {
+            warnings: vec![e.to_string()],
+            ..Default::default()
+        });
+        for warning in &rc.warnings {
+            eprintln!("\x1b[33m{}\x1b[0m", warning);
+        }
+        for (key, value) in &rc.exports {
+            env::set_var(key, value);
+        }
 
         Ok(Self {
             cwd,
-            history: VecDeque::with_capacity(MAX_HISTORY_SIZE),
-            history_pos: 0,
+            editor,
             session: None,
             config,
             last_exit_code: 0,
-            env_vars: std::collections::HashMap::new(),
+            env_vars: rc.exports.into_iter().collect(),
+            aliases: rc.aliases,
+            startup: rc.startup,
+            jobs: Jobs::default(),
+            explain_errors: false,
+            assistant: None,
         })
     }
 
@@ -113,16 +168,27 @@ impl Shell {
     pub async fn run(&mut self) -> Result<()> {
         self.print_welcome();
 
+        for command in std::mem::take(&mut self.startup) {
+            match self.execute_input(&command).await {
+                Ok(true) => return Ok(()),
+                Ok(false) => {}
+                Err(e) => {
+                    eprintln!("\x1b[31mshellrc:\x1b[0m {}: {}", command, e);
+                    self.last_exit_code = 1;
+                }
+            }
+        }
+
         loop {
-            // Print prompt
-            print!("{}", self.get_prompt());
-            io::stdout().flush()?;
+            self.report_finished_jobs();
 
-            // Read input
-            let mut input = String::new();
-            if io::stdin().read_line(&mut input).is_err() {
-                break;
-            }
+            // Read input; Ctrl+C clears the line, Ctrl+D exits
+            let prompt = self.get_prompt();
+            let input = match self.editor.readline(&prompt) {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(_) => break,
+            };
 
             let input = input.trim();
 
@@ -148,10 +214,20 @@ impl Shell {
             }
         }
 
+        if !self.jobs.is_empty() {
+            println!("Stopping {} background job(s)", self.jobs.len());
+        }
         println!("\nGoodbye!");
         Ok(())
     }
 
+    /// Print a line for each background job that finished since the last prompt
+    fn report_finished_jobs(&mut self) {
+        for job in self.jobs.reap() {
+            println!("{}", job.describe());
+        }
+    }
+
     /// Print welcome message
     fn print_welcome(&self) {
         println!(
@@ -165,10 +241,14 @@ impl Shell {
         println!("  \x1b[33mShell Commands:\x1b[0m");
         println!("    \x1b[32mcd <path>\x1b[0m        - Change directory (supports ~, relative, absolute)");
         println!("    \x1b[32mpwd\x1b[0m              - Print current working directory");
-        println!("    \x1b[32mhistory\x1b[0m          - Show command history");
+        println!("    \x1b[32mhistory\x1b[0m          - Show command history (Ctrl+R to search)");
         println!("    \x1b[32mclear\x1b[0m            - Clear the screen");
         println!("    \x1b[32mexport KEY=VAL\x1b[0m   - Set environment variable");
         println!("    \x1b[32menv\x1b[0m              - Show all environment variables");
+        println!("    \x1b[32malias NAME=CMD\x1b[0m   - Define an alias (unalias NAME to remove)");
+        println!("    \x1b[32m<command> &\x1b[0m      - Run in the background");
+        println!("    \x1b[32mjobs\x1b[0m             - List background jobs");
+        println!("    \x1b[32mfg [%N]\x1b[0m, \x1b[32mkill %N\x1b[0m - Wait for or stop a background job");
         println!("    \x1b[32mexit\x1b[0m, \x1b[32mquit\x1b[0m       - Exit the shell");
         println!();
         println!("  \x1b[33mAI Commands:\x1b[0m");
@@ -177,6 +257,9 @@ impl Shell {
         println!(
             "    \x1b[32mai <question>\x1b[0m    - Ask AI for help (requires ai-connect first)"
         );
+        println!("    \x1b[32m# <task>\x1b[0m         - Translate to a command; Enter runs it, Ctrl+C cancels");
+        println!("    \x1b[32mai-suggest \"...\"\x1b[0m  - Suggest a command for a task, then ask before running it");
+        println!("    \x1b[32mai-explain [on|off]\x1b[0m - Explain failed commands and suggest a fix");
         println!("    \x1b[32mchat\x1b[0m             - Enter interactive coding mode with tool execution");
         println!();
         println!("  \x1b[33mChat Mode (after running 'chat'):\x1b[0m");
@@ -186,30 +269,40 @@ impl Shell {
         println!("  \x1b[33mOther:\x1b[0m");
         println!("    \x1b[32mhelp\x1b[0m, \x1b[32m?\x1b[0m          - Show this help message");
         println!("    \x1b[32m<any command>\x1b[0m    - Run as shell command (ls, git, etc.)");
+        println!("    \x1b[32mTab\x1b[0m              - Complete commands and paths");
         println!();
     }
 
-    /// Add command to history
+    /// Add command to history and append it to the history file
     fn add_to_history(&mut self, cmd: &str) {
-        // Don't add empty or duplicate consecutive commands
-        if cmd.is_empty() {
+        // Duplicate consecutive commands are skipped by the editor
+        if !matches!(self.editor.add_history_entry(cmd), Ok(true)) {
             return;
         }
-        if let Some(last) = self.history.back() {
-            if last == cmd {
-                return;
+        if let Some(path) = history_path() {
+            if let Some(parent) = path.parent() {
+                let _ = std::fs::create_dir_all(parent);
+            }
+            if let Err(e) = self.editor.append_history(&path) {
+                tracing::debug!("Failed to save shell history: {}", e);
             }
         }
-
-        if self.history.len() >= MAX_HISTORY_SIZE {
-            self.history.pop_front();
-        }
-        self.history.push_back(cmd.to_string());
-        self.history_pos = self.history.len();
     }
 
     /// Execute input command
     async fn execute_input(&mut self, input: &str) -> Result<bool> {
+        // `# find the largest files` asks the LLM for a command
+        if let Some(intent) = input.strip_prefix('#') {
+            let intent = intent.trim();
+            if intent.is_empty() {
+                return Ok(false);
+            }
+            return self.run_intent(intent).await;
+        }
+
+        let expanded = rc::expand_alias(&self.aliases, input);
+        let input = expanded.as_deref().unwrap_or(input);
+
         // Parse built-in commands
         let parts: Vec<&str> = input.splitn(2, ' ').collect();
         let cmd = parts[0].to_lowercase();
@@ -238,6 +331,28 @@ impl Shell {
                 self.disconnect_ai();
             }
 
+            "ai-explain" => {
+                self.explain_errors = match args {
+                    "" => !self.explain_errors,
+                    "on" => true,
+                    "off" => false,
+                    _ => anyhow::bail!("Usage: ai-explain [on|off]"),
+                };
+                println!(
+                    "Explaining failed commands: {}",
+                    if self.explain_errors { "on" } else { "off" }
+                );
+            }
+
+            "ai-suggest" => {
+                let request = args.trim_matches('"').trim_matches('\'').trim();
+                if request.is_empty() {
+                    println!("Usage: ai-suggest \"<what you want to do>\"");
+                } else {
+                    return self.suggest_command(request).await;
+                }
+            }
+
             "ai" => {
                 if args.is_empty() {
                     println!("Usage: ai <question or request>");
@@ -267,9 +382,50 @@ impl Shell {
                 self.show_env();
             }
 
+            "alias" => {
+                self.handle_alias(args);
+            }
+
+            "jobs" => {
+                self.report_finished_jobs();
+                for (id, command, elapsed) in self.jobs.running() {
+                    println!("[{}]  Running    {} ({}s)", id, command, elapsed.as_secs());
+                }
+            }
+
+            "fg" => {
+                let id = if args.is_empty() {
+                    None
+                } else {
+                    Some(jobs::parse_job_id(args).context("Usage: fg [%job]")?)
+                };
+                let job = self.jobs.take(id).context("fg: no such job")?;
+                println!("\x1b[33m❯ {}\x1b[0m", job.command);
+                let status = job.wait().await?;
+                self.last_exit_code = status.code().unwrap_or(1);
+            }
+
+            // `kill %N` manages jobs; plain `kill PID` is the system command
+            "kill" if args.starts_with('%') => {
+                let id = jobs::parse_job_id(args).context("Usage: kill %job")?;
+                let job = self.jobs.kill(id).await?;
+                println!("[{}]  Killed     {}", job.id, job.command);
+            }
+
+            "unalias" => {
+                if self.aliases.remove(args).is_none() {
+                    println!("unalias: {}: not found", args);
+                }
+            }
+
             _ => {
-                // Execute as shell command
-                self.execute_shell_command(input).await?;
+                if let Some(command) = jobs::strip_background(input) {
+                    let job = self.jobs.spawn(command, &self.cwd, &self.env_vars)?;
+                    println!("[{}] {}", job.id, job.pid().unwrap_or_default());
+                } else {
+                    // Execute as shell command
+                    self.execute_shell_command(input).await?;
+                }
             }
         }
 
@@ -278,32 +434,31 @@ impl Shell {
 
     /// Change directory
     fn change_directory(&mut self, path: &str) -> Result<()> {
-        let new_path = if path.is_empty() || path == "~" {
-            dirs::home_dir().context("Could not find home directory")?
-        } else if path.starts_with('~') {
-            let home = dirs::home_dir().context("Could not find home directory")?;
-            home.join(&path[2..])
-        } else if path.starts_with('/') {
-            PathBuf::from(path)
+        let new_path = if path.is_empty() || path.starts_with('~') {
+            crate::platform::expand_home(if path.is_empty() { "~" } else { path })
+                .context("Could not find home directory")?
         } else {
+            // join() keeps absolute paths as they are, on every platform
             self.cwd.join(path)
         };
 
-        let canonical = new_path
-            .canonicalize()
+        let canonical = crate::platform::canonicalize(&new_path)
             .with_context(|| format!("cd: {}: No such directory", path))?;
 
         if !canonical.is_dir() {
             anyhow::bail!("cd: {}: Not a directory", path);
         }
 
+        if let Some(helper) = self.editor.helper_mut() {
+            helper.set_cwd(canonical.clone());
+        }
         self.cwd = canonical;
         Ok(())
     }
 
     /// Show command history
     fn show_history(&self) {
-        for (i, cmd) in self.history.iter().enumerate() {
+        for (i, cmd) in self.editor.history().iter().enumerate() {
             println!("{:5}  {}", i + 1, cmd);
         }
     }
@@ -382,19 +537,18 @@ impl Shell {
         println!("Type 'exit' or 'shell' to return to shell mode.\n");
 
         loop {
-            print!("\x1b[35mchat>\x1b[0m ");
-            io::stdout().flush()?;
-
-            let mut input = String::new();
-            if io::stdin().read_line(&mut input).is_err() {
-                break;
-            }
+            let input = match self.editor.readline("\x1b[35mchat>\x1b[0m ") {
+                Ok(line) => line,
+                Err(ReadlineError::Interrupted) => continue,
+                Err(_) => break,
+            };
 
             let input = input.trim();
 
             if input.is_empty() {
                 continue;
             }
+            self.add_to_history(input);
 
             if input == "exit" || input == "shell" || input == "quit" {
                 println!("\n\x1b[1;36m━━━ Returning to Shell Mode ━━━\x1b[0m\n");
@@ -431,9 +585,7 @@ impl Shell {
         println!("\x1b[33m❯ {}\x1b[0m", command);
         
         // Use tokio Command for async execution with piped output
-        let mut child = tokio::process::Command::new("sh")
-            .arg("-c")
-            .arg(command)
+        let mut child = crate::platform::shell_command(command)
             .current_dir(&self.cwd)
             .envs(&self.env_vars)
             .stdout(std::process::Stdio::piped())
@@ -453,6 +605,8 @@ impl Shell {
         
         let mut stdout_line = String::new();
         let mut stderr_line = String::new();
+        // Kept for ai-explain
+        let mut stderr_output = String::new();
         
         // Stream output in real-time using select! to handle both streams
         loop {
@@ -475,6 +629,7 @@ impl Shell {
                             // Print stderr in red
                             print!("\x1b[31m{}\x1b[0m", stderr_line);
                             io::stdout().flush()?;
+                            stderr_output.push_str(&stderr_line);
                             stderr_line.clear();
                         }
                         Err(_) => {},
@@ -488,16 +643,19 @@ impl Shell {
                         io::stdout().flush()?;
                         stdout_line.clear();
                     }
-                    while let Ok(n) = stderr_reader.read_line(&mut stderr_line).await {
-                        if n == 0 { break; }
-                        print!("\x1b[31m{}\x1b[0m", stderr_line);
-                        io::stdout().flush()?;
-                        stderr_line.clear();
-                    }
                     break;
                 }
             }
         }
+
+        // Drain stderr, which may still have output after stdout closed
+        while let Ok(n) = stderr_reader.read_line(&mut stderr_line).await {
+            if n == 0 { break; }
+            print!("\x1b[31m{}\x1b[0m", stderr_line);
+            io::stdout().flush()?;
+            stderr_output.push_str(&stderr_line);
+            stderr_line.clear();
+        }
         
         // Get the final exit status
         let status = child.wait().await?;
@@ -506,11 +664,98 @@ impl Shell {
         // Show exit status if command failed
         if !status.success() {
             println!("\x1b[31m[Exit status: {}]\x1b[0m", self.last_exit_code);
+            if self.explain_errors {
+                self.explain_failure(command, &stderr_output).await;
+            }
         }
 
         Ok(())
     }
 
+    /// LLM client for ai-explain and ai-suggest
+    async fn assistant(&mut self) -> Result<&dyn LlmClient> {
+        if self.assistant.is_none() {
+            self.assistant = Some(create_client(&self.config).await?);
+        }
+        Ok(self.assistant.as_deref().expect("assistant was just created"))
+    }
+
+    /// Print a one-line explanation and suggested fix for a failed command
+    async fn explain_failure(&mut self, command: &str, stderr: &str) {
+        let exit_code = self.last_exit_code;
+        let cwd = self.cwd.display().to_string();
+        let result = match self.assistant().await {
+            Ok(client) => assist::explain_failure(client, command, exit_code, stderr, &cwd).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(explanation) => {
+                println!("\x1b[36m💡 {}\x1b[0m", explanation.explanation);
+                if let Some(fix) = explanation.fix {
+                    println!("   Try: \x1b[32m{}\x1b[0m", fix);
+                }
+            }
+            Err(e) => println!("\x1b[33mai-explain: {}\x1b[0m", e),
+        }
+    }
+
+    /// Propose a command for a natural-language request and run it if confirmed
+    async fn suggest_command(&mut self, request: &str) -> Result<bool> {
+        let suggestion = self.translate(request).await?;
+
+        println!("  \x1b[1;32m{}\x1b[0m", suggestion.command);
+        let answer = match self.editor.readline("Run it? [y/N/e(dit)] ") {
+            Ok(answer) => answer.trim().to_lowercase(),
+            Err(_) => return Ok(false),
+        };
+        let command = match answer.as_str() {
+            "y" | "yes" => suggestion.command,
+            "e" | "edit" => match self
+                .editor
+                .readline_with_initial("\x1b[33m❯\x1b[0m ", (&suggestion.command, ""))
+            {
+                Ok(edited) if !edited.trim().is_empty() => edited.trim().to_string(),
+                _ => return Ok(false),
+            },
+            _ => return Ok(false),
+        };
+
+        self.add_to_history(&command);
+        Box::pin(self.execute_input(&command)).await
+    }
+
+    /// Translate a `# intent` line and put the command on the prompt;
+    /// Enter runs it (after any edits), Ctrl+C drops it
+    async fn run_intent(&mut self, intent: &str) -> Result<bool> {
+        let suggestion = self.translate(intent).await?;
+        let command = match self
+            .editor
+            .readline_with_initial("\x1b[33m❯\x1b[0m ", (&suggestion.command, ""))
+        {
+            Ok(command) if !command.trim().is_empty() => command.trim().to_string(),
+            _ => return Ok(false),
+        };
+
+        self.add_to_history(&command);
+        Box::pin(self.execute_input(&command)).await
+    }
+
+    /// Ask the LLM for a command, printing what it does and any risks
+    async fn translate(&mut self, request: &str) -> Result<assist::Suggestion> {
+        println!("\x1b[36m🤖 Thinking...\x1b[0m");
+        let cwd = self.cwd.display().to_string();
+        let suggestion = assist::suggest_command(self.assistant().await?, request, &cwd).await?;
+
+        if !suggestion.explanation.is_empty() {
+            println!("  {}", suggestion.explanation);
+        }
+        let analysis = CommandAnalysis::analyze(&suggestion.command);
+        if !analysis.categories.is_empty() {
+            println!("  \x1b[33m⚠ This command {}\x1b[0m", analysis.summary());
+        }
+        Ok(suggestion)
+    }
+
     /// Handle export command
     fn handle_export(&mut self, args: &str) -> Result<()> {
         if args.is_empty() {
@@ -538,6 +783,27 @@ impl Shell {
         Ok(())
     }
 
+    /// Handle alias command: list, show one, or define `NAME=VALUE`
+    fn handle_alias(&mut self, args: &str) {
+        if args.is_empty() || !args.contains('=') {
+            let name = (!args.is_empty()).then_some(args);
+            let listing = rc::list_aliases(&self.aliases, name);
+            if !listing.is_empty() {
+                println!("{}", listing);
+            } else if let Some(name) = name {
+                println!("alias: {}: not found", name);
+            }
+            return;
+        }
+
+        match rc::parse_assignment(args) {
+            Some((name, value)) => {
+                self.aliases.insert(name, value);
+            }
+            None => println!("Usage: alias NAME=VALUE"),
+        }
+    }
+
     /// Show environment variables
     fn show_env(&self) {
         for (key, value) in env::vars() {
@@ -548,6 +814,8 @@ impl Shell {
 
 /// Run the shell mode
 pub async fn run_shell(path: PathBuf, connect_ai: bool) -> Result<()> {
+    // Ctrl+C interrupts the foreground command or line, not the shell
+    let _interrupts = crate::shutdown::own_interrupts();
     let mut shell = Shell::new(path).await?;
 
     if connect_ai {
@@ -0,0 +1,171 @@
+//! `~/.config/safe-coder/shellrc`: aliases, exports and startup commands
+//!
+//! The file is read line by line when `safe-coder shell` starts:
+//!
+//! ```text
+//! # comments and blank lines are ignored
+//! alias gs='git status'
+//! export RUST_LOG=debug
+//! cd ~/work/app
+//! ```
+//!
+//! `alias` and `export` lines take effect before anything runs; every other
+//! line is a startup command, run in order once the shell is up.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+/// Parsed shellrc
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ShellRc {
+    pub aliases: BTreeMap<String, String>,
+    pub exports: Vec<(String, String)>,
+    pub startup: Vec<String>,
+    /// Lines that could not be parsed, with their line numbers
+    pub warnings: Vec<String>,
+}
+
+impl ShellRc {
+    /// Location of the shellrc
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("safe-coder").join("shellrc"))
+    }
+
+    /// Load the shellrc; a missing file gives an empty one
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path().filter(|p| p.exists()) else {
+            return Ok(Self::default());
+        };
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut rc = Self::default();
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (command, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let parsed = match command {
+                "alias" => parse_assignment(args).map(|(name, value)| {
+                    rc.aliases.insert(name, value);
+                }),
+                "export" => parse_assignment(args).map(|pair| rc.exports.push(pair)),
+                _ => {
+                    rc.startup.push(line.to_string());
+                    Some(())
+                }
+            };
+            if parsed.is_none() {
+                rc.warnings.push(format!(
+                    "shellrc line {}: expected `{} NAME=VALUE`",
+                    number + 1,
+                    command
+                ));
+            }
+        }
+        rc
+    }
+}
+
+/// Parse `NAME=VALUE`, dropping one layer of matching quotes around the value
+pub fn parse_assignment(args: &str) -> Option<(String, String)> {
+    let (name, value) = args.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    let value = value.trim();
+    let value = ['\'', '"']
+        .iter()
+        .find_map(|q| value.strip_prefix(*q)?.strip_suffix(*q))
+        .unwrap_or(value);
+    Some((name.to_string(), value.to_string()))
+}
+
+/// Replace a leading alias in `input`, following aliases of aliases.
+/// Returns `None` when the first word is not an alias.
+pub fn expand_alias(aliases: &BTreeMap<String, String>, input: &str) -> Option<String> {
+    let mut expanded = input.to_string();
+    let mut seen = Vec::new();
+    loop {
+        let (word, rest) = match expanded.split_once(char::is_whitespace) {
+            Some((word, rest)) => (word, Some(rest)),
+            None => (expanded.as_str(), None),
+        };
+        // Stop at a word that was already expanded, so `alias ls='ls -G'` works
+        if seen.iter().any(|s| s == word) {
+            break;
+        }
+        let Some(value) = aliases.get(word) else {
+            break;
+        };
+        seen.push(word.to_string());
+        expanded = match rest {
+            Some(rest) => format!("{} {}", value, rest),
+            None => value.clone(),
+        };
+    }
+    (!seen.is_empty()).then_some(expanded)
+}
+
+/// Output of the `alias` built-in: every alias, or the one asked for
+pub fn list_aliases(aliases: &BTreeMap<String, String>, name: Option<&str>) -> String {
+    aliases
+        .iter()
+        .filter(|(alias, _)| name.is_none_or(|n| n == alias.as_str()))
+        .map(|(alias, value)| format!("alias {}='{}'", alias, value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_shellrc() {
+        let rc = ShellRc::parse(
+            "# setup\n\
+             alias gs='git status'\n\
+             alias ll=\"ls -la\"\n\
+             export RUST_LOG=debug\n\
+             \n\
+             cd ~/work\n\
+             alias broken\n",
+        );
+        assert_eq!(rc.aliases["gs"], "git status");
+        assert_eq!(rc.aliases["ll"], "ls -la");
+        assert_eq!(
+            rc.exports,
+            vec![("RUST_LOG".to_string(), "debug".to_string())]
+        );
+        assert_eq!(rc.startup, vec!["cd ~/work"]);
+        assert_eq!(rc.warnings.len(), 1);
+        assert!(rc.warnings[0].starts_with("shellrc line 7"));
+    }
+
+    #[test]
+    fn test_expand_alias() {
+        let aliases: BTreeMap<String, String> =
+            [("gs", "git status"), ("ls", "ls -G"), ("l", "ls -la")]
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+
+        assert_eq!(
+            expand_alias(&aliases, "gs --short").as_deref(),
+            Some("git status --short")
+        );
+        assert_eq!(
+            expand_alias(&aliases, "l src").as_deref(),
+            Some("ls -G -la src")
+        );
+        assert_eq!(expand_alias(&aliases, "git gs"), None);
+    }
+}
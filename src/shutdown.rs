@@ -0,0 +1,234 @@
+//! Graceful shutdown
+//!
+//! SIGINT and SIGTERM stop the running command instead of killing the
+//! process mid-write. `run` drops the command, which cancels in-flight LLM
+//! calls and kills child processes spawned with `kill_on_drop`, then runs
+//! the cleanups components registered with `register`: disconnecting MCP
+//! servers, stopping orchestration runs and removing their worktrees, saving
+//! server sessions and restoring the terminal. Cleanups run newest first, all within
+//! `DRAIN_TIMEOUT`; a second signal exits at once.
+//!
+//! Interactive loops that handle Ctrl+C themselves, like the line-based
+//! shell, hold an `InterruptGuard` so SIGINT is left to them.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+
+use anyhow::Result;
+use tokio::sync::watch;
+
+/// Longest time cleanups get before the process exits anyway
+pub const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Cleanup = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()> + Send>> + Send>;
+
+struct Coordinator {
+    /// Signal number once a shutdown was requested, 0 before
+    signal: watch::Sender<i32>,
+    cleanups: Mutex<Vec<(u64, String, Cleanup)>>,
+    next_id: AtomicU64,
+}
+
+static COORDINATOR: LazyLock<Coordinator> = LazyLock::new(|| Coordinator {
+    signal: watch::channel(0).0,
+    cleanups: Mutex::new(Vec::new()),
+    next_id: AtomicU64::new(1),
+});
+
+/// Keeps a cleanup registered; dropping it unregisters the cleanup, for
+/// resources released normally
+#[must_use = "the cleanup is unregistered when this is dropped"]
+pub struct Registration(u64);
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        if let Ok(mut cleanups) = COORDINATOR.cleanups.lock() {
+            cleanups.retain(|(id, _, _)| *id != self.0);
+        }
+    }
+}
+
+static INTERRUPT_OWNERS: AtomicUsize = AtomicUsize::new(0);
+
+/// While held, SIGINT doesn't shut down; SIGTERM still does
+#[must_use = "SIGINT shuts down again when this is dropped"]
+pub struct InterruptGuard(());
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        INTERRUPT_OWNERS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Leave SIGINT to the caller, which handles Ctrl+C itself
+pub fn own_interrupts() -> InterruptGuard {
+    INTERRUPT_OWNERS.fetch_add(1, Ordering::SeqCst);
+    InterruptGuard(())
+}
+
+/// Run `cleanup` if the process shuts down while the registration is held
+pub fn register<F, Fut>(name: impl Into<String>, cleanup: F) -> Registration
+where
+    F: FnOnce() -> Fut + Send + 'static,
+    Fut: Future<Output = ()> + Send + 'static,
+{
+    let id = COORDINATOR.next_id.fetch_add(1, Ordering::Relaxed);
+    let cleanup: Cleanup = Box::new(move || Box::pin(cleanup()));
+    if let Ok(mut cleanups) = COORDINATOR.cleanups.lock() {
+        cleanups.push((id, name.into(), cleanup));
+    }
+    Registration(id)
+}
+
+/// Ask the running command to stop, as a signal would
+pub fn request(signal: i32) {
+    COORDINATOR.signal.send_if_modified(|current| {
+        let first = *current == 0;
+        if first {
+            *current = signal;
+        }
+        first
+    });
+}
+
+pub fn is_requested() -> bool {
+    *COORDINATOR.signal.borrow() != 0
+}
+
+/// Resolves once a shutdown was requested
+pub async fn requested() {
+    let mut signal = COORDINATOR.signal.subscribe();
+    let _ = signal.wait_for(|s| *s != 0).await;
+}
+
+/// Run spawned work until it finishes or a shutdown is requested. `run`
+/// only drops the command, so tasks spawned off it that own child processes
+/// use this to be dropped, and kill them, too.
+pub async fn until_requested<F: Future>(task: F) -> Option<F::Output> {
+    tokio::select! {
+        output = task => Some(output),
+        _ = requested() => None,
+    }
+}
+
+/// Run an entry point until it finishes or a signal arrives. After a signal
+/// the command is dropped, cleanups run and the process exits with 128 plus
+/// the signal number.
+pub async fn run<F>(command: F) -> Result<()>
+where
+    F: Future<Output = Result<()>>,
+{
+    listen_for_signals();
+    let mut command = Box::pin(command);
+    tokio::select! {
+        result = &mut command => return result,
+        _ = requested() => {}
+    }
+
+    // Cleanups of what the command releases as it's dropped are taken first
+    let cleanups = std::mem::take(
+        &mut *COORDINATOR
+            .cleanups
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()),
+    );
+    drop(command);
+    drain(cleanups).await;
+
+    std::process::exit(128 + *COORDINATOR.signal.borrow());
+}
+
+async fn drain(cleanups: Vec<(u64, String, Cleanup)>) {
+    if cleanups.is_empty() {
+        return;
+    }
+    tracing::info!("Shutting down: {} cleanup(s)", cleanups.len());
+    let all = async {
+        for (_, name, cleanup) in cleanups.into_iter().rev() {
+            tracing::debug!("Shutdown cleanup: {}", name);
+            cleanup().await;
+        }
+    };
+    let mut second_signal = COORDINATOR.signal.subscribe();
+    tokio::select! {
+        _ = all => tracing::info!("Shutdown complete"),
+        _ = tokio::time::sleep(DRAIN_TIMEOUT) => {
+            tracing::warn!("Shutdown cleanups took over {:?}; exiting anyway", DRAIN_TIMEOUT);
+        }
+        // `request` ignores later signals, so the listener flags them here
+        _ = second_signal.changed() => tracing::warn!("Second signal; exiting without cleanup"),
+    }
+}
+
+/// Turn SIGINT and SIGTERM into shutdown requests; a second one exits
+fn listen_for_signals() {
+    tokio::spawn(async {
+        loop {
+            let signal = next_signal().await;
+            if signal == 2 && INTERRUPT_OWNERS.load(Ordering::SeqCst) > 0 {
+                continue;
+            }
+            if is_requested() {
+                // Wake `drain`, which exits without waiting for the rest
+                COORDINATOR.signal.send_modify(|_| {});
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                std::process::exit(128 + signal);
+            }
+            request(signal);
+        }
+    });
+}
+
+#[cfg(unix)]
+async fn next_signal() -> i32 {
+    use tokio::signal::unix::{signal, SignalKind};
+    let (Ok(mut interrupt), Ok(mut terminate)) = (
+        signal(SignalKind::interrupt()),
+        signal(SignalKind::terminate()),
+    ) else {
+        return std::future::pending().await;
+    };
+    tokio::select! {
+        _ = interrupt.recv() => 2,
+        _ = terminate.recv() => 15,
+    }
+}
+
+#[cfg(not(unix))]
+async fn next_signal() -> i32 {
+    if tokio::signal::ctrl_c().await.is_err() {
+        return std::future::pending().await;
+    }
+    2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_registration_drop_unregisters() {
+        let ran = Arc::new(Mutex::new(false));
+        let flag = ran.clone();
+        let registration = register("test", move || async move {
+            *flag.lock().unwrap() = true;
+        });
+        let id = registration.0;
+        let registered = |id| {
+            COORDINATOR
+                .cleanups
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|(i, _, _)| *i == id)
+        };
+        assert!(registered(id));
+        drop(registration);
+        assert!(!registered(id));
+        assert!(!*ran.lock().unwrap());
+    }
+}
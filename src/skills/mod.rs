@@ -1,8 +1,15 @@
 //! Skills System
 //!
 //! Skills are loadable knowledge modules that inject specialized context
-//! into AI conversations. They can be triggered by file patterns or
-//! explicitly loaded via the /skill command.
+//! into AI conversations. They are activated automatically when the session
+//! reads or edits a file matching one of their triggers, or explicitly with
+//! `/skill on <name>`. `/skill off <name>` deactivates a skill and keeps it
+//! from being re-triggered.
+//!
+//! Active skills share a token budget (`[skills] token_budget`). When they
+//! don't all fit, skills are kept in priority order - explicitly loaded, then
+//! file-triggered, then built-in - and lower-priority skills are summarized
+//! or left out of the prompt.
 //!
 //! Skills are markdown files with optional YAML frontmatter:
 //! ```markdown
@@ -19,11 +26,61 @@
 //! ...
 //! ```
 
+pub mod packs;
+
+pub use packs::SkillPackManager;
+
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+/// Markdown files in skill directories (and packs) that aren't skills
+const NON_SKILL_FILES: [&str; 4] = ["readme", "license", "changelog", "contributing"];
+
+/// Rough characters-per-token ratio for skill markdown
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Maximum characters kept from a skill's body when it is summarized
+const SUMMARY_MAX_CHARS: usize = 400;
+
+/// Skills configuration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SkillsConfig {
+    /// Token budget for active skills in the system prompt (0 = unlimited)
+    #[serde(default = "default_token_budget")]
+    pub token_budget: usize,
+}
+
+fn default_token_budget() -> usize {
+    4000
+}
+
+impl Default for SkillsConfig {
+    fn default() -> Self {
+        Self {
+            token_budget: default_token_budget(),
+        }
+    }
+}
+
+/// Why a skill is active, used to decide what to keep when active skills
+/// exceed the token budget (higher is kept first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SkillPriority {
+    /// A built-in skill activated by a file trigger
+    Builtin,
+    /// A skill file activated by a file trigger
+    Triggered,
+    /// Loaded by the user with `/skill on`
+    Explicit,
+}
+
+/// Estimate the token count of a piece of skill text
+fn estimate_tokens(text: &str) -> usize {
+    text.len().div_ceil(CHARS_PER_TOKEN)
+}
+
 /// A loaded skill with its content and metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Skill {
@@ -106,6 +163,39 @@ impl Skill {
         result.push_str(&self.content);
         result
     }
+
+    /// Estimated number of tokens this skill adds to the prompt
+    pub fn estimated_tokens(&self) -> usize {
+        estimate_tokens(&self.to_prompt_injection())
+    }
+
+    /// A shortened injection used when the full skill doesn't fit the budget:
+    /// the description, headings and the start of the body
+    pub fn to_summary_injection(&self) -> String {
+        let mut result = format!("# Skill: {} (summarized)\n\n", self.name);
+        if let Some(ref desc) = self.description {
+            result.push_str(&format!("*{}*\n\n", desc));
+        }
+
+        let body = self.content.trim();
+        let excerpt = crate::utils::truncate_str(body, SUMMARY_MAX_CHARS);
+        result.push_str(excerpt);
+        if excerpt.len() < body.len() {
+            result.push_str("\n...");
+            let headings: Vec<&str> = body[excerpt.len()..]
+                .lines()
+                .map(str::trim)
+                .filter(|line| line.starts_with('#'))
+                .collect();
+            if !headings.is_empty() {
+                result.push_str("\n\nAlso covers:\n");
+                for heading in headings {
+                    result.push_str(&format!("- {}\n", heading.trim_start_matches('#').trim()));
+                }
+            }
+        }
+        result
+    }
 }
 
 /// Skill manager that handles loading and activation of skills
@@ -114,6 +204,10 @@ pub struct SkillManager {
     skills: HashMap<String, Skill>,
     /// Search paths for skill files
     search_paths: Vec<PathBuf>,
+    /// Skills turned off by the user, which triggers won't re-activate
+    suppressed: HashSet<String>,
+    /// Skills turned on explicitly by the user
+    explicit: HashSet<String>,
 }
 
 impl SkillManager {
@@ -122,9 +216,18 @@ impl SkillManager {
         Self {
             skills: HashMap::new(),
             search_paths: Vec::new(),
+            suppressed: HashSet::new(),
+            explicit: HashSet::new(),
         }
     }
 
+    /// Register the built-in skills (loaded skills with the same name replace them)
+    pub fn register_builtins(&mut self) {
+        self.register(builtin::rust_skill());
+        self.register(builtin::react_skill());
+        self.register(builtin::python_skill());
+    }
+
     /// Add a search path for skill files
     pub fn add_search_path(&mut self, path: PathBuf) {
         if !self.search_paths.contains(&path) {
@@ -147,13 +250,26 @@ impl SkillManager {
         manager
     }
 
-    /// Load all skills from search paths
+    /// Load all skills from search paths, including installed skill packs
+    /// (subdirectories, with skills at their root or in a `skills/` folder)
     pub async fn load_all(&mut self) -> Result<usize> {
         let mut loaded = 0;
 
         for search_path in self.search_paths.clone() {
             if search_path.exists() && search_path.is_dir() {
                 loaded += self.load_from_directory(&search_path).await?;
+
+                let packs = std::fs::read_dir(&search_path)?
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| path.is_dir());
+                for pack in packs {
+                    if pack.file_name().is_some_and(|n| n.to_string_lossy().starts_with('.')) {
+                        continue;
+                    }
+                    loaded += self.load_from_directory(&pack).await?;
+                    loaded += self.load_from_directory(&pack.join("skills")).await?;
+                }
             }
         }
 
@@ -171,7 +287,11 @@ impl SkillManager {
 
         for entry in entries.flatten() {
             let path = entry.path();
-            if path.is_file() {
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            if path.is_file() && !NON_SKILL_FILES.contains(&stem.as_str()) {
                 if let Some(ext) = path.extension() {
                     if ext == "md" || ext == "markdown" {
                         if let Ok(skill) = self.load_skill_file(&path).await {
@@ -204,16 +324,20 @@ impl SkillManager {
     pub fn activate(&mut self, name: &str) -> bool {
         if let Some(skill) = self.skills.get_mut(name) {
             skill.active = true;
+            self.suppressed.remove(name);
+            self.explicit.insert(name.to_string());
             true
         } else {
             false
         }
     }
 
-    /// Deactivate a skill by name
+    /// Deactivate a skill by name; it stays off until activated again
     pub fn deactivate(&mut self, name: &str) -> bool {
         if let Some(skill) = self.skills.get_mut(name) {
             skill.active = false;
+            self.suppressed.insert(name.to_string());
+            self.explicit.remove(name);
             true
         } else {
             false
@@ -233,34 +357,88 @@ impl SkillManager {
             .collect()
     }
 
-    /// Auto-activate skills based on files being worked on
-    pub fn auto_activate_for_files(&mut self, file_paths: &[&str]) {
-        for (_, skill) in self.skills.iter_mut() {
-            for file_path in file_paths {
-                if skill.matches_file(file_path) {
-                    skill.active = true;
-                    break;
-                }
+    /// Auto-activate skills based on files being worked on, returning the
+    /// names of newly activated skills (sorted). Skills the user turned off
+    /// are skipped.
+    pub fn auto_activate_for_files(&mut self, file_paths: &[&str]) -> Vec<String> {
+        let mut activated: Vec<String> = file_paths
+            .iter()
+            .flat_map(|path| self.get_matching(path))
+            .filter(|skill| !skill.active && !self.suppressed.contains(&skill.name))
+            .map(|skill| skill.name.clone())
+            .collect();
+        activated.sort();
+        activated.dedup();
+        for name in &activated {
+            if let Some(skill) = self.skills.get_mut(name) {
+                skill.active = true;
             }
         }
+        activated
     }
 
-    /// List all available skills
+    /// List all available skills (sorted by name)
     pub fn list(&self) -> Vec<&Skill> {
-        self.skills.values().collect()
+        let mut skills: Vec<&Skill> = self.skills.values().collect();
+        skills.sort_by(|a, b| a.name.cmp(&b.name));
+        skills
     }
 
-    /// Get combined content of all active skills for prompt injection
-    pub fn get_active_skills_prompt(&self) -> Option<String> {
-        let active: Vec<_> = self.get_active();
+    /// Priority of a skill when trimming to the token budget
+    pub fn priority(&self, skill: &Skill) -> SkillPriority {
+        if self.explicit.contains(&skill.name) {
+            SkillPriority::Explicit
+        } else if skill.source_path.is_some() {
+            SkillPriority::Triggered
+        } else {
+            SkillPriority::Builtin
+        }
+    }
+
+    /// Get combined content of active skills for prompt injection, fitted to
+    /// `token_budget` (0 = unlimited). Skills are taken in priority order; one
+    /// that doesn't fit is summarized, and if even the summary doesn't fit it
+    /// is only listed by name.
+    pub fn get_active_skills_prompt(&self, token_budget: usize) -> Option<String> {
+        let mut active: Vec<_> = self.get_active();
         if active.is_empty() {
             return None;
         }
+        active.sort_by(|a, b| {
+            self.priority(b)
+                .cmp(&self.priority(a))
+                .then_with(|| a.name.cmp(&b.name))
+        });
 
+        const SEPARATOR: &str = "\n\n---\n\n";
         let mut content = String::from("\n---\n# Active Skills\n\n");
+        let mut used = estimate_tokens(&content);
+        let mut omitted = Vec::new();
+
         for skill in active {
-            content.push_str(&skill.to_prompt_injection());
-            content.push_str("\n\n---\n\n");
+            let full = skill.to_prompt_injection();
+            let summary = skill.to_summary_injection();
+            let section = if token_budget == 0
+                || used + estimate_tokens(&full) + estimate_tokens(SEPARATOR) <= token_budget
+            {
+                full
+            } else if used + estimate_tokens(&summary) + estimate_tokens(SEPARATOR) <= token_budget {
+                summary
+            } else {
+                omitted.push(skill.name.as_str());
+                continue;
+            };
+            used += estimate_tokens(&section) + estimate_tokens(SEPARATOR);
+            content.push_str(&section);
+            content.push_str(SEPARATOR);
+        }
+
+        if !omitted.is_empty() {
+            tracing::debug!("Skills omitted to fit the token budget: {:?}", omitted);
+            content.push_str(&format!(
+                "Also active but omitted to fit the skills token budget: {}\n",
+                omitted.join(", ")
+            ));
         }
 
         Some(content)
@@ -279,7 +457,7 @@ impl Default for SkillManager {
 }
 
 /// Parse YAML frontmatter from markdown content
-fn parse_frontmatter(content: &str) -> Result<(serde_json::Value, String)> {
+pub(crate) fn parse_frontmatter(content: &str) -> Result<(serde_json::Value, String)> {
     let content = content.trim();
 
     if !content.starts_with("---") {
@@ -531,4 +709,77 @@ Content here.
         assert!(skill.matches_file("Cargo.toml"));
         assert!(!skill.matches_file("package.json"));
     }
+
+    #[tokio::test]
+    async fn test_load_all_includes_packs() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pack = dir.path().join("team-pack");
+        std::fs::create_dir_all(pack.join("skills")).unwrap();
+        std::fs::write(dir.path().join("local.md"), "Local skill").unwrap();
+        std::fs::write(pack.join("README.md"), "# Team pack").unwrap();
+        std::fs::write(pack.join("skills").join("docker.md"), "---\ntrigger: Dockerfile\n---\nUse multi-stage builds.").unwrap();
+
+        let mut manager = SkillManager::new();
+        manager.add_search_path(dir.path().to_path_buf());
+        assert_eq!(manager.load_all().await.unwrap(), 2);
+        assert!(manager.get("local").is_some());
+        assert!(manager.get("docker").is_some());
+        assert!(manager.get("README").is_none());
+    }
+
+    #[test]
+    fn test_auto_activate_respects_user_off() {
+        let mut manager = SkillManager::new();
+        manager.register_builtins();
+
+        assert_eq!(
+            manager.auto_activate_for_files(&["src/main.rs"]),
+            vec!["rust-patterns"]
+        );
+        // Already active skills aren't reported again
+        assert!(manager.auto_activate_for_files(&["src/lib.rs"]).is_empty());
+        assert!(manager
+            .get_active_skills_prompt(0)
+            .unwrap()
+            .contains("# Skill: rust-patterns"));
+
+        assert!(manager.deactivate("rust-patterns"));
+        assert!(manager.auto_activate_for_files(&["src/lib.rs"]).is_empty());
+        assert!(manager.get_active_skills_prompt(0).is_none());
+
+        assert!(manager.activate("rust-patterns"));
+        assert_eq!(manager.get_active().len(), 1);
+    }
+
+    #[test]
+    fn test_skills_prompt_budget_prefers_explicit() {
+        let mut manager = SkillManager::new();
+        manager.register_builtins();
+        let mut docker = Skill::from_content(
+            "---\nname: docker\ntrigger: Dockerfile\n---\nUse multi-stage builds.",
+            Some(PathBuf::from("docker.md")),
+        )
+        .unwrap();
+        docker.content.push_str(&"\nPin base image versions.".repeat(40));
+        manager.register(docker);
+
+        manager.auto_activate_for_files(&["src/main.rs", "app.py", "Dockerfile"]);
+        assert!(manager.activate("python-patterns"));
+
+        let python = manager.get("python-patterns").unwrap();
+        assert_eq!(manager.priority(python), SkillPriority::Explicit);
+        assert_eq!(manager.priority(manager.get("docker").unwrap()), SkillPriority::Triggered);
+        assert_eq!(manager.priority(manager.get("rust-patterns").unwrap()), SkillPriority::Builtin);
+
+        let unlimited = manager.get_active_skills_prompt(0).unwrap();
+        assert!(!unlimited.contains("(summarized)"));
+
+        // Room for the explicit skill in full and the triggered one summarized
+        let budget = python.estimated_tokens() + 200;
+        let prompt = manager.get_active_skills_prompt(budget).unwrap();
+        assert!(prompt.contains("# Skill: python-patterns\n"));
+        assert!(prompt.contains("# Skill: docker (summarized)"));
+        assert!(prompt.contains("omitted to fit the skills token budget: rust-patterns"));
+        assert!(prompt.find("python-patterns") < prompt.find("docker"));
+    }
 }
@@ -0,0 +1,377 @@
+//! Skill Packs
+//!
+//! Skill packs are git repositories of skill files installed into the user
+//! skills directory (`~/.config/safe-coder/skills/<pack>/`). Installed packs
+//! are tracked in `skills.lock` next to them so they can be updated or
+//! removed later:
+//!
+//! ```text
+//! safe-coder skill install gh:acme/rust-skills
+//! safe-coder skill install https://git.example.com/team/skills.git --ref v1.2.0
+//! safe-coder skill update
+//! safe-coder skill remove rust-skills
+//! ```
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tokio::process::Command;
+
+/// Name of the lockfile in the skills directory
+pub const LOCKFILE_NAME: &str = "skills.lock";
+
+/// An installed skill pack
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SkillPack {
+    /// Directory name under the skills directory
+    pub name: String,
+    /// Git URL the pack was cloned from
+    pub url: String,
+    /// Branch or tag requested at install time (remote default branch if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    /// Installed commit
+    pub commit: String,
+    /// Human-readable version (`git describe`)
+    pub version: String,
+    pub installed_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Contents of `skills.lock`
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    packs: Vec<SkillPack>,
+}
+
+/// Resolve an install source to a git URL.
+///
+/// `gh:user/repo` (optionally `gh:user/repo@ref`) expands to a GitHub URL;
+/// anything else is passed to git as-is. Returns the URL and the ref, if any.
+pub fn resolve_source(source: &str) -> Result<(String, Option<String>)> {
+    let source = source.trim();
+    if let Some(repo) = source.strip_prefix("gh:") {
+        let (repo, reference) = match repo.split_once('@') {
+            Some((repo, reference)) => (repo, Some(reference.to_string())),
+            None => (repo, None),
+        };
+        let repo = repo.trim_end_matches(".git");
+        let valid = repo.split('/').count() == 2
+            && repo.split('/').all(|part| {
+                !part.is_empty()
+                    && part
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            });
+        if !valid {
+            anyhow::bail!("Invalid GitHub source '{}', expected gh:user/repo", source);
+        }
+        return Ok((format!("https://github.com/{}.git", repo), reference));
+    }
+
+    if source.is_empty() {
+        anyhow::bail!("Skill pack source is empty");
+    }
+    Ok((source.to_string(), None))
+}
+
+/// Default pack name for a git URL (the repository name)
+pub fn pack_name_from_url(url: &str) -> String {
+    url.trim_end_matches('/')
+        .rsplit(['/', ':'])
+        .next()
+        .unwrap_or(url)
+        .trim_end_matches(".git")
+        .to_string()
+}
+
+/// Installs, updates and removes skill packs in a skills directory
+pub struct SkillPackManager {
+    skills_dir: PathBuf,
+}
+
+impl SkillPackManager {
+    pub fn new(skills_dir: PathBuf) -> Self {
+        Self { skills_dir }
+    }
+
+    /// Manager for the user skills directory (`~/.config/safe-coder/skills`)
+    pub fn user() -> Result<Self> {
+        let config_dir = dirs::config_dir().context("Could not determine config directory")?;
+        Ok(Self::new(config_dir.join("safe-coder").join("skills")))
+    }
+
+    pub fn skills_dir(&self) -> &Path {
+        &self.skills_dir
+    }
+
+    fn lockfile_path(&self) -> PathBuf {
+        self.skills_dir.join(LOCKFILE_NAME)
+    }
+
+    fn load_lockfile(&self) -> Result<Lockfile> {
+        let path = self.lockfile_path();
+        if !path.exists() {
+            return Ok(Lockfile::default());
+        }
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    fn save_lockfile(&self, lockfile: &Lockfile) -> Result<()> {
+        std::fs::create_dir_all(&self.skills_dir)?;
+        let content = toml::to_string_pretty(lockfile).context("Failed to serialize lockfile")?;
+        std::fs::write(self.lockfile_path(), content).context("Failed to write lockfile")
+    }
+
+    /// Installed packs
+    pub fn list(&self) -> Result<Vec<SkillPack>> {
+        Ok(self.load_lockfile()?.packs)
+    }
+
+    /// Clone a pack into the skills directory and record it in the lockfile
+    pub async fn install(
+        &self,
+        source: &str,
+        name: Option<&str>,
+        reference: Option<&str>,
+    ) -> Result<SkillPack> {
+        let (url, source_ref) = resolve_source(source)?;
+        let reference = reference.map(|r| r.to_string()).or(source_ref);
+        let name = name
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| pack_name_from_url(&url));
+        validate_pack_name(&name)?;
+
+        let mut lockfile = self.load_lockfile()?;
+        if lockfile.packs.iter().any(|p| p.name == name) {
+            anyhow::bail!(
+                "Skill pack '{}' is already installed; use `safe-coder skill update {}`",
+                name,
+                name
+            );
+        }
+        let dest = self.skills_dir.join(&name);
+        if dest.exists() {
+            anyhow::bail!("{} already exists", dest.display());
+        }
+        std::fs::create_dir_all(&self.skills_dir)?;
+
+        let mut args = vec!["clone", "--depth", "1"];
+        if let Some(ref r) = reference {
+            args.extend(["--branch", r.as_str()]);
+        }
+        args.push(url.as_str());
+        let dest_str = dest.to_string_lossy().to_string();
+        args.push(&dest_str);
+        git(&self.skills_dir, &args).await?;
+
+        let (commit, version) = match describe_checkout(&dest).await {
+            Ok(info) => info,
+            Err(e) => {
+                let _ = std::fs::remove_dir_all(&dest);
+                return Err(e);
+            }
+        };
+        let now = Utc::now();
+        let pack = SkillPack {
+            name,
+            url,
+            reference,
+            commit,
+            version,
+            installed_at: now,
+            updated_at: now,
+        };
+        lockfile.packs.push(pack.clone());
+        self.save_lockfile(&lockfile)?;
+        Ok(pack)
+    }
+
+    /// Update one pack (or all when `name` is None) to the latest commit of
+    /// its ref. Returns `(pack, previous_commit)` for each pack updated.
+    pub async fn update(&self, name: Option<&str>) -> Result<Vec<(SkillPack, String)>> {
+        let mut lockfile = self.load_lockfile()?;
+        if let Some(name) = name {
+            if !lockfile.packs.iter().any(|p| p.name == name) {
+                anyhow::bail!("Skill pack '{}' is not installed", name);
+            }
+        }
+
+        let mut updated = Vec::new();
+        for pack in lockfile
+            .packs
+            .iter_mut()
+            .filter(|p| name.is_none_or(|n| p.name == n))
+        {
+            let dir = self.skills_dir.join(&pack.name);
+            let target = pack.reference.as_deref().unwrap_or("HEAD");
+            git(&dir, &["fetch", "--depth", "1", "--tags", "origin", target])
+                .await
+                .with_context(|| format!("Failed to update skill pack '{}'", pack.name))?;
+            git(&dir, &["reset", "--hard", "FETCH_HEAD"]).await?;
+
+            let previous = pack.commit.clone();
+            let (commit, version) = describe_checkout(&dir).await?;
+            pack.commit = commit;
+            pack.version = version;
+            pack.updated_at = Utc::now();
+            updated.push((pack.clone(), previous));
+        }
+
+        self.save_lockfile(&lockfile)?;
+        Ok(updated)
+    }
+
+    /// Delete a pack's files and lockfile entry
+    pub fn remove(&self, name: &str) -> Result<SkillPack> {
+        validate_pack_name(name)?;
+        let mut lockfile = self.load_lockfile()?;
+        let index = lockfile
+            .packs
+            .iter()
+            .position(|p| p.name == name)
+            .with_context(|| format!("Skill pack '{}' is not installed", name))?;
+
+        let dir = self.skills_dir.join(name);
+        if dir.exists() {
+            std::fs::remove_dir_all(&dir)
+                .with_context(|| format!("Failed to delete {}", dir.display()))?;
+        }
+        let pack = lockfile.packs.remove(index);
+        self.save_lockfile(&lockfile)?;
+        Ok(pack)
+    }
+}
+
+/// Pack names become directory names, so keep them to a single path component
+fn validate_pack_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name != "."
+        && name != ".."
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        anyhow::bail!(
+            "Invalid skill pack name '{}' (use letters, digits, '-', '_' or '.')",
+            name
+        );
+    }
+    Ok(())
+}
+
+/// Run git in a directory, failing with its stderr
+async fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .await
+        .context("Failed to run git (is it installed?)")?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Commit and version of a checkout
+async fn describe_checkout(dir: &Path) -> Result<(String, String)> {
+    let commit = git(dir, &["rev-parse", "HEAD"]).await?;
+    let version = git(dir, &["describe", "--tags", "--always"])
+        .await
+        .unwrap_or_else(|_| commit.chars().take(7).collect());
+    Ok((commit, version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_source() {
+        assert_eq!(
+            resolve_source("gh:acme/rust-skills").unwrap(),
+            ("https://github.com/acme/rust-skills.git".to_string(), None)
+        );
+        assert_eq!(
+            resolve_source("gh:acme/rust-skills@v1.2").unwrap(),
+            (
+                "https://github.com/acme/rust-skills.git".to_string(),
+                Some("v1.2".to_string())
+            )
+        );
+        assert!(resolve_source("gh:acme").is_err());
+        assert!(resolve_source("gh:acme/../../etc").is_err());
+
+        assert_eq!(
+            pack_name_from_url("git@github.com:acme/team-skills.git"),
+            "team-skills"
+        );
+        assert_eq!(pack_name_from_url("https://example.com/skills/"), "skills");
+    }
+
+    async fn commit_skill(repo: &Path, file: &str, content: &str) {
+        std::fs::write(repo.join(file), content).unwrap();
+        git(repo, &["add", "."]).await.unwrap();
+        git(
+            repo,
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "-qm",
+                file,
+            ],
+        )
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_install_update_remove() {
+        let upstream = TempDir::new().unwrap();
+        if git(upstream.path(), &["init", "-q"]).await.is_err() {
+            return; // git not available
+        }
+        commit_skill(
+            upstream.path(),
+            "docker.md",
+            "---\ntrigger: Dockerfile\n---\nUse multi-stage builds.",
+        )
+        .await;
+
+        let skills = TempDir::new().unwrap();
+        let manager = SkillPackManager::new(skills.path().to_path_buf());
+        let source = format!("file://{}", upstream.path().display());
+
+        let pack = manager.install(&source, Some("ops"), None).await.unwrap();
+        assert!(skills.path().join("ops/docker.md").exists());
+        assert_eq!(manager.list().unwrap(), vec![pack.clone()]);
+        assert!(manager.install(&source, Some("ops"), None).await.is_err());
+
+        commit_skill(upstream.path(), "k8s.md", "Prefer Deployments.").await;
+        let updated = manager.update(Some("ops")).await.unwrap();
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].1, pack.commit);
+        assert_ne!(updated[0].0.commit, pack.commit);
+        assert!(skills.path().join("ops/k8s.md").exists());
+
+        manager.remove("ops").unwrap();
+        assert!(!skills.path().join("ops").exists());
+        assert!(manager.list().unwrap().is_empty());
+        assert!(manager.remove("ops").is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Custom Subagent Definitions
+//!
+//! Users define their own subagents as markdown files in
+//! `.safe-coder/agents/` (project) or `~/.config/safe-coder/agents/` (user).
+//! The frontmatter configures the agent and the body is its instructions:
+//! ```markdown
+//! ---
+//! name: security-reviewer
+//! description: Reviews changes for security issues
+//! tools: [read_file, grep, glob]
+//! model: claude-sonnet-4-20250514
+//! max_turns: 10
+//! ---
+//!
+//! You review code for injection, auth and secrets-handling bugs...
+//! ```
+//!
+//! The main agent runs them through the `task` tool.
+
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::skills::parse_frontmatter;
+
+/// A user-defined subagent loaded from a markdown file
+#[derive(Debug, Clone, PartialEq)]
+pub struct AgentDefinition {
+    /// Unique name, used to pick the agent in the `task` tool
+    pub name: String,
+    /// What the agent is for (shown to the main agent)
+    pub description: Option<String>,
+    /// Tools the agent may use (None = the default custom subagent tools)
+    pub tools: Option<Vec<String>>,
+    /// Model override for this agent
+    pub model: Option<String>,
+    /// Maximum conversation turns
+    pub max_turns: Option<usize>,
+    /// Instructions from the markdown body
+    pub prompt: String,
+    /// Source file path
+    pub source_path: Option<PathBuf>,
+}
+
+impl AgentDefinition {
+    /// Parse a definition from markdown with frontmatter
+    pub fn from_content(content: &str, source_path: Option<PathBuf>) -> Result<Self> {
+        let (frontmatter, body) = parse_frontmatter(content)?;
+        let field = |keys: &[&str]| keys.iter().find_map(|key| frontmatter.get(*key));
+        let string_field = |keys: &[&str]| {
+            field(keys)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string)
+        };
+
+        let name = string_field(&["name"])
+            .or_else(|| {
+                source_path
+                    .as_ref()
+                    .and_then(|p| p.file_stem())
+                    .map(|s| s.to_string_lossy().to_string())
+            })
+            .context("Agent definition has no name")?;
+
+        let tools = field(&["tools", "allowed_tools", "allowed-tools"]).map(|v| match v {
+            serde_json::Value::Array(items) => items
+                .iter()
+                .filter_map(|item| item.as_str())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+            other => other
+                .as_str()
+                .unwrap_or_default()
+                .split(',')
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect(),
+        });
+
+        let max_turns =
+            match string_field(&["max_turns", "max-turns"]) {
+                Some(value) => Some(value.parse::<usize>().with_context(|| {
+                    format!("Invalid max_turns for agent '{}': {}", name, value)
+                })?),
+                None => None,
+            };
+
+        if body.trim().is_empty() {
+            anyhow::bail!("Agent '{}' has no instructions", name);
+        }
+
+        Ok(Self {
+            name,
+            description: string_field(&["description"]),
+            tools,
+            model: string_field(&["model"]),
+            max_turns,
+            prompt: body,
+            source_path,
+        })
+    }
+}
+
+/// Directories searched for agent definitions, lowest precedence first
+fn agent_dirs(project_path: &Path) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(config_dir) = dirs::config_dir() {
+        dirs.push(config_dir.join("safe-coder").join("agents"));
+    }
+    dirs.push(project_path.join(".safe-coder").join("agents"));
+    dirs
+}
+
+/// Load agent definitions from a directory (invalid files are skipped with a warning)
+pub fn load_from_directory(dir: &Path) -> Vec<AgentDefinition> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut agents = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if !path.is_file() || path.extension().and_then(|ext| ext.to_str()) != Some("md") {
+            continue;
+        }
+        let parsed = std::fs::read_to_string(&path)
+            .context("Failed to read agent definition")
+            .and_then(|content| AgentDefinition::from_content(&content, Some(path.clone())));
+        match parsed {
+            Ok(agent) => agents.push(agent),
+            Err(e) => tracing::warn!("Skipping agent definition {}: {:#}", path.display(), e),
+        }
+    }
+    agents
+}
+
+/// Load the user's and the project's agent definitions, sorted by name.
+/// Project agents replace user agents with the same name.
+pub fn load_agent_definitions(project_path: &Path) -> Vec<AgentDefinition> {
+    let mut agents = BTreeMap::new();
+    for dir in agent_dirs(project_path) {
+        for agent in load_from_directory(&dir) {
+            agents.insert(agent.name.clone(), agent);
+        }
+    }
+    agents.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_agent_definition() {
+        let content = r#"---
+name: security-reviewer
+description: Reviews changes for security issues
+tools: [read_file, grep]
+model: claude-sonnet-4-20250514
+max_turns: 8
+---
+
+Look for injection bugs.
+"#;
+        let agent = AgentDefinition::from_content(content, None).unwrap();
+        assert_eq!(agent.name, "security-reviewer");
+        assert_eq!(
+            agent.tools,
+            Some(vec!["read_file".to_string(), "grep".to_string()])
+        );
+        assert_eq!(agent.model.as_deref(), Some("claude-sonnet-4-20250514"));
+        assert_eq!(agent.max_turns, Some(8));
+        assert_eq!(agent.prompt, "Look for injection bugs.");
+
+        let comma_list = "---\nallowed-tools: read_file, glob\n---\nExplore.";
+        let agent =
+            AgentDefinition::from_content(comma_list, Some(PathBuf::from("explorer.md"))).unwrap();
+        assert_eq!(agent.name, "explorer");
+        assert_eq!(
+            agent.tools,
+            Some(vec!["read_file".to_string(), "glob".to_string()])
+        );
+
+        assert!(AgentDefinition::from_content("---\nname: empty\n---\n", None).is_err());
+        assert!(AgentDefinition::from_content("---\nmax_turns: lots\n---\nHi", None).is_err());
+    }
+
+    #[test]
+    fn test_load_agent_definitions_from_project() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let agents_dir = dir.path().join(".safe-coder").join("agents");
+        std::fs::create_dir_all(&agents_dir).unwrap();
+        std::fs::write(agents_dir.join("reviewer.md"), "Review the diff.").unwrap();
+        std::fs::write(agents_dir.join("broken.md"), "---\nname: broken\n---\n").unwrap();
+        std::fs::write(agents_dir.join("notes.txt"), "Not an agent").unwrap();
+
+        let agents = load_from_directory(&agents_dir);
+        assert_eq!(agents.len(), 1);
+        assert_eq!(agents[0].name, "reviewer");
+        assert!(load_agent_definitions(dir.path())
+            .iter()
+            .any(|a| a.name == "reviewer"));
+    }
+}
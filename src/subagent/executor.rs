@@ -15,7 +15,7 @@ use crate::llm::{
     create_client, create_client_from_subagent_config, ContentBlock, LlmClient, Message,
     ToolDefinition,
 };
-use crate::tools::{ToolContext, ToolRegistry};
+use crate::tools::{render_tool_error, ToolContext, ToolRegistry};
 
 use super::prompts::build_subagent_prompt;
 use super::types::{SubagentEvent, SubagentKind, SubagentResult, SubagentScope};
@@ -66,10 +66,18 @@ impl SubagentExecutor {
             SubagentKind::Refactorer => "refactorer",
             SubagentKind::Documenter => "documenter",
             SubagentKind::Explorer => "explorer",
+            SubagentKind::Monitor => "monitor",
             SubagentKind::Custom => "custom",
         };
 
-        let llm_client = if let Some(subagent_model) = config.get_subagent_model(kind_str) {
+        let agent_model = scope.agent.as_ref().and_then(|agent| agent.model.clone());
+        let llm_client = if let Some(model) = agent_model {
+            // Model set in the agent definition, with the main provider
+            tracing::info!("🤖 {} subagent using model: {}", scope.display_name(&kind), model);
+            let mut agent_config = config.clone();
+            agent_config.llm.model = model;
+            create_client(&agent_config).await?
+        } else if let Some(subagent_model) = config.get_subagent_model(kind_str) {
             // Use per-subagent model configuration
             tracing::info!(
                 "🤖 {} subagent using custom model: {} ({})",
@@ -78,6 +86,12 @@ impl SubagentExecutor {
                 format!("{:?}", subagent_model.provider).to_lowercase()
             );
             create_client_from_subagent_config(subagent_model).await?
+        } else if let Some(model) = &config.llm.subagent_model {
+            // Default subagent model, with the main provider
+            tracing::info!("🤖 {} subagent using model: {}", kind.display_name(), model);
+            let mut subagent_config = config.clone();
+            subagent_config.llm.model = model.clone();
+            create_client(&subagent_config).await?
         } else {
             // Fall back to main LLM config
             create_client(config).await?
@@ -97,7 +111,8 @@ impl SubagentExecutor {
             compaction_warning_threshold: 2, // Warn earlier for subagents
             chars_per_token: 4,
         };
-        let context_manager = ContextManager::with_config(context_config);
+        let mut context_manager = ContextManager::with_config(context_config);
+        context_manager.set_output_reserve(config.llm.max_tokens);
 
         Ok(Self {
             id,
@@ -125,7 +140,7 @@ impl SubagentExecutor {
         // Send started event
         let _ = self.event_tx.send(SubagentEvent::Started {
             id: self.id.clone(),
-            kind: self.kind.clone(),
+            name: self.scope.display_name(&self.kind).to_string(),
             task: self.scope.task.clone(),
         });
 
@@ -184,6 +199,17 @@ impl SubagentExecutor {
             // Get available tools for this subagent kind
             let tools = self.get_filtered_tools();
 
+            // Make sure the request fits the context window
+            if let Some(result) =
+                self.context_manager
+                    .fit_request(&system_prompt, &tools, &mut self.messages)
+            {
+                let _ = self.event_tx.send(SubagentEvent::Thinking {
+                    id: self.id.clone(),
+                    message: result.summary(),
+                });
+            }
+
             // Log available tools for debugging
             let tool_names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
             let _ = self.event_tx.send(SubagentEvent::Thinking {
@@ -258,11 +284,11 @@ impl SubagentExecutor {
             for block in &message.content {
                 if let ContentBlock::ToolUse { id, name, input } = block {
                     // Check if tool is allowed for this subagent kind
-                    if !self.kind.is_tool_allowed(name.as_str()) {
+                    if !self.scope.is_tool_allowed(&self.kind, name.as_str()) {
                         let error_msg = format!(
                             "Tool '{}' is not available for {} subagent",
                             name,
-                            self.kind.display_name()
+                            self.scope.display_name(&self.kind)
                         );
                         let _ = self.event_tx.send(SubagentEvent::ToolStart {
                             id: self.id.clone(),
@@ -314,7 +340,7 @@ impl SubagentExecutor {
                                 output
                             }
                             Err(e) => {
-                                let error_msg = format!("Tool error: {}", e);
+                                let error_msg = render_tool_error(&e);
                                 errors.push(error_msg.clone());
                                 let _ = self.event_tx.send(SubagentEvent::ToolOutput {
                                     id: self.id.clone(),
@@ -368,13 +394,13 @@ impl SubagentExecutor {
         let summary = if success {
             format!(
                 "{} completed task in {} iteration(s)",
-                self.kind.display_name(),
+                self.scope.display_name(&self.kind),
                 iteration
             )
         } else {
             format!(
                 "{} encountered errors after {} iteration(s)",
-                self.kind.display_name(),
+                self.scope.display_name(&self.kind),
                 iteration
             )
         };
@@ -404,7 +430,7 @@ impl SubagentExecutor {
             .into_iter()
             .filter(|schema| {
                 let name = schema["name"].as_str().unwrap_or("");
-                self.kind.is_tool_allowed(name)
+                self.scope.is_tool_allowed(&self.kind, name)
             })
             .map(|schema| ToolDefinition {
                 name: schema["name"].as_str().unwrap().to_string(),
@@ -2,13 +2,21 @@
 //!
 //! Provides the ability to spawn specialized subagents for focused tasks.
 //! Subagents are autonomous agents that handle specific use cases like
-//! code analysis, testing, refactoring, or documentation.
+//! code analysis, testing, refactoring, or documentation. Users can define
+//! their own agents in `.safe-coder/agents/*.md`, which the main agent runs
+//! through the `task` tool.
 
+pub mod definitions;
 pub mod executor;
+pub mod monitor;
 pub mod prompts;
+pub mod task;
 pub mod tool;
 pub mod types;
 
+pub use definitions::load_agent_definitions;
 pub use executor::SubagentExecutor;
+pub use monitor::MonitorHub;
+pub use task::TaskTool;
 pub use tool::SubagentTool;
 pub use types::{SubagentEvent, SubagentKind, SubagentResult, SubagentScope};
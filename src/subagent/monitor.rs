@@ -0,0 +1,437 @@
+//! Monitor Subagents
+//!
+//! A monitor runs a long-lived command (test suite, build, dev server) in the
+//! background and tails its output. Failure lines are collected into short
+//! bursts, summarized by the LLM, and delivered to the parent session as
+//! notifications - both live, for the UI, and as pending updates that are
+//! added to the conversation on the session's next turn.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Child;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::AbortHandle;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::llm::{
+    create_client, create_client_from_subagent_config, ContentBlock, LlmClient, Message,
+};
+
+use super::prompts::build_subagent_prompt;
+use super::types::{SubagentEvent, SubagentKind, SubagentScope};
+
+/// How long to keep collecting failure lines before summarizing a burst
+const BURST_WINDOW: Duration = Duration::from_secs(3);
+
+/// Lines of recent output kept as context for a summary
+const CONTEXT_LINES: usize = 40;
+
+/// Failure lines included in one summary request
+const MAX_BURST_LINES: usize = 30;
+
+/// Output markers that indicate something went wrong
+const FAILURE_MARKERS: &[&str] = &[
+    "error:",
+    "error[",
+    "ERROR",
+    "FAILED",
+    "FAIL ",
+    "panicked at",
+    "Traceback (most recent call last)",
+    "Exception",
+    "npm ERR!",
+    "Segmentation fault",
+    "fatal:",
+];
+
+/// A message from a monitor to its parent session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonitorNotification {
+    /// Monitor that produced the notification
+    pub monitor_id: String,
+    /// Command being monitored
+    pub command: String,
+    /// What happened
+    pub summary: String,
+    /// Whether the user or agent should act on it
+    pub needs_attention: bool,
+}
+
+impl MonitorNotification {
+    /// Format for inclusion in the conversation
+    pub fn to_prompt_line(&self) -> String {
+        let marker = if self.needs_attention { "⚠" } else { "✓" };
+        format!(
+            "{} `{}` ({}): {}",
+            marker, self.command, self.monitor_id, self.summary
+        )
+    }
+}
+
+/// Shared state for a session's monitors: running monitors and the
+/// notifications they've produced
+pub struct MonitorHub {
+    pending: Mutex<Vec<MonitorNotification>>,
+    live: broadcast::Sender<MonitorNotification>,
+    running: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl MonitorHub {
+    pub fn new() -> Self {
+        let (live, _) = broadcast::channel(64);
+        Self {
+            pending: Mutex::new(Vec::new()),
+            live,
+            running: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Deliver a notification to live subscribers and queue it for the session
+    pub fn notify(&self, notification: MonitorNotification) {
+        let _ = self.live.send(notification.clone());
+        self.pending.lock().unwrap().push(notification);
+    }
+
+    /// Take the notifications the session hasn't seen yet
+    pub fn take_pending(&self) -> Vec<MonitorNotification> {
+        std::mem::take(&mut *self.pending.lock().unwrap())
+    }
+
+    /// Receive notifications as they happen
+    pub fn subscribe(&self) -> broadcast::Receiver<MonitorNotification> {
+        self.live.subscribe()
+    }
+
+    fn track(&self, id: String, handle: AbortHandle) {
+        self.running.lock().unwrap().insert(id, handle);
+    }
+
+    fn finish(&self, id: &str) {
+        self.running.lock().unwrap().remove(id);
+    }
+}
+
+impl Default for MonitorHub {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for MonitorHub {
+    fn drop(&mut self) {
+        // Monitored commands are killed when their task is dropped
+        for (_, handle) in self.running.get_mut().unwrap().drain() {
+            handle.abort();
+        }
+    }
+}
+
+/// Check whether an output line reports a failure
+pub fn is_failure_line(line: &str) -> bool {
+    FAILURE_MARKERS.iter().any(|marker| line.contains(marker))
+}
+
+/// Tracks recent output and groups failure lines into bursts
+#[derive(Default)]
+struct OutputWatcher {
+    recent: VecDeque<String>,
+    burst: Vec<String>,
+    burst_started: Option<Instant>,
+    reported: HashSet<String>,
+}
+
+impl OutputWatcher {
+    fn observe(&mut self, line: &str, now: Instant) {
+        if self.recent.len() == CONTEXT_LINES {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(line.to_string());
+
+        // Repeated failures (e.g. a dev server re-logging the same error)
+        // are only reported once
+        if is_failure_line(line) && self.reported.insert(line.trim().to_string()) {
+            if self.burst.len() < MAX_BURST_LINES {
+                self.burst.push(line.to_string());
+            }
+            self.burst_started.get_or_insert(now);
+        }
+    }
+
+    fn burst_due(&self, now: Instant) -> bool {
+        self.burst_started
+            .is_some_and(|started| now.duration_since(started) >= BURST_WINDOW)
+    }
+
+    /// Take the current burst with recent output as context
+    fn take_burst(&mut self) -> Option<String> {
+        self.burst_started = None;
+        if self.burst.is_empty() {
+            return None;
+        }
+        let failures = std::mem::take(&mut self.burst);
+        Some(format!(
+            "Failure lines:\n{}\n\nRecent output:\n{}",
+            failures.join("\n"),
+            self.tail()
+        ))
+    }
+
+    fn tail(&self) -> String {
+        self.recent.iter().cloned().collect::<Vec<_>>().join("\n")
+    }
+}
+
+/// Start monitoring `command` in the background, returning the monitor ID
+pub async fn spawn_monitor(
+    command: String,
+    project_path: PathBuf,
+    config: &Config,
+    hub: &Arc<MonitorHub>,
+    event_tx: mpsc::UnboundedSender<SubagentEvent>,
+) -> Result<String> {
+    let id = format!("monitor-{}", &Uuid::new_v4().to_string()[..8]);
+
+    let llm_client = match (config.get_subagent_model("monitor"), &config.llm.subagent_model) {
+        (Some(model), _) => create_client_from_subagent_config(model).await?,
+        (None, Some(model)) => {
+            let mut monitor_config = config.clone();
+            monitor_config.llm.model = model.clone();
+            create_client(&monitor_config).await?
+        }
+        (None, None) => create_client(config).await?,
+    };
+
+    let mut child = crate::platform::shell_command(&command)
+        .current_dir(&project_path)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("Failed to start monitored command: {}", command))?;
+
+    let (line_tx, line_rx) = mpsc::unbounded_channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, line_tx.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, line_tx);
+    }
+
+    let _ = event_tx.send(SubagentEvent::Started {
+        id: id.clone(),
+        name: SubagentKind::Monitor.display_name().to_string(),
+        task: command.clone(),
+    });
+
+    let monitor = Monitor {
+        id: id.clone(),
+        command,
+        llm_client,
+        hub: Arc::downgrade(hub),
+        event_tx,
+    };
+    let task = tokio::spawn(monitor.run(child, line_rx));
+    hub.track(id.clone(), task.abort_handle());
+
+    Ok(id)
+}
+
+fn forward_lines<R>(reader: R, tx: mpsc::UnboundedSender<String>)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+/// A running monitor
+struct Monitor {
+    id: String,
+    command: String,
+    llm_client: Box<dyn LlmClient>,
+    /// Weak so a dropped session stops its monitors
+    hub: Weak<MonitorHub>,
+    event_tx: mpsc::UnboundedSender<SubagentEvent>,
+}
+
+impl Monitor {
+    async fn run(self, mut child: Child, mut line_rx: mpsc::UnboundedReceiver<String>) {
+        let mut watcher = OutputWatcher::default();
+
+        loop {
+            tokio::select! {
+                line = line_rx.recv() => match line {
+                    Some(line) => watcher.observe(&line, Instant::now()),
+                    None => break, // stdout and stderr closed
+                },
+                _ = tokio::time::sleep(BURST_WINDOW) => {}
+            }
+
+            if watcher.burst_due(Instant::now()) {
+                if let Some(excerpt) = watcher.take_burst() {
+                    let summary = self.summarize(&excerpt).await;
+                    self.notify(summary, true);
+                }
+            }
+        }
+
+        let status = child.wait().await;
+        if let Some(excerpt) = watcher.take_burst() {
+            let summary = self.summarize(&excerpt).await;
+            self.notify(summary, true);
+        }
+
+        let success = status.as_ref().is_ok_and(|s| s.success());
+        let summary = match status {
+            Ok(status) if status.success() => "Finished successfully".to_string(),
+            Ok(status) => {
+                let tail = format!(
+                    "Exited with {}\n\nRecent output:\n{}",
+                    status,
+                    watcher.tail()
+                );
+                self.summarize(&tail).await
+            }
+            Err(e) => format!("Could not wait for the command: {}", e),
+        };
+        self.notify(summary.clone(), !success);
+
+        let _ = self.event_tx.send(SubagentEvent::Completed {
+            id: self.id.clone(),
+            success,
+            summary,
+        });
+        if let Some(hub) = self.hub.upgrade() {
+            hub.finish(&self.id);
+        }
+    }
+
+    fn notify(&self, summary: String, needs_attention: bool) {
+        let _ = self.event_tx.send(SubagentEvent::TextChunk {
+            id: self.id.clone(),
+            text: summary.clone(),
+        });
+        if let Some(hub) = self.hub.upgrade() {
+            hub.notify(MonitorNotification {
+                monitor_id: self.id.clone(),
+                command: self.command.clone(),
+                summary,
+                needs_attention,
+            });
+        }
+    }
+
+    /// Summarize an output excerpt with the LLM, falling back to the raw
+    /// failure lines if that fails
+    async fn summarize(&self, excerpt: &str) -> String {
+        let _ = self.event_tx.send(SubagentEvent::Thinking {
+            id: self.id.clone(),
+            message: "Summarizing new failures...".to_string(),
+        });
+
+        let system_prompt =
+            build_subagent_prompt(&SubagentKind::Monitor, &SubagentScope::new(&self.command));
+        let messages = vec![Message::user(format!(
+            "New output from `{}`:\n```\n{}\n```",
+            self.command, excerpt
+        ))];
+
+        match self
+            .llm_client
+            .send_message_with_system(&messages, &[], Some(&system_prompt))
+            .await
+        {
+            Ok(response) => {
+                let text: String = response
+                    .message
+                    .content
+                    .iter()
+                    .filter_map(|block| match block {
+                        ContentBlock::Text { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                if !text.trim().is_empty() {
+                    return text.trim().to_string();
+                }
+            }
+            Err(e) => tracing::warn!("Monitor {} could not summarize output: {}", self.id, e),
+        }
+
+        let failures: Vec<&str> = excerpt
+            .lines()
+            .filter(|line| is_failure_line(line))
+            .take(5)
+            .collect();
+        if failures.is_empty() {
+            excerpt.lines().next().unwrap_or_default().to_string()
+        } else {
+            failures.join("\n")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_lines() {
+        assert!(is_failure_line("error[E0308]: mismatched types"));
+        assert!(is_failure_line("test parser::tests::roundtrip ... FAILED"));
+        assert!(is_failure_line("thread 'main' panicked at src/main.rs:4:5"));
+        assert!(!is_failure_line("test result: ok. 12 passed; 0 failed"));
+        assert!(!is_failure_line("   Compiling safe-coder v0.1.0"));
+    }
+
+    #[test]
+    fn test_watcher_groups_failures_into_bursts() {
+        let start = Instant::now();
+        let mut watcher = OutputWatcher::default();
+        watcher.observe("running 3 tests", start);
+        watcher.observe("test a ... FAILED", start);
+        watcher.observe("test b ... FAILED", start);
+        assert!(!watcher.burst_due(start));
+        assert!(watcher.burst_due(start + BURST_WINDOW));
+
+        let burst = watcher.take_burst().unwrap();
+        assert!(burst.contains("test a ... FAILED\ntest b ... FAILED"));
+        assert!(burst.contains("running 3 tests"));
+        assert!(watcher.take_burst().is_none());
+
+        // The same failure logged again isn't reported twice
+        watcher.observe("test a ... FAILED", start);
+        assert!(watcher.take_burst().is_none());
+    }
+
+    #[test]
+    fn test_hub_queues_notifications() {
+        let hub = MonitorHub::new();
+        let mut live = hub.subscribe();
+        let notification = MonitorNotification {
+            monitor_id: "monitor-1".to_string(),
+            command: "cargo test".to_string(),
+            summary: "2 tests failed".to_string(),
+            needs_attention: true,
+        };
+        hub.notify(notification.clone());
+
+        assert_eq!(live.try_recv().unwrap(), notification);
+        assert_eq!(hub.take_pending(), vec![notification]);
+        assert!(hub.take_pending().is_empty());
+    }
+}
@@ -3,13 +3,19 @@
 //! Specialized system prompts for each subagent kind that focus the agent
 //! on its specific task and constrain its behavior.
 
+use super::definitions::AgentDefinition;
 use super::types::{SubagentKind, SubagentScope};
 
 /// Build a system prompt for a subagent based on its kind and scope
 pub fn build_subagent_prompt(kind: &SubagentKind, scope: &SubagentScope) -> String {
+    // Monitors don't explore or use tools - they only read the output they're given
+    if *kind == SubagentKind::Monitor {
+        return get_base_prompt(kind, scope);
+    }
+
     let base_prompt = get_base_prompt(kind, scope);
-    let tools_section = get_tools_section(kind);
-    let constraints_section = get_constraints_section();
+    let tools_section = get_tools_section(&scope.allowed_tools(kind));
+    let constraints_section = get_constraints_section(scope);
     let file_focus = get_file_focus_section(scope);
 
     format!(
@@ -44,6 +50,10 @@ You have all the tools you need. Explore the codebase and complete your task."##
 fn get_base_prompt(kind: &SubagentKind, scope: &SubagentScope) -> String {
     let discovery = get_discovery_section();
 
+    if let (SubagentKind::Custom, Some(agent)) = (kind, scope.agent.as_ref()) {
+        return get_agent_prompt(agent, scope, discovery);
+    }
+
     match kind {
         SubagentKind::CodeAnalyzer => format!(
             r##"You are a Code Analyzer subagent. Your task is to analyze code and provide insights.
@@ -197,6 +207,22 @@ Finished `dev` profile
             task = scope.task
         ),
 
+        SubagentKind::Monitor => format!(
+            r##"You are a Monitor subagent watching the output of a long-running command:
+`{task}`
+
+You are given failure lines and recent output as they appear. Write a short
+notification for the developer:
+- What failed (test names, error codes, failing requests)
+- Where, as file:line when the output shows it
+- The most likely cause, in one sentence
+
+Keep it to a few lines. Do not suggest long fixes and do not repeat output
+that isn't about the failure. If the output doesn't show a real problem
+(e.g. a warning or an expected error in a test), say so in one line."##,
+            task = scope.task
+        ),
+
         SubagentKind::Explorer => format!(
             r##"You are an Explorer subagent. Your task is to navigate and explore the codebase.
 {discovery}
@@ -254,8 +280,31 @@ Complete your task efficiently and report your findings/results clearly."##,
     }
 }
 
-fn get_tools_section(kind: &SubagentKind) -> String {
-    let tools = kind.allowed_tools();
+/// Prompt for a user-defined agent: its definition's instructions
+fn get_agent_prompt(agent: &AgentDefinition, scope: &SubagentScope, discovery: &str) -> String {
+    let purpose = agent
+        .description
+        .as_deref()
+        .map(|d| format!(" ({})", d))
+        .unwrap_or_default();
+    format!(
+        r##"You are the {name} subagent{purpose}.
+{discovery}
+
+## Your Task
+{task}
+
+## Instructions
+{instructions}"##,
+        name = agent.name,
+        purpose = purpose,
+        discovery = discovery,
+        task = scope.task,
+        instructions = agent.prompt
+    )
+}
+
+fn get_tools_section(tools: &[&str]) -> String {
     let tool_descriptions: Vec<&str> = tools
         .iter()
         .map(|t| match *t {
@@ -306,14 +355,17 @@ Start by exploring these patterns to understand the relevant code."##,
     )
 }
 
-fn get_constraints_section() -> &'static str {
-    r##"## Constraints
+fn get_constraints_section(scope: &SubagentScope) -> String {
+    format!(
+        r##"## Constraints
 - Complete your task efficiently - don't over-explore
 - Stay focused on your specific task
 - Report findings clearly and concisely
 - If you encounter blockers, report them and stop
 - You cannot spawn other subagents
-- Maximum iterations: 15 - be efficient"##
+- Maximum iterations: {} - be efficient"##,
+        scope.max_iterations
+    )
 }
 
 #[cfg(test)]
@@ -351,6 +403,32 @@ mod tests {
         assert!(prompt.contains("security auditor"));
     }
 
+    #[test]
+    fn test_agent_definition_prompt() {
+        let agent = AgentDefinition::from_content(
+            "---\nname: reviewer\ntools: [read_file, grep]\nmax_turns: 5\n---\nFlag unsafe blocks.",
+            None,
+        )
+        .unwrap();
+        let scope = SubagentScope::new("Review src/").with_agent(agent);
+        let prompt = build_subagent_prompt(&SubagentKind::Custom, &scope);
+
+        assert!(prompt.contains("You are the reviewer subagent"));
+        assert!(prompt.contains("Flag unsafe blocks."));
+        assert!(prompt.contains("grep - Search file contents"));
+        assert!(!prompt.contains("bash - Execute shell commands"));
+        assert!(prompt.contains("Maximum iterations: 5"));
+    }
+
+    #[test]
+    fn test_monitor_prompt() {
+        let scope = SubagentScope::new("cargo watch -x test");
+        let prompt = build_subagent_prompt(&SubagentKind::Monitor, &scope);
+
+        assert!(prompt.contains("cargo watch -x test"));
+        assert!(!prompt.contains("Available Tools"));
+    }
+
     #[test]
     fn test_file_focus_section() {
         let scope =
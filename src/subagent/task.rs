@@ -0,0 +1,178 @@
+//! Task Tool
+//!
+//! Lets the main agent delegate work to the user-defined subagents from
+//! `.safe-coder/agents/*.md`.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::PathBuf;
+use tokio::sync::mpsc;
+
+use crate::config::Config;
+use crate::tools::{Tool, ToolContext};
+
+use super::definitions::AgentDefinition;
+use super::executor::SubagentExecutor;
+use super::tool::forward_events;
+use super::types::{SubagentEvent, SubagentKind, SubagentScope};
+
+/// Tool for running a user-defined subagent
+pub struct TaskTool {
+    config: Config,
+    project_path: PathBuf,
+    agents: Vec<AgentDefinition>,
+    /// Tool description, listing the available agents
+    description: String,
+}
+
+impl TaskTool {
+    /// Create a task tool for the given agent definitions
+    pub fn new(config: Config, project_path: PathBuf, agents: Vec<AgentDefinition>) -> Self {
+        let mut description = String::from(
+            "Delegate a task to one of the project's custom subagents. The agent works \
+             autonomously with its own instructions and tools and reports back. Available agents:",
+        );
+        for agent in &agents {
+            description.push_str(&format!(
+                "\n- {}: {}",
+                agent.name,
+                agent.description.as_deref().unwrap_or("(no description)")
+            ));
+        }
+
+        Self {
+            config,
+            project_path,
+            agents,
+            description,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TaskParams {
+    agent: String,
+    task: String,
+    #[serde(default)]
+    file_patterns: Option<Vec<String>>,
+}
+
+#[async_trait]
+impl Tool for TaskTool {
+    fn name(&self) -> &str {
+        "task"
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        let names: Vec<&str> = self.agents.iter().map(|a| a.name.as_str()).collect();
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "agent": {
+                    "type": "string",
+                    "enum": names,
+                    "description": "Name of the agent to run"
+                },
+                "task": {
+                    "type": "string",
+                    "description": "The specific task for the agent to accomplish. Be clear and specific."
+                },
+                "file_patterns": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional file patterns to focus on (e.g., ['src/**/*.rs'])"
+                }
+            },
+            "required": ["agent", "task"]
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: TaskParams = serde_json::from_value(params)?;
+
+        let agent = self
+            .agents
+            .iter()
+            .find(|a| a.name == params.agent)
+            .ok_or_else(|| {
+                let names: Vec<&str> = self.agents.iter().map(|a| a.name.as_str()).collect();
+                anyhow::anyhow!(
+                    "Unknown agent: {}. Available agents: {}",
+                    params.agent,
+                    names.join(", ")
+                )
+            })?;
+
+        let mut scope = SubagentScope::new(&params.task).with_agent(agent.clone());
+        if let Some(patterns) = params.file_patterns {
+            scope = scope.with_file_patterns(patterns);
+        }
+        let timeout = scope.timeout;
+
+        let (event_tx, event_rx) = mpsc::unbounded_channel::<SubagentEvent>();
+        forward_events(event_rx, ctx.session_event_tx.clone());
+
+        let kind = SubagentKind::Custom;
+        let mut executor = SubagentExecutor::new(
+            kind.clone(),
+            scope,
+            self.project_path.clone(),
+            &self.config,
+            event_tx,
+        )
+        .await?;
+        let subagent_id = executor.id().to_string();
+
+        match tokio::time::timeout(timeout, executor.execute()).await {
+            Ok(Ok(result)) => Ok(format!(
+                "{} Agent {} ({})\n\n{}",
+                kind.icon(),
+                agent.name,
+                subagent_id,
+                result.format()
+            )),
+            Ok(Err(e)) => Ok(format!(
+                "{} Agent {} ({}) failed: {}",
+                kind.icon(),
+                agent.name,
+                subagent_id,
+                e
+            )),
+            Err(_) => Ok(format!(
+                "{} Agent {} ({}) timed out after {} seconds",
+                kind.icon(),
+                agent.name,
+                subagent_id,
+                timeout.as_secs()
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_tool_lists_agents() {
+        let agent = AgentDefinition::from_content(
+            "---\nname: reviewer\ndescription: Reviews diffs\n---\nReview.",
+            None,
+        )
+        .unwrap();
+        let tool = TaskTool::new(Config::default(), PathBuf::from("."), vec![agent]);
+
+        assert_eq!(tool.name(), "task");
+        assert!(tool.description().contains("- reviewer: Reviews diffs"));
+        let schema = tool.parameters_schema();
+        assert_eq!(
+            schema["properties"]["agent"]["enum"],
+            serde_json::json!(["reviewer"])
+        );
+    }
+}
@@ -10,9 +10,11 @@ use std::sync::Arc;
 use tokio::sync::{mpsc, Mutex};
 
 use crate::config::Config;
+use crate::session::SessionEvent;
 use crate::tools::{Tool, ToolContext};
 
 use super::executor::SubagentExecutor;
+use super::monitor::{spawn_monitor, MonitorHub};
 use super::types::{SubagentEvent, SubagentKind, SubagentResult, SubagentScope};
 
 /// Tool for spawning subagents
@@ -23,6 +25,8 @@ pub struct SubagentTool {
     project_path: Arc<Mutex<Option<PathBuf>>>,
     /// Event forwarder - sends subagent events to the parent session
     event_forwarder: Arc<Mutex<Option<mpsc::UnboundedSender<SubagentEvent>>>>,
+    /// Background monitors and their notifications
+    monitors: Arc<MonitorHub>,
 }
 
 impl SubagentTool {
@@ -32,9 +36,15 @@ impl SubagentTool {
             config: Arc::new(Mutex::new(None)),
             project_path: Arc::new(Mutex::new(None)),
             event_forwarder: Arc::new(Mutex::new(None)),
+            monitors: Arc::new(MonitorHub::new()),
         }
     }
 
+    /// Background monitors started by this tool
+    pub fn monitors(&self) -> Arc<MonitorHub> {
+        Arc::clone(&self.monitors)
+    }
+
     /// Initialize the tool with config and project path
     pub async fn initialize(
         &self,
@@ -62,6 +72,8 @@ struct SubagentParams {
     role: Option<String>,
     #[serde(default)]
     file_patterns: Option<Vec<String>>,
+    #[serde(default)]
+    command: Option<String>,
 }
 
 #[async_trait]
@@ -71,7 +83,7 @@ impl Tool for SubagentTool {
     }
 
     fn description(&self) -> &str {
-        "Spawn a specialized subagent to handle a focused task autonomously. Use this for complex subtasks that benefit from dedicated attention. Available kinds: code_analyzer (analyzes code, read-only), tester (creates/runs tests), refactorer (improves code structure), documenter (writes documentation), explorer (navigates codebase, finds code locations, read-only), custom (user-defined role)."
+        "Spawn a specialized subagent to handle a focused task autonomously. Use this for complex subtasks that benefit from dedicated attention. Available kinds: code_analyzer (analyzes code, read-only), tester (creates/runs tests), refactorer (improves code structure), documenter (writes documentation), explorer (navigates codebase, finds code locations, read-only), monitor (runs a long-running command such as a test watcher, build or dev server in the background and notifies you when failures appear; returns immediately), custom (user-defined role)."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -80,8 +92,8 @@ impl Tool for SubagentTool {
             "properties": {
                 "kind": {
                     "type": "string",
-                    "enum": ["code_analyzer", "tester", "refactorer", "documenter", "explorer", "custom"],
-                    "description": "Type of subagent to spawn. code_analyzer: read-only analysis, tester: creates/runs tests, refactorer: improves code, documenter: writes docs, explorer: codebase exploration (read-only), custom: user-defined"
+                    "enum": ["code_analyzer", "tester", "refactorer", "documenter", "explorer", "monitor", "custom"],
+                    "description": "Type of subagent to spawn. code_analyzer: read-only analysis, tester: creates/runs tests, refactorer: improves code, documenter: writes docs, explorer: codebase exploration (read-only), monitor: watches a background command, custom: user-defined"
                 },
                 "task": {
                     "type": "string",
@@ -95,6 +107,10 @@ impl Tool for SubagentTool {
                     "type": "array",
                     "items": { "type": "string" },
                     "description": "Optional file patterns to focus on (e.g., ['src/**/*.rs', 'tests/**/*.rs'])"
+                },
+                "command": {
+                    "type": "string",
+                    "description": "For 'monitor' kind only: the shell command to run and watch (e.g., 'cargo watch -x test', 'npm run dev')"
                 }
             },
             "required": ["kind", "task"]
@@ -112,10 +128,11 @@ impl Tool for SubagentTool {
             "refactorer" => SubagentKind::Refactorer,
             "documenter" => SubagentKind::Documenter,
             "explorer" => SubagentKind::Explorer,
+            "monitor" => SubagentKind::Monitor,
             "custom" => SubagentKind::Custom,
             _ => {
                 return Err(anyhow::anyhow!(
-                    "Invalid subagent kind: {}. Valid kinds: code_analyzer, tester, refactorer, documenter, explorer, custom",
+                    "Invalid subagent kind: {}. Valid kinds: code_analyzer, tester, refactorer, documenter, explorer, monitor, custom",
                     params.kind
                 ));
             }
@@ -144,88 +161,33 @@ impl Tool for SubagentTool {
         })?;
 
         // Create event channel
-        let (event_tx, mut event_rx) = mpsc::unbounded_channel::<SubagentEvent>();
+        let (event_tx, event_rx) = mpsc::unbounded_channel::<SubagentEvent>();
 
         // Forward events to session for live streaming
         // Prefer the session_event_tx from context (per-message) over the stored one
-        use crate::session::SessionEvent;
-        let forward_tx: Option<tokio::sync::mpsc::UnboundedSender<SessionEvent>> =
-            ctx.session_event_tx.clone();
-        tokio::spawn(async move {
-            while let Some(event) = event_rx.recv().await {
-                if let Some(ref tx) = forward_tx {
-                    // Convert SubagentEvent to SessionEvent and forward
-                    let session_event: SessionEvent = match &event {
-                        SubagentEvent::Started { id, kind, task } => {
-                            SessionEvent::SubagentStarted {
-                                id: id.clone(),
-                                kind: kind.display_name().to_string(),
-                                task: task.clone(),
-                            }
-                        }
-                        SubagentEvent::Thinking { id, message } => SessionEvent::SubagentProgress {
-                            id: id.clone(),
-                            message: message.clone(),
-                        },
-                        SubagentEvent::ToolStart {
-                            id,
-                            tool_name,
-                            description,
-                        } => SessionEvent::SubagentToolUsed {
-                            id: id.clone(),
-                            tool: tool_name.clone(),
-                            description: description.clone(),
-                        },
-                        SubagentEvent::ToolOutput {
-                            id,
-                            tool_name,
-                            output,
-                        } => SessionEvent::SubagentProgress {
-                            id: id.clone(),
-                            message: format!("{}: {}", tool_name, output),
-                        },
-                        SubagentEvent::ToolComplete {
-                            id,
-                            tool_name,
-                            success,
-                        } => SessionEvent::SubagentProgress {
-                            id: id.clone(),
-                            message: format!(
-                                "{} {}",
-                                tool_name,
-                                if *success { "done" } else { "failed" }
-                            ),
-                        },
-                        SubagentEvent::TextChunk { id, text } => SessionEvent::SubagentProgress {
-                            id: id.clone(),
-                            message: text.clone(),
-                        },
-                        SubagentEvent::IterationComplete {
-                            id,
-                            iteration,
-                            max_iterations,
-                        } => SessionEvent::SubagentProgress {
-                            id: id.clone(),
-                            message: format!("Iteration {}/{}", iteration, max_iterations),
-                        },
-                        SubagentEvent::Completed {
-                            id,
-                            success,
-                            summary,
-                        } => SessionEvent::SubagentCompleted {
-                            id: id.clone(),
-                            success: *success,
-                            summary: summary.clone(),
-                        },
-                        SubagentEvent::Error { id, error } => SessionEvent::SubagentProgress {
-                            id: id.clone(),
-                            message: format!("Error: {}", error),
-                        },
-                    };
-                    let _ = tx.send(session_event);
-                }
-            }
-        });
+        forward_events(event_rx, ctx.session_event_tx.clone());
+
+        // Monitors run in the background and report through the monitor hub
+        if kind == SubagentKind::Monitor {
+            let command = params
+                .command
+                .filter(|c| !c.trim().is_empty())
+                .ok_or_else(|| anyhow::anyhow!("The monitor kind requires a 'command' to run"))?;
+            let id = spawn_monitor(
+                command.clone(),
+                project_path.clone(),
+                config,
+                &self.monitors,
+                event_tx,
+            )
+            .await?;
+            return Ok(format!(
+                "{} Monitor {} is watching `{}` in the background. Failures will be reported as they appear.",
+                kind.icon(),
+                id,
+                command
+            ));
+        }
 
         // Create and run executor
         let mut executor =
@@ -256,6 +218,88 @@ impl Tool for SubagentTool {
     }
 }
 
+/// Forward a subagent's events to the session as SubagentStarted/Progress/Completed
+pub(crate) fn forward_events(
+    mut event_rx: mpsc::UnboundedReceiver<SubagentEvent>,
+    forward_tx: Option<mpsc::UnboundedSender<SessionEvent>>,
+) {
+    tokio::spawn(async move {
+        while let Some(event) = event_rx.recv().await {
+            if let Some(ref tx) = forward_tx {
+                // Convert SubagentEvent to SessionEvent and forward
+                let session_event: SessionEvent = match &event {
+                    SubagentEvent::Started { id, name, task } => {
+                        SessionEvent::SubagentStarted {
+                            id: id.clone(),
+                            kind: name.clone(),
+                            task: task.clone(),
+                        }
+                    }
+                    SubagentEvent::Thinking { id, message } => SessionEvent::SubagentProgress {
+                        id: id.clone(),
+                        message: message.clone(),
+                    },
+                    SubagentEvent::ToolStart {
+                        id,
+                        tool_name,
+                        description,
+                    } => SessionEvent::SubagentToolUsed {
+                        id: id.clone(),
+                        tool: tool_name.clone(),
+                        description: description.clone(),
+                    },
+                    SubagentEvent::ToolOutput {
+                        id,
+                        tool_name,
+                        output,
+                    } => SessionEvent::SubagentProgress {
+                        id: id.clone(),
+                        message: format!("{}: {}", tool_name, output),
+                    },
+                    SubagentEvent::ToolComplete {
+                        id,
+                        tool_name,
+                        success,
+                    } => SessionEvent::SubagentProgress {
+                        id: id.clone(),
+                        message: format!(
+                            "{} {}",
+                            tool_name,
+                            if *success { "done" } else { "failed" }
+                        ),
+                    },
+                    SubagentEvent::TextChunk { id, text } => SessionEvent::SubagentProgress {
+                        id: id.clone(),
+                        message: text.clone(),
+                    },
+                    SubagentEvent::IterationComplete {
+                        id,
+                        iteration,
+                        max_iterations,
+                    } => SessionEvent::SubagentProgress {
+                        id: id.clone(),
+                        message: format!("Iteration {}/{}", iteration, max_iterations),
+                    },
+                    SubagentEvent::Completed {
+                        id,
+                        success,
+                        summary,
+                    } => SessionEvent::SubagentCompleted {
+                        id: id.clone(),
+                        success: *success,
+                        summary: summary.clone(),
+                    },
+                    SubagentEvent::Error { id, error } => SessionEvent::SubagentProgress {
+                        id: id.clone(),
+                        message: format!("Error: {}", error),
+                    },
+                };
+                let _ = tx.send(session_event);
+            }
+        }
+    });
+}
+
 /// Format subagent result for display
 fn format_result(kind: &SubagentKind, id: &str, result: &SubagentResult) -> String {
     let status = if result.success {
@@ -5,6 +5,8 @@
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+use super::definitions::AgentDefinition;
+
 /// Types of specialized subagents available
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
@@ -19,6 +21,8 @@ pub enum SubagentKind {
     Documenter,
     /// Explores codebase structure, finds patterns, answers "where is X" questions (read-only)
     Explorer,
+    /// Watches a long-running background command and reports failures
+    Monitor,
     /// Custom subagent with user-defined role
     Custom,
 }
@@ -61,6 +65,8 @@ impl SubagentKind {
                 "ast_grep",
                 "code_search",
             ],
+            // Monitor only summarizes the output of the command it watches
+            SubagentKind::Monitor => &[],
             // Custom gets basic tools plus bash for flexibility
             SubagentKind::Custom => &["read_file", "list", "glob", "grep", "bash"],
         }
@@ -79,6 +85,7 @@ impl SubagentKind {
             SubagentKind::Refactorer => "Refactorer",
             SubagentKind::Documenter => "Documenter",
             SubagentKind::Explorer => "Explorer",
+            SubagentKind::Monitor => "Monitor",
             SubagentKind::Custom => "Custom Agent",
         }
     }
@@ -91,6 +98,7 @@ impl SubagentKind {
             SubagentKind::Refactorer => "🔧",
             SubagentKind::Documenter => "📝",
             SubagentKind::Explorer => "🔭",
+            SubagentKind::Monitor => "📡",
             SubagentKind::Custom => "🤖",
         }
     }
@@ -115,6 +123,8 @@ pub struct SubagentScope {
     pub timeout: Duration,
     /// Maximum iterations in the conversation loop (default: 15)
     pub max_iterations: usize,
+    /// User-defined agent this subagent runs as (Custom kind)
+    pub agent: Option<AgentDefinition>,
 }
 
 impl SubagentScope {
@@ -126,6 +136,38 @@ impl SubagentScope {
             file_patterns: Vec::new(),
             timeout: Duration::from_secs(300), // 5 minutes
             max_iterations: 15,
+            agent: None,
+        }
+    }
+
+    /// Run as a user-defined agent, taking its instructions, tools and turn limit
+    pub fn with_agent(mut self, agent: AgentDefinition) -> Self {
+        if let Some(max_turns) = agent.max_turns {
+            self.max_iterations = max_turns;
+        }
+        self.agent = Some(agent);
+        self
+    }
+
+    /// Tools the subagent may use: the agent definition's list if it has one,
+    /// otherwise the defaults for its kind
+    pub fn allowed_tools<'a>(&'a self, kind: &SubagentKind) -> Vec<&'a str> {
+        match self.agent.as_ref().and_then(|agent| agent.tools.as_ref()) {
+            Some(tools) => tools.iter().map(String::as_str).collect(),
+            None => kind.allowed_tools().to_vec(),
+        }
+    }
+
+    /// Check if a tool is allowed for this subagent
+    pub fn is_tool_allowed(&self, kind: &SubagentKind, tool_name: &str) -> bool {
+        self.allowed_tools(kind).contains(&tool_name)
+    }
+
+    /// Display name: the agent definition's name, or the kind's
+    pub fn display_name<'a>(&'a self, kind: &SubagentKind) -> &'a str {
+        match self.agent {
+            Some(ref agent) => &agent.name,
+            None => kind.display_name(),
         }
     }
 
@@ -230,7 +272,8 @@ pub enum SubagentEvent {
     /// Subagent started
     Started {
         id: String,
-        kind: SubagentKind,
+        /// Display name (the agent name for user-defined agents)
+        name: String,
         task: String,
     },
     /// Subagent is thinking/processing
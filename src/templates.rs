@@ -0,0 +1,572 @@
+//! Project templates for `safe-coder init --template`
+//!
+//! A template is a small starter project plus the Safe Coder setup that
+//! suits its stack: a SAFE_CODER.md memory file describing the layout and
+//! commands, a `.safe-coder/config.toml` with the build check and allowed
+//! hosts, and project skills with the stack's conventions. `{{name}}` in any
+//! file is replaced by the project name.
+
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+
+/// A starter project
+pub struct Template {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Project files, relative to the project root
+    pub files: &'static [(&'static str, &'static str)],
+    /// `.safe-coder/SAFE_CODER.md`
+    pub memory: &'static str,
+    /// `.safe-coder/config.toml`
+    pub config: &'static str,
+    /// Skills written to `.safe-coder/skills/<name>.md`
+    pub skills: &'static [(&'static str, &'static str)],
+    /// Commands to run next, shown after scaffolding
+    pub next_steps: &'static [&'static str],
+}
+
+/// All templates
+pub const TEMPLATES: &[Template] = &[RUST_CLI, AXUM_API, REACT_TS];
+
+/// The template called `name`
+pub fn find(name: &str) -> Option<&'static Template> {
+    TEMPLATES.iter().find(|t| t.name == name)
+}
+
+/// Template names, for error messages and help
+pub fn names() -> Vec<&'static str> {
+    TEMPLATES.iter().map(|t| t.name).collect()
+}
+
+/// What scaffolding wrote and what it left alone
+#[derive(Debug, Default)]
+pub struct Scaffolded {
+    pub created: Vec<PathBuf>,
+    /// Files that already existed and were kept
+    pub skipped: Vec<PathBuf>,
+}
+
+/// Write `template` into `root`. Existing files are never overwritten.
+pub fn scaffold(template: &Template, root: &Path) -> Result<Scaffolded> {
+    let name = project_name(root);
+    let mut files: Vec<(PathBuf, &str)> = template
+        .files
+        .iter()
+        .map(|(path, content)| (PathBuf::from(path), *content))
+        .collect();
+    let safe_coder = Path::new(".safe-coder");
+    files.push((safe_coder.join("SAFE_CODER.md"), template.memory));
+    files.push((safe_coder.join("config.toml"), template.config));
+    for (skill, content) in template.skills {
+        files.push((
+            safe_coder.join("skills").join(format!("{}.md", skill)),
+            content,
+        ));
+    }
+
+    let mut result = Scaffolded::default();
+    for (relative, content) in files {
+        let path = root.join(&relative);
+        if path.exists() {
+            result.skipped.push(relative);
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        std::fs::write(&path, content.replace("{{name}}", &name))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        result.created.push(relative);
+    }
+    Ok(result)
+}
+
+/// Package name for a project directory: its name, lowercased, with anything
+/// other than letters and digits turned into dashes
+fn project_name(root: &Path) -> String {
+    let dir = root
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    let name: String = dir
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let name = name.trim_matches('-');
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("app-{}", name).trim_end_matches('-').to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+const RUST_GITIGNORE: &str = "/target\n";
+
+const RUST_CLI: Template = Template {
+    name: "rust-cli",
+    description: "Rust command-line tool with clap and anyhow",
+    files: &[
+        (
+            "Cargo.toml",
+            r#"[package]
+name = "{{name}}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "1"
+clap = { version = "4", features = ["derive"] }
+"#,
+        ),
+        (
+            "src/main.rs",
+            r#"use anyhow::Result;
+use clap::Parser;
+
+/// {{name}}
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Who to greet
+    #[arg(default_value = "world")]
+    name: String,
+}
+
+fn greeting(name: &str) -> String {
+    format!("Hello, {}!", name)
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    println!("{}", greeting(&cli.name));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_greeting() {
+        assert_eq!(greeting("world"), "Hello, world!");
+    }
+}
+"#,
+        ),
+        (".gitignore", RUST_GITIGNORE),
+    ],
+    memory: r#"# {{name}}
+
+A Rust command-line tool.
+
+## Layout
+
+- `src/main.rs` - argument parsing (clap derive) and the entry point
+
+## Commands
+
+- Build: `cargo build`
+- Run: `cargo run -- <args>`
+- Test: `cargo test`
+- Lint: `cargo clippy --all-targets -- -D warnings`
+
+## Conventions
+
+- Return `anyhow::Result` from fallible functions; add `.context(...)` where the cause isn't obvious
+- Keep `main` thin: parse arguments, call into functions that can be unit tested
+- Write user-facing output to stdout and diagnostics to stderr
+- Unit tests live in a `#[cfg(test)] mod tests` block in the same file
+"#,
+    config: r#"# Safe Coder settings for this project
+
+[build]
+commands = { "Cargo.toml" = "cargo clippy --all-targets 2>&1" }
+
+[permissions]
+preset = "dev"
+"#,
+    skills: &[(
+        "rust-cli",
+        r#"---
+name: rust-cli
+trigger: "*.rs"
+description: Conventions for Rust command-line tools
+---
+
+## Command-line tools in Rust
+
+1. Define arguments with `#[derive(Parser)]`; document each field, clap uses the doc comment as help text
+2. Use subcommands (`#[derive(Subcommand)]`) once there is more than one action
+3. Exit non-zero on failure: return `anyhow::Result<()>` from `main`
+4. Print results to stdout, progress and errors to stderr (`eprintln!`)
+5. Read from stdin when a file argument is `-`
+6. Test argument parsing with `Cli::try_parse_from([...])`
+"#,
+    )],
+    next_steps: &["cargo run -- --help", "cargo test"],
+};
+
+const AXUM_API: Template = Template {
+    name: "axum-api",
+    description: "Rust HTTP API with axum, tokio and serde",
+    files: &[
+        (
+            "Cargo.toml",
+            r#"[package]
+name = "{{name}}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+anyhow = "1"
+axum = "0.7"
+serde = { version = "1", features = ["derive"] }
+serde_json = "1"
+tokio = { version = "1", features = ["full"] }
+tracing = "0.1"
+tracing-subscriber = { version = "0.3", features = ["env-filter"] }
+
+[dev-dependencies]
+http-body-util = "0.1"
+tower = { version = "0.5", features = ["util"] }
+"#,
+        ),
+        (
+            "src/main.rs",
+            r##"use anyhow::Result;
+use axum::{routing::get, Json, Router};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Health {
+    status: &'static str,
+}
+
+async fn health() -> Json<Health> {
+    Json(Health { status: "ok" })
+}
+
+fn app() -> Router {
+    Router::new().route("/health", get(health))
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let addr = std::env::var("ADDR").unwrap_or_else(|_| "127.0.0.1:3000".to_string());
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    tracing::info!("listening on {}", addr);
+    axum::serve(listener, app()).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::{Request, StatusCode};
+    use http_body_util::BodyExt;
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_health() {
+        let response = app()
+            .oneshot(Request::get("/health").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert_eq!(&body[..], br#"{"status":"ok"}"#);
+    }
+}
+"##,
+        ),
+        (".gitignore", RUST_GITIGNORE),
+    ],
+    memory: r#"# {{name}}
+
+An HTTP API built with axum on tokio.
+
+## Layout
+
+- `src/main.rs` - router (`app()`), handlers and server startup
+
+## Commands
+
+- Run: `cargo run` (listens on `ADDR`, default 127.0.0.1:3000; `RUST_LOG=debug` for logs)
+- Test: `cargo test`
+- Lint: `cargo clippy --all-targets -- -D warnings`
+- Try an endpoint: use the `http_request` tool against http://localhost:3000
+
+## Conventions
+
+- Build the router in `app()` so tests can call it with `tower::ServiceExt::oneshot` without a server
+- Request and response bodies are serde structs wrapped in `Json`
+- Return `Result<_, AppError>` from handlers once they can fail, with `AppError: IntoResponse`
+- Every new route gets a test next to the existing ones
+"#,
+    config: r#"# Safe Coder settings for this project
+
+[build]
+commands = { "Cargo.toml" = "cargo clippy --all-targets 2>&1" }
+
+[tools]
+http_allowed_hosts = ["localhost", "127.0.0.1"]
+
+[permissions]
+preset = "dev"
+"#,
+    skills: &[(
+        "axum-api",
+        r#"---
+name: axum-api
+trigger: "*.rs"
+description: Handler, error and testing patterns for axum APIs
+---
+
+## axum APIs
+
+1. Handlers are `async fn`s taking extractors (`Path`, `Query`, `Json`, `State`) and returning `impl IntoResponse`
+2. Put the body extractor (`Json<T>`) last in the argument list
+3. Share state with `Router::with_state` and `State<AppState>`; keep `AppState` cheap to clone (`Arc` inside)
+4. Map errors through one `AppError` type implementing `IntoResponse`, with the right status code per case
+5. Group routes with `Router::nest` by resource
+6. Test through the router: `app().oneshot(Request::...)`, then check status and JSON body
+"#,
+    )],
+    next_steps: &["cargo run", "curl http://127.0.0.1:3000/health"],
+};
+
+const REACT_TS: Template = Template {
+    name: "react-ts",
+    description: "React single-page app with TypeScript, Vite and Vitest",
+    files: &[
+        (
+            "package.json",
+            r#"{
+  "name": "{{name}}",
+  "private": true,
+  "version": "0.1.0",
+  "type": "module",
+  "scripts": {
+    "dev": "vite",
+    "build": "tsc --noEmit && vite build",
+    "preview": "vite preview",
+    "test": "vitest run"
+  },
+  "dependencies": {
+    "react": "^18.3.1",
+    "react-dom": "^18.3.1"
+  },
+  "devDependencies": {
+    "@testing-library/react": "^16.0.1",
+    "@types/react": "^18.3.11",
+    "@types/react-dom": "^18.3.1",
+    "@vitejs/plugin-react": "^4.3.2",
+    "jsdom": "^25.0.1",
+    "typescript": "^5.6.3",
+    "vite": "^5.4.9",
+    "vitest": "^2.1.3"
+  }
+}
+"#,
+        ),
+        (
+            "tsconfig.json",
+            r#"{
+  "compilerOptions": {
+    "target": "ES2020",
+    "lib": ["ES2020", "DOM", "DOM.Iterable"],
+    "module": "ESNext",
+    "moduleResolution": "bundler",
+    "jsx": "react-jsx",
+    "strict": true,
+    "noUnusedLocals": true,
+    "noUnusedParameters": true,
+    "isolatedModules": true,
+    "skipLibCheck": true,
+    "types": ["vitest/globals"]
+  },
+  "include": ["src"]
+}
+"#,
+        ),
+        (
+            "vite.config.ts",
+            r#"/// <reference types="vitest" />
+import { defineConfig } from 'vite';
+import react from '@vitejs/plugin-react';
+
+export default defineConfig({
+  plugins: [react()],
+  test: {
+    environment: 'jsdom',
+    globals: true,
+  },
+});
+"#,
+        ),
+        (
+            "index.html",
+            r#"<!doctype html>
+<html lang="en">
+  <head>
+    <meta charset="UTF-8" />
+    <meta name="viewport" content="width=device-width, initial-scale=1.0" />
+    <title>{{name}}</title>
+  </head>
+  <body>
+    <div id="root"></div>
+    <script type="module" src="/src/main.tsx"></script>
+  </body>
+</html>
+"#,
+        ),
+        (
+            "src/main.tsx",
+            r#"import { StrictMode } from 'react';
+import { createRoot } from 'react-dom/client';
+import App from './App';
+
+createRoot(document.getElementById('root')!).render(
+  <StrictMode>
+    <App />
+  </StrictMode>,
+);
+"#,
+        ),
+        (
+            "src/App.tsx",
+            r#"import { useState } from 'react';
+
+export default function App() {
+  const [count, setCount] = useState(0);
+  return (
+    <main>
+      <h1>{{name}}</h1>
+      <button onClick={() => setCount((c) => c + 1)}>Clicked {count} times</button>
+    </main>
+  );
+}
+"#,
+        ),
+        (
+            "src/App.test.tsx",
+            r#"import { fireEvent, render, screen } from '@testing-library/react';
+import App from './App';
+
+test('counts clicks', () => {
+  render(<App />);
+  fireEvent.click(screen.getByRole('button'));
+  expect(screen.getByRole('button').textContent).toBe('Clicked 1 times');
+});
+"#,
+        ),
+        (".gitignore", "node_modules\ndist\n"),
+    ],
+    memory: r#"# {{name}}
+
+A React single-page app written in TypeScript, built with Vite and tested with Vitest.
+
+## Layout
+
+- `src/main.tsx` - mounts the app
+- `src/App.tsx` - root component
+- `src/*.test.tsx` - component tests (Testing Library, jsdom)
+
+## Commands
+
+- Install: `npm install`
+- Dev server: `npm run dev` (http://localhost:5173)
+- Type check and build: `npm run build`
+- Test: `npm test`
+
+## Conventions
+
+- Function components with hooks; one component per file, named like the file
+- Props get an explicit type; no `any`
+- Test behaviour through Testing Library queries by role or text, not implementation details
+- Check rendered pages against the dev server with the `browser` tool when it is available
+"#,
+    config: r#"# Safe Coder settings for this project
+
+[build]
+commands = { "package.json" = "npx tsc --noEmit 2>&1" }
+
+[tools]
+http_allowed_hosts = ["localhost", "127.0.0.1"]
+protected_paths = ["node_modules/**", "dist/**"]
+
+[permissions]
+preset = "dev"
+"#,
+    skills: &[(
+        "react-ts",
+        r#"---
+name: react-ts
+triggers: ["*.tsx", "*.ts"]
+description: Component, typing and testing conventions for this React app
+---
+
+## React with TypeScript
+
+1. Type props with an interface named `<Component>Props`; give optional props defaults in the destructuring
+2. Derive values during render instead of mirroring them in state
+3. Keep side effects in `useEffect` with complete dependency lists, and return a cleanup when subscribing
+4. Lift state only as far as the nearest common parent; reach for context before a state library
+5. Tests render the component, interact through `screen.getByRole`/`getByText` and `fireEvent` or `userEvent`, and assert on what the user sees
+"#,
+    )],
+    next_steps: &["npm install", "npm run dev"],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scaffold_writes_project_and_setup() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("My Api");
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join(".gitignore"), "custom\n").unwrap();
+
+        let result = scaffold(find("axum-api").unwrap(), &root).unwrap();
+        assert!(result.created.contains(&PathBuf::from("src/main.rs")));
+        assert!(result
+            .created
+            .contains(&PathBuf::from(".safe-coder/skills/axum-api.md")));
+        assert_eq!(result.skipped, vec![PathBuf::from(".gitignore")]);
+        assert_eq!(
+            std::fs::read_to_string(root.join(".gitignore")).unwrap(),
+            "custom\n"
+        );
+
+        let manifest = std::fs::read_to_string(root.join("Cargo.toml")).unwrap();
+        assert!(manifest.contains("name = \"my-api\""));
+        let memory = std::fs::read_to_string(root.join(".safe-coder/SAFE_CODER.md")).unwrap();
+        assert!(memory.starts_with("# my-api\n"));
+    }
+
+    #[test]
+    fn test_template_configs_and_skills_load() {
+        for template in TEMPLATES {
+            let dir = tempfile::tempdir().unwrap();
+            scaffold(template, dir.path()).unwrap();
+            crate::config::Config::load_for_project(dir.path())
+                .unwrap_or_else(|e| panic!("{}: {:#}", template.name, e));
+
+            for (name, content) in template.skills {
+                let skill = crate::skills::Skill::from_content(content, None).unwrap();
+                assert_eq!(skill.name, *name);
+                assert!(!skill.triggers.is_empty());
+            }
+        }
+    }
+}
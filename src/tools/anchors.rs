@@ -0,0 +1,281 @@
+//! Semantic anchors for partial file reads
+//!
+//! Resolves `read_file`'s `symbol` and `around_line` parameters to the line
+//! range of a definition using tree-sitter, so the model can read one
+//! function or class of a large file without working out offsets.
+
+use anyhow::{Context, Result};
+use tree_sitter::{Node, Parser};
+
+use super::ast_grep::AstLanguage;
+
+/// Node kinds read as a unit: functions, types and their containers
+const DEFINITION_KINDS: &[&str] = &[
+    // Rust
+    "function_item",
+    "function_signature_item",
+    "struct_item",
+    "enum_item",
+    "union_item",
+    "trait_item",
+    "impl_item",
+    "mod_item",
+    "macro_definition",
+    "const_item",
+    "static_item",
+    "type_item",
+    // Python
+    "function_definition",
+    "class_definition",
+    "decorated_definition",
+    // JavaScript / TypeScript
+    "function_declaration",
+    "generator_function_declaration",
+    "class_declaration",
+    "abstract_class_declaration",
+    "method_definition",
+    "interface_declaration",
+    "type_alias_declaration",
+    "enum_declaration",
+    // Go
+    "method_declaration",
+    "type_declaration",
+];
+
+/// Comments and attributes directly above a definition belong to it
+const LEADING_KINDS: &[&str] = &[
+    "line_comment",
+    "block_comment",
+    "comment",
+    "attribute_item",
+    "decorator",
+];
+
+/// A definition's lines, 1-based and inclusive
+#[derive(Debug, Clone, PartialEq)]
+pub struct Anchor {
+    pub kind: String,
+    pub name: String,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+fn parse(source: &str, language: AstLanguage) -> Result<tree_sitter::Tree> {
+    let mut parser = Parser::new();
+    parser.set_language(&language.get_language())?;
+    parser.parse(source, None).context("Failed to parse file")
+}
+
+/// Definitions named `symbol`. A qualified name (`Config::new`,
+/// `Config.load`) only matches inside a definition named like the qualifier.
+pub fn find_symbol(source: &str, language: AstLanguage, symbol: &str) -> Result<Vec<Anchor>> {
+    let (qualifier, name) = match symbol.rsplit_once("::").or_else(|| symbol.rsplit_once('.')) {
+        Some((qualifier, name)) => (qualifier.rsplit(['.', ':']).next(), name),
+        None => (None, symbol),
+    };
+
+    let tree = parse(source, language)?;
+    let mut anchors = Vec::new();
+    let mut scopes = Vec::new();
+    collect_matches(
+        tree.root_node(),
+        source,
+        name,
+        qualifier,
+        &mut scopes,
+        &mut anchors,
+    );
+    Ok(anchors)
+}
+
+fn collect_matches(
+    node: Node,
+    source: &str,
+    name: &str,
+    qualifier: Option<&str>,
+    scopes: &mut Vec<String>,
+    anchors: &mut Vec<Anchor>,
+) {
+    let definition_name = DEFINITION_KINDS
+        .contains(&node.kind())
+        .then(|| definition_name(node, source))
+        .flatten();
+
+    if let Some(found) = &definition_name {
+        let in_scope = qualifier.is_none_or(|q| scopes.iter().any(|s| s == q));
+        if found == name && in_scope {
+            anchors.push(anchor(node, found.clone()));
+            // A decorated Python definition also contains the bare one
+            return;
+        }
+        scopes.push(found.clone());
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_matches(child, source, name, qualifier, scopes, anchors);
+    }
+
+    if definition_name.is_some() {
+        scopes.pop();
+    }
+}
+
+/// The innermost definition containing `line` (1-based)
+pub fn enclosing_definition(
+    source: &str,
+    language: AstLanguage,
+    line: usize,
+) -> Result<Option<Anchor>> {
+    let tree = parse(source, language)?;
+    let row = line.saturating_sub(1);
+
+    let mut node = tree.root_node();
+    let mut innermost = None;
+    'descend: loop {
+        let mut cursor = node.walk();
+        for child in node.children(&mut cursor) {
+            if child.start_position().row <= row && row <= child.end_position().row {
+                if DEFINITION_KINDS.contains(&child.kind()) {
+                    innermost = Some(child);
+                }
+                node = child;
+                continue 'descend;
+            }
+        }
+        break;
+    }
+
+    Ok(innermost.map(|node| {
+        let name = definition_name(node, source).unwrap_or_default();
+        anchor(node, name)
+    }))
+}
+
+/// Names of the definitions in a file, for a failed lookup
+pub fn list_symbols(source: &str, language: AstLanguage) -> Vec<String> {
+    let Ok(tree) = parse(source, language) else {
+        return Vec::new();
+    };
+    let mut names = Vec::new();
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if DEFINITION_KINDS.contains(&node.kind()) {
+            if let Some(name) = definition_name(node, source) {
+                if !names.contains(&name) {
+                    names.push(name);
+                }
+            }
+        }
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+    names
+}
+
+fn definition_name(node: Node, source: &str) -> Option<String> {
+    let named = match node.kind() {
+        "impl_item" => node.child_by_field_name("type"),
+        "decorated_definition" => node
+            .child_by_field_name("definition")
+            .and_then(|d| d.child_by_field_name("name")),
+        // `type Foo struct {...}` names its type_spec, not the declaration
+        "type_declaration" => node
+            .named_child(0)
+            .and_then(|spec| spec.child_by_field_name("name")),
+        _ => node.child_by_field_name("name"),
+    }?;
+    let text = named.utf8_text(source.as_bytes()).ok()?;
+    // `impl<T> Foo<T>` is found as `Foo`
+    Some(text.split('<').next().unwrap_or(text).trim().to_string())
+}
+
+fn anchor(node: Node, name: String) -> Anchor {
+    let mut start = node.start_position().row;
+    let mut prev = node.prev_sibling();
+    while let Some(sibling) = prev {
+        // Line comments end at the start of the next line
+        let end = sibling.end_position();
+        let end_row = if end.column == 0 {
+            end.row.saturating_sub(1)
+        } else {
+            end.row
+        };
+        if !LEADING_KINDS.contains(&sibling.kind()) || end_row + 1 < start {
+            break;
+        }
+        start = sibling.start_position().row;
+        prev = sibling.prev_sibling();
+    }
+    Anchor {
+        kind: node.kind().to_string(),
+        name,
+        start_line: start + 1,
+        end_line: node.end_position().row + 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = "use std::fmt;
+
+/// Settings
+#[derive(Debug)]
+struct Config {
+    name: String,
+}
+
+impl Config {
+    fn new() -> Self {
+        Config { name: String::new() }
+    }
+
+    fn load() -> Self {
+        Self::new()
+    }
+}
+
+fn new() {}
+";
+
+    #[test]
+    fn test_find_symbol_with_doc_comments_and_qualifier() {
+        let anchors = find_symbol(SOURCE, AstLanguage::Rust, "Config").unwrap();
+        assert_eq!(anchors.len(), 2);
+        assert_eq!((anchors[0].start_line, anchors[0].end_line), (3, 7));
+        assert_eq!(anchors[0].kind, "struct_item");
+        assert_eq!(anchors[1].kind, "impl_item");
+
+        assert_eq!(
+            find_symbol(SOURCE, AstLanguage::Rust, "new").unwrap().len(),
+            2
+        );
+        let method = find_symbol(SOURCE, AstLanguage::Rust, "Config::new").unwrap();
+        assert_eq!(method.len(), 1);
+        assert_eq!((method[0].start_line, method[0].end_line), (10, 12));
+
+        assert!(find_symbol(SOURCE, AstLanguage::Rust, "missing")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            list_symbols(SOURCE, AstLanguage::Rust),
+            vec!["Config", "new", "load"]
+        );
+    }
+
+    #[test]
+    fn test_enclosing_definition() {
+        let method = enclosing_definition(SOURCE, AstLanguage::Rust, 15)
+            .unwrap()
+            .unwrap();
+        assert_eq!(method.name, "load");
+        assert_eq!((method.start_line, method.end_line), (14, 16));
+
+        assert!(enclosing_definition(SOURCE, AstLanguage::Rust, 1)
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,421 @@
+//! Dependency vulnerability audits
+//!
+//! Runs cargo-audit, npm/pnpm audit or pip-audit for the project and turns
+//! their different JSON reports into one list of vulnerabilities, each with
+//! the affected package, advisory, severity and the fix if one exists.
+//! `/audit` feeds the result to the model to summarize and remediate.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::dependency::PackageManager;
+use super::{Tool, ToolContext, ToolError};
+
+/// One vulnerable package, normalized across auditors
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Vulnerability {
+    pub package: String,
+    /// Installed version, or the affected range when the auditor gives no version
+    pub version: String,
+    /// Advisory ID (RUSTSEC-…, GHSA-…, PYSEC-…)
+    pub id: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub severity: Option<String>,
+    pub title: String,
+    /// How to fix it, e.g. "upgrade to >=1.2.3", or None if no fix exists
+    pub fix: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+}
+
+/// Result of auditing a project
+#[derive(Debug, Serialize)]
+pub struct AuditReport {
+    /// The auditor that ran, e.g. "cargo-audit"
+    pub auditor: &'static str,
+    pub vulnerabilities: Vec<Vulnerability>,
+}
+
+impl AuditReport {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Prompt asking the model to summarize the findings and propose fixes.
+    /// With `fix`, it also tracks each remediation as a task and works
+    /// through them.
+    pub fn remediation_prompt(&self, fix: bool) -> String {
+        let mut prompt = format!(
+            "`{}` found {} known vulnerabilit{} in this project's dependencies:\n\n```json\n{}\n```\n\n\
+             Summarize them by severity, say which are reachable from direct dependencies, \
+             and propose a remediation for each (upgrade, replacement, or why it is safe to \
+             ignore). Group vulnerabilities that the same upgrade fixes.",
+            self.auditor,
+            self.vulnerabilities.len(),
+            if self.vulnerabilities.len() == 1 { "y" } else { "ies" },
+            self.to_json()
+        );
+        if fix {
+            prompt.push_str(
+                "\n\nThen add one todo per remediation with todowrite and carry them out, \
+                 using add_dependency for upgrades and run_tests after each one. Re-run \
+                 audit_dependencies at the end to confirm what is left.",
+            );
+        }
+        prompt
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditParams {
+    #[serde(default = "default_timeout")]
+    timeout_secs: u64,
+}
+
+fn default_timeout() -> u64 {
+    300
+}
+
+pub struct AuditDependenciesTool;
+
+#[async_trait]
+impl Tool for AuditDependenciesTool {
+    fn name(&self) -> &str {
+        "audit_dependencies"
+    }
+
+    fn description(&self) -> &str {
+        "Checks the project's dependencies for known vulnerabilities with cargo-audit, npm \
+         audit, pnpm audit or pip-audit, and returns them as JSON: package, version, advisory \
+         id, severity, title and the available fix."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Timeout in seconds (default 300)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: AuditParams = serde_json::from_value(params)?;
+        let report = audit(ctx.working_dir, params.timeout_secs).await?;
+        Ok(report.to_json())
+    }
+}
+
+/// Audit the project with the auditor for its package manager
+pub async fn audit(project: &Path, timeout_secs: u64) -> Result<AuditReport> {
+    let manager = PackageManager::detect(project).ok_or_else(|| {
+        ToolError::InvalidInput(
+            "No Cargo.toml, package.json, pyproject.toml or requirements.txt found".into(),
+        )
+    })?;
+    let (auditor, program, args): (&'static str, &str, Vec<&str>) = match manager {
+        PackageManager::Cargo => ("cargo-audit", "cargo", vec!["audit", "--json"]),
+        PackageManager::Npm => ("npm audit", "npm", vec!["audit", "--json"]),
+        PackageManager::Pnpm => ("pnpm audit", "pnpm", vec!["audit", "--json"]),
+        PackageManager::Yarn => bail!(
+            "yarn projects aren't supported; run `yarn npm audit --json` (yarn 2+) or \
+             `yarn audit --json` with bash"
+        ),
+        PackageManager::Poetry | PackageManager::Pip => {
+            if project.join("requirements.txt").exists() {
+                (
+                    "pip-audit",
+                    "pip-audit",
+                    vec!["-f", "json", "-r", "requirements.txt"],
+                )
+            } else {
+                ("pip-audit", "pip-audit", vec!["-f", "json"])
+            }
+        }
+    };
+    let installed = match auditor {
+        "cargo-audit" => which::which("cargo-audit").is_ok(),
+        _ => which::which(program).is_ok(),
+    };
+    if !installed {
+        let hint = match auditor {
+            "cargo-audit" => "cargo install cargo-audit",
+            "pip-audit" => "pip install pip-audit",
+            _ => "install Node.js",
+        };
+        bail!("{} is not installed ({})", auditor, hint);
+    }
+
+    let shown = format!("{} {}", program, args.join(" "));
+    let output = tokio::time::timeout(
+        Duration::from_secs(timeout_secs),
+        Command::new(program)
+            .args(&args)
+            .current_dir(project)
+            .env("NO_COLOR", "1")
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| ToolError::Timeout {
+        command: shown.clone(),
+        secs: timeout_secs,
+    })?
+    .with_context(|| format!("Failed to run {}", shown))?;
+
+    // Auditors exit non-zero when they find something, so judge by the report
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let json: serde_json::Value = serde_json::from_str(stdout.trim()).with_context(|| {
+        format!(
+            "`{}` didn't produce a JSON report: {}",
+            shown,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+    })?;
+    let vulnerabilities = match manager {
+        PackageManager::Cargo => parse_cargo_audit(&json),
+        PackageManager::Npm | PackageManager::Pnpm | PackageManager::Yarn => parse_npm_audit(&json),
+        PackageManager::Poetry | PackageManager::Pip => parse_pip_audit(&json),
+    };
+    Ok(AuditReport {
+        auditor,
+        vulnerabilities,
+    })
+}
+
+fn strings(value: &serde_json::Value) -> Vec<String> {
+    value
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_str().map(String::from))
+        .collect()
+}
+
+fn string(value: &serde_json::Value) -> Option<String> {
+    value.as_str().filter(|s| !s.is_empty()).map(String::from)
+}
+
+/// Findings from `cargo audit --json`
+pub fn parse_cargo_audit(json: &serde_json::Value) -> Vec<Vulnerability> {
+    json["vulnerabilities"]["list"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|entry| {
+            let advisory = &entry["advisory"];
+            let patched = strings(&entry["versions"]["patched"]);
+            Vulnerability {
+                package: string(&entry["package"]["name"]).unwrap_or_default(),
+                version: string(&entry["package"]["version"]).unwrap_or_default(),
+                id: string(&advisory["id"]).unwrap_or_default(),
+                aliases: strings(&advisory["aliases"]),
+                severity: string(&advisory["severity"]),
+                title: string(&advisory["title"]).unwrap_or_default(),
+                fix: (!patched.is_empty()).then(|| format!("upgrade to {}", patched.join(" or "))),
+                url: string(&advisory["url"]),
+            }
+        })
+        .collect()
+}
+
+/// Findings from `npm audit --json` (npm 7+) or the older advisories format
+/// that pnpm still prints
+pub fn parse_npm_audit(json: &serde_json::Value) -> Vec<Vulnerability> {
+    if let Some(advisories) = json["advisories"].as_object() {
+        return advisories
+            .values()
+            .map(|advisory| {
+                let patched = string(&advisory["patched_versions"]);
+                Vulnerability {
+                    package: string(&advisory["module_name"]).unwrap_or_default(),
+                    version: advisory["findings"][0]["version"]
+                        .as_str()
+                        .or_else(|| advisory["vulnerable_versions"].as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    id: string(&advisory["github_advisory_id"])
+                        .unwrap_or_else(|| advisory["id"].to_string()),
+                    aliases: strings(&advisory["cves"]),
+                    severity: string(&advisory["severity"]),
+                    title: string(&advisory["title"]).unwrap_or_default(),
+                    fix: patched
+                        .filter(|p| p != "<0.0.0")
+                        .map(|p| format!("upgrade to {}", p)),
+                    url: string(&advisory["url"]),
+                }
+            })
+            .collect();
+    }
+
+    let mut found = Vec::new();
+    for (name, entry) in json["vulnerabilities"].as_object().into_iter().flatten() {
+        let fix = match &entry["fixAvailable"] {
+            serde_json::Value::Bool(true) => Some("npm audit fix".to_string()),
+            serde_json::Value::Object(fix) => Some(format!(
+                "upgrade {} to {}{}",
+                fix.get("name").and_then(|v| v.as_str()).unwrap_or(name),
+                fix.get("version").and_then(|v| v.as_str()).unwrap_or("?"),
+                if fix.get("isSemVerMajor").and_then(|v| v.as_bool()) == Some(true) {
+                    " (semver-major)"
+                } else {
+                    ""
+                }
+            )),
+            _ => None,
+        };
+        // `via` lists advisories as objects; strings name the vulnerable
+        // dependency this package pulls in, which has its own entry
+        for via in entry["via"].as_array().into_iter().flatten() {
+            if !via.is_object() {
+                continue;
+            }
+            let url = string(&via["url"]);
+            found.push(Vulnerability {
+                package: name.clone(),
+                version: string(&via["range"])
+                    .or_else(|| string(&entry["range"]))
+                    .unwrap_or_default(),
+                id: url
+                    .as_deref()
+                    .and_then(|u| u.rsplit('/').next())
+                    .map(String::from)
+                    .unwrap_or_else(|| via["source"].to_string()),
+                aliases: Vec::new(),
+                severity: string(&via["severity"]).or_else(|| string(&entry["severity"])),
+                title: string(&via["title"]).unwrap_or_default(),
+                fix: fix.clone(),
+                url,
+            });
+        }
+    }
+    found
+}
+
+/// Findings from `pip-audit -f json`, which is either a list of dependencies
+/// or (since 2.5) an object holding one
+pub fn parse_pip_audit(json: &serde_json::Value) -> Vec<Vulnerability> {
+    let dependencies = json
+        .get("dependencies")
+        .unwrap_or(json)
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    let mut found = Vec::new();
+    for dependency in &dependencies {
+        for vuln in dependency["vulns"].as_array().into_iter().flatten() {
+            let fixed = strings(&vuln["fix_versions"]);
+            let description = vuln["description"].as_str().unwrap_or_default();
+            found.push(Vulnerability {
+                package: string(&dependency["name"]).unwrap_or_default(),
+                version: string(&dependency["version"]).unwrap_or_default(),
+                id: string(&vuln["id"]).unwrap_or_default(),
+                aliases: strings(&vuln["aliases"]),
+                severity: None,
+                title: crate::utils::truncate_str(
+                    description.lines().next().unwrap_or_default(),
+                    200,
+                )
+                .to_string(),
+                fix: (!fixed.is_empty()).then(|| format!("upgrade to {}", fixed.join(" or "))),
+                url: None,
+            });
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo_and_pip_audit() {
+        let cargo = serde_json::json!({
+            "vulnerabilities": {
+                "found": true,
+                "count": 1,
+                "list": [{
+                    "advisory": {
+                        "id": "RUSTSEC-2024-0003",
+                        "package": "h2",
+                        "title": "Resource exhaustion vulnerability in h2",
+                        "aliases": ["GHSA-8r5v-vm4m-4g25"],
+                        "url": "https://github.com/hyperium/h2/pull/737"
+                    },
+                    "versions": {"patched": ["^0.3.24", ">=0.4.2"], "unaffected": []},
+                    "package": {"name": "h2", "version": "0.3.20"}
+                }]
+            }
+        });
+        let found = parse_cargo_audit(&cargo);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].package, "h2");
+        assert_eq!(found[0].version, "0.3.20");
+        assert_eq!(
+            found[0].fix.as_deref(),
+            Some("upgrade to ^0.3.24 or >=0.4.2")
+        );
+        assert_eq!(found[0].aliases, vec!["GHSA-8r5v-vm4m-4g25"]);
+
+        let pip = serde_json::json!({
+            "dependencies": [
+                {"name": "requests", "version": "2.25.0", "vulns": [
+                    {"id": "PYSEC-2023-74", "fix_versions": ["2.31.0"], "aliases": ["CVE-2023-32681"],
+                     "description": "Requests leaks Proxy-Authorization headers.\nMore detail."}
+                ]},
+                {"name": "flask", "version": "3.0.0", "vulns": []}
+            ]
+        });
+        let found = parse_pip_audit(&pip);
+        assert_eq!(found.len(), 1);
+        assert_eq!(
+            found[0].title,
+            "Requests leaks Proxy-Authorization headers."
+        );
+        assert_eq!(found[0].fix.as_deref(), Some("upgrade to 2.31.0"));
+    }
+
+    #[test]
+    fn test_parse_npm_audit() {
+        let npm = serde_json::json!({
+            "vulnerabilities": {
+                "lodash": {
+                    "name": "lodash",
+                    "severity": "high",
+                    "via": [{
+                        "source": 1096305,
+                        "name": "lodash",
+                        "title": "Prototype Pollution in lodash",
+                        "url": "https://github.com/advisories/GHSA-jf85-cpcp-j695",
+                        "severity": "critical",
+                        "range": "<4.17.12"
+                    }],
+                    "range": "<=4.17.20",
+                    "fixAvailable": true
+                },
+                "express-helper": {
+                    "name": "express-helper",
+                    "severity": "high",
+                    "via": ["lodash"],
+                    "fixAvailable": {"name": "express-helper", "version": "2.0.0", "isSemVerMajor": true}
+                }
+            }
+        });
+        let found = parse_npm_audit(&npm);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, "GHSA-jf85-cpcp-j695");
+        assert_eq!(found[0].severity.as_deref(), Some("critical"));
+        assert_eq!(found[0].version, "<4.17.12");
+        assert_eq!(found[0].fix.as_deref(), Some("npm audit fix"));
+    }
+}
@@ -5,9 +5,8 @@ use serde::Deserialize;
 
 use std::process::Stdio;
 use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 
-use super::{Tool, ToolContext};
+use super::{Tool, ToolContext, ToolError};
 
 pub struct BashTool;
 
@@ -61,10 +60,15 @@ impl BashTool {
         let mut result = output[..safe_end].to_string();
         let truncated_bytes = output.len() - safe_end;
         result.push_str(&format!(
-            "\n\n[OUTPUT TRUNCATED: {} bytes omitted. Total output was {} bytes, limit is {} bytes]",
+            "\n\n[OUTPUT TRUNCATED: {} bytes omitted. Total output was {} bytes, limit is {} bytes]\n{}",
             truncated_bytes,
             output.len(),
-            max_bytes
+            max_bytes,
+            ToolError::OutputTruncated {
+                omitted_bytes: truncated_bytes,
+                limit: max_bytes,
+            }
+            .tag_line()
         ));
 
         result
@@ -78,7 +82,19 @@ impl Tool for BashTool {
     }
 
     fn description(&self) -> &str {
-        "Executes a bash command in the project directory and returns the output. Commands have a configurable timeout (default: 120s) and output size limit."
+        static DESCRIPTION: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+        DESCRIPTION.get_or_init(|| {
+            let mut description = "Executes a bash command in the project directory and returns the output. Commands have a configurable timeout (default: 120s) and output size limit.".to_string();
+            // Without sh (Windows without Git Bash), the model has to write for the native shell
+            let shell = crate::platform::Shell::current();
+            if *shell != crate::platform::Shell::Sh {
+                description.push_str(&format!(
+                    " On this machine commands run in {}, so use its syntax.",
+                    shell.name()
+                ));
+            }
+            description
+        })
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -128,6 +144,9 @@ impl Tool for BashTool {
             }
         }
 
+        // Protected paths are enforced even when dangerous command warnings are off
+        ctx.check_protected_command(&params.command)?;
+
         // Use config timeout as default, allow override from params
         let timeout_secs = params.timeout.unwrap_or(ctx.config.bash_timeout_secs);
         let timeout = tokio::time::Duration::from_secs(timeout_secs);
@@ -139,9 +158,7 @@ impl Tool for BashTool {
         );
 
         // Spawn the process with piped stdout/stderr for better control
-        let mut child = Command::new("sh")
-            .arg("-c")
-            .arg(&params.command)
+        let mut child = crate::platform::shell_command(&params.command)
             .current_dir(ctx.working_dir)
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
@@ -253,28 +270,15 @@ impl Tool for BashTool {
                         tracing::error!("Failed to kill timed-out process: {}", kill_err);
                     }
 
-                    let timeout_msg = format!(
-                        "⏱️  COMMAND TIMED OUT\n\n\
-                        The command '{}' exceeded the timeout of {} seconds and was terminated.\n\n\
-                        Possible reasons:\n\
-                        - The command is taking longer than expected\n\
-                        - The command is waiting for input\n\
-                        - The command entered an infinite loop\n\n\
-                        You can:\n\
-                        1. Increase the timeout by passing a 'timeout' parameter (e.g., timeout: 300)\n\
-                        2. Modify the default timeout in config:\n\
-                           [tools]\n\
-                           bash_timeout_secs = 300\n\
-                        3. Break the command into smaller operations",
-                        params.command,
-                        timeout_secs
-                    );
-                    
+                    let error = ToolError::Timeout {
+                        command: params.command,
+                        secs: timeout_secs,
+                    };
                     if let Some(ref callback) = ctx.output_callback {
-                        callback(timeout_msg.clone());
+                        callback(format!("⏱️  {}", error));
                     }
-                    
-                    Ok(timeout_msg)
+
+                    Err(error.into())
                 }
             }
         } else {
@@ -341,22 +345,11 @@ impl Tool for BashTool {
                         tracing::error!("Failed to kill timed-out process: {}", kill_err);
                     }
 
-                    Ok(format!(
-                        "⏱️  COMMAND TIMED OUT\n\n\
-                        The command '{}' exceeded the timeout of {} seconds and was terminated.\n\n\
-                        Possible reasons:\n\
-                        - The command is taking longer than expected\n\
-                        - The command is waiting for input\n\
-                        - The command entered an infinite loop\n\n\
-                        You can:\n\
-                        1. Increase the timeout by passing a 'timeout' parameter (e.g., timeout: 300)\n\
-                        2. Modify the default timeout in config:\n\
-                           [tools]\n\
-                           bash_timeout_secs = 300\n\
-                        3. Break the command into smaller operations",
-                        params.command,
-                        timeout_secs
-                    ))
+                    Err(ToolError::Timeout {
+                        command: params.command,
+                        secs: timeout_secs,
+                    }
+                    .into())
                 }
             }
         }
@@ -401,5 +394,9 @@ mod tests {
         let truncated = BashTool::truncate_output(long_output.clone(), 100);
         assert!(truncated.len() < long_output.len());
         assert!(truncated.contains("OUTPUT TRUNCATED"));
+        assert_eq!(
+            crate::tools::ToolErrorInfo::parse(&truncated).unwrap().code,
+            "output_truncated"
+        );
     }
 }
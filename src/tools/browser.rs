@@ -0,0 +1,375 @@
+//! Headless browser for checking that a web app renders
+//!
+//! Built with the `browser` feature. Each call launches a local Chrome or
+//! Chromium (found on PATH or via the `CHROME` environment variable), loads
+//! one page and closes the browser again. URLs are limited to
+//! `tools.http_allowed_hosts`, like `http_request`. Screenshots are written
+//! under the project and attached to the next message as images, so a
+//! multimodal model sees what the page looks like.
+
+use std::path::{Path, PathBuf};
+
+/// Where a screenshot is written unless the call gives a path
+pub const DEFAULT_SCREENSHOT_PATH: &str = ".safe-coder/screenshots/screenshot.png";
+
+/// The file a `screenshot` call writes, or None for other operations
+pub fn screenshot_path(working_dir: &Path, input: &serde_json::Value) -> Option<PathBuf> {
+    if input.get("operation").and_then(|v| v.as_str()) != Some("screenshot") {
+        return None;
+    }
+    let path = input
+        .get("path")
+        .and_then(|v| v.as_str())
+        .unwrap_or(DEFAULT_SCREENSHOT_PATH);
+    Some(working_dir.join(path))
+}
+
+#[cfg(feature = "browser")]
+pub use headless::BrowserTool;
+
+#[cfg(feature = "browser")]
+mod headless {
+    use anyhow::{Context, Result};
+    use async_trait::async_trait;
+    use chromiumoxide::cdp::js_protocol::runtime::{
+        ConsoleApiCalledType, EventConsoleApiCalled, EventExceptionThrown,
+    };
+    use chromiumoxide::page::ScreenshotParams;
+    use chromiumoxide::{Browser, BrowserConfig, Page};
+    use futures::{FutureExt, StreamExt};
+    use serde::Deserialize;
+    use std::time::Duration;
+
+    use super::super::http_request::host_allowed;
+    use super::super::{Tool, ToolContext, ToolError};
+    use super::screenshot_path;
+    use crate::utils::truncate_str;
+
+    #[derive(Debug, Deserialize)]
+    struct BrowserParams {
+        operation: String,
+        url: String,
+        #[serde(default)]
+        selector: Option<String>,
+        #[serde(default)]
+        full_page: bool,
+        #[serde(default = "default_wait_ms")]
+        wait_ms: u64,
+        #[serde(default = "default_timeout")]
+        timeout_secs: u64,
+    }
+
+    fn default_wait_ms() -> u64 {
+        500
+    }
+
+    fn default_timeout() -> u64 {
+        30
+    }
+
+    /// Visible page text returned by `browse`
+    const MAX_TEXT_CHARS: usize = 10_000;
+
+    /// Elements described by `query_dom`
+    const MAX_ELEMENTS: usize = 20;
+
+    /// HTML shown per element by `query_dom`
+    const MAX_ELEMENT_HTML_CHARS: usize = 500;
+
+    pub struct BrowserTool;
+
+    #[async_trait]
+    impl Tool for BrowserTool {
+        fn name(&self) -> &str {
+            "browser"
+        }
+
+        fn description(&self) -> &str {
+            "Loads a page in a headless browser to check that a web app actually renders. \
+             Operations: browse (title, visible text and JavaScript errors), screenshot \
+             (saves a PNG and shows it to you) and query_dom (elements matching a CSS \
+             selector). Start the dev server first. Only hosts allowed in \
+             tools.http_allowed_hosts can be loaded (localhost by default)."
+        }
+
+        fn parameters_schema(&self) -> serde_json::Value {
+            serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "operation": {
+                        "type": "string",
+                        "enum": ["browse", "screenshot", "query_dom"],
+                        "description": "What to do with the page"
+                    },
+                    "url": {
+                        "type": "string",
+                        "description": "Page to load, e.g. http://localhost:5173/"
+                    },
+                    "selector": {
+                        "type": "string",
+                        "description": "CSS selector (required for query_dom)"
+                    },
+                    "path": {
+                        "type": "string",
+                        "description": "Where to save a screenshot, relative to the project (default .safe-coder/screenshots/screenshot.png)"
+                    },
+                    "full_page": {
+                        "type": "boolean",
+                        "description": "Capture the whole page rather than the 1280x800 viewport (default false)"
+                    },
+                    "wait_ms": {
+                        "type": "integer",
+                        "description": "Extra time to wait after load for client-side rendering (default 500)"
+                    },
+                    "timeout_secs": {
+                        "type": "integer",
+                        "description": "Timeout in seconds (default 30)"
+                    }
+                },
+                "required": ["operation", "url"]
+            })
+        }
+
+        async fn execute(
+            &self,
+            params: serde_json::Value,
+            ctx: &ToolContext<'_>,
+        ) -> Result<String> {
+            let output_path = screenshot_path(ctx.working_dir, &params);
+            let params: BrowserParams = serde_json::from_value(params)?;
+            if !matches!(
+                params.operation.as_str(),
+                "browse" | "screenshot" | "query_dom"
+            ) {
+                return Err(ToolError::InvalidInput(format!(
+                    "Unknown operation '{}'; use browse, screenshot or query_dom",
+                    params.operation
+                ))
+                .into());
+            }
+            if params.operation == "query_dom" && params.selector.is_none() {
+                return Err(ToolError::InvalidInput("query_dom needs a selector".into()).into());
+            }
+
+            let url = url::Url::parse(&params.url).map_err(|e| {
+                ToolError::InvalidInput(format!("Invalid URL {}: {}", params.url, e))
+            })?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(ToolError::InvalidInput(
+                    "Only http and https URLs are supported".into(),
+                )
+                .into());
+            }
+            let host = url.host_str().unwrap_or_default();
+            if !host_allowed(host, &ctx.config.http_allowed_hosts) {
+                return Err(ToolError::PermissionDenied(format!(
+                    "{} is not in tools.http_allowed_hosts ({}). Ask the user to add it if the page is needed.",
+                    host,
+                    ctx.config.http_allowed_hosts.join(", ")
+                ))
+                .into());
+            }
+
+            let timeout = Duration::from_secs(params.timeout_secs);
+            let config = BrowserConfig::builder()
+                .window_size(1280, 800)
+                .request_timeout(timeout)
+                .build()
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "No Chrome or Chromium found ({}). Install one or set CHROME to its path.",
+                        e
+                    )
+                })?;
+            let (mut browser, mut handler) = Browser::launch(config)
+                .await
+                .context("Failed to launch the browser")?;
+            let handler_task = tokio::spawn(async move {
+                while let Some(event) = handler.next().await {
+                    if event.is_err() {
+                        break;
+                    }
+                }
+            });
+
+            let result =
+                tokio::time::timeout(timeout, visit(&browser, url.as_str(), &params, output_path))
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(ToolError::Timeout {
+                            command: format!("browser {} {}", params.operation, url),
+                            secs: params.timeout_secs,
+                        }
+                        .into())
+                    });
+
+            let _ = browser.close().await;
+            let _ = browser.wait().await;
+            handler_task.abort();
+            result
+        }
+    }
+
+    async fn visit(
+        browser: &Browser,
+        url: &str,
+        params: &BrowserParams,
+        output_path: Option<std::path::PathBuf>,
+    ) -> Result<String> {
+        let page = browser.new_page("about:blank").await?;
+        let mut exceptions = page.event_listener::<EventExceptionThrown>().await?;
+        let mut console = page.event_listener::<EventConsoleApiCalled>().await?;
+
+        page.goto(url)
+            .await
+            .with_context(|| format!("Failed to load {}", url))?;
+        tokio::time::sleep(Duration::from_millis(params.wait_ms)).await;
+
+        let mut errors = Vec::new();
+        while let Some(Some(event)) = exceptions.next().now_or_never() {
+            let details = &event.exception_details;
+            let message = details
+                .exception
+                .as_ref()
+                .and_then(|e| e.description.clone())
+                .unwrap_or_else(|| details.text.clone());
+            errors.push(format!("uncaught: {}", first_line(&message)));
+        }
+        while let Some(Some(event)) = console.next().now_or_never() {
+            if event.r#type == ConsoleApiCalledType::Error {
+                let args: Vec<String> = event
+                    .args
+                    .iter()
+                    .map(|arg| match (&arg.value, &arg.description) {
+                        (Some(serde_json::Value::String(s)), _) => s.clone(),
+                        (Some(value), _) => value.to_string(),
+                        (None, Some(description)) => description.clone(),
+                        (None, None) => String::new(),
+                    })
+                    .collect();
+                errors.push(format!("console.error: {}", first_line(&args.join(" "))));
+            }
+        }
+
+        let title = page.get_title().await?.unwrap_or_default();
+        let final_url = page.url().await?.unwrap_or_else(|| url.to_string());
+        let mut out = format!("{}\nTitle: {}\n", final_url, title);
+        if errors.is_empty() {
+            out.push_str("JavaScript errors: none\n");
+        } else {
+            out.push_str(&format!("JavaScript errors ({}):\n", errors.len()));
+            for error in &errors {
+                out.push_str(&format!("  {}\n", error));
+            }
+        }
+
+        match params.operation.as_str() {
+            "screenshot" => {
+                let path = output_path.context("No screenshot path")?;
+                let png = page
+                    .screenshot(
+                        ScreenshotParams::builder()
+                            .full_page(params.full_page)
+                            .build(),
+                    )
+                    .await
+                    .context("Failed to capture screenshot")?;
+                if let Some(parent) = path.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+                std::fs::write(&path, &png)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                out.push_str(&format!(
+                    "\nScreenshot saved to {} ({} KB); it is attached below.",
+                    path.display(),
+                    png.len() / 1024
+                ));
+            }
+            "query_dom" => {
+                let selector = params.selector.as_deref().unwrap_or_default();
+                out.push('\n');
+                out.push_str(&query_dom(&page, selector).await?);
+            }
+            _ => {
+                let text: String = page
+                    .evaluate("document.body ? document.body.innerText : ''")
+                    .await?
+                    .into_value()
+                    .unwrap_or_default();
+                out.push_str("\nVisible text:\n");
+                out.push_str(&cut(text.trim(), MAX_TEXT_CHARS));
+            }
+        }
+        Ok(out)
+    }
+
+    async fn query_dom(page: &Page, selector: &str) -> Result<String> {
+        let elements = match page.find_elements(selector).await {
+            Ok(elements) => elements,
+            // CDP reports both "no match" and a bad selector as errors
+            Err(e) if e.to_string().contains("Could not find node") => Vec::new(),
+            Err(e) => {
+                return Err(
+                    ToolError::InvalidInput(format!("Selector '{}': {}", selector, e)).into(),
+                )
+            }
+        };
+        if elements.is_empty() {
+            return Ok(format!("No elements match '{}'", selector));
+        }
+        let mut out = format!("{} element(s) match '{}'\n", elements.len(), selector);
+        for (i, element) in elements.iter().take(MAX_ELEMENTS).enumerate() {
+            let html = element.outer_html().await?.unwrap_or_default();
+            let text = element.inner_text().await?.unwrap_or_default();
+            out.push_str(&format!(
+                "\n[{}] {}\n    text: {}\n",
+                i + 1,
+                cut(&html, MAX_ELEMENT_HTML_CHARS),
+                cut(text.trim(), MAX_ELEMENT_HTML_CHARS)
+            ));
+        }
+        if elements.len() > MAX_ELEMENTS {
+            out.push_str(&format!("\n... {} more\n", elements.len() - MAX_ELEMENTS));
+        }
+        Ok(out)
+    }
+
+    fn first_line(text: &str) -> &str {
+        text.lines().next().unwrap_or_default()
+    }
+
+    fn cut(text: &str, max_chars: usize) -> String {
+        let shown = truncate_str(text, max_chars);
+        if shown.len() < text.len() {
+            format!("{}…", shown)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screenshot_path() {
+        let dir = Path::new("/project");
+        let input = serde_json::json!({ "operation": "screenshot", "url": "http://localhost/" });
+        assert_eq!(
+            screenshot_path(dir, &input),
+            Some(dir.join(DEFAULT_SCREENSHOT_PATH))
+        );
+        let input = serde_json::json!({
+            "operation": "screenshot",
+            "url": "http://localhost/",
+            "path": "shots/home.png"
+        });
+        assert_eq!(
+            screenshot_path(dir, &input),
+            Some(PathBuf::from("/project/shots/home.png"))
+        );
+        let input = serde_json::json!({ "operation": "browse", "url": "http://localhost/" });
+        assert_eq!(screenshot_path(dir, &input), None);
+    }
+}
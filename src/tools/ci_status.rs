@@ -0,0 +1,481 @@
+//! CI status for the current branch
+//!
+//! Looks up the latest GitHub Actions runs for the branch checked out in
+//! the project, or queries `tools.ci_status_url` for other CI systems. Logs
+//! of failing jobs are condensed to their error lines so the model can fix
+//! a red build without reading the whole log. Backs both the `ci_status`
+//! tool and the `/ci` command.
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+use tokio::process::Command;
+
+use super::{Tool, ToolContext};
+use crate::config::ToolConfig;
+use crate::subagent::monitor::is_failure_line;
+use crate::utils::truncate_str;
+
+const GITHUB_API: &str = "https://api.github.com";
+
+/// Log lines kept per failing job
+const MAX_LOG_LINES: usize = 40;
+
+/// Characters of a generic status response shown
+const MAX_STATUS_CHARS: usize = 5_000;
+
+/// CI state of a branch, rendered for the model or the user
+#[derive(Debug, Clone)]
+pub struct CiReport {
+    pub text: String,
+    /// Whether a run failed
+    pub failing: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CiStatusParams {
+    #[serde(default)]
+    branch: Option<String>,
+    #[serde(default = "default_logs")]
+    logs: bool,
+}
+
+fn default_logs() -> bool {
+    true
+}
+
+pub struct CiStatusTool;
+
+#[async_trait]
+impl Tool for CiStatusTool {
+    fn name(&self) -> &str {
+        "ci_status"
+    }
+
+    fn description(&self) -> &str {
+        "Shows the CI status (GitHub Actions, or tools.ci_status_url) of the latest run on \
+         the current branch, with the condensed logs of failing jobs. Use it after pushing, \
+         or when asked why CI is red."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "branch": {
+                    "type": "string",
+                    "description": "Branch to check (default: the checked-out branch)"
+                },
+                "logs": {
+                    "type": "boolean",
+                    "description": "Include condensed logs of failing jobs (default true)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: CiStatusParams = serde_json::from_value(params)?;
+        let report = ci_report(
+            ctx.working_dir,
+            ctx.config,
+            params.branch.as_deref(),
+            params.logs,
+        )
+        .await?;
+        Ok(report.text)
+    }
+}
+
+impl CiReport {
+    /// Message asking the model to fix the failures in this report
+    pub fn fix_prompt(&self) -> String {
+        format!(
+            "CI is failing on this branch.\n\n{}\n\nFind the cause of these failures and fix it.",
+            self.text
+        )
+    }
+}
+
+/// CI status of `branch` (the checked-out branch by default)
+pub async fn ci_report(
+    project: &Path,
+    config: &ToolConfig,
+    branch: Option<&str>,
+    with_logs: bool,
+) -> Result<CiReport> {
+    let branch = match branch {
+        Some(branch) => branch.to_string(),
+        None => git(project, &["rev-parse", "--abbrev-ref", "HEAD"]).await?,
+    };
+
+    if let Some(url) = &config.ci_status_url {
+        return generic_report(&url.replace("{branch}", &branch)).await;
+    }
+
+    let remote = git(project, &["remote", "get-url", "origin"]).await?;
+    let Some((owner, repo)) = parse_github_remote(&remote) else {
+        bail!(
+            "origin ({}) is not a GitHub repository; set tools.ci_status_url to query another CI",
+            remote
+        );
+    };
+    GitHub::new()
+        .await?
+        .report(&owner, &repo, &branch, with_logs)
+        .await
+}
+
+async fn git(project: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(project)
+        .output()
+        .await
+        .context("Failed to run git")?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Owner and repository of a GitHub remote URL (https, ssh or scp-style)
+pub fn parse_github_remote(url: &str) -> Option<(String, String)> {
+    let path = url
+        .strip_prefix("git@github.com:")
+        .or_else(|| url.split_once("github.com/").map(|(_, path)| path))?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    let (owner, repo) = path.split_once('/')?;
+    if owner.is_empty() || repo.is_empty() || repo.contains('/') {
+        return None;
+    }
+    Some((owner.to_string(), repo.to_string()))
+}
+
+/// GitHub token from `GITHUB_TOKEN`/`GH_TOKEN`, or the GitHub CLI's login
+pub(crate) async fn github_token() -> Option<String> {
+    match std::env::var("GITHUB_TOKEN").or_else(|_| std::env::var("GH_TOKEN")) {
+        Ok(token) if !token.is_empty() => Some(token),
+        _ => Command::new("gh")
+            .args(["auth", "token"])
+            .output()
+            .await
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string())
+            .filter(|t| !t.is_empty()),
+    }
+}
+
+struct GitHub {
+    client: reqwest::Client,
+    token: Option<String>,
+}
+
+impl GitHub {
+    async fn new() -> Result<Self> {
+        let token = github_token().await;
+        let client = reqwest::Client::builder()
+            .user_agent("SafeCoder/1.0")
+            .build()?;
+        Ok(Self { client, token })
+    }
+
+    fn get(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self
+            .client
+            .get(url)
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28");
+        match &self.token {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+
+    async fn json(&self, url: &str) -> Result<serde_json::Value> {
+        let response = self.get(url).send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let hint = if self.token.is_none() {
+                " (set GITHUB_TOKEN or run `gh auth login`)"
+            } else {
+                ""
+            };
+            bail!("GitHub API returned {} for {}{}", status, url, hint);
+        }
+        Ok(response.json().await?)
+    }
+
+    async fn report(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+        with_logs: bool,
+    ) -> Result<CiReport> {
+        let base = format!("{}/repos/{}/{}", GITHUB_API, owner, repo);
+        let runs = self
+            .json(&format!(
+                "{}/actions/runs?branch={}&per_page=20",
+                base,
+                url::form_urlencoded::byte_serialize(branch.as_bytes()).collect::<String>()
+            ))
+            .await?;
+        let runs = runs["workflow_runs"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let Some(latest) = runs.first() else {
+            return Ok(CiReport {
+                text: format!(
+                    "No GitHub Actions runs for branch {} in {}/{}.",
+                    branch, owner, repo
+                ),
+                failing: false,
+            });
+        };
+
+        // Every workflow triggered by the newest commit, newest run of each
+        let sha = latest["head_sha"].as_str().unwrap_or_default();
+        let mut seen = std::collections::HashSet::new();
+        let runs: Vec<&serde_json::Value> = runs
+            .iter()
+            .filter(|r| r["head_sha"] == sha)
+            .filter(|r| seen.insert(r["workflow_id"].to_string()))
+            .collect();
+
+        let mut text = format!(
+            "CI for {}/{} branch {} at {}:\n",
+            owner,
+            repo,
+            branch,
+            truncate_str(sha, 7)
+        );
+        let mut failing = false;
+        let mut logs = String::new();
+        for run in runs {
+            let state = run_state(run);
+            text.push_str(&format!(
+                "{} {} — {}  {}\n",
+                state_icon(state),
+                run["name"].as_str().unwrap_or("workflow"),
+                state,
+                run["html_url"].as_str().unwrap_or_default()
+            ));
+            if !matches!(state, "failure" | "timed_out") {
+                continue;
+            }
+            failing = true;
+
+            let jobs = self
+                .json(&format!(
+                    "{}/actions/runs/{}/jobs?per_page=100",
+                    base, run["id"]
+                ))
+                .await?;
+            for job in jobs["jobs"].as_array().into_iter().flatten() {
+                if !matches!(job["conclusion"].as_str(), Some("failure" | "timed_out")) {
+                    continue;
+                }
+                let job_name = job["name"].as_str().unwrap_or("job");
+                let failed_steps: Vec<&str> = job["steps"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter(|s| s["conclusion"] == "failure")
+                    .filter_map(|s| s["name"].as_str())
+                    .collect();
+                text.push_str(&format!("    ✗ job {}", job_name));
+                if !failed_steps.is_empty() {
+                    text.push_str(&format!(" (failed step: {})", failed_steps.join(", ")));
+                }
+                text.push('\n');
+
+                if with_logs {
+                    let log = match self
+                        .get(&format!("{}/actions/jobs/{}/logs", base, job["id"]))
+                        .send()
+                        .await
+                        .and_then(|r| r.error_for_status())
+                    {
+                        Ok(response) => condense_log(&response.text().await.unwrap_or_default()),
+                        Err(e) => format!("(log unavailable: {})", e),
+                    };
+                    logs.push_str(&format!("\n── {} ──\n{}\n", job_name, log));
+                }
+            }
+        }
+        if !logs.is_empty() {
+            text.push_str("\nFailing job logs (condensed):");
+            text.push_str(&logs);
+        }
+        Ok(CiReport { text, failing })
+    }
+}
+
+/// A run's conclusion once completed, its status before
+fn run_state(run: &serde_json::Value) -> &str {
+    match run["status"].as_str() {
+        Some("completed") => run["conclusion"].as_str().unwrap_or("completed"),
+        Some(status) => status,
+        None => "unknown",
+    }
+}
+
+fn state_icon(state: &str) -> &'static str {
+    match state {
+        "success" => "✓",
+        "failure" | "timed_out" => "✗",
+        "cancelled" | "skipped" | "neutral" => "○",
+        _ => "…",
+    }
+}
+
+/// Error lines of a job log with a line of context each, or its end if no
+/// line looks like an error
+pub fn condense_log(log: &str) -> String {
+    let timestamp = Regex::new(r"^\d{4}-\d\d-\d\dT[\d:.]+Z ").unwrap();
+    let lines: Vec<String> = log
+        .lines()
+        .map(|l| timestamp.replace(l, "").into_owned())
+        .filter(|l| !l.starts_with("##[group]") && !l.starts_with("##[endgroup]"))
+        .collect();
+
+    let errors: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, l)| l.starts_with("##[error]") || is_failure_line(l))
+        .map(|(i, _)| i)
+        .collect();
+    if errors.is_empty() {
+        let start = lines.len().saturating_sub(MAX_LOG_LINES);
+        return lines[start..].join("\n");
+    }
+
+    let mut shown: Vec<usize> = Vec::new();
+    for i in errors {
+        for j in i.saturating_sub(1)..=(i + 1).min(lines.len() - 1) {
+            if shown.last().is_none_or(|&last| j > last) {
+                shown.push(j);
+            }
+        }
+    }
+    let mut out = Vec::new();
+    let mut previous = None;
+    for &i in shown.iter().take(MAX_LOG_LINES) {
+        if previous.is_some_and(|p: usize| i > p + 1) {
+            out.push("...".to_string());
+        }
+        out.push(truncate_str(&lines[i], 300).to_string());
+        previous = Some(i);
+    }
+    if shown.len() > MAX_LOG_LINES {
+        out.push(format!("... {} more lines", shown.len() - MAX_LOG_LINES));
+    }
+    out.join("\n")
+}
+
+/// Query a generic CI status endpoint
+async fn generic_report(url: &str) -> Result<CiReport> {
+    let response = reqwest::Client::builder()
+        .user_agent("SafeCoder/1.0")
+        .build()?
+        .get(url)
+        .send()
+        .await
+        .with_context(|| format!("Failed to query {}", url))?;
+    let status = response.status();
+    let body = response.text().await.unwrap_or_default();
+    if !status.is_success() {
+        bail!("{} returned {}", url, status);
+    }
+    let (body, failing) = match serde_json::from_str::<serde_json::Value>(&body) {
+        Ok(json) => (
+            serde_json::to_string_pretty(&json).unwrap_or(body),
+            json_reports_failure(&json),
+        ),
+        Err(_) => {
+            let lower = body.to_lowercase();
+            let failing = lower.contains("fail") || lower.contains("error");
+            (body, failing)
+        }
+    };
+    Ok(CiReport {
+        text: format!(
+            "CI status from {}:\n{}",
+            url,
+            truncate_str(&body, MAX_STATUS_CHARS)
+        ),
+        failing,
+    })
+}
+
+/// Whether a status document has a failed `status`, `state`, `conclusion`
+/// or `result` field
+fn json_reports_failure(json: &serde_json::Value) -> bool {
+    ["status", "state", "conclusion", "result"]
+        .iter()
+        .any(|key| {
+            json[key].as_str().is_some_and(|v| {
+                matches!(
+                    v.to_lowercase().as_str(),
+                    "failed" | "failure" | "error" | "errored" | "broken" | "timed_out"
+                )
+            })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_github_remote() {
+        let expected = Some(("acme".to_string(), "app".to_string()));
+        assert_eq!(parse_github_remote("git@github.com:acme/app.git"), expected);
+        assert_eq!(parse_github_remote("https://github.com/acme/app"), expected);
+        assert_eq!(
+            parse_github_remote("ssh://git@github.com/acme/app.git"),
+            expected
+        );
+        assert_eq!(parse_github_remote("https://gitlab.com/acme/app.git"), None);
+    }
+
+    #[test]
+    fn test_condense_log_keeps_error_lines() {
+        let mut log = String::new();
+        for i in 0..200 {
+            log.push_str(&format!(
+                "2024-05-01T10:00:00.1234567Z compiling crate {}\n",
+                i
+            ));
+        }
+        log.push_str("2024-05-01T10:01:00.0000000Z test tests::parse ... FAILED\n");
+        log.push_str(
+            "2024-05-01T10:01:00.0000000Z thread 'tests::parse' panicked at src/lib.rs:4:5:\n",
+        );
+        log.push_str(
+            "2024-05-01T10:01:00.0000000Z ##[error]Process completed with exit code 101.\n",
+        );
+
+        let condensed = condense_log(&log);
+        assert!(condensed.starts_with("compiling crate 199\ntest tests::parse ... FAILED"));
+        assert!(condensed.contains("panicked at src/lib.rs:4:5"));
+        assert!(condensed.ends_with("##[error]Process completed with exit code 101."));
+        assert!(!condensed.contains("crate 100"));
+
+        assert!(json_reports_failure(
+            &serde_json::json!({"state": "FAILED"})
+        ));
+        assert!(!json_reports_failure(
+            &serde_json::json!({"state": "passed"})
+        ));
+    }
+}
@@ -0,0 +1,791 @@
+//! Adding and removing dependencies through the package manager
+//!
+//! `add_dependency` and `remove_dependency` run cargo, npm, yarn, pnpm,
+//! poetry or pip instead of having the model edit manifests by hand, then
+//! check that the dependency graph still resolves and report the version
+//! that was actually picked. If the package manager or the resolution
+//! check fails, the manifest and lock files are put back as they were
+//! (`pip check` conflicts are only reported, since the environment may have
+//! had them already).
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::{Tool, ToolContext, ToolError};
+
+/// Lines of package manager output shown when it fails
+const MAX_ERROR_LINES: usize = 40;
+
+/// Package managers the dependency tools drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Cargo,
+    Npm,
+    Yarn,
+    Pnpm,
+    Poetry,
+    Pip,
+}
+
+impl PackageManager {
+    /// The package manager a project uses, from its manifest and lock files
+    pub fn detect(project: &Path) -> Option<Self> {
+        if project.join("Cargo.toml").exists() {
+            return Some(Self::Cargo);
+        }
+        if project.join("package.json").exists() {
+            if project.join("pnpm-lock.yaml").exists() {
+                return Some(Self::Pnpm);
+            }
+            if project.join("yarn.lock").exists() {
+                return Some(Self::Yarn);
+            }
+            return Some(Self::Npm);
+        }
+        let pyproject = std::fs::read_to_string(project.join("pyproject.toml")).unwrap_or_default();
+        if pyproject.contains("[tool.poetry") {
+            return Some(Self::Poetry);
+        }
+        if project.join("requirements.txt").exists() {
+            return Some(Self::Pip);
+        }
+        None
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "cargo" => Some(Self::Cargo),
+            "npm" => Some(Self::Npm),
+            "yarn" => Some(Self::Yarn),
+            "pnpm" => Some(Self::Pnpm),
+            "poetry" => Some(Self::Poetry),
+            "pip" => Some(Self::Pip),
+            _ => None,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo",
+            Self::Npm => "npm",
+            Self::Yarn => "yarn",
+            Self::Pnpm => "pnpm",
+            Self::Poetry => "poetry",
+            Self::Pip => "pip",
+        }
+    }
+
+    /// Files the package manager may rewrite, restored if a change fails
+    fn manifest_files(self, dev: bool) -> &'static [&'static str] {
+        match self {
+            Self::Cargo => &["Cargo.toml", "Cargo.lock"],
+            Self::Npm => &["package.json", "package-lock.json"],
+            Self::Yarn => &["package.json", "yarn.lock"],
+            Self::Pnpm => &["package.json", "pnpm-lock.yaml"],
+            Self::Poetry => &["pyproject.toml", "poetry.lock"],
+            Self::Pip if dev => &["requirements-dev.txt"],
+            Self::Pip => &["requirements.txt"],
+        }
+    }
+
+    /// The manifest a dependency is recorded in
+    fn manifest(self, dev: bool) -> &'static str {
+        self.manifest_files(dev)[0]
+    }
+
+    fn is_node(self) -> bool {
+        matches!(self, Self::Npm | Self::Yarn | Self::Pnpm)
+    }
+
+    /// Arguments that add `spec`
+    fn add_args(self, spec: &str, dev: bool, features: &[String]) -> Vec<String> {
+        let mut args: Vec<String> = match self {
+            Self::Cargo => vec!["add".into()],
+            Self::Npm => vec!["install".into()],
+            Self::Yarn | Self::Pnpm | Self::Poetry => vec!["add".into()],
+            Self::Pip => ["-m", "pip", "install"].map(String::from).to_vec(),
+        };
+        args.push(spec.to_string());
+        if dev {
+            args.extend(self.dev_flag());
+        }
+        if !features.is_empty() {
+            args.extend(["--features".to_string(), features.join(",")]);
+        }
+        args
+    }
+
+    /// Arguments that remove `name`
+    fn remove_args(self, name: &str, dev: bool) -> Vec<String> {
+        let mut args: Vec<String> = match self {
+            Self::Cargo | Self::Yarn | Self::Pnpm | Self::Poetry => vec!["remove".into()],
+            Self::Npm => vec!["uninstall".into()],
+            Self::Pip => ["-m", "pip", "uninstall", "-y"].map(String::from).to_vec(),
+        };
+        args.push(name.to_string());
+        // npm, yarn and pnpm remove from whichever section lists the package
+        if dev && matches!(self, Self::Cargo | Self::Poetry) {
+            args.extend(self.dev_flag());
+        }
+        args
+    }
+
+    fn dev_flag(self) -> Vec<String> {
+        match self {
+            Self::Cargo | Self::Yarn => vec!["--dev".into()],
+            Self::Npm | Self::Pnpm => vec!["--save-dev".into()],
+            Self::Poetry => vec!["--group".into(), "dev".into()],
+            Self::Pip => Vec::new(),
+        }
+    }
+
+    fn program(self, project: &Path) -> String {
+        match self {
+            Self::Pip => python_program(project),
+            other => other.label().to_string(),
+        }
+    }
+
+    /// How `name` at `version` is written on the command line
+    fn spec(self, name: &str, version: Option<&str>) -> String {
+        match (self, version) {
+            (_, None) => name.to_string(),
+            (Self::Pip, Some(v)) if v.starts_with(['=', '<', '>', '~', '!']) => {
+                format!("{}{}", name, v)
+            }
+            (Self::Pip, Some(v)) => format!("{}=={}", name, v),
+            (_, Some(v)) => format!("{}@{}", name, v),
+        }
+    }
+}
+
+/// The project's virtualenv Python if there is one, so pip doesn't install
+/// into the system interpreter
+fn python_program(project: &Path) -> String {
+    for venv in [".venv", "venv"] {
+        let python = if cfg!(windows) {
+            project.join(venv).join("Scripts").join("python.exe")
+        } else {
+            project.join(venv).join("bin").join("python")
+        };
+        if python.exists() {
+            return python.to_string_lossy().into_owned();
+        }
+    }
+    if which::which("python3").is_ok() {
+        "python3".to_string()
+    } else {
+        "python".to_string()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct AddDependencyParams {
+    name: String,
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    dev: bool,
+    #[serde(default)]
+    features: Vec<String>,
+    #[serde(default)]
+    manager: Option<String>,
+    #[serde(default = "default_timeout")]
+    timeout_secs: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RemoveDependencyParams {
+    name: String,
+    #[serde(default)]
+    dev: bool,
+    #[serde(default)]
+    manager: Option<String>,
+    #[serde(default = "default_timeout")]
+    timeout_secs: u64,
+}
+
+fn default_timeout() -> u64 {
+    300
+}
+
+pub struct AddDependencyTool;
+
+#[async_trait]
+impl Tool for AddDependencyTool {
+    fn name(&self) -> &str {
+        "add_dependency"
+    }
+
+    fn description(&self) -> &str {
+        "Adds a dependency with the project's package manager (cargo add, npm/yarn/pnpm, \
+         poetry add or pip), checks that dependencies still resolve and reports the exact \
+         version picked. Use this instead of editing Cargo.toml, package.json, pyproject.toml \
+         or requirements.txt by hand. Manifest changes are rolled back if anything fails."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Package name, e.g. serde, @types/node, requests"
+                },
+                "version": {
+                    "type": "string",
+                    "description": "Version or requirement (default latest), e.g. 1.0, ^4.18, >=2.31"
+                },
+                "dev": {
+                    "type": "boolean",
+                    "description": "Add as a dev/test dependency (default false)"
+                },
+                "features": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Cargo features to enable"
+                },
+                "manager": {
+                    "type": "string",
+                    "enum": ["cargo", "npm", "yarn", "pnpm", "poetry", "pip"],
+                    "description": "Package manager to use instead of the detected one"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Timeout in seconds (default 300)"
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: AddDependencyParams = serde_json::from_value(params)?;
+        check_argument("name", &params.name)?;
+        if let Some(version) = &params.version {
+            check_argument("version", version)?;
+        }
+        for feature in &params.features {
+            check_argument("feature", feature)?;
+        }
+        let manager = resolve_manager(params.manager.as_deref(), ctx.working_dir)?;
+        if !params.features.is_empty() && manager != PackageManager::Cargo {
+            return Err(ToolError::InvalidInput(
+                "features are only supported for cargo dependencies".into(),
+            )
+            .into());
+        }
+
+        let project = ctx.working_dir;
+        let snapshot = Snapshot::take(project, manager.manifest_files(params.dev));
+        let spec = manager.spec(&params.name, params.version.as_deref());
+        let program = manager.program(project);
+        let args = manager.add_args(&spec, params.dev, &params.features);
+        let timeout = params.timeout_secs;
+
+        let output = run(project, &program, &args, timeout).await?;
+        if !output.success {
+            snapshot.restore();
+            return Ok(failure(
+                &program,
+                &args,
+                &output.text,
+                "nothing was changed",
+            ));
+        }
+
+        if manager == PackageManager::Pip {
+            let version = installed_version(manager, project, &params.name, timeout)
+                .await
+                .context("pip installed the package but `pip show` can't find it")?;
+            let file = project.join(manager.manifest(params.dev));
+            let existing = std::fs::read_to_string(&file).unwrap_or_default();
+            let pinned = format!("{}=={}", params.name, version);
+            std::fs::write(&file, pin_requirement(&existing, &params.name, &pinned))
+                .with_context(|| format!("Failed to write {}", file.display()))?;
+        }
+
+        let conflicts = match verify_resolution(manager, project, timeout).await {
+            // The environment may have had conflicts before, so pip's are reported
+            // rather than rolled back
+            Err(problem) if manager == PackageManager::Pip => Some(problem),
+            Err(problem) => {
+                snapshot.restore();
+                return Ok(format!(
+                    "`{}` succeeded but dependencies no longer resolve, so the change was \
+                     rolled back:\n{}",
+                    shown(&program, &args),
+                    problem
+                ));
+            }
+            Ok(()) => None,
+        };
+
+        let version = installed_version(manager, project, &params.name, timeout)
+            .await
+            .unwrap_or_else(|| "unknown".to_string());
+        let mut out = format!(
+            "Added {} {} to {}{} with `{}`",
+            params.name,
+            version,
+            manager.manifest(params.dev),
+            if params.dev { " (dev)" } else { "" },
+            shown(&program, &args)
+        );
+        if manager.is_node() {
+            if let Some(range) = declared_node_range(project, &params.name) {
+                out.push_str(&format!("\npackage.json requirement: {}", range));
+            }
+        }
+        match conflicts {
+            Some(problem) => out.push_str(&format!("\npip check reports conflicts:\n{}", problem)),
+            None => out.push_str("\nDependencies resolve."),
+        }
+        Ok(out)
+    }
+}
+
+pub struct RemoveDependencyTool;
+
+#[async_trait]
+impl Tool for RemoveDependencyTool {
+    fn name(&self) -> &str {
+        "remove_dependency"
+    }
+
+    fn description(&self) -> &str {
+        "Removes a dependency with the project's package manager (cargo remove, npm uninstall, \
+         yarn/pnpm/poetry remove or pip uninstall) and checks that the rest still resolve. \
+         Use this instead of editing manifests by hand."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Package name"
+                },
+                "dev": {
+                    "type": "boolean",
+                    "description": "Remove from the dev/test dependencies (default false)"
+                },
+                "manager": {
+                    "type": "string",
+                    "enum": ["cargo", "npm", "yarn", "pnpm", "poetry", "pip"],
+                    "description": "Package manager to use instead of the detected one"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Timeout in seconds (default 300)"
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: RemoveDependencyParams = serde_json::from_value(params)?;
+        check_argument("name", &params.name)?;
+        let manager = resolve_manager(params.manager.as_deref(), ctx.working_dir)?;
+
+        let project = ctx.working_dir;
+        let timeout = params.timeout_secs;
+        let previous = installed_version(manager, project, &params.name, timeout).await;
+        let snapshot = Snapshot::take(project, manager.manifest_files(params.dev));
+        let program = manager.program(project);
+        let args = manager.remove_args(&params.name, params.dev);
+
+        let output = run(project, &program, &args, timeout).await?;
+        if !output.success {
+            snapshot.restore();
+            return Ok(failure(
+                &program,
+                &args,
+                &output.text,
+                "nothing was changed",
+            ));
+        }
+        if manager == PackageManager::Pip {
+            let file = project.join(manager.manifest(params.dev));
+            if let Ok(existing) = std::fs::read_to_string(&file) {
+                std::fs::write(&file, unpin_requirement(&existing, &params.name))
+                    .with_context(|| format!("Failed to write {}", file.display()))?;
+            }
+        }
+
+        if let Err(problem) = verify_resolution(manager, project, timeout).await {
+            snapshot.restore();
+            return Ok(format!(
+                "`{}` succeeded but dependencies no longer resolve, so the change was rolled \
+                 back:\n{}",
+                shown(&program, &args),
+                problem
+            ));
+        }
+
+        Ok(format!(
+            "Removed {}{} from {} with `{}`\nDependencies resolve. Remove any remaining uses \
+             of it from the code.",
+            params.name,
+            previous.map(|v| format!(" {}", v)).unwrap_or_default(),
+            manager.manifest(params.dev),
+            shown(&program, &args)
+        ))
+    }
+}
+
+fn resolve_manager(requested: Option<&str>, project: &Path) -> Result<PackageManager> {
+    match requested {
+        Some(name) => PackageManager::from_name(name).ok_or_else(|| {
+            ToolError::InvalidInput(format!(
+                "Unknown package manager '{}'; use cargo, npm, yarn, pnpm, poetry or pip",
+                name
+            ))
+            .into()
+        }),
+        None => PackageManager::detect(project).ok_or_else(|| {
+            ToolError::InvalidInput(
+                "No Cargo.toml, package.json, poetry pyproject.toml or requirements.txt found; \
+                 pass `manager`"
+                    .into(),
+            )
+            .into()
+        }),
+    }
+}
+
+/// Reject values that the package manager would read as options
+fn check_argument(what: &str, value: &str) -> Result<()> {
+    if value.is_empty() || value.starts_with('-') || value.chars().any(char::is_whitespace) {
+        return Err(ToolError::InvalidInput(format!("Invalid {} '{}'", what, value)).into());
+    }
+    Ok(())
+}
+
+/// Manifest and lock file contents from before a change
+struct Snapshot(Vec<(PathBuf, Option<Vec<u8>>)>);
+
+impl Snapshot {
+    fn take(project: &Path, files: &[&str]) -> Self {
+        Self(
+            files
+                .iter()
+                .map(|f| {
+                    let path = project.join(f);
+                    let content = std::fs::read(&path).ok();
+                    (path, content)
+                })
+                .collect(),
+        )
+    }
+
+    fn restore(&self) {
+        for (path, content) in &self.0 {
+            let result = match content {
+                Some(content) => std::fs::write(path, content),
+                None if path.exists() => std::fs::remove_file(path),
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                tracing::warn!("Failed to restore {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+struct CommandOutput {
+    success: bool,
+    text: String,
+}
+
+async fn run(
+    project: &Path,
+    program: &str,
+    args: &[String],
+    timeout: u64,
+) -> Result<CommandOutput> {
+    let output = tokio::time::timeout(
+        Duration::from_secs(timeout),
+        Command::new(program)
+            .args(args)
+            .current_dir(project)
+            .env("CARGO_TERM_COLOR", "never")
+            .env("NO_COLOR", "1")
+            .env("PIP_DISABLE_PIP_VERSION_CHECK", "1")
+            .env("POETRY_NO_INTERACTION", "1")
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| ToolError::Timeout {
+        command: shown(program, args),
+        secs: timeout,
+    })?
+    .with_context(|| format!("Failed to run {} (is it installed?)", program))?;
+    Ok(CommandOutput {
+        success: output.status.success(),
+        text: format!(
+            "{}\n{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ),
+    })
+}
+
+/// Check that the dependency graph resolves after a change. npm, yarn, pnpm
+/// and poetry resolve as part of add/remove; cargo add doesn't always, and
+/// pip never checks the environment as a whole.
+async fn verify_resolution(
+    manager: PackageManager,
+    project: &Path,
+    timeout: u64,
+) -> std::result::Result<(), String> {
+    let args: Vec<String> = match manager {
+        PackageManager::Cargo => ["metadata", "--format-version", "1"]
+            .map(String::from)
+            .to_vec(),
+        PackageManager::Pip => ["-m", "pip", "check"].map(String::from).to_vec(),
+        _ => return Ok(()),
+    };
+    let program = manager.program(project);
+    match run(project, &program, &args, timeout).await {
+        Ok(output) if output.success => Ok(()),
+        Ok(output) => Err(tail(&output.text, MAX_ERROR_LINES)),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// The version of `name` the lock file or environment has, after resolution
+async fn installed_version(
+    manager: PackageManager,
+    project: &Path,
+    name: &str,
+    timeout: u64,
+) -> Option<String> {
+    match manager {
+        PackageManager::Cargo => {
+            let args = ["metadata", "--format-version", "1"].map(String::from);
+            let output = run(project, "cargo", &args, timeout).await.ok()?;
+            let json: serde_json::Value = serde_json::from_str(output.text.lines().next()?).ok()?;
+            cargo_versions(&json, name)
+        }
+        PackageManager::Npm | PackageManager::Yarn | PackageManager::Pnpm => {
+            let path = project.join("node_modules").join(name).join("package.json");
+            let json: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()?;
+            json["version"].as_str().map(String::from)
+        }
+        PackageManager::Poetry | PackageManager::Pip => {
+            let args: Vec<String> = if manager == PackageManager::Poetry {
+                vec!["show".into(), name.to_string()]
+            } else {
+                ["-m", "pip", "show", name].map(String::from).to_vec()
+            };
+            let output = run(project, &manager.program(project), &args, timeout)
+                .await
+                .ok()?;
+            if !output.success {
+                return None;
+            }
+            field_value(&output.text, "version")
+        }
+    }
+}
+
+/// Resolved versions of a package in `cargo metadata` output, comma-separated
+/// when the graph has more than one
+pub fn cargo_versions(metadata: &serde_json::Value, name: &str) -> Option<String> {
+    let wanted = normalize(name);
+    let mut versions: Vec<&str> = metadata["packages"]
+        .as_array()?
+        .iter()
+        .filter(|p| p["name"].as_str().map(normalize) == Some(wanted.clone()))
+        .filter_map(|p| p["version"].as_str())
+        .collect();
+    versions.sort_unstable();
+    versions.dedup();
+    if versions.is_empty() {
+        None
+    } else {
+        Some(versions.join(", "))
+    }
+}
+
+/// The requirement package.json records for `name`
+fn declared_node_range(project: &Path, name: &str) -> Option<String> {
+    let json: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(project.join("package.json")).ok()?).ok()?;
+    ["dependencies", "devDependencies", "optionalDependencies"]
+        .iter()
+        .find_map(|section| json[section][name].as_str())
+        .map(String::from)
+}
+
+/// Value of a `Key: value` or `key : value` line, as printed by pip show
+/// and poetry show
+fn field_value(text: &str, key: &str) -> Option<String> {
+    text.lines().find_map(|line| {
+        let (k, v) = line.split_once(':')?;
+        k.trim()
+            .eq_ignore_ascii_case(key)
+            .then(|| v.trim().to_string())
+    })
+}
+
+/// Package names compare case-insensitively with `-`, `_` and `.` equal
+fn normalize(name: &str) -> String {
+    name.to_lowercase().replace(['_', '.'], "-")
+}
+
+/// Name of the package a requirements.txt line refers to, if any
+fn requirement_name(line: &str) -> Option<String> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+        return None;
+    }
+    let end = line
+        .find(|c: char| !(c.is_alphanumeric() || matches!(c, '-' | '_' | '.')))
+        .unwrap_or(line.len());
+    Some(normalize(&line[..end]))
+}
+
+/// requirements.txt with `name` pinned as `pinned`, replacing an existing
+/// line for it or appending one
+pub fn pin_requirement(existing: &str, name: &str, pinned: &str) -> String {
+    let wanted = normalize(name);
+    let mut replaced = false;
+    let mut lines: Vec<String> = existing
+        .lines()
+        .map(|line| {
+            if requirement_name(line).as_deref() == Some(&wanted) {
+                replaced = true;
+                pinned.to_string()
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    if !replaced {
+        lines.push(pinned.to_string());
+    }
+    lines.join("\n") + "\n"
+}
+
+/// requirements.txt without the line for `name`
+pub fn unpin_requirement(existing: &str, name: &str) -> String {
+    let wanted = normalize(name);
+    let lines: Vec<&str> = existing
+        .lines()
+        .filter(|line| requirement_name(line).as_deref() != Some(&wanted))
+        .collect();
+    if lines.is_empty() {
+        String::new()
+    } else {
+        lines.join("\n") + "\n"
+    }
+}
+
+fn shown(program: &str, args: &[String]) -> String {
+    format!("{} {}", program, args.join(" "))
+}
+
+fn failure(program: &str, args: &[String], output: &str, note: &str) -> String {
+    format!(
+        "`{}` failed; {}:\n{}",
+        shown(program, args),
+        note,
+        tail(output, MAX_ERROR_LINES)
+    )
+}
+
+fn tail(text: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = text.trim().lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    lines[start..].join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_and_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(PackageManager::detect(dir.path()), None);
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        std::fs::write(dir.path().join("yarn.lock"), "").unwrap();
+        assert_eq!(
+            PackageManager::detect(dir.path()),
+            Some(PackageManager::Yarn)
+        );
+
+        let manager = PackageManager::Cargo;
+        let args = manager.add_args(&manager.spec("serde", Some("1")), true, &["derive".into()]);
+        assert_eq!(
+            shown(&manager.program(dir.path()), &args),
+            "cargo add serde@1 --dev --features derive"
+        );
+        assert_eq!(
+            PackageManager::Pip.spec("requests", Some("2.31")),
+            "requests==2.31"
+        );
+        assert_eq!(
+            PackageManager::Pip.spec("requests", Some(">=2")),
+            "requests>=2"
+        );
+        let args = PackageManager::Npm.remove_args("left-pad", true);
+        assert_eq!(shown("npm", &args), "npm uninstall left-pad");
+
+        assert!(check_argument("name", "--registry=evil").is_err());
+        assert!(check_argument("name", "@types/node").is_ok());
+    }
+
+    #[test]
+    fn test_requirements_pinning_and_versions() {
+        let existing = "# deps\nRequests>=2.0\nflask==3.0.0\n";
+        assert_eq!(
+            pin_requirement(existing, "requests", "requests==2.32.3"),
+            "# deps\nrequests==2.32.3\nflask==3.0.0\n"
+        );
+        assert_eq!(
+            pin_requirement("", "typing_extensions", "typing_extensions==4.12.2"),
+            "typing_extensions==4.12.2\n"
+        );
+        assert_eq!(
+            unpin_requirement(existing, "Flask"),
+            "# deps\nRequests>=2.0\n"
+        );
+
+        let metadata = serde_json::json!({
+            "packages": [
+                {"name": "serde_json", "version": "1.0.128"},
+                {"name": "syn", "version": "2.0.77"},
+                {"name": "syn", "version": "1.0.109"}
+            ]
+        });
+        assert_eq!(
+            cargo_versions(&metadata, "serde-json").as_deref(),
+            Some("1.0.128")
+        );
+        assert_eq!(
+            cargo_versions(&metadata, "syn").as_deref(),
+            Some("1.0.109, 2.0.77")
+        );
+        assert_eq!(cargo_versions(&metadata, "tokio"), None);
+
+        assert_eq!(
+            field_value("Name: requests\nVersion: 2.32.3\n", "version").as_deref(),
+            Some("2.32.3")
+        );
+        assert_eq!(
+            field_value(" name         : black\n version      : 24.8.0\n", "version").as_deref(),
+            Some("24.8.0")
+        );
+    }
+}
@@ -3,7 +3,7 @@ use async_trait::async_trait;
 use serde::Deserialize;
 
 
-use super::{Tool, ToolContext};
+use super::{Tool, ToolContext, ToolError};
 
 pub struct EditTool;
 
@@ -55,15 +55,29 @@ impl Tool for EditTool {
         let params: EditParams = serde_json::from_value(params)
             .context("Invalid parameters for edit_file")?;
 
+        ctx.check_protected_path(self.name(), &params.file_path)?;
+
         let file_path = ctx.working_dir.join(&params.file_path);
 
         if !file_path.exists() {
-            anyhow::bail!("File not found: {}", params.file_path);
+            return Err(ToolError::NotFound {
+                path: params.file_path,
+            }
+            .into());
         }
 
-        let content = std::fs::read_to_string(&file_path)
+        let content = ctx.read_file(&file_path)
             .context("Failed to read file")?;
 
+        // Editing a copy the model read before someone else changed the file
+        // would silently undo their change
+        if ctx.is_stale(&file_path, &content) {
+            return Err(ToolError::StaleRead {
+                path: params.file_path,
+            }
+            .into());
+        }
+
         let new_content = if params.replace_all {
             content.replace(&params.old_string, &params.new_string)
         } else {
@@ -75,12 +89,18 @@ impl Tool for EditTool {
                 result.push_str(&content[pos + params.old_string.len()..]);
                 result
             } else {
-                anyhow::bail!("String not found in file: {}", params.old_string);
+                return Err(ToolError::PatchConflict {
+                    path: params.file_path,
+                    old_string: params.old_string,
+                }
+                .into());
             }
         };
 
         std::fs::write(&file_path, &new_content)
             .context("Failed to write file")?;
+        ctx.invalidate_file(&file_path);
+        ctx.record_seen(&file_path, &new_content);
 
         Ok(format!("Successfully edited {}", params.file_path))
     }
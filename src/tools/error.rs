@@ -0,0 +1,191 @@
+//! Structured tool errors
+//!
+//! Tools still return `anyhow::Result`, but failures the model can act on
+//! are raised as a [`ToolError`]. When a failure is turned into a tool
+//! result it keeps the readable `Error: ...` line and gains a trailing
+//! `[tool_error] {"code": ..., "hint": ...}` line, so the model and the UIs
+//! can tell a stale edit from a timeout without matching on message text.
+
+use serde::{Deserialize, Serialize};
+
+use super::ProtectedPathViolation;
+
+/// Prefix of the machine-readable line appended to tool results
+pub const TOOL_ERROR_TAG: &str = "[tool_error]";
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ToolError {
+    #[error("File not found: {path}")]
+    NotFound { path: String },
+    #[error("{0}")]
+    PermissionDenied(String),
+    #[error("Command timed out after {secs}s: {command}")]
+    Timeout { command: String, secs: u64 },
+    #[error("String not found in file {path}: {old_string}")]
+    PatchConflict { path: String, old_string: String },
+    #[error("{path} changed since it was last read")]
+    StaleRead { path: String },
+    #[error("Output truncated: {omitted_bytes} bytes omitted (limit is {limit} bytes)")]
+    OutputTruncated { omitted_bytes: usize, limit: usize },
+    #[error("Invalid parameters: {0}")]
+    InvalidInput(String),
+}
+
+impl ToolError {
+    /// Stable identifier, e.g. `patch_conflict`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } => "not_found",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::Timeout { .. } => "timeout",
+            Self::PatchConflict { .. } => "patch_conflict",
+            Self::StaleRead { .. } => "stale_read",
+            Self::OutputTruncated { .. } => "output_truncated",
+            Self::InvalidInput(_) => "invalid_input",
+        }
+    }
+
+    /// What the model should try next
+    pub fn hint(&self) -> String {
+        match self {
+            Self::NotFound { .. } => {
+                "Check the path with list or glob before retrying.".to_string()
+            }
+            Self::PermissionDenied(_) => {
+                "Do not retry or work around this; continue without it or ask the user.".to_string()
+            }
+            Self::Timeout { .. } => "Pass a larger 'timeout' (or raise tools.bash_timeout_secs), \
+                or split the command into smaller steps. Commands waiting for input never finish."
+                .to_string(),
+            Self::PatchConflict { .. } => "The file no longer contains old_string. Read it \
+                again and copy the text to replace exactly, including whitespace."
+                .to_string(),
+            Self::StaleRead { .. } => "Someone else edited the file since you read it. Read \
+                it again and redo the edit against its current content so their changes are kept."
+                .to_string(),
+            Self::OutputTruncated { .. } => {
+                "Narrow the command (grep, head, tail) to see the part you need.".to_string()
+            }
+            Self::InvalidInput(_) => "Fix the parameters to match the tool's schema.".to_string(),
+        }
+    }
+
+    /// The `[tool_error]` line for this error
+    pub fn tag_line(&self) -> String {
+        let info = ToolErrorInfo {
+            code: self.code().to_string(),
+            hint: self.hint(),
+        };
+        format!(
+            "{} {}",
+            TOOL_ERROR_TAG,
+            serde_json::to_string(&info).unwrap_or_default()
+        )
+    }
+
+    /// Find the structured error behind an `anyhow` error, falling back to
+    /// the kind of any I/O error in its chain
+    pub fn classify(err: &anyhow::Error) -> Option<ToolError> {
+        if let Some(e) = err.downcast_ref::<ToolError>() {
+            return Some(e.clone());
+        }
+        if let Some(e) = err.downcast_ref::<ProtectedPathViolation>() {
+            return Some(Self::PermissionDenied(e.to_string()));
+        }
+        if err.downcast_ref::<serde_json::Error>().is_some() {
+            return Some(Self::InvalidInput(format!("{:#}", err)));
+        }
+        err.chain()
+            .filter_map(|cause| cause.downcast_ref::<std::io::Error>())
+            .find_map(|io| match io.kind() {
+                std::io::ErrorKind::NotFound => Some(Self::NotFound {
+                    path: format!("{:#}", err),
+                }),
+                std::io::ErrorKind::PermissionDenied => {
+                    Some(Self::PermissionDenied(format!("{:#}", err)))
+                }
+                std::io::ErrorKind::TimedOut => Some(Self::Timeout {
+                    command: format!("{:#}", err),
+                    secs: 0,
+                }),
+                _ => None,
+            })
+    }
+}
+
+/// The machine-readable part of a tool result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolErrorInfo {
+    pub code: String,
+    pub hint: String,
+}
+
+impl ToolErrorInfo {
+    /// Pull the structured error out of a tool result, if it carries one
+    pub fn parse(result: &str) -> Option<Self> {
+        result
+            .lines()
+            .rev()
+            .find_map(|line| line.strip_prefix(TOOL_ERROR_TAG))
+            .and_then(|json| serde_json::from_str(json.trim()).ok())
+    }
+}
+
+/// Render a failed tool call as the result sent back to the model
+pub fn render_tool_error(err: &anyhow::Error) -> String {
+    match ToolError::classify(err) {
+        Some(tool_error) => format!("Error: {:#}\n{}", err, tool_error.tag_line()),
+        None => format!("Error: {:#}", err),
+    }
+}
+
+/// Replace the `[tool_error]` line with a short human-readable note for display
+pub fn humanize_tool_output(output: &str) -> String {
+    let Some(info) = ToolErrorInfo::parse(output) else {
+        return output.to_string();
+    };
+    let body = output
+        .lines()
+        .filter(|line| !line.starts_with(TOOL_ERROR_TAG))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("{}\n[{}] {}", body, info.code, info.hint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_render_round_trips_code_and_hint() {
+        let err = anyhow::Error::new(ToolError::PatchConflict {
+            path: "src/lib.rs".to_string(),
+            old_string: "fn old()".to_string(),
+        });
+        let result = render_tool_error(&err);
+        assert!(result.starts_with("Error: String not found in file src/lib.rs: fn old()"));
+
+        let info = ToolErrorInfo::parse(&result).unwrap();
+        assert_eq!(info.code, "patch_conflict");
+        assert!(info.hint.contains("Read it again"));
+
+        let display = humanize_tool_output(&result);
+        assert!(!display.contains(TOOL_ERROR_TAG));
+        assert!(display.contains("[patch_conflict]"));
+    }
+
+    #[test]
+    fn test_classify_io_errors_and_plain_messages() {
+        let err = std::fs::read_to_string("/definitely/not/here")
+            .context("Failed to read file")
+            .unwrap_err();
+        assert_eq!(ToolError::classify(&err).unwrap().code(), "not_found");
+        assert!(render_tool_error(&err).contains("No such file"));
+
+        let plain = anyhow::anyhow!("Unknown git command");
+        assert_eq!(ToolError::classify(&plain), None);
+        assert_eq!(render_tool_error(&plain), "Error: Unknown git command");
+        assert_eq!(ToolErrorInfo::parse("Error: Unknown git command"), None);
+    }
+}
@@ -0,0 +1,234 @@
+//! Per-session file content cache
+//!
+//! The agent tends to read the same handful of files over and over. Entries
+//! are keyed by path and validated against the file's mtime and size on
+//! every lookup, so edits made outside the tools (by bash, an editor, git)
+//! are picked up; the write and edit tools also invalidate explicitly.
+//!
+//! The cache also remembers a hash of each file as the model last read or
+//! wrote it, so `edit_file` can refuse to edit a file that changed since.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Files larger than this are always read from disk
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Total cached content before least recently used entries are dropped
+const MAX_TOTAL_BYTES: usize = 64 * 1024 * 1024;
+
+struct CachedFile {
+    modified: SystemTime,
+    len: u64,
+    content: Arc<String>,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Inner {
+    entries: HashMap<PathBuf, CachedFile>,
+    total_bytes: usize,
+    clock: u64,
+    stats: FileCacheStats,
+    /// Hash of each file's content as the model last saw it
+    seen: HashMap<PathBuf, u64>,
+}
+
+/// Hit/miss counters for `/stats`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileCacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    /// Bytes returned from memory instead of disk
+    pub bytes_served: u64,
+    /// Time spent reading files on misses
+    pub read_time: Duration,
+}
+
+impl FileCacheStats {
+    pub fn hit_rate(&self) -> f32 {
+        let total = self.hits + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            (self.hits as f32 / total as f32) * 100.0
+        }
+    }
+
+    /// Disk time avoided, estimated from the average miss
+    pub fn time_saved(&self) -> Duration {
+        if self.misses == 0 {
+            return Duration::ZERO;
+        }
+        self.read_time / self.misses as u32 * self.hits as u32
+    }
+}
+
+/// Shared, cheaply clonable file cache
+#[derive(Clone, Default)]
+pub struct FileCache {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl FileCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a file as UTF-8, serving it from memory when it hasn't changed
+    pub fn read(&self, path: &Path) -> std::io::Result<Arc<String>> {
+        let meta = std::fs::metadata(path)?;
+        let modified = meta.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+        let len = meta.len();
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.clock += 1;
+            let clock = inner.clock;
+            if let Some(entry) = inner.entries.get_mut(path) {
+                if entry.modified == modified && entry.len == len {
+                    entry.last_used = clock;
+                    let content = Arc::clone(&entry.content);
+                    inner.stats.hits += 1;
+                    inner.stats.bytes_served += len;
+                    return Ok(content);
+                }
+            }
+        }
+
+        let started = Instant::now();
+        let content = Arc::new(std::fs::read_to_string(path)?);
+        let elapsed = started.elapsed();
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.stats.misses += 1;
+        inner.stats.read_time += elapsed;
+        if len <= MAX_FILE_BYTES {
+            let last_used = inner.clock;
+            if let Some(old) = inner.entries.insert(
+                path.to_path_buf(),
+                CachedFile {
+                    modified,
+                    len,
+                    content: Arc::clone(&content),
+                    last_used,
+                },
+            ) {
+                inner.total_bytes -= old.content.len();
+            }
+            inner.total_bytes += content.len();
+            inner.evict();
+        }
+        Ok(content)
+    }
+
+    /// Forget a path, e.g. after writing it
+    pub fn invalidate(&self, path: &Path) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(old) = inner.entries.remove(path) {
+            inner.total_bytes -= old.content.len();
+        }
+    }
+
+    /// Remember `content` as what the model now knows of `path`
+    pub fn record_seen(&self, path: &Path, content: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.seen.insert(path.to_path_buf(), content_hash(content));
+    }
+
+    /// Whether `path` now holds `content` that differs from what the model
+    /// last read or wrote. Files it never saw are not stale.
+    pub fn is_stale(&self, path: &Path, content: &str) -> bool {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .seen
+            .get(path)
+            .is_some_and(|hash| *hash != content_hash(content))
+    }
+
+    pub fn stats(&self) -> FileCacheStats {
+        self.inner.lock().unwrap().stats
+    }
+}
+
+fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl Inner {
+    fn evict(&mut self) {
+        while self.total_bytes > MAX_TOTAL_BYTES {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(p, _)| p.clone())
+            else {
+                break;
+            };
+            if let Some(old) = self.entries.remove(&oldest) {
+                self.total_bytes -= old.content.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repeated_reads_hit_memory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.rs");
+        std::fs::write(&path, "fn main() {}").unwrap();
+
+        let cache = FileCache::new();
+        assert_eq!(cache.read(&path).unwrap().as_str(), "fn main() {}");
+        assert_eq!(cache.read(&path).unwrap().as_str(), "fn main() {}");
+
+        let stats = cache.stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.bytes_served, 12);
+    }
+
+    #[test]
+    fn test_changes_on_disk_are_picked_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.rs");
+        std::fs::write(&path, "old").unwrap();
+
+        let cache = FileCache::new();
+        cache.read(&path).unwrap();
+
+        // A different size is caught even when the mtime doesn't move
+        std::fs::write(&path, "changed").unwrap();
+        assert_eq!(cache.read(&path).unwrap().as_str(), "changed");
+
+        cache.invalidate(&path);
+        cache.read(&path).unwrap();
+        assert_eq!(cache.stats().misses, 3);
+        assert_eq!(cache.stats().hits, 0);
+    }
+
+    #[test]
+    fn test_stale_after_change_since_seen() {
+        let path = Path::new("/project/a.rs");
+        let cache = FileCache::new();
+        assert!(!cache.is_stale(path, "anything"));
+
+        cache.record_seen(path, "v1");
+        assert!(!cache.is_stale(path, "v1"));
+        assert!(cache.is_stale(path, "v2"));
+
+        cache.record_seen(path, "v2");
+        assert!(!cache.is_stale(path, "v2"));
+    }
+}
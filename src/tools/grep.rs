@@ -132,29 +132,33 @@ impl Tool for GrepTool {
             let working_dir = ctx.working_dir.to_path_buf();
             let path_buf = path.to_path_buf();
 
-            // Search this file
+            // Search this file, from memory when it's in the file cache
             let mut searcher = Searcher::new();
-            let _ = searcher.search_path(
-                &matcher,
-                &path_buf,
-                UTF8(|line_num, line| {
-                    let mut results_guard = results_clone.lock().unwrap();
-                    if results_guard.len() < limit {
-                        let relative_path = path_buf
-                            .strip_prefix(&working_dir)
-                            .map(|p| p.to_string_lossy().to_string())
-                            .unwrap_or_else(|_| path_buf.to_string_lossy().to_string());
-
-                        results_guard.push(format!(
-                            "{}:{}: {}",
-                            relative_path,
-                            line_num,
-                            line.trim()
-                        ));
-                    }
-                    Ok(results_guard.len() < limit)
-                }),
-            );
+            let cached = ctx
+                .file_cache
+                .as_ref()
+                .and_then(|cache| cache.read(&path_buf).ok());
+            let sink = UTF8(|line_num, line| {
+                let mut results_guard = results_clone.lock().unwrap();
+                if results_guard.len() < limit {
+                    let relative_path = path_buf
+                        .strip_prefix(&working_dir)
+                        .map(|p| p.to_string_lossy().to_string())
+                        .unwrap_or_else(|_| path_buf.to_string_lossy().to_string());
+
+                    results_guard.push(format!(
+                        "{}:{}: {}",
+                        relative_path,
+                        line_num,
+                        line.trim()
+                    ));
+                }
+                Ok(results_guard.len() < limit)
+            });
+            let _ = match cached {
+                Some(content) => searcher.search_slice(&matcher, content.as_bytes(), sink),
+                None => searcher.search_path(&matcher, &path_buf, sink),
+            };
         }
 
         let results = results.lock().unwrap();
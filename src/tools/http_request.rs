@@ -0,0 +1,323 @@
+//! HTTP requests for exercising APIs
+//!
+//! Lets the agent call the endpoints it is building. Requests only go to
+//! hosts matching `tools.http_allowed_hosts` (local ones by default), and
+//! redirects are reported rather than followed so they can't leave that
+//! list. Cookies persist for the registry's lifetime, i.e. per session, so
+//! a login request authenticates the calls after it.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use super::{Tool, ToolContext, ToolError};
+use crate::utils::truncate_str;
+
+/// Response body characters returned to the model
+const MAX_BODY_CHARS: usize = 20_000;
+/// Response body bytes read before the rest is dropped, enough for
+/// `MAX_BODY_CHARS` of any text
+const MAX_BODY_BYTES: usize = 4 * MAX_BODY_CHARS;
+
+#[derive(Debug, Deserialize)]
+struct HttpRequestParams {
+    url: String,
+    #[serde(default = "default_method")]
+    method: String,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: Option<String>,
+    #[serde(default = "default_timeout")]
+    timeout_secs: u64,
+}
+
+fn default_method() -> String {
+    "GET".to_string()
+}
+
+fn default_timeout() -> u64 {
+    30
+}
+
+pub struct HttpRequestTool {
+    client: reqwest::Client,
+}
+
+impl HttpRequestTool {
+    pub fn new() -> Self {
+        let client = reqwest::Client::builder()
+            .cookie_provider(Arc::new(reqwest::cookie::Jar::default()))
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent("SafeCoder/1.0")
+            .build()
+            .unwrap_or_default();
+        Self { client }
+    }
+}
+
+impl Default for HttpRequestTool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Whether `host` matches one of the allowed host patterns
+pub(super) fn host_allowed(host: &str, patterns: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        pattern.eq_ignore_ascii_case(host)
+            || glob::Pattern::new(&pattern.to_lowercase())
+                .is_ok_and(|p| p.matches(&host.to_lowercase()))
+    })
+}
+
+#[async_trait]
+impl Tool for HttpRequestTool {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Sends an HTTP request and returns the status, headers and body. Use it to \
+         exercise API endpoints you built or are debugging. Cookies set by responses are \
+         sent on later requests. Only hosts allowed in tools.http_allowed_hosts can be \
+         reached (localhost by default); redirects are returned, not followed."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "url": {
+                    "type": "string",
+                    "description": "Full URL, e.g. http://localhost:3000/api/users"
+                },
+                "method": {
+                    "type": "string",
+                    "description": "HTTP method (default GET)"
+                },
+                "headers": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Request headers"
+                },
+                "body": {
+                    "type": "string",
+                    "description": "Request body. JSON bodies are sent as application/json unless a Content-Type header is given."
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Timeout in seconds (default 30)"
+                }
+            },
+            "required": ["url"]
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: HttpRequestParams = serde_json::from_value(params)?;
+
+        let url = url::Url::parse(&params.url)
+            .map_err(|e| ToolError::InvalidInput(format!("Invalid URL {}: {}", params.url, e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(
+                ToolError::InvalidInput("Only http and https URLs are supported".into()).into(),
+            );
+        }
+        let host = url.host_str().unwrap_or_default();
+        if !host_allowed(host, &ctx.config.http_allowed_hosts) {
+            return Err(ToolError::PermissionDenied(format!(
+                "{} is not in tools.http_allowed_hosts ({}). Ask the user to add it if the request is needed.",
+                host,
+                ctx.config.http_allowed_hosts.join(", ")
+            ))
+            .into());
+        }
+        let method = reqwest::Method::from_bytes(params.method.to_uppercase().as_bytes())
+            .map_err(|_| ToolError::InvalidInput(format!("Invalid method {}", params.method)))?;
+
+        let mut request = self
+            .client
+            .request(method.clone(), url.clone())
+            .timeout(Duration::from_secs(params.timeout_secs));
+        let has_content_type = params
+            .headers
+            .keys()
+            .any(|k| k.eq_ignore_ascii_case("content-type"));
+        for (name, value) in &params.headers {
+            request = request.header(name, value);
+        }
+        if let Some(body) = params.body {
+            if !has_content_type && serde_json::from_str::<serde_json::Value>(&body).is_ok() {
+                request = request.header("content-type", "application/json");
+            }
+            request = request.body(body);
+        }
+
+        let started = Instant::now();
+        let response = request
+            .send()
+            .await
+            .with_context(|| format!("{} {} failed", method, url))?;
+        let elapsed = started.elapsed();
+
+        let status = response.status();
+        let mut out = format!("HTTP {} ({} ms)\n", status, elapsed.as_millis());
+        for (name, value) in response.headers() {
+            out.push_str(&format!(
+                "{}: {}\n",
+                name,
+                String::from_utf8_lossy(value.as_bytes())
+            ));
+        }
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+
+        let mut response = response;
+        let mut body = Vec::new();
+        let mut complete = true;
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .context("Failed to read response body")?
+        {
+            let room = MAX_BODY_BYTES - body.len();
+            if chunk.len() > room {
+                body.extend_from_slice(&chunk[..room]);
+                complete = false;
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+        out.push('\n');
+        out.push_str(&render_body(&body, &content_type, complete));
+        Ok(out)
+    }
+}
+
+/// The body as text, pretty-printed if it is JSON and cut to `MAX_BODY_CHARS`.
+/// A body that isn't `complete` was cut at `MAX_BODY_BYTES`.
+fn render_body(bytes: &[u8], content_type: &str, complete: bool) -> String {
+    if bytes.is_empty() {
+        return "(empty body)".to_string();
+    }
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        // The cut may fall inside a character
+        Err(e) if !complete && e.error_len().is_none() => {
+            std::str::from_utf8(&bytes[..e.valid_up_to()]).expect("valid prefix")
+        }
+        Err(_) if complete => return format!("<{} bytes of {}>", bytes.len(), content_type),
+        Err(_) => return format!("<over {} bytes of {}>", bytes.len(), content_type),
+    };
+    if !complete {
+        return format!(
+            "{}\n... [body truncated, over {} bytes]",
+            truncate_str(text, MAX_BODY_CHARS),
+            MAX_BODY_BYTES
+        );
+    }
+    let text = match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(json) => serde_json::to_string_pretty(&json).unwrap_or_else(|_| text.to_string()),
+        Err(_) => text.to_string(),
+    };
+    let shown = truncate_str(&text, MAX_BODY_CHARS);
+    if shown.len() < text.len() {
+        format!(
+            "{}\n... [body truncated, {} more chars]",
+            shown,
+            text[shown.len()..].chars().count()
+        )
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ToolConfig;
+    use axum::{http::HeaderMap, routing::get, Router};
+
+    #[test]
+    fn test_host_patterns() {
+        let patterns = vec!["localhost".to_string(), "*.internal".to_string()];
+        assert!(host_allowed("localhost", &patterns));
+        assert!(host_allowed("API.internal", &patterns));
+        assert!(!host_allowed("example.com", &patterns));
+    }
+
+    #[tokio::test]
+    async fn test_cookies_persist_and_hosts_are_gated() {
+        let app = Router::new()
+            .route(
+                "/login",
+                get(|| async { ([("set-cookie", "session=abc; Path=/")], "ok") }),
+            )
+            .route(
+                "/me",
+                get(|headers: HeaderMap| async move {
+                    let cookie = headers
+                        .get("cookie")
+                        .and_then(|v| v.to_str().ok())
+                        .unwrap_or("none")
+                        .to_string();
+                    format!("{{\"cookie\": \"{}\"}}", cookie)
+                }),
+            )
+            .route("/large", get(|| async { "é".repeat(1 << 20) }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let dir = tempfile::tempdir().unwrap();
+        let config = ToolConfig::default();
+        let ctx = ToolContext::new(dir.path(), &config);
+        let tool = HttpRequestTool::new();
+
+        let login = tool
+            .execute(
+                serde_json::json!({ "url": format!("http://{}/login", addr) }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert!(login.starts_with("HTTP 200 OK"));
+        assert!(login.contains("set-cookie: session=abc"));
+
+        let me = tool
+            .execute(
+                serde_json::json!({ "url": format!("http://{}/me", addr) }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert!(me.contains("\"cookie\": \"session=abc\""));
+
+        let large = tool
+            .execute(
+                serde_json::json!({ "url": format!("http://{}/large", addr) }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+        assert!(large.ends_with(&format!("[body truncated, over {} bytes]", MAX_BODY_BYTES)));
+        assert!(large.len() < MAX_BODY_BYTES);
+
+        let err = tool
+            .execute(serde_json::json!({ "url": "https://example.com/" }), &ctx)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            ToolError::classify(&err).unwrap().code(),
+            "permission_denied"
+        );
+    }
+}
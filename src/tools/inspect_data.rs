@@ -0,0 +1,566 @@
+//! Data inspection for CSV, Parquet, SQLite and DuckDB files
+//!
+//! Shows the schema, row count and a few sample rows of a dataset, or runs
+//! a read-only SQL query against a database, so the model can work with
+//! data files without reading them into context whole.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use futures::TryStreamExt;
+use serde::Deserialize;
+use std::path::Path;
+
+use super::{Tool, ToolContext, ToolError};
+use crate::utils::truncate_str;
+
+/// Sample rows shown for a file when no `limit` is given
+const DEFAULT_SAMPLE_ROWS: usize = 10;
+
+/// Query result rows shown when no `limit` is given
+const DEFAULT_QUERY_ROWS: usize = 50;
+
+/// Upper bound on `limit`
+const MAX_ROWS: usize = 500;
+
+/// Cells longer than this are cut in tables
+const MAX_CELL_CHARS: usize = 60;
+
+#[derive(Debug, Deserialize)]
+struct InspectDataParams {
+    /// Data file (absolute or relative to the working directory)
+    path: String,
+    /// Read-only SQL to run against a SQLite or DuckDB file
+    #[serde(default)]
+    query: Option<String>,
+    /// Rows to show
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format {
+    Csv(u8),
+    Parquet,
+    Sqlite,
+    DuckDb,
+}
+
+impl Format {
+    fn detect(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_lowercase();
+        match ext.as_str() {
+            "csv" => Some(Self::Csv(b',')),
+            "tsv" | "tab" => Some(Self::Csv(b'\t')),
+            "parquet" | "pq" => Some(Self::Parquet),
+            "sqlite" | "sqlite3" | "db" | "db3" => Some(Self::Sqlite),
+            "duckdb" | "ddb" => Some(Self::DuckDb),
+            _ => None,
+        }
+    }
+}
+
+pub struct InspectDataTool;
+
+#[async_trait]
+impl Tool for InspectDataTool {
+    fn name(&self) -> &str {
+        "inspect_data"
+    }
+
+    fn description(&self) -> &str {
+        "Inspects a data file without reading it whole. For CSV/TSV and Parquet files, \
+         shows the columns and their types, the row count and sample rows. For SQLite and \
+         DuckDB databases, lists the tables with their schemas and row counts, or runs a \
+         read-only SQL query given in 'query'."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "The data file (.csv, .tsv, .parquet, .sqlite/.db, .duckdb), absolute or relative to working directory"
+                },
+                "query": {
+                    "type": "string",
+                    "description": "Read-only SQL to run (SQLite and DuckDB files only)"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Rows to show (default 10 sample rows, or 50 query rows; at most 500)"
+                }
+            },
+            "required": ["path"]
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: InspectDataParams = serde_json::from_value(params)?;
+        let path = ctx.working_dir.join(&params.path);
+        if !path.exists() {
+            return Err(ToolError::NotFound { path: params.path }.into());
+        }
+        let format = Format::detect(&path).ok_or_else(|| {
+            ToolError::InvalidInput(format!(
+                "{} is not a CSV, TSV, Parquet, SQLite or DuckDB file",
+                params.path
+            ))
+        })?;
+
+        let name = params.path.clone();
+        match (format, params.query) {
+            (Format::Csv(_) | Format::Parquet, Some(_)) => Err(ToolError::InvalidInput(
+                "query is only supported for SQLite and DuckDB files".to_string(),
+            )
+            .into()),
+            (Format::Csv(delimiter), None) => {
+                let limit = row_limit(params.limit, DEFAULT_SAMPLE_ROWS);
+                tokio::task::spawn_blocking(move || inspect_csv(&path, &name, delimiter, limit))
+                    .await?
+            }
+            (Format::Parquet, None) => {
+                let limit = row_limit(params.limit, DEFAULT_SAMPLE_ROWS);
+                tokio::task::spawn_blocking(move || inspect_parquet(&path, &name, limit)).await?
+            }
+            (Format::Sqlite, None) => sqlite_overview(&path, &name).await,
+            (Format::Sqlite, Some(query)) => {
+                sqlite_query(&path, &query, row_limit(params.limit, DEFAULT_QUERY_ROWS)).await
+            }
+            (Format::DuckDb, query) => {
+                let query = query.unwrap_or_else(|| {
+                    "SELECT table_name, column_name, data_type FROM information_schema.columns \
+                     ORDER BY table_name, ordinal_position"
+                        .to_string()
+                });
+                duckdb_query(&path, &query, ctx).await
+            }
+        }
+    }
+}
+
+fn row_limit(limit: Option<usize>, default: usize) -> usize {
+    limit.unwrap_or(default).clamp(1, MAX_ROWS)
+}
+
+/// Inferred type of a CSV column, widened as values are seen
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnType {
+    Empty,
+    Boolean,
+    Integer,
+    Float,
+    Text,
+}
+
+impl ColumnType {
+    fn of(value: &str) -> Self {
+        if value.parse::<i64>().is_ok() {
+            Self::Integer
+        } else if value.parse::<f64>().is_ok() {
+            Self::Float
+        } else if matches!(value.to_lowercase().as_str(), "true" | "false") {
+            Self::Boolean
+        } else {
+            Self::Text
+        }
+    }
+
+    fn widen(self, value: &str) -> Self {
+        let seen = Self::of(value);
+        match (self, seen) {
+            (Self::Empty, t) => t,
+            (a, b) if a == b => a,
+            (Self::Integer, Self::Float) | (Self::Float, Self::Integer) => Self::Float,
+            _ => Self::Text,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Text => "text",
+        }
+    }
+}
+
+fn inspect_csv(path: &Path, name: &str, delimiter: u8, limit: usize) -> Result<String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_path(path)
+        .with_context(|| format!("Failed to open {}", name))?;
+    let headers: Vec<String> = reader.headers()?.iter().map(str::to_string).collect();
+
+    let mut types = vec![ColumnType::Empty; headers.len()];
+    let mut empty = vec![0usize; headers.len()];
+    let mut samples = Vec::new();
+    let mut rows = 0usize;
+    for record in reader.records() {
+        let record = record.with_context(|| format!("Failed to parse row {}", rows + 1))?;
+        for (i, value) in record.iter().enumerate().take(headers.len()) {
+            if value.is_empty() {
+                empty[i] += 1;
+            } else {
+                types[i] = types[i].widen(value);
+            }
+        }
+        if samples.len() < limit {
+            samples.push(record.iter().map(str::to_string).collect::<Vec<_>>());
+        }
+        rows += 1;
+    }
+
+    let mut out = format!(
+        "{}: {} rows, {} columns\n\nColumns:\n",
+        name,
+        rows,
+        headers.len()
+    );
+    for (i, header) in headers.iter().enumerate() {
+        out.push_str(&format!("  {}: {}", header, types[i].name()));
+        if empty[i] > 0 {
+            out.push_str(&format!(" ({} empty)", empty[i]));
+        }
+        out.push('\n');
+    }
+    out.push_str(&sample_section(&headers, &samples, rows));
+    Ok(out)
+}
+
+fn inspect_parquet(path: &Path, name: &str, limit: usize) -> Result<String> {
+    use parquet::basic::ConvertedType;
+    use parquet::file::reader::{FileReader, SerializedFileReader};
+
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", name))?;
+    let reader = SerializedFileReader::new(file).context("Not a valid Parquet file")?;
+    let metadata = reader.metadata().file_metadata();
+    let rows = metadata.num_rows().max(0) as usize;
+    let fields = metadata.schema().get_fields();
+
+    let mut out = format!(
+        "{}: {} rows, {} columns, {} row groups\n\nColumns:\n",
+        name,
+        rows,
+        fields.len(),
+        reader.num_row_groups()
+    );
+    for field in fields {
+        let info = field.get_basic_info();
+        let kind = if field.is_primitive() {
+            // Older writers only set the converted type (e.g. UTF8)
+            let annotation = match info.logical_type() {
+                Some(logical) => Some(format!("{:?}", logical)),
+                None if info.converted_type() != ConvertedType::NONE => {
+                    Some(info.converted_type().to_string())
+                }
+                None => None,
+            };
+            match annotation {
+                Some(annotation) => format!("{} ({})", field.get_physical_type(), annotation),
+                None => field.get_physical_type().to_string(),
+            }
+        } else {
+            "group".to_string()
+        };
+        let nullable =
+            if info.has_repetition() && info.repetition() == parquet::basic::Repetition::OPTIONAL {
+                ", nullable"
+            } else {
+                ""
+            };
+        out.push_str(&format!("  {}: {}{}\n", info.name(), kind, nullable));
+    }
+
+    let headers: Vec<String> = fields
+        .iter()
+        .map(|f| f.get_basic_info().name().to_string())
+        .collect();
+    let mut samples = Vec::new();
+    for row in reader.get_row_iter(None)?.take(limit) {
+        let row = row?;
+        samples.push(
+            row.get_column_iter()
+                .map(|(_, field)| field.to_string())
+                .collect(),
+        );
+    }
+    out.push_str(&sample_section(&headers, &samples, rows));
+    Ok(out)
+}
+
+async fn open_sqlite(path: &Path) -> Result<sqlx::SqliteConnection> {
+    use sqlx::sqlite::SqliteConnectOptions;
+    use sqlx::ConnectOptions;
+
+    SqliteConnectOptions::new()
+        .filename(path)
+        .read_only(true)
+        .connect()
+        .await
+        .with_context(|| format!("Failed to open {} as SQLite", path.display()))
+}
+
+/// Tables and views with their schemas and row counts
+async fn sqlite_overview(path: &Path, name: &str) -> Result<String> {
+    let mut conn = open_sqlite(path).await?;
+    let objects: Vec<(String, String, Option<String>)> = sqlx::query_as(
+        "SELECT type, name, sql FROM sqlite_master \
+         WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+    )
+    .fetch_all(&mut conn)
+    .await?;
+
+    if objects.is_empty() {
+        return Ok(format!("{}: SQLite database with no tables", name));
+    }
+    let mut out = format!(
+        "{}: SQLite database, {} tables and views\n",
+        name,
+        objects.len()
+    );
+    for (kind, table, sql) in objects {
+        let count: Option<i64> = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM \"{}\"",
+            table.replace('"', "\"\"")
+        ))
+        .fetch_one(&mut conn)
+        .await
+        .ok();
+        let count = count.map(|c| format!("{} rows", c)).unwrap_or_default();
+        out.push_str(&format!(
+            "\n{} {} ({})\n{}\n",
+            kind,
+            table,
+            count,
+            sql.unwrap_or_default()
+        ));
+    }
+    out.push_str("\nRun SQL with the query parameter to look at the data.");
+    Ok(out)
+}
+
+async fn sqlite_query(path: &Path, query: &str, limit: usize) -> Result<String> {
+    use sqlx::{Column, Row, TypeInfo, ValueRef};
+
+    let mut conn = open_sqlite(path).await?;
+    let mut stream = sqlx::query(query).fetch(&mut conn);
+    let mut headers = Vec::new();
+    let mut rows = Vec::new();
+    let mut more = false;
+    while let Some(row) = stream.try_next().await.context("Query failed")? {
+        if rows.len() == limit {
+            more = true;
+            break;
+        }
+        if headers.is_empty() {
+            headers = row.columns().iter().map(|c| c.name().to_string()).collect();
+        }
+        let mut cells = Vec::new();
+        for i in 0..row.len() {
+            let raw = row.try_get_raw(i)?;
+            let cell = if raw.is_null() {
+                "NULL".to_string()
+            } else {
+                match raw.type_info().name() {
+                    "INTEGER" => row.try_get::<i64, _>(i)?.to_string(),
+                    "REAL" => row.try_get::<f64, _>(i)?.to_string(),
+                    "BLOB" => format!("<{} bytes>", row.try_get::<Vec<u8>, _>(i)?.len()),
+                    _ => row.try_get::<String, _>(i)?,
+                }
+            };
+            cells.push(cell);
+        }
+        rows.push(cells);
+    }
+
+    if rows.is_empty() {
+        return Ok("Query returned no rows".to_string());
+    }
+    let mut out = render_table(&headers, &rows);
+    if more {
+        out.push_str(&format!(
+            "\n[Showing the first {} rows. Narrow the query or raise limit for more.]",
+            limit
+        ));
+    }
+    Ok(out)
+}
+
+/// DuckDB has no embedded driver here; use its CLI when installed
+async fn duckdb_query(path: &Path, query: &str, ctx: &ToolContext<'_>) -> Result<String> {
+    let output = tokio::process::Command::new("duckdb")
+        .arg("-readonly")
+        .arg(path)
+        .arg("-c")
+        .arg(query)
+        .current_dir(ctx.working_dir)
+        .output()
+        .await;
+    let output = match output {
+        Ok(output) => output,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            anyhow::bail!("DuckDB files need the duckdb CLI on PATH (https://duckdb.org)")
+        }
+        Err(e) => return Err(e).context("Failed to run duckdb"),
+    };
+    if !output.status.success() {
+        anyhow::bail!(
+            "duckdb failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(truncate_str(&stdout, ctx.config.max_output_bytes).to_string())
+}
+
+fn sample_section(headers: &[String], samples: &[Vec<String>], rows: usize) -> String {
+    if samples.is_empty() {
+        return String::new();
+    }
+    let title = if samples.len() < rows {
+        format!("\nFirst {} rows:\n", samples.len())
+    } else {
+        "\nRows:\n".to_string()
+    };
+    title + &render_table(headers, samples)
+}
+
+/// Markdown table with long cells cut
+fn render_table(headers: &[String], rows: &[Vec<String>]) -> String {
+    let cell = |value: &str| {
+        let value = value.replace(['\n', '\r'], " ").replace('|', "\\|");
+        let cut = truncate_str(&value, MAX_CELL_CHARS);
+        if cut.len() < value.len() {
+            format!("{}…", cut)
+        } else {
+            value
+        }
+    };
+    let mut out = format!(
+        "| {} |\n|{}\n",
+        headers
+            .iter()
+            .map(|h| cell(h))
+            .collect::<Vec<_>>()
+            .join(" | "),
+        " --- |".repeat(headers.len())
+    );
+    for row in rows {
+        out.push_str(&format!(
+            "| {} |\n",
+            row.iter().map(|v| cell(v)).collect::<Vec<_>>().join(" | ")
+        ));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_schema_and_samples() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.csv");
+        std::fs::write(
+            &path,
+            "id,name,score,active\n1,Ada,9.5,true\n2,Bob,7,false\n3,,8,true\n",
+        )
+        .unwrap();
+
+        let out = inspect_csv(&path, "people.csv", b',', 2).unwrap();
+        assert!(out.starts_with("people.csv: 3 rows, 4 columns"));
+        assert!(out.contains("  id: integer\n"));
+        assert!(out.contains("  name: text (1 empty)\n"));
+        assert!(out.contains("  score: float\n"));
+        assert!(out.contains("  active: boolean\n"));
+        assert!(out.contains("First 2 rows:\n| id | name | score | active |"));
+        assert!(out.contains("| 2 | Bob | 7 | false |"));
+        assert!(!out.contains("| 3 |"));
+    }
+
+    #[test]
+    fn test_parquet_schema_and_samples() {
+        use parquet::data_type::{ByteArray, ByteArrayType, Int64Type};
+        use parquet::file::properties::WriterProperties;
+        use parquet::file::writer::SerializedFileWriter;
+        use std::sync::Arc;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.parquet");
+        let schema = parquet::schema::parser::parse_message_type(
+            "message people { required int64 id; optional binary name (UTF8); }",
+        )
+        .unwrap();
+        let props = Arc::new(WriterProperties::builder().build());
+        let file = std::fs::File::create(&path).unwrap();
+        let mut writer = SerializedFileWriter::new(file, Arc::new(schema), props).unwrap();
+        let mut group = writer.next_row_group().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<Int64Type>()
+            .write_batch(&[1, 2, 3], None, None)
+            .unwrap();
+        column.close().unwrap();
+        let mut column = group.next_column().unwrap().unwrap();
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(
+                &[ByteArray::from("ada"), ByteArray::from("bob")],
+                Some(&[1, 1, 0]),
+                None,
+            )
+            .unwrap();
+        column.close().unwrap();
+        group.close().unwrap();
+        writer.close().unwrap();
+
+        let out = inspect_parquet(&path, "people.parquet", 2).unwrap();
+        assert!(out.starts_with("people.parquet: 3 rows, 2 columns, 1 row groups"));
+        assert!(out.contains("  id: INT64\n"));
+        assert!(out.contains("  name: BYTE_ARRAY (UTF8), nullable\n"));
+        assert!(out.contains("First 2 rows:\n| id | name |"));
+        assert!(out.contains("| 2 | \"bob\" |"));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_is_read_only() {
+        use sqlx::sqlite::SqliteConnectOptions;
+        use sqlx::ConnectOptions;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        let mut conn = SqliteConnectOptions::new()
+            .filename(&path)
+            .create_if_missing(true)
+            .connect()
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, avatar BLOB)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (name, avatar) VALUES ('ada', x'0102'), ('bob', NULL)")
+            .execute(&mut conn)
+            .await
+            .unwrap();
+        drop(conn);
+
+        let overview = sqlite_overview(&path, "app.db").await.unwrap();
+        assert!(overview.contains("table users (2 rows)"));
+        assert!(overview.contains("CREATE TABLE users"));
+
+        let rows = sqlite_query(&path, "SELECT * FROM users ORDER BY id", 1)
+            .await
+            .unwrap();
+        assert!(rows.starts_with("| id | name | avatar |"));
+        assert!(rows.contains("| 1 | ada | <2 bytes> |"));
+        assert!(rows.contains("Showing the first 1 rows"));
+
+        assert!(sqlite_query(&path, "DELETE FROM users", 10).await.is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use anyhow::Result;
 use async_trait::async_trait;
+use std::collections::HashSet;
 use std::fmt;
 use std::path::Path;
 use std::sync::Arc;
@@ -30,8 +31,13 @@ impl AgentMode {
                 "grep",        // Search file contents
                 "ast_grep",    // AST-based code search
                 "code_search", // Advanced multi-pattern code search
+                "inspect_data", // Schema and samples of data files, read-only SQL
                 "webfetch",    // Fetch web content
                 "todoread",    // Read task list
+                "ci_status",   // CI results for the current branch
+                "audit_dependencies", // Known vulnerabilities in dependencies
+                "open_in_editor", // Show a file in the attached editor
+                "search_tools", // Load tools that aren't sent by default
             ],
             AgentMode::Build => &[
                 "read_file",
@@ -42,13 +48,26 @@ impl AgentMode {
                 "grep",
                 "ast_grep",
                 "code_search",
+                "inspect_data",
                 "bash",
+                "run_tests",
+                "add_dependency",
+                "remove_dependency",
+                "audit_dependencies",
                 "webfetch",
+                "http_request",
+                "browser",
                 "todowrite",
+                "todoupdate",
                 "todoread",
+                "update_plan",
                 "build_config",
+                "ci_status",
+                "open_in_editor",
                 // "orchestrate", // Disabled for v1 launch - coming soon
                 "subagent",
+                "task",
+                "search_tools",
             ],
         }
     }
@@ -85,23 +104,59 @@ impl AgentMode {
     }
 }
 
+/// Tools whose schemas are sent every turn when lazy loading is on; the
+/// others are loaded through `search_tools`
+pub const CORE_TOOLS: &[&str] = &[
+    "read_file",
+    "write_file",
+    "edit_file",
+    "list_file",
+    "glob",
+    "grep",
+    "bash",
+    "run_tests",
+    "todowrite",
+    "todoupdate",
+    "todoread",
+    "update_plan",
+    "subagent",
+    "task",
+    "search_tools",
+];
+
 impl fmt::Display for AgentMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.short_name())
     }
 }
 
+pub mod anchors;
 pub mod ast_grep;
+pub mod audit;
 pub mod bash;
+pub mod browser;
 pub mod build_config;
+pub mod ci_status;
 pub mod code_search;
+pub mod dependency;
 
 pub mod edit;
+pub mod error;
+pub mod file_cache;
 pub mod glob;
 pub mod grep;
+pub mod http_request;
+pub mod inspect_data;
 pub mod list;
+pub mod open_in_editor;
 pub mod orchestrate;
+pub mod plan;
+pub mod preview;
+pub mod protected;
 pub mod read;
+pub mod repair;
+pub mod run_tests;
+pub mod search_tools;
 pub mod subagent;
 pub mod todo;
 pub mod webfetch;
@@ -109,17 +164,31 @@ pub mod write;
 pub mod git;
 
 pub use ast_grep::{patterns, search_file, AstGrepParams, AstGrepTool, AstLanguage, AstMatch};
+pub use audit::AuditDependenciesTool;
 pub use bash::BashTool;
+#[cfg(feature = "browser")]
+pub use browser::BrowserTool;
 pub use build_config::BuildConfigTool;
+pub use ci_status::CiStatusTool;
 pub use code_search::CodeSearchTool;
+pub use dependency::{AddDependencyTool, RemoveDependencyTool};
 pub use edit::EditTool;
+pub use error::{render_tool_error, ToolError, ToolErrorInfo};
+pub use file_cache::FileCache;
 pub use glob::GlobTool;
 pub use grep::GrepTool;
+pub use http_request::HttpRequestTool;
+pub use inspect_data::InspectDataTool;
 pub use list::ListTool;
+pub use open_in_editor::OpenInEditorTool;
 pub use orchestrate::OrchestrateTool;
+pub use plan::PlanUpdateTool;
+pub use protected::{ProtectedPathViolation, ProtectedPaths};
 pub use read::ReadTool;
+pub use run_tests::RunTestsTool;
+pub use search_tools::SearchToolsTool;
 pub use subagent::SubagentTool;
-pub use todo::{TodoReadTool, TodoWriteTool};
+pub use todo::{TodoReadTool, TodoUpdateTool, TodoWriteTool};
 pub use webfetch::WebFetchTool;
 pub use write::WriteTool;
 pub use git::GitTool;
@@ -136,6 +205,8 @@ pub struct ToolContext<'a> {
     pub output_callback: Option<OutputCallback>,
     /// Optional session event sender for subagent streaming
     pub session_event_tx: Option<mpsc::UnboundedSender<crate::session::SessionEvent>>,
+    /// Optional per-session file content cache used by read_file and grep
+    pub file_cache: Option<FileCache>,
 }
 
 impl<'a> ToolContext<'a> {
@@ -145,6 +216,7 @@ impl<'a> ToolContext<'a> {
             config,
             output_callback: None,
             session_event_tx: None,
+            file_cache: None,
         }
     }
 
@@ -158,6 +230,7 @@ impl<'a> ToolContext<'a> {
             config,
             output_callback: Some(callback),
             session_event_tx: None,
+            file_cache: None,
         }
     }
 
@@ -168,6 +241,59 @@ impl<'a> ToolContext<'a> {
         self.session_event_tx = Some(tx);
         self
     }
+
+    pub fn with_file_cache(mut self, cache: FileCache) -> Self {
+        self.file_cache = Some(cache);
+        self
+    }
+
+    /// Read a file as UTF-8, through the file cache when there is one
+    pub fn read_file(&self, path: &Path) -> std::io::Result<Arc<String>> {
+        match &self.file_cache {
+            Some(cache) => cache.read(path),
+            None => std::fs::read_to_string(path).map(Arc::new),
+        }
+    }
+
+    /// Remember `content` as what the model last read or wrote of `path`
+    pub fn record_seen(&self, path: &Path, content: &str) {
+        if let Some(cache) = &self.file_cache {
+            cache.record_seen(path, content);
+        }
+    }
+
+    /// Whether `path` changed since the model last read or wrote it
+    pub fn is_stale(&self, path: &Path, content: &str) -> bool {
+        self.file_cache
+            .as_ref()
+            .is_some_and(|cache| cache.is_stale(path, content))
+    }
+
+    /// Drop a path from the file cache after modifying it
+    pub fn invalidate_file(&self, path: &Path) {
+        if let Some(cache) = &self.file_cache {
+            cache.invalidate(path);
+        }
+    }
+
+    /// Protected path patterns from `tools.protected_paths`
+    pub fn protected_paths(&self) -> ProtectedPaths {
+        ProtectedPaths::new(&self.config.protected_paths)
+    }
+
+    /// Refuse modification of a path covered by `tools.protected_paths`
+    pub fn check_protected_path(&self, tool: &str, path: &str) -> Result<()> {
+        self.protected_paths()
+            .check_path(tool, self.working_dir, path)
+            .map_err(anyhow::Error::new)
+    }
+
+    /// Refuse shell commands that would modify a protected path
+    pub fn check_protected_command(&self, command: &str) -> Result<()> {
+        self.protected_paths()
+            .check_command(self.working_dir, command)
+            .map_err(anyhow::Error::new)
+    }
 }
 
 #[async_trait]
@@ -176,6 +302,11 @@ pub trait Tool: Send + Sync {
     fn description(&self) -> &str;
     fn parameters_schema(&self) -> serde_json::Value;
     async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String>;
+
+    /// Whether the tool can be used in `mode`
+    fn available_in(&self, mode: AgentMode) -> bool {
+        mode.is_tool_enabled(self.name())
+    }
 }
 
 pub struct ToolRegistry {
@@ -207,17 +338,30 @@ impl ToolRegistry {
         registry.register(Box::new(GrepTool));
         registry.register(Box::new(AstGrepTool));
         registry.register(Box::new(CodeSearchTool));
+        registry.register(Box::new(InspectDataTool));
         // Shell execution
         registry.register(Box::new(BashTool));
+        registry.register(Box::new(RunTestsTool));
+        registry.register(Box::new(AddDependencyTool));
+        registry.register(Box::new(RemoveDependencyTool));
+        registry.register(Box::new(AuditDependenciesTool));
         // Web access
         registry.register(Box::new(WebFetchTool));
+        registry.register(Box::new(HttpRequestTool::new()));
+        #[cfg(feature = "browser")]
+        registry.register(Box::new(BrowserTool));
         // Task tracking
         registry.register(Box::new(TodoWriteTool));
+        registry.register(Box::new(TodoUpdateTool));
         registry.register(Box::new(TodoReadTool));
+        registry.register(Box::new(PlanUpdateTool));
         // Build configuration
         registry.register(Box::new(BuildConfigTool));
         // Git tool
         registry.register(Box::new(GitTool));
+        registry.register(Box::new(CiStatusTool));
+        // Attached editor
+        registry.register(Box::new(OpenInEditorTool));
         registry
     }
 
@@ -262,17 +406,30 @@ impl ToolRegistry {
         self.register(Box::new(GrepTool));
         self.register(Box::new(AstGrepTool));
         self.register(Box::new(CodeSearchTool));
+        self.register(Box::new(InspectDataTool));
         // Shell execution
         self.register(Box::new(BashTool));
+        self.register(Box::new(RunTestsTool));
+        self.register(Box::new(AddDependencyTool));
+        self.register(Box::new(RemoveDependencyTool));
+        self.register(Box::new(AuditDependenciesTool));
         // Web access
         self.register(Box::new(WebFetchTool));
+        self.register(Box::new(HttpRequestTool::new()));
+        #[cfg(feature = "browser")]
+        self.register(Box::new(BrowserTool));
         // Task tracking
         self.register(Box::new(TodoWriteTool));
+        self.register(Box::new(TodoUpdateTool));
         self.register(Box::new(TodoReadTool));
+        self.register(Box::new(PlanUpdateTool));
         // Build configuration
         self.register(Box::new(BuildConfigTool));
         // Git tool
         self.register(Box::new(GitTool));
+        self.register(Box::new(CiStatusTool));
+        // Attached editor
+        self.register(Box::new(OpenInEditorTool));
 
         // Create event channel for subagent communication
         let (event_tx, mut event_rx) = mpsc::unbounded_channel::<SubagentEvent>();
@@ -284,10 +441,10 @@ impl ToolRegistry {
                 // Convert SubagentEvent to SessionEvent and forward
                 if let Some(ref tx) = forward_tx {
                     let session_event: SessionEvent = match event {
-                        SubagentEvent::Started { id, kind, task } => {
+                        SubagentEvent::Started { id, name, task } => {
                             SessionEvent::SubagentStarted {
                                 id,
-                                kind: kind.display_name().to_string(),
+                                kind: name,
                                 task,
                             }
                         }
@@ -374,6 +531,17 @@ impl ToolRegistry {
             inner: subagent_tool,
         }));
 
+        // Custom agents from .safe-coder/agents, run through the task tool
+        let agents = crate::subagent::load_agent_definitions(&project_path);
+        if !agents.is_empty() {
+            tracing::info!("Loaded {} custom agent(s)", agents.len());
+            self.register(Box::new(crate::subagent::TaskTool::new(
+                config.clone(),
+                project_path.clone(),
+                agents,
+            )));
+        }
+
         // Create and initialize orchestrate tool
         let orchestrate_tool = Arc::new(OrchestrateTool::new());
         orchestrate_tool.initialize(config, project_path).await;
@@ -384,6 +552,11 @@ impl ToolRegistry {
         self
     }
 
+    /// Background monitors started by the subagent tool, if it's registered
+    pub fn monitor_hub(&self) -> Option<Arc<crate::subagent::MonitorHub>> {
+        self.subagent_tool.as_ref().map(|tool| tool.monitors())
+    }
+
     pub fn register(&mut self, tool: Box<dyn Tool>) {
         self.tools.push(tool);
     }
@@ -411,9 +584,27 @@ impl ToolRegistry {
     /// Get tool schemas filtered by agent mode
     /// Only returns tools that are enabled for the given mode
     pub fn get_tools_schema_for_mode(&self, mode: AgentMode) -> Vec<serde_json::Value> {
+        self.get_tools_schema_for_turn(mode, None)
+    }
+
+    /// Tool schemas to send for a turn in `mode`. With lazy loading
+    /// (`search_tools` registered), only the core tools and those in
+    /// `loaded` are included; pass None to include every tool.
+    pub fn get_tools_schema_for_turn(
+        &self,
+        mode: AgentMode,
+        loaded: Option<&HashSet<String>>,
+    ) -> Vec<serde_json::Value> {
+        let lazy = self.get_tool(search_tools::SEARCH_TOOLS).is_some();
         self.tools
             .iter()
-            .filter(|tool| mode.is_tool_enabled(tool.name()))
+            .filter(|tool| tool.available_in(mode))
+            .filter(|tool| match loaded {
+                Some(loaded) if lazy => {
+                    CORE_TOOLS.contains(&tool.name()) || loaded.contains(tool.name())
+                }
+                _ => true,
+            })
             .map(|tool| {
                 serde_json::json!({
                     "name": tool.name(),
@@ -424,9 +615,33 @@ impl ToolRegistry {
             .collect()
     }
 
+    /// Register `search_tools` over every registered tool outside
+    /// `CORE_TOOLS`, so their schemas are only sent once loaded. Call it
+    /// after all other tools (including MCP tools) are registered.
+    pub fn enable_lazy_loading(&mut self) {
+        let catalog: Vec<search_tools::LoadableTool> = self
+            .tools
+            .iter()
+            .filter(|tool| !CORE_TOOLS.contains(&tool.name()))
+            .map(|tool| search_tools::LoadableTool {
+                name: tool.name().to_string(),
+                description: tool.description().to_string(),
+                plan: tool.available_in(AgentMode::Plan),
+                build: tool.available_in(AgentMode::Build),
+            })
+            .filter(|tool| tool.plan || tool.build)
+            .collect();
+        if !catalog.is_empty() {
+            self.register(Box::new(SearchToolsTool::new(catalog)));
+        }
+    }
+
     /// Check if a tool can be executed in the given mode
     pub fn can_execute_in_mode(&self, tool_name: &str, mode: AgentMode) -> bool {
-        mode.is_tool_enabled(tool_name)
+        self.get_tool(tool_name).map_or_else(
+            || mode.is_tool_enabled(tool_name),
+            |tool| tool.available_in(mode),
+        )
     }
 }
 
@@ -0,0 +1,66 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::path::PathBuf;
+
+use super::{Tool, ToolContext};
+
+#[derive(Debug, Deserialize)]
+struct OpenInEditorParams {
+    /// File to show (absolute or relative to the working directory)
+    file_path: String,
+    /// 1-based line to jump to
+    #[serde(default)]
+    line: Option<usize>,
+}
+
+pub struct OpenInEditorTool;
+
+#[async_trait]
+impl Tool for OpenInEditorTool {
+    fn name(&self) -> &str {
+        "open_in_editor"
+    }
+
+    fn description(&self) -> &str {
+        "Opens a file in the user's editor, optionally at a line. \
+         Use it to point the user at code you are discussing or just changed. \
+         Only works when an editor is attached (the system prompt then has an Editor section)."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "The file to open (absolute or relative to working directory)"
+                },
+                "line": {
+                    "type": "integer",
+                    "description": "1-based line to jump to"
+                }
+            },
+            "required": ["file_path"]
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: OpenInEditorParams = serde_json::from_value(params)?;
+        let path = PathBuf::from(&params.file_path);
+        let path = if path.is_absolute() {
+            path
+        } else {
+            ctx.working_dir.join(path)
+        };
+        if !path.exists() {
+            anyhow::bail!("File not found: {}", params.file_path);
+        }
+
+        let editor = crate::ide::open_in_editor(ctx.working_dir, &path, params.line)?;
+        Ok(match params.line {
+            Some(line) => format!("Opened {}:{} in {}", params.file_path, line, editor),
+            None => format!("Opened {} in {}", params.file_path, editor),
+        })
+    }
+}
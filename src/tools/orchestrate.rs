@@ -226,6 +226,7 @@ impl Tool for OrchestrateTool {
             relevant_files: params.relevant_files.clone(),
             dependencies: vec![],
             preferred_worker: Some(worker_kind.clone()),
+            tags: vec![],
             priority: 0,
             status: TaskStatus::Pending,
         };
@@ -0,0 +1,84 @@
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::{Tool, ToolContext, ToolError};
+use crate::planning::artifact::format_status;
+use crate::planning::{PlanStepStatus, PlanStore};
+
+/// Ticks off steps of a plan saved in `.safe-coder/plans/`
+pub struct PlanUpdateTool;
+
+#[derive(Debug, Deserialize)]
+struct PlanUpdateParams {
+    step: usize,
+    status: String,
+    #[serde(default)]
+    plan_id: Option<String>,
+    #[serde(default)]
+    note: Option<String>,
+}
+
+fn parse_status(status: &str) -> Option<PlanStepStatus> {
+    match status {
+        "pending" => Some(PlanStepStatus::Pending),
+        "in_progress" => Some(PlanStepStatus::InProgress),
+        "completed" => Some(PlanStepStatus::Completed),
+        "failed" => Some(PlanStepStatus::Failed),
+        "skipped" => Some(PlanStepStatus::Skipped),
+        _ => None,
+    }
+}
+
+#[async_trait]
+impl Tool for PlanUpdateTool {
+    fn name(&self) -> &str {
+        "update_plan"
+    }
+
+    fn description(&self) -> &str {
+        "Updates a step of the saved plan (.safe-coder/plans/). Mark each step in_progress when \
+        you start it and completed (or failed/skipped) when you are done. Returns the remaining steps."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "step": {
+                    "type": "integer",
+                    "description": "Step number as shown in the plan (1-based)"
+                },
+                "status": {
+                    "type": "string",
+                    "enum": ["pending", "in_progress", "completed", "failed", "skipped"]
+                },
+                "plan_id": {
+                    "type": "string",
+                    "description": "Plan to update (default: the newest unfinished plan)"
+                },
+                "note": {
+                    "type": "string",
+                    "description": "Optional summary of the outcome, or why the step failed"
+                }
+            },
+            "required": ["step", "status"]
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: PlanUpdateParams =
+            serde_json::from_value(params).context("Invalid parameters for update_plan")?;
+        let status = parse_status(&params.status).ok_or_else(|| {
+            ToolError::InvalidInput(format!("Unknown step status '{}'", params.status))
+        })?;
+
+        let plan = PlanStore::new(ctx.working_dir).update_step(
+            params.plan_id.as_deref(),
+            params.step,
+            status,
+            params.note,
+        )?;
+        Ok(format_status(&plan))
+    }
+}
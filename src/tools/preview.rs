@@ -0,0 +1,292 @@
+//! Summaries `read_file` returns instead of raw text
+//!
+//! Binaries, lockfiles, minified bundles and very large files would flood
+//! the context (or fail to decode) if returned whole. Unless the model asks
+//! for a line range, they are described instead: what the file is, its
+//! size, and a preview or a structured summary.
+
+use std::io::Read;
+use std::path::Path;
+
+use crate::utils::truncate_str;
+
+/// Bytes sniffed to tell binary from text
+const SNIFF_BYTES: usize = 8192;
+
+/// Text files larger than this get a head/tail preview
+const LARGE_FILE_BYTES: usize = 256 * 1024;
+
+/// Lines shown from the start and end of a large file
+const PREVIEW_HEAD_LINES: usize = 60;
+const PREVIEW_TAIL_LINES: usize = 20;
+
+/// Lines longer than this are cut when shown
+const MAX_LINE_CHARS: usize = 2000;
+
+/// Average line length above which a file is treated as minified, for
+/// files of at least `MINIFIED_MIN_BYTES`
+const MINIFIED_AVG_LINE_CHARS: usize = 500;
+const MINIFIED_MIN_BYTES: usize = 16 * 1024;
+
+/// Packages listed by name in a lockfile summary
+const MAX_LOCKFILE_PACKAGES: usize = 200;
+
+/// Describe `path` if it isn't text. Reads only its first few KB.
+pub fn binary_summary(path: &Path) -> std::io::Result<Option<String>> {
+    let mut file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut head = Vec::with_capacity(SNIFF_BYTES);
+    file.by_ref()
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut head)?;
+    if !looks_binary(&head) {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "[Binary file: {}, {}. Its contents are not shown; inspect it with bash \
+        (e.g. `file`, `xxd | head`) if needed.]",
+        format_size(size as usize),
+        kind_of(&head)
+    )))
+}
+
+/// NUL bytes or invalid UTF-8 (ignoring a character cut at the end)
+fn looks_binary(head: &[u8]) -> bool {
+    if head.contains(&0) {
+        return true;
+    }
+    match std::str::from_utf8(head) {
+        Ok(_) => false,
+        Err(e) => e.error_len().is_some(),
+    }
+}
+
+/// Common formats by magic number
+fn kind_of(head: &[u8]) -> &'static str {
+    const MAGIC: &[(&[u8], &str)] = &[
+        (b"\x89PNG", "PNG image"),
+        (b"\xFF\xD8\xFF", "JPEG image"),
+        (b"GIF8", "GIF image"),
+        (b"%PDF", "PDF document"),
+        (b"PK\x03\x04", "zip archive"),
+        (b"\x1F\x8B", "gzip archive"),
+        (b"\x7FELF", "ELF executable"),
+        (b"MZ", "Windows executable"),
+        (b"\xCF\xFA\xED\xFE", "Mach-O executable"),
+        (b"\0asm", "WebAssembly module"),
+        (b"SQLite format 3", "SQLite database"),
+    ];
+    MAGIC
+        .iter()
+        .find(|(magic, _)| head.starts_with(magic))
+        .map(|(_, kind)| *kind)
+        .unwrap_or("unknown format")
+}
+
+/// Summary for text that shouldn't be returned whole: lockfiles, minified
+/// files and files over `LARGE_FILE_BYTES`
+pub fn text_summary(path: &Path, content: &str) -> Option<String> {
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let lockfile = match file_name {
+        "Cargo.lock" => cargo_lock_summary(content),
+        "package-lock.json" => package_lock_summary(content),
+        _ => None,
+    };
+    if lockfile.is_some() {
+        return lockfile;
+    }
+
+    let lines: Vec<&str> = content.lines().collect();
+    let longest = lines.iter().map(|l| l.len()).max().unwrap_or(0);
+    let stats = format!(
+        "{}, {} lines, longest line {} chars",
+        format_size(content.len()),
+        lines.len(),
+        longest
+    );
+
+    if content.len() >= MINIFIED_MIN_BYTES
+        && content.len() / lines.len().max(1) > MINIFIED_AVG_LINE_CHARS
+    {
+        return Some(format!(
+            "[Minified or generated file: {}. Start of the file:]\n{}\n\n\
+            [Read a range with offset/limit only if you need it; search it with grep instead.]",
+            stats,
+            truncate_str(content, MAX_LINE_CHARS)
+        ));
+    }
+
+    if content.len() > LARGE_FILE_BYTES && lines.len() > PREVIEW_HEAD_LINES + PREVIEW_TAIL_LINES {
+        let tail_start = lines.len() - PREVIEW_TAIL_LINES;
+        return Some(format!(
+            "[Large file: {}. Showing the first {} and last {} lines.]\n{}\n     ...\n{}\n\n\
+            [Use symbol, around_line or offset/limit to read the part you need.]",
+            stats,
+            PREVIEW_HEAD_LINES,
+            PREVIEW_TAIL_LINES,
+            number_lines(&lines[..PREVIEW_HEAD_LINES], 0),
+            number_lines(&lines[tail_start..], tail_start)
+        ));
+    }
+    None
+}
+
+fn cargo_lock_summary(content: &str) -> Option<String> {
+    let lock: toml::Value = toml::from_str(content).ok()?;
+    let packages = lock.get("package")?.as_array()?;
+    let names: Vec<String> = packages
+        .iter()
+        .filter_map(|p| {
+            Some(format!(
+                "{} {}",
+                p.get("name")?.as_str()?,
+                p.get("version")?.as_str()?
+            ))
+        })
+        .collect();
+    Some(lockfile_summary("Cargo.lock", &names))
+}
+
+fn package_lock_summary(content: &str) -> Option<String> {
+    let lock: serde_json::Value = serde_json::from_str(content).ok()?;
+    let mut names: Vec<String> = match lock.get("packages").and_then(|p| p.as_object()) {
+        // lockfileVersion 2+: keyed by install path, "" is the project itself
+        Some(packages) => packages
+            .iter()
+            .filter(|(path, _)| !path.is_empty())
+            .map(|(path, p)| {
+                let name = path.rsplit("node_modules/").next().unwrap_or(path);
+                format!("{} {}", name, p["version"].as_str().unwrap_or("?"))
+            })
+            .collect(),
+        None => lock
+            .get("dependencies")?
+            .as_object()?
+            .iter()
+            .map(|(name, p)| format!("{} {}", name, p["version"].as_str().unwrap_or("?")))
+            .collect(),
+    };
+    names.sort();
+    names.dedup();
+    Some(lockfile_summary("package-lock.json", &names))
+}
+
+fn lockfile_summary(kind: &str, packages: &[String]) -> String {
+    let mut summary = format!(
+        "[{} summary: {} locked packages. Raw contents are not shown; \
+        read a range with offset/limit if you need the exact entries.]\n",
+        kind,
+        packages.len()
+    );
+    for package in packages.iter().take(MAX_LOCKFILE_PACKAGES) {
+        summary.push_str(package);
+        summary.push('\n');
+    }
+    if packages.len() > MAX_LOCKFILE_PACKAGES {
+        summary.push_str(&format!(
+            "... {} more\n",
+            packages.len() - MAX_LOCKFILE_PACKAGES
+        ));
+    }
+    summary
+}
+
+/// Lines prefixed with their 1-based numbers, the first being `offset + 1`.
+/// Overlong lines are cut.
+pub fn number_lines(lines: &[&str], offset: usize) -> String {
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let shown = truncate_str(line, MAX_LINE_CHARS);
+            if shown.len() < line.len() {
+                format!(
+                    "{:5}→{}… [line cut, {} more chars]",
+                    offset + i + 1,
+                    shown,
+                    line[shown.len()..].chars().count()
+                )
+            } else {
+                format!("{:5}→{}", offset + i + 1, line)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} bytes", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_detection() {
+        let dir = tempfile::tempdir().unwrap();
+        let png = dir.path().join("logo.png");
+        std::fs::write(&png, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        let summary = binary_summary(&png).unwrap().unwrap();
+        assert!(summary.starts_with("[Binary file: 16 bytes, PNG image."));
+
+        let text = dir.path().join("a.txt");
+        std::fs::write(&text, "héllo").unwrap();
+        assert!(binary_summary(&text).unwrap().is_none());
+
+        // A multi-byte character cut by the sniff window is still text
+        assert!(!looks_binary(&"é".as_bytes()[..1]));
+        assert!(looks_binary(b"\xC3\x28abc"));
+    }
+
+    #[test]
+    fn test_lockfiles_are_summarized() {
+        let cargo = "version = 3\n\n[[package]]\nname = \"anyhow\"\nversion = \"1.0.86\"\n\n\
+            [[package]]\nname = \"serde\"\nversion = \"1.0.200\"\n";
+        let summary = text_summary(Path::new("/p/Cargo.lock"), cargo).unwrap();
+        assert!(summary.starts_with("[Cargo.lock summary: 2 locked packages."));
+        assert!(summary.contains("anyhow 1.0.86\nserde 1.0.200\n"));
+
+        let npm = r#"{"lockfileVersion": 3, "packages": {
+            "": {"name": "app"},
+            "node_modules/react": {"version": "18.2.0"},
+            "node_modules/a/node_modules/b": {"version": "1.0.0"}
+        }}"#;
+        let summary = text_summary(Path::new("package-lock.json"), npm).unwrap();
+        assert!(summary.contains("2 locked packages"));
+        assert!(summary.contains("b 1.0.0\nreact 18.2.0\n"));
+    }
+
+    #[test]
+    fn test_minified_and_large_files() {
+        assert!(text_summary(Path::new("a.rs"), "fn main() {}\n").is_none());
+        let short_json = format!("{{\"a\": \"{}\"}}", "x".repeat(1000));
+        assert!(text_summary(Path::new("a.json"), &short_json).is_none());
+
+        let minified = format!("var a={};\n", "1+".repeat(10000));
+        let summary = text_summary(Path::new("app.min.js"), &minified).unwrap();
+        assert!(summary.starts_with("[Minified or generated file:"));
+        assert!(summary.len() < 3000);
+
+        let large: String = (0..40000).map(|i| format!("line {}\n", i)).collect();
+        let summary = text_summary(Path::new("big.txt"), &large).unwrap();
+        assert!(summary.starts_with("[Large file:"));
+        assert!(summary.contains("    1→line 0\n"));
+        assert!(summary.contains("40000→line 39999\n"));
+        assert!(!summary.contains("line 100\n"));
+    }
+
+    #[test]
+    fn test_long_lines_are_cut() {
+        let long = "x".repeat(MAX_LINE_CHARS + 10);
+        let numbered = number_lines(&["short", &long], 4);
+        assert!(numbered.starts_with("    5→short\n    6→xxx"));
+        assert!(numbered.ends_with("… [line cut, 10 more chars]"));
+    }
+}
@@ -0,0 +1,299 @@
+//! Protected Paths
+//!
+//! Enforces `tools.protected_paths`: glob patterns (relative to the project
+//! root) that write_file, edit_file and bash may never modify, regardless of
+//! agent mode or approval settings. Violations are reported as a structured
+//! refusal so the model can tell a policy block apart from an I/O failure.
+
+use glob::{MatchOptions, Pattern};
+use std::fmt;
+use std::path::{Component, Path, PathBuf};
+
+use crate::permissions::{CommandAnalysis, CommandCategory};
+
+/// Command categories that can modify the paths they are given
+const MUTATING_CATEGORIES: &[CommandCategory] = &[
+    CommandCategory::FileWrite,
+    CommandCategory::FilesystemDelete,
+    CommandCategory::PermissionChange,
+    CommandCategory::GitDestructive,
+];
+
+/// A refused attempt to touch a protected path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProtectedPathViolation {
+    /// Tool that attempted the modification
+    pub tool: String,
+    /// Path as given by the model
+    pub path: String,
+    /// Configured pattern that matched
+    pub pattern: String,
+}
+
+impl fmt::Display for ProtectedPathViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "🔒 PROTECTED PATH\n\n\
+            tool: {}\n\
+            path: {}\n\
+            matched pattern: {}\n\n\
+            This path is listed in tools.protected_paths and cannot be modified by any tool. \
+            Do not retry this operation or work around it with another tool. \
+            Continue without changing this path, or ask the user to change it themselves.",
+            self.tool, self.path, self.pattern
+        )
+    }
+}
+
+impl std::error::Error for ProtectedPathViolation {}
+
+/// Compiled set of protected path patterns
+#[derive(Debug, Clone, Default)]
+pub struct ProtectedPaths {
+    patterns: Vec<(String, Pattern)>,
+}
+
+impl ProtectedPaths {
+    /// Compile the configured patterns, skipping any that are invalid
+    pub fn new(patterns: &[String]) -> Self {
+        let patterns = patterns
+            .iter()
+            .filter_map(|p| match Pattern::new(p.trim_start_matches("./")) {
+                Ok(compiled) => Some((p.clone(), compiled)),
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid protected path pattern '{}': {}", p, e);
+                    None
+                }
+            })
+            .collect();
+        Self { patterns }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Return the pattern protecting `path`, if any.
+    ///
+    /// Relative paths are resolved against `working_dir` and `..` components are
+    /// collapsed first, so `src/../.git/config` is caught as well. A directory
+    /// pattern such as `.git/**` also protects the directory itself.
+    pub fn matching_pattern(&self, working_dir: &Path, path: &str) -> Option<&str> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let candidate = relative_to(working_dir, path)?;
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+
+        self.patterns.iter().find_map(|(raw, pattern)| {
+            let matched = pattern.matches_with(&candidate, options)
+                || directory_of(raw).is_some_and(|dir| {
+                    Pattern::new(dir)
+                        .map(|p| p.matches_with(&candidate, options))
+                        .unwrap_or(false)
+                })
+                // `**/name` should also match `name` at the project root
+                || raw
+                    .strip_prefix("**/")
+                    .is_some_and(|rest| Pattern::new(rest).is_ok_and(|p| p.matches_with(&candidate, options)));
+            matched.then_some(raw.as_str())
+        })
+    }
+
+    /// Check a single path about to be modified by `tool`
+    pub fn check_path(
+        &self,
+        tool: &str,
+        working_dir: &Path,
+        path: &str,
+    ) -> Result<(), ProtectedPathViolation> {
+        match self.matching_pattern(working_dir, path) {
+            Some(pattern) => Err(ProtectedPathViolation {
+                tool: tool.to_string(),
+                path: path.to_string(),
+                pattern: pattern.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Check a shell command for arguments or redirect targets that are protected.
+    ///
+    /// Redirect targets are always checked; arguments only for the sub-commands
+    /// that can modify files, so reading a protected file (`cat .env`,
+    /// `grep key .env | tee out.log`) is still allowed.
+    pub fn check_command(
+        &self,
+        working_dir: &Path,
+        command: &str,
+    ) -> Result<(), ProtectedPathViolation> {
+        if self.is_empty() {
+            return Ok(());
+        }
+
+        let analysis = CommandAnalysis::analyze(command);
+        for cmd in &analysis.commands {
+            for target in cmd.redirect_targets() {
+                self.check_path("bash", working_dir, target)?;
+            }
+
+            let categories = cmd.program_categories();
+            if !MUTATING_CATEGORIES.iter().any(|c| categories.contains(c)) {
+                continue;
+            }
+            for word in cmd.argv().iter().skip(1) {
+                if word.starts_with('-') {
+                    continue;
+                }
+                // Handle `--output=path` style arguments
+                let candidate = word.rsplit_once('=').map(|(_, v)| v).unwrap_or(word);
+                if candidate.is_empty() {
+                    continue;
+                }
+                self.check_path("bash", working_dir, candidate)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Strip a trailing `/**` (or `/**/*`) from a directory pattern
+fn directory_of(pattern: &str) -> Option<&str> {
+    pattern
+        .strip_suffix("/**/*")
+        .or_else(|| pattern.strip_suffix("/**"))
+        .filter(|dir| !dir.is_empty())
+}
+
+/// Resolve `path` against `working_dir` and express it relative to it.
+///
+/// Paths outside the project are returned as normalized absolute paths so that
+/// absolute patterns can still match them.
+fn relative_to(working_dir: &Path, path: &str) -> Option<String> {
+    let path = expand_home(path);
+    let joined = normalize(&working_dir.join(path));
+    let root = normalize(working_dir);
+
+    let resolved = match joined.strip_prefix(&root) {
+        Ok(rel) => rel.to_path_buf(),
+        Err(_) => joined,
+    };
+    let s = resolved.to_string_lossy().replace('\\', "/");
+    if s.is_empty() {
+        None
+    } else {
+        Some(s)
+    }
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    PathBuf::from(path)
+}
+
+/// Lexically collapse `.` and `..` components without touching the filesystem
+fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn protected() -> ProtectedPaths {
+        ProtectedPaths::new(&[
+            ".git/**".to_string(),
+            "**/.env".to_string(),
+            "migrations/**".to_string(),
+        ])
+    }
+
+    #[test]
+    fn test_matching_paths() {
+        let root = Path::new("/project");
+        let p = protected();
+
+        assert_eq!(p.matching_pattern(root, ".git/config"), Some(".git/**"));
+        assert_eq!(p.matching_pattern(root, ".git"), Some(".git/**"));
+        assert_eq!(p.matching_pattern(root, ".env"), Some("**/.env"));
+        assert_eq!(
+            p.matching_pattern(root, "services/api/.env"),
+            Some("**/.env")
+        );
+        assert_eq!(
+            p.matching_pattern(root, "./migrations/001_init.sql"),
+            Some("migrations/**")
+        );
+        assert_eq!(
+            p.matching_pattern(root, "src/../.git/HEAD"),
+            Some(".git/**")
+        );
+        assert_eq!(p.matching_pattern(root, "/project/.env"), Some("**/.env"));
+
+        assert_eq!(p.matching_pattern(root, "src/main.rs"), None);
+        assert_eq!(p.matching_pattern(root, ".env.example"), None);
+        assert_eq!(p.matching_pattern(root, "src/migrations/mod.rs"), None);
+        assert_eq!(p.matching_pattern(root, ".gitignore"), None);
+    }
+
+    #[test]
+    fn test_check_command() {
+        let root = Path::new("/project");
+        let p = protected();
+
+        assert!(p.check_command(root, "echo SECRET=1 > .env").is_err());
+        assert!(p.check_command(root, "rm -rf .git").is_err());
+        assert!(p
+            .check_command(root, "sed -i 's/a/b/' migrations/001.sql")
+            .is_err());
+        assert!(p
+            .check_command(root, "mv hook.sh .git/hooks/pre-commit")
+            .is_err());
+
+        // Reads and unrelated writes are fine
+        assert!(p.check_command(root, "cat .env").is_ok());
+        assert!(p.check_command(root, "git status").is_ok());
+        assert!(p.check_command(root, "echo hi > notes.txt").is_ok());
+        assert!(p.check_command(root, "cat .env && rm tmp.txt").is_ok());
+        assert!(p
+            .check_command(root, "grep secret .env | tee out.log")
+            .is_ok());
+        assert!(p
+            .check_command(root, "grep secret notes.txt | tee .env")
+            .is_err());
+        assert!(p
+            .check_command(root, "cat notes.txt && echo x >> .env")
+            .is_err());
+    }
+
+    #[test]
+    fn test_violation_message() {
+        let err = protected()
+            .check_path("write_file", Path::new("/project"), ".env")
+            .unwrap_err();
+        let msg = err.to_string();
+        assert!(msg.contains("PROTECTED PATH"));
+        assert!(msg.contains("tool: write_file"));
+        assert!(msg.contains("matched pattern: **/.env"));
+    }
+}
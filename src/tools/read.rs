@@ -1,9 +1,16 @@
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde::Deserialize;
+use std::path::Path;
 
 
-use super::{Tool, ToolContext};
+use super::anchors::{self, Anchor};
+use super::ast_grep::AstLanguage;
+use super::preview::{self, number_lines};
+use super::{Tool, ToolContext, ToolError};
+
+/// Lines shown on each side of `around_line` outside any definition
+const AROUND_LINE_CONTEXT: usize = 20;
 
 pub struct ReadTool;
 
@@ -14,6 +21,12 @@ struct ReadParams {
     offset: Option<usize>,
     #[serde(default)]
     limit: Option<usize>,
+    /// Read only this function, class or type (e.g. `Config::new`)
+    #[serde(default)]
+    symbol: Option<String>,
+    /// Read only the definition containing this 1-based line
+    #[serde(default)]
+    around_line: Option<usize>,
 }
 
 #[async_trait]
@@ -23,7 +36,9 @@ impl Tool for ReadTool {
     }
 
     fn description(&self) -> &str {
-        "Reads a file from the filesystem. Returns the contents with line numbers."
+        "Reads a file from the filesystem. Returns the contents with line numbers. \
+         For large files, pass symbol (a function, class or type name) or around_line \
+         to read just that definition."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -41,6 +56,14 @@ impl Tool for ReadTool {
                 "limit": {
                     "type": "number",
                     "description": "The number of lines to read (optional)"
+                },
+                "symbol": {
+                    "type": "string",
+                    "description": "Read only the definition of this function, class, struct or type, e.g. 'parse' or 'Config::new' (optional; Rust, TypeScript, JavaScript, Python, Go)"
+                },
+                "around_line": {
+                    "type": "number",
+                    "description": "Read only the function or class containing this line, e.g. from a stack trace (optional)"
                 }
             },
             "required": ["file_path"]
@@ -54,26 +77,99 @@ impl Tool for ReadTool {
         let file_path = ctx.working_dir.join(&params.file_path);
 
         if !file_path.exists() {
-            anyhow::bail!("File not found: {}", params.file_path);
+            return Err(ToolError::NotFound {
+                path: params.file_path,
+            }
+            .into());
+        }
+
+        if let Some(summary) = preview::binary_summary(&file_path)
+            .context("Failed to read file")?
+        {
+            return Ok(summary);
         }
 
-        let content = std::fs::read_to_string(&file_path)
+        let content = ctx.read_file(&file_path)
             .context("Failed to read file")?;
+        ctx.record_seen(&file_path, &content);
 
         let lines: Vec<&str> = content.lines().collect();
-        let offset = params.offset.unwrap_or(0);
         // Default limit to 500 lines to avoid context bloat, unless explicitly specified
         const MAX_DEFAULT_LINES: usize = 500;
+
+        if let Some(symbol) = &params.symbol {
+            let language = language_for(&file_path).ok_or_else(|| {
+                ToolError::InvalidInput(format!(
+                    "symbol lookup is not supported for {}; use offset/limit",
+                    params.file_path
+                ))
+            })?;
+            let found = anchors::find_symbol(&content, language, symbol)?;
+            if found.is_empty() {
+                let available = anchors::list_symbols(&content, language);
+                return Err(ToolError::InvalidInput(format!(
+                    "No definition named '{}' in {}. Definitions: {}",
+                    symbol,
+                    params.file_path,
+                    available.join(", ")
+                ))
+                .into());
+            }
+            let limit = params.limit.unwrap_or(MAX_DEFAULT_LINES);
+            return Ok(found
+                .iter()
+                .map(|anchor| render_anchor(&lines, anchor, limit))
+                .collect::<Vec<_>>()
+                .join("\n\n"));
+        }
+
+        if let Some(line) = params.around_line {
+            if line == 0 || line > lines.len() {
+                return Err(ToolError::InvalidInput(format!(
+                    "around_line {} is outside {} ({} lines)",
+                    line,
+                    params.file_path,
+                    lines.len()
+                ))
+                .into());
+            }
+            let enclosing = match language_for(&file_path) {
+                Some(language) => anchors::enclosing_definition(&content, language, line)?,
+                None => None,
+            };
+            let limit = params.limit.unwrap_or(MAX_DEFAULT_LINES);
+            return Ok(match enclosing {
+                Some(anchor) => render_anchor(&lines, &anchor, limit),
+                None => {
+                    let start = line.saturating_sub(AROUND_LINE_CONTEXT + 1);
+                    let end = (line + AROUND_LINE_CONTEXT).min(lines.len());
+                    format!(
+                        "[lines {}-{} of {}]\n{}",
+                        start + 1,
+                        end,
+                        lines.len(),
+                        number_lines(&lines[start..end], start)
+                    )
+                }
+            });
+        }
+
+        // Lockfiles, minified and very large files are summarised unless a
+        // range is asked for
+        if params.offset.is_none() && params.limit.is_none() {
+            if let Some(summary) = preview::text_summary(&file_path, &content) {
+                return Ok(summary);
+            }
+        }
+
+        let offset = params.offset.unwrap_or(0);
         let limit = params.limit.unwrap_or(MAX_DEFAULT_LINES.min(lines.len()));
         let total_lines = lines.len();
 
-        let selected_lines = lines.iter()
-            .skip(offset)
-            .take(limit)
-            .enumerate()
-            .map(|(i, line)| format!("{:5}→{}", offset + i + 1, line))
-            .collect::<Vec<_>>()
-            .join("\n");
+        let selected_lines = number_lines(
+            &lines[offset.min(total_lines)..(offset + limit).min(total_lines)],
+            offset,
+        );
 
         // Add info about truncation if file is larger than what we're showing
         if total_lines > offset + limit {
@@ -89,3 +185,32 @@ impl Tool for ReadTool {
         }
     }
 }
+
+fn language_for(path: &Path) -> Option<AstLanguage> {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .and_then(AstLanguage::from_extension)
+}
+
+/// A definition's lines under a header naming it, cut to `limit` lines
+fn render_anchor(lines: &[&str], anchor: &Anchor, limit: usize) -> String {
+    let start = anchor.start_line - 1;
+    let end = anchor.end_line.min(lines.len()).min(start + limit);
+    let mut rendered = format!(
+        "[{} {}: lines {}-{} of {}]\n{}",
+        anchor.kind,
+        anchor.name,
+        anchor.start_line,
+        anchor.end_line,
+        lines.len(),
+        number_lines(&lines[start..end], start)
+    );
+    if end < anchor.end_line {
+        rendered.push_str(&format!(
+            "\n\n[Showing lines {}-{}. Use offset/limit to read more.]",
+            start + 1,
+            end
+        ));
+    }
+    rendered
+}
@@ -0,0 +1,113 @@
+//! Error-context enrichment for failed tool calls
+//!
+//! An edit usually fails because the model's picture of the file is stale,
+//! and left alone it tends to send the same wrong edit again. Attaching the
+//! file as it is on disk now gives the model what it needs to fix the call
+//! on its next attempt.
+
+use super::ToolContext;
+
+/// Tools whose failures are usually caused by stale file contents
+const REPAIRABLE_TOOLS: &[&str] = &["edit_file"];
+
+/// Most lines of current content attached to an error
+const MAX_CONTEXT_LINES: usize = 300;
+
+/// The file a failed call targeted, when the tool is one worth repairing
+pub fn repair_target(name: &str, input: &serde_json::Value) -> Option<String> {
+    if !REPAIRABLE_TOOLS.contains(&name) {
+        return None;
+    }
+    input
+        .get("file_path")
+        .or_else(|| input.get("path"))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+}
+
+/// Append the target file's current contents to a tool error.
+///
+/// Large files are cut to a window around the first line of `old_string`
+/// (matched ignoring indentation), which is where the edit was aimed.
+pub fn enrich_error(
+    error: &str,
+    path: &str,
+    input: &serde_json::Value,
+    ctx: &ToolContext<'_>,
+) -> String {
+    let Ok(content) = ctx.read_file(&ctx.working_dir.join(path)) else {
+        return error.to_string();
+    };
+    let lines: Vec<&str> = content.lines().collect();
+
+    let anchor = input
+        .get("old_string")
+        .and_then(|v| v.as_str())
+        .and_then(|old| anchor_line(&lines, old))
+        .unwrap_or(0);
+    let start = anchor
+        .saturating_sub(MAX_CONTEXT_LINES / 2)
+        .min(lines.len().saturating_sub(MAX_CONTEXT_LINES));
+    let end = (start + MAX_CONTEXT_LINES).min(lines.len());
+
+    let excerpt = lines[start..end]
+        .iter()
+        .enumerate()
+        .map(|(i, line)| format!("{:5}→{}", start + i + 1, line))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let range = if end - start < lines.len() {
+        format!(" (lines {}-{} of {})", start + 1, end, lines.len())
+    } else {
+        String::new()
+    };
+
+    format!(
+        "{}\n\n[CURRENT CONTENT OF {}{}]\n{}\n\n\
+        This is the file as it is on disk now. Retry once, copying old_string exactly \
+        from the content above (without the line number prefixes).",
+        error, path, range, excerpt
+    )
+}
+
+/// Index of the first line matching the first non-blank line of `old`
+fn anchor_line(lines: &[&str], old: &str) -> Option<usize> {
+    let first = old.lines().map(str::trim).find(|l| !l.is_empty())?;
+    lines.iter().position(|line| line.trim() == first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ToolConfig;
+
+    #[test]
+    fn test_repair_target_only_for_edits() {
+        let input = serde_json::json!({ "file_path": "src/lib.rs", "old_string": "x" });
+        assert_eq!(
+            repair_target("edit_file", &input).as_deref(),
+            Some("src/lib.rs")
+        );
+        assert_eq!(repair_target("bash", &input), None);
+    }
+
+    #[test]
+    fn test_enrich_error_windows_around_old_string() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = (1..=1000)
+            .map(|i| format!("    line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        std::fs::write(dir.path().join("big.rs"), content).unwrap();
+
+        let config = ToolConfig::default();
+        let ctx = ToolContext::new(dir.path(), &config);
+        let input = serde_json::json!({ "old_string": "line 800\nstale" });
+        let enriched = enrich_error("Error: String not found", "big.rs", &input, &ctx);
+
+        assert!(enriched.starts_with("Error: String not found"));
+        assert!(enriched.contains("(lines 650-949 of 1000)"));
+        assert!(enriched.contains("  800→    line 800"));
+        assert!(!enriched.contains("→    line 1\n"));
+    }
+}
@@ -0,0 +1,582 @@
+//! Test runner with structured failure reports
+//!
+//! Detects the project's test command (cargo test, jest or pytest), runs
+//! it and parses the output into failures with their test name, location
+//! and message. The model gets a one-line summary and details for failing
+//! tests only, rather than the runner's full log.
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use regex::Regex;
+use serde::Deserialize;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+use super::{Tool, ToolContext, ToolError};
+use crate::utils::truncate_str;
+
+/// Failures described in full; the rest are listed by name
+const MAX_DETAILED_FAILURES: usize = 10;
+
+/// Lines of output kept per failure
+const MAX_FAILURE_LINES: usize = 25;
+
+/// Lines of output shown when it can't be parsed
+const MAX_RAW_LINES: usize = 60;
+
+/// Test frameworks whose output is parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestRunner {
+    Cargo,
+    Jest,
+    Pytest,
+}
+
+impl TestRunner {
+    /// The runner for a project, from its manifest files
+    pub fn detect(project: &Path) -> Option<Self> {
+        if project.join("Cargo.toml").exists() {
+            return Some(Self::Cargo);
+        }
+        let package_json =
+            std::fs::read_to_string(project.join("package.json")).unwrap_or_default();
+        let jest_config = ["js", "ts", "mjs", "cjs", "json"]
+            .iter()
+            .any(|ext| project.join(format!("jest.config.{}", ext)).exists());
+        if jest_config || package_json.contains("\"jest\"") {
+            return Some(Self::Jest);
+        }
+        let pytest_markers = [
+            "pytest.ini",
+            "conftest.py",
+            "pyproject.toml",
+            "setup.py",
+            "setup.cfg",
+            "tox.ini",
+        ];
+        if pytest_markers.iter().any(|m| project.join(m).exists()) {
+            return Some(Self::Pytest);
+        }
+        None
+    }
+
+    /// The runner a custom command invokes, if it is one we can parse
+    fn for_command(command: &str) -> Option<Self> {
+        if command.contains("cargo test") {
+            Some(Self::Cargo)
+        } else if command.contains("jest") {
+            Some(Self::Jest)
+        } else if command.contains("pytest") {
+            Some(Self::Pytest)
+        } else {
+            None
+        }
+    }
+
+    /// Program and arguments, with `filter` selecting tests by name
+    fn command(self, filter: Option<&str>) -> (&'static str, Vec<String>) {
+        let mut args: Vec<String> = match self {
+            Self::Cargo => vec!["test".into()],
+            Self::Jest => ["jest", "--ci", "--json", "--testLocationInResults"]
+                .map(String::from)
+                .to_vec(),
+            Self::Pytest => ["-m", "pytest", "-q", "--tb=short", "-rfE"]
+                .map(String::from)
+                .to_vec(),
+        };
+        if let Some(filter) = filter {
+            match self {
+                Self::Cargo => args.push(filter.to_string()),
+                Self::Jest => args.extend(["-t".to_string(), filter.to_string()]),
+                Self::Pytest => args.extend(["-k".to_string(), filter.to_string()]),
+            }
+        }
+        let program = match self {
+            Self::Cargo => "cargo",
+            Self::Jest => "npx",
+            Self::Pytest if which::which("python3").is_ok() => "python3",
+            Self::Pytest => "python",
+        };
+        (program, args)
+    }
+}
+
+/// One failing test
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestFailure {
+    pub name: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub message: String,
+}
+
+/// Counts and failures parsed from a test run
+#[derive(Debug, Default, PartialEq)]
+pub struct TestReport {
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub failures: Vec<TestFailure>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RunTestsParams {
+    #[serde(default)]
+    filter: Option<String>,
+    #[serde(default)]
+    command: Option<String>,
+    #[serde(default = "default_timeout")]
+    timeout_secs: u64,
+}
+
+fn default_timeout() -> u64 {
+    600
+}
+
+pub struct RunTestsTool;
+
+#[async_trait]
+impl Tool for RunTestsTool {
+    fn name(&self) -> &str {
+        "run_tests"
+    }
+
+    fn description(&self) -> &str {
+        "Runs the project's tests (cargo test, jest or pytest, detected automatically) and \
+         returns pass/fail counts plus the name, location and message of each failing test. \
+         Prefer this over running the test command with bash."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "filter": {
+                    "type": "string",
+                    "description": "Only run tests whose name matches (cargo test <filter>, jest -t, pytest -k)"
+                },
+                "command": {
+                    "type": "string",
+                    "description": "Test command to run instead of the detected one, e.g. 'cargo test -p core'"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Timeout in seconds (default 600)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: RunTestsParams = serde_json::from_value(params)?;
+
+        let (runner, mut command, shown) = match &params.command {
+            Some(command) => (
+                TestRunner::for_command(command),
+                crate::platform::shell_command(command),
+                command.clone(),
+            ),
+            None => {
+                let runner = TestRunner::detect(ctx.working_dir).ok_or_else(|| {
+                    ToolError::InvalidInput(
+                        "No cargo, jest or pytest project found; pass the test command as `command`"
+                            .into(),
+                    )
+                })?;
+                let (program, args) = runner.command(params.filter.as_deref());
+                let mut command = Command::new(program);
+                command.args(&args);
+                (
+                    Some(runner),
+                    command,
+                    format!("{} {}", program, args.join(" ")),
+                )
+            }
+        };
+        command
+            .current_dir(ctx.working_dir)
+            .env("CARGO_TERM_COLOR", "never")
+            .env("NO_COLOR", "1")
+            .env("FORCE_COLOR", "0")
+            .kill_on_drop(true);
+
+        let started = Instant::now();
+        let output =
+            tokio::time::timeout(Duration::from_secs(params.timeout_secs), command.output())
+                .await
+                .map_err(|_| ToolError::Timeout {
+                    command: shown.clone(),
+                    secs: params.timeout_secs,
+                })?
+                .with_context(|| format!("Failed to run {}", shown))?;
+        let elapsed = started.elapsed().as_secs_f64();
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let combined = format!("{}\n{}", stdout, stderr);
+        let report = match runner {
+            Some(TestRunner::Cargo) => parse_cargo(&combined),
+            Some(TestRunner::Jest) => parse_jest(&stdout, ctx.working_dir),
+            Some(TestRunner::Pytest) => parse_pytest(&combined),
+            None => None,
+        };
+
+        let status = if output.status.success() {
+            "passed"
+        } else {
+            "FAILED"
+        };
+        let Some(report) = report else {
+            // Unknown runner, or it failed before running any test (e.g. a
+            // compile error): show the end of its output
+            return Ok(format!(
+                "`{}` {} in {:.1}s ({}). Output:\n{}",
+                shown,
+                status,
+                elapsed,
+                output.status,
+                tail(&combined, MAX_RAW_LINES)
+            ));
+        };
+        Ok(render_report(&shown, &report, status, elapsed))
+    }
+}
+
+fn render_report(command: &str, report: &TestReport, status: &str, elapsed: f64) -> String {
+    let mut out = format!(
+        "`{}` {}: {} passed, {} failed, {} skipped ({:.1}s)\n",
+        command, status, report.passed, report.failed, report.skipped, elapsed
+    );
+    for (i, failure) in report.failures.iter().enumerate() {
+        let location = match (&failure.file, failure.line) {
+            (Some(file), Some(line)) => format!(" ({}:{})", file, line),
+            (Some(file), None) => format!(" ({})", file),
+            _ => String::new(),
+        };
+        if i < MAX_DETAILED_FAILURES {
+            out.push_str(&format!(
+                "\nFAILED {}{}\n{}\n",
+                failure.name,
+                location,
+                indent(&head(&failure.message, MAX_FAILURE_LINES))
+            ));
+        } else {
+            out.push_str(&format!("FAILED {}{}\n", failure.name, location));
+        }
+    }
+    out
+}
+
+/// Parse libtest output. None if no test binary ran.
+pub fn parse_cargo(output: &str) -> Option<TestReport> {
+    let totals =
+        Regex::new(r"test result: \w+\. (\d+) passed; (\d+) failed; (\d+) ignored").unwrap();
+    let panic_location = Regex::new(r"panicked at ([^:\s]+):(\d+):\d+").unwrap();
+
+    let mut report = TestReport::default();
+    let mut ran = false;
+    for caps in totals.captures_iter(output) {
+        ran = true;
+        report.passed += caps[1].parse::<usize>().unwrap_or(0);
+        report.failed += caps[2].parse::<usize>().unwrap_or(0);
+        report.skipped += caps[3].parse::<usize>().unwrap_or(0);
+    }
+    if !ran {
+        return None;
+    }
+
+    // Each failure's captured output sits in a "---- name stdout ----" block
+    let mut current: Option<(String, Vec<&str>)> = None;
+    let mut blocks = Vec::new();
+    for line in output.lines() {
+        if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            blocks.extend(current.take());
+            current = Some((name.to_string(), Vec::new()));
+        } else if line == "failures:" || line.starts_with("test result:") {
+            blocks.extend(current.take());
+        } else if let Some((_, lines)) = &mut current {
+            lines.push(line);
+        }
+    }
+    blocks.extend(current);
+
+    for (name, lines) in blocks {
+        let text = lines.join("\n");
+        let location = panic_location.captures(&text);
+        let message: Vec<&str> = lines
+            .iter()
+            .filter(|l| !l.starts_with("note: run with `RUST_BACKTRACE"))
+            .copied()
+            .collect();
+        report.failures.push(TestFailure {
+            name,
+            file: location.as_ref().map(|c| c[1].to_string()),
+            line: location.as_ref().and_then(|c| c[2].parse().ok()),
+            message: message.join("\n").trim().to_string(),
+        });
+    }
+    Some(report)
+}
+
+/// Parse `pytest -q --tb=short -rfE` output. None if no tests ran.
+pub fn parse_pytest(output: &str) -> Option<TestReport> {
+    let counts = Regex::new(r"(\d+) (passed|failed|skipped|errors?|xfailed|xpassed)").unwrap();
+    let summary_line = output
+        .lines()
+        .rev()
+        .find(|l| counts.is_match(l) && l.contains(" in "))?;
+
+    let mut report = TestReport::default();
+    for caps in counts.captures_iter(summary_line) {
+        let n: usize = caps[1].parse().unwrap_or(0);
+        match &caps[2] {
+            "passed" | "xpassed" => report.passed += n,
+            "failed" | "error" | "errors" => report.failed += n,
+            _ => report.skipped += n,
+        }
+    }
+
+    // Tracebacks, keyed by the "____ test_name ____" header above each
+    let header = Regex::new(r"^_{3,} (?:ERROR at \w+ of )?(.+?) _{3,}$").unwrap();
+    let mut tracebacks: Vec<(String, Vec<&str>)> = Vec::new();
+    let mut in_tracebacks = false;
+    for line in output.lines() {
+        if line.starts_with('=') {
+            in_tracebacks = line.contains("FAILURES") || line.contains("ERRORS");
+            continue;
+        }
+        if !in_tracebacks {
+            continue;
+        }
+        if let Some(caps) = header.captures(line) {
+            tracebacks.push((caps[1].to_string(), Vec::new()));
+        } else if let Some((_, lines)) = tracebacks.last_mut() {
+            lines.push(line);
+        }
+    }
+
+    let frame = Regex::new(r"^(\S+\.py):(\d+): ").unwrap();
+    for line in output.lines() {
+        let Some(rest) = line
+            .strip_prefix("FAILED ")
+            .or_else(|| line.strip_prefix("ERROR "))
+        else {
+            continue;
+        };
+        let (node_id, summary) = rest.split_once(" - ").unwrap_or((rest, ""));
+        let file = node_id.split("::").next().unwrap_or(node_id);
+        let short_name = node_id.rsplit("::").next().unwrap_or(node_id);
+        let traceback = tracebacks
+            .iter()
+            .find(|(name, _)| name == short_name || node_id.ends_with(name.as_str()));
+
+        // The deepest frame in the test's own file
+        let line_number = traceback.and_then(|(_, lines)| {
+            lines.iter().rev().find_map(|l| {
+                let caps = frame.captures(l)?;
+                (&caps[1] == file).then(|| caps[2].parse().ok()).flatten()
+            })
+        });
+        let message = match traceback {
+            Some((_, lines)) => lines.join("\n").trim().to_string(),
+            None => summary.to_string(),
+        };
+        report.failures.push(TestFailure {
+            name: node_id.to_string(),
+            file: Some(file.to_string()),
+            line: line_number,
+            message,
+        });
+    }
+    Some(report)
+}
+
+/// Parse the report `jest --json --testLocationInResults` prints on
+/// stdout. None if it isn't there.
+pub fn parse_jest(stdout: &str, project: &Path) -> Option<TestReport> {
+    let start = stdout.find('{')?;
+    let json: serde_json::Value = serde_json::from_str(stdout[start..].trim()).ok()?;
+    let count = |key: &str| json[key].as_u64().unwrap_or(0) as usize;
+    let mut report = TestReport {
+        passed: count("numPassedTests"),
+        failed: count("numFailedTests"),
+        skipped: count("numPendingTests") + count("numTodoTests"),
+        failures: Vec::new(),
+    };
+
+    for suite in json["testResults"].as_array()? {
+        let path = suite["name"].as_str().unwrap_or_default();
+        let file = Path::new(path)
+            .strip_prefix(project)
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|_| path.to_string());
+        let assertions = suite["assertionResults"].as_array();
+
+        // A suite that failed to load has no assertions, only a message
+        if suite["status"] == "failed" && assertions.is_none_or(|a| a.is_empty()) {
+            report.failed += 1;
+            report.failures.push(TestFailure {
+                name: file.clone(),
+                file: Some(file),
+                line: None,
+                message: suite["message"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .trim()
+                    .to_string(),
+            });
+            continue;
+        }
+        for assertion in assertions.into_iter().flatten() {
+            if assertion["status"] != "failed" {
+                continue;
+            }
+            let messages: Vec<&str> = assertion["failureMessages"]
+                .as_array()
+                .map(|m| m.iter().filter_map(|v| v.as_str()).collect())
+                .unwrap_or_default();
+            report.failures.push(TestFailure {
+                name: assertion["fullName"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                file: Some(file.clone()),
+                line: assertion["location"]["line"].as_u64().map(|l| l as u32),
+                message: messages.join("\n").trim().to_string(),
+            });
+        }
+    }
+    Some(report)
+}
+
+fn head(text: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut shown: Vec<String> = lines
+        .iter()
+        .take(max_lines)
+        .map(|l| truncate_str(l, 300).to_string())
+        .collect();
+    if lines.len() > max_lines {
+        shown.push(format!("... {} more lines", lines.len() - max_lines));
+    }
+    shown.join("\n")
+}
+
+fn tail(text: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = text.trim_end().lines().collect();
+    let start = lines.len().saturating_sub(max_lines);
+    lines[start..].join("\n")
+}
+
+fn indent(text: &str) -> String {
+    text.lines()
+        .map(|l| format!("    {}", l))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cargo_failures() {
+        let output = "
+running 3 tests
+test tests::adds ... ok
+test tests::subtracts ... FAILED
+test tests::slow ... ignored
+
+failures:
+
+---- tests::subtracts stdout ----
+
+thread 'tests::subtracts' panicked at src/lib.rs:12:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::subtracts
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+        let report = parse_cargo(output).unwrap();
+        assert_eq!((report.passed, report.failed, report.skipped), (1, 1, 1));
+        assert_eq!(report.failures.len(), 1);
+        let failure = &report.failures[0];
+        assert_eq!(failure.name, "tests::subtracts");
+        assert_eq!(failure.file.as_deref(), Some("src/lib.rs"));
+        assert_eq!(failure.line, Some(12));
+        assert!(failure.message.contains("left: 1"));
+        assert!(!failure.message.contains("RUST_BACKTRACE"));
+
+        // A compile error runs no tests
+        assert!(parse_cargo("error[E0425]: cannot find value `x`").is_none());
+    }
+
+    #[test]
+    fn test_parse_pytest_failures() {
+        let output = "
+.F.                                                                      [100%]
+=================================== FAILURES ===================================
+___________________________________ test_add ___________________________________
+tests/test_math.py:7: in test_add
+    assert add(1, 1) == 3
+E   assert 2 == 3
+=========================== short test summary info ============================
+FAILED tests/test_math.py::test_add - assert 2 == 3
+1 failed, 2 passed in 0.03s
+";
+        let report = parse_pytest(output).unwrap();
+        assert_eq!((report.passed, report.failed, report.skipped), (2, 1, 0));
+        let failure = &report.failures[0];
+        assert_eq!(failure.name, "tests/test_math.py::test_add");
+        assert_eq!(failure.file.as_deref(), Some("tests/test_math.py"));
+        assert_eq!(failure.line, Some(7));
+        assert!(failure.message.contains("E   assert 2 == 3"));
+    }
+
+    #[test]
+    fn test_parse_jest_report() {
+        let stdout = r#"{
+            "numPassedTests": 4, "numFailedTests": 1, "numPendingTests": 1, "numTodoTests": 0,
+            "testResults": [
+                {
+                    "name": "/app/src/sum.test.js",
+                    "status": "failed",
+                    "message": "",
+                    "assertionResults": [
+                        {"fullName": "sum adds", "status": "passed", "failureMessages": []},
+                        {"fullName": "sum handles negatives", "status": "failed",
+                         "location": {"line": 9, "column": 3},
+                         "failureMessages": ["Error: expect(received).toBe(expected)\n\nExpected: -1\nReceived: 1"]}
+                    ]
+                },
+                {
+                    "name": "/app/src/broken.test.js",
+                    "status": "failed",
+                    "message": "SyntaxError: Unexpected token (3:4)",
+                    "assertionResults": []
+                }
+            ]
+        }"#;
+        let report = parse_jest(stdout, Path::new("/app")).unwrap();
+        assert_eq!((report.passed, report.failed, report.skipped), (4, 2, 1));
+        assert_eq!(report.failures.len(), 2);
+        assert_eq!(report.failures[0].name, "sum handles negatives");
+        assert_eq!(report.failures[0].file.as_deref(), Some("src/sum.test.js"));
+        assert_eq!(report.failures[0].line, Some(9));
+        assert!(report.failures[1].message.starts_with("SyntaxError"));
+
+        let rendered = render_report("npx jest", &report, "FAILED", 1.0);
+        assert!(rendered.starts_with("`npx jest` FAILED: 4 passed, 2 failed, 1 skipped"));
+        assert!(rendered.contains("FAILED sum handles negatives (src/sum.test.js:9)\n    Error:"));
+    }
+}
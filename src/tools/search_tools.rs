@@ -0,0 +1,334 @@
+//! Lazy tool loading
+//!
+//! Only the core tools (`CORE_TOOLS`) have their schemas sent every turn.
+//! The specialized built-ins and MCP tools are listed by name in the
+//! `search_tools` description; the model searches for what it needs and the
+//! matches are sent from the next request on. Which tools are loaded is read
+//! back from the conversation: any tool that was called or returned by a
+//! search stays loaded until compaction drops those messages.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+use super::{Tool, ToolContext};
+use crate::llm::{ContentBlock, Message};
+
+/// Name of the meta-tool
+pub const SEARCH_TOOLS: &str = "search_tools";
+
+/// Matches returned when the model doesn't ask for a number
+const DEFAULT_LIMIT: usize = 5;
+
+/// Most matches returned by one search
+const MAX_LIMIT: usize = 20;
+
+/// Longest description shown in search results
+const MAX_DESCRIPTION_CHARS: usize = 160;
+
+/// A tool that can be loaded through `search_tools`
+#[derive(Debug, Clone)]
+pub struct LoadableTool {
+    pub name: String,
+    pub description: String,
+    /// Available in plan mode
+    pub plan: bool,
+    /// Available in build mode
+    pub build: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchToolsParams {
+    /// Words describing the capability needed
+    query: String,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+pub struct SearchToolsTool {
+    catalog: Vec<LoadableTool>,
+    description: String,
+}
+
+impl SearchToolsTool {
+    pub fn new(catalog: Vec<LoadableTool>) -> Self {
+        let names: Vec<&str> = catalog.iter().map(|tool| tool.name.as_str()).collect();
+        let description = format!(
+            "Loads tools that aren't available by default. Describe the capability you need \
+             (e.g. \"http request\", \"vulnerabilities\", \"github issues\") and the matching \
+             tools can be called from your next message. Loadable tools: {}.",
+            names.join(", ")
+        );
+        Self {
+            catalog,
+            description,
+        }
+    }
+
+    /// Catalog entries matching `query`, best first
+    pub fn search(&self, query: &str, limit: usize) -> Vec<&LoadableTool> {
+        let query = query.to_lowercase();
+        let terms: Vec<&str> = query
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .filter(|term| !term.is_empty())
+            .collect();
+
+        let mut matches: Vec<(usize, &LoadableTool)> = self
+            .catalog
+            .iter()
+            .filter_map(|tool| {
+                let name = tool.name.to_lowercase();
+                let text =
+                    format!("{} {}", name.replace('_', " "), tool.description).to_lowercase();
+                let score: usize = terms
+                    .iter()
+                    .map(|term| {
+                        if name == *term {
+                            10
+                        } else if name.contains(term) {
+                            3
+                        } else if text.contains(term) {
+                            1
+                        } else {
+                            0
+                        }
+                    })
+                    .sum();
+                (score > 0).then_some((score, tool))
+            })
+            .collect();
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        matches
+            .into_iter()
+            .take(limit)
+            .map(|(_, tool)| tool)
+            .collect()
+    }
+}
+
+#[async_trait]
+impl Tool for SearchToolsTool {
+    fn name(&self) -> &str {
+        SEARCH_TOOLS
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "query": {
+                    "type": "string",
+                    "description": "Words describing what you need, or a tool name"
+                },
+                "limit": {
+                    "type": "integer",
+                    "description": "Most tools to load (default: 5, max: 20)"
+                }
+            },
+            "required": ["query"]
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, _ctx: &ToolContext<'_>) -> Result<String> {
+        let params: SearchToolsParams = serde_json::from_value(params)?;
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let matches = self.search(&params.query, limit);
+        if matches.is_empty() {
+            let names: Vec<&str> = self.catalog.iter().map(|tool| tool.name.as_str()).collect();
+            return Ok(format!(
+                "No tools match '{}'. Loadable tools: {}",
+                params.query,
+                names.join(", ")
+            ));
+        }
+
+        let mut output = format!(
+            "Loaded {} tool(s); call them from your next message:\n",
+            matches.len()
+        );
+        for tool in matches {
+            let description = tool.description.lines().next().unwrap_or_default();
+            let description = if description.chars().count() > MAX_DESCRIPTION_CHARS {
+                format!(
+                    "{}...",
+                    crate::utils::truncate_str(description, MAX_DESCRIPTION_CHARS - 3)
+                )
+            } else {
+                description.to_string()
+            };
+            let mode = match (tool.plan, tool.build) {
+                (false, true) => " (BUILD mode only)",
+                (true, false) => " (PLAN mode only)",
+                _ => "",
+            };
+            output.push_str(&format!("- `{}`{}: {}\n", tool.name, mode, description));
+        }
+        Ok(output)
+    }
+}
+
+/// Tools the conversation has loaded: every tool it called, and every tool
+/// a `search_tools` call returned
+pub fn loaded_tools(messages: &[Message]) -> HashSet<String> {
+    let mut loaded = HashSet::new();
+    let mut searches = HashSet::new();
+    for block in messages.iter().flat_map(|msg| &msg.content) {
+        match block {
+            ContentBlock::ToolUse { id, name, .. } => {
+                if name == SEARCH_TOOLS {
+                    searches.insert(id.as_str());
+                }
+                loaded.insert(name.clone());
+            }
+            ContentBlock::ToolResult {
+                tool_use_id,
+                content,
+            } if searches.contains(tool_use_id.as_str()) => {
+                loaded.extend(content.lines().filter_map(|line| {
+                    let name = line.strip_prefix("- `")?;
+                    name.split_once('`').map(|(name, _)| name.to_string())
+                }));
+            }
+            _ => {}
+        }
+    }
+    loaded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ToolConfig;
+    use crate::llm::Role;
+    use crate::tools::AgentMode;
+
+    fn loadable(name: &str, description: &str) -> LoadableTool {
+        LoadableTool {
+            name: name.to_string(),
+            description: description.to_string(),
+            plan: false,
+            build: true,
+        }
+    }
+
+    fn search_tool() -> SearchToolsTool {
+        SearchToolsTool::new(vec![
+            loadable(
+                "http_request",
+                "Sends an HTTP request and returns the response",
+            ),
+            loadable(
+                "audit_dependencies",
+                "Checks dependencies for known vulnerabilities",
+            ),
+            loadable(
+                "mcp_github_create_issue",
+                "Create a new issue in a GitHub repository",
+            ),
+            loadable(
+                "mcp_github_list_issues",
+                "List issues in a GitHub repository",
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_search_ranks_name_matches_first() {
+        let tool = search_tool();
+        assert!(tool.description().contains("mcp_github_list_issues"));
+
+        let names = |query: &str| -> Vec<String> {
+            tool.search(query, 5)
+                .into_iter()
+                .map(|t| t.name.clone())
+                .collect()
+        };
+        assert_eq!(names("http_request"), ["http_request"]);
+        assert_eq!(names("vulnerabilities")[0], "audit_dependencies");
+        assert_eq!(names("create github issue")[0], "mcp_github_create_issue");
+        assert_eq!(names("github").len(), 2);
+        assert!(names("kubernetes").is_empty());
+        assert_eq!(tool.search("github", 1).len(), 1);
+    }
+
+    #[test]
+    fn test_registry_sends_core_and_loaded_tools() {
+        let schema_names = |registry: &crate::tools::ToolRegistry,
+                            loaded: Option<&HashSet<String>>| {
+            registry
+                .get_tools_schema_for_turn(AgentMode::Build, loaded)
+                .iter()
+                .map(|schema| schema["name"].as_str().unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let mut registry = crate::tools::ToolRegistry::new_without_subagents();
+        let all = schema_names(&registry, Some(&HashSet::new()));
+        assert!(all.contains(&"http_request".to_string()));
+
+        registry.enable_lazy_loading();
+        let core = schema_names(&registry, Some(&HashSet::new()));
+        assert!(core.contains(&"read_file".to_string()));
+        assert!(core.contains(&SEARCH_TOOLS.to_string()));
+        assert!(!core.contains(&"http_request".to_string()));
+        assert!(core.len() < all.len());
+
+        let loaded = HashSet::from(["http_request".to_string()]);
+        assert!(schema_names(&registry, Some(&loaded)).contains(&"http_request".to_string()));
+        assert_eq!(schema_names(&registry, None).len(), all.len() + 1);
+    }
+
+    #[tokio::test]
+    async fn test_search_results_load_the_tools() {
+        let tool = search_tool();
+        let config = ToolConfig::default();
+        let ctx = ToolContext::new(std::path::Path::new("."), &config);
+        let output = tool
+            .execute(serde_json::json!({"query": "github issues"}), &ctx)
+            .await
+            .unwrap();
+        assert!(output.contains("(BUILD mode only)"));
+
+        let messages = vec![
+            Message {
+                role: Role::Assistant,
+                content: vec![
+                    ContentBlock::ToolUse {
+                        id: "1".to_string(),
+                        name: SEARCH_TOOLS.to_string(),
+                        input: serde_json::json!({"query": "github issues"}),
+                    },
+                    ContentBlock::ToolUse {
+                        id: "2".to_string(),
+                        name: "http_request".to_string(),
+                        input: serde_json::json!({}),
+                    },
+                ],
+            },
+            Message {
+                role: Role::User,
+                content: vec![
+                    ContentBlock::ToolResult {
+                        tool_use_id: "1".to_string(),
+                        content: output,
+                    },
+                    ContentBlock::ToolResult {
+                        tool_use_id: "2".to_string(),
+                        content: "- `not_a_tool`: ignored outside search results".to_string(),
+                    },
+                ],
+            },
+        ];
+        let loaded = loaded_tools(&messages);
+        assert!(loaded.contains("mcp_github_create_issue"));
+        assert!(loaded.contains("mcp_github_list_issues"));
+        assert!(loaded.contains("http_request"));
+        assert!(!loaded.contains("audit_dependencies"));
+        assert!(!loaded.contains("not_a_tool"));
+    }
+}
@@ -1,9 +1,17 @@
-use anyhow::Result;
+//! Todo list tools backed by `.safe-coder/todos.json`
+//!
+//! The list belongs to the project rather than the process: every change is
+//! written to disk, each new request reloads it, and open items are shown
+//! to the model in the system prompt so unfinished work carries over
+//! between sessions.
+
+use anyhow::{Context, Result};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
-use super::{Tool, ToolContext};
+use super::{Tool, ToolContext, ToolError};
 
 /// Maximum number of todo items allowed (prevents infinite task lists)
 const MAX_TODO_ITEMS: usize = 20;
@@ -27,6 +35,51 @@ fn default_priority() -> u8 {
     3
 }
 
+impl TodoItem {
+    pub fn is_done(&self) -> bool {
+        self.status == "completed"
+    }
+}
+
+/// Map accepted status spellings to the stored form ("done" is "completed")
+fn normalize_status(status: &str) -> Option<&'static str> {
+    match status {
+        "pending" => Some("pending"),
+        "in_progress" => Some("in_progress"),
+        "completed" | "done" => Some("completed"),
+        _ => None,
+    }
+}
+
+/// The todo list file of one project
+pub struct TodoStore {
+    path: PathBuf,
+}
+
+impl TodoStore {
+    pub fn new(project_path: &Path) -> Self {
+        Self {
+            path: project_path.join(".safe-coder").join("todos.json"),
+        }
+    }
+
+    /// Saved todos; a missing or unreadable file is an empty list
+    pub fn load(&self) -> Vec<TodoItem> {
+        std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, todos: &[TodoItem]) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).context("Failed to create .safe-coder directory")?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(todos)?)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
 /// Global todo list storage (shared across tool instances)
 lazy_static::lazy_static! {
     static ref TODO_LIST: Arc<Mutex<Vec<TodoItem>>> = Arc::new(Mutex::new(Vec::new()));
@@ -39,10 +92,80 @@ pub fn get_todo_list() -> Vec<TodoItem> {
     TODO_LIST.lock().unwrap().clone()
 }
 
-/// Clear the todo list (called at the start of each new request)
-pub fn clear_todo_list() {
-    TODO_LIST.lock().unwrap().clear();
+/// Reload the project's todo list from disk (called at the start of each new request)
+pub fn load_todo_list(project_path: &Path) -> Vec<TodoItem> {
+    let todos = TodoStore::new(project_path).load();
+    *TODO_LIST.lock().unwrap() = todos.clone();
     reset_turns_counter();
+    todos
+}
+
+/// Save a new list for the project and make it the current one
+fn set_todo_list(project_path: &Path, todos: Vec<TodoItem>) -> Result<()> {
+    TodoStore::new(project_path).save(&todos)?;
+    *TODO_LIST.lock().unwrap() = todos;
+    reset_turns_counter();
+    Ok(())
+}
+
+/// System prompt section listing the items not done yet
+pub fn open_todos_prompt(todos: &[TodoItem]) -> Option<String> {
+    let open: Vec<String> = todos
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !t.is_done())
+        .map(|(i, t)| format!("{}. {} {}", i + 1, status_icon(&t.status), t.content))
+        .collect();
+    if open.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "\n\n## Open Todos\n\
+        These items in .safe-coder/todos.json are not done yet. Continue them if they \
+        relate to the request, and keep the list current with todoupdate.\n{}\n",
+        open.join("\n")
+    ))
+}
+
+fn status_icon(status: &str) -> &'static str {
+    match status {
+        "completed" => "[x]",
+        "in_progress" => "[>]",
+        "pending" => "[ ]",
+        _ => "[?]",
+    }
+}
+
+/// "Todo list updated: ..." summary returned by the writing tools
+fn summarize(todo_list: &[TodoItem]) -> String {
+    let pending = todo_list.iter().filter(|t| t.status == "pending").count();
+    let in_progress = todo_list
+        .iter()
+        .filter(|t| t.status == "in_progress")
+        .count();
+    let completed = todo_list.iter().filter(|t| t.is_done()).count();
+
+    // Show active task if one is in progress
+    let active_msg = match todo_list.iter().find(|t| t.status == "in_progress") {
+        Some(active) if in_progress == 1 => {
+            let form = if active.active_form.is_empty() {
+                &active.content
+            } else {
+                &active.active_form
+            };
+            format!(" Currently: {}", form)
+        }
+        _ => String::new(),
+    };
+
+    format!(
+        "Todo list updated: {} total ({} pending, {} in progress, {} completed).{}",
+        todo_list.len(),
+        pending,
+        in_progress,
+        completed,
+        active_msg
+    )
 }
 
 /// Increment turns without update counter (called by session after each LLM response)
@@ -85,9 +208,9 @@ impl Tool for TodoWriteTool {
     }
 
     fn description(&self) -> &str {
-        "Write/update the todo list for tracking tasks during this session. \
+        "Replace the project's todo list (saved in .safe-coder/todos.json). \
          Use this to plan work, track progress, and organize complex tasks. \
-         The todo list helps maintain context across multiple steps."
+         The list is kept across requests and sessions; use todoupdate to change one item."
     }
 
     fn parameters_schema(&self) -> serde_json::Value {
@@ -106,7 +229,7 @@ impl Tool for TodoWriteTool {
                             },
                             "status": {
                                 "type": "string",
-                                "enum": ["pending", "in_progress", "completed"],
+                                "enum": ["pending", "in_progress", "completed", "done"],
                                 "description": "The task status. Only ONE task can be in_progress at a time."
                             },
                             "active_form": {
@@ -128,8 +251,8 @@ impl Tool for TodoWriteTool {
         })
     }
 
-    async fn execute(&self, params: serde_json::Value, _ctx: &ToolContext<'_>) -> Result<String> {
-        let params: TodoWriteParams = serde_json::from_value(params)?;
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let mut params: TodoWriteParams = serde_json::from_value(params)?;
 
         // Constraint 1: Max 20 items
         if params.todos.len() > MAX_TODO_ITEMS {
@@ -140,6 +263,13 @@ impl Tool for TodoWriteTool {
             ));
         }
 
+        for todo in &mut params.todos {
+            let status = normalize_status(&todo.status).ok_or_else(|| {
+                ToolError::InvalidInput(format!("Unknown todo status '{}'", todo.status))
+            })?;
+            todo.status = status.to_string();
+        }
+
         // Constraint 2: Only one in_progress at a time
         let in_progress_count = params
             .todos
@@ -154,45 +284,168 @@ impl Tool for TodoWriteTool {
             ));
         }
 
-        let mut todo_list = TODO_LIST.lock().unwrap();
-        *todo_list = params.todos;
+        let summary = summarize(&params.todos);
+        set_todo_list(ctx.working_dir, params.todos)?;
+        Ok(summary)
+    }
+}
 
-        // Reset the turns counter since todos were updated
-        drop(todo_list); // Release lock before calling reset
-        reset_turns_counter();
+// ============ TodoUpdate Tool ============
 
-        let todo_list = TODO_LIST.lock().unwrap();
-        let pending = todo_list.iter().filter(|t| t.status == "pending").count();
-        let in_progress = todo_list
-            .iter()
-            .filter(|t| t.status == "in_progress")
-            .count();
-        let completed = todo_list.iter().filter(|t| t.status == "completed").count();
-
-        // Show active task if one is in progress
-        let active_msg = if in_progress == 1 {
-            if let Some(active) = todo_list.iter().find(|t| t.status == "in_progress") {
-                let form = if active.active_form.is_empty() {
-                    &active.content
-                } else {
-                    &active.active_form
-                };
-                format!(" Currently: {}", form)
-            } else {
-                String::new()
+#[derive(Debug, Deserialize)]
+struct TodoUpdateParams {
+    action: String,
+    #[serde(default)]
+    item: Option<usize>,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    active_form: Option<String>,
+    #[serde(default)]
+    priority: Option<u8>,
+}
+
+pub struct TodoUpdateTool;
+
+#[async_trait]
+impl Tool for TodoUpdateTool {
+    fn name(&self) -> &str {
+        "todoupdate"
+    }
+
+    fn description(&self) -> &str {
+        "Add, update or remove a single item of the project's todo list without rewriting it. \
+         Items are numbered as shown by todoread. Marking an item in_progress moves any other \
+         in-progress item back to pending."
+    }
+
+    fn parameters_schema(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["add", "update", "remove"]
+                },
+                "item": {
+                    "type": "integer",
+                    "description": "Item number (1-based) for update and remove"
+                },
+                "content": {
+                    "type": "string",
+                    "description": "Task description (required for add)"
+                },
+                "status": {
+                    "type": "string",
+                    "enum": ["pending", "in_progress", "completed", "done"]
+                },
+                "active_form": {
+                    "type": "string",
+                    "description": "Present tense form shown during execution"
+                },
+                "priority": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": 5
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let params: TodoUpdateParams = serde_json::from_value(params)?;
+        if !matches!(params.action.as_str(), "add" | "update" | "remove") {
+            return Err(ToolError::InvalidInput(format!(
+                "Unknown todo action '{}'",
+                params.action
+            ))
+            .into());
+        }
+        let status = params
+            .status
+            .as_deref()
+            .map(|s| {
+                normalize_status(s)
+                    .ok_or_else(|| ToolError::InvalidInput(format!("Unknown todo status '{}'", s)))
+            })
+            .transpose()?;
+
+        let mut todos = TodoStore::new(ctx.working_dir).load();
+        let index = match (params.action.as_str(), params.item) {
+            ("add", _) => None,
+            (_, Some(n)) if (1..=todos.len()).contains(&n) => Some(n - 1),
+            (_, Some(n)) => {
+                return Err(ToolError::InvalidInput(format!(
+                    "No todo item {} (the list has {})",
+                    n,
+                    todos.len()
+                ))
+                .into())
+            }
+            (_, None) => {
+                return Err(ToolError::InvalidInput(format!(
+                    "'item' is required for {}",
+                    params.action
+                ))
+                .into())
             }
-        } else {
-            String::new()
         };
 
-        Ok(format!(
-            "Todo list updated: {} total ({} pending, {} in progress, {} completed).{}",
-            todo_list.len(),
-            pending,
-            in_progress,
-            completed,
-            active_msg
-        ))
+        match (params.action.as_str(), index) {
+            ("add", _) => {
+                if todos.len() >= MAX_TODO_ITEMS {
+                    return Ok(format!(
+                        "Error: The todo list already has {} items. Remove or complete some first.",
+                        MAX_TODO_ITEMS
+                    ));
+                }
+                let content = params.content.ok_or_else(|| {
+                    ToolError::InvalidInput("'content' is required to add an item".to_string())
+                })?;
+                todos.push(TodoItem {
+                    content,
+                    status: status.unwrap_or("pending").to_string(),
+                    active_form: params.active_form.unwrap_or_default(),
+                    priority: params.priority.unwrap_or_else(default_priority),
+                });
+            }
+            ("update", Some(i)) => {
+                let todo = &mut todos[i];
+                if let Some(content) = params.content {
+                    todo.content = content;
+                }
+                if let Some(status) = status {
+                    todo.status = status.to_string();
+                }
+                if let Some(active_form) = params.active_form {
+                    todo.active_form = active_form;
+                }
+                if let Some(priority) = params.priority {
+                    todo.priority = priority;
+                }
+            }
+            ("remove", Some(i)) => {
+                todos.remove(i);
+            }
+            _ => unreachable!("action validated above"),
+        }
+
+        // Keep a single item in progress: the one just touched wins
+        if status == Some("in_progress") {
+            let current = index.unwrap_or(todos.len() - 1);
+            for (i, todo) in todos.iter_mut().enumerate() {
+                if i != current && todo.status == "in_progress" {
+                    todo.status = "pending".to_string();
+                }
+            }
+        }
+
+        let summary = summarize(&todos);
+        set_todo_list(ctx.working_dir, todos)?;
+        Ok(summary)
     }
 }
 
@@ -219,8 +472,8 @@ impl Tool for TodoReadTool {
         })
     }
 
-    async fn execute(&self, _params: serde_json::Value, _ctx: &ToolContext<'_>) -> Result<String> {
-        let todo_list = TODO_LIST.lock().unwrap();
+    async fn execute(&self, _params: serde_json::Value, ctx: &ToolContext<'_>) -> Result<String> {
+        let todo_list = TodoStore::new(ctx.working_dir).load();
 
         if todo_list.is_empty() {
             return Ok("No todos in the list.".to_string());
@@ -231,17 +484,10 @@ impl Tool for TodoReadTool {
         output.push("".to_string());
 
         for (idx, todo) in todo_list.iter().enumerate() {
-            let status_icon = match todo.status.as_str() {
-                "completed" => "[x]",
-                "in_progress" => "[>]",
-                "pending" => "[ ]",
-                _ => "[?]",
-            };
-
             output.push(format!(
                 "{}. {} {}",
                 idx + 1,
-                status_icon,
+                status_icon(&todo.status),
                 todo.content
             ));
         }
@@ -252,7 +498,7 @@ impl Tool for TodoReadTool {
             .iter()
             .filter(|t| t.status == "in_progress")
             .count();
-        let completed = todo_list.iter().filter(|t| t.status == "completed").count();
+        let completed = todo_list.iter().filter(|t| t.is_done()).count();
 
         output.push("".to_string());
         output.push(format!(
@@ -263,3 +509,47 @@ impl Tool for TodoReadTool {
         Ok(output.join("\n"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ToolConfig;
+
+    #[tokio::test]
+    async fn test_todos_persist_and_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ToolConfig::default();
+        let ctx = ToolContext::new(dir.path(), &config);
+
+        for content in ["Write parser", "Add tests"] {
+            TodoUpdateTool
+                .execute(
+                    serde_json::json!({ "action": "add", "content": content }),
+                    &ctx,
+                )
+                .await
+                .unwrap();
+        }
+        TodoUpdateTool
+            .execute(
+                serde_json::json!({ "action": "update", "item": 1, "status": "done" }),
+                &ctx,
+            )
+            .await
+            .unwrap();
+
+        let todos = TodoStore::new(dir.path()).load();
+        assert_eq!(todos.len(), 2);
+        assert_eq!(todos[0].status, "completed");
+        assert_eq!(todos[1].status, "pending");
+
+        let prompt = open_todos_prompt(&todos).unwrap();
+        assert!(prompt.contains("2. [ ] Add tests"));
+        assert!(!prompt.contains("Write parser"));
+
+        assert!(TodoUpdateTool
+            .execute(serde_json::json!({ "action": "remove", "item": 5 }), &ctx)
+            .await
+            .is_err());
+    }
+}
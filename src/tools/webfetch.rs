@@ -133,7 +133,7 @@ impl Tool for WebFetchTool {
 }
 
 /// Simple HTML to text extraction
-fn extract_text_from_html(html: &str) -> String {
+pub(crate) fn extract_text_from_html(html: &str) -> String {
     // Remove script and style tags with their content
     let re_script = regex::Regex::new(r"(?is)<script[^>]*>.*?</script>").unwrap();
     let re_style = regex::Regex::new(r"(?is)<style[^>]*>.*?</style>").unwrap();
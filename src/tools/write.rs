@@ -44,6 +44,8 @@ impl Tool for WriteTool {
         let params: WriteParams = serde_json::from_value(params)
             .context("Invalid parameters for write_file")?;
 
+        ctx.check_protected_path(self.name(), &params.file_path)?;
+
         let file_path = ctx.working_dir.join(&params.file_path);
 
         // Create parent directories if they don't exist
@@ -53,6 +55,8 @@ impl Tool for WriteTool {
 
         std::fs::write(&file_path, &params.content)
             .context("Failed to write file")?;
+        ctx.invalidate_file(&file_path);
+        ctx.record_seen(&file_path, &params.content);
 
         Ok(format!("Successfully wrote to {}", params.file_path))
     }
@@ -0,0 +1,455 @@
+//! Demo mode backend
+//!
+//! A scripted LLM client and an in-memory project stand in for the provider
+//! and the file system. The demo runs a tool loop over them and sends the same
+//! events a server session would, so `--demo` goes through the shell TUI's
+//! normal rendering: plan sidebar, thinking, tool blocks, diffs and test output.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+use crate::llm::{ContentBlock, LlmClient, LlmResponse, Message, Role, TokenUsage, ToolDefinition};
+use crate::planning::{PlanEvent, PlanStep, TaskPlan};
+use crate::server::types::ServerEvent;
+use crate::session::SessionEvent;
+
+/// Pause between steps so each one can be seen arriving
+const STEP_DELAY: Duration = Duration::from_millis(700);
+
+const CARGO_TOML: &str = r#"[package]
+name = "stats"
+version = "0.1.0"
+edition = "2021"
+"#;
+
+const LIB_RS: &str = r#"/// Average of the values, or 0 for an empty slice
+pub fn average(values: &[i32]) -> i32 {
+    let sum: i32 = values.iter().sum();
+    sum / values.len() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn averages_values() {
+        assert_eq!(average(&[2, 4, 6]), 4);
+    }
+
+    #[test]
+    fn empty_slice_is_zero() {
+        assert_eq!(average(&[]), 0);
+    }
+}
+"#;
+
+const BUGGY_BODY: &str = "    let sum: i32 = values.iter().sum();\n    sum / values.len() as i32";
+
+const FIXED_BODY: &str =
+    "    if values.is_empty() {\n        return 0;\n    }\n    let sum: i32 = values.iter().sum();\n    sum / values.len() as i32";
+
+/// Steps shown in the sidebar, and the step each scripted response belongs to
+const PLAN_STEPS: &[&str] = &[
+    "Reproduce the failing test",
+    "Guard against an empty slice",
+    "Run the tests again",
+];
+const RESPONSE_STEPS: &[usize] = &[0, 0, 1, 2, 2];
+
+/// The demo project, kept in memory so nothing on disk is touched
+pub struct DemoFs {
+    files: BTreeMap<String, String>,
+}
+
+impl DemoFs {
+    pub fn sample() -> Self {
+        let files = [
+            ("Cargo.toml", CARGO_TOML),
+            ("src/lib.rs", LIB_RS),
+            ("README.md", "# stats\n\nSmall statistics helpers.\n"),
+        ]
+        .into_iter()
+        .map(|(path, content)| (path.to_string(), content.to_string()))
+        .collect();
+        Self { files }
+    }
+
+    fn read(&self, path: &str) -> Result<&str> {
+        self.files
+            .get(path)
+            .map(String::as_str)
+            .ok_or_else(|| anyhow::anyhow!("File not found: {}", path))
+    }
+
+    /// Replace a file's content, returning what it was before
+    fn write(&mut self, path: &str, content: String) -> String {
+        self.files
+            .insert(path.to_string(), content)
+            .unwrap_or_default()
+    }
+
+    /// `cargo test` output for the project as it is now
+    fn test_output(&self) -> String {
+        let fixed = self
+            .files
+            .get("src/lib.rs")
+            .is_some_and(|lib| lib.contains("values.is_empty()"));
+        if fixed {
+            "running 2 tests\n\
+             test tests::averages_values ... ok\n\
+             test tests::empty_slice_is_zero ... ok\n\n\
+             test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out"
+                .to_string()
+        } else {
+            "running 2 tests\n\
+             test tests::averages_values ... ok\n\
+             test tests::empty_slice_is_zero ... FAILED\n\n\
+             failures:\n\n\
+             ---- tests::empty_slice_is_zero stdout ----\n\
+             thread 'tests::empty_slice_is_zero' panicked at src/lib.rs:4:5:\n\
+             attempt to divide by zero\n\n\
+             test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out"
+                .to_string()
+        }
+    }
+}
+
+/// LLM client that plays back the demo script, one response per request
+pub struct ScriptedClient {
+    script: Vec<LlmResponse>,
+    next: Mutex<usize>,
+}
+
+impl ScriptedClient {
+    pub fn new(script: Vec<LlmResponse>) -> Self {
+        Self {
+            script,
+            next: Mutex::new(0),
+        }
+    }
+
+    /// Index of the response the next request will get
+    fn position(&self) -> usize {
+        *self.next.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[async_trait]
+impl LlmClient for ScriptedClient {
+    async fn send_message_with_system(
+        &self,
+        _messages: &[Message],
+        _tools: &[ToolDefinition],
+        _system_prompt: Option<&str>,
+    ) -> Result<LlmResponse> {
+        let mut next = self.next.lock().unwrap_or_else(|e| e.into_inner());
+        let response = self.script.get(*next).cloned().unwrap_or_else(|| {
+            response(
+                vec![text(
+                    "That's the end of the demo. Run `safe-coder` without `--demo` and `/connect` to work on your own code.",
+                )],
+                0,
+            )
+        });
+        *next += 1;
+        Ok(response)
+    }
+}
+
+fn text(text: &str) -> ContentBlock {
+    ContentBlock::Text {
+        text: text.to_string(),
+    }
+}
+
+fn tool(id: &str, name: &str, input: Value) -> ContentBlock {
+    ContentBlock::ToolUse {
+        id: id.to_string(),
+        name: name.to_string(),
+        input,
+    }
+}
+
+fn response(content: Vec<ContentBlock>, output_tokens: usize) -> LlmResponse {
+    LlmResponse {
+        message: Message::assistant(content),
+        usage: Some(TokenUsage::new(1800, output_tokens)),
+    }
+}
+
+/// Fix a failing test: run the tests, read the code, edit it, run them again
+fn fix_failing_test_script() -> Vec<LlmResponse> {
+    vec![
+        response(
+            vec![
+                ContentBlock::Thinking {
+                    thinking: "The user wants the failing test fixed.\nRunning the tests first shows which one fails and why.".to_string(),
+                    signature: None,
+                },
+                text("Let me run the tests to see what's failing."),
+                tool("demo_1", "run_tests", json!({})),
+            ],
+            64,
+        ),
+        response(
+            vec![
+                text("`empty_slice_is_zero` panics with a division by zero. Let me look at `average`."),
+                tool("demo_2", "read_file", json!({ "file_path": "src/lib.rs" })),
+            ],
+            48,
+        ),
+        response(
+            vec![
+                text("`average` divides by `values.len()`, which is 0 for an empty slice. I'll return 0 early in that case, as the doc comment promises."),
+                tool(
+                    "demo_3",
+                    "edit_file",
+                    json!({
+                        "file_path": "src/lib.rs",
+                        "old_string": BUGGY_BODY,
+                        "new_string": FIXED_BODY,
+                    }),
+                ),
+            ],
+            120,
+        ),
+        response(
+            vec![
+                text("Now the tests again."),
+                tool("demo_4", "run_tests", json!({})),
+            ],
+            32,
+        ),
+        response(
+            vec![text(
+                "Fixed. `average` now returns 0 for an empty slice instead of dividing by zero, and both tests pass.\n\n\
+                 - `src/lib.rs`: early return when `values` is empty",
+            )],
+            56,
+        ),
+    ]
+}
+
+/// Runs the demo tool loop and reports it as server events
+pub struct DemoBackend {
+    client: ScriptedClient,
+    fs: DemoFs,
+    messages: Vec<Message>,
+    step_delay: Duration,
+}
+
+impl Default for DemoBackend {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemoBackend {
+    pub fn new() -> Self {
+        Self {
+            client: ScriptedClient::new(fix_failing_test_script()),
+            fs: DemoFs::sample(),
+            messages: Vec::new(),
+            step_delay: STEP_DELAY,
+        }
+    }
+
+    /// Answer one user message, sending events until the turn is over
+    pub async fn run_turn(&mut self, input: &str, tx: mpsc::UnboundedSender<ServerEvent>) {
+        let send = |event: SessionEvent| {
+            let _ = tx.send(ServerEvent::from(event));
+        };
+
+        self.messages.push(Message::user(input.to_string()));
+        let plan_id = "demo-plan".to_string();
+        let mut step = None;
+        if self.client.position() == 0 {
+            let plan = TaskPlan::new(plan_id.clone(), input.to_string())
+                .with_title("Fix the failing test".to_string())
+                .with_steps(
+                    PLAN_STEPS
+                        .iter()
+                        .enumerate()
+                        .map(|(i, s)| PlanStep::new(format!("step-{}", i + 1), s.to_string()))
+                        .collect(),
+                );
+            send(SessionEvent::Plan(PlanEvent::PlanCreated { plan }));
+        }
+
+        loop {
+            send(SessionEvent::Thinking("Thinking...".to_string()));
+            tokio::time::sleep(self.step_delay).await;
+
+            // Move the sidebar on to the step this response belongs to
+            if let Some(&next_step) = RESPONSE_STEPS.get(self.client.position()) {
+                if step != Some(next_step) {
+                    if let Some(done) = step {
+                        send(step_completed(&plan_id, done));
+                    }
+                    send(SessionEvent::Plan(PlanEvent::StepStarted {
+                        plan_id: plan_id.clone(),
+                        step_id: format!("step-{}", next_step + 1),
+                        description: PLAN_STEPS[next_step].to_string(),
+                    }));
+                    step = Some(next_step);
+                }
+            }
+
+            let response = match self.client.send_message(&self.messages, &[]).await {
+                Ok(response) => response,
+                Err(e) => {
+                    let _ = tx.send(ServerEvent::Error {
+                        message: e.to_string(),
+                    });
+                    break;
+                }
+            };
+            if let Some(usage) = &response.usage {
+                send(SessionEvent::TokenUsage {
+                    input_tokens: usage.input_tokens,
+                    output_tokens: usage.output_tokens,
+                    cache_read_tokens: None,
+                    cache_creation_tokens: None,
+                });
+            }
+
+            let has_tools = response
+                .message
+                .content
+                .iter()
+                .any(|block| matches!(block, ContentBlock::ToolUse { .. }));
+            let mut results = Vec::new();
+            for block in &response.message.content {
+                match block {
+                    ContentBlock::Thinking { thinking, .. } => {
+                        send(SessionEvent::ModelReasoning(thinking.clone()))
+                    }
+                    ContentBlock::Text { text } if has_tools => {
+                        send(SessionEvent::Reasoning(text.clone()))
+                    }
+                    ContentBlock::Text { text } => send(SessionEvent::TextChunk(text.clone())),
+                    ContentBlock::ToolUse { id, name, input } => {
+                        tokio::time::sleep(self.step_delay).await;
+                        let output = self.run_tool(name, input, &send);
+                        results.push(ContentBlock::ToolResult {
+                            tool_use_id: id.clone(),
+                            content: output,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            self.messages.push(response.message);
+
+            if results.is_empty() {
+                break;
+            }
+            self.messages.push(Message {
+                role: Role::User,
+                content: results,
+            });
+        }
+
+        if let Some(done) = step {
+            send(step_completed(&plan_id, done));
+        }
+        let _ = tx.send(ServerEvent::Completed);
+    }
+
+    /// Run a tool against the in-memory project
+    fn run_tool(&mut self, name: &str, input: &Value, send: &impl Fn(SessionEvent)) -> String {
+        let path = input
+            .get("file_path")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        send(SessionEvent::ToolStart {
+            name: name.to_string(),
+            description: if path.is_empty() {
+                "cargo test".to_string()
+            } else {
+                path.clone()
+            },
+        });
+
+        let result = match name {
+            "read_file" => self.fs.read(&path).map(str::to_string),
+            "edit_file" => {
+                let old_string = input["old_string"].as_str().unwrap_or_default();
+                let new_string = input["new_string"].as_str().unwrap_or_default();
+                self.fs.read(&path).map(str::to_string).and_then(|content| {
+                    if !content.contains(old_string) {
+                        anyhow::bail!("old_string not found in {}", path);
+                    }
+                    let updated = content.replacen(old_string, new_string, 1);
+                    let old_content = self.fs.write(&path, updated.clone());
+                    send(SessionEvent::FileDiff {
+                        path: path.clone(),
+                        old_content,
+                        new_content: updated,
+                    });
+                    Ok(format!("Edited {}", path))
+                })
+            }
+            "run_tests" => Ok(self.fs.test_output()),
+            _ => Err(anyhow::anyhow!("{} is not available in the demo", name)),
+        };
+
+        let (success, output) = match result {
+            Ok(output) => (true, output),
+            Err(e) => (false, format!("Error: {}", e)),
+        };
+        send(SessionEvent::ToolOutput {
+            name: name.to_string(),
+            output: output.clone(),
+        });
+        send(SessionEvent::ToolComplete {
+            name: name.to_string(),
+            success,
+        });
+        output
+    }
+}
+
+fn step_completed(plan_id: &str, step: usize) -> SessionEvent {
+    SessionEvent::Plan(PlanEvent::StepCompleted {
+        plan_id: plan_id.to_string(),
+        step_id: format!("step-{}", step + 1),
+        success: true,
+        output: None,
+        error: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_demo_script_fixes_the_test() {
+        let mut backend = DemoBackend::new();
+        backend.step_delay = Duration::ZERO;
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        backend.run_turn("fix the failing test", tx).await;
+
+        let mut diffs = 0;
+        let mut outputs = Vec::new();
+        while let Ok(event) = rx.try_recv() {
+            match event {
+                ServerEvent::FileDiff { .. } => diffs += 1,
+                ServerEvent::ToolOutput { output, .. } => outputs.push(output),
+                ServerEvent::Error { message } => panic!("demo failed: {}", message),
+                _ => {}
+            }
+        }
+        assert_eq!(diffs, 1);
+        assert!(outputs.first().unwrap().contains("FAILED"));
+        assert!(outputs.last().unwrap().contains("test result: ok"));
+    }
+}
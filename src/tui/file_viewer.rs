@@ -0,0 +1,172 @@
+//! File viewer for `path:line` references in AI output
+//!
+//! Enter on an empty prompt lists the existing files mentioned by the last
+//! response and its tool results. Opening one shows the file scrolled to the
+//! referenced line; files edited during the response can be switched to
+//! their diff.
+
+use similar::TextDiff;
+use std::path::{Path, PathBuf};
+
+use super::shell_app::FileDiff;
+use crate::output_filters::PathRef;
+
+/// Lines kept above the referenced line when a file is opened
+const CONTEXT_ABOVE: usize = 5;
+
+/// A file open in the viewer
+#[derive(Debug, Clone)]
+pub struct OpenFile {
+    pub path: String,
+    /// Referenced line (1-based)
+    pub line: Option<usize>,
+    pub lines: Vec<String>,
+    /// First line shown (0-based)
+    pub scroll: usize,
+    /// Unified diff of the edit made to this file, if any
+    pub diff: Option<Vec<String>>,
+    pub show_diff: bool,
+}
+
+impl OpenFile {
+    /// Lines currently shown: the file or its diff
+    pub fn shown_lines(&self) -> &[String] {
+        match (&self.diff, self.show_diff) {
+            (Some(diff), true) => diff,
+            _ => &self.lines,
+        }
+    }
+}
+
+/// File viewer state
+#[derive(Default)]
+pub struct FileViewer {
+    /// Whether the viewer is visible
+    pub visible: bool,
+    pub refs: Vec<PathRef>,
+    /// Currently selected reference
+    pub selected: usize,
+    pub open: Option<OpenFile>,
+    /// Error from the last open, shown in the footer
+    pub status: Option<String>,
+    project_path: PathBuf,
+    diffs: Vec<FileDiff>,
+}
+
+impl FileViewer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Show `refs`, opening the file directly when there is only one
+    pub fn open_refs(&mut self, refs: Vec<PathRef>, project_path: &Path, diffs: Vec<FileDiff>) {
+        self.visible = true;
+        self.refs = refs;
+        self.selected = 0;
+        self.open = None;
+        self.status = None;
+        self.project_path = project_path.to_path_buf();
+        self.diffs = diffs;
+        if self.refs.len() == 1 {
+            self.open_selected();
+        }
+    }
+
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.open = None;
+        self.refs.clear();
+        self.diffs.clear();
+    }
+
+    /// Go back from an open file to the list, or close the viewer
+    pub fn back(&mut self) {
+        if self.open.is_some() && self.refs.len() > 1 {
+            self.open = None;
+        } else {
+            self.close();
+        }
+    }
+
+    pub fn move_up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        if self.selected + 1 < self.refs.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Read the selected file and scroll to its referenced line
+    pub fn open_selected(&mut self) {
+        let Some(reference) = self.refs.get(self.selected) else {
+            return;
+        };
+        let full_path = self.project_path.join(&reference.path);
+        let content = match std::fs::read_to_string(&full_path) {
+            Ok(content) => content,
+            Err(e) => {
+                self.status = Some(format!("Failed to read {}: {}", reference.path, e));
+                return;
+            }
+        };
+        let diff = self
+            .diffs
+            .iter()
+            .rev()
+            .find(|d| same_file(&d.path, &reference.path))
+            .map(|d| {
+                TextDiff::from_lines(&d.old_content, &d.new_content)
+                    .unified_diff()
+                    .context_radius(3)
+                    .header(&reference.path, &reference.path)
+                    .to_string()
+                    .lines()
+                    .map(str::to_string)
+                    .collect()
+            });
+        let line = reference.line;
+        self.status = None;
+        self.open = Some(OpenFile {
+            path: reference.path.clone(),
+            line,
+            lines: content.lines().map(str::to_string).collect(),
+            scroll: line.map_or(0, |l| l.saturating_sub(1 + CONTEXT_ABOVE)),
+            // Files without a line number open on the change when there is one
+            show_diff: diff.is_some() && line.is_none(),
+            diff,
+        });
+    }
+
+    /// Switch between the file and its diff
+    pub fn toggle_diff(&mut self) {
+        if let Some(file) = &mut self.open {
+            if file.diff.is_some() {
+                file.show_diff = !file.show_diff;
+                file.scroll = if file.show_diff {
+                    0
+                } else {
+                    file.line.map_or(0, |l| l.saturating_sub(1 + CONTEXT_ABOVE))
+                };
+            }
+        }
+    }
+
+    /// Scroll the open file by `delta` lines
+    pub fn scroll_by(&mut self, delta: isize) {
+        if let Some(file) = &mut self.open {
+            let max = file.shown_lines().len().saturating_sub(1);
+            file.scroll = file.scroll.saturating_add_signed(delta).min(max);
+        }
+    }
+}
+
+/// Whether two paths (either may be absolute) name the same file
+fn same_file(a: &str, b: &str) -> bool {
+    let (a, b) = (
+        Path::new(a.trim_start_matches("./")),
+        Path::new(b.trim_start_matches("./")),
+    );
+    a.ends_with(b) || b.ends_with(a)
+}
@@ -0,0 +1,197 @@
+//! Images pasted into the terminal
+//!
+//! Terminals hand over a pasted image in different ways: kitty and iTerm2
+//! can send it inline with their graphics protocols, some paste a `data:`
+//! URL, and otherwise it is only on the system clipboard. Each is saved to a
+//! temp file and attached by path like any other image.
+
+use anyhow::{Context, Result};
+use base64::Engine;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// iTerm2 inline image: `ESC ] 1337 ; File=<args> : <base64> BEL`
+const ITERM2_PREFIX: &str = "\x1b]1337;File=";
+
+/// kitty graphics command: `ESC _ G <control> ; <base64> ESC \`
+const KITTY_PREFIX: &str = "\x1b_G";
+
+/// An encoded image and the extension it is saved with
+pub struct PastedImage {
+    bytes: Vec<u8>,
+    extension: &'static str,
+}
+
+impl PastedImage {
+    /// Keep images in formats providers accept; anything else is dropped
+    fn from_encoded(bytes: Vec<u8>) -> Option<Self> {
+        let extension = match image::guess_format(&bytes).ok()? {
+            image::ImageFormat::Png => "png",
+            image::ImageFormat::Jpeg => "jpg",
+            image::ImageFormat::Gif => "gif",
+            image::ImageFormat::WebP => "webp",
+            _ => return None,
+        };
+        Some(Self { bytes, extension })
+    }
+
+    fn from_rgba(width: u32, height: u32, rgba: Vec<u8>) -> Option<Self> {
+        let img = image::RgbaImage::from_raw(width, height, rgba)?;
+        let mut bytes = Vec::new();
+        img.write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+            .ok()?;
+        Some(Self {
+            bytes,
+            extension: "png",
+        })
+    }
+
+    /// Write the image to a temp file for attaching
+    pub fn save(&self) -> Result<PathBuf> {
+        let dir = std::env::temp_dir().join("safe-coder");
+        std::fs::create_dir_all(&dir).context("Failed to create temp directory")?;
+        let path = dir.join(format!(
+            "paste-{}.{}",
+            chrono::Local::now().format("%Y%m%d-%H%M%S%.3f"),
+            self.extension
+        ));
+        std::fs::write(&path, &self.bytes)
+            .with_context(|| format!("Failed to save pasted image to {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// An image sent inline in pasted text, if there is one
+pub fn decode(text: &str) -> Option<PastedImage> {
+    decode_iterm2(text)
+        .or_else(|| decode_kitty(text))
+        .or_else(|| decode_data_url(text))
+}
+
+/// The image on the system clipboard, if there is one
+pub fn from_clipboard() -> Result<Option<PastedImage>> {
+    let mut clipboard = arboard::Clipboard::new().context("Failed to access clipboard")?;
+    let Ok(image) = clipboard.get_image() else {
+        return Ok(None);
+    };
+    let pasted = PastedImage::from_rgba(
+        image.width as u32,
+        image.height as u32,
+        image.bytes.into_owned(),
+    )
+    .context("Failed to encode clipboard image as PNG")?;
+    Ok(Some(pasted))
+}
+
+fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    base64::engine::general_purpose::STANDARD.decode(data).ok()
+}
+
+fn decode_iterm2(text: &str) -> Option<PastedImage> {
+    let rest = &text[text.find(ITERM2_PREFIX)? + ITERM2_PREFIX.len()..];
+    let (_args, rest) = rest.split_once(':')?;
+    let end = rest.find(['\x07', '\x1b']).unwrap_or(rest.len());
+    PastedImage::from_encoded(decode_base64(&rest[..end])?)
+}
+
+/// Decode a kitty graphics transmission, joining chunked payloads
+///
+/// PNG (`f=100`) and raw RGB/RGBA (`f=24`/`f=32` with `s` and `v`) are
+/// supported, optionally zlib-compressed (`o=z`).
+fn decode_kitty(text: &str) -> Option<PastedImage> {
+    let mut control = Vec::new();
+    let mut payload = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(KITTY_PREFIX) {
+        let command = &rest[start + KITTY_PREFIX.len()..];
+        let end = command.find("\x1b\\")?;
+        let (keys, data) = command[..end].split_once(';').unwrap_or((&command[..end], ""));
+        // Later chunks only carry `m`; the first has the format keys
+        if control.is_empty() {
+            control = keys
+                .split(',')
+                .filter_map(|kv| kv.split_once('='))
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect();
+        }
+        payload.push_str(data);
+        rest = &command[end + 2..];
+    }
+    if payload.is_empty() {
+        return None;
+    }
+
+    let key = |name: &str| {
+        control
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+    };
+    let mut bytes = decode_base64(&payload)?;
+    if key("o") == Some("z") {
+        let mut inflated = Vec::new();
+        flate2::read::ZlibDecoder::new(bytes.as_slice())
+            .read_to_end(&mut inflated)
+            .ok()?;
+        bytes = inflated;
+    }
+
+    match key("f").unwrap_or("32") {
+        "100" => PastedImage::from_encoded(bytes),
+        format @ ("24" | "32") => {
+            let width: u32 = key("s")?.parse().ok()?;
+            let height: u32 = key("v")?.parse().ok()?;
+            let rgba = if format == "24" {
+                bytes
+                    .chunks_exact(3)
+                    .flat_map(|px| [px[0], px[1], px[2], 255])
+                    .collect()
+            } else {
+                bytes
+            };
+            PastedImage::from_rgba(width, height, rgba)
+        }
+        _ => None,
+    }
+}
+
+fn decode_data_url(text: &str) -> Option<PastedImage> {
+    let (_, data) = text.trim().strip_prefix("data:image/")?.split_once(";base64,")?;
+    PastedImage::from_encoded(decode_base64(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png() -> Vec<u8> {
+        PastedImage::from_rgba(1, 1, vec![255, 0, 0, 255]).unwrap().bytes
+    }
+
+    #[test]
+    fn test_decode_iterm2() {
+        let data = base64::engine::general_purpose::STANDARD.encode(png());
+        let text = format!("{}inline=1;size=70:{}\x07", ITERM2_PREFIX, data);
+        let image = decode(&text).unwrap();
+        assert_eq!(image.extension, "png");
+    }
+
+    #[test]
+    fn test_decode_chunked_kitty_rgb() {
+        let data = base64::engine::general_purpose::STANDARD.encode([1, 2, 3, 4, 5, 6]);
+        let (first, second) = data.split_at(4);
+        let text = format!(
+            "{KITTY_PREFIX}a=T,f=24,s=2,v=1,m=1;{first}\x1b\\{KITTY_PREFIX}m=0;{second}\x1b\\"
+        );
+        let image = decode(&text).unwrap();
+        assert_eq!(image.extension, "png");
+        let decoded = image::load_from_memory(&image.bytes).unwrap().to_rgba8();
+        assert_eq!(decoded.get_pixel(1, 0).0, [4, 5, 6, 255]);
+    }
+
+    #[test]
+    fn test_plain_text_is_not_an_image() {
+        assert!(decode("fn main() {}").is_none());
+    }
+}
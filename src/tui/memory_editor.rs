@@ -0,0 +1,282 @@
+//! Memory editor modal for `/memory edit`
+//!
+//! Lists the entries of `.safe-coder/SAFE_CODER.md` and edits, adds or
+//! deletes them one at a time. The file is watched while the editor is open
+//! so edits made outside the TUI show up straight away.
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::memory::entries::{self, MemoryEntry};
+use crate::memory::learned::memory_file;
+
+/// Section new entries go to when nothing is selected
+const DEFAULT_SECTION: &str = "Learned";
+
+/// Entry being edited (or added, when `original` is None)
+#[derive(Debug, Clone)]
+pub struct EntryEdit {
+    pub original: Option<MemoryEntry>,
+    pub section: Option<String>,
+    pub buffer: String,
+    /// Cursor position in `buffer`, in bytes
+    pub cursor: usize,
+}
+
+/// Memory editor state
+pub struct MemoryEditor {
+    /// Whether the editor is visible
+    pub visible: bool,
+    pub entries: Vec<MemoryEntry>,
+    /// Currently selected entry
+    pub selected: usize,
+    pub editing: Option<EntryEdit>,
+    /// Result of the last action, shown in the footer
+    pub status: Option<String>,
+    /// `d` was pressed once; pressing it again deletes the selected entry
+    pub confirm_delete: bool,
+    project_path: PathBuf,
+    watcher: Option<RecommendedWatcher>,
+    /// Set by the watcher when the memory file changes
+    changed: Arc<AtomicBool>,
+}
+
+impl Default for MemoryEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryEditor {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            entries: Vec::new(),
+            selected: 0,
+            editing: None,
+            status: None,
+            confirm_delete: false,
+            project_path: PathBuf::new(),
+            watcher: None,
+            changed: Arc::default(),
+        }
+    }
+
+    /// Open the editor on the project's memory file
+    pub fn open(&mut self, project_path: &Path) {
+        self.visible = true;
+        self.project_path = project_path.to_path_buf();
+        self.selected = 0;
+        self.editing = None;
+        self.status = None;
+        self.reload();
+        self.watch();
+    }
+
+    /// Close the editor and stop watching the file
+    pub fn close(&mut self) {
+        self.visible = false;
+        self.editing = None;
+        self.entries.clear();
+        self.watcher = None;
+    }
+
+    /// Re-read the file, keeping the selection where it was
+    pub fn reload(&mut self) {
+        match entries::load(&self.project_path) {
+            Ok(document) => self.entries = document.entries(),
+            Err(e) => self.status = Some(format!("{:#}", e)),
+        }
+        self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+    }
+
+    /// Reload if the file changed on disk since the last check. Returns
+    /// true when the entries were reloaded.
+    pub fn reload_if_changed(&mut self) -> bool {
+        if !self.visible || !self.changed.swap(false, Ordering::Relaxed) {
+            return false;
+        }
+        let before = self.entries.clone();
+        self.reload();
+        if self.entries != before && self.editing.is_some() {
+            self.status = Some("SAFE_CODER.md changed on disk".to_string());
+        }
+        true
+    }
+
+    /// Watch the directory rather than the file, which editors may replace
+    /// and which doesn't exist before the first entry is added
+    fn watch(&mut self) {
+        let path = memory_file(&self.project_path);
+        let Some(dir) = path.parent() else {
+            return;
+        };
+        if std::fs::create_dir_all(dir).is_err() {
+            return;
+        }
+        let changed = Arc::clone(&self.changed);
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if event.paths.iter().any(|p| p.ends_with("SAFE_CODER.md")) {
+                    changed.store(true, Ordering::Relaxed);
+                }
+            }
+        });
+        self.watcher = match watcher {
+            Ok(mut watcher) => match watcher.watch(dir, RecursiveMode::NonRecursive) {
+                Ok(()) => Some(watcher),
+                Err(e) => {
+                    tracing::debug!("Failed to watch {}: {}", dir.display(), e);
+                    None
+                }
+            },
+            Err(e) => {
+                tracing::debug!("File watching unavailable: {}", e);
+                None
+            }
+        };
+    }
+
+    pub fn selected_entry(&self) -> Option<&MemoryEntry> {
+        self.entries.get(self.selected)
+    }
+
+    pub fn move_up(&mut self) {
+        self.confirm_delete = false;
+        self.status = None;
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn move_down(&mut self) {
+        self.confirm_delete = false;
+        self.status = None;
+        if self.selected + 1 < self.entries.len() {
+            self.selected += 1;
+        }
+    }
+
+    /// Start editing the selected entry
+    pub fn start_edit(&mut self) {
+        if let Some(entry) = self.selected_entry().cloned() {
+            self.editing = Some(EntryEdit {
+                section: entry.section.clone(),
+                cursor: entry.text.len(),
+                buffer: entry.text.clone(),
+                original: Some(entry),
+            });
+            self.status = None;
+        }
+    }
+
+    /// Start a new entry in the selected entry's section
+    pub fn start_add(&mut self) {
+        let section = match self.selected_entry() {
+            Some(entry) => entry.section.clone(),
+            None => Some(DEFAULT_SECTION.to_string()),
+        };
+        self.editing = Some(EntryEdit {
+            original: None,
+            section,
+            buffer: "- ".to_string(),
+            cursor: 2,
+        });
+        self.status = None;
+    }
+
+    pub fn cancel_edit(&mut self) {
+        self.editing = None;
+    }
+
+    /// Delete the selected entry from the file, on the second press
+    pub fn delete_selected(&mut self) {
+        let Some(entry) = self.selected_entry().cloned() else {
+            return;
+        };
+        if !self.confirm_delete {
+            self.confirm_delete = true;
+            self.status = Some("Press d again to delete this entry".to_string());
+            return;
+        }
+        self.confirm_delete = false;
+        self.status = Some(
+            match entries::update_entry(&self.project_path, &entry, "") {
+                Ok(()) => "Entry deleted".to_string(),
+                Err(e) => format!("{:#}", e),
+            },
+        );
+        self.reload();
+    }
+
+    /// Write the entry being edited to the file
+    pub fn save_edit(&mut self) {
+        let Some(edit) = self.editing.take() else {
+            return;
+        };
+        let result = match &edit.original {
+            Some(original) => entries::update_entry(&self.project_path, original, &edit.buffer),
+            None if edit.buffer.trim().is_empty() || edit.buffer.trim() == "-" => Ok(()),
+            None => entries::add_entry(&self.project_path, edit.section.as_deref(), &edit.buffer),
+        };
+        match result {
+            Ok(()) => {
+                self.status = Some("Saved".to_string());
+                self.reload();
+                if edit.original.is_none() {
+                    self.select_text(edit.buffer.trim_end());
+                }
+            }
+            Err(e) => {
+                // Keep the text so it isn't lost; the list shows the file as it is now
+                self.status = Some(match edit.original {
+                    Some(_) => format!("{:#}. Enter adds your text as a new entry", e),
+                    None => format!("{:#}", e),
+                });
+                self.reload();
+                self.editing = Some(EntryEdit {
+                    original: None,
+                    ..edit
+                });
+            }
+        }
+    }
+
+    fn select_text(&mut self, text: &str) {
+        if let Some(i) = self.entries.iter().position(|e| e.text == text) {
+            self.selected = i;
+        }
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        if let Some(edit) = &mut self.editing {
+            edit.buffer.insert(edit.cursor, c);
+            edit.cursor += c.len_utf8();
+        }
+    }
+
+    pub fn backspace(&mut self) {
+        if let Some(edit) = &mut self.editing {
+            if let Some(c) = edit.buffer[..edit.cursor].chars().next_back() {
+                edit.cursor -= c.len_utf8();
+                edit.buffer.remove(edit.cursor);
+            }
+        }
+    }
+
+    pub fn cursor_left(&mut self) {
+        if let Some(edit) = &mut self.editing {
+            if let Some(c) = edit.buffer[..edit.cursor].chars().next_back() {
+                edit.cursor -= c.len_utf8();
+            }
+        }
+    }
+
+    pub fn cursor_right(&mut self) {
+        if let Some(edit) = &mut self.editing {
+            if let Some(c) = edit.buffer[edit.cursor..].chars().next() {
+                edit.cursor += c.len_utf8();
+            }
+        }
+    }
+}
@@ -12,15 +12,20 @@ use std::io;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, oneshot, Mutex};
 
 mod app;
 mod autocomplete;
 mod banner;
+mod demo;
 mod enhanced_ui;
 mod file_picker;
+mod file_viewer;
+mod image_paste;
+mod memory_editor;
 mod messages;
 mod model_picker;
+mod orchestrator_dashboard;
 mod sidebar;
 mod spinner;
 mod styled_components;
@@ -54,11 +59,14 @@ pub use app::App;
 pub use messages::{BackgroundTask, BackgroundTaskStatus, ChatMessage, MessageType, ToolExecution};
 
 // Export shell-first TUI components
-pub use shell_app::{AttachedImage, BlockOutput, BlockType, CommandBlock, ShellPrompt, ShellTuiApp, SlashCommand};
-pub use shell_runner::{run_shell_tui, ShellTuiRunner};
+pub use shell_app::{BlockOutput, BlockType, CommandBlock, ShellPrompt, ShellTuiApp, SlashCommand};
+pub use orchestrator_dashboard::run_orchestrator_dashboard;
+pub use shell_runner::{run_shell_demo, run_shell_tui, ShellTuiRunner};
 
-use crate::orchestrator::{Orchestrator, OrchestratorConfig};
+use crate::approval::UserMode;
+use crate::orchestrator::{Orchestrator, OrchestratorConfig, OrchestratorResponse, TaskReview};
 use crate::session::Session;
+use crate::tools::AgentMode;
 
 /// Message from the LLM processing task
 #[derive(Debug)]
@@ -85,40 +93,9 @@ impl TuiRunner {
         Ok(())
     }
 
-    pub async fn run_demo(&mut self) -> Result<()> {
-        // Setup terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
-
-        // Add demo messages
-        self.app.start_session("demo-session-12345".to_string());
-        self.app
-            .add_system_message("Demo mode - Type 'exit' to quit.");
-        self.app.add_user_message("Create a hello.rs file");
-        self.app
-            .add_assistant_message("I'll create a simple Hello World program in Rust for you.");
-
-        // Add some tool executions
-        let mut tool1 = ToolExecution::new("write_file".to_string(), "hello.rs".to_string());
-        tool1.complete("File created successfully".to_string());
-        self.app.add_tool_execution(tool1);
-
-        // Run the demo app
-        let result = self.run_demo_app(&mut terminal).await;
-
-        // Restore terminal
-        disable_raw_mode()?;
-        execute!(
-            terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        )?;
-        terminal.show_cursor()?;
-
-        result
+    /// Show a system message when the UI starts
+    pub fn add_system_message(&mut self, content: &str) {
+        self.app.add_system_message(content);
     }
 
     pub async fn run(&mut self, session: Session) -> Result<()> {
@@ -129,11 +106,14 @@ impl TuiRunner {
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
+        let crash_guard = crate::crash::guard_terminal();
+
         // Wrap session in Arc<Mutex> for shared access
         let session = Arc::new(Mutex::new(session));
 
         // Run the app
-        let result = self.run_app(&mut terminal, session).await;
+        let result = self.run_app(&mut terminal, session.clone()).await;
+        drop(crash_guard);
 
         // Restore terminal
         disable_raw_mode()?;
@@ -144,6 +124,11 @@ impl TuiRunner {
         )?;
         terminal.show_cursor()?;
 
+        // A clean exit ends the session so there is nothing left to recover
+        if result.is_ok() {
+            session.lock().await.stop().await?;
+        }
+
         result
     }
 
@@ -162,6 +147,11 @@ impl TuiRunner {
         // Channel for LLM updates (async responses)
         let (llm_tx, mut llm_rx) = mpsc::unbounded_channel::<LlmUpdate>();
 
+        // Task ids awaiting review, and where to send the accepted ones
+        let mut pending_review: Option<(Vec<String>, oneshot::Sender<Vec<String>>)> = None;
+        // Where to send the answer to a plan waiting for approval
+        let mut pending_plan: Option<oneshot::Sender<bool>> = None;
+
         loop {
             // Only redraw when needed
             if self.app.needs_redraw {
@@ -195,8 +185,33 @@ impl TuiRunner {
                                     break;
                                 }
 
-                                // Check if this is an orchestrate command
-                                if input.starts_with("/orchestrate ") || input.starts_with("/orch ")
+                                // Answer a plan waiting for approval
+                                if pending_plan.is_some()
+                                    && (input == "/approve" || input == "/reject")
+                                {
+                                    let approved = input == "/approve";
+                                    self.app.add_user_message(&input);
+                                    self.app.set_status(if approved {
+                                        "Spawning workers..."
+                                    } else {
+                                        "Ready"
+                                    });
+                                    let _ = pending_plan.take().unwrap().send(approved);
+                                } else if pending_review.is_some()
+                                    && (input.starts_with("/accept")
+                                        || input.starts_with("/reject"))
+                                {
+                                    let (task_ids, decision) = pending_review.take().unwrap();
+                                    let accepted = review_decision(&input, &task_ids);
+                                    self.app.add_user_message(&input);
+                                    self.app.set_status(&format!(
+                                        "Merging {} of {} tasks...",
+                                        accepted.len(),
+                                        task_ids.len()
+                                    ));
+                                    let _ = decision.send(accepted);
+                                } else if input.starts_with("/orchestrate ")
+                                    || input.starts_with("/orch ")
                                 {
                                     let task_text = input
                                         .strip_prefix("/orchestrate ")
@@ -210,11 +225,15 @@ impl TuiRunner {
                                             "Orchestrating: {}",
                                             task_text
                                         ));
-                                        self.app.set_status("Spawning workers...");
+                                        self.app.set_status("Planning...");
 
-                                        // Spawn orchestration in background
+                                        // Spawn orchestration in background; Plan mode
+                                        // waits for the plan to be approved
                                         let project_path_clone = project_path.clone();
-                                        let config_clone = orchestrator_config.clone();
+                                        let mut config_clone = orchestrator_config.clone();
+                                        if self.app.agent_mode == AgentMode::Plan {
+                                            config_clone.user_mode = UserMode::Plan;
+                                        }
                                         let task_text_owned = task_text.to_string();
                                         let orch_tx_clone = orch_tx.clone();
 
@@ -356,6 +375,46 @@ impl TuiRunner {
                             task_count, summary
                         ));
                     }
+                    OrchestrationUpdate::PlanReady { plan, decision } => {
+                        self.app.add_orchestration_message(&plan);
+                        self.app.add_orchestration_message(
+                            "Start the workers with /approve, or /reject the plan",
+                        );
+                        self.app.set_status("Waiting for plan approval");
+                        pending_plan = Some(decision);
+                    }
+                    OrchestrationUpdate::ReviewReady { reviews, decision } => {
+                        for (i, review) in reviews.iter().enumerate() {
+                            let mut message = format!(
+                                "Review {}/{}: {} (task {}, {:?})\n{}\n",
+                                i + 1,
+                                reviews.len(),
+                                review.description,
+                                review.task_id,
+                                review.worker_kind,
+                                review.diff.stat_line()
+                            );
+                            let lines: Vec<&str> = review.diff.patch.lines().collect();
+                            for line in lines.iter().take(REVIEW_DIFF_LINES) {
+                                message.push('\n');
+                                message.push_str(line);
+                            }
+                            if lines.len() > REVIEW_DIFF_LINES {
+                                message.push_str(&format!(
+                                    "\n... {} more lines in {}",
+                                    lines.len() - REVIEW_DIFF_LINES,
+                                    review.task_id
+                                ));
+                            }
+                            self.app.add_orchestration_message(&message);
+                        }
+                        self.app.add_orchestration_message(
+                            "Merge with /accept (all), /accept <task-id>... or /reject",
+                        );
+                        self.app.set_status("Waiting for review");
+                        pending_review =
+                            Some((reviews.into_iter().map(|r| r.task_id).collect(), decision));
+                    }
                     OrchestrationUpdate::AllComplete { summary } => {
                         self.app.add_orchestration_message(&format!(
                             "Orchestration complete!\n{}",
@@ -376,100 +435,13 @@ impl TuiRunner {
 
         Ok(())
     }
-
-    async fn run_demo_app<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
-        loop {
-            // Only redraw when needed
-            if self.app.needs_redraw {
-                let theme = self.app.theme_manager.get_current_theme();
-                terminal.draw(|f| draw_enhanced(f, &mut self.app, &theme))?;
-                self.app.clear_dirty();
-            }
-
-            // Handle events (16ms = ~60fps for smooth scrolling)
-            if event::poll(Duration::from_millis(16))? {
-                if let Event::Key(key) = event::read()? {
-                    match key.code {
-                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            break;
-                        }
-                        KeyCode::Char('g') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                            self.app.cycle_agent_mode();
-                        }
-                        KeyCode::Char(c) => {
-                            self.app.input_push(c);
-                        }
-                        KeyCode::Backspace => {
-                            self.app.input_pop();
-                        }
-                        KeyCode::Enter => {
-                            let input = self.app.input_submit();
-                            if !input.is_empty() {
-                                if input == "exit" || input == "quit" {
-                                    break;
-                                }
-
-                                // Add user message
-                                self.app.add_user_message(&input);
-
-                                // Simulate thinking with dynamic messages
-                                self.app.set_thinking(true);
-
-                                self.app.set_processing_message("Analyzing request");
-                                tokio::time::sleep(Duration::from_millis(600)).await;
-
-                                self.app.set_processing_message("Generating response");
-                                tokio::time::sleep(Duration::from_millis(600)).await;
-
-                                self.app.set_processing_message("Executing tools");
-                                tokio::time::sleep(Duration::from_millis(600)).await;
-
-                                self.app.set_thinking(false);
-
-                                // Add demo response
-                                self.app.add_assistant_message(
-                                    "This is demo mode. In production, I would process your request using the LLM and execute tools in isolated git workspaces."
-                                );
-
-                                // Add a demo tool execution
-                                let mut tool =
-                                    ToolExecution::new("demo_tool".to_string(), input.clone());
-                                tool.complete("Demo execution completed".to_string());
-                                self.app.add_tool_execution(tool);
-                            }
-                        }
-                        KeyCode::Up => {
-                            self.app.scroll_up();
-                        }
-                        KeyCode::Down => {
-                            self.app.scroll_down();
-                        }
-                        KeyCode::PageUp => {
-                            self.app.scroll_page_up();
-                        }
-                        KeyCode::PageDown => {
-                            self.app.scroll_page_down();
-                        }
-                        KeyCode::Tab => {
-                            self.app.cycle_focus();
-                        }
-                        KeyCode::F(1) => {
-                            self.app.cycle_theme_sync();
-                        }
-                        _ => {}
-                    }
-                }
-            }
-
-            self.app.tick();
-        }
-
-        Ok(())
-    }
 }
 
+/// Most diff lines shown inline per task while reviewing
+const REVIEW_DIFF_LINES: usize = 80;
+
 /// Updates from background orchestration tasks
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 enum OrchestrationUpdate {
     PlanCreated {
         summary: String,
@@ -491,6 +463,16 @@ enum OrchestrationUpdate {
         task_id: String,
         error: String,
     },
+    /// The plan is waiting for `/approve` or `/reject` before workers start
+    PlanReady {
+        plan: String,
+        decision: oneshot::Sender<bool>,
+    },
+    /// Successful tasks are waiting for `/accept` or `/reject` before merging
+    ReviewReady {
+        reviews: Vec<TaskReview>,
+        decision: oneshot::Sender<Vec<String>>,
+    },
     AllComplete {
         summary: String,
     },
@@ -519,21 +501,45 @@ async fn run_orchestration_background(
         }
     };
 
-    // Process the request
-    // NOTE: The orchestrator currently processes tasks synchronously, so we receive
-    // all results at once. Future improvement: refactor orchestrator to emit progress
-    // events during execution for real-time UI updates.
-    match orchestrator.process_request(&task_text).await {
-        Ok(response) => {
-            // Send plan created update
-            let _ = tx.send(OrchestrationUpdate::PlanCreated {
-                summary: response.plan.summary.clone(),
-                task_count: response.plan.tasks.len(),
+    let plan = match orchestrator.plan_request(&task_text).await {
+        Ok(plan) => plan,
+        Err(e) => {
+            let _ = tx.send(OrchestrationUpdate::Error {
+                message: e.to_string(),
+            });
+            return;
+        }
+    };
+    let _ = tx.send(OrchestrationUpdate::PlanCreated {
+        summary: plan.summary.clone(),
+        task_count: plan.tasks.len(),
+    });
+
+    if orchestrator.config.user_mode == UserMode::Plan {
+        let (decision_tx, decision_rx) = oneshot::channel();
+        let _ = tx.send(OrchestrationUpdate::PlanReady {
+            plan: orchestrator.format_orchestration_plan(&plan),
+            decision: decision_tx,
+        });
+        // A closed TUI runs nothing
+        if !decision_rx.await.unwrap_or(false) {
+            let _ = tx.send(OrchestrationUpdate::AllComplete {
+                summary: OrchestratorResponse::rejected(plan).summary,
             });
+            return;
+        }
+    }
 
+    // NOTE: Results arrive once all workers are done; the task updates below
+    // are sent afterwards rather than as each worker progresses.
+    match orchestrator.execute_plan(plan).await {
+        Ok(response) => {
             // Send task updates for each result
-            for (i, task) in response.plan.tasks.iter().enumerate() {
-                let result = &response.task_results[i];
+            for task in &response.plan.tasks {
+                let Some(result) = response.task_results.iter().find(|r| r.task_id == task.id)
+                else {
+                    continue;
+                };
                 let worker_kind = format!("{:?}", result.worker_kind);
 
                 // Send started notification
@@ -568,6 +574,22 @@ async fn run_orchestration_background(
                 }
             }
 
+            let mut response = response;
+            if !response.pending_review.is_empty() {
+                let (decision_tx, decision_rx) = oneshot::channel();
+                let _ = tx.send(OrchestrationUpdate::ReviewReady {
+                    reviews: response.pending_review.clone(),
+                    decision: decision_tx,
+                });
+                // A closed TUI merges nothing
+                let accepted = decision_rx.await.unwrap_or_default();
+                if let Err(e) = orchestrator.merge_tasks(&mut response, &accepted).await {
+                    let _ = tx.send(OrchestrationUpdate::Error {
+                        message: format!("Merge failed: {}", e),
+                    });
+                }
+            }
+
             // Send completion
             let _ = tx.send(OrchestrationUpdate::AllComplete {
                 summary: response.summary,
@@ -583,3 +605,20 @@ async fn run_orchestration_background(
     // Cleanup
     let _ = orchestrator.cleanup().await;
 }
+
+/// Task ids accepted by a `/accept [ids]` or `/reject` answer
+fn review_decision(input: &str, task_ids: &[String]) -> Vec<String> {
+    let mut words = input.split_whitespace();
+    if words.next() != Some("/accept") {
+        return Vec::new();
+    }
+    let chosen: Vec<&str> = words.collect();
+    if chosen.is_empty() || chosen == ["all"] {
+        return task_ids.to_vec();
+    }
+    task_ids
+        .iter()
+        .filter(|id| chosen.contains(&id.as_str()))
+        .cloned()
+        .collect()
+}
@@ -69,18 +69,24 @@ impl ModelPicker {
         self.filter.clear();
     }
 
-    /// Load models for the given provider
+    /// Load models for the given provider from the model registry
     pub fn load_models(&mut self, provider: LlmProvider, active_model: &str) {
         self.models.clear();
 
-        let models = get_models_for_provider(&provider);
-
-        for (name, id, desc) in models {
+        let registry = crate::llm::models::registry();
+        for choice in registry.choices(&provider) {
+            let context = registry
+                .lookup(&choice.id)
+                .map(|info| format_context_window(info.context_window));
+            let description = match (&choice.description, context) {
+                (Some(desc), Some(context)) => Some(format!("{} ({})", desc, context)),
+                (desc, context) => desc.clone().or(context),
+            };
             self.models.push(ModelEntry {
-                name: name.to_string(),
-                id: id.to_string(),
-                description: desc.map(|s| s.to_string()),
-                is_active: id == active_model,
+                name: choice.name.clone(),
+                id: choice.id.clone(),
+                description,
+                is_active: choice.id == active_model,
             });
         }
 
@@ -154,54 +160,11 @@ impl ModelPicker {
     }
 }
 
-/// Get available models for a provider
-fn get_models_for_provider(provider: &LlmProvider) -> Vec<(&'static str, &'static str, Option<&'static str>)> {
-    match provider {
-        LlmProvider::GitHubCopilot => vec![
-            ("GPT-5 Mini", "gpt-5-mini", Some("Fast GPT-5 model")),
-            ("GPT-5", "gpt-5", Some("OpenAI GPT-5")),
-            ("GPT-5.1", "gpt-5.1", Some("Enhanced GPT-5")),
-            ("GPT-5.1-Codex", "gpt-5.1-codex", Some("Code-optimized GPT-5.1")),
-            ("GPT-5.1-Codex-Mini", "gpt-5.1-codex-mini", Some("Fast code model")),
-            ("GPT-5.1-Codex-Max", "gpt-5.1-codex-max", Some("Most capable code model")),
-            ("GPT-5-Codex (Preview)", "gpt-5-codex", Some("GPT-5 Codex preview")),
-            ("Grok Code Fast 1", "grok-code-fast-1", Some("xAI Grok for code")),
-            ("Claude Sonnet 4", "claude-sonnet-4", Some("Anthropic Claude Sonnet 4")),
-            ("Claude Sonnet 4.5", "claude-sonnet-4.5", Some("Latest Claude Sonnet")),
-            ("GPT-4o", "gpt-4o", Some("GPT-4o model")),
-            ("o1", "o1", Some("OpenAI reasoning model")),
-            ("o3-mini", "o3-mini", Some("Mini reasoning model")),
-        ],
-        LlmProvider::Anthropic => vec![
-            ("Claude Sonnet 4", "claude-sonnet-4-20250514", Some("Latest Claude Sonnet")),
-            ("Claude 3.5 Sonnet", "claude-3-5-sonnet-20241022", Some("Previous generation Sonnet")),
-            ("Claude 3.5 Haiku", "claude-3-5-haiku-20241022", Some("Fast and efficient")),
-            ("Claude Opus 4", "claude-opus-4-20250514", Some("Most capable Claude")),
-        ],
-        LlmProvider::OpenAI => vec![
-            ("GPT-4o", "gpt-4o", Some("Latest GPT-4o")),
-            ("GPT-4o Mini", "gpt-4o-mini", Some("Faster, cheaper")),
-            ("GPT-4 Turbo", "gpt-4-turbo", Some("High performance")),
-            ("o1", "o1", Some("Reasoning model")),
-            ("o1-mini", "o1-mini", Some("Smaller reasoning")),
-        ],
-        LlmProvider::OpenRouter => vec![
-            ("Claude Sonnet 4", "anthropic/claude-sonnet-4", Some("Via OpenRouter")),
-            ("GPT-4o", "openai/gpt-4o", Some("Via OpenRouter")),
-            ("Llama 3.1 70B", "meta-llama/llama-3.1-70b-instruct", Some("Open source")),
-            ("Mistral Large", "mistralai/mistral-large-latest", Some("Mistral's flagship")),
-            ("DeepSeek V3", "deepseek/deepseek-chat", Some("DeepSeek's latest")),
-        ],
-        LlmProvider::Ollama => vec![
-            ("Llama 3.2", "llama3.2", Some("Latest Llama")),
-            ("CodeLlama", "codellama", Some("Code-focused")),
-            ("Mistral", "mistral", Some("Mistral 7B")),
-            ("DeepSeek Coder", "deepseek-coder", Some("Code specialist")),
-            ("Qwen 2.5 Coder", "qwen2.5-coder", Some("Alibaba's coder")),
-        ],
-        LlmProvider::OpenAIGeneric => vec![
-            ("Default", "default", Some("Server default model")),
-            ("Custom", "custom", Some("Enter custom model name")),
-        ],
+/// Context window as shown next to a model, e.g. "200k" or "1M"
+fn format_context_window(tokens: usize) -> String {
+    if tokens >= 1_000_000 {
+        format!("{}M", tokens / 1_000_000)
+    } else {
+        format!("{}k", tokens / 1_000)
     }
 }
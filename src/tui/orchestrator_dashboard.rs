@@ -0,0 +1,379 @@
+//! Live dashboard for `safe-coder orchestrate --watch`
+//!
+//! Shows one row per worker with its task, kind, state, elapsed time and
+//! last line of output, updated from the workers' event stream. The selected
+//! worker can be cancelled or restarted without touching the others.
+
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    Frame, Terminal,
+};
+use std::io;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+use crate::orchestrator::worker::CANCELLED;
+use crate::orchestrator::{
+    Orchestrator, OrchestratorResponse, WorkerControl, WorkerEvent, WorkerKind,
+};
+
+/// State of one dashboard row
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RowState {
+    Running,
+    Done,
+    Failed,
+    Cancelled,
+    Restarting,
+}
+
+impl RowState {
+    fn label(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Done => "done",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+            Self::Restarting => "restarting",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Running | Self::Restarting => Color::Yellow,
+            Self::Done => Color::Green,
+            Self::Failed => Color::Red,
+            Self::Cancelled => Color::DarkGray,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct WorkerRow {
+    task_id: String,
+    description: String,
+    kind: WorkerKind,
+    state: RowState,
+    started: Instant,
+    elapsed: Option<Duration>,
+    last_line: String,
+}
+
+impl WorkerRow {
+    fn elapsed(&self) -> Duration {
+        self.elapsed.unwrap_or_else(|| self.started.elapsed())
+    }
+}
+
+/// Rows built from worker events, in the order workers started
+#[derive(Debug, Default)]
+struct DashboardState {
+    rows: Vec<WorkerRow>,
+    selected: usize,
+}
+
+impl DashboardState {
+    fn apply(&mut self, event: WorkerEvent) {
+        match event {
+            WorkerEvent::Started {
+                task_id,
+                description,
+                worker,
+            } => {
+                let row = WorkerRow {
+                    task_id: task_id.clone(),
+                    description,
+                    kind: worker,
+                    state: RowState::Running,
+                    started: Instant::now(),
+                    elapsed: None,
+                    last_line: String::new(),
+                };
+                // A restarted task reuses its row
+                match self.rows.iter_mut().find(|r| r.task_id == task_id) {
+                    Some(existing) => *existing = row,
+                    None => self.rows.push(row),
+                }
+            }
+            WorkerEvent::OutputLine { task_id, line }
+            | WorkerEvent::ErrorLine { task_id, line } => {
+                if let Some(row) = self.row_mut(&task_id) {
+                    if !line.trim().is_empty() {
+                        row.last_line = line.trim().to_string();
+                    }
+                }
+            }
+            WorkerEvent::Completed { task_id, .. } => self.finish(&task_id, RowState::Done, None),
+            WorkerEvent::Failed { task_id, error } => {
+                if error == CANCELLED {
+                    self.finish(&task_id, RowState::Cancelled, None);
+                } else {
+                    let line = error.lines().next().unwrap_or_default().to_string();
+                    self.finish(&task_id, RowState::Failed, Some(line));
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self, task_id: &str, state: RowState, line: Option<String>) {
+        if let Some(row) = self.row_mut(task_id) {
+            // The cancelled run of a restarting task ends here; keep the row waiting
+            if row.state == RowState::Restarting {
+                return;
+            }
+            row.state = state;
+            row.elapsed = Some(row.started.elapsed());
+            if let Some(line) = line {
+                row.last_line = line;
+            }
+        }
+    }
+
+    fn row_mut(&mut self, task_id: &str) -> Option<&mut WorkerRow> {
+        self.rows.iter_mut().find(|r| r.task_id == task_id)
+    }
+
+    fn selected_row(&self) -> Option<&WorkerRow> {
+        self.rows.get(self.selected)
+    }
+
+    fn running(&self) -> impl Iterator<Item = &WorkerRow> {
+        self.rows
+            .iter()
+            .filter(|r| matches!(r.state, RowState::Running | RowState::Restarting))
+    }
+}
+
+/// Run `request` on `orchestrator` while showing the dashboard. Returns the
+/// orchestrator (for review and cleanup) along with the result.
+pub async fn run_orchestrator_dashboard(
+    mut orchestrator: Orchestrator,
+    request: String,
+) -> Result<(Orchestrator, Result<OrchestratorResponse>)> {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    orchestrator.set_event_sender(event_tx);
+    let controls = orchestrator.controls();
+
+    let run = tokio::spawn(async move {
+        let result = orchestrator.process_request(&request).await;
+        (orchestrator, result)
+    });
+    // A signal stops the run with the dashboard, killing its workers
+    let stop_run = run.abort_handle();
+    let _stop_run = crate::shutdown::register("stop orchestration", move || async move {
+        stop_run.abort();
+    });
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+    let restore = crate::shutdown::register("restore terminal", || async {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show);
+    });
+    let crash_guard = crate::crash::guard_terminal();
+
+    let started = Instant::now();
+    let mut state = DashboardState::default();
+    let mut quitting = false;
+
+    let result: Result<()> = async {
+        loop {
+            while let Ok(event) = event_rx.try_recv() {
+                state.apply(event);
+            }
+            let finished = run.is_finished();
+            if finished && quitting {
+                return Ok(());
+            }
+
+            terminal.draw(|f| draw(f, &state, started.elapsed(), finished, quitting))?;
+
+            if !event::poll(Duration::from_millis(100))? {
+                continue;
+            }
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => {
+                    state.selected = state.selected.saturating_sub(1);
+                }
+                KeyCode::Down | KeyCode::Char('j') if state.selected + 1 < state.rows.len() => {
+                    state.selected += 1;
+                }
+                KeyCode::Char('c') => {
+                    if let Some(row) = state.selected_row() {
+                        let _ = controls.send(WorkerControl::Cancel(row.task_id.clone()));
+                    }
+                }
+                KeyCode::Char('r') if !finished => {
+                    if let Some(row) = state.selected_row() {
+                        let task_id = row.task_id.clone();
+                        let _ = controls.send(WorkerControl::Restart(task_id.clone()));
+                        if let Some(row) = state.row_mut(&task_id) {
+                            row.state = RowState::Restarting;
+                            row.elapsed = None;
+                        }
+                    }
+                }
+                KeyCode::Char('q') | KeyCode::Esc | KeyCode::Enter => {
+                    // Stop whatever is still running, then leave once the run returns
+                    for row in state.running() {
+                        let _ = controls.send(WorkerControl::Cancel(row.task_id.clone()));
+                    }
+                    quitting = true;
+                }
+                _ => {}
+            }
+        }
+    }
+    .await;
+
+    drop(restore);
+    drop(crash_guard);
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result?;
+
+    Ok(run.await?)
+}
+
+fn draw(f: &mut Frame, state: &DashboardState, elapsed: Duration, finished: bool, quitting: bool) {
+    let [header, table, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Min(3),
+        Constraint::Length(1),
+    ])
+    .areas(f.area());
+
+    let count = |s: RowState| state.rows.iter().filter(|r| r.state == s).count();
+    let status = if finished {
+        "finished".to_string()
+    } else if quitting {
+        "stopping...".to_string()
+    } else {
+        format!("{} running", state.running().count())
+    };
+    f.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled(
+                "🎯 Orchestrator ",
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(
+                "{} · {} · {} done, {} failed, {} cancelled",
+                format_elapsed(elapsed),
+                status,
+                count(RowState::Done),
+                count(RowState::Failed),
+                count(RowState::Cancelled)
+            )),
+        ])),
+        header,
+    );
+
+    let rows = state.rows.iter().map(|row| {
+        Row::new(vec![
+            Cell::from(format!("{} {}", row.task_id, row.description)),
+            Cell::from(format!("{:?}", row.kind)),
+            Cell::from(row.state.label()).style(Style::default().fg(row.state.color())),
+            Cell::from(format_elapsed(row.elapsed())),
+            Cell::from(row.last_line.clone()),
+        ])
+    });
+    let widths = [
+        Constraint::Percentage(30),
+        Constraint::Length(14),
+        Constraint::Length(10),
+        Constraint::Length(8),
+        Constraint::Fill(1),
+    ];
+    let table_widget = Table::new(rows, widths)
+        .header(
+            Row::new(["Task", "Worker", "State", "Elapsed", "Last output"])
+                .style(Style::default().add_modifier(Modifier::BOLD)),
+        )
+        .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+        .block(Block::default().borders(Borders::ALL));
+    let mut table_state = TableState::default().with_selected(Some(state.selected));
+    f.render_stateful_widget(table_widget, table, &mut table_state);
+
+    let keys = if finished {
+        "↑/↓ select · q continue to review"
+    } else {
+        "↑/↓ select · c cancel · r restart · q stop all and quit"
+    };
+    f.render_widget(
+        Paragraph::new(keys).style(Style::default().fg(Color::DarkGray)),
+        footer,
+    );
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn started(task_id: &str) -> WorkerEvent {
+        WorkerEvent::Started {
+            task_id: task_id.into(),
+            description: "Do it".into(),
+            worker: WorkerKind::ClaudeCode,
+        }
+    }
+
+    #[test]
+    fn test_dashboard_state() {
+        let mut state = DashboardState::default();
+        state.apply(started("task-1"));
+        state.apply(started("task-2"));
+        state.apply(WorkerEvent::OutputLine {
+            task_id: "task-1".into(),
+            line: "Editing src/lib.rs".into(),
+        });
+        state.apply(WorkerEvent::OutputLine {
+            task_id: "task-1".into(),
+            line: "   ".into(),
+        });
+        state.apply(WorkerEvent::Failed {
+            task_id: "task-2".into(),
+            error: CANCELLED.into(),
+        });
+
+        assert_eq!(state.rows[0].last_line, "Editing src/lib.rs");
+        assert_eq!(state.rows[1].state, RowState::Cancelled);
+        assert_eq!(state.running().count(), 1);
+
+        // A restart keeps the row and ignores the cancelled run's result
+        state.row_mut("task-1").unwrap().state = RowState::Restarting;
+        state.apply(WorkerEvent::Failed {
+            task_id: "task-1".into(),
+            error: CANCELLED.into(),
+        });
+        assert_eq!(state.rows[0].state, RowState::Restarting);
+        state.apply(started("task-1"));
+        assert_eq!(state.rows.len(), 2);
+        assert_eq!(state.rows[0].state, RowState::Running);
+        assert_eq!(format_elapsed(Duration::from_secs(125)), "2:05");
+    }
+}
@@ -4,7 +4,7 @@
 //! where commands execute inline with visual blocks and AI is contextually available.
 
 use chrono::{DateTime, Local};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::fmt;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -13,11 +13,15 @@ use uuid::Uuid;
 
 use super::autocomplete::Autocomplete;
 use super::file_picker::FilePicker;
+use super::file_viewer::FileViewer;
+use super::memory_editor::MemoryEditor;
 use super::model_picker::ModelPicker;
 use super::sidebar::SidebarState;
 use super::spinner::Spinner;
 use crate::client::SafeCoderClient;
 use crate::config::Config;
+use crate::llm::Capabilities;
+use crate::session::attachments::{Attachment, AttachmentKind};
 use crate::planning::PlanEvent;
 use crate::tools::AgentMode;
 
@@ -108,6 +112,8 @@ pub struct PendingToolApproval {
     pub response_tx: tokio::sync::mpsc::UnboundedSender<bool>,
     /// Whether this is a high-risk operation
     pub high_risk: bool,
+    /// Where the tool comes from when it isn't built in (e.g. an MCP server)
+    pub origin: Option<String>,
 }
 
 /// Maximum number of commands to keep in history
@@ -213,12 +219,22 @@ impl CommandAutocomplete {
                 description: "Redo a previously undone change".to_string(),
                 usage: Some("Re-apply previously undone changes".to_string()),
             },
+            CommandSuggestion {
+                command: "/diff".to_string(),
+                description: "Review changes since the session started".to_string(),
+                usage: Some("Diff against the snapshot taken at startup".to_string()),
+            },
             
             // Memory and context
             CommandSuggestion {
                 command: "/memory".to_string(),
                 description: "Memory management".to_string(),
-                usage: Some("/memory add <text> | show | refresh".to_string()),
+                usage: Some("/memory edit | add <text> | show".to_string()),
+            },
+            CommandSuggestion {
+                command: "/prompts".to_string(),
+                description: "Show system prompt overrides".to_string(),
+                usage: Some("Files in .safe-coder/prompts/ and any errors in them".to_string()),
             },
             CommandSuggestion {
                 command: "/compact".to_string(),
@@ -247,6 +263,16 @@ impl CommandAutocomplete {
                 description: "List available models".to_string(),
                 usage: Some("Show models available for current provider".to_string()),
             },
+            CommandSuggestion {
+                command: "/set".to_string(),
+                description: "Set sampling parameters".to_string(),
+                usage: Some("/set [temperature|top_p|stop] <value|default>".to_string()),
+            },
+            CommandSuggestion {
+                command: "/attach".to_string(),
+                description: "Attach a file, directory or URL".to_string(),
+                usage: Some("/attach <path|url> - Send with the next message; /attach clear removes all".to_string()),
+            },
             CommandSuggestion {
                 command: "/provider".to_string(),
                 description: "Switch AI provider".to_string(),
@@ -302,7 +328,7 @@ impl CommandAutocomplete {
             CommandSuggestion {
                 command: "/init".to_string(),
                 description: "Initialize project context".to_string(),
-                usage: Some("Create SAFE_CODER.md project context file".to_string()),
+                usage: Some("/init [force] - generate SAFE_CODER.md from the code base".to_string()),
             },
             
             // Checkpoints
@@ -328,12 +354,31 @@ impl CommandAutocomplete {
                 description: "Skill management".to_string(),
                 usage: Some("Alias for /skill".to_string()),
             },
+
+            // MCP
+            CommandSuggestion {
+                command: "/mcp".to_string(),
+                description: "MCP server resources and prompts".to_string(),
+                usage: Some("/mcp resources | prompts | status; /mcp:<server>:<prompt> [args]".to_string()),
+            },
             
             // Unified planning
             CommandSuggestion {
                 command: "/plan".to_string(),
                 description: "Show planning status".to_string(),
-                usage: Some("/plan show | groups | history".to_string()),
+                usage: Some("/plan show | status | list | resume [id] | groups | history".to_string()),
+            },
+
+            // CI
+            CommandSuggestion {
+                command: "/ci".to_string(),
+                description: "CI status of the current branch".to_string(),
+                usage: Some("Show the latest CI run; failing job logs are sent to the AI".to_string()),
+            },
+            CommandSuggestion {
+                command: "/audit".to_string(),
+                description: "Audit dependencies for vulnerabilities".to_string(),
+                usage: Some("/audit [fix] - the AI proposes remediations, or applies them with fix".to_string()),
             },
             
             // Other utilities
@@ -347,7 +392,7 @@ impl CommandAutocomplete {
             CommandSuggestion {
                 command: "/connect".to_string(),
                 description: "Connect to AI service".to_string(),
-                usage: Some("Establish connection to the AI assistant".to_string()),
+                usage: Some("/connect [list|<session-id>] - New session, or attach to one running on the server".to_string()),
             },
             CommandSuggestion {
                 command: "/disconnect".to_string(),
@@ -424,29 +469,29 @@ impl CommandAutocomplete {
                     },
                     CommandSuggestion {
                         command: "share".to_string(),
-                        description: "Generate shareable link".to_string(),
-                        usage: Some("share <id> - Create share link".to_string()),
+                        description: "Upload an encrypted copy to share".to_string(),
+                        usage: Some("share <id> - Print share URL and key".to_string()),
                     },
                 ];
                 self.filter_subcommands(subcommands, args);
             }
             "/memory" => {
                 let subcommands = vec![
+                    CommandSuggestion {
+                        command: "edit".to_string(),
+                        description: "Edit memory entries".to_string(),
+                        usage: Some("edit - View, edit and delete SAFE_CODER.md entries".to_string()),
+                    },
                     CommandSuggestion {
                         command: "add".to_string(),
                         description: "Add to memory".to_string(),
-                        usage: Some("add <text> - Add custom instruction".to_string()),
+                        usage: Some("add <text> - Add an entry to SAFE_CODER.md".to_string()),
                     },
                     CommandSuggestion {
                         command: "show".to_string(),
                         description: "Show current memory".to_string(),
                         usage: Some("show - Display all memory".to_string()),
                     },
-                    CommandSuggestion {
-                        command: "refresh".to_string(),
-                        description: "Reload from SAFE_CODER.md".to_string(),
-                        usage: Some("refresh - Reload instructions".to_string()),
-                    },
                 ];
                 self.filter_subcommands(subcommands, args);
             }
@@ -493,14 +538,14 @@ impl CommandAutocomplete {
                         usage: Some("list - Show available skills".to_string()),
                     },
                     CommandSuggestion {
-                        command: "activate".to_string(),
+                        command: "on".to_string(),
                         description: "Activate a skill".to_string(),
-                        usage: Some("activate <name> - Enable skill".to_string()),
+                        usage: Some("on <name> - Enable skill".to_string()),
                     },
                     CommandSuggestion {
-                        command: "deactivate".to_string(),
+                        command: "off".to_string(),
                         description: "Deactivate a skill".to_string(),
-                        usage: Some("deactivate <name> - Disable skill".to_string()),
+                        usage: Some("off <name> - Disable skill and stop auto-activation".to_string()),
                     },
                     CommandSuggestion {
                         command: "info".to_string(),
@@ -510,6 +555,26 @@ impl CommandAutocomplete {
                 ];
                 self.filter_subcommands(subcommands, args);
             }
+            "/mcp" => {
+                let subcommands = vec![
+                    CommandSuggestion {
+                        command: "resources".to_string(),
+                        description: "List MCP resources".to_string(),
+                        usage: Some("resources - Attach with @mcp:<server>/<resource>".to_string()),
+                    },
+                    CommandSuggestion {
+                        command: "prompts".to_string(),
+                        description: "List MCP prompts".to_string(),
+                        usage: Some("prompts - Run with /mcp:<server>:<prompt>".to_string()),
+                    },
+                    CommandSuggestion {
+                        command: "status".to_string(),
+                        description: "Show MCP server health".to_string(),
+                        usage: Some("status - State, latency and tool count per server".to_string()),
+                    },
+                ];
+                self.filter_subcommands(subcommands, args);
+            }
             "/plan" => {
                 let subcommands = vec![
                     CommandSuggestion {
@@ -517,6 +582,21 @@ impl CommandAutocomplete {
                         description: "Show current plan status".to_string(),
                         usage: Some("show - Display plan status".to_string()),
                     },
+                    CommandSuggestion {
+                        command: "status".to_string(),
+                        description: "Show remaining steps of the saved plan".to_string(),
+                        usage: Some("status - Progress of .safe-coder/plans".to_string()),
+                    },
+                    CommandSuggestion {
+                        command: "list".to_string(),
+                        description: "List saved plans".to_string(),
+                        usage: Some("list - Plans in .safe-coder/plans".to_string()),
+                    },
+                    CommandSuggestion {
+                        command: "resume".to_string(),
+                        description: "Continue a saved plan".to_string(),
+                        usage: Some("resume [id] - Newest unfinished plan by default".to_string()),
+                    },
                     CommandSuggestion {
                         command: "groups".to_string(),
                         description: "Show step groups".to_string(),
@@ -698,6 +778,8 @@ pub enum BlockType {
     AiReasoning,
     /// AI thinking/reasoning BEFORE tool calls (the LLM's explanation of what it will do)
     AiThinking,
+    /// The model's thinking blocks (extended thinking), collapsed by default
+    AiThought,
     /// System message (welcome, errors, notifications)
     SystemMessage,
     /// Orchestration task
@@ -827,6 +909,8 @@ pub struct CommandBlock {
     pub render_version: u32,
     /// Reasoning text that led to this block (shown before tool calls)
     pub reasoning: Option<String>,
+    /// Whether long code blocks in the output are shown in full
+    pub expanded: bool,
 }
 
 impl CommandBlock {
@@ -847,6 +931,7 @@ impl CommandBlock {
             diagnostic_counts: None,
             render_version: 0,
             reasoning: None,
+            expanded: false,
         }
     }
 
@@ -975,6 +1060,8 @@ pub struct ShellTuiApp {
     pub cwd: PathBuf,
     /// Environment variables set in this shell session
     pub env_vars: HashMap<String, String>,
+    /// Aliases from the shellrc and the `alias` built-in
+    pub aliases: BTreeMap<String, String>,
     /// Exit code of last command
     pub last_exit_code: i32,
     /// Project root path
@@ -1031,6 +1118,10 @@ pub struct ShellTuiApp {
     pub file_picker: FilePicker,
     /// Model picker for /model command
     pub model_picker: ModelPicker,
+    /// Memory editor for /memory edit
+    pub memory_editor: MemoryEditor,
+    /// Viewer for file references in AI output
+    pub file_viewer: FileViewer,
     /// Command autocomplete for slash commands
     pub command_autocomplete: CommandAutocomplete,
     /// Commands modal visibility
@@ -1089,6 +1180,8 @@ pub struct ShellTuiApp {
     pub doom_loop_message: Option<String>,
     /// Sender to respond to doom loop prompt
     pub doom_loop_tx: Option<tokio::sync::mpsc::UnboundedSender<bool>>,
+    /// When the session stops waiting for an answer
+    pub doom_loop_deadline: Option<std::time::Instant>,
 
     // === Render Cache ===
     /// Cached render width (invalidate cache if width changes)
@@ -1099,10 +1192,12 @@ pub struct ShellTuiApp {
     // === Provider/Model Display ===
     /// Display name for current model (e.g., "claude-sonnet-4", "gpt-4o")
     pub model_display: String,
+    /// What the connected session's model supports; None until known
+    pub capabilities: Option<Capabilities>,
 
-    // === Attached Images ===
-    /// Images attached to the current message (base64 data, media_type)
-    pub attached_images: Vec<AttachedImage>,
+    // === Attachments ===
+    /// Files, directories, URLs and images sent with the next message
+    pub attachments: Vec<Attachment>,
 
     // === Request Queue ===
     /// Queued messages to process after current AI response completes
@@ -1113,17 +1208,6 @@ pub struct ShellTuiApp {
     pub logo_visible: bool,
 }
 
-/// An image attached to a message
-#[derive(Debug, Clone)]
-pub struct AttachedImage {
-    /// Base64-encoded image data
-    pub data: String,
-    /// MIME type (e.g., "image/png")
-    pub media_type: String,
-    /// Original size in bytes
-    pub size_bytes: usize,
-}
-
 impl ShellTuiApp {
     /// Create a new shell TUI application
     pub fn new(project_path: PathBuf, config: Config) -> Self {
@@ -1135,11 +1219,15 @@ impl ShellTuiApp {
 
         // Get model display name and context window before moving config
         let model_display = config.llm.model.clone();
-        let context_window = config.context.max_tokens;
+        let context_window = config.context.max_tokens_for(
+            crate::llm::models::capabilities_for(&config.llm.provider, &config.llm.model)
+                .max_context,
+        );
 
         let mut app = Self {
             cwd: cwd.clone(),
             env_vars: HashMap::new(),
+            aliases: BTreeMap::new(),
             last_exit_code: 0,
             project_path: project_path.clone(),
 
@@ -1168,6 +1256,8 @@ impl ShellTuiApp {
             autocomplete: Autocomplete::new(),
             file_picker: FilePicker::new(),
             model_picker: ModelPicker::new(),
+            memory_editor: MemoryEditor::new(),
+            file_viewer: FileViewer::new(),
             command_autocomplete: CommandAutocomplete::new(),
             commands_modal_visible: false,
 
@@ -1197,13 +1287,15 @@ impl ShellTuiApp {
             doom_loop_prompt_id: None,
             doom_loop_message: None,
             doom_loop_tx: None,
+            doom_loop_deadline: None,
 
             cached_render_width: 0,
             cached_total_lines: 0,
 
             model_display,
+            capabilities: None,
 
-            attached_images: Vec::new(),
+            attachments: Vec::new(),
 
             queued_messages: VecDeque::new(),
 
@@ -1230,6 +1322,10 @@ impl ShellTuiApp {
         let prompt = app.current_prompt();
         app.blocks.push(CommandBlock::system(welcome, prompt));
 
+        // Show todos left over from earlier sessions
+        let todos = crate::tools::todo::TodoStore::new(&app.project_path).load();
+        app.sidebar.update_todos(&todos);
+
         app
     }
 
@@ -1303,6 +1399,11 @@ impl ShellTuiApp {
             if has_running {
                 self.needs_redraw = true;
             }
+
+            // The session has given up waiting, so the prompt is stale
+            if self.doom_loop_time_remaining() == Some(std::time::Duration::ZERO) {
+                self.clear_doom_loop();
+            }
         }
     }
 
@@ -1834,10 +1935,17 @@ impl ShellTuiApp {
     }
 
     /// Set doom loop prompt for HTTP-based response
-    pub fn set_doom_loop_prompt_http(&mut self, prompt_id: String, message: String) {
+    pub fn set_doom_loop_prompt_http(
+        &mut self,
+        prompt_id: String,
+        message: String,
+        timeout_secs: u64,
+    ) {
         self.doom_loop_prompt_id = Some(prompt_id);
         self.doom_loop_message = Some(message);
         self.doom_loop_tx = None; // No direct channel, use HTTP
+        self.doom_loop_deadline = (timeout_secs > 0)
+            .then(|| std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs));
         self.doom_loop_visible = true;
         self.needs_redraw = true;
     }
@@ -1869,12 +1977,19 @@ impl ShellTuiApp {
         self.doom_loop_visible && self.doom_loop_prompt_id.is_some()
     }
 
+    /// Time left before the session answers the doom loop prompt itself
+    pub fn doom_loop_time_remaining(&self) -> Option<std::time::Duration> {
+        self.doom_loop_deadline
+            .map(|deadline| deadline.saturating_duration_since(std::time::Instant::now()))
+    }
+
     /// Clear doom loop state (after responding via HTTP)
     pub fn clear_doom_loop(&mut self) {
         self.doom_loop_visible = false;
         self.doom_loop_prompt_id = None;
         self.doom_loop_message = None;
         self.doom_loop_tx = None;
+        self.doom_loop_deadline = None;
         self.needs_redraw = true;
     }
 
@@ -2082,19 +2197,15 @@ impl ShellTuiApp {
 
     /// Change directory
     pub fn change_directory(&mut self, path: &str) -> Result<(), String> {
-        let new_path = if path.is_empty() || path == "~" {
-            dirs::home_dir().ok_or("Could not find home directory")?
-        } else if path.starts_with('~') {
-            let home = dirs::home_dir().ok_or("Could not find home directory")?;
-            home.join(&path[2..])
-        } else if path.starts_with('/') {
-            PathBuf::from(path)
+        let new_path = if path.is_empty() || path.starts_with('~') {
+            crate::platform::expand_home(if path.is_empty() { "~" } else { path })
+                .ok_or("Could not find home directory")?
         } else {
+            // join() keeps absolute paths as they are, on every platform
             self.cwd.join(path)
         };
 
-        let canonical = new_path
-            .canonicalize()
+        let canonical = crate::platform::canonicalize(&new_path)
             .map_err(|_| format!("cd: {}: No such directory", path))?;
 
         if !canonical.is_dir() {
@@ -2140,7 +2251,15 @@ impl ShellTuiApp {
         let cmd = input.split_whitespace().next().unwrap_or("");
         matches!(
             cmd,
-            "cd" | "pwd" | "exit" | "quit" | "clear" | "history" | "export" | "env"
+            "cd" | "pwd"
+                | "exit"
+                | "quit"
+                | "clear"
+                | "history"
+                | "export"
+                | "env"
+                | "alias"
+                | "unalias"
         )
     }
 
@@ -2200,11 +2319,22 @@ impl ShellTuiApp {
 
         let rest = input[1..].trim();
         let parts: Vec<&str> = rest.splitn(2, ' ').collect();
+
+        // MCP server prompts: /mcp:<server>:<prompt> [args] (names are case-sensitive)
+        if let Some(prompt) = parts.first().and_then(|cmd| cmd.strip_prefix("mcp:")) {
+            let (server, name) = prompt.split_once(':')?;
+            return Some(SlashCommand::McpPrompt {
+                server: server.to_string(),
+                name: name.to_string(),
+                args: parts.get(1).map(|s| s.to_string()).unwrap_or_default(),
+            });
+        }
+
         let cmd = parts.first().map(|s| s.to_lowercase()).unwrap_or_default();
         let args = parts.get(1).map(|s| s.to_string());
 
         match cmd.as_str() {
-            "connect" => Some(SlashCommand::Connect),
+            "connect" => Some(SlashCommand::Connect(args)),
             "disconnect" => Some(SlashCommand::Disconnect),
             "orchestrate" | "orch" => Some(SlashCommand::Orchestrate(args.unwrap_or_default())),
             "help" => Some(SlashCommand::Help),
@@ -2215,90 +2345,55 @@ impl ShellTuiApp {
             "models" => Some(SlashCommand::Models),
             "provider" => Some(SlashCommand::Provider(args)),
             "model" => Some(SlashCommand::Model(args)),
+            "set" => Some(SlashCommand::Set(args)),
+            "attach" => Some(SlashCommand::Attach(args)),
             "login" => Some(SlashCommand::Login(args)),
             "about" => Some(SlashCommand::About),
+            "skill" | "skills" => Some(SlashCommand::Skill(args)),
+            "mcp" => Some(SlashCommand::Mcp(args)),
+            "plan" => Some(SlashCommand::Plan(args)),
+            "ci" => Some(SlashCommand::Ci),
+            "audit" => Some(SlashCommand::Audit(args)),
+            "init" => Some(SlashCommand::Init(args)),
+            "memory" => Some(SlashCommand::Memory(args)),
+            "prompts" => Some(SlashCommand::Prompts),
+            "diff" => Some(SlashCommand::Diff),
             _ => None,
         }
     }
 
-    // === Image Attachment Methods ===
-
-    /// Paste image from clipboard
-    /// Returns true if an image was pasted, false if clipboard has no image
-    pub fn paste_image_from_clipboard(&mut self) -> Result<bool, String> {
-        use arboard::Clipboard;
-        use base64::Engine;
-
-        let mut clipboard = Clipboard::new().map_err(|e| format!("Failed to access clipboard: {}", e))?;
-
-        // Try to get image data from clipboard
-        if let Ok(image_data) = clipboard.get_image() {
-            // Convert RGBA bytes to PNG
-            let width = image_data.width as u32;
-            let height = image_data.height as u32;
-
-            // Create PNG from raw image data
-            let img = image::RgbaImage::from_raw(width, height, image_data.bytes.into_owned())
-                .ok_or("Failed to create image from clipboard data")?;
-
-            // Encode as PNG
-            let mut png_data = Vec::new();
-            img.write_to(&mut std::io::Cursor::new(&mut png_data), image::ImageFormat::Png)
-                .map_err(|e| format!("Failed to encode image as PNG: {}", e))?;
-
-            let size_bytes = png_data.len();
-
-            // Base64 encode
-            let base64_data = base64::engine::general_purpose::STANDARD.encode(&png_data);
-
-            // Add to attached images
-            self.attached_images.push(AttachedImage {
-                data: base64_data,
-                media_type: "image/png".to_string(),
-                size_bytes,
-            });
-
-            self.needs_redraw = true;
-            return Ok(true);
-        }
-
-        Ok(false)
+    /// Whether the model accepts images; assumed until capabilities are known
+    pub fn supports_images(&self) -> bool {
+        self.capabilities.is_none_or(|c| c.vision)
     }
 
-    /// Remove an attached image by index
-    pub fn remove_attached_image(&mut self, index: usize) {
-        if index < self.attached_images.len() {
-            self.attached_images.remove(index);
-            self.needs_redraw = true;
-        }
-    }
 
-    /// Clear all attached images
-    pub fn clear_attached_images(&mut self) {
-        self.attached_images.clear();
+    // === Attachment Methods ===
+
+    /// Add a file, directory, URL or image to send with the next message
+    pub fn add_attachment(&mut self, attachment: Attachment) {
+        self.attachments.retain(|a| a.source != attachment.source);
+        self.attachments.push(attachment);
         self.needs_redraw = true;
     }
 
-    /// Check if there are attached images
-    pub fn has_attached_images(&self) -> bool {
-        !self.attached_images.is_empty()
+    /// Check if there are attachments
+    pub fn has_attachments(&self) -> bool {
+        !self.attachments.is_empty()
     }
 
-    /// Take attached images (moves them out, returning ownership)
-    pub fn take_attached_images(&mut self) -> Vec<AttachedImage> {
-        std::mem::take(&mut self.attached_images)
+    /// Drop image attachments, returning how many were removed
+    pub fn remove_image_attachments(&mut self) -> usize {
+        let before = self.attachments.len();
+        self.attachments.retain(|a| a.kind != AttachmentKind::Image);
+        self.needs_redraw = true;
+        before - self.attachments.len()
     }
 
-    /// Get human-readable size string for total attached images
-    pub fn attached_images_size_display(&self) -> String {
-        let total_bytes: usize = self.attached_images.iter().map(|i| i.size_bytes).sum();
-        if total_bytes < 1024 {
-            format!("{} B", total_bytes)
-        } else if total_bytes < 1024 * 1024 {
-            format!("{:.1} KB", total_bytes as f64 / 1024.0)
-        } else {
-            format!("{:.1} MB", total_bytes as f64 / (1024.0 * 1024.0))
-        }
+    /// Take attachments (moves them out, returning ownership)
+    pub fn take_attachments(&mut self) -> Vec<Attachment> {
+        self.needs_redraw = true;
+        std::mem::take(&mut self.attachments)
     }
 
     /// Extract file context patterns from input (words starting with @)
@@ -2308,7 +2403,10 @@ impl ShellTuiApp {
         let mut file_patterns = Vec::new();
 
         for word in input.split_whitespace() {
-            if word.starts_with('@') && word.len() > 1 {
+            // MCP resource mentions are resolved by the session, so keep them in the query
+            if word.starts_with("@mcp:") {
+                query_parts.push(word);
+            } else if word.starts_with('@') && word.len() > 1 {
                 // This is a file pattern
                 file_patterns.push(word[1..].to_string());
             } else {
@@ -2323,8 +2421,9 @@ impl ShellTuiApp {
 /// Slash commands (e.g., /connect, /help)
 #[derive(Debug, Clone)]
 pub enum SlashCommand {
-    /// Connect to AI service
-    Connect,
+    /// Connect to AI service, or attach to a running server session
+    /// (`/connect [list|<session-id>]`)
+    Connect(Option<String>),
     /// Disconnect from AI service
     Disconnect,
     /// Run orchestration task
@@ -2345,8 +2444,36 @@ pub enum SlashCommand {
     Provider(Option<String>),
     /// Switch or show current model
     Model(Option<String>),
+    /// Show or set a sampling parameter (`/set temperature 0.2`)
+    Set(Option<String>),
+    /// Attach a file, directory or URL to the next message (`/attach <path|url>|clear`)
+    Attach(Option<String>),
     /// Login to a provider
     Login(Option<String>),
     /// Show about/logo popup
     About,
+    /// List or toggle skills (`/skill list|on|off <name>`)
+    Skill(Option<String>),
+    /// List MCP resources or prompts (`/mcp resources|prompts`)
+    Mcp(Option<String>),
+    /// Run an MCP server prompt (`/mcp:<server>:<prompt> [args]`)
+    McpPrompt {
+        server: String,
+        name: String,
+        args: String,
+    },
+    /// Saved plans (`/plan status|list|resume [id]`)
+    Plan(Option<String>),
+    /// CI status of the current branch; failures are sent to the AI
+    Ci,
+    /// Dependency vulnerability audit (`/audit [fix]`)
+    Audit(Option<String>),
+    /// Generate SAFE_CODER.md from the code base (`/init [force]`)
+    Init(Option<String>),
+    /// Project memory (`/memory edit|add <text>|show`)
+    Memory(Option<String>),
+    /// System prompt overrides in `.safe-coder/prompts/`
+    Prompts,
+    /// Changes made since the session started
+    Diff,
 }
@@ -6,7 +6,8 @@
 use anyhow::{Context, Result};
 use crossterm::{
     event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseEventKind,
+        self, DisableBracketedPaste, DisableFocusChange, DisableMouseCapture, EnableBracketedPaste,
+        EnableFocusChange, EnableMouseCapture, Event, KeyCode, KeyModifiers, MouseEventKind,
     },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
@@ -22,16 +23,35 @@ use tokio::io::{AsyncBufReadExt, BufReader as TokioBufReader};
 use tokio::process::Command as TokioCommand;
 use tokio::sync::{mpsc, Mutex};
 
-use super::shell_app::{BlockOutput, BlockType, CommandBlock, FileDiff, ShellTuiApp, SlashCommand};
+use super::shell_app::{
+    BlockOutput, BlockType, CommandBlock, FileDiff, PendingToolApproval, ShellTuiApp, SlashCommand,
+};
+use super::demo::DemoBackend;
+use super::file_picker::FilePicker;
+use super::image_paste;
 use super::shell_ui;
-use crate::client::{SafeCoderClient, ServerManager, DEFAULT_PORT};
+use crate::client::{AttachmentInput, SafeCoderClient, ServerManager, DEFAULT_PORT};
 use crate::config::Config;
-use crate::llm::create_client;
+use crate::git::{diff::format_stat, GitManager};
+use crate::custom_commands::{extract_shell_snippets, CommandContext, CustomCommandManager};
+use crate::llm::{create_client, Capabilities, LlmClient};
+use crate::session::attachments::{self, AttachmentKind};
 use crate::lsp::{LspManager, default_lsp_configs};
+use crate::permissions::CommandAnalysis;
+use crate::shell::assist::{self, Explanation, Suggestion};
+use crate::shell::rc::{self, ShellRc};
+use crate::notifications::webhook::{WebhookEvent, WebhookNotifier};
+use crate::notifications::{Notification, Notifier};
 use crate::auth::run_device_flow;
 use crate::orchestrator::TaskPlan;
-use crate::planning::PlanEvent;
-use crate::server::types::ServerEvent;
+use crate::output_filters::{find_path_refs, OutputPipeline, PathRef};
+use crate::prompts::overrides::PromptOverrides;
+use crate::tools::error::humanize_tool_output;
+use crate::planning::{PlanEvent, PlanStore};
+use crate::server::types::{
+    McpPromptDto, McpResourceDto, McpServerStatusDto, ServerEvent, SkillDto,
+};
+use crate::session::MessageOverrides;
 use crate::unified_planning::{ExecutionMode, UnifiedPlanner};
 
 // Use shared truncate_str from utils
@@ -51,6 +71,15 @@ enum CommandUpdate {
         stderr: String,
         exit_code: i32,
     },
+    /// The LLM translated a request into a command
+    Suggested {
+        block_id: String,
+        result: std::result::Result<Suggestion, String>,
+    },
+    /// The LLM explained why a command failed
+    Explained {
+        result: std::result::Result<Explanation, String>,
+    },
 }
 
 /// Message types for AI updates
@@ -60,6 +89,8 @@ enum AiUpdate {
     Thinking { block_id: String, message: String },
     /// AI reasoning text (explanation before/between tool calls)
     Reasoning { block_id: String, text: String },
+    /// The model's thinking blocks
+    ModelReasoning { block_id: String, text: String },
     /// AI response text chunk
     TextChunk { block_id: String, text: String },
     /// AI final response
@@ -126,12 +157,52 @@ enum AiUpdate {
     DoomLoopPrompt {
         prompt_id: String,
         message: String,
+        timeout_secs: u64,
+    },
+    /// Tool approval prompt (prompt_id for API response)
+    ToolApprovalPrompt {
+        prompt_id: String,
+        tool: String,
+        description: String,
+        high_risk: bool,
+        origin: Option<String>,
+        always_ask: bool,
     },
     /// Todo list update
     TodoList {
         block_id: String,
         todos: Vec<crate::tools::todo::TodoItem>,
     },
+    /// A skill was activated by a file the AI touched
+    SkillActivated {
+        block_id: String,
+        name: String,
+        file: String,
+    },
+    /// The provider rate limited a request and it will be retried
+    RateLimited {
+        block_id: String,
+        provider: String,
+        retry_in_secs: u64,
+        attempt: u32,
+        max_retries: u32,
+    },
+    /// A background monitor reported on the command it watches
+    MonitorNotification {
+        block_id: String,
+        command: String,
+        summary: String,
+        needs_attention: bool,
+    },
+    /// An MCP server dropped out, was restarted, or could not be restarted
+    McpServerHealth {
+        block_id: String,
+        server: String,
+        status: String,
+        message: String,
+    },
+    /// The session's model changed and supports a different feature set
+    Capabilities { capabilities: Capabilities },
 }
 
 /// Message types for orchestration updates
@@ -174,12 +245,37 @@ enum OrchestrationUpdate {
     Error { block_id: String, message: String },
 }
 
+/// Per-query settings for AI queries started by custom commands
+#[derive(Debug, Clone, Default)]
+struct QueryOverrides {
+    /// Text shown in the query block instead of the full prompt
+    display: Option<String>,
+    /// Allowed tools, model and shell snippets sent with the query
+    message: MessageOverrides,
+}
+
 /// Shell TUI runner
 pub struct ShellTuiRunner {
     app: ShellTuiApp,
     config: Config,
     lsp_manager: Option<LspManager>,
     server_manager: ServerManager,
+    notifier: Notifier,
+    webhooks: Option<WebhookNotifier>,
+    /// (description, worker) of orchestration tasks by task id, for failure webhooks
+    orchestration_tasks: std::collections::HashMap<String, (String, String)>,
+    /// Shellrc commands still to run when the event loop starts
+    startup_commands: Vec<String>,
+    /// Filters applied to AI responses before they are shown
+    output_pipeline: OutputPipeline,
+    /// Scripted backend answering AI queries in demo mode
+    demo: Option<Arc<Mutex<DemoBackend>>>,
+    /// Git tree of the project when the shell started, for `/diff`
+    start_snapshot: Option<String>,
+    /// Explain failed commands with the LLM (`ai-explain`)
+    explain_errors: bool,
+    /// Client for `# intent`, `ai-suggest` and `ai-explain`, created on first use
+    assistant: Option<Arc<dyn LlmClient>>,
 }
 
 impl ShellTuiRunner {
@@ -200,14 +296,66 @@ impl ShellTuiRunner {
             }
         }
 
+        // Aliases and exports from the shellrc apply before anything runs
+        let rc = ShellRc::load().unwrap_or_else(|e| ShellRc {
+            warnings: vec![e.to_string()],
+            ..Default::default()
+        });
+        for warning in rc.warnings {
+            let prompt = app.current_prompt();
+            app.add_block(CommandBlock::system(warning, prompt));
+        }
+        for (key, value) in rc.exports {
+            std::env::set_var(&key, &value);
+            app.env_vars.insert(key, value);
+        }
+        app.aliases = rc.aliases;
+
         Self {
             app,
+            startup_commands: rc.startup,
+            output_pipeline: OutputPipeline::from_config(&config.output, &project_path),
+            notifier: Notifier::new(config.notifications.clone()),
+            webhooks: WebhookNotifier::from_config(&config.notifications),
+            orchestration_tasks: std::collections::HashMap::new(),
             config,
             lsp_manager: None,
             server_manager: ServerManager::new(DEFAULT_PORT),
+            demo: None,
+            start_snapshot: None,
+            explain_errors: false,
+            assistant: None,
         }
     }
 
+    /// Answer AI queries from the demo script instead of a server
+    pub fn with_demo(mut self) -> Self {
+        self.demo = Some(Arc::new(Mutex::new(DemoBackend::new())));
+        self.app.set_ai_connected(true);
+        let prompt = self.app.current_prompt();
+        self.app.add_block(CommandBlock::system(
+            "Demo mode: no API key needed and no files are changed. Ask anything (e.g. \"fix the failing test\") to watch a scripted session.".to_string(),
+            prompt,
+        ));
+        self
+    }
+
+    /// Notify that a command or AI block finished, if it ran long enough
+    fn notify_block_finished(&mut self, block_id: &str, success: bool) {
+        let Some(block) = self.app.get_block_mut(block_id) else {
+            return;
+        };
+        let elapsed = chrono::Local::now()
+            .signed_duration_since(block.timestamp)
+            .to_std()
+            .unwrap_or_default();
+        self.notifier.notify(Notification::TaskFinished {
+            title: block.input.clone(),
+            elapsed,
+            success,
+        });
+    }
+
     /// Initialize LSP servers (runs in background, non-blocking)
     fn spawn_lsp_init(&self) -> tokio::task::JoinHandle<Option<LspManager>> {
         if !self.config.lsp.enabled {
@@ -243,25 +391,62 @@ impl ShellTuiRunner {
         // Spawn LSP initialization in background (non-blocking)
         let lsp_handle = self.spawn_lsp_init();
 
+        // Outside git repositories /diff has nothing to compare against
+        self.start_snapshot = GitManager::new(self.app.project_path.clone())
+            .snapshot_tree()
+            .await
+            .ok();
+
         // Setup terminal
         enable_raw_mode()?;
         let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        execute!(
+            stdout,
+            EnterAlternateScreen,
+            EnableMouseCapture,
+            EnableFocusChange,
+            EnableBracketedPaste
+        )?;
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
+        // SIGTERM skips the restore below, so leave the terminal usable then too
+        let restore = crate::shutdown::register("restore terminal", || async {
+            let _ = disable_raw_mode();
+            let _ = execute!(
+                io::stdout(),
+                LeaveAlternateScreen,
+                DisableMouseCapture,
+                DisableFocusChange,
+                DisableBracketedPaste,
+                crossterm::cursor::Show
+            );
+        });
+
+        let crash_guard = crate::crash::guard_terminal();
 
         // Run the event loop, passing LSP handle for background completion
         let result = self.run_event_loop(&mut terminal, lsp_handle).await;
+        drop(restore);
+        drop(crash_guard);
 
         // Restore terminal
         disable_raw_mode()?;
         execute!(
             terminal.backend_mut(),
             LeaveAlternateScreen,
-            DisableMouseCapture
+            DisableMouseCapture,
+            DisableFocusChange,
+            DisableBracketedPaste
         )?;
         terminal.show_cursor()?;
 
+        if self.server_manager.release_if_shared().await {
+            eprintln!(
+                "Leaving the server on port {} running for other attached clients.",
+                self.server_manager.port()
+            );
+        }
+
         result
     }
 
@@ -283,6 +468,16 @@ impl ShellTuiRunner {
         let (ai_tx, mut ai_rx) = mpsc::unbounded_channel::<AiUpdate>();
         let (orch_tx, mut orch_rx) = mpsc::unbounded_channel::<OrchestrationUpdate>();
 
+        // Startup commands from the shellrc run as shell commands, never as AI queries
+        for command in std::mem::take(&mut self.startup_commands) {
+            let command = rc::expand_alias(&self.app.aliases, &command).unwrap_or(command);
+            if ShellTuiApp::is_builtin_command(&command) {
+                self.execute_builtin(&command)?;
+            } else {
+                self.execute_shell_command(&command, cmd_tx.clone()).await?;
+            }
+        }
+
         // Track whether LSP initialization is complete
         let mut lsp_init_complete = false;
 
@@ -370,6 +565,9 @@ impl ShellTuiRunner {
                         }
                         _ => {}
                     },
+                    Event::Paste(text) => self.handle_paste(&text).await,
+                    Event::FocusGained => self.notifier.set_focused(true),
+                    Event::FocusLost => self.notifier.set_focused(false),
                     _ => {}
                 }
             }
@@ -388,6 +586,10 @@ impl ShellTuiRunner {
                             block.complete_streaming(exit_code);
                             self.app.last_exit_code = exit_code;
                         }
+                        self.notify_block_finished(&block_id, exit_code == 0);
+                        if exit_code != 0 && self.explain_errors {
+                            self.explain_failure(&block_id, exit_code, cmd_tx.clone()).await;
+                        }
                         self.app.mark_dirty();
                     }
                     CommandUpdate::Failed {
@@ -397,6 +599,21 @@ impl ShellTuiRunner {
                         exit_code,
                     } => {
                         self.app.fail_block(&block_id, message, stderr, exit_code);
+                        self.notify_block_finished(&block_id, false);
+                    }
+                    CommandUpdate::Suggested { block_id, result } => {
+                        self.show_suggestion(&block_id, result);
+                    }
+                    CommandUpdate::Explained { result } => {
+                        let message = match result {
+                            Ok(explanation) => match explanation.fix {
+                                Some(fix) => format!("💡 {}\n   Try: {}", explanation.explanation, fix),
+                                None => format!("💡 {}", explanation.explanation),
+                            },
+                            Err(e) => format!("ai-explain: {}", e),
+                        };
+                        let prompt = self.app.current_prompt();
+                        self.app.add_block(CommandBlock::system(message, prompt));
                     }
                 }
             }
@@ -433,6 +650,19 @@ impl ShellTuiRunner {
                         existing.push_str(&text);
                         self.app.mark_dirty();
                     }
+                    AiUpdate::ModelReasoning { block_id, text } => {
+                        let prompt = self.app.current_prompt();
+                        let mut thought =
+                            CommandBlock::new(String::new(), BlockType::AiThought, prompt);
+                        thought.output = BlockOutput::Success(text);
+                        thought.exit_code = Some(0);
+
+                        if let Some(parent) = self.app.get_block_mut(&block_id) {
+                            thought.collapsed = !parent.expanded;
+                            parent.add_child(thought);
+                        }
+                        self.app.mark_dirty();
+                    }
                     AiUpdate::TextChunk { block_id, text } => {
                         // Skip empty text
                         if text.trim().is_empty() {
@@ -457,7 +687,8 @@ impl ShellTuiRunner {
                             let prompt = self.app.current_prompt();
                             let mut reasoning_block =
                                 CommandBlock::new(String::new(), BlockType::AiReasoning, prompt);
-                            reasoning_block.output = BlockOutput::Success(text);
+                            reasoning_block.output =
+                                BlockOutput::Success(self.output_pipeline.apply(&text));
                             reasoning_block.exit_code = Some(0);
 
                             if let Some(parent) = self.app.get_block_mut(&block_id) {
@@ -490,6 +721,7 @@ impl ShellTuiRunner {
                         self.app.mark_dirty();
                     }
                     AiUpdate::Response { block_id, text } => {
+                        let text = self.output_pipeline.apply(&text);
                         if let Some(block) = self.app.get_block_mut(&block_id) {
                             block.output = BlockOutput::Success(text);
                         }
@@ -537,6 +769,7 @@ impl ShellTuiRunner {
                             if let Some(child) = parent.children.iter_mut().rev().find(|c| {
                                 matches!(&c.block_type, BlockType::AiToolExecution { tool_name: n } if n == &tool_name)
                             }) {
+                                let output = humanize_tool_output(&output);
                                 // Truncate output for display - show more for readability
                                 // 2000 chars is roughly 40 lines of code
                                 let display_output = if output.chars().count() > 2000 {
@@ -631,8 +864,8 @@ impl ShellTuiRunner {
                                 child.duration_ms = Some(elapsed.num_milliseconds().max(0) as u64);
                             }
                         }
-                        // If todowrite tool completed, sync todos to sidebar
-                        if tool_name == "todowrite" && success {
+                        // If the todo list changed, sync todos to sidebar
+                        if matches!(tool_name.as_str(), "todowrite" | "todoupdate") && success {
                             self.app.sync_todos_to_sidebar();
                         }
                         self.app.mark_dirty();
@@ -683,20 +916,23 @@ impl ShellTuiRunner {
                         self.app.mark_dirty();
                     }
                     AiUpdate::Complete { block_id } => {
+                        let pipeline = &self.output_pipeline;
                         if let Some(block) = self.app.get_block_mut(&block_id) {
                             // Convert streaming to success if needed
                             if let BlockOutput::Streaming { lines, .. } = &block.output {
-                                block.output = BlockOutput::Success(lines.join("\n"));
+                                block.output = BlockOutput::Success(pipeline.apply(&lines.join("\n")));
                             } else if matches!(block.output, BlockOutput::Pending) {
                                 block.output = BlockOutput::Success(String::new());
                             }
                             block.exit_code = Some(0);
                         }
                         self.app.set_ai_thinking(false);
+                        self.notify_block_finished(&block_id, true);
                     }
                     AiUpdate::Error { block_id, message } => {
                         self.app.fail_block(&block_id, message, String::new(), 1);
                         self.app.set_ai_thinking(false);
+                        self.notify_block_finished(&block_id, false);
                     }
                     AiUpdate::PlanEvent { event, .. } => {
                         // Update sidebar with plan event
@@ -705,6 +941,9 @@ impl ShellTuiRunner {
                     AiUpdate::PlanAwaitingApproval { plan_id } => {
                         // Store plan_id for TUI to use when user approves/rejects via HTTP
                         self.app.set_pending_plan_id(plan_id);
+                        self.notifier.notify(Notification::ApprovalNeeded {
+                            what: "A plan is ready for review".to_string(),
+                        });
                     }
                     AiUpdate::TokenUsage {
                         input_tokens,
@@ -721,6 +960,104 @@ impl ShellTuiRunner {
                             cache_creation_tokens,
                         );
                     }
+                    AiUpdate::Capabilities { capabilities } => {
+                        self.app.capabilities = Some(capabilities);
+                        if !capabilities.vision && self.app.remove_image_attachments() > 0 {
+                            let prompt = self.app.current_prompt();
+                            let block = CommandBlock::system(
+                                format!(
+                                    "📎 Attached images removed: {} doesn't accept images",
+                                    self.app.model_display
+                                ),
+                                prompt,
+                            );
+                            self.app.add_block(block);
+                        }
+                    }
+                    AiUpdate::SkillActivated {
+                        block_id,
+                        name,
+                        file,
+                    } => {
+                        // Show the activation inline, dimmed like thinking text
+                        let prompt = self.app.current_prompt();
+                        let mut note =
+                            CommandBlock::new(String::new(), BlockType::AiThinking, prompt);
+                        note.output = BlockOutput::Success(format!(
+                            "📚 Skill activated: {} (triggered by {})",
+                            name, file
+                        ));
+                        note.exit_code = Some(0);
+
+                        if let Some(parent) = self.app.get_block_mut(&block_id) {
+                            parent.add_child(note);
+                        }
+                        self.app.mark_dirty();
+                    }
+                    AiUpdate::RateLimited {
+                        block_id,
+                        provider,
+                        retry_in_secs,
+                        attempt,
+                        max_retries,
+                    } => {
+                        let prompt = self.app.current_prompt();
+                        let mut note =
+                            CommandBlock::new(String::new(), BlockType::AiThinking, prompt);
+                        note.output = BlockOutput::Success(format!(
+                            "⏳ Rate limited by {}, retrying in {}s (attempt {}/{})",
+                            provider, retry_in_secs, attempt, max_retries
+                        ));
+                        note.exit_code = Some(0);
+
+                        if let Some(parent) = self.app.get_block_mut(&block_id) {
+                            parent.add_child(note);
+                        }
+                        self.app.mark_dirty();
+                    }
+                    AiUpdate::MonitorNotification {
+                        block_id,
+                        command,
+                        summary,
+                        needs_attention,
+                    } => {
+                        let prompt = self.app.current_prompt();
+                        let mut note =
+                            CommandBlock::new(String::new(), BlockType::AiThinking, prompt);
+                        let icon = if needs_attention { "⚠" } else { "📡" };
+                        note.output =
+                            BlockOutput::Success(format!("{} {}: {}", icon, command, summary));
+                        note.exit_code = Some(if needs_attention { 1 } else { 0 });
+
+                        if let Some(parent) = self.app.get_block_mut(&block_id) {
+                            parent.add_child(note);
+                        }
+                        self.app.mark_dirty();
+                    }
+                    AiUpdate::McpServerHealth {
+                        block_id,
+                        server,
+                        status,
+                        message,
+                    } => {
+                        let prompt = self.app.current_prompt();
+                        let mut note =
+                            CommandBlock::new(String::new(), BlockType::AiThinking, prompt);
+                        let icon = match status.as_str() {
+                            "restarted" => "🔌",
+                            _ => "⚠",
+                        };
+                        note.output = BlockOutput::Success(format!(
+                            "{} MCP server '{}' {}: {}",
+                            icon, server, status, message
+                        ));
+                        note.exit_code = Some(if status == "restarted" { 0 } else { 1 });
+
+                        if let Some(parent) = self.app.get_block_mut(&block_id) {
+                            parent.add_child(note);
+                        }
+                        self.app.mark_dirty();
+                    }
                     AiUpdate::ContextCompressed {
                         tokens_compressed, ..
                     } => {
@@ -734,9 +1071,61 @@ impl ShellTuiRunner {
                     AiUpdate::DoomLoopPrompt {
                         prompt_id,
                         message,
+                        timeout_secs,
                     } => {
                         // Store doom loop prompt for TUI to handle via HTTP
-                        self.app.set_doom_loop_prompt_http(prompt_id, message);
+                        self.notifier.notify(Notification::ApprovalNeeded {
+                            what: message.clone(),
+                        });
+                        self.app
+                            .set_doom_loop_prompt_http(prompt_id, message, timeout_secs);
+                    }
+                    AiUpdate::ToolApprovalPrompt {
+                        prompt_id,
+                        tool,
+                        description,
+                        high_risk,
+                        origin,
+                        always_ask,
+                    } => {
+                        let Some(client) = self.app.client.clone() else {
+                            continue;
+                        };
+
+                        // The local permission mode may already cover this tool,
+                        // unless its trust level says to always ask
+                        if !always_ask && !self.app.permission_mode.needs_approval(&tool) {
+                            tokio::spawn(async move {
+                                let client = client.lock().await;
+                                if let Err(e) = client.respond_to_tool_approval(&prompt_id, true).await {
+                                    tracing::error!("Failed to respond to tool approval: {}", e);
+                                }
+                            });
+                            continue;
+                        }
+
+                        self.notifier.notify(Notification::ApprovalNeeded {
+                            what: format!("{}: {}", tool, description),
+                        });
+
+                        // Show the approval modal and forward the decision via HTTP
+                        let (response_tx, mut response_rx) =
+                            tokio::sync::mpsc::unbounded_channel::<bool>();
+                        tokio::spawn(async move {
+                            let approved = response_rx.recv().await.unwrap_or(false);
+                            let client = client.lock().await;
+                            if let Err(e) = client.respond_to_tool_approval(&prompt_id, approved).await {
+                                tracing::error!("Failed to respond to tool approval: {}", e);
+                            }
+                        });
+                        self.app.set_pending_tool_approval(PendingToolApproval {
+                            tool_name: tool,
+                            description,
+                            args_preview: String::new(),
+                            response_tx,
+                            high_risk,
+                            origin,
+                        });
                     }
                     AiUpdate::TodoList { block_id, todos } => {
                         // Update todo list in sidebar AND inline display
@@ -844,6 +1233,8 @@ impl ShellTuiRunner {
                             };
                             parent.add_child(child);
                         }
+                        self.orchestration_tasks
+                            .insert(task_id, (description, worker));
                         self.app.mark_dirty();
                     }
                     OrchestrationUpdate::TaskOutput {
@@ -895,6 +1286,19 @@ impl ShellTuiRunner {
                                     if tool_name == &format!("task-{}", task_id))
                             }) {
                                 let status = if success { "✓" } else { "✗" };
+                                if let (false, Some(webhooks)) = (success, &self.webhooks) {
+                                    let (task, worker) = self
+                                        .orchestration_tasks
+                                        .get(&task_id)
+                                        .cloned()
+                                        .unwrap_or_else(|| (task_id.clone(), String::new()));
+                                    webhooks.notify(WebhookEvent::TaskFailed {
+                                        task,
+                                        worker,
+                                        error: output.clone(),
+                                        session_id: None,
+                                    });
+                                }
                                 let truncated_output = if output.chars().count() > 200 {
                                     format!("{}...", truncate_str(&output, 200))
                                 } else {
@@ -922,6 +1326,25 @@ impl ShellTuiRunner {
                             ));
                             block.exit_code = Some(if fail_count == 0 { 0 } else { 1 });
                         }
+                        self.notifier.notify(Notification::OrchestrationComplete {
+                            succeeded: success_count,
+                            failed: fail_count,
+                        });
+                        if let Some(webhooks) = &self.webhooks {
+                            let request = self
+                                .app
+                                .get_block_mut(&block_id)
+                                .map(|block| block.input.clone())
+                                .unwrap_or_default();
+                            webhooks.notify(WebhookEvent::OrchestrationComplete {
+                                request,
+                                succeeded: success_count,
+                                failed: fail_count,
+                                summary,
+                                session_id: None,
+                            });
+                        }
+                        self.orchestration_tasks.clear();
                         self.app.mark_dirty();
                     }
                     OrchestrationUpdate::Error { block_id, message } => {
@@ -961,6 +1384,11 @@ impl ShellTuiRunner {
                 }
             }
 
+            // Pick up edits made to SAFE_CODER.md outside the memory editor
+            if self.app.memory_editor.reload_if_changed() {
+                self.app.needs_redraw = true;
+            }
+
             // Tick animations
             self.app.tick();
         }
@@ -968,6 +1396,27 @@ impl ShellTuiRunner {
         Ok(())
     }
 
+    /// Answer the active doom loop prompt through the server
+    async fn respond_to_doom_loop(&mut self, continue_anyway: bool) {
+        if let Some(prompt_id) = self.app.doom_loop_prompt_id.clone() {
+            if let Some(client) = &self.app.client {
+                let client = client.lock().await;
+                if let Err(e) = client.respond_to_doom_loop(&prompt_id, continue_anyway).await {
+                    tracing::error!("Failed to respond to doom loop: {}", e);
+                }
+            }
+        }
+        let message = if continue_anyway {
+            "⚠️ Continuing past potential loop. The loop detector has been reset."
+        } else {
+            "🛑 Stopped due to detected loop. You can modify your request and try again."
+        };
+        let prompt = self.app.current_prompt();
+        self.app
+            .add_block(CommandBlock::system(message.to_string(), prompt));
+        self.app.clear_doom_loop();
+    }
+
     /// Handle a key event, returns true if should exit
     async fn handle_key_event(
         &mut self,
@@ -992,6 +1441,20 @@ impl ShellTuiRunner {
             }
         }
 
+        // Handle doom loop prompt (drawn above everything else)
+        if self.app.has_doom_loop_prompt() {
+            match code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    self.respond_to_doom_loop(true).await;
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    self.respond_to_doom_loop(false).await;
+                }
+                _ => {} // Ignore other keys
+            }
+            return Ok(false);
+        }
+
         // Handle tool approval modal (highest priority)
         if self.app.has_pending_tool_approval() {
             match code {
@@ -1033,6 +1496,17 @@ impl ShellTuiRunner {
             }
         }
 
+        // Memory editor takes all keys while open
+        if self.app.memory_editor.visible {
+            self.handle_memory_editor_key(code, modifiers);
+            return Ok(false);
+        }
+
+        if self.app.file_viewer.visible {
+            self.handle_file_viewer_key(code);
+            return Ok(false);
+        }
+
         // Handle plan approval popup
         if self.app.is_plan_approval_visible() {
             match code {
@@ -1052,7 +1526,7 @@ impl ShellTuiRunner {
 
                     // Trigger AI to execute the plan by sending a message
                     self.execute_ai_query(
-                        "The plan has been approved. Execute it now step by step.",
+                        "The plan has been approved. Execute it now step by step, marking each step with update_plan as you finish it.",
                         ai_tx.clone(),
                     )
                     .await?;
@@ -1118,6 +1592,30 @@ impl ShellTuiRunner {
                 self.app.input_clear();
             }
 
+            // Ctrl+O - show or collapse long code blocks and thoughts in the last AI response
+            KeyCode::Char('o') if modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(block) = self
+                    .app
+                    .blocks
+                    .iter_mut()
+                    .rev()
+                    .find(|b| b.block_type == BlockType::AiQuery)
+                {
+                    block.expanded = !block.expanded;
+                    block.render_version = block.render_version.wrapping_add(1);
+                    let expanded = block.expanded;
+                    for thought in block
+                        .children
+                        .iter_mut()
+                        .filter(|c| c.block_type == BlockType::AiThought)
+                    {
+                        thought.collapsed = !expanded;
+                        thought.render_version = thought.render_version.wrapping_add(1);
+                    }
+                }
+                self.app.mark_dirty();
+            }
+
             // Ctrl+P - cycle permission mode (YOLO/EDIT/ASK)
             KeyCode::Char('p') if modifiers.contains(KeyModifiers::CONTROL) => {
                 self.app.cycle_permission_mode();
@@ -1181,9 +1679,14 @@ impl ShellTuiRunner {
                         crate::tools::AgentMode::Plan => "plan",
                         crate::tools::AgentMode::Build => "build",
                     };
+                    let ai_tx = ai_tx.clone();
                     tokio::spawn(async move {
                         let client = client.lock().await;
                         let _ = client.set_mode(mode_str).await;
+                        // PLAN and BUILD may use different models
+                        if let Ok(capabilities) = client.get_capabilities().await {
+                            let _ = ai_tx.send(AiUpdate::Capabilities { capabilities });
+                        }
                     });
                 }
             }
@@ -1195,8 +1698,7 @@ impl ShellTuiRunner {
 
             // Ctrl+Shift+V - clear attached images
             KeyCode::Char('V') if modifiers.contains(KeyModifiers::CONTROL) && modifiers.contains(KeyModifiers::SHIFT) => {
-                if self.app.has_attached_images() {
-                    self.app.clear_attached_images();
+                if self.app.remove_image_attachments() > 0 {
                     let prompt = self.app.current_prompt();
                     let block = CommandBlock::system(
                         "📎 Attached images cleared".to_string(),
@@ -1208,35 +1710,21 @@ impl ShellTuiRunner {
 
             // Ctrl+V - paste (check for images first)
             KeyCode::Char('v') if modifiers.contains(KeyModifiers::CONTROL) => {
-                // Try to paste an image from clipboard
-                match self.app.paste_image_from_clipboard() {
-                    Ok(true) => {
-                        // Image pasted successfully
-                        let count = self.app.attached_images.len();
-                        let size = self.app.attached_images_size_display();
-                        let prompt = self.app.current_prompt();
-                        let block = CommandBlock::system(
-                            format!("📎 Image pasted ({} image{}, {})", count, if count == 1 { "" } else { "s" }, size),
-                            prompt,
-                        );
-                        self.app.add_block(block);
-                    }
-                    Ok(false) => {
+                match image_paste::from_clipboard() {
+                    Ok(Some(image)) => self.attach_pasted_image(image).await,
+                    Ok(None) => {
                         // No image in clipboard, try to paste text
                         if let Ok(mut clipboard) = arboard::Clipboard::new() {
                             if let Ok(text) = clipboard.get_text() {
-                                // Insert text at cursor position
-                                for c in text.chars() {
-                                    // Skip newlines - paste as single line
-                                    if c != '\n' && c != '\r' {
-                                        self.app.input_push(c);
-                                    }
-                                }
+                                self.handle_paste(&text).await;
                             }
                         }
                     }
                     Err(e) => {
-                        tracing::warn!("Clipboard access failed: {}", e);
+                        tracing::warn!("Image paste failed: {}", e);
+                        let prompt = self.app.current_prompt();
+                        let block = CommandBlock::system(format!("📎 {}", e), prompt);
+                        self.app.add_block(block);
                     }
                 }
             }
@@ -1434,6 +1922,9 @@ impl ShellTuiRunner {
                 } else if self.app.autocomplete_visible() {
                     // Apply autocomplete selection
                     self.app.apply_autocomplete();
+                } else if self.app.input.trim().is_empty() {
+                    // Enter on an empty prompt opens files referenced by the last response
+                    self.open_file_references();
                 } else {
                     let input = self.app.input_submit();
                     if !input.is_empty() {
@@ -1517,7 +2008,7 @@ impl ShellTuiRunner {
 
                 // Trigger AI to execute the plan
                 self.execute_ai_query(
-                    "The plan has been approved. Execute it now step by step.",
+                    "The plan has been approved. Execute it now step by step, marking each step with update_plan as you finish it.",
                     ai_tx.clone(),
                 )
                 .await?;
@@ -1535,50 +2026,78 @@ impl ShellTuiRunner {
             }
         }
 
-        // Check for doom loop response commands when a doom loop prompt is active
-        if self.app.has_doom_loop_prompt() {
-            if input_lower == "continue" || input_lower == "yes" || input_lower == "y" {
-                // Get prompt_id and respond via HTTP API
-                if let Some(prompt_id) = self.app.doom_loop_prompt_id.clone() {
-                    if let Some(client) = &self.app.client {
-                        let client = client.lock().await;
-                        if let Err(e) = client.respond_to_doom_loop(&prompt_id, true).await {
-                            tracing::error!("Failed to respond to doom loop: {}", e);
-                        }
-                    }
-                }
-                let prompt = self.app.current_prompt();
-                let block = CommandBlock::system(
-                    "⚠️ Continuing past potential loop. The loop detector has been reset.".to_string(),
-                    prompt,
-                );
-                self.app.add_block(block);
-                self.app.clear_doom_loop();
-                return Ok(());
-            } else if input_lower == "stop" || input_lower == "no" || input_lower == "n" {
-                // Get prompt_id and respond via HTTP API
-                if let Some(prompt_id) = self.app.doom_loop_prompt_id.clone() {
-                    if let Some(client) = &self.app.client {
-                        let client = client.lock().await;
-                        if let Err(e) = client.respond_to_doom_loop(&prompt_id, false).await {
-                            tracing::error!("Failed to respond to doom loop: {}", e);
-                        }
-                    }
+        // Check for slash commands first (e.g., /connect, /help)
+        if let Some(slash_cmd) = ShellTuiApp::parse_slash_command(input) {
+            return self.execute_slash_command(slash_cmd, ai_tx, orch_tx).await;
+        }
+
+        // Then user-defined commands from .safe-coder/commands (e.g., /deploy staging)
+        if let Some(name) = input.strip_prefix('/').and_then(|rest| rest.split_whitespace().next()) {
+            if CustomCommandManager::command_exists(&self.app.project_path, name) {
+                return self.execute_custom_command(input, ai_tx).await;
+            }
+        }
+
+        // `# find the largest files` asks the LLM for a command
+        if let Some(intent) = input.strip_prefix('#') {
+            let intent = intent.trim();
+            if !intent.is_empty() {
+                self.translate(input, intent, cmd_tx).await?;
+            }
+            return Ok(());
+        }
+
+        // AI help with commands, as in the legacy shell
+        let (cmd, args) = input
+            .split_once(' ')
+            .map_or((input, ""), |(cmd, args)| (cmd, args.trim()));
+        match cmd {
+            "ai-suggest" => {
+                let request = args.trim_matches('"').trim_matches('\'').trim();
+                if request.is_empty() {
+                    let prompt = self.app.current_prompt();
+                    self.app.add_block(CommandBlock::system(
+                        "Usage: ai-suggest \"<what you want to do>\"".to_string(),
+                        prompt,
+                    ));
+                    return Ok(());
                 }
+                return self.translate(input, request, cmd_tx).await;
+            }
+            "ai-explain" => {
+                self.explain_errors = match args {
+                    "" => !self.explain_errors,
+                    "on" => true,
+                    "off" => false,
+                    _ => anyhow::bail!("Usage: ai-explain [on|off]"),
+                };
                 let prompt = self.app.current_prompt();
-                let block = CommandBlock::system(
-                    "🛑 Stopped due to detected loop. You can modify your request and try again.".to_string(),
+                self.app.add_block(CommandBlock::system(
+                    format!(
+                        "Explaining failed commands: {}",
+                        if self.explain_errors { "on" } else { "off" }
+                    ),
                     prompt,
-                );
-                self.app.add_block(block);
-                self.app.clear_doom_loop();
+                ));
                 return Ok(());
             }
+            // Commands here never block the prompt, so there are no jobs to
+            // manage; `kill PID` still goes to the system command
+            "jobs" | "fg" => {
+                return self.explain_job_control(input);
+            }
+            "kill" if args.starts_with('%') => {
+                return self.explain_job_control(input);
+            }
+            _ => {}
         }
 
-        // Check for slash commands first (e.g., /connect, /help)
-        if let Some(slash_cmd) = ShellTuiApp::parse_slash_command(input) {
-            return self.execute_slash_command(slash_cmd, ai_tx, orch_tx).await;
+        // Aliases always expand to shell commands
+        if let Some(expanded) = rc::expand_alias(&self.app.aliases, input) {
+            if ShellTuiApp::is_builtin_command(&expanded) {
+                return self.execute_builtin(&expanded);
+            }
+            return self.execute_shell_command(&expanded, cmd_tx).await;
         }
 
         // Check for built-in shell commands (cd, pwd, exit, etc.)
@@ -1595,6 +2114,21 @@ impl ShellTuiRunner {
         self.execute_ai_query(input, ai_tx).await
     }
 
+    /// Point job control commands at the legacy shell, which has them
+    fn explain_job_control(&mut self, input: &str) -> Result<()> {
+        let prompt = self.app.current_prompt();
+        let mut block = CommandBlock::new(input.to_string(), BlockType::ShellCommand, prompt);
+        block.fail(
+            "jobs, fg and kill %N are only in the legacy shell (safe-coder shell --no-tui). \
+             Here every command already runs without blocking the prompt."
+                .to_string(),
+            String::new(),
+            1,
+        );
+        self.app.add_block(block);
+        Ok(())
+    }
+
     /// Execute a built-in command
     fn execute_builtin(&mut self, input: &str) -> Result<()> {
         let parts: Vec<&str> = input.splitn(2, ' ').collect();
@@ -1682,6 +2216,43 @@ impl ShellTuiRunner {
                 self.app.add_block(block);
             }
 
+            "alias" => {
+                let mut block =
+                    CommandBlock::new(input.to_string(), BlockType::ShellCommand, prompt);
+                if args.contains('=') {
+                    match rc::parse_assignment(args) {
+                        Some((name, value)) => {
+                            self.app.aliases.insert(name, value);
+                            block.complete(String::new(), 0);
+                        }
+                        None => {
+                            block.fail("Usage: alias NAME=VALUE".to_string(), String::new(), 1)
+                        }
+                    }
+                } else {
+                    let name = (!args.is_empty()).then_some(args);
+                    let listing = rc::list_aliases(&self.app.aliases, name);
+                    match name {
+                        Some(name) if listing.is_empty() => {
+                            block.fail(format!("alias: {}: not found", name), String::new(), 1)
+                        }
+                        _ => block.complete(listing, 0),
+                    }
+                }
+                self.app.add_block(block);
+            }
+
+            "unalias" => {
+                let mut block =
+                    CommandBlock::new(input.to_string(), BlockType::ShellCommand, prompt);
+                if self.app.aliases.remove(args).is_some() {
+                    block.complete(String::new(), 0);
+                } else {
+                    block.fail(format!("unalias: {}: not found", args), String::new(), 1);
+                }
+                self.app.add_block(block);
+            }
+
             "env" => {
                 let mut block =
                     CommandBlock::new(input.to_string(), BlockType::ShellCommand, prompt);
@@ -1705,13 +2276,22 @@ Shell Commands:
   clear             Clear screen
   export KEY=VAL    Set environment variable
   env               Show all environment variables
+  alias NAME=CMD    Define an alias (unalias NAME to remove)
   exit, quit        Exit shell
 
+Commands run without blocking the prompt. Job control (<command> &, jobs,
+fg, kill %N) is only in the legacy shell: safe-coder shell --no-tui.
+
+Aliases, exports and startup commands can go in ~/.config/safe-coder/shellrc.
+
 AI Commands (prefix with @):
   @connect          Connect to AI
   @disconnect       Disconnect from AI
   @ <query>         Ask AI for help (with shell context)
   @orchestrate      Run multi-agent task
+  # <task>          Translate to a command; Enter runs it, Ctrl+C drops it
+  ai-suggest "..."  Same as # <task>
+  ai-explain [on|off]  Explain failed commands and suggest a fix
 
 Keyboard Shortcuts:
   Ctrl+C            Cancel/clear input (or exit if empty)
@@ -1774,17 +2354,184 @@ Keyboard Shortcuts:
         Ok(())
     }
 
-    /// Execute a slash command (e.g., /connect, /help)
-    async fn execute_slash_command(
+    /// LLM client for `# intent`, `ai-suggest` and `ai-explain`
+    async fn assistant(&mut self) -> Result<Arc<dyn LlmClient>> {
+        if let Some(client) = &self.assistant {
+            return Ok(Arc::clone(client));
+        }
+        let client: Arc<dyn LlmClient> = Arc::from(create_client(&self.config).await?);
+        self.assistant = Some(Arc::clone(&client));
+        Ok(client)
+    }
+
+    /// Ask the LLM for a command that does what `request` describes; the
+    /// answer lands on the prompt (see [`Self::show_suggestion`])
+    async fn translate(
         &mut self,
-        cmd: SlashCommand,
-        _ai_tx: mpsc::UnboundedSender<AiUpdate>,
-        orch_tx: mpsc::UnboundedSender<OrchestrationUpdate>,
+        input: &str,
+        request: &str,
+        tx: mpsc::UnboundedSender<CommandUpdate>,
     ) -> Result<()> {
-        match cmd {
-            SlashCommand::Connect => {
-                self.connect_ai().await?;
-            }
+        let prompt = self.app.current_prompt();
+        let block = CommandBlock::new(input.to_string(), BlockType::ShellCommand, prompt);
+        let block_id = block.id.clone();
+        self.app.add_block(block);
+
+        let client = match self.assistant().await {
+            Ok(client) => client,
+            Err(e) => {
+                self.app.fail_block(&block_id, e.to_string(), String::new(), 1);
+                return Ok(());
+            }
+        };
+        let request = request.to_string();
+        let cwd = self.app.cwd.display().to_string();
+        tokio::spawn(async move {
+            let result = assist::suggest_command(client.as_ref(), &request, &cwd)
+                .await
+                .map_err(|e| e.to_string());
+            let _ = tx.send(CommandUpdate::Suggested { block_id, result });
+        });
+        Ok(())
+    }
+
+    /// Ask the LLM why a shell command block failed, answering with a
+    /// [`CommandUpdate::Explained`]
+    async fn explain_failure(
+        &mut self,
+        block_id: &str,
+        exit_code: i32,
+        tx: mpsc::UnboundedSender<CommandUpdate>,
+    ) {
+        let Some(block) = self.app.get_block_mut(block_id) else {
+            return;
+        };
+        let command = block.input.clone();
+        let stderr: Vec<String> = match &block.output {
+            BlockOutput::Streaming { lines, .. } => lines
+                .iter()
+                .filter_map(|line| line.strip_prefix("stderr: "))
+                .map(str::to_string)
+                .collect(),
+            _ => Vec::new(),
+        };
+        let client = match self.assistant().await {
+            Ok(client) => client,
+            Err(e) => {
+                let _ = tx.send(CommandUpdate::Explained {
+                    result: Err(e.to_string()),
+                });
+                return;
+            }
+        };
+        let cwd = self.app.cwd.display().to_string();
+        tokio::spawn(async move {
+            let result = assist::explain_failure(
+                client.as_ref(),
+                &command,
+                exit_code,
+                &stderr.join("\n"),
+                &cwd,
+            )
+            .await
+            .map_err(|e| e.to_string());
+            let _ = tx.send(CommandUpdate::Explained { result });
+        });
+    }
+
+    /// Show what a suggested command does and any risks, and put it on the
+    /// prompt: Enter runs it (after any edits), Ctrl+C drops it
+    fn show_suggestion(&mut self, block_id: &str, result: std::result::Result<Suggestion, String>) {
+        let suggestion = match result {
+            Ok(suggestion) => suggestion,
+            Err(e) => {
+                self.app.fail_block(block_id, e, String::new(), 1);
+                return;
+            }
+        };
+
+        let mut lines = vec![format!("❯ {}", suggestion.command)];
+        if !suggestion.explanation.is_empty() {
+            lines.push(format!("  {}", suggestion.explanation));
+        }
+        let analysis = CommandAnalysis::analyze(&suggestion.command);
+        if !analysis.categories.is_empty() {
+            lines.push(format!("  ⚠ This command {}", analysis.summary()));
+        }
+        // Don't throw away something typed while waiting
+        if self.app.input.is_empty() {
+            lines.push("Enter runs it, Ctrl+C drops it".to_string());
+            self.app.cursor_pos = suggestion.command.len();
+            self.app.input = suggestion.command;
+        }
+        self.app.complete_block(block_id, lines.join("\n"), 0);
+    }
+
+    /// Execute a slash command (e.g., /connect, /help)
+    async fn execute_slash_command(
+        &mut self,
+        cmd: SlashCommand,
+        ai_tx: mpsc::UnboundedSender<AiUpdate>,
+        orch_tx: mpsc::UnboundedSender<OrchestrationUpdate>,
+    ) -> Result<()> {
+        match cmd {
+            SlashCommand::Skill(args) => {
+                self.execute_skill_command(args.as_deref().unwrap_or(""), ai_tx);
+            }
+
+            SlashCommand::Set(args) => {
+                self.execute_set_command(args.as_deref().unwrap_or(""), ai_tx);
+            }
+
+            SlashCommand::Attach(args) => {
+                self.execute_attach_command(args.as_deref().unwrap_or(""))
+                    .await;
+            }
+
+            SlashCommand::Mcp(args) => {
+                self.execute_mcp_command(args.as_deref().unwrap_or(""), ai_tx);
+            }
+
+            SlashCommand::McpPrompt { server, name, args } => {
+                self.execute_mcp_prompt(&server, &name, &args, ai_tx).await?;
+            }
+
+            SlashCommand::Plan(args) => {
+                self.execute_plan_command(args.as_deref().unwrap_or(""), ai_tx)
+                    .await?;
+            }
+
+            SlashCommand::Ci => {
+                self.execute_ci_command(ai_tx).await?;
+            }
+
+            SlashCommand::Audit(args) => {
+                let fix = args.as_deref().is_some_and(|a| a.trim().eq_ignore_ascii_case("fix"));
+                self.execute_audit_command(fix, ai_tx).await?;
+            }
+
+            SlashCommand::Init(args) => {
+                let force = args.as_deref().is_some_and(|a| a.trim().eq_ignore_ascii_case("force"));
+                self.execute_init_command(force).await;
+            }
+
+            SlashCommand::Memory(args) => {
+                self.execute_memory_command(args.as_deref().unwrap_or(""));
+            }
+
+            SlashCommand::Prompts => {
+                self.show_prompt_overrides();
+            }
+
+            SlashCommand::Diff => {
+                self.show_session_diff().await;
+            }
+
+            SlashCommand::Connect(args) => match args.as_deref().map(str::trim) {
+                None | Some("") => self.connect_ai().await?,
+                Some("list") => self.list_server_sessions().await,
+                Some(id) => self.attach_ai(id, ai_tx).await,
+            },
 
             SlashCommand::Disconnect => {
                 self.disconnect_ai();
@@ -1807,9 +2554,13 @@ Keyboard Shortcuts:
                         crate::tools::AgentMode::Plan => "plan",
                         crate::tools::AgentMode::Build => "build",
                     };
+                    let ai_tx = ai_tx.clone();
                     tokio::spawn(async move {
                         let client = client.lock().await;
                         let _ = client.set_mode(mode_str).await;
+                        if let Ok(capabilities) = client.get_capabilities().await {
+                            let _ = ai_tx.send(AiUpdate::Capabilities { capabilities });
+                        }
                     });
                 }
             }
@@ -1826,6 +2577,16 @@ Commands:
   /mode             Toggle permission mode (ASK/EDIT/YOLO)
   /agent            Toggle agent mode (PLAN/BUILD)
   /orchestrate      Run multi-agent task
+  /skill            List skills; /skill on|off <name> to toggle
+  /mcp              List MCP resources (attach with @mcp:server/resource), prompts, or status
+  /ci               Show CI status; failing job logs are sent to the AI
+  /audit [fix]      Audit dependencies; the AI proposes (or applies) remediations
+  /init [force]     Generate SAFE_CODER.md from the code base
+  /memory [edit]    View, edit and delete SAFE_CODER.md entries
+  /prompts          Show system prompt overrides from .safe-coder/prompts/
+  /diff             Show everything changed since the shell started
+  /set [name value] Show or set temperature, top_p or stop for this session
+  /attach [path|url] Send a file, directory or URL with the next message
 
 Shell:
   Type any shell command (ls, git, cargo, etc.)
@@ -1870,8 +2631,11 @@ Keyboard:
     • webfetch  - Fetch URL content
 
   Task Tracking:
-    • todowrite - Update task list
-    • todoread  - Read task list"#;
+    • todowrite  - Replace task list
+    • todoupdate - Add, update or remove a task
+    • todoread   - Read task list
+
+  Tasks are saved in .safe-coder/todos.json and kept across sessions."#;
                 let block = CommandBlock::system(tools_text.to_string(), prompt);
                 self.app.add_block(block);
             }
@@ -2191,8 +2955,8 @@ Keyboard:
     async fn get_copilot_models(&self) -> Result<Vec<crate::llm::copilot::CopilotModel>> {
         use crate::config::LlmProvider;
 
-        let token_path = crate::config::Config::token_path(&LlmProvider::GitHubCopilot)?;
-        if !token_path.exists() {
+        let token_path = self.config.active_token_path(&LlmProvider::GitHubCopilot)?;
+        if !crate::auth::StoredToken::exists(&token_path) {
             anyhow::bail!("Not logged in to GitHub Copilot");
         }
 
@@ -2214,9 +2978,13 @@ Keyboard:
         let auth = GitHubCopilotAuth::new();
         let token = run_device_flow(&auth, "GitHub Copilot").await?;
 
-        // Save the token
-        let token_path = crate::config::Config::token_path(&LlmProvider::GitHubCopilot)?;
-        token.save(&token_path)?;
+        // Save the token for the active account
+        let account = self.config.active_account(&LlmProvider::GitHubCopilot);
+        crate::config::Config::save_account_token(
+            &LlmProvider::GitHubCopilot,
+            account.as_deref(),
+            &token,
+        )?;
 
         Ok(())
     }
@@ -2808,11 +3576,634 @@ Keyboard:
         }
     }
 
+    /// List or toggle the session's skills via the server
+    fn execute_skill_command(&mut self, args: &str, tx: mpsc::UnboundedSender<AiUpdate>) {
+        let prompt = self.app.current_prompt();
+        let Some(client) = self.app.client.clone().filter(|_| self.app.ai_connected) else {
+            let block =
+                CommandBlock::system("AI not connected. Run /connect first.".to_string(), prompt);
+            self.app.add_block(block);
+            return;
+        };
+
+        let mut parts = args.split_whitespace();
+        let action = parts.next().unwrap_or("list").to_lowercase();
+        let name = parts.next().map(|s| s.to_string());
+
+        let block = CommandBlock::system("Loading skills...".to_string(), prompt);
+        let block_id = block.id.clone();
+        self.app.add_block(block);
+
+        // The session may be busy with a query, so don't block the UI on it
+        tokio::spawn(async move {
+            let client = client.lock().await;
+            let result = match (action.as_str(), name) {
+                ("list" | "ls", _) => client.list_skills().await.map(|skills| format_skill_list(&skills)),
+                ("on" | "activate" | "enable", Some(name)) => client
+                    .set_skill_active(&name, true)
+                    .await
+                    .map(|_| format!("✓ Skill '{}' activated.", name)),
+                ("off" | "deactivate" | "disable", Some(name)) => client
+                    .set_skill_active(&name, false)
+                    .await
+                    .map(|_| format!("✓ Skill '{}' deactivated. It won't be re-activated by matching files.", name)),
+                ("info" | "show", Some(name)) => client.list_skills().await.and_then(|skills| {
+                    skills
+                        .iter()
+                        .find(|s| s.name == name)
+                        .map(|s| format_skill_list(std::slice::from_ref(s)))
+                        .ok_or_else(|| anyhow::anyhow!("Skill '{}' not found", name))
+                }),
+                _ => Err(anyhow::anyhow!("Usage: /skill [list | on <name> | off <name> | info <name>]")),
+            };
+
+            let text = match result {
+                Ok(text) => text,
+                Err(e) => format!("❌ {}", e),
+            };
+            let _ = tx.send(AiUpdate::Response { block_id, text });
+        });
+    }
+
+    /// Attach a file, directory or URL, list attachments, or clear them
+    async fn execute_attach_command(&mut self, args: &str) {
+        match args.trim() {
+            "" => {
+                let message = if self.app.has_attachments() {
+                    let mut message = "📎 Sent with the next message:".to_string();
+                    for attachment in &self.app.attachments {
+                        message.push_str(&format!(
+                            "\n  {} {} ({})",
+                            attachment.icon(),
+                            attachment.source,
+                            FilePicker::format_size(attachment.content.len() as u64)
+                        ));
+                    }
+                    message
+                } else {
+                    "No attachments. Usage: /attach <path|url>".to_string()
+                };
+                let prompt = self.app.current_prompt();
+                self.app.add_block(CommandBlock::system(message, prompt));
+            }
+            "clear" => {
+                self.app.take_attachments();
+                let prompt = self.app.current_prompt();
+                self.app.add_block(CommandBlock::system(
+                    "📎 Attachments cleared".to_string(),
+                    prompt,
+                ));
+            }
+            spec => self.attach(spec.trim_matches(['"', '\''])).await,
+        }
+    }
+
+    /// Resolve a path or URL and add it to the next message
+    async fn attach(&mut self, spec: &str) {
+        let message = match attachments::resolve(spec, &self.app.cwd).await {
+            Ok(attachment)
+                if attachment.kind == AttachmentKind::Image && !self.app.supports_images() =>
+            {
+                format!("📎 {} doesn't accept images", self.app.model_display)
+            }
+            Ok(attachment) => {
+                let message = format!(
+                    "📎 Attached {} {} ({})",
+                    attachment.icon(),
+                    attachment.source,
+                    FilePicker::format_size(attachment.content.len() as u64)
+                );
+                self.app.add_attachment(attachment);
+                message
+            }
+            Err(e) => format!("📎 {}", e),
+        };
+        let prompt = self.app.current_prompt();
+        self.app.add_block(CommandBlock::system(message, prompt));
+    }
+
+    /// Save a pasted image to a temp file and attach it
+    async fn attach_pasted_image(&mut self, image: image_paste::PastedImage) {
+        if !self.app.supports_images() {
+            let prompt = self.app.current_prompt();
+            let message = format!("📎 {} doesn't accept images", self.app.model_display);
+            self.app.add_block(CommandBlock::system(message, prompt));
+            return;
+        }
+        match image.save() {
+            Ok(path) => self.attach(&path.to_string_lossy()).await,
+            Err(e) => {
+                let prompt = self.app.current_prompt();
+                self.app.add_block(CommandBlock::system(format!("📎 {}", e), prompt));
+            }
+        }
+    }
+
+    /// Paste text into the input; files dropped on the terminal and images
+    /// sent inline (kitty, iTerm2, `data:` URLs) are attached
+    async fn handle_paste(&mut self, text: &str) {
+        if let Some(image) = image_paste::decode(text) {
+            self.attach_pasted_image(image).await;
+            return;
+        }
+        if let Some(paths) = attachments::dropped_paths(text, &self.app.cwd) {
+            for path in paths {
+                self.attach(&path.to_string_lossy()).await;
+            }
+            return;
+        }
+        for c in text.chars() {
+            // Skip newlines - paste as single line
+            if c != '\n' && c != '\r' {
+                self.app.input_push(c);
+            }
+        }
+    }
+
+    /// Show or set the session's sampling parameters (`/set temperature 0.2`)
+    fn execute_set_command(&mut self, args: &str, tx: mpsc::UnboundedSender<AiUpdate>) {
+        let prompt = self.app.current_prompt();
+        let Some(client) = self.app.client.clone().filter(|_| self.app.ai_connected) else {
+            let block =
+                CommandBlock::system("AI not connected. Run /connect first.".to_string(), prompt);
+            self.app.add_block(block);
+            return;
+        };
+
+        let (name, value) = match args.trim().split_once(char::is_whitespace) {
+            Some((name, value)) => (name.to_string(), Some(value.trim().to_string())),
+            None => (args.trim().to_string(), None),
+        };
+
+        let block = CommandBlock::system("Updating parameters...".to_string(), prompt);
+        let block_id = block.id.clone();
+        self.app.add_block(block);
+
+        // The session may be busy with a query, so don't block the UI on it
+        tokio::spawn(async move {
+            let client = client.lock().await;
+            let result = match (name.as_str(), value) {
+                ("", _) => client.get_params().await.map(|params| params.to_string()),
+                (name, Some(value)) => client
+                    .set_param(name, &value)
+                    .await
+                    .map(|params| format!("✓ {} set to {}\n\n{}", name, value, params)),
+                _ => Err(anyhow::anyhow!(
+                    "Usage: /set [temperature | top_p | stop] <value> (or 'default')"
+                )),
+            };
+
+            let text = match result {
+                Ok(text) => text,
+                Err(e) => format!("❌ {}", e),
+            };
+            let _ = tx.send(AiUpdate::Response { block_id, text });
+        });
+    }
+
+    /// Show or resume plans saved in .safe-coder/plans (read straight from disk)
+    async fn execute_plan_command(
+        &mut self,
+        args: &str,
+        ai_tx: mpsc::UnboundedSender<AiUpdate>,
+    ) -> Result<()> {
+        use crate::planning::artifact::{format_plan_list, format_status, resume_prompt};
+
+        let store = PlanStore::new(&self.app.project_path);
+        let mut parts = args.split_whitespace();
+        let text = match parts.next().unwrap_or("status").to_lowercase().as_str() {
+            "list" | "ls" => format_plan_list(&store.list()),
+            "resume" | "continue" => match store.resolve(parts.next()) {
+                Ok(plan) => {
+                    self.app.set_agent_mode(crate::tools::AgentMode::Build);
+                    if let Some(client) = &self.app.client {
+                        let client: Arc<Mutex<SafeCoderClient>> = Arc::clone(client);
+                        tokio::spawn(async move {
+                            let client = client.lock().await;
+                            let _ = client.set_mode("build").await;
+                        });
+                    }
+                    let prompt = resume_prompt(&plan, &store.path_for(&plan.id));
+                    return self.execute_ai_query(&prompt, ai_tx).await;
+                }
+                Err(e) => format!("📋 {}", e),
+            },
+            _ => match store.resolve(None) {
+                Ok(plan) => format_status(&plan),
+                Err(e) => format!("📋 {}", e),
+            },
+        };
+
+        let prompt = self.app.current_prompt();
+        self.app.add_block(CommandBlock::system(text, prompt));
+        Ok(())
+    }
+
+    /// Show the current branch's CI status, sending failures to the AI
+    async fn execute_ci_command(&mut self, ai_tx: mpsc::UnboundedSender<AiUpdate>) -> Result<()> {
+        let report = crate::tools::ci_status::ci_report(
+            &self.app.project_path,
+            &self.config.tools,
+            None,
+            true,
+        )
+        .await;
+        let text = match report {
+            Ok(report) if report.failing && self.app.ai_connected => {
+                let overrides = QueryOverrides {
+                    display: Some("/ci".to_string()),
+                    ..Default::default()
+                };
+                return self
+                    .execute_ai_query_with_overrides(&report.fix_prompt(), overrides, ai_tx)
+                    .await;
+            }
+            Ok(report) => report.text,
+            Err(e) => format!("❌ {}", e),
+        };
+
+        let prompt = self.app.current_prompt();
+        self.app.add_block(CommandBlock::system(text, prompt));
+        Ok(())
+    }
+
+    /// Audit dependencies and have the AI summarize what it finds
+    async fn execute_audit_command(
+        &mut self,
+        fix: bool,
+        ai_tx: mpsc::UnboundedSender<AiUpdate>,
+    ) -> Result<()> {
+        let text = match crate::tools::audit::audit(&self.app.project_path, 300).await {
+            Ok(report) if report.vulnerabilities.is_empty() => {
+                format!("✅ {}: no known vulnerabilities", report.auditor)
+            }
+            Ok(report) if self.app.ai_connected => {
+                let overrides = QueryOverrides {
+                    display: Some(if fix { "/audit fix" } else { "/audit" }.to_string()),
+                    ..Default::default()
+                };
+                return self
+                    .execute_ai_query_with_overrides(&report.remediation_prompt(fix), overrides, ai_tx)
+                    .await;
+            }
+            Ok(report) => report.to_json(),
+            Err(e) => format!("❌ {}", e),
+        };
+
+        let prompt = self.app.current_prompt();
+        self.app.add_block(CommandBlock::system(text, prompt));
+        Ok(())
+    }
+
+    /// Write SAFE_CODER.md from an analysis of the project
+    async fn execute_init_command(&mut self, force: bool) {
+        // Without a working provider the file still gets the detected facts
+        let client = create_client(&self.config).await.ok();
+        let result = crate::memory::analysis::write_project_memory(
+            &self.app.project_path,
+            client.as_deref(),
+            force,
+        )
+        .await;
+        let text = match result {
+            Ok(path) => format!(
+                "✓ Wrote {} from an analysis of the project. Review and edit it; it is included in every session.",
+                path.display()
+            ),
+            Err(e) => format!("❌ {}", e),
+        };
+        let prompt = self.app.current_prompt();
+        self.app.add_block(CommandBlock::system(text, prompt));
+    }
+
+    /// Open the memory editor, or add to / print SAFE_CODER.md
+    fn execute_memory_command(&mut self, args: &str) {
+        let (action, rest) = args.trim().split_once(' ').unwrap_or((args.trim(), ""));
+        let text = match action.to_lowercase().as_str() {
+            "" | "edit" => {
+                self.app.memory_editor.open(&self.app.project_path);
+                self.app.needs_redraw = true;
+                return;
+            }
+            "add" if !rest.trim().is_empty() => {
+                match crate::memory::learned::remember(
+                    &self.app.project_path,
+                    rest,
+                    crate::memory::learned::LearnedSource::User,
+                ) {
+                    Ok(true) => format!("🧠 Remembered: {}", rest.trim()),
+                    Ok(false) => format!("🧠 Already in project memory: {}", rest.trim()),
+                    Err(e) => format!("❌ {}", e),
+                }
+            }
+            "show" => {
+                let path = crate::memory::learned::memory_file(&self.app.project_path);
+                match std::fs::read_to_string(&path) {
+                    Ok(content) => format!("📄 {}\n\n{}", path.display(), content),
+                    Err(_) => "📄 No SAFE_CODER.md yet. Run /init to generate one.".to_string(),
+                }
+            }
+            _ => "Usage: /memory [edit] | add <text> | show".to_string(),
+        };
+        let prompt = self.app.current_prompt();
+        self.app.add_block(CommandBlock::system(text, prompt));
+    }
+
+    /// Show the changes made since the shell started, untracked files included
+    async fn show_session_diff(&mut self) {
+        const MAX_LINES: usize = 400;
+
+        let text = match &self.start_snapshot {
+            None => "📝 /diff needs a git repository to snapshot the project".to_string(),
+            Some(base) => match GitManager::new(self.app.project_path.clone())
+                .diff_since(base)
+                .await
+            {
+                Ok((_, files)) if files.is_empty() => {
+                    "📝 No changes since the session started".to_string()
+                }
+                Ok((patch, files)) => {
+                    let lines: Vec<&str> = patch.lines().collect();
+                    let mut text = format!(
+                        "📝 {}\n{}",
+                        format_stat(&files),
+                        lines[..lines.len().min(MAX_LINES)].join("\n")
+                    );
+                    if lines.len() > MAX_LINES {
+                        text.push_str(&format!("\n... ({} more lines)", lines.len() - MAX_LINES));
+                    }
+                    text
+                }
+                Err(e) => format!("❌ {}", e),
+            },
+        };
+        let prompt = self.app.current_prompt();
+        self.app.add_block(CommandBlock::system(text, prompt));
+    }
+
+    /// List the prompt files in effect and the ones skipped as invalid
+    fn show_prompt_overrides(&mut self) {
+        let overrides = PromptOverrides::load(&self.app.project_path);
+        let dir = PromptOverrides::dir(&self.app.project_path);
+        let mut text = format!("📝 System prompt files in {}\n", dir.display());
+        let active = overrides.active();
+        if active.is_empty() && overrides.errors.is_empty() {
+            text.push_str(
+                "\nNone; the built-in prompt is used. Add base.md, plan.md, build.md or tools.md \
+                 to replace a section, or <section>.append.md to extend it. Files can use \
+                 {{project_name}}, {{mode}}, {{date}} and {{default}}.",
+            );
+        }
+        for file in active {
+            text.push_str(&format!("\n  ✓ {}", file));
+        }
+        for error in &overrides.errors {
+            text.push_str(&format!("\n  ✗ {} (skipped)", error));
+        }
+        let prompt = self.app.current_prompt();
+        self.app.add_block(CommandBlock::system(text, prompt));
+    }
+
+    /// Keys for the memory editor: browse entries, or edit the open one
+    fn handle_memory_editor_key(&mut self, code: KeyCode, modifiers: KeyModifiers) {
+        let editor = &mut self.app.memory_editor;
+        self.app.needs_redraw = true;
+
+        if editor.editing.is_some() {
+            match code {
+                KeyCode::Esc => editor.cancel_edit(),
+                KeyCode::Enter if modifiers.contains(KeyModifiers::ALT) => editor.insert_char('\n'),
+                KeyCode::Enter => editor.save_edit(),
+                KeyCode::Backspace => editor.backspace(),
+                KeyCode::Left => editor.cursor_left(),
+                KeyCode::Right => editor.cursor_right(),
+                KeyCode::Char(c) => editor.insert_char(c),
+                _ => {}
+            }
+            return;
+        }
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => editor.close(),
+            KeyCode::Up | KeyCode::Char('k') => editor.move_up(),
+            KeyCode::Down | KeyCode::Char('j') => editor.move_down(),
+            KeyCode::Enter | KeyCode::Char('e') => editor.start_edit(),
+            KeyCode::Char('a') => editor.start_add(),
+            KeyCode::Char('d') | KeyCode::Delete => editor.delete_selected(),
+            KeyCode::Char('r') => editor.reload(),
+            _ => {}
+        }
+    }
+
+    /// Open the viewer on the existing files mentioned in the last AI
+    /// response, its reasoning and its tool results
+    fn open_file_references(&mut self) {
+        let Some(block) = self
+            .app
+            .blocks
+            .iter()
+            .rev()
+            .find(|b| matches!(b.block_type, BlockType::AiQuery))
+        else {
+            return;
+        };
+
+        let mut texts = vec![block.output.get_text()];
+        let mut diffs = Vec::new();
+        let mut edited = Vec::new();
+        for child in &block.children {
+            texts.push(child.output.get_text());
+            texts.extend(child.reasoning.clone());
+            if let Some(diff) = &child.diff {
+                edited.push(PathRef {
+                    path: diff.path.clone(),
+                    line: None,
+                    code: false,
+                });
+                diffs.push(diff.clone());
+            }
+        }
+
+        let project = &self.app.project_path;
+        let mut refs: Vec<PathRef> = Vec::new();
+        let found = texts.iter().flat_map(|text| find_path_refs(text));
+        for reference in found.chain(edited) {
+            let seen = refs
+                .iter()
+                .any(|r| r.path == reference.path && r.line == reference.line);
+            if !seen && reference.exists_in(project) {
+                refs.push(reference);
+            }
+        }
+        if refs.is_empty() {
+            return;
+        }
+        self.app.file_viewer.open_refs(refs, &self.app.project_path, diffs);
+        self.app.mark_dirty();
+    }
+
+    /// Keys for the file viewer: pick a reference, then scroll the file
+    fn handle_file_viewer_key(&mut self, code: KeyCode) {
+        let viewer = &mut self.app.file_viewer;
+        self.app.needs_redraw = true;
+
+        if viewer.open.is_some() {
+            match code {
+                KeyCode::Esc | KeyCode::Backspace => viewer.back(),
+                KeyCode::Char('q') => viewer.close(),
+                KeyCode::Up | KeyCode::Char('k') => viewer.scroll_by(-1),
+                KeyCode::Down | KeyCode::Char('j') => viewer.scroll_by(1),
+                KeyCode::PageUp => viewer.scroll_by(-20),
+                KeyCode::PageDown | KeyCode::Char(' ') => viewer.scroll_by(20),
+                KeyCode::Home | KeyCode::Char('g') => viewer.scroll_by(isize::MIN),
+                KeyCode::End | KeyCode::Char('G') => viewer.scroll_by(isize::MAX),
+                KeyCode::Tab | KeyCode::Char('d') => viewer.toggle_diff(),
+                _ => {}
+            }
+            return;
+        }
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') => viewer.close(),
+            KeyCode::Up | KeyCode::Char('k') => viewer.move_up(),
+            KeyCode::Down | KeyCode::Char('j') => viewer.move_down(),
+            KeyCode::Enter => viewer.open_selected(),
+            _ => {}
+        }
+    }
+
+    /// List MCP resources or prompts via the server
+    fn execute_mcp_command(&mut self, args: &str, tx: mpsc::UnboundedSender<AiUpdate>) {
+        let prompt = self.app.current_prompt();
+        let Some(client) = self.app.client.clone().filter(|_| self.app.ai_connected) else {
+            let block =
+                CommandBlock::system("AI not connected. Run /connect first.".to_string(), prompt);
+            self.app.add_block(block);
+            return;
+        };
+
+        let action = args.split_whitespace().next().unwrap_or("resources").to_lowercase();
+
+        let block = CommandBlock::system("Loading MCP servers...".to_string(), prompt);
+        let block_id = block.id.clone();
+        self.app.add_block(block);
+
+        tokio::spawn(async move {
+            let client = client.lock().await;
+            let result = match action.as_str() {
+                "resources" | "resource" | "res" => client
+                    .list_mcp_resources()
+                    .await
+                    .map(|resources| format_mcp_resources(&resources)),
+                "prompts" | "prompt" => client
+                    .list_mcp_prompts()
+                    .await
+                    .map(|prompts| format_mcp_prompts(&prompts)),
+                "status" => client
+                    .mcp_status()
+                    .await
+                    .map(|statuses| format_mcp_status(&statuses)),
+                _ => Err(anyhow::anyhow!("Usage: /mcp [resources | prompts | status]")),
+            };
+
+            let text = match result {
+                Ok(text) => text,
+                Err(e) => format!("❌ {}", e),
+            };
+            let _ = tx.send(AiUpdate::Response { block_id, text });
+        });
+    }
+
+    /// Render an MCP server prompt and send it to the AI
+    async fn execute_mcp_prompt(
+        &mut self,
+        server: &str,
+        name: &str,
+        args: &str,
+        tx: mpsc::UnboundedSender<AiUpdate>,
+    ) -> Result<()> {
+        let display = format!("/mcp:{}:{} {}", server, name, args).trim_end().to_string();
+        let rendered = match self.app.client.clone().filter(|_| self.app.ai_connected) {
+            Some(client) => client.lock().await.get_mcp_prompt(server, name, args).await,
+            None => Err(anyhow::anyhow!("AI not connected. Run /connect first.")),
+        };
+
+        match rendered {
+            Ok(prompt) => {
+                let overrides = QueryOverrides {
+                    display: Some(display),
+                    ..Default::default()
+                };
+                self.execute_ai_query_with_overrides(&prompt, overrides, tx)
+                    .await
+            }
+            Err(e) => {
+                let prompt = self.app.current_prompt();
+                let mut block = CommandBlock::new(display, BlockType::SystemMessage, prompt);
+                block.fail(format!("❌ {}", e), String::new(), 1);
+                self.app.add_block(block);
+                Ok(())
+            }
+        }
+    }
+
+    /// Render a custom command and send it to the AI, showing validation errors inline
+    async fn execute_custom_command(
+        &mut self,
+        input: &str,
+        tx: mpsc::UnboundedSender<AiUpdate>,
+    ) -> Result<()> {
+        let rendered = match CustomCommandManager::new(self.app.project_path.clone()).await {
+            Ok(manager) => match manager.parse_invocation(input) {
+                Some((command, args)) => command
+                    .render(args, &CommandContext::default())
+                    .map(|prompt| (prompt, command.allowed_tools.clone(), command.model.clone())),
+                None => Err(anyhow::anyhow!("Command not found")),
+            },
+            Err(e) => Err(e),
+        };
+
+        match rendered {
+            Ok((prompt, allowed_tools, model)) => {
+                // `!` snippets run on the server, behind the session's permission checks
+                let (prompt, shell_snippets) = extract_shell_snippets(&prompt);
+                let overrides = QueryOverrides {
+                    display: Some(input.to_string()),
+                    message: MessageOverrides {
+                        allowed_tools: (!allowed_tools.is_empty()).then_some(allowed_tools),
+                        model,
+                        shell_snippets,
+                    },
+                };
+                self.execute_ai_query_with_overrides(&prompt, overrides, tx)
+                    .await
+            }
+            Err(e) => {
+                let prompt = self.app.current_prompt();
+                let mut block =
+                    CommandBlock::new(input.to_string(), BlockType::SystemMessage, prompt);
+                block.fail(format!("❌ {}", e), String::new(), 1);
+                self.app.add_block(block);
+                Ok(())
+            }
+        }
+    }
+
     /// Execute an AI query (natural language, possibly with @file context)
     async fn execute_ai_query(
         &mut self,
         input: &str,
         tx: mpsc::UnboundedSender<AiUpdate>,
+    ) -> Result<()> {
+        self.execute_ai_query_with_overrides(input, QueryOverrides::default(), tx)
+            .await
+    }
+
+    async fn execute_ai_query_with_overrides(
+        &mut self,
+        input: &str,
+        overrides: QueryOverrides,
+        tx: mpsc::UnboundedSender<AiUpdate>,
     ) -> Result<()> {
         if !self.app.ai_connected {
             let prompt = self.app.current_prompt();
@@ -2866,12 +4257,27 @@ Keyboard:
             }
         }
 
+        let attachments: Vec<AttachmentInput> = self
+            .app
+            .take_attachments()
+            .into_iter()
+            .map(|a| AttachmentInput {
+                path: a.source,
+                content: Some(a.content),
+            })
+            .collect();
+
         // Create AI query block
-        let display_input = if file_patterns.is_empty() {
+        let mut display_input = if let Some(ref display) = overrides.display {
+            display.clone()
+        } else if file_patterns.is_empty() {
             input.to_string()
         } else {
             format!("{} (with {} file(s))", query, file_patterns.len())
         };
+        if !attachments.is_empty() {
+            display_input.push_str(&format!(" 📎 {}", attachments.len()));
+        }
 
         let prompt = self.app.current_prompt();
         let block = CommandBlock::new(display_input.clone(), BlockType::AiQuery, prompt);
@@ -2896,6 +4302,20 @@ Keyboard:
             )
         };
 
+        if let Some(demo) = &self.demo {
+            let demo = Arc::clone(demo);
+            let (event_tx, event_rx) = mpsc::unbounded_channel();
+            tokio::spawn(async move {
+                demo.lock().await.run_turn(&query, event_tx).await;
+            });
+            let ai_tx = tx.clone();
+            tokio::spawn(async move {
+                forward_server_events_to_ai_updates(event_rx, block_id.clone(), ai_tx.clone()).await;
+                let _ = ai_tx.send(AiUpdate::Complete { block_id });
+            });
+            return Ok(());
+        }
+
         // Use HTTP client for async task
         if let Some(client) = &self.app.client {
             let client: Arc<Mutex<SafeCoderClient>> = Arc::clone(client);
@@ -2934,7 +4354,10 @@ Keyboard:
 
                 // Send message via HTTP
                 tracing::debug!("AI query: Sending message to server");
-                if let Err(e) = client_guard.send_message(&full_query).await {
+                if let Err(e) = client_guard
+                    .send_message_with_attachments(&full_query, attachments, overrides.message)
+                    .await
+                {
                     tracing::error!("AI query: Failed to send message: {}", e);
                     let _ = ai_tx.send(AiUpdate::Error {
                         block_id: block_id_clone,
@@ -3011,6 +4434,8 @@ Keyboard:
                     "Connected to AI via HTTP, session_id: {}",
                     session_response.id
                 );
+                crate::crash::record_server_session(Some(&session_response.id));
+                self.app.capabilities = client.get_capabilities().await.ok();
                 self.app.client = Some(Arc::new(Mutex::new(client)));
                 self.app.set_ai_connected(true);
                 block.complete(
@@ -3039,6 +4464,122 @@ Keyboard:
         Ok(())
     }
 
+    /// Sessions running on the server, for attaching with `/connect <id>`
+    async fn list_server_sessions(&mut self) {
+        let prompt = self.app.current_prompt();
+        let mut block = CommandBlock::new("connect list".to_string(), BlockType::AiQuery, prompt);
+
+        if !self.server_manager.is_running().await {
+            block.complete("No server is running.".to_string(), 0);
+            self.app.add_block(block);
+            return;
+        }
+
+        let client = SafeCoderClient::new(self.server_manager.port());
+        match client.list_sessions().await {
+            Ok(sessions) if sessions.is_empty() => {
+                block.complete("No sessions are running on the server.".to_string(), 0);
+            }
+            Ok(sessions) => {
+                let current = self.app.client.as_ref().and_then(|c| {
+                    c.try_lock().ok().and_then(|c| c.session_id().map(str::to_string))
+                });
+                let mut output = String::from("Running sessions:\n");
+                for session in &sessions {
+                    let marker = if current.as_deref() == Some(session.id.as_str()) { "*" } else { " " };
+                    let status = if session.is_processing { "busy" } else { "idle" };
+                    output.push_str(&format!(
+                        "{} {}  {}  {}, {} client(s)  {}\n",
+                        marker,
+                        &session.id[..session.id.len().min(8)],
+                        session.project_path,
+                        status,
+                        session.clients,
+                        session.created_at,
+                    ));
+                }
+                output.push_str("\nAttach with /connect <id>");
+                block.complete(output, 0);
+            }
+            Err(e) => block.fail(format!("Failed to list sessions: {}", e), String::new(), 1),
+        }
+        self.app.add_block(block);
+    }
+
+    /// Attach to a session another client (e.g. the desktop app) created
+    ///
+    /// If it is working on a message, the turn so far is replayed and
+    /// followed live in a new block.
+    async fn attach_ai(&mut self, id: &str, tx: mpsc::UnboundedSender<AiUpdate>) {
+        let prompt = self.app.current_prompt();
+        let mut block = CommandBlock::new(format!("connect {}", id), BlockType::AiQuery, prompt);
+
+        if self.app.ai_connected {
+            block.complete("Already connected to AI. Run /disconnect first.".to_string(), 0);
+            self.app.add_block(block);
+            return;
+        }
+        if let Err(e) = self.server_manager.ensure_running().await {
+            block.fail(format!("Failed to start server: {}", e), String::new(), 1);
+            self.app.add_block(block);
+            return;
+        }
+
+        let mut client = SafeCoderClient::new(self.server_manager.port());
+        let session = match client.attach_session(id).await {
+            Ok(session) => session,
+            Err(e) => {
+                block.fail(e.to_string(), "List sessions with /connect list".to_string(), 1);
+                self.app.add_block(block);
+                return;
+            }
+        };
+        tracing::info!("Attached to session {}", session.id);
+        crate::crash::record_server_session(Some(&session.id));
+        self.app.capabilities = client.get_capabilities().await.ok();
+        let client = Arc::new(Mutex::new(client));
+        self.app.client = Some(Arc::clone(&client));
+        self.app.set_ai_connected(true);
+        block.complete(
+            format!(
+                "Attached to session {} in {} ({} other client(s)). /disconnect detaches without ending it.",
+                &session.id[..session.id.len().min(8)],
+                session.project_path,
+                session.clients
+            ),
+            0,
+        );
+        self.app.add_block(block);
+
+        if !session.is_processing {
+            return;
+        }
+        let prompt = self.app.current_prompt();
+        let block = CommandBlock::new("(turn in progress)".to_string(), BlockType::AiQuery, prompt);
+        let block_id = block.id.clone();
+        self.app.add_block(block);
+        self.app.set_ai_thinking(true);
+        tokio::spawn(async move {
+            let event_rx = client
+                .lock()
+                .await
+                .subscribe_events_with_replay(usize::MAX)
+                .await;
+            match event_rx {
+                Ok(event_rx) => {
+                    forward_server_events_to_ai_updates(event_rx, block_id.clone(), tx.clone()).await;
+                    let _ = tx.send(AiUpdate::Complete { block_id });
+                }
+                Err(e) => {
+                    let _ = tx.send(AiUpdate::Error {
+                        block_id,
+                        message: format!("Failed to subscribe to events: {}", e),
+                    });
+                }
+            }
+        });
+    }
+
     /// Disconnect from AI service
     async fn disconnect_ai(&mut self) {
         let prompt = self.app.current_prompt();
@@ -3052,6 +4593,7 @@ Keyboard:
             }
             self.app.client = None;
             self.app.set_ai_connected(false);
+            crate::crash::record_server_session(None);
             block.complete("Disconnected from AI.".to_string(), 0);
         } else {
             block.complete("Not connected to AI.".to_string(), 0);
@@ -3092,6 +4634,10 @@ async fn forward_server_events_to_ai_updates(
                 block_id: block_id.clone(),
                 text,
             },
+            ServerEvent::ModelReasoning { text } => AiUpdate::ModelReasoning {
+                block_id: block_id.clone(),
+                text,
+            },
             ServerEvent::ToolStart { name, description } => AiUpdate::ToolStart {
                 block_id: block_id.clone(),
                 tool_name: name,
@@ -3166,6 +4712,7 @@ async fn forward_server_events_to_ai_updates(
                         .into_iter()
                         .map(|s| PlanStep::new(s.id, s.description))
                         .collect(),
+                    risks: Vec::new(),
                     created_at: chrono::Utc::now(),
                     started_at: None,
                     completed_at: None,
@@ -3201,6 +4748,8 @@ async fn forward_server_events_to_ai_updates(
                 AiUpdate::PlanAwaitingApproval { plan_id }
             }
             ServerEvent::PlanApproved { .. } | ServerEvent::PlanRejected { .. } => continue,
+            // The TUI finds file references itself (Enter on an empty prompt)
+            ServerEvent::FileLinks { .. } => continue,
             ServerEvent::TokenUsage {
                 input_tokens,
                 output_tokens,
@@ -3217,14 +4766,72 @@ async fn forward_server_events_to_ai_updates(
                 block_id: block_id.clone(),
                 tokens_compressed,
             },
-            ServerEvent::DoomLoopPrompt { prompt_id, message } => AiUpdate::DoomLoopPrompt {
+            ServerEvent::DoomLoopPrompt {
+                prompt_id,
+                message,
+                timeout_secs,
+            } => AiUpdate::DoomLoopPrompt {
                 prompt_id,
                 message,
+                timeout_secs,
+            },
+            ServerEvent::ToolApprovalPrompt {
+                prompt_id,
+                tool,
+                description,
+                high_risk,
+                origin,
+                always_ask,
+            } => AiUpdate::ToolApprovalPrompt {
+                prompt_id,
+                tool,
+                description,
+                high_risk,
+                origin,
+                always_ask,
             },
             ServerEvent::Error { message } => AiUpdate::Error {
                 block_id: block_id.clone(),
                 message,
             },
+            ServerEvent::SkillActivated { name, file } => AiUpdate::SkillActivated {
+                block_id: block_id.clone(),
+                name,
+                file,
+            },
+            ServerEvent::RateLimited {
+                provider,
+                retry_in_secs,
+                attempt,
+                max_retries,
+            } => AiUpdate::RateLimited {
+                block_id: block_id.clone(),
+                provider,
+                retry_in_secs,
+                attempt,
+                max_retries,
+            },
+            ServerEvent::MonitorNotification {
+                command,
+                summary,
+                needs_attention,
+                ..
+            } => AiUpdate::MonitorNotification {
+                block_id: block_id.clone(),
+                command,
+                summary,
+                needs_attention,
+            },
+            ServerEvent::McpServerHealth {
+                server,
+                status,
+                message,
+            } => AiUpdate::McpServerHealth {
+                block_id: block_id.clone(),
+                server,
+                status,
+                message,
+            },
             ServerEvent::TodoList { todos } => AiUpdate::TodoList {
                 block_id: block_id.clone(),
                 todos: todos.into_iter().map(|t| crate::tools::todo::TodoItem {
@@ -3274,6 +4881,100 @@ async fn forward_server_events_to_ai_updates(
     }
 }
 
+/// Format skills for `/skill list`
+fn format_skill_list(skills: &[SkillDto]) -> String {
+    if skills.is_empty() {
+        return "No skills available. Add .md files to .safe-coder/skills/".to_string();
+    }
+
+    let mut output = String::from("📚 Skills (● active):\n\n");
+    for skill in skills {
+        let status = if skill.active { "●" } else { "○" };
+        let triggers = if skill.triggers.is_empty() {
+            "manual".to_string()
+        } else {
+            skill.triggers.join(", ")
+        };
+        output.push_str(&format!(
+            "  {} {:<18} {} (triggers: {}, ~{} tokens)\n",
+            status,
+            skill.name,
+            skill.description.as_deref().unwrap_or(""),
+            triggers,
+            skill.tokens
+        ));
+    }
+    output.push_str("\nSkills activate when the AI reads or edits matching files. Use /skill on|off <name> to toggle.");
+    output
+}
+
+/// Format MCP resources for `/mcp resources`
+fn format_mcp_resources(resources: &[McpResourceDto]) -> String {
+    if resources.is_empty() {
+        return "No MCP resources available. Configure servers under [mcp] in your config.".to_string();
+    }
+
+    let mut output = String::from("🔌 MCP resources:\n\n");
+    for resource in resources {
+        output.push_str(&format!(
+            "  @mcp:{}/{}  {}\n",
+            resource.server,
+            resource.uri,
+            resource.description.as_deref().unwrap_or(&resource.name)
+        ));
+    }
+    output.push_str("\nMention a resource in your message to attach its contents.");
+    output
+}
+
+/// Format MCP server health for `/mcp status`
+fn format_mcp_status(statuses: &[McpServerStatusDto]) -> String {
+    if statuses.is_empty() {
+        return "No MCP servers configured.".to_string();
+    }
+
+    let mut output = String::from("🔌 MCP servers:\n\n");
+    for status in statuses {
+        let icon = if status.state == "connected" { "●" } else { "○" };
+        let mut details = vec![status.state.clone()];
+        if let Some(latency) = status.latency_ms {
+            details.push(format!("{}ms", latency));
+        }
+        if status.tool_count > 0 {
+            details.push(format!("{} tool(s)", status.tool_count));
+        }
+        if status.restarts > 0 {
+            details.push(format!("{} restart(s)", status.restarts));
+        }
+        output.push_str(&format!(
+            "  {} {}  {}\n      {} {}\n",
+            icon,
+            status.name,
+            details.join(", "),
+            status.transport,
+            status.endpoint
+        ));
+    }
+    output
+}
+
+/// Format MCP prompts for `/mcp prompts`
+fn format_mcp_prompts(prompts: &[McpPromptDto]) -> String {
+    if prompts.is_empty() {
+        return "No MCP prompts available. Configure servers under [mcp] in your config.".to_string();
+    }
+
+    let mut output = String::from("🔌 MCP prompts:\n\n");
+    for prompt in prompts {
+        output.push_str(&format!(
+            "  {}  {}\n",
+            prompt.usage,
+            prompt.description.as_deref().unwrap_or("")
+        ));
+    }
+    output
+}
+
 /// Execute a command asynchronously and stream output
 async fn execute_command_async(
     command: String,
@@ -3282,9 +4983,7 @@ async fn execute_command_async(
     block_id: String,
     tx: mpsc::UnboundedSender<CommandUpdate>,
 ) -> Result<()> {
-    let mut child = TokioCommand::new("sh")
-        .arg("-c")
-        .arg(&command)
+    let mut child = crate::platform::shell_command(&command)
         .current_dir(&cwd)
         .envs(&env_vars)
         .stdout(Stdio::piped())
@@ -3343,6 +5042,12 @@ async fn execute_command_async(
 }
 
 /// Run the shell TUI (convenience function)
+/// Run the shell TUI with the scripted demo backend
+pub async fn run_shell_demo(project_path: PathBuf) -> Result<()> {
+    let config = Config::load().unwrap_or_default();
+    ShellTuiRunner::new(project_path, config).with_demo().run().await
+}
+
 pub async fn run_shell_tui(project_path: PathBuf, auto_connect_ai: bool) -> Result<()> {
     let config = Config::load()?;
     let mut runner = ShellTuiRunner::new(project_path, config);
@@ -24,7 +24,9 @@ use super::markdown::{has_markdown, render_markdown_lines};
 use super::shell_app::{BlockOutput, BlockType, CommandBlock, FileDiff, ShellTuiApp};
 
 use super::sidebar::{PlanStepDisplay, TodoPlanDisplay, ToolStepStatus};
+use crate::output_filters::{CollapseCodeBlocks, OutputFilter};
 use crate::planning::PlanStepStatus;
+use crate::session::attachments::AttachmentKind;
 
 // ============================================================================
 // Color Palette - OpenCode inspired, dark and minimal
@@ -46,6 +48,7 @@ const BG_PRIMARY: Color = Color::Rgb(0, 0, 0); // Pure black background
 const BG_BLOCK: Color = Color::Rgb(15, 15, 15); // Slightly lighter for blocks
 const BG_INPUT: Color = Color::Rgb(20, 20, 20); // Input area
 const BG_STATUS: Color = Color::Rgb(10, 10, 10); // Status bar
+const BG_CHIP: Color = Color::Rgb(40, 40, 45); // Attachment chips
 
 const BORDER_SUBTLE: Color = Color::Rgb(40, 40, 45); // Subtle borders
 
@@ -191,6 +194,14 @@ pub fn draw(f: &mut Frame, app: &mut ShellTuiApp) {
         draw_file_picker_popup(f, app, size);
     }
 
+    if app.memory_editor.visible {
+        draw_memory_editor_popup(f, app, size);
+    }
+
+    if app.file_viewer.visible {
+        draw_file_viewer_popup(f, app, size);
+    }
+
     // Command autocomplete popup (for slash commands)
     if app.command_autocomplete.visible && !app.command_autocomplete.suggestions.is_empty() {
         draw_command_autocomplete_popup(f, app, size);
@@ -218,6 +229,11 @@ pub fn draw(f: &mut Frame, app: &mut ShellTuiApp) {
     if app.pending_tool_approval.is_some() {
         draw_tool_approval_modal(f, app, size);
     }
+
+    // Doom loop prompt blocks the running tool until answered
+    if app.has_doom_loop_prompt() {
+        draw_doom_loop_modal(f, app, size);
+    }
 }
 
 fn calculate_input_height(app: &ShellTuiApp) -> u16 {
@@ -237,7 +253,9 @@ fn calculate_input_height(app: &ShellTuiApp) -> u16 {
     };
     // Max 3 lines, minimal padding
     let lines = wrapped_count.min(3) as u16;
-    lines + 1 // just top border
+    // One more for the attachment chips
+    let chips = u16::from(app.has_attachments());
+    lines + chips + 1 // just top border
 }
 
 // ============================================================================
@@ -342,6 +360,7 @@ fn draw_messages(f: &mut Frame, app: &mut ShellTuiApp, area: Rect) {
             todos,
             &elapsed_str,
             total_tokens,
+            app.config.output.collapse_code_lines,
         );
         all_lines.push(MessageLine::Empty);
     }
@@ -858,6 +877,7 @@ fn render_block(
     todos: Option<&Vec<crate::tools::todo::TodoItem>>,
     _elapsed_str: &str,
     _total_tokens: usize,
+    collapse_code_lines: usize,
 ) {
     match &block.block_type {
         BlockType::SystemMessage => {
@@ -956,7 +976,14 @@ fn render_block(
             }
 
             // Final AI response - render with markdown if detected
-            let text = block.output.get_text();
+            let mut text = block.output.get_text();
+            if !block.expanded {
+                let collapse = CollapseCodeBlocks {
+                    max_lines: collapse_code_lines,
+                    hint: " (Ctrl+O to expand)",
+                };
+                text = collapse.apply(&text);
+            }
             if !text.is_empty() {
                 lines.push(MessageLine::Empty);
                 lines.push(MessageLine::BlockStart);
@@ -1074,6 +1101,8 @@ fn render_block(
             }
         }
 
+        BlockType::AiThought => render_thought(lines, block, width),
+
         BlockType::Subagent { kind } => {
             // Render subagent like an AI tool execution
             lines.push(MessageLine::UserHeader {
@@ -1237,10 +1266,45 @@ fn render_child_block(
             }
         }
 
+        BlockType::AiThought => render_thought(lines, block, width),
+
         _ => {}
     }
 }
 
+/// Model thinking: a one-line summary while collapsed, the full text when expanded
+fn render_thought(lines: &mut Vec<MessageLine>, block: &CommandBlock, width: usize) {
+    let text = block.output.get_text();
+    let line_count = text.lines().filter(|l| !l.trim().is_empty()).count();
+    if line_count == 0 {
+        return;
+    }
+    lines.push(MessageLine::Empty);
+    if block.collapsed {
+        lines.push(MessageLine::ThinkingText {
+            text: format!(
+                "💭 Thought for {} line{} (Ctrl+O to expand)",
+                line_count,
+                if line_count == 1 { "" } else { "s" }
+            ),
+        });
+        return;
+    }
+    lines.push(MessageLine::ThinkingText {
+        text: "💭 Thought".to_string(),
+    });
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        for wrapped in wrap(line, width.saturating_sub(6)) {
+            lines.push(MessageLine::ThinkingText {
+                text: wrapped.to_string(),
+            });
+        }
+    }
+}
+
 fn render_output(lines: &mut Vec<MessageLine>, output: &BlockOutput, width: usize) {
     render_output_with_limit(lines, output, width, Some(20))
 }
@@ -1425,7 +1489,7 @@ fn render_tool_output_claude_style(
                 format!("{} matches", matches)
             }
         }
-        "todowrite" | "todoread" => {
+        "todowrite" | "todoupdate" | "todoread" => {
             "Updated".to_string()
         }
         _ => {
@@ -1591,11 +1655,11 @@ fn draw_input_area(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
     let inner = block.inner(area);
     f.render_widget(block, area);
 
-    // Check for attached images and adjust available height
-    let has_images = app.has_attached_images();
+    // Check for attachments and adjust available height
+    let has_chips = app.has_attachments();
 
-    // Split inner area if we have images
-    let (image_area, input_inner) = if has_images && inner.height > 1 {
+    // Split inner area if we have attachments
+    let (chip_area, input_inner) = if has_chips && inner.height > 1 {
         let chunks = ratatui::layout::Layout::default()
             .direction(ratatui::layout::Direction::Vertical)
             .constraints([
@@ -1608,27 +1672,28 @@ fn draw_input_area(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
         (None, inner)
     };
 
-    // Render image indicator if present
-    if let Some(img_area) = image_area {
-        let count = app.attached_images.len();
-        let size = app.attached_images_size_display();
-        let image_spans = vec![
-            Span::styled("📎 ", Style::default().fg(ACCENT_YELLOW)),
-            Span::styled(
-                format!("{} image{} attached", count, if count == 1 { "" } else { "s" }),
-                Style::default().fg(ACCENT_YELLOW),
-            ),
-            Span::styled(
-                format!(" ({})", size),
-                Style::default().fg(TEXT_DIM),
-            ),
-            Span::styled(
-                " - Press Ctrl+Shift+V to clear",
-                Style::default().fg(TEXT_MUTED),
-            ),
-        ];
-        let image_line = Paragraph::new(Line::from(image_spans));
-        f.render_widget(image_line, img_area);
+    // Render a chip per attachment
+    if let Some(chip_area) = chip_area {
+        let mut chip_spans = Vec::new();
+        for attachment in &app.attachments {
+            chip_spans.push(Span::styled(
+                format!(" {} {} ", attachment.icon(), attachment.label()),
+                Style::default().fg(ACCENT_YELLOW).bg(BG_CHIP),
+            ));
+            chip_spans.push(Span::raw(" "));
+        }
+        let has_images = app
+            .attachments
+            .iter()
+            .any(|a| a.kind == AttachmentKind::Image);
+        let hint = if has_images {
+            " - /attach clear, Ctrl+Shift+V to clear images"
+        } else {
+            " - /attach clear to remove"
+        };
+        chip_spans.push(Span::styled(hint, Style::default().fg(TEXT_MUTED)));
+        let chip_line = Paragraph::new(Line::from(chip_spans));
+        f.render_widget(chip_line, chip_area);
     }
 
     let available_width = input_inner.width.saturating_sub(3) as usize; // Account for "> " prefix
@@ -2580,6 +2645,251 @@ fn draw_model_picker_popup(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
     f.render_widget(help, help_area);
 }
 
+fn draw_memory_editor_popup(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
+    let editor = &app.memory_editor;
+    let width = (area.width * 4 / 5).max(40).min(area.width);
+    let height = (area.height * 3 / 4).max(10).min(area.height);
+    let popup_area = Rect {
+        x: (area.width.saturating_sub(width)) / 2,
+        y: (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" Project Memory (.safe-coder/SAFE_CODER.md) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(ACCENT_MAGENTA))
+        .style(Style::default().bg(BG_BLOCK));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let edit_height = match &editor.editing {
+        Some(edit) => (edit.buffer.lines().count().max(1) as u16 + 2).min(inner.height / 2),
+        None => 0,
+    };
+    let chunks = Layout::vertical([
+        Constraint::Min(1),
+        Constraint::Length(edit_height),
+        Constraint::Length(1),
+    ])
+    .split(inner);
+
+    // Entry list, scrolled to keep the selection visible
+    let list_area = chunks[0];
+    if editor.entries.is_empty() {
+        let empty = Paragraph::new("No entries yet. Press a to add one, or run /init.")
+            .style(Style::default().fg(TEXT_MUTED));
+        f.render_widget(empty, list_area);
+    } else {
+        let visible = list_area.height as usize;
+        let offset = editor.selected.saturating_sub(visible.saturating_sub(1));
+        let text_width = (list_area.width as usize).saturating_sub(18);
+        let items: Vec<ListItem> = editor
+            .entries
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(visible)
+            .map(|(i, entry)| {
+                let section = entry.section.as_deref().unwrap_or("(top)");
+                let first_line = entry.text.lines().next().unwrap_or_default();
+                let more = if entry.text.lines().count() > 1 { " …" } else { "" };
+                let style = if i == editor.selected {
+                    Style::default()
+                        .fg(BG_PRIMARY)
+                        .bg(ACCENT_MAGENTA)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(TEXT_PRIMARY)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<15} ", truncate_str(section, 15)),
+                        Style::default().fg(ACCENT_CYAN),
+                    ),
+                    Span::styled(format!("{}{}", truncate_str(first_line, text_width), more), style),
+                ]))
+            })
+            .collect();
+        f.render_widget(List::new(items), list_area);
+    }
+
+    // Text of the entry being edited, with a cursor marker
+    if let Some(edit) = &editor.editing {
+        let mut text = edit.buffer.clone();
+        text.insert(edit.cursor, '▏');
+        let title = match &edit.original {
+            Some(_) => " Edit entry ".to_string(),
+            None => format!(" New entry in {} ", edit.section.as_deref().unwrap_or("(top)")),
+        };
+        let edit_block = Block::default()
+            .title(title)
+            .borders(Borders::TOP)
+            .border_style(Style::default().fg(ACCENT_CYAN));
+        let para = Paragraph::new(text)
+            .block(edit_block)
+            .style(Style::default().fg(TEXT_PRIMARY))
+            .wrap(Wrap { trim: false });
+        f.render_widget(para, chunks[1]);
+    }
+
+    // Status or key help
+    let help = match (&editor.status, &editor.editing) {
+        (Some(status), _) => Line::from(Span::styled(status.clone(), Style::default().fg(ACCENT_YELLOW))),
+        (None, Some(_)) => Line::from(vec![
+            Span::styled("Enter", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" save  ", Style::default().fg(TEXT_MUTED)),
+            Span::styled("Alt+Enter", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" new line  ", Style::default().fg(TEXT_MUTED)),
+            Span::styled("Esc", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" cancel", Style::default().fg(TEXT_MUTED)),
+        ]),
+        (None, None) => Line::from(vec![
+            Span::styled("↑↓", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" navigate  ", Style::default().fg(TEXT_MUTED)),
+            Span::styled("Enter", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" edit  ", Style::default().fg(TEXT_MUTED)),
+            Span::styled("a", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" add  ", Style::default().fg(TEXT_MUTED)),
+            Span::styled("d", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" delete  ", Style::default().fg(TEXT_MUTED)),
+            Span::styled("Esc", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" close", Style::default().fg(TEXT_MUTED)),
+        ]),
+    };
+    f.render_widget(Paragraph::new(help), chunks[2]);
+}
+
+fn draw_file_viewer_popup(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
+    let viewer = &app.file_viewer;
+    let width = (area.width * 9 / 10).max(40).min(area.width);
+    let height = (area.height * 4 / 5).max(10).min(area.height);
+    let popup_area = Rect {
+        x: (area.width.saturating_sub(width)) / 2,
+        y: (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let title = match &viewer.open {
+        Some(file) if file.show_diff => format!(" {} (diff) ", file.path),
+        Some(file) => match file.line {
+            Some(line) => format!(" {}:{} ", file.path, line),
+            None => format!(" {} ", file.path),
+        },
+        None => " Referenced files ".to_string(),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(ACCENT_MAGENTA))
+        .style(Style::default().bg(BG_BLOCK));
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let chunks = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+    let body = chunks[0];
+    let visible = body.height as usize;
+
+    let lines: Vec<Line> = match &viewer.open {
+        Some(file) => {
+            let shown = file.shown_lines();
+            let number_width = shown.len().to_string().len();
+            shown
+                .iter()
+                .enumerate()
+                .skip(file.scroll)
+                .take(visible)
+                .map(|(i, text)| {
+                    if file.show_diff {
+                        let color = match text.chars().next() {
+                            Some('+') if !text.starts_with("+++") => ACCENT_GREEN,
+                            Some('-') if !text.starts_with("---") => ACCENT_RED,
+                            Some('@') => ACCENT_CYAN,
+                            _ => TEXT_PRIMARY,
+                        };
+                        return Line::from(Span::styled(text.clone(), Style::default().fg(color)));
+                    }
+                    let target = file.line == Some(i + 1);
+                    let text_style = if target {
+                        Style::default().fg(ACCENT_YELLOW).bg(BORDER_SUBTLE)
+                    } else {
+                        Style::default().fg(TEXT_PRIMARY)
+                    };
+                    Line::from(vec![
+                        Span::styled(
+                            format!("{:>width$} ", i + 1, width = number_width),
+                            Style::default().fg(if target { ACCENT_YELLOW } else { TEXT_MUTED }),
+                        ),
+                        Span::styled(text.replace('\t', "    "), text_style),
+                    ])
+                })
+                .collect()
+        }
+        None => {
+            let offset = viewer.selected.saturating_sub(visible.saturating_sub(1));
+            viewer
+                .refs
+                .iter()
+                .enumerate()
+                .skip(offset)
+                .take(visible)
+                .map(|(i, reference)| {
+                    let style = if i == viewer.selected {
+                        Style::default()
+                            .fg(BG_PRIMARY)
+                            .bg(ACCENT_MAGENTA)
+                            .add_modifier(Modifier::BOLD)
+                    } else {
+                        Style::default().fg(TEXT_PRIMARY)
+                    };
+                    let mut text = reference.path.clone();
+                    if let Some(line) = reference.line {
+                        text.push_str(&format!(":{}", line));
+                    }
+                    Line::from(Span::styled(text, style))
+                })
+                .collect()
+        }
+    };
+    f.render_widget(Paragraph::new(lines), body);
+
+    let help = match (&viewer.status, &viewer.open) {
+        (Some(status), _) => Line::from(Span::styled(status.clone(), Style::default().fg(ACCENT_YELLOW))),
+        (None, Some(file)) => {
+            let mut spans = vec![
+                Span::styled("↑↓ PgUp PgDn", Style::default().fg(ACCENT_CYAN)),
+                Span::styled(" scroll  ", Style::default().fg(TEXT_MUTED)),
+            ];
+            if file.diff.is_some() {
+                let label = if file.show_diff { " file  " } else { " diff  " };
+                spans.push(Span::styled("Tab", Style::default().fg(ACCENT_CYAN)));
+                spans.push(Span::styled(label, Style::default().fg(TEXT_MUTED)));
+            }
+            spans.push(Span::styled("Esc", Style::default().fg(ACCENT_CYAN)));
+            spans.push(Span::styled(
+                if viewer.refs.len() > 1 { " back" } else { " close" },
+                Style::default().fg(TEXT_MUTED),
+            ));
+            Line::from(spans)
+        }
+        (None, None) => Line::from(vec![
+            Span::styled("↑↓", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" navigate  ", Style::default().fg(TEXT_MUTED)),
+            Span::styled("Enter", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" open  ", Style::default().fg(TEXT_MUTED)),
+            Span::styled("Esc", Style::default().fg(ACCENT_CYAN)),
+            Span::styled(" close", Style::default().fg(TEXT_MUTED)),
+        ]),
+    };
+    f.render_widget(Paragraph::new(help), chunks[1]);
+}
+
 fn draw_file_picker_popup(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
     let filtered = app.file_picker.filtered_entries();
     if filtered.is_empty() && app.file_picker.filter.is_empty() {
@@ -3069,7 +3379,7 @@ fn draw_tool_approval_modal(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
 
     // Calculate modal size
     let modal_width = (area.width as f32 * 0.7).min(80.0) as u16;
-    let modal_height = 14u16;
+    let modal_height = if approval.origin.is_some() { 15u16 } else { 14u16 };
 
     let popup_area = Rect {
         x: (area.width.saturating_sub(modal_width)) / 2,
@@ -3111,6 +3421,17 @@ fn draw_tool_approval_modal(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
         ),
     ]));
 
+    // Third-party tools show where they come from
+    if let Some(ref origin) = approval.origin {
+        lines.push(Line::from(vec![
+            Span::styled("From: ", Style::default().fg(TEXT_SECONDARY)),
+            Span::styled(
+                origin.as_str(),
+                Style::default().fg(ACCENT_YELLOW).add_modifier(Modifier::BOLD),
+            ),
+        ]));
+    }
+
     // Description
     lines.push(Line::from(""));
     lines.push(Line::from(vec![
@@ -3169,3 +3490,68 @@ fn draw_tool_approval_modal(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
     let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
     f.render_widget(paragraph, inner);
 }
+
+/// Draw the doom loop prompt asking whether the agent may keep going
+fn draw_doom_loop_modal(f: &mut Frame, app: &ShellTuiApp, area: Rect) {
+    let message = app.doom_loop_message.as_deref().unwrap_or_default();
+
+    let modal_width = (area.width as f32 * 0.7).min(80.0) as u16;
+    let modal_height = 12u16;
+
+    let popup_area = Rect {
+        x: (area.width.saturating_sub(modal_width)) / 2,
+        y: (area.height.saturating_sub(modal_height)) / 2,
+        width: modal_width,
+        height: modal_height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let block = Block::default()
+        .title(" 🔁 Loop Detected ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(ACCENT_YELLOW))
+        .style(Style::default().bg(BG_BLOCK));
+
+    let inner = block.inner(popup_area);
+    f.render_widget(block, popup_area);
+
+    let mut lines: Vec<Line> = Vec::new();
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        message,
+        Style::default().fg(TEXT_PRIMARY),
+    )));
+    lines.push(Line::from(""));
+
+    if let Some(remaining) = app.doom_loop_time_remaining() {
+        lines.push(Line::from(Span::styled(
+            format!("Timing out in {}s", remaining.as_secs()),
+            Style::default().fg(TEXT_DIM),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    lines.push(Line::from(vec![
+        Span::styled("  ", Style::default()),
+        Span::styled(
+            " Y ",
+            Style::default()
+                .fg(Color::Black)
+                .bg(ACCENT_GREEN)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" Continue  ", Style::default().fg(TEXT_PRIMARY)),
+        Span::styled(
+            " N ",
+            Style::default()
+                .fg(Color::Black)
+                .bg(ACCENT_RED)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" Stop", Style::default().fg(TEXT_PRIMARY)),
+    ]));
+
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+    f.render_widget(paragraph, inner);
+}
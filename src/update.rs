@@ -0,0 +1,300 @@
+//! Self-update
+//!
+//! `safe-coder update` asks GitHub for the latest release of
+//! `update.repository`, downloads the binary built for this platform and
+//! replaces the running executable with it. The release workflow publishes
+//! a `<asset>.sha256` checksum and a `<asset>.sig` Ed25519 signature next to
+//! every binary. Both must match. The release public key comes from
+//! `update.public_key` or is built in through `SAFE_CODER_RELEASE_PUBLIC_KEY`,
+//! in the format of `safe-coder policy keygen`. Without a key nothing is
+//! installed unless the user passes `--allow-unsigned`, since a checksum
+//! published next to the binary doesn't show who built it.
+//!
+//! Managed installs turn the command off with `update.enabled = false`, set
+//! in the global config or by the organization policy.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::UpdateConfig;
+
+/// Release tag this binary was built for, set by the release workflow
+/// (`v<version>-<commit>`). None for builds from source.
+pub const BUILD_TAG: Option<&str> = option_env!("SAFE_CODER_RELEASE_TAG");
+
+/// Public key release binaries are signed with, set by the release workflow
+const BUILD_PUBLIC_KEY: Option<&str> = option_env!("SAFE_CODER_RELEASE_PUBLIC_KEY");
+
+/// A GitHub release
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    fn asset(&self, name: &str) -> Result<&ReleaseAsset> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .with_context(|| format!("Release {} has no {}", self.tag_name, name))
+    }
+}
+
+/// Name of the release binary for `os` and `arch` (as in `std::env::consts`)
+pub fn asset_name(os: &str, arch: &str) -> Option<&'static str> {
+    match (os, arch) {
+        ("linux", "x86_64") => Some("safe-coder-linux-x86_64"),
+        ("macos", "x86_64") => Some("safe-coder-macos-x86_64"),
+        ("macos", "aarch64") => Some("safe-coder-macos-aarch64"),
+        ("windows", "x86_64") => Some("safe-coder-windows-x86_64.exe"),
+        _ => None,
+    }
+}
+
+/// The version in a `v<version>-<commit>` tag, as numbers
+fn tag_version(tag: &str) -> Vec<u64> {
+    let version = tag.trim_start_matches('v');
+    let version = version.split('-').next().unwrap_or(version);
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Whether `latest` should replace this build. Release builds know their
+/// tag, so any other release that isn't an older version is newer. Builds
+/// from source only know their version and take strictly newer ones.
+pub fn is_newer(latest: &str, build_tag: Option<&str>, build_version: &str) -> bool {
+    let latest_version = tag_version(latest);
+    let build_version = tag_version(build_version);
+    match build_tag {
+        Some(tag) => tag != latest && latest_version >= build_version,
+        None => latest_version > build_version,
+    }
+}
+
+fn client() -> Result<reqwest::Client> {
+    reqwest::Client::builder()
+        .user_agent("SafeCoder/1.0")
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+/// The latest release of `repository` ("owner/name")
+pub async fn latest_release(repository: &str) -> Result<Release> {
+    let url = format!(
+        "https://api.github.com/repos/{}/releases/latest",
+        repository
+    );
+    client()?
+        .get(&url)
+        .header("Accept", "application/vnd.github+json")
+        .send()
+        .await
+        .with_context(|| format!("Failed to reach {}", url))?
+        .error_for_status()
+        .with_context(|| format!("No release found for {}", repository))?
+        .json()
+        .await
+        .context("Unexpected response from the GitHub releases API")
+}
+
+async fn download(asset: &ReleaseAsset) -> Result<Vec<u8>> {
+    let bytes = client()?
+        .get(&asset.browser_download_url)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to download {}", asset.name))?
+        .bytes()
+        .await
+        .with_context(|| format!("Failed to download {}", asset.name))?;
+    Ok(bytes.to_vec())
+}
+
+/// Check `binary` against a `sha256sum`-style checksum file
+pub fn verify_checksum(binary: &[u8], checksum_file: &str) -> Result<()> {
+    let expected = checksum_file
+        .split_whitespace()
+        .next()
+        .context("Checksum file is empty")?;
+    let actual = format!("{:x}", Sha256::digest(binary));
+    if !expected.eq_ignore_ascii_case(&actual) {
+        bail!(
+            "Checksum mismatch: expected {}, downloaded {}",
+            expected,
+            actual
+        );
+    }
+    Ok(())
+}
+
+/// Check `binary` against a base64 Ed25519 signature and public key
+pub fn verify_signature(binary: &[u8], signature: &str, public_key: &str) -> Result<()> {
+    let key: [u8; 32] = STANDARD
+        .decode(public_key.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .context("Invalid release public key")?;
+    let key = VerifyingKey::from_bytes(&key).context("Invalid release public key")?;
+    let signature: [u8; 64] = STANDARD
+        .decode(signature.trim())
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .context("Release signature is malformed")?;
+    key.verify(binary, &Signature::from_bytes(&signature))
+        .context("Release signature does not match the release public key")
+}
+
+/// Download this platform's binary from `release` and verify it. Returns
+/// the binary and whether its signature was checked, which is only skipped
+/// when no release key is known and `allow_unsigned` is set.
+pub async fn download_verified(
+    release: &Release,
+    config: &UpdateConfig,
+    allow_unsigned: bool,
+) -> Result<(Vec<u8>, bool)> {
+    let public_key = config.public_key.as_deref().or(BUILD_PUBLIC_KEY);
+    if public_key.is_none() && !allow_unsigned {
+        bail!(
+            "No release public key is known, so the download's signature can't be checked. \
+             Set `public_key` under [update] in ~/.config/safe-coder/config.toml, or rerun \
+             with --allow-unsigned to install on the release's checksum alone"
+        );
+    }
+
+    let name = asset_name(std::env::consts::OS, std::env::consts::ARCH).with_context(|| {
+        format!(
+            "No release binary is built for {}-{}; build from source instead",
+            std::env::consts::OS,
+            std::env::consts::ARCH
+        )
+    })?;
+
+    let binary = download(release.asset(name)?).await?;
+    let checksum = download(release.asset(&format!("{}.sha256", name))?).await?;
+    verify_checksum(&binary, &String::from_utf8_lossy(&checksum))?;
+
+    let Some(public_key) = public_key else {
+        return Ok((binary, false));
+    };
+    let signature = download(release.asset(&format!("{}.sig", name))?).await?;
+    verify_signature(&binary, &String::from_utf8_lossy(&signature), public_key)?;
+    Ok((binary, true))
+}
+
+/// Replace the executable at `exe` with `binary`. The new binary is written
+/// next to it and renamed over it, so an interrupted update leaves the old
+/// one in place. Windows can't replace a running executable, so there the
+/// old one is first renamed to `<exe>.old`, which the next update removes.
+pub fn install(binary: &[u8], exe: &Path) -> Result<()> {
+    use std::io::Write;
+
+    let dir = exe
+        .parent()
+        .context("The executable has no parent directory")?;
+    let old = old_path(exe);
+    if old.exists() {
+        let _ = std::fs::remove_file(&old);
+    }
+
+    let mut file = tempfile::Builder::new()
+        .prefix(".safe-coder-update")
+        .tempfile_in(dir)
+        .with_context(|| {
+            format!(
+                "Can't write to {}; rerun with permission to replace {}",
+                dir.display(),
+                exe.display()
+            )
+        })?;
+    file.write_all(binary)?;
+    file.as_file().sync_all()?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    if cfg!(windows) {
+        std::fs::rename(exe, &old)
+            .with_context(|| format!("Failed to move {} aside", exe.display()))?;
+    }
+    if let Err(e) = file.persist(exe) {
+        if cfg!(windows) {
+            let _ = std::fs::rename(&old, exe);
+        }
+        return Err(e.error).with_context(|| format!("Failed to replace {}", exe.display()));
+    }
+    Ok(())
+}
+
+fn old_path(exe: &Path) -> PathBuf {
+    let mut name = exe.file_name().unwrap_or_default().to_os_string();
+    name.push(".old");
+    exe.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        // Release builds take any other release of the same or a later version
+        assert!(is_newer("v0.1.0-def456", Some("v0.1.0-abc123"), "0.1.0"));
+        assert!(is_newer("v0.2.0-def456", Some("v0.1.0-abc123"), "0.1.0"));
+        assert!(!is_newer("v0.1.0-abc123", Some("v0.1.0-abc123"), "0.1.0"));
+        assert!(!is_newer("v0.0.9-def456", Some("v0.1.0-abc123"), "0.1.0"));
+
+        // Source builds only take later versions
+        assert!(is_newer("v0.2.0-def456", None, "0.1.0"));
+        assert!(is_newer("v0.10.0-def456", None, "0.9.0"));
+        assert!(!is_newer("v0.1.0-def456", None, "0.1.0"));
+    }
+
+    #[test]
+    fn test_verify_checksum_and_signature() {
+        let binary = b"new safe-coder";
+        let checksum = format!(
+            "{}  safe-coder-linux-x86_64\n",
+            format!("{:x}", Sha256::digest(binary))
+        );
+        assert!(verify_checksum(binary, &checksum).is_ok());
+        assert!(verify_checksum(b"tampered", &checksum).is_err());
+
+        let (secret, public) = crate::policy::generate_keypair();
+        let signature = crate::policy::sign(binary, &secret).unwrap();
+        assert!(verify_signature(binary, &signature, &public).is_ok());
+        assert!(verify_signature(b"tampered", &signature, &public).is_err());
+
+        let (_, other) = crate::policy::generate_keypair();
+        assert!(verify_signature(binary, &signature, &other).is_err());
+    }
+
+    #[test]
+    fn test_install_replaces_the_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let exe = dir.path().join("safe-coder");
+        std::fs::write(&exe, b"old").unwrap();
+
+        install(b"new", &exe).unwrap();
+        assert_eq!(std::fs::read(&exe).unwrap(), b"new");
+        let leftovers = std::fs::read_dir(dir.path()).unwrap().count();
+        assert_eq!(leftovers, if cfg!(windows) { 2 } else { 1 });
+    }
+}
@@ -2,7 +2,9 @@ use anyhow::Result;
 use assert_fs::prelude::*;
 use assert_fs::TempDir;
 use safe_coder::config::{Config, LlmConfig, LlmProvider, GitConfig, OrchestratorConfig, ToolConfig, LspConfigWrapper, CacheConfig, CheckpointConfig, SubagentConfig, BuildConfig, ContextConfig};
+use safe_coder::hooks::HooksConfig;
 use safe_coder::mcp::McpConfig;
+use safe_coder::skills::SkillsConfig;
 use std::env;
 use std::path::PathBuf;
 use std::process::Stdio;
@@ -130,10 +132,19 @@ edition = "2021"
             llm: LlmConfig {
                 provider: LlmProvider::Anthropic,
                 model: "test-model".to_string(),
+                plan_model: None,
+                build_model: None,
+                subagent_model: None,
                 api_key: Some("test-key".to_string()),
                 max_tokens: 1000,
                 base_url: None,
                 claude_code_oauth_compat: false,
+                account: None,
+                rate_limit: Default::default(),
+                http: Default::default(),
+                reasoning: Default::default(),
+                params: Default::default(),
+                fixtures: Default::default(),
             },
             git: GitConfig::default(),
             orchestrator: OrchestratorConfig::default(),
@@ -145,6 +156,16 @@ edition = "2021"
             subagents: SubagentConfig::default(),
             build: BuildConfig::default(),
             context: ContextConfig::default(),
+            hooks: HooksConfig::default(),
+            skills: SkillsConfig::default(),
+            permissions: Default::default(),
+            loop_detection: Default::default(),
+            notifications: Default::default(),
+            memory: Default::default(),
+            output: Default::default(),
+            share: Default::default(),
+            server: Default::default(),
+            update: Default::default(),
         };
 
         let config_path = self.config_dir.path().join("safe-coder").join("config.toml");
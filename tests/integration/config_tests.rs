@@ -55,6 +55,7 @@ async fn test_config_save() -> Result<()> {
             api_key: Some("new-api-key".to_string()),
             max_tokens: 2000,
             claude_code_oauth_compat: false,
+            account: None,
         },
         git: GitConfig {
             auto_commit: false,
@@ -124,6 +125,7 @@ async fn test_config_validation() -> Result<()> {
             api_key: None,
             max_tokens: 0, // Invalid token count
             claude_code_oauth_compat: false,
+            account: None,
         },
         git: GitConfig {
             auto_commit: true,
@@ -164,6 +166,7 @@ async fn test_config_provider_switching() -> Result<()> {
                 api_key: Some("test-key".to_string()),
                 max_tokens: 1000,
                 claude_code_oauth_compat: false,
+                account: None,
             },
             git: GitConfig::default(),
             orchestrator: OrchestratorConfig::default(),
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use safe_coder::config::{Config, FixtureConfig, FixtureMode};
+use safe_coder::session::Session;
+use safe_coder::tools::AgentMode;
+use serial_test::serial;
+use std::path::PathBuf;
+
+use crate::common::*;
+
+/// Config that answers from a recorded fixture instead of a provider
+fn replay_config(fixture: &str) -> Config {
+    let mut config = Config::default();
+    config.llm.fixtures = FixtureConfig {
+        mode: FixtureMode::Replay,
+        path: Some(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+                .join("tests/integration/fixtures")
+                .join(fixture),
+        ),
+    };
+    config.lsp.enabled = false;
+    config
+}
+
+#[tokio::test]
+#[serial]
+async fn test_replayed_tool_loop_writes_file() -> Result<()> {
+    let env = TestEnvironment::new()?;
+    env.setup_test_project()?;
+    env.init_git().await?;
+
+    let mut session = Session::new(replay_config("write_file.jsonl"), env.project_path.clone()).await?;
+    session.set_agent_mode(AgentMode::Build);
+    session.set_approval_mode("yolo")?;
+
+    let reply = session.send_message("Write hello.txt".to_string()).await?;
+
+    assert_contains(&reply, "Created hello.txt");
+    assert_eq!(
+        std::fs::read_to_string(env.project_path.join("hello.txt"))?,
+        "hello from a fixture\n"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn test_replay_fails_when_fixture_runs_out() -> Result<()> {
+    let env = TestEnvironment::new()?;
+    env.setup_test_project()?;
+
+    let mut session = Session::new(replay_config("write_file.jsonl"), env.project_path.clone()).await?;
+    session.set_agent_mode(AgentMode::Build);
+    session.set_approval_mode("yolo")?;
+
+    session.send_message("Write hello.txt".to_string()).await?;
+    assert!(session.send_message("And again".to_string()).await.is_err());
+    Ok(())
+}
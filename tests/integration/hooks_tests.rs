@@ -252,14 +252,16 @@ async fn test_hook_context_for_file() -> Result<()> {
 async fn test_hook_types_all() -> Result<()> {
     let all_types = HookType::all();
 
-    // Should have all 9 hook types
-    assert_eq!(all_types.len(), 9);
+    // Should have all 11 hook types
+    assert_eq!(all_types.len(), 11);
 
     // Verify specific types exist
     assert!(all_types.contains(&HookType::PreToolUse));
     assert!(all_types.contains(&HookType::PostToolUse));
     assert!(all_types.contains(&HookType::PrePrompt));
     assert!(all_types.contains(&HookType::PostResponse));
+    assert!(all_types.contains(&HookType::PreMessage));
+    assert!(all_types.contains(&HookType::PostMessage));
     assert!(all_types.contains(&HookType::SessionStart));
     assert!(all_types.contains(&HookType::SessionEnd));
     assert!(all_types.contains(&HookType::PreFileWrite));
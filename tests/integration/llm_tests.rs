@@ -16,6 +16,7 @@ async fn test_llm_client_creation() -> Result<()> {
         api_key: Some("test-key".to_string()),
         max_tokens: 1000,
         claude_code_oauth_compat: false,
+        account: None,
     };
 
     let client = create_client(&config).await?;
@@ -64,6 +65,7 @@ async fn test_anthropic_client_with_mock() -> Result<()> {
         api_key: Some("test-key".to_string()),
         max_tokens: 1000,
         claude_code_oauth_compat: false,
+        account: None,
     };
 
     let client = create_client(&config).await?;
@@ -95,6 +97,7 @@ async fn test_openai_client_creation() -> Result<()> {
         api_key: Some("test-key".to_string()),
         max_tokens: 1000,
         claude_code_oauth_compat: false,
+        account: None,
     };
 
     let client = create_client(&config).await?;
@@ -146,6 +149,7 @@ async fn test_openai_client_with_mock() -> Result<()> {
         api_key: Some("test-key".to_string()),
         max_tokens: 1000,
         claude_code_oauth_compat: false,
+        account: None,
     };
 
     let client = create_client(&config).await?;
@@ -170,6 +174,7 @@ async fn test_ollama_client_creation() -> Result<()> {
         api_key: None, // Ollama doesn't require API key
         max_tokens: 1000,
         claude_code_oauth_compat: false,
+        account: None,
     };
 
     let client = create_client(&config).await?;
@@ -189,6 +194,7 @@ async fn test_github_copilot_client_creation() -> Result<()> {
         api_key: Some("ghu_123456789".to_string()),
         max_tokens: 1000,
         claude_code_oauth_compat: false,
+        account: None,
     };
 
     let client = create_client(&config).await?;
@@ -209,6 +215,7 @@ async fn test_llm_config_validation() -> Result<()> {
         api_key: None,
         max_tokens: 1000,
         claude_code_oauth_compat: false,
+        account: None,
     };
 
     // This should either fail or use environment variables
@@ -254,6 +261,7 @@ async fn test_llm_client_error_handling() -> Result<()> {
         api_key: Some("invalid-key".to_string()),
         max_tokens: 1000,
         claude_code_oauth_compat: false,
+        account: None,
     };
 
     let client = create_client(&config).await?;
@@ -282,6 +290,7 @@ mod caching_tests {
             api_key: Some("test-key".to_string()),
             max_tokens: 1000,
             claude_code_oauth_compat: false,
+            account: None,
         };
 
         let base_client = create_client(&config).await?;
@@ -317,6 +326,7 @@ mod provider_switching_tests {
                 api_key: Some("test-key".to_string()),
                 max_tokens: 1000,
                 claude_code_oauth_compat: false,
+                account: None,
             };
 
             let client = create_client(&config).await?;
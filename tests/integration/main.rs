@@ -15,6 +15,7 @@ mod hooks_tests;
 mod skills_tests;
 mod ast_grep_tests;
 mod subagent_config_tests;
+mod fixture_tests;
 
 // Integration test modules (may need fixes)
 // mod cli_tests;
@@ -284,7 +284,7 @@ fn test_skill_get_active_skills_prompt() -> Result<()> {
 
     manager.activate("skill1");
 
-    let prompt = manager.get_active_skills_prompt();
+    let prompt = manager.get_active_skills_prompt(0);
     assert!(prompt.is_some());
 
     let prompt_content = prompt.unwrap();
@@ -300,7 +300,7 @@ fn test_skill_get_active_skills_prompt_none() -> Result<()> {
     let manager = SkillManager::new();
 
     // No active skills
-    let prompt = manager.get_active_skills_prompt();
+    let prompt = manager.get_active_skills_prompt(0);
     assert!(prompt.is_none());
 
     Ok(())
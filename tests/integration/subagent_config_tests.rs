@@ -32,6 +32,7 @@ fn test_subagent_config_with_analyzer() {
         documenter: None,
         explorer: None,
         custom: None,
+        monitor: None,
     };
 
     assert!(config.analyzer.is_some());
@@ -208,6 +209,7 @@ fn test_subagent_config_serialization() -> Result<()> {
         documenter: None,
         explorer: None,
         custom: None,
+        monitor: None,
     };
 
     let serialized = toml::to_string_pretty(&config)?;
@@ -294,6 +294,11 @@ async fn test_dangerous_command_detection() -> Result<()> {
         max_output_bytes: 1024,
         warn_dangerous_commands: true,
         dangerous_patterns: vec!["rm -rf".to_string(), "mkfs".to_string()],
+        protected_paths: vec![],
+        enrich_errors: true,
+        http_allowed_hosts: vec![],
+        ci_status_url: None,
+        lazy_loading: true,
     };
     let context = ToolContext::new(&env.project_path, &config);
 
@@ -1,6 +1,6 @@
 use anyhow::Result;
 use safe_coder::config::ToolConfig;
-use safe_coder::tools::{AgentMode, ToolContext, ToolRegistry};
+use safe_coder::tools::{AgentMode, FileCache, ToolContext, ToolError, ToolRegistry};
 use tempfile::TempDir;
 
 #[test]
@@ -123,6 +123,11 @@ fn test_tool_context_creation() {
         max_output_bytes: 1_048_576,
         warn_dangerous_commands: true,
         dangerous_patterns: vec![],
+        protected_paths: vec![],
+        enrich_errors: true,
+        http_allowed_hosts: vec![],
+        ci_status_url: None,
+        lazy_loading: true,
     };
 
     let context = ToolContext::new(project_path, &config);
@@ -150,6 +155,11 @@ mod file_operations_tests {
             max_output_bytes: 1_048_576,
             warn_dangerous_commands: true,
             dangerous_patterns: vec![],
+            protected_paths: vec![],
+            enrich_errors: true,
+            http_allowed_hosts: vec![],
+            ci_status_url: None,
+            lazy_loading: true,
         };
 
         let context = ToolContext::new(project_path, &config);
@@ -161,6 +171,132 @@ mod file_operations_tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_protected_paths_are_refused() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project_path = temp_dir.path();
+        std::fs::write(project_path.join(".env"), "SECRET=1")?;
+
+        let config = ToolConfig {
+            protected_paths: vec!["**/.env".to_string(), "migrations/**".to_string()],
+            ..ToolConfig::default()
+        };
+        let context = ToolContext::new(project_path, &config);
+        let registry = ToolRegistry::new_without_subagents();
+
+        let write = registry.get_tool("write_file").unwrap();
+        let err = write
+            .execute(
+                serde_json::json!({ "file_path": "migrations/001.sql", "content": "DROP TABLE" }),
+                &context,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("PROTECTED PATH"));
+        assert!(!project_path.join("migrations/001.sql").exists());
+
+        let edit = registry.get_tool("edit_file").unwrap();
+        let result = edit
+            .execute(
+                serde_json::json!({ "file_path": ".env", "old_string": "1", "new_string": "2" }),
+                &context,
+            )
+            .await;
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(project_path.join(".env"))?, "SECRET=1");
+
+        let bash = registry.get_tool("bash").unwrap();
+        assert!(bash
+            .execute(serde_json::json!({ "command": "echo SECRET=2 > .env" }), &context)
+            .await
+            .is_err());
+        assert_eq!(std::fs::read_to_string(project_path.join(".env"))?, "SECRET=1");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_edit_refuses_file_changed_since_read() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project_path = temp_dir.path();
+        std::fs::write(project_path.join("lib.rs"), "fn a() {}\n")?;
+
+        let config = ToolConfig::default();
+        let context = ToolContext::new(project_path, &config).with_file_cache(FileCache::new());
+        let registry = ToolRegistry::new_without_subagents();
+        let read = registry.get_tool("read_file").unwrap();
+        let edit = registry.get_tool("edit_file").unwrap();
+        let params = serde_json::json!({
+            "file_path": "lib.rs", "old_string": "fn a() {}", "new_string": "fn a() { todo!() }"
+        });
+
+        read.execute(serde_json::json!({ "file_path": "lib.rs" }), &context)
+            .await?;
+        // The user edits the file in their editor meanwhile
+        std::fs::write(project_path.join("lib.rs"), "fn a() {}\nfn user() {}\n")?;
+
+        let err = edit.execute(params.clone(), &context).await.unwrap_err();
+        assert_eq!(ToolError::classify(&err).unwrap().code(), "stale_read");
+        assert_eq!(
+            std::fs::read_to_string(project_path.join("lib.rs"))?,
+            "fn a() {}\nfn user() {}\n"
+        );
+
+        read.execute(serde_json::json!({ "file_path": "lib.rs" }), &context)
+            .await?;
+        edit.execute(params, &context).await?;
+        assert_eq!(
+            std::fs::read_to_string(project_path.join("lib.rs"))?,
+            "fn a() { todo!() }\nfn user() {}\n"
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_file_by_symbol_and_line() -> Result<()> {
+        let temp_dir = TempDir::new()?;
+        let project_path = temp_dir.path();
+        let mut source = String::from("fn first() {\n    1\n}\n\n");
+        source.push_str(&"// filler\n".repeat(1000));
+        source.push_str("\n/// Second\nfn second() -> u32 {\n    2\n}\n");
+        std::fs::write(project_path.join("big.rs"), &source)?;
+
+        let config = ToolConfig::default();
+        let context = ToolContext::new(project_path, &config);
+        let registry = ToolRegistry::new_without_subagents();
+        let read = registry.get_tool("read_file").unwrap();
+
+        let result = read
+            .execute(
+                serde_json::json!({ "file_path": "big.rs", "symbol": "second" }),
+                &context,
+            )
+            .await?;
+        assert!(result.starts_with("[function_item second: lines 1006-1009 of 1009]"));
+        assert!(result.contains(" 1006→/// Second"));
+        assert!(!result.contains("filler"));
+
+        let result = read
+            .execute(
+                serde_json::json!({ "file_path": "big.rs", "around_line": 2 }),
+                &context,
+            )
+            .await?;
+        assert!(result.starts_with("[function_item first: lines 1-3 of 1009]"));
+
+        let err = read
+            .execute(
+                serde_json::json!({ "file_path": "big.rs", "symbol": "third" }),
+                &context,
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Definitions: first, second"));
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]